@@ -0,0 +1,242 @@
+//! Role-based authorization for the admin API.
+//!
+//! Admin endpoints are grouped by the kind of operation they perform
+//! ([`RouteGroup`]). Each authenticated caller is resolved to an
+//! [`AdminPrincipal`] carrying one or more [`AdminRole`]s, and a request is
+//! only forwarded to its handler when at least one of those roles grants the
+//! route group.
+//!
+//! | Role | Read APIs | Replay | DLQ management | Config changes | Privacy erasure |
+//! |------|:---------:|:------:|:--------------:|:--------------:|:---------------:|
+//! | `viewer` | ✓ | | | | |
+//! | `operator` | ✓ | ✓ | ✓ | | |
+//! | `admin` | ✓ | ✓ | ✓ | ✓ | |
+//! | `security` | ✓ | | | | ✓ |
+//!
+//! Privacy erasure is deliberately kept out of the `admin` role so that
+//! destructive data removal requires an explicitly designated principal.
+//!
+//! Principals are configured in
+//! [`SecurityConfig::admin_principals`](crate::config::SecurityConfig::admin_principals).
+
+use axum::http::Method;
+use queue_keeper_core::audit_logging::AuditActor;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+// ============================================================================
+// Roles and Route Groups
+// ============================================================================
+
+/// Role assigned to an admin API principal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AdminRole {
+    /// Read-only access to query and configuration endpoints.
+    Viewer,
+
+    /// Day-to-day operations: replay, session reset and DLQ management.
+    Operator,
+
+    /// Everything an operator can do plus runtime configuration changes.
+    Admin,
+
+    /// Read access plus privacy erasure of stored event data.
+    Security,
+}
+
+impl AdminRole {
+    /// Get string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Operator => "operator",
+            Self::Admin => "admin",
+            Self::Security => "security",
+        }
+    }
+
+    /// Check whether this role grants access to the given route group.
+    pub fn grants(&self, group: RouteGroup) -> bool {
+        match self {
+            Self::Viewer => matches!(group, RouteGroup::ReadApi),
+            Self::Operator => matches!(
+                group,
+                RouteGroup::ReadApi | RouteGroup::Replay | RouteGroup::DlqManagement
+            ),
+            Self::Admin => !matches!(group, RouteGroup::PrivacyErasure),
+            Self::Security => matches!(group, RouteGroup::ReadApi | RouteGroup::PrivacyErasure),
+        }
+    }
+}
+
+impl fmt::Display for AdminRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Category of admin operation used for permission checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteGroup {
+    /// Read-only admin queries (`GET` requests).
+    ReadApi,
+
+    /// Event replay and session reset.
+    Replay,
+
+    /// Dead-letter queue inspection, requeue and purge.
+    DlqManagement,
+
+    /// Runtime configuration changes (log level, sampling, metrics baseline).
+    ConfigChanges,
+
+    /// Removal of stored event data for privacy requests and event deletion.
+    PrivacyErasure,
+}
+
+impl RouteGroup {
+    /// Get string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReadApi => "read_api",
+            Self::Replay => "replay",
+            Self::DlqManagement => "dlq_management",
+            Self::ConfigChanges => "config_changes",
+            Self::PrivacyErasure => "privacy_erasure",
+        }
+    }
+
+    /// Classify an admin request into its route group.
+    ///
+    /// Classification is by path prefix first, so that a `GET` on a DLQ or
+    /// privacy route still requires the corresponding permission. Deleting a
+    /// stored event removes event data just like a privacy request, so it
+    /// shares the privacy erasure group. Remaining
+    /// `GET` requests are read APIs; any other mutating request not matched
+    /// by a more specific rule is treated as a configuration change, which is
+    /// the most restrictive non-erasure group.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use axum::http::Method;
+    /// use queue_keeper_api::authorization::RouteGroup;
+    ///
+    /// assert_eq!(
+    ///     RouteGroup::classify(&Method::POST, "/admin/events/01H/replay"),
+    ///     RouteGroup::Replay
+    /// );
+    /// assert_eq!(
+    ///     RouteGroup::classify(&Method::GET, "/admin/config"),
+    ///     RouteGroup::ReadApi
+    /// );
+    /// ```
+    pub fn classify(method: &Method, path: &str) -> Self {
+        if path.starts_with("/admin/privacy")
+            || (*method == Method::DELETE && path.starts_with("/admin/events/"))
+        {
+            return Self::PrivacyErasure;
+        }
+        if path.starts_with("/admin/dlq") {
+            return Self::DlqManagement;
+        }
+        if *method == Method::GET || *method == Method::HEAD {
+            return Self::ReadApi;
+        }
+        if path.ends_with("/replay")
+            || (path.starts_with("/admin/sessions/") && path.ends_with("/reset"))
+        {
+            return Self::Replay;
+        }
+        Self::ConfigChanges
+    }
+}
+
+impl fmt::Display for RouteGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// ============================================================================
+// Principal
+// ============================================================================
+
+/// Authenticated caller of the admin API.
+///
+/// Inserted into the request extensions by
+/// [`admin_auth_middleware`](crate::middleware::admin_auth_middleware) so
+/// handlers can attribute their actions via `Extension<AdminPrincipal>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdminPrincipal {
+    /// Principal name as configured (appears in audit entries).
+    pub name: String,
+
+    /// Roles held by this principal.
+    pub roles: Vec<AdminRole>,
+}
+
+impl AdminPrincipal {
+    /// Name used for the principal authenticated via the legacy single
+    /// `admin_api_key`.
+    pub const LEGACY_ADMIN_NAME: &'static str = "admin";
+
+    /// Name used when admin authentication is disabled (development mode).
+    pub const ANONYMOUS_NAME: &'static str = "anonymous";
+
+    /// Create a principal with the given roles.
+    pub fn new(name: impl Into<String>, roles: Vec<AdminRole>) -> Self {
+        Self {
+            name: name.into(),
+            roles,
+        }
+    }
+
+    /// Principal for the legacy `admin_api_key`.
+    ///
+    /// Holds both `admin` and `security` so that deployments configured with
+    /// a single key keep unrestricted access to every admin endpoint.
+    pub fn legacy_admin() -> Self {
+        Self::new(
+            Self::LEGACY_ADMIN_NAME,
+            vec![AdminRole::Admin, AdminRole::Security],
+        )
+    }
+
+    /// Principal used when no admin credentials are configured.
+    ///
+    /// Matches the pre-existing open-access behaviour of development mode.
+    pub fn anonymous() -> Self {
+        Self::new(
+            Self::ANONYMOUS_NAME,
+            vec![AdminRole::Admin, AdminRole::Security],
+        )
+    }
+
+    /// Check whether any of this principal's roles grants the route group.
+    pub fn is_permitted(&self, group: RouteGroup) -> bool {
+        self.roles.iter().any(|role| role.grants(group))
+    }
+
+    /// Convert to the audit actor recorded for actions by this principal.
+    pub fn to_audit_actor(&self) -> AuditActor {
+        let roles = self
+            .roles
+            .iter()
+            .map(AdminRole::as_str)
+            .collect::<Vec<_>>()
+            .join(",");
+        AuditActor::User {
+            user_id: self.name.clone(),
+            username: self.name.clone(),
+            email: None,
+            role: Some(roles),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "authorization_tests.rs"]
+mod tests;
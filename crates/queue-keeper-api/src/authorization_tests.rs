@@ -0,0 +1,203 @@
+//! Tests for admin API role-based authorization.
+
+use super::*;
+
+mod role_tests {
+    use super::*;
+
+    /// Verify that viewers can only use read APIs.
+    #[test]
+    fn test_viewer_grants_only_read_api() {
+        assert!(AdminRole::Viewer.grants(RouteGroup::ReadApi));
+        assert!(!AdminRole::Viewer.grants(RouteGroup::Replay));
+        assert!(!AdminRole::Viewer.grants(RouteGroup::DlqManagement));
+        assert!(!AdminRole::Viewer.grants(RouteGroup::ConfigChanges));
+        assert!(!AdminRole::Viewer.grants(RouteGroup::PrivacyErasure));
+    }
+
+    /// Verify that operators can replay and manage the DLQ but not change config.
+    #[test]
+    fn test_operator_grants_replay_and_dlq() {
+        assert!(AdminRole::Operator.grants(RouteGroup::ReadApi));
+        assert!(AdminRole::Operator.grants(RouteGroup::Replay));
+        assert!(AdminRole::Operator.grants(RouteGroup::DlqManagement));
+        assert!(!AdminRole::Operator.grants(RouteGroup::ConfigChanges));
+        assert!(!AdminRole::Operator.grants(RouteGroup::PrivacyErasure));
+    }
+
+    /// Verify that admins can do everything except privacy erasure.
+    #[test]
+    fn test_admin_grants_everything_but_privacy_erasure() {
+        assert!(AdminRole::Admin.grants(RouteGroup::ReadApi));
+        assert!(AdminRole::Admin.grants(RouteGroup::Replay));
+        assert!(AdminRole::Admin.grants(RouteGroup::DlqManagement));
+        assert!(AdminRole::Admin.grants(RouteGroup::ConfigChanges));
+        assert!(!AdminRole::Admin.grants(RouteGroup::PrivacyErasure));
+    }
+
+    /// Verify that the security role is limited to reads and privacy erasure.
+    #[test]
+    fn test_security_grants_read_and_privacy_erasure() {
+        assert!(AdminRole::Security.grants(RouteGroup::ReadApi));
+        assert!(AdminRole::Security.grants(RouteGroup::PrivacyErasure));
+        assert!(!AdminRole::Security.grants(RouteGroup::Replay));
+        assert!(!AdminRole::Security.grants(RouteGroup::ConfigChanges));
+    }
+
+    /// Verify that roles deserialize from snake_case configuration values.
+    #[test]
+    fn test_role_deserializes_from_snake_case() {
+        let roles: Vec<AdminRole> =
+            serde_json::from_str(r#"["viewer","operator","admin","security"]"#).unwrap();
+        assert_eq!(
+            roles,
+            vec![
+                AdminRole::Viewer,
+                AdminRole::Operator,
+                AdminRole::Admin,
+                AdminRole::Security
+            ]
+        );
+    }
+}
+
+mod route_group_tests {
+    use super::*;
+
+    /// Verify that GET requests on general admin routes are read APIs.
+    #[test]
+    fn test_get_requests_are_read_api() {
+        assert_eq!(
+            RouteGroup::classify(&Method::GET, "/admin/config"),
+            RouteGroup::ReadApi
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::GET, "/admin/logging/level"),
+            RouteGroup::ReadApi
+        );
+    }
+
+    /// Verify that replay and session reset map to the replay group.
+    #[test]
+    fn test_replay_and_reset_are_replay_group() {
+        assert_eq!(
+            RouteGroup::classify(&Method::POST, "/admin/events/01ABC/replay"),
+            RouteGroup::Replay
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::POST, "/admin/sessions/o/r/pull_request/1/reset"),
+            RouteGroup::Replay
+        );
+    }
+
+    /// Verify that DLQ routes require DLQ permission even for GET.
+    #[test]
+    fn test_dlq_routes_are_dlq_management() {
+        assert_eq!(
+            RouteGroup::classify(&Method::GET, "/admin/dlq"),
+            RouteGroup::DlqManagement
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::POST, "/admin/dlq/01ABC/requeue"),
+            RouteGroup::DlqManagement
+        );
+    }
+
+    /// Verify that privacy routes require privacy erasure permission.
+    #[test]
+    fn test_privacy_routes_are_privacy_erasure() {
+        assert_eq!(
+            RouteGroup::classify(&Method::DELETE, "/admin/privacy/events/01ABC"),
+            RouteGroup::PrivacyErasure
+        );
+    }
+
+    /// Verify that deleting an event is privacy erasure, not a config change.
+    #[test]
+    fn test_event_delete_is_privacy_erasure() {
+        assert_eq!(
+            RouteGroup::classify(&Method::DELETE, "/admin/events/01ABC"),
+            RouteGroup::PrivacyErasure
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::GET, "/admin/events/01ABC"),
+            RouteGroup::ReadApi
+        );
+    }
+
+    /// Verify that only the security role may delete events.
+    #[test]
+    fn test_event_delete_role_matrix() {
+        let group = RouteGroup::classify(&Method::DELETE, "/admin/events/01ABC");
+        assert!(!AdminRole::Viewer.grants(group));
+        assert!(!AdminRole::Operator.grants(group));
+        assert!(!AdminRole::Admin.grants(group));
+        assert!(AdminRole::Security.grants(group));
+    }
+
+    /// Verify that other mutating requests are treated as config changes.
+    #[test]
+    fn test_other_mutations_are_config_changes() {
+        assert_eq!(
+            RouteGroup::classify(&Method::PUT, "/admin/logging/level"),
+            RouteGroup::ConfigChanges
+        );
+        assert_eq!(
+            RouteGroup::classify(&Method::POST, "/admin/metrics/reset"),
+            RouteGroup::ConfigChanges
+        );
+    }
+}
+
+mod principal_tests {
+    use super::*;
+
+    /// Verify that a principal is permitted when any role grants the group.
+    #[test]
+    fn test_principal_permitted_by_any_role() {
+        let principal = AdminPrincipal::new("alice", vec![AdminRole::Viewer, AdminRole::Security]);
+        assert!(principal.is_permitted(RouteGroup::PrivacyErasure));
+        assert!(!principal.is_permitted(RouteGroup::Replay));
+    }
+
+    /// Verify that a principal with no roles is denied everything.
+    #[test]
+    fn test_principal_without_roles_is_denied() {
+        let principal = AdminPrincipal::new("nobody", vec![]);
+        assert!(!principal.is_permitted(RouteGroup::ReadApi));
+    }
+
+    /// Verify that the legacy admin key keeps access to every route group.
+    #[test]
+    fn test_legacy_admin_has_full_access() {
+        let principal = AdminPrincipal::legacy_admin();
+        for group in [
+            RouteGroup::ReadApi,
+            RouteGroup::Replay,
+            RouteGroup::DlqManagement,
+            RouteGroup::ConfigChanges,
+            RouteGroup::PrivacyErasure,
+        ] {
+            assert!(principal.is_permitted(group), "denied {}", group);
+        }
+    }
+
+    /// Verify that the audit actor carries the principal name and roles.
+    #[test]
+    fn test_to_audit_actor_records_name_and_roles() {
+        let principal = AdminPrincipal::new("bob", vec![AdminRole::Operator]);
+        match principal.to_audit_actor() {
+            AuditActor::User {
+                user_id,
+                username,
+                role,
+                ..
+            } => {
+                assert_eq!(user_id, "bob");
+                assert_eq!(username, "bob");
+                assert_eq!(role.as_deref(), Some("operator"));
+            }
+            other => panic!("unexpected actor: {:?}", other),
+        }
+    }
+}
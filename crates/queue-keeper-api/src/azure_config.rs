@@ -38,6 +38,10 @@ pub struct AzureProductionConfig {
 
     /// Azure region for resources
     pub region: String,
+
+    /// Azure cloud the resources live in (public, China, US Government)
+    #[serde(default)]
+    pub cloud: AzureCloud,
 }
 
 impl AzureProductionConfig {
@@ -51,6 +55,7 @@ impl AzureProductionConfig {
     /// - `AZURE_APPINSIGHTS_CONNECTION_STRING`: Application Insights connection string
     /// - `AZURE_ENVIRONMENT`: Environment name (dev, staging, production)
     /// - `AZURE_REGION`: Azure region
+    /// - `AZURE_CLOUD` (optional): `public` (default), `china` or `us_government`
     ///
     /// # Errors
     /// Returns error if required variables are missing or invalid
@@ -94,6 +99,19 @@ impl AzureProductionConfig {
                 variable: "AZURE_REGION".to_string(),
             })?;
 
+        let cloud = match std::env::var("AZURE_CLOUD") {
+            Ok(name) => {
+                AzureCloud::from_name(&name).ok_or_else(|| AzureConfigError::InvalidEnvVar {
+                    variable: "AZURE_CLOUD".to_string(),
+                    message: format!(
+                        "unknown cloud '{}'; expected public, china or us_government",
+                        name
+                    ),
+                })?
+            }
+            Err(_) => AzureCloud::default(),
+        };
+
         // Determine if we're in production based on environment
         let is_production = environment == "production";
 
@@ -139,6 +157,7 @@ impl AzureProductionConfig {
             },
             environment,
             region,
+            cloud,
         };
 
         // Validate before returning
@@ -150,7 +169,8 @@ impl AzureProductionConfig {
     /// Validate configuration
     ///
     /// Checks that all required fields are present and valid:
-    /// - Key Vault URL is HTTPS and ends with .vault.azure.net
+    /// - Key Vault URL is HTTPS and in the configured cloud's Key Vault domain
+    ///   (e.g. `.vault.azure.net`, `.vault.azure.cn`)
     /// - Storage account name is valid (3-24 chars, lowercase/numbers)
     /// - Service Bus namespace is valid
     /// - Environment is one of: dev, staging, production
@@ -164,10 +184,14 @@ impl AzureProductionConfig {
             });
         }
 
-        if !self.key_vault.vault_url.contains(".vault.azure.net") {
+        let key_vault_domain = format!(".{}", self.cloud.key_vault_suffix());
+        if !self.key_vault.vault_url.contains(&key_vault_domain) {
             return Err(AzureConfigError::InvalidKeyVaultUrl {
                 url: self.key_vault.vault_url.clone(),
-                reason: "Key Vault URL must end with .vault.azure.net".to_string(),
+                reason: format!(
+                    "Key Vault URL must end with {} for the {} cloud",
+                    key_vault_domain, self.cloud
+                ),
             });
         }
 
@@ -230,10 +254,94 @@ impl fmt::Debug for AzureProductionConfig {
             .field("telemetry", &self.telemetry)
             .field("environment", &self.environment)
             .field("region", &self.region)
+            .field("cloud", &self.cloud)
             .finish()
     }
 }
 
+/// Azure cloud environment.
+///
+/// Sovereign clouds use different Entra ID authority hosts and service DNS
+/// suffixes from the public cloud; this type is the single source of those
+/// values for configuration validation and credential setup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AzureCloud {
+    /// Azure public cloud
+    #[default]
+    Public,
+
+    /// Azure China (operated by 21Vianet)
+    China,
+
+    /// Azure US Government
+    UsGovernment,
+}
+
+impl AzureCloud {
+    /// Parse a cloud name as used in configuration (`public`, `china`,
+    /// `us_government`)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "public" => Some(Self::Public),
+            "china" => Some(Self::China),
+            "us_government" => Some(Self::UsGovernment),
+            _ => None,
+        }
+    }
+
+    /// Get string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::China => "china",
+            Self::UsGovernment => "us_government",
+        }
+    }
+
+    /// Entra ID (Azure AD) authority host used for token requests
+    pub fn authority_host(&self) -> &'static str {
+        match self {
+            Self::Public => "https://login.microsoftonline.com",
+            Self::China => "https://login.chinacloudapi.cn",
+            Self::UsGovernment => "https://login.microsoftonline.us",
+        }
+    }
+
+    /// DNS suffix of Service Bus namespaces
+    pub fn service_bus_suffix(&self) -> &'static str {
+        match self {
+            Self::Public => "servicebus.windows.net",
+            Self::China => "servicebus.chinacloudapi.cn",
+            Self::UsGovernment => "servicebus.usgovcloudapi.net",
+        }
+    }
+
+    /// DNS suffix of Key Vault instances
+    pub fn key_vault_suffix(&self) -> &'static str {
+        match self {
+            Self::Public => "vault.azure.net",
+            Self::China => "vault.azure.cn",
+            Self::UsGovernment => "vault.usgovcloudapi.net",
+        }
+    }
+
+    /// DNS suffix of Blob Storage accounts
+    pub fn blob_storage_suffix(&self) -> &'static str {
+        match self {
+            Self::Public => "blob.core.windows.net",
+            Self::China => "blob.core.chinacloudapi.cn",
+            Self::UsGovernment => "blob.core.usgovcloudapi.net",
+        }
+    }
+}
+
+impl fmt::Display for AzureCloud {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// Azure Key Vault configuration
 #[derive(Clone, Serialize, Deserialize)]
 pub struct AzureKeyVaultConfig {
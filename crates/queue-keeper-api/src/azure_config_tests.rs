@@ -92,6 +92,7 @@ mod production_config_tests {
             ),
             environment: "production".to_string(),
             region: "eastus".to_string(),
+            cloud: AzureCloud::Public,
         };
 
         // Act
@@ -129,6 +130,7 @@ mod production_config_tests {
             ),
             environment: "production".to_string(),
             region: "eastus".to_string(),
+            cloud: AzureCloud::Public,
         };
 
         // Act
@@ -166,6 +168,7 @@ mod production_config_tests {
             ),
             environment: "invalid-env".to_string(), // Invalid environment
             region: "eastus".to_string(),
+            cloud: AzureCloud::Public,
         };
 
         // Act
@@ -203,6 +206,7 @@ mod production_config_tests {
             ),
             environment: "production".to_string(),
             region: String::new(), // Empty region
+            cloud: AzureCloud::Public,
         };
 
         // Act
@@ -237,6 +241,7 @@ mod production_config_tests {
             ),
             environment: "production".to_string(),
             region: "eastus".to_string(),
+            cloud: AzureCloud::Public,
         };
 
         // Act
@@ -245,6 +250,77 @@ mod production_config_tests {
         // Assert
         assert!(result.is_ok(), "Valid configuration should pass validation");
     }
+
+    fn sovereign_config(vault_url: &str, cloud: AzureCloud) -> AzureProductionConfig {
+        AzureProductionConfig {
+            key_vault: AzureKeyVaultConfig::production(vault_url.to_string()),
+            blob_storage: AzureBlobStorageConfig::production(
+                "prodstorage".to_string(),
+                "webhooks".to_string(),
+            ),
+            service_bus: AzureServiceBusConfig::production("prod-sb".to_string()),
+            telemetry: AzureTelemetryConfig::production(
+                "InstrumentationKey=prod-key".to_string(),
+                "1.0.0".to_string(),
+            ),
+            environment: "production".to_string(),
+            region: "chinanorth3".to_string(),
+            cloud,
+        }
+    }
+
+    /// Verify that sovereign-cloud Key Vault URLs pass for their own cloud.
+    #[test]
+    fn test_validate_sovereign_cloud_key_vault_url() {
+        let china = sovereign_config("https://prod.vault.azure.cn/", AzureCloud::China);
+        assert!(china.validate().is_ok());
+
+        let gov = sovereign_config(
+            "https://prod.vault.usgovcloudapi.net/",
+            AzureCloud::UsGovernment,
+        );
+        assert!(gov.validate().is_ok());
+    }
+
+    /// Verify that a Key Vault URL from another cloud is rejected.
+    #[test]
+    fn test_validate_key_vault_url_from_other_cloud_fails() {
+        let config = sovereign_config("https://prod.vault.azure.cn/", AzureCloud::Public);
+        assert!(matches!(
+            config.validate(),
+            Err(AzureConfigError::InvalidKeyVaultUrl { .. })
+        ));
+    }
+}
+
+// ============================================================================
+// AzureCloud Tests
+// ============================================================================
+
+mod azure_cloud_tests {
+    use super::*;
+
+    /// Verify that cloud names round-trip through `from_name` and `as_str`.
+    #[test]
+    fn test_cloud_names_round_trip() {
+        for cloud in [
+            AzureCloud::Public,
+            AzureCloud::China,
+            AzureCloud::UsGovernment,
+        ] {
+            assert_eq!(AzureCloud::from_name(cloud.as_str()), Some(cloud));
+        }
+        assert_eq!(AzureCloud::from_name("germany"), None);
+    }
+
+    /// Verify the endpoints used for the China cloud.
+    #[test]
+    fn test_china_cloud_endpoints() {
+        let cloud = AzureCloud::China;
+        assert_eq!(cloud.authority_host(), "https://login.chinacloudapi.cn");
+        assert_eq!(cloud.service_bus_suffix(), "servicebus.chinacloudapi.cn");
+        assert_eq!(cloud.key_vault_suffix(), "vault.azure.cn");
+    }
 }
 
 // ============================================================================
@@ -0,0 +1,380 @@
+//! Per-bot delivery pause.
+//!
+//! Pausing a bot holds its deliveries without affecting other bots: events
+//! that match a paused bot are routed to every other matching bot as usual,
+//! and a copy is parked in the bot's hold queue in the `held` state. Resuming
+//! releases the held events to the bot one at a time, in arrival order;
+//! events arriving while the release is under way join the back of the hold
+//! queue, so the bot still sees events in order. The bot is unpaused once its
+//! hold queue is empty.
+//!
+//! Resetting a session discards the held events of that session, like any
+//! other pending delivery (see [`crate::session_epochs`]).
+//!
+//! # Limitations
+//!
+//! Held events are kept in memory. Events held when the service stops are
+//! lost, and each replica holds only the events it received; pause and
+//! resume the bot on every replica.
+
+use crate::queue_delivery::{deliver_event_to_queues, QueueDeliveryConfig};
+use queue_keeper_core::{
+    bot_config::BotConfiguration, event_info, queue_integration::EventRouter,
+    webhook::WrappedEvent, BotName, SessionId, Timestamp,
+};
+use queue_runtime::QueueClient;
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{info, warn};
+
+// ============================================================================
+// Status Types
+// ============================================================================
+
+/// Delivery state of a paused bot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotPauseState {
+    /// Deliveries are held.
+    Paused,
+
+    /// Held events are being released; new events are still held behind them.
+    Resuming,
+}
+
+/// Pause state of one bot, as reported by the admin API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BotPauseStatus {
+    pub bot_name: BotName,
+    pub state: BotPauseState,
+    /// When the bot was paused.
+    pub paused_at: Timestamp,
+    /// Principal that paused the bot.
+    pub paused_by: String,
+    /// Why the bot was paused.
+    pub reason: String,
+    /// Events in the `held` state, waiting for release.
+    pub held_events: usize,
+}
+
+/// Oldest held event of one bot, for age-based escalation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeldBacklog {
+    pub bot_name: BotName,
+    /// When the oldest held event was held.
+    pub oldest_held_at: Timestamp,
+    pub held_events: usize,
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// An event in the `held` state.
+#[derive(Debug, Clone)]
+pub struct HeldEvent {
+    /// When the event was held.
+    pub held_at: Timestamp,
+    pub event: WrappedEvent,
+}
+
+#[derive(Debug)]
+struct PausedBot {
+    paused_at: Timestamp,
+    paused_by: String,
+    reason: String,
+    resuming: bool,
+    held: VecDeque<HeldEvent>,
+}
+
+impl PausedBot {
+    fn status(&self, bot_name: &BotName) -> BotPauseStatus {
+        BotPauseStatus {
+            bot_name: bot_name.clone(),
+            state: if self.resuming {
+                BotPauseState::Resuming
+            } else {
+                BotPauseState::Paused
+            },
+            paused_at: self.paused_at,
+            paused_by: self.paused_by.clone(),
+            reason: self.reason.clone(),
+            held_events: self.held.len(),
+        }
+    }
+}
+
+/// Paused bots and their held events.
+///
+/// Shared by every clone of [`QueueDeliveryConfig`], so a pause issued
+/// through the admin API reaches deliveries spawned by the webhook handler.
+#[derive(Debug, Default)]
+pub struct BotPauseRegistry {
+    bots: Mutex<HashMap<BotName, PausedBot>>,
+}
+
+impl BotPauseRegistry {
+    /// Create a registry with no paused bots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pause a bot.
+    ///
+    /// Pausing a bot that is resuming stops the release; the remaining
+    /// events stay held.
+    pub fn pause(&self, bot_name: &BotName, paused_by: &str, reason: &str) -> BotPauseStatus {
+        let mut bots = self.bots.lock().unwrap();
+        let bot = bots.entry(bot_name.clone()).or_insert_with(|| PausedBot {
+            paused_at: Timestamp::now(),
+            paused_by: paused_by.to_string(),
+            reason: reason.to_string(),
+            resuming: false,
+            held: VecDeque::new(),
+        });
+        bot.resuming = false;
+        bot.status(bot_name)
+    }
+
+    /// Mark a paused bot as resuming.
+    ///
+    /// Returns `None` if the bot is not paused. The caller releases the held
+    /// events with [`release_held_events`].
+    pub fn resume(&self, bot_name: &BotName) -> Option<BotPauseStatus> {
+        let mut bots = self.bots.lock().unwrap();
+        let bot = bots.get_mut(bot_name)?;
+        bot.resuming = true;
+        Some(bot.status(bot_name))
+    }
+
+    /// Pause state of every paused or resuming bot, by bot name.
+    pub fn statuses(&self) -> Vec<BotPauseStatus> {
+        let bots = self.bots.lock().unwrap();
+        let mut statuses: Vec<_> = bots.iter().map(|(name, bot)| bot.status(name)).collect();
+        statuses.sort_by(|a, b| a.bot_name.as_str().cmp(b.bot_name.as_str()));
+        statuses
+    }
+
+    /// Whether the bot is paused or resuming.
+    pub fn is_paused(&self, bot_name: &BotName) -> bool {
+        self.bots.lock().unwrap().contains_key(bot_name)
+    }
+
+    /// Hold `event` for every paused bot it matches.
+    ///
+    /// Returns the configuration to route the event with: `bot_config`
+    /// itself when no paused bot matches, otherwise a copy without the bots
+    /// the event was held for.
+    pub fn hold_for_paused_bots(
+        &self,
+        event: &WrappedEvent,
+        bot_config: Arc<BotConfiguration>,
+    ) -> Arc<BotConfiguration> {
+        let mut bots = self.bots.lock().unwrap();
+        if bots.is_empty() {
+            return bot_config;
+        }
+
+        let held_at = Timestamp::now();
+        let mut held_for = Vec::new();
+        for bot in bot_config.get_target_bots(event) {
+            if let Some(paused) = bots.get_mut(&bot.name) {
+                paused.held.push_back(HeldEvent {
+                    held_at,
+                    event: event.clone(),
+                });
+                held_for.push(bot.name.clone());
+            }
+        }
+        if held_for.is_empty() {
+            return bot_config;
+        }
+
+        event_info!(
+            event,
+            held_for = ?held_for.iter().map(BotName::as_str).collect::<Vec<_>>(),
+            "Event held for paused bots"
+        );
+        Arc::new(BotConfiguration {
+            bots: bot_config
+                .bots
+                .iter()
+                .filter(|bot| !held_for.contains(&bot.name))
+                .cloned()
+                .collect(),
+            settings: bot_config.settings.clone(),
+        })
+    }
+
+    /// Take the next held event of a resuming bot.
+    ///
+    /// Returns `None` when the bot was paused again, or when its hold queue
+    /// is empty; in the latter case the bot is unpaused.
+    fn next_held(&self, bot_name: &BotName) -> Option<WrappedEvent> {
+        let mut bots = self.bots.lock().unwrap();
+        let bot = bots.get_mut(bot_name)?;
+        if !bot.resuming {
+            return None;
+        }
+        let next = bot.held.pop_front();
+        if next.is_none() {
+            bots.remove(bot_name);
+        }
+        next.map(|held| held.event)
+    }
+
+    /// Oldest held event of every bot with held events, by bot name.
+    pub fn held_backlogs(&self) -> Vec<HeldBacklog> {
+        let bots = self.bots.lock().unwrap();
+        let mut backlogs: Vec<_> = bots
+            .iter()
+            .filter_map(|(name, bot)| {
+                bot.held.front().map(|oldest| HeldBacklog {
+                    bot_name: name.clone(),
+                    oldest_held_at: oldest.held_at,
+                    held_events: bot.held.len(),
+                })
+            })
+            .collect();
+        backlogs.sort_by(|a, b| a.bot_name.as_str().cmp(b.bot_name.as_str()));
+        backlogs
+    }
+
+    /// Take the events held for `bot_name` for longer than `max_age`.
+    ///
+    /// The bot stays paused. Events are taken from the front of the hold
+    /// queue, oldest first, so the remaining events keep their order.
+    pub fn take_held_older_than(
+        &self,
+        bot_name: &BotName,
+        max_age: Duration,
+        now: Timestamp,
+    ) -> Vec<HeldEvent> {
+        let mut bots = self.bots.lock().unwrap();
+        let Some(bot) = bots.get_mut(bot_name) else {
+            return Vec::new();
+        };
+        let mut taken = Vec::new();
+        while bot
+            .held
+            .front()
+            .is_some_and(|held| now.duration_since(held.held_at) > max_age)
+        {
+            taken.extend(bot.held.pop_front());
+        }
+        taken
+    }
+
+    /// Put events taken with [`take_held_older_than`](Self::take_held_older_than)
+    /// back at the front of the hold queue, in their original order.
+    ///
+    /// Returns the events that could not be put back because the bot is no
+    /// longer paused.
+    pub fn return_held(&self, bot_name: &BotName, events: Vec<HeldEvent>) -> Vec<HeldEvent> {
+        let mut bots = self.bots.lock().unwrap();
+        let Some(bot) = bots.get_mut(bot_name) else {
+            return events;
+        };
+        for held in events.into_iter().rev() {
+            bot.held.push_front(held);
+        }
+        Vec::new()
+    }
+
+    /// Unpause a bot and drop its held events.
+    ///
+    /// Used when there is no queue to release the events to. Returns the
+    /// number of events dropped.
+    pub fn discard(&self, bot_name: &BotName) -> usize {
+        self.bots
+            .lock()
+            .unwrap()
+            .remove(bot_name)
+            .map(|bot| bot.held.len())
+            .unwrap_or(0)
+    }
+
+    /// Drop the held events of a session, for a session reset.
+    ///
+    /// Returns the number of held deliveries dropped.
+    pub fn discard_session(&self, session_id: &SessionId) -> usize {
+        let mut bots = self.bots.lock().unwrap();
+        let mut discarded = 0;
+        for bot in bots.values_mut() {
+            let before = bot.held.len();
+            bot.held
+                .retain(|held| held.event.session_id.as_ref() != Some(session_id));
+            discarded += before - bot.held.len();
+        }
+        discarded
+    }
+}
+
+// ============================================================================
+// Release
+// ============================================================================
+
+/// Release the held events of a resuming bot, oldest first.
+///
+/// Each event goes through the normal delivery path — retries, DLQ and
+/// session epochs — restricted to this bot. Stops when the hold queue is
+/// empty, which unpauses the bot, or when the bot is paused again. Returns
+/// the number of events released.
+pub async fn release_held_events(
+    bot_name: BotName,
+    event_router: Arc<dyn EventRouter>,
+    bot_config: Arc<BotConfiguration>,
+    queue_client: Arc<dyn QueueClient>,
+    delivery_config: QueueDeliveryConfig,
+) -> usize {
+    let registry = delivery_config.bot_pauses.clone();
+    let Some(bot) = bot_config.bots.iter().find(|b| b.name == bot_name) else {
+        let dropped = registry.discard(&bot_name);
+        warn!(
+            bot_name = %bot_name,
+            dropped,
+            "Bot is no longer configured; dropped its held events"
+        );
+        return 0;
+    };
+    let single_bot = Arc::new(BotConfiguration {
+        bots: vec![bot.clone()],
+        settings: bot_config.settings.clone(),
+    });
+
+    // The bot stays registered while resuming so new events queue up behind
+    // the held ones; the released events themselves must bypass the registry.
+    let release_config = QueueDeliveryConfig {
+        bot_pauses: Arc::new(BotPauseRegistry::new()),
+        ..delivery_config
+    };
+
+    let mut released = 0;
+    while let Some(event) = registry.next_held(&bot_name) {
+        deliver_event_to_queues(
+            event,
+            event_router.clone(),
+            single_bot.clone(),
+            queue_client.clone(),
+            release_config.clone(),
+        )
+        .await;
+        released += 1;
+    }
+
+    info!(
+        bot_name = %bot_name,
+        released,
+        resumed = !registry.is_paused(&bot_name),
+        "Released held events"
+    );
+    released
+}
+
+#[cfg(test)]
+#[path = "bot_pause_tests.rs"]
+mod tests;
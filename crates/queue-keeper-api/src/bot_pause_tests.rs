@@ -0,0 +1,156 @@
+//! Tests for per-bot delivery pause.
+
+use super::*;
+use crate::test_fixtures::{bot, bot_config, session_event};
+
+/// Verify that events are routed unchanged when no bot is paused.
+#[test]
+fn test_no_paused_bots_keeps_configuration() {
+    let registry = BotPauseRegistry::new();
+    let config = bot_config(&["alpha", "beta"]);
+
+    let routed =
+        registry.hold_for_paused_bots(&session_event("owner/repo/pull_request/1"), config.clone());
+
+    assert!(Arc::ptr_eq(&routed, &config));
+}
+
+/// Verify that an event is held for the paused bot and still routed to the
+/// other bots.
+#[test]
+fn test_paused_bot_is_held_and_removed_from_routing() {
+    let registry = BotPauseRegistry::new();
+    registry.pause(&bot("alpha"), "ops", "maintenance");
+
+    let routed = registry.hold_for_paused_bots(
+        &session_event("owner/repo/pull_request/1"),
+        bot_config(&["alpha", "beta"]),
+    );
+
+    let names: Vec<_> = routed.bots.iter().map(|b| b.name.as_str()).collect();
+    assert_eq!(names, vec!["beta"]);
+    let status = &registry.statuses()[0];
+    assert_eq!(status.bot_name, bot("alpha"));
+    assert_eq!(status.state, BotPauseState::Paused);
+    assert_eq!(status.paused_by, "ops");
+    assert_eq!(status.reason, "maintenance");
+    assert_eq!(status.held_events, 1);
+}
+
+/// Verify that pausing an already paused bot keeps its original details.
+#[test]
+fn test_pause_twice_keeps_original_reason() {
+    let registry = BotPauseRegistry::new();
+    registry.pause(&bot("alpha"), "ops", "maintenance");
+
+    let status = registry.pause(&bot("alpha"), "someone-else", "again");
+
+    assert_eq!(status.paused_by, "ops");
+    assert_eq!(status.reason, "maintenance");
+}
+
+/// Verify that only paused bots can be resumed.
+#[test]
+fn test_resume_requires_paused_bot() {
+    let registry = BotPauseRegistry::new();
+    assert!(registry.resume(&bot("alpha")).is_none());
+
+    registry.pause(&bot("alpha"), "ops", "maintenance");
+    let status = registry.resume(&bot("alpha")).unwrap();
+
+    assert_eq!(status.state, BotPauseState::Resuming);
+    assert!(registry.is_paused(&bot("alpha")));
+}
+
+/// Verify that held events are released in arrival order and that the bot
+/// is unpaused once its hold queue is empty.
+#[test]
+fn test_held_events_released_in_order() {
+    let registry = BotPauseRegistry::new();
+    let config = bot_config(&["alpha"]);
+    registry.pause(&bot("alpha"), "ops", "maintenance");
+    let first = session_event("owner/repo/pull_request/1");
+    let second = session_event("owner/repo/pull_request/2");
+    registry.hold_for_paused_bots(&first, config.clone());
+    registry.hold_for_paused_bots(&second, config);
+    registry.resume(&bot("alpha"));
+
+    assert_eq!(
+        registry.next_held(&bot("alpha")).unwrap().event_id,
+        first.event_id
+    );
+    assert_eq!(
+        registry.next_held(&bot("alpha")).unwrap().event_id,
+        second.event_id
+    );
+    assert!(registry.next_held(&bot("alpha")).is_none());
+    assert!(!registry.is_paused(&bot("alpha")));
+}
+
+/// Verify that pausing a resuming bot stops the release.
+#[test]
+fn test_pause_while_resuming_stops_release() {
+    let registry = BotPauseRegistry::new();
+    registry.pause(&bot("alpha"), "ops", "maintenance");
+    registry.hold_for_paused_bots(
+        &session_event("owner/repo/pull_request/1"),
+        bot_config(&["alpha"]),
+    );
+    registry.resume(&bot("alpha"));
+
+    let status = registry.pause(&bot("alpha"), "ops", "maintenance");
+
+    assert_eq!(status.state, BotPauseState::Paused);
+    assert!(registry.next_held(&bot("alpha")).is_none());
+    assert_eq!(registry.statuses()[0].held_events, 1);
+}
+
+/// Verify that a session reset drops only the held events of that session.
+#[test]
+fn test_discard_session_drops_matching_events() {
+    let registry = BotPauseRegistry::new();
+    let config = bot_config(&["alpha", "beta"]);
+    registry.pause(&bot("alpha"), "ops", "maintenance");
+    registry.pause(&bot("beta"), "ops", "maintenance");
+    registry.hold_for_paused_bots(&session_event("owner/repo/pull_request/1"), config.clone());
+    registry.hold_for_paused_bots(&session_event("owner/repo/pull_request/2"), config);
+
+    let discarded =
+        registry.discard_session(&SessionId::from_parts("owner", "repo", "pull_request", "1"));
+
+    assert_eq!(discarded, 2);
+    assert!(registry.statuses().iter().all(|s| s.held_events == 1));
+}
+
+/// Verify that old held events can be taken for diversion and put back in
+/// their original order.
+#[test]
+fn test_take_and_return_held_events_keeps_order() {
+    let registry = BotPauseRegistry::new();
+    let config = bot_config(&["alpha"]);
+    registry.pause(&bot("alpha"), "ops", "maintenance");
+    let first = session_event("owner/repo/pull_request/1");
+    let second = session_event("owner/repo/pull_request/2");
+    registry.hold_for_paused_bots(&first, config.clone());
+    registry.hold_for_paused_bots(&second, config);
+    let later = Timestamp::now().add_seconds(60);
+
+    assert!(registry
+        .take_held_older_than(&bot("alpha"), Duration::from_secs(3600), later)
+        .is_empty());
+    let taken = registry.take_held_older_than(&bot("alpha"), Duration::from_secs(30), later);
+    assert_eq!(taken.len(), 2);
+    assert!(registry.held_backlogs().is_empty());
+    assert!(registry.is_paused(&bot("alpha")));
+
+    assert!(registry.return_held(&bot("alpha"), taken).is_empty());
+    registry.resume(&bot("alpha"));
+    assert_eq!(
+        registry.next_held(&bot("alpha")).unwrap().event_id,
+        first.event_id
+    );
+    assert_eq!(
+        registry.next_held(&bot("alpha")).unwrap().event_id,
+        second.event_id
+    );
+}
@@ -1,11 +1,46 @@
 //! Configuration types for the HTTP service
 
-use crate::azure_config::AzureKeyVaultConfig;
+use crate::authorization::AdminRole;
+use crate::azure_config::{AzureCloud, AzureKeyVaultConfig};
+use crate::deduplication::{DeduplicationConfig, DeduplicationStoreConfig};
+use crate::delivery_audit::DeliveryAuditConfig;
+use crate::developer::DeveloperConfig;
 use crate::errors::ConfigError;
+use crate::escalation::EscalationConfig;
+use crate::event_deletion::EventDeletionConfig;
+use crate::event_stream::EventStreamConfig;
+use crate::freshness::FreshnessConfig;
+use crate::gateway::GatewayConfig;
+use crate::github_app::GitHubAppConfig;
+use crate::outbox::OutboxConfig;
+use crate::payload_profiler::PayloadProfilerConfig;
+use crate::queue_depth::QueueDepthConfig;
+use crate::responses::EventListParams;
+use crate::retry::{RetryConfig, RetryPolicy};
+use crate::session_shards::DeliveryWorkerConfig;
+use crate::shutdown_report::ShutdownReportConfig;
+use crate::supervisor::SupervisorConfig;
+use crate::telemetry::TelemetryExportConfig;
+use crate::token_cache::TokenCacheConfig;
+use crate::unknown_event_types::UnknownEventTypesConfig;
+use ipnet::IpNet;
+use queue_keeper_core::adapters::FailoverConfig;
+use queue_keeper_core::blob_storage::ContentEncoding;
+use queue_keeper_core::key_vault::SecretName;
+use queue_keeper_core::payload_retention::PayloadRetentionConfig;
+use queue_keeper_core::pubsub::RoutingConfig;
+use queue_keeper_core::session_tracker::SessionTrackerConfig;
+use queue_keeper_core::units::{ByteSize, HumanDuration};
 use queue_keeper_core::webhook::generic_provider::{
     GenericProviderConfig, GenericProviderConfigError, WebhookSecretConfig,
 };
+use queue_keeper_core::webhook::{
+    EnvelopeExtensionRegistry, EnvelopeExtensionsConfig, ReplayProtectionConfig,
+    StorageSamplingConfig, WebhookSourceConfig, MAX_CANDIDATE_SECRETS, PRIMARY_SECRET_LABEL,
+};
+use queue_keeper_core::Timestamp;
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Service configuration
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -34,6 +69,15 @@ pub struct ServiceConfig {
     #[serde(default)]
     pub providers: Vec<ProviderConfig>,
 
+    /// REST API base URL of the GitHub instance behind providers without
+    /// `github_enterprise` settings.
+    ///
+    /// Defaults to [`DEFAULT_GITHUB_API_URL`]. Providers with
+    /// [`GitHubEnterpriseConfig`] use their instance's API URL instead; see
+    /// [`api_url_for`](Self::api_url_for).
+    #[serde(default)]
+    pub github_api_url: Option<String>,
+
     /// Configuration-driven generic webhook providers.
     ///
     /// Each entry registers a non-GitHub provider (e.g. `jira`, `slack`)
@@ -46,6 +90,19 @@ pub struct ServiceConfig {
     #[serde(default)]
     pub generic_providers: Vec<GenericProviderConfig>,
 
+    /// Webhook sources for other forges (GitLab, Bitbucket).
+    ///
+    /// Each entry registers a [`WebhookSourceConfig`] under its provider
+    /// ID, served at `/webhook/{id}`. Source events are normalized into
+    /// the same envelope and session model as GitHub events. An empty list
+    /// is valid.
+    ///
+    /// Provider IDs must not conflict with IDs in
+    /// [`providers`](Self::providers) or
+    /// [`generic_providers`](Self::generic_providers).
+    #[serde(default)]
+    pub webhook_sources: Vec<WebhookSourceConfig>,
+
     /// Azure Key Vault configuration.
     ///
     /// Required when any provider in [`providers`](Self::providers) or
@@ -70,6 +127,133 @@ pub struct ServiceConfig {
     /// YAML shapes.
     #[serde(default)]
     pub queue: QueueBackendConfig,
+
+    /// Whether events go to one queue per bot or to a shared topic.
+    #[serde(default)]
+    pub routing: RoutingConfig,
+
+    /// Outbound network settings (proxy and additional trusted CAs).
+    ///
+    /// Applied to all egress — GitHub, Key Vault and queue providers — by
+    /// [`outbound::apply_network_config`](crate::outbound::apply_network_config)
+    /// at startup.
+    #[serde(default)]
+    pub network: NetworkConfig,
+
+    /// Cloud endpoint overrides for sovereign clouds and S3-compatible
+    /// storage.
+    #[serde(default)]
+    pub endpoints: EndpointConfig,
+
+    /// Delivery worker settings, including sticky session shards.
+    #[serde(default)]
+    pub delivery: DeliveryWorkerConfig,
+
+    /// Retry policies of queue delivery and blob storage.
+    #[serde(default)]
+    pub retry: RetryConfig,
+
+    /// Built-in envelope extensions added to GitHub events.
+    #[serde(default)]
+    pub extensions: EnvelopeExtensionsConfig,
+
+    /// Age thresholds for the bot configuration and cached secrets.
+    #[serde(default)]
+    pub freshness: FreshnessConfig,
+
+    /// Age thresholds for escalating held and failing bot deliveries.
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+
+    /// Restart backoff and shutdown timeout for background jobs.
+    #[serde(default)]
+    pub supervisor: SupervisorConfig,
+
+    /// GitHub App credentials used to authenticate to the GitHub API.
+    ///
+    /// Requires the `key_vault` section, which holds the App's private key.
+    #[serde(default)]
+    pub github_app: Option<GitHubAppConfig>,
+
+    /// Shared cache of GitHub App installation tokens.
+    #[serde(default)]
+    pub token_cache: TokenCacheConfig,
+
+    /// Destinations receiving re-signed copies of raw GitHub deliveries.
+    #[serde(default)]
+    pub gateways: Vec<GatewayConfig>,
+
+    /// Opt-in sampling of payload field statistics.
+    #[serde(default)]
+    pub payload_profiler: PayloadProfilerConfig,
+
+    /// Handling of GitHub event types missing from the known-types registry.
+    #[serde(default)]
+    pub unknown_event_types: UnknownEventTypesConfig,
+
+    /// Audit records of messages accepted by bot queues.
+    #[serde(default)]
+    pub delivery_audit: DeliveryAuditConfig,
+
+    /// Durable outbox of accepted events awaiting queue delivery.
+    #[serde(default)]
+    pub outbox: OutboxConfig,
+
+    /// Developer mode; normally switched on with `--dev`.
+    #[serde(default)]
+    pub developer: DeveloperConfig,
+
+    /// Session tracking behind the `/api/sessions` routes.
+    #[serde(default)]
+    pub sessions: SessionTrackerConfig,
+
+    /// Per-event-type rules deciding which payloads are stored.
+    #[serde(default)]
+    pub storage_sampling: StorageSamplingConfig,
+
+    /// Rejection of GitHub deliveries whose delivery ID was already received.
+    #[serde(default)]
+    pub replay_protection: ReplayProtectionConfig,
+
+    /// Processing of each GitHub delivery ID at most once.
+    #[serde(default)]
+    pub deduplication: DeduplicationConfig,
+
+    /// Time range guardrails of `GET /api/events`.
+    #[serde(default)]
+    pub event_queries: EventQueryConfig,
+
+    /// Soft deletion and purge of stored events.
+    #[serde(default)]
+    pub event_deletion: EventDeletionConfig,
+
+    /// Compression and encryption of payloads written to event storage.
+    #[serde(default)]
+    pub event_storage: EventStorageConfig,
+
+    /// Secondary event storage receiving writes while the primary fails.
+    #[serde(default)]
+    pub event_storage_failover: EventStorageFailoverConfig,
+
+    /// Live stream of processed events at `GET /api/events/stream`.
+    #[serde(default)]
+    pub event_stream: EventStreamConfig,
+
+    /// Time to live of stored payloads and what happens once it passed.
+    #[serde(default)]
+    pub payload_retention: PayloadRetentionConfig,
+
+    /// Periodic sampling of the depth of every bot queue.
+    #[serde(default)]
+    pub queue_depth: QueueDepthConfig,
+
+    /// Report of how the process stopped, served on the next start.
+    #[serde(default)]
+    pub shutdown_report: ShutdownReportConfig,
+
+    /// OpenTelemetry trace export to an OTLP collector.
+    #[serde(default)]
+    pub telemetry: TelemetryExportConfig,
 }
 
 impl ServiceConfig {
@@ -136,18 +320,69 @@ impl ServiceConfig {
                 });
             }
         }
+        for source in &self.webhook_sources {
+            source
+                .validate()
+                .map_err(|message| ConfigError::ProviderValidation { message })?;
+            if !seen.insert(source.provider_id()) {
+                return Err(ConfigError::ProviderValidation {
+                    message: format!(
+                        "duplicate provider ID '{}': each provider ID must be unique across providers, generic_providers and webhook_sources",
+                        source.provider_id()
+                    ),
+                });
+            }
+        }
+
+        if let Some(url) = &self.github_api_url {
+            if https_host(url).is_empty() {
+                return Err(ConfigError::ProviderValidation {
+                    message: format!("github_api_url must be an https:// URL (got '{}')", url),
+                });
+            }
+        }
+
+        // Deliveries are matched to GHES providers by their host, so each
+        // instance may only be configured once.
+        let mut enterprise_hosts = std::collections::HashSet::new();
+        for provider in &self.providers {
+            if let Some(enterprise) = &provider.github_enterprise {
+                if !enterprise_hosts.insert(enterprise.expected_host()) {
+                    return Err(ConfigError::ProviderValidation {
+                        message: format!(
+                            "provider '{}': enterprise host '{}' is already used by another provider",
+                            provider.id,
+                            enterprise.expected_host()
+                        ),
+                    });
+                }
+            }
+        }
 
         // Verify Key Vault configuration is present when any provider requires it.
         let needs_key_vault = self
             .providers
             .iter()
-            .any(|p| matches!(&p.secret, Some(ProviderSecretConfig::KeyVault { .. })))
+            .flat_map(ProviderConfig::secret_sources)
+            .any(|s| matches!(s, ProviderSecretConfig::KeyVault { .. }))
             || self.generic_providers.iter().any(|p| {
                 matches!(
                     &p.webhook_secret,
                     Some(WebhookSecretConfig::KeyVault { .. })
                 )
-            });
+            })
+            || self.webhook_sources.iter().any(|s| {
+                matches!(
+                    &s.webhook_secret,
+                    Some(WebhookSecretConfig::KeyVault { .. })
+                )
+            })
+            || self
+                .gateways
+                .iter()
+                .any(|g| matches!(&g.secret, ProviderSecretConfig::KeyVault { .. }))
+            || self.event_storage.encryption_key_secret.is_some()
+            || self.github_app.is_some();
         // EnvironmentVariable and Literal secrets do not require Key Vault.
 
         if needs_key_vault {
@@ -183,8 +418,292 @@ impl ServiceConfig {
             .validate()
             .map_err(|msg| ConfigError::ProviderValidation { message: msg })?;
 
+        self.routing
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+        if self.routing.is_topic() && matches!(self.queue, QueueBackendConfig::AwsSqs { .. }) {
+            return Err(ConfigError::Invalid {
+                message: "routing.mode 'topic' requires the azure_service_bus or in_memory \
+                          queue backend; the aws_sqs backend cannot publish to SNS topics"
+                    .to_string(),
+            });
+        }
+
+        if self.server.max_body_size.as_u64() == 0 {
+            return Err(ConfigError::Invalid {
+                message: "server.max_body_size must be greater than zero".to_string(),
+            });
+        }
+
+        self.webhooks
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.security.validate()?;
+
+        self.network.validate()?;
+
+        self.endpoints
+            .validate(&self.queue, self.key_vault.as_ref())?;
+
+        self.delivery
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.retry
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.freshness
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.escalation
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.supervisor
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        if let Some(github_app) = &self.github_app {
+            github_app.validate()?;
+        }
+
+        self.token_cache.validate()?;
+
+        self.payload_profiler
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.unknown_event_types
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.delivery_audit
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.outbox
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.developer
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.sessions
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.storage_sampling
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.replay_protection
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.deduplication
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.event_queries
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.event_deletion
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.event_storage
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.event_storage_failover
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.event_stream
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.payload_retention
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.queue_depth
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.shutdown_report
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        self.telemetry
+            .validate()
+            .map_err(|message| ConfigError::Invalid { message })?;
+
+        let mut gateway_names = std::collections::HashSet::new();
+        for gateway in &self.gateways {
+            gateway.validate()?;
+            if !gateway_names.insert(gateway.name.as_str()) {
+                return Err(ConfigError::Invalid {
+                    message: format!("duplicate gateway name '{}'", gateway.name),
+                });
+            }
+        }
+
+        EnvelopeExtensionRegistry::from_config(&self.extensions).map_err(|e| {
+            ConfigError::Invalid {
+                message: e.to_string(),
+            }
+        })?;
+
         Ok(())
     }
+
+    /// Retry policy for queue delivery: `retry.delivery`, or `delivery.retry`
+    /// when that section is absent.
+    pub fn delivery_retry_policy(&self) -> &RetryPolicy {
+        self.retry.delivery.as_ref().unwrap_or(&self.delivery.retry)
+    }
+
+    /// REST API base URL for the given provider, without a trailing slash.
+    ///
+    /// Providers with `github_enterprise` settings use their instance's API
+    /// URL; every other provider uses `github_api_url`, which defaults to
+    /// [`DEFAULT_GITHUB_API_URL`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_api::config::ServiceConfig;
+    ///
+    /// let mut config = ServiceConfig::default();
+    /// assert_eq!(config.api_url_for("github"), "https://api.github.com");
+    ///
+    /// config.github_api_url = Some("https://github-proxy.corp.example/".to_string());
+    /// assert_eq!(config.api_url_for("github"), "https://github-proxy.corp.example");
+    /// ```
+    pub fn api_url_for(&self, provider_id: &str) -> String {
+        let enterprise = self
+            .providers
+            .iter()
+            .find(|p| p.id == provider_id)
+            .and_then(|p| p.github_enterprise.as_ref());
+        match (enterprise, &self.github_api_url) {
+            (Some(enterprise), _) => enterprise.api_url(),
+            (None, Some(url)) => url.trim_end_matches('/').to_string(),
+            (None, None) => DEFAULT_GITHUB_API_URL.to_string(),
+        }
+    }
+
+    /// The provider whose `github_enterprise` settings expect deliveries
+    /// from `host`, compared case-insensitively.
+    ///
+    /// Used to resolve deliveries carrying an `X-GitHub-Enterprise-Host`
+    /// header to the provider, and so the webhook secret, of that instance.
+    pub fn enterprise_provider(&self, host: &str) -> Option<&ProviderConfig> {
+        let host = host.trim().to_ascii_lowercase();
+        self.providers.iter().find(|p| {
+            p.github_enterprise
+                .as_ref()
+                .is_some_and(|enterprise| enterprise.expected_host() == host)
+        })
+    }
+}
+
+/// REST API base URL of github.com.
+pub const DEFAULT_GITHUB_API_URL: &str = "https://api.github.com";
+
+// ============================================================================
+// Redaction
+// ============================================================================
+
+/// Placeholder that replaces secret values in [`RedactedServiceConfig`].
+pub const REDACTED_VALUE: &str = "***";
+
+/// A [`ServiceConfig`] serialized with every secret-bearing field replaced
+/// by [`REDACTED_VALUE`], as returned by `GET /admin/config`.
+///
+/// Plain serialization already omits most secrets. The redacted form shows
+/// `***` where one is set instead, so operators can see that the admin API
+/// key, a Redis URL, a connection string or a proxy password is configured
+/// without seeing it. Literal webhook secrets are shown as `***` as well.
+#[derive(Debug, Clone, Copy)]
+pub struct RedactedServiceConfig<'a>(pub &'a ServiceConfig);
+
+impl RedactedServiceConfig<'_> {
+    /// The redacted configuration as a JSON value.
+    pub fn to_value(&self) -> serde_json::Value {
+        let config = self.0;
+        let mut value = serde_json::to_value(config).expect("ServiceConfig must be serialisable");
+        redact_literal_secrets(&mut value);
+
+        let mut set = |parent: &str, field: &str| {
+            if let Some(object) = value.pointer_mut(parent).and_then(|v| v.as_object_mut()) {
+                object.insert(field.to_string(), REDACTED_VALUE.into());
+            }
+        };
+        if config.security.admin_api_key.is_some() {
+            set("/security", "admin_api_key");
+        }
+        for index in 0..config.security.admin_principals.len() {
+            set(&format!("/security/admin_principals/{}", index), "api_key");
+        }
+        if let RateLimiterStoreConfig::Redis { url, .. } = &config.security.rate_limiter_store {
+            if !url.is_empty() {
+                set("/security/rate_limiter_store", "url");
+            }
+        }
+        if let crate::token_cache::TokenCacheStoreConfig::Redis { url, .. } =
+            &config.token_cache.store
+        {
+            if !url.is_empty() {
+                set("/token_cache/store", "url");
+            }
+        }
+        if let DeduplicationStoreConfig::Redis { url, .. } = &config.deduplication.store {
+            if !url.is_empty() {
+                set("/deduplication/store", "url");
+            }
+        }
+        if let QueueBackendConfig::AzureServiceBus {
+            connection_string: Some(_),
+            ..
+        } = &config.queue
+        {
+            set("/queue", "connection_string");
+        }
+        if matches!(&config.network.proxy, Some(proxy) if proxy.password.is_some()) {
+            set("/network/proxy", "password");
+        }
+        value
+    }
+}
+
+impl Serialize for RedactedServiceConfig<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value().serialize(serializer)
+    }
+}
+
+/// Replace the value of every `{"type": "literal", "value": ...}` secret
+/// source within `value`.
+fn redact_literal_secrets(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(object) => {
+            if object.get("type").and_then(|t| t.as_str()) == Some("literal")
+                && object.contains_key("value")
+            {
+                object.insert("value".to_string(), REDACTED_VALUE.into());
+            }
+            object.values_mut().for_each(redact_literal_secrets);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(redact_literal_secrets),
+        _ => {}
+    }
 }
 
 // ============================================================================
@@ -207,7 +726,11 @@ impl ServiceConfig {
 ///     secret: Some(ProviderSecretConfig::KeyVault {
 ///         secret_name: "github-webhook-secret".to_string(),
 ///     }),
+///     additional_secrets: vec![],
 ///     allowed_event_types: vec![],
+///     github_enterprise: None,
+///     installation_ids: vec![],
+///     per_repository_secrets: false,
 /// };
 /// assert!(config.validate().is_ok());
 /// ```
@@ -232,12 +755,44 @@ pub struct ProviderConfig {
     #[serde(default)]
     pub secret: Option<ProviderSecretConfig>,
 
+    /// Further secrets tried after `secret`, in order, while it is rotated.
+    ///
+    /// Requires `secret`. Together with it, at most
+    /// [`MAX_CANDIDATE_SECRETS`] secrets are allowed.
+    #[serde(default)]
+    pub additional_secrets: Vec<AdditionalSecretConfig>,
+
     /// Allowlist of event types this provider accepts.
     ///
     /// An empty list means all event types are accepted. Non-empty lists
     /// cause requests with unlisted event types to be rejected.
     #[serde(default)]
     pub allowed_event_types: Vec<String>,
+
+    /// GitHub Enterprise Server settings.
+    ///
+    /// Absent for providers that receive webhooks from github.com.
+    #[serde(default)]
+    pub github_enterprise: Option<GitHubEnterpriseConfig>,
+
+    /// GitHub App installation IDs registered on this source.
+    ///
+    /// Installation IDs are only unique within one GitHub instance, so each
+    /// source keeps its own registry. An empty list accepts any installation;
+    /// a non-empty list rejects events whose `installation.id` is missing or
+    /// not listed.
+    #[serde(default)]
+    pub installation_ids: Vec<u64>,
+
+    /// Resolve the webhook secret per repository.
+    ///
+    /// When `true`, each Key Vault secret (`secret` and any Key Vault
+    /// `additional_secrets`) is looked up as `{secret_name}--{owner}--{repo}`,
+    /// then `{secret_name}--{owner}`, and finally `secret_name` itself, using
+    /// the first one that exists. Requires a Key Vault `secret`. Defaults to
+    /// `false`.
+    #[serde(default)]
+    pub per_repository_secrets: bool,
 }
 
 fn default_require_signature() -> bool {
@@ -254,6 +809,11 @@ impl ProviderConfig {
     /// - `id` contains characters outside `[a-z0-9\-_]`
     /// - `require_signature` is `true` but `secret` is `None`
     /// - The `secret` source is internally invalid (e.g. empty Key Vault name)
+    /// - `additional_secrets` are set without `secret`, are too many, have
+    ///   empty or duplicate labels, or have an invalid source
+    /// - The `github_enterprise` URLs are not HTTPS URLs or the expected
+    ///   enterprise host is not a bare host name
+    /// - `per_repository_secrets` is set without a Key Vault `secret`
     pub fn validate(&self) -> Result<(), ConfigError> {
         // Validate provider ID format by delegating to ProviderId::new().
         // This ensures a single source of truth for the allowed character set
@@ -278,9 +838,292 @@ impl ProviderConfig {
         if let Some(secret) = &self.secret {
             secret.validate(&self.id)?;
         }
+        self.validate_additional_secrets()?;
 
+        if self.per_repository_secrets
+            && !matches!(self.secret, Some(ProviderSecretConfig::KeyVault { .. }))
+        {
+            return Err(ConfigError::ProviderValidation {
+                message: format!(
+                    "provider '{}': per_repository_secrets requires a Key Vault secret source",
+                    self.id
+                ),
+            });
+        }
+
+        if let Some(enterprise) = &self.github_enterprise {
+            enterprise.validate(&self.id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Validate the `additional_secrets` entries.
+    fn validate_additional_secrets(&self) -> Result<(), ConfigError> {
+        if self.additional_secrets.is_empty() {
+            return Ok(());
+        }
+        let invalid = |message: String| ConfigError::ProviderValidation {
+            message: format!("provider '{}': {}", self.id, message),
+        };
+        if self.secret.is_none() {
+            return Err(invalid(
+                "additional_secrets require a secret source".to_string(),
+            ));
+        }
+        if self.additional_secrets.len() >= MAX_CANDIDATE_SECRETS {
+            return Err(invalid(format!(
+                "at most {} additional_secrets are allowed",
+                MAX_CANDIDATE_SECRETS - 1
+            )));
+        }
+
+        let mut labels = std::collections::HashSet::from([PRIMARY_SECRET_LABEL]);
+        for additional in &self.additional_secrets {
+            if additional.label.is_empty() {
+                return Err(invalid(
+                    "additional_secrets labels must not be empty".to_string(),
+                ));
+            }
+            if !labels.insert(additional.label.as_str()) {
+                return Err(invalid(format!(
+                    "duplicate additional_secrets label '{}'",
+                    additional.label
+                )));
+            }
+            additional.source.validate(&self.id)?;
+        }
         Ok(())
     }
+
+    /// Every secret source of this provider: `secret`, then
+    /// `additional_secrets`.
+    pub fn secret_sources(&self) -> impl Iterator<Item = &ProviderSecretConfig> {
+        self.secret
+            .iter()
+            .chain(self.additional_secrets.iter().map(|a| &a.source))
+    }
+
+    /// Signature scheme accepted by this provider.
+    ///
+    /// Only GitHub Enterprise Server providers can opt in to legacy SHA-1
+    /// signatures; all other providers accept SHA-256 only.
+    pub fn signature_scheme(&self) -> GitHubSignatureScheme {
+        self.github_enterprise
+            .as_ref()
+            .map(|e| e.signature_scheme)
+            .unwrap_or_default()
+    }
+
+    /// Whether an event from `installation_id` is accepted by this source.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_api::config::ProviderConfig;
+    ///
+    /// let mut config = ProviderConfig {
+    ///     id: "ghes".to_string(),
+    ///     require_signature: false,
+    ///     secret: None,
+    ///     additional_secrets: vec![],
+    ///     allowed_event_types: vec![],
+    ///     github_enterprise: None,
+    ///     installation_ids: vec![],
+    ///     per_repository_secrets: false,
+    /// };
+    /// assert!(config.accepts_installation(None));
+    ///
+    /// config.installation_ids = vec![42];
+    /// assert!(config.accepts_installation(Some(42)));
+    /// assert!(!config.accepts_installation(Some(7)));
+    /// assert!(!config.accepts_installation(None));
+    /// ```
+    pub fn accepts_installation(&self, installation_id: Option<u64>) -> bool {
+        self.installation_ids.is_empty()
+            || installation_id.is_some_and(|id| self.installation_ids.contains(&id))
+    }
+}
+
+/// GitHub Enterprise Server settings for a GitHub-style provider.
+///
+/// # YAML example
+///
+/// ```yaml
+/// providers:
+///   - id: "ghes"
+///     secret:
+///       type: key_vault
+///       secret_name: "ghes-webhook-secret"
+///     github_enterprise:
+///       base_url: "https://github.corp.example"
+///       # api_base_url defaults to <base_url>/api/v3
+///       # host defaults to the host of base_url and must match the
+///       # X-GitHub-Enterprise-Host header of every delivery
+///       # GHES releases before 3.0 only send the SHA-1 X-Hub-Signature header
+///       signature_scheme: sha256_or_sha1
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitHubEnterpriseConfig {
+    /// Web URL of the GHES instance, e.g. `https://github.corp.example`.
+    pub base_url: String,
+
+    /// REST API base URL. Defaults to `<base_url>/api/v3`.
+    #[serde(default)]
+    pub api_base_url: Option<String>,
+
+    /// Webhook signature headers accepted from this instance.
+    #[serde(default)]
+    pub signature_scheme: GitHubSignatureScheme,
+
+    /// Expected `X-GitHub-Enterprise-Host` header value. Defaults to the
+    /// host of `base_url`.
+    #[serde(default)]
+    pub host: Option<String>,
+}
+
+impl GitHubEnterpriseConfig {
+    /// REST API base URL, without a trailing slash.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_api::config::{GitHubEnterpriseConfig, GitHubSignatureScheme};
+    ///
+    /// let config = GitHubEnterpriseConfig {
+    ///     base_url: "https://github.corp.example/".to_string(),
+    ///     api_base_url: None,
+    ///     signature_scheme: GitHubSignatureScheme::Sha256,
+    ///     host: None,
+    /// };
+    /// assert_eq!(config.api_url(), "https://github.corp.example/api/v3");
+    /// ```
+    pub fn api_url(&self) -> String {
+        match &self.api_base_url {
+            Some(url) => url.trim_end_matches('/').to_string(),
+            None => format!("{}/api/v3", self.base_url.trim_end_matches('/')),
+        }
+    }
+
+    /// Host name that deliveries from this instance must announce in the
+    /// `X-GitHub-Enterprise-Host` header, lowercased.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_api::config::{GitHubEnterpriseConfig, GitHubSignatureScheme};
+    ///
+    /// let mut config = GitHubEnterpriseConfig {
+    ///     base_url: "https://GitHub.Corp.Example/".to_string(),
+    ///     api_base_url: None,
+    ///     signature_scheme: GitHubSignatureScheme::Sha256,
+    ///     host: None,
+    /// };
+    /// assert_eq!(config.expected_host(), "github.corp.example");
+    ///
+    /// config.host = Some("ghes-primary.corp.example".to_string());
+    /// assert_eq!(config.expected_host(), "ghes-primary.corp.example");
+    /// ```
+    pub fn expected_host(&self) -> String {
+        match &self.host {
+            Some(host) => host.to_ascii_lowercase(),
+            None => https_host(&self.base_url).to_ascii_lowercase(),
+        }
+    }
+
+    /// Validate the GHES settings for the given provider ID.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::ProviderValidation`] when `base_url` or
+    /// `api_base_url` is not an `https://` URL with a host, or when `host`
+    /// is not a bare host name.
+    pub fn validate(&self, provider_id: &str) -> Result<(), ConfigError> {
+        let urls = std::iter::once(("base_url", self.base_url.as_str())).chain(
+            self.api_base_url
+                .as_deref()
+                .map(|url| ("api_base_url", url)),
+        );
+        for (field, url) in urls {
+            if https_host(url).is_empty() {
+                return Err(ConfigError::ProviderValidation {
+                    message: format!(
+                        "provider '{}': github_enterprise.{} must be an https:// URL (got '{}')",
+                        provider_id, field, url
+                    ),
+                });
+            }
+        }
+        if let Some(host) = &self.host {
+            let valid = !host.is_empty()
+                && host
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | ':'));
+            if !valid {
+                return Err(ConfigError::ProviderValidation {
+                    message: format!(
+                        "provider '{}': github_enterprise.host must be a host name without scheme or path (got '{}')",
+                        provider_id, host
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Host part of an `https://` URL, or `""` when `url` is not HTTPS.
+fn https_host(url: &str) -> &str {
+    url.strip_prefix("https://")
+        .and_then(|rest| rest.split('/').next())
+        .unwrap_or_default()
+}
+
+/// Webhook signature headers accepted from a GitHub instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GitHubSignatureScheme {
+    /// `X-Hub-Signature-256` (HMAC-SHA256) only.
+    #[default]
+    Sha256,
+
+    /// `X-Hub-Signature-256` when present, otherwise the legacy
+    /// `X-Hub-Signature` (HMAC-SHA1) sent by GHES releases before 3.0.
+    Sha256OrSha1,
+}
+
+/// A secret tried after a provider's main `secret` while it is rotated.
+///
+/// Deliveries signed with a `retired` secret are refused with a
+/// retired-secret error, so senders still using it can be identified. The
+/// label is logged with every match; the secret never is.
+///
+/// # YAML example
+///
+/// ```yaml
+/// additional_secrets:
+///   - label: previous
+///     source:
+///       type: key_vault
+///       secret_name: "github-webhook-secret-previous"
+///   - label: "2025-q4"
+///     retired: true
+///     source:
+///       type: key_vault
+///       secret_name: "github-webhook-secret-2025-q4"
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdditionalSecretConfig {
+    /// Name logged when this secret matches. Must be unique per provider
+    /// and differ from `primary`.
+    pub label: String,
+
+    /// Whether deliveries signed with this secret are refused.
+    #[serde(default)]
+    pub retired: bool,
+
+    /// Where the secret is read from.
+    pub source: ProviderSecretConfig,
 }
 
 /// Source for a provider's HMAC-SHA256 webhook secret.
@@ -424,6 +1267,146 @@ impl ProviderSecretConfig {
     }
 }
 
+/// Time range guardrails of event listings (the `event_queries`
+/// configuration section).
+///
+/// A listing without `since` reads every stored event. Both guardrails are
+/// off by default.
+///
+/// ```yaml
+/// event_queries:
+///   require_since: true
+///   max_lookback: 7d
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventQueryConfig {
+    /// Reject listings without a `since` time range.
+    pub require_since: bool,
+
+    /// Reject listings whose `since` lies further back than this. Implies
+    /// `require_since`.
+    pub max_lookback: Option<HumanDuration>,
+}
+
+impl EventQueryConfig {
+    /// Validate the guardrails.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_lookback.is_some_and(|d| d.is_zero()) {
+            return Err("event_queries.max_lookback must not be zero".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether listings must be bounded by a time range.
+    pub fn requires_time_range(&self) -> bool {
+        self.require_since || self.max_lookback.is_some()
+    }
+
+    /// Check the time range of a listing at `now`.
+    ///
+    /// # Errors
+    /// Returns why the listing is rejected: `since` is missing or invalid
+    /// while a time range is required, or it lies beyond `max_lookback`.
+    pub fn check(&self, params: &EventListParams, now: Timestamp) -> Result<(), String> {
+        if !self.requires_time_range() {
+            return Ok(());
+        }
+        let Some(since) = params.since.as_deref() else {
+            return Err("a time range is required: set `since`".to_string());
+        };
+        let since = Timestamp::from_rfc3339(since)
+            .map_err(|_| format!("`since` is not an RFC 3339 timestamp: '{}'", since))?;
+        if let Some(max_lookback) = self.max_lookback {
+            if now.duration_since(since) > max_lookback.as_duration() {
+                return Err(format!(
+                    "`since` may lie at most {} in the past",
+                    max_lookback
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Event storage settings (the `event_storage` configuration section).
+///
+/// Payload bodies are compressed, then encrypted, before they are written;
+/// stored payloads are decrypted and decompressed on read whatever the
+/// current settings, so either can be switched on or changed without
+/// migrating existing data. Encryption requires the `key_vault` section.
+///
+/// ```yaml
+/// event_storage:
+///   compression: zstd   # identity (default) | gzip | zstd
+///   encryption_key_secret: queue-keeper-payload-key
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventStorageConfig {
+    /// Compression of payload bodies written to event storage.
+    pub compression: ContentEncoding,
+
+    /// Key Vault secret holding the base64 key that wraps the data key of
+    /// each payload; payloads are not encrypted when unset.
+    pub encryption_key_secret: Option<String>,
+}
+
+impl EventStorageConfig {
+    /// Validate the settings.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(secret) = &self.encryption_key_secret {
+            SecretName::new(secret)
+                .map_err(|e| format!("event_storage.encryption_key_secret: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Failover of event storage to a secondary location (the
+/// `event_storage_failover` configuration section).
+///
+/// Off unless `secondary_path` is set; see
+/// [`FailoverBlobStorage`](queue_keeper_core::adapters::FailoverBlobStorage).
+///
+/// ```yaml
+/// event_storage_failover:
+///   secondary_path: /mnt/secondary/events
+///   compression: gzip
+///   failure_threshold: 3
+///   reconcile_interval: 1m
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventStorageFailoverConfig {
+    /// Event storage written to while the primary fails.
+    pub secondary_path: Option<PathBuf>,
+
+    /// Compression of payload bodies written to the secondary storage;
+    /// defaults to `event_storage.compression`.
+    pub compression: Option<ContentEncoding>,
+
+    /// When to fail over, and how often to reconcile.
+    #[serde(flatten)]
+    pub failover: FailoverConfig,
+}
+
+impl EventStorageFailoverConfig {
+    /// Validate the settings.
+    pub fn validate(&self) -> Result<(), String> {
+        if self
+            .secondary_path
+            .as_ref()
+            .is_some_and(|p| p.as_os_str().is_empty())
+        {
+            return Err("event_storage_failover.secondary_path must not be empty".to_string());
+        }
+        self.failover
+            .validate()
+            .map_err(|message| format!("event_storage_failover.{}", message))
+    }
+}
+
 /// HTTP server configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerConfig {
@@ -433,14 +1416,19 @@ pub struct ServerConfig {
     /// Port to listen on
     pub port: u16,
 
-    /// Request timeout in seconds
-    pub timeout_seconds: u64,
+    /// Request timeout, e.g. `30s`. A bare number is seconds; the legacy
+    /// `timeout_seconds` key is still accepted.
+    #[serde(alias = "timeout_seconds")]
+    pub timeout: HumanDuration,
 
-    /// Graceful shutdown timeout in seconds
-    pub shutdown_timeout_seconds: u64,
+    /// Graceful shutdown timeout, e.g. `30s`. A bare number is seconds; the
+    /// legacy `shutdown_timeout_seconds` key is still accepted.
+    #[serde(alias = "shutdown_timeout_seconds")]
+    pub shutdown_timeout: HumanDuration,
 
-    /// Maximum request size in bytes
-    pub max_body_size: usize,
+    /// Maximum webhook request body size, e.g. `10MB`. A bare number is
+    /// bytes. Larger bodies are rejected with HTTP 413.
+    pub max_body_size: ByteSize,
 
     /// Enable CORS
     pub enable_cors: bool,
@@ -454,9 +1442,9 @@ impl Default for ServerConfig {
         Self {
             host: "0.0.0.0".to_string(),
             port: 8080,
-            timeout_seconds: 30,
-            shutdown_timeout_seconds: 30,
-            max_body_size: 10 * 1024 * 1024, // 10MB
+            timeout: HumanDuration::from_secs(30),
+            shutdown_timeout: HumanDuration::from_secs(30),
+            max_body_size: ByteSize::mib(10),
             enable_cors: true,
             enable_compression: true,
         }
@@ -475,6 +1463,9 @@ impl Default for ServerConfig {
 /// settings that do not yet have a per-provider equivalent
 /// (e.g. `store_payloads`, `rate_limit_per_repo`).
 ///
+/// `rate_limit_per_repo` is enforced by the webhook handler for every
+/// provider; see [`crate::repository_rate_limit`].
+///
 /// > **Note**: `require_signature` in `WebhookConfig` and `ProviderConfig` is
 /// > **not** enforced by the routing layer. Signature validation is delegated
 /// > entirely to the processor's [`SignatureValidator`]. The field is present for
@@ -496,7 +1487,8 @@ pub struct WebhookConfig {
     /// Per-provider filtering is configured via [`ProviderConfig::allowed_event_types`].
     pub allowed_event_types: Vec<String>,
 
-    /// Maximum events per repository per minute
+    /// Maximum events per repository per minute, over a sliding window
+    /// (`None` = unlimited). Deliveries over the limit receive HTTP 429.
     pub rate_limit_per_repo: Option<u32>,
 }
 
@@ -512,18 +1504,37 @@ impl Default for WebhookConfig {
     }
 }
 
+impl WebhookConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.rate_limit_per_repo == Some(0) {
+            return Err(
+                "webhooks.rate_limit_per_repo must be greater than zero; omit it to disable \
+                 per-repository rate limiting"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
 /// Security configuration
 #[derive(Clone, Serialize, Deserialize)]
 pub struct SecurityConfig {
-    /// Enable request rate limiting
+    /// Enable request rate limiting on the webhook, API and admin routes
+    /// (see [`crate::request_rate_limit`]). Off by default.
     #[serde(default = "SecurityConfig::default_enable_rate_limiting")]
     pub enable_rate_limiting: bool,
 
-    /// Global rate limit (requests per minute)
+    /// Global rate limit (requests per minute, shared by all clients)
     #[serde(default = "SecurityConfig::default_global_rate_limit")]
     pub global_rate_limit: u32,
 
-    /// Enable IP-based rate limiting
+    /// Enable IP-based rate limiting: the per-IP request limit while
+    /// `enable_rate_limiting` is on, and authentication-failure escalation
     #[serde(default = "SecurityConfig::default_enable_ip_rate_limiting")]
     pub enable_ip_rate_limiting: bool,
 
@@ -569,6 +1580,22 @@ pub struct SecurityConfig {
     #[serde(default = "SecurityConfig::default_auth_block_duration_secs")]
     pub auth_block_duration_secs: u64,
 
+    /// Where rate limiter state is kept.
+    ///
+    /// The default in-memory store is per process; when several replicas run
+    /// behind a load balancer, use a shared store so that an IP is counted
+    /// and restricted consistently across all of them.
+    #[serde(default)]
+    pub rate_limiter_store: RateLimiterStoreConfig,
+
+    /// Reverse proxies whose `X-Forwarded-For` and `X-Real-IP` headers are
+    /// trusted, as addresses or CIDR ranges.
+    ///
+    /// Requests from any other peer are keyed on the peer address, so a
+    /// client cannot pick the IP it is rate limited and audited as.
+    #[serde(default, deserialize_with = "deserialize_trusted_proxies")]
+    pub trusted_proxies: Vec<IpNet>,
+
     /// Enable request logging
     #[serde(default = "SecurityConfig::default_log_requests")]
     pub log_requests: bool,
@@ -590,6 +1617,39 @@ pub struct SecurityConfig {
     /// returned by the `/admin/config` endpoint or written to log output.
     #[serde(default, skip_serializing)]
     pub admin_api_key: Option<String>,
+
+    /// Named admin API principals with role-based permissions.
+    ///
+    /// Each principal authenticates with its own bearer key and is granted
+    /// access to admin route groups according to its roles (see
+    /// [`crate::authorization`]). The legacy [`admin_api_key`](Self::admin_api_key)
+    /// remains valid alongside these entries and carries full access.
+    ///
+    /// When both this list is empty and `admin_api_key` is `None`, admin
+    /// endpoints are open (development mode).
+    #[serde(default)]
+    pub admin_principals: Vec<AdminPrincipalConfig>,
+}
+
+/// Read trusted proxies given as CIDR ranges or single addresses.
+fn deserialize_trusted_proxies<'de, D>(deserializer: D) -> Result<Vec<IpNet>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|proxy| {
+            proxy
+                .parse::<IpNet>()
+                .or_else(|_| proxy.parse::<std::net::IpAddr>().map(IpNet::from))
+                .map_err(|_| {
+                    serde::de::Error::custom(format!(
+                        "invalid trusted proxy '{}': expected an IP address or CIDR range",
+                        proxy
+                    ))
+                })
+        })
+        .collect()
 }
 
 impl std::fmt::Debug for SecurityConfig {
@@ -607,19 +1667,96 @@ impl std::fmt::Debug for SecurityConfig {
                 &self.auth_rate_restrict_duration_secs,
             )
             .field("auth_block_duration_secs", &self.auth_block_duration_secs)
+            .field("rate_limiter_store", &self.rate_limiter_store)
+            .field("trusted_proxies", &self.trusted_proxies)
             .field("log_requests", &self.log_requests)
             .field("log_request_bodies", &self.log_request_bodies)
             .field(
                 "admin_api_key",
                 &self.admin_api_key.as_ref().map(|_| "<REDACTED>"),
             )
+            .field("admin_principals", &self.admin_principals)
             .finish()
     }
 }
 
 impl SecurityConfig {
+    /// Validate the security configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] when:
+    /// - an admin principal has an empty name or API key
+    /// - an admin principal has no roles
+    /// - two admin principals share a name or an API key
+    /// - the rate limiter store is incomplete (see
+    ///   [`RateLimiterStoreConfig::validate`])
+    /// - request rate limiting is enabled with a zero global or IP limit
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.rate_limiter_store.validate()?;
+
+        if self.enable_rate_limiting {
+            if self.global_rate_limit == 0 {
+                return Err(ConfigError::Invalid {
+                    message: "security.global_rate_limit must be greater than 0 when rate limiting is enabled"
+                        .to_string(),
+                });
+            }
+            if self.enable_ip_rate_limiting && self.ip_rate_limit == 0 {
+                return Err(ConfigError::Invalid {
+                    message: "security.ip_rate_limit must be greater than 0 when IP rate limiting is enabled"
+                        .to_string(),
+                });
+            }
+        }
+
+        let mut names = std::collections::HashSet::new();
+        let mut keys = std::collections::HashSet::new();
+        for principal in &self.admin_principals {
+            if principal.name.is_empty() {
+                return Err(ConfigError::Invalid {
+                    message: "security.admin_principals: principal name must not be empty"
+                        .to_string(),
+                });
+            }
+            if principal.api_key.is_empty() {
+                return Err(ConfigError::Invalid {
+                    message: format!(
+                        "security.admin_principals: principal '{}' has an empty api_key",
+                        principal.name
+                    ),
+                });
+            }
+            if principal.roles.is_empty() {
+                return Err(ConfigError::Invalid {
+                    message: format!(
+                        "security.admin_principals: principal '{}' must have at least one role",
+                        principal.name
+                    ),
+                });
+            }
+            if !names.insert(principal.name.as_str()) {
+                return Err(ConfigError::Invalid {
+                    message: format!(
+                        "security.admin_principals: duplicate principal name '{}'",
+                        principal.name
+                    ),
+                });
+            }
+            if !keys.insert(principal.api_key.as_str()) {
+                return Err(ConfigError::Invalid {
+                    message: format!(
+                        "security.admin_principals: principal '{}' reuses another principal's api_key",
+                        principal.name
+                    ),
+                });
+            }
+        }
+        Ok(())
+    }
+
     fn default_enable_rate_limiting() -> bool {
-        true
+        false
     }
 
     fn default_global_rate_limit() -> u32 {
@@ -662,7 +1799,7 @@ impl SecurityConfig {
 impl Default for SecurityConfig {
     fn default() -> Self {
         Self {
-            enable_rate_limiting: true,
+            enable_rate_limiting: SecurityConfig::default_enable_rate_limiting(),
             global_rate_limit: 1000,
             enable_ip_rate_limiting: true,
             ip_rate_limit: 100,
@@ -672,13 +1809,189 @@ impl Default for SecurityConfig {
             auth_rate_restrict_duration_secs:
                 SecurityConfig::default_auth_rate_restrict_duration_secs(),
             auth_block_duration_secs: SecurityConfig::default_auth_block_duration_secs(),
+            rate_limiter_store: RateLimiterStoreConfig::default(),
+            trusted_proxies: Vec::new(),
             log_requests: true,
             log_request_bodies: false,
             admin_api_key: None,
+            admin_principals: Vec::new(),
+        }
+    }
+}
+
+/// Backend holding rate limiter state.
+///
+/// # YAML examples
+///
+/// ```yaml
+/// # Per-process state (default)
+/// security:
+///   rate_limiter_store:
+///     backend: in_memory
+///
+/// # Shared across replicas
+/// security:
+///   rate_limiter_store:
+///     backend: redis
+///     # Supply via QK__SECURITY__RATE_LIMITER_STORE__URL
+///     url: "rediss://:password@cache.example.com:6380/0"
+///     key_prefix: queue-keeper:ratelimit
+///     window: sliding
+/// ```
+///
+/// The Redis backend requires the `redis-rate-limiter` cargo feature.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum RateLimiterStoreConfig {
+    /// State is kept in process memory and lost on restart.
+    #[default]
+    InMemory,
+
+    /// State is kept in Redis and shared by every replica using the same
+    /// `key_prefix`.
+    Redis {
+        /// Redis connection URL (`redis://` or `rediss://`).
+        ///
+        /// Excluded from serialization (e.g. `/admin/config` response) because
+        /// the URL may embed a password.
+        #[serde(default, skip_serializing)]
+        url: String,
+
+        /// Prefix for every key written by the rate limiter.
+        #[serde(default = "RateLimiterStoreConfig::default_key_prefix")]
+        key_prefix: String,
+
+        /// How failures are counted within the window.
+        #[serde(default)]
+        window: RateLimitWindow,
+    },
+}
+
+impl RateLimiterStoreConfig {
+    /// Configuration name of the selected backend (the `backend` tag).
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            Self::InMemory => "in_memory",
+            Self::Redis { .. } => "redis",
+        }
+    }
+
+    /// Validate the rate limiter store configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] when the Redis backend has an empty
+    /// `url` or `key_prefix`, or a URL that is not `redis://` or `rediss://`.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            Self::InMemory => Ok(()),
+            Self::Redis {
+                url, key_prefix, ..
+            } => {
+                if url.is_empty() {
+                    return Err(ConfigError::Invalid {
+                        message:
+                            "security.rate_limiter_store.url must be set for the redis backend"
+                                .to_string(),
+                    });
+                }
+                if !url.starts_with("redis://") && !url.starts_with("rediss://") {
+                    return Err(ConfigError::Invalid {
+                        message: "security.rate_limiter_store.url must use the redis:// or rediss:// scheme"
+                            .to_string(),
+                    });
+                }
+                if key_prefix.is_empty() {
+                    return Err(ConfigError::Invalid {
+                        message: "security.rate_limiter_store.key_prefix must not be empty"
+                            .to_string(),
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn default_key_prefix() -> String {
+        "queue-keeper:ratelimit".to_string()
+    }
+}
+
+/// The Redis URL is redacted in `Debug` output.
+impl std::fmt::Debug for RateLimiterStoreConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InMemory => f.write_str("InMemory"),
+            Self::Redis {
+                url,
+                key_prefix,
+                window,
+            } => f
+                .debug_struct("Redis")
+                .field("url", &(!url.is_empty()).then_some("<REDACTED>"))
+                .field("key_prefix", key_prefix)
+                .field("window", window)
+                .finish(),
         }
     }
 }
 
+/// Failure counting algorithm for shared rate limiter stores.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitWindow {
+    /// Counts failures in the trailing window ending now. Matches the
+    /// in-memory limiter exactly; one sorted-set entry per failure.
+    #[default]
+    Sliding,
+
+    /// Counts failures in fixed, aligned windows. One counter per IP and
+    /// window, but a burst straddling a window boundary is split across two
+    /// counts.
+    Fixed,
+}
+
+/// A named admin API principal and its roles.
+///
+/// # YAML example
+///
+/// ```yaml
+/// security:
+///   admin_principals:
+///     - name: oncall
+///       roles: [operator]
+///       # Supply via QK__SECURITY__ADMIN_PRINCIPALS__0__API_KEY
+///       api_key: "..."
+///     - name: privacy-officer
+///       roles: [security]
+///       api_key: "..."
+/// ```
+#[derive(Clone, Serialize, Deserialize)]
+pub struct AdminPrincipalConfig {
+    /// Principal name recorded in audit entries.
+    pub name: String,
+
+    /// Bearer key that authenticates this principal.
+    ///
+    /// Excluded from serialization so it is never returned by
+    /// `/admin/config`.
+    #[serde(default, skip_serializing)]
+    pub api_key: String,
+
+    /// Roles granted to this principal.
+    pub roles: Vec<AdminRole>,
+}
+
+impl std::fmt::Debug for AdminPrincipalConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AdminPrincipalConfig")
+            .field("name", &self.name)
+            .field("api_key", &"<REDACTED>")
+            .field("roles", &self.roles)
+            .finish()
+    }
+}
+
 /// Logging configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggingConfig {
@@ -702,6 +2015,307 @@ impl Default for LoggingConfig {
     }
 }
 
+// ============================================================================
+// Outbound Network Configuration
+// ============================================================================
+
+/// Outbound network configuration for egress to GitHub and cloud providers.
+///
+/// # YAML example
+///
+/// ```yaml
+/// network:
+///   proxy:
+///     url: "http://proxy.corp.example:3128"
+///     username: "svc-queue-keeper"
+///     # Supply via QK__NETWORK__PROXY__PASSWORD
+///     password: "..."
+///     no_proxy: ["localhost", "127.0.0.1", ".svc.cluster.local"]
+///   ca_bundle_path: "/etc/queue-keeper/corp-root-ca.pem"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NetworkConfig {
+    /// Proxy used for all outbound HTTP and HTTPS traffic.
+    #[serde(default)]
+    pub proxy: Option<ProxyConfig>,
+
+    /// PEM file with additional root CAs.
+    ///
+    /// The certificates are trusted in addition to the system roots, not
+    /// instead of them.
+    #[serde(default)]
+    pub ca_bundle_path: Option<PathBuf>,
+}
+
+impl NetworkConfig {
+    /// Validate the outbound network settings.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] when:
+    /// - the proxy URL is not an `http://` or `https://` URL
+    /// - the proxy URL embeds credentials (use `username`/`password` instead)
+    /// - a proxy password is set without a username
+    /// - the CA bundle cannot be read or contains no PEM certificates
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if let Some(proxy) = &self.proxy {
+            proxy.validate()?;
+        }
+
+        if let Some(path) = &self.ca_bundle_path {
+            let pem = std::fs::read_to_string(path).map_err(|e| ConfigError::Invalid {
+                message: format!(
+                    "network.ca_bundle_path: cannot read '{}': {}",
+                    path.display(),
+                    e
+                ),
+            })?;
+            if !pem.contains("-----BEGIN CERTIFICATE-----") {
+                return Err(ConfigError::Invalid {
+                    message: format!(
+                        "network.ca_bundle_path: '{}' contains no PEM certificates",
+                        path.display()
+                    ),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Outbound HTTP(S) proxy settings.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProxyConfig {
+    /// Proxy URL, e.g. `http://proxy.corp.example:3128`.
+    pub url: String,
+
+    /// Username for proxy authentication.
+    #[serde(default)]
+    pub username: Option<String>,
+
+    /// Password for proxy authentication.
+    ///
+    /// Excluded from serialization so it is never returned by
+    /// `/admin/config`.
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+
+    /// Hosts that bypass the proxy (`NO_PROXY` syntax: host names, domain
+    /// suffixes starting with `.`, IP addresses or CIDR ranges).
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+impl ProxyConfig {
+    /// Validate the proxy settings.
+    ///
+    /// # Errors
+    ///
+    /// See [`NetworkConfig::validate`].
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let Some((scheme, rest)) = self.url.split_once("://") else {
+            return Err(ConfigError::Invalid {
+                message: format!("network.proxy.url: '{}' is not a URL", self.url),
+            });
+        };
+        if scheme != "http" && scheme != "https" {
+            return Err(ConfigError::Invalid {
+                message: format!(
+                    "network.proxy.url: unsupported scheme '{}' (expected http or https)",
+                    scheme
+                ),
+            });
+        }
+        let authority = rest.split('/').next().unwrap_or_default();
+        if authority.is_empty() {
+            return Err(ConfigError::Invalid {
+                message: "network.proxy.url: missing proxy host".to_string(),
+            });
+        }
+        if authority.contains('@') {
+            return Err(ConfigError::Invalid {
+                message: "network.proxy.url: credentials must be supplied via \
+                          `username` and `password`, not embedded in the URL"
+                    .to_string(),
+            });
+        }
+        if self.password.is_some() && self.username.is_none() {
+            return Err(ConfigError::Invalid {
+                message: "network.proxy.password requires network.proxy.username".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for ProxyConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProxyConfig")
+            .field("url", &self.url)
+            .field("username", &self.username)
+            .field("password", &self.password.as_ref().map(|_| "<REDACTED>"))
+            .field("no_proxy", &self.no_proxy)
+            .finish()
+    }
+}
+
+// ============================================================================
+// Endpoint Overrides
+// ============================================================================
+
+/// Cloud endpoint overrides for deployments outside the public clouds.
+///
+/// # YAML example
+///
+/// ```yaml
+/// endpoints:
+///   azure:
+///     cloud: china          # public | china | us_government
+///   aws:
+///     sqs_endpoint_url: "https://sqs.cn-north-1.amazonaws.com.cn"
+///     s3_endpoint_url: "http://minio.storage.svc:9000"
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EndpointConfig {
+    /// Azure cloud selection and authority override.
+    #[serde(default)]
+    pub azure: AzureEndpointConfig,
+
+    /// AWS service endpoint overrides.
+    #[serde(default)]
+    pub aws: AwsEndpointConfig,
+}
+
+impl EndpointConfig {
+    /// Validate endpoint overrides against the configured Azure resources.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] when:
+    /// - an override is not an `http://` or `https://` URL
+    ///   (`authority_host` must be `https://`)
+    /// - the Service Bus namespace is not in the selected cloud's DNS suffix
+    /// - the Key Vault URL is not in the selected cloud's DNS suffix
+    pub fn validate(
+        &self,
+        queue: &QueueBackendConfig,
+        key_vault: Option<&AzureKeyVaultConfig>,
+    ) -> Result<(), ConfigError> {
+        if let Some(authority) = &self.azure.authority_host {
+            validate_endpoint_url("endpoints.azure.authority_host", authority, false)?;
+        }
+        if let Some(url) = &self.aws.sqs_endpoint_url {
+            validate_endpoint_url("endpoints.aws.sqs_endpoint_url", url, true)?;
+        }
+        if let Some(url) = &self.aws.s3_endpoint_url {
+            validate_endpoint_url("endpoints.aws.s3_endpoint_url", url, true)?;
+        }
+
+        let cloud = self.azure.cloud;
+
+        if let QueueBackendConfig::AzureServiceBus {
+            namespace: Some(namespace),
+            ..
+        } = queue
+        {
+            // Bare namespace names are expanded by the provider; only check
+            // fully-qualified host names.
+            if namespace.contains('.')
+                && !namespace.ends_with(&format!(".{}", cloud.service_bus_suffix()))
+            {
+                return Err(ConfigError::Invalid {
+                    message: format!(
+                        "queue.azure_service_bus.namespace '{}' is not a {} cloud namespace \
+                         (expected *.{}); set endpoints.azure.cloud to match",
+                        namespace,
+                        cloud,
+                        cloud.service_bus_suffix()
+                    ),
+                });
+            }
+        }
+
+        if let Some(kv) = key_vault {
+            let host = kv
+                .vault_url
+                .split("://")
+                .nth(1)
+                .and_then(|rest| rest.split('/').next())
+                .unwrap_or_default();
+            if !host.is_empty() && !host.ends_with(&format!(".{}", cloud.key_vault_suffix())) {
+                return Err(ConfigError::Invalid {
+                    message: format!(
+                        "key_vault.vault_url '{}' is not a {} cloud Key Vault \
+                         (expected *.{}); set endpoints.azure.cloud to match",
+                        kv.vault_url,
+                        cloud,
+                        cloud.key_vault_suffix()
+                    ),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Azure cloud selection.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AzureEndpointConfig {
+    /// Azure cloud hosting the Service Bus namespace and Key Vault.
+    #[serde(default)]
+    pub cloud: AzureCloud,
+
+    /// Entra ID authority host override, for clouds not covered by
+    /// [`AzureCloud`]. Defaults to the selected cloud's authority.
+    #[serde(default)]
+    pub authority_host: Option<String>,
+}
+
+impl AzureEndpointConfig {
+    /// Authority host used for Azure credential token requests.
+    pub fn authority_host(&self) -> &str {
+        self.authority_host
+            .as_deref()
+            .unwrap_or_else(|| self.cloud.authority_host())
+    }
+}
+
+/// AWS service endpoint overrides.
+///
+/// Use these for AWS partitions with non-standard endpoints, VPC endpoints,
+/// or S3-compatible storage such as MinIO.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AwsEndpointConfig {
+    /// SQS endpoint URL.
+    #[serde(default)]
+    pub sqs_endpoint_url: Option<String>,
+
+    /// S3 endpoint URL.
+    #[serde(default)]
+    pub s3_endpoint_url: Option<String>,
+}
+
+/// Check that an endpoint override is an absolute URL with a host.
+fn validate_endpoint_url(field: &str, url: &str, allow_http: bool) -> Result<(), ConfigError> {
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| allow_http.then(|| url.strip_prefix("http://")).flatten());
+    let host = rest.and_then(|r| r.split('/').next()).unwrap_or_default();
+    if host.is_empty() {
+        let expected = if allow_http {
+            "an http:// or https:// URL"
+        } else {
+            "an https:// URL"
+        };
+        return Err(ConfigError::Invalid {
+            message: format!("{}: '{}' must be {}", field, url, expected),
+        });
+    }
+    Ok(())
+}
+
 // ============================================================================
 // Queue Backend Configuration
 // ============================================================================
@@ -826,6 +2440,15 @@ fn default_queue_true() -> bool {
 }
 
 impl QueueBackendConfig {
+    /// Configuration name of the selected provider (the `provider` tag).
+    pub fn provider_name(&self) -> &'static str {
+        match self {
+            Self::InMemory { .. } => "in_memory",
+            Self::AzureServiceBus { .. } => "azure_service_bus",
+            Self::AwsSqs { .. } => "aws_sqs",
+        }
+    }
+
     /// Validate the queue backend configuration for completeness.
     ///
     /// Returns an error string describing the first problem found.
@@ -0,0 +1,333 @@
+//! Deduplication of webhook deliveries by `X-GitHub-Delivery` ID.
+//!
+//! GitHub retries deliveries, and without deduplication each retry reaches
+//! the bots as a new event. Deduplication is off by default. When the
+//! `deduplication` section is enabled, every GitHub provider claims the
+//! delivery ID in a shared [`DeduplicationStore`] before processing; a
+//! delivery whose ID was already processed within `ttl` is answered with
+//! `200 OK`, status `already_processed` and the original event's ID, and is
+//! not processed again (see
+//! [`WebhookProcessorImpl::with_deduplication`](queue_keeper_core::webhook::WebhookProcessorImpl::with_deduplication)).
+//!
+//! - A [`ReplayGuard`], the store of replay protection, keeps the IDs in
+//!   process memory (the default), so each replica deduplicates only what
+//!   it received itself.
+//! - `RedisDeduplicationStore` (cargo feature `redis-deduplication`) keeps
+//!   the IDs in Redis, so a retry is recognised whichever replica receives
+//!   it.
+//!
+//! Store errors never reject a delivery: it is processed and the error is
+//! logged, so an unavailable Redis degrades to processing duplicates rather
+//! than to an outage.
+
+use std::{sync::Arc, time::Duration};
+
+use queue_keeper_core::{
+    units::human_duration,
+    webhook::{DeduplicationStore, ReplayGuard},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::ConfigError;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Delivery deduplication settings (the `deduplication` configuration
+/// section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// deduplication:
+///   enabled: true
+///   ttl: 24h
+///   store:
+///     backend: redis
+///     # Supply via QK__DEDUPLICATION__STORE__URL
+///     url: "rediss://:password@cache.example.com:6380/0"
+///     key_prefix: queue-keeper:deliveries
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeduplicationConfig {
+    /// Process each delivery ID at most once within `ttl`.
+    pub enabled: bool,
+
+    /// How long a processed delivery ID is remembered.
+    #[serde(with = "human_duration")]
+    pub ttl: Duration,
+
+    /// Delivery IDs kept by the in-memory store; the least recently
+    /// received is forgotten first.
+    pub max_tracked: usize,
+
+    /// Where processed delivery IDs are kept.
+    pub store: DeduplicationStoreConfig,
+}
+
+impl Default for DeduplicationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl: Duration::from_secs(24 * 60 * 60),
+            max_tracked: 100_000,
+            store: DeduplicationStoreConfig::default(),
+        }
+    }
+}
+
+impl DeduplicationConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ttl.is_zero() {
+            return Err("deduplication.ttl must not be zero".to_string());
+        }
+        if self.max_tracked == 0 {
+            return Err("deduplication.max_tracked must be at least 1".to_string());
+        }
+        self.store.validate()
+    }
+}
+
+/// Backend for processed delivery IDs.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum DeduplicationStoreConfig {
+    /// Delivery IDs are kept in process memory and lost on restart.
+    #[default]
+    InMemory,
+
+    /// Delivery IDs are kept in Redis and shared by every replica using the
+    /// same `key_prefix`.
+    Redis {
+        /// Redis connection URL (`redis://` or `rediss://`).
+        ///
+        /// Excluded from serialization (e.g. `/admin/config` response) because
+        /// the URL may embed a password.
+        #[serde(default, skip_serializing)]
+        url: String,
+
+        /// Prefix for every key written by the store.
+        #[serde(default = "DeduplicationStoreConfig::default_key_prefix")]
+        key_prefix: String,
+    },
+}
+
+impl DeduplicationStoreConfig {
+    /// Configuration name of the selected backend (the `backend` tag).
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            Self::InMemory => "in_memory",
+            Self::Redis { .. } => "redis",
+        }
+    }
+
+    /// Validate the store settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting: an empty or
+    /// non-Redis `url`, or an empty `key_prefix`.
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::InMemory => Ok(()),
+            Self::Redis { url, key_prefix } => {
+                if url.is_empty() {
+                    return Err(
+                        "deduplication.store.url must be set for the redis backend".to_string()
+                    );
+                }
+                if !url.starts_with("redis://") && !url.starts_with("rediss://") {
+                    return Err(
+                        "deduplication.store.url must use the redis:// or rediss:// scheme"
+                            .to_string(),
+                    );
+                }
+                if key_prefix.is_empty() {
+                    return Err("deduplication.store.key_prefix must not be empty".to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn default_key_prefix() -> String {
+        "queue-keeper:deliveries".to_string()
+    }
+}
+
+/// The Redis URL is redacted in `Debug` output.
+impl std::fmt::Debug for DeduplicationStoreConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InMemory => f.write_str("InMemory"),
+            Self::Redis { url, key_prefix } => f
+                .debug_struct("Redis")
+                .field("url", &(!url.is_empty()).then_some("<REDACTED>"))
+                .field("key_prefix", key_prefix)
+                .finish(),
+        }
+    }
+}
+
+/// Build the deduplication store configured in `config`, or `None` when
+/// deduplication is disabled.
+///
+/// # Errors
+///
+/// - [`ConfigError::ProviderNotCompiled`] when the Redis backend is selected
+///   but the `redis-deduplication` feature is disabled.
+/// - [`ConfigError::Invalid`] when the Redis client cannot connect.
+pub async fn build_deduplication_store(
+    config: &DeduplicationConfig,
+) -> Result<Option<Arc<dyn DeduplicationStore>>, ConfigError> {
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    match &config.store {
+        DeduplicationStoreConfig::InMemory => Ok(Some(Arc::new(ReplayGuard::new(
+            config.ttl,
+            config.max_tracked,
+        )))),
+        #[cfg(feature = "redis-deduplication")]
+        DeduplicationStoreConfig::Redis { url, key_prefix } => {
+            let store =
+                redis_store::RedisDeduplicationStore::connect(url, key_prefix.clone(), config.ttl)
+                    .await
+                    .map_err(|e| ConfigError::Invalid {
+                        message: format!("deduplication.store: {}", e),
+                    })?;
+            Ok(Some(Arc::new(store)))
+        }
+        #[cfg(not(feature = "redis-deduplication"))]
+        other @ DeduplicationStoreConfig::Redis { .. } => Err(ConfigError::ProviderNotCompiled {
+            component: "Deduplication store".to_string(),
+            provider: other.backend_name().to_string(),
+            feature: "redis-deduplication".to_string(),
+        }),
+    }
+}
+
+// ============================================================================
+// Redis Store
+// ============================================================================
+
+#[cfg(feature = "redis-deduplication")]
+pub use redis_store::RedisDeduplicationStore;
+
+#[cfg(feature = "redis-deduplication")]
+mod redis_store {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use queue_keeper_core::{
+        webhook::{DeduplicationError, DeduplicationStore, DeliveryClaim},
+        EventId,
+    };
+    use redis::aio::ConnectionManager;
+
+    /// Redis-backed [`DeduplicationStore`] shared by all replicas.
+    ///
+    /// Each delivery is stored at `{prefix}:{delivery_id}` with a TTL, its
+    /// value the ID of the event it was claimed for. A claim is a
+    /// `SET ... NX PX`, so exactly one replica claims a delivery.
+    pub struct RedisDeduplicationStore {
+        connection: ConnectionManager,
+        key_prefix: String,
+        ttl: Duration,
+    }
+
+    impl RedisDeduplicationStore {
+        /// Connect to the Redis server at `url`.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`DeduplicationError::Unavailable`] when the URL is
+        /// invalid or the initial connection fails.
+        pub async fn connect(
+            url: &str,
+            key_prefix: String,
+            ttl: Duration,
+        ) -> Result<Self, DeduplicationError> {
+            let client = redis::Client::open(url).map_err(unavailable)?;
+            let connection = ConnectionManager::new(client).await.map_err(unavailable)?;
+            Ok(Self {
+                connection,
+                key_prefix,
+                ttl,
+            })
+        }
+
+        fn key(&self, delivery_id: &str) -> String {
+            format!("{}:{}", self.key_prefix, delivery_id)
+        }
+    }
+
+    #[async_trait]
+    impl DeduplicationStore for RedisDeduplicationStore {
+        async fn claim(
+            &self,
+            delivery_id: &str,
+            event_id: EventId,
+        ) -> Result<DeliveryClaim, DeduplicationError> {
+            let key = self.key(delivery_id);
+            let mut connection = self.connection.clone();
+            let claimed: Option<String> = redis::cmd("SET")
+                .arg(&key)
+                .arg(event_id.to_string())
+                .arg("NX")
+                .arg("PX")
+                .arg(self.ttl.as_millis().max(1) as u64)
+                .query_async(&mut connection)
+                .await
+                .map_err(unavailable)?;
+            if claimed.is_some() {
+                return Ok(DeliveryClaim::Claimed);
+            }
+
+            let value: Option<String> = redis::cmd("GET")
+                .arg(&key)
+                .query_async(&mut connection)
+                .await
+                .map_err(unavailable)?;
+            let original = match value {
+                // Expired or released between the two commands.
+                None => return self.claim(delivery_id, event_id).await,
+                Some(value) => {
+                    value
+                        .parse::<EventId>()
+                        .map_err(|e| DeduplicationError::InvalidEntry {
+                            key,
+                            message: e.to_string(),
+                        })?
+                }
+            };
+            Ok(DeliveryClaim::Duplicate { event_id: original })
+        }
+
+        async fn release(&self, delivery_id: &str) -> Result<(), DeduplicationError> {
+            let mut connection = self.connection.clone();
+            let _: u64 = redis::cmd("DEL")
+                .arg(self.key(delivery_id))
+                .query_async(&mut connection)
+                .await
+                .map_err(unavailable)?;
+            Ok(())
+        }
+    }
+
+    fn unavailable(e: redis::RedisError) -> DeduplicationError {
+        DeduplicationError::Unavailable {
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "deduplication_tests.rs"]
+mod tests;
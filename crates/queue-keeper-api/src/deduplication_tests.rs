@@ -0,0 +1,87 @@
+//! Tests for delivery deduplication settings and store selection.
+
+use super::*;
+use queue_keeper_core::{webhook::DeliveryClaim, EventId};
+
+/// Deduplication is off by default and, once enabled, keeps delivery IDs
+/// in memory.
+#[tokio::test]
+async fn test_default_builds_in_memory_store() {
+    let config = DeduplicationConfig::default();
+    assert!(!config.enabled);
+    assert_eq!(config.store, DeduplicationStoreConfig::InMemory);
+    config.validate().unwrap();
+    assert!(build_deduplication_store(&config).await.unwrap().is_none());
+
+    let enabled = DeduplicationConfig {
+        enabled: true,
+        ..Default::default()
+    };
+    let store = build_deduplication_store(&enabled).await.unwrap().unwrap();
+    let original = EventId::new();
+    assert_eq!(
+        store.claim("d-1", original).await.unwrap(),
+        DeliveryClaim::Claimed
+    );
+    assert_eq!(
+        store.claim("d-1", EventId::new()).await.unwrap(),
+        DeliveryClaim::Duplicate { event_id: original }
+    );
+}
+
+/// The Redis backend is parsed from TOML and its URL is checked.
+#[test]
+fn test_redis_store_config() {
+    let config: DeduplicationConfig = toml::from_str(
+        r#"
+        ttl = "2h"
+
+        [store]
+        backend = "redis"
+        url = "rediss://:secret@cache:6380/0"
+        "#,
+    )
+    .unwrap();
+    assert_eq!(config.ttl, Duration::from_secs(7_200));
+    assert_eq!(
+        config.store,
+        DeduplicationStoreConfig::Redis {
+            url: "rediss://:secret@cache:6380/0".to_string(),
+            key_prefix: "queue-keeper:deliveries".to_string(),
+        }
+    );
+    config.validate().unwrap();
+    assert!(!format!("{:?}", config.store).contains("secret"));
+
+    let invalid = DeduplicationStoreConfig::Redis {
+        url: "http://cache:6379".to_string(),
+        key_prefix: "qk".to_string(),
+    };
+    assert!(invalid
+        .validate()
+        .unwrap_err()
+        .contains("deduplication.store.url"));
+    let zero_ttl = DeduplicationConfig {
+        ttl: Duration::ZERO,
+        ..Default::default()
+    };
+    assert!(zero_ttl.validate().is_err());
+}
+
+/// Selecting Redis without the feature is a configuration error.
+#[cfg(not(feature = "redis-deduplication"))]
+#[tokio::test]
+async fn test_redis_without_feature_is_rejected() {
+    let config = DeduplicationConfig {
+        enabled: true,
+        store: DeduplicationStoreConfig::Redis {
+            url: "redis://localhost:6379".to_string(),
+            key_prefix: "qk".to_string(),
+        },
+        ..Default::default()
+    };
+    assert!(matches!(
+        build_deduplication_store(&config).await,
+        Err(ConfigError::ProviderNotCompiled { .. })
+    ));
+}
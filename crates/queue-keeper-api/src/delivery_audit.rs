@@ -0,0 +1,367 @@
+//! Delivery audit records for compliance.
+//!
+//! Proving that event X reached queue Y at time Z should not require keeping
+//! a copy of every payload wherever it went. When `delivery_audit.enabled` is
+//! set, the delivery task writes one [`DeliveryAuditRecord`] per message a
+//! bot queue accepts: the event ID, the SHA-256 of the event, the queue, the
+//! message ID the queue provider assigned and the time. The payload itself
+//! stays in event storage; the hash lets an auditor match a retained copy
+//! against the record.
+//!
+//! The hash covers the JSON serialization of the wrapped event, which is the
+//! message body bot queues receive unless a transform or field encryption
+//! rewrites it for that bot.
+//!
+//! `sample_rate` limits the volume on busy deployments. Sampling is decided
+//! from the event ID, so an audited event has a record for every queue it
+//! reached, retries included.
+//!
+//! Records are appended to a hash-chained [`FilesystemAuditLogger`] in
+//! `delivery_audit.directory` and served by `GET /admin/audit/deliveries`.
+
+use queue_keeper_core::{
+    audit_logging::{
+        AuditAction, AuditActor, AuditContext, AuditError, AuditEvent, AuditEventType, AuditLogger,
+        AuditQuery, AuditQuerySpec, AuditResource, AuditResult, FilesystemAuditLogger,
+        PaginationOptions, SortOrder,
+    },
+    queue_integration::SuccessfulDelivery,
+    EventId, Timestamp, WrappedEvent,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{collections::HashMap, fmt, path::PathBuf, sync::Arc};
+use tracing::warn;
+
+/// Additional data entry marking an audit event as a delivery record.
+const RECORD_TYPE_KEY: &str = "record_type";
+
+/// [`RECORD_TYPE_KEY`] value of delivery records.
+const RECORD_TYPE_QUEUE_DELIVERY: &str = "queue_delivery";
+
+const EVENT_ID_KEY: &str = "event_id";
+const PAYLOAD_SHA256_KEY: &str = "payload_sha256";
+const BOT_NAME_KEY: &str = "bot_name";
+const QUEUE_NAME_KEY: &str = "queue_name";
+const MESSAGE_ID_KEY: &str = "message_id";
+
+/// Records returned by a query when no limit is given.
+pub const DEFAULT_QUERY_LIMIT: usize = 100;
+
+/// Largest number of records a single query returns.
+pub const MAX_QUERY_LIMIT: usize = 1000;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Delivery audit settings (the `delivery_audit` configuration section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// delivery_audit:
+///   enabled: true
+///   sample_rate: 0.1
+///   directory: "/var/lib/queue-keeper/audit/deliveries"
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeliveryAuditConfig {
+    /// Whether deliveries are audited. Off by default.
+    pub enabled: bool,
+
+    /// Fraction of events whose deliveries are audited, in `(0.0, 1.0]`.
+    pub sample_rate: f64,
+
+    /// Directory of the delivery audit log files.
+    pub directory: PathBuf,
+}
+
+impl Default for DeliveryAuditConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_rate: 1.0,
+            directory: PathBuf::from("./audit/deliveries"),
+        }
+    }
+}
+
+impl DeliveryAuditConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sample_rate.is_nan() || self.sample_rate <= 0.0 || self.sample_rate > 1.0 {
+            return Err(format!(
+                "delivery_audit.sample_rate must be in (0.0, 1.0] (got {})",
+                self.sample_rate
+            ));
+        }
+        if self.enabled && self.directory.as_os_str().is_empty() {
+            return Err("delivery_audit.directory must not be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Records
+// ============================================================================
+
+/// Proof that a bot queue accepted an event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeliveryAuditRecord {
+    pub event_id: EventId,
+
+    /// Hex-encoded SHA-256 of the serialized event.
+    pub payload_sha256: String,
+
+    pub bot_name: String,
+    pub queue_name: String,
+
+    /// Message ID assigned by the queue provider.
+    pub message_id: String,
+
+    pub delivered_at: Timestamp,
+}
+
+impl DeliveryAuditRecord {
+    /// Read a delivery record back from its audit event.
+    ///
+    /// Returns `None` for audit events that are not delivery records.
+    pub fn from_audit_event(event: &AuditEvent) -> Option<Self> {
+        let data = &event.context.additional_data;
+        if data.get(RECORD_TYPE_KEY).map(String::as_str) != Some(RECORD_TYPE_QUEUE_DELIVERY) {
+            return None;
+        }
+        Some(Self {
+            event_id: data.get(EVENT_ID_KEY)?.parse().ok()?,
+            payload_sha256: data.get(PAYLOAD_SHA256_KEY)?.clone(),
+            bot_name: data.get(BOT_NAME_KEY)?.clone(),
+            queue_name: data.get(QUEUE_NAME_KEY)?.clone(),
+            message_id: data.get(MESSAGE_ID_KEY)?.clone(),
+            delivered_at: event.occurred_at,
+        })
+    }
+}
+
+/// Build the audit event recording one accepted message.
+fn delivery_audit_event(
+    event: &WrappedEvent,
+    payload_sha256: &str,
+    delivery: &SuccessfulDelivery,
+) -> AuditEvent {
+    let additional_data = HashMap::from([
+        (
+            RECORD_TYPE_KEY.to_string(),
+            RECORD_TYPE_QUEUE_DELIVERY.to_string(),
+        ),
+        (EVENT_ID_KEY.to_string(), event.event_id.to_string()),
+        (PAYLOAD_SHA256_KEY.to_string(), payload_sha256.to_string()),
+        (BOT_NAME_KEY.to_string(), delivery.bot_name.to_string()),
+        (
+            QUEUE_NAME_KEY.to_string(),
+            delivery.queue_name.as_str().to_string(),
+        ),
+        (MESSAGE_ID_KEY.to_string(), delivery.message_id.to_string()),
+    ]);
+
+    AuditEvent::new(
+        AuditEventType::WebhookProcessing,
+        AuditActor::System {
+            component_name: "queue-keeper".to_string(),
+            instance_id: "delivery-audit".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+        AuditResource::Queue {
+            queue_name: delivery.queue_name.as_str().to_string(),
+            session_id: event.session_id.clone(),
+        },
+        AuditAction::Route {
+            destination: delivery.queue_name.as_str().to_string(),
+        },
+        AuditResult::Success {
+            duration: None,
+            details: None,
+        },
+        AuditContext {
+            correlation_id: Some(event.correlation_id.to_string()),
+            additional_data,
+            ..AuditContext::default()
+        },
+    )
+}
+
+/// Hex-encoded SHA-256 of the serialized event.
+pub fn payload_sha256(event: &WrappedEvent) -> Result<String, serde_json::Error> {
+    let body = serde_json::to_vec(event)?;
+    Ok(hex::encode(Sha256::digest(&body)))
+}
+
+// ============================================================================
+// Auditor
+// ============================================================================
+
+/// Audit log that delivery records are written to and queried from.
+pub trait DeliveryAuditStore: AuditLogger + AuditQuery {}
+
+impl<T: AuditLogger + AuditQuery> DeliveryAuditStore for T {}
+
+/// Filter of `GET /admin/audit/deliveries`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeliveryAuditQuery {
+    /// Only records of this event.
+    pub event_id: Option<EventId>,
+
+    /// Only records of this queue.
+    pub queue: Option<String>,
+
+    /// Records returned, newest first; defaults to [`DEFAULT_QUERY_LIMIT`]
+    /// and is capped at [`MAX_QUERY_LIMIT`].
+    pub limit: Option<usize>,
+}
+
+/// Writes and queries delivery audit records.
+///
+/// The default auditor is disabled: it records nothing and has nothing to
+/// query.
+#[derive(Clone, Default)]
+pub struct DeliveryAuditor {
+    store: Option<Arc<dyn DeliveryAuditStore>>,
+    sample_rate: f64,
+}
+
+impl fmt::Debug for DeliveryAuditor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeliveryAuditor")
+            .field("enabled", &self.is_enabled())
+            .field("sample_rate", &self.sample_rate)
+            .finish()
+    }
+}
+
+impl DeliveryAuditor {
+    /// Create an auditor writing a sample of deliveries to `store`.
+    pub fn new(store: Arc<dyn DeliveryAuditStore>, sample_rate: f64) -> Self {
+        Self {
+            store: Some(store),
+            sample_rate,
+        }
+    }
+
+    /// Create the auditor described by the `delivery_audit` section.
+    ///
+    /// # Errors
+    /// Returns [`AuditError::StorageError`] when the audit directory cannot
+    /// be created.
+    pub fn from_config(config: &DeliveryAuditConfig) -> Result<Self, AuditError> {
+        if !config.enabled {
+            return Ok(Self::default());
+        }
+        let logger = FilesystemAuditLogger::new(config.directory.clone())?;
+        Ok(Self::new(Arc::new(logger), config.sample_rate))
+    }
+
+    /// Whether deliveries are audited.
+    pub fn is_enabled(&self) -> bool {
+        self.store.is_some()
+    }
+
+    /// Whether the deliveries of `event_id` are audited.
+    pub fn is_sampled(&self, event_id: &EventId) -> bool {
+        if self.sample_rate >= 1.0 {
+            return true;
+        }
+        let digest = Sha256::digest(event_id.to_string().as_bytes());
+        let mut prefix = [0u8; 8];
+        prefix.copy_from_slice(&digest[..8]);
+        (u64::from_be_bytes(prefix) as f64 / u64::MAX as f64) < self.sample_rate
+    }
+
+    /// Record every message in `deliveries` if the event is sampled.
+    ///
+    /// Failures are logged and otherwise ignored; auditing never fails a
+    /// delivery.
+    pub async fn record(&self, event: &WrappedEvent, deliveries: &[SuccessfulDelivery]) {
+        let Some(store) = &self.store else {
+            return;
+        };
+        if deliveries.is_empty() || !self.is_sampled(&event.event_id) {
+            return;
+        }
+
+        let payload_sha256 = match payload_sha256(event) {
+            Ok(hash) => hash,
+            Err(e) => {
+                warn!(event_id = %event.event_id, error = %e, "Failed to hash event for delivery audit");
+                return;
+            }
+        };
+        let audit_events = deliveries
+            .iter()
+            .map(|delivery| delivery_audit_event(event, &payload_sha256, delivery))
+            .collect();
+        if let Err(e) = store.log_events_batch(audit_events).await {
+            warn!(event_id = %event.event_id, error = %e, "Failed to record delivery audit entries");
+        }
+    }
+
+    /// Find delivery records, newest first.
+    ///
+    /// A disabled auditor returns no records.
+    ///
+    /// # Errors
+    /// Returns the audit log's error when it cannot be read.
+    pub async fn query(
+        &self,
+        query: &DeliveryAuditQuery,
+    ) -> Result<Vec<DeliveryAuditRecord>, AuditError> {
+        let Some(store) = &self.store else {
+            return Ok(Vec::new());
+        };
+
+        let mut custom_filters = HashMap::from([(
+            RECORD_TYPE_KEY.to_string(),
+            RECORD_TYPE_QUEUE_DELIVERY.to_string(),
+        )]);
+        if let Some(event_id) = &query.event_id {
+            custom_filters.insert(EVENT_ID_KEY.to_string(), event_id.to_string());
+        }
+        if let Some(queue) = &query.queue {
+            custom_filters.insert(QUEUE_NAME_KEY.to_string(), queue.clone());
+        }
+        let spec = AuditQuerySpec {
+            time_range: None,
+            event_types: Some(vec![AuditEventType::WebhookProcessing]),
+            actors: None,
+            resources: None,
+            actions: None,
+            results: None,
+            search_text: None,
+            custom_filters,
+        };
+        let pagination = PaginationOptions {
+            page: 1,
+            per_page: query
+                .limit
+                .unwrap_or(DEFAULT_QUERY_LIMIT)
+                .clamp(1, MAX_QUERY_LIMIT),
+            sort_by: None,
+            sort_order: SortOrder::Descending,
+        };
+
+        let result = store.query_events(spec, pagination).await?;
+        Ok(result
+            .events
+            .iter()
+            .filter_map(DeliveryAuditRecord::from_audit_event)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+#[path = "delivery_audit_tests.rs"]
+mod tests;
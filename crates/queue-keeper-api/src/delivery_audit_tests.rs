@@ -0,0 +1,179 @@
+//! Tests for delivery audit records.
+
+use super::*;
+use crate::test_fixtures::{delivered, event};
+
+/// An auditor writing every delivery to a temporary audit log.
+fn filesystem_auditor(sample_rate: f64) -> (DeliveryAuditor, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let config = DeliveryAuditConfig {
+        enabled: true,
+        sample_rate,
+        directory: dir.path().to_path_buf(),
+    };
+    (DeliveryAuditor::from_config(&config).unwrap(), dir)
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Verify that delivery auditing is disabled by default.
+#[test]
+fn test_default_config_is_disabled_and_valid() {
+    let config = DeliveryAuditConfig::default();
+
+    assert!(!config.enabled);
+    assert!(config.validate().is_ok());
+    assert!(!DeliveryAuditor::from_config(&config).unwrap().is_enabled());
+}
+
+/// Verify that sample rates outside `(0.0, 1.0]` are rejected.
+#[test]
+fn test_invalid_sample_rate_rejected() {
+    for sample_rate in [0.0, -0.5, 1.5, f64::NAN] {
+        let config = DeliveryAuditConfig {
+            sample_rate,
+            ..DeliveryAuditConfig::default()
+        };
+        assert!(
+            config.validate().is_err(),
+            "{} should be invalid",
+            sample_rate
+        );
+    }
+}
+
+// ============================================================================
+// Recording and Querying
+// ============================================================================
+
+/// Verify that each accepted message is recorded with the event hash and
+/// the provider message ID.
+#[tokio::test]
+async fn test_records_each_delivery() {
+    let (auditor, _dir) = filesystem_auditor(1.0);
+    let event = event();
+    let deliveries = vec![delivered("alpha"), delivered("beta")];
+
+    auditor.record(&event, &deliveries).await;
+
+    let mut records = auditor
+        .query(&DeliveryAuditQuery {
+            event_id: Some(event.event_id),
+            ..DeliveryAuditQuery::default()
+        })
+        .await
+        .unwrap();
+    records.sort_by(|a, b| a.bot_name.cmp(&b.bot_name));
+
+    assert_eq!(records.len(), 2);
+    let expected_hash = payload_sha256(&event).unwrap();
+    for (record, delivery) in records.iter().zip(&deliveries) {
+        assert_eq!(record.event_id, event.event_id);
+        assert_eq!(record.payload_sha256, expected_hash);
+        assert_eq!(record.bot_name, delivery.bot_name.to_string());
+        assert_eq!(record.queue_name, delivery.queue_name.as_str());
+        assert_eq!(record.message_id, delivery.message_id.to_string());
+    }
+}
+
+/// Verify that queries filter by event and queue.
+#[tokio::test]
+async fn test_query_filters_by_event_and_queue() {
+    let (auditor, _dir) = filesystem_auditor(1.0);
+    let first = event();
+    let second = event();
+    auditor
+        .record(&first, &[delivered("alpha"), delivered("beta")])
+        .await;
+    auditor.record(&second, &[delivered("alpha")]).await;
+
+    let by_queue = auditor
+        .query(&DeliveryAuditQuery {
+            queue: Some("queue-keeper-alpha".to_string()),
+            ..DeliveryAuditQuery::default()
+        })
+        .await
+        .unwrap();
+    let by_both = auditor
+        .query(&DeliveryAuditQuery {
+            event_id: Some(second.event_id),
+            queue: Some("queue-keeper-beta".to_string()),
+            ..DeliveryAuditQuery::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(by_queue.len(), 2);
+    assert!(by_both.is_empty());
+}
+
+/// Verify that the query limit returns the newest records.
+#[tokio::test]
+async fn test_query_limit_returns_newest() {
+    let (auditor, _dir) = filesystem_auditor(1.0);
+    let older = event();
+    let newer = event();
+    auditor.record(&older, &[delivered("alpha")]).await;
+    tokio::time::sleep(std::time::Duration::from_millis(5)).await;
+    auditor.record(&newer, &[delivered("alpha")]).await;
+
+    let records = auditor
+        .query(&DeliveryAuditQuery {
+            limit: Some(1),
+            ..DeliveryAuditQuery::default()
+        })
+        .await
+        .unwrap();
+
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].event_id, newer.event_id);
+}
+
+/// Verify that a disabled auditor records and returns nothing.
+#[tokio::test]
+async fn test_disabled_auditor_records_nothing() {
+    let auditor = DeliveryAuditor::default();
+
+    auditor.record(&event(), &[delivered("alpha")]).await;
+
+    assert!(auditor
+        .query(&DeliveryAuditQuery::default())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+// ============================================================================
+// Sampling
+// ============================================================================
+
+/// Verify that sampling is decided per event, the same way by every
+/// auditor, and keeps roughly the configured fraction.
+#[tokio::test]
+async fn test_sampling_is_stable_per_event() {
+    let (auditor, _dir) = filesystem_auditor(0.25);
+    let (other, _other_dir) = filesystem_auditor(0.25);
+    let event_ids: Vec<EventId> = (0..2000).map(|_| EventId::new()).collect();
+
+    let sampled = event_ids.iter().filter(|id| auditor.is_sampled(id)).count();
+
+    assert!((300..700).contains(&sampled), "sampled {} of 2000", sampled);
+    assert!(event_ids
+        .iter()
+        .all(|id| auditor.is_sampled(id) == other.is_sampled(id)));
+}
+
+/// Verify that audit events other than delivery records are ignored.
+#[test]
+fn test_other_audit_events_are_not_records() {
+    let event = event();
+    let delivery = delivered("alpha");
+    let mut audit_event = delivery_audit_event(&event, "abc", &delivery);
+    assert!(DeliveryAuditRecord::from_audit_event(&audit_event).is_some());
+
+    audit_event.context.additional_data.remove(RECORD_TYPE_KEY);
+
+    assert!(DeliveryAuditRecord::from_audit_event(&audit_event).is_none());
+}
@@ -0,0 +1,252 @@
+//! Correlation of provider delivery IDs with events and queue messages.
+//!
+//! When GitHub support (or a provider's delivery log) hands us a delivery
+//! GUID, operators need everything Queue-Keeper did with it: the event it
+//! became, and the message ID each bot queue assigned. The
+//! [`DeliveryIndex`] records both sides as they happen:
+//!
+//! 1. The webhook handler records `X-GitHub-Delivery` → [`EventId`] when it
+//!    accepts a wrapped event.
+//! 2. The delivery task records the per-bot message IDs returned by each
+//!    routing attempt.
+//!
+//! Events derived from an indexed event (replays, DLQ redrives, retries) are
+//! attached to the original delivery through their lineage root, so one
+//! lookup of `GET /api/deliveries/{github_delivery_id}` returns the whole
+//! downstream picture.
+//!
+//! # Limitations
+//!
+//! The index is held in memory and bounded: once it holds `capacity` events
+//! the oldest are evicted. Each replica only knows the deliveries it
+//! received itself.
+
+use queue_keeper_core::{
+    queue_integration::SuccessfulDelivery,
+    webhook::{LineageCause, WrappedEvent},
+    EventId, Timestamp,
+};
+use serde::Serialize;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::RwLock,
+};
+
+/// Number of events retained by [`DeliveryIndex::default`].
+pub const DEFAULT_DELIVERY_INDEX_CAPACITY: usize = 100_000;
+
+// ============================================================================
+// Correlation Records
+// ============================================================================
+
+/// Everything downstream of one provider delivery.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DeliveryCorrelation {
+    /// Delivery ID sent by the provider (`X-GitHub-Delivery` for GitHub).
+    pub github_delivery_id: String,
+
+    /// Events created from the delivery, oldest first. Provider redeliveries
+    /// of the same GUID and events derived by replay, redrive or retry
+    /// appear as additional entries.
+    pub events: Vec<EventCorrelation>,
+}
+
+/// One event and the queue messages it produced.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EventCorrelation {
+    pub event_id: EventId,
+    pub provider: String,
+    pub event_type: String,
+    pub received_at: Timestamp,
+
+    /// How the event was derived from the original; `None` for events
+    /// created directly from a webhook.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cause: Option<LineageCause>,
+
+    /// Messages accepted by bot queues, in delivery order.
+    pub messages: Vec<QueueMessageCorrelation>,
+}
+
+/// A message accepted by a bot queue.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct QueueMessageCorrelation {
+    pub bot_name: String,
+    pub queue_name: String,
+    pub message_id: String,
+    pub delivered_at: Timestamp,
+}
+
+// ============================================================================
+// Delivery Index
+// ============================================================================
+
+#[derive(Debug, Default)]
+struct IndexState {
+    /// Delivery ID → events, oldest first.
+    deliveries: HashMap<String, Vec<EventId>>,
+    /// Event → delivery ID and correlation record.
+    events: HashMap<EventId, (String, EventCorrelation)>,
+    /// Insertion order, for eviction.
+    order: VecDeque<EventId>,
+}
+
+impl IndexState {
+    fn insert(&mut self, github_delivery_id: &str, record: EventCorrelation, capacity: usize) {
+        let event_id = record.event_id;
+        if self.events.contains_key(&event_id) {
+            return;
+        }
+
+        while self.order.len() >= capacity {
+            match self.order.pop_front() {
+                Some(oldest) => self.evict(&oldest),
+                None => break,
+            }
+        }
+
+        self.deliveries
+            .entry(github_delivery_id.to_string())
+            .or_default()
+            .push(event_id);
+        self.events
+            .insert(event_id, (github_delivery_id.to_string(), record));
+        self.order.push_back(event_id);
+    }
+
+    fn evict(&mut self, event_id: &EventId) {
+        let Some((github_delivery_id, _)) = self.events.remove(event_id) else {
+            return;
+        };
+        if let Some(event_ids) = self.deliveries.get_mut(&github_delivery_id) {
+            event_ids.retain(|id| id != event_id);
+            if event_ids.is_empty() {
+                self.deliveries.remove(&github_delivery_id);
+            }
+        }
+    }
+}
+
+/// Bounded in-memory index from provider delivery IDs to events and queue
+/// message IDs.
+///
+/// Shared through [`QueueDeliveryConfig`](crate::queue_delivery::QueueDeliveryConfig)
+/// so the webhook handler, delivery tasks and the query API see the same
+/// index.
+#[derive(Debug)]
+pub struct DeliveryIndex {
+    capacity: usize,
+    state: RwLock<IndexState>,
+}
+
+impl Default for DeliveryIndex {
+    fn default() -> Self {
+        Self::new(DEFAULT_DELIVERY_INDEX_CAPACITY)
+    }
+}
+
+impl DeliveryIndex {
+    /// Create an index retaining at most `capacity` events.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: RwLock::new(IndexState::default()),
+        }
+    }
+
+    /// Record that `event` was created from the provider delivery
+    /// `github_delivery_id`.
+    pub fn record_event(&self, github_delivery_id: &str, event: &WrappedEvent) {
+        let record = EventCorrelation {
+            event_id: event.event_id,
+            provider: event.provider.clone(),
+            event_type: event.event_type.clone(),
+            received_at: event.received_at,
+            cause: None,
+            messages: Vec::new(),
+        };
+        self.state
+            .write()
+            .unwrap()
+            .insert(github_delivery_id, record, self.capacity);
+    }
+
+    /// Record the queue messages produced by a delivery attempt for `event`.
+    ///
+    /// Events not yet indexed are attached to the delivery of their lineage
+    /// root, if that is indexed; otherwise the messages are ignored.
+    /// Messages already recorded are not duplicated.
+    pub fn record_messages(&self, event: &WrappedEvent, delivered: &[SuccessfulDelivery]) {
+        if delivered.is_empty() {
+            return;
+        }
+
+        let mut state = self.state.write().unwrap();
+        if !state.events.contains_key(&event.event_id) {
+            let Some(lineage) = event.lineage else {
+                return;
+            };
+            let Some((github_delivery_id, _)) = state.events.get(&lineage.root_event_id) else {
+                return;
+            };
+            let github_delivery_id = github_delivery_id.clone();
+            let record = EventCorrelation {
+                event_id: event.event_id,
+                provider: event.provider.clone(),
+                event_type: event.event_type.clone(),
+                received_at: event.received_at,
+                cause: Some(lineage.cause),
+                messages: Vec::new(),
+            };
+            state.insert(&github_delivery_id, record, self.capacity);
+        }
+
+        let Some((_, record)) = state.events.get_mut(&event.event_id) else {
+            return;
+        };
+        let delivered_at = Timestamp::now();
+        for delivery in delivered {
+            let message_id = delivery.message_id.as_str().to_string();
+            let known = record
+                .messages
+                .iter()
+                .any(|m| m.bot_name == delivery.bot_name.as_str() && m.message_id == message_id);
+            if !known {
+                record.messages.push(QueueMessageCorrelation {
+                    bot_name: delivery.bot_name.as_str().to_string(),
+                    queue_name: delivery.queue_name.as_str().to_string(),
+                    message_id,
+                    delivered_at,
+                });
+            }
+        }
+    }
+
+    /// Look up everything recorded for a provider delivery ID.
+    pub fn lookup(&self, github_delivery_id: &str) -> Option<DeliveryCorrelation> {
+        let state = self.state.read().unwrap();
+        let event_ids = state.deliveries.get(github_delivery_id)?;
+        let events = event_ids
+            .iter()
+            .filter_map(|id| state.events.get(id).map(|(_, record)| record.clone()))
+            .collect();
+        Some(DeliveryCorrelation {
+            github_delivery_id: github_delivery_id.to_string(),
+            events,
+        })
+    }
+
+    /// Number of events currently indexed.
+    pub fn len(&self) -> usize {
+        self.state.read().unwrap().order.len()
+    }
+
+    /// Whether the index is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+#[path = "delivery_index_tests.rs"]
+mod tests;
@@ -0,0 +1,85 @@
+//! Tests for the delivery correlation index.
+
+use super::*;
+use crate::test_fixtures::{delivered, event};
+
+const DELIVERY: &str = "12345678-1234-1234-1234-123456789012";
+
+/// A delivery ID resolves to its event and the per-bot message IDs.
+#[test]
+fn test_lookup_returns_event_and_messages() {
+    let index = DeliveryIndex::default();
+    let event = event();
+    index.record_event(DELIVERY, &event);
+
+    let first = delivered("review-bot");
+    let second = delivered("triage-bot");
+    index.record_messages(&event, std::slice::from_ref(&first));
+    index.record_messages(&event, &[first.clone(), second.clone()]);
+
+    let correlation = index.lookup(DELIVERY).expect("delivery is indexed");
+    assert_eq!(correlation.github_delivery_id, DELIVERY);
+    assert_eq!(correlation.events.len(), 1);
+
+    let record = &correlation.events[0];
+    assert_eq!(record.event_id, event.event_id);
+    assert_eq!(record.cause, None);
+    let message_ids: Vec<&str> = record
+        .messages
+        .iter()
+        .map(|m| m.message_id.as_str())
+        .collect();
+    assert_eq!(
+        message_ids,
+        vec![first.message_id.as_str(), second.message_id.as_str()],
+        "retried attempts must not duplicate messages"
+    );
+    assert_eq!(record.messages[1].bot_name, "triage-bot");
+
+    assert!(index.lookup("unknown").is_none());
+}
+
+/// Replays of an indexed event are attached to the original delivery.
+#[test]
+fn test_derived_events_join_original_delivery() {
+    let index = DeliveryIndex::default();
+    let original = event();
+    index.record_event(DELIVERY, &original);
+
+    let replay = original.derive(LineageCause::Replay);
+    let redrive = replay.derive(LineageCause::Redrive);
+    index.record_messages(&replay, &[delivered("review-bot")]);
+    index.record_messages(&redrive, &[delivered("review-bot")]);
+
+    // Unrelated events are not indexed
+    index.record_messages(&event(), &[delivered("review-bot")]);
+
+    let correlation = index.lookup(DELIVERY).unwrap();
+    let causes: Vec<Option<LineageCause>> = correlation.events.iter().map(|e| e.cause).collect();
+    assert_eq!(
+        causes,
+        vec![
+            None,
+            Some(LineageCause::Replay),
+            Some(LineageCause::Redrive)
+        ]
+    );
+    assert_eq!(index.len(), 3);
+}
+
+/// The oldest events are evicted once the index is full.
+#[test]
+fn test_capacity_evicts_oldest() {
+    let index = DeliveryIndex::new(2);
+    let events: Vec<WrappedEvent> = (0..3).map(|_| event()).collect();
+    for (i, event) in events.iter().enumerate() {
+        index.record_event(&format!("delivery-{}", i), event);
+    }
+
+    assert_eq!(index.len(), 2);
+    assert!(index.lookup("delivery-0").is_none());
+    assert_eq!(
+        index.lookup("delivery-2").unwrap().events[0].event_id,
+        events[2].event_id
+    );
+}
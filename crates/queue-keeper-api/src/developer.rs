@@ -0,0 +1,333 @@
+//! Developer mode.
+//!
+//! Started with `--dev`, the service trades
+//! production safeguards for fast feedback while a bot is being built:
+//!
+//! - Webhook signatures are optional. Providers without a secret accept
+//!   unsigned deliveries ([`relax_signature_requirements`]), and a signature
+//!   that does not match is logged as a warning instead of being rejected.
+//! - The queue backend, rate limiter and token cache default to in-memory
+//!   ([`development_defaults`]), and logging defaults to `debug`.
+//! - Every routed envelope is logged pretty-printed.
+//! - Configuration files are watched ([`ConfigWatcher`]) and the service
+//!   restarts when one changes.
+//! - `GET /dev/last-events` returns the most recent events and the queues
+//!   they reached.
+//!
+//! None of this applies outside developer mode: the defaults stay strict and
+//! the `/dev` routes do not exist.
+
+use crate::config::ServiceConfig;
+use queue_keeper_core::{queue_integration::DeliveryResult, Timestamp, WrappedEvent};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::Mutex,
+    time::{Duration, SystemTime},
+};
+use tracing::{info, warn};
+
+/// Largest accepted `developer.recent_events`.
+pub const MAX_RECENT_EVENTS: usize = 1000;
+
+/// Events returned by `GET /dev/last-events` when no limit is given.
+pub const DEFAULT_RECENT_EVENTS_LIMIT: usize = 20;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Developer mode settings (the `developer` configuration section).
+///
+/// `enabled` is set by the `--dev` flag only; it is never read from a file
+/// or environment variable.
+///
+/// # YAML example
+///
+/// ```yaml
+/// developer:
+///   recent_events: 50
+///   dump_envelopes: true
+///   watch_config: true
+///   watch_interval_seconds: 2
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeveloperConfig {
+    /// Whether developer mode is on. Off unless started with `--dev`.
+    #[serde(skip_deserializing)]
+    pub enabled: bool,
+
+    /// Events kept for `GET /dev/last-events`.
+    pub recent_events: usize,
+
+    /// Whether routed envelopes are logged pretty-printed.
+    pub dump_envelopes: bool,
+
+    /// Whether the service restarts when a configuration file changes.
+    pub watch_config: bool,
+
+    /// How often configuration files are checked for changes.
+    pub watch_interval_seconds: u64,
+}
+
+impl Default for DeveloperConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            recent_events: 50,
+            dump_envelopes: true,
+            watch_config: true,
+            watch_interval_seconds: 2,
+        }
+    }
+}
+
+impl DeveloperConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.recent_events == 0 || self.recent_events > MAX_RECENT_EVENTS {
+            return Err(format!(
+                "developer.recent_events must be between 1 and {} (got {})",
+                MAX_RECENT_EVENTS, self.recent_events
+            ));
+        }
+        if self.watch_interval_seconds == 0 {
+            return Err("developer.watch_interval_seconds must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Configuration layer applied in developer mode, over the built-in defaults
+/// and the system-wide configuration file but under local files and
+/// environment variables.
+///
+/// Makes signatures optional and keeps every backend in memory, so a shared
+/// system configuration naming cloud services does not get in the way of
+/// local development.
+pub fn development_defaults() -> serde_json::Value {
+    serde_json::json!({
+        "webhooks": { "require_signature": false },
+        "queue": { "provider": "in_memory" },
+        "security": { "rate_limiter_store": { "backend": "in_memory" } },
+        "token_cache": { "store": { "backend": "in_memory" } },
+    })
+}
+
+/// Let providers and webhook sources without a secret accept unsigned
+/// deliveries.
+///
+/// Clears `require_signature` on every provider and webhook source that has
+/// no secret, so the configuration validates; returns their IDs.
+pub fn relax_signature_requirements(config: &mut ServiceConfig) -> Vec<String> {
+    let providers = config
+        .providers
+        .iter_mut()
+        .filter(|provider| provider.require_signature && provider.secret.is_none())
+        .map(|provider| {
+            provider.require_signature = false;
+            provider.id.clone()
+        });
+    let sources = config
+        .webhook_sources
+        .iter_mut()
+        .filter(|source| source.require_signature && source.webhook_secret.is_none())
+        .map(|source| {
+            source.require_signature = false;
+            source.provider_id().to_string()
+        });
+    providers.chain(sources).collect()
+}
+
+// ============================================================================
+// Recent Events
+// ============================================================================
+
+/// An event seen by the delivery task, for `GET /dev/last-events`.
+#[derive(Debug, Clone, Serialize)]
+pub struct RecentEvent {
+    /// The wrapped event as routed.
+    pub envelope: WrappedEvent,
+
+    /// When the event was last routed.
+    pub routed_at: Timestamp,
+
+    /// Queues that accepted the event.
+    pub delivered_to: Vec<String>,
+
+    /// Queues that did not, with the error.
+    pub failed: Vec<RecentFailure>,
+}
+
+/// A queue that did not accept a recent event.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RecentFailure {
+    pub queue_name: String,
+    pub error: String,
+}
+
+/// Query of `GET /dev/last-events`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RecentEventsQuery {
+    /// Events returned, newest first; defaults to
+    /// [`DEFAULT_RECENT_EVENTS_LIMIT`].
+    pub limit: Option<usize>,
+}
+
+impl RecentEventsQuery {
+    /// The requested number of events.
+    pub fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_RECENT_EVENTS_LIMIT)
+    }
+}
+
+/// Developer mode state shared by the delivery task and the `/dev` routes.
+#[derive(Debug)]
+pub struct DeveloperTools {
+    config: DeveloperConfig,
+    recent: Mutex<VecDeque<RecentEvent>>,
+}
+
+impl DeveloperTools {
+    /// Create the tools for a developer mode configuration.
+    pub fn new(config: DeveloperConfig) -> Self {
+        Self {
+            recent: Mutex::new(VecDeque::with_capacity(config.recent_events)),
+            config,
+        }
+    }
+
+    /// Record the outcome of routing `event`.
+    ///
+    /// A retry replaces the outcome of the event's earlier attempt. The
+    /// envelope is logged the first time an event is routed.
+    pub fn record_delivery(&self, event: &WrappedEvent, result: &DeliveryResult) {
+        let routed = RecentEvent {
+            envelope: event.clone(),
+            routed_at: Timestamp::now(),
+            delivered_to: result
+                .successful
+                .iter()
+                .map(|d| d.queue_name.as_str().to_string())
+                .collect(),
+            failed: result
+                .failed
+                .iter()
+                .map(|f| RecentFailure {
+                    queue_name: f.queue_name.as_str().to_string(),
+                    error: f.error.clone(),
+                })
+                .collect(),
+        };
+
+        let mut recent = self.recent.lock().unwrap();
+        if let Some(existing) = recent
+            .iter_mut()
+            .find(|r| r.envelope.event_id == event.event_id)
+        {
+            *existing = routed;
+            return;
+        }
+        while recent.len() >= self.config.recent_events {
+            recent.pop_front();
+        }
+        recent.push_back(routed);
+        drop(recent);
+
+        if self.config.dump_envelopes {
+            dump_envelope(event, result);
+        }
+    }
+
+    /// The most recent events, newest first.
+    pub fn recent_events(&self, limit: usize) -> Vec<RecentEvent> {
+        self.recent
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Log `event` pretty-printed with the queues it was routed to.
+fn dump_envelope(event: &WrappedEvent, result: &DeliveryResult) {
+    match serde_json::to_string_pretty(event) {
+        Ok(envelope) => info!(
+            event_id = %event.event_id,
+            delivered = result.successful.len(),
+            failed = result.failed.len(),
+            "Routed envelope:\n{}",
+            envelope
+        ),
+        Err(e) => warn!(event_id = %event.event_id, error = %e, "Failed to print routed envelope"),
+    }
+}
+
+// ============================================================================
+// Configuration Watcher
+// ============================================================================
+
+/// Detects changes to configuration files by polling their metadata.
+///
+/// A file that appears, disappears, or changes its modification time or
+/// size counts as changed.
+#[derive(Debug)]
+pub struct ConfigWatcher {
+    interval: Duration,
+    files: HashMap<PathBuf, Option<(Option<SystemTime>, u64)>>,
+}
+
+impl ConfigWatcher {
+    /// Watch `paths`, recording their current state.
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>, interval: Duration) -> Self {
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let state = file_state(&path);
+                (path, state)
+            })
+            .collect();
+        Self { interval, files }
+    }
+
+    /// Wait until a watched file changes and return its path.
+    pub async fn changed(&mut self) -> PathBuf {
+        loop {
+            tokio::time::sleep(self.interval).await;
+            if let Some(path) = self.poll() {
+                return path;
+            }
+        }
+    }
+
+    /// Return a watched file that changed since the last poll, if any.
+    pub fn poll(&mut self) -> Option<PathBuf> {
+        let mut changed = None;
+        for (path, state) in &mut self.files {
+            let current = file_state(path);
+            if current != *state {
+                *state = current;
+                changed.get_or_insert_with(|| path.clone());
+            }
+        }
+        changed
+    }
+}
+
+fn file_state(path: &Path) -> Option<(Option<SystemTime>, u64)> {
+    std::fs::metadata(path)
+        .ok()
+        .map(|metadata| (metadata.modified().ok(), metadata.len()))
+}
+
+#[cfg(test)]
+#[path = "developer_tests.rs"]
+mod tests;
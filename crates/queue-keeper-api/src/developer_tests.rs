@@ -0,0 +1,210 @@
+//! Tests for developer mode.
+
+use super::*;
+use crate::config::{ProviderConfig, ProviderSecretConfig};
+use crate::test_fixtures::{delivered, event, failed};
+use queue_keeper_core::{
+    queue_integration::{FailedDelivery, SuccessfulDelivery},
+    webhook::{generic_provider::WebhookSecretConfig, WebhookSourceConfig, WebhookSourceKind},
+    EventId,
+};
+
+fn result(
+    event: &WrappedEvent,
+    successful: Vec<SuccessfulDelivery>,
+    failed: Vec<FailedDelivery>,
+) -> DeliveryResult {
+    DeliveryResult {
+        event_id: event.event_id,
+        successful,
+        failed,
+    }
+}
+
+fn tools(recent_events: usize) -> DeveloperTools {
+    DeveloperTools::new(DeveloperConfig {
+        enabled: true,
+        recent_events,
+        dump_envelopes: false,
+        ..DeveloperConfig::default()
+    })
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Verify that developer mode is off by default.
+#[test]
+fn test_default_config_is_disabled_and_valid() {
+    let config = DeveloperConfig::default();
+
+    assert!(!config.enabled);
+    assert!(config.validate().is_ok());
+}
+
+/// Verify that invalid buffer sizes and watch intervals are rejected.
+#[test]
+fn test_invalid_settings_rejected() {
+    for config in [
+        DeveloperConfig {
+            recent_events: 0,
+            ..DeveloperConfig::default()
+        },
+        DeveloperConfig {
+            recent_events: MAX_RECENT_EVENTS + 1,
+            ..DeveloperConfig::default()
+        },
+        DeveloperConfig {
+            watch_interval_seconds: 0,
+            ..DeveloperConfig::default()
+        },
+    ] {
+        assert!(config.validate().is_err(), "{:?} should be invalid", config);
+    }
+}
+
+/// Verify that the developer defaults deserialize over the built-in
+/// defaults into an in-memory, signature-optional configuration.
+#[test]
+fn test_development_defaults_layer() {
+    let mut layered = serde_json::to_value(ServiceConfig::default()).unwrap();
+    let defaults = development_defaults();
+    for (section, values) in defaults.as_object().unwrap() {
+        for (key, value) in values.as_object().unwrap() {
+            layered[section][key] = value.clone();
+        }
+    }
+
+    let config: ServiceConfig = serde_json::from_value(layered).unwrap();
+
+    assert!(!config.webhooks.require_signature);
+    assert!(matches!(
+        config.queue,
+        crate::config::QueueBackendConfig::InMemory { .. }
+    ));
+    assert!(config.validate().is_ok());
+}
+
+/// Verify that a configuration cannot switch developer mode on.
+#[test]
+fn test_enabled_not_read_from_configuration() {
+    let config: DeveloperConfig = serde_json::from_value(serde_json::json!({
+        "enabled": true,
+        "recent_events": 5,
+    }))
+    .unwrap();
+
+    assert!(!config.enabled);
+    assert_eq!(config.recent_events, 5);
+}
+
+/// Verify that only providers and sources without a secret stop requiring
+/// signatures.
+#[test]
+fn test_relax_signature_requirements() {
+    let unsigned: ProviderConfig = serde_json::from_value(serde_json::json!({
+        "id": "github",
+        "require_signature": true,
+    }))
+    .unwrap();
+    let signed = ProviderConfig {
+        id: "enterprise".to_string(),
+        secret: Some(ProviderSecretConfig::Literal {
+            value: "secret".to_string(),
+        }),
+        ..unsigned.clone()
+    };
+    let unsigned_source: WebhookSourceConfig =
+        serde_json::from_value(serde_json::json!({ "kind": "gitlab" })).unwrap();
+    let signed_source = WebhookSourceConfig {
+        kind: WebhookSourceKind::Bitbucket,
+        webhook_secret: Some(WebhookSecretConfig::Literal {
+            value: "secret".to_string(),
+        }),
+        ..unsigned_source.clone()
+    };
+    let mut config = ServiceConfig {
+        providers: vec![unsigned, signed],
+        webhook_sources: vec![unsigned_source, signed_source],
+        ..ServiceConfig::default()
+    };
+    assert!(config.validate().is_err());
+
+    let relaxed = relax_signature_requirements(&mut config);
+
+    assert_eq!(relaxed, vec!["github".to_string(), "gitlab".to_string()]);
+    assert!(config.providers[1].require_signature);
+    assert!(config.webhook_sources[1].require_signature);
+    assert!(config.validate().is_ok());
+}
+
+// ============================================================================
+// Recent Events
+// ============================================================================
+
+/// Verify that recent events are returned newest first and bounded.
+#[test]
+fn test_recent_events_newest_first_and_bounded() {
+    let tools = tools(2);
+    let events: Vec<_> = (0..3).map(|_| event()).collect();
+    for event in &events {
+        tools.record_delivery(event, &result(event, vec![delivered("alpha")], vec![]));
+    }
+
+    let ids: Vec<EventId> = tools
+        .recent_events(10)
+        .iter()
+        .map(|r| r.envelope.event_id)
+        .collect();
+
+    assert_eq!(ids, vec![events[2].event_id, events[1].event_id]);
+    assert_eq!(tools.recent_events(1).len(), 1);
+}
+
+/// Verify that a retry replaces the outcome of the earlier attempt.
+#[test]
+fn test_retry_replaces_outcome() {
+    let tools = tools(10);
+    let event = event();
+    tools.record_delivery(
+        &event,
+        &result(&event, vec![delivered("alpha")], vec![failed("beta", true)]),
+    );
+
+    tools.record_delivery(
+        &event,
+        &result(&event, vec![delivered("alpha"), delivered("beta")], vec![]),
+    );
+
+    let recent = tools.recent_events(10);
+    assert_eq!(recent.len(), 1);
+    assert_eq!(
+        recent[0].delivered_to,
+        vec!["queue-keeper-alpha", "queue-keeper-beta"]
+    );
+    assert!(recent[0].failed.is_empty());
+}
+
+// ============================================================================
+// Configuration Watcher
+// ============================================================================
+
+/// Verify that creating, changing and removing a file are all detected.
+#[test]
+fn test_watcher_detects_changes() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("service.yaml");
+    let mut watcher = ConfigWatcher::new([path.clone()], Duration::from_secs(1));
+    assert_eq!(watcher.poll(), None);
+
+    std::fs::write(&path, "server:\n  port: 8080\n").unwrap();
+    assert_eq!(watcher.poll(), Some(path.clone()));
+    assert_eq!(watcher.poll(), None);
+
+    std::fs::write(&path, "server:\n  port: 18080\n").unwrap();
+    assert_eq!(watcher.poll(), Some(path.clone()));
+
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(watcher.poll(), Some(path));
+}
@@ -9,17 +9,32 @@
 //! - Retry history (attempts made, timestamps)
 //! - Sufficient information for later replay
 //!
+//! Records older than the configured [`DlqRetentionPolicy`] are moved to an
+//! archive store as gzip-compressed NDJSON bundles and removed from the
+//! active store; an archived bundle can be restored on demand.
+//!
 //! See specs/requirements/functional-requirements.md REQ-007 for DLQ requirements.
 //! See specs/vocabulary.md "Dead Letter Queue" for concept definition.
 
-use chrono::{Datelike, Timelike};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
 use queue_keeper_core::{
-    blob_storage::{BlobStorage, BlobStorageError, WebhookPayload},
+    blob_storage::{
+        BlobStorage, BlobStorageError, DateRange, PayloadFilter, PayloadMetadata, WebhookPayload,
+    },
+    event_error, event_info, event_warn,
+    logging::{LogContext, LogFields},
+    storage_format::{check_format_version, legacy_format_version, CURRENT_FORMAT_VERSION},
     webhook::WrappedEvent,
     BotName, EventId, QueueName, Repository, Timestamp,
 };
 use serde::{Deserialize, Serialize};
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+    sync::Arc,
+    time::Duration,
+};
 use tracing::{error, info, warn};
 
 // ============================================================================
@@ -100,6 +115,13 @@ pub struct FailedEventRecord {
 
     /// Correlation ID for tracing
     pub correlation_id: String,
+
+    /// Persisted format version of the record
+    ///
+    /// Records written before versioning read as version 1; see
+    /// [`queue_keeper_core::storage_format`].
+    #[serde(default = "legacy_format_version")]
+    pub format_version: u32,
 }
 
 impl FailedEventRecord {
@@ -122,6 +144,7 @@ impl FailedEventRecord {
             retry_attempts,
             first_attempt_at,
             moved_to_dlq_at: Timestamp::now(),
+            format_version: CURRENT_FORMAT_VERSION,
         }
     }
 
@@ -141,6 +164,12 @@ impl FailedEventRecord {
     }
 }
 
+impl LogFields for FailedEventRecord {
+    fn log_context(&self) -> LogContext {
+        self.event.log_context()
+    }
+}
+
 // ============================================================================
 // DLQ Metadata (stored separately for listing without loading full records)
 // ============================================================================
@@ -178,20 +207,59 @@ pub struct DlqMetadata {
 #[derive(Clone)]
 pub struct DlqStorageService {
     storage: Arc<dyn BlobStorage>,
+    archive: Option<DlqArchive>,
+}
+
+/// Archive store and the policy that feeds it
+#[derive(Clone)]
+struct DlqArchive {
+    storage: Arc<dyn BlobStorage>,
+    policy: DlqRetentionPolicy,
 }
 
 impl std::fmt::Debug for DlqStorageService {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("DlqStorageService")
             .field("storage", &"<BlobStorage>")
+            .field(
+                "retention_policy",
+                &self.archive.as_ref().map(|a| &a.policy),
+            )
             .finish()
     }
 }
 
 impl DlqStorageService {
     /// Create a new DLQ storage service
+    ///
+    /// Without [`with_archive`](Self::with_archive) records are retained
+    /// indefinitely.
     pub fn new(storage: Arc<dyn BlobStorage>) -> Self {
-        Self { storage }
+        Self {
+            storage,
+            archive: None,
+        }
+    }
+
+    /// Enable retention: expired records are archived to `archive_storage`
+    ///
+    /// The archive store should be separate from the active DLQ store so
+    /// that bundles are never mistaken for DLQ records.
+    pub fn with_archive(
+        mut self,
+        archive_storage: Arc<dyn BlobStorage>,
+        policy: DlqRetentionPolicy,
+    ) -> Self {
+        self.archive = Some(DlqArchive {
+            storage: archive_storage,
+            policy,
+        });
+        self
+    }
+
+    /// Retention policy, if an archive store is configured
+    pub fn retention_policy(&self) -> Option<&DlqRetentionPolicy> {
+        self.archive.as_ref().map(|a| &a.policy)
     }
 
     /// Persist a failed event to DLQ storage
@@ -213,8 +281,8 @@ impl DlqStorageService {
     ) -> Result<String, BlobStorageError> {
         let blob_path = record.to_blob_path();
 
-        info!(
-            event_id = %record.event_id,
+        event_info!(
+            record,
             blob_path = %blob_path,
             reason = ?record.reason,
             failed_queues = record.failed_queues.len(),
@@ -254,8 +322,8 @@ impl DlqStorageService {
             .store_payload(&record.event_id, &payload)
             .await?;
 
-        info!(
-            event_id = %record.event_id,
+        event_info!(
+            record,
             blob_path = %metadata.blob_path,
             size_bytes = metadata.size_bytes,
             "Successfully persisted failed event to DLQ"
@@ -284,12 +352,378 @@ impl DlqStorageService {
                     .map_err(|e| BlobStorageError::SerializationFailed {
                         message: format!("Failed to deserialize DLQ record: {}", e),
                     })?;
+                check_format_version(record.format_version).map_err(|_| {
+                    BlobStorageError::UnsupportedFormatVersion {
+                        path: stored.metadata.blob_path.clone(),
+                        version: record.format_version,
+                    }
+                })?;
 
                 Ok(Some(record))
             }
             None => Ok(None),
         }
     }
+
+    /// Archive every record moved to the DLQ before `now - retention`
+    ///
+    /// Expired records are written to the archive store in bundles of at
+    /// most [`DlqRetentionPolicy::bundle_max_records`], oldest first. A
+    /// record is deleted from the active store only after the bundle that
+    /// contains it has been stored. If a delete fails the record stays in
+    /// the active store and is archived again by the next sweep; restoring
+    /// either bundle yields the same record.
+    ///
+    /// Entries in the active store that cannot be read as DLQ records are
+    /// left in place and counted in [`DlqArchiveReport::skipped_records`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DlqArchiveError::NotConfigured`] without an archive store,
+    /// or [`DlqArchiveError::Storage`] if listing or storing a bundle fails.
+    pub async fn archive_expired(
+        &self,
+        now: Timestamp,
+    ) -> Result<DlqArchiveReport, DlqArchiveError> {
+        let archive = self
+            .archive
+            .as_ref()
+            .ok_or(DlqArchiveError::NotConfigured)?;
+        let cutoff = now.subtract_duration(archive.policy.retention());
+
+        // Records are listed by first attempt, which never follows the move
+        // to the DLQ, so this is a superset of the expired records.
+        let filter = PayloadFilter {
+            date_range: Some(DateRange {
+                start: Timestamp::from_datetime(DateTime::<Utc>::UNIX_EPOCH),
+                end: cutoff,
+            }),
+            ..Default::default()
+        };
+        let candidates = self.storage.list_payloads(&filter).await?;
+
+        let mut report = DlqArchiveReport::default();
+        let mut expired = Vec::new();
+        for metadata in candidates {
+            match self.get_failed_event(&metadata.event_id).await {
+                Ok(Some(record)) if record.moved_to_dlq_at < cutoff => expired.push(record),
+                Ok(_) => {}
+                Err(e) => {
+                    warn!(
+                        event_id = %metadata.event_id,
+                        error = %e,
+                        "Skipping unreadable DLQ entry during archival"
+                    );
+                    report.skipped_records += 1;
+                }
+            }
+        }
+        expired.sort_by_key(|record| record.moved_to_dlq_at);
+
+        for chunk in expired.chunks(archive.policy.bundle_max_records.max(1)) {
+            let bundle = archive.store_bundle(chunk, now).await?;
+
+            for record in chunk {
+                match self.storage.delete_payload(&record.event_id).await {
+                    Ok(()) => report.archived_records += 1,
+                    Err(e) => {
+                        event_warn!(
+                            record,
+                            bundle_id = %bundle.bundle_id,
+                            error = %e,
+                            "Archived DLQ record could not be removed from the active store"
+                        );
+                        report.failed_deletes += 1;
+                    }
+                }
+            }
+
+            info!(
+                bundle_id = %bundle.bundle_id,
+                records = bundle.record_count,
+                size_bytes = bundle.size_bytes,
+                "Archived DLQ bundle"
+            );
+            report.bundles.push(bundle);
+        }
+
+        Ok(report)
+    }
+
+    /// Restore every record in an archived bundle to the active store
+    ///
+    /// The whole bundle is decoded before anything is written, so a corrupt
+    /// bundle restores nothing. The bundle itself is kept in the archive;
+    /// restoring it twice overwrites the same records.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`DlqArchiveError::BundleNotFound`] for an unknown bundle,
+    /// [`DlqArchiveError::CorruptBundle`] if it cannot be decoded, and
+    /// [`DlqArchiveError::Storage`] if reading or re-persisting fails.
+    pub async fn restore_bundle(
+        &self,
+        bundle_id: &EventId,
+    ) -> Result<DlqRestoreReport, DlqArchiveError> {
+        let archive = self
+            .archive
+            .as_ref()
+            .ok_or(DlqArchiveError::NotConfigured)?;
+
+        let stored = archive
+            .storage
+            .get_payload(bundle_id)
+            .await?
+            .filter(|stored| stored.payload.metadata.event_type == DLQ_ARCHIVE_EVENT_TYPE)
+            .ok_or(DlqArchiveError::BundleNotFound {
+                bundle_id: *bundle_id,
+            })?;
+
+        let records = decode_bundle(&stored.payload.body).map_err(|message| {
+            DlqArchiveError::CorruptBundle {
+                bundle_id: *bundle_id,
+                message,
+            }
+        })?;
+
+        for record in &records {
+            self.persist_failed_event(record).await?;
+        }
+
+        info!(
+            bundle_id = %bundle_id,
+            records = records.len(),
+            "Restored archived DLQ bundle"
+        );
+
+        Ok(DlqRestoreReport {
+            bundle_id: *bundle_id,
+            restored_records: records.len(),
+        })
+    }
+
+    /// Run [`archive_expired`](Self::archive_expired) every
+    /// [`DlqRetentionPolicy::sweep_interval`] in a background task
+    ///
+    /// Returns `None` when no archive store is configured. Sweep failures
+    /// are logged and retried on the next tick.
+    pub fn spawn_retention_sweeper(self: Arc<Self>) -> Option<tokio::task::JoinHandle<()>> {
+        let interval = self.retention_policy()?.sweep_interval();
+
+        Some(tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                match self.archive_expired(Timestamp::now()).await {
+                    Ok(report) if report.bundles.is_empty() => {}
+                    Ok(report) => info!(
+                        bundles = report.bundles.len(),
+                        archived_records = report.archived_records,
+                        failed_deletes = report.failed_deletes,
+                        skipped_records = report.skipped_records,
+                        "DLQ retention sweep complete"
+                    ),
+                    Err(e) => error!(error = %e, "DLQ retention sweep failed"),
+                }
+            }
+        }))
+    }
+}
+
+// ============================================================================
+// DLQ Retention and Archival
+// ============================================================================
+
+/// Event type recorded on archive bundles in the archive store
+pub const DLQ_ARCHIVE_EVENT_TYPE: &str = "dlq_archive_bundle";
+
+/// Retention policy for DLQ records
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DlqRetentionPolicy {
+    /// Days a record stays in the active store before it is archived
+    pub retention_days: u32,
+
+    /// Maximum number of records per archive bundle
+    pub bundle_max_records: usize,
+
+    /// Seconds between retention sweeps
+    pub sweep_interval_seconds: u64,
+}
+
+impl Default for DlqRetentionPolicy {
+    fn default() -> Self {
+        Self {
+            retention_days: 30,
+            bundle_max_records: 1_000,
+            sweep_interval_seconds: 3_600,
+        }
+    }
+}
+
+impl DlqRetentionPolicy {
+    /// Create a policy archiving records after `retention_days`
+    pub fn new(retention_days: u32) -> Self {
+        Self {
+            retention_days,
+            ..Default::default()
+        }
+    }
+
+    /// Age after which a record is archived
+    pub fn retention(&self) -> Duration {
+        Duration::from_secs(u64::from(self.retention_days) * 86_400)
+    }
+
+    /// Interval between retention sweeps (at least one second)
+    pub fn sweep_interval(&self) -> Duration {
+        Duration::from_secs(self.sweep_interval_seconds.max(1))
+    }
+}
+
+/// Errors from DLQ archival and restore
+#[derive(Debug, thiserror::Error)]
+pub enum DlqArchiveError {
+    /// No archive store was attached with `with_archive`
+    #[error("DLQ archive storage is not configured")]
+    NotConfigured,
+
+    /// No bundle with this ID exists in the archive store
+    #[error("DLQ archive bundle not found: {bundle_id}")]
+    BundleNotFound { bundle_id: EventId },
+
+    /// The bundle could not be decompressed or parsed
+    #[error("DLQ archive bundle {bundle_id} is corrupt: {message}")]
+    CorruptBundle { bundle_id: EventId, message: String },
+
+    /// The active or archive store failed
+    #[error("DLQ storage error: {0}")]
+    Storage(#[from] BlobStorageError),
+}
+
+/// An archived bundle of DLQ records
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DlqArchiveBundle {
+    /// Bundle identifier, used to restore it
+    pub bundle_id: EventId,
+
+    /// Blob path in the archive store
+    pub blob_path: String,
+
+    /// Number of records in the bundle
+    pub record_count: usize,
+
+    /// Compressed size in bytes
+    pub size_bytes: u64,
+}
+
+/// Outcome of a retention sweep
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DlqArchiveReport {
+    /// Bundles written to the archive store
+    pub bundles: Vec<DlqArchiveBundle>,
+
+    /// Records archived and removed from the active store
+    pub archived_records: usize,
+
+    /// Records archived but still present in the active store
+    pub failed_deletes: usize,
+
+    /// Active-store entries that could not be read as DLQ records
+    pub skipped_records: usize,
+}
+
+/// Outcome of restoring an archived bundle
+#[derive(Debug, Clone, Serialize)]
+pub struct DlqRestoreReport {
+    /// Bundle that was restored
+    pub bundle_id: EventId,
+
+    /// Records written back to the active store
+    pub restored_records: usize,
+}
+
+impl DlqArchive {
+    /// Compress `records` into a bundle and store it in the archive
+    async fn store_bundle(
+        &self,
+        records: &[FailedEventRecord],
+        now: Timestamp,
+    ) -> Result<DlqArchiveBundle, BlobStorageError> {
+        let bundle_id = EventId::new();
+        let body = encode_bundle(records)?;
+
+        let payload = WebhookPayload {
+            body: bytes::Bytes::from(body),
+            headers: HashMap::from([
+                (
+                    "content-type".to_string(),
+                    "application/x-ndjson".to_string(),
+                ),
+                ("content-encoding".to_string(), "gzip".to_string()),
+            ]),
+            metadata: PayloadMetadata {
+                event_id: bundle_id,
+                event_type: DLQ_ARCHIVE_EVENT_TYPE.to_string(),
+                repository: None,
+                signature_valid: true,
+                received_at: now,
+                delivery_id: None,
+            },
+        };
+
+        let metadata = self.storage.store_payload(&bundle_id, &payload).await?;
+
+        Ok(DlqArchiveBundle {
+            bundle_id,
+            blob_path: metadata.blob_path,
+            record_count: records.len(),
+            size_bytes: metadata.size_bytes,
+        })
+    }
+}
+
+/// Serialize records as gzip-compressed NDJSON, one record per line
+fn encode_bundle(records: &[FailedEventRecord]) -> Result<Vec<u8>, BlobStorageError> {
+    let serialization_failed = |message: String| BlobStorageError::SerializationFailed { message };
+
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    for record in records {
+        serde_json::to_writer(&mut encoder, record)
+            .map_err(|e| serialization_failed(format!("Failed to serialize DLQ record: {}", e)))?;
+        encoder
+            .write_all(b"\n")
+            .map_err(|e| serialization_failed(format!("Failed to compress DLQ bundle: {}", e)))?;
+    }
+    encoder
+        .finish()
+        .map_err(|e| serialization_failed(format!("Failed to compress DLQ bundle: {}", e)))
+}
+
+/// Decode a bundle produced by [`encode_bundle`]
+///
+/// Records from the previous format version are upgraded, so restoring a
+/// bundle writes them back in the current format.
+fn decode_bundle(body: &[u8]) -> Result<Vec<FailedEventRecord>, String> {
+    let mut ndjson = String::new();
+    GzDecoder::new(body)
+        .read_to_string(&mut ndjson)
+        .map_err(|e| format!("failed to decompress bundle: {}", e))?;
+
+    ndjson
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            let mut record: FailedEventRecord =
+                serde_json::from_str(line).map_err(|e| format!("record {}: {}", index + 1, e))?;
+            check_format_version(record.format_version)
+                .map_err(|e| format!("record {}: {}", index + 1, e))?;
+            record.format_version = CURRENT_FORMAT_VERSION;
+            Ok(record)
+        })
+        .collect()
 }
 
 // ============================================================================
@@ -352,16 +786,16 @@ pub async fn persist_to_dlq(
     match dlq_service {
         Some(service) => match service.persist_failed_event(record).await {
             Ok(blob_path) => {
-                info!(
-                    event_id = %record.event_id,
+                event_info!(
+                    record,
                     blob_path = %blob_path,
                     "Event persisted to DLQ"
                 );
                 true
             }
             Err(e) => {
-                error!(
-                    event_id = %record.event_id,
+                event_error!(
+                    record,
                     error = %e,
                     "Failed to persist event to DLQ - event may be lost"
                 );
@@ -369,8 +803,8 @@ pub async fn persist_to_dlq(
             }
         },
         None => {
-            warn!(
-                event_id = %record.event_id,
+            event_warn!(
+                record,
                 "DLQ service not configured - failed event not persisted"
             );
             false
@@ -11,6 +11,7 @@ use queue_keeper_core::{
 };
 use std::collections::HashMap;
 use std::sync::Mutex;
+use std::time::Duration;
 
 // ============================================================================
 // Mock BlobStorage
@@ -63,6 +64,9 @@ impl BlobStorage for MockBlobStorage {
             content_type: "application/json".to_string(),
             created_at: Timestamp::now(),
             checksum_sha256: checksum,
+            format_version: queue_keeper_core::storage_format::CURRENT_FORMAT_VERSION,
+            content_encoding: queue_keeper_core::blob_storage::ContentEncoding::Identity,
+            encryption: None,
             metadata: payload.metadata.clone(),
         };
 
@@ -320,6 +324,38 @@ fn test_failed_event_record_serialization() {
     assert_eq!(deserialized.failed_queues.len(), record.failed_queues.len());
 }
 
+/// New records carry the current format version; records written before
+/// versioning read as version 1.
+#[test]
+fn test_failed_event_record_format_version() {
+    use queue_keeper_core::storage_format::{CURRENT_FORMAT_VERSION, LEGACY_FORMAT_VERSION};
+
+    let record = create_failed_event_record();
+    assert_eq!(record.format_version, CURRENT_FORMAT_VERSION);
+
+    let mut value = serde_json::to_value(&record).unwrap();
+    value.as_object_mut().unwrap().remove("format_version");
+    let legacy: FailedEventRecord = serde_json::from_value(value).unwrap();
+    assert_eq!(legacy.format_version, LEGACY_FORMAT_VERSION);
+}
+
+/// Records from a newer release are refused instead of misread.
+#[tokio::test]
+async fn test_get_failed_event_rejects_newer_format() {
+    let storage = Arc::new(MockBlobStorage::new());
+    let service = DlqStorageService::new(storage);
+
+    let mut record = create_failed_event_record();
+    record.format_version = queue_keeper_core::storage_format::CURRENT_FORMAT_VERSION + 1;
+    service.persist_failed_event(&record).await.unwrap();
+
+    let result = service.get_failed_event(&record.event_id).await;
+    assert!(matches!(
+        result,
+        Err(BlobStorageError::UnsupportedFormatVersion { .. })
+    ));
+}
+
 // ============================================================================
 // DlqStorageService Tests
 // ============================================================================
@@ -542,3 +578,144 @@ async fn test_full_dlq_workflow() {
         assert_eq!(retrieved.retry_attempts, original.retry_attempts);
     }
 }
+
+// ============================================================================
+// Retention and Archival Tests
+// ============================================================================
+
+/// Persist a record that was moved to the DLQ `age_days` ago.
+async fn persist_aged_record(service: &DlqStorageService, age_days: u64) -> FailedEventRecord {
+    let mut record = create_failed_event_record();
+    record.moved_to_dlq_at =
+        Timestamp::now().subtract_duration(Duration::from_secs(age_days * 86_400));
+    service.persist_failed_event(&record).await.unwrap();
+    record
+}
+
+/// Expired records move to a compressed bundle and can be restored.
+#[tokio::test]
+async fn test_archive_expired_and_restore() {
+    let active = MockBlobStorage::new();
+    let archive = MockBlobStorage::new();
+    let service = DlqStorageService::new(Arc::new(active.clone()))
+        .with_archive(Arc::new(archive.clone()), DlqRetentionPolicy::new(30));
+
+    let old = persist_aged_record(&service, 40).await;
+    let recent = persist_aged_record(&service, 1).await;
+
+    let report = service.archive_expired(Timestamp::now()).await.unwrap();
+    assert_eq!(report.bundles.len(), 1);
+    assert_eq!(report.archived_records, 1);
+    assert_eq!(report.failed_deletes, 0);
+    assert_eq!(active.get_payload_count(), 1);
+    assert!(service
+        .get_failed_event(&old.event_id)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(service
+        .get_failed_event(&recent.event_id)
+        .await
+        .unwrap()
+        .is_some());
+
+    let bundle_id = report.bundles[0].bundle_id;
+    let restored = service.restore_bundle(&bundle_id).await.unwrap();
+    assert_eq!(restored.restored_records, 1);
+    let record = service
+        .get_failed_event(&old.event_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.reason, old.reason);
+    assert_eq!(record.moved_to_dlq_at, old.moved_to_dlq_at);
+}
+
+/// Bundles are gzip-compressed NDJSON capped at the configured size.
+#[tokio::test]
+async fn test_archive_bundles_are_capped_gzip_ndjson() {
+    let archive = MockBlobStorage::new();
+    let policy = DlqRetentionPolicy {
+        bundle_max_records: 2,
+        ..DlqRetentionPolicy::new(7)
+    };
+    let service = DlqStorageService::new(Arc::new(MockBlobStorage::new()))
+        .with_archive(Arc::new(archive.clone()), policy);
+
+    for _ in 0..3 {
+        persist_aged_record(&service, 10).await;
+    }
+
+    let report = service.archive_expired(Timestamp::now()).await.unwrap();
+    let mut sizes: Vec<_> = report.bundles.iter().map(|b| b.record_count).collect();
+    sizes.sort();
+    assert_eq!(sizes, vec![1, 2]);
+
+    let stored = archive
+        .get_payload(&report.bundles[0].bundle_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stored.payload.metadata.event_type, DLQ_ARCHIVE_EVENT_TYPE);
+    assert_eq!(
+        stored
+            .payload
+            .headers
+            .get("content-encoding")
+            .map(String::as_str),
+        Some("gzip")
+    );
+    let records = decode_bundle(&stored.payload.body).unwrap();
+    assert_eq!(records.len(), report.bundles[0].record_count);
+}
+
+/// Archival and restore require an archive store.
+#[tokio::test]
+async fn test_archive_not_configured() {
+    let service = DlqStorageService::new(Arc::new(MockBlobStorage::new()));
+
+    assert!(service.retention_policy().is_none());
+    assert!(matches!(
+        service.archive_expired(Timestamp::now()).await,
+        Err(DlqArchiveError::NotConfigured)
+    ));
+    assert!(matches!(
+        service.restore_bundle(&EventId::new()).await,
+        Err(DlqArchiveError::NotConfigured)
+    ));
+}
+
+/// Restoring an unknown or corrupt bundle fails without writing records.
+#[tokio::test]
+async fn test_restore_unknown_and_corrupt_bundles() {
+    let active = MockBlobStorage::new();
+    let archive = MockBlobStorage::new();
+    let service = DlqStorageService::new(Arc::new(active.clone()))
+        .with_archive(Arc::new(archive.clone()), DlqRetentionPolicy::default());
+
+    assert!(matches!(
+        service.restore_bundle(&EventId::new()).await,
+        Err(DlqArchiveError::BundleNotFound { .. })
+    ));
+
+    let bundle_id = EventId::new();
+    let payload = WebhookPayload {
+        body: bytes::Bytes::from_static(b"not gzip"),
+        headers: HashMap::new(),
+        metadata: PayloadMetadata {
+            event_id: bundle_id,
+            event_type: DLQ_ARCHIVE_EVENT_TYPE.to_string(),
+            repository: None,
+            signature_valid: true,
+            received_at: Timestamp::now(),
+            delivery_id: None,
+        },
+    };
+    archive.store_payload(&bundle_id, &payload).await.unwrap();
+
+    assert!(matches!(
+        service.restore_bundle(&bundle_id).await,
+        Err(DlqArchiveError::CorruptBundle { .. })
+    ));
+    assert_eq!(active.get_payload_count(), 0);
+}
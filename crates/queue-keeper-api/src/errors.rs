@@ -97,6 +97,16 @@ pub enum WebhookHandlerError {
     /// in the [`ProviderRegistry`](crate::provider_registry::ProviderRegistry).
     #[error("Webhook provider not found: {provider}")]
     ProviderNotFound { provider: String },
+
+    /// Delivery is not from a source the provider accepts
+    ///
+    /// Maps to: `403 Forbidden` (permanent error, do not retry)
+    ///
+    /// Occurs when the `X-GitHub-Enterprise-Host` header does not match the
+    /// provider's GitHub instance, or the installation is not in the
+    /// provider's installation registry.
+    #[error("Webhook source rejected by provider '{provider}': {reason}")]
+    SourceRejected { provider: String, reason: String },
 }
 
 impl IntoResponse for WebhookHandlerError {
@@ -148,6 +158,13 @@ impl IntoResponse for WebhookHandlerError {
                 warn!(provider = %provider, "Webhook provider not found");
                 (StatusCode::NOT_FOUND, self.to_string(), None)
             }
+            Self::SourceRejected {
+                ref provider,
+                ref reason,
+            } => {
+                warn!(provider = %provider, reason = %reason, "Webhook source rejected");
+                (StatusCode::FORBIDDEN, self.to_string(), None)
+            }
         };
 
         // Build JSON error response
@@ -201,4 +218,13 @@ pub enum ConfigError {
 
     #[error("Provider configuration validation failed: {message}")]
     ProviderValidation { message: String },
+
+    #[error(
+        "{component} provider '{provider}' is not compiled into this binary; rebuild with `--features {feature}`"
+    )]
+    ProviderNotCompiled {
+        component: String,
+        provider: String,
+        feature: String,
+    },
 }
@@ -0,0 +1,559 @@
+//! Age-based escalation of held and failing deliveries.
+//!
+//! Neither a paused bot nor a bot whose queue keeps rejecting events fails
+//! a webhook request, so a backlog can build up without anyone noticing.
+//! The [`EscalationMonitor`] measures how long each bot's backlog has
+//! existed:
+//!
+//! - `held`: age of the oldest event held for a paused bot (see
+//!   [`crate::bot_pause`]).
+//! - `failing`: time since a delivery to the bot first failed, without a
+//!   successful delivery since.
+//!
+//! When an age exceeds `escalation.max_age_seconds` the backlog is
+//! escalated: the monitor logs a warning, increments
+//! `delivery_escalations_total{bot_name,kind}`, sets
+//! `delivery_escalated_backlogs{bot_name,kind}` to 1 and, when
+//! `escalation.notification_url` is set, posts an [`Escalation`] to it.
+//!
+//! With `escalation.fallback_queue` set, held events older than the
+//! threshold are diverted to that queue instead of waiting for the bot to be
+//! resumed. A diverted event goes through the bot's transform, projection
+//! and field encryption, so the fallback queue never sees more than the bot
+//! would have. Failing deliveries are not diverted: they keep their retries
+//! and land in the DLQ as before.
+
+use crate::{
+    bot_pause::{BotPauseRegistry, HeldEvent},
+    metrics::ServiceMetrics,
+    queue_delivery::QueueDeliveryConfig,
+};
+use queue_keeper_core::{
+    bot_config::SharedBotConfiguration,
+    event_debug, event_error, event_info,
+    logging::LogFields,
+    queue_integration::{DefaultEventRouter, DeliveryResult},
+    BotName, EventId, Timestamp,
+};
+use queue_runtime::{Message, QueueClient, QueueName};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// Message attribute naming the bot a diverted event was held for.
+pub const ATTRIBUTE_ESCALATED_BOT: &str = "escalated_bot";
+
+/// Timeout for posting one escalation notification.
+const NOTIFICATION_TIMEOUT: Duration = Duration::from_secs(10);
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Escalation thresholds (the `escalation` configuration section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// escalation:
+///   check_interval_seconds: 60
+///   max_age_seconds: 14400
+///   notification_url: "https://alerts.example.com/queue-keeper"
+///   fallback_queue: "queue-keeper-fallback"
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EscalationConfig {
+    /// How often backlog ages are checked.
+    #[serde(default = "EscalationConfig::default_check_interval_seconds")]
+    pub check_interval_seconds: u64,
+
+    /// Age after which a held or failing backlog is escalated. `None` (the
+    /// default) disables escalation.
+    #[serde(default)]
+    pub max_age_seconds: Option<u64>,
+
+    /// URL receiving a JSON `POST` for every new escalation.
+    #[serde(default)]
+    pub notification_url: Option<String>,
+
+    /// Queue receiving held events older than `max_age_seconds`.
+    #[serde(default)]
+    pub fallback_queue: Option<String>,
+}
+
+impl EscalationConfig {
+    fn default_check_interval_seconds() -> u64 {
+        60
+    }
+
+    /// Whether backlogs are checked at all.
+    pub fn is_enabled(&self) -> bool {
+        self.max_age_seconds.is_some()
+    }
+
+    /// Validate the thresholds and destinations.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.check_interval_seconds == 0 {
+            return Err("escalation.check_interval_seconds must be at least 1".to_string());
+        }
+        if self.max_age_seconds == Some(0) {
+            return Err("escalation.max_age_seconds must be at least 1".to_string());
+        }
+        if !self.is_enabled() && (self.notification_url.is_some() || self.fallback_queue.is_some())
+        {
+            return Err(
+                "escalation.max_age_seconds is required when a notification URL or fallback \
+                 queue is configured"
+                    .to_string(),
+            );
+        }
+        if let Some(url) = &self.notification_url {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err(format!(
+                    "escalation.notification_url must be an http(s) URL (got '{}')",
+                    url
+                ));
+            }
+        }
+        if let Some(queue) = &self.fallback_queue {
+            QueueName::new(queue.clone())
+                .map_err(|e| format!("escalation.fallback_queue is invalid: {}", e))?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: Self::default_check_interval_seconds(),
+            max_age_seconds: None,
+            notification_url: None,
+            fallback_queue: None,
+        }
+    }
+}
+
+// ============================================================================
+// Failing Bots
+// ============================================================================
+
+#[derive(Debug)]
+struct FailingBot {
+    since: Timestamp,
+    events: HashSet<EventId>,
+}
+
+/// Bots whose most recent deliveries failed.
+///
+/// Shared by every clone of [`QueueDeliveryConfig`]; the delivery loop
+/// records the outcome of every routing attempt.
+#[derive(Debug, Default)]
+pub struct FailingBots {
+    bots: Mutex<HashMap<BotName, FailingBot>>,
+}
+
+/// A bot whose deliveries have been failing, for age-based escalation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailingBacklog {
+    pub bot_name: BotName,
+    /// When the first delivery since the last success failed.
+    pub failing_since: Timestamp,
+    /// Distinct events that failed since then.
+    pub failed_events: usize,
+}
+
+impl FailingBots {
+    /// Create a tracker with no failing bots.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the outcome of one routing attempt.
+    ///
+    /// A successful delivery clears the bot; a failed one marks it failing
+    /// from now on, unless it was already failing.
+    pub fn record_result(&self, result: &DeliveryResult) {
+        if result.successful.is_empty() && result.failed.is_empty() {
+            return;
+        }
+        let mut bots = self.bots.lock().unwrap();
+        for delivered in &result.successful {
+            bots.remove(&delivered.bot_name);
+        }
+        for failure in &result.failed {
+            bots.entry(failure.bot_name.clone())
+                .or_insert_with(|| FailingBot {
+                    since: Timestamp::now(),
+                    events: HashSet::new(),
+                })
+                .events
+                .insert(result.event_id);
+        }
+    }
+
+    /// Every failing bot, by bot name.
+    pub fn backlogs(&self) -> Vec<FailingBacklog> {
+        let bots = self.bots.lock().unwrap();
+        let mut backlogs: Vec<_> = bots
+            .iter()
+            .map(|(name, bot)| FailingBacklog {
+                bot_name: name.clone(),
+                failing_since: bot.since,
+                failed_events: bot.events.len(),
+            })
+            .collect();
+        backlogs.sort_by(|a, b| a.bot_name.as_str().cmp(b.bot_name.as_str()));
+        backlogs
+    }
+}
+
+// ============================================================================
+// Escalations
+// ============================================================================
+
+/// Why a bot's backlog was escalated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EscalationKind {
+    /// Events held for a paused bot.
+    Held,
+
+    /// Deliveries to the bot keep failing.
+    Failing,
+}
+
+impl EscalationKind {
+    /// Stable label used in metrics and notifications.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Held => "held",
+            Self::Failing => "failing",
+        }
+    }
+}
+
+/// A backlog older than the escalation threshold.
+///
+/// This is also the body of the notification posted to
+/// `escalation.notification_url`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Escalation {
+    pub bot_name: BotName,
+    pub kind: EscalationKind,
+    /// When the backlog started: the oldest held event, or the first failure.
+    pub since: Timestamp,
+    pub age_seconds: u64,
+    /// Events in the backlog when it was checked.
+    pub events: usize,
+    /// Events diverted to the fallback queue by this check.
+    pub diverted_events: usize,
+}
+
+// ============================================================================
+// Monitor
+// ============================================================================
+
+/// Checks backlog ages and escalates the ones over the threshold.
+pub struct EscalationMonitor {
+    config: EscalationConfig,
+    bot_pauses: Arc<BotPauseRegistry>,
+    failing_bots: Arc<FailingBots>,
+    fallback: Option<FallbackDelivery>,
+    client: reqwest::Client,
+    /// Backlogs escalated by the last check, to act on transitions.
+    escalated: Mutex<HashSet<(BotName, EscalationKind)>>,
+}
+
+impl std::fmt::Debug for EscalationMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EscalationMonitor")
+            .field("config", &self.config)
+            .field("fallback", &self.fallback.is_some())
+            .finish()
+    }
+}
+
+impl EscalationMonitor {
+    /// Create a monitor over the held and failing deliveries of
+    /// `delivery_config`.
+    pub fn new(config: EscalationConfig, delivery_config: &QueueDeliveryConfig) -> Self {
+        Self {
+            config,
+            bot_pauses: delivery_config.bot_pauses.clone(),
+            failing_bots: delivery_config.failing_bots.clone(),
+            fallback: None,
+            client: reqwest::Client::new(),
+            escalated: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Divert held events to the fallback queue through `queue_client`.
+    ///
+    /// Each event is prepared by `router` for the bot it was held for, as
+    /// found in `bot_config`.
+    pub fn with_fallback_delivery(
+        mut self,
+        queue_client: Arc<dyn QueueClient>,
+        router: Arc<DefaultEventRouter>,
+        bot_config: Arc<SharedBotConfiguration>,
+    ) -> Self {
+        self.fallback = Some(FallbackDelivery {
+            queue_client,
+            router,
+            bot_config,
+        });
+        self
+    }
+
+    /// Find the backlogs over the threshold and divert old held events.
+    pub async fn escalations(&self) -> Vec<Escalation> {
+        let Some(max_age) = self.config.max_age_seconds.map(Duration::from_secs) else {
+            return Vec::new();
+        };
+        let now = Timestamp::now();
+        let mut escalations = Vec::new();
+
+        for backlog in self.bot_pauses.held_backlogs() {
+            let age = now.duration_since(backlog.oldest_held_at);
+            if age <= max_age {
+                continue;
+            }
+            let diverted_events = self.divert_held(&backlog.bot_name, max_age, now).await;
+            escalations.push(Escalation {
+                bot_name: backlog.bot_name,
+                kind: EscalationKind::Held,
+                since: backlog.oldest_held_at,
+                age_seconds: age.as_secs(),
+                events: backlog.held_events,
+                diverted_events,
+            });
+        }
+
+        for backlog in self.failing_bots.backlogs() {
+            let age = now.duration_since(backlog.failing_since);
+            if age <= max_age {
+                continue;
+            }
+            escalations.push(Escalation {
+                bot_name: backlog.bot_name,
+                kind: EscalationKind::Failing,
+                since: backlog.failing_since,
+                age_seconds: age.as_secs(),
+                events: backlog.failed_events,
+                diverted_events: 0,
+            });
+        }
+
+        escalations
+    }
+
+    /// Escalate the backlogs over the threshold.
+    ///
+    /// Warnings, the escalation counter and notifications fire once per
+    /// backlog, when it first crosses the threshold; the gauge follows every
+    /// check.
+    pub async fn check(&self, metrics: &ServiceMetrics) -> Vec<Escalation> {
+        let escalations = self.escalations().await;
+
+        let now_escalated: HashSet<(BotName, EscalationKind)> = escalations
+            .iter()
+            .map(|e| (e.bot_name.clone(), e.kind))
+            .collect();
+        let newly_escalated: Vec<&Escalation> = {
+            let mut previously_escalated = self.escalated.lock().unwrap();
+            let newly_escalated = escalations
+                .iter()
+                .filter(|e| !previously_escalated.contains(&(e.bot_name.clone(), e.kind)))
+                .collect();
+            for (bot_name, kind) in previously_escalated.difference(&now_escalated) {
+                info!(bot_name = %bot_name, kind = kind.as_str(), "Backlog is no longer escalated");
+                metrics
+                    .delivery_escalated_backlogs
+                    .with_label_values(&[bot_name.as_str(), kind.as_str()])
+                    .set(0);
+            }
+            *previously_escalated = now_escalated;
+            newly_escalated
+        };
+
+        for escalation in &escalations {
+            let labels = [escalation.bot_name.as_str(), escalation.kind.as_str()];
+            metrics
+                .delivery_escalated_backlogs
+                .with_label_values(&labels)
+                .set(1);
+            if escalation.diverted_events > 0 {
+                metrics
+                    .escalation_diverted_events_total
+                    .with_label_values(&[escalation.bot_name.as_str()])
+                    .inc_by(escalation.diverted_events as u64);
+            }
+        }
+
+        for escalation in newly_escalated {
+            warn!(
+                bot_name = %escalation.bot_name,
+                kind = escalation.kind.as_str(),
+                since = %escalation.since,
+                age_seconds = escalation.age_seconds,
+                events = escalation.events,
+                diverted_events = escalation.diverted_events,
+                "Bot backlog exceeded its escalation threshold"
+            );
+            metrics
+                .delivery_escalations_total
+                .with_label_values(&[escalation.bot_name.as_str(), escalation.kind.as_str()])
+                .inc();
+            self.notify(escalation).await;
+        }
+
+        escalations
+    }
+
+    /// Run [`check`](Self::check) every `check_interval_seconds`, forever.
+    pub async fn run(&self, metrics: &ServiceMetrics) {
+        let interval = Duration::from_secs(self.config.check_interval_seconds);
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            self.check(metrics).await;
+        }
+    }
+
+    /// Send the held events of `bot_name` older than `max_age` to the
+    /// fallback queue. Returns the number of events diverted.
+    ///
+    /// Events the queue rejects go back to the front of the hold queue and
+    /// are tried again on the next check.
+    async fn divert_held(&self, bot_name: &BotName, max_age: Duration, now: Timestamp) -> usize {
+        let (Some(fallback), Some(delivery)) = (&self.config.fallback_queue, &self.fallback) else {
+            return 0;
+        };
+        let Ok(queue) = QueueName::new(fallback.clone()) else {
+            return 0;
+        };
+
+        let mut pending = self
+            .bot_pauses
+            .take_held_older_than(bot_name, max_age, now)
+            .into_iter();
+        let mut diverted = 0;
+        while let Some(held) = pending.next() {
+            let log_context = held.event.log_context().with_bot(bot_name);
+            let sent = match delivery.message(&held, bot_name).await {
+                Ok(Some(message)) => delivery
+                    .queue_client
+                    .send_message(&queue, message)
+                    .await
+                    .map_err(|e| e.to_string()),
+                Ok(None) => {
+                    event_debug!(log_context, "Transform skipped held event; not diverted");
+                    continue;
+                }
+                Err(e) => Err(e),
+            };
+            match sent {
+                Ok(message_id) => {
+                    event_info!(
+                        log_context,
+                        fallback_queue = %fallback,
+                        message_id = %message_id,
+                        "Held event diverted to fallback queue"
+                    );
+                    diverted += 1;
+                }
+                Err(error) => {
+                    event_error!(
+                        log_context,
+                        fallback_queue = %fallback,
+                        error = %error,
+                        "Failed to divert held event; keeping it held"
+                    );
+                    let remaining = std::iter::once(held).chain(pending).collect();
+                    for lost in self.bot_pauses.return_held(bot_name, remaining) {
+                        event_error!(
+                            lost.event.log_context().with_bot(bot_name),
+                            "Bot was resumed while its held events were being diverted; event dropped"
+                        );
+                    }
+                    break;
+                }
+            }
+        }
+        diverted
+    }
+
+    /// Post `escalation` to the notification URL, if one is configured.
+    async fn notify(&self, escalation: &Escalation) {
+        let Some(url) = &self.config.notification_url else {
+            return;
+        };
+        let result = self
+            .client
+            .post(url.as_str())
+            .json(escalation)
+            .timeout(NOTIFICATION_TIMEOUT)
+            .send()
+            .await;
+        let error = match result {
+            Ok(response) if response.status().is_success() => return,
+            Ok(response) => format!("status {}", response.status().as_u16()),
+            Err(e) => e.to_string(),
+        };
+        warn!(
+            bot_name = %escalation.bot_name,
+            kind = escalation.kind.as_str(),
+            error = %error,
+            "Escalation notification was not accepted"
+        );
+    }
+}
+
+/// What diverting held events to the fallback queue needs.
+struct FallbackDelivery {
+    queue_client: Arc<dyn QueueClient>,
+    router: Arc<DefaultEventRouter>,
+    bot_config: Arc<SharedBotConfiguration>,
+}
+
+impl FallbackDelivery {
+    /// Build the fallback queue message for an event held for `bot_name`.
+    ///
+    /// The message is the one the bot would receive, plus the bot the event
+    /// was held for; `None` when the bot's transform skips the event.
+    async fn message(
+        &self,
+        held: &HeldEvent,
+        bot_name: &BotName,
+    ) -> Result<Option<Message>, String> {
+        let bot_config = self.bot_config.current();
+        let bot = bot_config
+            .bots
+            .iter()
+            .find(|bot| &bot.name == bot_name)
+            .ok_or_else(|| "bot is no longer configured".to_string())?;
+        let message = self
+            .router
+            .message_for_bot(&held.event, bot)
+            .await
+            .map_err(|failure| failure.error)?;
+        Ok(message.map(|message| {
+            message.with_attribute(
+                ATTRIBUTE_ESCALATED_BOT.to_string(),
+                bot_name.as_str().to_string(),
+            )
+        }))
+    }
+}
+
+#[cfg(test)]
+#[path = "escalation_tests.rs"]
+mod tests;
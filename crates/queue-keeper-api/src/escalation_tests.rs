@@ -0,0 +1,309 @@
+//! Tests for age-based escalation of held and failing deliveries.
+
+use super::*;
+use crate::test_fixtures::{
+    bot, bot_config, delivered, event, event_with_payload, failed, subscription,
+};
+use queue_keeper_core::{
+    bot_config::EventTypePattern,
+    payload_projection::PayloadProjectionConfig,
+    queue_integration::{FailedDelivery, SuccessfulDelivery},
+    BotConfiguration,
+};
+use queue_runtime::QueueClientFactory;
+
+fn result(successful: Vec<SuccessfulDelivery>, failed: Vec<FailedDelivery>) -> DeliveryResult {
+    DeliveryResult {
+        event_id: EventId::new(),
+        successful,
+        failed,
+    }
+}
+
+/// Configuration escalating every backlog that exists at all.
+fn immediate() -> EscalationConfig {
+    EscalationConfig {
+        max_age_seconds: Some(0),
+        ..EscalationConfig::default()
+    }
+}
+
+fn hold_for(delivery_config: &QueueDeliveryConfig, name: &str) {
+    delivery_config
+        .bot_pauses
+        .pause(&bot(name), "ops", "maintenance");
+    delivery_config
+        .bot_pauses
+        .hold_for_paused_bots(&event(), bot_config(&[name]));
+}
+
+/// Let a backlog age past a zero-second threshold.
+fn age_backlogs() {
+    std::thread::sleep(Duration::from_millis(5));
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Verify that escalation is disabled by default.
+#[test]
+fn test_default_config_is_disabled_and_valid() {
+    let config = EscalationConfig::default();
+
+    assert!(!config.is_enabled());
+    assert_eq!(config.check_interval_seconds, 60);
+    assert!(config.validate().is_ok());
+}
+
+/// Verify that a notification URL or fallback queue needs a threshold.
+#[test]
+fn test_destinations_require_max_age() {
+    let config = EscalationConfig {
+        notification_url: Some("https://alerts.example.com".to_string()),
+        ..EscalationConfig::default()
+    };
+
+    assert!(config.validate().unwrap_err().contains("max_age_seconds"));
+}
+
+/// Verify that invalid thresholds and destinations are rejected.
+#[test]
+fn test_invalid_settings_rejected() {
+    let enabled = EscalationConfig {
+        max_age_seconds: Some(3600),
+        ..EscalationConfig::default()
+    };
+
+    for config in [
+        EscalationConfig {
+            max_age_seconds: Some(0),
+            ..enabled.clone()
+        },
+        EscalationConfig {
+            check_interval_seconds: 0,
+            ..enabled.clone()
+        },
+        EscalationConfig {
+            notification_url: Some("alerts.example.com".to_string()),
+            ..enabled.clone()
+        },
+        EscalationConfig {
+            fallback_queue: Some("Not A Queue!".to_string()),
+            ..enabled.clone()
+        },
+    ] {
+        assert!(config.validate().is_err(), "{:?} should be invalid", config);
+    }
+    assert!(enabled.validate().is_ok());
+}
+
+// ============================================================================
+// Failing Bots
+// ============================================================================
+
+/// Verify that failures are counted per distinct event until a success.
+#[test]
+fn test_failing_bot_cleared_by_success() {
+    let failing = FailingBots::new();
+    let first = result(vec![], vec![failed("alpha", true)]);
+    failing.record_result(&first);
+    failing.record_result(&first);
+    failing.record_result(&result(
+        vec![delivered("beta")],
+        vec![failed("alpha", true)],
+    ));
+
+    let backlogs = failing.backlogs();
+    assert_eq!(backlogs.len(), 1);
+    assert_eq!(backlogs[0].bot_name, bot("alpha"));
+    assert_eq!(backlogs[0].failed_events, 2);
+
+    failing.record_result(&result(vec![delivered("alpha")], vec![]));
+    assert!(failing.backlogs().is_empty());
+}
+
+/// Verify that further failures keep the time of the first one.
+#[test]
+fn test_failing_since_is_first_failure() {
+    let failing = FailingBots::new();
+    failing.record_result(&result(vec![], vec![failed("alpha", true)]));
+    let since = failing.backlogs()[0].failing_since;
+    age_backlogs();
+
+    failing.record_result(&result(vec![], vec![failed("alpha", true)]));
+
+    assert_eq!(failing.backlogs()[0].failing_since, since);
+}
+
+// ============================================================================
+// Monitor
+// ============================================================================
+
+/// Verify that nothing is escalated while escalation is disabled.
+#[tokio::test]
+async fn test_disabled_monitor_escalates_nothing() {
+    let delivery_config = QueueDeliveryConfig::default();
+    hold_for(&delivery_config, "alpha");
+    age_backlogs();
+    let monitor = EscalationMonitor::new(EscalationConfig::default(), &delivery_config);
+
+    assert!(monitor.escalations().await.is_empty());
+}
+
+/// Verify that backlogs younger than the threshold are not escalated.
+#[tokio::test]
+async fn test_young_backlog_not_escalated() {
+    let delivery_config = QueueDeliveryConfig::default();
+    hold_for(&delivery_config, "alpha");
+    let config = EscalationConfig {
+        max_age_seconds: Some(3600),
+        ..EscalationConfig::default()
+    };
+    let monitor = EscalationMonitor::new(config, &delivery_config);
+
+    assert!(monitor.escalations().await.is_empty());
+}
+
+/// Verify that old held and failing backlogs are both escalated, and that
+/// held events stay held without a fallback queue.
+#[tokio::test]
+async fn test_held_and_failing_backlogs_escalated() {
+    let delivery_config = QueueDeliveryConfig::default();
+    hold_for(&delivery_config, "alpha");
+    delivery_config
+        .failing_bots
+        .record_result(&result(vec![], vec![failed("beta", true)]));
+    age_backlogs();
+    let monitor = EscalationMonitor::new(immediate(), &delivery_config);
+
+    let escalations = monitor.escalations().await;
+
+    let kinds: Vec<_> = escalations
+        .iter()
+        .map(|e| (e.bot_name.as_str(), e.kind))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ("alpha", EscalationKind::Held),
+            ("beta", EscalationKind::Failing)
+        ]
+    );
+    assert!(escalations.iter().all(|e| e.events == 1));
+    assert_eq!(escalations[0].diverted_events, 0);
+    assert_eq!(delivery_config.bot_pauses.statuses()[0].held_events, 1);
+}
+
+/// Verify that a diverted event is the one the bot would have received,
+/// projected, and names the bot it was held for.
+#[tokio::test]
+async fn test_diverted_event_prepared_for_bot() {
+    let delivery_config = QueueDeliveryConfig::default();
+    let mut alpha = subscription("alpha", EventTypePattern::Wildcard("*".to_string()));
+    alpha.projection = Some(PayloadProjectionConfig {
+        fields: vec!["/kept".to_string()],
+        ..PayloadProjectionConfig::default()
+    });
+    let bots = Arc::new(BotConfiguration {
+        bots: vec![alpha],
+        ..(*bot_config(&[])).clone()
+    });
+    delivery_config
+        .bot_pauses
+        .pause(&bot("alpha"), "ops", "maintenance");
+    delivery_config.bot_pauses.hold_for_paused_bots(
+        &event_with_payload(serde_json::json!({ "kept": 1, "dropped": 2 })),
+        bots.clone(),
+    );
+    age_backlogs();
+
+    let queue_client: Arc<dyn QueueClient> = Arc::from(QueueClientFactory::create_test_client());
+    let config = EscalationConfig {
+        fallback_queue: Some("queue-keeper-fallback".to_string()),
+        ..immediate()
+    };
+    let monitor = EscalationMonitor::new(config, &delivery_config).with_fallback_delivery(
+        queue_client.clone(),
+        Arc::new(DefaultEventRouter::new()),
+        Arc::new(SharedBotConfiguration::new((*bots).clone())),
+    );
+
+    let escalations = monitor.escalations().await;
+    assert_eq!(escalations[0].diverted_events, 1);
+
+    let fallback = QueueName::new("queue-keeper-fallback".to_string()).unwrap();
+    let received = queue_client
+        .receive_message(&fallback, chrono::Duration::seconds(1))
+        .await
+        .unwrap()
+        .expect("diverted event");
+    let body: serde_json::Value = serde_json::from_slice(&received.body).unwrap();
+    assert_eq!(body["payload"], serde_json::json!({ "kept": 1 }));
+    assert_eq!(received.attributes[ATTRIBUTE_ESCALATED_BOT], "alpha");
+    assert_eq!(received.attributes["bot_name"], "alpha");
+}
+
+/// Verify that an escalation is counted once and the gauge is cleared when
+/// the backlog goes away.
+#[tokio::test]
+async fn test_check_counts_transitions() {
+    let metrics = ServiceMetrics::default();
+    let delivery_config = QueueDeliveryConfig::default();
+    delivery_config
+        .failing_bots
+        .record_result(&result(vec![], vec![failed("alpha", true)]));
+    age_backlogs();
+    let monitor = EscalationMonitor::new(immediate(), &delivery_config);
+    let labels = ["alpha", "failing"];
+
+    monitor.check(&metrics).await;
+    monitor.check(&metrics).await;
+
+    assert_eq!(
+        metrics
+            .delivery_escalations_total
+            .with_label_values(&labels)
+            .get(),
+        1
+    );
+    assert_eq!(
+        metrics
+            .delivery_escalated_backlogs
+            .with_label_values(&labels)
+            .get(),
+        1
+    );
+
+    delivery_config
+        .failing_bots
+        .record_result(&result(vec![delivered("alpha")], vec![]));
+    monitor.check(&metrics).await;
+
+    assert_eq!(
+        metrics
+            .delivery_escalated_backlogs
+            .with_label_values(&labels)
+            .get(),
+        0
+    );
+}
+
+/// Verify the JSON body posted to the notification URL.
+#[test]
+fn test_escalation_serializes_kind_as_snake_case() {
+    let escalation = Escalation {
+        bot_name: bot("alpha"),
+        kind: EscalationKind::Held,
+        since: Timestamp::now(),
+        age_seconds: 7200,
+        events: 3,
+        diverted_events: 0,
+    };
+
+    let body = serde_json::to_value(&escalation).unwrap();
+
+    assert_eq!(body["bot_name"], "alpha");
+    assert_eq!(body["kind"], "held");
+    assert_eq!(body["age_seconds"], 7200);
+}
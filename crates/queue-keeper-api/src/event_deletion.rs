@@ -0,0 +1,380 @@
+//! Soft deletion and scheduled purge of stored events.
+//!
+//! Stored events are never removed in a single step. `DELETE
+//! /admin/events/{event_id}` records an [`EventTombstone`] naming the
+//! principal, the reason and the time; the event is then excluded from
+//! event, session and statistics listings (`GET /api/events?include_deleted=true`
+//! still shows it) but stays readable by ID. Once `event_deletion.purge_after`
+//! has passed, the [`EventPurger`] removes the stored event and its
+//! tombstone. Both phases are written to the audit log.
+//!
+//! Tombstones are written to `event_deletion.directory`, one JSON file per
+//! event, so pending purges survive a restart. Without a directory they are
+//! kept in memory and a restart restores the deleted events to listings.
+
+use crate::{metrics::ServiceMetrics, responses::EventStore};
+use queue_keeper_core::{
+    audit_logging::{
+        AuditAction, AuditActor, AuditContext, AuditEvent, AuditEventType, AuditLogger,
+        AuditResource, AuditResult,
+    },
+    units::human_duration,
+    EventId, Timestamp,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// Interval between two purges of the events whose purge time has passed.
+pub const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Event deletion settings (the `event_deletion` configuration section).
+///
+/// ```yaml
+/// event_deletion:
+///   purge_after: 30d
+///   directory: /var/lib/queue-keeper/tombstones
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventDeletionConfig {
+    /// Time between the soft deletion of an event and its purge.
+    #[serde(with = "human_duration")]
+    pub purge_after: Duration,
+
+    /// Directory the tombstones are written to. Tombstones are kept in
+    /// memory when unset.
+    pub directory: Option<PathBuf>,
+}
+
+impl Default for EventDeletionConfig {
+    fn default() -> Self {
+        Self {
+            purge_after: Duration::from_secs(30 * 24 * 3600),
+            directory: None,
+        }
+    }
+}
+
+impl EventDeletionConfig {
+    /// Validate the settings.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.purge_after.is_zero() {
+            return Err("event_deletion.purge_after must not be zero".to_string());
+        }
+        if self
+            .directory
+            .as_ref()
+            .is_some_and(|d| d.as_os_str().is_empty())
+        {
+            return Err("event_deletion.directory must not be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Tombstones
+// ============================================================================
+
+/// Record of the soft deletion of a stored event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventTombstone {
+    /// The deleted event.
+    pub event_id: EventId,
+
+    /// Principal that deleted the event.
+    pub deleted_by: String,
+
+    /// Why the event was deleted.
+    pub reason: String,
+
+    /// When the event was deleted.
+    pub deleted_at: Timestamp,
+
+    /// When the event becomes due for purging.
+    pub purge_at: Timestamp,
+}
+
+/// Errors recording or removing tombstones.
+#[derive(Debug, thiserror::Error)]
+pub enum EventDeletionError {
+    #[error("Tombstone directory {path} could not be read: {message}")]
+    Load { path: String, message: String },
+
+    #[error("Tombstone of event {event_id} could not be written: {message}")]
+    Persist { event_id: EventId, message: String },
+}
+
+/// Tombstones of the soft-deleted events.
+#[derive(Debug)]
+pub struct EventTombstones {
+    purge_after: Duration,
+    directory: Option<PathBuf>,
+    tombstones: Mutex<HashMap<EventId, EventTombstone>>,
+}
+
+impl Default for EventTombstones {
+    fn default() -> Self {
+        Self::new(EventDeletionConfig::default().purge_after)
+    }
+}
+
+impl EventTombstones {
+    /// Keep tombstones in memory, purging events `purge_after` their
+    /// deletion.
+    pub fn new(purge_after: Duration) -> Self {
+        Self {
+            purge_after,
+            directory: None,
+            tombstones: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Open the tombstones described by `config`, loading those written to
+    /// its directory by earlier runs.
+    pub fn open(config: &EventDeletionConfig) -> Result<Self, EventDeletionError> {
+        let mut tombstones = Self::new(config.purge_after);
+        if let Some(directory) = &config.directory {
+            let loaded = load_directory(directory)?;
+            tombstones.tombstones = Mutex::new(loaded);
+            tombstones.directory = Some(directory.clone());
+        }
+        Ok(tombstones)
+    }
+
+    /// Record the deletion of `event_id` by `deleted_by`.
+    ///
+    /// Deleting an event twice keeps the first tombstone, which is returned.
+    pub fn delete(
+        &self,
+        event_id: EventId,
+        deleted_by: &str,
+        reason: &str,
+    ) -> Result<EventTombstone, EventDeletionError> {
+        let mut tombstones = self.tombstones.lock().unwrap();
+        if let Some(existing) = tombstones.get(&event_id) {
+            return Ok(existing.clone());
+        }
+
+        let deleted_at = Timestamp::now();
+        let tombstone = EventTombstone {
+            event_id,
+            deleted_by: deleted_by.to_string(),
+            reason: reason.to_string(),
+            deleted_at,
+            purge_at: deleted_at.add_seconds(self.purge_after.as_secs()),
+        };
+        if let Some(directory) = &self.directory {
+            write_tombstone(directory, &tombstone)?;
+        }
+        tombstones.insert(event_id, tombstone.clone());
+        Ok(tombstone)
+    }
+
+    /// Tombstone of `event_id`, if it is deleted.
+    pub fn get(&self, event_id: &EventId) -> Option<EventTombstone> {
+        self.tombstones.lock().unwrap().get(event_id).cloned()
+    }
+
+    /// Whether `event_id` is deleted.
+    pub fn is_deleted(&self, event_id: &EventId) -> bool {
+        self.tombstones.lock().unwrap().contains_key(event_id)
+    }
+
+    /// Tombstones whose purge time is at or before `now`, oldest first.
+    pub fn due(&self, now: Timestamp) -> Vec<EventTombstone> {
+        let mut due: Vec<EventTombstone> = self
+            .tombstones
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|t| t.purge_at <= now)
+            .cloned()
+            .collect();
+        due.sort_by_key(|t| t.purge_at);
+        due
+    }
+
+    /// Forget the tombstone of a purged event.
+    pub fn remove(&self, event_id: &EventId) -> Result<(), EventDeletionError> {
+        let mut tombstones = self.tombstones.lock().unwrap();
+        if let Some(directory) = &self.directory {
+            match std::fs::remove_file(tombstone_path(directory, event_id)) {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => {
+                    return Err(EventDeletionError::Persist {
+                        event_id: *event_id,
+                        message: e.to_string(),
+                    })
+                }
+            }
+        }
+        tombstones.remove(event_id);
+        Ok(())
+    }
+
+    /// Number of deleted events not yet purged.
+    pub fn len(&self) -> usize {
+        self.tombstones.lock().unwrap().len()
+    }
+
+    /// Whether no deleted event is waiting to be purged.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn tombstone_path(directory: &Path, event_id: &EventId) -> PathBuf {
+    directory.join(format!("{}.json", event_id))
+}
+
+/// Write `tombstone` to a temporary file and rename it into place, so a
+/// crash never leaves a partial tombstone.
+fn write_tombstone(directory: &Path, tombstone: &EventTombstone) -> Result<(), EventDeletionError> {
+    let persist_error = |message: String| EventDeletionError::Persist {
+        event_id: tombstone.event_id,
+        message,
+    };
+    let json = serde_json::to_vec_pretty(tombstone).map_err(|e| persist_error(e.to_string()))?;
+    let path = tombstone_path(directory, &tombstone.event_id);
+    let temp_path = path.with_extension("json.tmp");
+    std::fs::create_dir_all(directory).map_err(|e| persist_error(e.to_string()))?;
+    std::fs::write(&temp_path, json).map_err(|e| persist_error(e.to_string()))?;
+    std::fs::rename(&temp_path, &path).map_err(|e| persist_error(e.to_string()))
+}
+
+fn load_directory(
+    directory: &Path,
+) -> Result<HashMap<EventId, EventTombstone>, EventDeletionError> {
+    let load_error = |message: String| EventDeletionError::Load {
+        path: directory.display().to_string(),
+        message,
+    };
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(load_error(e.to_string())),
+    };
+
+    let mut tombstones = HashMap::new();
+    for entry in entries {
+        let path = entry.map_err(|e| load_error(e.to_string()))?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let bytes = std::fs::read(&path).map_err(|e| load_error(e.to_string()))?;
+        let tombstone: EventTombstone = serde_json::from_slice(&bytes)
+            .map_err(|e| load_error(format!("{}: {}", path.display(), e)))?;
+        tombstones.insert(tombstone.event_id, tombstone);
+    }
+    Ok(tombstones)
+}
+
+// ============================================================================
+// Purge
+// ============================================================================
+
+/// Periodically purges the soft-deleted events whose purge time has passed.
+pub struct EventPurger {
+    event_store: Arc<dyn EventStore>,
+    audit_logger: Option<Arc<dyn AuditLogger>>,
+}
+
+impl EventPurger {
+    /// Purge the deleted events of `event_store`.
+    pub fn new(event_store: Arc<dyn EventStore>) -> Self {
+        Self {
+            event_store,
+            audit_logger: None,
+        }
+    }
+
+    /// Record every purge in `audit_logger`.
+    pub fn with_audit_logger(mut self, audit_logger: Arc<dyn AuditLogger>) -> Self {
+        self.audit_logger = Some(audit_logger);
+        self
+    }
+
+    /// Purge the events that are due and return their tombstones.
+    pub async fn purge(&self, metrics: &ServiceMetrics) -> Vec<EventTombstone> {
+        let purged = match self.event_store.purge_deleted(Timestamp::now()).await {
+            Ok(purged) => purged,
+            Err(e) => {
+                warn!(error = %e, "Purge of deleted events failed");
+                return Vec::new();
+            }
+        };
+
+        for tombstone in &purged {
+            metrics.events_purged_total.inc();
+            info!(
+                event_id = %tombstone.event_id,
+                deleted_by = %tombstone.deleted_by,
+                deleted_at = %tombstone.deleted_at,
+                "Purged deleted event"
+            );
+            if let Some(audit_logger) = &self.audit_logger {
+                if let Err(e) = audit_logger.log_event(purge_audit_event(tombstone)).await {
+                    warn!(
+                        error = %e,
+                        event_id = %tombstone.event_id,
+                        "Failed to record event purge audit entry"
+                    );
+                }
+            }
+        }
+        purged
+    }
+
+    /// Run [`purge`](Self::purge) every [`PURGE_INTERVAL`], forever.
+    pub async fn run(&self, metrics: &ServiceMetrics) {
+        let mut ticker = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            ticker.tick().await;
+            self.purge(metrics).await;
+        }
+    }
+}
+
+/// Audit entry of the purge of the event `tombstone` describes.
+fn purge_audit_event(tombstone: &EventTombstone) -> AuditEvent {
+    AuditEvent::new(
+        AuditEventType::Compliance,
+        AuditActor::System {
+            component_name: "queue-keeper".to_string(),
+            instance_id: "event-purger".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        },
+        AuditResource::Data {
+            data_type: "event".to_string(),
+            identifier: tombstone.event_id.to_string(),
+            location: None,
+        },
+        AuditAction::Delete {
+            reason: Some(tombstone.reason.clone()),
+        },
+        AuditResult::Success {
+            duration: None,
+            details: Some(format!(
+                "purged; deleted by {} at {}",
+                tombstone.deleted_by, tombstone.deleted_at
+            )),
+        },
+        AuditContext::default(),
+    )
+}
+
+#[cfg(test)]
+#[path = "event_deletion_tests.rs"]
+mod tests;
@@ -0,0 +1,122 @@
+//! Tests for soft deletion and purge of stored events.
+
+use super::*;
+use crate::responses::{store_wrapped_event_to_blob, BlobBackedEventStore, EventListParams};
+use crate::test_fixtures::event;
+use queue_keeper_core::{
+    adapters::filesystem_storage::FilesystemBlobStorage, blob_storage::BlobStorage,
+};
+
+/// A deleted event keeps its first tombstone and becomes due after
+/// `purge_after`.
+#[test]
+fn test_tombstone_lifecycle() {
+    let tombstones = EventTombstones::new(Duration::from_secs(60));
+    let event_id = EventId::new();
+
+    let first = tombstones
+        .delete(event_id, "alice", "leaked token")
+        .unwrap();
+    let second = tombstones.delete(event_id, "bob", "again").unwrap();
+    assert_eq!(first, second);
+    assert_eq!(first.deleted_by, "alice");
+    assert!(tombstones.is_deleted(&event_id));
+
+    assert!(tombstones.due(Timestamp::now()).is_empty());
+    assert_eq!(tombstones.due(first.purge_at), vec![first]);
+
+    tombstones.remove(&event_id).unwrap();
+    assert!(tombstones.is_empty());
+}
+
+/// Tombstones written to the configured directory are loaded again.
+#[test]
+fn test_tombstones_persist_in_directory() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = EventDeletionConfig {
+        directory: Some(dir.path().join("tombstones")),
+        ..EventDeletionConfig::default()
+    };
+    let event_id = EventId::new();
+
+    let tombstone = EventTombstones::open(&config)
+        .unwrap()
+        .delete(event_id, "alice", "leaked token")
+        .unwrap();
+
+    let reopened = EventTombstones::open(&config).unwrap();
+    assert_eq!(reopened.get(&event_id), Some(tombstone));
+
+    reopened.remove(&event_id).unwrap();
+    assert!(EventTombstones::open(&config).unwrap().is_empty());
+}
+
+/// A deleted event is only listed on request, and is removed from storage
+/// by the purger once due.
+#[tokio::test]
+async fn test_deleted_event_hidden_then_purged() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage: Arc<dyn BlobStorage> = Arc::new(
+        FilesystemBlobStorage::new(dir.path().to_path_buf())
+            .await
+            .unwrap(),
+    );
+    let kept = event();
+    let deleted = event();
+    store_wrapped_event_to_blob(storage.as_ref(), &kept)
+        .await
+        .unwrap();
+    store_wrapped_event_to_blob(storage.as_ref(), &deleted)
+        .await
+        .unwrap();
+    let tombstones = Arc::new(EventTombstones::new(Duration::ZERO));
+    let store: Arc<dyn EventStore> =
+        Arc::new(BlobBackedEventStore::new(storage.clone()).with_tombstones(tombstones.clone()));
+
+    store
+        .delete_event(&deleted.event_id, "alice", "leaked token")
+        .await
+        .unwrap();
+    let listed = store.list_events(EventListParams::default()).await.unwrap();
+    assert_eq!(listed.events.len(), 1);
+    assert_eq!(listed.events[0].event_id, kept.event_id);
+    let all = store
+        .list_events(EventListParams {
+            include_deleted: Some(true),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(all.events.len(), 2);
+    assert!(all.events.iter().any(|e| e.status == "deleted"));
+
+    let metrics = ServiceMetrics::default();
+    let purged = EventPurger::new(store.clone()).purge(&metrics).await;
+    assert_eq!(purged.len(), 1);
+    assert_eq!(metrics.events_purged_total.get(), 1);
+    assert!(tombstones.is_empty());
+    assert!(storage
+        .get_payload(&deleted.event_id)
+        .await
+        .unwrap()
+        .is_none());
+}
+
+/// Only stored events can be deleted.
+#[tokio::test]
+async fn test_delete_unknown_event_not_found() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage: Arc<dyn BlobStorage> = Arc::new(
+        FilesystemBlobStorage::new(dir.path().to_path_buf())
+            .await
+            .unwrap(),
+    );
+    let store = BlobBackedEventStore::new(storage);
+
+    let result = store.delete_event(&EventId::new(), "alice", "typo").await;
+
+    assert!(matches!(
+        result,
+        Err(queue_keeper_core::QueueKeeperError::NotFound { .. })
+    ));
+}
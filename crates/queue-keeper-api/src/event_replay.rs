@@ -0,0 +1,709 @@
+//! Event replay.
+//!
+//! Implements the core [`EventReplayService`] behind
+//! `POST /admin/events/{event_id}/replay`. A replay reads the stored event
+//! envelope from the event blob store ([`BlobEventRetriever`]), derives a
+//! new event from it with [`LineageCause::Replay`] and delivers that event
+//! through the normal routing and delivery path — bot subscriptions, paused
+//! bots, retries, DLQ and session epochs ([`PipelineReplayExecutor`]).
+//!
+//! A replay can be restricted to specific bots, and a dry run reports the
+//! bots the event would be routed to without delivering it.
+//!
+//! # Limitations
+//!
+//! Only single-event replays are supported. The stored envelope is the
+//! normalized event; the provider's raw request is not kept, so a replay
+//! routes the event as it was normalized when received. Replay results are
+//! kept in memory by the replica that ran them, up to
+//! [`MAX_RETAINED_REPLAYS`].
+
+use crate::{
+    queue_delivery::{deliver_event_to_queues, QueueDeliveryConfig, QueueDeliveryOutcome},
+    responses::store_wrapped_event_to_blob,
+    statistics::StatisticsCollector,
+};
+use async_trait::async_trait;
+use queue_keeper_core::{
+    blob_storage::BlobStorage,
+    bot_config::{BotConfiguration, BotSubscription, SharedBotConfiguration},
+    event_replay::{
+        EventFilter, EventReplayResult, EventReplayService, EventRetriever, ExecutionCapacity,
+        ExecutionMetadata, ExecutionStrategy, ReplayConfiguration, ReplayError, ReplayEstimate,
+        ReplayExecutionOptions, ReplayExecutor, ReplayId, ReplayListFilter, ReplayOutcome,
+        ReplayProgress, ReplayRequest, ReplayResults, ReplayServiceStatus, ReplayState,
+        ReplayStatistics, ReplayStatus, ReplayType, ReplayValidationResult, ResourceEstimate,
+        ResourceUsage, RoutingSummary, StorageMetadata, StoredEvent,
+    },
+    queue_integration::EventRouter,
+    webhook::{deserialize_envelope, LineageCause, WrappedEvent},
+    BotName, EventId, Repository, SessionId, Timestamp,
+};
+use queue_runtime::QueueClient;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// Number of finished replays whose results are kept for the status API.
+pub const MAX_RETAINED_REPLAYS: usize = 1_000;
+
+/// Reason recorded for dry-run replays, which deliver nothing.
+const DRY_RUN_REASON: &str = "dry run";
+
+// ============================================================================
+// Retriever
+// ============================================================================
+
+/// Reads stored event envelopes from the event blob store.
+///
+/// Envelopes are written by the webhook handler with
+/// [`store_wrapped_event_to_blob`]; events whose payload was sampled out are
+/// not stored and cannot be replayed.
+pub struct BlobEventRetriever {
+    storage: Arc<dyn BlobStorage>,
+}
+
+impl BlobEventRetriever {
+    /// Create a retriever over the event blob store.
+    pub fn new(storage: Arc<dyn BlobStorage>) -> Self {
+        Self { storage }
+    }
+}
+
+/// Error for the bulk queries this service does not support.
+fn bulk_replay_unsupported() -> ReplayError {
+    ReplayError::InvalidRequest {
+        reason: "Only single-event replays are supported".to_string(),
+    }
+}
+
+#[async_trait]
+impl EventRetriever for BlobEventRetriever {
+    async fn get_event(&self, event_id: EventId) -> Result<Option<StoredEvent>, ReplayError> {
+        let stored = match self.storage.get_payload(&event_id).await {
+            Ok(Some(stored)) => stored,
+            Ok(None) => return Ok(None),
+            Err(e) => {
+                return Err(ReplayError::StorageError {
+                    message: e.to_string(),
+                })
+            }
+        };
+        let envelope = deserialize_envelope(&stored.payload.body).map_err(|e| {
+            ReplayError::EventRetrievalFailed {
+                message: format!("Failed to deserialise event {}: {}", event_id, e),
+            }
+        })?;
+
+        Ok(Some(StoredEvent {
+            envelope,
+            storage_metadata: StorageMetadata {
+                blob_path: stored.metadata.blob_path,
+                stored_at: stored.metadata.created_at,
+                content_hash: stored.metadata.checksum_sha256,
+                signature_valid: stored.payload.metadata.signature_valid,
+                size_bytes: stored.metadata.size_bytes,
+            },
+            raw_payload: None,
+            processing_history: None,
+        }))
+    }
+
+    async fn get_session_events(
+        &self,
+        _session_id: SessionId,
+    ) -> Result<Vec<StoredEvent>, ReplayError> {
+        Err(bulk_replay_unsupported())
+    }
+
+    async fn get_events_by_filter(
+        &self,
+        _filter: EventFilter,
+        _start_time: Timestamp,
+        _end_time: Timestamp,
+        _limit: Option<usize>,
+    ) -> Result<Vec<StoredEvent>, ReplayError> {
+        Err(bulk_replay_unsupported())
+    }
+
+    async fn get_repository_events(
+        &self,
+        _repository: &Repository,
+        _start_time: Timestamp,
+        _end_time: Timestamp,
+        _limit: Option<usize>,
+    ) -> Result<Vec<StoredEvent>, ReplayError> {
+        Err(bulk_replay_unsupported())
+    }
+
+    async fn count_events_by_filter(
+        &self,
+        _filter: &EventFilter,
+        _start_time: Timestamp,
+        _end_time: Timestamp,
+    ) -> Result<usize, ReplayError> {
+        Err(bulk_replay_unsupported())
+    }
+
+    async fn is_recent_duplicate(
+        &self,
+        _event_id: EventId,
+        _window: Duration,
+    ) -> Result<bool, ReplayError> {
+        // Replays are explicit operator actions; every request is honoured.
+        Ok(false)
+    }
+}
+
+// ============================================================================
+// Executor
+// ============================================================================
+
+/// Delivers replayed events through the routing and delivery pipeline.
+pub struct PipelineReplayExecutor {
+    event_router: Arc<dyn EventRouter>,
+    bot_config: Arc<SharedBotConfiguration>,
+    queue_client: Option<Arc<dyn QueueClient>>,
+    delivery_config: QueueDeliveryConfig,
+    event_storage: Option<Arc<dyn BlobStorage>>,
+    statistics: Option<Arc<StatisticsCollector>>,
+}
+
+impl PipelineReplayExecutor {
+    /// Create an executor delivering with the service's router, bot
+    /// configuration and queue client.
+    ///
+    /// Without a queue client replays can only be dry runs. With
+    /// `event_storage`, each replay event is stored like a received event,
+    /// so it appears in the event API and its lineage.
+    pub fn new(
+        event_router: Arc<dyn EventRouter>,
+        bot_config: Arc<SharedBotConfiguration>,
+        queue_client: Option<Arc<dyn QueueClient>>,
+        delivery_config: QueueDeliveryConfig,
+        event_storage: Option<Arc<dyn BlobStorage>>,
+    ) -> Self {
+        Self {
+            event_router,
+            bot_config,
+            queue_client,
+            delivery_config,
+            event_storage,
+            statistics: None,
+        }
+    }
+
+    /// Count delivered replays in the service statistics.
+    pub fn with_statistics(mut self, statistics: Arc<StatisticsCollector>) -> Self {
+        self.statistics = Some(statistics);
+        self
+    }
+
+    /// The bots `event` is routed to, restricted to `target_bots` unless it
+    /// is empty.
+    ///
+    /// # Errors
+    /// Returns [`ReplayError::InvalidRequest`] when a target bot is not
+    /// configured.
+    pub fn route(
+        &self,
+        event: &WrappedEvent,
+        target_bots: &[BotName],
+    ) -> Result<Vec<BotSubscription>, ReplayError> {
+        let bot_config = self.bot_config.current();
+        if let Some(unknown) = target_bots
+            .iter()
+            .find(|name| !bot_config.bots.iter().any(|bot| &bot.name == *name))
+        {
+            return Err(ReplayError::InvalidRequest {
+                reason: format!("Bot '{}' is not configured", unknown),
+            });
+        }
+        Ok(bot_config
+            .get_target_bots(event)
+            .into_iter()
+            .filter(|bot| target_bots.is_empty() || target_bots.contains(&bot.name))
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl ReplayExecutor for PipelineReplayExecutor {
+    async fn execute_replay(
+        &self,
+        replay_id: ReplayId,
+        events: Vec<StoredEvent>,
+        options: ReplayExecutionOptions,
+    ) -> Result<ReplayResults, ReplayError> {
+        let started = Instant::now();
+        let mut event_results = Vec::with_capacity(events.len());
+        for event in events {
+            let result = self.replay_single_event(event, options.clone()).await?;
+            let failed = matches!(result.result, ReplayOutcome::Failed { .. });
+            event_results.push(result);
+            if failed && options.fail_fast {
+                break;
+            }
+        }
+        Ok(replay_results(replay_id, event_results, started.elapsed()))
+    }
+
+    /// Replay one stored event.
+    ///
+    /// The replay is a new event derived from the stored one, and the
+    /// returned result carries its ID.
+    async fn replay_single_event(
+        &self,
+        event: StoredEvent,
+        options: ReplayExecutionOptions,
+    ) -> Result<EventReplayResult, ReplayError> {
+        let started = Instant::now();
+        let original_id = event.envelope.event_id;
+        let skipped = |reason: &str, routed_bots: Vec<BotName>| EventReplayResult {
+            event_id: original_id,
+            result: ReplayOutcome::Skipped {
+                reason: reason.to_string(),
+            },
+            routed_bots,
+            processing_time: started.elapsed(),
+            errors: Vec::new(),
+            was_duplicate: false,
+        };
+
+        if !event.is_replayable() {
+            return Ok(skipped("stored event is not replayable", Vec::new()));
+        }
+        let bots = self.route(&event.envelope, &options.target_bots)?;
+        if bots.is_empty() {
+            return Ok(skipped("no bot subscription matches the event", Vec::new()));
+        }
+        let Some(queue_client) = &self.queue_client else {
+            return Err(ReplayError::ServiceUnavailable {
+                service: "queue_delivery".to_string(),
+                message: "Queue delivery is not configured".to_string(),
+            });
+        };
+
+        let replay = event.envelope.derive(LineageCause::Replay);
+        let replay_id = replay.event_id;
+        if let Some(storage) = &self.event_storage {
+            if let Err(e) = store_wrapped_event_to_blob(storage.as_ref(), &replay).await {
+                warn!(event_id = %replay_id, error = %e, "Failed to store replay event");
+            }
+        }
+
+        if let Some(statistics) = &self.statistics {
+            statistics.record_event(&replay.event_type, replay.origin);
+        }
+
+        let routed_bots: Vec<BotName> = bots.iter().map(|bot| bot.name.clone()).collect();
+        let bot_config = Arc::new(BotConfiguration {
+            bots,
+            settings: self.bot_config.current().settings.clone(),
+        });
+        let outcome = deliver_event_to_queues(
+            replay,
+            self.event_router.clone(),
+            bot_config,
+            queue_client.clone(),
+            self.delivery_config.clone(),
+        )
+        .await;
+
+        let result = match outcome {
+            QueueDeliveryOutcome::AllQueuesSucceeded { .. }
+            | QueueDeliveryOutcome::NoTargetQueues { .. } => ReplayOutcome::Success,
+            QueueDeliveryOutcome::SomeQueuesFailed {
+                successful_count,
+                failed_count,
+                ..
+            } => ReplayOutcome::Failed {
+                error: format!(
+                    "{} of {} bot queues failed",
+                    failed_count,
+                    successful_count + failed_count
+                ),
+            },
+            QueueDeliveryOutcome::CompleteFailure { error, .. } => ReplayOutcome::Failed { error },
+            QueueDeliveryOutcome::CancelledBySessionReset { .. } => ReplayOutcome::Skipped {
+                reason: "session was reset during delivery".to_string(),
+            },
+            QueueDeliveryOutcome::BufferedForPausedSession { .. } => ReplayOutcome::Skipped {
+                reason: "buffered until the paused session resumes".to_string(),
+            },
+        };
+        let errors = match &result {
+            ReplayOutcome::Failed { error } => vec![error.clone()],
+            _ => Vec::new(),
+        };
+        Ok(EventReplayResult {
+            event_id: replay_id,
+            result,
+            routed_bots,
+            processing_time: started.elapsed(),
+            errors,
+            was_duplicate: false,
+        })
+    }
+
+    async fn validate_events(
+        &self,
+        events: &[StoredEvent],
+        options: &ReplayExecutionOptions,
+    ) -> Result<ReplayValidationResult, ReplayError> {
+        let mut validation_errors = Vec::new();
+        for event in events {
+            let event_id = event.envelope.event_id;
+            if !event.is_replayable() {
+                validation_errors.push(format!("{}: stored event is not replayable", event_id));
+            } else if self
+                .route(&event.envelope, &options.target_bots)?
+                .is_empty()
+            {
+                validation_errors.push(format!(
+                    "{}: no bot subscription matches the event",
+                    event_id
+                ));
+            }
+        }
+        Ok(ReplayValidationResult {
+            total_events: events.len(),
+            valid_events: events.len() - validation_errors.len(),
+            invalid_events: validation_errors.len(),
+            validation_errors,
+            estimated_processing_time: Duration::ZERO,
+        })
+    }
+
+    async fn get_execution_capacity(&self) -> Result<ExecutionCapacity, ReplayError> {
+        Ok(ExecutionCapacity {
+            available_threads: 1,
+            max_events_per_second: 0.0,
+            current_load: 0.0,
+            can_accept_new: self.queue_client.is_some(),
+        })
+    }
+}
+
+/// Results of a replay from its per-event results.
+fn replay_results(
+    replay_id: ReplayId,
+    event_results: Vec<EventReplayResult>,
+    duration: Duration,
+) -> ReplayResults {
+    let mut events_by_bot: HashMap<BotName, usize> = HashMap::new();
+    for result in &event_results {
+        for bot in &result.routed_bots {
+            *events_by_bot.entry(bot.clone()).or_default() += 1;
+        }
+    }
+    let final_status = if event_results
+        .iter()
+        .any(|r| matches!(r.result, ReplayOutcome::Failed { .. }))
+    {
+        ReplayState::Failed
+    } else {
+        ReplayState::Completed
+    };
+    let statistics = ReplayStatistics {
+        total_duration: duration,
+        replay_duration: duration,
+        avg_event_processing_time: duration
+            .checked_div(event_results.len() as u32)
+            .unwrap_or_default(),
+        events_by_bot: events_by_bot.clone(),
+        ..ReplayStatistics::default()
+    };
+    let config = ReplayConfiguration::default();
+
+    ReplayResults {
+        replay_id,
+        final_status,
+        statistics,
+        routing_summary: RoutingSummary {
+            events_by_bot,
+            unrouted_events: event_results
+                .iter()
+                .filter(|r| r.routed_bots.is_empty())
+                .count(),
+            routing_errors: event_results
+                .iter()
+                .flat_map(|r| r.errors.iter().cloned())
+                .collect(),
+            new_bot_matches: HashMap::new(),
+        },
+        event_results,
+        errors: Vec::new(),
+        execution_metadata: ExecutionMetadata {
+            executor_instance: "pipeline".to_string(),
+            execution_strategy: ExecutionStrategy {
+                batch_size: 1,
+                concurrency_level: 1,
+                ordering_strategy: "sequential".to_string(),
+                retry_strategy: "queue_delivery".to_string(),
+            },
+            replay_config: config,
+            resource_usage: ResourceUsage {
+                peak_memory_bytes: 0,
+                cpu_time: Duration::ZERO,
+                network_bytes: 0,
+                storage_operations: 0,
+            },
+        },
+    }
+}
+
+// ============================================================================
+// Service
+// ============================================================================
+
+/// A finished replay kept for the status API.
+struct ReplayRecord {
+    request: ReplayRequest,
+    status: ReplayStatus,
+    results: ReplayResults,
+}
+
+/// Replays single stored events through the delivery pipeline.
+///
+/// Replays run to completion inside [`EventReplayService::submit_replay`];
+/// their status and results are available afterwards by replay ID.
+pub struct PipelineReplayService {
+    retriever: Option<Arc<dyn EventRetriever>>,
+    executor: Arc<PipelineReplayExecutor>,
+    replays: Mutex<VecDeque<ReplayRecord>>,
+}
+
+impl PipelineReplayService {
+    /// Create a replay service.
+    ///
+    /// Without a retriever (no event storage configured) every replay fails
+    /// with [`ReplayError::ServiceUnavailable`].
+    pub fn new(
+        retriever: Option<Arc<dyn EventRetriever>>,
+        executor: Arc<PipelineReplayExecutor>,
+    ) -> Self {
+        Self {
+            retriever,
+            executor,
+            replays: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Load the stored event a single-event request replays.
+    async fn load_event(&self, request: &ReplayRequest) -> Result<StoredEvent, ReplayError> {
+        let ReplayType::SingleEvent { event_id } = request.replay_type else {
+            return Err(bulk_replay_unsupported());
+        };
+        let Some(retriever) = &self.retriever else {
+            return Err(ReplayError::ServiceUnavailable {
+                service: "event_storage".to_string(),
+                message: "Event storage is not configured".to_string(),
+            });
+        };
+        retriever
+            .get_event(event_id)
+            .await?
+            .ok_or(ReplayError::EventNotFound { event_id })
+    }
+
+    /// Keep a finished replay, dropping the oldest beyond
+    /// [`MAX_RETAINED_REPLAYS`].
+    fn retain(&self, record: ReplayRecord) {
+        let mut replays = self.replays.lock().unwrap();
+        replays.push_back(record);
+        while replays.len() > MAX_RETAINED_REPLAYS {
+            replays.pop_front();
+        }
+    }
+
+    fn find<T>(
+        &self,
+        replay_id: ReplayId,
+        read: impl FnOnce(&ReplayRecord) -> T,
+    ) -> Result<T, ReplayError> {
+        self.replays
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|record| record.status.replay_id == replay_id)
+            .map(read)
+            .ok_or(ReplayError::ReplayNotFound { replay_id })
+    }
+}
+
+#[async_trait]
+impl EventReplayService for PipelineReplayService {
+    /// Run a single-event replay.
+    ///
+    /// A dry run routes the event without delivering it; its result is
+    /// [`ReplayOutcome::Skipped`] with the bots the event would reach.
+    async fn submit_replay(&self, request: ReplayRequest) -> Result<ReplayId, ReplayError> {
+        request.validate()?;
+        let started_at = Timestamp::now();
+        let started = Instant::now();
+        let event = self.load_event(&request).await?;
+        let load_duration = started.elapsed();
+        let original_id = event.envelope.event_id;
+
+        let result = if request.dry_run {
+            let routed_bots = self
+                .executor
+                .route(&event.envelope, &request.target_bots)?
+                .into_iter()
+                .map(|bot| bot.name)
+                .collect();
+            EventReplayResult {
+                event_id: original_id,
+                result: ReplayOutcome::Skipped {
+                    reason: DRY_RUN_REASON.to_string(),
+                },
+                routed_bots,
+                processing_time: started.elapsed(),
+                errors: Vec::new(),
+                was_duplicate: false,
+            }
+        } else {
+            let options = ReplayExecutionOptions {
+                target_bots: request.target_bots.clone(),
+                ..ReplayExecutionOptions::default()
+            };
+            self.executor.replay_single_event(event, options).await?
+        };
+
+        let mut results = replay_results(request.replay_id, vec![result], started.elapsed());
+        results.statistics.load_duration = load_duration;
+        let outcome = &results.event_results[0].result;
+        let status = ReplayStatus {
+            replay_id: request.replay_id,
+            state: results.final_status.clone(),
+            progress: ReplayProgress {
+                total_events: 1,
+                events_completed: usize::from(*outcome == ReplayOutcome::Success),
+                events_failed: usize::from(matches!(outcome, ReplayOutcome::Failed { .. })),
+                events_skipped: usize::from(matches!(outcome, ReplayOutcome::Skipped { .. })),
+                ..ReplayProgress::default()
+            },
+            errors: Vec::new(),
+            started_at,
+            completed_at: Some(Timestamp::now()),
+            statistics: results.statistics.clone(),
+        };
+
+        info!(
+            replay_id = %request.replay_id,
+            event_id = %original_id,
+            replay_event_id = %results.event_results[0].event_id,
+            requester = %request.requester,
+            dry_run = request.dry_run,
+            state = ?status.state,
+            "Event replay finished"
+        );
+
+        let replay_id = request.replay_id;
+        self.retain(ReplayRecord {
+            request,
+            status,
+            results,
+        });
+        Ok(replay_id)
+    }
+
+    async fn get_replay_status(&self, replay_id: ReplayId) -> Result<ReplayStatus, ReplayError> {
+        self.find(replay_id, |record| record.status.clone())
+    }
+
+    async fn list_replays(
+        &self,
+        filter: Option<ReplayListFilter>,
+    ) -> Result<Vec<ReplayStatus>, ReplayError> {
+        let replays = self.replays.lock().unwrap();
+        let mut statuses: Vec<ReplayStatus> = replays
+            .iter()
+            .rev()
+            .filter(|record| {
+                let Some(filter) = &filter else {
+                    return true;
+                };
+                filter
+                    .state
+                    .as_ref()
+                    .is_none_or(|state| *state == record.status.state)
+                    && filter
+                        .requester
+                        .as_ref()
+                        .is_none_or(|requester| *requester == record.request.requester)
+                    && filter
+                        .started_after
+                        .is_none_or(|after| record.status.started_at > after)
+                    && filter
+                        .started_before
+                        .is_none_or(|before| record.status.started_at < before)
+            })
+            .map(|record| record.status.clone())
+            .collect();
+        if let Some(limit) = filter.and_then(|f| f.limit) {
+            statuses.truncate(limit);
+        }
+        Ok(statuses)
+    }
+
+    async fn cancel_replay(
+        &self,
+        replay_id: ReplayId,
+        _requester: String,
+    ) -> Result<(), ReplayError> {
+        self.find(replay_id, |_| ())?;
+        Err(ReplayError::InvalidRequest {
+            reason: format!("Replay {} has already finished", replay_id),
+        })
+    }
+
+    async fn get_replay_results(&self, replay_id: ReplayId) -> Result<ReplayResults, ReplayError> {
+        self.find(replay_id, |record| record.results.clone())
+    }
+
+    async fn validate_replay_request(
+        &self,
+        request: &ReplayRequest,
+    ) -> Result<ReplayEstimate, ReplayError> {
+        request.validate()?;
+        let event = self.load_event(request).await?;
+        let options = ReplayExecutionOptions {
+            target_bots: request.target_bots.clone(),
+            ..ReplayExecutionOptions::default()
+        };
+        let validation = self
+            .executor
+            .validate_events(std::slice::from_ref(&event), &options)
+            .await?;
+        Ok(ReplayEstimate {
+            estimated_events: validation.valid_events,
+            estimated_duration: validation.estimated_processing_time,
+            estimated_resources: ResourceEstimate {
+                memory_mb: 0,
+                network_mb: event.storage_metadata.size_bytes / (1024 * 1024),
+                storage_operations: 1,
+            },
+            warnings: validation.validation_errors,
+        })
+    }
+
+    async fn get_service_status(&self) -> Result<ReplayServiceStatus, ReplayError> {
+        Ok(ReplayServiceStatus {
+            active_replays: 0,
+            max_replays: ReplayConfiguration::default().max_concurrent_replays,
+            healthy: self.retriever.is_some(),
+            capacity_utilization: 0.0,
+            pending_requests: 0,
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "event_replay_tests.rs"]
+mod tests;
@@ -0,0 +1,163 @@
+//! Tests for event replay.
+
+use super::*;
+use crate::test_fixtures::{event, subscription};
+use queue_keeper_core::{
+    adapters::filesystem_storage::FilesystemBlobStorage,
+    bot_config::{BotConfigurationSettings, EventTypePattern},
+    queue_integration::DefaultEventRouter,
+};
+
+fn bot(name: &str, event_type: &str) -> BotSubscription {
+    subscription(name, EventTypePattern::Exact(event_type.to_string()))
+}
+
+fn executor() -> PipelineReplayExecutor {
+    PipelineReplayExecutor::new(
+        Arc::new(DefaultEventRouter::new()),
+        Arc::new(SharedBotConfiguration::new(BotConfiguration {
+            bots: vec![
+                bot("alpha", "pull_request"),
+                bot("beta", "pull_request"),
+                bot("gamma", "push"),
+            ],
+            settings: BotConfigurationSettings::default(),
+        })),
+        None,
+        QueueDeliveryConfig::default(),
+        None,
+    )
+}
+
+/// Store `event` in a fresh filesystem blob store.
+async fn storage_with(test_name: &str, event: &WrappedEvent) -> Arc<dyn BlobStorage> {
+    let path = std::env::temp_dir().join(format!("qk-event-replay-test-{}", test_name));
+    let _ = std::fs::remove_dir_all(&path);
+    let storage: Arc<dyn BlobStorage> = Arc::new(FilesystemBlobStorage::new(path).await.unwrap());
+    store_wrapped_event_to_blob(storage.as_ref(), event)
+        .await
+        .unwrap();
+    storage
+}
+
+fn service(storage: Option<Arc<dyn BlobStorage>>) -> PipelineReplayService {
+    PipelineReplayService::new(
+        storage.map(|s| Arc::new(BlobEventRetriever::new(s)) as Arc<dyn EventRetriever>),
+        Arc::new(executor()),
+    )
+}
+
+fn request(event_id: EventId, dry_run: bool) -> ReplayRequest {
+    let mut request = ReplayRequest::single_event(event_id, "ops".to_string(), "test".to_string());
+    request.dry_run = dry_run;
+    request
+}
+
+/// Verify that stored envelopes are read back for replay.
+#[tokio::test]
+async fn test_retriever_reads_stored_envelope() {
+    let event = event();
+    let retriever = BlobEventRetriever::new(storage_with("retrieve", &event).await);
+
+    let stored = retriever.get_event(event.event_id).await.unwrap().unwrap();
+    assert_eq!(stored.envelope.event_id, event.event_id);
+    assert!(stored.is_replayable());
+    assert!(retriever.get_event(EventId::new()).await.unwrap().is_none());
+}
+
+/// Verify that routing honours subscriptions and the target bot filter.
+#[test]
+fn test_route_restricts_to_target_bots() {
+    let executor = executor();
+    let names = |bots: Vec<BotSubscription>| {
+        bots.into_iter()
+            .map(|b| b.name.as_str().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        names(executor.route(&event(), &[]).unwrap()),
+        ["alpha", "beta"]
+    );
+    assert_eq!(
+        names(
+            executor
+                .route(&event(), &[BotName::new("beta").unwrap()])
+                .unwrap()
+        ),
+        ["beta"]
+    );
+    assert!(executor
+        .route(&event(), &[BotName::new("gamma").unwrap()])
+        .unwrap()
+        .is_empty());
+    assert!(matches!(
+        executor.route(&event(), &[BotName::new("missing").unwrap()]),
+        Err(ReplayError::InvalidRequest { .. })
+    ));
+}
+
+/// Verify that a dry run reports the routing decision and delivers nothing.
+#[tokio::test]
+async fn test_dry_run_reports_routes() {
+    let event = event();
+    let service = service(Some(storage_with("dry-run", &event).await));
+
+    let replay_id = service
+        .submit_replay(request(event.event_id, true))
+        .await
+        .unwrap();
+
+    let results = service.get_replay_results(replay_id).await.unwrap();
+    let result = &results.event_results[0];
+    assert_eq!(result.event_id, event.event_id);
+    assert_eq!(
+        result.result,
+        ReplayOutcome::Skipped {
+            reason: DRY_RUN_REASON.to_string()
+        }
+    );
+    assert_eq!(result.routed_bots.len(), 2);
+    let status = service.get_replay_status(replay_id).await.unwrap();
+    assert_eq!(status.state, ReplayState::Completed);
+    assert_eq!(status.progress.events_skipped, 1);
+    assert_eq!(service.list_replays(None).await.unwrap().len(), 1);
+}
+
+/// Verify the errors of replays that cannot run.
+#[tokio::test]
+async fn test_replay_errors() {
+    let event = event();
+
+    let without_storage = service(None);
+    assert!(matches!(
+        without_storage
+            .submit_replay(request(event.event_id, true))
+            .await,
+        Err(ReplayError::ServiceUnavailable { .. })
+    ));
+
+    let service = service(Some(storage_with("errors", &event).await));
+    assert!(matches!(
+        service.submit_replay(request(EventId::new(), true)).await,
+        Err(ReplayError::EventNotFound { .. })
+    ));
+    // Delivery needs a queue client
+    assert!(matches!(
+        service.submit_replay(request(event.event_id, false)).await,
+        Err(ReplayError::ServiceUnavailable { .. })
+    ));
+    let session = ReplayRequest::session(
+        SessionId::from_parts("owner", "repo", "pull_request", "1"),
+        "ops".to_string(),
+        "test".to_string(),
+    );
+    assert!(matches!(
+        service.submit_replay(session).await,
+        Err(ReplayError::InvalidRequest { .. })
+    ));
+    assert!(matches!(
+        service.get_replay_status(ReplayId::new()).await,
+        Err(ReplayError::ReplayNotFound { .. })
+    ));
+}
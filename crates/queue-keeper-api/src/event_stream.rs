@@ -0,0 +1,266 @@
+//! Live stream of processed events.
+//!
+//! The service broadcasts a [`StreamedEvent`] summary of every event it
+//! accepts, of every webhook it rejects and of every delivery to a bot
+//! queue to the clients of `GET /api/events/stream`. The route serves
+//! server-sent events: each summary is an `event` message with a JSON body.
+//! Nothing is stored, so a client only sees what happens while it is
+//! connected.
+//!
+//! Clients share a buffer of `buffer_size` summaries. A client that falls
+//! further behind skips the oldest and receives a `lagged` message carrying
+//! the number it missed. Publishing never waits for a client, and builds no
+//! summary while nobody is connected.
+
+use axum::response::sse::{Event, KeepAlive, Sse};
+use queue_keeper_core::{
+    queue_integration::DeliveryResult, units::human_duration, webhook::WrappedEvent, EventId,
+    SessionId, Timestamp,
+};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    Stream, StreamExt,
+};
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Live event stream settings (the `event_stream` configuration section).
+///
+/// ```yaml
+/// event_stream:
+///   enabled: true
+///   buffer_size: 1024
+///   keep_alive_interval: 15s
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventStreamConfig {
+    /// Whether `GET /api/events/stream` is served.
+    pub enabled: bool,
+
+    /// Summaries buffered for a client before it starts skipping them.
+    pub buffer_size: usize,
+
+    /// Time between two keep-alive comments on an idle stream, so proxies
+    /// do not close it.
+    #[serde(with = "human_duration")]
+    pub keep_alive_interval: Duration,
+}
+
+impl Default for EventStreamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            buffer_size: 1024,
+            keep_alive_interval: Duration::from_secs(15),
+        }
+    }
+}
+
+impl EventStreamConfig {
+    /// Validate the settings.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.buffer_size == 0 || self.buffer_size > 65_536 {
+            return Err("event_stream.buffer_size must be between 1 and 65536".to_string());
+        }
+        if self.keep_alive_interval < Duration::from_secs(1) {
+            return Err("event_stream.keep_alive_interval must be at least 1s".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Streamed Events
+// ============================================================================
+
+/// What happened to a streamed event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamedEventStatus {
+    /// The webhook was accepted and its delivery started.
+    Received,
+
+    /// The webhook failed processing and was answered with an error.
+    Rejected,
+
+    /// A bot queue accepted the event.
+    Delivered,
+
+    /// Sending the event to a bot queue failed; a transient failure may
+    /// still be retried.
+    DeliveryFailed,
+}
+
+impl StreamedEventStatus {
+    /// The status as serialized, e.g. `delivery_failed`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Received => "received",
+            Self::Rejected => "rejected",
+            Self::Delivered => "delivered",
+            Self::DeliveryFailed => "delivery_failed",
+        }
+    }
+
+    /// Whether the status reports a failure.
+    pub fn is_error(&self) -> bool {
+        matches!(self, Self::Rejected | Self::DeliveryFailed)
+    }
+}
+
+/// Summary of an event sent to the clients of `GET /api/events/stream`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreamedEvent {
+    /// `None` for rejected webhooks, which never became events.
+    pub event_id: Option<EventId>,
+    pub provider: String,
+    pub event_type: String,
+    pub action: Option<String>,
+    /// Repository full name (`owner/repo`), when the payload names one.
+    pub repository: Option<String>,
+    pub session_id: Option<SessionId>,
+    pub status: StreamedEventStatus,
+    /// Bot of a `delivered` or `delivery_failed` summary.
+    pub bot_name: Option<String>,
+    /// Queue of a `delivered` or `delivery_failed` summary.
+    pub queue_name: Option<String>,
+    /// Why the webhook was rejected or the delivery failed.
+    pub message: Option<String>,
+    pub timestamp: Timestamp,
+}
+
+impl StreamedEvent {
+    fn new(event: &WrappedEvent, status: StreamedEventStatus) -> Self {
+        Self {
+            event_id: Some(event.event_id),
+            provider: event.provider.clone(),
+            event_type: event.event_type.clone(),
+            action: event.action.clone(),
+            repository: event
+                .payload
+                .pointer("/repository/full_name")
+                .and_then(|name| name.as_str())
+                .map(str::to_string),
+            session_id: event.session_id.clone(),
+            status,
+            bot_name: None,
+            queue_name: None,
+            message: None,
+            timestamp: Timestamp::now(),
+        }
+    }
+}
+
+// ============================================================================
+// Broadcaster
+// ============================================================================
+
+/// Broadcasts event summaries to the clients of `GET /api/events/stream`.
+#[derive(Debug)]
+pub struct EventStream {
+    sender: broadcast::Sender<StreamedEvent>,
+    keep_alive_interval: Duration,
+}
+
+impl EventStream {
+    pub fn new(config: &EventStreamConfig) -> Self {
+        let (sender, _) = broadcast::channel(config.buffer_size.max(1));
+        Self {
+            sender,
+            keep_alive_interval: config.keep_alive_interval,
+        }
+    }
+
+    /// Receive every summary published from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<StreamedEvent> {
+        self.sender.subscribe()
+    }
+
+    /// Number of connected clients.
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Publish a summary to the connected clients, if any.
+    pub fn publish(&self, event: StreamedEvent) {
+        // Sending only fails when nobody is connected.
+        let _ = self.sender.send(event);
+    }
+
+    /// Publish that `event` was accepted.
+    pub fn publish_received(&self, event: &WrappedEvent) {
+        if self.subscribers() > 0 {
+            self.publish(StreamedEvent::new(event, StreamedEventStatus::Received));
+        }
+    }
+
+    /// Publish the outcome of one routing attempt of `event`, one summary
+    /// per bot queue.
+    pub fn publish_delivery(&self, event: &WrappedEvent, result: &DeliveryResult) {
+        if self.subscribers() == 0 {
+            return;
+        }
+        for delivery in &result.successful {
+            self.publish(StreamedEvent {
+                bot_name: Some(delivery.bot_name.to_string()),
+                queue_name: Some(delivery.queue_name.to_string()),
+                ..StreamedEvent::new(event, StreamedEventStatus::Delivered)
+            });
+        }
+        for failure in &result.failed {
+            self.publish(StreamedEvent {
+                bot_name: Some(failure.bot_name.to_string()),
+                queue_name: Some(failure.queue_name.to_string()),
+                message: Some(failure.error.clone()),
+                ..StreamedEvent::new(event, StreamedEventStatus::DeliveryFailed)
+            });
+        }
+    }
+
+    /// Publish that a webhook of `event_type` failed processing.
+    pub fn publish_rejected(
+        &self,
+        provider: &str,
+        event_type: &str,
+        repository: Option<String>,
+        reason: &str,
+    ) {
+        if self.subscribers() > 0 {
+            self.publish(StreamedEvent {
+                event_id: None,
+                provider: provider.to_string(),
+                event_type: event_type.to_string(),
+                action: None,
+                repository,
+                session_id: None,
+                status: StreamedEventStatus::Rejected,
+                bot_name: None,
+                queue_name: None,
+                message: Some(reason.to_string()),
+                timestamp: Timestamp::now(),
+            });
+        }
+    }
+
+    /// Server-sent events response streaming every summary published from
+    /// now on.
+    pub fn sse(&self) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+        let stream = BroadcastStream::new(self.subscribe()).map(|received| match received {
+            Ok(event) => Event::default().event("event").json_data(event),
+            Err(BroadcastStreamRecvError::Lagged(missed)) => {
+                Ok(Event::default().event("lagged").data(missed.to_string()))
+            }
+        });
+        Sse::new(stream).keep_alive(KeepAlive::new().interval(self.keep_alive_interval))
+    }
+}
+
+#[cfg(test)]
+#[path = "event_stream_tests.rs"]
+mod tests;
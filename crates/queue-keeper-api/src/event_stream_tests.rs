@@ -0,0 +1,106 @@
+//! Tests for the live event stream.
+
+use super::*;
+use crate::test_fixtures::{delivered, event_with_payload, failed};
+
+/// An event of the `owner/repo` repository.
+fn repository_event() -> WrappedEvent {
+    event_with_payload(serde_json::json!({ "repository": { "full_name": "owner/repo" } }))
+}
+
+/// The defaults validate; out of range settings do not.
+#[test]
+fn test_config_validation() {
+    assert!(EventStreamConfig::default().validate().is_ok());
+
+    let config = EventStreamConfig {
+        buffer_size: 0,
+        ..EventStreamConfig::default()
+    };
+    assert!(config.validate().unwrap_err().contains("buffer_size"));
+
+    let config = EventStreamConfig {
+        keep_alive_interval: Duration::from_millis(100),
+        ..EventStreamConfig::default()
+    };
+    assert!(config
+        .validate()
+        .unwrap_err()
+        .contains("keep_alive_interval"));
+}
+
+/// A received event is summarised with its repository.
+#[test]
+fn test_publish_received() {
+    let stream = EventStream::new(&EventStreamConfig::default());
+    let mut receiver = stream.subscribe();
+    let event = repository_event();
+
+    stream.publish_received(&event);
+
+    let streamed = receiver.try_recv().unwrap();
+    assert_eq!(streamed.event_id, Some(event.event_id));
+    assert_eq!(streamed.event_type, "pull_request");
+    assert_eq!(streamed.repository.as_deref(), Some("owner/repo"));
+    assert_eq!(streamed.status, StreamedEventStatus::Received);
+    assert!(!streamed.status.is_error());
+}
+
+/// A routing attempt publishes one summary per bot queue.
+#[test]
+fn test_publish_delivery() {
+    let stream = EventStream::new(&EventStreamConfig::default());
+    let mut receiver = stream.subscribe();
+    let event = repository_event();
+    let result = DeliveryResult {
+        event_id: event.event_id,
+        successful: vec![delivered("labeler")],
+        failed: vec![failed("assigner", true)],
+    };
+
+    stream.publish_delivery(&event, &result);
+
+    let delivered = receiver.try_recv().unwrap();
+    assert_eq!(delivered.status, StreamedEventStatus::Delivered);
+    assert_eq!(delivered.bot_name.as_deref(), Some("labeler"));
+    let failed = receiver.try_recv().unwrap();
+    assert_eq!(failed.status, StreamedEventStatus::DeliveryFailed);
+    assert_eq!(failed.queue_name.as_deref(), Some("queue-keeper-assigner"));
+    assert_eq!(failed.message.as_deref(), Some("queue unavailable"));
+    assert!(failed.status.is_error());
+    assert!(receiver.try_recv().is_err());
+}
+
+/// Rejections carry no event ID, and publishing without clients is a no-op.
+#[test]
+fn test_publish_rejected() {
+    let stream = EventStream::new(&EventStreamConfig::default());
+    stream.publish_rejected("github", "issues", None, "invalid signature");
+    assert_eq!(stream.subscribers(), 0);
+
+    let mut receiver = stream.subscribe();
+    stream.publish_rejected(
+        "github",
+        "issues",
+        Some("owner/repo".to_string()),
+        "invalid signature",
+    );
+
+    let streamed = receiver.try_recv().unwrap();
+    assert_eq!(streamed.event_id, None);
+    assert_eq!(streamed.status, StreamedEventStatus::Rejected);
+    assert_eq!(streamed.message.as_deref(), Some("invalid signature"));
+    assert!(receiver.try_recv().is_err());
+}
+
+/// Summaries round-trip through their JSON form, statuses in snake case.
+#[test]
+fn test_streamed_event_json() {
+    let streamed = StreamedEvent::new(&repository_event(), StreamedEventStatus::DeliveryFailed);
+    let json = serde_json::to_value(&streamed).unwrap();
+    assert_eq!(json["status"], "delivery_failed");
+    assert_eq!(json["status"], StreamedEventStatus::DeliveryFailed.as_str());
+
+    let parsed: StreamedEvent = serde_json::from_value(json).unwrap();
+    assert_eq!(parsed, streamed);
+}
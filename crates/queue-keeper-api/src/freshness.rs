@@ -0,0 +1,354 @@
+//! Freshness monitoring for bot configuration and cached secrets.
+//!
+//! Queue-Keeper keeps working on stale inputs: a bot configuration that
+//! failed to reload, or a secret cache that keeps serving a value the vault
+//! has since rotated. Neither fails a request, so neither shows up in error
+//! rates. The [`FreshnessMonitor`] reports how old each input is:
+//!
+//! - `bot_config_age_seconds`: time since the bot configuration was loaded
+//!   or last reloaded.
+//! - `secret_cache_age_seconds{secret}`: time since each cached secret was
+//!   fetched from the Key Vault.
+//!
+//! When an age exceeds its threshold (`freshness.bot_config_max_age_seconds`,
+//! `freshness.secret_max_age_seconds`) the monitor logs a warning and
+//! `GET /health/deep` reports the check with `"warning": true`. Stale inputs
+//! are warnings, not failures: the service stays healthy and ready.
+
+use crate::{metrics::ServiceMetrics, responses::HealthCheckResult};
+use queue_keeper_core::{key_vault::KeyVaultProvider, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
+};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Name under which the bot configuration is reported.
+pub const BOT_CONFIG_ITEM: &str = "bot_config";
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Freshness thresholds (the `freshness` configuration section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// freshness:
+///   check_interval_seconds: 60
+///   bot_config_max_age_seconds: 86400
+///   secret_max_age_seconds: 3600
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FreshnessConfig {
+    /// How often ages are recomputed for the gauges and warnings.
+    #[serde(default = "FreshnessConfig::default_check_interval_seconds")]
+    pub check_interval_seconds: u64,
+
+    /// Age after which the bot configuration is reported stale. `None`
+    /// (the default) never warns, which suits configurations that are only
+    /// loaded at startup.
+    #[serde(default)]
+    pub bot_config_max_age_seconds: Option<u64>,
+
+    /// Age after which a cached secret is reported stale; set it to the
+    /// secret rotation period. Defaults to one hour. `None` never warns.
+    #[serde(default = "FreshnessConfig::default_secret_max_age_seconds")]
+    pub secret_max_age_seconds: Option<u64>,
+}
+
+impl FreshnessConfig {
+    fn default_check_interval_seconds() -> u64 {
+        60
+    }
+
+    fn default_secret_max_age_seconds() -> Option<u64> {
+        Some(3600)
+    }
+
+    /// Validate the thresholds.
+    ///
+    /// # Errors
+    /// Returns a description of the first zero interval or threshold.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.check_interval_seconds == 0 {
+            return Err("freshness.check_interval_seconds must be at least 1".to_string());
+        }
+        if self.bot_config_max_age_seconds == Some(0) {
+            return Err("freshness.bot_config_max_age_seconds must be at least 1".to_string());
+        }
+        if self.secret_max_age_seconds == Some(0) {
+            return Err("freshness.secret_max_age_seconds must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Default for FreshnessConfig {
+    fn default() -> Self {
+        Self {
+            check_interval_seconds: Self::default_check_interval_seconds(),
+            bot_config_max_age_seconds: None,
+            secret_max_age_seconds: Self::default_secret_max_age_seconds(),
+        }
+    }
+}
+
+// ============================================================================
+// Reports
+// ============================================================================
+
+/// Age of one monitored input.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FreshnessItem {
+    /// `bot_config`, or the secret name.
+    pub name: String,
+
+    /// When the input was loaded or fetched.
+    pub refreshed_at: Timestamp,
+
+    pub age_seconds: u64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age_seconds: Option<u64>,
+
+    /// The age exceeds `max_age_seconds`.
+    pub stale: bool,
+}
+
+impl FreshnessItem {
+    fn new(name: String, refreshed_at: Timestamp, now: Timestamp, max_age: Option<u64>) -> Self {
+        let age_seconds = now.duration_since(refreshed_at).as_secs();
+        Self {
+            name,
+            refreshed_at,
+            age_seconds,
+            max_age_seconds: max_age,
+            stale: max_age.is_some_and(|max| age_seconds > max),
+        }
+    }
+}
+
+/// Ages of the bot configuration and every cached secret.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FreshnessReport {
+    pub bot_config: FreshnessItem,
+
+    /// Cached secrets, ordered by name. Empty without a Key Vault.
+    pub secrets: Vec<FreshnessItem>,
+
+    /// Why secret ages could not be read, if they could not.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secrets_error: Option<String>,
+}
+
+impl FreshnessReport {
+    /// Inputs whose age exceeds their threshold.
+    pub fn stale(&self) -> impl Iterator<Item = &FreshnessItem> {
+        std::iter::once(&self.bot_config)
+            .chain(self.secrets.iter())
+            .filter(|item| item.stale)
+    }
+
+    /// Warning-level health checks: `bot_config_freshness` and
+    /// `secret_freshness`.
+    pub fn health_checks(&self) -> Vec<(String, HealthCheckResult)> {
+        let bot_config = HealthCheckResult {
+            healthy: true,
+            warning: self.bot_config.stale,
+            message: format!(
+                "Bot configuration loaded {}s ago{}",
+                self.bot_config.age_seconds,
+                if self.bot_config.stale {
+                    " (stale)"
+                } else {
+                    ""
+                }
+            ),
+            duration_ms: 0,
+        };
+
+        let stale: Vec<&str> = self
+            .secrets
+            .iter()
+            .filter(|s| s.stale)
+            .map(|s| s.name.as_str())
+            .collect();
+        let secrets = HealthCheckResult {
+            healthy: true,
+            warning: !stale.is_empty() || self.secrets_error.is_some(),
+            message: match (&self.secrets_error, stale.is_empty()) {
+                (Some(error), _) => format!("Secret cache ages unavailable: {}", error),
+                (None, true) => format!("{} cached secret(s) within age limit", self.secrets.len()),
+                (None, false) => format!("Stale cached secret(s): {}", stale.join(", ")),
+            },
+            duration_ms: 0,
+        };
+
+        vec![
+            ("bot_config_freshness".to_string(), bot_config),
+            ("secret_freshness".to_string(), secrets),
+        ]
+    }
+}
+
+// ============================================================================
+// Monitor
+// ============================================================================
+
+/// Tracks when the bot configuration was loaded and reads secret ages from
+/// the Key Vault cache.
+///
+/// Shared by the health checker, which reports freshness in
+/// `GET /health/deep`, and the background task started by
+/// [`FreshnessMonitor::start`], which exports the gauges.
+pub struct FreshnessMonitor {
+    config: FreshnessConfig,
+    bot_config_loaded_at: RwLock<Timestamp>,
+    key_vault: Option<Arc<dyn KeyVaultProvider>>,
+    /// Items reported stale by the last check, to warn on transitions.
+    stale: Mutex<HashSet<String>>,
+}
+
+impl std::fmt::Debug for FreshnessMonitor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FreshnessMonitor")
+            .field("config", &self.config)
+            .field("bot_config_loaded_at", &self.bot_config_loaded_at())
+            .field("key_vault", &self.key_vault.is_some())
+            .finish()
+    }
+}
+
+impl FreshnessMonitor {
+    /// Create a monitor; the bot configuration counts as loaded now.
+    pub fn new(config: FreshnessConfig) -> Self {
+        Self {
+            config,
+            bot_config_loaded_at: RwLock::new(Timestamp::now()),
+            key_vault: None,
+            stale: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Report the ages of secrets cached by `key_vault`.
+    pub fn with_key_vault(mut self, key_vault: Arc<dyn KeyVaultProvider>) -> Self {
+        self.key_vault = Some(key_vault);
+        self
+    }
+
+    /// Record that the bot configuration was (re)loaded now.
+    pub fn record_bot_config_loaded(&self) {
+        self.record_bot_config_loaded_at(Timestamp::now());
+    }
+
+    /// Record that the bot configuration was (re)loaded at `loaded_at`.
+    pub fn record_bot_config_loaded_at(&self, loaded_at: Timestamp) {
+        *self.bot_config_loaded_at.write().unwrap() = loaded_at;
+    }
+
+    /// When the bot configuration was last loaded.
+    pub fn bot_config_loaded_at(&self) -> Timestamp {
+        *self.bot_config_loaded_at.read().unwrap()
+    }
+
+    /// Compute the current ages.
+    pub async fn report(&self) -> FreshnessReport {
+        let now = Timestamp::now();
+        let bot_config = FreshnessItem::new(
+            BOT_CONFIG_ITEM.to_string(),
+            self.bot_config_loaded_at(),
+            now,
+            self.config.bot_config_max_age_seconds,
+        );
+
+        let (secrets, secrets_error) = match &self.key_vault {
+            None => (Vec::new(), None),
+            Some(key_vault) => match key_vault.secret_freshness().await {
+                Ok(secrets) => (
+                    secrets
+                        .into_iter()
+                        .map(|s| {
+                            FreshnessItem::new(
+                                s.name.as_str().to_string(),
+                                s.refreshed_at,
+                                now,
+                                self.config.secret_max_age_seconds,
+                            )
+                        })
+                        .collect(),
+                    None,
+                ),
+                Err(e) => (Vec::new(), Some(e.to_string())),
+            },
+        };
+
+        FreshnessReport {
+            bot_config,
+            secrets,
+            secrets_error,
+        }
+    }
+
+    /// Compute the ages, export them as gauges and warn about inputs that
+    /// became stale since the previous check.
+    pub async fn check(&self, metrics: &ServiceMetrics) -> FreshnessReport {
+        let report = self.report().await;
+
+        metrics
+            .bot_config_age_seconds
+            .set(report.bot_config.age_seconds as i64);
+        // Reset so secrets evicted from the cache do not keep reporting
+        metrics.secret_cache_age_seconds.reset();
+        for secret in &report.secrets {
+            metrics
+                .secret_cache_age_seconds
+                .with_label_values(&[&secret.name])
+                .set(secret.age_seconds as i64);
+        }
+
+        let now_stale: HashSet<String> = report.stale().map(|item| item.name.clone()).collect();
+        let mut previously_stale = self.stale.lock().unwrap();
+        for item in report.stale() {
+            if !previously_stale.contains(&item.name) {
+                warn!(
+                    item = %item.name,
+                    age_seconds = item.age_seconds,
+                    max_age_seconds = ?item.max_age_seconds,
+                    refreshed_at = %item.refreshed_at,
+                    "Input exceeded its freshness threshold"
+                );
+            }
+        }
+        for name in previously_stale.difference(&now_stale) {
+            info!(item = %name, "Input is fresh again");
+        }
+        *previously_stale = now_stale;
+        drop(previously_stale);
+
+        report
+    }
+
+    /// Run [`check`](Self::check) every `check_interval_seconds`, forever.
+    pub async fn run(&self, metrics: &ServiceMetrics) {
+        let interval = Duration::from_secs(self.config.check_interval_seconds);
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            self.check(metrics).await;
+        }
+    }
+
+    /// Spawn [`run`](Self::run) until the returned task is aborted.
+    pub fn start(self: Arc<Self>, metrics: Arc<ServiceMetrics>) -> JoinHandle<()> {
+        tokio::spawn(async move { self.run(&metrics).await })
+    }
+}
+
+#[cfg(test)]
+#[path = "freshness_tests.rs"]
+mod tests;
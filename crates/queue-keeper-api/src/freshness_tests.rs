@@ -0,0 +1,109 @@
+//! Tests for configuration and secret freshness monitoring.
+
+use super::*;
+use queue_keeper_core::{
+    adapters::InMemoryKeyVaultProvider,
+    key_vault::{SecretName, SecretValue},
+};
+
+fn config(bot_config_max_age: Option<u64>, secret_max_age: Option<u64>) -> FreshnessConfig {
+    FreshnessConfig {
+        bot_config_max_age_seconds: bot_config_max_age,
+        secret_max_age_seconds: secret_max_age,
+        ..FreshnessConfig::default()
+    }
+}
+
+/// A bot configuration older than its threshold is stale until reloaded.
+#[tokio::test]
+async fn test_bot_config_age_exceeding_threshold_is_stale() {
+    let monitor = FreshnessMonitor::new(config(Some(60), None));
+    monitor
+        .record_bot_config_loaded_at(Timestamp::now().subtract_duration(Duration::from_secs(120)));
+
+    let report = monitor.report().await;
+    assert!(report.bot_config.age_seconds >= 120);
+    assert!(report.bot_config.stale);
+    assert_eq!(
+        report.stale().map(|i| i.name.as_str()).collect::<Vec<_>>(),
+        vec![BOT_CONFIG_ITEM]
+    );
+
+    monitor.record_bot_config_loaded();
+    assert!(!monitor.report().await.bot_config.stale);
+}
+
+/// Without a threshold the bot configuration never becomes stale.
+#[tokio::test]
+async fn test_bot_config_without_threshold_is_never_stale() {
+    let monitor = FreshnessMonitor::new(FreshnessConfig::default());
+    monitor.record_bot_config_loaded_at(
+        Timestamp::now().subtract_duration(Duration::from_secs(30 * 86400)),
+    );
+
+    assert!(!monitor.report().await.bot_config.stale);
+}
+
+/// Secrets cached by the Key Vault are reported with their ages, and the
+/// gauges follow the report.
+#[tokio::test]
+async fn test_cached_secrets_are_reported_and_exported() {
+    let key_vault = Arc::new(InMemoryKeyVaultProvider::new());
+    let name = SecretName::new("github-webhook-secret").unwrap();
+    key_vault.add_secret(name.clone(), SecretValue::from_string("s3cret".to_string()));
+    key_vault.get_secret(&name).await.unwrap();
+
+    let monitor = FreshnessMonitor::new(FreshnessConfig::default()).with_key_vault(key_vault);
+    let metrics = ServiceMetrics::default();
+    let report = monitor.check(&metrics).await;
+
+    assert_eq!(report.secrets.len(), 1);
+    assert_eq!(report.secrets[0].name, "github-webhook-secret");
+    assert!(!report.secrets[0].stale);
+    assert_eq!(
+        metrics
+            .secret_cache_age_seconds
+            .with_label_values(&["github-webhook-secret"])
+            .get(),
+        report.secrets[0].age_seconds as i64
+    );
+}
+
+/// Stale inputs produce warning checks that stay healthy.
+#[test]
+fn test_health_checks_warn_without_failing() {
+    let now = Timestamp::now();
+    let old = now.subtract_duration(Duration::from_secs(7200));
+    let report = FreshnessReport {
+        bot_config: FreshnessItem::new(BOT_CONFIG_ITEM.to_string(), now, now, Some(60)),
+        secrets: vec![FreshnessItem::new(
+            "github-webhook-secret".to_string(),
+            old,
+            now,
+            Some(3600),
+        )],
+        secrets_error: None,
+    };
+
+    let checks: std::collections::HashMap<String, HealthCheckResult> =
+        report.health_checks().into_iter().collect();
+    let secrets = &checks["secret_freshness"];
+    assert!(secrets.healthy);
+    assert!(secrets.warning);
+    assert!(secrets.message.contains("github-webhook-secret"));
+    assert!(!checks["bot_config_freshness"].warning);
+}
+
+/// Zero intervals and thresholds are rejected.
+#[test]
+fn test_config_validation() {
+    assert!(FreshnessConfig::default().validate().is_ok());
+    assert!(config(Some(0), None).validate().is_err());
+    assert!(config(None, Some(0)).validate().is_err());
+
+    let no_interval = FreshnessConfig {
+        check_interval_seconds: 0,
+        ..FreshnessConfig::default()
+    };
+    assert!(no_interval.validate().is_err());
+}
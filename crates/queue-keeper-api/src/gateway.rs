@@ -0,0 +1,537 @@
+//! Webhook gateway destinations.
+//!
+//! A gateway destination receives the original webhook delivery rather than
+//! a queue message, so Queue-Keeper can sit in front of legacy bots that only
+//! understand GitHub's webhook format:
+//!
+//! - The raw request body is forwarded byte for byte.
+//! - The original headers (`X-GitHub-Event`, `X-GitHub-Delivery`,
+//!   `User-Agent`, ...) are preserved, except hop-by-hop headers and the
+//!   original signatures.
+//! - `X-Hub-Signature-256` is recomputed with the destination's own secret,
+//!   so each receiver verifies deliveries exactly as it would from GitHub.
+//!   The legacy SHA-1 `X-Hub-Signature` header is not sent.
+//! - `X-Queue-Keeper-Key-Id`, `X-Queue-Keeper-Timestamp` and
+//!   `X-Queue-Keeper-Signature` sign the body, event ID and signing time
+//!   with the same secret (see [`queue_keeper_core::request_signing`]).
+//!   The key ID follows a secret rotated in Key Vault, so receivers can
+//!   accept the previous and the new secret while the rotation rolls out.
+//!
+//! Only deliveries that passed signature validation on a GitHub-format
+//! provider are forwarded. Forwarding runs after the webhook has been
+//! acknowledged and never affects the response or queue delivery; failed
+//! requests are retried with the destination's [`RetryPolicy`] and counted
+//! in `gateway_forwards_total{gateway, outcome}`.
+//!
+//! [`ReqwestTransport`] sends the requests of bot HTTP destinations
+//! ([`queue_keeper_core::http_delivery`]), which sign and retry on their own.
+
+use crate::{
+    config::ProviderSecretConfig, errors::ConfigError, metrics::ServiceMetrics, retry::RetryPolicy,
+};
+use async_trait::async_trait;
+use axum::http::{HeaderMap, HeaderName};
+use bytes::Bytes;
+use hmac::{Hmac, KeyInit, Mac};
+use queue_keeper_core::{
+    bot_config::EventTypePattern,
+    http_delivery::HttpTransport,
+    key_vault::{KeyVaultProvider, SecretName},
+    request_signing::{self, RequestSignature},
+    units::human_duration,
+    EventId,
+};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{str::FromStr, sync::Arc, time::Duration};
+use tracing::{debug, info, warn};
+
+/// Header carrying the Queue-Keeper event ID on forwarded deliveries.
+pub const EVENT_ID_HEADER: &str = "x-queue-keeper-event-id";
+
+/// Request headers that are never copied to a gateway destination.
+///
+/// Hop-by-hop headers belong to the inbound connection, and the original
+/// signatures were computed with Queue-Keeper's secret, not the destination's.
+/// Inbound request signature headers are replaced by our own.
+const DROPPED_HEADERS: &[&str] = &[
+    "host",
+    "content-length",
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "x-hub-signature",
+    "x-hub-signature-256",
+    request_signing::KEY_ID_HEADER,
+    request_signing::TIMESTAMP_HEADER,
+    request_signing::SIGNATURE_HEADER,
+];
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// One gateway destination (an entry of the `gateways` configuration list).
+///
+/// # YAML example
+///
+/// ```yaml
+/// gateways:
+///   - name: legacy-triage
+///     url: https://triage.internal.example.com/webhook
+///     secret:
+///       type: key_vault
+///       secret_name: legacy-triage-webhook-secret
+///     events: ["issues", "pull_request"]
+///     providers: ["github"]
+///     timeout: 10s
+///     retry:
+///       max_attempts: 3
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GatewayConfig {
+    /// Destination name used in logs and metrics.
+    pub name: String,
+
+    /// Webhook receiver URL (`http://` or `https://`).
+    pub url: String,
+
+    /// Secret used to sign forwarded deliveries for this destination.
+    pub secret: ProviderSecretConfig,
+
+    /// Event type patterns to forward, using the bot subscription syntax
+    /// (`issues`, `pull_request.*`, `!push`). Defaults to every event.
+    #[serde(default = "default_events")]
+    pub events: Vec<String>,
+
+    /// Providers whose deliveries are forwarded. Empty forwards deliveries
+    /// from every GitHub-format provider.
+    #[serde(default)]
+    pub providers: Vec<String>,
+
+    /// Timeout for one forwarding request.
+    #[serde(default = "default_timeout", with = "human_duration")]
+    pub timeout: Duration,
+
+    /// Retries for failed forwarding requests.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+}
+
+fn default_events() -> Vec<String> {
+    vec!["*".to_string()]
+}
+
+fn default_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+impl GatewayConfig {
+    /// Validate this destination.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] for an empty name, a URL without an
+    /// `http(s)://` scheme, an empty secret source, an invalid event pattern,
+    /// a zero timeout or an invalid retry policy.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |message: String| ConfigError::Invalid {
+            message: format!("gateway '{}': {}", self.name, message),
+        };
+
+        if self.name.is_empty() {
+            return Err(ConfigError::Invalid {
+                message: "gateway name must not be empty".to_string(),
+            });
+        }
+        let host = self
+            .url
+            .strip_prefix("https://")
+            .or_else(|| self.url.strip_prefix("http://"))
+            .and_then(|rest| rest.split('/').next())
+            .unwrap_or_default();
+        if host.is_empty() {
+            return Err(invalid(format!(
+                "url must be an http:// or https:// URL (got '{}')",
+                self.url
+            )));
+        }
+        let secret_present = match &self.secret {
+            ProviderSecretConfig::KeyVault { secret_name } => !secret_name.is_empty(),
+            ProviderSecretConfig::EnvironmentVariable { env_var_name } => !env_var_name.is_empty(),
+            ProviderSecretConfig::Literal { value } => !value.is_empty(),
+        };
+        if !secret_present {
+            return Err(invalid("secret source must not be empty".to_string()));
+        }
+        if self.events.is_empty() {
+            return Err(invalid("events must list at least one pattern".to_string()));
+        }
+        self.patterns().map_err(invalid)?;
+        if self.timeout.is_zero() {
+            return Err(invalid("timeout must be greater than zero".to_string()));
+        }
+        self.retry.validate().map_err(invalid)?;
+        Ok(())
+    }
+
+    fn patterns(&self) -> Result<Vec<EventTypePattern>, String> {
+        self.events
+            .iter()
+            .map(|pattern| EventTypePattern::from_str(pattern).map_err(|e| e.to_string()))
+            .collect()
+    }
+}
+
+// ============================================================================
+// Signing
+// ============================================================================
+
+/// Compute the `X-Hub-Signature-256` header value for `body`.
+pub fn sign_body(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Headers sent to a destination: the original headers without
+/// [`DROPPED_HEADERS`], plus the recomputed signature and the event ID.
+pub fn forwarded_headers(original: &HeaderMap, signature: &str, event_id: &EventId) -> HeaderMap {
+    let mut headers = HeaderMap::with_capacity(original.len() + 2);
+    for (name, value) in original {
+        if !DROPPED_HEADERS.contains(&name.as_str()) {
+            headers.append(name.clone(), value.clone());
+        }
+    }
+    if let Ok(value) = signature.parse() {
+        headers.insert(HeaderName::from_static("x-hub-signature-256"), value);
+    }
+    if let Ok(value) = event_id.to_string().parse() {
+        headers.insert(HeaderName::from_static(EVENT_ID_HEADER), value);
+    }
+    headers
+}
+
+/// Set the `X-Queue-Keeper-*` request signature headers of `headers`.
+pub fn insert_request_signature(headers: &mut HeaderMap, signature: &RequestSignature) {
+    for (name, value) in signature.headers() {
+        if let Ok(value) = value.parse() {
+            headers.insert(HeaderName::from_static(name), value);
+        }
+    }
+}
+
+// ============================================================================
+// Forwarding
+// ============================================================================
+
+/// Where a destination's signing secret comes from at send time.
+enum GatewaySecret {
+    /// Literal or environment-variable secret, read once at startup.
+    Static(String),
+
+    /// Key Vault secret, fetched (through the provider's cache) per delivery.
+    KeyVault {
+        provider: Arc<dyn KeyVaultProvider>,
+        name: SecretName,
+    },
+}
+
+impl GatewaySecret {
+    async fn resolve(&self) -> Result<String, String> {
+        match self {
+            Self::Static(secret) => Ok(secret.clone()),
+            Self::KeyVault { provider, name } => provider
+                .get_secret(name)
+                .await
+                .map(|value| value.expose_secret().to_string())
+                .map_err(|e| e.to_string()),
+        }
+    }
+}
+
+struct GatewayDestination {
+    name: String,
+    url: String,
+    secret: GatewaySecret,
+    patterns: Vec<EventTypePattern>,
+    providers: Vec<String>,
+    timeout: Duration,
+    retry: RetryPolicy,
+}
+
+impl GatewayDestination {
+    fn accepts(&self, provider: &str, event_type: &str) -> bool {
+        if !self.providers.is_empty() && !self.providers.iter().any(|p| p == provider) {
+            return false;
+        }
+        let excluded = self.patterns.iter().any(|pattern| {
+            matches!(pattern, EventTypePattern::Exclude(excluded) if excluded == event_type)
+        });
+        !excluded
+            && self
+                .patterns
+                .iter()
+                .any(|pattern| pattern.matches(event_type))
+    }
+}
+
+/// Forwards raw webhook deliveries to the configured gateway destinations.
+pub struct WebhookGateway {
+    destinations: Vec<Arc<GatewayDestination>>,
+    client: reqwest::Client,
+    metrics: Option<Arc<ServiceMetrics>>,
+}
+
+impl WebhookGateway {
+    /// Build the gateway from the `gateways` configuration list.
+    ///
+    /// Environment-variable secrets are read here. Returns `Ok(None)` when no
+    /// destination is configured.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] when a destination fails validation,
+    /// its environment variable is unset or empty, or it uses a Key Vault
+    /// secret and `key_vault` is `None`.
+    pub fn from_config(
+        configs: &[GatewayConfig],
+        key_vault: Option<Arc<dyn KeyVaultProvider>>,
+    ) -> Result<Option<Self>, ConfigError> {
+        if configs.is_empty() {
+            return Ok(None);
+        }
+
+        let mut destinations = Vec::with_capacity(configs.len());
+        for config in configs {
+            config.validate()?;
+            let invalid = |message: String| ConfigError::Invalid {
+                message: format!("gateway '{}': {}", config.name, message),
+            };
+            let secret = match &config.secret {
+                ProviderSecretConfig::Literal { value } => {
+                    warn!(
+                        gateway = %config.name,
+                        "Gateway is configured with a literal secret. \
+                         Literal secrets are for development and testing only."
+                    );
+                    GatewaySecret::Static(value.clone())
+                }
+                ProviderSecretConfig::EnvironmentVariable { env_var_name } => {
+                    match std::env::var(env_var_name) {
+                        Ok(value) if !value.is_empty() => GatewaySecret::Static(value),
+                        _ => {
+                            return Err(invalid(format!(
+                                "environment variable '{}' is not set or empty",
+                                env_var_name
+                            )))
+                        }
+                    }
+                }
+                ProviderSecretConfig::KeyVault { secret_name } => {
+                    let provider = key_vault.clone().ok_or_else(|| {
+                        invalid(
+                            "uses a Key Vault secret but no Key Vault is configured".to_string(),
+                        )
+                    })?;
+                    let name = SecretName::new(secret_name.as_str())
+                        .map_err(|e| invalid(e.to_string()))?;
+                    GatewaySecret::KeyVault { provider, name }
+                }
+            };
+            destinations.push(Arc::new(GatewayDestination {
+                name: config.name.clone(),
+                url: config.url.clone(),
+                secret,
+                patterns: config.patterns().map_err(invalid)?,
+                providers: config.providers.clone(),
+                timeout: config.timeout,
+                retry: config.retry.clone(),
+            }));
+        }
+
+        Ok(Some(Self {
+            destinations,
+            client: reqwest::Client::new(),
+            metrics: None,
+        }))
+    }
+
+    /// Count forwards in `gateway_forwards_total`.
+    pub fn with_metrics(mut self, metrics: Arc<ServiceMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Names of the destinations accepting `event_type` from `provider`.
+    pub fn destinations_for(&self, provider: &str, event_type: &str) -> Vec<&str> {
+        self.destinations
+            .iter()
+            .filter(|destination| destination.accepts(provider, event_type))
+            .map(|destination| destination.name.as_str())
+            .collect()
+    }
+
+    /// Forward one delivery to every matching destination.
+    ///
+    /// Each destination is sent to on its own task; the returned handles
+    /// complete when that destination has accepted the delivery or its
+    /// retries are exhausted.
+    pub fn forward(
+        &self,
+        provider: &str,
+        event_type: &str,
+        event_id: EventId,
+        headers: &HeaderMap,
+        body: Bytes,
+    ) -> Vec<tokio::task::JoinHandle<bool>> {
+        self.destinations
+            .iter()
+            .filter(|destination| destination.accepts(provider, event_type))
+            .map(|destination| {
+                let destination = destination.clone();
+                let client = self.client.clone();
+                let metrics = self.metrics.clone();
+                let headers = headers.clone();
+                let body = body.clone();
+                tokio::spawn(async move {
+                    let delivered =
+                        send_with_retry(&client, &destination, &headers, body, &event_id).await;
+                    if let Some(metrics) = metrics {
+                        metrics.record_gateway_forward(&destination.name, delivered);
+                    }
+                    delivered
+                })
+            })
+            .collect()
+    }
+}
+
+/// Send one delivery to `destination`, retrying transport errors, `429` and
+/// `5xx` responses. Returns whether the destination accepted it.
+async fn send_with_retry(
+    client: &reqwest::Client,
+    destination: &GatewayDestination,
+    original_headers: &HeaderMap,
+    body: Bytes,
+    event_id: &EventId,
+) -> bool {
+    let secret = match destination.secret.resolve().await {
+        Ok(secret) => secret,
+        Err(error) => {
+            warn!(
+                gateway = %destination.name,
+                event_id = %event_id,
+                error = %error,
+                "Could not read gateway secret; delivery not forwarded"
+            );
+            return false;
+        }
+    };
+    let mut headers = forwarded_headers(original_headers, &sign_body(&secret, &body), event_id);
+    let event_id_value = event_id.to_string();
+
+    let mut attempt = 0;
+    loop {
+        // Re-signed per attempt so retries keep a fresh timestamp
+        let signature = RequestSignature::sign(
+            secret.as_bytes(),
+            chrono::Utc::now().timestamp(),
+            &event_id_value,
+            &body,
+        );
+        insert_request_signature(&mut headers, &signature);
+        let result = client
+            .post(destination.url.as_str())
+            .headers(headers.clone())
+            .body(body.clone())
+            .timeout(destination.timeout)
+            .send()
+            .await;
+
+        let (retryable, reason) = match result {
+            Ok(response) if response.status().is_success() => {
+                info!(
+                    gateway = %destination.name,
+                    event_id = %event_id,
+                    status = response.status().as_u16(),
+                    "Forwarded webhook to gateway destination"
+                );
+                return true;
+            }
+            Ok(response) => {
+                let status = response.status();
+                (
+                    status.is_server_error() || status.as_u16() == 429,
+                    format!("status {}", status.as_u16()),
+                )
+            }
+            Err(e) => (true, e.to_string()),
+        };
+
+        if !retryable || !destination.retry.should_retry(attempt) {
+            warn!(
+                gateway = %destination.name,
+                event_id = %event_id,
+                attempts = attempt + 1,
+                reason = %reason,
+                "Gateway destination did not accept forwarded webhook"
+            );
+            return false;
+        }
+
+        let delay = destination.retry.calculate_delay(attempt);
+        debug!(
+            gateway = %destination.name,
+            event_id = %event_id,
+            attempt = attempt + 1,
+            delay_ms = delay.as_millis() as u64,
+            reason = %reason,
+            "Retrying gateway forward"
+        );
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+// ============================================================================
+// Bot HTTP Destinations
+// ============================================================================
+
+/// [`HttpTransport`] over reqwest, used by the router to push events to bot
+/// HTTP destinations.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+#[async_trait]
+impl HttpTransport for ReqwestTransport {
+    async fn post(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        body: Bytes,
+        timeout: Duration,
+    ) -> Result<u16, String> {
+        let mut request = self.client.post(url).body(body).timeout(timeout);
+        for (name, value) in headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request
+            .send()
+            .await
+            .map(|response| response.status().as_u16())
+            .map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+#[path = "gateway_tests.rs"]
+mod tests;
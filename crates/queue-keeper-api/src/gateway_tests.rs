@@ -0,0 +1,318 @@
+//! Tests for webhook gateway destinations.
+
+use super::*;
+use crate::config::ServiceConfig;
+use axum::{extract::State, http::StatusCode, routing::post, Router};
+use std::sync::Mutex;
+
+const SECRET: &str = "destination-secret";
+
+fn config(name: &str, url: &str) -> GatewayConfig {
+    GatewayConfig {
+        name: name.to_string(),
+        url: url.to_string(),
+        secret: ProviderSecretConfig::Literal {
+            value: SECRET.to_string(),
+        },
+        events: default_events(),
+        providers: vec![],
+        timeout: Duration::from_secs(5),
+        retry: RetryPolicy::new(2, Duration::from_millis(10), Duration::from_millis(20), 2.0)
+            .without_jitter(),
+    }
+}
+
+fn github_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-github-event", "issues".parse().unwrap());
+    headers.insert("x-github-delivery", "d-1".parse().unwrap());
+    headers.insert("user-agent", "GitHub-Hookshot/abc".parse().unwrap());
+    headers.insert("content-type", "application/json".parse().unwrap());
+    headers.insert("x-hub-signature-256", "sha256=original".parse().unwrap());
+    headers.insert("x-hub-signature", "sha1=original".parse().unwrap());
+    headers.insert("x-queue-keeper-signature", "v1=spoofed".parse().unwrap());
+    headers.insert("host", "queue-keeper.example.com".parse().unwrap());
+    headers.insert("content-length", "17".parse().unwrap());
+    headers
+}
+
+/// Received requests and the statuses to answer them with, in order.
+#[derive(Clone, Default)]
+struct Receiver {
+    requests: Arc<Mutex<Vec<(HeaderMap, Bytes)>>>,
+    statuses: Arc<Mutex<Vec<StatusCode>>>,
+}
+
+async fn receive(State(receiver): State<Receiver>, headers: HeaderMap, body: Bytes) -> StatusCode {
+    receiver.requests.lock().unwrap().push((headers, body));
+    let mut statuses = receiver.statuses.lock().unwrap();
+    if statuses.is_empty() {
+        StatusCode::OK
+    } else {
+        statuses.remove(0)
+    }
+}
+
+/// Start a webhook receiver answering with `statuses`, then 200.
+async fn start_receiver(statuses: Vec<StatusCode>) -> (String, Receiver) {
+    let receiver = Receiver {
+        statuses: Arc::new(Mutex::new(statuses)),
+        ..Default::default()
+    };
+    let app = Router::new()
+        .route("/webhook", post(receive))
+        .with_state(receiver.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/webhook", listener.local_addr().unwrap());
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    (url, receiver)
+}
+
+// ============================================================================
+// Signing
+// ============================================================================
+
+/// Signatures match GitHub's documented test vector.
+#[test]
+fn test_sign_body_matches_github_vector() {
+    assert_eq!(
+        sign_body("It's a Secret to Everybody", b"Hello, World!"),
+        "sha256=757107ea0eb2509fc211221cce984b8a37570b6d7586c22c46f4379c8b043e17"
+    );
+}
+
+/// Original headers are kept, signatures replaced and hop-by-hop headers
+/// dropped.
+#[test]
+fn test_forwarded_headers_replace_signature() {
+    let event_id = EventId::new();
+    let headers = forwarded_headers(&github_headers(), "sha256=new", &event_id);
+
+    assert_eq!(headers["x-github-event"], "issues");
+    assert_eq!(headers["x-github-delivery"], "d-1");
+    assert_eq!(headers["user-agent"], "GitHub-Hookshot/abc");
+    assert_eq!(headers["content-type"], "application/json");
+    assert_eq!(headers["x-hub-signature-256"], "sha256=new");
+    assert_eq!(headers[EVENT_ID_HEADER], event_id.to_string().as_str());
+    assert!(!headers.contains_key("x-hub-signature"));
+    assert!(!headers.contains_key(request_signing::SIGNATURE_HEADER));
+    assert!(!headers.contains_key("host"));
+    assert!(!headers.contains_key("content-length"));
+}
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+/// Destinations filter by provider and event pattern, including exclusions.
+#[test]
+fn test_destinations_match_provider_and_events() {
+    let everything = config("everything", "http://a.example.com/webhook");
+    let issues_only = GatewayConfig {
+        events: vec!["issues".to_string()],
+        providers: vec!["github".to_string()],
+        ..config("issues-only", "http://b.example.com/webhook")
+    };
+    let no_push = GatewayConfig {
+        events: vec!["*".to_string(), "!push".to_string()],
+        ..config("no-push", "http://c.example.com/webhook")
+    };
+    let gateway = WebhookGateway::from_config(&[everything, issues_only, no_push], None)
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(
+        gateway.destinations_for("github", "issues"),
+        vec!["everything", "issues-only", "no-push"]
+    );
+    assert_eq!(
+        gateway.destinations_for("github", "push"),
+        vec!["everything"]
+    );
+    assert_eq!(
+        gateway.destinations_for("ghes", "issues"),
+        vec!["everything", "no-push"]
+    );
+}
+
+// ============================================================================
+// Forwarding
+// ============================================================================
+
+/// The receiver gets the original body and headers, signed with the
+/// destination's secret.
+#[tokio::test]
+async fn test_forward_resigns_raw_delivery() {
+    let (url, receiver) = start_receiver(vec![]).await;
+    let gateway = WebhookGateway::from_config(&[config("legacy", &url)], None)
+        .unwrap()
+        .unwrap();
+    let body = Bytes::from_static(br#"{"action":"opened"}"#);
+    let event_id = EventId::new();
+
+    let handles = gateway.forward(
+        "github",
+        "issues",
+        event_id,
+        &github_headers(),
+        body.clone(),
+    );
+    assert_eq!(handles.len(), 1);
+    for handle in handles {
+        assert!(handle.await.unwrap());
+    }
+
+    let requests = receiver.requests.lock().unwrap();
+    let (headers, received) = &requests[0];
+    assert_eq!(received, &body);
+    assert_eq!(headers["x-github-event"], "issues");
+    assert_eq!(
+        headers["x-hub-signature-256"],
+        sign_body(SECRET, &body).as_str()
+    );
+    assert!(!headers.contains_key("x-hub-signature"));
+
+    let header = |name: &str| headers[name].to_str().unwrap();
+    let verifier = queue_keeper_core::RequestVerifier::new(["previous-secret", SECRET]);
+    assert_eq!(
+        verifier.verify(
+            header(request_signing::KEY_ID_HEADER),
+            header(request_signing::TIMESTAMP_HEADER),
+            header(request_signing::SIGNATURE_HEADER),
+            header(EVENT_ID_HEADER),
+            &body,
+        ),
+        Ok(())
+    );
+}
+
+/// Server errors are retried; client errors are not.
+#[tokio::test]
+async fn test_forward_retries_server_errors_only() {
+    let (url, receiver) = start_receiver(vec![StatusCode::SERVICE_UNAVAILABLE]).await;
+    let gateway = WebhookGateway::from_config(&[config("flaky", &url)], None)
+        .unwrap()
+        .unwrap();
+    let handles = gateway.forward(
+        "github",
+        "push",
+        EventId::new(),
+        &github_headers(),
+        Bytes::from_static(b"{}"),
+    );
+    assert!(handles.into_iter().next().unwrap().await.unwrap());
+    assert_eq!(receiver.requests.lock().unwrap().len(), 2);
+
+    let (url, receiver) = start_receiver(vec![StatusCode::UNAUTHORIZED]).await;
+    let gateway = WebhookGateway::from_config(&[config("rejecting", &url)], None)
+        .unwrap()
+        .unwrap();
+    let handles = gateway.forward(
+        "github",
+        "push",
+        EventId::new(),
+        &github_headers(),
+        Bytes::from_static(b"{}"),
+    );
+    assert!(!handles.into_iter().next().unwrap().await.unwrap());
+    assert_eq!(receiver.requests.lock().unwrap().len(), 1);
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Destinations parse with defaults and human-readable durations.
+#[test]
+fn test_config_parses_with_defaults() {
+    let config: GatewayConfig = serde_json::from_str(
+        r#"{
+            "name": "legacy",
+            "url": "https://legacy.example.com/webhook",
+            "secret": { "type": "environment_variable", "env_var_name": "LEGACY_SECRET" },
+            "timeout": "3s"
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.events, vec!["*"]);
+    assert!(config.providers.is_empty());
+    assert_eq!(config.timeout, Duration::from_secs(3));
+    assert!(config.validate().is_ok());
+}
+
+/// Invalid destinations are rejected.
+#[test]
+fn test_config_validation() {
+    assert!(config("", "https://a.example.com").validate().is_err());
+    assert!(config("a", "ftp://a.example.com").validate().is_err());
+    assert!(config("a", "https://").validate().is_err());
+
+    let empty_secret = GatewayConfig {
+        secret: ProviderSecretConfig::Literal {
+            value: String::new(),
+        },
+        ..config("a", "https://a.example.com")
+    };
+    assert!(empty_secret.validate().is_err());
+
+    let no_events = GatewayConfig {
+        events: vec![],
+        ..config("a", "https://a.example.com")
+    };
+    assert!(no_events.validate().is_err());
+
+    let zero_timeout = GatewayConfig {
+        timeout: Duration::ZERO,
+        ..config("a", "https://a.example.com")
+    };
+    assert!(zero_timeout.validate().is_err());
+}
+
+/// Duplicate gateway names and Key Vault secrets without a vault are
+/// rejected by the service configuration.
+#[test]
+fn test_service_config_validates_gateways() {
+    let duplicate = ServiceConfig {
+        gateways: vec![
+            config("legacy", "https://a.example.com"),
+            config("legacy", "https://b.example.com"),
+        ],
+        ..Default::default()
+    };
+    assert!(duplicate.validate().is_err());
+
+    let key_vault_secret = ServiceConfig {
+        gateways: vec![GatewayConfig {
+            secret: ProviderSecretConfig::KeyVault {
+                secret_name: "legacy-secret".to_string(),
+            },
+            ..config("legacy", "https://a.example.com")
+        }],
+        ..Default::default()
+    };
+    assert!(key_vault_secret.validate().is_err());
+}
+
+/// No destinations build no gateway; unresolvable secrets fail the build.
+#[test]
+fn test_from_config_resolves_secrets() {
+    assert!(WebhookGateway::from_config(&[], None).unwrap().is_none());
+
+    let missing_env = GatewayConfig {
+        secret: ProviderSecretConfig::EnvironmentVariable {
+            env_var_name: "QK_TEST_GATEWAY_SECRET_THAT_IS_NOT_SET".to_string(),
+        },
+        ..config("legacy", "https://a.example.com")
+    };
+    assert!(WebhookGateway::from_config(&[missing_env], None).is_err());
+
+    let key_vault = GatewayConfig {
+        secret: ProviderSecretConfig::KeyVault {
+            secret_name: "legacy-secret".to_string(),
+        },
+        ..config("legacy", "https://a.example.com")
+    };
+    assert!(WebhookGateway::from_config(&[key_vault], None).is_err());
+}
@@ -0,0 +1,470 @@
+//! GitHub App authentication.
+//!
+//! When the `github_app` section is configured, the service authenticates to
+//! the GitHub API as the App through github-bot-sdk's [`GitHubAppAuth`]:
+//!
+//! - [`KeyVaultAppSecrets`] reads the App's PEM private key from Key Vault.
+//! - [`Rs256Signer`] signs the App JWTs with that key.
+//! - [`GitHubRestClient`] exchanges a JWT for an installation token.
+//! - [`TokenCache`] keeps the installation tokens, shared between replicas
+//!   when the `token_cache` store is Redis.
+//!
+//! [`build_github_app_auth`] assembles the provider at startup; it is held in
+//! [`AppState::github_auth`](crate::AppState::github_auth).
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use github_bot_sdk::{
+    auth::{
+        jwt::{JwtGenerator, RS256JwtGenerator},
+        tokens::{AuthConfig, GitHubAppAuth},
+        AuthenticationProvider, GitHubApiClient, GitHubAppId, Installation, InstallationId,
+        InstallationPermissions, JsonWebToken, JwtClaims, JwtSigner, PrivateKey, RateLimitInfo,
+        Repository, RepositoryId, SecretProvider,
+    },
+    error::{ApiError, SecretError, SigningError, ValidationError},
+};
+use queue_keeper_core::{
+    key_vault::{KeyVaultError, KeyVaultProvider, SecretName},
+    units::human_duration,
+};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+
+use crate::{
+    config::DEFAULT_GITHUB_API_URL,
+    errors::ConfigError,
+    token_cache::{build_token_cache, TokenCache, TokenCacheConfig},
+};
+
+/// `User-Agent` sent with every GitHub API request.
+const USER_AGENT: &str = "queue-keeper";
+
+/// REST API version requested from GitHub.
+const API_VERSION: &str = "2022-11-28";
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// GitHub App credentials (the `github_app` configuration section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// github_app:
+///   app_id: 123456
+///   private_key_secret: queue-keeper-github-app-key
+///   timeout: 10s
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GitHubAppConfig {
+    /// The App's numeric ID.
+    pub app_id: u64,
+
+    /// Name of the Key Vault secret holding the App's PEM-encoded private
+    /// key, as downloaded from the App settings.
+    pub private_key_secret: String,
+
+    /// Timeout for one GitHub API request.
+    #[serde(default = "default_timeout", with = "human_duration")]
+    pub timeout: Duration,
+}
+
+fn default_timeout() -> Duration {
+    Duration::from_secs(10)
+}
+
+impl GitHubAppConfig {
+    /// Validate the GitHub App configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] when `app_id` is zero, the private
+    /// key secret name is not a valid Key Vault secret name, or `timeout` is
+    /// zero.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |message: String| {
+            Err(ConfigError::Invalid {
+                message: format!("github_app.{}", message),
+            })
+        };
+        if self.app_id == 0 {
+            return invalid("app_id must be set".to_string());
+        }
+        if let Err(e) = SecretName::new(self.private_key_secret.as_str()) {
+            return invalid(format!("private_key_secret: {}", e));
+        }
+        if self.timeout.is_zero() {
+            return invalid("timeout must be greater than zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Provider
+// ============================================================================
+
+/// The GitHub App authentication provider built by [`build_github_app_auth`].
+pub type GitHubAppAuthProvider =
+    GitHubAppAuth<KeyVaultAppSecrets, Rs256Signer, GitHubRestClient, TokenCache>;
+
+/// Build the GitHub App authentication provider, with the installation token
+/// cache configured in `token_cache`.
+///
+/// `github_api_url` is the top-level `github_api_url` setting; tokens are
+/// minted at [`DEFAULT_GITHUB_API_URL`] when it is `None`.
+///
+/// Returns `Ok(None)` when `config` is `None`.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Invalid`] when `key_vault` is `None`, the
+/// configuration is invalid, or the token cache cannot be built (see
+/// [`build_token_cache`]).
+pub async fn build_github_app_auth(
+    config: Option<&GitHubAppConfig>,
+    github_api_url: Option<&str>,
+    token_cache: &TokenCacheConfig,
+    key_vault: Option<&Arc<dyn KeyVaultProvider>>,
+) -> Result<Option<Arc<dyn AuthenticationProvider>>, ConfigError> {
+    let Some(config) = config else {
+        return Ok(None);
+    };
+    config.validate()?;
+    let key_vault = key_vault.ok_or_else(|| ConfigError::Invalid {
+        message: "github_app.private_key_secret: a `key_vault` section is required".to_string(),
+    })?;
+    let private_key_secret =
+        SecretName::new(config.private_key_secret.as_str()).map_err(|e| ConfigError::Invalid {
+            message: format!("github_app.private_key_secret: {}", e),
+        })?;
+
+    let api_url = github_api_url
+        .unwrap_or(DEFAULT_GITHUB_API_URL)
+        .trim_end_matches('/');
+
+    let cache = build_token_cache(token_cache, Some(key_vault)).await?;
+    let auth_config = AuthConfig {
+        // The cache decides when a token is refreshed (see `TokenCache`)
+        token_refresh_margin: chrono::Duration::zero(),
+        github_api_url: api_url.to_string(),
+        user_agent: USER_AGENT.to_string(),
+        ..AuthConfig::default()
+    };
+    let provider: GitHubAppAuthProvider = GitHubAppAuth::new(
+        KeyVaultAppSecrets::new(
+            GitHubAppId::new(config.app_id),
+            private_key_secret,
+            Arc::clone(key_vault),
+        ),
+        Rs256Signer,
+        GitHubRestClient::new(api_url, config.timeout),
+        cache,
+        auth_config,
+    );
+    info!(
+        app_id = config.app_id,
+        api_url = %api_url,
+        token_cache = token_cache.store.backend_name(),
+        "GitHub App authentication enabled"
+    );
+    Ok(Some(Arc::new(provider)))
+}
+
+// ============================================================================
+// Secrets
+// ============================================================================
+
+/// GitHub App secrets read from Key Vault.
+pub struct KeyVaultAppSecrets {
+    app_id: GitHubAppId,
+    private_key_secret: SecretName,
+    key_vault: Arc<dyn KeyVaultProvider>,
+}
+
+impl KeyVaultAppSecrets {
+    /// Read the private key of App `app_id` from `private_key_secret`.
+    pub fn new(
+        app_id: GitHubAppId,
+        private_key_secret: SecretName,
+        key_vault: Arc<dyn KeyVaultProvider>,
+    ) -> Self {
+        Self {
+            app_id,
+            private_key_secret,
+            key_vault,
+        }
+    }
+}
+
+#[async_trait]
+impl SecretProvider for KeyVaultAppSecrets {
+    async fn get_private_key(&self) -> Result<PrivateKey, SecretError> {
+        let key = self.private_key_secret.as_str().to_string();
+        let value = self
+            .key_vault
+            .get_secret(&self.private_key_secret)
+            .await
+            .map_err(|e| match e {
+                KeyVaultError::SecretNotFound { .. } => SecretError::NotFound { key: key.clone() },
+                KeyVaultError::AccessDenied { .. } => {
+                    SecretError::AccessDenied { key: key.clone() }
+                }
+                other => SecretError::ProviderUnavailable(other.to_string()),
+            })?;
+        PrivateKey::from_pem(value.expose_secret()).map_err(|_| SecretError::InvalidFormat { key })
+    }
+
+    async fn get_app_id(&self) -> Result<GitHubAppId, SecretError> {
+        Ok(self.app_id)
+    }
+
+    /// Webhook secrets are configured per provider, not per App.
+    async fn get_webhook_secret(&self) -> Result<String, SecretError> {
+        Err(SecretError::NotFound {
+            key: "webhook_secret".to_string(),
+        })
+    }
+
+    /// The Key Vault provider caches secrets itself.
+    fn cache_duration(&self) -> chrono::Duration {
+        chrono::Duration::zero()
+    }
+}
+
+// ============================================================================
+// Signing
+// ============================================================================
+
+/// Signs App JWTs with RS256.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Rs256Signer;
+
+#[async_trait]
+impl JwtSigner for Rs256Signer {
+    async fn sign_jwt(
+        &self,
+        claims: JwtClaims,
+        private_key: &PrivateKey,
+    ) -> Result<JsonWebToken, SigningError> {
+        // GitHub rejects JWTs living longer than ten minutes
+        let lifetime = chrono::Duration::seconds(claims.exp - claims.iat)
+            .clamp(chrono::Duration::zero(), chrono::Duration::minutes(10));
+        RS256JwtGenerator::with_expiration(private_key.clone(), lifetime)
+            .generate_jwt(claims.iss)
+            .await
+            .map_err(|e| SigningError::SigningFailed {
+                message: e.to_string(),
+            })
+    }
+
+    fn validate_private_key(&self, key: &PrivateKey) -> Result<(), ValidationError> {
+        let pem =
+            std::str::from_utf8(key.key_data()).map_err(|e| ValidationError::InvalidFormat {
+                field: "private_key".to_string(),
+                message: e.to_string(),
+            })?;
+        PrivateKey::from_pem(pem).map(|_| ())
+    }
+}
+
+// ============================================================================
+// REST Client
+// ============================================================================
+
+/// The GitHub REST API calls made by the authentication provider.
+pub struct GitHubRestClient {
+    client: reqwest::Client,
+    api_url: String,
+    timeout: Duration,
+}
+
+impl GitHubRestClient {
+    /// Call the REST API at `api_url`, e.g. `https://api.github.com`.
+    pub fn new(api_url: impl Into<String>, timeout: Duration) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url: api_url.into().trim_end_matches('/').to_string(),
+            timeout,
+        }
+    }
+
+    async fn send<T: serde::de::DeserializeOwned>(
+        &self,
+        request: reqwest::RequestBuilder,
+        bearer: &str,
+    ) -> Result<T, ApiError> {
+        let response = request
+            .bearer_auth(bearer)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .header("X-GitHub-Api-Version", API_VERSION)
+            .timeout(self.timeout)
+            .send()
+            .await
+            .map_err(transport_error)?;
+        let status = response.status().as_u16();
+        let rate_limit_reset = rate_limit_reset(response.headers());
+        let body = response.bytes().await.map_err(transport_error)?;
+        if (200..300).contains(&status) {
+            return Ok(serde_json::from_slice(&body)?);
+        }
+        let message = String::from_utf8_lossy(&body).into_owned();
+        Err(match status {
+            401 => ApiError::AuthenticationFailed,
+            403 | 429 if rate_limit_reset.is_some() => ApiError::RateLimitExceeded {
+                reset_at: rate_limit_reset.unwrap_or_else(Utc::now),
+            },
+            403 | 429 if message.contains("secondary rate limit") => ApiError::SecondaryRateLimit,
+            403 => ApiError::AuthorizationFailed,
+            404 => ApiError::NotFound,
+            status => ApiError::HttpError { status, message },
+        })
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.api_url, path)
+    }
+}
+
+#[derive(Deserialize)]
+struct AccessTokenResponse {
+    token: String,
+    expires_at: DateTime<Utc>,
+    #[serde(default)]
+    permissions: InstallationPermissions,
+    #[serde(default)]
+    repositories: Vec<RepositoryRef>,
+}
+
+#[derive(Deserialize)]
+struct RepositoryRef {
+    id: u64,
+}
+
+#[derive(Deserialize)]
+struct RepositoriesResponse {
+    repositories: Vec<Repository>,
+}
+
+#[derive(Deserialize)]
+struct RateLimitResponse {
+    rate: RateLimitResource,
+}
+
+#[derive(Deserialize)]
+struct RateLimitResource {
+    limit: u32,
+    remaining: u32,
+    used: u32,
+    reset: i64,
+}
+
+#[async_trait]
+impl GitHubApiClient for GitHubRestClient {
+    async fn create_installation_access_token(
+        &self,
+        installation_id: InstallationId,
+        jwt: &JsonWebToken,
+    ) -> Result<github_bot_sdk::auth::InstallationToken, ApiError> {
+        let path = format!("/app/installations/{}/access_tokens", installation_id);
+        let response: AccessTokenResponse = self
+            .send(self.client.post(self.url(&path)), jwt.token())
+            .await?;
+        Ok(github_bot_sdk::auth::InstallationToken::new(
+            response.token,
+            installation_id,
+            response.expires_at,
+            response.permissions,
+            response
+                .repositories
+                .into_iter()
+                .map(|r| RepositoryId::new(r.id))
+                .collect(),
+        ))
+    }
+
+    /// Returns the first 100 installations.
+    async fn list_app_installations(
+        &self,
+        jwt: &JsonWebToken,
+    ) -> Result<Vec<Installation>, ApiError> {
+        self.send(
+            self.client.get(self.url("/app/installations?per_page=100")),
+            jwt.token(),
+        )
+        .await
+    }
+
+    /// Returns the first 100 repositories.
+    async fn list_installation_repositories(
+        &self,
+        _installation_id: InstallationId,
+        token: &github_bot_sdk::auth::InstallationToken,
+    ) -> Result<Vec<Repository>, ApiError> {
+        let response: RepositoriesResponse = self
+            .send(
+                self.client
+                    .get(self.url("/installation/repositories?per_page=100")),
+                token.token(),
+            )
+            .await?;
+        Ok(response.repositories)
+    }
+
+    async fn get_repository(
+        &self,
+        repo_id: RepositoryId,
+        token: &github_bot_sdk::auth::InstallationToken,
+    ) -> Result<Repository, ApiError> {
+        let path = format!("/repositories/{}", repo_id);
+        self.send(self.client.get(self.url(&path)), token.token())
+            .await
+    }
+
+    async fn get_rate_limit(
+        &self,
+        token: &github_bot_sdk::auth::InstallationToken,
+    ) -> Result<RateLimitInfo, ApiError> {
+        let response: RateLimitResponse = self
+            .send(self.client.get(self.url("/rate_limit")), token.token())
+            .await?;
+        let rate = response.rate;
+        Ok(RateLimitInfo {
+            limit: rate.limit,
+            remaining: rate.remaining,
+            reset_at: DateTime::from_timestamp(rate.reset, 0).unwrap_or_else(Utc::now),
+            used: rate.used,
+        })
+    }
+}
+
+/// When the primary rate limit resets, if the response says it is exhausted.
+fn rate_limit_reset(headers: &reqwest::header::HeaderMap) -> Option<DateTime<Utc>> {
+    let header = |name: &str| headers.get(name)?.to_str().ok()?.parse::<i64>().ok();
+    if header("x-ratelimit-remaining")? != 0 {
+        return None;
+    }
+    DateTime::from_timestamp(header("x-ratelimit-reset")?, 0)
+}
+
+/// Map a transport failure. The SDK's `HttpClientError` wraps a different
+/// reqwest version, so the failure is reported by message.
+fn transport_error(e: reqwest::Error) -> ApiError {
+    if e.is_timeout() {
+        ApiError::Timeout
+    } else {
+        ApiError::HttpError {
+            status: e.status().map_or(0, |status| status.as_u16()),
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "github_app_tests.rs"]
+mod tests;
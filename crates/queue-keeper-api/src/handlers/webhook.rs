@@ -4,8 +4,13 @@
 //! `POST /webhook/{provider}`.
 
 use crate::{
-    queue_delivery::spawn_queue_delivery, responses::store_wrapped_event_to_blob, AppState,
-    WebhookHandlerError, WebhookResponse,
+    queue_delivery::{queue_delivery_task, spawn_queue_delivery, QueueDeliveryOutcome},
+    repository_rate_limit::{event_repository, repository_full_name},
+    responses::store_wrapped_event_to_blob,
+    session_shards::shard_key,
+    telemetry,
+    unknown_event_types::{deliver_for_review, UnknownEventTypeMode},
+    AppState, WebhookHandlerError, WebhookResponse,
 };
 use axum::{
     extract::{Path, State},
@@ -14,12 +19,19 @@ use axum::{
 };
 use bytes::Bytes;
 use queue_keeper_core::{
+    event_debug, event_error, event_info, event_warn,
+    logging::LogFields,
     monitoring::MetricsCollector,
-    webhook::{ProcessingOutput, WebhookHeaders, WebhookRequest},
+    queue_integration::{direct_filter_attributes, TRACE_PARENT_ATTRIBUTE},
+    webhook::{
+        EventOrigin, PayloadStorage, ProcessingOutput, WebhookError, WebhookHeaders,
+        WebhookRequest, WrappedEvent, ENTERPRISE_HOST_HEADER,
+    },
+    BlobStorage, MonotonicTimestamp, PipelineStage, StageTimings,
 };
 use queue_runtime::{Message, QueueName};
-use std::collections::HashMap;
-use tracing::{error, info, instrument, warn};
+use std::{collections::HashMap, sync::Arc, time::Instant};
+use tracing::{debug, info, instrument, warn, Span};
 
 /// Handle a webhook for a specific provider.
 ///
@@ -29,16 +41,39 @@ use tracing::{error, info, instrument, warn};
 /// # Request Flow
 ///
 /// 1. Extract provider name from the URL path.
-/// 2. Look it up in the [`ProviderRegistry`]; return 404 if absent.
-/// 3. Parse provider-agnostic webhook headers.
-/// 4. Delegate to the provider's [`WebhookProcessor::process_webhook`].
-/// 5. Return `200 OK` with [`WebhookResponse`] on success.
+/// 2. Look it up in the [`ProviderRegistry`]; return 404 if absent. A
+///    delivery from a GitHub Enterprise host sent to a provider without
+///    `github_enterprise` settings is handed to the provider configured for
+///    that host (see
+///    [`ServiceConfig::enterprise_provider`](crate::config::ServiceConfig::enterprise_provider)).
+/// 3. Parse provider-agnostic webhook headers and check that the delivery
+///    comes from a source the provider accepts (GitHub Enterprise host and
+///    installation registry).
+/// 4. Count GitHub event types missing from the known-types registry and,
+///    in strict mode, reject them or set them aside for review (see
+///    [`crate::unknown_event_types`]).
+/// 5. Delegate to the provider's [`WebhookProcessor::process_webhook`].
+/// 6. Reject the event with `429 Too Many Requests` when its repository is
+///    over `webhooks.rate_limit_per_repo` (see
+///    [`crate::repository_rate_limit`]), releasing its deduplication claim.
+/// 7. Write routed events to the outbox when it is enabled (see
+///    [`crate::outbox`]), then start their delivery.
+/// 8. Return `200 OK` with [`WebhookResponse`] on success, with status
+///    `already_processed` when the processor skipped a delivery whose ID it
+///    had already processed (see [`crate::deduplication`]).
+///
+/// Each pipeline stage the request reaches is timed (see [`StageTimings`])
+/// and recorded in the `webhook_stage_duration_seconds` histogram; the
+/// per-request breakdown is logged at debug level.
 ///
 /// # Errors
 ///
 /// - [`WebhookHandlerError::ProviderNotFound`] when the provider is not registered.
 /// - [`WebhookHandlerError::InvalidHeaders`] when required headers are missing or malformed.
-/// - [`WebhookHandlerError::ProcessingFailed`] when the processor pipeline fails.
+/// - [`WebhookHandlerError::SourceRejected`] when the delivery comes from another source.
+/// - [`WebhookHandlerError::RateLimitExceeded`] when the repository is over its rate limit.
+/// - [`WebhookHandlerError::ProcessingFailed`] when the processor pipeline fails,
+///   or the event type is unknown and `unknown_event_types.mode` is `reject`.
 #[instrument(skip(state, headers, body), fields(provider = %provider))]
 pub async fn handle_provider_webhook(
     State(state): State<AppState>,
@@ -49,127 +84,246 @@ pub async fn handle_provider_webhook(
     info!(provider = %provider, "Received webhook request");
 
     // Resolve provider – return 404 for unknown providers before any further work
+    if !state.provider_registry.contains(&provider) {
+        return Err(WebhookHandlerError::ProviderNotFound { provider });
+    }
+    let provider = resolve_enterprise_provider(&state, provider, &headers);
     let processor = state.provider_registry.get(&provider).ok_or_else(|| {
         WebhookHandlerError::ProviderNotFound {
             provider: provider.clone(),
         }
     })?;
 
-    // Start timing for metrics
-    let start = std::time::Instant::now();
-
-    // Convert headers to HashMap (lowercase keys for consistent lookup)
-    let header_map: HashMap<String, String> = headers
-        .iter()
-        .map(|(k, v)| {
-            (
-                k.as_str().to_lowercase(),
-                v.to_str().unwrap_or("").to_string(),
-            )
-        })
-        .collect();
+    // Start timing for metrics. The same receipt becomes the event's
+    // received_at, so stored timestamps and latency metrics share one origin.
+    let receipt = MonotonicTimestamp::now();
+    let timings = StageTimings::new();
 
-    // Determine whether this is a generic (non-GitHub) provider.
-    // Generic providers do not send GitHub-specific headers, so we use a
-    // relaxed parser that falls back to safe defaults instead of failing.
-    //
-    // The set is pre-built at startup (O(1) lookup here vs. O(n) scan).
-    //
-    // Note: generic providers do not support `allowed_event_types` filtering
-    // — all event types are accepted regardless of configuration. If you need
-    // per-event filtering for a generic provider, implement it downstream.
-    let is_generic_provider = state.generic_provider_ids.contains(&provider);
-
-    let webhook_headers = if is_generic_provider {
-        // For generic providers, never fail on missing GitHub headers — the
-        // provider's own process_webhook will re-extract values from raw headers.
-        WebhookHeaders::from_http_headers_relaxed(&header_map)
-    } else {
-        // For GitHub and other strict providers, require all GitHub-specific headers.
-        match WebhookHeaders::from_http_headers(&header_map) {
-            Ok(h) => h,
-            Err(e) => {
-                let duration = start.elapsed();
-                state.metrics.record_webhook_request(duration, false);
-                state.metrics.record_webhook_validation_failure();
-                return Err(WebhookHandlerError::InvalidHeaders(e));
-            }
+    let header_parse = Instant::now();
+    let parsed_headers = parse_headers(&state, &provider, &headers).and_then(|parsed| {
+        check_source(&state, &provider, &parsed.1, &body)?;
+        Ok(parsed)
+    });
+    timings.record(PipelineStage::HeaderParse, header_parse.elapsed());
+    let (webhook_headers, header_map) = match parsed_headers {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            record_stage_timings(&state, &timings);
+            state
+                .metrics
+                .record_webhook_request(receipt.elapsed(), false);
+            state.metrics.record_webhook_validation_failure();
+            state.statistics.record_failure();
+            return Err(e);
         }
     };
 
-    // Enforce per-provider allowed_event_types if configured.
-    // An empty list means all event types are accepted.
-    //
-    // Note: require_signature enforcement is delegated to the processor's
-    // SignatureValidator. When a SignatureValidator is wired into the
-    // DefaultWebhookProcessor it will reject requests with an invalid or
-    // missing signature regardless of the ProviderConfig setting.
-    let provider_config = state.config.providers.iter().find(|p| p.id == provider);
-    if let Some(pc) = provider_config {
-        if !pc.allowed_event_types.is_empty()
-            && !pc.allowed_event_types.contains(&webhook_headers.event_type)
-        {
-            let duration = start.elapsed();
-            state.metrics.record_webhook_request(duration, false);
-            state.metrics.record_webhook_validation_failure();
-            return Err(WebhookHandlerError::InvalidHeaders(
-                queue_keeper_core::ValidationError::InvalidFormat {
-                    // Use a provider-neutral field name so non-GitHub providers
-                    // receive a sensible error rather than a GitHub header name.
-                    field: "event-type".to_string(),
-                    message: format!(
-                        "event type '{}' is not in the allowed list for provider '{}'",
-                        webhook_headers.event_type, provider
-                    ),
-                },
-            ));
-        }
+    let unknown_type_mode = unknown_event_type_mode(&state, &provider, &webhook_headers);
+    if unknown_type_mode == Some(UnknownEventTypeMode::Reject) {
+        state
+            .metrics
+            .record_webhook_request(receipt.elapsed(), false);
+        state.statistics.record_failure();
+        return Err(WebhookHandlerError::ProcessingFailed(
+            WebhookError::UnknownEventType {
+                event_type: webhook_headers.event_type,
+            },
+        ));
     }
+    let review_queue = match unknown_type_mode {
+        Some(UnknownEventTypeMode::Review) => state
+            .config
+            .unknown_event_types
+            .review_queue
+            .clone()
+            .and_then(|queue| QueueName::new(queue).ok()),
+        _ => None,
+    };
+
+    let github_delivery_id = webhook_headers.delivery_id.clone();
+
+    // Gateway destinations receive the delivery exactly as it arrived.
+    let raw_body = state.gateway.as_ref().map(|_| body.clone());
+    let body_len = body.len();
+
+    // Rejections are streamed with what the delivery said about itself.
+    let rejection = state.event_stream.as_ref().map(|_| {
+        (
+            webhook_headers.event_type.clone(),
+            repository_full_name(&body),
+        )
+    });
 
     // Create webhook request, carrying the full header map so generic providers
     // can resolve FieldSource::Header values from any header name.
-    let webhook_request = WebhookRequest::with_raw_headers(webhook_headers, header_map, body);
+    let webhook_request = WebhookRequest::with_raw_headers(webhook_headers, header_map, body)
+        .with_receipt(receipt)
+        .with_timings(timings.clone());
 
     // Delegate to the provider-specific processor
-    let processing_output = match processor.process_webhook(webhook_request).await {
+    let processing_result = processor.process_webhook(webhook_request).await;
+    record_stage_timings(&state, &timings);
+    let processing_output = match processing_result {
+        Ok(ProcessingOutput::AlreadyProcessed {
+            delivery_id,
+            event_id,
+            ..
+        }) => {
+            state.metrics.webhook_duplicate_deliveries_total.inc();
+            state
+                .metrics
+                .record_webhook_request(receipt.elapsed(), true);
+            state.statistics.record_duplicate();
+            info!(
+                provider = %provider,
+                delivery_id = %delivery_id,
+                "Webhook delivery already processed - skipped"
+            );
+            return Ok(Json(WebhookResponse {
+                event_id,
+                session_id: None,
+                status: "already_processed".to_string(),
+                message: format!("Delivery '{}' was already processed", delivery_id),
+            }));
+        }
         Ok(output) => output,
         Err(e) => {
-            let duration = start.elapsed();
+            let duration = receipt.elapsed();
             state.metrics.record_webhook_request(duration, false);
+            state.statistics.record_failure();
+            if let (Some(stream), Some((event_type, repository))) = (&state.event_stream, rejection)
+            {
+                stream.publish_rejected(&provider, &event_type, repository, &e.to_string());
+            }
+            debug!(
+                provider = %provider,
+                total_ms = duration.as_secs_f64() * 1_000.0,
+                stages = %timings,
+                "Webhook stage timing breakdown (failed)"
+            );
             return Err(WebhookHandlerError::ProcessingFailed(e));
         }
     };
 
-    info!(
-        event_id = %processing_output.event_id(),
+    // The limit is keyed on the validated event, so a forged payload cannot
+    // spend another repository's budget.
+    if let (Some(limiter), Some(repository)) = (
+        &state.repository_rate_limiter,
+        processing_output.as_wrapped().and_then(event_repository),
+    ) {
+        if let Err(retry_after) = limiter.check(repository) {
+            let retry_after_seconds = retry_after.as_secs_f64().ceil().max(1.0) as u64;
+            warn!(
+                provider = %provider,
+                repository = %repository,
+                limit = limiter.limit(),
+                retry_after_secs = retry_after_seconds,
+                "Repository webhook rate limit exceeded"
+            );
+            // The event is dropped, so a redelivery must not be skipped as
+            // already processed.
+            if let Some(store) = &state.deduplication {
+                if let Err(e) = store.release(&github_delivery_id).await {
+                    warn!(
+                        delivery_id = %github_delivery_id,
+                        error = %e,
+                        "Failed to release deduplication claim of a throttled delivery"
+                    );
+                }
+            }
+            state
+                .metrics
+                .record_webhook_request(receipt.elapsed(), false);
+            state.statistics.record_failure();
+            return Err(WebhookHandlerError::RateLimitExceeded {
+                retry_after_seconds,
+            });
+        }
+    }
+
+    let log_context = processing_output.log_context();
+    event_info!(
+        log_context,
         event_type = processing_output.event_type().unwrap_or("unknown"),
-        session_id = ?processing_output.session_id(),
         provider = %provider,
         "Successfully processed webhook - returning immediate response"
     );
 
-    let duration = start.elapsed();
+    let duration = receipt.elapsed();
     state.metrics.record_webhook_request(duration, true);
+    state.statistics.record_event(
+        processing_output.event_type().unwrap_or("unknown"),
+        processing_output
+            .as_wrapped()
+            .map_or(EventOrigin::Github, |event| event.origin),
+    );
+    event_debug!(
+        log_context,
+        provider = %provider,
+        total_ms = duration.as_secs_f64() * 1_000.0,
+        stages = %timings,
+        "Webhook stage timing breakdown"
+    );
 
     let event_id = processing_output.event_id();
     let session_id = processing_output.session_id().cloned();
 
+    // Queue messages carry the trace context so consumers join the trace.
+    let trace_parent = telemetry::trace_parent(&Span::current(), &headers);
+
+    // Re-sign and forward the raw delivery to gateway destinations. Generic
+    // providers are excluded: their payloads are not in GitHub's format.
+    if let (Some(gateway), Some(raw_body), Some(event_type)) =
+        (&state.gateway, raw_body, processing_output.event_type())
+    {
+        if !state.generic_provider_ids.contains(&provider) {
+            gateway.forward(&provider, event_type, event_id, &headers, raw_body);
+        }
+    }
+
     // Spawn async queue delivery — fire-and-forget in both modes.
-    if let ProcessingOutput::Wrapped(wrapped_event) = processing_output {
+    if let ProcessingOutput::Wrapped(mut wrapped_event) = processing_output {
+        wrapped_event.trace_parent = trace_parent;
+        state
+            .delivery_config
+            .delivery_index
+            .record_event(&github_delivery_id, &wrapped_event);
+        if let Some(stream) = &state.event_stream {
+            stream.publish_received(&wrapped_event);
+        }
+
+        // Replayed, synthetic and imported events do not feed intake baselines.
+        if let Some(profiler) = &state.payload_profiler {
+            if wrapped_event.origin.counts_toward_baselines() {
+                profiler.observe(&wrapped_event.event_type, &wrapped_event.payload, body_len);
+            }
+        }
+
         // Persist the wrapped event to blob storage so that /api/events queries
         // return real data. This is fire-and-forget: a storage failure does not
         // fail the webhook response — the event has already been enqueued for
-        // delivery. Storage errors are logged for investigation.
-        if let Some(ref blob_storage) = state.event_blob_storage {
+        // delivery. Storage errors are logged for investigation. Events whose
+        // payload storage sampling skipped are not persisted; those stored only
+        // on failure are kept until their delivery ends.
+        let store_on_failure = match &state.event_blob_storage {
+            Some(storage) if wrapped_event.payload_storage == PayloadStorage::OnFailure => {
+                Some((storage.clone(), wrapped_event.clone()))
+            }
+            _ => None,
+        };
+        if let Some(blob_storage) = state
+            .event_blob_storage
+            .as_ref()
+            .filter(|_| wrapped_event.payload_storage.is_stored())
+        {
             let event_to_persist = wrapped_event.clone();
             let storage = blob_storage.clone();
-            let persist_event_id = event_id;
             tokio::spawn(async move {
                 if let Err(e) =
                     store_wrapped_event_to_blob(storage.as_ref(), &event_to_persist).await
                 {
-                    warn!(
-                        event_id = %persist_event_id,
+                    event_warn!(
+                        event_to_persist,
                         error = %e,
                         "Failed to persist WrappedEvent to blob; \
                          event was delivered but will not appear in /api/events"
@@ -178,11 +332,45 @@ pub async fn handle_provider_webhook(
             });
         }
 
-        if let Some(queue_client) = &state.queue_client {
+        // Routed events are written to the outbox before the response, so
+        // their delivery survives a restart.
+        if review_queue.is_none() && state.queue_client.is_some() {
+            admit_to_outbox(&state, &wrapped_event).await;
+        }
+
+        if let Some(review_queue) = review_queue {
+            // Unknown event types in review mode bypass bot routing.
+            if let Some(queue_client) = &state.queue_client {
+                tokio::spawn(deliver_for_review(
+                    wrapped_event,
+                    queue_client.clone(),
+                    review_queue,
+                ));
+            }
+        } else if let (Some(queue_client), Some(dispatcher)) =
+            (&state.queue_client, &state.shard_dispatcher)
+        {
+            // Sticky shards run a session's deliveries one after another.
+            let key = shard_key(&wrapped_event);
+            let delivery = queue_delivery_task(
+                wrapped_event,
+                state.event_router.clone(),
+                state.bot_config.current(),
+                queue_client.clone(),
+                state.delivery_config.clone(),
+            );
+            dispatcher.dispatch(
+                &key,
+                Box::pin(async move {
+                    let outcome = delivery.await;
+                    persist_failed_delivery(store_on_failure, &outcome).await;
+                }),
+            );
+        } else if let Some(queue_client) = &state.queue_client {
             let handle = spawn_queue_delivery(
                 wrapped_event,
                 state.event_router.clone(),
-                state.bot_config.clone(),
+                state.bot_config.current(),
                 queue_client.clone(),
                 state.delivery_config.clone(),
             );
@@ -190,15 +378,17 @@ pub async fn handle_provider_webhook(
             // panics the JoinHandle will hold the panic payload until dropped.
             // Spawning a watcher task ensures the panic is surfaced in logs
             // rather than silently discarded, and allows tracing the event_id.
-            let logged_event_id = event_id;
+            let panic_context = log_context.clone();
             tokio::spawn(async move {
-                if let Err(join_err) = handle.await {
-                    if join_err.is_panic() {
-                        error!(
-                            event_id = %logged_event_id,
+                match handle.await {
+                    Ok(outcome) => persist_failed_delivery(store_on_failure, &outcome).await,
+                    Err(join_err) if join_err.is_panic() => {
+                        event_error!(
+                            panic_context,
                             "Queue delivery task panicked — event may not have been delivered"
                         );
                     }
+                    Err(_) => {}
                 }
             });
         }
@@ -209,37 +399,46 @@ pub async fn handle_provider_webhook(
     } = processing_output
     {
         match processing_output.direct_target_queue().map(str::to_owned) {
-            Some(queue_name_str) => match QueueName::new(queue_name_str.clone()) {
+            Some(queue_name_str) => match QueueName::new(queue_name_str) {
                 Ok(queue_name) => {
                     if let Some(queue_client) = &state.queue_client {
                         let queue_client = queue_client.clone();
-                        let logged_event_id = event_id;
-                        let message = Message::new(payload.clone())
+                        let delivery_context = log_context.clone();
+                        let mut message = Message::new(payload.clone())
                             .with_correlation_id(metadata.correlation_id().to_string());
+                        for (name, value) in direct_filter_attributes(metadata) {
+                            message = message.with_attribute(name.to_string(), value);
+                        }
+                        if let Some(trace_parent) = &trace_parent {
+                            message = message.with_attribute(
+                                TRACE_PARENT_ATTRIBUTE.to_string(),
+                                trace_parent.clone(),
+                            );
+                        }
                         let handle = tokio::spawn(async move {
                             match queue_client.send_message(&queue_name, message).await {
                                 Ok(message_id) => {
-                                    info!(
-                                        event_id = %logged_event_id,
+                                    event_info!(
+                                        delivery_context,
                                         message_id = %message_id,
                                         "Direct-mode payload delivered to queue"
                                     );
                                 }
                                 Err(e) => {
-                                    error!(
-                                        event_id = %logged_event_id,
+                                    event_error!(
+                                        delivery_context,
                                         error = %e,
                                         "Failed to deliver direct-mode payload to queue"
                                     );
                                 }
                             }
                         });
-                        let monitor_event_id = event_id;
+                        let panic_context = log_context.clone();
                         tokio::spawn(async move {
                             if let Err(join_err) = handle.await {
                                 if join_err.is_panic() {
-                                    error!(
-                                        event_id = %monitor_event_id,
+                                    event_error!(
+                                        panic_context,
                                         "Direct-mode queue delivery task panicked \
                                          — payload may not have been delivered"
                                     );
@@ -247,25 +446,23 @@ pub async fn handle_provider_webhook(
                             }
                         });
                     } else {
-                        warn!(
-                            event_id = %event_id,
-                            queue_name = %queue_name_str,
+                        event_warn!(
+                            log_context,
                             "Direct-mode event has no queue client configured — event not delivered"
                         );
                     }
                 }
                 Err(e) => {
-                    warn!(
-                        event_id = %event_id,
-                        queue_name = %queue_name_str,
+                    event_warn!(
+                        log_context,
                         error = %e,
                         "Direct-mode provider has invalid target_queue — event not delivered"
                     );
                 }
             },
             None => {
-                warn!(
-                    event_id = %event_id,
+                event_warn!(
+                    log_context,
                     "Direct-mode event has no target_queue configured — event not delivered"
                 );
             }
@@ -279,3 +476,267 @@ pub async fn handle_provider_webhook(
         message: "Webhook processed successfully".to_string(),
     }))
 }
+
+/// Parse and check the request headers for `provider`.
+///
+/// Returns the parsed headers together with the full header map (lowercase
+/// names) for providers that read non-GitHub headers.
+fn parse_headers(
+    state: &AppState,
+    provider: &str,
+    headers: &HeaderMap,
+) -> Result<(WebhookHeaders, HashMap<String, String>), WebhookHandlerError> {
+    // Convert headers to HashMap (lowercase keys for consistent lookup)
+    let header_map: HashMap<String, String> = headers
+        .iter()
+        .map(|(k, v)| {
+            (
+                k.as_str().to_lowercase(),
+                v.to_str().unwrap_or("").to_string(),
+            )
+        })
+        .collect();
+
+    // Determine whether this is a generic (non-GitHub) provider.
+    // Generic providers do not send GitHub-specific headers, so we use a
+    // relaxed parser that falls back to safe defaults instead of failing.
+    //
+    // The set is pre-built at startup (O(1) lookup here vs. O(n) scan).
+    //
+    // Note: generic providers do not support `allowed_event_types` filtering
+    // — all event types are accepted regardless of configuration. If you need
+    // per-event filtering for a generic provider, implement it downstream.
+    let is_generic_provider = state.generic_provider_ids.contains(provider);
+
+    let webhook_headers = if is_generic_provider {
+        // For generic providers, never fail on missing GitHub headers — the
+        // provider's own process_webhook will re-extract values from raw headers.
+        WebhookHeaders::from_http_headers_relaxed(&header_map)
+    } else {
+        // For GitHub and other strict providers, require all GitHub-specific headers.
+        match WebhookHeaders::from_http_headers(&header_map) {
+            Ok(h) => h,
+            Err(e) => return Err(WebhookHandlerError::InvalidHeaders(e)),
+        }
+    };
+
+    // Enforce per-provider allowed_event_types if configured.
+    // An empty list means all event types are accepted.
+    //
+    // Note: require_signature enforcement is delegated to the processor's
+    // SignatureValidator. When a SignatureValidator is wired into the
+    // DefaultWebhookProcessor it will reject requests with an invalid or
+    // missing signature regardless of the ProviderConfig setting.
+    let provider_config = state.config.providers.iter().find(|p| p.id == provider);
+    if let Some(pc) = provider_config {
+        if !pc.allowed_event_types.is_empty()
+            && !pc.allowed_event_types.contains(&webhook_headers.event_type)
+        {
+            return Err(WebhookHandlerError::InvalidHeaders(
+                queue_keeper_core::ValidationError::InvalidFormat {
+                    // Use a provider-neutral field name so non-GitHub providers
+                    // receive a sensible error rather than a GitHub header name.
+                    field: "event-type".to_string(),
+                    message: format!(
+                        "event type '{}' is not in the allowed list for provider '{}'",
+                        webhook_headers.event_type, provider
+                    ),
+                },
+            ));
+        }
+    }
+
+    Ok((webhook_headers, header_map))
+}
+
+/// Count and log a GitHub delivery whose event type is not in the
+/// known-types registry.
+///
+/// Returns the configured handling for an unknown event type, or `None` for
+/// known types and generic providers.
+fn unknown_event_type_mode(
+    state: &AppState,
+    provider: &str,
+    headers: &WebhookHeaders,
+) -> Option<UnknownEventTypeMode> {
+    let config = &state.config.unknown_event_types;
+    if state.generic_provider_ids.contains(provider) || config.is_known(&headers.event_type) {
+        return None;
+    }
+    state
+        .metrics
+        .unknown_event_types_total
+        .with_label_values(&[provider, headers.event_type.as_str(), config.mode.as_str()])
+        .inc();
+    warn!(
+        provider = %provider,
+        event_type = %headers.event_type,
+        mode = config.mode.as_str(),
+        "Received webhook with an event type missing from the known-types registry"
+    );
+    Some(config.mode)
+}
+
+/// Provider that handles a delivery addressed to `provider`.
+///
+/// A delivery announcing a GitHub Enterprise host is handed to the provider
+/// configured for that host when `provider` is a GitHub provider without
+/// `github_enterprise` settings, so it is checked against that instance's
+/// webhook secret and installation registry. Every other delivery stays
+/// with `provider`; [`check_source`] rejects those from unexpected hosts.
+fn resolve_enterprise_provider(state: &AppState, provider: String, headers: &HeaderMap) -> String {
+    let Some(host) = headers
+        .get(ENTERPRISE_HOST_HEADER)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return provider;
+    };
+    let addressed_enterprise = state
+        .config
+        .providers
+        .iter()
+        .any(|p| p.id == provider && p.github_enterprise.is_some());
+    if addressed_enterprise || state.generic_provider_ids.contains(&provider) {
+        return provider;
+    }
+
+    match state.config.enterprise_provider(host) {
+        Some(pc) if state.provider_registry.contains(&pc.id) => {
+            debug!(
+                provider = %provider,
+                resolved_provider = %pc.id,
+                enterprise_host = %host.trim(),
+                "Resolved GitHub Enterprise delivery to the provider of its host"
+            );
+            pc.id.clone()
+        }
+        _ => provider,
+    }
+}
+
+/// Check that a delivery comes from the source configured for `provider`.
+///
+/// Providers with `github_enterprise` settings only accept deliveries whose
+/// `X-GitHub-Enterprise-Host` header names their instance; other providers
+/// reject deliveries carrying the header. A non-empty `installation_ids`
+/// registry rejects installations it does not list. Providers without a
+/// configuration entry are not checked.
+fn check_source(
+    state: &AppState,
+    provider: &str,
+    header_map: &HashMap<String, String>,
+    body: &[u8],
+) -> Result<(), WebhookHandlerError> {
+    let Some(pc) = state.config.providers.iter().find(|p| p.id == provider) else {
+        return Ok(());
+    };
+    let rejected = |reason: String| WebhookHandlerError::SourceRejected {
+        provider: provider.to_string(),
+        reason,
+    };
+
+    let sent_host = header_map
+        .get(ENTERPRISE_HOST_HEADER)
+        .map(|h| h.trim().to_ascii_lowercase());
+    match (&pc.github_enterprise, sent_host) {
+        (Some(enterprise), Some(host)) if host != enterprise.expected_host() => {
+            return Err(rejected(format!(
+                "enterprise host '{}' does not match '{}'",
+                host,
+                enterprise.expected_host()
+            )));
+        }
+        (Some(_), None) => {
+            return Err(rejected(
+                "missing X-GitHub-Enterprise-Host header".to_string(),
+            ));
+        }
+        (None, Some(host)) => {
+            return Err(rejected(format!(
+                "deliveries from enterprise host '{}' are not accepted",
+                host
+            )));
+        }
+        _ => {}
+    }
+
+    if !pc.installation_ids.is_empty() {
+        let installation_id = installation_id(body);
+        if !pc.accepts_installation(installation_id) {
+            return Err(rejected(match installation_id {
+                Some(id) => format!("installation {} is not registered", id),
+                None => "delivery has no installation".to_string(),
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+/// `installation.id` of a GitHub payload, if present.
+fn installation_id(body: &[u8]) -> Option<u64> {
+    #[derive(serde::Deserialize)]
+    struct Payload {
+        installation: Option<Installation>,
+    }
+    #[derive(serde::Deserialize)]
+    struct Installation {
+        id: u64,
+    }
+
+    serde_json::from_slice::<Payload>(body)
+        .ok()?
+        .installation
+        .map(|i| i.id)
+}
+
+/// Record the stages a request reached in the per-stage histograms.
+fn record_stage_timings(state: &AppState, timings: &StageTimings) {
+    state.metrics.record_webhook_stages(timings);
+}
+
+/// Record `event` and its target bots in the outbox, when one is enabled.
+///
+/// A failed write does not fail the request: deduplication has already
+/// claimed the delivery ID, so a retry by the provider would be skipped.
+/// The event is delivered without an outbox entry instead.
+async fn admit_to_outbox(state: &AppState, event: &WrappedEvent) {
+    let Some(outbox) = &state.delivery_config.outbox else {
+        return;
+    };
+    let bot_config = state.bot_config.current();
+    let bots = bot_config.get_target_bots(event);
+    if let Err(e) = outbox.admit(event, &bots).await {
+        state.metrics.outbox_write_failures_total.inc();
+        event_error!(
+            event,
+            error = %e,
+            "Failed to write outbox entry; delivering the event without it"
+        );
+    }
+}
+
+/// Persist an event whose payload storage sampling deferred to delivery
+/// failure, if its delivery failed.
+///
+/// `pending` is `None` when the event was already persisted on receipt or
+/// no event storage is configured.
+async fn persist_failed_delivery(
+    pending: Option<(Arc<dyn BlobStorage>, WrappedEvent)>,
+    outcome: &QueueDeliveryOutcome,
+) {
+    let Some((storage, event)) = pending else {
+        return;
+    };
+    if !outcome.has_failures() {
+        return;
+    }
+    match store_wrapped_event_to_blob(storage.as_ref(), &event).await {
+        Ok(()) => event_info!(event, "Persisted WrappedEvent after failed delivery"),
+        Err(e) => event_warn!(
+            event,
+            error = %e,
+            "Failed to persist WrappedEvent after failed delivery"
+        ),
+    }
+}
@@ -11,59 +11,155 @@
 //! See specs/interfaces/http-service.md for complete specification.
 
 // Public modules
+pub mod authorization;
 pub mod azure_config;
+pub mod bot_pause;
 pub mod config;
+pub mod deduplication;
+pub mod delivery_audit;
+pub mod delivery_index;
+pub mod developer;
 pub mod dlq_storage;
 pub mod errors;
+pub mod escalation;
+pub mod event_deletion;
+pub mod event_replay;
+pub mod event_stream;
+pub mod freshness;
+pub mod gateway;
+pub mod github_app;
 pub mod handlers;
+pub mod log_filter;
 pub mod metrics;
 pub mod middleware;
+pub mod outbound;
+pub mod outbox;
+pub mod payload_profiler;
 pub mod provider_registry;
+pub mod queue_batching;
 pub mod queue_delivery;
+pub mod queue_depth;
+pub mod rate_limit_store;
+pub mod read_only;
+pub mod repository_rate_limit;
+pub mod request_rate_limit;
 pub mod responses;
 pub mod retry;
-
+pub mod retry_budget;
+pub mod selftest;
+pub mod session_epochs;
+pub mod session_shards;
+pub mod shutdown_report;
+pub mod startup_report;
+pub mod statistics;
+pub mod stuck_sessions;
+pub mod supervisor;
+pub mod telemetry;
+pub mod token_cache;
+pub mod unknown_event_types;
+
+use crate::dlq_storage::DlqArchiveError;
 use crate::queue_delivery::QueueDeliveryConfig;
 use axum::{
-    extract::{Path, Query, State},
+    extract::{DefaultBodyLimit, Path, Query, State},
     http::StatusCode,
     response::{IntoResponse, Json, Response},
-    routing::{get, post, put},
-    Router,
+    routing::{delete, get, post, put},
+    Extension, Router,
 };
+use github_bot_sdk::auth::AuthenticationProvider;
 use prometheus::TextEncoder;
 use queue_keeper_core::{
+    audit_logging::{
+        AuditAction, AuditContext, AuditLogger, AuditResource, AuditResult, StdoutAuditLogger,
+    },
     blob_storage::BlobStorage,
-    bot_config::BotConfiguration,
+    bot_config::{BotConfigError, BotConfiguration, SharedBotConfiguration},
+    circuit_breaker::CircuitBreakerGroup,
+    event_replay::{EventReplayService, EventRetriever, ReplayError, ReplayOutcome, ReplayRequest},
+    field_encryption::FieldEncryptor,
+    http_delivery::HttpForwarder,
+    key_vault::KeyVaultProvider,
+    payload_retention::PayloadRetentionSweeper,
+    pubsub::{subscription_rules, TopicEventRouter},
     queue_integration::{DefaultEventRouter, EventRouter},
-    EventId, QueueKeeperError, SessionId,
+    session_control::SessionPauseStatus,
+    session_tracker::{SessionTracker, TrackedEventStatus},
+    webhook::DeduplicationStore,
+    BotName, EventId, QueueKeeperError, SessionId, TransformError, TransformRegistry,
+    TransformStatus,
 };
 use queue_runtime::QueueClient;
 use serde_json::json;
 use std::{
     collections::{HashMap, HashSet},
     net::SocketAddr,
-    sync::Arc,
-    time::Duration,
+    sync::{Arc, OnceLock},
 };
 use tower::ServiceBuilder;
-use tower_http::{compression::CompressionLayer, cors::CorsLayer, trace::TraceLayer};
+use tower_http::{
+    compression::CompressionLayer,
+    cors::CorsLayer,
+    trace::{DefaultMakeSpan, MakeSpan, TraceLayer},
+};
 use tracing::{error, info, instrument, warn};
 
 // Re-export public types
+pub use authorization::{AdminPrincipal, AdminRole, RouteGroup};
 pub use azure_config::{
-    AzureBlobStorageConfig, AzureConfigError, AzureKeyVaultConfig, AzureProductionConfig,
-    AzureServiceBusConfig, AzureTelemetryConfig,
+    AzureBlobStorageConfig, AzureCloud, AzureConfigError, AzureKeyVaultConfig,
+    AzureProductionConfig, AzureServiceBusConfig, AzureTelemetryConfig,
 };
+pub use bot_pause::{BotPauseRegistry, BotPauseState, BotPauseStatus};
 pub use config::{
-    LoggingConfig, ProviderConfig, ProviderSecretConfig, QueueBackendConfig, SecurityConfig,
-    ServerConfig, ServiceConfig, WebhookConfig,
+    AdditionalSecretConfig, AdminPrincipalConfig, AwsEndpointConfig, AzureEndpointConfig,
+    EndpointConfig, GitHubEnterpriseConfig, GitHubSignatureScheme, LoggingConfig, NetworkConfig,
+    ProviderConfig, ProviderSecretConfig, ProxyConfig, QueueBackendConfig, RateLimitWindow,
+    RateLimiterStoreConfig, RedactedServiceConfig, SecurityConfig, ServerConfig, ServiceConfig,
+    WebhookConfig,
 };
+pub use deduplication::{DeduplicationConfig, DeduplicationStoreConfig};
+pub use delivery_audit::{DeliveryAuditConfig, DeliveryAuditRecord, DeliveryAuditor};
+pub use delivery_index::{DeliveryCorrelation, DeliveryIndex};
+pub use developer::{DeveloperConfig, DeveloperTools, RecentEvent};
 pub use errors::{ConfigError, ServiceError, WebhookHandlerError};
+pub use escalation::{EscalationConfig, EscalationMonitor};
+pub use event_deletion::{
+    EventDeletionConfig, EventDeletionError, EventPurger, EventTombstone, EventTombstones,
+};
+pub use event_replay::{BlobEventRetriever, PipelineReplayExecutor, PipelineReplayService};
+pub use event_stream::{EventStream, EventStreamConfig, StreamedEvent, StreamedEventStatus};
+pub use freshness::{FreshnessConfig, FreshnessMonitor, FreshnessReport};
+pub use gateway::{GatewayConfig, ReqwestTransport, WebhookGateway};
+pub use github_app::{build_github_app_auth, GitHubAppConfig};
+pub use log_filter::LogFilter;
 pub use metrics::{ServiceMetrics, TelemetryConfig};
 pub use middleware::{IpFailureTracker, IpTier};
+pub use outbox::{Outbox, OutboxConfig, OutboxDispatcher, OutboxEntry, OutboxError};
+pub use payload_profiler::{PayloadProfiler, PayloadProfilerConfig, PayloadStatsResponse};
 pub use provider_registry::{InvalidProviderIdError, ProviderId, ProviderRegistry};
+pub use queue_batching::{BatchingQueueClient, QueueBatchingConfig};
+pub use queue_depth::{QueueDepthConfig, QueueDepthSampler, QueueDepthSource};
+pub use rate_limit_store::{RateLimiterStore, RateLimiterStoreError};
+pub use read_only::{ReadOnlyMode, ReadOnlyStatus};
+pub use repository_rate_limit::RepositoryRateLimiter;
+pub use request_rate_limit::RequestRateLimiter;
 pub use responses::*;
+pub use retry::RetryConfig;
+pub use retry_budget::{RetryBudget, RetryBudgetConfig};
+pub use selftest::{SelfTest, SelfTestReport};
+pub use session_shards::{DeliveryWorkerConfig, SessionShardDispatcher};
+pub use shutdown_report::{ExitReason, ShutdownRecorder, ShutdownReport, ShutdownReportConfig};
+pub use startup_report::{PreflightStatus, RuntimeEnvironment, StartupReport, ValueSource};
+pub use statistics::StatisticsCollector;
+pub use stuck_sessions::StuckSessionDetector;
+pub use supervisor::{JobContext, JobState, JobStatus, Supervisor, SupervisorConfig};
+pub use telemetry::{OtlpProtocol, TelemetryExportConfig, TraceSampler};
+pub use token_cache::{
+    InstallationToken, TokenCache, TokenCacheConfig, TokenCacheError, TokenCacheStore,
+    TokenCacheStoreConfig,
+};
+pub use unknown_event_types::{UnknownEventTypeMode, UnknownEventTypesConfig};
 
 // Re-export handlers that are referenced by integration tests or external code.
 pub use handlers::webhook::handle_provider_webhook;
@@ -90,14 +186,33 @@ pub struct AppState {
     /// Metrics collector for observability
     pub metrics: Arc<ServiceMetrics>,
 
+    /// Webhook and replay counters served at `/api/stats`.
+    ///
+    /// Created by [`AppState::new`] and shared with the replay executor.
+    pub statistics: Arc<StatisticsCollector>,
+
     /// OpenTelemetry configuration for tracing
     pub telemetry_config: Arc<TelemetryConfig>,
 
+    /// Sampler of exported traces, changed by `PUT /admin/tracing/sampling`.
+    ///
+    /// Starts at `telemetry_config.sampling_ratio`. Set via
+    /// [`AppState::with_trace_sampler`] to share the sampler installed in
+    /// the tracer provider.
+    pub trace_sampler: TraceSampler,
+
+    /// Log filter, changed by `PUT /admin/logging/level`.
+    ///
+    /// Detached from any subscriber until set via
+    /// [`AppState::with_log_filter`] with the service's reload handle.
+    pub log_filter: LogFilter,
+
     /// Set of provider IDs that are generic (non-GitHub) providers.
     ///
-    /// Pre-built at startup from [`ServiceConfig::generic_providers`] to enable
-    /// O(1) lookup in the hot request path instead of scanning the full list
-    /// on every webhook request.
+    /// Pre-built at startup from [`ServiceConfig::generic_providers`] and
+    /// [`ServiceConfig::webhook_sources`] to enable O(1) lookup in the hot
+    /// request path instead of scanning the full list on every webhook
+    /// request.
     pub generic_provider_ids: Arc<HashSet<String>>,
 
     /// Queue client for delivering events to bot queues.
@@ -111,15 +226,40 @@ pub struct AppState {
     pub event_router: Arc<dyn EventRouter>,
 
     /// Bot subscription configuration defining which bots receive which events.
-    pub bot_config: Arc<BotConfiguration>,
+    ///
+    /// Replaced at runtime by the configuration watcher and
+    /// `PUT /admin/bots/config`; take one [`SharedBotConfiguration::current`]
+    /// snapshot per event.
+    pub bot_config: Arc<SharedBotConfiguration>,
 
     /// Configuration for queue delivery retry and DLQ behaviour.
     pub delivery_config: QueueDeliveryConfig,
 
     /// IP-based authentication failure rate limiter.
     ///
-    /// `None` when `SecurityConfig::enable_ip_rate_limiting = false`.
-    pub ip_rate_limiter: Option<Arc<IpFailureTracker>>,
+    /// `None` when `SecurityConfig::enable_ip_rate_limiting = false`. The
+    /// backend is selected by `SecurityConfig::rate_limiter_store`.
+    pub ip_rate_limiter: Option<Arc<dyn RateLimiterStore>>,
+
+    /// Global and per-IP request rate limiter for the webhook, API and admin
+    /// routes.
+    ///
+    /// `None` disables request rate limiting. Set via
+    /// [`AppState::with_request_rate_limiter`].
+    pub request_rate_limiter: Option<Arc<RequestRateLimiter>>,
+
+    /// Per-repository webhook rate limiter (`webhooks.rate_limit_per_repo`).
+    ///
+    /// `None` disables per-repository limits. Set via
+    /// [`AppState::with_repository_rate_limiter`].
+    pub repository_rate_limiter: Option<Arc<RepositoryRateLimiter>>,
+
+    /// Delivery deduplication store shared with the webhook processors.
+    ///
+    /// Deliveries the handler rejects after processing are released from
+    /// it, so a redelivery is processed. `None` when deduplication is
+    /// disabled. Set via [`AppState::with_deduplication`].
+    pub deduplication: Option<Arc<dyn DeduplicationStore>>,
 
     /// Admin API key for authenticated admin endpoints.
     ///
@@ -135,6 +275,78 @@ pub struct AppState {
     /// can return real data. When `None`, those endpoints return empty results
     /// (development / testing mode with no storage configured).
     pub event_blob_storage: Option<Arc<dyn BlobStorage>>,
+
+    /// Audit logger recording admin API actions with the acting principal.
+    ///
+    /// `None` disables admin audit entries (the default for tests). Set via
+    /// [`AppState::with_audit_logger`].
+    pub audit_logger: Option<Arc<dyn AuditLogger>>,
+
+    /// Read-only switch that rejects mutating admin operations while enabled.
+    pub read_only: Arc<ReadOnlyMode>,
+
+    /// Delivery shards preserving per-session order.
+    ///
+    /// `None` spawns one delivery task per event. Set via
+    /// [`AppState::with_shard_dispatcher`].
+    pub shard_dispatcher: Option<SessionShardDispatcher>,
+
+    /// Per-bot WebAssembly transforms, shared with the event router.
+    ///
+    /// `None` when no bot configures a transform. Set via
+    /// [`AppState::with_transforms`].
+    pub transforms: Option<Arc<TransformRegistry>>,
+
+    /// Gateway destinations receiving re-signed raw deliveries.
+    ///
+    /// `None` when no gateway is configured. Set via
+    /// [`AppState::with_gateway`].
+    pub gateway: Option<Arc<WebhookGateway>>,
+
+    /// GitHub App authentication, handing out cached installation tokens.
+    ///
+    /// `None` when no `github_app` section is configured. Set via
+    /// [`AppState::with_github_auth`].
+    pub github_auth: Option<Arc<dyn AuthenticationProvider>>,
+
+    /// Report of how the service started, served at `/debug/startup-report`.
+    ///
+    /// `None` when the binary did not produce one. Set via
+    /// [`AppState::with_startup_report`].
+    pub startup_report: Option<Arc<StartupReport>>,
+
+    /// Report of how the previous process stopped, served at
+    /// `/debug/last-shutdown`.
+    ///
+    /// `None` when no report was found. Set via
+    /// [`AppState::with_last_shutdown_report`].
+    pub last_shutdown_report: Option<Arc<ShutdownReport>>,
+
+    /// Payload field statistics profiler.
+    ///
+    /// `None` unless `payload_profiler.enabled` is set. Set via
+    /// [`AppState::with_payload_profiler`].
+    pub payload_profiler: Option<Arc<PayloadProfiler>>,
+
+    /// Sweeper archiving or deleting expired payloads, reported by
+    /// `GET /admin/storage/retention`.
+    ///
+    /// `None` unless `payload_retention.enabled` is set. Set via
+    /// [`AppState::with_payload_retention`].
+    pub payload_retention: Option<Arc<PayloadRetentionSweeper>>,
+
+    /// Broadcaster of event summaries to `GET /api/events/stream`.
+    ///
+    /// `None` unless `event_stream.enabled` is set. The same broadcaster is
+    /// shared with the delivery configuration. Set via
+    /// [`AppState::with_event_stream`].
+    pub event_stream: Option<Arc<EventStream>>,
+
+    /// Replays stored events for `POST /admin/events/{event_id}/replay`.
+    ///
+    /// Built from the event blob storage, router, bot configuration, queue
+    /// client and delivery configuration passed to [`AppState::new`].
+    pub replay_service: Arc<dyn EventReplayService>,
 }
 
 impl AppState {
@@ -150,18 +362,39 @@ impl AppState {
         generic_provider_ids: HashSet<String>,
         queue_client: Option<Arc<dyn QueueClient>>,
         event_router: Arc<dyn EventRouter>,
-        bot_config: Arc<BotConfiguration>,
+        bot_config: Arc<SharedBotConfiguration>,
         delivery_config: QueueDeliveryConfig,
-        ip_rate_limiter: Option<Arc<IpFailureTracker>>,
+        ip_rate_limiter: Option<Arc<dyn RateLimiterStore>>,
         admin_api_key: Option<String>,
         event_blob_storage: Option<Arc<dyn BlobStorage>>,
     ) -> Self {
+        let statistics = Arc::new(StatisticsCollector::new());
+        let replay_executor = PipelineReplayExecutor::new(
+            event_router.clone(),
+            bot_config.clone(),
+            queue_client.clone(),
+            delivery_config.clone(),
+            event_blob_storage.clone(),
+        )
+        .with_statistics(statistics.clone());
+        let replay_service = PipelineReplayService::new(
+            event_blob_storage.clone().map(|storage| {
+                Arc::new(BlobEventRetriever::new(storage)) as Arc<dyn EventRetriever>
+            }),
+            Arc::new(replay_executor),
+        );
+
         Self {
             config,
             provider_registry,
             health_checker,
             event_store,
             metrics,
+            statistics,
+            trace_sampler: TraceSampler::new(telemetry_config.sampling_ratio),
+            log_filter: LogFilter::detached(&log_filter::level_directives(
+                &telemetry_config.log_level,
+            )),
             telemetry_config,
             generic_provider_ids: Arc::new(generic_provider_ids),
             queue_client,
@@ -169,10 +402,115 @@ impl AppState {
             bot_config,
             delivery_config,
             ip_rate_limiter,
+            request_rate_limiter: None,
+            repository_rate_limiter: None,
+            deduplication: None,
             admin_api_key,
             event_blob_storage,
+            audit_logger: None,
+            read_only: Arc::new(ReadOnlyMode::new()),
+            shard_dispatcher: None,
+            transforms: None,
+            gateway: None,
+            github_auth: None,
+            startup_report: None,
+            last_shutdown_report: None,
+            payload_profiler: None,
+            payload_retention: None,
+            event_stream: None,
+            replay_service: Arc::new(replay_service),
         }
     }
+
+    /// Attach an audit logger for admin API actions.
+    pub fn with_audit_logger(mut self, audit_logger: Arc<dyn AuditLogger>) -> Self {
+        self.audit_logger = Some(audit_logger);
+        self
+    }
+
+    /// Deliver events through sticky session shards.
+    pub fn with_shard_dispatcher(mut self, dispatcher: SessionShardDispatcher) -> Self {
+        self.shard_dispatcher = Some(dispatcher);
+        self
+    }
+
+    /// Expose the transforms applied by the event router to the admin API.
+    pub fn with_transforms(mut self, transforms: Arc<TransformRegistry>) -> Self {
+        self.transforms = Some(transforms);
+        self
+    }
+
+    /// Forward verified deliveries to gateway destinations.
+    pub fn with_gateway(mut self, gateway: Arc<WebhookGateway>) -> Self {
+        self.gateway = Some(gateway);
+        self
+    }
+
+    /// Authenticate to the GitHub API as the configured App.
+    pub fn with_github_auth(mut self, github_auth: Arc<dyn AuthenticationProvider>) -> Self {
+        self.github_auth = Some(github_auth);
+        self
+    }
+
+    /// Serve the startup report at `/debug/startup-report`.
+    pub fn with_startup_report(mut self, report: Arc<StartupReport>) -> Self {
+        self.startup_report = Some(report);
+        self
+    }
+
+    /// Serve the previous process's shutdown report at `/debug/last-shutdown`.
+    pub fn with_last_shutdown_report(mut self, report: Arc<ShutdownReport>) -> Self {
+        self.last_shutdown_report = Some(report);
+        self
+    }
+
+    /// Sample processed payloads into `/api/stats/payloads`.
+    pub fn with_payload_profiler(mut self, profiler: Arc<PayloadProfiler>) -> Self {
+        self.payload_profiler = Some(profiler);
+        self
+    }
+
+    /// Report the payload retention sweeper at `/admin/storage/retention`.
+    pub fn with_payload_retention(mut self, sweeper: Arc<PayloadRetentionSweeper>) -> Self {
+        self.payload_retention = Some(sweeper);
+        self
+    }
+
+    /// Broadcast event summaries to `GET /api/events/stream`.
+    pub fn with_event_stream(mut self, stream: Arc<EventStream>) -> Self {
+        self.event_stream = Some(stream);
+        self
+    }
+
+    /// Sample exported traces with the sampler of the tracer provider.
+    pub fn with_trace_sampler(mut self, sampler: TraceSampler) -> Self {
+        self.trace_sampler = sampler;
+        self
+    }
+
+    /// Change the log filter installed in the service's subscriber.
+    pub fn with_log_filter(mut self, filter: LogFilter) -> Self {
+        self.log_filter = filter;
+        self
+    }
+
+    /// Limit the request rate of the webhook, API and admin routes.
+    pub fn with_request_rate_limiter(mut self, limiter: Arc<RequestRateLimiter>) -> Self {
+        self.request_rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Limit the webhooks accepted per repository.
+    pub fn with_repository_rate_limiter(mut self, limiter: Arc<RepositoryRateLimiter>) -> Self {
+        self.repository_rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Release deliveries rejected after processing from `store`.
+    pub fn with_deduplication(mut self, store: Arc<dyn DeduplicationStore>) -> Self {
+        self.deduplication = Some(store);
+        self
+    }
 }
 
 // ============================================================================
@@ -181,11 +519,24 @@ impl AppState {
 
 /// Create HTTP router with all endpoints
 pub fn create_router(state: AppState) -> Router {
+    // Webhook bodies are limited to `server.max_body_size` by
+    // `body_limit_middleware`, which replaces axum's default 2 MB limit.
+    let max_body_size =
+        usize::try_from(state.config.server.max_body_size.as_u64()).unwrap_or(usize::MAX);
     let webhook_routes = Router::new()
         .route(
             "/webhook/{provider}",
             post(handlers::webhook::handle_provider_webhook),
         )
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            crate::middleware::body_limit_middleware,
+        ))
+        .route_layer(DefaultBodyLimit::max(max_body_size))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            crate::middleware::request_rate_limit_middleware,
+        ))
         .route_layer(axum::middleware::from_fn_with_state(
             state.clone(),
             crate::middleware::ip_rate_limit_middleware,
@@ -202,47 +553,102 @@ pub fn create_router(state: AppState) -> Router {
 
     let api_routes = Router::new()
         .route("/api/events", get(list_events))
+        .route("/api/events/stream", get(stream_events))
         .route("/api/events/{event_id}", get(get_event))
+        .route("/api/events/{event_id}/lineage", get(get_event_lineage))
+        .route(
+            "/api/deliveries/{github_delivery_id}",
+            get(get_delivery_correlation),
+        )
         .route("/api/sessions", get(list_sessions))
+        .route("/api/sessions/stuck", get(list_stuck_sessions))
         .route("/api/sessions/{session_id}", get(get_session))
-        .route("/api/stats", get(get_statistics));
+        .route("/api/stats", get(get_statistics))
+        .route("/api/stats/payloads", get(get_payload_statistics))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            crate::middleware::request_rate_limit_middleware,
+        ));
 
     let observability_routes = Router::new()
         .route("/metrics", get(metrics_endpoint))
         .route("/debug/pprof", get(debug_profile))
-        .route("/debug/vars", get(debug_vars));
+        .route("/debug/vars", get(debug_vars))
+        .route("/debug/startup-report", get(debug_startup_report))
+        .route("/debug/last-shutdown", get(debug_last_shutdown));
 
     let admin_routes = Router::new()
+        .route("/admin/events/{event_id}", delete(delete_event))
         .route("/admin/events/{event_id}/replay", post(replay_event))
+        .route("/admin/sessions/paused", get(list_paused_sessions))
+        .route("/admin/sessions/{session_id}/pause", post(pause_session))
+        .route("/admin/sessions/{session_id}/resume", post(resume_session))
         .route("/admin/sessions/{session_id}/reset", post(reset_session))
+        .route(
+            "/admin/dlq/archives/{bundle_id}/restore",
+            post(restore_dlq_archive),
+        )
         .route("/admin/config", get(get_config))
         .route("/admin/logging/level", get(get_log_level))
         .route("/admin/logging/level", put(set_log_level))
         .route("/admin/tracing/sampling", get(get_trace_sampling))
         .route("/admin/tracing/sampling", put(set_trace_sampling))
         .route("/admin/metrics/reset", post(reset_metrics))
+        .route("/admin/readonly", get(get_read_only))
+        .route("/admin/readonly", put(set_read_only))
+        .route("/admin/transforms", get(list_transforms))
+        .route("/admin/transforms/{bot_name}", put(set_transform_enabled))
+        .route("/admin/bots/config", put(set_bot_config))
+        .route("/admin/bots/paused", get(list_paused_bots))
+        .route("/admin/bots/{bot_name}/pause", post(pause_bot))
+        .route("/admin/bots/{bot_name}/resume", post(resume_bot))
+        .route("/admin/selftest", post(run_selftest))
+        .route("/admin/audit/deliveries", get(list_delivery_audit_records))
+        .route("/admin/storage/retention", get(get_payload_retention))
         .route_layer(axum::middleware::from_fn_with_state(
             state.clone(),
             crate::middleware::admin_auth_middleware,
         ))
+        .route_layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            crate::middleware::request_rate_limit_middleware,
+        ))
         .route_layer(axum::middleware::from_fn_with_state(
             state.clone(),
             crate::middleware::ip_rate_limit_middleware,
         ));
 
+    // Developer routes only exist in developer mode.
+    let dev_routes = if state.delivery_config.dev_tools.is_some() {
+        Router::new().route("/dev/last-events", get(list_last_events))
+    } else {
+        Router::new()
+    };
+
     Router::new()
         .merge(webhook_routes)
         .merge(health_routes)
         .merge(api_routes)
         .merge(observability_routes)
         .merge(admin_routes)
+        .merge(dev_routes)
         .layer(
             ServiceBuilder::new()
-                .layer(TraceLayer::new_for_http())
+                .layer(TraceLayer::new_for_http().make_span_with(
+                    |request: &axum::extract::Request| {
+                        // Continue the caller's trace when it sent a traceparent
+                        let span = DefaultMakeSpan::new().make_span(request);
+                        telemetry::set_parent_from_headers(&span, request.headers());
+                        span
+                    },
+                ))
                 .layer(CompressionLayer::new())
                 .layer(CorsLayer::permissive())
                 .layer(axum::middleware::from_fn(request_logging_middleware))
-                .layer(axum::middleware::from_fn(metrics_middleware))
+                .layer(axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    metrics_middleware,
+                ))
                 .into_inner(),
         )
         .with_state(state)
@@ -257,8 +663,18 @@ pub async fn start_server(
     event_store: Arc<dyn EventStore>,
     generic_provider_ids: HashSet<String>,
     queue_client: Option<Arc<dyn QueueClient>>,
-    bot_config: Arc<BotConfiguration>,
+    bot_config: Arc<SharedBotConfiguration>,
     event_blob_storage: Option<Arc<dyn BlobStorage>>,
+    key_vault: Option<Arc<dyn KeyVaultProvider>>,
+    freshness: Option<Arc<FreshnessMonitor>>,
+    supervisor: Arc<Supervisor>,
+    startup_report: Option<Arc<StartupReport>>,
+    shutdown_recorder: Option<Arc<ShutdownRecorder>>,
+    trace_sampler: Option<TraceSampler>,
+    log_filter: Option<LogFilter>,
+    queue_depth_source: Option<Arc<dyn QueueDepthSource>>,
+    queue_circuits: Option<Arc<dyn CircuitBreakerGroup>>,
+    deduplication: Option<Arc<dyn DeduplicationStore>>,
 ) -> Result<(), ServiceError> {
     // Validate configuration before initializing any infrastructure
     config.validate().map_err(ServiceError::Configuration)?;
@@ -266,7 +682,10 @@ pub async fn start_server(
     // Warn when literal secrets are present — they should only be used in
     // development or testing, never in production deployments.
     for provider in &config.providers {
-        if let Some(config::ProviderSecretConfig::Literal { .. }) = &provider.secret {
+        if provider
+            .secret_sources()
+            .any(|s| matches!(s, config::ProviderSecretConfig::Literal { .. }))
+        {
             warn!(
                 provider = %provider.id,
                 "Provider is configured with a literal webhook secret. \
@@ -283,6 +702,75 @@ pub async fn start_server(
         })
     })?;
 
+    // Background jobs run under the supervisor, which restarts them when
+    // they fail and stops them after the server has drained.
+    supervisor.attach_metrics(metrics.clone());
+
+    // Queue circuit breakers report their states as `circuit_breaker_state`.
+    if let Some(circuits) = &queue_circuits {
+        circuits.attach_metrics(metrics.clone());
+    }
+
+    // The shutdown report checkpoints while running so that a crash still
+    // leaves a report for the next start.
+    if let Some(recorder) = &shutdown_recorder {
+        recorder.attach_metrics(metrics.clone());
+        let recorder = recorder.clone();
+        supervisor.spawn("shutdown_report_checkpoint", move |mut ctx| {
+            let recorder = recorder.clone();
+            async move {
+                tokio::select! {
+                    _ = recorder.run_checkpoints() => {}
+                    _ = ctx.stopped() => {}
+                }
+                Ok(())
+            }
+        });
+    }
+    if let Some(freshness) = &freshness {
+        let freshness = freshness.clone();
+        bot_config.on_replace(move |_| freshness.record_bot_config_loaded());
+    }
+    if let Some(freshness) = freshness {
+        let metrics = metrics.clone();
+        supervisor.spawn("freshness_monitor", move |mut ctx| {
+            let freshness = freshness.clone();
+            let metrics = metrics.clone();
+            async move {
+                tokio::select! {
+                    _ = freshness.run(&metrics) => {}
+                    _ = ctx.stopped() => {}
+                }
+                Ok(())
+            }
+        });
+    }
+    match queue_depth_source {
+        Some(source) if config.queue_depth.enabled => {
+            let sampler = Arc::new(QueueDepthSampler::new(
+                config.queue_depth.clone(),
+                source,
+                bot_config.clone(),
+            ));
+            let metrics = metrics.clone();
+            supervisor.spawn("queue_depth_sampler", move |mut ctx| {
+                let sampler = sampler.clone();
+                let metrics = metrics.clone();
+                async move {
+                    tokio::select! {
+                        _ = sampler.run(&metrics) => {}
+                        _ = ctx.stopped() => {}
+                    }
+                    Ok(())
+                }
+            });
+        }
+        None if config.queue_depth.enabled => {
+            warn!("Queue depth sampling is enabled but the queue backend cannot report depths");
+        }
+        _ => {}
+    }
+
     // Note: TelemetryConfig reads the environment directly from the
     // QK__TELEMETRY__ENVIRONMENT env var rather than from ServiceConfig.
     // YAML-file configuration does not apply to this value; only the
@@ -291,26 +779,367 @@ pub async fn start_server(
         "queue-keeper".to_string(),
         std::env::var("QK__TELEMETRY__ENVIRONMENT").unwrap_or_else(|_| "production".to_string()),
     ));
+    let trace_sampler = trace_sampler.unwrap_or_else(|| {
+        TraceSampler::new(
+            config
+                .telemetry
+                .initial_sampling_ratio(&telemetry_config.environment),
+        )
+    });
+
+    // Gateway destinations read their environment-variable secrets now so a
+    // missing secret stops startup instead of failing every forward.
+    let gateway = WebhookGateway::from_config(&config.gateways, key_vault.clone())
+        .map_err(ServiceError::Configuration)?
+        .map(|gateway| Arc::new(gateway.with_metrics(metrics.clone())));
+    if gateway.is_some() {
+        info!(
+            destinations = config.gateways.len(),
+            "Webhook gateway forwarding enabled"
+        );
+    }
+
+    // The installation token cache is built with the App's authentication
+    // provider, so a missing private key or unreachable cache stops startup.
+    let github_auth = build_github_app_auth(
+        config.github_app.as_ref(),
+        config.github_api_url.as_deref(),
+        &config.token_cache,
+        key_vault.as_ref(),
+    )
+    .await
+    .map_err(ServiceError::Configuration)?;
+
+    // Bots with field encryption or an HTTP destination need their keys
+    // from the Key Vault; refuse to start rather than fail every delivery to
+    // them.
+    let startup_bot_config = bot_config.current();
+    let has_key_vault = key_vault.is_some();
+    let mut router = DefaultEventRouter::new();
+    let field_encryptor = match key_vault {
+        Some(key_vault) => {
+            let field_encryptor = Arc::new(FieldEncryptor::new(key_vault.clone()));
+            router = router
+                .with_field_encryption(field_encryptor.clone())
+                .with_http_forwarder(Arc::new(HttpForwarder::new(
+                    Arc::new(ReqwestTransport::default()),
+                    key_vault,
+                )));
+            Some(field_encryptor)
+        }
+        None => {
+            if let Some(message) = key_vault_requirement(&startup_bot_config) {
+                return Err(ServiceError::Configuration(ConfigError::Invalid {
+                    message,
+                }));
+            }
+            None
+        }
+    };
+
+    // Compile bot transforms up front so a broken module stops startup
+    // instead of failing deliveries.
+    let transforms = TransformRegistry::load(&startup_bot_config).map_err(|e| match e {
+        TransformError::NotCompiled => {
+            ServiceError::Configuration(ConfigError::ProviderNotCompiled {
+                component: "Transform".to_string(),
+                provider: "wasm".to_string(),
+                feature: "wasm-transforms".to_string(),
+            })
+        }
+        e => ServiceError::Configuration(ConfigError::Invalid {
+            message: e.to_string(),
+        }),
+    })?;
+    let transforms = (!transforms.is_empty()).then(|| Arc::new(transforms));
+    if let Some(transforms) = &transforms {
+        router = router.with_transforms(transforms.clone());
+    }
+    let router = Arc::new(router);
+    let event_router: Arc<dyn EventRouter> = match &config.routing.topic {
+        Some(topic) if config.routing.is_topic() => {
+            Arc::new(topic_event_router(topic, &startup_bot_config)?)
+        }
+        _ => router.clone(),
+    };
 
-    let event_router: Arc<dyn EventRouter> = Arc::new(DefaultEventRouter::new());
+    // Reloaded bot configurations must be servable by the router built
+    // above; anything else needs a restart.
+    let topic_routing = config.routing.is_topic();
+    bot_config.set_reload_check(move |current, new| {
+        reload_check(current, new, has_key_vault, topic_routing)
+    });
 
     // Build IP rate limiter if enabled (spec assertion #19, three-tier escalation).
-    // All thresholds and durations are configurable via SecurityConfig.
-    let ip_rate_limiter = if config.security.enable_ip_rate_limiting {
-        Some(Arc::new(IpFailureTracker::new(
-            config.security.auth_failure_threshold,
-            config.security.auth_block_threshold,
-            Duration::from_secs(config.security.auth_failure_window_secs),
-            Duration::from_secs(config.security.auth_rate_restrict_duration_secs),
-            Duration::from_secs(config.security.auth_block_duration_secs),
-        )))
-    } else {
-        None
-    };
+    // All thresholds and durations are configurable via SecurityConfig; the
+    // state backend is selected by SecurityConfig::rate_limiter_store.
+    let ip_rate_limiter = rate_limit_store::build_rate_limiter_store(&config.security)
+        .await
+        .map_err(ServiceError::Configuration)?;
+    if ip_rate_limiter.is_some() {
+        info!(
+            backend = config.security.rate_limiter_store.backend_name(),
+            "IP rate limiter enabled"
+        );
+    }
+
+    // Request rate limits for the webhook, API and admin routes; health and
+    // metrics endpoints stay unlimited so probes never see 429s.
+    let request_rate_limiter = RequestRateLimiter::from_config(&config.security).map(Arc::new);
+    if request_rate_limiter.is_some() {
+        info!(
+            global_rate_limit = config.security.global_rate_limit,
+            ip_rate_limit = config.security.ip_rate_limit,
+            per_ip = config.security.enable_ip_rate_limiting,
+            "Request rate limiting enabled"
+        );
+    }
+
+    let repository_rate_limiter = config.webhooks.rate_limit_per_repo.map(|limit| {
+        info!(
+            rate_limit_per_repo = limit,
+            "Per-repository webhook rate limiting enabled"
+        );
+        Arc::new(RepositoryRateLimiter::new(limit).with_metrics(metrics.clone()))
+    });
 
     let admin_api_key = config.security.admin_api_key.clone();
 
-    let state = AppState::new(
+    // Batch the sends to each bot queue during bursts.
+    let queue_client = queue_client.map(|client| {
+        let batching = &config.delivery.batching;
+        if batching.enabled && !client.supports_batching() {
+            warn!(
+                "Queue send batching is enabled but the queue backend cannot send batches; \
+                 sending messages one by one"
+            );
+        } else if batching.enabled {
+            info!(
+                max_messages = batching.max_messages,
+                max_delay_ms = batching.max_delay.as_millis() as u64,
+                "Queue send batching enabled"
+            );
+        }
+        BatchingQueueClient::wrap(client, batching, Some(metrics.clone()))
+    });
+
+    // Sticky session shards only matter when events are delivered in-process.
+    let shard_dispatcher = (config.delivery.is_sharded() && queue_client.is_some())
+        .then(|| SessionShardDispatcher::start(&config.delivery, Some(metrics.clone())));
+
+    let delivery_audit = DeliveryAuditor::from_config(&config.delivery_audit).map_err(|e| {
+        ServiceError::Configuration(ConfigError::Invalid {
+            message: format!("delivery audit log could not be opened: {}", e),
+        })
+    })?;
+    if delivery_audit.is_enabled() {
+        info!(
+            sample_rate = config.delivery_audit.sample_rate,
+            directory = %config.delivery_audit.directory.display(),
+            "Delivery audit enabled"
+        );
+    }
+
+    let dev_tools = config.developer.enabled.then(|| {
+        warn!(
+            "DEVELOPER MODE is enabled: webhook signatures are not enforced and \
+             /dev/last-events exposes recent payloads. Never run this in production."
+        );
+        Arc::new(DeveloperTools::new(config.developer.clone()))
+    });
+
+    let event_stream = config
+        .event_stream
+        .enabled
+        .then(|| Arc::new(EventStream::new(&config.event_stream)));
+
+    let retry_budget =
+        RetryBudget::new(config.delivery.retry_budget.clone()).with_metrics(metrics.clone());
+    if retry_budget.is_enabled() {
+        info!(
+            ratio = config.delivery.retry_budget.ratio,
+            min_retries = config.delivery.retry_budget.min_retries,
+            "Delivery retry budget enabled"
+        );
+    }
+
+    // The outbox only guards deliveries made in-process.
+    let outbox = match (&queue_client, config.outbox.enabled) {
+        (Some(_), true) => {
+            let mut outbox = Outbox::open(&config.outbox).map_err(|e| {
+                ServiceError::Configuration(ConfigError::Invalid {
+                    message: format!("outbox could not be opened: {}", e),
+                })
+            })?;
+            // Fields the bots encrypt are encrypted on disk too.
+            if let Some(field_encryptor) = &field_encryptor {
+                outbox = outbox.with_field_encryption(field_encryptor.clone());
+            }
+            info!(
+                directory = %config.outbox.directory.display(),
+                pending_entries = outbox.len(),
+                "Delivery outbox enabled"
+            );
+            metrics.outbox_pending_entries.set(outbox.len() as i64);
+            Some(Arc::new(outbox))
+        }
+        (None, true) => {
+            warn!("outbox is enabled but no queue client is configured; skipping");
+            None
+        }
+        _ => None,
+    };
+
+    let delivery_config = QueueDeliveryConfig {
+        retry_policy: config.delivery_retry_policy().clone(),
+        retry_budget: Arc::new(retry_budget),
+        session_tracker: Arc::new(SessionTracker::new(config.sessions.clone())),
+        delivery_audit: Arc::new(delivery_audit),
+        metrics: Some(metrics.clone()),
+        dev_tools,
+        event_stream: event_stream.clone(),
+        outbox: outbox.clone(),
+        ..QueueDeliveryConfig::default()
+    };
+
+    // Work accepted but unfinished when the process stops.
+    if let Some(recorder) = &shutdown_recorder {
+        let tracker = delivery_config.session_tracker.clone();
+        recorder.track_residue("in_flight_events", move || tracker.in_flight_events());
+        let bot_pauses = delivery_config.bot_pauses.clone();
+        recorder.track_residue("held_events", move || {
+            bot_pauses
+                .held_backlogs()
+                .iter()
+                .map(|backlog| backlog.held_events as u64)
+                .sum()
+        });
+        if let Some(dispatcher) = shard_dispatcher.clone() {
+            recorder.track_residue("pending_shard_deliveries", move || {
+                dispatcher.pending().iter().map(|&n| n as u64).sum()
+            });
+        }
+        if let Some(outbox) = outbox.clone() {
+            recorder.track_residue("outbox_entries", move || outbox.len() as u64);
+        }
+    }
+
+    // Entries left by an earlier run and failed deliveries are redelivered
+    // from the outbox.
+    if let (Some(outbox), Some(queue_client)) = (outbox, &queue_client) {
+        let dispatcher = Arc::new(OutboxDispatcher::new(
+            outbox,
+            event_router.clone(),
+            bot_config.clone(),
+            queue_client.clone(),
+            delivery_config.clone(),
+        ));
+        let metrics = metrics.clone();
+        supervisor.spawn("outbox_dispatcher", move |mut ctx| {
+            let dispatcher = dispatcher.clone();
+            let metrics = metrics.clone();
+            async move {
+                tokio::select! {
+                    _ = dispatcher.run(&metrics) => {}
+                    _ = ctx.stopped() => {}
+                }
+                Ok(())
+            }
+        });
+    }
+
+    // The escalation monitor shares the hold queues and failure tracking of
+    // the delivery configuration handed to the app state.
+    if config.escalation.is_enabled() {
+        let mut monitor = EscalationMonitor::new(config.escalation.clone(), &delivery_config);
+        if let Some(queue_client) = &queue_client {
+            monitor = monitor.with_fallback_delivery(
+                queue_client.clone(),
+                router.clone(),
+                bot_config.clone(),
+            );
+        }
+        let monitor = Arc::new(monitor);
+        let metrics = metrics.clone();
+        supervisor.spawn("escalation_monitor", move |mut ctx| {
+            let monitor = monitor.clone();
+            let metrics = metrics.clone();
+            async move {
+                tokio::select! {
+                    _ = monitor.run(&metrics) => {}
+                    _ = ctx.stopped() => {}
+                }
+                Ok(())
+            }
+        });
+    }
+
+    let detector = Arc::new(StuckSessionDetector::new(
+        delivery_config.session_tracker.clone(),
+    ));
+    let detector_metrics = metrics.clone();
+    supervisor.spawn("stuck_session_detector", move |mut ctx| {
+        let detector = detector.clone();
+        let metrics = detector_metrics.clone();
+        async move {
+            tokio::select! {
+                _ = detector.run(&metrics) => {}
+                _ = ctx.stopped() => {}
+            }
+            Ok(())
+        }
+    });
+
+    let audit_logger: Arc<dyn AuditLogger> = Arc::new(StdoutAuditLogger::new());
+    let purger =
+        Arc::new(EventPurger::new(event_store.clone()).with_audit_logger(audit_logger.clone()));
+    let purger_metrics = metrics.clone();
+    supervisor.spawn("event_purger", move |mut ctx| {
+        let purger = purger.clone();
+        let metrics = purger_metrics.clone();
+        async move {
+            tokio::select! {
+                _ = purger.run(&metrics) => {}
+                _ = ctx.stopped() => {}
+            }
+            Ok(())
+        }
+    });
+
+    // Expired payloads are swept from the event storage they were written to.
+    let payload_retention = match (&config.payload_retention, &event_blob_storage) {
+        (retention, Some(storage)) if retention.enabled => {
+            let sweeper = PayloadRetentionSweeper::from_config(retention, storage.clone())
+                .map_err(|message| ServiceError::Configuration(ConfigError::Invalid { message }))?;
+            info!(
+                action = retention.action.as_str(),
+                sweep_interval = ?retention.sweep_interval,
+                "Payload retention enabled"
+            );
+            let sweeper = Arc::new(sweeper);
+            let job = sweeper.clone();
+            let retention_metrics = metrics.clone();
+            supervisor.spawn("payload_retention_sweeper", move |mut ctx| {
+                let sweeper = job.clone();
+                let metrics = retention_metrics.clone();
+                async move {
+                    tokio::select! {
+                        _ = sweeper.run(|report| metrics.record_payload_retention_sweep(report)) => {}
+                        _ = ctx.stopped() => {}
+                    }
+                    Ok(())
+                }
+            });
+            Some(sweeper)
+        }
+        (retention, None) if retention.enabled => {
+            warn!("payload_retention is enabled but no event storage is configured; skipping");
+            None
+        }
+        _ => None,
+    };
+
+    let mut state = AppState::new(
         config.clone(),
         provider_registry,
         health_checker,
@@ -321,26 +1150,86 @@ pub async fn start_server(
         queue_client,
         event_router,
         bot_config,
-        QueueDeliveryConfig::default(),
+        delivery_config,
         ip_rate_limiter,
         admin_api_key,
         event_blob_storage,
-    );
+    )
+    .with_audit_logger(audit_logger);
+    if let Some(dispatcher) = shard_dispatcher {
+        state = state.with_shard_dispatcher(dispatcher);
+    }
+    if let Some(transforms) = transforms {
+        state = state.with_transforms(transforms);
+    }
+    if let Some(gateway) = gateway {
+        state = state.with_gateway(gateway);
+    }
+    if let Some(github_auth) = github_auth {
+        state = state.with_github_auth(github_auth);
+    }
+    if let Some(report) = startup_report {
+        state = state.with_startup_report(report);
+    }
+    state = state.with_trace_sampler(trace_sampler);
+    if let Some(filter) = log_filter {
+        state = state.with_log_filter(filter);
+    }
+    if let Some(previous) = shutdown_recorder.as_ref().and_then(|r| r.previous()) {
+        previous.log("Previous shutdown report");
+        state = state.with_last_shutdown_report(Arc::new(previous.clone()));
+    }
+    if let Some(limiter) = request_rate_limiter {
+        state = state.with_request_rate_limiter(limiter);
+    }
+    if let Some(sweeper) = payload_retention {
+        state = state.with_payload_retention(sweeper);
+    }
+    if let Some(limiter) = repository_rate_limiter {
+        state = state.with_repository_rate_limiter(limiter);
+    }
+    if let Some(store) = deduplication {
+        state = state.with_deduplication(store);
+    }
+    if let Some(stream) = event_stream {
+        state = state.with_event_stream(stream);
+    }
+    if config.payload_profiler.enabled {
+        info!(
+            sample_rate = config.payload_profiler.sample_rate,
+            "Payload field profiler enabled"
+        );
+        state = state.with_payload_profiler(Arc::new(PayloadProfiler::new(
+            config.payload_profiler.clone(),
+        )));
+    }
     let app = create_router(state);
 
     let addr = SocketAddr::from(([0, 0, 0, 0], config.server.port));
-    let listener =
-        tokio::net::TcpListener::bind(addr)
-            .await
-            .map_err(|e| ServiceError::BindFailed {
+    let listener = match tokio::net::TcpListener::bind(addr).await {
+        Ok(listener) => listener,
+        Err(e) => {
+            let error = ServiceError::BindFailed {
                 address: addr.to_string(),
                 message: e.to_string(),
-            })?;
+            };
+            if let Some(recorder) = &shutdown_recorder {
+                recorder.finish(ExitReason::Error {
+                    message: error.to_string(),
+                });
+            }
+            return Err(error);
+        }
+    };
 
     info!("Starting HTTP server on {}", addr);
 
     // Set up graceful shutdown signal handling with configured timeout
-    let shutdown_timeout = std::time::Duration::from_secs(config.server.shutdown_timeout_seconds);
+    let shutdown_timeout = config.server.shutdown_timeout.as_duration();
+
+    // Which signal started the shutdown, for the shutdown report
+    let received_signal: Arc<OnceLock<&'static str>> = Arc::new(OnceLock::new());
+    let signal_slot = received_signal.clone();
 
     let shutdown_signal = async move {
         let ctrl_c = async {
@@ -362,9 +1251,11 @@ pub async fn start_server(
 
         tokio::select! {
             _ = ctrl_c => {
+                let _ = signal_slot.set("SIGINT");
                 info!("Received SIGINT (Ctrl+C), initiating graceful shutdown with {}s timeout", shutdown_timeout.as_secs());
             },
             _ = terminate => {
+                let _ = signal_slot.set("SIGTERM");
                 info!("Received SIGTERM, initiating graceful shutdown with {}s timeout", shutdown_timeout.as_secs());
             },
         }
@@ -374,12 +1265,35 @@ pub async fn start_server(
     // Note: axum's graceful shutdown will allow in-flight requests to complete
     // before shutting down. The server will stop accepting new connections immediately
     // upon receiving the shutdown signal, then wait for in-flight requests to finish.
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal)
-        .await
-        .map_err(|e| ServiceError::ServerFailed {
-            message: e.to_string(),
-        })?;
+    let served = axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(shutdown_signal)
+    .await
+    .map_err(|e| ServiceError::ServerFailed {
+        message: e.to_string(),
+    });
+
+    // Stop background jobs once no request can depend on them any more
+    supervisor.shutdown().await;
+
+    if let Some(recorder) = &shutdown_recorder {
+        let reason = match &served {
+            Ok(()) => ExitReason::Signal {
+                signal: received_signal
+                    .get()
+                    .copied()
+                    .unwrap_or("unknown")
+                    .to_string(),
+            },
+            Err(e) => ExitReason::Error {
+                message: e.to_string(),
+            },
+        };
+        recorder.finish(reason);
+    }
+    served?;
 
     info!("HTTP server shutdown complete");
     Ok(())
@@ -395,8 +1309,20 @@ async fn list_events(
     State(state): State<AppState>,
     Query(params): Query<EventListParams>,
 ) -> Result<Json<EventListResponse>, StatusCode> {
+    if let Err(reason) = state
+        .config
+        .event_queries
+        .check(&params, queue_keeper_core::Timestamp::now())
+    {
+        warn!(reason = %reason, "Rejected unbounded event listing");
+        return Err(StatusCode::BAD_REQUEST);
+    }
     match state.event_store.list_events(params).await {
         Ok(response) => Ok(Json(response)),
+        Err(QueueKeeperError::Validation(e)) => {
+            warn!(error = %e, "Invalid event list parameters");
+            Err(StatusCode::BAD_REQUEST)
+        }
         Err(e) => {
             error!(error = %e, "Failed to list events");
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -404,6 +1330,20 @@ async fn list_events(
     }
 }
 
+/// Stream event summaries as server-sent events
+///
+/// Returns `404 Not Found` when `event_stream.enabled` is off. See
+/// [`crate::event_stream`] for the messages sent.
+#[instrument(skip(state))]
+async fn stream_events(State(state): State<AppState>) -> Result<Response, StatusCode> {
+    let stream = state.event_stream.as_ref().ok_or(StatusCode::NOT_FOUND)?;
+    info!(
+        subscribers = stream.subscribers() + 1,
+        "Event stream client connected"
+    );
+    Ok(stream.sse().into_response())
+}
+
 /// Get specific event details
 #[instrument(skip(state))]
 async fn get_event(
@@ -429,60 +1369,145 @@ async fn get_event(
     }
 }
 
-/// List active sessions
+/// Get the replay/redrive/retry lineage tree of an event
 #[instrument(skip(state))]
-async fn list_sessions(
+async fn get_event_lineage(
     State(state): State<AppState>,
-    Query(params): Query<SessionListParams>,
-) -> Result<Json<SessionListResponse>, StatusCode> {
-    match state.event_store.list_sessions(params).await {
-        Ok(response) => Ok(Json(response)),
+    Path(event_id_str): Path<String>,
+) -> Result<Json<EventLineageResponse>, StatusCode> {
+    let event_id: EventId = match event_id_str.parse() {
+        Ok(id) => id,
         Err(e) => {
-            error!(error = %e, "Failed to list sessions");
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            warn!(error = %e, "Invalid event ID format");
+            return Err(StatusCode::BAD_REQUEST);
         }
-    }
-}
+    };
 
-/// Get specific session details
-#[instrument(skip(state))]
-async fn get_session(
-    State(state): State<AppState>,
-    Path(session_id_str): Path<String>,
-) -> Result<Json<SessionDetailResponse>, StatusCode> {
-    // Parse session ID - it's a string in owner/repo/type/id format
-    let session_id = match SessionId::new(session_id_str.clone()) {
-        Ok(id) => id,
+    let event = match state.event_store.get_event(&event_id).await {
+        Ok(event) => event,
+        Err(QueueKeeperError::NotFound { .. }) => return Err(StatusCode::NOT_FOUND),
         Err(e) => {
-            warn!(error = %e, "Invalid session ID format");
-            return Err(StatusCode::BAD_REQUEST);
+            error!(error = %e, event_id = %event_id, "Failed to get event");
+            return Err(StatusCode::INTERNAL_SERVER_ERROR);
         }
     };
 
-    match state.event_store.get_session(&session_id).await {
-        Ok(details) => Ok(Json(SessionDetailResponse { session: details })),
+    match state.event_store.get_lineage(&event_id).await {
+        Ok(tree) => Ok(Json(EventLineageResponse {
+            event_id,
+            root_event_id: event.root_event_id(),
+            tree,
+        })),
         Err(QueueKeeperError::NotFound { .. }) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
-            error!(error = %e, session_id = %session_id, "Failed to get session");
+            error!(error = %e, event_id = %event_id, "Failed to get event lineage");
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-/// Get system statistics
+/// Find the event and per-bot queue messages of a provider delivery
 #[instrument(skip(state))]
-async fn get_statistics(
+async fn get_delivery_correlation(
     State(state): State<AppState>,
-) -> Result<Json<StatisticsResponse>, StatusCode> {
-    match state.event_store.get_statistics().await {
-        Ok(response) => Ok(Json(response)),
-        Err(e) => {
-            error!(error = %e, "Failed to get statistics");
+    Path(github_delivery_id): Path<String>,
+) -> Result<Json<DeliveryCorrelation>, StatusCode> {
+    match state
+        .delivery_config
+        .delivery_index
+        .lookup(&github_delivery_id)
+    {
+        Some(correlation) => Ok(Json(correlation)),
+        None => Err(StatusCode::NOT_FOUND),
+    }
+}
+
+/// List the sessions seen since the service started
+#[instrument(skip(state))]
+async fn list_sessions(
+    State(state): State<AppState>,
+    Query(params): Query<SessionListParams>,
+) -> Result<Json<SessionListResponse>, StatusCode> {
+    match list_tracked_sessions(&state.delivery_config.session_tracker, &params) {
+        Ok(response) => Ok(Json(response)),
+        Err(QueueKeeperError::Validation(e)) => {
+            warn!(error = %e, "Invalid session list parameters");
+            Err(StatusCode::BAD_REQUEST)
+        }
+        Err(e) => {
+            error!(error = %e, "Failed to list sessions");
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
+/// List the sessions held up by an old undelivered event
+#[instrument(skip(state))]
+async fn list_stuck_sessions(State(state): State<AppState>) -> Json<StuckSessionListResponse> {
+    let tracker = &state.delivery_config.session_tracker;
+    Json(StuckSessionListResponse {
+        stuck_after_seconds: tracker.stuck_after().as_secs(),
+        in_flight_events: tracker.in_flight_events(),
+        sessions: tracker.stuck(),
+    })
+}
+
+/// Get specific session details
+#[instrument(skip(state))]
+async fn get_session(
+    State(state): State<AppState>,
+    Path(session_id_str): Path<String>,
+) -> Result<Json<SessionDetailResponse>, StatusCode> {
+    // Parse session ID - it's a string in owner/repo/type/id format
+    let session_id = match SessionId::new(session_id_str.clone()) {
+        Ok(id) => id,
+        Err(e) => {
+            warn!(error = %e, "Invalid session ID format");
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    };
+
+    // Sessions not seen since the service started are read from storage
+    if let Some(tracked) = state.delivery_config.session_tracker.get(&session_id) {
+        return Ok(Json(SessionDetailResponse {
+            session: tracked.into(),
+        }));
+    }
+    match state.event_store.get_session(&session_id).await {
+        Ok(details) => Ok(Json(SessionDetailResponse { session: details })),
+        Err(QueueKeeperError::NotFound { .. }) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            error!(error = %e, session_id = %session_id, "Failed to get session");
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+/// Get system statistics
+///
+/// Counts what this replica handled since it started (see
+/// [`crate::statistics`]); `active_sessions` comes from the session tracker.
+#[instrument(skip(state))]
+async fn get_statistics(State(state): State<AppState>) -> Json<StatisticsResponse> {
+    let active_sessions = state.delivery_config.session_tracker.active_sessions();
+    Json(state.statistics.snapshot(active_sessions))
+}
+
+/// Get payload field statistics
+///
+/// Reports `enabled: false` with no statistics when the profiler is off.
+#[instrument(skip(state))]
+async fn get_payload_statistics(State(state): State<AppState>) -> Json<PayloadStatsResponse> {
+    Json(match &state.payload_profiler {
+        Some(profiler) => profiler.snapshot(),
+        None => PayloadStatsResponse {
+            enabled: false,
+            sample_rate: 0.0,
+            event_types: Vec::new(),
+        },
+    })
+}
+
 // ============================================================================
 // Observability Handlers
 // ============================================================================
@@ -509,6 +1534,10 @@ async fn debug_profile(
 }
 
 /// Debug variables endpoint
+///
+/// Includes the mean duration of each webhook pipeline stage since startup
+/// (`webhook_stage.<stage>.mean_ms`) and the stage breakdown of the most
+/// recent webhook request (`webhook_stage.last_request`).
 #[instrument(skip_all)]
 async fn debug_vars(State(state): State<AppState>) -> Json<DebugVarsResponse> {
     let mut vars = HashMap::new();
@@ -537,106 +1566,1312 @@ async fn debug_vars(State(state): State<AppState>) -> Json<DebugVarsResponse> {
         state.telemetry_config.json_logging.to_string(),
     );
 
+    for (stage, count, mean) in state.metrics.webhook_stage_summary() {
+        vars.insert(format!("webhook_stage.{}.count", stage), count.to_string());
+        vars.insert(
+            format!("webhook_stage.{}.mean_ms", stage),
+            format!("{:.3}", mean.as_secs_f64() * 1_000.0),
+        );
+    }
+    if let Some(breakdown) = state
+        .metrics
+        .last_webhook_stage_breakdown
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+    {
+        vars.insert("webhook_stage.last_request".to_string(), breakdown);
+    }
+
     Json(DebugVarsResponse { vars })
 }
 
+/// Startup report endpoint
+///
+/// Returns `404 Not Found` when the binary did not produce a report.
+#[instrument(skip_all)]
+async fn debug_startup_report(
+    State(state): State<AppState>,
+) -> Result<Json<StartupReport>, StatusCode> {
+    state
+        .startup_report
+        .as_deref()
+        .cloned()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
+/// Last shutdown report endpoint
+///
+/// Returns `404 Not Found` when no report of a previous process was found.
+#[instrument(skip_all)]
+async fn debug_last_shutdown(
+    State(state): State<AppState>,
+) -> Result<Json<ShutdownReport>, StatusCode> {
+    state
+        .last_shutdown_report
+        .as_deref()
+        .cloned()
+        .map(Json)
+        .ok_or(StatusCode::NOT_FOUND)
+}
+
 // ============================================================================
-// Admin Handlers (Stubs)
+// Admin Handlers
 // ============================================================================
 
 /// Replay an event
+///
+/// Reads the stored envelope and delivers a new event derived from it
+/// through the routing pipeline, optionally to a single bot. A dry run
+/// reports the bots the event would be routed to without delivering it.
+/// The body is optional.
 async fn replay_event(
-    State(_state): State<AppState>,
-    Path(_event_id): Path<String>,
-) -> Result<Json<ReplayResponse>, StatusCode> {
-    // TODO: Implement event replay
-    // See specs/interfaces/http-service.md
-    Err(StatusCode::NOT_IMPLEMENTED)
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(event_id): Path<String>,
+    body: Option<Json<ReplayEventRequest>>,
+) -> Response {
+    let event_id = match event_id.parse::<EventId>() {
+        Ok(id) => id,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": "invalid_event_id",
+                    "message": e.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+    let Json(request) = body.unwrap_or_default();
+    let reason = request
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty())
+        .unwrap_or("Replay requested through the admin API")
+        .to_string();
+
+    let mut replay_request =
+        ReplayRequest::single_event(event_id, principal.name.clone(), reason.clone());
+    replay_request.target_bots = request.target_bot.into_iter().collect();
+    replay_request.dry_run = request.dry_run;
+
+    let results = match state.replay_service.submit_replay(replay_request).await {
+        Ok(replay_id) => state.replay_service.get_replay_results(replay_id).await,
+        Err(e) => Err(e),
+    };
+    let results = match results {
+        Ok(results) => results,
+        Err(e) => {
+            let (status, code) = match &e {
+                ReplayError::EventNotFound { .. } => (StatusCode::NOT_FOUND, "event_not_found"),
+                ReplayError::InvalidRequest { .. } => {
+                    (StatusCode::BAD_REQUEST, "invalid_replay_request")
+                }
+                ReplayError::ServiceUnavailable { .. } => {
+                    (StatusCode::SERVICE_UNAVAILABLE, "replay_unavailable")
+                }
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "replay_failed"),
+            };
+            if status.is_server_error() {
+                error!(event_id = %event_id, error = %e, "Failed to replay event");
+            }
+            return (
+                status,
+                Json(json!({
+                    "error": code,
+                    "message": e.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let Some(result) = results.event_results.first() else {
+        error!(event_id = %event_id, "Replay finished without an event result");
+        return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let bot_config = state.bot_config.current();
+    let routes: Vec<ReplayRoute> = result
+        .routed_bots
+        .iter()
+        .filter_map(|name| bot_config.bots.iter().find(|bot| &bot.name == name))
+        .map(|bot| ReplayRoute {
+            bot_name: bot.name.clone(),
+            queue: bot.queue.clone(),
+            held: state.delivery_config.bot_pauses.is_paused(&bot.name),
+        })
+        .collect();
+    let replay_event_id = (result.event_id != event_id).then_some(result.event_id);
+    let (status, message) = match &result.result {
+        _ if request.dry_run => (
+            "dry_run",
+            format!("Event would be routed to {} bot(s)", routes.len()),
+        ),
+        ReplayOutcome::Success => (
+            "replayed",
+            format!("Event replayed to {} bot(s)", routes.len()),
+        ),
+        ReplayOutcome::Skipped { reason } => ("skipped", format!("Replay skipped: {}", reason)),
+        ReplayOutcome::Failed { error } => ("failed", format!("Replay failed: {}", error)),
+        ReplayOutcome::Partial { failed_bots, .. } => (
+            "failed",
+            format!("Replay failed for {} bot(s)", failed_bots.len()),
+        ),
+    };
+
+    info!(
+        event_id = %event_id,
+        replay_id = %results.replay_id,
+        principal = %principal.name,
+        dry_run = request.dry_run,
+        status,
+        "Event replay requested"
+    );
+
+    if !request.dry_run {
+        if let Some(audit_logger) = &state.audit_logger {
+            let result = if status == "failed" {
+                AuditResult::Failure {
+                    error_code: "replay_failed".to_string(),
+                    error_message: message.clone(),
+                    retryable: true,
+                }
+            } else {
+                AuditResult::Success {
+                    duration: None,
+                    details: Some(message.clone()),
+                }
+            };
+            if let Err(e) = audit_logger
+                .log_admin_action(
+                    principal.to_audit_actor(),
+                    AuditResource::Administrative {
+                        resource_type: "event".to_string(),
+                        resource_id: event_id.to_string(),
+                    },
+                    AuditAction::Custom {
+                        operation: "event_replay".to_string(),
+                        details: Some(reason),
+                    },
+                    result,
+                    AuditContext::default(),
+                )
+                .await
+            {
+                warn!(error = %e, event_id = %event_id, "Failed to record event replay audit entry");
+            }
+        }
+    }
+
+    let status_code = if status == "failed" {
+        StatusCode::BAD_GATEWAY
+    } else {
+        StatusCode::OK
+    };
+    (
+        status_code,
+        Json(ReplayResponse {
+            event_id,
+            status: status.to_string(),
+            message,
+            replay_id: results.replay_id,
+            replay_event_id,
+            dry_run: request.dry_run,
+            routes,
+        }),
+    )
+        .into_response()
+}
+
+/// Soft-delete a stored event
+///
+/// Records a tombstone with the acting principal and the reason; the event
+/// is excluded from listings and purged after `event_deletion.purge_after`.
+/// See [`event_deletion`].
+async fn delete_event(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(event_id): Path<String>,
+    Json(request): Json<DeleteEventRequest>,
+) -> Response {
+    let event_id = match event_id.parse::<EventId>() {
+        Ok(id) => id,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": "invalid_event_id",
+                    "message": e.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "missing_reason",
+                "message": "A non-empty deletion reason is required"
+            })),
+        )
+            .into_response();
+    }
+
+    let tombstone = match state
+        .event_store
+        .delete_event(&event_id, &principal.name, reason)
+        .await
+    {
+        Ok(tombstone) => tombstone,
+        Err(e) => {
+            let (status, code) = match &e {
+                QueueKeeperError::NotFound { .. } => (StatusCode::NOT_FOUND, "event_not_found"),
+                QueueKeeperError::Configuration { .. } => {
+                    (StatusCode::SERVICE_UNAVAILABLE, "deletion_unavailable")
+                }
+                _ => (StatusCode::INTERNAL_SERVER_ERROR, "deletion_failed"),
+            };
+            if status.is_server_error() {
+                error!(event_id = %event_id, error = %e, "Failed to delete event");
+            }
+            return (
+                status,
+                Json(json!({
+                    "error": code,
+                    "message": e.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    state.metrics.events_deleted_total.inc();
+    info!(
+        event_id = %event_id,
+        principal = %principal.name,
+        reason = %reason,
+        purge_at = %tombstone.purge_at,
+        "Event soft-deleted"
+    );
+
+    if let Some(audit_logger) = &state.audit_logger {
+        if let Err(e) = audit_logger
+            .log_admin_action(
+                principal.to_audit_actor(),
+                AuditResource::Data {
+                    data_type: "event".to_string(),
+                    identifier: event_id.to_string(),
+                    location: None,
+                },
+                AuditAction::Delete {
+                    reason: Some(reason.to_string()),
+                },
+                AuditResult::Success {
+                    duration: None,
+                    details: Some(format!("soft-deleted; purge at {}", tombstone.purge_at)),
+                },
+                AuditContext::default(),
+            )
+            .await
+        {
+            warn!(error = %e, event_id = %event_id, "Failed to record event deletion audit entry");
+        }
+    }
+
+    Json(DeleteEventResponse {
+        status: "deleted".to_string(),
+        tombstone,
+    })
+    .into_response()
 }
 
 /// Reset session state
+///
+/// Cancels pending deliveries for the session (dropping their retry state)
+/// and bumps the session epoch stamped on subsequently delivered events.
+/// Events buffered while the session is paused are dropped too; the session
+/// stays paused. The reset reason and the acting principal are recorded in
+/// the audit log.
+///
+/// Session IDs contain `/` separators, so clients must percent-encode them
+/// in the path (e.g. `owner%2Frepo%2Fpull_request%2F42`).
 async fn reset_session(
-    State(_state): State<AppState>,
-    Path(_session_id): Path<String>,
-) -> Result<Json<ResetResponse>, StatusCode> {
-    // TODO: Implement session reset
-    // See specs/interfaces/http-service.md
-    Err(StatusCode::NOT_IMPLEMENTED)
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(session_id): Path<String>,
+    Json(request): Json<ResetSessionRequest>,
+) -> Response {
+    let session_id = match SessionId::new(session_id) {
+        Ok(id) => id,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": "invalid_session_id",
+                    "message": e.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "missing_reason",
+                "message": "A non-empty reset reason is required"
+            })),
+        )
+            .into_response();
+    }
+
+    let summary = state.delivery_config.session_epochs.reset(&session_id);
+    let discarded_held = state
+        .delivery_config
+        .bot_pauses
+        .discard_session(&session_id);
+    let discarded_buffered = state.delivery_config.session_control.discard(&session_id);
+    for event_id in &discarded_buffered {
+        state.delivery_config.session_tracker.record_outcome(
+            &session_id,
+            *event_id,
+            TrackedEventStatus::Cancelled,
+        );
+        if let Some(outbox) = &state.delivery_config.outbox {
+            outbox.complete(event_id);
+        }
+    }
+    let cancelled_deliveries =
+        summary.cancelled_deliveries + discarded_held + discarded_buffered.len();
+
+    info!(
+        session_id = %session_id,
+        principal = %principal.name,
+        reason = %reason,
+        previous_epoch = summary.previous_epoch,
+        session_epoch = summary.new_epoch,
+        cancelled_deliveries,
+        discarded_held,
+        discarded_buffered = discarded_buffered.len(),
+        "Session reset"
+    );
+
+    if let Some(audit_logger) = &state.audit_logger {
+        if let Err(e) = audit_logger
+            .log_admin_action(
+                principal.to_audit_actor(),
+                AuditResource::Administrative {
+                    resource_type: "session".to_string(),
+                    resource_id: session_id.to_string(),
+                },
+                AuditAction::Custom {
+                    operation: "session_reset".to_string(),
+                    details: Some(reason.to_string()),
+                },
+                AuditResult::Success {
+                    duration: None,
+                    details: Some(format!(
+                        "epoch {} -> {}, {} pending deliveries cancelled",
+                        summary.previous_epoch, summary.new_epoch, cancelled_deliveries
+                    )),
+                },
+                AuditContext::default(),
+            )
+            .await
+        {
+            warn!(error = %e, session_id = %session_id, "Failed to record session reset audit entry");
+        }
+    }
+
+    Json(ResetResponse {
+        session_id,
+        status: "reset".to_string(),
+        message: format!(
+            "Session reset to epoch {}; {} pending deliveries cancelled",
+            summary.new_epoch, cancelled_deliveries
+        ),
+        previous_epoch: summary.previous_epoch,
+        session_epoch: summary.new_epoch,
+        cancelled_deliveries,
+    })
+    .into_response()
 }
 
-/// Get current configuration
-async fn get_config(State(state): State<AppState>) -> Json<ServiceConfig> {
-    Json(state.config)
+/// Restore an archived DLQ bundle
+///
+/// Writes every record in the bundle back to the active DLQ store. The
+/// bundle ID is reported in the retention sweep log and in the archive
+/// store's blob metadata.
+async fn restore_dlq_archive(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(bundle_id): Path<String>,
+) -> Response {
+    let bundle_id = match bundle_id.parse::<EventId>() {
+        Ok(id) => id,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": "invalid_bundle_id",
+                    "message": e.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let Some(dlq_service) = &state.delivery_config.dlq_service else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({
+                "error": "dlq_not_configured",
+                "message": "DLQ storage is not configured"
+            })),
+        )
+            .into_response();
+    };
+
+    let report = match dlq_service.restore_bundle(&bundle_id).await {
+        Ok(report) => report,
+        Err(e) => {
+            let (status, code) = match &e {
+                DlqArchiveError::NotConfigured => (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    "dlq_archive_not_configured",
+                ),
+                DlqArchiveError::BundleNotFound { .. } => {
+                    (StatusCode::NOT_FOUND, "bundle_not_found")
+                }
+                DlqArchiveError::CorruptBundle { .. } => {
+                    (StatusCode::UNPROCESSABLE_ENTITY, "bundle_corrupt")
+                }
+                DlqArchiveError::Storage(_) => (StatusCode::INTERNAL_SERVER_ERROR, "storage_error"),
+            };
+            if status.is_server_error() {
+                error!(bundle_id = %bundle_id, error = %e, "Failed to restore DLQ archive bundle");
+            }
+            return (
+                status,
+                Json(json!({
+                    "error": code,
+                    "message": e.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    info!(
+        bundle_id = %bundle_id,
+        principal = %principal.name,
+        restored_records = report.restored_records,
+        "DLQ archive bundle restored"
+    );
+
+    if let Some(audit_logger) = &state.audit_logger {
+        if let Err(e) = audit_logger
+            .log_admin_action(
+                principal.to_audit_actor(),
+                AuditResource::Administrative {
+                    resource_type: "dlq_archive".to_string(),
+                    resource_id: bundle_id.to_string(),
+                },
+                AuditAction::Custom {
+                    operation: "dlq_archive_restore".to_string(),
+                    details: None,
+                },
+                AuditResult::Success {
+                    duration: None,
+                    details: Some(format!("{} records restored", report.restored_records)),
+                },
+                AuditContext::default(),
+            )
+            .await
+        {
+            warn!(error = %e, bundle_id = %bundle_id, "Failed to record DLQ restore audit entry");
+        }
+    }
+
+    Json(DlqRestoreResponse {
+        bundle_id,
+        status: "restored".to_string(),
+        restored_records: report.restored_records,
+    })
+    .into_response()
+}
+
+/// Run the pipeline self-test
+///
+/// Returns the per-stage report with 200 when every stage passed or was
+/// skipped, and with 503 when any stage failed.
+async fn run_selftest(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+) -> Response {
+    let report = SelfTest::from_state(&state).run().await;
+
+    if report.passed {
+        info!(
+            principal = %principal.name,
+            duration_ms = report.duration_ms,
+            "Pipeline self-test passed"
+        );
+    } else {
+        warn!(
+            principal = %principal.name,
+            duration_ms = report.duration_ms,
+            "Pipeline self-test failed"
+        );
+    }
+
+    if let Some(audit_logger) = &state.audit_logger {
+        let summary = report
+            .stages
+            .iter()
+            .map(|s| format!("{}={:?}", s.stage.as_str(), s.status))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let result = if report.passed {
+            AuditResult::Success {
+                duration: None,
+                details: Some(summary),
+            }
+        } else {
+            AuditResult::Failure {
+                error_code: "SELFTEST_FAILED".to_string(),
+                error_message: summary,
+                retryable: true,
+            }
+        };
+        if let Err(e) = audit_logger
+            .log_admin_action(
+                principal.to_audit_actor(),
+                AuditResource::Administrative {
+                    resource_type: "pipeline".to_string(),
+                    resource_id: "selftest".to_string(),
+                },
+                AuditAction::Custom {
+                    operation: "selftest".to_string(),
+                    details: None,
+                },
+                result,
+                AuditContext::default(),
+            )
+            .await
+        {
+            warn!(error = %e, "Failed to record self-test audit entry");
+        }
+    }
+
+    let status = if report.passed {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    (status, Json(report)).into_response()
+}
+
+/// Get current configuration, with the version of the bot configuration
+///
+/// Secrets are redacted. With `include_defaults=true` the response also
+/// names the source of every value; that needs the startup report, which
+/// records the sources the configuration was loaded from.
+async fn get_config(
+    State(state): State<AppState>,
+    Query(params): Query<ConfigParams>,
+) -> Json<ConfigResponse> {
+    let config = RedactedServiceConfig(&state.config).to_value();
+    let value_sources = params
+        .include_defaults
+        .then_some(state.startup_report.as_ref())
+        .flatten()
+        .map(|report| startup_report::value_sources(&config, &report.config_sources));
+    let bot_config = state.bot_config.snapshot();
+    Json(ConfigResponse {
+        config,
+        bot_config_version: bot_config.version,
+        bot_config_loaded_at: bot_config.loaded_at,
+        value_sources,
+    })
 }
 
 /// Get current log level
 async fn get_log_level(State(state): State<AppState>) -> Json<LogLevelResponse> {
-    Json(LogLevelResponse {
-        level: state.telemetry_config.log_level.clone(),
-    })
+    Json(log_level_response(&state))
 }
 
 /// Set log level at runtime
 ///
-/// Validates the requested level and echoes it back in the response.
-/// Note: durable state mutation requires changing `telemetry_config` in
-/// `AppState` from `Arc<TelemetryConfig>` to `Arc<RwLock<TelemetryConfig>>`
-/// and wiring in a `tracing_subscriber::reload::Handle` for live level
-/// updates. Until then, the validated level is not persisted across requests.
+/// Replaces the log filter of the running service so the service crates log
+/// at the requested level. The change is not persisted and is lost on
+/// restart.
 async fn set_log_level(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Json(request): Json<SetLogLevelRequest>,
 ) -> Response {
     let level = request.level.to_lowercase();
-    match level.as_str() {
-        "trace" | "debug" | "info" | "warn" | "error" => {
-            Json(LogLevelResponse { level }).into_response()
-        }
-        _ => (
+    if !log_filter::VALID_LOG_LEVELS.contains(&level.as_str()) {
+        return (
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "error": "invalid_log_level",
-                "message": format!("Invalid log level '{}'. Valid values: trace, debug, info, warn, error", request.level)
+                "message": format!("Invalid log level '{}'. Valid values: {}", request.level, log_filter::VALID_LOG_LEVELS.join(", "))
             })),
         )
-            .into_response(),
+            .into_response();
+    }
+    if let Err(message) = state.log_filter.set_level(&level) {
+        error!(error = %message, "Failed to change the log level");
+        return (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(json!({
+                "error": "log_filter_unavailable",
+                "message": message
+            })),
+        )
+            .into_response();
+    }
+    info!(level = %level, filter = %state.log_filter.filter(), "Log level changed");
+    Json(log_level_response(&state)).into_response()
+}
+
+fn log_level_response(state: &AppState) -> LogLevelResponse {
+    LogLevelResponse {
+        level: state.log_filter.level(),
+        filter: state.log_filter.filter(),
     }
 }
 
 /// Get current trace sampling configuration
 async fn get_trace_sampling(State(state): State<AppState>) -> Json<TraceSamplingResponse> {
-    Json(TraceSamplingResponse {
-        sampling_ratio: state.telemetry_config.sampling_ratio,
-        service_name: state.telemetry_config.service_name.clone(),
-    })
+    Json(trace_sampling_response(&state))
 }
 
 /// Set trace sampling ratio at runtime
 ///
-/// Validates the requested ratio is in `[0.0, 1.0]` and echoes it back.
-/// Note: durable state mutation requires changing `telemetry_config` in
-/// `AppState` from `Arc<TelemetryConfig>` to `Arc<RwLock<TelemetryConfig>>`
-/// and propagating the new ratio to the OpenTelemetry sampler. Until then,
-/// the validated ratio is not persisted across requests.
+/// The new ratio applies to traces started from now on; traces continued
+/// from a caller keep the caller's sampling decision. The ratio is not
+/// persisted and resets to the configured value on restart.
 async fn set_trace_sampling(
     State(state): State<AppState>,
     Json(request): Json<SetTraceSamplingRequest>,
 ) -> Response {
-    if !(0.0..=1.0).contains(&request.sampling_ratio) {
+    if let Err(message) = state.trace_sampler.set_ratio(request.sampling_ratio) {
         return (
             StatusCode::BAD_REQUEST,
             Json(json!({
                 "error": "invalid_sampling_ratio",
-                "message": "Sampling ratio must be between 0.0 and 1.0 inclusive"
+                "message": message
             })),
         )
             .into_response();
     }
-    Json(TraceSamplingResponse {
-        sampling_ratio: request.sampling_ratio,
+    info!(
+        sampling_ratio = request.sampling_ratio,
+        "Trace sampling ratio changed"
+    );
+    Json(trace_sampling_response(&state)).into_response()
+}
+
+fn trace_sampling_response(state: &AppState) -> TraceSamplingResponse {
+    TraceSamplingResponse {
+        sampling_ratio: state.trace_sampler.ratio(),
         service_name: state.telemetry_config.service_name.clone(),
-    })
-    .into_response()
+        export_enabled: state.config.telemetry.enabled,
+    }
+}
+
+/// Get read-only mode status
+async fn get_read_only(State(state): State<AppState>) -> Json<ReadOnlyStatus> {
+    Json(state.read_only.status())
+}
+
+/// Enable or disable read-only mode
+///
+/// Enabling requires a non-empty reason, which is returned in the body of
+/// every rejected request. The change and the acting principal are recorded
+/// in the audit log.
+async fn set_read_only(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Json(request): Json<SetReadOnlyRequest>,
+) -> Response {
+    let reason = request.reason.as_deref().map(str::trim).unwrap_or_default();
+
+    if request.enabled {
+        if reason.is_empty() {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": "missing_reason",
+                    "message": "A non-empty reason is required to enable read-only mode"
+                })),
+            )
+                .into_response();
+        }
+        state.read_only.enable(reason, principal.name.clone());
+        warn!(
+            principal = %principal.name,
+            reason = %reason,
+            "Read-only mode enabled; mutating admin operations will be rejected"
+        );
+    } else if state.read_only.disable() {
+        info!(principal = %principal.name, "Read-only mode disabled");
+    }
+
+    if let Some(audit_logger) = &state.audit_logger {
+        if let Err(e) = audit_logger
+            .log_admin_action(
+                principal.to_audit_actor(),
+                AuditResource::Administrative {
+                    resource_type: "read_only".to_string(),
+                    resource_id: read_only::READ_ONLY_TOGGLE_PATH.to_string(),
+                },
+                AuditAction::Configure {
+                    setting: "read_only".to_string(),
+                    value: Some(request.enabled.to_string()),
+                },
+                AuditResult::Success {
+                    duration: None,
+                    details: (!reason.is_empty()).then(|| reason.to_string()),
+                },
+                AuditContext::default(),
+            )
+            .await
+        {
+            warn!(error = %e, "Failed to record read-only mode audit entry");
+        }
+    }
+
+    Json(state.read_only.status()).into_response()
+}
+
+/// List the state of every loaded bot transform
+async fn list_transforms(State(state): State<AppState>) -> Json<Vec<TransformStatus>> {
+    Json(
+        state
+            .transforms
+            .as_ref()
+            .map(|transforms| transforms.statuses())
+            .unwrap_or_default(),
+    )
+}
+
+/// Switch a bot's transform on or off
+///
+/// Disabling is the kill switch for a misbehaving module: events are then
+/// delivered to the bot unchanged. Enabling also re-arms a transform that
+/// tripped after repeated failures.
+async fn set_transform_enabled(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(bot_name): Path<String>,
+    Json(request): Json<SetTransformRequest>,
+) -> Response {
+    let not_found = || {
+        (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "error": "transform_not_found",
+                "message": format!("No transform is loaded for bot '{}'", bot_name)
+            })),
+        )
+            .into_response()
+    };
+
+    let Ok(bot) = BotName::new(&bot_name) else {
+        return not_found();
+    };
+    let Some(status) = state
+        .transforms
+        .as_ref()
+        .and_then(|transforms| transforms.set_enabled(&bot, request.enabled))
+    else {
+        return not_found();
+    };
+
+    warn!(
+        principal = %principal.name,
+        bot_name = %bot_name,
+        enabled = request.enabled,
+        "Bot transform switched"
+    );
+
+    if let Some(audit_logger) = &state.audit_logger {
+        if let Err(e) = audit_logger
+            .log_admin_action(
+                principal.to_audit_actor(),
+                AuditResource::Administrative {
+                    resource_type: "transform".to_string(),
+                    resource_id: bot_name.clone(),
+                },
+                AuditAction::Configure {
+                    setting: "transform.enabled".to_string(),
+                    value: Some(request.enabled.to_string()),
+                },
+                AuditResult::Success {
+                    duration: None,
+                    details: None,
+                },
+                AuditContext::default(),
+            )
+            .await
+        {
+            warn!(error = %e, "Failed to record transform audit entry");
+        }
+    }
+
+    Json(status).into_response()
+}
+
+/// Replace the bot configuration
+///
+/// The new configuration is validated and swapped in atomically; events
+/// already being routed finish under the previous one. Changes that need a
+/// restart, such as a changed transform, are rejected with 409.
+async fn set_bot_config(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Json(bot_config): Json<BotConfiguration>,
+) -> Response {
+    let bots = bot_config.bots.len();
+    if let Err(e) = bot_config.validate() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "invalid_bot_config",
+                "message": e.to_string()
+            })),
+        )
+            .into_response();
+    }
+    let version = match state.bot_config.replace(bot_config) {
+        Ok(version) => version,
+        Err(e) => {
+            return (
+                StatusCode::CONFLICT,
+                Json(json!({
+                    "error": "restart_required",
+                    "message": e.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    info!(
+        principal = %principal.name,
+        version,
+        bots,
+        "Bot configuration replaced"
+    );
+
+    if let Some(audit_logger) = &state.audit_logger {
+        if let Err(e) = audit_logger
+            .log_admin_action(
+                principal.to_audit_actor(),
+                AuditResource::Administrative {
+                    resource_type: "bot_config".to_string(),
+                    resource_id: "bots".to_string(),
+                },
+                AuditAction::Configure {
+                    setting: "bot_config.version".to_string(),
+                    value: Some(version.to_string()),
+                },
+                AuditResult::Success {
+                    duration: None,
+                    details: None,
+                },
+                AuditContext::default(),
+            )
+            .await
+        {
+            warn!(error = %e, "Failed to record bot configuration audit entry");
+        }
+    }
+
+    Json(BotConfigUpdateResponse { version, bots }).into_response()
+}
+
+/// List the bots whose deliveries are paused
+async fn list_paused_bots(State(state): State<AppState>) -> Json<Vec<BotPauseStatus>> {
+    Json(state.delivery_config.bot_pauses.statuses())
+}
+
+/// Pause deliveries to a bot
+///
+/// Events matching the bot are held in the `held` state until the bot is
+/// resumed; other bots keep receiving them. Pausing an already paused bot
+/// keeps its original reason, and stops a release that is under way.
+///
+/// Topic routing publishes each event once for every bot, so a single bot
+/// cannot be paused in that mode.
+async fn pause_bot(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(bot_name): Path<String>,
+    Json(request): Json<PauseBotRequest>,
+) -> Response {
+    let Some(bot) = BotName::new(&bot_name).ok().filter(|bot| {
+        state
+            .bot_config
+            .current()
+            .bots
+            .iter()
+            .any(|b| &b.name == bot)
+    }) else {
+        return (
+            StatusCode::NOT_FOUND,
+            Json(json!({
+                "error": "bot_not_found",
+                "message": format!("No bot named '{}' is configured", bot_name)
+            })),
+        )
+            .into_response();
+    };
+
+    if state.config.routing.is_topic() {
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error": "topic_routing",
+                "message": "Bots cannot be paused individually in topic routing mode"
+            })),
+        )
+            .into_response();
+    }
+
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "missing_reason",
+                "message": "A non-empty pause reason is required"
+            })),
+        )
+            .into_response();
+    }
+
+    let status = state
+        .delivery_config
+        .bot_pauses
+        .pause(&bot, &principal.name, reason);
+
+    warn!(
+        principal = %principal.name,
+        bot_name = %bot,
+        reason = %reason,
+        held_events = status.held_events,
+        "Bot deliveries paused"
+    );
+
+    record_pause_audit(
+        &state,
+        &principal,
+        "bot",
+        bot.as_str(),
+        "bot_pause",
+        Some(reason),
+    )
+    .await;
+
+    Json(status).into_response()
+}
+
+/// Resume deliveries to a paused bot
+///
+/// Returns immediately with the bot in the `resuming` state; the held events
+/// are released in the background, oldest first. Events arriving meanwhile
+/// are held behind them, and the bot is unpaused once none are left.
+async fn resume_bot(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(bot_name): Path<String>,
+) -> Response {
+    let registry = state.delivery_config.bot_pauses.clone();
+    let Some((bot, status)) = BotName::new(&bot_name)
+        .ok()
+        .and_then(|bot| registry.resume(&bot).map(|status| (bot, status)))
+    else {
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error": "bot_not_paused",
+                "message": format!("Bot '{}' is not paused", bot_name)
+            })),
+        )
+            .into_response();
+    };
+
+    info!(
+        principal = %principal.name,
+        bot_name = %bot,
+        held_events = status.held_events,
+        "Resuming bot deliveries"
+    );
+
+    match &state.queue_client {
+        Some(queue_client) => {
+            tokio::spawn(bot_pause::release_held_events(
+                bot.clone(),
+                state.event_router.clone(),
+                state.bot_config.current(),
+                queue_client.clone(),
+                state.delivery_config.clone(),
+            ));
+        }
+        None => {
+            // Queue delivery is disabled; there is nowhere to release to.
+            registry.discard(&bot);
+        }
+    }
+
+    record_pause_audit(&state, &principal, "bot", bot.as_str(), "bot_resume", None).await;
+
+    (StatusCode::ACCEPTED, Json(status)).into_response()
+}
+
+/// List the sessions whose deliveries are paused
+async fn list_paused_sessions(State(state): State<AppState>) -> Json<Vec<SessionPauseStatus>> {
+    Json(state.delivery_config.session_control.statuses())
+}
+
+/// Pause deliveries of a session
+///
+/// Events of the session are buffered in arrival order until it is resumed;
+/// other sessions are not affected. Pausing an already paused session keeps
+/// its original reason, and stops a release that is under way.
+///
+/// Session IDs must be percent-encoded in the path, as for session reset.
+async fn pause_session(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(session_id): Path<String>,
+    Json(request): Json<PauseSessionRequest>,
+) -> Response {
+    let session_id = match SessionId::new(session_id) {
+        Ok(id) => id,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(json!({
+                    "error": "invalid_session_id",
+                    "message": e.to_string()
+                })),
+            )
+                .into_response();
+        }
+    };
+
+    let reason = request.reason.trim();
+    if reason.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(json!({
+                "error": "missing_reason",
+                "message": "A non-empty pause reason is required"
+            })),
+        )
+            .into_response();
+    }
+
+    let status = state
+        .delivery_config
+        .session_control
+        .pause(&session_id, &principal.name, reason);
+
+    warn!(
+        principal = %principal.name,
+        session_id = %session_id,
+        reason = %reason,
+        buffered_events = status.buffered_events,
+        "Session deliveries paused"
+    );
+
+    record_pause_audit(
+        &state,
+        &principal,
+        "session",
+        session_id.as_str(),
+        "session_pause",
+        Some(reason),
+    )
+    .await;
+
+    Json(status).into_response()
+}
+
+/// Resume deliveries of a paused session
+///
+/// Returns immediately with the session in the `resuming` state; the
+/// buffered events are delivered in the background, oldest first. Events
+/// arriving meanwhile are buffered behind them, and the session is unpaused
+/// once none are left.
+async fn resume_session(
+    State(state): State<AppState>,
+    Extension(principal): Extension<AdminPrincipal>,
+    Path(session_id): Path<String>,
+) -> Response {
+    let store = state.delivery_config.session_control.clone();
+    let Some((session_id, status)) = SessionId::new(session_id.clone())
+        .ok()
+        .and_then(|id| store.resume(&id).map(|status| (id, status)))
+    else {
+        return (
+            StatusCode::CONFLICT,
+            Json(json!({
+                "error": "session_not_paused",
+                "message": format!("Session '{}' is not paused", session_id)
+            })),
+        )
+            .into_response();
+    };
+
+    info!(
+        principal = %principal.name,
+        session_id = %session_id,
+        buffered_events = status.buffered_events,
+        "Resuming session deliveries"
+    );
+
+    match &state.queue_client {
+        Some(queue_client) => {
+            tokio::spawn(queue_delivery::release_session_events(
+                session_id.clone(),
+                state.event_router.clone(),
+                state.bot_config.current(),
+                queue_client.clone(),
+                state.delivery_config.clone(),
+            ));
+        }
+        None => {
+            // Queue delivery is disabled; there is nowhere to release to.
+            store.remove(&session_id);
+        }
+    }
+
+    record_pause_audit(
+        &state,
+        &principal,
+        "session",
+        session_id.as_str(),
+        "session_resume",
+        None,
+    )
+    .await;
+
+    (StatusCode::ACCEPTED, Json(status)).into_response()
+}
+
+/// Record a bot or session pause or resume in the audit log.
+async fn record_pause_audit(
+    state: &AppState,
+    principal: &AdminPrincipal,
+    resource_type: &str,
+    resource_id: &str,
+    operation: &str,
+    reason: Option<&str>,
+) {
+    let Some(audit_logger) = &state.audit_logger else {
+        return;
+    };
+    if let Err(e) = audit_logger
+        .log_admin_action(
+            principal.to_audit_actor(),
+            AuditResource::Administrative {
+                resource_type: resource_type.to_string(),
+                resource_id: resource_id.to_string(),
+            },
+            AuditAction::Custom {
+                operation: operation.to_string(),
+                details: reason.map(str::to_string),
+            },
+            AuditResult::Success {
+                duration: None,
+                details: None,
+            },
+            AuditContext::default(),
+        )
+        .await
+    {
+        warn!(
+            error = %e,
+            resource_type,
+            resource_id,
+            "Failed to record pause audit entry"
+        );
+    }
+}
+
+/// List delivery audit records, newest first
+///
+/// Filters by `event_id` and `queue`; returns 503 when delivery auditing is
+/// not enabled.
+#[instrument(skip(state))]
+async fn list_delivery_audit_records(
+    State(state): State<AppState>,
+    Query(query): Query<delivery_audit::DeliveryAuditQuery>,
+) -> Response {
+    let auditor = &state.delivery_config.delivery_audit;
+    if !auditor.is_enabled() {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({
+                "error": "delivery_audit_not_configured",
+                "message": "Delivery auditing is not enabled"
+            })),
+        )
+            .into_response();
+    }
+
+    match auditor.query(&query).await {
+        Ok(records) => Json(records).into_response(),
+        Err(e) => {
+            error!(error = %e, "Failed to query delivery audit records");
+            StatusCode::INTERNAL_SERVER_ERROR.into_response()
+        }
+    }
+}
+
+/// Report payload retention sweeps and the data they reclaimed
+///
+/// Returns 503 when payload retention is not enabled.
+#[instrument(skip(state))]
+async fn get_payload_retention(State(state): State<AppState>) -> Response {
+    match &state.payload_retention {
+        Some(sweeper) => Json(sweeper.status()).into_response(),
+        None => (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(json!({
+                "error": "payload_retention_not_configured",
+                "message": "Payload retention is not enabled"
+            })),
+        )
+            .into_response(),
+    }
+}
+
+/// List the most recently routed events, newest first (developer mode only)
+#[instrument(skip(state))]
+async fn list_last_events(
+    State(state): State<AppState>,
+    Query(query): Query<developer::RecentEventsQuery>,
+) -> Response {
+    match &state.delivery_config.dev_tools {
+        Some(dev_tools) => Json(dev_tools.recent_events(query.limit())).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
 }
 
 /// Reset metrics (for development/testing)
@@ -746,22 +2981,122 @@ async fn request_logging_middleware(
 
 /// Metrics collection middleware
 ///
-/// Records HTTP request metrics including:
-/// - Request/response duration histogram
+/// Reject a reloaded bot configuration that the running router cannot
+/// serve without a restart.
+fn reload_check(
+    current: &BotConfiguration,
+    new: &BotConfiguration,
+    has_key_vault: bool,
+    topic_routing: bool,
+) -> Result<(), BotConfigError> {
+    let reject = |message: String| {
+        Err(BotConfigError::ValidationError {
+            errors: vec![message],
+        })
+    };
+    if topic_routing && current != new {
+        return reject(
+            "topic subscription rules are provisioned at startup; restart to change bots \
+             while routing.mode is topic"
+                .to_string(),
+        );
+    }
+    if !has_key_vault {
+        if let Some(message) = key_vault_requirement(new) {
+            return reject(message);
+        }
+    }
+    let transform = |config: &BotConfiguration, name: &BotName| {
+        config
+            .bots
+            .iter()
+            .find(|bot| &bot.name == name)
+            .and_then(|bot| bot.transform.clone())
+    };
+    for bot in current.bots.iter().chain(&new.bots) {
+        if transform(current, &bot.name) != transform(new, &bot.name) {
+            return reject(format!(
+                "Bot '{}' changes its transform; transforms are compiled at startup, restart to apply",
+                bot.name
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Describe the first bot of `config` that needs a Key Vault: field
+/// encryption keys and HTTP destination signing secrets are read from it.
+fn key_vault_requirement(config: &BotConfiguration) -> Option<String> {
+    config.bots.iter().find_map(|bot| {
+        let feature = if bot.encryption.is_some() {
+            "field encryption"
+        } else if bot.http.is_some() {
+            "an HTTP destination"
+        } else {
+            return None;
+        };
+        Some(format!(
+            "Bot '{}' uses {}, which requires a Key Vault (key_vault configuration)",
+            bot.name, feature
+        ))
+    })
+}
+
+/// Build the router for topic mode after checking every bot can be served
+/// from the topic.
+///
+/// The provider has no management client for subscription rules, so the
+/// materialized rules are logged for provisioning alongside the topic.
+fn topic_event_router(
+    topic: &str,
+    bot_config: &BotConfiguration,
+) -> Result<TopicEventRouter, ServiceError> {
+    let invalid = |message: String| ServiceError::Configuration(ConfigError::Invalid { message });
+    let topic = queue_keeper_core::QueueName::new(topic.to_string())
+        .map_err(|e| invalid(format!("routing.topic is invalid: {}", e)))?;
+    let rules = subscription_rules(bot_config).map_err(|e| invalid(e.to_string()))?;
+    for rule in &rules {
+        info!(
+            topic = %topic,
+            subscription = %rule.subscription,
+            filter = %rule.filter,
+            requires_session = rule.requires_session,
+            "Topic subscription rule"
+        );
+    }
+    info!(
+        topic = %topic,
+        subscriptions = rules.len(),
+        "Publishing events to topic; each bot reads from its own subscription"
+    );
+    Ok(TopicEventRouter::new(topic))
+}
+
+/// Records HTTP request metrics into [`ServiceMetrics`] including:
+/// - Request/response duration histogram, overall and per route
 /// - Request/response size tracking
-/// - Status code distribution
-/// - Active request gauge
-#[instrument(skip(request, next), fields(
+/// - Status code distribution per route, tenant and bot
+///
+/// The route label is the matched route template (`/webhook/{provider}`), or
+/// `unmatched` for requests no route handled. The tenant and bot labels are
+/// only set when the path names a registered provider or configured bot, so
+/// arbitrary paths cannot grow the label set.
+#[instrument(skip(state, request, next), fields(
     method = %request.method(),
     path
 ))]
 async fn metrics_middleware(
+    State(state): State<AppState>,
     request: axum::extract::Request,
     next: axum::middleware::Next,
 ) -> Response {
     let start = std::time::Instant::now();
     let method = request.method().clone();
     let uri = request.uri().path().to_string();
+    let route = request
+        .extensions()
+        .get::<axum::extract::MatchedPath>()
+        .map(|matched| matched.as_str().to_string());
 
     // Normalize path for metrics (remove IDs, keep structure)
     // This prevents cardinality explosion in metrics
@@ -799,9 +3134,61 @@ async fn metrics_middleware(
         "HTTP request metrics"
     );
 
+    let route = route.as_deref().unwrap_or(UNMATCHED_ROUTE_LABEL);
+    let (tenant, bot) = resolve_route_tenant(&state, route, &uri);
+    state.metrics.record_http_route_request(
+        &metrics::HttpRouteLabels {
+            method: method.as_str(),
+            route,
+            status: status.as_str(),
+            tenant: tenant.unwrap_or(NO_TENANT_LABEL),
+            bot: bot.unwrap_or(NO_TENANT_LABEL),
+        },
+        duration,
+        request_size,
+        response_size,
+    );
+
     response
 }
 
+/// Route label for requests that did not match any route.
+const UNMATCHED_ROUTE_LABEL: &str = "unmatched";
+
+/// Tenant and bot label when the request is not addressed to one.
+const NO_TENANT_LABEL: &str = "none";
+
+/// Resolve the provider and bot a request was addressed to.
+///
+/// Walks the matched route template alongside the request path and picks
+/// the `{provider}` and `{bot_name}` segments. A segment is only returned
+/// when it names a registered provider or configured bot.
+fn resolve_route_tenant<'a>(
+    state: &AppState,
+    route: &str,
+    path: &'a str,
+) -> (Option<&'a str>, Option<&'a str>) {
+    let mut tenant = None;
+    let mut bot = None;
+    for (template, segment) in route.split('/').zip(path.split('/')) {
+        match template {
+            "{provider}" if state.provider_registry.contains(segment) => tenant = Some(segment),
+            "{bot_name}"
+                if state
+                    .bot_config
+                    .current()
+                    .bots
+                    .iter()
+                    .any(|b| b.name.as_str() == segment) =>
+            {
+                bot = Some(segment)
+            }
+            _ => {}
+        }
+    }
+    (tenant, bot)
+}
+
 /// Check if a string looks like a UUID with proper 8-4-4-4-12 hyphen pattern
 ///
 /// Validates UUID format by checking:
@@ -866,3 +3253,6 @@ fn normalize_path_for_metrics(path: &str) -> String {
 #[cfg(test)]
 #[path = "lib_tests.rs"]
 mod tests;
+
+#[cfg(test)]
+mod test_fixtures;
@@ -1,6 +1,7 @@
-//! Tests for provider-specific webhook routing in the HTTP layer.
+//! Tests for provider-specific webhook routing and admin endpoints in the HTTP layer.
 
 use super::*;
+use crate::test_fixtures::event_of_type;
 use async_trait::async_trait;
 use axum::{
     body::Body,
@@ -51,7 +52,7 @@ impl WebhookProcessor for MockWebhookProcessor {
         _request: WebhookRequest,
     ) -> Result<ProcessingOutput, WebhookError> {
         *self.called.lock().unwrap() = true;
-        Ok(ProcessingOutput::Wrapped(test_wrapped_event()))
+        Ok(ProcessingOutput::Wrapped(event_of_type("ping")))
     }
 
     async fn validate_signature(
@@ -79,7 +80,7 @@ impl WebhookProcessor for MockWebhookProcessor {
         &self,
         _request: &WebhookRequest,
     ) -> Result<WrappedEvent, NormalizationError> {
-        Ok(test_wrapped_event())
+        Ok(event_of_type("ping"))
     }
 }
 
@@ -87,18 +88,6 @@ impl WebhookProcessor for MockWebhookProcessor {
 // Test helpers
 // ============================================================================
 
-/// Build a minimal [`WrappedEvent`] suitable for mock responses.
-fn test_wrapped_event() -> WrappedEvent {
-    WrappedEvent::new(
-        "github".to_string(),
-        "ping".to_string(),
-        None,
-        None,
-        serde_json::json!({}),
-        None,
-    )
-}
-
 /// Returns a shared [`ServiceMetrics`] instance.
 ///
 /// Prometheus registers metrics with a global registry that rejects duplicate
@@ -129,10 +118,12 @@ fn test_app_state(registry: ProviderRegistry) -> AppState {
         std::collections::HashSet::new(),
         None, // queue delivery disabled in unit tests
         Arc::new(queue_keeper_core::queue_integration::DefaultEventRouter::new()),
-        Arc::new(queue_keeper_core::bot_config::BotConfiguration {
-            bots: vec![],
-            settings: queue_keeper_core::bot_config::BotConfigurationSettings::default(),
-        }),
+        Arc::new(queue_keeper_core::bot_config::SharedBotConfiguration::new(
+            queue_keeper_core::bot_config::BotConfiguration {
+                bots: vec![],
+                settings: queue_keeper_core::bot_config::BotConfigurationSettings::default(),
+            },
+        )),
         queue_delivery::QueueDeliveryConfig::default(),
         None, // ip_rate_limiter: disabled in unit tests
         None, // admin_api_key: no auth in unit tests
@@ -178,6 +169,179 @@ async fn test_known_provider_routes_to_processor() {
     );
 }
 
+/// A processed webhook's stage breakdown is exposed through /debug/vars.
+#[tokio::test]
+async fn test_webhook_stage_timings_in_debug_vars() {
+    let mut registry = ProviderRegistry::new();
+    registry.register(
+        ProviderId::new("github").unwrap(),
+        Arc::new(MockWebhookProcessor::new()),
+    );
+    let app = create_router(test_app_state(registry));
+
+    let response = app
+        .clone()
+        .oneshot(ping_request("/webhook/github"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/debug/vars")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let vars = &json["vars"];
+    assert!(vars["webhook_stage.last_request"]
+        .as_str()
+        .unwrap()
+        .contains("header_parse="));
+    assert_ne!(vars["webhook_stage.header_parse.count"], "0");
+    assert!(vars["webhook_stage.normalization.mean_ms"].is_string());
+}
+
+/// The startup report is served at /debug/startup-report when present.
+#[tokio::test]
+async fn test_debug_startup_report() {
+    let request = || {
+        Request::builder()
+            .uri("/debug/startup-report")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+    let response = app.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let report = StartupReport::new(
+        &ServiceConfig::default(),
+        vec![],
+        vec![],
+        RuntimeEnvironment::default(),
+    );
+    let app = create_router(
+        test_app_state(ProviderRegistry::new()).with_startup_report(Arc::new(report)),
+    );
+    let response = app.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["queue_backend"], "in_memory");
+    assert!(json["preflight"].as_array().unwrap().len() >= 3);
+}
+
+/// The previous shutdown report is served at /debug/last-shutdown when
+/// present.
+#[tokio::test]
+async fn test_debug_last_shutdown() {
+    let request = || {
+        Request::builder()
+            .uri("/debug/last-shutdown")
+            .body(Body::empty())
+            .unwrap()
+    };
+
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+    let response = app.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let recorder = ShutdownRecorder::new(
+        ShutdownReportConfig {
+            enabled: false,
+            ..ShutdownReportConfig::default()
+        },
+        Arc::new(Supervisor::new(SupervisorConfig::default())),
+    );
+    let report = recorder.report(ExitReason::Unclean);
+    let app = create_router(
+        test_app_state(ProviderRegistry::new()).with_last_shutdown_report(Arc::new(report)),
+    );
+    let response = app.oneshot(request()).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["reason"]["kind"], "unclean");
+}
+
+/// `/api/stats` reports the statistics collector of the state.
+#[tokio::test]
+async fn test_statistics_endpoint() {
+    let state = test_app_state(ProviderRegistry::new());
+    state
+        .statistics
+        .record_event("issues", queue_keeper_core::webhook::EventOrigin::Github);
+    state.statistics.record_failure();
+    let app = create_router(state);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/stats")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["total_events"], 1);
+    assert_eq!(json["events_per_hour"], 1.0);
+    assert_eq!(json["error_rate"], 0.5);
+    assert_eq!(json["active_sessions"], 0);
+    assert_eq!(json["events_by_type"]["issues"], 1);
+    assert_eq!(json["events_by_origin"]["github"], 1);
+}
+
+/// Payload statistics report the profiler as disabled until it is attached.
+#[tokio::test]
+async fn test_payload_statistics_endpoint() {
+    let request = || {
+        Request::builder()
+            .uri("/api/stats/payloads")
+            .body(Body::empty())
+            .unwrap()
+    };
+    let read = |response: axum::response::Response| async move {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+    };
+
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+    let json = read(app.oneshot(request()).await.unwrap()).await;
+    assert_eq!(json["enabled"], false);
+
+    let profiler = Arc::new(PayloadProfiler::new(PayloadProfilerConfig {
+        enabled: true,
+        sample_rate: 1.0,
+        ..Default::default()
+    }));
+    profiler.record("issues", &serde_json::json!({"action": "opened"}), 20);
+    let app =
+        create_router(test_app_state(ProviderRegistry::new()).with_payload_profiler(profiler));
+    let json = read(app.oneshot(request()).await.unwrap()).await;
+    assert_eq!(json["enabled"], true);
+    assert_eq!(json["event_types"][0]["event_type"], "issues");
+    assert_eq!(json["event_types"][0]["fields"][0]["path"], "action");
+}
+
 /// Verify that POST /webhook/{unknown} returns 404 when the provider is not
 /// registered.
 #[tokio::test]
@@ -272,6 +436,318 @@ async fn test_unknown_provider_404_has_error_body() {
     );
 }
 
+/// In strict mode an unknown GitHub event type is rejected before it reaches
+/// the processor, while known types are still processed.
+#[tokio::test]
+async fn test_unknown_event_type_rejected_in_strict_mode() {
+    let mock = Arc::new(MockWebhookProcessor::new());
+    let mut registry = ProviderRegistry::new();
+    registry.register(ProviderId::new("github").unwrap(), mock.clone());
+    let mut state = test_app_state(registry);
+    state.config.unknown_event_types.mode = UnknownEventTypeMode::Reject;
+    let app = create_router(state);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/webhook/github")
+        .header("x-github-event", "some_future_github_event")
+        .header("x-github-delivery", "12345678-1234-1234-1234-123456789abc")
+        .header("content-type", "application/json")
+        .body(Body::empty())
+        .unwrap();
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert!(!mock.was_called());
+
+    let response = app.oneshot(ping_request("/webhook/github")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(mock.was_called());
+}
+
+/// A redelivery of a processed delivery ID is answered with
+/// `already_processed` and the original event ID.
+#[tokio::test]
+async fn test_duplicate_delivery_reports_already_processed() {
+    let store = Arc::new(queue_keeper_core::webhook::ReplayGuard::new(
+        std::time::Duration::from_secs(60),
+        100,
+    ));
+    let provider = queue_keeper_core::webhook::GithubWebhookProvider::new(None, None, None)
+        .with_deduplication(store);
+    let mut registry = ProviderRegistry::new();
+    registry.register(ProviderId::new("github").unwrap(), Arc::new(provider));
+    let app = create_router(test_app_state(registry));
+
+    let delivery = || {
+        let payload = serde_json::json!({
+            "ref": "refs/heads/main",
+            "repository": {
+                "id": 1,
+                "name": "repo",
+                "full_name": "org/repo",
+                "owner": { "id": 2, "login": "org", "type": "Organization" }
+            }
+        });
+        Request::builder()
+            .method("POST")
+            .uri("/webhook/github")
+            .header("x-github-event", "push")
+            .header("x-github-delivery", "12345678-1234-1234-1234-123456789abc")
+            // Not checked: the provider has no signature validator.
+            .header("x-hub-signature-256", "sha256=unchecked")
+            .header("content-type", "application/json")
+            .body(Body::from(payload.to_string()))
+            .unwrap()
+    };
+    let json =
+        |bytes: axum::body::Bytes| -> serde_json::Value { serde_json::from_slice(&bytes).unwrap() };
+
+    let first = app.clone().oneshot(delivery()).await.unwrap();
+    assert_eq!(first.status(), StatusCode::OK);
+    let first = json(
+        axum::body::to_bytes(first.into_body(), usize::MAX)
+            .await
+            .unwrap(),
+    );
+    assert_eq!(first["status"], "processed");
+
+    let second = app.oneshot(delivery()).await.unwrap();
+    assert_eq!(second.status(), StatusCode::OK);
+    let second = json(
+        axum::body::to_bytes(second.into_body(), usize::MAX)
+            .await
+            .unwrap(),
+    );
+    assert_eq!(second["status"], "already_processed");
+    assert_eq!(second["event_id"], first["event_id"]);
+}
+
+/// Build a `ping` webhook request carrying `size` bytes of body.
+fn sized_ping_request(size: usize, declare_length: bool) -> Request<Body> {
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri("/webhook/github")
+        .header("x-github-event", "ping")
+        .header("x-github-delivery", "12345678-1234-1234-1234-123456789abc")
+        .header("content-type", "application/json");
+    if declare_length {
+        builder = builder.header("content-length", size);
+    }
+    builder.body(Body::from(vec![b' '; size])).unwrap()
+}
+
+/// Webhook bodies over `server.max_body_size` are rejected with 413 and the
+/// configured maximum, whether or not their length is declared.
+#[tokio::test]
+async fn test_webhook_body_over_max_size_rejected() {
+    let mock = Arc::new(MockWebhookProcessor::new());
+    let mut registry = ProviderRegistry::new();
+    registry.register(ProviderId::new("github").unwrap(), mock.clone());
+    let mut state = test_app_state(registry);
+    state.config.server.max_body_size = queue_keeper_core::units::ByteSize::kib(1);
+    let app = create_router(state);
+
+    for declare_length in [true, false] {
+        let response = app
+            .clone()
+            .oneshot(sized_ping_request(1025, declare_length))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert!(json["error"].as_str().unwrap().contains("max: 1024 bytes"));
+    }
+    assert!(!mock.was_called());
+
+    let response = app.oneshot(sized_ping_request(1024, true)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(mock.was_called());
+}
+
+/// The configured maximum replaces axum's default 2 MB body limit.
+#[tokio::test]
+async fn test_webhook_body_above_axum_default_accepted() {
+    let mock = Arc::new(MockWebhookProcessor::new());
+    let mut registry = ProviderRegistry::new();
+    registry.register(ProviderId::new("github").unwrap(), mock.clone());
+    let app = create_router(test_app_state(registry));
+
+    let response = app
+        .oneshot(sized_ping_request(3 * 1024 * 1024, false))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(mock.was_called());
+}
+
+// ============================================================================
+// Source allow-list tests
+// ============================================================================
+
+/// Build an [`AppState`] with a github.com provider and a GHES provider whose
+/// installation registry holds only installation 7.
+fn multi_source_state() -> (
+    AppState,
+    Arc<MockWebhookProcessor>,
+    Arc<MockWebhookProcessor>,
+) {
+    let github = Arc::new(MockWebhookProcessor::new());
+    let ghes = Arc::new(MockWebhookProcessor::new());
+    let mut registry = ProviderRegistry::new();
+    registry.register(ProviderId::new("github").unwrap(), github.clone());
+    registry.register(ProviderId::new("ghes-eu").unwrap(), ghes.clone());
+
+    let mut state = test_app_state(registry);
+    let provider = |id: &str| ProviderConfig {
+        id: id.to_string(),
+        require_signature: false,
+        secret: None,
+        additional_secrets: vec![],
+        allowed_event_types: vec![],
+        github_enterprise: None,
+        installation_ids: vec![],
+        per_repository_secrets: false,
+    };
+    state.config.providers = vec![
+        provider("github"),
+        ProviderConfig {
+            github_enterprise: Some(GitHubEnterpriseConfig {
+                base_url: "https://ghes-eu.corp.example".to_string(),
+                api_base_url: None,
+                signature_scheme: GitHubSignatureScheme::Sha256,
+                host: None,
+            }),
+            installation_ids: vec![7],
+            ..provider("ghes-eu")
+        },
+    ];
+    (state, github, ghes)
+}
+
+/// Build a `ping` request announcing `enterprise_host` for `installation_id`.
+fn source_request(
+    path: &str,
+    enterprise_host: Option<&str>,
+    installation_id: u64,
+) -> Request<Body> {
+    let mut builder = Request::builder()
+        .method("POST")
+        .uri(path)
+        .header("x-github-event", "ping")
+        .header("x-github-delivery", "12345678-1234-1234-1234-123456789abc")
+        .header("content-type", "application/json");
+    if let Some(host) = enterprise_host {
+        builder = builder.header("x-github-enterprise-host", host);
+    }
+    builder
+        .body(Body::from(
+            serde_json::json!({ "installation": { "id": installation_id } }).to_string(),
+        ))
+        .unwrap()
+}
+
+/// A GHES delivery with the expected host and a registered installation is
+/// processed by the GHES provider.
+#[tokio::test]
+async fn test_enterprise_delivery_from_expected_source_is_accepted() {
+    let (state, _github, ghes) = multi_source_state();
+    let app = create_router(state);
+
+    let response = app
+        .oneshot(source_request(
+            "/webhook/ghes-eu",
+            Some("GHES-EU.corp.example"),
+            7,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(ghes.was_called());
+}
+
+/// Deliveries announcing the wrong instance, or none, are rejected with 403
+/// before reaching the processor.
+#[tokio::test]
+async fn test_enterprise_host_mismatch_is_rejected() {
+    let (state, github, ghes) = multi_source_state();
+    let app = create_router(state);
+
+    for request in [
+        source_request("/webhook/ghes-eu", Some("ghes-us.corp.example"), 7),
+        source_request("/webhook/ghes-eu", None, 7),
+        source_request("/webhook/github", Some("ghes-us.corp.example"), 7),
+    ] {
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    }
+    assert!(!ghes.was_called());
+    assert!(!github.was_called());
+}
+
+/// A GHES delivery sent to the github.com provider is handled by the
+/// provider configured for its host, and checked against its registry.
+#[tokio::test]
+async fn test_enterprise_delivery_resolved_by_host() {
+    let (state, github, ghes) = multi_source_state();
+    let app = create_router(state);
+
+    let response = app
+        .clone()
+        .oneshot(source_request(
+            "/webhook/github",
+            Some("ghes-eu.corp.example"),
+            8,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    assert!(!ghes.was_called());
+
+    let response = app
+        .oneshot(source_request(
+            "/webhook/github",
+            Some("ghes-eu.corp.example"),
+            7,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(ghes.was_called());
+    assert!(!github.was_called());
+}
+
+/// An installation missing from the provider's registry is rejected.
+#[tokio::test]
+async fn test_unregistered_installation_is_rejected() {
+    let (state, _github, ghes) = multi_source_state();
+    let app = create_router(state);
+
+    let response = app
+        .oneshot(source_request(
+            "/webhook/ghes-eu",
+            Some("ghes-eu.corp.example"),
+            8,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::FORBIDDEN);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert!(json["error"]
+        .as_str()
+        .unwrap()
+        .contains("installation 8 is not registered"));
+    assert!(!ghes.was_called());
+}
+
 // ============================================================================
 // Event API handler tests
 // ============================================================================
@@ -287,9 +763,9 @@ impl EventStore for AlwaysFailingEventStore {
     ) -> Result<EventListResponse, QueueKeeperError> {
         Ok(EventListResponse {
             events: vec![],
-            total: 0,
-            page: params.page.unwrap_or(1),
-            per_page: params.per_page.unwrap_or(50),
+            next_cursor: None,
+            limit: params.limit.unwrap_or(50),
+            total: Some(0),
         })
     }
 
@@ -305,7 +781,8 @@ impl EventStore for AlwaysFailingEventStore {
     ) -> Result<SessionListResponse, QueueKeeperError> {
         Ok(SessionListResponse {
             sessions: vec![],
-            total: 0,
+            next_cursor: None,
+            total: Some(0),
         })
     }
 
@@ -325,6 +802,8 @@ impl EventStore for AlwaysFailingEventStore {
             active_sessions: 0,
             error_rate: 0.0,
             uptime_seconds: 0,
+            events_by_type: Default::default(),
+            events_by_origin: Default::default(),
         })
     }
 }
@@ -344,10 +823,12 @@ fn test_app_state_with_store(store: impl EventStore + 'static) -> AppState {
         std::collections::HashSet::new(),
         None,
         Arc::new(queue_keeper_core::queue_integration::DefaultEventRouter::new()),
-        Arc::new(queue_keeper_core::bot_config::BotConfiguration {
-            bots: vec![],
-            settings: queue_keeper_core::bot_config::BotConfigurationSettings::default(),
-        }),
+        Arc::new(queue_keeper_core::bot_config::SharedBotConfiguration::new(
+            queue_keeper_core::bot_config::BotConfiguration {
+                bots: vec![],
+                settings: queue_keeper_core::bot_config::BotConfigurationSettings::default(),
+            },
+        )),
         queue_delivery::QueueDeliveryConfig::default(),
         None,
         None,
@@ -397,3 +878,1194 @@ async fn test_get_session_returns_500_on_internal_error() {
         "Internal storage errors must surface as 500, not 404"
     );
 }
+
+/// GET /api/sessions and /api/sessions/{session_id} must report the sessions
+/// recorded by the session tracker.
+#[tokio::test]
+async fn test_sessions_served_from_tracker() {
+    let state = test_app_state(ProviderRegistry::new());
+    let event = WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        Some(SessionId::from_parts("owner", "repo", "pull_request", "1")),
+        serde_json::json!({}),
+        None,
+    );
+    state
+        .delivery_config
+        .session_tracker
+        .record_received(&event);
+    let app = create_router(state);
+
+    let list = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/sessions?pending=true")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(list.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(list.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["total"], 1);
+    assert_eq!(
+        json["sessions"][0]["session_id"],
+        "owner/repo/pull_request/1"
+    );
+    assert_eq!(json["sessions"][0]["status"], "active");
+    assert_eq!(json["sessions"][0]["pending_events"], 1);
+
+    let detail = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/sessions/owner%2Frepo%2Fpull_request%2F1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(detail.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(detail.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["session"]["event_count"], 1);
+    assert_eq!(
+        json["session"]["events"][0]["event_id"],
+        event.event_id.to_string()
+    );
+    assert_eq!(json["session"]["events"][0]["status"], "pending");
+}
+
+/// GET /api/sessions/stuck must report in-flight events and no stuck session
+/// while every event is younger than `sessions.stuck_after`.
+#[tokio::test]
+async fn test_stuck_sessions_route() {
+    let state = test_app_state(ProviderRegistry::new());
+    let event = WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        Some(SessionId::from_parts("owner", "repo", "pull_request", "1")),
+        serde_json::json!({}),
+        None,
+    );
+    state
+        .delivery_config
+        .session_tracker
+        .record_received(&event);
+
+    let response = create_router(state)
+        .oneshot(
+            Request::builder()
+                .uri("/api/sessions/stuck")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["stuck_after_seconds"], 900);
+    assert_eq!(json["in_flight_events"], 1);
+    assert_eq!(json["sessions"], serde_json::json!([]));
+}
+
+// ============================================================================
+// Session reset tests
+// ============================================================================
+
+/// Build a session reset request with the given JSON body.
+fn reset_request(session_path: &str, body: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/admin/sessions/{}/reset", session_path))
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// POST /admin/sessions/{session_id}/reset must bump the session epoch and
+/// report it in the response.
+#[tokio::test]
+async fn test_reset_session_bumps_epoch() {
+    let state = test_app_state(ProviderRegistry::new());
+    let session_epochs = state.delivery_config.session_epochs.clone();
+    let app = create_router(state);
+
+    let response = app
+        .oneshot(reset_request(
+            "owner%2Frepo%2Fpull_request%2F1",
+            r#"{"reason":"stuck consumer"}"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["session_epoch"], 1);
+    assert_eq!(json["previous_epoch"], 0);
+    assert_eq!(
+        session_epochs.current_epoch(&SessionId::from_parts("owner", "repo", "pull_request", "1")),
+        1
+    );
+}
+
+/// A session reset without a reason must be rejected with 400.
+#[tokio::test]
+async fn test_reset_session_requires_reason() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(reset_request(
+            "owner%2Frepo%2Fpull_request%2F1",
+            r#"{"reason":"  "}"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+/// A session reset with a malformed session ID must be rejected with 400.
+#[tokio::test]
+async fn test_reset_session_rejects_invalid_session_id() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(reset_request("owner%2F%2Frepo", r#"{"reason":"cleanup"}"#))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+// ============================================================================
+// Event deletion tests
+// ============================================================================
+
+/// Build an event deletion request with the given JSON body.
+fn delete_request(event_id: &str, body: &str) -> Request<Body> {
+    Request::builder()
+        .method("DELETE")
+        .uri(format!("/admin/events/{}", event_id))
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// DELETE /admin/events/{event_id} must record a tombstone and hide the
+/// event from listings.
+#[tokio::test]
+async fn test_delete_event_soft_deletes() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage: Arc<dyn BlobStorage> = Arc::new(
+        queue_keeper_core::adapters::filesystem_storage::FilesystemBlobStorage::new(
+            dir.path().to_path_buf(),
+        )
+        .await
+        .unwrap(),
+    );
+    let event = WrappedEvent::new(
+        "github".to_string(),
+        "push".to_string(),
+        None,
+        None,
+        serde_json::json!({}),
+        None,
+    );
+    store_wrapped_event_to_blob(storage.as_ref(), &event)
+        .await
+        .unwrap();
+    let mut state = test_app_state(ProviderRegistry::new());
+    state.event_store = Arc::new(BlobBackedEventStore::new(storage));
+    let event_store = state.event_store.clone();
+
+    let response = create_router(state)
+        .oneshot(delete_request(
+            &event.event_id.to_string(),
+            r#"{"reason":"contains a leaked token"}"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["status"], "deleted");
+    assert_eq!(json["reason"], "contains a leaked token");
+    assert!(json["purge_at"].is_string());
+    let listed = event_store
+        .list_events(EventListParams::default())
+        .await
+        .unwrap();
+    assert!(listed.events.is_empty());
+}
+
+/// An event deletion without a reason must be rejected with 400.
+#[tokio::test]
+async fn test_delete_event_requires_reason() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(delete_request(
+            &EventId::new().to_string(),
+            r#"{"reason":" "}"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+// ============================================================================
+// Event replay tests
+// ============================================================================
+
+/// Build an event replay request, with a JSON body unless `body` is empty.
+fn replay_request(event_id: &str, body: &str) -> Request<Body> {
+    let builder = Request::builder()
+        .method("POST")
+        .uri(format!("/admin/events/{}/replay", event_id));
+    if body.is_empty() {
+        builder.body(Body::empty()).unwrap()
+    } else {
+        builder
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap()
+    }
+}
+
+/// Replaying without event storage must be rejected with 503.
+#[tokio::test]
+async fn test_replay_event_requires_event_storage() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(replay_request(&EventId::new().to_string(), ""))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+/// A replay with a malformed event ID must be rejected with 400.
+#[tokio::test]
+async fn test_replay_event_rejects_invalid_event_id() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(replay_request("not-a-ulid", r#"{"dry_run":true}"#))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+// ============================================================================
+// DLQ archive restore tests
+// ============================================================================
+
+/// Build a DLQ archive restore request for the given bundle ID.
+fn restore_request(bundle_id: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/admin/dlq/archives/{}/restore", bundle_id))
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Restoring a bundle without DLQ storage must be rejected with 503.
+#[tokio::test]
+async fn test_restore_dlq_archive_requires_dlq() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(restore_request(&EventId::new().to_string()))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+/// A restore with a malformed bundle ID must be rejected with 400.
+#[tokio::test]
+async fn test_restore_dlq_archive_rejects_invalid_bundle_id() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app.oneshot(restore_request("not-a-ulid")).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+// ============================================================================
+// Bot pause tests
+// ============================================================================
+
+/// Build a bot pause or resume request.
+fn bot_pause_request(bot_name: &str, action: &str, body: &str) -> Request<Body> {
+    Request::builder()
+        .method("POST")
+        .uri(format!("/admin/bots/{}/{}", bot_name, action))
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// Pausing a bot that is not configured must be rejected with 404.
+#[tokio::test]
+async fn test_pause_unknown_bot_returns_404() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(bot_pause_request(
+            "missing-bot",
+            "pause",
+            r#"{"reason":"maintenance"}"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+/// Resuming a bot that is not paused must be rejected with 409.
+#[tokio::test]
+async fn test_resume_unpaused_bot_returns_409() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(bot_pause_request("missing-bot", "resume", ""))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+// ============================================================================
+// Self-test tests
+// ============================================================================
+
+/// POST /admin/selftest reports every stage and passes without backends.
+#[tokio::test]
+async fn test_selftest_reports_stages() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/admin/selftest")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let report: SelfTestReport = serde_json::from_slice(&body).unwrap();
+    assert!(report.passed);
+    assert_eq!(report.stages.len(), 5);
+}
+
+// ============================================================================
+// Log level tests
+// ============================================================================
+
+/// PUT /admin/logging/level replaces the subscriber's filter, and GET
+/// reports the filter in effect.
+#[tokio::test]
+async fn test_set_log_level_reloads_filter() {
+    let (_layer, handle) =
+        tracing_subscriber::reload::Layer::<_, tracing_subscriber::Registry>::new(
+            tracing_subscriber::EnvFilter::new("queue_keeper_api=info"),
+        );
+    let log_filter = LogFilter::new(handle);
+    let app =
+        create_router(test_app_state(ProviderRegistry::new()).with_log_filter(log_filter.clone()));
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .method("PUT")
+                .uri("/admin/logging/level")
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"level":"DEBUG"}"#))
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(log_filter.filter().contains("queue_keeper_api=debug"));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/admin/logging/level")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["level"], "debug");
+    assert!(json["filter"]
+        .as_str()
+        .unwrap()
+        .contains("queue_keeper_core=debug"));
+}
+
+// ============================================================================
+// Delivery audit tests
+// ============================================================================
+
+fn delivery_audit_request(query: &str) -> Request<Body> {
+    Request::builder()
+        .method("GET")
+        .uri(format!("/admin/audit/deliveries{}", query))
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Querying delivery audit records while auditing is disabled returns 503.
+#[tokio::test]
+async fn test_delivery_audit_requires_enabled_auditor() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app.oneshot(delivery_audit_request("")).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+/// GET /admin/audit/deliveries returns the records of the requested event.
+#[tokio::test]
+async fn test_delivery_audit_returns_event_records() {
+    let dir = tempfile::tempdir().unwrap();
+    let auditor = DeliveryAuditor::from_config(&DeliveryAuditConfig {
+        enabled: true,
+        directory: dir.path().to_path_buf(),
+        ..DeliveryAuditConfig::default()
+    })
+    .unwrap();
+    let event = event_of_type("ping");
+    let delivery = queue_keeper_core::queue_integration::SuccessfulDelivery {
+        bot_name: BotName::new("audit-bot").unwrap(),
+        queue_name: queue_keeper_core::QueueName::new("queue-keeper-audit-bot").unwrap(),
+        message_id: queue_runtime::MessageId::new(),
+    };
+    auditor
+        .record(&event, std::slice::from_ref(&delivery))
+        .await;
+    auditor.record(&event_of_type("ping"), &[delivery]).await;
+    let mut state = test_app_state(ProviderRegistry::new());
+    state.delivery_config.delivery_audit = Arc::new(auditor);
+    let app = create_router(state);
+
+    let response = app
+        .oneshot(delivery_audit_request(&format!(
+            "?event_id={}",
+            event.event_id
+        )))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let records: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let records = records.as_array().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0]["event_id"], event.event_id.to_string());
+    assert_eq!(records[0]["queue_name"], "queue-keeper-audit-bot");
+    assert_eq!(records[0]["payload_sha256"].as_str().unwrap().len(), 64);
+}
+
+// ============================================================================
+// Payload retention tests
+// ============================================================================
+
+fn payload_retention_request() -> Request<Body> {
+    Request::builder()
+        .method("GET")
+        .uri("/admin/storage/retention")
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// Querying payload retention while it is disabled returns 503.
+#[tokio::test]
+async fn test_payload_retention_requires_enabled_sweeper() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app.oneshot(payload_retention_request()).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+}
+
+/// GET /admin/storage/retention reports the sweeps and reclaimed totals.
+#[tokio::test]
+async fn test_payload_retention_reports_sweeps() {
+    let dir = tempfile::tempdir().unwrap();
+    let storage = Arc::new(
+        queue_keeper_core::adapters::filesystem_storage::FilesystemBlobStorage::new(
+            dir.path().to_path_buf(),
+        )
+        .await
+        .unwrap(),
+    );
+    let sweeper = PayloadRetentionSweeper::from_config(
+        &queue_keeper_core::payload_retention::PayloadRetentionConfig {
+            enabled: true,
+            default_ttl: Some(queue_keeper_core::units::HumanDuration::from_secs(86_400)),
+            ..Default::default()
+        },
+        storage,
+    )
+    .unwrap();
+    sweeper
+        .sweep(queue_keeper_core::Timestamp::now())
+        .await
+        .unwrap();
+    let state = test_app_state(ProviderRegistry::new()).with_payload_retention(Arc::new(sweeper));
+    let app = create_router(state);
+
+    let response = app.oneshot(payload_retention_request()).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let status: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(status["action"], "delete");
+    assert_eq!(status["sweeps"], 1);
+    assert_eq!(status["totals"]["reclaimed_bytes"], 0);
+}
+
+// ============================================================================
+// Developer mode tests
+// ============================================================================
+
+fn last_events_request() -> Request<Body> {
+    Request::builder()
+        .method("GET")
+        .uri("/dev/last-events")
+        .body(Body::empty())
+        .unwrap()
+}
+
+/// The developer routes do not exist outside developer mode.
+#[tokio::test]
+async fn test_last_events_not_routed_outside_developer_mode() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app.oneshot(last_events_request()).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+/// GET /dev/last-events returns the recorded events in developer mode.
+#[tokio::test]
+async fn test_last_events_returns_recent_events() {
+    let dev_tools = Arc::new(DeveloperTools::new(DeveloperConfig {
+        enabled: true,
+        dump_envelopes: false,
+        ..DeveloperConfig::default()
+    }));
+    let event = event_of_type("ping");
+    dev_tools.record_delivery(
+        &event,
+        &queue_keeper_core::queue_integration::DeliveryResult::new(event.event_id),
+    );
+    let mut state = test_app_state(ProviderRegistry::new());
+    state.delivery_config.dev_tools = Some(dev_tools);
+    let app = create_router(state);
+
+    let response = app.oneshot(last_events_request()).await.unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let events: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    let events = events.as_array().unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(
+        events[0]["envelope"]["event_id"],
+        event.event_id.to_string()
+    );
+}
+
+// ============================================================================
+// Read-only mode tests
+// ============================================================================
+
+/// Build a read-only toggle request with the given JSON body.
+fn read_only_request(body: &str) -> Request<Body> {
+    Request::builder()
+        .method("PUT")
+        .uri("/admin/readonly")
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+/// While read-only mode is enabled, a session reset must be rejected with
+/// 409 and an explanatory body, and must not bump the epoch.
+#[tokio::test]
+async fn test_read_only_mode_rejects_session_reset() {
+    let state = test_app_state(ProviderRegistry::new());
+    let session_epochs = state.delivery_config.session_epochs.clone();
+    let app = create_router(state);
+
+    let response = app
+        .clone()
+        .oneshot(read_only_request(
+            r#"{"enabled":true,"reason":"incident 42"}"#,
+        ))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app
+        .oneshot(reset_request(
+            "owner%2Frepo%2Fpull_request%2F1",
+            r#"{"reason":"stuck consumer"}"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["error"], "read_only");
+    assert_eq!(json["reason"], "incident 42");
+    assert_eq!(
+        session_epochs.current_epoch(&SessionId::from_parts("owner", "repo", "pull_request", "1")),
+        0
+    );
+}
+
+/// Read APIs and health checks must keep working in read-only mode.
+#[tokio::test]
+async fn test_read_only_mode_allows_queries_and_health() {
+    let state = test_app_state(ProviderRegistry::new());
+    state.read_only.enable("audit", "test");
+    let app = create_router(state);
+
+    for uri in ["/admin/config", "/admin/readonly", "/health", "/api/events"] {
+        let response = app
+            .clone()
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(
+            response.status(),
+            StatusCode::OK,
+            "GET {} must succeed",
+            uri
+        );
+    }
+}
+
+/// Disabling read-only mode must be possible while it is enabled.
+#[tokio::test]
+async fn test_read_only_mode_can_be_disabled() {
+    let state = test_app_state(ProviderRegistry::new());
+    state.read_only.enable("audit", "test");
+    let read_only = state.read_only.clone();
+    let app = create_router(state);
+
+    let response = app
+        .oneshot(read_only_request(r#"{"enabled":false}"#))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(!read_only.is_enabled());
+}
+
+/// Enabling read-only mode without a reason must be rejected with 400.
+#[tokio::test]
+async fn test_read_only_mode_requires_reason() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(read_only_request(r#"{"enabled":true}"#))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+/// GET /api/events and /api/sessions must reject cursors they did not issue
+/// with 400.
+#[tokio::test]
+async fn test_list_endpoints_reject_invalid_cursor() {
+    for uri in [
+        "/api/events?cursor=not-a-cursor",
+        "/api/sessions?cursor=not-a-cursor",
+    ] {
+        let app = create_router(test_app_state(ProviderRegistry::new()));
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST, "{}", uri);
+    }
+}
+
+/// GET /api/events must reject listings without `since` when
+/// `event_queries.require_since` is set.
+#[tokio::test]
+async fn test_list_events_requires_time_range_when_configured() {
+    let mut state = test_app_state(ProviderRegistry::new());
+    state.config.event_queries.require_since = true;
+
+    for (uri, expected) in [
+        ("/api/events", StatusCode::BAD_REQUEST),
+        ("/api/events?since=2026-01-01T00:00:00Z", StatusCode::OK),
+    ] {
+        let response = create_router(state.clone())
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), expected, "{}", uri);
+    }
+}
+
+// ============================================================================
+// Transform kill switch tests
+// ============================================================================
+
+/// Transform engine that skips every event.
+struct SkipEngine;
+
+impl queue_keeper_core::wasm_transform::TransformEngine for SkipEngine {
+    fn run(&self, _input: &[u8]) -> Result<Vec<u8>, TransformError> {
+        Ok(br#"{"action":"skip"}"#.to_vec())
+    }
+}
+
+/// PUT /admin/transforms/{bot} switches a loaded transform off and reports
+/// it in GET /admin/transforms; unknown bots return 404.
+#[tokio::test]
+async fn test_transform_kill_switch() {
+    let mut transforms = TransformRegistry::new();
+    transforms.register(
+        &BotName::new("custom-bot").unwrap(),
+        queue_keeper_core::WasmTransformConfig::new("custom.wasm"),
+        Box::new(SkipEngine),
+    );
+    let state = test_app_state(ProviderRegistry::new()).with_transforms(Arc::new(transforms));
+    let app = create_router(state);
+
+    let switch = |bot: &str| {
+        Request::builder()
+            .method("PUT")
+            .uri(format!("/admin/transforms/{}", bot))
+            .header("content-type", "application/json")
+            .body(Body::from(r#"{"enabled":false}"#))
+            .unwrap()
+    };
+
+    let response = app.clone().oneshot(switch("custom-bot")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = app.clone().oneshot(switch("other-bot")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/admin/transforms")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json[0]["bot_name"], "custom-bot");
+    assert_eq!(json[0]["enabled"], false);
+}
+
+// ============================================================================
+// Configuration endpoint tests
+// ============================================================================
+
+/// GET /admin/config redacts secrets, and names the source of every value
+/// with include_defaults=true.
+#[tokio::test]
+async fn test_admin_config_redacts_secrets_and_reports_sources() {
+    let mut state = test_app_state(ProviderRegistry::new());
+    state.config.network.proxy = Some(ProxyConfig {
+        url: "http://proxy:3128".to_string(),
+        username: None,
+        password: Some("proxy-password".to_string()),
+        no_proxy: vec![],
+    });
+    let report = StartupReport::new(
+        &state.config,
+        vec![
+            startup_report::ConfigSource::defaults(),
+            startup_report::ConfigSource::file("config/service", true)
+                .with_keys(vec!["network.proxy".to_string()]),
+        ],
+        vec![],
+        RuntimeEnvironment::default(),
+    );
+    let app = create_router(state.with_startup_report(Arc::new(report)));
+    let get = |uri: &str| {
+        let app = app.clone();
+        let request = Request::builder().uri(uri).body(Body::empty()).unwrap();
+        async move {
+            let response = app.oneshot(request).await.unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            serde_json::from_slice::<serde_json::Value>(&body).unwrap()
+        }
+    };
+
+    let json = get("/admin/config").await;
+    assert_eq!(json["network"]["proxy"]["password"], "***");
+    assert!(!json.to_string().contains("proxy-password"));
+    assert!(json.get("value_sources").is_none());
+
+    let json = get("/admin/config?include_defaults=true").await;
+    let sources = &json["value_sources"];
+    assert_eq!(sources["network.proxy.password"]["kind"], "file");
+    assert_eq!(sources["network.proxy.password"]["name"], "config/service");
+    assert_eq!(sources["server.port"]["kind"], "defaults");
+}
+
+// ============================================================================
+// Bot configuration reload tests
+// ============================================================================
+
+/// Build a `PUT /admin/bots/config` request subscribing the named bots to
+/// `issues.opened`.
+fn bot_config_request(bot_names: &[&str]) -> Request<Body> {
+    use queue_keeper_core::bot_config::{
+        BotConfigurationSettings, BotSpecificConfig, BotSubscription, EventTypePattern,
+    };
+
+    let bot_config = BotConfiguration {
+        bots: bot_names
+            .iter()
+            .map(|name| BotSubscription {
+                name: BotName::new(*name).unwrap(),
+                queue: queue_keeper_core::QueueName::new(format!("queue-keeper-{}", name)).unwrap(),
+                events: vec![EventTypePattern::Exact("issues.opened".to_string())],
+                ordered: false,
+                repository_filter: None,
+                config: BotSpecificConfig::new(),
+                encryption: None,
+                transform: None,
+                projection: None,
+                http: None,
+                sender_filter: None,
+            })
+            .collect(),
+        settings: BotConfigurationSettings::default(),
+    };
+    Request::builder()
+        .method("PUT")
+        .uri("/admin/bots/config")
+        .header("content-type", "application/json")
+        .body(Body::from(serde_json::to_string(&bot_config).unwrap()))
+        .unwrap()
+}
+
+/// A valid bot configuration replaces the current one and bumps the version
+/// reported by `/admin/config`; an invalid one is rejected and leaves it.
+#[tokio::test]
+async fn test_bot_config_replaced_at_runtime() {
+    let state = test_app_state(ProviderRegistry::new());
+    let bot_config = state.bot_config.clone();
+    let app = create_router(state);
+
+    let response = app
+        .clone()
+        .oneshot(bot_config_request(&["new-bot"]))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["version"], 2);
+    assert_eq!(json["bots"], 1);
+    assert_eq!(bot_config.current().bots[0].name.as_str(), "new-bot");
+
+    let response = app
+        .clone()
+        .oneshot(bot_config_request(&["dup-bot", "dup-bot"]))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(bot_config.version(), 2);
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/admin/config")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["bot_config_version"], 2);
+    assert!(json["server"].is_object());
+}
+
+/// A replacement vetoed by the reload check is rejected with 409.
+#[tokio::test]
+async fn test_bot_config_change_requiring_restart_returns_409() {
+    let state = test_app_state(ProviderRegistry::new());
+    state.bot_config.set_reload_check(|_, _| {
+        Err(queue_keeper_core::BotConfigError::ValidationError {
+            errors: vec!["restart required".to_string()],
+        })
+    });
+    let app = create_router(state);
+
+    let response = app.oneshot(bot_config_request(&["new-bot"])).await.unwrap();
+    assert_eq!(response.status(), StatusCode::CONFLICT);
+}
+
+// ============================================================================
+// Delivery correlation tests
+// ============================================================================
+
+/// GET /api/deliveries/{id} returns the event and queue messages recorded for
+/// a delivery, and 404 for unknown deliveries.
+#[tokio::test]
+async fn test_delivery_correlation_lookup() {
+    let state = test_app_state(ProviderRegistry::new());
+    let event = WrappedEvent::new(
+        "github".to_string(),
+        "push".to_string(),
+        None,
+        None,
+        serde_json::json!({}),
+        None,
+    );
+    state
+        .delivery_config
+        .delivery_index
+        .record_event("delivery-guid-1", &event);
+    let app = create_router(state);
+
+    let response = app
+        .clone()
+        .oneshot(
+            Request::builder()
+                .uri("/api/deliveries/delivery-guid-1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["github_delivery_id"], "delivery-guid-1");
+    assert_eq!(json["events"][0]["event_id"], event.event_id.to_string());
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/deliveries/unknown-guid")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Event lineage tests
+// ============================================================================
+
+/// GET /api/events/{id}/lineage must reject malformed event IDs with 400.
+#[tokio::test]
+async fn test_event_lineage_rejects_invalid_event_id() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri("/api/events/not-a-ulid/lineage")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+/// GET /api/events/{id}/lineage must return 404 for unknown events.
+#[tokio::test]
+async fn test_event_lineage_unknown_event_returns_not_found() {
+    let app = create_router(test_app_state(ProviderRegistry::new()));
+
+    let response = app
+        .oneshot(
+            Request::builder()
+                .uri(format!("/api/events/{}/lineage", EventId::new()))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+}
+
+// ============================================================================
+// Request metrics tests
+// ============================================================================
+
+/// Count of `http_requests_by_route_total` for one label set.
+fn route_request_count(state: &AppState, labels: [&str; 5]) -> u64 {
+    state
+        .metrics
+        .http_requests_by_route_total
+        .with_label_values(&labels)
+        .get()
+}
+
+/// Webhook requests are counted under the route template and the provider
+/// they were addressed to.
+#[tokio::test]
+async fn test_metrics_middleware_records_route_and_tenant() {
+    let mut registry = ProviderRegistry::new();
+    registry.register(
+        ProviderId::new("github").unwrap(),
+        Arc::new(MockWebhookProcessor::new()),
+    );
+    let state = test_app_state(registry);
+    let labels = ["POST", "/webhook/{provider}", "200", "github", "none"];
+    let before = route_request_count(&state, labels);
+
+    let response = create_router(state.clone())
+        .oneshot(ping_request("/webhook/github"))
+        .await
+        .unwrap();
+
+    assert_eq!(response.status(), StatusCode::OK);
+    // Metrics are shared across tests, so other requests may also be counted.
+    assert!(route_request_count(&state, labels) > before);
+}
+
+/// Every request updates the unlabeled HTTP totals, and webhook requests the
+/// webhook counters and histograms.
+#[tokio::test]
+async fn test_metrics_middleware_records_http_and_webhook_totals() {
+    let mut registry = ProviderRegistry::new();
+    registry.register(
+        ProviderId::new("github").unwrap(),
+        Arc::new(MockWebhookProcessor::new()),
+    );
+    let state = test_app_state(registry);
+    let metrics = state.metrics.clone();
+    let http_before = metrics.http_requests_total.get();
+    let http_duration_before = metrics.http_request_duration.get_sample_count();
+    let webhook_before = metrics.webhook_requests_total.get();
+    let webhook_duration_before = metrics.webhook_duration_seconds.get_sample_count();
+    let failures_before = metrics.webhook_validation_failures.get();
+
+    let app = create_router(state);
+    let response = app
+        .clone()
+        .oneshot(ping_request("/webhook/github"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let response = app
+        .oneshot(
+            Request::builder()
+                .method("POST")
+                .uri("/webhook/github")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+    // Metrics are shared across tests, so other requests may also be counted.
+    assert!(metrics.http_requests_total.get() >= http_before + 2);
+    assert!(metrics.http_request_duration.get_sample_count() >= http_duration_before + 2);
+    assert!(metrics.webhook_requests_total.get() >= webhook_before + 2);
+    assert!(metrics.webhook_duration_seconds.get_sample_count() >= webhook_duration_before + 2);
+    assert!(metrics.webhook_validation_failures.get() > failures_before);
+}
+
+/// Unregistered providers and unrouted paths do not become label values.
+#[tokio::test]
+async fn test_metrics_middleware_bounds_label_values() {
+    let state = test_app_state(ProviderRegistry::new());
+    let unknown_provider = ["POST", "/webhook/{provider}", "404", "none", "none"];
+    let unmatched = ["GET", "unmatched", "404", "none", "none"];
+    let before_provider = route_request_count(&state, unknown_provider);
+    let before_unmatched = route_request_count(&state, unmatched);
+
+    let app = create_router(state.clone());
+    app.clone()
+        .oneshot(ping_request("/webhook/made-up-provider"))
+        .await
+        .unwrap();
+    app.oneshot(
+        Request::builder()
+            .uri("/no/such/path/12345")
+            .body(Body::empty())
+            .unwrap(),
+    )
+    .await
+    .unwrap();
+
+    assert!(route_request_count(&state, unknown_provider) > before_provider);
+    assert!(route_request_count(&state, unmatched) > before_unmatched);
+}
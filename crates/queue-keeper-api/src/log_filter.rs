@@ -0,0 +1,140 @@
+//! Runtime control of the log filter.
+//!
+//! The service installs its `EnvFilter` behind a
+//! [`tracing_subscriber::reload`] layer and hands the reload handle to the
+//! HTTP layer as a [`LogFilter`]. `PUT /admin/logging/level` replaces the
+//! filter, so the new level applies to the running process without a
+//! restart; `GET /admin/logging/level` reports the filter in effect.
+//!
+//! Changes are not persisted: after a restart the filter comes from
+//! `RUST_LOG` or the built-in defaults again.
+
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::{filter::LevelFilter, reload, EnvFilter};
+
+/// Levels accepted by [`LogFilter::set_level`].
+pub const VALID_LOG_LEVELS: [&str; 5] = ["trace", "debug", "info", "warn", "error"];
+
+/// Targets whose level [`LogFilter::set_level`] sets.
+const SERVICE_TARGETS: [&str; 4] = [
+    "queue_keeper_service",
+    "queue_keeper_api",
+    "queue_keeper_core",
+    "tower_http",
+];
+
+/// Filter directives logging the service crates at `level`.
+///
+/// Other crates are left out, so raising the level does not also turn on
+/// the debug output of the HTTP and SDK dependencies.
+pub fn level_directives(level: &str) -> String {
+    SERVICE_TARGETS
+        .iter()
+        .map(|target| format!("{}={}", target, level))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Access to the filter in effect.
+trait FilterSlot: Send + Sync {
+    fn with_current(&self, f: &mut dyn FnMut(&EnvFilter)) -> Result<(), String>;
+    fn replace(&self, filter: EnvFilter) -> Result<(), String>;
+}
+
+impl<S: 'static> FilterSlot for reload::Handle<EnvFilter, S> {
+    fn with_current(&self, f: &mut dyn FnMut(&EnvFilter)) -> Result<(), String> {
+        reload::Handle::with_current(self, |filter| f(filter)).map_err(|e| e.to_string())
+    }
+
+    fn replace(&self, filter: EnvFilter) -> Result<(), String> {
+        self.reload(filter).map_err(|e| e.to_string())
+    }
+}
+
+/// A filter that is not installed in any subscriber.
+struct Detached(Mutex<EnvFilter>);
+
+impl FilterSlot for Detached {
+    fn with_current(&self, f: &mut dyn FnMut(&EnvFilter)) -> Result<(), String> {
+        f(&self.0.lock().expect("log filter lock poisoned"));
+        Ok(())
+    }
+
+    fn replace(&self, filter: EnvFilter) -> Result<(), String> {
+        *self.0.lock().expect("log filter lock poisoned") = filter;
+        Ok(())
+    }
+}
+
+/// The log filter of the service, changed by `PUT /admin/logging/level`.
+///
+/// Clones share the filter.
+#[derive(Clone)]
+pub struct LogFilter {
+    slot: Arc<dyn FilterSlot>,
+}
+
+impl std::fmt::Debug for LogFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogFilter")
+            .field("filter", &self.filter())
+            .finish()
+    }
+}
+
+impl LogFilter {
+    /// Control the filter installed in the subscriber behind `handle`.
+    pub fn new<S: 'static>(handle: reload::Handle<EnvFilter, S>) -> Self {
+        Self {
+            slot: Arc::new(handle),
+        }
+    }
+
+    /// A filter that is only reported, not applied to any subscriber; for
+    /// hosts that install their own logging.
+    pub fn detached(directives: &str) -> Self {
+        Self {
+            slot: Arc::new(Detached(Mutex::new(EnvFilter::new(directives)))),
+        }
+    }
+
+    /// The directives of the filter in effect, e.g.
+    /// `queue_keeper_api=info,tower_http=debug`.
+    pub fn filter(&self) -> String {
+        let mut directives = String::new();
+        let _ = self
+            .slot
+            .with_current(&mut |filter| directives = filter.to_string());
+        directives
+    }
+
+    /// The most verbose level any directive enables, e.g. `debug`, or `off`.
+    pub fn level(&self) -> String {
+        let mut level = LevelFilter::OFF;
+        let _ = self.slot.with_current(&mut |filter| {
+            level = filter.max_level_hint().unwrap_or(LevelFilter::TRACE)
+        });
+        level.to_string().to_lowercase()
+    }
+
+    /// Log the service crates at `level` from now on.
+    ///
+    /// # Errors
+    /// Returns an error when `level` is not one of [`VALID_LOG_LEVELS`] or
+    /// the subscriber holding the filter is gone.
+    pub fn set_level(&self, level: &str) -> Result<(), String> {
+        let level = level.to_lowercase();
+        if !VALID_LOG_LEVELS.contains(&level.as_str()) {
+            return Err(format!(
+                "Invalid log level '{}'. Valid values: {}",
+                level,
+                VALID_LOG_LEVELS.join(", ")
+            ));
+        }
+        self.slot.replace(EnvFilter::new(level_directives(&level)))
+    }
+}
+
+#[cfg(test)]
+#[path = "log_filter_tests.rs"]
+mod tests;
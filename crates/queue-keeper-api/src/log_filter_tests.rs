@@ -0,0 +1,45 @@
+//! Tests for runtime log filter control.
+
+use super::*;
+use tracing::Level;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// A new level applies to the subscriber immediately.
+#[test]
+fn test_set_level_reloads_subscriber_filter() {
+    let (layer, handle) = reload::Layer::new(EnvFilter::new(level_directives("info")));
+    let subscriber = tracing_subscriber::registry().with(layer);
+    let log_filter = LogFilter::new(handle);
+
+    tracing::subscriber::with_default(subscriber, || {
+        assert!(!tracing::enabled!(target: "queue_keeper_api", Level::DEBUG));
+
+        log_filter.set_level("debug").unwrap();
+        assert!(tracing::enabled!(target: "queue_keeper_api", Level::DEBUG));
+        assert!(!tracing::enabled!(target: "hyper", Level::DEBUG));
+        assert_eq!(log_filter.level(), "debug");
+        assert!(log_filter.filter().contains("queue_keeper_core=debug"));
+    });
+}
+
+/// Unknown levels leave the filter unchanged.
+#[test]
+fn test_set_level_rejects_unknown_level() {
+    let log_filter = LogFilter::detached(&level_directives("warn"));
+    assert!(log_filter.set_level("verbose").is_err());
+    assert_eq!(log_filter.level(), "warn");
+
+    log_filter.set_level("ERROR").unwrap();
+    assert_eq!(log_filter.level(), "error");
+}
+
+/// Levels are reported from directives set outside the service, e.g. in
+/// `RUST_LOG`.
+#[test]
+fn test_level_of_custom_directives() {
+    let log_filter = LogFilter::detached("warn,queue_keeper_api=trace");
+    assert_eq!(log_filter.level(), "trace");
+    let filter = log_filter.filter();
+    assert!(filter.contains("queue_keeper_api=trace"), "{}", filter);
+    assert!(filter.contains("warn"), "{}", filter);
+}
@@ -1,7 +1,46 @@
 //! Metrics collection and observability types for the API service.
 
-use prometheus::{Gauge, Histogram, IntCounter, IntCounterVec, IntGauge, IntGaugeVec};
-use std::sync::Arc;
+use prometheus::{
+    Gauge, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+};
+use queue_keeper_core::payload_retention::RetentionSweepReport;
+use queue_keeper_core::queue_integration::DeliveryResult;
+use queue_keeper_core::webhook::{is_known_event_type, WrappedEvent};
+use queue_keeper_core::{PipelineStage, StageTimings, Timestamp};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Labels of one HTTP request for the per-route metrics.
+#[derive(Debug, Clone, Copy)]
+pub struct HttpRouteLabels<'a> {
+    /// HTTP method, e.g. `POST`.
+    pub method: &'a str,
+
+    /// Matched route template, e.g. `/webhook/{provider}`.
+    pub route: &'a str,
+
+    /// Response status code, e.g. `200`.
+    pub status: &'a str,
+
+    /// Provider the request was addressed to, or `"none"`.
+    pub tenant: &'a str,
+
+    /// Configured bot the request was addressed to, or `"none"`.
+    pub bot: &'a str,
+}
+
+/// `event_type` label of events whose type is not in the known-types
+/// registry, to keep label cardinality bounded.
+pub const OTHER_EVENT_TYPE_LABEL: &str = "other";
+
+/// `event_type` label value of `event_type`.
+fn event_type_label(event_type: &str) -> &str {
+    if is_known_event_type(event_type) {
+        event_type
+    } else {
+        OTHER_EVENT_TYPE_LABEL
+    }
+}
 
 /// Service metrics for observability
 #[derive(Debug)]
@@ -11,43 +50,99 @@ pub struct ServiceMetrics {
     pub http_request_duration: Histogram,
     pub http_request_size: Histogram,
     pub http_response_size: Histogram,
+    pub http_requests_by_route_total: IntCounterVec,
+    pub http_request_duration_by_route_seconds: HistogramVec,
 
     // Webhook processing metrics
     pub webhook_requests_total: IntCounter,
     pub webhook_duration_seconds: Histogram,
     pub webhook_validation_failures: IntCounter,
     pub webhook_queue_routing_duration: Histogram,
+    pub webhook_stage_duration_seconds: HistogramVec,
+    pub webhook_repository_throttled_total: IntCounter,
+    pub webhook_throttled_repositories: IntGauge,
+    pub webhook_duplicate_deliveries_total: IntCounter,
+
+    /// Stage breakdown of the most recent webhook request, for `/debug/vars`.
+    pub last_webhook_stage_breakdown: Mutex<Option<String>>,
 
     // Queue management metrics
     pub queue_depth_messages: IntGaugeVec,
     pub queue_processing_rate: Gauge,
     pub dead_letter_queue_depth: IntGauge,
     pub session_ordering_violations: IntCounter,
+    pub queue_batch_size_messages: Histogram,
+    pub queue_batch_fallbacks_total: IntCounter,
+
+    // Sharded delivery metrics
+    pub delivery_shard_pending: IntGaugeVec,
+    pub delivery_shard_load_imbalance: Gauge,
+    pub delivery_shards_active: IntGauge,
+
+    // Freshness metrics
+    pub bot_config_age_seconds: IntGauge,
+    pub secret_cache_age_seconds: IntGaugeVec,
+
+    // Background job supervision
+    pub background_job_restarts_total: IntCounterVec,
+
+    // Webhook gateway forwarding
+    pub gateway_forwards_total: IntCounterVec,
+
+    // Age-based escalation of held and failing deliveries
+    pub delivery_escalations_total: IntCounterVec,
+    pub delivery_escalated_backlogs: IntGaugeVec,
+    pub escalation_diverted_events_total: IntCounterVec,
+
+    // GitHub event types missing from the known-types registry
+    pub unknown_event_types_total: IntCounterVec,
 
     // Bot-specific metrics
     pub events_processed_per_bot: IntCounterVec,
+    pub queue_deliveries_total: IntCounterVec,
+    pub queue_delivery_latency_seconds: HistogramVec,
+    pub queue_depth_sample_failures_total: IntCounterVec,
     pub queue_send_errors_total: IntCounter,
     pub active_sessions: IntGauge,
 
+    // Sessions whose oldest in-flight delivery is older than stuck_after
+    pub sessions_stuck: IntGauge,
+    pub session_in_flight_deliveries: IntGauge,
+
     // Replay and administrative operations
     pub replay_operations_total: IntCounter,
     pub replay_events_processed: IntCounter,
     pub replay_failures_total: IntCounter,
 
+    // Soft deletion and purge of stored events
+    pub events_deleted_total: IntCounter,
+    pub events_purged_total: IntCounter,
+
+    // Durable outbox of accepted events
+    pub outbox_pending_entries: IntGauge,
+    pub outbox_redeliveries_total: IntCounter,
+    pub outbox_write_failures_total: IntCounter,
+
+    // Payload retention
+    pub payload_retention_payloads_total: IntCounterVec,
+    pub payload_retention_reclaimed_bytes_total: IntCounter,
+
     // Error and security metrics
     pub error_rate_by_category: IntCounterVec,
     pub circuit_breaker_state: IntGaugeVec,
     pub retry_attempts_total: IntCounterVec,
+    pub retry_budget_exhausted_total: IntCounter,
     pub blob_storage_failures: IntCounter,
     pub signature_validation_failures: IntCounter,
     pub authentication_failures_total: IntCounter,
+    pub rate_limited_requests_total: IntCounterVec,
 }
 
 impl ServiceMetrics {
     pub fn new() -> Result<Arc<Self>, prometheus::Error> {
         use prometheus::{
-            register_gauge, register_histogram, register_int_counter, register_int_counter_vec,
-            register_int_gauge, register_int_gauge_vec,
+            register_gauge, register_histogram, register_histogram_vec, register_int_counter,
+            register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
         };
 
         Ok(Arc::new(Self {
@@ -70,6 +165,17 @@ impl ServiceMetrics {
                 "HTTP response size in bytes",
                 vec![100.0, 1000.0, 10000.0, 100000.0, 1000000.0]
             )?,
+            http_requests_by_route_total: register_int_counter_vec!(
+                "http_requests_by_route_total",
+                "HTTP requests by method, matched route, status, tenant and bot",
+                &["method", "route", "status", "tenant", "bot"]
+            )?,
+            http_request_duration_by_route_seconds: register_histogram_vec!(
+                "http_request_duration_by_route_seconds",
+                "HTTP request processing time by method and matched route",
+                &["method", "route"],
+                vec![0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0, 10.0]
+            )?,
 
             webhook_requests_total: register_int_counter!(
                 "webhook_requests_total",
@@ -89,11 +195,30 @@ impl ServiceMetrics {
                 "Time to route to all bot queues",
                 vec![0.001, 0.01, 0.1, 0.2, 0.5, 1.0]
             )?,
+            webhook_stage_duration_seconds: register_histogram_vec!(
+                "webhook_stage_duration_seconds",
+                "Webhook processing time per pipeline stage",
+                &["stage"],
+                vec![0.0001, 0.0005, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0]
+            )?,
+            webhook_repository_throttled_total: register_int_counter!(
+                "webhook_repository_throttled_total",
+                "Webhooks rejected by the per-repository rate limit"
+            )?,
+            webhook_throttled_repositories: register_int_gauge!(
+                "webhook_throttled_repositories",
+                "Repositories currently over the per-repository rate limit"
+            )?,
+            webhook_duplicate_deliveries_total: register_int_counter!(
+                "webhook_duplicate_deliveries_total",
+                "Webhook deliveries skipped because their delivery ID was already processed"
+            )?,
+            last_webhook_stage_breakdown: Mutex::new(None),
 
             queue_depth_messages: register_int_gauge_vec!(
                 "queue_depth_messages",
                 "Messages waiting in each bot queue",
-                &["queue_name"]
+                &["queue_name", "bot_name"]
             )?,
             queue_processing_rate: register_gauge!(
                 "queue_processing_rate",
@@ -107,11 +232,94 @@ impl ServiceMetrics {
                 "session_ordering_violations",
                 "Events processed out of order"
             )?,
+            queue_batch_size_messages: register_histogram!(
+                "queue_batch_size_messages",
+                "Messages submitted per batched queue send",
+                vec![1.0, 2.0, 5.0, 10.0, 25.0, 50.0, 100.0]
+            )?,
+            queue_batch_fallbacks_total: register_int_counter!(
+                "queue_batch_fallbacks_total",
+                "Failed batch sends whose messages were sent one by one"
+            )?,
+
+            delivery_shard_pending: register_int_gauge_vec!(
+                "delivery_shard_pending",
+                "Deliveries queued or in flight on each delivery shard",
+                &["shard"]
+            )?,
+            delivery_shard_load_imbalance: register_gauge!(
+                "delivery_shard_load_imbalance",
+                "Busiest delivery shard's pending count divided by the mean (1.0 = balanced)"
+            )?,
+            delivery_shards_active: register_int_gauge!(
+                "delivery_shards_active",
+                "Delivery shards currently receiving sessions"
+            )?,
+
+            bot_config_age_seconds: register_int_gauge!(
+                "bot_config_age_seconds",
+                "Seconds since the bot configuration was loaded or reloaded"
+            )?,
+            secret_cache_age_seconds: register_int_gauge_vec!(
+                "secret_cache_age_seconds",
+                "Seconds since each cached secret was fetched from the Key Vault",
+                &["secret"]
+            )?,
+
+            background_job_restarts_total: register_int_counter_vec!(
+                "background_job_restarts_total",
+                "Restarts of supervised background jobs after a panic or error",
+                &["job"]
+            )?,
+
+            gateway_forwards_total: register_int_counter_vec!(
+                "gateway_forwards_total",
+                "Raw webhooks forwarded to gateway destinations by outcome",
+                &["gateway", "outcome"]
+            )?,
+
+            delivery_escalations_total: register_int_counter_vec!(
+                "delivery_escalations_total",
+                "Bot backlogs that were held or failing for longer than the escalation threshold",
+                &["bot_name", "kind"]
+            )?,
+            delivery_escalated_backlogs: register_int_gauge_vec!(
+                "delivery_escalated_backlogs",
+                "Whether each bot backlog is currently escalated (1) or not (0)",
+                &["bot_name", "kind"]
+            )?,
+            escalation_diverted_events_total: register_int_counter_vec!(
+                "escalation_diverted_events_total",
+                "Escalated events diverted to the fallback destination",
+                &["bot_name"]
+            )?,
+
+            unknown_event_types_total: register_int_counter_vec!(
+                "unknown_event_types_total",
+                "Deliveries with an event type missing from the known-types registry",
+                &["provider", "event_type", "action"]
+            )?,
 
             events_processed_per_bot: register_int_counter_vec!(
                 "events_processed_per_bot",
-                "Events routed to each bot queue",
-                &["bot_name"]
+                "Events delivered to each bot queue",
+                &["bot_name", "event_type"]
+            )?,
+            queue_deliveries_total: register_int_counter_vec!(
+                "queue_deliveries_total",
+                "Delivery attempts to bot queues, by outcome",
+                &["queue_name", "bot_name", "event_type", "outcome"]
+            )?,
+            queue_delivery_latency_seconds: register_histogram_vec!(
+                "queue_delivery_latency_seconds",
+                "Time from webhook receipt until a bot queue accepted the event",
+                &["queue_name", "bot_name"],
+                vec![0.01, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0, 60.0, 300.0]
+            )?,
+            queue_depth_sample_failures_total: register_int_counter_vec!(
+                "queue_depth_sample_failures_total",
+                "Queue depth samples the queue provider failed to return",
+                &["queue_name"]
             )?,
             queue_send_errors_total: register_int_counter!(
                 "queue_send_errors_total",
@@ -121,6 +329,14 @@ impl ServiceMetrics {
                 "active_sessions",
                 "Number of active message sessions"
             )?,
+            sessions_stuck: register_int_gauge!(
+                "sessions_stuck",
+                "Sessions whose oldest in-flight event is older than sessions.stuck_after"
+            )?,
+            session_in_flight_deliveries: register_int_gauge!(
+                "session_in_flight_deliveries",
+                "Events of tracked sessions accepted but not yet delivered"
+            )?,
 
             replay_operations_total: register_int_counter!(
                 "replay_operations_total",
@@ -135,6 +351,38 @@ impl ServiceMetrics {
                 "Failed replay operations"
             )?,
 
+            events_deleted_total: register_int_counter!(
+                "events_deleted_total",
+                "Stored events soft-deleted through the admin API"
+            )?,
+            events_purged_total: register_int_counter!(
+                "events_purged_total",
+                "Soft-deleted events permanently removed from storage"
+            )?,
+
+            outbox_pending_entries: register_int_gauge!(
+                "outbox_pending_entries",
+                "Accepted events in the outbox whose delivery is not complete"
+            )?,
+            outbox_redeliveries_total: register_int_counter!(
+                "outbox_redeliveries_total",
+                "Events redelivered from the outbox"
+            )?,
+            outbox_write_failures_total: register_int_counter!(
+                "outbox_write_failures_total",
+                "Accepted events delivered without an outbox entry because it could not be written"
+            )?,
+
+            payload_retention_payloads_total: register_int_counter_vec!(
+                "payload_retention_payloads_total",
+                "Expired payloads handled by retention sweeps, by outcome",
+                &["outcome"]
+            )?,
+            payload_retention_reclaimed_bytes_total: register_int_counter!(
+                "payload_retention_reclaimed_bytes_total",
+                "Stored bytes freed by retention sweeps"
+            )?,
+
             error_rate_by_category: register_int_counter_vec!(
                 "error_rate_by_category",
                 "Errors grouped by category and transience",
@@ -150,6 +398,10 @@ impl ServiceMetrics {
                 "Retry operations by service",
                 &["service"]
             )?,
+            retry_budget_exhausted_total: register_int_counter!(
+                "retry_budget_exhausted_total",
+                "Delivery retries deferred because the global retry budget was exhausted"
+            )?,
             blob_storage_failures: register_int_counter!(
                 "blob_storage_failures",
                 "Audit trail storage failures"
@@ -162,6 +414,11 @@ impl ServiceMetrics {
                 "authentication_failures_total",
                 "Failed authentication attempts"
             )?,
+            rate_limited_requests_total: register_int_counter_vec!(
+                "rate_limited_requests_total",
+                "Requests rejected by the request rate limiter, by exhausted limit",
+                &["scope"]
+            )?,
         }))
     }
 
@@ -177,6 +434,34 @@ impl ServiceMetrics {
         self.http_response_size.observe(response_size as f64);
     }
 
+    /// Record one HTTP request with its route labels.
+    ///
+    /// Also updates the unlabeled totals recorded by
+    /// [`record_http_request`](Self::record_http_request). `route` must be a
+    /// route template or normalized path, and `tenant` and `bot` must come
+    /// from configuration (or be `"none"`), to keep label cardinality bounded.
+    pub fn record_http_route_request(
+        &self,
+        labels: &HttpRouteLabels<'_>,
+        duration: std::time::Duration,
+        request_size: u64,
+        response_size: u64,
+    ) {
+        self.record_http_request(duration, request_size, response_size);
+        self.http_requests_by_route_total
+            .with_label_values(&[
+                labels.method,
+                labels.route,
+                labels.status,
+                labels.tenant,
+                labels.bot,
+            ])
+            .inc();
+        self.http_request_duration_by_route_seconds
+            .with_label_values(&[labels.method, labels.route])
+            .observe(duration.as_secs_f64());
+    }
+
     pub fn record_webhook_request(&self, duration: std::time::Duration, success: bool) {
         self.webhook_requests_total.inc();
         self.webhook_duration_seconds
@@ -185,6 +470,121 @@ impl ServiceMetrics {
             self.webhook_validation_failures.inc();
         }
     }
+
+    /// Record every stage of one webhook request and keep its breakdown for
+    /// `/debug/vars`.
+    pub fn record_webhook_stages(&self, timings: &StageTimings) {
+        for (stage, duration) in timings.breakdown() {
+            queue_keeper_core::monitoring::MetricsCollector::record_webhook_stage(
+                self, stage, duration,
+            );
+        }
+        *self
+            .last_webhook_stage_breakdown
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()) = Some(timings.to_string());
+    }
+
+    /// Count one request rejected because the `scope` limit was exhausted.
+    pub fn record_rate_limited_request(&self, scope: &str) {
+        self.rate_limited_requests_total
+            .with_label_values(&[scope])
+            .inc();
+    }
+
+    /// Count one forwarded webhook for a gateway destination.
+    pub fn record_gateway_forward(&self, gateway: &str, delivered: bool) {
+        let outcome = if delivered { "delivered" } else { "failed" };
+        self.gateway_forwards_total
+            .with_label_values(&[gateway, outcome])
+            .inc();
+    }
+
+    /// Record the per-queue outcome of one delivery attempt of `event`.
+    ///
+    /// Accepted deliveries also observe the time since the webhook was
+    /// received, so retried deliveries show up in the latency tail.
+    pub fn record_delivery_result(&self, event: &WrappedEvent, result: &DeliveryResult) {
+        let event_type = event_type_label(&event.event_type);
+        let latency = Timestamp::now()
+            .duration_since(event.received_at)
+            .as_secs_f64();
+        for delivery in &result.successful {
+            let (queue, bot) = (delivery.queue_name.as_str(), delivery.bot_name.as_str());
+            self.queue_deliveries_total
+                .with_label_values(&[queue, bot, event_type, "delivered"])
+                .inc();
+            self.events_processed_per_bot
+                .with_label_values(&[bot, event_type])
+                .inc();
+            self.queue_delivery_latency_seconds
+                .with_label_values(&[queue, bot])
+                .observe(latency);
+        }
+        for failure in &result.failed {
+            let outcome = if failure.is_transient {
+                "transient_failure"
+            } else {
+                "permanent_failure"
+            };
+            self.queue_deliveries_total
+                .with_label_values(&[
+                    failure.queue_name.as_str(),
+                    failure.bot_name.as_str(),
+                    event_type,
+                    outcome,
+                ])
+                .inc();
+        }
+    }
+
+    /// Set the sampled depth of the queue of `bot_name`.
+    pub fn record_bot_queue_depth(&self, queue_name: &str, bot_name: &str, depth: u64) {
+        self.queue_depth_messages
+            .with_label_values(&[queue_name, bot_name])
+            .set(i64::try_from(depth).unwrap_or(i64::MAX));
+    }
+
+    /// Count one queue whose depth could not be sampled.
+    pub fn record_queue_depth_sample_failure(&self, queue_name: &str) {
+        self.queue_depth_sample_failures_total
+            .with_label_values(&[queue_name])
+            .inc();
+    }
+
+    /// Count the payloads and bytes reclaimed by one retention sweep.
+    pub fn record_payload_retention_sweep(&self, report: &RetentionSweepReport) {
+        for (outcome, count) in [
+            ("deleted", report.deleted_payloads),
+            ("archived", report.archived_payloads),
+            ("failed", report.failed_payloads),
+        ] {
+            self.payload_retention_payloads_total
+                .with_label_values(&[outcome])
+                .inc_by(count);
+        }
+        self.payload_retention_reclaimed_bytes_total
+            .inc_by(report.reclaimed_bytes);
+    }
+
+    /// Sample count and mean duration of each stage since startup.
+    pub fn webhook_stage_summary(&self) -> Vec<(PipelineStage, u64, Duration)> {
+        PipelineStage::ALL
+            .iter()
+            .map(|stage| {
+                let histogram = self
+                    .webhook_stage_duration_seconds
+                    .with_label_values(&[stage.as_str()]);
+                let count = histogram.get_sample_count();
+                let mean = if count == 0 {
+                    Duration::ZERO
+                } else {
+                    Duration::from_secs_f64(histogram.get_sample_sum() / count as f64)
+                };
+                (*stage, count, mean)
+            })
+            .collect()
+    }
 }
 
 // Implement MetricsCollector trait from queue-keeper-core
@@ -203,19 +603,16 @@ impl queue_keeper_core::monitoring::MetricsCollector for ServiceMetrics {
         self.signature_validation_failures.inc();
     }
 
-    fn record_queue_routing(&self, duration: std::time::Duration, queue_count: usize) {
+    fn record_webhook_stage(&self, stage: PipelineStage, duration: Duration) {
+        self.webhook_stage_duration_seconds
+            .with_label_values(&[stage.as_str()])
+            .observe(duration.as_secs_f64());
+    }
+
+    fn record_queue_routing(&self, duration: std::time::Duration, _queue_count: usize) {
+        // Per-bot counts are recorded by record_delivery_result
         self.webhook_queue_routing_duration
             .observe(duration.as_secs_f64());
-        // Increment counter for each bot queue routed to
-        // Use "multiple" label when routing to multiple queues
-        let bot_label = if queue_count <= 1 {
-            "single"
-        } else {
-            "multiple"
-        };
-        self.events_processed_per_bot
-            .with_label_values(&[bot_label])
-            .inc_by(queue_count as u64);
     }
 
     fn record_queue_delivery_attempt(&self, success: bool) {
@@ -252,8 +649,9 @@ impl queue_keeper_core::monitoring::MetricsCollector for ServiceMetrics {
     }
 
     fn record_queue_depth(&self, queue_name: &str, depth: i64) {
+        // Not attributed to a bot; the sampler records per bot
         self.queue_depth_messages
-            .with_label_values(&[queue_name])
+            .with_label_values(&[queue_name, "none"])
             .set(depth);
     }
 
@@ -285,8 +683,8 @@ impl Default for ServiceMetrics {
         // This is a stub implementation for testing only.
         // In production, use ServiceMetrics::new() instead.
         use prometheus::{
-            register_gauge, register_histogram, register_int_counter, register_int_counter_vec,
-            register_int_gauge, register_int_gauge_vec,
+            register_gauge, register_histogram, register_histogram_vec, register_int_counter,
+            register_int_counter_vec, register_int_gauge, register_int_gauge_vec,
         };
         use std::sync::atomic::{AtomicU64, Ordering};
 
@@ -320,6 +718,19 @@ impl Default for ServiceMetrics {
                 vec![]
             )
             .unwrap(),
+            http_requests_by_route_total: register_int_counter_vec!(
+                format!("http_requests_by_route_total_test_{}", suffix),
+                "Test HTTP requests by route",
+                &["method", "route", "status", "tenant", "bot"]
+            )
+            .unwrap(),
+            http_request_duration_by_route_seconds: register_histogram_vec!(
+                format!("http_request_duration_by_route_seconds_test_{}", suffix),
+                "Test HTTP duration by route",
+                &["method", "route"],
+                vec![]
+            )
+            .unwrap(),
             webhook_requests_total: register_int_counter!(
                 format!("webhook_requests_total_test_{}", suffix),
                 "Test webhook requests"
@@ -342,10 +753,33 @@ impl Default for ServiceMetrics {
                 vec![]
             )
             .unwrap(),
+            webhook_stage_duration_seconds: register_histogram_vec!(
+                format!("webhook_stage_duration_seconds_test_{}", suffix),
+                "Test webhook stage duration",
+                &["stage"],
+                vec![]
+            )
+            .unwrap(),
+            webhook_repository_throttled_total: register_int_counter!(
+                format!("webhook_repository_throttled_total_test_{}", suffix),
+                "Test webhooks rejected by the per-repository rate limit"
+            )
+            .unwrap(),
+            webhook_throttled_repositories: register_int_gauge!(
+                format!("webhook_throttled_repositories_test_{}", suffix),
+                "Test throttled repositories"
+            )
+            .unwrap(),
+            webhook_duplicate_deliveries_total: register_int_counter!(
+                format!("webhook_duplicate_deliveries_total_test_{}", suffix),
+                "Test duplicate webhook deliveries"
+            )
+            .unwrap(),
+            last_webhook_stage_breakdown: Mutex::new(None),
             queue_depth_messages: register_int_gauge_vec!(
                 format!("queue_depth_messages_test_{}", suffix),
                 "Test queue depth",
-                &["queue_name"]
+                &["queue_name", "bot_name"]
             )
             .unwrap(),
             queue_processing_rate: register_gauge!(
@@ -363,6 +797,80 @@ impl Default for ServiceMetrics {
                 "Test session ordering violations"
             )
             .unwrap(),
+            queue_batch_size_messages: register_histogram!(
+                format!("queue_batch_size_messages_test_{}", suffix),
+                "Test queue batch size",
+                vec![]
+            )
+            .unwrap(),
+            queue_batch_fallbacks_total: register_int_counter!(
+                format!("queue_batch_fallbacks_total_test_{}", suffix),
+                "Test queue batch fallbacks"
+            )
+            .unwrap(),
+            delivery_shard_pending: register_int_gauge_vec!(
+                format!("delivery_shard_pending_test_{}", suffix),
+                "Test delivery shard pending",
+                &["shard"]
+            )
+            .unwrap(),
+            delivery_shard_load_imbalance: register_gauge!(
+                format!("delivery_shard_load_imbalance_test_{}", suffix),
+                "Test delivery shard load imbalance"
+            )
+            .unwrap(),
+            delivery_shards_active: register_int_gauge!(
+                format!("delivery_shards_active_test_{}", suffix),
+                "Test active delivery shards"
+            )
+            .unwrap(),
+            bot_config_age_seconds: register_int_gauge!(
+                format!("bot_config_age_seconds_test_{}", suffix),
+                "Test bot config age"
+            )
+            .unwrap(),
+            secret_cache_age_seconds: register_int_gauge_vec!(
+                format!("secret_cache_age_seconds_test_{}", suffix),
+                "Test secret cache age",
+                &["secret"]
+            )
+            .unwrap(),
+            background_job_restarts_total: register_int_counter_vec!(
+                format!("background_job_restarts_total_test_{}", suffix),
+                "Test background job restarts",
+                &["job"]
+            )
+            .unwrap(),
+            gateway_forwards_total: register_int_counter_vec!(
+                format!("gateway_forwards_total_test_{}", suffix),
+                "Test gateway forwards",
+                &["gateway", "outcome"]
+            )
+            .unwrap(),
+            delivery_escalations_total: register_int_counter_vec!(
+                format!("delivery_escalations_total_test_{}", suffix),
+                "Test delivery escalations",
+                &["bot_name", "kind"]
+            )
+            .unwrap(),
+            delivery_escalated_backlogs: register_int_gauge_vec!(
+                format!("delivery_escalated_backlogs_test_{}", suffix),
+                "Test escalated backlogs",
+                &["bot_name", "kind"]
+            )
+            .unwrap(),
+            escalation_diverted_events_total: register_int_counter_vec!(
+                format!("escalation_diverted_events_total_test_{}", suffix),
+                "Test escalation diverted events",
+                &["bot_name"]
+            )
+            .unwrap(),
+            unknown_event_types_total: register_int_counter_vec!(
+                format!("unknown_event_types_total_test_{}", suffix),
+                "Test unknown event types",
+                &["provider", "event_type", "action"]
+            )
+            .unwrap(),
             error_rate_by_category: register_int_counter_vec!(
                 format!("error_rate_by_category_test_{}", suffix),
                 "Test error rate",
@@ -381,6 +889,11 @@ impl Default for ServiceMetrics {
                 &["service"]
             )
             .unwrap(),
+            retry_budget_exhausted_total: register_int_counter!(
+                format!("retry_budget_exhausted_total_test_{}", suffix),
+                "Test retry budget exhaustion"
+            )
+            .unwrap(),
             blob_storage_failures: register_int_counter!(
                 format!("blob_storage_failures_test_{}", suffix),
                 "Test blob storage failures"
@@ -389,7 +902,25 @@ impl Default for ServiceMetrics {
             events_processed_per_bot: register_int_counter_vec!(
                 format!("events_processed_per_bot_test_{}", suffix),
                 "Test events per bot",
-                &["bot_name"]
+                &["bot_name", "event_type"]
+            )
+            .unwrap(),
+            queue_deliveries_total: register_int_counter_vec!(
+                format!("queue_deliveries_total_test_{}", suffix),
+                "Test queue deliveries",
+                &["queue_name", "bot_name", "event_type", "outcome"]
+            )
+            .unwrap(),
+            queue_delivery_latency_seconds: register_histogram_vec!(
+                format!("queue_delivery_latency_seconds_test_{}", suffix),
+                "Test queue delivery latency",
+                &["queue_name", "bot_name"]
+            )
+            .unwrap(),
+            queue_depth_sample_failures_total: register_int_counter_vec!(
+                format!("queue_depth_sample_failures_total_test_{}", suffix),
+                "Test queue depth sample failures",
+                &["queue_name"]
             )
             .unwrap(),
             queue_send_errors_total: register_int_counter!(
@@ -402,6 +933,16 @@ impl Default for ServiceMetrics {
                 "Test active sessions"
             )
             .unwrap(),
+            sessions_stuck: register_int_gauge!(
+                format!("sessions_stuck_test_{}", suffix),
+                "Test stuck sessions"
+            )
+            .unwrap(),
+            session_in_flight_deliveries: register_int_gauge!(
+                format!("session_in_flight_deliveries_test_{}", suffix),
+                "Test session in-flight deliveries"
+            )
+            .unwrap(),
             replay_operations_total: register_int_counter!(
                 format!("replay_operations_total_test_{}", suffix),
                 "Test replay operations"
@@ -417,6 +958,42 @@ impl Default for ServiceMetrics {
                 "Test replay failures"
             )
             .unwrap(),
+            events_deleted_total: register_int_counter!(
+                format!("events_deleted_total_test_{}", suffix),
+                "Test soft-deleted events"
+            )
+            .unwrap(),
+            events_purged_total: register_int_counter!(
+                format!("events_purged_total_test_{}", suffix),
+                "Test purged events"
+            )
+            .unwrap(),
+            outbox_pending_entries: register_int_gauge!(
+                format!("outbox_pending_entries_test_{}", suffix),
+                "Test outbox pending entries"
+            )
+            .unwrap(),
+            outbox_redeliveries_total: register_int_counter!(
+                format!("outbox_redeliveries_total_test_{}", suffix),
+                "Test outbox redeliveries"
+            )
+            .unwrap(),
+            outbox_write_failures_total: register_int_counter!(
+                format!("outbox_write_failures_total_test_{}", suffix),
+                "Test outbox write failures"
+            )
+            .unwrap(),
+            payload_retention_payloads_total: register_int_counter_vec!(
+                format!("payload_retention_payloads_total_test_{}", suffix),
+                "Test payload retention payloads",
+                &["outcome"]
+            )
+            .unwrap(),
+            payload_retention_reclaimed_bytes_total: register_int_counter!(
+                format!("payload_retention_reclaimed_bytes_total_test_{}", suffix),
+                "Test payload retention reclaimed bytes"
+            )
+            .unwrap(),
             signature_validation_failures: register_int_counter!(
                 format!("signature_validation_failures_test_{}", suffix),
                 "Test signature validation failures"
@@ -427,6 +1004,12 @@ impl Default for ServiceMetrics {
                 "Test authentication failures"
             )
             .unwrap(),
+            rate_limited_requests_total: register_int_counter_vec!(
+                format!("rate_limited_requests_total_test_{}", suffix),
+                "Test rate limited requests",
+                &["scope"]
+            )
+            .unwrap(),
         }
     }
 }
@@ -497,3 +1080,7 @@ impl TelemetryConfig {
         }
     }
 }
+
+#[cfg(test)]
+#[path = "metrics_tests.rs"]
+mod tests;
@@ -0,0 +1,101 @@
+//! Tests for the labelled delivery and queue depth metrics.
+
+use super::*;
+use crate::test_fixtures::{delivered, event_of_type, failed};
+use queue_keeper_core::EventId;
+
+/// Every queue of an attempt is counted under its bot, event type and
+/// outcome; only accepted deliveries observe latency.
+#[test]
+fn test_record_delivery_result_labels_each_queue() {
+    let metrics = ServiceMetrics::default();
+    let result = DeliveryResult {
+        event_id: EventId::new(),
+        successful: vec![delivered("labeler")],
+        failed: vec![failed("releaser", true), failed("notifier", false)],
+    };
+
+    metrics.record_delivery_result(&event_of_type("pull_request"), &result);
+
+    for (labels, expected) in [
+        (
+            [
+                "queue-keeper-labeler",
+                "labeler",
+                "pull_request",
+                "delivered",
+            ],
+            1,
+        ),
+        (
+            [
+                "queue-keeper-releaser",
+                "releaser",
+                "pull_request",
+                "transient_failure",
+            ],
+            1,
+        ),
+        (
+            [
+                "queue-keeper-notifier",
+                "notifier",
+                "pull_request",
+                "permanent_failure",
+            ],
+            1,
+        ),
+    ] {
+        assert_eq!(
+            metrics
+                .queue_deliveries_total
+                .with_label_values(&labels)
+                .get(),
+            expected,
+            "{:?}",
+            labels
+        );
+    }
+    assert_eq!(
+        metrics
+            .events_processed_per_bot
+            .with_label_values(&["labeler", "pull_request"])
+            .get(),
+        1
+    );
+    assert_eq!(
+        metrics
+            .queue_delivery_latency_seconds
+            .with_label_values(&["queue-keeper-labeler", "labeler"])
+            .get_sample_count(),
+        1
+    );
+    assert_eq!(
+        metrics
+            .queue_delivery_latency_seconds
+            .with_label_values(&["queue-keeper-releaser", "releaser"])
+            .get_sample_count(),
+        0
+    );
+}
+
+/// Event types outside the known-types registry share one label value.
+#[test]
+fn test_record_delivery_result_folds_unknown_event_types() {
+    let metrics = ServiceMetrics::default();
+    let result = DeliveryResult {
+        event_id: EventId::new(),
+        successful: vec![delivered("labeler")],
+        failed: Vec::new(),
+    };
+
+    metrics.record_delivery_result(&event_of_type("made_up_event"), &result);
+
+    assert_eq!(
+        metrics
+            .events_processed_per_bot
+            .with_label_values(&["labeler", OTHER_EVENT_TYPE_LABEL])
+            .get(),
+        1
+    );
+}
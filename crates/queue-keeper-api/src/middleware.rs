@@ -5,23 +5,38 @@
 //!   ([`IpFailureTracker`], [`IpTier`], [`ip_rate_limit_middleware`]) — spec
 //!   assertion #19 and `specs/security/rate-limiting.md` §"Security Response
 //!   Escalation"
-//! - Admin endpoint authentication ([`admin_auth_middleware`])
+//! - Global and per-IP request rate limiting
+//!   ([`request_rate_limit_middleware`], see [`crate::request_rate_limit`])
+//! - Webhook body size limiting against `server.max_body_size`
+//!   ([`body_limit_middleware`])
+//! - Admin endpoint authentication, role-based authorization and read-only
+//!   mode enforcement ([`admin_auth_middleware`])
 
 use std::{
     collections::HashMap,
+    net::{IpAddr, SocketAddr},
     sync::{Arc, Mutex},
     time::{Duration, Instant},
 };
 
 use axum::{
-    body::Body,
-    extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    body::{Body, Bytes, HttpBody},
+    extract::{ConnectInfo, Request, State},
+    http::{header::CONTENT_LENGTH, HeaderMap, StatusCode},
     middleware::Next,
-    response::Response,
+    response::{IntoResponse, Response},
+};
+use bytes::BytesMut;
+use ipnet::IpNet;
+use queue_keeper_core::audit_logging::{
+    AuditAction, AuditContext, AuditResource, AuditResult, HttpContext, SecurityContext,
+    SecurityLevel,
 };
 use tracing::{info, warn};
 
+use crate::authorization::{AdminPrincipal, RouteGroup};
+use crate::errors::WebhookHandlerError;
+use crate::read_only::ReadOnlyStatus;
 use crate::AppState;
 
 // ============================================================================
@@ -414,6 +429,9 @@ impl IpFailureTracker {
 ///
 /// The middleware is a transparent pass-through when `AppState::ip_rate_limiter`
 /// is `None` (i.e. when [`SecurityConfig::enable_ip_rate_limiting`] is `false`).
+/// State is read and written through the configured
+/// [`RateLimiterStore`](crate::rate_limit_store::RateLimiterStore); store
+/// errors are logged and the request is allowed.
 ///
 /// # Spec Reference
 ///
@@ -431,8 +449,19 @@ pub async fn ip_rate_limit_middleware(
         None => return next.run(request).await,
     };
 
-    let client_ip = extract_client_ip(request.headers());
-    let tier = tracker.check_tier(&client_ip);
+    let client_ip = client_ip(&state, &request);
+    let tier = match tracker.check_tier(&client_ip).await {
+        Ok(tier) => tier,
+        Err(e) => {
+            // Fail open: an unavailable store must not turn into an outage.
+            warn!(
+                client_ip = %client_ip,
+                error = %e,
+                "IP rate limiter store unavailable; allowing request"
+            );
+            IpTier::Normal
+        }
+    };
 
     if tier.is_restricted() {
         let retry_after = tier.retry_after_secs();
@@ -442,28 +471,35 @@ pub async fn ip_rate_limit_middleware(
             retry_after_secs = retry_after,
             "IP rate limited: too many authentication failures"
         );
-        return build_too_many_requests_response(retry_after);
+        return build_too_many_requests_response("Too many authentication failures", retry_after);
     }
 
     let response = next.run(request).await;
 
     if response.status() == StatusCode::UNAUTHORIZED {
-        // record_failure returns the new tier in the same lock acquisition,
-        // eliminating the need for a separate check_tier call.
-        match tracker.record_failure(&client_ip) {
-            IpTier::Normal => {
+        // record_failure returns the new tier, eliminating the need for a
+        // separate check_tier call.
+        match tracker.record_failure(&client_ip).await {
+            Err(e) => {
+                warn!(
+                    client_ip = %client_ip,
+                    error = %e,
+                    "Failed to record authentication failure in IP rate limiter store"
+                );
+            }
+            Ok(IpTier::Normal) => {
                 info!(
                     client_ip = %client_ip,
                     "Authentication failure recorded for IP"
                 );
             }
-            IpTier::RateRestricted { .. } => {
+            Ok(IpTier::RateRestricted { .. }) => {
                 warn!(
                     client_ip = %client_ip,
                     "IP escalated to RateRestricted tier after authentication failure"
                 );
             }
-            IpTier::Blocked { .. } => {
+            Ok(IpTier::Blocked { .. }) => {
                 warn!(
                     client_ip = %client_ip,
                     "IP escalated to Blocked tier after authentication failure"
@@ -475,78 +511,407 @@ pub async fn ip_rate_limit_middleware(
     response
 }
 
-/// Admin endpoint authentication middleware.
+/// Global and per-IP request rate limiting middleware.
+///
+/// Checks each request against [`AppState::request_rate_limiter`] and
+/// rejects it with HTTP 429 and a `Retry-After` header when the client IP's
+/// limit or the global limit is exhausted. Rejections are counted in
+/// `rate_limited_requests_total` by scope (`global` or `ip`).
+///
+/// The middleware is a transparent pass-through when
+/// `AppState::request_rate_limiter` is `None` (i.e. when
+/// `SecurityConfig::enable_rate_limiting` is `false`).
+///
+/// [`AppState::request_rate_limiter`]: crate::AppState::request_rate_limiter
+pub async fn request_rate_limit_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(limiter) = &state.request_rate_limiter else {
+        return next.run(request).await;
+    };
+
+    let client_ip = client_ip(&state, &request);
+    if let Err(rejected) = limiter.check(&client_ip) {
+        let retry_after = rejected.retry_after_secs();
+        warn!(
+            client_ip = %client_ip,
+            scope = rejected.scope.as_str(),
+            retry_after_secs = retry_after,
+            "Request rate limit exceeded"
+        );
+        state
+            .metrics
+            .record_rate_limited_request(rejected.scope.as_str());
+        return build_too_many_requests_response("Rate limit exceeded", retry_after);
+    }
+
+    next.run(request).await
+}
+
+/// Webhook body size limiting middleware.
+///
+/// Rejects requests whose body exceeds `server.max_body_size` with
+/// [`WebhookHandlerError::PayloadTooLarge`] (HTTP 413) carrying the
+/// configured maximum. A declared `Content-Length` over the limit is
+/// rejected before the body is read; otherwise the body is read until it
+/// ends or crosses the limit, so an oversized payload is never buffered
+/// beyond the limit nor handed to the webhook handler. For bodies without a
+/// `Content-Length` the reported size is the bytes received when the limit
+/// was crossed.
+pub async fn body_limit_middleware(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let max_size =
+        usize::try_from(state.config.server.max_body_size.as_u64()).unwrap_or(usize::MAX);
+
+    let declared_size = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<usize>().ok());
+    if let Some(size) = declared_size.filter(|size| *size > max_size) {
+        return WebhookHandlerError::PayloadTooLarge { size, max_size }.into_response();
+    }
+
+    let (parts, body) = request.into_parts();
+    match read_body_limited(body, max_size).await {
+        Ok(bytes) => {
+            next.run(Request::from_parts(parts, Body::from(bytes)))
+                .await
+        }
+        Err(BodyLimitError::TooLarge { received }) => WebhookHandlerError::PayloadTooLarge {
+            size: received,
+            max_size,
+        }
+        .into_response(),
+        Err(BodyLimitError::Read(e)) => {
+            warn!(error = %e, "Failed to read request body");
+            Response::builder()
+                .status(StatusCode::BAD_REQUEST)
+                .header("content-type", "application/json")
+                .body(Body::from(r#"{"error":"Failed to read request body"}"#))
+                .unwrap()
+        }
+    }
+}
+
+/// Why [`read_body_limited`] stopped reading a body.
+enum BodyLimitError {
+    /// The body crossed the limit after `received` bytes.
+    TooLarge { received: usize },
+    /// The body could not be read.
+    Read(axum::Error),
+}
+
+/// Read `body` into memory, stopping as soon as it exceeds `max_size` bytes.
+async fn read_body_limited(body: Body, max_size: usize) -> Result<Bytes, BodyLimitError> {
+    let mut body = std::pin::pin!(body);
+    let mut buffer = BytesMut::new();
+    while let Some(frame) = std::future::poll_fn(|cx| body.as_mut().poll_frame(cx)).await {
+        let Ok(data) = frame.map_err(BodyLimitError::Read)?.into_data() else {
+            continue; // trailers carry no payload
+        };
+        let received = buffer.len() + data.len();
+        if received > max_size {
+            return Err(BodyLimitError::TooLarge { received });
+        }
+        buffer.extend_from_slice(&data);
+    }
+    Ok(buffer.freeze())
+}
+
+/// Admin endpoint authentication and authorization middleware.
+///
+/// Resolves the caller to an [`AdminPrincipal`] from the
+/// `Authorization: Bearer <key>` header:
+///
+/// - a key matching [`AppState::admin_api_key`] yields the legacy `admin`
+///   principal with full access;
+/// - a key matching an entry in `SecurityConfig::admin_principals` yields
+///   that named principal with its configured roles.
+///
+/// Requests with an absent or unknown key receive HTTP 401. The request is
+/// then classified into a [`RouteGroup`]; principals whose roles do not grant
+/// that group receive HTTP 403. Authorized requests carry the principal in
+/// their extensions so handlers can attribute actions.
 ///
-/// When [`AppState::admin_api_key`] is `Some`, every request must carry a
-/// matching `Authorization: Bearer <key>` header. Requests that are absent or
-/// carry an incorrect key receive HTTP 401 without reaching the handler.
+/// While [`AppState::read_only`] is enabled, authorized mutating requests
+/// (every route group except read APIs, apart from the read-only toggle
+/// itself) receive HTTP 409 with the reason read-only mode was enabled.
 ///
-/// When `admin_api_key` is `None`, the middleware is a transparent pass-through
-/// so that deployments without an explicit admin key remain accessible.
+/// When neither `admin_api_key` nor any admin principal is configured, the
+/// middleware authenticates every caller as the anonymous principal so that
+/// development deployments remain accessible.
 ///
-/// The key comparison uses constant-time equality to prevent timing side-channels.
+/// Every authorized or denied request is recorded through
+/// [`AppState::audit_logger`] (when configured) with the acting principal.
+///
+/// Key comparisons use constant-time equality to prevent timing side-channels.
 ///
 /// [`AppState::admin_api_key`]: crate::AppState::admin_api_key
+/// [`AppState::audit_logger`]: crate::AppState::audit_logger
+/// [`AppState::read_only`]: crate::AppState::read_only
 pub async fn admin_auth_middleware(
     State(state): State<AppState>,
-    request: Request,
+    mut request: Request,
     next: Next,
 ) -> Response {
-    let expected = match &state.admin_api_key {
-        Some(k) => k.clone(),
-        None => return next.run(request).await,
+    let principal = match resolve_admin_principal(&state, request.headers()) {
+        Some(p) => p,
+        None => return build_admin_unauthorized_response(),
     };
 
-    match extract_bearer_token(request.headers()) {
-        Some(provided) if constant_time_eq(provided.as_bytes(), expected.as_bytes()) => {
-            next.run(request).await
+    let group = RouteGroup::classify(request.method(), request.uri().path());
+    let audit = AdminRequestAudit {
+        method: request.method().to_string(),
+        path: request.uri().path().to_string(),
+        source_ip: client_ip(&state, &request),
+        correlation_id: request.extensions().get::<String>().cloned(),
+    };
+
+    if !principal.is_permitted(group) {
+        warn!(
+            principal = %principal.name,
+            route_group = %group,
+            path = %audit.path,
+            "Admin request denied: principal lacks permission for route group"
+        );
+        audit
+            .record(
+                &state,
+                &principal,
+                group,
+                StatusCode::FORBIDDEN,
+                Duration::ZERO,
+            )
+            .await;
+        return build_admin_forbidden_response(group);
+    }
+
+    if let Some(status) = state.read_only.blocks(group, &audit.path) {
+        warn!(
+            principal = %principal.name,
+            route_group = %group,
+            path = %audit.path,
+            "Admin request rejected: service is in read-only mode"
+        );
+        audit
+            .record(
+                &state,
+                &principal,
+                group,
+                StatusCode::CONFLICT,
+                Duration::ZERO,
+            )
+            .await;
+        return build_read_only_response(group, &status);
+    }
+
+    request.extensions_mut().insert(principal.clone());
+    let start = Instant::now();
+    let response = next.run(request).await;
+
+    audit
+        .record(
+            &state,
+            &principal,
+            group,
+            response.status(),
+            start.elapsed(),
+        )
+        .await;
+
+    response
+}
+
+/// Request details captured for the admin audit entry.
+struct AdminRequestAudit {
+    method: String,
+    path: String,
+    source_ip: String,
+    correlation_id: Option<String>,
+}
+
+impl AdminRequestAudit {
+    /// Record the admin request through the configured audit logger.
+    ///
+    /// Audit failures are logged and never affect the response.
+    async fn record(
+        &self,
+        state: &AppState,
+        principal: &AdminPrincipal,
+        group: RouteGroup,
+        status: StatusCode,
+        duration: Duration,
+    ) {
+        let Some(audit_logger) = &state.audit_logger else {
+            return;
+        };
+
+        let granted = status != StatusCode::FORBIDDEN;
+        let action = match group {
+            RouteGroup::ReadApi => AuditAction::Read { query: None },
+            RouteGroup::ConfigChanges => AuditAction::Configure {
+                setting: self.path.clone(),
+                value: None,
+            },
+            RouteGroup::PrivacyErasure => AuditAction::Delete {
+                reason: Some(if self.path.starts_with("/admin/events/") {
+                    "event deletion".to_string()
+                } else {
+                    "privacy erasure".to_string()
+                }),
+            },
+            RouteGroup::Replay | RouteGroup::DlqManagement => AuditAction::Process {
+                operation: format!("{} {}", self.method, self.path),
+            },
+        };
+        let result = if status.is_success() {
+            AuditResult::Success {
+                duration: Some(duration),
+                details: Some(format!("HTTP {}", status.as_u16())),
+            }
+        } else {
+            AuditResult::Failure {
+                error_code: status.as_u16().to_string(),
+                error_message: status
+                    .canonical_reason()
+                    .unwrap_or("request failed")
+                    .to_string(),
+                retryable: status.is_server_error(),
+            }
+        };
+        let context = AuditContext {
+            correlation_id: self.correlation_id.clone(),
+            source_ip: Some(self.source_ip.clone()),
+            http_context: Some(HttpContext {
+                method: self.method.clone(),
+                path: self.path.clone(),
+                query_params: None,
+                headers: HashMap::new(),
+                response_status: Some(status.as_u16()),
+            }),
+            security: Some(SecurityContext {
+                authentication_method: Some("bearer".to_string()),
+                authorization_granted: granted,
+                security_level: SecurityLevel::Internal,
+                threat_indicators: vec![],
+                compliance_tags: vec![],
+            }),
+            ..Default::default()
+        };
+
+        if let Err(e) = audit_logger
+            .log_admin_action(
+                principal.to_audit_actor(),
+                AuditResource::Administrative {
+                    resource_type: group.as_str().to_string(),
+                    resource_id: self.path.clone(),
+                },
+                action,
+                result,
+                context,
+            )
+            .await
+        {
+            warn!(error = %e, path = %self.path, "Failed to record admin audit entry");
+        }
+    }
+}
+
+/// Resolve the admin principal for a request.
+///
+/// Returns `None` when credentials are configured but the request does not
+/// present a matching bearer key.
+fn resolve_admin_principal(state: &AppState, headers: &HeaderMap) -> Option<AdminPrincipal> {
+    let principals = &state.config.security.admin_principals;
+    if state.admin_api_key.is_none() && principals.is_empty() {
+        return Some(AdminPrincipal::anonymous());
+    }
+
+    let provided = extract_bearer_token(headers)?;
+
+    if let Some(expected) = &state.admin_api_key {
+        if constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+            return Some(AdminPrincipal::legacy_admin());
         }
-        _ => build_admin_unauthorized_response(),
     }
+
+    principals
+        .iter()
+        .find(|p| constant_time_eq(provided.as_bytes(), p.api_key.as_bytes()))
+        .map(|p| AdminPrincipal::new(p.name.clone(), p.roles.clone()))
 }
 
 // ============================================================================
 // Private Helpers
 // ============================================================================
 
-/// Extract the client IP from proxy headers, falling back to `"unknown"`.
+/// The client IP of `request`, see [`extract_client_ip`].
+fn client_ip(state: &AppState, request: &Request) -> String {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip());
+    extract_client_ip(
+        request.headers(),
+        peer,
+        &state.config.security.trusted_proxies,
+    )
+}
+
+/// Extract the client IP of a request from the connection's peer address.
 ///
-/// Priority:
-/// 1. First (leftmost, original client) IP in `X-Forwarded-For`
+/// Proxy headers are honoured only when `peer` is one of `trusted_proxies`
+/// (`security.trusted_proxies`):
+/// 1. The rightmost address in `X-Forwarded-For` that is not a trusted
+///    proxy; addresses to its left were supplied by the client and can be
+///    forged. When every address is a trusted proxy, the leftmost one.
 /// 2. `X-Real-IP`
-/// 3. `"unknown"`
-///
-/// # Security
-///
-/// These headers are **fully controlled by the sender** and can be trivially
-/// spoofed unless the service is deployed behind a reverse proxy that strips
-/// and re-appends them. Ensure a trusted proxy (e.g. an ingress controller or
-/// load balancer) is the only entity that can set `X-Forwarded-For` and
-/// `X-Real-IP` before this service receives the request.
-pub fn extract_client_ip(headers: &HeaderMap) -> String {
-    // 45 chars is the maximum length of a valid IP address string
-    // (IPv6 mapped IPv4, e.g. "0000:0000:0000:0000:0000:0000:255.255.255.255").
-    // Values longer than this are not valid IPs and must not be used as map
-    // keys to prevent adversarially large strings from bloating the tracker.
-    const MAX_IP_LEN: usize = 45;
+/// 3. `peer`
+///
+/// Header values that are not IP addresses are ignored. Without a peer
+/// address (a request not served from a socket) the result is `"unknown"`.
+pub fn extract_client_ip(
+    headers: &HeaderMap,
+    peer: Option<IpAddr>,
+    trusted_proxies: &[IpNet],
+) -> String {
+    let Some(peer) = peer else {
+        return "unknown".to_string();
+    };
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|net| net.contains(ip));
+    if !is_trusted(&peer) {
+        return peer.to_string();
+    }
 
     if let Some(xff) = headers.get("x-forwarded-for").and_then(|v| v.to_str().ok()) {
-        if let Some(first) = xff.split(',').next() {
-            let ip = first.trim();
-            if !ip.is_empty() && ip.len() <= MAX_IP_LEN {
-                return ip.to_string();
+        let hops: Option<Vec<IpAddr>> = xff.split(',').map(|hop| hop.trim().parse().ok()).collect();
+        if let Some(hops) = hops {
+            if let Some(client) = hops
+                .iter()
+                .rev()
+                .find(|ip| !is_trusted(ip))
+                .or_else(|| hops.first())
+            {
+                return client.to_string();
             }
         }
     }
 
-    if let Some(real_ip) = headers.get("x-real-ip").and_then(|v| v.to_str().ok()) {
-        let ip = real_ip.trim();
-        if !ip.is_empty() && ip.len() <= MAX_IP_LEN {
-            return ip.to_string();
-        }
+    if let Some(real_ip) = headers
+        .get("x-real-ip")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<IpAddr>().ok())
+    {
+        return real_ip.to_string();
     }
 
-    "unknown".to_string()
+    peer.to_string()
 }
 
 /// Extract the bearer token from `Authorization: Bearer <token>`.
@@ -574,19 +939,49 @@ fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
         == 0
 }
 
-fn build_too_many_requests_response(retry_after_secs: u64) -> Response {
+fn build_too_many_requests_response(message: &str, retry_after_secs: u64) -> Response {
     Response::builder()
         .status(StatusCode::TOO_MANY_REQUESTS)
         .header("content-type", "application/json")
         .header("retry-after", retry_after_secs.to_string())
         .header("x-ratelimit-remaining", "0")
         .body(Body::from(format!(
-            r#"{{"error":"Too many authentication failures","retry_after_seconds":{}}}"#,
-            retry_after_secs
+            r#"{{"error":"{}","retry_after_seconds":{}}}"#,
+            message, retry_after_secs
         )))
         .unwrap()
 }
 
+fn build_admin_forbidden_response(group: RouteGroup) -> Response {
+    Response::builder()
+        .status(StatusCode::FORBIDDEN)
+        .header("content-type", "application/json")
+        .body(Body::from(format!(
+            r#"{{"error":"Forbidden","message":"Principal is not permitted to perform {} operations","route_group":"{}"}}"#,
+            group, group
+        )))
+        .unwrap()
+}
+
+fn build_read_only_response(group: RouteGroup, status: &ReadOnlyStatus) -> Response {
+    let body = serde_json::json!({
+        "error": "read_only",
+        "message": format!(
+            "Queue-Keeper is in read-only mode; {} operations are rejected until it is disabled via PUT /admin/readonly",
+            group
+        ),
+        "route_group": group,
+        "reason": status.reason,
+        "enabled_by": status.enabled_by,
+        "enabled_at": status.enabled_at,
+    });
+    Response::builder()
+        .status(StatusCode::CONFLICT)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
 fn build_admin_unauthorized_response() -> Response {
     Response::builder()
         .status(StatusCode::UNAUTHORIZED)
@@ -376,6 +376,7 @@ mod ip_tier_escalation_tests {
 mod extract_client_ip_tests {
     use super::*;
     use axum::http::{HeaderMap, HeaderName, HeaderValue};
+    use std::net::IpAddr;
 
     fn headers_with(key: &str, value: &str) -> HeaderMap {
         let mut h = HeaderMap::new();
@@ -386,25 +387,71 @@ mod extract_client_ip_tests {
         h
     }
 
-    /// Verify that the leftmost (original client) IP in X-Forwarded-For is used.
+    fn ip(value: &str) -> Option<IpAddr> {
+        Some(value.parse().unwrap())
+    }
+
+    fn proxies() -> Vec<IpNet> {
+        vec!["10.0.0.0/8".parse().unwrap()]
+    }
+
+    /// Verify that the rightmost untrusted IP in X-Forwarded-For is used for a
+    /// request from a trusted proxy.
+    #[test]
+    fn test_uses_client_ip_from_x_forwarded_for() {
+        let headers = headers_with("x-forwarded-for", "203.0.113.1, 10.0.0.2");
+        assert_eq!(
+            extract_client_ip(&headers, ip("10.0.0.1"), &proxies()),
+            "203.0.113.1"
+        );
+    }
+
+    /// Verify that addresses a client prepends to X-Forwarded-For are ignored.
+    #[test]
+    fn test_ignores_forged_x_forwarded_for_prefix() {
+        let headers = headers_with("x-forwarded-for", "198.51.100.7, 203.0.113.1");
+        assert_eq!(
+            extract_client_ip(&headers, ip("10.0.0.1"), &proxies()),
+            "203.0.113.1"
+        );
+    }
+
+    /// Verify that proxy headers from an untrusted peer are ignored.
     #[test]
-    fn test_uses_first_ip_from_x_forwarded_for() {
-        let headers = headers_with("x-forwarded-for", "203.0.113.1, 10.0.0.1, 192.168.1.1");
-        assert_eq!(extract_client_ip(&headers), "203.0.113.1");
+    fn test_untrusted_peer_headers_ignored() {
+        let mut headers = headers_with("x-forwarded-for", "203.0.113.1");
+        headers.insert("x-real-ip", HeaderValue::from_static("203.0.113.5"));
+        assert_eq!(
+            extract_client_ip(&headers, ip("198.51.100.9"), &proxies()),
+            "198.51.100.9"
+        );
+        assert_eq!(extract_client_ip(&headers, ip("10.0.0.1"), &[]), "10.0.0.1");
     }
 
     /// Verify that X-Real-IP is used when X-Forwarded-For is absent.
     #[test]
     fn test_falls_back_to_x_real_ip() {
         let headers = headers_with("x-real-ip", "203.0.113.5");
-        assert_eq!(extract_client_ip(&headers), "203.0.113.5");
+        assert_eq!(
+            extract_client_ip(&headers, ip("10.0.0.1"), &proxies()),
+            "203.0.113.5"
+        );
+    }
+
+    /// Verify that the peer address is used when no IP header is present.
+    #[test]
+    fn test_falls_back_to_peer() {
+        assert_eq!(
+            extract_client_ip(&HeaderMap::new(), ip("10.0.0.1"), &proxies()),
+            "10.0.0.1"
+        );
     }
 
-    /// Verify that "unknown" is returned when no IP header is present.
+    /// Verify that "unknown" is returned without a peer address.
     #[test]
-    fn test_returns_unknown_when_no_ip_header() {
-        let headers = HeaderMap::new();
-        assert_eq!(extract_client_ip(&headers), "unknown");
+    fn test_returns_unknown_without_peer() {
+        let headers = headers_with("x-forwarded-for", "203.0.113.1");
+        assert_eq!(extract_client_ip(&headers, None, &proxies()), "unknown");
     }
 
     /// Verify that X-Forwarded-For takes priority over X-Real-IP when both
@@ -418,22 +465,32 @@ mod extract_client_ip_tests {
         );
         h.insert(
             HeaderName::from_bytes(b"x-real-ip").unwrap(),
-            HeaderValue::from_static("10.0.0.1"),
+            HeaderValue::from_static("203.0.113.9"),
+        );
+        assert_eq!(
+            extract_client_ip(&h, ip("10.0.0.1"), &proxies()),
+            "203.0.113.1"
         );
-        assert_eq!(extract_client_ip(&h), "203.0.113.1");
     }
+
     /// Verify that surrounding whitespace in X-Forwarded-For IPs is trimmed.
     #[test]
     fn test_x_forwarded_for_ip_is_trimmed() {
-        let headers = headers_with("x-forwarded-for", "  203.0.113.2  , 10.0.0.1");
-        assert_eq!(extract_client_ip(&headers), "203.0.113.2");
+        let headers = headers_with("x-forwarded-for", "  203.0.113.2  , 10.0.0.2");
+        assert_eq!(
+            extract_client_ip(&headers, ip("10.0.0.1"), &proxies()),
+            "203.0.113.2"
+        );
     }
-    /// Verify that an IP string longer than 45 characters (max valid IPv6 length)
-    /// is rejected and falls back to \"unknown\".
+
+    /// Verify that header values that are not IP addresses are ignored.
     #[test]
-    fn test_overlong_ip_is_rejected() {
+    fn test_invalid_ip_is_rejected() {
         let headers = headers_with("x-forwarded-for", &"a".repeat(46));
-        assert_eq!(extract_client_ip(&headers), "unknown");
+        assert_eq!(
+            extract_client_ip(&headers, ip("10.0.0.1"), &proxies()),
+            "10.0.0.1"
+        );
     }
 }
 
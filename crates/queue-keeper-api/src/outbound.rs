@@ -0,0 +1,233 @@
+//! Outbound network settings: HTTP(S) proxy, additional trusted CAs and
+//! cloud endpoint overrides.
+//!
+//! Queue-Keeper does not build the HTTP clients used for egress itself:
+//! github-bot-sdk, the queue-runtime providers and the Azure Key Vault client
+//! each construct their own. Their HTTP stacks all read the conventional
+//! process environment — `HTTPS_PROXY`, `HTTP_PROXY`, `NO_PROXY` and
+//! `SSL_CERT_FILE` — so [`apply_network_config`] exports those variables once
+//! at startup, before any client exists. Every provider therefore sees the
+//! same proxy and trust settings from a single [`NetworkConfig`].
+//!
+//! `SSL_CERT_FILE` replaces the trust store rather than extending it, so the
+//! configured CA bundle is concatenated with the system bundle into a file
+//! under the temporary directory and that combined file is exported. The
+//! file is created exclusively, under a random name and readable only by
+//! the service user, so another local user cannot plant or swap its CAs.
+//!
+//! Endpoint overrides for sovereign clouds and S3-compatible storage are
+//! exported the same way by [`apply_endpoint_config`]: `AZURE_AUTHORITY_HOST`
+//! for Azure credentials and `AWS_ENDPOINT_URL_SQS` / `AWS_ENDPOINT_URL_S3`
+//! for the AWS SDK.
+
+use crate::azure_config::AzureCloud;
+use crate::config::{EndpointConfig, NetworkConfig, ProxyConfig};
+use crate::errors::ConfigError;
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+use tempfile::TempPath;
+use tracing::info;
+
+/// Locations of the system CA bundle on common Linux distributions.
+const SYSTEM_CA_BUNDLES: &[&str] = &[
+    "/etc/ssl/certs/ca-certificates.crt",
+    "/etc/pki/tls/certs/ca-bundle.crt",
+    "/etc/ssl/ca-bundle.pem",
+    "/etc/ssl/cert.pem",
+];
+
+/// Apply outbound network settings to the process environment.
+///
+/// Must be called before any outbound HTTP client is constructed, and while
+/// no other thread is running, i.e. before the async runtime is started.
+///
+/// Returns the combined CA bundle when one is configured. The file is
+/// deleted when it is dropped, so keep it for as long as the process runs.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Invalid`] when the combined CA bundle cannot be
+/// written.
+pub fn apply_network_config(config: &NetworkConfig) -> Result<Option<TempPath>, ConfigError> {
+    if let Some(proxy) = &config.proxy {
+        for (name, value) in proxy_environment(proxy) {
+            std::env::set_var(name, value);
+        }
+        info!(
+            proxy = %proxy.url,
+            authenticated = proxy.username.is_some(),
+            no_proxy = ?proxy.no_proxy,
+            "Outbound proxy configured"
+        );
+    }
+
+    let Some(additional) = &config.ca_bundle_path else {
+        return Ok(None);
+    };
+    let system = system_ca_bundle();
+    let output = combine_ca_bundles(system.as_deref(), additional, &std::env::temp_dir())?;
+    std::env::set_var("SSL_CERT_FILE", &output);
+    info!(
+        ca_bundle = %additional.display(),
+        system_bundle = ?system,
+        combined_bundle = %output.display(),
+        "Additional CA bundle trusted for outbound TLS"
+    );
+    Ok(Some(output))
+}
+
+/// Apply cloud endpoint overrides to the process environment.
+///
+/// Must be called before any Azure credential or AWS client is constructed,
+/// and while no other thread is running, i.e. before the async runtime is
+/// started.
+pub fn apply_endpoint_config(config: &EndpointConfig) {
+    let vars = endpoint_environment(config);
+    if vars.is_empty() {
+        return;
+    }
+    for (name, value) in &vars {
+        std::env::set_var(name, value);
+    }
+    info!(
+        azure_cloud = %config.azure.cloud,
+        overrides = ?vars,
+        "Cloud endpoint overrides configured"
+    );
+}
+
+/// Environment variables that carry the configured endpoint overrides.
+///
+/// The Azure authority is only exported when it differs from the public
+/// cloud default so that an operator-provided `AZURE_AUTHORITY_HOST` is left
+/// untouched in the common case.
+pub fn endpoint_environment(config: &EndpointConfig) -> Vec<(&'static str, String)> {
+    let mut vars = Vec::new();
+    if config.azure.cloud != AzureCloud::Public || config.azure.authority_host.is_some() {
+        vars.push((
+            "AZURE_AUTHORITY_HOST",
+            config.azure.authority_host().to_string(),
+        ));
+    }
+    if let Some(url) = &config.aws.sqs_endpoint_url {
+        vars.push(("AWS_ENDPOINT_URL_SQS", url.clone()));
+    }
+    if let Some(url) = &config.aws.s3_endpoint_url {
+        vars.push(("AWS_ENDPOINT_URL_S3", url.clone()));
+    }
+    vars
+}
+
+/// Environment variables that route outbound traffic through the proxy.
+///
+/// Both upper- and lower-case names are returned because HTTP stacks differ
+/// in which spelling they read.
+pub fn proxy_environment(proxy: &ProxyConfig) -> Vec<(&'static str, String)> {
+    let url = proxy_url_with_credentials(proxy);
+    let mut vars = vec![
+        ("HTTPS_PROXY", url.clone()),
+        ("https_proxy", url.clone()),
+        ("HTTP_PROXY", url.clone()),
+        ("http_proxy", url),
+    ];
+    if !proxy.no_proxy.is_empty() {
+        let no_proxy = proxy.no_proxy.join(",");
+        vars.push(("NO_PROXY", no_proxy.clone()));
+        vars.push(("no_proxy", no_proxy));
+    }
+    vars
+}
+
+/// Write `system` (when present) followed by `additional` to a new file in
+/// `directory`.
+///
+/// The file gets a random name and owner-only permissions and is created
+/// exclusively; it is deleted when the returned path is dropped.
+///
+/// # Errors
+///
+/// Returns [`ConfigError::Invalid`] when a bundle cannot be read or the
+/// output cannot be written.
+pub fn combine_ca_bundles(
+    system: Option<&Path>,
+    additional: &Path,
+    directory: &Path,
+) -> Result<TempPath, ConfigError> {
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).map_err(|e| ConfigError::Invalid {
+            message: format!("cannot read CA bundle '{}': {}", path.display(), e),
+        })
+    };
+
+    let mut combined = match system {
+        Some(path) => read(path)?,
+        None => String::new(),
+    };
+    if !combined.is_empty() && !combined.ends_with('\n') {
+        combined.push('\n');
+    }
+    combined.push_str(&read(additional)?);
+
+    let write_error = |e: std::io::Error| ConfigError::Invalid {
+        message: format!(
+            "cannot write combined CA bundle in '{}': {}",
+            directory.display(),
+            e
+        ),
+    };
+    let mut output = tempfile::Builder::new()
+        .prefix("queue-keeper-ca-bundle-")
+        .suffix(".pem")
+        .tempfile_in(directory)
+        .map_err(write_error)?;
+    output
+        .write_all(combined.as_bytes())
+        .and_then(|()| output.as_file().sync_all())
+        .map_err(write_error)?;
+    Ok(output.into_temp_path())
+}
+
+/// Locate the system CA bundle, honouring an existing `SSL_CERT_FILE`.
+pub fn system_ca_bundle() -> Option<PathBuf> {
+    std::env::var_os("SSL_CERT_FILE")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(SYSTEM_CA_BUNDLES.iter().map(PathBuf::from))
+        .find(|path| path.is_file())
+}
+
+/// Proxy URL with percent-encoded credentials inserted, if configured.
+fn proxy_url_with_credentials(proxy: &ProxyConfig) -> String {
+    let Some(username) = &proxy.username else {
+        return proxy.url.clone();
+    };
+    let Some((scheme, rest)) = proxy.url.split_once("://") else {
+        return proxy.url.clone();
+    };
+
+    let mut userinfo = percent_encode(username);
+    if let Some(password) = &proxy.password {
+        userinfo.push(':');
+        userinfo.push_str(&percent_encode(password));
+    }
+    format!("{}://{}@{}", scheme, userinfo, rest)
+}
+
+/// Percent-encode everything except RFC 3986 unreserved characters.
+fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[path = "outbound_tests.rs"]
+mod tests;
@@ -0,0 +1,646 @@
+//! Durable outbox of accepted events.
+//!
+//! The webhook handler answers `200 OK` before queue delivery runs, so an
+//! event whose delivery was still under way when the process stopped was
+//! lost. With `outbox.enabled` set, the handler writes an [`OutboxEntry`]
+//! (the wrapped event and the bots it is routed to) to `outbox.directory`
+//! before responding. Every delivery attempt removes the bots whose queue
+//! acknowledged the message, and those that failed permanently, which the
+//! dead letter queue records as before; the entry is deleted once no bot is
+//! left.
+//!
+//! Entries whose delivery ended with transient failures, and entries left
+//! by an earlier run, are redelivered to their remaining bots by the
+//! [`OutboxDispatcher`]. Redeliveries back off from `outbox.retry_delay` to
+//! `outbox.max_retry_delay` and continue until the queues accept the event.
+//!
+//! Entries are written to a temporary file that is synced and renamed into
+//! place, and the directory is synced after the rename, so a crash leaves
+//! the previous version of an entry or the new one. Fields a routed bot
+//! encrypts ([`BotSubscription::encryption`]) are encrypted in the entry
+//! with that bot's key and decrypted again for redelivery. An entry that
+//! cannot be read when the outbox is opened is renamed to
+//! `<event_id>.json.corrupt` and skipped, rather than stopping startup.
+//!
+//! # Limitations
+//!
+//! - A redelivered event takes a new place in its session's send order, so
+//!   bots may receive it after newer events of the same session.
+//! - Bots paused when the event is routed leave the entry: their copy is
+//!   kept in the in-memory hold queue (see [`crate::bot_pause`]).
+//! - A failed outbox write does not fail the webhook. Deduplication has
+//!   already claimed the delivery ID, so the provider's retry would be
+//!   skipped; the event is delivered without the guarantee and counted in
+//!   `outbox_write_failures_total`.
+//! - Each replica drains only its own directory; give every replica its own.
+//!
+//! [`BotSubscription::encryption`]: queue_keeper_core::bot_config::BotSubscription::encryption
+
+use crate::{
+    metrics::ServiceMetrics,
+    queue_delivery::{deliver_event_to_queues, QueueDeliveryConfig, QueueDeliveryOutcome},
+};
+use queue_keeper_core::{
+    bot_config::{BotConfiguration, BotSubscription, SharedBotConfiguration},
+    field_encryption::FieldEncryptor,
+    queue_integration::{DeliveryResult, EventRouter},
+    units::human_duration,
+    webhook::WrappedEvent,
+    BotName, EventId, Timestamp,
+};
+use queue_runtime::QueueClient;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{info, warn};
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Outbox settings (the `outbox` configuration section).
+///
+/// ```yaml
+/// outbox:
+///   enabled: true
+///   directory: /var/lib/queue-keeper/outbox
+///   retry_delay: 30s
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OutboxConfig {
+    /// Whether accepted events are written to the outbox. Off by default.
+    pub enabled: bool,
+
+    /// Directory of the outbox entries, one JSON file per event.
+    pub directory: PathBuf,
+
+    /// Interval between two scans for entries due for redelivery.
+    #[serde(with = "human_duration")]
+    pub dispatch_interval: Duration,
+
+    /// Delay before the first redelivery of an entry; doubled after every
+    /// redelivery.
+    #[serde(with = "human_duration")]
+    pub retry_delay: Duration,
+
+    /// Longest delay between two redeliveries of an entry.
+    #[serde(with = "human_duration")]
+    pub max_retry_delay: Duration,
+}
+
+impl Default for OutboxConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            directory: PathBuf::from("./outbox"),
+            dispatch_interval: Duration::from_secs(10),
+            retry_delay: Duration::from_secs(30),
+            max_retry_delay: Duration::from_secs(600),
+        }
+    }
+}
+
+impl OutboxConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && self.directory.as_os_str().is_empty() {
+            return Err("outbox.directory must not be empty".to_string());
+        }
+        if self.dispatch_interval.is_zero() {
+            return Err("outbox.dispatch_interval must not be zero".to_string());
+        }
+        if self.retry_delay.as_secs() == 0 {
+            return Err("outbox.retry_delay must be at least one second".to_string());
+        }
+        if self.max_retry_delay < self.retry_delay {
+            return Err(
+                "outbox.max_retry_delay must not be less than outbox.retry_delay".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Entries
+// ============================================================================
+
+/// An accepted event whose delivery is not acknowledged by every bot yet.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutboxEntry {
+    /// The event, as accepted by the webhook handler.
+    pub event: WrappedEvent,
+
+    /// Bots whose queues have not acknowledged the event.
+    pub bots: Vec<BotName>,
+
+    /// When the event was accepted.
+    pub accepted_at: Timestamp,
+
+    /// Redeliveries scheduled so far.
+    pub attempts: u32,
+
+    /// When the entry becomes due for redelivery.
+    pub next_attempt_at: Timestamp,
+}
+
+/// Errors reading or writing outbox entries.
+#[derive(Debug, thiserror::Error)]
+pub enum OutboxError {
+    #[error("Outbox directory {path} could not be read: {message}")]
+    Load { path: String, message: String },
+
+    #[error("Outbox entry of event {event_id} could not be written: {message}")]
+    Persist { event_id: EventId, message: String },
+
+    #[error("Outbox entry of event {event_id} could not be encrypted: {message}")]
+    Encrypt { event_id: EventId, message: String },
+}
+
+/// An entry and whether a delivery of its event is under way.
+#[derive(Debug)]
+struct Slot {
+    entry: OutboxEntry,
+    in_flight: bool,
+}
+
+/// Entries of the accepted events still to be delivered.
+pub struct Outbox {
+    config: OutboxConfig,
+    slots: Mutex<HashMap<EventId, Slot>>,
+    field_encryptor: Option<Arc<FieldEncryptor>>,
+}
+
+impl std::fmt::Debug for Outbox {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Outbox")
+            .field("config", &self.config)
+            .field("entries", &self.len())
+            .field("field_encryption", &self.field_encryptor.is_some())
+            .finish()
+    }
+}
+
+impl Outbox {
+    /// Open the outbox described by `config`, loading the entries written
+    /// to its directory by earlier runs. Loaded entries are due at once;
+    /// unreadable ones are quarantined.
+    pub fn open(config: &OutboxConfig) -> Result<Self, OutboxError> {
+        let slots = load_directory(&config.directory)?
+            .into_iter()
+            .map(|(event_id, entry)| {
+                (
+                    event_id,
+                    Slot {
+                        entry,
+                        in_flight: false,
+                    },
+                )
+            })
+            .collect();
+        Ok(Self {
+            config: config.clone(),
+            slots: Mutex::new(slots),
+            field_encryptor: None,
+        })
+    }
+
+    /// Encrypt the fields of bots with field encryption in the entries, with
+    /// the keys `encryptor` loads.
+    ///
+    /// Without an encryptor, events routed to such bots are not admitted.
+    pub fn with_field_encryption(mut self, encryptor: Arc<FieldEncryptor>) -> Self {
+        self.field_encryptor = Some(encryptor);
+        self
+    }
+
+    /// Record `event` before it is delivered to `bots`.
+    ///
+    /// The entry is written to disk before this returns; the delivery
+    /// started by the caller is in flight, so the dispatcher leaves the
+    /// entry alone until it ends. Nothing is recorded without bots.
+    pub async fn admit(
+        &self,
+        event: &WrappedEvent,
+        bots: &[&BotSubscription],
+    ) -> Result<(), OutboxError> {
+        if bots.is_empty() {
+            return Ok(());
+        }
+        let accepted_at = Timestamp::now();
+        let entry = OutboxEntry {
+            event: self.encrypt(event, bots).await?,
+            bots: bots.iter().map(|bot| bot.name.clone()).collect(),
+            accepted_at,
+            attempts: 0,
+            next_attempt_at: accepted_at,
+        };
+        let directory = self.config.directory.clone();
+        let written = entry.clone();
+        tokio::task::spawn_blocking(move || write_entry(&directory, &written))
+            .await
+            .map_err(|e| OutboxError::Persist {
+                event_id: event.event_id,
+                message: e.to_string(),
+            })??;
+        self.slots.lock().unwrap().insert(
+            event.event_id,
+            Slot {
+                entry,
+                in_flight: true,
+            },
+        );
+        Ok(())
+    }
+
+    /// Remove the bots of one delivery attempt of `event_id` that need no
+    /// redelivery: those whose queue accepted the event and those that
+    /// failed permanently.
+    pub fn record_result(&self, event_id: EventId, result: &DeliveryResult) {
+        self.update(event_id, |slot| {
+            slot.entry.bots.retain(|bot| {
+                !result.successful.iter().any(|s| &s.bot_name == bot)
+                    && !result
+                        .failed
+                        .iter()
+                        .any(|f| &f.bot_name == bot && !f.is_transient)
+            });
+        });
+    }
+
+    /// Remove `bots` from the entry of `event_id`: the event is held for
+    /// them while they are paused.
+    pub fn hand_off(&self, event_id: EventId, bots: &[BotName]) {
+        if bots.is_empty() {
+            return;
+        }
+        self.update(event_id, |slot| {
+            slot.entry.bots.retain(|bot| !bots.contains(bot));
+        });
+    }
+
+    /// Settle the entry of a delivery that ended with `outcome`.
+    ///
+    /// Deliveries that ended with transient failures are scheduled for
+    /// redelivery to the bots left in the entry. Events buffered for a
+    /// paused session keep their entry until the buffered delivery ends.
+    /// Any other outcome completes the entry: the event reached every bot
+    /// it was routed to, matched none, was cancelled by a session reset or
+    /// was persisted to the dead letter queue.
+    pub fn finish(&self, outcome: &QueueDeliveryOutcome) {
+        match outcome {
+            QueueDeliveryOutcome::BufferedForPausedSession { .. } => {}
+            QueueDeliveryOutcome::SomeQueuesFailed {
+                event_id,
+                persisted_to_dlq: false,
+                ..
+            }
+            | QueueDeliveryOutcome::CompleteFailure {
+                event_id,
+                persisted_to_dlq: false,
+                ..
+            } => self.retry_later(*event_id),
+            QueueDeliveryOutcome::AllQueuesSucceeded { event_id, .. }
+            | QueueDeliveryOutcome::NoTargetQueues { event_id }
+            | QueueDeliveryOutcome::CancelledBySessionReset { event_id, .. }
+            | QueueDeliveryOutcome::SomeQueuesFailed { event_id, .. }
+            | QueueDeliveryOutcome::CompleteFailure { event_id, .. } => self.complete(event_id),
+        }
+    }
+
+    /// Delete the entry of `event_id`.
+    pub fn complete(&self, event_id: &EventId) {
+        let mut slots = self.slots.lock().unwrap();
+        if slots.remove(event_id).is_some() {
+            if let Err(e) = remove_entry(&self.config.directory, event_id) {
+                warn!(event_id = %event_id, error = %e, "Failed to delete outbox entry");
+            }
+        }
+    }
+
+    /// Entries due for redelivery at `now`, oldest first. They are in
+    /// flight until their delivery ends.
+    pub fn take_due(&self, now: Timestamp) -> Vec<OutboxEntry> {
+        let mut slots = self.slots.lock().unwrap();
+        let mut due: Vec<OutboxEntry> = slots
+            .values_mut()
+            .filter(|slot| !slot.in_flight && slot.entry.next_attempt_at <= now)
+            .map(|slot| {
+                slot.in_flight = true;
+                slot.entry.clone()
+            })
+            .collect();
+        due.sort_by_key(|entry| entry.accepted_at);
+        due
+    }
+
+    /// Entry of `event_id`, if its delivery is not complete.
+    pub fn get(&self, event_id: &EventId) -> Option<OutboxEntry> {
+        self.slots
+            .lock()
+            .unwrap()
+            .get(event_id)
+            .map(|slot| slot.entry.clone())
+    }
+
+    /// Number of events whose delivery is not complete.
+    pub fn len(&self) -> usize {
+        self.slots.lock().unwrap().len()
+    }
+
+    /// Whether every accepted event was delivered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// `event` as written to disk: the fields of every bot in `bots` with
+    /// field encryption encrypted under that bot's key.
+    async fn encrypt(
+        &self,
+        event: &WrappedEvent,
+        bots: &[&BotSubscription],
+    ) -> Result<WrappedEvent, OutboxError> {
+        let encrypt_error = |message: String| OutboxError::Encrypt {
+            event_id: event.event_id,
+            message,
+        };
+        let mut stored = event.clone();
+        for config in bots.iter().filter_map(|bot| bot.encryption.as_ref()) {
+            let Some(encryptor) = &self.field_encryptor else {
+                return Err(encrypt_error(
+                    "field encryption is configured but no key vault is available".to_string(),
+                ));
+            };
+            stored = encryptor
+                .encrypt_event(&stored, config)
+                .await
+                .map_err(|e| encrypt_error(e.to_string()))?;
+        }
+        Ok(stored)
+    }
+
+    /// The event of `entry` with its encrypted fields restored.
+    async fn decrypt(&self, entry: &OutboxEntry) -> Result<WrappedEvent, OutboxError> {
+        let Some(encryptor) = &self.field_encryptor else {
+            return Ok(entry.event.clone());
+        };
+        encryptor
+            .decrypt_event(&entry.event)
+            .await
+            .map_err(|e| OutboxError::Encrypt {
+                event_id: entry.event.event_id,
+                message: e.to_string(),
+            })
+    }
+
+    /// Schedule the next redelivery of the entry of `event_id`.
+    fn retry_later(&self, event_id: EventId) {
+        let delay = |attempts: u32| {
+            let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+            self.config
+                .retry_delay
+                .saturating_mul(factor)
+                .min(self.config.max_retry_delay)
+        };
+        self.update(event_id, |slot| {
+            slot.entry.attempts += 1;
+            slot.entry.next_attempt_at =
+                Timestamp::now().add_seconds(delay(slot.entry.attempts).as_secs());
+            slot.in_flight = false;
+        });
+    }
+
+    /// Apply `change` to the entry of `event_id` and write it back, or
+    /// delete it once no bot is left.
+    fn update(&self, event_id: EventId, change: impl FnOnce(&mut Slot)) {
+        let mut slots = self.slots.lock().unwrap();
+        let Some(slot) = slots.get_mut(&event_id) else {
+            return;
+        };
+        // Bots are only ever removed, so a change shows in the count
+        let before = (slot.entry.bots.len(), slot.entry.attempts);
+        change(slot);
+        let result = if slot.entry.bots.is_empty() {
+            slots.remove(&event_id);
+            remove_entry(&self.config.directory, &event_id)
+        } else if (slot.entry.bots.len(), slot.entry.attempts) != before {
+            write_entry(&self.config.directory, &slot.entry)
+        } else {
+            Ok(())
+        };
+        if let Err(e) = result {
+            warn!(event_id = %event_id, error = %e, "Failed to update outbox entry");
+        }
+    }
+}
+
+fn entry_path(directory: &Path, event_id: &EventId) -> PathBuf {
+    directory.join(format!("{}.json", event_id))
+}
+
+/// Write `entry` to a temporary file and rename it into place, so a crash
+/// never leaves a partial entry.
+///
+/// The file is synced before the rename and the directory after it, so the
+/// entry is on disk once this returns.
+fn write_entry(directory: &Path, entry: &OutboxEntry) -> Result<(), OutboxError> {
+    let persist_error = |message: String| OutboxError::Persist {
+        event_id: entry.event.event_id,
+        message,
+    };
+    let json = serde_json::to_vec(entry).map_err(|e| persist_error(e.to_string()))?;
+    let path = entry_path(directory, &entry.event.event_id);
+    let temp_path = path.with_extension("json.tmp");
+    let write = || -> std::io::Result<()> {
+        std::fs::create_dir_all(directory)?;
+        let mut file = std::fs::File::create(&temp_path)?;
+        file.write_all(&json)?;
+        file.sync_all()?;
+        std::fs::rename(&temp_path, &path)?;
+        sync_directory(directory)
+    };
+    write().map_err(|e| persist_error(e.to_string()))
+}
+
+/// Sync `directory`, so the renames and deletions in it survive a crash.
+#[cfg(unix)]
+fn sync_directory(directory: &Path) -> std::io::Result<()> {
+    std::fs::File::open(directory)?.sync_all()
+}
+
+/// Directories cannot be opened for syncing outside Unix.
+#[cfg(not(unix))]
+fn sync_directory(_directory: &Path) -> std::io::Result<()> {
+    Ok(())
+}
+
+fn remove_entry(directory: &Path, event_id: &EventId) -> Result<(), OutboxError> {
+    match std::fs::remove_file(entry_path(directory, event_id)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(OutboxError::Persist {
+            event_id: *event_id,
+            message: e.to_string(),
+        }),
+    }
+}
+
+fn load_directory(directory: &Path) -> Result<HashMap<EventId, OutboxEntry>, OutboxError> {
+    let load_error = |message: String| OutboxError::Load {
+        path: directory.display().to_string(),
+        message,
+    };
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(HashMap::new()),
+        Err(e) => return Err(load_error(e.to_string())),
+    };
+
+    let mut loaded = HashMap::new();
+    for entry in entries {
+        let path = entry.map_err(|e| load_error(e.to_string()))?.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let read = std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| {
+                serde_json::from_slice::<OutboxEntry>(&bytes).map_err(|e| e.to_string())
+            });
+        match read {
+            Ok(entry) => {
+                loaded.insert(entry.event.event_id, entry);
+            }
+            Err(error) => quarantine(&path, &error),
+        }
+    }
+    Ok(loaded)
+}
+
+/// Rename the unreadable entry at `path` to `<name>.corrupt`, so it is kept
+/// for inspection but no longer loaded.
+fn quarantine(path: &Path, error: &str) {
+    let target = path.with_extension("json.corrupt");
+    match std::fs::rename(path, &target) {
+        Ok(()) => warn!(
+            path = %path.display(),
+            quarantined_to = %target.display(),
+            error = %error,
+            "Quarantined unreadable outbox entry"
+        ),
+        Err(e) => warn!(
+            path = %path.display(),
+            error = %error,
+            rename_error = %e,
+            "Skipping unreadable outbox entry that could not be quarantined"
+        ),
+    }
+}
+
+// ============================================================================
+// Dispatcher
+// ============================================================================
+
+/// Periodically redelivers the outbox entries that are due.
+pub struct OutboxDispatcher {
+    outbox: Arc<Outbox>,
+    event_router: Arc<dyn EventRouter>,
+    bot_config: Arc<SharedBotConfiguration>,
+    queue_client: Arc<dyn QueueClient>,
+    delivery_config: QueueDeliveryConfig,
+}
+
+impl OutboxDispatcher {
+    /// Create a dispatcher redelivering the entries of `outbox` through
+    /// `queue_client`, with the current bot configuration.
+    pub fn new(
+        outbox: Arc<Outbox>,
+        event_router: Arc<dyn EventRouter>,
+        bot_config: Arc<SharedBotConfiguration>,
+        queue_client: Arc<dyn QueueClient>,
+        delivery_config: QueueDeliveryConfig,
+    ) -> Self {
+        Self {
+            outbox,
+            event_router,
+            bot_config,
+            queue_client,
+            delivery_config,
+        }
+    }
+
+    /// Redeliver the entries that are due, one after another, and return
+    /// their number.
+    pub async fn dispatch(&self, metrics: &ServiceMetrics) -> usize {
+        let due = self.outbox.take_due(Timestamp::now());
+        for entry in &due {
+            let event = match self.outbox.decrypt(entry).await {
+                Ok(event) => event,
+                Err(e) => {
+                    warn!(
+                        event_id = %entry.event.event_id,
+                        error = %e,
+                        "Failed to decrypt outbox entry; retrying later"
+                    );
+                    self.outbox.retry_later(entry.event.event_id);
+                    continue;
+                }
+            };
+            metrics.outbox_redeliveries_total.inc();
+            info!(
+                event_id = %entry.event.event_id,
+                attempts = entry.attempts,
+                bots = entry.bots.len(),
+                "Redelivering event from the outbox"
+            );
+            deliver_event_to_queues(
+                event,
+                self.event_router.clone(),
+                plan_config(&self.bot_config.current(), &entry.bots),
+                self.queue_client.clone(),
+                self.delivery_config.clone(),
+            )
+            .await;
+        }
+        metrics.outbox_pending_entries.set(self.outbox.len() as i64);
+        due.len()
+    }
+
+    /// Run [`dispatch`](Self::dispatch) every `outbox.dispatch_interval`,
+    /// forever.
+    pub async fn run(&self, metrics: &ServiceMetrics) {
+        let mut ticker = tokio::time::interval(self.outbox.config.dispatch_interval);
+        loop {
+            ticker.tick().await;
+            self.dispatch(metrics).await;
+        }
+    }
+}
+
+/// Narrow `bot_config` to `bots`, so a redelivery skips the queues that
+/// already accepted the event. Bots removed from the configuration since the
+/// event was accepted are not redelivered to.
+fn plan_config(bot_config: &BotConfiguration, bots: &[BotName]) -> Arc<BotConfiguration> {
+    Arc::new(BotConfiguration {
+        bots: bot_config
+            .bots
+            .iter()
+            .filter(|bot| bots.contains(&bot.name))
+            .cloned()
+            .collect(),
+        settings: bot_config.settings.clone(),
+    })
+}
+
+#[cfg(test)]
+#[path = "outbox_tests.rs"]
+mod tests;
@@ -0,0 +1,272 @@
+//! Tests for the durable outbox of accepted events.
+
+use super::*;
+use crate::test_fixtures::{bot, delivered, event, event_with_payload, failed, subscription};
+use queue_keeper_core::{
+    adapters::InMemoryKeyVaultProvider,
+    bot_config::EventTypePattern,
+    field_encryption::FieldEncryptionConfig,
+    key_vault::{SecretName, SecretValue},
+};
+
+const KEY_SECRET: &str = "queue-keeper-test-outbox-key";
+
+fn bots(names: &[&str]) -> Vec<BotName> {
+    names.iter().map(|name| bot(name)).collect()
+}
+
+/// Record `event` for the bots called `names`.
+async fn admit(outbox: &Outbox, event: &WrappedEvent, names: &[&str]) {
+    let subscriptions: Vec<BotSubscription> = names
+        .iter()
+        .map(|name| subscription(name, EventTypePattern::Wildcard("*".to_string())))
+        .collect();
+    let subscriptions: Vec<&BotSubscription> = subscriptions.iter().collect();
+    outbox.admit(event, &subscriptions).await.unwrap();
+}
+
+/// An encryptor whose Key Vault holds the key named [`KEY_SECRET`].
+fn field_encryptor() -> Arc<FieldEncryptor> {
+    let provider = InMemoryKeyVaultProvider::new();
+    provider.add_secret(
+        SecretName::new(KEY_SECRET).unwrap(),
+        SecretValue::from_string("BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc=".to_string()),
+    );
+    Arc::new(FieldEncryptor::new(Arc::new(provider)))
+}
+
+fn config(directory: &Path) -> OutboxConfig {
+    OutboxConfig {
+        enabled: true,
+        directory: directory.join("outbox"),
+        ..OutboxConfig::default()
+    }
+}
+
+/// Entries written before the response are loaded again, due at once.
+#[tokio::test]
+async fn test_entries_survive_restart() {
+    let dir = tempfile::tempdir().unwrap();
+    let event = event();
+
+    let outbox = Outbox::open(&config(dir.path())).unwrap();
+    admit(&outbox, &event, &["bot-a", "bot-b"]).await;
+    // The delivery started by the handler is in flight
+    assert!(outbox.take_due(Timestamp::now()).is_empty());
+    drop(outbox);
+
+    let reopened = Outbox::open(&config(dir.path())).unwrap();
+    let due = reopened.take_due(Timestamp::now());
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].event.event_id, event.event_id);
+    assert_eq!(due[0].bots, bots(&["bot-a", "bot-b"]));
+    assert!(reopened.take_due(Timestamp::now()).is_empty());
+}
+
+/// Events without target bots get no entry.
+#[tokio::test]
+async fn test_admit_without_bots_records_nothing() {
+    let dir = tempfile::tempdir().unwrap();
+    let outbox = Outbox::open(&config(dir.path())).unwrap();
+
+    outbox.admit(&event(), &[]).await.unwrap();
+
+    assert!(outbox.is_empty());
+    assert!(!dir.path().join("outbox").exists());
+}
+
+/// Acknowledged and permanently failed bots leave the entry, on disk too;
+/// the entry is deleted once no bot is left.
+#[tokio::test]
+async fn test_record_result_removes_settled_bots() {
+    let dir = tempfile::tempdir().unwrap();
+    let event = event();
+    let outbox = Outbox::open(&config(dir.path())).unwrap();
+    admit(&outbox, &event, &["bot-a", "bot-b", "bot-c"]).await;
+
+    outbox.record_result(
+        event.event_id,
+        &DeliveryResult {
+            event_id: event.event_id,
+            successful: vec![delivered("bot-a")],
+            failed: vec![failed("bot-b", false), failed("bot-c", true)],
+        },
+    );
+    assert_eq!(outbox.get(&event.event_id).unwrap().bots, bots(&["bot-c"]));
+    let reopened = Outbox::open(&config(dir.path())).unwrap();
+    assert_eq!(
+        reopened.get(&event.event_id).unwrap().bots,
+        bots(&["bot-c"])
+    );
+
+    outbox.record_result(
+        event.event_id,
+        &DeliveryResult {
+            event_id: event.event_id,
+            successful: vec![delivered("bot-c")],
+            failed: Vec::new(),
+        },
+    );
+    assert!(outbox.is_empty());
+    assert!(Outbox::open(&config(dir.path())).unwrap().is_empty());
+}
+
+/// Bots the event is held for leave the entry.
+#[tokio::test]
+async fn test_hand_off_removes_held_bots() {
+    let dir = tempfile::tempdir().unwrap();
+    let event = event();
+    let outbox = Outbox::open(&config(dir.path())).unwrap();
+    admit(&outbox, &event, &["bot-a", "bot-b"]).await;
+
+    outbox.hand_off(event.event_id, &bots(&["bot-b"]));
+
+    assert_eq!(outbox.get(&event.event_id).unwrap().bots, bots(&["bot-a"]));
+}
+
+/// Transient failures schedule a redelivery after the retry delay.
+#[tokio::test]
+async fn test_finish_failure_schedules_redelivery() {
+    let dir = tempfile::tempdir().unwrap();
+    let event = event();
+    let outbox = Outbox::open(&config(dir.path())).unwrap();
+    admit(&outbox, &event, &["bot-a"]).await;
+
+    outbox.finish(&QueueDeliveryOutcome::CompleteFailure {
+        event_id: event.event_id,
+        error: "queue unavailable".to_string(),
+        persisted_to_dlq: false,
+    });
+
+    let entry = outbox.get(&event.event_id).unwrap();
+    assert_eq!(entry.attempts, 1);
+    assert!(outbox.take_due(Timestamp::now()).is_empty());
+    let due = outbox.take_due(Timestamp::now().add_seconds(31));
+    assert_eq!(due.len(), 1);
+
+    // The redelivery fails again: the delay doubles
+    outbox.finish(&QueueDeliveryOutcome::CompleteFailure {
+        event_id: event.event_id,
+        error: "queue unavailable".to_string(),
+        persisted_to_dlq: false,
+    });
+    assert!(outbox.take_due(Timestamp::now().add_seconds(31)).is_empty());
+    assert_eq!(outbox.take_due(Timestamp::now().add_seconds(61)).len(), 1);
+}
+
+/// Delivered, dead-lettered and cancelled events complete their entry;
+/// buffered events keep it.
+#[tokio::test]
+async fn test_finish_completes_entry() {
+    let dir = tempfile::tempdir().unwrap();
+    let outbox = Outbox::open(&config(dir.path())).unwrap();
+    let outcomes: [fn(EventId) -> QueueDeliveryOutcome; 3] = [
+        |event_id| QueueDeliveryOutcome::NoTargetQueues { event_id },
+        |event_id| QueueDeliveryOutcome::CompleteFailure {
+            event_id,
+            error: "queue unavailable".to_string(),
+            persisted_to_dlq: true,
+        },
+        |event_id| QueueDeliveryOutcome::CancelledBySessionReset {
+            event_id,
+            session_epoch: 1,
+        },
+    ];
+    for outcome in outcomes {
+        let event = event();
+        admit(&outbox, &event, &["bot-a"]).await;
+        outbox.finish(&outcome(event.event_id));
+        assert!(outbox.get(&event.event_id).is_none());
+    }
+
+    let event = event();
+    admit(&outbox, &event, &["bot-a"]).await;
+    outbox.finish(&QueueDeliveryOutcome::BufferedForPausedSession {
+        event_id: event.event_id,
+    });
+    assert!(outbox.get(&event.event_id).is_some());
+    assert!(outbox.take_due(Timestamp::now()).is_empty());
+}
+
+/// An unreadable entry is renamed aside and the remaining entries load.
+#[tokio::test]
+async fn test_corrupt_entry_quarantined() {
+    let dir = tempfile::tempdir().unwrap();
+    let event = event();
+    let outbox = Outbox::open(&config(dir.path())).unwrap();
+    admit(&outbox, &event, &["bot-a"]).await;
+    drop(outbox);
+    let corrupt = dir.path().join("outbox").join("corrupt.json");
+    std::fs::write(&corrupt, b"{\"event\":").unwrap();
+
+    let reopened = Outbox::open(&config(dir.path())).unwrap();
+
+    assert_eq!(reopened.len(), 1);
+    assert!(reopened.get(&event.event_id).is_some());
+    assert!(!corrupt.exists());
+    assert!(dir
+        .path()
+        .join("outbox")
+        .join("corrupt.json.corrupt")
+        .exists());
+}
+
+/// Fields a routed bot encrypts are not written in plain text and are
+/// restored for redelivery.
+#[tokio::test]
+async fn test_encrypted_fields_not_stored_in_plain_text() {
+    let dir = tempfile::tempdir().unwrap();
+    let event = event_with_payload(serde_json::json!({ "secret": "hunter2" }));
+    let outbox = Outbox::open(&config(dir.path()))
+        .unwrap()
+        .with_field_encryption(field_encryptor());
+    let mut protected = subscription("bot-a", EventTypePattern::Wildcard("*".to_string()));
+    protected.encryption = Some(FieldEncryptionConfig {
+        key_secret: KEY_SECRET.to_string(),
+        fields: vec!["/secret".to_string()],
+    });
+
+    outbox.admit(&event, &[&protected]).await.unwrap();
+
+    let written =
+        std::fs::read_to_string(entry_path(&dir.path().join("outbox"), &event.event_id)).unwrap();
+    assert!(!written.contains("hunter2"));
+    let entry = outbox.get(&event.event_id).unwrap();
+    assert_ne!(entry.event.payload, event.payload);
+    assert_eq!(outbox.decrypt(&entry).await.unwrap().payload, event.payload);
+
+    // Without a key vault the event is not admitted
+    let plain = Outbox::open(&config(dir.path())).unwrap();
+    assert!(matches!(
+        plain.admit(&event, &[&protected]).await,
+        Err(OutboxError::Encrypt { .. })
+    ));
+}
+
+/// Redeliveries need a directory, a scan interval and usable delays.
+#[tokio::test]
+async fn test_validate() {
+    assert!(OutboxConfig::default().validate().is_ok());
+
+    for invalid in [
+        OutboxConfig {
+            enabled: true,
+            directory: PathBuf::new(),
+            ..OutboxConfig::default()
+        },
+        OutboxConfig {
+            dispatch_interval: Duration::ZERO,
+            ..OutboxConfig::default()
+        },
+        OutboxConfig {
+            retry_delay: Duration::from_millis(500),
+            ..OutboxConfig::default()
+        },
+        OutboxConfig {
+            max_retry_delay: Duration::from_secs(10),
+            ..OutboxConfig::default()
+        },
+    ] {
+        assert!(invalid.validate().is_err(), "{:?}", invalid);
+    }
+}
@@ -0,0 +1,337 @@
+//! Payload field statistics profiler.
+//!
+//! An opt-in profiler that samples processed payloads and records, per event
+//! type, how often each field is present, how large it is and which JSON
+//! types it takes. The statistics are served at `GET /api/stats/payloads`
+//! and help to:
+//!
+//! - design transformation allow-lists from the fields bots actually get;
+//! - notice payload growth or new fields after GitHub API changes.
+//!
+//! Field paths join object keys with `.`; array elements share one path
+//! ending in `[]` (e.g. `pull_request.labels[].name`). A field is counted
+//! once per sampled payload however many array elements contain it. Sizes
+//! are the length of the field's JSON serialization in bytes.
+//!
+//! Statistics are kept in memory per process and reset on restart. The
+//! number of event types and fields per event type is capped so unexpected
+//! payloads cannot grow memory without bound; paths beyond the cap are
+//! counted as untracked.
+
+use rand::RngExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::Mutex,
+};
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Profiler settings (the `payload_profiler` configuration section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// payload_profiler:
+///   enabled: true
+///   sample_rate: 0.01
+///   max_depth: 6
+///   max_fields_per_event_type: 1000
+///   max_event_types: 200
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PayloadProfilerConfig {
+    /// Whether payloads are sampled. Off by default.
+    pub enabled: bool,
+
+    /// Fraction of processed payloads that are profiled, in `(0.0, 1.0]`.
+    pub sample_rate: f64,
+
+    /// Nesting depth below which fields are not recorded separately.
+    pub max_depth: usize,
+
+    /// Distinct field paths tracked per event type.
+    pub max_fields_per_event_type: usize,
+
+    /// Distinct event types tracked.
+    pub max_event_types: usize,
+}
+
+impl Default for PayloadProfilerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_rate: 0.01,
+            max_depth: 6,
+            max_fields_per_event_type: 1000,
+            max_event_types: 200,
+        }
+    }
+}
+
+impl PayloadProfilerConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sample_rate.is_nan() || self.sample_rate <= 0.0 || self.sample_rate > 1.0 {
+            return Err(format!(
+                "payload_profiler.sample_rate must be in (0.0, 1.0] (got {})",
+                self.sample_rate
+            ));
+        }
+        if self.max_depth == 0 {
+            return Err("payload_profiler.max_depth must be at least 1".to_string());
+        }
+        if self.max_fields_per_event_type == 0 || self.max_event_types == 0 {
+            return Err(
+                "payload_profiler.max_fields_per_event_type and max_event_types must be at least 1"
+                    .to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Response Types
+// ============================================================================
+
+/// Response of `GET /api/stats/payloads`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayloadStatsResponse {
+    /// Whether the profiler is sampling payloads.
+    pub enabled: bool,
+
+    /// Fraction of payloads sampled.
+    pub sample_rate: f64,
+
+    /// Statistics per event type, by event type name.
+    pub event_types: Vec<EventTypePayloadStats>,
+}
+
+/// Field statistics of one event type.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventTypePayloadStats {
+    /// Event type.
+    pub event_type: String,
+
+    /// Payloads sampled.
+    pub samples: u64,
+
+    /// Mean size of a sampled payload in bytes.
+    pub mean_payload_bytes: u64,
+
+    /// Largest sampled payload in bytes.
+    pub max_payload_bytes: u64,
+
+    /// Field occurrences not tracked because the field cap was reached.
+    pub untracked_fields: u64,
+
+    /// Statistics per field path, by path.
+    pub fields: Vec<FieldPayloadStats>,
+}
+
+/// Statistics of one field path.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldPayloadStats {
+    /// Field path.
+    pub path: String,
+
+    /// Fraction of sampled payloads containing the field.
+    pub presence: f64,
+
+    /// Mean size in bytes over the payloads containing the field.
+    pub mean_bytes: u64,
+
+    /// Largest size in bytes seen in one payload.
+    pub max_bytes: u64,
+
+    /// JSON types seen (`string`, `number`, `object`, ...).
+    pub types: Vec<String>,
+}
+
+// ============================================================================
+// Profiler
+// ============================================================================
+
+#[derive(Default)]
+struct FieldProfile {
+    present: u64,
+    total_bytes: u64,
+    max_bytes: u64,
+    types: BTreeSet<&'static str>,
+}
+
+#[derive(Default)]
+struct EventTypeProfile {
+    samples: u64,
+    total_bytes: u64,
+    max_bytes: u64,
+    untracked_fields: u64,
+    fields: BTreeMap<String, FieldProfile>,
+}
+
+/// One field's occurrences within a single payload.
+#[derive(Default)]
+struct Occurrence {
+    bytes: u64,
+    types: BTreeSet<&'static str>,
+}
+
+/// Samples payloads and aggregates field statistics per event type.
+pub struct PayloadProfiler {
+    config: PayloadProfilerConfig,
+    profiles: Mutex<HashMap<String, EventTypeProfile>>,
+}
+
+impl PayloadProfiler {
+    /// Create a profiler with `config`.
+    pub fn new(config: PayloadProfilerConfig) -> Self {
+        Self {
+            config,
+            profiles: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Whether the next payload should be profiled.
+    pub fn should_sample(&self) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        self.config.sample_rate >= 1.0
+            || rand::rng().random_range(0.0..1.0) < self.config.sample_rate
+    }
+
+    /// Sample `payload` of `event_type` if it is selected for profiling.
+    pub fn observe(&self, event_type: &str, payload: &Value, payload_bytes: usize) {
+        if self.should_sample() {
+            self.record(event_type, payload, payload_bytes);
+        }
+    }
+
+    /// Record one payload of `event_type` of `payload_bytes` bytes.
+    pub fn record(&self, event_type: &str, payload: &Value, payload_bytes: usize) {
+        let mut occurrences = BTreeMap::new();
+        collect_fields(payload, "", 0, self.config.max_depth, &mut occurrences);
+
+        let mut profiles = self.profiles.lock().unwrap_or_else(|e| e.into_inner());
+        if !profiles.contains_key(event_type) && profiles.len() >= self.config.max_event_types {
+            return;
+        }
+        let profile = profiles.entry(event_type.to_string()).or_default();
+        profile.samples += 1;
+        profile.total_bytes += payload_bytes as u64;
+        profile.max_bytes = profile.max_bytes.max(payload_bytes as u64);
+
+        for (path, occurrence) in occurrences {
+            let tracked = profile.fields.len() < self.config.max_fields_per_event_type;
+            let field = match profile.fields.get_mut(&path) {
+                Some(field) => field,
+                None if tracked => profile.fields.entry(path).or_default(),
+                None => {
+                    profile.untracked_fields += 1;
+                    continue;
+                }
+            };
+            field.present += 1;
+            field.total_bytes += occurrence.bytes;
+            field.max_bytes = field.max_bytes.max(occurrence.bytes);
+            field.types.extend(occurrence.types);
+        }
+    }
+
+    /// Current statistics, sorted by event type and field path.
+    pub fn snapshot(&self) -> PayloadStatsResponse {
+        let profiles = self.profiles.lock().unwrap_or_else(|e| e.into_inner());
+        let mut event_types: Vec<EventTypePayloadStats> = profiles
+            .iter()
+            .map(|(event_type, profile)| EventTypePayloadStats {
+                event_type: event_type.clone(),
+                samples: profile.samples,
+                mean_payload_bytes: profile.total_bytes / profile.samples.max(1),
+                max_payload_bytes: profile.max_bytes,
+                untracked_fields: profile.untracked_fields,
+                fields: profile
+                    .fields
+                    .iter()
+                    .map(|(path, field)| FieldPayloadStats {
+                        path: path.clone(),
+                        presence: field.present as f64 / profile.samples.max(1) as f64,
+                        mean_bytes: field.total_bytes / field.present.max(1),
+                        max_bytes: field.max_bytes,
+                        types: field.types.iter().map(|t| t.to_string()).collect(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        event_types.sort_by(|a, b| a.event_type.cmp(&b.event_type));
+
+        PayloadStatsResponse {
+            enabled: self.config.enabled,
+            sample_rate: self.config.sample_rate,
+            event_types,
+        }
+    }
+}
+
+/// Add every field below `value` (at `prefix`) to `occurrences`.
+fn collect_fields(
+    value: &Value,
+    prefix: &str,
+    depth: usize,
+    max_depth: usize,
+    occurrences: &mut BTreeMap<String, Occurrence>,
+) {
+    if depth >= max_depth {
+        return;
+    }
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                let occurrence = occurrences.entry(path.clone()).or_default();
+                occurrence.bytes += serialized_len(child);
+                occurrence.types.insert(json_type(child));
+                collect_fields(child, &path, depth + 1, max_depth, occurrences);
+            }
+        }
+        Value::Array(items) => {
+            let path = format!("{}[]", prefix);
+            for item in items {
+                if item.is_object() {
+                    collect_fields(item, &path, depth, max_depth, occurrences);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn serialized_len(value: &Value) -> u64 {
+    serde_json::to_vec(value).map_or(0, |bytes| bytes.len() as u64)
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+#[path = "payload_profiler_tests.rs"]
+mod tests;
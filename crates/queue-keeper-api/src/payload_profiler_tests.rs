@@ -0,0 +1,170 @@
+//! Tests for the payload field statistics profiler.
+
+use super::*;
+use serde_json::json;
+
+fn profiler() -> PayloadProfiler {
+    PayloadProfiler::new(PayloadProfilerConfig {
+        enabled: true,
+        sample_rate: 1.0,
+        ..Default::default()
+    })
+}
+
+fn field<'a>(stats: &'a EventTypePayloadStats, path: &str) -> &'a FieldPayloadStats {
+    stats
+        .fields
+        .iter()
+        .find(|field| field.path == path)
+        .unwrap_or_else(|| panic!("no field '{}'", path))
+}
+
+// ============================================================================
+// Recording
+// ============================================================================
+
+/// Presence, sizes and types are aggregated per field path.
+#[test]
+fn test_records_presence_size_and_types() {
+    let profiler = profiler();
+    profiler.record(
+        "pull_request",
+        &json!({"action": "opened", "number": 1, "pull_request": {"title": "abc"}}),
+        100,
+    );
+    profiler.record(
+        "pull_request",
+        &json!({"action": "closed", "number": 2, "pull_request": {"title": null}}),
+        300,
+    );
+
+    let snapshot = profiler.snapshot();
+    let stats = &snapshot.event_types[0];
+    assert_eq!(stats.event_type, "pull_request");
+    assert_eq!(stats.samples, 2);
+    assert_eq!(stats.mean_payload_bytes, 200);
+    assert_eq!(stats.max_payload_bytes, 300);
+
+    let action = field(stats, "action");
+    assert_eq!(action.presence, 1.0);
+    assert_eq!(action.max_bytes, 8); // "opened" / "closed" with quotes
+    assert_eq!(action.types, vec!["string"]);
+
+    let title = field(stats, "pull_request.title");
+    assert_eq!(title.types, vec!["null", "string"]);
+    assert_eq!(field(stats, "pull_request").types, vec!["object"]);
+}
+
+/// Fields missing from some payloads have a presence below one.
+#[test]
+fn test_partial_presence() {
+    let profiler = profiler();
+    profiler.record("issues", &json!({"label": {"name": "bug"}}), 10);
+    profiler.record("issues", &json!({}), 10);
+    profiler.record("issues", &json!({}), 10);
+    profiler.record("issues", &json!({}), 10);
+
+    let snapshot = profiler.snapshot();
+    assert_eq!(field(&snapshot.event_types[0], "label.name").presence, 0.25);
+}
+
+/// Array elements share one path and count once per payload.
+#[test]
+fn test_array_elements_share_a_path() {
+    let profiler = profiler();
+    profiler.record(
+        "issues",
+        &json!({"labels": [{"name": "bug"}, {"name": "ui"}, {"color": "fff"}]}),
+        10,
+    );
+
+    let snapshot = profiler.snapshot();
+    let stats = &snapshot.event_types[0];
+    let name = field(stats, "labels[].name");
+    assert_eq!(name.presence, 1.0);
+    assert_eq!(name.max_bytes, 9); // "bug" + "ui" with quotes
+    assert_eq!(field(stats, "labels[].color").presence, 1.0);
+    assert_eq!(field(stats, "labels").types, vec!["array"]);
+}
+
+/// Fields below `max_depth` are not recorded separately.
+#[test]
+fn test_max_depth_limits_paths() {
+    let profiler = PayloadProfiler::new(PayloadProfilerConfig {
+        enabled: true,
+        sample_rate: 1.0,
+        max_depth: 2,
+        ..Default::default()
+    });
+    profiler.record("push", &json!({"a": {"b": {"c": 1}}}), 10);
+
+    let snapshot = profiler.snapshot();
+    let paths: Vec<&str> = snapshot.event_types[0]
+        .fields
+        .iter()
+        .map(|field| field.path.as_str())
+        .collect();
+    assert_eq!(paths, vec!["a", "a.b"]);
+}
+
+/// Paths beyond the field cap and event types beyond the type cap are not
+/// tracked.
+#[test]
+fn test_caps_bound_memory() {
+    let profiler = PayloadProfiler::new(PayloadProfilerConfig {
+        enabled: true,
+        sample_rate: 1.0,
+        max_fields_per_event_type: 2,
+        max_event_types: 1,
+        ..Default::default()
+    });
+    profiler.record("push", &json!({"a": 1, "b": 2, "c": 3}), 10);
+    profiler.record("issues", &json!({"a": 1}), 10);
+
+    let snapshot = profiler.snapshot();
+    assert_eq!(snapshot.event_types.len(), 1);
+    assert_eq!(snapshot.event_types[0].fields.len(), 2);
+    assert_eq!(snapshot.event_types[0].untracked_fields, 1);
+}
+
+// ============================================================================
+// Sampling
+// ============================================================================
+
+/// A disabled profiler samples nothing; a full sample rate samples
+/// everything.
+#[test]
+fn test_sampling() {
+    let disabled = PayloadProfiler::new(PayloadProfilerConfig::default());
+    disabled.observe("push", &json!({"a": 1}), 10);
+    assert!(disabled.snapshot().event_types.is_empty());
+    assert!(!disabled.snapshot().enabled);
+
+    let profiler = profiler();
+    for _ in 0..5 {
+        profiler.observe("push", &json!({"a": 1}), 10);
+    }
+    assert_eq!(profiler.snapshot().event_types[0].samples, 5);
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Sample rates outside (0, 1] and zero caps are rejected.
+#[test]
+fn test_config_validation() {
+    assert!(PayloadProfilerConfig::default().validate().is_ok());
+    for sample_rate in [0.0, -0.5, 1.5, f64::NAN] {
+        let config = PayloadProfilerConfig {
+            sample_rate,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err(), "{} accepted", sample_rate);
+    }
+    let config = PayloadProfilerConfig {
+        max_depth: 0,
+        ..Default::default()
+    };
+    assert!(config.validate().is_err());
+}
@@ -1,6 +1,7 @@
 //! Tests for [`ProviderId`] and [`ProviderRegistry`].
 
 use super::*;
+use crate::test_fixtures::event_of_type;
 use async_trait::async_trait;
 use queue_keeper_core::{
     webhook::{
@@ -23,7 +24,7 @@ impl WebhookProcessor for NoopWebhookProcessor {
         &self,
         _request: WebhookRequest,
     ) -> Result<ProcessingOutput, WebhookError> {
-        Ok(ProcessingOutput::Wrapped(test_wrapped_event()))
+        Ok(ProcessingOutput::Wrapped(event_of_type("ping")))
     }
 
     async fn validate_signature(
@@ -51,21 +52,10 @@ impl WebhookProcessor for NoopWebhookProcessor {
         &self,
         _request: &WebhookRequest,
     ) -> Result<WrappedEvent, NormalizationError> {
-        Ok(test_wrapped_event())
+        Ok(event_of_type("ping"))
     }
 }
 
-fn test_wrapped_event() -> WrappedEvent {
-    WrappedEvent::new(
-        "github".to_string(),
-        "ping".to_string(),
-        None,
-        None,
-        serde_json::json!({}),
-        None,
-    )
-}
-
 // ============================================================================
 // ProviderId tests
 // ============================================================================
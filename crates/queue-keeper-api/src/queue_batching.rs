@@ -0,0 +1,365 @@
+//! Batched sends to bot queues.
+//!
+//! Every delivery sends its message to each bot queue on its own, which
+//! costs one round trip per message when an organisation-wide push produces
+//! hundreds of webhooks per second. When `delivery.batching` is enabled the
+//! queue client is wrapped in a [`BatchingQueueClient`]:
+//!
+//! - Messages sent to the same queue are collected into a batch.
+//! - A batch is submitted with one `send_messages` call once it holds
+//!   `max_messages` messages or its first message has waited `max_delay`,
+//!   whichever comes first.
+//! - Each sender still receives the message ID of its own message, so the
+//!   delivery pipeline is unchanged.
+//! - When a batch send fails, its messages are sent one by one, so a single
+//!   rejected message does not fail the whole batch and every sender sees
+//!   the error of its own message.
+//!
+//! Batch sizes are exported as the `queue_batch_size_messages` histogram,
+//! and batches that failed and were split as `queue_batch_fallbacks_total`.
+//! Backends that do not support batching are not wrapped.
+
+use crate::metrics::ServiceMetrics;
+use async_trait::async_trait;
+use chrono::Duration as ChronoDuration;
+use queue_keeper_core::units::human_duration;
+use queue_runtime::{
+    Message, MessageId, ProviderType, QueueClient, QueueError, QueueName, ReceiptHandle,
+    ReceivedMessage, SessionClient, SessionId,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+use tracing::warn;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Queue send batching settings (the `delivery.batching` configuration
+/// section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// delivery:
+///   batching:
+///     enabled: true
+///     max_messages: 50
+///     max_delay: 20ms
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueueBatchingConfig {
+    /// Whether sends to a queue are batched. Off by default.
+    pub enabled: bool,
+
+    /// Messages after which a batch is submitted without waiting.
+    pub max_messages: usize,
+
+    /// Longest time a message waits for its batch to fill.
+    #[serde(with = "human_duration")]
+    pub max_delay: Duration,
+}
+
+impl Default for QueueBatchingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_messages: 10,
+            max_delay: Duration::from_millis(20),
+        }
+    }
+}
+
+impl QueueBatchingConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_messages == 0 {
+            return Err("batching.max_messages must be at least 1".to_string());
+        }
+        if self.max_delay.is_zero() {
+            return Err("batching.max_delay must not be zero".to_string());
+        }
+        if self.max_delay > Duration::from_secs(1) {
+            return Err("batching.max_delay must not exceed 1s".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Batching Queue Client
+// ============================================================================
+
+/// Reply channel of one message waiting in a batch.
+type Reply = oneshot::Sender<Result<MessageId, QueueError>>;
+
+/// Messages waiting to be sent to one queue.
+#[derive(Default)]
+struct PendingBatch {
+    messages: Vec<Message>,
+    replies: Vec<Reply>,
+    /// Incremented each time the batch is taken, so a timer started for an
+    /// earlier batch leaves the current one alone.
+    generation: u64,
+}
+
+struct Batcher {
+    inner: Arc<dyn QueueClient>,
+    max_messages: usize,
+    max_delay: Duration,
+    metrics: Option<Arc<ServiceMetrics>>,
+    /// Pending batch of each queue, keyed by queue name.
+    pending: Mutex<HashMap<String, PendingBatch>>,
+}
+
+impl Batcher {
+    /// Take the batch of `queue` if it is still the one of `generation`.
+    fn take(&self, queue: &QueueName, generation: u64) -> Option<(Vec<Message>, Vec<Reply>)> {
+        let mut pending = self.pending.lock().unwrap();
+        let batch = pending.get_mut(queue.as_str())?;
+        if batch.generation != generation || batch.messages.is_empty() {
+            return None;
+        }
+        batch.generation += 1;
+        Some((
+            std::mem::take(&mut batch.messages),
+            std::mem::take(&mut batch.replies),
+        ))
+    }
+
+    /// Submit a batch and answer each of its senders.
+    async fn submit(&self, queue: QueueName, messages: Vec<Message>, replies: Vec<Reply>) {
+        if let Some(metrics) = &self.metrics {
+            metrics
+                .queue_batch_size_messages
+                .observe(messages.len() as f64);
+        }
+
+        let count = messages.len();
+        let fallback = (count > 1).then(|| messages.clone());
+        let error = match self.inner.send_messages(&queue, messages).await {
+            Ok(ids) if ids.len() == count => {
+                for (reply, id) in replies.into_iter().zip(ids) {
+                    let _ = reply.send(Ok(id));
+                }
+                return;
+            }
+            Ok(ids) => QueueError::ProviderError {
+                provider: "Batching".to_string(),
+                code: "IncompleteBatch".to_string(),
+                message: format!(
+                    "{} messages sent, {} message IDs returned",
+                    count,
+                    ids.len()
+                ),
+            },
+            Err(error) => error,
+        };
+
+        let Some(messages) = fallback else {
+            if let Some(reply) = replies.into_iter().next() {
+                let _ = reply.send(Err(error));
+            }
+            return;
+        };
+
+        warn!(
+            queue = %queue.as_str(),
+            batch_size = count,
+            error = %error,
+            "Batch send failed; sending its messages one by one"
+        );
+        if let Some(metrics) = &self.metrics {
+            metrics.queue_batch_fallbacks_total.inc();
+        }
+        for (message, reply) in messages.into_iter().zip(replies) {
+            let _ = reply.send(self.inner.send_message(&queue, message).await);
+        }
+    }
+}
+
+/// [`QueueClient`] that collects the messages sent to each queue into
+/// batches.
+///
+/// Sending a message waits until its batch was submitted and returns the
+/// message's own ID or error. Messages keep their order within a batch;
+/// events of a session stay in order because each delivery waits for its
+/// send before the next one starts (see
+/// [`SessionSequencer`](queue_keeper_core::queue_integration::SessionSequencer)).
+/// All other operations are passed to the wrapped client.
+pub struct BatchingQueueClient {
+    batcher: Arc<Batcher>,
+}
+
+impl BatchingQueueClient {
+    /// Batch the sends of `inner` as configured in `config`. `metrics`, when
+    /// given, receives the batch sizes and fallbacks.
+    pub fn new(
+        inner: Arc<dyn QueueClient>,
+        config: &QueueBatchingConfig,
+        metrics: Option<Arc<ServiceMetrics>>,
+    ) -> Self {
+        Self {
+            batcher: Arc::new(Batcher {
+                inner,
+                max_messages: config.max_messages.max(1),
+                max_delay: config.max_delay,
+                metrics,
+                pending: Mutex::new(HashMap::new()),
+            }),
+        }
+    }
+
+    /// Wrap `inner` when batching is enabled and the backend supports it;
+    /// otherwise return `inner` unchanged.
+    pub fn wrap(
+        inner: Arc<dyn QueueClient>,
+        config: &QueueBatchingConfig,
+        metrics: Option<Arc<ServiceMetrics>>,
+    ) -> Arc<dyn QueueClient> {
+        if !config.enabled || !inner.supports_batching() {
+            return inner;
+        }
+        Arc::new(Self::new(inner, config, metrics))
+    }
+
+    /// Number of messages waiting for their batch to be submitted.
+    pub fn pending_messages(&self) -> usize {
+        self.batcher
+            .pending
+            .lock()
+            .unwrap()
+            .values()
+            .map(|batch| batch.messages.len())
+            .sum()
+    }
+}
+
+#[async_trait]
+impl QueueClient for BatchingQueueClient {
+    async fn send_message(
+        &self,
+        queue: &QueueName,
+        message: Message,
+    ) -> Result<MessageId, QueueError> {
+        let (reply, receiver) = oneshot::channel();
+        let (full, timer) = {
+            let mut pending = self.batcher.pending.lock().unwrap();
+            let batch = pending.entry(queue.as_str().to_string()).or_default();
+            batch.messages.push(message);
+            batch.replies.push(reply);
+            let generation = batch.generation;
+            let full = batch.messages.len() >= self.batcher.max_messages;
+            let timer = batch.messages.len() == 1 && !full;
+            (full.then_some(generation), timer.then_some(generation))
+        };
+
+        // Submitted from their own task, so a sender that stops waiting does
+        // not abandon the other messages of its batch.
+        if let Some(generation) = full {
+            if let Some((messages, replies)) = self.batcher.take(queue, generation) {
+                let batcher = Arc::clone(&self.batcher);
+                let queue = queue.clone();
+                tokio::spawn(async move { batcher.submit(queue, messages, replies).await });
+            }
+        }
+        if let Some(generation) = timer {
+            let batcher = Arc::clone(&self.batcher);
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(batcher.max_delay).await;
+                if let Some((messages, replies)) = batcher.take(&queue, generation) {
+                    batcher.submit(queue, messages, replies).await;
+                }
+            });
+        }
+
+        receiver.await.unwrap_or_else(|_| {
+            Err(QueueError::ProviderError {
+                provider: "Batching".to_string(),
+                code: "BatchAborted".to_string(),
+                message: "Batch was dropped before it was sent".to_string(),
+            })
+        })
+    }
+
+    async fn send_messages(
+        &self,
+        queue: &QueueName,
+        messages: Vec<Message>,
+    ) -> Result<Vec<MessageId>, QueueError> {
+        self.batcher.inner.send_messages(queue, messages).await
+    }
+
+    async fn receive_message(
+        &self,
+        queue: &QueueName,
+        timeout: ChronoDuration,
+    ) -> Result<Option<ReceivedMessage>, QueueError> {
+        self.batcher.inner.receive_message(queue, timeout).await
+    }
+
+    async fn receive_messages(
+        &self,
+        queue: &QueueName,
+        max_messages: u32,
+        timeout: ChronoDuration,
+    ) -> Result<Vec<ReceivedMessage>, QueueError> {
+        self.batcher
+            .inner
+            .receive_messages(queue, max_messages, timeout)
+            .await
+    }
+
+    async fn complete_message(&self, receipt: ReceiptHandle) -> Result<(), QueueError> {
+        self.batcher.inner.complete_message(receipt).await
+    }
+
+    async fn abandon_message(&self, receipt: ReceiptHandle) -> Result<(), QueueError> {
+        self.batcher.inner.abandon_message(receipt).await
+    }
+
+    async fn dead_letter_message(
+        &self,
+        receipt: ReceiptHandle,
+        reason: String,
+    ) -> Result<(), QueueError> {
+        self.batcher
+            .inner
+            .dead_letter_message(receipt, reason)
+            .await
+    }
+
+    async fn accept_session(
+        &self,
+        queue: &QueueName,
+        session_id: Option<SessionId>,
+    ) -> Result<Box<dyn SessionClient>, QueueError> {
+        self.batcher.inner.accept_session(queue, session_id).await
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        self.batcher.inner.provider_type()
+    }
+
+    fn supports_sessions(&self) -> bool {
+        self.batcher.inner.supports_sessions()
+    }
+
+    fn supports_batching(&self) -> bool {
+        self.batcher.inner.supports_batching()
+    }
+}
+
+#[cfg(test)]
+#[path = "queue_batching_tests.rs"]
+mod tests;
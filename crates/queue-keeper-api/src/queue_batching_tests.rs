@@ -0,0 +1,221 @@
+//! Tests for batched queue sends.
+
+use super::*;
+
+/// Queue client that records batch sends and rejects messages whose body is
+/// `bad`.
+#[derive(Default)]
+struct MockQueueClient {
+    batches: Mutex<Vec<Vec<String>>>,
+    single_sends: Mutex<Vec<String>>,
+    fail_batches: bool,
+}
+
+fn body(message: &Message) -> String {
+    String::from_utf8(message.body.to_vec()).unwrap()
+}
+
+fn message(body: &str) -> Message {
+    Message::new(body.as_bytes().to_vec().into())
+}
+
+fn queue() -> QueueName {
+    QueueName::new("queue-keeper-test-bot".to_string()).unwrap()
+}
+
+#[async_trait]
+impl QueueClient for MockQueueClient {
+    async fn send_message(
+        &self,
+        queue: &QueueName,
+        message: Message,
+    ) -> Result<MessageId, QueueError> {
+        if body(&message) == "bad" {
+            return Err(QueueError::QueueNotFound {
+                queue_name: queue.as_str().to_string(),
+            });
+        }
+        self.single_sends.lock().unwrap().push(body(&message));
+        Ok(MessageId::new())
+    }
+
+    async fn send_messages(
+        &self,
+        queue: &QueueName,
+        messages: Vec<Message>,
+    ) -> Result<Vec<MessageId>, QueueError> {
+        if self.fail_batches {
+            return Err(QueueError::QueueNotFound {
+                queue_name: queue.as_str().to_string(),
+            });
+        }
+        self.batches
+            .lock()
+            .unwrap()
+            .push(messages.iter().map(body).collect());
+        Ok(messages.iter().map(|_| MessageId::new()).collect())
+    }
+
+    async fn receive_message(
+        &self,
+        _queue: &QueueName,
+        _timeout: ChronoDuration,
+    ) -> Result<Option<ReceivedMessage>, QueueError> {
+        Ok(None)
+    }
+
+    async fn receive_messages(
+        &self,
+        _queue: &QueueName,
+        _max_messages: u32,
+        _timeout: ChronoDuration,
+    ) -> Result<Vec<ReceivedMessage>, QueueError> {
+        Ok(vec![])
+    }
+
+    async fn complete_message(&self, _receipt: ReceiptHandle) -> Result<(), QueueError> {
+        Ok(())
+    }
+
+    async fn abandon_message(&self, _receipt: ReceiptHandle) -> Result<(), QueueError> {
+        Ok(())
+    }
+
+    async fn dead_letter_message(
+        &self,
+        _receipt: ReceiptHandle,
+        _reason: String,
+    ) -> Result<(), QueueError> {
+        Ok(())
+    }
+
+    async fn accept_session(
+        &self,
+        _queue: &QueueName,
+        _session_id: Option<SessionId>,
+    ) -> Result<Box<dyn SessionClient>, QueueError> {
+        unimplemented!("Sessions not used by the batching tests")
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::InMemory
+    }
+
+    fn supports_sessions(&self) -> bool {
+        false
+    }
+
+    fn supports_batching(&self) -> bool {
+        true
+    }
+}
+
+fn batching(max_messages: usize, max_delay: Duration) -> QueueBatchingConfig {
+    QueueBatchingConfig {
+        enabled: true,
+        max_messages,
+        max_delay,
+    }
+}
+
+/// A full batch is submitted at once, in one send, in the order the
+/// messages were sent.
+#[tokio::test]
+async fn test_full_batch_submitted_without_waiting() {
+    let inner = Arc::new(MockQueueClient::default());
+    let metrics = Arc::new(ServiceMetrics::default());
+    let client = BatchingQueueClient::new(
+        inner.clone(),
+        &batching(3, Duration::from_secs(1)),
+        Some(metrics.clone()),
+    );
+    let queue = queue();
+
+    let sends = tokio::time::timeout(Duration::from_millis(500), async {
+        tokio::join!(
+            client.send_message(&queue, message("a")),
+            client.send_message(&queue, message("b")),
+            client.send_message(&queue, message("c")),
+        )
+    })
+    .await
+    .expect("A full batch must not wait for max_delay");
+
+    assert!(sends.0.is_ok() && sends.1.is_ok() && sends.2.is_ok());
+    assert_eq!(*inner.batches.lock().unwrap(), vec![vec!["a", "b", "c"]]);
+    assert_eq!(metrics.queue_batch_size_messages.get_sample_count(), 1);
+    assert_eq!(metrics.queue_batch_size_messages.get_sample_sum(), 3.0);
+    assert_eq!(client.pending_messages(), 0);
+}
+
+/// A batch that does not fill is submitted after `max_delay`.
+#[tokio::test]
+async fn test_partial_batch_submitted_after_delay() {
+    let inner = Arc::new(MockQueueClient::default());
+    let client = BatchingQueueClient::new(
+        inner.clone(),
+        &batching(10, Duration::from_millis(20)),
+        None,
+    );
+    let queue = queue();
+
+    let (first, second) = tokio::join!(
+        client.send_message(&queue, message("a")),
+        client.send_message(&queue, message("b")),
+    );
+
+    assert!(first.is_ok() && second.is_ok());
+    assert_eq!(*inner.batches.lock().unwrap(), vec![vec!["a", "b"]]);
+}
+
+/// A failed batch is sent message by message, so only the rejected message
+/// fails.
+#[tokio::test]
+async fn test_failed_batch_sent_one_by_one() {
+    let inner = Arc::new(MockQueueClient {
+        fail_batches: true,
+        ..Default::default()
+    });
+    let metrics = Arc::new(ServiceMetrics::default());
+    let client = BatchingQueueClient::new(
+        inner.clone(),
+        &batching(2, Duration::from_secs(1)),
+        Some(metrics.clone()),
+    );
+    let queue = queue();
+
+    let (good, bad) = tokio::join!(
+        client.send_message(&queue, message("good")),
+        client.send_message(&queue, message("bad")),
+    );
+
+    assert!(good.is_ok());
+    assert!(matches!(bad, Err(QueueError::QueueNotFound { .. })));
+    assert_eq!(*inner.single_sends.lock().unwrap(), vec!["good"]);
+    assert_eq!(metrics.queue_batch_fallbacks_total.get(), 1);
+}
+
+/// Batching is skipped when disabled.
+#[test]
+fn test_wrap_only_when_enabled() {
+    let inner: Arc<dyn QueueClient> = Arc::new(MockQueueClient::default());
+
+    let unchanged = BatchingQueueClient::wrap(inner.clone(), &QueueBatchingConfig::default(), None);
+    assert!(Arc::ptr_eq(&unchanged, &inner));
+
+    let wrapped = BatchingQueueClient::wrap(
+        inner.clone(),
+        &batching(10, Duration::from_millis(20)),
+        None,
+    );
+    assert!(!Arc::ptr_eq(&wrapped, &inner));
+}
+
+/// Batch sizes and delays are checked.
+#[test]
+fn test_config_validation() {
+    assert!(QueueBatchingConfig::default().validate().is_ok());
+    assert!(batching(0, Duration::from_millis(20)).validate().is_err());
+    assert!(batching(10, Duration::ZERO).validate().is_err());
+    assert!(batching(10, Duration::from_secs(5)).validate().is_err());
+}
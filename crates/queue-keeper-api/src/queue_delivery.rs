@@ -7,20 +7,40 @@
 //! path (queue delivery with retries), ensuring GitHub receives a response
 //! within the 10-second timeout.
 //!
+//! Failures are tracked per bot queue: a retry only re-sends the event to the
+//! queues whose delivery failed transiently, never to those that accepted it.
+//!
 //! See specs/interfaces/queue-client.md for queue operations specification.
 //! See specs/constraints.md for retry and performance requirements.
 
+use crate::bot_pause::BotPauseRegistry;
+use crate::delivery_audit::DeliveryAuditor;
+use crate::delivery_index::DeliveryIndex;
+use crate::developer::DeveloperTools;
 use crate::dlq_storage::{DlqReason, DlqStorageService, FailedEventRecord, FailedQueueInfo};
+use crate::escalation::FailingBots;
+use crate::event_stream::EventStream;
+use crate::metrics::ServiceMetrics;
+use crate::outbox::Outbox;
 use crate::retry::{RetryPolicy, RetryState};
+use crate::retry_budget::RetryBudget;
+use crate::session_epochs::{SessionDeliveryGuard, SessionEpochRegistry};
 use queue_keeper_core::{
     bot_config::BotConfiguration,
-    queue_integration::{DeliveryResult, EventRouter, FailedDelivery, SuccessfulDelivery},
+    event_error, event_info, event_warn,
+    logging::{LogContext, LogFields},
+    queue_integration::{
+        DeliveryResult, EventRouter, FailedDelivery, QueueDeliveryError, SequenceTicket,
+        SessionSequencer, SuccessfulDelivery,
+    },
+    session_control::SessionControlStore,
+    session_tracker::{SessionTracker, TrackedEventStatus},
     webhook::WrappedEvent,
-    EventId, Timestamp,
+    BotName, EventId, SessionId, Timestamp,
 };
 use queue_runtime::QueueClient;
-use std::sync::Arc;
-use tracing::{error, info, warn};
+use std::{future::Future, sync::Arc, time::Duration};
+use tracing::{info, info_span, Instrument, Span};
 
 // ============================================================================
 // Queue Delivery Configuration
@@ -47,6 +67,84 @@ pub struct QueueDeliveryConfig {
     /// When `None`, failed events that exhaust retries are logged but not
     /// persisted. `enable_dlq` must also be `true` for persistence to occur.
     pub dlq_service: Option<Arc<DlqStorageService>>,
+
+    /// Global cap on retries, shared by every delivery worker.
+    ///
+    /// Disabled unless `delivery.retry_budget.enabled` is set; shared by
+    /// every clone like `session_epochs`.
+    pub retry_budget: Arc<RetryBudget>,
+
+    /// Session epochs used to cancel pending deliveries on session reset.
+    ///
+    /// Shared by every clone of this configuration, so a reset issued through
+    /// the admin API reaches deliveries spawned by the webhook handler.
+    pub session_epochs: Arc<SessionEpochRegistry>,
+
+    /// Sessions paused by the admin API.
+    ///
+    /// Events of a paused session are buffered instead of being routed, and
+    /// released in order by [`release_session_events`]; shared by every
+    /// clone like `session_epochs`.
+    pub session_control: Arc<SessionControlStore>,
+
+    /// Send order of the events of each session.
+    ///
+    /// A delivery takes its ticket when the event is accepted and sends only
+    /// after the earlier deliveries of its session finished, so concurrent
+    /// deliveries cannot reorder a session; shared by every clone like
+    /// `session_epochs`.
+    pub session_sequencer: Arc<SessionSequencer>,
+
+    /// Index correlating provider delivery IDs with queue message IDs.
+    ///
+    /// Every routing attempt records the message IDs the bot queues
+    /// returned; shared by every clone like `session_epochs`.
+    pub delivery_index: Arc<DeliveryIndex>,
+
+    /// Event counts, activity and status of each session.
+    ///
+    /// Every delivery records its event when accepted and its outcome when
+    /// finished, for the `/api/sessions` routes; shared by every clone like
+    /// `session_epochs`.
+    pub session_tracker: Arc<SessionTracker>,
+
+    /// Compliance records of the messages bot queues accepted.
+    ///
+    /// Disabled unless `delivery_audit.enabled` is set; shared by every
+    /// clone like `session_epochs`.
+    pub delivery_audit: Arc<DeliveryAuditor>,
+
+    /// Bots whose deliveries are held by the admin API.
+    ///
+    /// Events matching a paused bot are parked in its hold queue instead of
+    /// being routed to it; shared by every clone like `session_epochs`.
+    pub bot_pauses: Arc<BotPauseRegistry>,
+
+    /// Bots whose deliveries keep failing, for age-based escalation.
+    ///
+    /// Every routing attempt records its per-bot outcome; shared by every
+    /// clone like `session_epochs`.
+    pub failing_bots: Arc<FailingBots>,
+
+    /// Prometheus metrics fed with the per-queue outcome of every routing
+    /// attempt; `None` when the host exports no metrics.
+    pub metrics: Option<Arc<ServiceMetrics>>,
+
+    /// Recent events and envelope dumps; only set in developer mode.
+    ///
+    /// Shared by every clone like `session_epochs`.
+    pub dev_tools: Option<Arc<DeveloperTools>>,
+
+    /// Broadcaster of event summaries to `GET /api/events/stream`, told the
+    /// per-bot outcome of every routing attempt; `None` when the stream is
+    /// disabled.
+    pub event_stream: Option<Arc<EventStream>>,
+
+    /// Durable record of the accepted events still to be delivered.
+    ///
+    /// Every routing attempt removes the bots it settled and every delivery
+    /// settles the entry of its event; `None` unless `outbox.enabled` is set.
+    pub outbox: Option<Arc<Outbox>>,
 }
 
 impl QueueDeliveryConfig {
@@ -91,6 +189,16 @@ pub enum QueueDeliveryOutcome {
         /// Indicates if the event was persisted to DLQ
         persisted_to_dlq: bool,
     },
+
+    /// Delivery was abandoned because the event's session was reset
+    CancelledBySessionReset {
+        event_id: EventId,
+        /// Session epoch the event was stamped with before the reset
+        session_epoch: u64,
+    },
+
+    /// The event's session is paused; the event is buffered until it resumes
+    BufferedForPausedSession { event_id: EventId },
 }
 
 impl QueueDeliveryOutcome {
@@ -111,6 +219,148 @@ impl QueueDeliveryOutcome {
                 | QueueDeliveryOutcome::CompleteFailure { .. }
         )
     }
+
+    /// How the outcome is recorded by the session tracker
+    pub fn tracked_status(&self) -> TrackedEventStatus {
+        match self {
+            QueueDeliveryOutcome::AllQueuesSucceeded { .. }
+            | QueueDeliveryOutcome::NoTargetQueues { .. } => TrackedEventStatus::Delivered,
+            QueueDeliveryOutcome::SomeQueuesFailed { .. }
+            | QueueDeliveryOutcome::CompleteFailure { .. } => TrackedEventStatus::Failed,
+            QueueDeliveryOutcome::CancelledBySessionReset { .. } => TrackedEventStatus::Cancelled,
+            QueueDeliveryOutcome::BufferedForPausedSession { .. } => TrackedEventStatus::Pending,
+        }
+    }
+}
+
+// ============================================================================
+// Delivery Attempt Tracing
+// ============================================================================
+
+/// Outcome of a single delivery attempt, recorded on its tracing span.
+///
+/// Each pass through the retry loop produces one `delivery_attempt` span.
+/// The span is linked (`follows_from`) to the webhook intake span and to the
+/// previous attempt, so a trace shows the complete retry history of an event
+/// even though the intake request has long since completed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryAttemptOutcome {
+    /// Every target queue accepted the event.
+    Delivered,
+
+    /// No bot subscription matched the event.
+    NoTargets,
+
+    /// At least one transient failure occurred and another attempt is scheduled.
+    RetryScheduled,
+
+    /// The retry budget was exhausted, so the next attempt was deferred.
+    RetryDeferred,
+
+    /// The attempt failed and no further attempts will be made.
+    Failed,
+
+    /// The session was reset while a retry was pending.
+    Cancelled,
+}
+
+impl DeliveryAttemptOutcome {
+    /// Stable label used as the `outcome` span field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Delivered => "delivered",
+            Self::NoTargets => "no_targets",
+            Self::RetryScheduled => "retry_scheduled",
+            Self::RetryDeferred => "retry_deferred",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Create the span for one delivery attempt.
+///
+/// The span records the attempt number and the comma-separated list of target
+/// queues; `outcome`, `failed_queues` and `retry_delay_ms` are recorded once
+/// the attempt completes. Causality links point at the intake span and, for
+/// retries, at the previous attempt.
+fn delivery_attempt_span(
+    event: &WrappedEvent,
+    attempt: u32,
+    target_queues: &str,
+    intake_span: &Span,
+    previous_attempt: Option<&Span>,
+) -> Span {
+    let span = info_span!(
+        "delivery_attempt",
+        event_id = %event.event_id,
+        correlation_id = %event.correlation_id,
+        attempt = attempt,
+        target_queues = %target_queues,
+        outcome = tracing::field::Empty,
+        failed_queues = tracing::field::Empty,
+        retry_delay_ms = tracing::field::Empty,
+    );
+    span.follows_from(intake_span);
+    if let Some(previous) = previous_attempt {
+        span.follows_from(previous);
+    }
+    span
+}
+
+/// Sleep for the retry delay, returning early if the session is reset.
+///
+/// Returns `false` when the delivery was cancelled by a session reset.
+async fn wait_for_retry(delay: Duration, session_guard: Option<&mut SessionDeliveryGuard>) -> bool {
+    match session_guard {
+        Some(guard) => {
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => !guard.is_cancelled(),
+                _ = guard.cancelled() => false,
+            }
+        }
+        None => {
+            tokio::time::sleep(delay).await;
+            true
+        }
+    }
+}
+
+/// Wait until the retry budget allows `retries` more sends, deferring by
+/// the budget's deferral each time it does not.
+///
+/// Returns `false` when the delivery was cancelled by a session reset.
+async fn wait_for_retry_budget(
+    event: &WrappedEvent,
+    retry_budget: &RetryBudget,
+    retries: usize,
+    attempt_span: &Span,
+    mut session_guard: Option<&mut SessionDeliveryGuard>,
+) -> bool {
+    while !retry_budget.try_acquire(retries) {
+        let deferral = retry_budget.deferral();
+        event_warn!(
+            event,
+            retries = retries,
+            deferral_ms = deferral.as_millis(),
+            "Retry budget exhausted; retry deferred"
+        );
+        attempt_span.record("outcome", DeliveryAttemptOutcome::RetryDeferred.as_str());
+        if !wait_for_retry(deferral, session_guard.as_deref_mut()).await {
+            return false;
+        }
+    }
+    true
+}
+
+/// Comma-separated list of the queues an event is expected to reach.
+fn target_queue_label(event: &WrappedEvent, bot_config: &BotConfiguration) -> String {
+    bot_config
+        .get_target_bots(event)
+        .iter()
+        .map(|bot| bot.queue.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
 // ============================================================================
@@ -123,9 +373,20 @@ impl QueueDeliveryOutcome {
 /// after the immediate HTTP response. It handles:
 ///
 /// 1. Initial delivery attempt to all target queues
-/// 2. Retry logic with exponential backoff for transient failures
+/// 2. Retry logic with exponential backoff for transient failures, deferred
+///    while the global retry budget is exhausted (see [`crate::retry_budget`])
 /// 3. Partial failure handling (retry only failed queues)
 /// 4. DLQ persistence for permanent failures or exhausted retries
+/// 5. Cancellation when the event's session is reset (see
+///    [`crate::session_epochs`]); the event is stamped with the session's
+///    current epoch before the first attempt
+/// 6. Holding the event for paused bots (see [`crate::bot_pause`]); it is
+///    routed to the remaining target bots only
+/// 7. Waiting for the earlier events of its session to finish delivery (see
+///    [`SessionSequencer`]); the event is stamped with its session sequence
+/// 8. Buffering the event while its session is paused (see
+///    [`queue_keeper_core::session_control`]); it is delivered by
+///    [`release_session_events`] when the session resumes
 ///
 /// # Arguments
 ///
@@ -168,32 +429,274 @@ pub async fn deliver_event_to_queues(
     delivery_config: QueueDeliveryConfig,
 ) -> QueueDeliveryOutcome {
     let event_id = event.event_id;
+    let session_id = event.session_id.clone();
+    let session_tracker = delivery_config.session_tracker.clone();
+    let (event, sequence_ticket) = take_sequence_ticket(event, &delivery_config);
+    session_tracker.record_received(&event);
+
+    if let Some(ticket) = &sequence_ticket {
+        ticket.turn().await;
+    }
+    let outcome = deliver_event_with_intake_span(
+        event,
+        event_router,
+        bot_config,
+        queue_client,
+        delivery_config,
+        Span::current(),
+    )
+    .await;
+    drop(sequence_ticket);
+
+    record_session_outcome(&session_tracker, session_id.as_ref(), event_id, &outcome);
+    outcome
+}
+
+/// Deliver the events buffered while `session_id` was paused, oldest first.
+///
+/// Called after [`SessionControlStore::resume`]. Each event is delivered
+/// before the next is taken, and events arriving meanwhile are buffered
+/// behind them, so the session keeps its order; the session is unpaused once
+/// its buffer is empty. Stops early if the session is paused again. The
+/// session tracker counted the events when they were buffered, so only their
+/// outcomes are recorded.
+///
+/// Returns the number of events released.
+pub async fn release_session_events(
+    session_id: SessionId,
+    event_router: Arc<dyn EventRouter>,
+    bot_config: Arc<BotConfiguration>,
+    queue_client: Arc<dyn QueueClient>,
+    delivery_config: QueueDeliveryConfig,
+) -> usize {
+    let store = delivery_config.session_control.clone();
+    let session_tracker = delivery_config.session_tracker.clone();
+
+    // The session stays in the store while resuming so new events queue up
+    // behind the buffered ones; the released events themselves must bypass it.
+    let release_config = QueueDeliveryConfig {
+        session_control: Arc::new(SessionControlStore::new()),
+        ..delivery_config
+    };
+
+    let mut released = 0;
+    while let Some(event) = store.next_buffered(&session_id) {
+        let event_id = event.event_id;
+        let outcome = deliver_event_with_intake_span(
+            event,
+            event_router.clone(),
+            bot_config.clone(),
+            queue_client.clone(),
+            release_config.clone(),
+            Span::current(),
+        )
+        .await;
+        record_session_outcome(&session_tracker, Some(&session_id), event_id, &outcome);
+        released += 1;
+    }
+
+    info!(
+        session_id = %session_id,
+        released,
+        resumed = !store.is_paused(&session_id),
+        "Released buffered session events"
+    );
+    released
+}
+
+/// Take the event's place in its session's send order and stamp it with its
+/// sequence number.
+///
+/// Events without a session are not sequenced.
+fn take_sequence_ticket(
+    event: WrappedEvent,
+    delivery_config: &QueueDeliveryConfig,
+) -> (WrappedEvent, Option<SequenceTicket>) {
+    let Some(session_id) = &event.session_id else {
+        return (event, None);
+    };
+    let ticket = delivery_config.session_sequencer.ticket(session_id);
+    (event.with_session_sequence(ticket.sequence()), Some(ticket))
+}
+
+/// Record the outcome of delivering event `event_id` with the session
+/// tracker, for events that belong to a session.
+fn record_session_outcome(
+    session_tracker: &SessionTracker,
+    session_id: Option<&SessionId>,
+    event_id: EventId,
+    outcome: &QueueDeliveryOutcome,
+) {
+    if let Some(session_id) = session_id {
+        session_tracker.record_outcome(session_id, event_id, outcome.tracked_status());
+    }
+}
+
+/// Delivery loop shared by [`deliver_event_to_queues`] and
+/// [`spawn_queue_delivery`].
+///
+/// `intake_span` is the span that was current when the webhook was accepted;
+/// every attempt span links back to it. The outcome settles the event's
+/// outbox entry (see [`crate::outbox`]).
+async fn deliver_event_with_intake_span(
+    event: WrappedEvent,
+    event_router: Arc<dyn EventRouter>,
+    bot_config: Arc<BotConfiguration>,
+    queue_client: Arc<dyn QueueClient>,
+    delivery_config: QueueDeliveryConfig,
+    intake_span: Span,
+) -> QueueDeliveryOutcome {
+    let outbox = delivery_config.outbox.clone();
+    let outcome = deliver_with_retries(
+        event,
+        event_router,
+        bot_config,
+        queue_client,
+        delivery_config,
+        intake_span,
+    )
+    .await;
+    if let Some(outbox) = outbox {
+        outbox.finish(&outcome);
+    }
+    outcome
+}
+
+/// Route `event` until it is delivered, fails permanently or is cancelled.
+async fn deliver_with_retries(
+    event: WrappedEvent,
+    event_router: Arc<dyn EventRouter>,
+    bot_config: Arc<BotConfiguration>,
+    queue_client: Arc<dyn QueueClient>,
+    delivery_config: QueueDeliveryConfig,
+    intake_span: Span,
+) -> QueueDeliveryOutcome {
+    let event_id = event.event_id;
+    let Some(event) = delivery_config.session_control.buffer(event) else {
+        info!(
+            event_id = %event_id,
+            "Queue delivery buffered until the paused session resumes"
+        );
+        return QueueDeliveryOutcome::BufferedForPausedSession { event_id };
+    };
+    let mut bot_config = {
+        let routed = delivery_config
+            .bot_pauses
+            .hold_for_paused_bots(&event, bot_config.clone());
+        // Held copies are delivered from the hold queue, not the outbox
+        if let Some(outbox) = &delivery_config.outbox {
+            if !Arc::ptr_eq(&routed, &bot_config) {
+                outbox.hand_off(event_id, &held_bots(&event, &bot_config, &routed));
+            }
+        }
+        routed
+    };
     let mut retry_state = RetryState::new();
+    // Outcomes of earlier attempts that are not retried
+    let mut delivered: Vec<SuccessfulDelivery> = Vec::new();
+    let mut permanent_failures: Vec<FailedDelivery> = Vec::new();
     let first_attempt_at = Timestamp::now();
+    let target_queues = target_queue_label(&event, &bot_config);
+    let mut previous_attempt: Option<Span> = None;
+
+    let mut session_guard = event
+        .session_id
+        .as_ref()
+        .map(|session_id| delivery_config.session_epochs.track(session_id));
+    let event = match &session_guard {
+        Some(guard) => event.with_session_epoch(guard.epoch()),
+        None => event,
+    };
 
     loop {
-        // Attempt delivery to all target queues
-        match event_router
+        let attempt_span = delivery_attempt_span(
+            &event,
+            retry_state.total_attempts,
+            &target_queues,
+            &intake_span,
+            previous_attempt.as_ref(),
+        );
+
+        // Attempt delivery to all target queues; a session reset abandons
+        // the sends still in flight
+        let route = event_router
             .route_event(&event, &bot_config, queue_client.as_ref())
-            .await
-        {
+            .instrument(attempt_span.clone());
+        let routed = match session_guard.as_mut() {
+            Some(guard) => {
+                tokio::select! {
+                    biased;
+                    _ = guard.cancelled() => {
+                        attempt_span.record("outcome", DeliveryAttemptOutcome::Cancelled.as_str());
+                        return cancelled_by_session_reset(&event);
+                    }
+                    routed = route => routed,
+                }
+            }
+            None => route.await,
+        };
+        let routed = routed.or_else(|error| match error {
+            // Failed bots are retried and reported one by one
+            QueueDeliveryError::PartialDelivery { result, .. } => Ok(result),
+            QueueDeliveryError::CompleteFailure { failures } => Ok(DeliveryResult {
+                event_id,
+                successful: Vec::new(),
+                failed: failures,
+            }),
+            error => Err(error),
+        });
+        if let Ok(result) = &routed {
+            delivery_config
+                .delivery_index
+                .record_messages(&event, &result.successful);
+            if let Some(outbox) = &delivery_config.outbox {
+                outbox.record_result(event_id, result);
+            }
+            delivery_config
+                .delivery_audit
+                .record(&event, &result.successful)
+                .await;
+            delivery_config.failing_bots.record_result(result);
+            if let Some(metrics) = &delivery_config.metrics {
+                metrics.record_delivery_result(&event, result);
+            }
+            if let Some(stream) = &delivery_config.event_stream {
+                stream.publish_delivery(&event, result);
+            }
+            delivery_config
+                .retry_budget
+                .record_sends(result.successful.len());
+        }
+
+        // A retry only re-sends to the bots that failed transiently; report
+        // the event's deliveries across all attempts.
+        let routed = routed.map(|mut result| {
+            result.successful.splice(0..0, delivered.drain(..));
+            result.failed.splice(0..0, permanent_failures.drain(..));
+            result
+        });
+        if let (Some(dev_tools), Ok(result)) = (&delivery_config.dev_tools, &routed) {
+            dev_tools.record_delivery(&event, result);
+        }
+
+        match routed {
             Ok(result) if result.is_no_op() => {
                 // No target queues matched (must check before is_complete_success
                 // because is_complete_success is also true when no targets)
-                info!(
-                    event_id = %event_id,
-                    "No target queues matched for event"
-                );
+                attempt_span.record("outcome", DeliveryAttemptOutcome::NoTargets.as_str());
+                event_info!(event, "No target queues matched for event");
 
                 return QueueDeliveryOutcome::NoTargetQueues { event_id };
             }
 
             Ok(result) if result.is_complete_success() => {
                 // All deliveries succeeded
-                info!(
-                    event_id = %event_id,
+                attempt_span.record("outcome", DeliveryAttemptOutcome::Delivered.as_str());
+                event_info!(
+                    event,
                     successful_count = result.successful.len(),
                     total_attempts = retry_state.total_attempts,
+                    latency_ms = event.age().as_millis(),
                     "Event delivered to all target queues"
                 );
 
@@ -207,6 +710,7 @@ pub async fn deliver_event_to_queues(
                 // Partial or complete failure - check if we should retry
                 let transient_failures: Vec<_> =
                     result.failed.iter().filter(|f| f.is_transient).collect();
+                attempt_span.record("failed_queues", result.failed.len());
 
                 if !transient_failures.is_empty()
                     && retry_state.can_retry(&delivery_config.retry_policy)
@@ -214,21 +718,44 @@ pub async fn deliver_event_to_queues(
                     // Retry transient failures with backoff
                     let delay = retry_state.get_delay(&delivery_config.retry_policy);
 
-                    warn!(
-                        event_id = %event_id,
+                    event_warn!(
+                        event,
                         transient_failures = transient_failures.len(),
                         permanent_failures = result.failed.len() - transient_failures.len(),
                         attempt = retry_state.total_attempts,
                         delay_ms = delay.as_millis(),
                         "Retrying transient queue delivery failures"
                     );
+                    attempt_span.record("outcome", DeliveryAttemptOutcome::RetryScheduled.as_str());
+                    attempt_span.record("retry_delay_ms", delay.as_millis() as u64);
 
-                    tokio::time::sleep(delay).await;
+                    if !wait_for_retry(delay, session_guard.as_mut()).await
+                        || !wait_for_retry_budget(
+                            &event,
+                            &delivery_config.retry_budget,
+                            transient_failures.len(),
+                            &attempt_span,
+                            session_guard.as_mut(),
+                        )
+                        .await
+                    {
+                        attempt_span.record("outcome", DeliveryAttemptOutcome::Cancelled.as_str());
+                        return cancelled_by_session_reset(&event);
+                    }
+                    bot_config = retry_targets(&bot_config, &transient_failures);
+                    delivered = result.successful;
+                    permanent_failures = result
+                        .failed
+                        .into_iter()
+                        .filter(|f| !f.is_transient)
+                        .collect();
                     retry_state.next_attempt();
+                    previous_attempt = Some(attempt_span);
                     continue;
                 }
 
                 // Max retries exceeded or all failures are permanent
+                attempt_span.record("outcome", DeliveryAttemptOutcome::Failed.as_str());
                 return handle_final_delivery_result(
                     &event,
                     result,
@@ -244,22 +771,39 @@ pub async fn deliver_event_to_queues(
                 if error.is_transient() && retry_state.can_retry(&delivery_config.retry_policy) {
                     let delay = retry_state.get_delay(&delivery_config.retry_policy);
 
-                    warn!(
-                        event_id = %event_id,
+                    event_warn!(
+                        event,
                         error = %error,
                         attempt = retry_state.total_attempts,
                         delay_ms = delay.as_millis(),
                         "Retrying after routing error"
                     );
+                    attempt_span.record("outcome", DeliveryAttemptOutcome::RetryScheduled.as_str());
+                    attempt_span.record("retry_delay_ms", delay.as_millis() as u64);
 
-                    tokio::time::sleep(delay).await;
+                    if !wait_for_retry(delay, session_guard.as_mut()).await
+                        || !wait_for_retry_budget(
+                            &event,
+                            &delivery_config.retry_budget,
+                            1,
+                            &attempt_span,
+                            session_guard.as_mut(),
+                        )
+                        .await
+                    {
+                        attempt_span.record("outcome", DeliveryAttemptOutcome::Cancelled.as_str());
+                        return cancelled_by_session_reset(&event);
+                    }
                     retry_state.next_attempt();
+                    previous_attempt = Some(attempt_span);
                     continue;
                 }
 
+                attempt_span.record("outcome", DeliveryAttemptOutcome::Failed.as_str());
+
                 // Permanent error or max retries exceeded
-                error!(
-                    event_id = %event_id,
+                event_error!(
+                    event,
                     error = %error,
                     total_attempts = retry_state.total_attempts,
                     "Queue delivery failed permanently"
@@ -285,6 +829,50 @@ pub async fn deliver_event_to_queues(
     }
 }
 
+/// Narrow `bot_config` to the bots of `failures`, so a retry does not send
+/// the event again to queues that already accepted it.
+fn retry_targets(
+    bot_config: &BotConfiguration,
+    failures: &[&FailedDelivery],
+) -> Arc<BotConfiguration> {
+    Arc::new(BotConfiguration {
+        bots: bot_config
+            .bots
+            .iter()
+            .filter(|bot| failures.iter().any(|f| f.bot_name == bot.name))
+            .cloned()
+            .collect(),
+        settings: bot_config.settings.clone(),
+    })
+}
+
+/// Bots of `bot_config` the event was held for: those `routed` leaves out.
+fn held_bots(
+    event: &WrappedEvent,
+    bot_config: &BotConfiguration,
+    routed: &BotConfiguration,
+) -> Vec<BotName> {
+    bot_config
+        .get_target_bots(event)
+        .into_iter()
+        .filter(|bot| !routed.bots.iter().any(|r| r.name == bot.name))
+        .map(|bot| bot.name.clone())
+        .collect()
+}
+
+/// Build the outcome for a delivery abandoned by a session reset.
+fn cancelled_by_session_reset(event: &WrappedEvent) -> QueueDeliveryOutcome {
+    event_info!(
+        event,
+        session_epoch = event.session_epoch,
+        "Queue delivery cancelled by session reset"
+    );
+    QueueDeliveryOutcome::CancelledBySessionReset {
+        event_id: event.event_id,
+        session_epoch: event.session_epoch,
+    }
+}
+
 /// Handle the final delivery result after retries are exhausted
 ///
 /// Processes remaining failures and optionally persists to DLQ.
@@ -308,10 +896,11 @@ async fn handle_final_delivery_result(
 
     // Log each failure
     for failure in &result.failed {
-        error!(
-            event_id = %event_id,
-            bot_name = %failure.bot_name,
-            queue_name = %failure.queue_name,
+        event_error!(
+            event
+                .log_context()
+                .with_bot(&failure.bot_name)
+                .with_queue(&failure.queue_name),
             error = %failure.error,
             is_transient = failure.is_transient,
             "Queue delivery failed for bot"
@@ -330,8 +919,8 @@ async fn handle_final_delivery_result(
     .await;
 
     if successful_count > 0 {
-        warn!(
-            event_id = %event_id,
+        event_warn!(
+            event,
             successful_count = successful_count,
             failed_count = failed_count,
             total_attempts = total_attempts,
@@ -346,8 +935,8 @@ async fn handle_final_delivery_result(
             persisted_to_dlq,
         }
     } else {
-        error!(
-            event_id = %event_id,
+        event_error!(
+            event,
             failed_count = failed_count,
             total_attempts = total_attempts,
             persisted_to_dlq = persisted_to_dlq,
@@ -380,8 +969,8 @@ async fn persist_delivery_failures_to_dlq(
     let dlq_service = match &delivery_config.dlq_service {
         Some(service) => service,
         None => {
-            warn!(
-                event_id = %event.event_id,
+            event_warn!(
+                event,
                 failed_count = failed.len(),
                 "DLQ service not configured - failed events not persisted"
             );
@@ -430,8 +1019,8 @@ async fn persist_delivery_failures_to_dlq(
     // Persist to DLQ
     match dlq_service.persist_failed_event(&record).await {
         Ok(blob_path) => {
-            info!(
-                event_id = %event.event_id,
+            event_info!(
+                event,
                 blob_path = %blob_path,
                 failed_count = failed.len(),
                 "Failed event persisted to DLQ"
@@ -439,8 +1028,8 @@ async fn persist_delivery_failures_to_dlq(
             true
         }
         Err(e) => {
-            error!(
-                event_id = %event.event_id,
+            event_error!(
+                event,
                 error = %e,
                 "Failed to persist event to DLQ - event may be lost"
             );
@@ -466,8 +1055,8 @@ async fn persist_routing_error_to_dlq(
     let dlq_service = match &delivery_config.dlq_service {
         Some(service) => service,
         None => {
-            warn!(
-                event_id = %event.event_id,
+            event_warn!(
+                event,
                 error = %error,
                 "DLQ service not configured - routing error not persisted"
             );
@@ -490,16 +1079,16 @@ async fn persist_routing_error_to_dlq(
 
     match dlq_service.persist_failed_event(&record).await {
         Ok(blob_path) => {
-            info!(
-                event_id = %event.event_id,
+            event_info!(
+                event,
                 blob_path = %blob_path,
                 "Routing error persisted to DLQ"
             );
             true
         }
         Err(e) => {
-            error!(
-                event_id = %event.event_id,
+            event_error!(
+                event,
                 error = %e,
                 "Failed to persist routing error to DLQ"
             );
@@ -533,7 +1122,7 @@ async fn persist_routing_error_to_dlq(
 /// let handle = spawn_queue_delivery(
 ///     event_envelope.clone(),
 ///     state.event_router.clone(),
-///     state.bot_config.clone(),
+///     state.bot_config.current(),
 ///     state.queue_client.clone(),
 ///     QueueDeliveryConfig::default(),
 /// );
@@ -548,62 +1137,112 @@ pub fn spawn_queue_delivery(
     queue_client: Arc<dyn QueueClient>,
     delivery_config: QueueDeliveryConfig,
 ) -> tokio::task::JoinHandle<QueueDeliveryOutcome> {
+    tokio::spawn(queue_delivery_task(
+        event,
+        event_router,
+        bot_config,
+        queue_client,
+        delivery_config,
+    ))
+}
+
+/// Build the background delivery task for `event` without spawning it.
+///
+/// Used by [`spawn_queue_delivery`] and by the
+/// [`SessionShardDispatcher`](crate::session_shards::SessionShardDispatcher),
+/// which runs the tasks of a session one after another on its shard. Must be
+/// called while the webhook handler's span is current: the returned task's
+/// span links back to it.
+pub fn queue_delivery_task(
+    event: WrappedEvent,
+    event_router: Arc<dyn EventRouter>,
+    bot_config: Arc<BotConfiguration>,
+    queue_client: Arc<dyn QueueClient>,
+    delivery_config: QueueDeliveryConfig,
+) -> impl Future<Output = QueueDeliveryOutcome> + Send + 'static {
     let event_id = event.event_id;
 
-    tokio::spawn(async move {
-        info!(
-            event_id = %event_id,
-            "Starting async queue delivery"
-        );
+    // Capture the intake span synchronously, while the webhook handler's span
+    // is still current. The task outlives the request, so it links to the
+    // intake span rather than being parented by it.
+    let intake_span = Span::current();
+    let delivery_span = info_span!("queue_delivery", event_id = %event_id);
+    delivery_span.follows_from(&intake_span);
+
+    let log_context = event.log_context();
 
-        let outcome = deliver_event_to_queues(
+    // Recorded when the event is accepted, so events queued on a session
+    // shard count as pending. The sequence ticket is taken at the same time,
+    // so the session's sends follow the order the events were accepted in.
+    let session_id = event.session_id.clone();
+    let session_tracker = delivery_config.session_tracker.clone();
+    let (event, sequence_ticket) = take_sequence_ticket(event, &delivery_config);
+    session_tracker.record_received(&event);
+
+    let task = async move {
+        event_info!(log_context, "Starting async queue delivery");
+
+        if let Some(ticket) = &sequence_ticket {
+            ticket.turn().await;
+        }
+        let outcome = deliver_event_with_intake_span(
             event,
             event_router,
             bot_config,
             queue_client,
             delivery_config,
+            intake_span,
         )
         .await;
+        drop(sequence_ticket);
 
-        match &outcome {
-            QueueDeliveryOutcome::AllQueuesSucceeded {
-                successful_count, ..
-            } => {
-                info!(
-                    event_id = %event_id,
-                    successful_count = successful_count,
-                    "Async queue delivery completed successfully"
-                );
-            }
-            QueueDeliveryOutcome::NoTargetQueues { .. } => {
-                info!(
-                    event_id = %event_id,
-                    "Async queue delivery completed (no targets)"
-                );
-            }
-            QueueDeliveryOutcome::SomeQueuesFailed {
-                successful_count,
-                failed_count,
-                ..
-            } => {
-                warn!(
-                    event_id = %event_id,
-                    successful_count = successful_count,
-                    failed_count = failed_count,
-                    "Async queue delivery completed with partial failures"
-                );
-            }
-            QueueDeliveryOutcome::CompleteFailure { error, .. } => {
-                error!(
-                    event_id = %event_id,
-                    error = error,
-                    "Async queue delivery failed completely"
-                );
-            }
-        }
-
+        log_delivery_outcome(&log_context, &outcome);
+        record_session_outcome(&session_tracker, session_id.as_ref(), event_id, &outcome);
         outcome
-    })
+    };
+
+    task.instrument(delivery_span)
+}
+
+/// Log the final outcome of a background delivery.
+fn log_delivery_outcome(log_context: &LogContext, outcome: &QueueDeliveryOutcome) {
+    match outcome {
+        QueueDeliveryOutcome::AllQueuesSucceeded {
+            successful_count, ..
+        } => {
+            event_info!(
+                log_context,
+                successful_count = successful_count,
+                "Async queue delivery completed successfully"
+            );
+        }
+        QueueDeliveryOutcome::NoTargetQueues { .. } => {
+            event_info!(log_context, "Async queue delivery completed (no targets)");
+        }
+        QueueDeliveryOutcome::SomeQueuesFailed {
+            successful_count,
+            failed_count,
+            ..
+        } => {
+            event_warn!(
+                log_context,
+                successful_count = successful_count,
+                failed_count = failed_count,
+                "Async queue delivery completed with partial failures"
+            );
+        }
+        QueueDeliveryOutcome::CompleteFailure { error, .. } => {
+            event_error!(
+                log_context,
+                error = error,
+                "Async queue delivery failed completely"
+            );
+        }
+        QueueDeliveryOutcome::CancelledBySessionReset { .. }
+        | QueueDeliveryOutcome::BufferedForPausedSession { .. } => {
+            // Already logged by the delivery loop
+        }
+    }
 }
 
 #[cfg(test)]
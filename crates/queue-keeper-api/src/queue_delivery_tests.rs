@@ -1,6 +1,7 @@
 //! Tests for queue delivery module
 
 use super::*;
+use crate::test_fixtures::event;
 
 // ============================================================================
 // QueueDeliveryConfig Tests
@@ -185,6 +186,19 @@ fn test_config_custom_retry_policy() {
         retry_policy: custom_policy.clone(),
         enable_dlq: true,
         dlq_service: None,
+        session_epochs: Default::default(),
+        session_sequencer: Default::default(),
+        session_control: Default::default(),
+        delivery_index: Default::default(),
+        session_tracker: Default::default(),
+        delivery_audit: Default::default(),
+        bot_pauses: Default::default(),
+        failing_bots: Default::default(),
+        metrics: None,
+        dev_tools: None,
+        event_stream: None,
+        outbox: None,
+        retry_budget: Default::default(),
     };
 
     assert_eq!(config.retry_policy.max_attempts, 3);
@@ -201,12 +215,51 @@ fn test_config_without_dlq() {
         retry_policy: RetryPolicy::default(),
         enable_dlq: false,
         dlq_service: None,
+        session_epochs: Default::default(),
+        session_sequencer: Default::default(),
+        session_control: Default::default(),
+        delivery_index: Default::default(),
+        session_tracker: Default::default(),
+        delivery_audit: Default::default(),
+        bot_pauses: Default::default(),
+        failing_bots: Default::default(),
+        metrics: None,
+        dev_tools: None,
+        event_stream: None,
+        outbox: None,
+        retry_budget: Default::default(),
     };
 
     assert!(!config.enable_dlq);
     assert!(config.dlq_service.is_none());
 }
 
+/// Verify that a delivery cancelled by session reset is neither a success nor
+/// a failure.
+#[test]
+fn test_delivery_outcome_cancelled_by_session_reset() {
+    let outcome = QueueDeliveryOutcome::CancelledBySessionReset {
+        event_id: queue_keeper_core::EventId::new(),
+        session_epoch: 0,
+    };
+
+    assert!(!outcome.is_success());
+    assert!(!outcome.has_failures());
+}
+
+/// Verify that waiting for a retry returns early when the session is reset.
+#[tokio::test]
+async fn test_wait_for_retry_cancelled_by_session_reset() {
+    let registry = Arc::new(crate::session_epochs::SessionEpochRegistry::new());
+    let session = queue_keeper_core::SessionId::from_parts("owner", "repo", "issue", "1");
+    let mut guard = registry.track(&session);
+    registry.reset(&session);
+
+    let completed = wait_for_retry(std::time::Duration::from_secs(60), Some(&mut guard)).await;
+
+    assert!(!completed);
+}
+
 // ============================================================================
 // Outcome Pattern Matching Tests
 // ============================================================================
@@ -354,3 +407,212 @@ fn test_config_builder_pattern() {
     // with_dlq_service does not automatically enable DLQ; caller must set enable_dlq explicitly
     assert!(!config.enable_dlq);
 }
+
+// ============================================================================
+// DeliveryAttemptOutcome Tests
+// ============================================================================
+
+/// Verify that every attempt outcome maps to a distinct, stable span label.
+#[test]
+fn test_delivery_attempt_outcome_labels() {
+    assert_eq!(DeliveryAttemptOutcome::Delivered.as_str(), "delivered");
+    assert_eq!(DeliveryAttemptOutcome::NoTargets.as_str(), "no_targets");
+    assert_eq!(
+        DeliveryAttemptOutcome::RetryScheduled.as_str(),
+        "retry_scheduled"
+    );
+    assert_eq!(
+        DeliveryAttemptOutcome::RetryDeferred.as_str(),
+        "retry_deferred"
+    );
+    assert_eq!(DeliveryAttemptOutcome::Failed.as_str(), "failed");
+    assert_eq!(DeliveryAttemptOutcome::Cancelled.as_str(), "cancelled");
+}
+
+/// Verify that the target queue label lists nothing when no bot matches.
+#[test]
+fn test_target_queue_label_empty_without_subscriptions() {
+    let event = event();
+    let bot_config = BotConfiguration {
+        bots: vec![],
+        settings: Default::default(),
+    };
+
+    assert_eq!(target_queue_label(&event, &bot_config), "");
+}
+
+// ============================================================================
+// Delivery Attempt Span Tests
+// ============================================================================
+
+mod attempt_span_tests {
+    use super::*;
+    use crate::test_fixtures::{bot_config, delivered, failed};
+    use queue_keeper_core::queue_integration::DeliveryResult;
+    use queue_runtime::QueueClientFactory;
+    use std::{
+        collections::{HashMap, VecDeque},
+        sync::Mutex,
+        time::Duration,
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Subscriber,
+    };
+    use tracing_subscriber::{layer::Context, prelude::*, Layer};
+
+    /// A span seen by [`CaptureLayer`], with its fields rendered as text.
+    #[derive(Debug, Clone, Default)]
+    struct CapturedSpan {
+        name: &'static str,
+        fields: HashMap<String, String>,
+        follows_from: Vec<u64>,
+    }
+
+    impl Visit for CapturedSpan {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.fields
+                .insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.fields
+                .insert(field.name().to_string(), format!("{:?}", value));
+        }
+    }
+
+    /// Layer recording every span, its fields and its causality links.
+    #[derive(Clone, Default)]
+    struct CaptureLayer {
+        spans: Arc<Mutex<Vec<(u64, CapturedSpan)>>>,
+    }
+
+    impl CaptureLayer {
+        fn spans_named(&self, name: &str) -> Vec<(u64, CapturedSpan)> {
+            self.spans
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|(_, span)| span.name == name)
+                .cloned()
+                .collect()
+        }
+
+        fn with_span(&self, id: &Id, update: impl FnOnce(&mut CapturedSpan)) {
+            let mut spans = self.spans.lock().unwrap();
+            if let Some((_, span)) = spans.iter_mut().find(|(i, _)| *i == id.into_u64()) {
+                update(span);
+            }
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for CaptureLayer {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _ctx: Context<'_, S>) {
+            let mut span = CapturedSpan {
+                name: attrs.metadata().name(),
+                ..CapturedSpan::default()
+            };
+            attrs.record(&mut span);
+            self.spans.lock().unwrap().push((id.into_u64(), span));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, _ctx: Context<'_, S>) {
+            self.with_span(id, |span| values.record(span));
+        }
+
+        fn on_follows_from(&self, id: &Id, follows: &Id, _ctx: Context<'_, S>) {
+            self.with_span(id, |span| span.follows_from.push(follows.into_u64()));
+        }
+    }
+
+    /// Router answering each attempt with the next scripted result.
+    struct ScriptedRouter {
+        results: Mutex<VecDeque<DeliveryResult>>,
+    }
+
+    #[async_trait::async_trait]
+    impl EventRouter for ScriptedRouter {
+        async fn route_event(
+            &self,
+            _event: &WrappedEvent,
+            _config: &BotConfiguration,
+            _queue_client: &dyn QueueClient,
+        ) -> Result<DeliveryResult, QueueDeliveryError> {
+            Ok(self
+                .results
+                .lock()
+                .unwrap()
+                .pop_front()
+                .expect("unscripted delivery attempt"))
+        }
+    }
+
+    /// Verify that each delivery attempt gets one span, named and populated
+    /// with its outcome, that follows from the intake span and, for a retry,
+    /// from the previous attempt.
+    #[tokio::test]
+    async fn test_attempt_spans_follow_intake_span() {
+        let capture = CaptureLayer::default();
+        let _default = tracing_subscriber::registry()
+            .with(capture.clone())
+            .set_default();
+        let event = event();
+        let event_id = event.event_id;
+        let bots = bot_config(&["bot-a"]);
+        let target_queues = target_queue_label(&event, &bots);
+        let router = Arc::new(ScriptedRouter {
+            results: Mutex::new(VecDeque::from([
+                DeliveryResult {
+                    event_id,
+                    successful: Vec::new(),
+                    failed: vec![failed("bot-a", true)],
+                },
+                DeliveryResult {
+                    event_id,
+                    successful: vec![delivered("bot-a")],
+                    failed: Vec::new(),
+                },
+            ])),
+        });
+        let config = QueueDeliveryConfig {
+            retry_policy: RetryPolicy {
+                initial_delay: Duration::from_millis(1),
+                max_delay: Duration::from_millis(1),
+                use_jitter: false,
+                ..RetryPolicy::default()
+            },
+            ..QueueDeliveryConfig::default()
+        };
+        let intake = tracing::info_span!("webhook_intake");
+
+        let outcome = deliver_event_to_queues(
+            event,
+            router,
+            bots,
+            Arc::from(QueueClientFactory::create_test_client()),
+            config,
+        )
+        .instrument(intake)
+        .await;
+
+        assert!(outcome.is_success(), "{:?}", outcome);
+        let intake_id = capture.spans_named("webhook_intake")[0].0;
+        let attempts = capture.spans_named("delivery_attempt");
+        assert_eq!(attempts.len(), 2);
+        let (first_id, first) = &attempts[0];
+        let (_, second) = &attempts[1];
+        for (attempt, span) in [("1", first), ("2", second)] {
+            assert_eq!(span.fields["event_id"], event_id.to_string());
+            assert_eq!(span.fields["attempt"], attempt);
+            assert_eq!(span.fields["target_queues"], target_queues);
+            assert_eq!(span.follows_from[0], intake_id);
+        }
+        assert_eq!(first.fields["outcome"], "retry_scheduled");
+        assert_eq!(first.fields["failed_queues"], "1");
+        assert_eq!(first.fields["retry_delay_ms"], "1");
+        assert_eq!(first.follows_from, vec![intake_id]);
+        assert_eq!(second.fields["outcome"], "delivered");
+        assert_eq!(second.follows_from, vec![intake_id, *first_id]);
+    }
+}
@@ -0,0 +1,161 @@
+//! Sampling of bot queue depths.
+//!
+//! The queue runtime has no depth query, so the service hands the HTTP layer
+//! a provider-specific [`QueueDepthSource`]. When the `queue_depth` section
+//! enables sampling, the [`QueueDepthSampler`] asks it for the depth of every
+//! queue in the bot configuration each `sample_interval` and sets
+//! `queue_depth_messages{queue_name, bot_name}`. Queues the source cannot
+//! read count towards `queue_depth_sample_failures_total{queue_name}` and
+//! keep their last sampled depth.
+//!
+//! The bot configuration is re-read on every sweep, so bots added by a
+//! reload are sampled from the next sweep on.
+
+use crate::metrics::ServiceMetrics;
+use async_trait::async_trait;
+use queue_keeper_core::{bot_config::SharedBotConfiguration, units::human_duration};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Queue depth sampling settings (the `queue_depth` configuration section).
+///
+/// ```yaml
+/// queue_depth:
+///   enabled: true
+///   sample_interval: 1m
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QueueDepthConfig {
+    /// Whether queue depths are sampled. Off by default: every sample is a
+    /// request to the queue provider.
+    pub enabled: bool,
+
+    /// Time between two sweeps over the bot queues.
+    #[serde(with = "human_duration")]
+    pub sample_interval: Duration,
+}
+
+impl Default for QueueDepthConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            sample_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl QueueDepthConfig {
+    /// Validate the settings.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.sample_interval < Duration::from_secs(1) {
+            return Err("queue_depth.sample_interval must be at least 1s".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Sources
+// ============================================================================
+
+/// Reads the number of messages waiting in a queue from the queue provider.
+#[async_trait]
+pub trait QueueDepthSource: Send + Sync {
+    /// Approximate number of messages waiting in `queue_name`.
+    ///
+    /// # Errors
+    /// Returns a description of why the provider could not report the depth.
+    async fn queue_depth(&self, queue_name: &str) -> Result<u64, String>;
+}
+
+// ============================================================================
+// Sampler
+// ============================================================================
+
+/// Periodically records the depth of every bot queue.
+pub struct QueueDepthSampler {
+    config: QueueDepthConfig,
+    source: Arc<dyn QueueDepthSource>,
+    bot_config: Arc<SharedBotConfiguration>,
+}
+
+impl std::fmt::Debug for QueueDepthSampler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueueDepthSampler")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl QueueDepthSampler {
+    pub fn new(
+        config: QueueDepthConfig,
+        source: Arc<dyn QueueDepthSource>,
+        bot_config: Arc<SharedBotConfiguration>,
+    ) -> Self {
+        Self {
+            config,
+            source,
+            bot_config,
+        }
+    }
+
+    /// Sample every bot queue once and record the depths.
+    ///
+    /// Each queue is read once even when several bots share it. Returns the
+    /// depths that were read, by queue name.
+    pub async fn sample(&self, metrics: &ServiceMetrics) -> HashMap<String, u64> {
+        let bots = self.bot_config.current();
+        let mut depths = HashMap::new();
+        let mut failed = Vec::new();
+        for bot in &bots.bots {
+            let queue = bot.queue.as_str();
+            if !depths.contains_key(queue) && !failed.contains(&queue) {
+                match self.source.queue_depth(queue).await {
+                    Ok(depth) => {
+                        depths.insert(queue.to_string(), depth);
+                    }
+                    Err(error) => {
+                        warn!(queue = %queue, error = %error, "Failed to sample queue depth");
+                        metrics.record_queue_depth_sample_failure(queue);
+                        failed.push(queue);
+                    }
+                }
+            }
+            if let Some(depth) = depths.get(queue) {
+                metrics.record_bot_queue_depth(queue, bot.name.as_str(), *depth);
+            }
+        }
+        debug!(
+            sampled = depths.len(),
+            failed = failed.len(),
+            "Sampled bot queue depths"
+        );
+        depths
+    }
+
+    /// Run [`sample`](Self::sample) every `sample_interval`, forever.
+    pub async fn run(&self, metrics: &ServiceMetrics) {
+        let mut ticker = tokio::time::interval(self.config.sample_interval);
+        loop {
+            ticker.tick().await;
+            self.sample(metrics).await;
+        }
+    }
+
+    /// Spawn [`run`](Self::run) until the returned task is aborted.
+    pub fn start(self: Arc<Self>, metrics: Arc<ServiceMetrics>) -> JoinHandle<()> {
+        tokio::spawn(async move { self.run(&metrics).await })
+    }
+}
+
+#[cfg(test)]
+#[path = "queue_depth_tests.rs"]
+mod tests;
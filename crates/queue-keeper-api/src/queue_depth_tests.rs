@@ -0,0 +1,130 @@
+//! Tests for bot queue depth sampling.
+
+use super::*;
+use queue_keeper_core::{
+    bot_config::{BotConfigurationSettings, BotSpecificConfig, BotSubscription, EventTypePattern},
+    BotConfiguration, BotName, QueueName,
+};
+use std::sync::Mutex;
+
+/// Source reporting fixed depths and recording the queues it was asked for.
+#[derive(Default)]
+struct FixedDepths {
+    depths: HashMap<String, u64>,
+    requests: Mutex<Vec<String>>,
+}
+
+#[async_trait]
+impl QueueDepthSource for FixedDepths {
+    async fn queue_depth(&self, queue_name: &str) -> Result<u64, String> {
+        self.requests.lock().unwrap().push(queue_name.to_string());
+        self.depths
+            .get(queue_name)
+            .copied()
+            .ok_or_else(|| format!("queue {} does not exist", queue_name))
+    }
+}
+
+fn subscription(name: &str, queue: &str) -> BotSubscription {
+    BotSubscription {
+        name: BotName::new(name).unwrap(),
+        queue: QueueName::new(queue).unwrap(),
+        events: vec![EventTypePattern::Wildcard("*".to_string())],
+        ordered: false,
+        repository_filter: None,
+        config: BotSpecificConfig::new(),
+        encryption: None,
+        transform: None,
+        projection: None,
+        http: None,
+        sender_filter: None,
+    }
+}
+
+fn bot_config(bots: Vec<BotSubscription>) -> Arc<SharedBotConfiguration> {
+    Arc::new(SharedBotConfiguration::new(BotConfiguration {
+        bots,
+        settings: BotConfigurationSettings::default(),
+    }))
+}
+
+/// A queue shared by two bots is read once and reported for both.
+#[tokio::test]
+async fn test_sample_records_depth_per_bot() {
+    let source = Arc::new(FixedDepths {
+        depths: HashMap::from([
+            ("queue-keeper-triage".to_string(), 7),
+            ("queue-keeper-release".to_string(), 0),
+        ]),
+        ..FixedDepths::default()
+    });
+    let sampler = QueueDepthSampler::new(
+        QueueDepthConfig::default(),
+        source.clone(),
+        bot_config(vec![
+            subscription("labeler", "queue-keeper-triage"),
+            subscription("assigner", "queue-keeper-triage"),
+            subscription("releaser", "queue-keeper-release"),
+        ]),
+    );
+    let metrics = ServiceMetrics::default();
+
+    let depths = sampler.sample(&metrics).await;
+
+    assert_eq!(depths.get("queue-keeper-triage"), Some(&7));
+    assert_eq!(source.requests.lock().unwrap().len(), 2);
+    for (queue, bot, depth) in [
+        ("queue-keeper-triage", "labeler", 7),
+        ("queue-keeper-triage", "assigner", 7),
+        ("queue-keeper-release", "releaser", 0),
+    ] {
+        let gauge = metrics
+            .queue_depth_messages
+            .with_label_values(&[queue, bot]);
+        assert_eq!(gauge.get(), depth, "{}/{}", queue, bot);
+    }
+}
+
+/// A queue the source cannot read is counted as a failure and asked for
+/// only once per sweep.
+#[tokio::test]
+async fn test_sample_counts_failed_queues() {
+    let source = Arc::new(FixedDepths::default());
+    let sampler = QueueDepthSampler::new(
+        QueueDepthConfig::default(),
+        source.clone(),
+        bot_config(vec![
+            subscription("labeler", "queue-keeper-gone"),
+            subscription("assigner", "queue-keeper-gone"),
+        ]),
+    );
+    let metrics = ServiceMetrics::default();
+
+    assert!(sampler.sample(&metrics).await.is_empty());
+    assert_eq!(source.requests.lock().unwrap().len(), 1);
+    assert_eq!(
+        metrics
+            .queue_depth_sample_failures_total
+            .with_label_values(&["queue-keeper-gone"])
+            .get(),
+        1
+    );
+}
+
+/// The interval is read as a human duration and must be at least a second.
+#[test]
+fn test_config_parses_and_validates_interval() {
+    let config: QueueDepthConfig = serde_json::from_value(serde_json::json!({
+        "enabled": true,
+        "sample_interval": "30s",
+    }))
+    .unwrap();
+    assert_eq!(config.sample_interval, Duration::from_secs(30));
+    assert!(config.validate().is_ok());
+
+    let config = QueueDepthConfig {
+        sample_interval: Duration::from_millis(100),
+        ..QueueDepthConfig::default()
+    };
+    assert!(config.validate().is_err());
+}
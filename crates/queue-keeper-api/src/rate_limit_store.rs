@@ -0,0 +1,358 @@
+//! Rate limiter state backends.
+//!
+//! [`ip_rate_limit_middleware`](crate::middleware::ip_rate_limit_middleware)
+//! reads and updates per-IP escalation state through the [`RateLimiterStore`]
+//! trait:
+//!
+//! - [`IpFailureTracker`] keeps the state in process memory (the default).
+//!   Each replica counts only the failures it sees itself.
+//! - `RedisRateLimiterStore` (cargo feature `redis-rate-limiter`) keeps the
+//!   state in Redis so that every replica behind a load balancer shares the
+//!   same failure counts and restrictions.
+//!
+//! Both backends apply the same three-tier escalation rules; see
+//! [`IpFailureTracker`] and [`IpTier`].
+//!
+//! Store errors never reject requests: the middleware logs them and lets the
+//! request through, so an unavailable Redis degrades to no IP rate limiting
+//! rather than to an outage.
+
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::config::{RateLimiterStoreConfig, SecurityConfig};
+use crate::errors::ConfigError;
+use crate::middleware::{IpFailureTracker, IpTier};
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors returned by a [`RateLimiterStore`].
+#[derive(Debug, thiserror::Error)]
+pub enum RateLimiterStoreError {
+    /// The backend could not be reached or rejected the operation.
+    #[error("Rate limiter store unavailable: {message}")]
+    Unavailable { message: String },
+
+    /// The backend returned state this limiter did not write.
+    #[error("Rate limiter store returned invalid state for '{key}': {message}")]
+    InvalidState { key: String, message: String },
+}
+
+// ============================================================================
+// Store Trait
+// ============================================================================
+
+/// Storage for per-IP authentication failure counts and escalation tiers.
+#[async_trait]
+pub trait RateLimiterStore: Send + Sync {
+    /// Return the current tier for `ip`, expiring timed tiers whose penalty
+    /// has elapsed.
+    async fn check_tier(&self, ip: &str) -> Result<IpTier, RateLimiterStoreError>;
+
+    /// Record one authentication failure for `ip` and return its tier after
+    /// escalation.
+    async fn record_failure(&self, ip: &str) -> Result<IpTier, RateLimiterStoreError>;
+}
+
+#[async_trait]
+impl RateLimiterStore for IpFailureTracker {
+    async fn check_tier(&self, ip: &str) -> Result<IpTier, RateLimiterStoreError> {
+        Ok(IpFailureTracker::check_tier(self, ip))
+    }
+
+    async fn record_failure(&self, ip: &str) -> Result<IpTier, RateLimiterStoreError> {
+        Ok(IpFailureTracker::record_failure(self, ip))
+    }
+}
+
+/// Build the IP rate limiter configured in `security`.
+///
+/// Returns `None` when IP rate limiting is disabled.
+///
+/// # Errors
+///
+/// - [`ConfigError::ProviderNotCompiled`] when the Redis backend is selected
+///   but the `redis-rate-limiter` feature is disabled.
+/// - [`ConfigError::Invalid`] when the Redis client cannot be created.
+pub async fn build_rate_limiter_store(
+    security: &SecurityConfig,
+) -> Result<Option<Arc<dyn RateLimiterStore>>, ConfigError> {
+    if !security.enable_ip_rate_limiting {
+        return Ok(None);
+    }
+
+    let policy = EscalationPolicy::from_config(security);
+    match &security.rate_limiter_store {
+        RateLimiterStoreConfig::InMemory => Ok(Some(Arc::new(IpFailureTracker::new(
+            policy.rate_restrict_threshold,
+            policy.block_threshold,
+            policy.window,
+            policy.rate_restrict_duration,
+            policy.block_duration,
+        )))),
+        #[cfg(feature = "redis-rate-limiter")]
+        RateLimiterStoreConfig::Redis {
+            url,
+            key_prefix,
+            window,
+        } => {
+            let store = redis_store::RedisRateLimiterStore::connect(
+                url,
+                key_prefix.clone(),
+                *window,
+                policy,
+            )
+            .await
+            .map_err(|e| ConfigError::Invalid {
+                message: format!("security.rate_limiter_store: {}", e),
+            })?;
+            Ok(Some(Arc::new(store)))
+        }
+        #[cfg(not(feature = "redis-rate-limiter"))]
+        other @ RateLimiterStoreConfig::Redis { .. } => Err(ConfigError::ProviderNotCompiled {
+            component: "Rate limiter store".to_string(),
+            provider: other.backend_name().to_string(),
+            feature: "redis-rate-limiter".to_string(),
+        }),
+    }
+}
+
+/// Thresholds and durations of the three-tier escalation, taken from
+/// [`SecurityConfig`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EscalationPolicy {
+    /// Failure count that triggers [`IpTier::RateRestricted`].
+    pub rate_restrict_threshold: usize,
+    /// Failure count above which an IP is [`IpTier::Blocked`].
+    pub block_threshold: usize,
+    /// Failure counting window.
+    pub window: Duration,
+    /// How long an IP stays rate-restricted.
+    pub rate_restrict_duration: Duration,
+    /// How long an IP stays blocked.
+    pub block_duration: Duration,
+}
+
+impl EscalationPolicy {
+    /// Read the escalation settings from `security`.
+    pub fn from_config(security: &SecurityConfig) -> Self {
+        Self {
+            rate_restrict_threshold: security.auth_failure_threshold,
+            block_threshold: security.auth_block_threshold,
+            window: Duration::from_secs(security.auth_failure_window_secs),
+            rate_restrict_duration: Duration::from_secs(security.auth_rate_restrict_duration_secs),
+            block_duration: Duration::from_secs(security.auth_block_duration_secs),
+        }
+    }
+}
+
+// ============================================================================
+// Redis Store
+// ============================================================================
+
+#[cfg(feature = "redis-rate-limiter")]
+pub use redis_store::RedisRateLimiterStore;
+
+#[cfg(feature = "redis-rate-limiter")]
+mod redis_store {
+    use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+    use async_trait::async_trait;
+    use redis::aio::ConnectionManager;
+
+    use super::{EscalationPolicy, RateLimiterStore, RateLimiterStoreError};
+    use crate::config::RateLimitWindow;
+    use crate::middleware::IpTier;
+
+    const TIER_RATE_RESTRICTED: &str = "rate_restricted";
+    const TIER_BLOCKED: &str = "blocked";
+
+    /// Redis-backed [`RateLimiterStore`] shared by all replicas.
+    ///
+    /// Keys, all under `key_prefix`:
+    ///
+    /// | Key | Type | Contents |
+    /// |-----|------|----------|
+    /// | `{prefix}:tier:{ip}` | string with TTL | `rate_restricted` or `blocked`; expires with the penalty |
+    /// | `{prefix}:failures:{ip}` | sorted set | one member per failure, scored by time (sliding window) |
+    /// | `{prefix}:failures:{ip}:{n}` | counter with TTL | failures in aligned window `n` (fixed window) |
+    ///
+    /// Failure counts are updated in a single `MULTI` transaction; tier
+    /// escalation follows the same rules as [`IpFailureTracker`](crate::middleware::IpFailureTracker).
+    /// A concurrent escalation on another replica can at worst re-apply the
+    /// same tier.
+    pub struct RedisRateLimiterStore {
+        connection: ConnectionManager,
+        key_prefix: String,
+        window: RateLimitWindow,
+        policy: EscalationPolicy,
+    }
+
+    impl RedisRateLimiterStore {
+        /// Connect to the Redis server at `url`.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`RateLimiterStoreError::Unavailable`] when the URL is
+        /// invalid or the initial connection fails.
+        pub async fn connect(
+            url: &str,
+            key_prefix: String,
+            window: RateLimitWindow,
+            policy: EscalationPolicy,
+        ) -> Result<Self, RateLimiterStoreError> {
+            let client = redis::Client::open(url).map_err(unavailable)?;
+            let connection = ConnectionManager::new(client).await.map_err(unavailable)?;
+            Ok(Self {
+                connection,
+                key_prefix,
+                window,
+                policy,
+            })
+        }
+
+        fn tier_key(&self, ip: &str) -> String {
+            format!("{}:tier:{}", self.key_prefix, ip)
+        }
+
+        fn failures_key(&self, ip: &str) -> String {
+            format!("{}:failures:{}", self.key_prefix, ip)
+        }
+
+        async fn read_tier(&self, ip: &str) -> Result<IpTier, RateLimiterStoreError> {
+            let key = self.tier_key(ip);
+            let mut connection = self.connection.clone();
+            let (value, ttl_ms): (Option<String>, i64) = redis::pipe()
+                .cmd("GET")
+                .arg(&key)
+                .cmd("PTTL")
+                .arg(&key)
+                .query_async(&mut connection)
+                .await
+                .map_err(unavailable)?;
+
+            let Some(value) = value else {
+                return Ok(IpTier::Normal);
+            };
+            // PTTL is negative when the key has just expired or has no TTL;
+            // treat both as an already-elapsed penalty.
+            let until = Instant::now() + Duration::from_millis(ttl_ms.max(0) as u64);
+            match value.as_str() {
+                TIER_RATE_RESTRICTED => Ok(IpTier::RateRestricted { until }),
+                TIER_BLOCKED => Ok(IpTier::Blocked { until }),
+                other => Err(RateLimiterStoreError::InvalidState {
+                    key,
+                    message: format!("unknown tier '{}'", other),
+                }),
+            }
+        }
+
+        async fn count_failure(&self, ip: &str) -> Result<u64, RateLimiterStoreError> {
+            let mut connection = self.connection.clone();
+            let now_ms = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_millis() as u64;
+            let window_ms = self.policy.window.as_millis().max(1) as u64;
+
+            let (count,): (u64,) = match self.window {
+                RateLimitWindow::Sliding => {
+                    let key = self.failures_key(ip);
+                    let member = format!("{}-{}", now_ms, rand::random::<u32>());
+                    redis::pipe()
+                        .atomic()
+                        .cmd("ZREMRANGEBYSCORE")
+                        .arg(&key)
+                        .arg("-inf")
+                        .arg(now_ms.saturating_sub(window_ms))
+                        .ignore()
+                        .cmd("ZADD")
+                        .arg(&key)
+                        .arg(now_ms)
+                        .arg(member)
+                        .ignore()
+                        .cmd("ZCARD")
+                        .arg(&key)
+                        .cmd("PEXPIRE")
+                        .arg(&key)
+                        .arg(window_ms)
+                        .ignore()
+                        .query_async(&mut connection)
+                        .await
+                        .map_err(unavailable)?
+                }
+                RateLimitWindow::Fixed => {
+                    let key = format!("{}:{}", self.failures_key(ip), now_ms / window_ms);
+                    redis::pipe()
+                        .atomic()
+                        .cmd("INCR")
+                        .arg(&key)
+                        .cmd("PEXPIRE")
+                        .arg(&key)
+                        .arg(window_ms)
+                        .ignore()
+                        .query_async(&mut connection)
+                        .await
+                        .map_err(unavailable)?
+                }
+            };
+            Ok(count)
+        }
+
+        async fn set_tier(
+            &self,
+            ip: &str,
+            tier: &str,
+            duration: Duration,
+        ) -> Result<(), RateLimiterStoreError> {
+            let mut connection = self.connection.clone();
+            let _: Option<String> = redis::cmd("SET")
+                .arg(self.tier_key(ip))
+                .arg(tier)
+                .arg("PX")
+                .arg(duration.as_millis().max(1) as u64)
+                .query_async(&mut connection)
+                .await
+                .map_err(unavailable)?;
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl RateLimiterStore for RedisRateLimiterStore {
+        async fn check_tier(&self, ip: &str) -> Result<IpTier, RateLimiterStoreError> {
+            self.read_tier(ip).await
+        }
+
+        async fn record_failure(&self, ip: &str) -> Result<IpTier, RateLimiterStoreError> {
+            let count = self.count_failure(ip).await? as usize;
+            let tier = self.read_tier(ip).await?;
+
+            if count > self.policy.block_threshold && !matches!(tier, IpTier::Blocked { .. }) {
+                self.set_tier(ip, TIER_BLOCKED, self.policy.block_duration)
+                    .await?;
+            } else if count >= self.policy.rate_restrict_threshold && matches!(tier, IpTier::Normal)
+            {
+                self.set_tier(ip, TIER_RATE_RESTRICTED, self.policy.rate_restrict_duration)
+                    .await?;
+            } else {
+                return Ok(tier);
+            }
+
+            self.read_tier(ip).await
+        }
+    }
+
+    fn unavailable(e: redis::RedisError) -> RateLimiterStoreError {
+        RateLimiterStoreError::Unavailable {
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "rate_limit_store_tests.rs"]
+mod tests;
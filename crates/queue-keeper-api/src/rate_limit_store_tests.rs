@@ -0,0 +1,79 @@
+//! Tests for rate limiter state backends.
+
+use super::*;
+
+fn security(threshold: usize) -> SecurityConfig {
+    SecurityConfig {
+        auth_failure_threshold: threshold,
+        auth_block_threshold: threshold * 10,
+        ..Default::default()
+    }
+}
+
+/// The in-memory tracker escalates through the store trait.
+#[tokio::test]
+async fn test_in_memory_store_escalates() {
+    let store = build_rate_limiter_store(&security(2))
+        .await
+        .unwrap()
+        .expect("IP rate limiting is enabled by default");
+
+    assert_eq!(
+        store.record_failure("203.0.113.1").await.unwrap(),
+        IpTier::Normal
+    );
+    assert!(matches!(
+        store.record_failure("203.0.113.1").await.unwrap(),
+        IpTier::RateRestricted { .. }
+    ));
+    assert!(store
+        .check_tier("203.0.113.1")
+        .await
+        .unwrap()
+        .is_restricted());
+    assert_eq!(
+        store.check_tier("203.0.113.2").await.unwrap(),
+        IpTier::Normal
+    );
+}
+
+/// No store is built when IP rate limiting is disabled.
+#[tokio::test]
+async fn test_disabled_builds_no_store() {
+    let cfg = SecurityConfig {
+        enable_ip_rate_limiting: false,
+        ..Default::default()
+    };
+    assert!(build_rate_limiter_store(&cfg).await.unwrap().is_none());
+}
+
+/// The escalation policy mirrors the security configuration.
+#[test]
+fn test_policy_from_config() {
+    let policy = EscalationPolicy::from_config(&SecurityConfig::default());
+    assert_eq!(policy.rate_restrict_threshold, 10);
+    assert_eq!(policy.block_threshold, 50);
+    assert_eq!(policy.window, Duration::from_secs(300));
+    assert_eq!(policy.rate_restrict_duration, Duration::from_secs(3_600));
+    assert_eq!(policy.block_duration, Duration::from_secs(86_400));
+}
+
+/// Selecting Redis without the feature is a configuration error.
+#[cfg(not(feature = "redis-rate-limiter"))]
+#[tokio::test]
+async fn test_redis_without_feature_is_rejected() {
+    use crate::config::RateLimitWindow;
+
+    let cfg = SecurityConfig {
+        rate_limiter_store: RateLimiterStoreConfig::Redis {
+            url: "redis://localhost:6379".to_string(),
+            key_prefix: "qk".to_string(),
+            window: RateLimitWindow::Sliding,
+        },
+        ..Default::default()
+    };
+    assert!(matches!(
+        build_rate_limiter_store(&cfg).await,
+        Err(ConfigError::ProviderNotCompiled { .. })
+    ));
+}
@@ -0,0 +1,115 @@
+//! Read-only mode for incident investigations and audits.
+//!
+//! While read-only mode is enabled, query APIs, health checks and webhook
+//! ingestion keep working, but every mutating admin operation (replay,
+//! session reset, configuration changes, DLQ requeue and purge, privacy
+//! erasure) is rejected with `409 Conflict` and a body explaining why.
+//!
+//! The mode is toggled through `PUT /admin/readonly`, which is itself exempt
+//! so that it can always be switched off again. The state is held in memory
+//! and resets to disabled on restart.
+
+use crate::authorization::RouteGroup;
+use queue_keeper_core::Timestamp;
+use serde::Serialize;
+use std::sync::Mutex;
+
+/// Admin path that toggles read-only mode and is never blocked by it.
+pub const READ_ONLY_TOGGLE_PATH: &str = "/admin/readonly";
+
+// ============================================================================
+// Read-Only Mode
+// ============================================================================
+
+/// Snapshot of the read-only mode state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReadOnlyStatus {
+    /// Whether mutating admin operations are currently rejected.
+    pub enabled: bool,
+
+    /// Why read-only mode was enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+
+    /// Principal that enabled read-only mode.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled_by: Option<String>,
+
+    /// When read-only mode was enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled_at: Option<Timestamp>,
+}
+
+/// Details recorded when read-only mode is enabled.
+#[derive(Debug, Clone)]
+struct ReadOnlyState {
+    reason: String,
+    enabled_by: String,
+    enabled_at: Timestamp,
+}
+
+/// Process-wide read-only switch shared by the admin middleware and handlers.
+#[derive(Debug, Default)]
+pub struct ReadOnlyMode {
+    state: Mutex<Option<ReadOnlyState>>,
+}
+
+impl ReadOnlyMode {
+    /// Create a switch with read-only mode disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enable read-only mode, replacing the reason of an earlier activation.
+    pub fn enable(&self, reason: impl Into<String>, enabled_by: impl Into<String>) {
+        *self.state.lock().unwrap() = Some(ReadOnlyState {
+            reason: reason.into(),
+            enabled_by: enabled_by.into(),
+            enabled_at: Timestamp::now(),
+        });
+    }
+
+    /// Disable read-only mode. Returns `true` if it was enabled.
+    pub fn disable(&self) -> bool {
+        self.state.lock().unwrap().take().is_some()
+    }
+
+    /// Check whether read-only mode is enabled.
+    pub fn is_enabled(&self) -> bool {
+        self.state.lock().unwrap().is_some()
+    }
+
+    /// Current state of the switch.
+    pub fn status(&self) -> ReadOnlyStatus {
+        match self.state.lock().unwrap().as_ref() {
+            Some(state) => ReadOnlyStatus {
+                enabled: true,
+                reason: Some(state.reason.clone()),
+                enabled_by: Some(state.enabled_by.clone()),
+                enabled_at: Some(state.enabled_at),
+            },
+            None => ReadOnlyStatus {
+                enabled: false,
+                reason: None,
+                enabled_by: None,
+                enabled_at: None,
+            },
+        }
+    }
+
+    /// Check whether an admin request must be rejected.
+    ///
+    /// Returns the current status when read-only mode is enabled and the
+    /// request is a mutating operation other than the read-only toggle.
+    pub fn blocks(&self, group: RouteGroup, path: &str) -> Option<ReadOnlyStatus> {
+        if group == RouteGroup::ReadApi || path == READ_ONLY_TOGGLE_PATH {
+            return None;
+        }
+        let status = self.status();
+        status.enabled.then_some(status)
+    }
+}
+
+#[cfg(test)]
+#[path = "read_only_tests.rs"]
+mod tests;
@@ -0,0 +1,56 @@
+//! Tests for the read-only mode switch.
+
+use super::*;
+
+/// A new switch starts disabled and blocks nothing.
+#[test]
+fn test_new_switch_is_disabled() {
+    let mode = ReadOnlyMode::new();
+
+    assert!(!mode.is_enabled());
+    assert!(!mode.status().enabled);
+    assert!(mode
+        .blocks(RouteGroup::Replay, "/admin/events/x/replay")
+        .is_none());
+}
+
+/// Enabling records the reason and principal; disabling clears them.
+#[test]
+fn test_enable_and_disable() {
+    let mode = ReadOnlyMode::new();
+    mode.enable("incident 42", "on-call");
+
+    let status = mode.status();
+    assert!(status.enabled);
+    assert_eq!(status.reason.as_deref(), Some("incident 42"));
+    assert_eq!(status.enabled_by.as_deref(), Some("on-call"));
+    assert!(status.enabled_at.is_some());
+
+    assert!(mode.disable());
+    assert!(!mode.disable());
+    assert_eq!(mode.status().reason, None);
+}
+
+/// Mutating route groups are blocked while reads and the toggle are not.
+#[test]
+fn test_blocks_only_mutating_operations() {
+    let mode = ReadOnlyMode::new();
+    mode.enable("audit", "admin");
+
+    assert!(mode.blocks(RouteGroup::ReadApi, "/admin/config").is_none());
+    assert!(mode
+        .blocks(RouteGroup::ConfigChanges, READ_ONLY_TOGGLE_PATH)
+        .is_none());
+    for group in [
+        RouteGroup::Replay,
+        RouteGroup::DlqManagement,
+        RouteGroup::ConfigChanges,
+        RouteGroup::PrivacyErasure,
+    ] {
+        assert!(
+            mode.blocks(group, "/admin/other").is_some(),
+            "{} must be blocked",
+            group
+        );
+    }
+}
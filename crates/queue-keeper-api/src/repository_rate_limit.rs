@@ -0,0 +1,220 @@
+//! Per-repository webhook rate limiting.
+//!
+//! `webhooks.rate_limit_per_repo` caps the webhooks accepted per repository
+//! per minute, so one noisy repository (a bot loop, a mass relabel) cannot
+//! starve the others of processing and queue capacity. The webhook handler
+//! checks each event once the provider has validated and normalized it,
+//! keyed on the payload's `repository.full_name`; events without a
+//! repository, such as installation events, are not limited. A rejected
+//! event's delivery ID is released from deduplication, so a redelivery is
+//! processed.
+//!
+//! Counts use a sliding-window counter: the count of the current one-minute
+//! window plus the previous window's count, weighted by how much of it still
+//! overlaps the trailing minute. This approximates a true sliding window
+//! with two counters per repository.
+//!
+//! A rejected delivery receives HTTP 429 with a `Retry-After` header and is
+//! counted in `webhook_repository_throttled_total`;
+//! `webhook_throttled_repositories` is the number of repositories whose
+//! latest delivery was rejected.
+
+use crate::metrics::ServiceMetrics;
+use queue_keeper_core::webhook::WrappedEvent;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Length of one counting window.
+const WINDOW: Duration = Duration::from_secs(60);
+
+/// Number of tracked repositories above which idle ones are dropped.
+const MAX_TRACKED_REPOSITORIES: usize = 10_000;
+
+/// Counters of one repository.
+#[derive(Debug, Clone, Copy)]
+struct RepositoryWindow {
+    /// Start of the current window.
+    started: Instant,
+    current: u32,
+    previous: u32,
+    /// Whether the latest delivery was rejected.
+    throttled: bool,
+}
+
+impl RepositoryWindow {
+    fn new(now: Instant) -> Self {
+        Self {
+            started: now,
+            current: 0,
+            previous: 0,
+            throttled: false,
+        }
+    }
+
+    /// Move the window forward to the one containing `now`.
+    fn advance(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.started);
+        if elapsed < WINDOW {
+            return;
+        }
+        let windows = (elapsed.as_secs_f64() / WINDOW.as_secs_f64()).floor() as u32;
+        self.previous = if windows == 1 { self.current } else { 0 };
+        self.current = 0;
+        self.started += WINDOW * windows;
+    }
+
+    /// Share of the previous window still inside the trailing minute.
+    fn previous_weight(&self, now: Instant) -> f64 {
+        let into_window = now.saturating_duration_since(self.started);
+        1.0 - into_window.as_secs_f64() / WINDOW.as_secs_f64()
+    }
+
+    /// Estimated deliveries in the trailing minute.
+    fn estimate(&self, now: Instant) -> f64 {
+        f64::from(self.previous) * self.previous_weight(now) + f64::from(self.current)
+    }
+
+    /// Time until one more delivery fits within `limit`.
+    fn retry_after(&self, limit: u32, now: Instant) -> Duration {
+        let into_window = now.saturating_duration_since(self.started);
+        let until_next_window = WINDOW.saturating_sub(into_window);
+        if self.current >= limit || self.previous == 0 {
+            return until_next_window;
+        }
+        // previous * (1 - t / WINDOW) + current + 1 <= limit
+        let free_at = 1.0 - f64::from(limit - self.current - 1) / f64::from(self.previous);
+        Duration::from_secs_f64(free_at.max(0.0) * WINDOW.as_secs_f64())
+            .saturating_sub(into_window)
+            .min(until_next_window)
+    }
+}
+
+/// Sliding-window webhook rate limiter keyed on repository full name.
+#[derive(Debug)]
+pub struct RepositoryRateLimiter {
+    limit: u32,
+    repositories: Mutex<HashMap<String, RepositoryWindow>>,
+    metrics: Option<Arc<ServiceMetrics>>,
+}
+
+impl RepositoryRateLimiter {
+    /// Create a limiter accepting `limit` webhooks per repository per
+    /// minute.
+    ///
+    /// # Panics
+    ///
+    /// Panics when `limit` is zero; [`ServiceConfig::validate`] rejects such
+    /// configurations.
+    ///
+    /// [`ServiceConfig::validate`]: crate::config::ServiceConfig::validate
+    pub fn new(limit: u32) -> Self {
+        assert!(limit > 0, "per-repository rate limit must be positive");
+        Self {
+            limit,
+            repositories: Mutex::new(HashMap::new()),
+            metrics: None,
+        }
+    }
+
+    /// Count throttled deliveries and repositories in `metrics`.
+    pub fn with_metrics(mut self, metrics: Arc<ServiceMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Webhooks accepted per repository per minute.
+    pub fn limit(&self) -> u32 {
+        self.limit
+    }
+
+    /// Accept or reject one webhook for `repository`.
+    ///
+    /// Returns the time until the repository is below its limit again when
+    /// the webhook is rejected.
+    pub fn check(&self, repository: &str) -> Result<(), Duration> {
+        self.check_at(repository, Instant::now())
+    }
+
+    /// [`check`](Self::check) at `now`.
+    pub fn check_at(&self, repository: &str, now: Instant) -> Result<(), Duration> {
+        let mut repositories = self.repositories.lock().unwrap();
+        if repositories.len() >= MAX_TRACKED_REPOSITORIES && !repositories.contains_key(repository)
+        {
+            self.prune(&mut repositories, now);
+        }
+
+        let window = repositories
+            .entry(repository.to_string())
+            .or_insert_with(|| RepositoryWindow::new(now));
+        window.advance(now);
+
+        if window.estimate(now) + 1.0 > f64::from(self.limit) {
+            if let Some(metrics) = &self.metrics {
+                metrics.webhook_repository_throttled_total.inc();
+                if !window.throttled {
+                    metrics.webhook_throttled_repositories.inc();
+                }
+            }
+            window.throttled = true;
+            return Err(window.retry_after(self.limit, now));
+        }
+
+        window.current += 1;
+        if window.throttled {
+            window.throttled = false;
+            if let Some(metrics) = &self.metrics {
+                metrics.webhook_throttled_repositories.dec();
+            }
+        }
+        Ok(())
+    }
+
+    /// Number of repositories currently tracked.
+    pub fn tracked_repositories(&self) -> usize {
+        self.repositories.lock().unwrap().len()
+    }
+
+    /// Drop repositories with no deliveries in the trailing minute.
+    fn prune(&self, repositories: &mut HashMap<String, RepositoryWindow>, now: Instant) {
+        repositories.retain(|_, window| {
+            window.advance(now);
+            let idle = window.current == 0 && window.estimate(now) < 1.0;
+            if idle && window.throttled {
+                if let Some(metrics) = &self.metrics {
+                    metrics.webhook_throttled_repositories.dec();
+                }
+            }
+            !idle
+        });
+    }
+}
+
+/// The `repository.full_name` of a normalized event's payload, if present.
+pub fn event_repository(event: &WrappedEvent) -> Option<&str> {
+    event.payload.pointer("/repository/full_name")?.as_str()
+}
+
+/// The `repository.full_name` of a JSON webhook payload, if present.
+pub fn repository_full_name(body: &[u8]) -> Option<String> {
+    #[derive(serde::Deserialize)]
+    struct Payload {
+        repository: Option<Repository>,
+    }
+
+    #[derive(serde::Deserialize)]
+    struct Repository {
+        full_name: String,
+    }
+
+    serde_json::from_slice::<Payload>(body)
+        .ok()?
+        .repository
+        .map(|repository| repository.full_name)
+}
+
+#[cfg(test)]
+#[path = "repository_rate_limit_tests.rs"]
+mod tests;
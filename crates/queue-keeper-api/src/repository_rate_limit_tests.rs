@@ -0,0 +1,87 @@
+//! Tests for per-repository webhook rate limiting.
+
+use super::*;
+
+fn secs(value: u64) -> Duration {
+    Duration::from_secs(value)
+}
+
+/// A repository is limited on its own; other repositories are unaffected.
+#[test]
+fn test_repository_limited_independently() {
+    let limiter = RepositoryRateLimiter::new(3);
+    let now = Instant::now();
+
+    for _ in 0..3 {
+        assert!(limiter.check_at("org/noisy", now).is_ok());
+    }
+    assert_eq!(limiter.check_at("org/noisy", now), Err(secs(60)));
+    assert!(limiter.check_at("org/quiet", now).is_ok());
+    assert_eq!(limiter.tracked_repositories(), 2);
+}
+
+/// The previous window's deliveries count in proportion to their overlap
+/// with the trailing minute.
+#[test]
+fn test_previous_window_slides_out() {
+    let limiter = RepositoryRateLimiter::new(3);
+    let start = Instant::now();
+    for _ in 0..3 {
+        assert!(limiter.check_at("org/repo", start).is_ok());
+    }
+
+    // All three still fall inside the trailing minute.
+    let retry_after = limiter.check_at("org/repo", start + secs(60)).unwrap_err();
+    assert_eq!(retry_after.as_secs_f64().round(), 20.0);
+
+    // A third of the previous window has slid out.
+    assert!(limiter.check_at("org/repo", start + secs(81)).is_ok());
+    assert!(limiter.check_at("org/repo", start + secs(81)).is_err());
+
+    // Two idle windows reset the repository.
+    for _ in 0..3 {
+        assert!(limiter.check_at("org/repo", start + secs(200)).is_ok());
+    }
+}
+
+/// Throttled deliveries and repositories are counted.
+#[test]
+fn test_throttled_metrics() {
+    let metrics = Arc::new(ServiceMetrics::default());
+    let limiter = RepositoryRateLimiter::new(1).with_metrics(metrics.clone());
+    let start = Instant::now();
+
+    assert!(limiter.check_at("org/repo", start).is_ok());
+    assert!(limiter.check_at("org/repo", start).is_err());
+    assert!(limiter.check_at("org/repo", start).is_err());
+    assert_eq!(metrics.webhook_repository_throttled_total.get(), 2);
+    assert_eq!(metrics.webhook_throttled_repositories.get(), 1);
+
+    assert!(limiter.check_at("org/repo", start + secs(120)).is_ok());
+    assert_eq!(metrics.webhook_throttled_repositories.get(), 0);
+}
+
+/// The repository is read from the payload when present.
+#[test]
+fn test_repository_full_name() {
+    assert_eq!(
+        repository_full_name(
+            br#"{"action":"opened","repository":{"full_name":"org/repo","id":1}}"#
+        ),
+        Some("org/repo".to_string())
+    );
+    assert_eq!(repository_full_name(br#"{"action":"created"}"#), None);
+    assert_eq!(repository_full_name(b"not json"), None);
+}
+
+/// The repository of a normalized event is read from its payload.
+#[test]
+fn test_event_repository() {
+    let mut event = crate::test_fixtures::event_with_payload(serde_json::json!({
+        "repository": { "full_name": "org/repo" }
+    }));
+    assert_eq!(event_repository(&event), Some("org/repo"));
+
+    event.payload = serde_json::json!({ "installation": { "id": 1 } });
+    assert_eq!(event_repository(&event), None);
+}
@@ -0,0 +1,243 @@
+//! Request rate limiting.
+//!
+//! Caps the request rate of the webhook, API and admin routes with token
+//! buckets: one shared by all clients (`security.global_rate_limit`) and one
+//! per client IP (`security.ip_rate_limit`), both in requests per minute.
+//! Each bucket holds up to a minute's worth of requests, so short bursts are
+//! absorbed, and refills continuously at the configured rate.
+//!
+//! A request is admitted only when both its IP's bucket and the global
+//! bucket have a token; otherwise it is rejected with HTTP 429 and a
+//! `Retry-After` of the time until the exhausted bucket refills one token.
+//! An IP rejected by its own limit does not spend a global token, so one
+//! noisy client cannot exhaust the limit for everyone else.
+//!
+//! This is distinct from the authentication-failure escalation in
+//! [`crate::middleware::IpFailureTracker`], which restricts IPs that fail
+//! authentication repeatedly.
+
+use crate::config::SecurityConfig;
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Number of tracked IPs above which buckets are dropped.
+const MAX_TRACKED_IPS: usize = 10_000;
+
+/// Number of tracked IPs left after the least recently used buckets are
+/// dropped, so that pruning is not repeated for every new IP.
+const PRUNED_TRACKED_IPS: usize = MAX_TRACKED_IPS - MAX_TRACKED_IPS / 10;
+
+// ============================================================================
+// Rejection
+// ============================================================================
+
+/// Which limit rejected a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitScope {
+    /// The limit shared by all clients.
+    Global,
+    /// The client IP's own limit.
+    Ip,
+}
+
+impl RateLimitScope {
+    /// Label used in metrics and responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RateLimitScope::Global => "global",
+            RateLimitScope::Ip => "ip",
+        }
+    }
+}
+
+/// A request rejected by the [`RequestRateLimiter`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimited {
+    /// The exhausted limit.
+    pub scope: RateLimitScope,
+    /// Time until the exhausted limit admits another request.
+    pub retry_after: Duration,
+}
+
+impl RateLimited {
+    /// `retry_after` rounded up to whole seconds, at least one, for the
+    /// `Retry-After` header.
+    pub fn retry_after_secs(&self) -> u64 {
+        let secs = self.retry_after.as_secs();
+        let secs = if self.retry_after.subsec_nanos() > 0 {
+            secs + 1
+        } else {
+            secs
+        };
+        secs.max(1)
+    }
+}
+
+// ============================================================================
+// Token Bucket
+// ============================================================================
+
+/// Token bucket holding up to `capacity` tokens, refilled at `capacity`
+/// tokens per minute.
+#[derive(Debug, Clone, Copy)]
+struct TokenBucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+impl TokenBucket {
+    fn full(capacity: u32, now: Instant) -> Self {
+        Self {
+            tokens: f64::from(capacity),
+            updated: now,
+        }
+    }
+
+    /// Add the tokens accrued since the last update.
+    fn refill(&mut self, capacity: u32, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        let rate = f64::from(capacity) / 60.0;
+        self.tokens = (self.tokens + elapsed * rate).min(f64::from(capacity));
+        self.updated = now;
+    }
+
+    /// Whether the bucket would be full at `now`.
+    fn is_full_at(&self, capacity: u32, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.tokens + elapsed * f64::from(capacity) / 60.0 >= f64::from(capacity)
+    }
+
+    /// Whether a token is available, after refilling.
+    fn has_token(&mut self, capacity: u32, now: Instant) -> bool {
+        self.refill(capacity, now);
+        self.tokens >= 1.0
+    }
+
+    /// Time until a token is available.
+    fn wait(&self, capacity: u32) -> Duration {
+        let missing = (1.0 - self.tokens).max(0.0);
+        Duration::from_secs_f64(missing * 60.0 / f64::from(capacity))
+    }
+}
+
+// ============================================================================
+// Request Rate Limiter
+// ============================================================================
+
+/// Global and per-IP request rate limiter.
+#[derive(Debug)]
+pub struct RequestRateLimiter {
+    global_limit: u32,
+    ip_limit: Option<u32>,
+    global: Mutex<TokenBucket>,
+    ips: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl RequestRateLimiter {
+    /// Create a limiter admitting `global_limit` requests per minute in
+    /// total and, when set, `ip_limit` per minute from each IP.
+    ///
+    /// # Panics
+    ///
+    /// Panics when a limit is zero; [`SecurityConfig::validate`] rejects
+    /// such configurations.
+    pub fn new(global_limit: u32, ip_limit: Option<u32>) -> Self {
+        assert!(global_limit > 0, "global rate limit must be positive");
+        assert!(ip_limit != Some(0), "IP rate limit must be positive");
+        Self {
+            global_limit,
+            ip_limit,
+            global: Mutex::new(TokenBucket::full(global_limit, Instant::now())),
+            ips: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Build the limiter described by `security`.
+    ///
+    /// Returns `None` when `enable_rate_limiting` is off. The per-IP limit
+    /// applies only while `enable_ip_rate_limiting` is on.
+    pub fn from_config(security: &SecurityConfig) -> Option<Self> {
+        security.enable_rate_limiting.then(|| {
+            Self::new(
+                security.global_rate_limit,
+                security
+                    .enable_ip_rate_limiting
+                    .then_some(security.ip_rate_limit),
+            )
+        })
+    }
+
+    /// Admit or reject one request from `client_ip`.
+    pub fn check(&self, client_ip: &str) -> Result<(), RateLimited> {
+        self.check_at(client_ip, Instant::now())
+    }
+
+    /// [`check`](Self::check) at `now`.
+    pub fn check_at(&self, client_ip: &str, now: Instant) -> Result<(), RateLimited> {
+        let mut ips = self.ips.lock().unwrap();
+        let mut ip_bucket = None;
+        if let Some(limit) = self.ip_limit {
+            if ips.len() >= MAX_TRACKED_IPS && !ips.contains_key(client_ip) {
+                Self::prune(&mut ips, limit, now);
+            }
+            let bucket = ips
+                .entry(client_ip.to_string())
+                .or_insert_with(|| TokenBucket::full(limit, now));
+            if !bucket.has_token(limit, now) {
+                return Err(RateLimited {
+                    scope: RateLimitScope::Ip,
+                    retry_after: bucket.wait(limit),
+                });
+            }
+            ip_bucket = Some(bucket);
+        }
+
+        let mut global = self.global.lock().unwrap();
+        if !global.has_token(self.global_limit, now) {
+            return Err(RateLimited {
+                scope: RateLimitScope::Global,
+                retry_after: global.wait(self.global_limit),
+            });
+        }
+        global.tokens -= 1.0;
+        if let Some(bucket) = ip_bucket {
+            bucket.tokens -= 1.0;
+        }
+        Ok(())
+    }
+
+    /// Number of IPs currently tracked.
+    pub fn tracked_ips(&self) -> usize {
+        self.ips.lock().unwrap().len()
+    }
+
+    /// Drop the buckets of IPs that have refilled completely, which behave
+    /// exactly like a new bucket, then the least recently used ones until
+    /// [`PRUNED_TRACKED_IPS`] remain.
+    ///
+    /// A bucket's last refill is its last request, so clients rotating
+    /// through addresses evict each other rather than growing the map.
+    fn prune(ips: &mut HashMap<String, TokenBucket>, limit: u32, now: Instant) {
+        ips.retain(|_, bucket| !bucket.is_full_at(limit, now));
+        if ips.len() <= PRUNED_TRACKED_IPS {
+            return;
+        }
+
+        let excess = ips.len() - PRUNED_TRACKED_IPS;
+        let mut by_age: Vec<(Instant, String)> = ips
+            .iter()
+            .map(|(ip, bucket)| (bucket.updated, ip.clone()))
+            .collect();
+        by_age.select_nth_unstable_by_key(excess - 1, |(updated, _)| *updated);
+        for (_, ip) in &by_age[..excess] {
+            ips.remove(ip);
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "request_rate_limit_tests.rs"]
+mod tests;
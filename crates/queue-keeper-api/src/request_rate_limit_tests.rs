@@ -0,0 +1,114 @@
+//! Tests for global and per-IP request rate limiting.
+
+use super::*;
+
+/// An IP is admitted up to its limit, then rejected with the time until its
+/// bucket refills one token; other IPs are unaffected.
+#[test]
+fn test_ip_limit_rejects_after_burst() {
+    let limiter = RequestRateLimiter::new(1000, Some(3));
+    let now = Instant::now();
+
+    for _ in 0..3 {
+        assert!(limiter.check_at("10.0.0.1", now).is_ok());
+    }
+    let rejected = limiter.check_at("10.0.0.1", now).unwrap_err();
+    assert_eq!(rejected.scope, RateLimitScope::Ip);
+    assert_eq!(rejected.retry_after, Duration::from_secs(20));
+    assert_eq!(rejected.retry_after_secs(), 20);
+
+    assert!(limiter.check_at("10.0.0.2", now).is_ok());
+}
+
+/// The global limit applies across IPs, and requests rejected by an IP's own
+/// limit do not spend global tokens.
+#[test]
+fn test_global_limit_shared_across_ips() {
+    let limiter = RequestRateLimiter::new(4, Some(2));
+    let now = Instant::now();
+
+    assert!(limiter.check_at("10.0.0.1", now).is_ok());
+    assert!(limiter.check_at("10.0.0.1", now).is_ok());
+    for _ in 0..5 {
+        assert_eq!(
+            limiter.check_at("10.0.0.1", now).unwrap_err().scope,
+            RateLimitScope::Ip
+        );
+    }
+    assert!(limiter.check_at("10.0.0.2", now).is_ok());
+    assert!(limiter.check_at("10.0.0.3", now).is_ok());
+
+    let rejected = limiter.check_at("10.0.0.4", now).unwrap_err();
+    assert_eq!(rejected.scope, RateLimitScope::Global);
+    assert_eq!(rejected.retry_after_secs(), 15);
+}
+
+/// Buckets refill continuously at the configured rate.
+#[test]
+fn test_tokens_refill_over_time() {
+    let limiter = RequestRateLimiter::new(60, None);
+    let start = Instant::now();
+
+    for _ in 0..60 {
+        assert!(limiter.check_at("10.0.0.1", start).is_ok());
+    }
+    assert!(limiter.check_at("10.0.0.1", start).is_err());
+
+    let later = start + Duration::from_millis(1500);
+    assert!(limiter.check_at("10.0.0.1", later).is_ok());
+    let rejected = limiter.check_at("10.0.0.1", later).unwrap_err();
+    assert_eq!(rejected.retry_after, Duration::from_millis(500));
+    assert_eq!(rejected.retry_after_secs(), 1);
+    assert_eq!(limiter.tracked_ips(), 0);
+}
+
+/// The limiter follows the security settings and is off by default.
+#[test]
+fn test_from_config() {
+    assert!(RequestRateLimiter::from_config(&SecurityConfig::default()).is_none());
+
+    let enabled = SecurityConfig {
+        enable_rate_limiting: true,
+        ..SecurityConfig::default()
+    };
+    assert!(RequestRateLimiter::from_config(&enabled).is_some());
+
+    let global_only = SecurityConfig {
+        enable_rate_limiting: true,
+        enable_ip_rate_limiting: false,
+        global_rate_limit: 5,
+        ..SecurityConfig::default()
+    };
+    let limiter = RequestRateLimiter::from_config(&global_only).unwrap();
+    let now = Instant::now();
+    for _ in 0..5 {
+        assert!(limiter.check_at("10.0.0.1", now).is_ok());
+    }
+    assert_eq!(
+        limiter.check_at("10.0.0.1", now).unwrap_err().scope,
+        RateLimitScope::Global
+    );
+}
+
+/// Once the tracked IPs are at capacity and none has refilled, the least
+/// recently used buckets are dropped.
+#[test]
+fn test_prune_drops_least_recently_used() {
+    let limiter = RequestRateLimiter::new(1_000_000, Some(1));
+    let start = Instant::now();
+
+    for i in 0..MAX_TRACKED_IPS {
+        let now = start + Duration::from_millis(i as u64);
+        assert!(limiter.check_at(&format!("ip-{}", i), now).is_ok());
+    }
+    assert_eq!(limiter.tracked_ips(), MAX_TRACKED_IPS);
+
+    let now = start + Duration::from_millis(MAX_TRACKED_IPS as u64);
+    assert!(limiter.check_at("ip-new", now).is_ok());
+    assert_eq!(limiter.tracked_ips(), PRUNED_TRACKED_IPS + 1);
+
+    // The most recent IP is still limited; the oldest starts afresh.
+    let latest = format!("ip-{}", MAX_TRACKED_IPS - 1);
+    assert!(limiter.check_at(&latest, now).is_err());
+    assert!(limiter.check_at("ip-0", now).is_ok());
+}
@@ -1,13 +1,21 @@
 //! Response types, query parameters, and supporting types for the API.
 
-use crate::ProviderRegistry;
+use crate::event_deletion::{EventTombstone, EventTombstones};
+use crate::{
+    freshness::FreshnessMonitor, startup_report::ValueSource, supervisor::Supervisor,
+    ProviderRegistry,
+};
 use queue_keeper_core::blob_storage::{
-    BlobStorage, BlobStorageError, PayloadFilter, PayloadMetadata, WebhookPayload,
+    BlobStorage, BlobStorageError, DateRange, PayloadFilter, PayloadMetadata, WebhookPayload,
+};
+use queue_keeper_core::circuit_breaker::{CircuitBreakerGroup, CircuitState};
+use queue_keeper_core::webhook::{deserialize_envelope, EventOrigin, LineageNode, WrappedEvent};
+use queue_keeper_core::{
+    BotName, EventId, QueueKeeperError, QueueName, ReplayId, Repository, SessionId, SessionTracker,
+    StuckSession, Timestamp, TrackedSession, ValidationError,
 };
-use queue_keeper_core::webhook::WrappedEvent;
-use queue_keeper_core::{EventId, QueueKeeperError, Repository, SessionId, Timestamp};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::Instant;
 use tracing::{debug, error, warn};
@@ -19,8 +27,12 @@ use tracing::{debug, error, warn};
 /// Webhook processing response
 #[derive(Debug, Serialize)]
 pub struct WebhookResponse {
+    /// Event created from the delivery. For an `already_processed`
+    /// delivery, the event created from the original delivery.
     pub event_id: EventId,
     pub session_id: Option<SessionId>,
+    /// `processed`, or `already_processed` when the delivery ID was
+    /// processed before and the delivery was skipped.
     pub status: String,
     pub message: String,
 }
@@ -50,12 +62,18 @@ pub struct ReadinessResponse {
 }
 
 /// Event list response
+///
+/// Events are ordered newest first. Pass `next_cursor` back as `cursor` to
+/// fetch the following page; it is absent on the last page.
 #[derive(Debug, Serialize)]
 pub struct EventListResponse {
     pub events: Vec<EventSummary>,
-    pub total: usize,
-    pub page: usize,
-    pub per_page: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    pub limit: usize,
+    /// Number of matching events; omitted when `include_total=false`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
 }
 
 /// Event detail response
@@ -64,11 +82,29 @@ pub struct EventDetailResponse {
     pub event: WrappedEvent,
 }
 
+/// Event lineage response
+#[derive(Debug, Serialize)]
+pub struct EventLineageResponse {
+    /// Event the lineage was requested for
+    pub event_id: EventId,
+    /// Original webhook event at the top of the lineage
+    pub root_event_id: EventId,
+    /// Lineage tree starting at the oldest available ancestor
+    pub tree: LineageNode,
+}
+
 /// Session list response
+///
+/// Sessions are ordered by most recent activity. Pass `next_cursor` back as
+/// `cursor` to fetch the following page; it is absent on the last page.
 #[derive(Debug, Serialize)]
 pub struct SessionListResponse {
     pub sessions: Vec<SessionSummary>,
-    pub total: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
+    /// Number of matching sessions; omitted when `include_total=false`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<usize>,
 }
 
 /// Session detail response
@@ -77,7 +113,23 @@ pub struct SessionDetailResponse {
     pub session: SessionDetails,
 }
 
+/// Stuck session list response
+///
+/// Sessions whose oldest in-flight event has waited longer than
+/// `stuck_after_seconds`, longest waiting first.
+#[derive(Debug, Serialize)]
+pub struct StuckSessionListResponse {
+    pub stuck_after_seconds: u64,
+    /// Events accepted but not yet delivered across all tracked sessions
+    pub in_flight_events: u64,
+    pub sessions: Vec<StuckSession>,
+}
+
 /// Statistics response
+///
+/// `total_events`, `events_per_hour` and `events_by_type` count live
+/// deliveries only; replayed, synthetic and imported events are reported in
+/// `events_by_origin`.
 #[derive(Debug, Serialize)]
 pub struct StatisticsResponse {
     pub total_events: u64,
@@ -85,6 +137,37 @@ pub struct StatisticsResponse {
     pub active_sessions: u64,
     pub error_rate: f64,
     pub uptime_seconds: u64,
+    /// Live events per event type. Omitted when no event was counted.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub events_by_type: BTreeMap<String, u64>,
+    /// Stored events per origin, including origins that do not count toward
+    /// `total_events`. Omitted when no events are stored.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub events_by_origin: BTreeMap<EventOrigin, u64>,
+}
+
+/// Event replay request
+///
+/// The body is optional; without it the event is delivered to every bot
+/// whose subscription matches it.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ReplayEventRequest {
+    /// Deliver only to this bot.
+    pub target_bot: Option<BotName>,
+    /// Report the routing decisions without delivering.
+    pub dry_run: bool,
+    /// Why the event is being replayed (recorded in the audit log).
+    pub reason: Option<String>,
+}
+
+/// A bot queue a replayed event was (or, in a dry run, would be) routed to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReplayRoute {
+    pub bot_name: BotName,
+    pub queue: QueueName,
+    /// Whether the bot is paused, so the event is held instead of delivered
+    pub held: bool,
 }
 
 /// Event replay response
@@ -93,6 +176,22 @@ pub struct ReplayResponse {
     pub event_id: EventId,
     pub status: String,
     pub message: String,
+    /// ID of the replay operation
+    pub replay_id: ReplayId,
+    /// ID of the event derived by the replay; omitted for dry runs and
+    /// replays that delivered nothing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replay_event_id: Option<EventId>,
+    pub dry_run: bool,
+    /// Bot queues the event was routed to
+    pub routes: Vec<ReplayRoute>,
+}
+
+/// Session reset request
+#[derive(Debug, Deserialize)]
+pub struct ResetSessionRequest {
+    /// Why the session is being reset (recorded in the audit log).
+    pub reason: String,
 }
 
 /// Session reset response
@@ -101,6 +200,66 @@ pub struct ResetResponse {
     pub session_id: SessionId,
     pub status: String,
     pub message: String,
+    /// Session epoch before the reset
+    pub previous_epoch: u64,
+    /// Session epoch stamped on events delivered after the reset
+    pub session_epoch: u64,
+    /// Number of pending deliveries that were cancelled
+    pub cancelled_deliveries: usize,
+}
+
+/// Event deletion request
+#[derive(Debug, Deserialize)]
+pub struct DeleteEventRequest {
+    /// Why the event is being deleted (recorded in the tombstone and the
+    /// audit log).
+    pub reason: String,
+}
+
+/// Event deletion response
+#[derive(Debug, Serialize)]
+pub struct DeleteEventResponse {
+    pub status: String,
+    #[serde(flatten)]
+    pub tombstone: EventTombstone,
+}
+
+/// DLQ archive restore response
+#[derive(Debug, Serialize)]
+pub struct DlqRestoreResponse {
+    pub bundle_id: EventId,
+    pub status: String,
+    /// Number of records written back to the active DLQ store
+    pub restored_records: usize,
+}
+
+/// Read-only mode toggle request
+#[derive(Debug, Deserialize)]
+pub struct SetReadOnlyRequest {
+    pub enabled: bool,
+    /// Why read-only mode is being enabled (required when `enabled` is true).
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Transform kill switch request
+#[derive(Debug, Deserialize)]
+pub struct SetTransformRequest {
+    pub enabled: bool,
+}
+
+/// Bot delivery pause request
+#[derive(Debug, Deserialize)]
+pub struct PauseBotRequest {
+    /// Why the bot is being paused (recorded in the audit log).
+    pub reason: String,
+}
+
+/// Session pause request
+#[derive(Debug, Deserialize)]
+pub struct PauseSessionRequest {
+    /// Why the session is being paused (recorded in the audit log).
+    pub reason: String,
 }
 
 /// Debug profile response
@@ -118,10 +277,45 @@ pub struct DebugVarsResponse {
     pub vars: HashMap<String, String>,
 }
 
+/// Service configuration response of `GET /admin/config`
+#[derive(Debug, Serialize)]
+pub struct ConfigResponse {
+    /// The configuration, with secrets redacted.
+    #[serde(flatten)]
+    pub config: serde_json::Value,
+    /// Version of the bot configuration in use; 1 at startup.
+    pub bot_config_version: u64,
+    /// When the bot configuration in use was loaded.
+    pub bot_config_loaded_at: Timestamp,
+    /// Source of every value, by dotted path; only with
+    /// `include_defaults=true`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_sources: Option<BTreeMap<String, ValueSource>>,
+}
+
+/// Query parameters of `GET /admin/config`
+#[derive(Debug, Default, Deserialize)]
+pub struct ConfigParams {
+    /// Also report whether each value came from the defaults, a file or
+    /// an environment variable.
+    #[serde(default)]
+    pub include_defaults: bool,
+}
+
+/// Bot configuration replacement response
+#[derive(Debug, Serialize)]
+pub struct BotConfigUpdateResponse {
+    pub version: u64,
+    pub bots: usize,
+}
+
 /// Log level response
 #[derive(Debug, Serialize)]
 pub struct LogLevelResponse {
+    /// Most verbose level the filter enables.
     pub level: String,
+    /// Filter directives in effect, in `RUST_LOG` syntax.
+    pub filter: String,
 }
 
 /// Set log level request
@@ -135,6 +329,8 @@ pub struct SetLogLevelRequest {
 pub struct TraceSamplingResponse {
     pub sampling_ratio: f64,
     pub service_name: String,
+    /// Whether spans are exported to an OTLP collector (`telemetry.enabled`).
+    pub export_enabled: bool,
 }
 
 /// Set trace sampling request
@@ -156,14 +352,20 @@ pub struct MetricsResetResponse {
 // ============================================================================
 
 /// Parameters for event listing
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 pub struct EventListParams {
-    pub page: Option<usize>,
-    pub per_page: Option<usize>,
+    /// Cursor from a previous response's `next_cursor`
+    pub cursor: Option<String>,
+    /// Page size (default 50, maximum 500)
+    pub limit: Option<usize>,
+    /// Whether to count all matching events (default true)
+    pub include_total: Option<bool>,
     pub event_type: Option<String>,
     pub repository: Option<String>,
     pub session_id: Option<String>,
     pub since: Option<String>,
+    /// Whether to list soft-deleted events (default false)
+    pub include_deleted: Option<bool>,
 }
 
 /// Parameters for session listing
@@ -172,13 +374,144 @@ pub struct SessionListParams {
     pub repository: Option<String>,
     pub entity_type: Option<String>,
     pub status: Option<String>,
+    /// Only list sessions with events waiting for delivery
+    pub pending: Option<bool>,
+    /// Cursor from a previous response's `next_cursor`
+    pub cursor: Option<String>,
+    /// Page size (default: all remaining sessions)
     pub limit: Option<usize>,
+    /// Whether to count all matching sessions (default true)
+    pub include_total: Option<bool>,
 }
 
 // ============================================================================
 // Supporting Types
 // ============================================================================
 
+/// Opaque pagination cursor for the list endpoints.
+///
+/// Records the event ID that ordered the last item of the previous page. As
+/// event IDs are ULIDs, the next page continues strictly below that ID, so
+/// events stored while a client pages through the list neither shift nor
+/// repeat entries. Clients must treat the encoded form as opaque.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageCursor {
+    after: EventId,
+}
+
+impl PageCursor {
+    const PREFIX: &'static str = "v1:";
+
+    /// Cursor continuing after `event_id`
+    pub fn after(event_id: EventId) -> Self {
+        Self { after: event_id }
+    }
+
+    /// Event ID of the last item on the previous page
+    pub fn event_id(&self) -> EventId {
+        self.after
+    }
+
+    /// Encode for use in a response's `next_cursor`
+    pub fn encode(&self) -> String {
+        hex::encode(format!("{}{}", Self::PREFIX, self.after))
+    }
+
+    /// Decode a client-supplied cursor
+    pub fn decode(cursor: &str) -> Result<Self, ValidationError> {
+        let invalid = || ValidationError::InvalidFormat {
+            field: "cursor".to_string(),
+            message: "not a cursor returned by this API".to_string(),
+        };
+
+        let bytes = hex::decode(cursor).map_err(|_| invalid())?;
+        let text = String::from_utf8(bytes).map_err(|_| invalid())?;
+        let after = text
+            .strip_prefix(Self::PREFIX)
+            .and_then(|id| id.parse().ok())
+            .ok_or_else(invalid)?;
+        Ok(Self { after })
+    }
+
+    /// Decode an optional cursor parameter
+    fn from_param(cursor: Option<&str>) -> Result<Option<Self>, QueueKeeperError> {
+        cursor
+            .map(Self::decode)
+            .transpose()
+            .map_err(QueueKeeperError::Validation)
+    }
+}
+
+/// Take one page from `items`, sorted newest first by their event ID key.
+///
+/// Returns the page and the cursor for the next page, if any items remain.
+fn paginate<T>(
+    items: Vec<(EventId, T)>,
+    cursor: Option<PageCursor>,
+    limit: usize,
+) -> (Vec<T>, Option<String>) {
+    let mut remaining = items
+        .into_iter()
+        .filter(|(id, _)| cursor.is_none_or(|c| *id < c.event_id()))
+        .peekable();
+
+    let page: Vec<(EventId, T)> = remaining.by_ref().take(limit).collect();
+    let next_cursor = match (remaining.peek(), page.last()) {
+        (Some(_), Some((id, _))) => Some(PageCursor::after(*id).encode()),
+        _ => None,
+    };
+    (
+        page.into_iter().map(|(_, item)| item).collect(),
+        next_cursor,
+    )
+}
+
+/// List the sessions recorded by `tracker` for `GET /api/sessions`.
+///
+/// Sessions are ordered by their latest event, newest first, and paged with
+/// the same cursors as [`EventStore::list_sessions`]. Only sessions seen
+/// since the service started are listed.
+pub fn list_tracked_sessions(
+    tracker: &SessionTracker,
+    params: &SessionListParams,
+) -> Result<SessionListResponse, QueueKeeperError> {
+    let cursor = PageCursor::from_param(params.cursor.as_deref())?;
+    let limit = params.limit.unwrap_or(usize::MAX).max(1);
+
+    let sessions: Vec<(EventId, SessionSummary)> = tracker
+        .list()
+        .into_iter()
+        .filter(|s| {
+            params
+                .repository
+                .as_ref()
+                .is_none_or(|r| *r == s.repository)
+                && params
+                    .entity_type
+                    .as_ref()
+                    .is_none_or(|t| *t == s.entity_type)
+                && params
+                    .status
+                    .as_ref()
+                    .is_none_or(|status| status == s.status.as_str())
+                && (!params.pending.unwrap_or(false) || s.pending_events > 0)
+        })
+        .map(|s| (s.last_event_id, SessionSummary::from(s)))
+        .collect();
+
+    // Total is counted before the cursor and limit are applied
+    let total = params
+        .include_total
+        .unwrap_or(true)
+        .then_some(sessions.len());
+    let (sessions, next_cursor) = paginate(sessions, cursor, limit);
+    Ok(SessionListResponse {
+        sessions,
+        next_cursor,
+        total,
+    })
+}
+
 /// Event summary for listing
 #[derive(Debug, Serialize)]
 pub struct EventSummary {
@@ -199,9 +532,26 @@ pub struct SessionSummary {
     pub entity_id: String,
     pub status: String,
     pub event_count: u32,
+    /// Events accepted but not yet delivered
+    pub pending_events: u64,
     pub last_activity: Timestamp,
 }
 
+impl From<TrackedSession> for SessionSummary {
+    fn from(session: TrackedSession) -> Self {
+        Self {
+            session_id: session.session_id,
+            repository: session.repository,
+            entity_type: session.entity_type,
+            entity_id: session.entity_id,
+            status: session.status.to_string(),
+            event_count: session.event_count.min(u64::from(u32::MAX)) as u32,
+            pending_events: session.pending_events,
+            last_activity: session.last_activity,
+        }
+    }
+}
+
 /// Detailed session information
 #[derive(Debug, Serialize)]
 pub struct SessionDetails {
@@ -213,13 +563,50 @@ pub struct SessionDetails {
     pub created_at: Timestamp,
     pub last_activity: Timestamp,
     pub event_count: u32,
+    /// Events accepted but not yet delivered
+    pub pending_events: u64,
     pub events: Vec<EventSummary>,
 }
 
+impl From<TrackedSession> for SessionDetails {
+    /// Details of a tracked session; `events` holds its latest events,
+    /// newest first.
+    fn from(session: TrackedSession) -> Self {
+        let events = session
+            .recent_events
+            .iter()
+            .map(|event| EventSummary {
+                event_id: event.event_id,
+                event_type: event.event_type.clone(),
+                repository: session.repository.clone(),
+                session_id: session.session_id.clone(),
+                occurred_at: event.received_at,
+                status: event.status.as_str().to_string(),
+            })
+            .collect();
+        Self {
+            repository: repository_from_full_name(&session.repository),
+            session_id: session.session_id,
+            entity_type: session.entity_type,
+            entity_id: session.entity_id,
+            status: session.status.to_string(),
+            created_at: session.first_seen,
+            last_activity: session.last_activity,
+            event_count: session.event_count.min(u64::from(u32::MAX)) as u32,
+            pending_events: session.pending_events,
+            events,
+        }
+    }
+}
+
 /// Health check result for individual components
 #[derive(Debug, Serialize, Clone)]
 pub struct HealthCheckResult {
     pub healthy: bool,
+    /// Degraded but not failing (for example stale configuration); does not
+    /// affect the overall status.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub warning: bool,
     pub message: String,
     pub duration_ms: u64,
 }
@@ -260,6 +647,32 @@ pub trait EventStore: Send + Sync {
     /// Get event by ID
     async fn get_event(&self, event_id: &EventId) -> Result<WrappedEvent, QueueKeeperError>;
 
+    /// Get the lineage tree containing an event.
+    ///
+    /// The tree starts at the lineage root, or at the oldest ancestor still
+    /// available when the root has been removed. The default implementation
+    /// follows parent references through [`get_event`](Self::get_event) and
+    /// therefore only returns the requested event's ancestors; stores that can
+    /// enumerate events should override it to include every descendant.
+    async fn get_lineage(&self, event_id: &EventId) -> Result<LineageNode, QueueKeeperError> {
+        let mut chain = vec![self.get_event(event_id).await?];
+        while let Some(lineage) = chain.last().and_then(|e| e.lineage) {
+            if chain.len() > MAX_LINEAGE_DEPTH {
+                break;
+            }
+            match self.get_event(&lineage.parent_event_id).await {
+                Ok(parent) => chain.push(parent),
+                Err(QueueKeeperError::NotFound { .. }) => break,
+                Err(e) => return Err(e),
+            }
+        }
+        let top = chain.last().map(|e| e.event_id).unwrap_or(*event_id);
+        LineageNode::build_tree(top, &chain).ok_or_else(|| QueueKeeperError::NotFound {
+            resource: "event".to_string(),
+            id: event_id.to_string(),
+        })
+    }
+
     /// List sessions with filters
     async fn list_sessions(
         &self,
@@ -272,6 +685,36 @@ pub trait EventStore: Send + Sync {
 
     /// Get system statistics
     async fn get_statistics(&self) -> Result<StatisticsResponse, QueueKeeperError>;
+
+    /// Soft-delete an event on behalf of `deleted_by`.
+    ///
+    /// The event is excluded from listings until it is purged. Deleting an
+    /// event twice returns the first tombstone. The default implementation
+    /// reports that the store does not support deletion.
+    async fn delete_event(
+        &self,
+        event_id: &EventId,
+        deleted_by: &str,
+        reason: &str,
+    ) -> Result<EventTombstone, QueueKeeperError> {
+        let _ = (deleted_by, reason);
+        Err(QueueKeeperError::Configuration {
+            message: format!(
+                "event {} cannot be deleted: the event store does not support deletion",
+                event_id
+            ),
+        })
+    }
+
+    /// Permanently remove the soft-deleted events due for purging at `now`
+    /// and return their tombstones.
+    ///
+    /// Events that cannot be removed stay deleted and are retried by the
+    /// next purge. The default implementation purges nothing.
+    async fn purge_deleted(&self, now: Timestamp) -> Result<Vec<EventTombstone>, QueueKeeperError> {
+        let _ = now;
+        Ok(Vec::new())
+    }
 }
 
 // ============================================================================
@@ -300,6 +743,7 @@ impl HealthChecker for DefaultHealthChecker {
             "service".to_string(),
             HealthCheckResult {
                 healthy: true,
+                warning: false,
                 message: "Service is running".to_string(),
                 duration_ms: start.elapsed().as_millis() as u64,
             },
@@ -320,6 +764,7 @@ impl HealthChecker for DefaultHealthChecker {
             "service".to_string(),
             HealthCheckResult {
                 healthy: true,
+                warning: false,
                 message: "Service is running".to_string(),
                 duration_ms: start.elapsed().as_millis() as u64,
             },
@@ -360,14 +805,60 @@ impl HealthChecker for DefaultHealthChecker {
 /// - **Ready** (`true`): ≥ 1 provider registered.
 /// - **Not ready** (`false`): provider registry is empty (Kubernetes will not route
 ///   traffic until a subsequent `/ready` poll returns 200).
+///
+/// With a [`FreshnessMonitor`] attached, the deep health check also reports
+/// configuration and secret freshness as warnings. With a [`Supervisor`]
+/// attached, it reports each background job. With event storage attached, it
+/// reports the storage health, including failover to a secondary endpoint.
+/// With circuit breakers attached, it reports each circuit: an open circuit
+/// fails the check.
 pub struct ServiceHealthChecker {
     provider_registry: Arc<ProviderRegistry>,
+    freshness: Option<Arc<FreshnessMonitor>>,
+    supervisor: Option<Arc<Supervisor>>,
+    event_storage: Option<Arc<dyn BlobStorage>>,
+    circuit_breakers: Option<Arc<dyn CircuitBreakerGroup>>,
 }
 
 impl ServiceHealthChecker {
     /// Create a new checker bound to the given provider registry.
     pub fn new(provider_registry: Arc<ProviderRegistry>) -> Self {
-        Self { provider_registry }
+        Self {
+            provider_registry,
+            freshness: None,
+            supervisor: None,
+            event_storage: None,
+            circuit_breakers: None,
+        }
+    }
+
+    /// Report freshness warnings from `freshness` in the deep health check.
+    pub fn with_freshness(mut self, freshness: Arc<FreshnessMonitor>) -> Self {
+        self.freshness = Some(freshness);
+        self
+    }
+
+    /// Report the background jobs run by `supervisor` in the deep health
+    /// check. A job that was given up on fails the check.
+    pub fn with_supervisor(mut self, supervisor: Arc<Supervisor>) -> Self {
+        self.supervisor = Some(supervisor);
+        self
+    }
+
+    /// Report the health of `event_storage` in the deep health check. A
+    /// healthy storage that reports a message, such as one that failed over
+    /// to its secondary endpoint, is a warning.
+    pub fn with_event_storage(mut self, event_storage: Arc<dyn BlobStorage>) -> Self {
+        self.event_storage = Some(event_storage);
+        self
+    }
+
+    /// Report the circuits of `circuit_breakers` in the deep health check,
+    /// as `circuit_<name>`. An open circuit fails the check; a half-open
+    /// circuit probing for recovery is a warning.
+    pub fn with_circuit_breakers(mut self, circuit_breakers: Arc<dyn CircuitBreakerGroup>) -> Self {
+        self.circuit_breakers = Some(circuit_breakers);
+        self
     }
 }
 
@@ -383,6 +874,7 @@ impl HealthChecker for ServiceHealthChecker {
             "service".to_string(),
             HealthCheckResult {
                 healthy: true,
+                warning: false,
                 message: "Service is running".to_string(),
                 duration_ms: start.elapsed().as_millis() as u64,
             },
@@ -391,6 +883,7 @@ impl HealthChecker for ServiceHealthChecker {
             "providers".to_string(),
             HealthCheckResult {
                 healthy: providers_healthy,
+                warning: false,
                 message: format!("{} webhook provider(s) registered", provider_count),
                 duration_ms: start.elapsed().as_millis() as u64,
             },
@@ -413,6 +906,7 @@ impl HealthChecker for ServiceHealthChecker {
             "service".to_string(),
             HealthCheckResult {
                 healthy: true,
+                warning: false,
                 message: "Service is running".to_string(),
                 duration_ms: start.elapsed().as_millis() as u64,
             },
@@ -421,13 +915,68 @@ impl HealthChecker for ServiceHealthChecker {
             "providers".to_string(),
             HealthCheckResult {
                 healthy: providers_healthy,
+                warning: false,
                 message: format!("{} webhook provider(s) registered", provider_count),
                 duration_ms: start.elapsed().as_millis() as u64,
             },
         );
 
+        // Stale inputs are warnings only and never fail the check
+        if let Some(freshness) = &self.freshness {
+            let freshness_start = std::time::Instant::now();
+            let report = freshness.report().await;
+            for (name, mut result) in report.health_checks() {
+                result.duration_ms = freshness_start.elapsed().as_millis() as u64;
+                checks.insert(name, result);
+            }
+        }
+
+        let mut jobs_healthy = true;
+        if let Some(supervisor) = &self.supervisor {
+            for (name, result) in supervisor.health_checks() {
+                jobs_healthy &= result.healthy;
+                checks.insert(name, result);
+            }
+        }
+
+        let mut storage_healthy = true;
+        if let Some(storage) = &self.event_storage {
+            let storage_start = std::time::Instant::now();
+            let result = match storage.health_check().await {
+                Ok(health) => HealthCheckResult {
+                    healthy: health.healthy,
+                    warning: health.healthy && health.error_message.is_some(),
+                    message: health.error_message.unwrap_or_else(|| {
+                        if health.healthy {
+                            "Event storage is available".to_string()
+                        } else {
+                            "Event storage is unavailable".to_string()
+                        }
+                    }),
+                    duration_ms: storage_start.elapsed().as_millis() as u64,
+                },
+                Err(e) => HealthCheckResult {
+                    healthy: false,
+                    warning: false,
+                    message: format!("Event storage health check failed: {}", e),
+                    duration_ms: storage_start.elapsed().as_millis() as u64,
+                },
+            };
+            storage_healthy = result.healthy;
+            checks.insert("event_storage".to_string(), result);
+        }
+
+        let mut circuits_healthy = true;
+        if let Some(circuit_breakers) = &self.circuit_breakers {
+            for (name, state) in circuit_breakers.circuit_states() {
+                let result = circuit_health_check(&name, state);
+                circuits_healthy &= result.healthy;
+                checks.insert(format!("circuit_{}", name), result);
+            }
+        }
+
         HealthStatus {
-            is_healthy: providers_healthy,
+            is_healthy: providers_healthy && jobs_healthy && storage_healthy && circuits_healthy,
             checks,
         }
     }
@@ -440,6 +989,29 @@ impl HealthChecker for ServiceHealthChecker {
     }
 }
 
+/// Deep health check entry for the circuit `name` in `state`.
+fn circuit_health_check(name: &str, state: CircuitState) -> HealthCheckResult {
+    let (healthy, warning, message) = match state {
+        CircuitState::Closed => (true, false, format!("Circuit {} is closed", name)),
+        CircuitState::HalfOpen => (
+            true,
+            true,
+            format!("Circuit {} is half-open; probing for recovery", name),
+        ),
+        CircuitState::Open => (
+            false,
+            false,
+            format!("Circuit {} is open; requests fail fast", name),
+        ),
+    };
+    HealthCheckResult {
+        healthy,
+        warning,
+        message,
+        duration_ms: 0,
+    }
+}
+
 // ============================================================================
 // Blob-Backed Event Store
 // ============================================================================
@@ -546,6 +1118,30 @@ fn extract_repository_from_wrapped_event(event: &WrappedEvent) -> Option<Reposit
     ))
 }
 
+/// Repository placeholder built from an `owner/repo` full name, for sessions
+/// whose events carry no repository metadata.
+fn repository_from_full_name(full_name: &str) -> Repository {
+    use queue_keeper_core::{RepositoryId, User, UserId, UserType};
+
+    let (owner, name) = full_name.split_once('/').unwrap_or(("unknown", "unknown"));
+    Repository::new(
+        RepositoryId::new(0),
+        name.to_string(),
+        full_name.to_string(),
+        User {
+            id: UserId::new(0),
+            login: owner.to_string(),
+            user_type: UserType::User,
+        },
+        false,
+    )
+}
+
+/// Maximum number of parent references followed when resolving lineage.
+///
+/// Guards against reference cycles in corrupted or hand-edited envelopes.
+const MAX_LINEAGE_DEPTH: usize = 100;
+
 /// Event store backed by blob storage.
 ///
 /// Persisted [`WrappedEvent`] objects are read from the blob storage instance
@@ -566,23 +1162,34 @@ fn extract_repository_from_wrapped_event(event: &WrappedEvent) -> Option<Reposit
 /// report a meaningful `uptime_seconds` value.
 pub struct BlobBackedEventStore {
     storage: Arc<dyn BlobStorage>,
+    tombstones: Arc<EventTombstones>,
     started_at: Instant,
 }
 
 impl BlobBackedEventStore {
     /// Create a new store wrapping the provided blob storage.
+    ///
+    /// Tombstones of deleted events are kept in memory; see
+    /// [`with_tombstones`](Self::with_tombstones).
     pub fn new(storage: Arc<dyn BlobStorage>) -> Self {
         Self {
             storage,
+            tombstones: Arc::new(EventTombstones::default()),
             started_at: Instant::now(),
         }
     }
 
+    /// Record event deletions in `tombstones`.
+    pub fn with_tombstones(mut self, tombstones: Arc<EventTombstones>) -> Self {
+        self.tombstones = tombstones;
+        self
+    }
+
     /// Deserialise a [`WrappedEvent`] from a [`StoredWebhook`] body.
     fn deserialise_event(
         stored: &queue_keeper_core::blob_storage::StoredWebhook,
     ) -> Option<WrappedEvent> {
-        match serde_json::from_slice(&stored.payload.body) {
+        match deserialize_envelope(&stored.payload.body) {
             Ok(event) => Some(event),
             Err(e) => {
                 warn!(
@@ -649,13 +1256,24 @@ impl BlobBackedEventStore {
         }
     }
 
-    /// Load all stored events, deserialising each blob body.
+    /// Load all stored events that are not deleted, deserialising each blob
+    /// body.
     ///
     /// Blobs that fail to deserialise (e.g. raw webhook payloads accidentally
     /// in the same storage) are silently skipped with a warning.
     async fn load_all_events(
         &self,
         filter: &PayloadFilter,
+    ) -> Result<Vec<WrappedEvent>, QueueKeeperError> {
+        self.load_events(filter, false).await
+    }
+
+    /// Load all stored events, including the deleted ones when
+    /// `include_deleted` is set.
+    async fn load_events(
+        &self,
+        filter: &PayloadFilter,
+        include_deleted: bool,
     ) -> Result<Vec<WrappedEvent>, QueueKeeperError> {
         let blob_list = self
             .storage
@@ -665,6 +1283,9 @@ impl BlobBackedEventStore {
 
         let mut events = Vec::with_capacity(blob_list.len());
         for meta in blob_list {
+            if !include_deleted && self.tombstones.is_deleted(&meta.event_id) {
+                continue;
+            }
             match self.storage.get_payload(&meta.event_id).await {
                 Ok(Some(stored)) => {
                     if let Some(event) = Self::deserialise_event(&stored) {
@@ -706,17 +1327,8 @@ impl EventStore for BlobBackedEventStore {
         &self,
         params: EventListParams,
     ) -> Result<EventListResponse, QueueKeeperError> {
-        let page = params.page.unwrap_or(1).max(1);
-        let per_page = params.per_page.unwrap_or(50).clamp(1, 500);
-        let offset = (page - 1) * per_page;
-
-        let filter = PayloadFilter {
-            repository: params.repository.clone(),
-            event_type: params.event_type.clone(),
-            ..Default::default()
-        };
-
-        let all_events = self.load_all_events(&filter).await?;
+        let cursor = PageCursor::from_param(params.cursor.as_deref())?;
+        let limit = params.limit.unwrap_or(50).clamp(1, 500);
 
         // Parse `since` as an RFC 3339 timestamp. Invalid values are logged
         // and treated as absent so callers receive a predictable result set
@@ -733,12 +1345,21 @@ impl EventStore for BlobBackedEventStore {
                     }
                 });
 
-        // Apply in-memory filters (since, session_id) not supported at blob-list level.
-        // TODO: push the `since` cutoff down to the storage layer via
-        // `PayloadFilter.date_range` once that field is wired in the blob-storage
-        // implementations, eliminating the need to deserialise all blobs before
-        // filtering.
-        let filtered: Vec<&WrappedEvent> = all_events
+        // The cutoff is pushed down so that stores honouring `date_range`
+        // never load older events; it is re-applied below for those that
+        // do not.
+        let filter = PayloadFilter {
+            repository: params.repository.clone(),
+            event_type: params.event_type.clone(),
+            date_range: since_ts.map(DateRange::since),
+            ..Default::default()
+        };
+
+        let include_deleted = params.include_deleted.unwrap_or(false);
+        let all_events = self.load_events(&filter, include_deleted).await?;
+
+        // Apply in-memory filters not supported at blob-list level.
+        let mut filtered: Vec<(EventId, &WrappedEvent)> = all_events
             .iter()
             .filter(|e| since_ts.is_none_or(|ts| e.received_at >= ts))
             .filter(|e| {
@@ -749,30 +1370,42 @@ impl EventStore for BlobBackedEventStore {
                         .unwrap_or(false)
                 })
             })
+            .map(|e| (e.event_id, e))
             .collect();
+        filtered.sort_by(|a, b| b.0.cmp(&a.0));
+
+        let total = params
+            .include_total
+            .unwrap_or(true)
+            .then_some(filtered.len());
+        let (page, next_cursor) = paginate(filtered, cursor, limit);
 
-        let total = filtered.len();
-        let events = filtered
+        let events = page
             .into_iter()
-            .skip(offset)
-            .take(per_page)
-            .map(Self::to_event_summary)
+            .map(|event| {
+                let mut summary = Self::to_event_summary(event);
+                if include_deleted && self.tombstones.is_deleted(&event.event_id) {
+                    summary.status = "deleted".to_string();
+                }
+                summary
+            })
             .collect();
 
         Ok(EventListResponse {
             events,
+            next_cursor,
+            limit,
             total,
-            page,
-            per_page,
         })
     }
 
     async fn get_event(&self, event_id: &EventId) -> Result<WrappedEvent, QueueKeeperError> {
         match self.storage.get_payload(event_id).await {
-            Ok(Some(stored)) => serde_json::from_slice::<WrappedEvent>(&stored.payload.body)
-                .map_err(|e| QueueKeeperError::Internal {
+            Ok(Some(stored)) => {
+                deserialize_envelope(&stored.payload.body).map_err(|e| QueueKeeperError::Internal {
                     message: format!("Failed to deserialise event {}: {}", event_id, e),
-                }),
+                })
+            }
             Ok(None) => Err(QueueKeeperError::NotFound {
                 resource: "event".to_string(),
                 id: event_id.to_string(),
@@ -781,10 +1414,37 @@ impl EventStore for BlobBackedEventStore {
         }
     }
 
+    async fn get_lineage(&self, event_id: &EventId) -> Result<LineageNode, QueueKeeperError> {
+        let all_events = self.load_all_events(&PayloadFilter::default()).await?;
+        let by_id: HashMap<EventId, &WrappedEvent> =
+            all_events.iter().map(|e| (e.event_id, e)).collect();
+
+        let mut top = *by_id
+            .get(event_id)
+            .ok_or_else(|| QueueKeeperError::NotFound {
+                resource: "event".to_string(),
+                id: event_id.to_string(),
+            })?;
+        for _ in 0..MAX_LINEAGE_DEPTH {
+            match top.lineage.and_then(|l| by_id.get(&l.parent_event_id)) {
+                Some(parent) => top = parent,
+                None => break,
+            }
+        }
+
+        LineageNode::build_tree(top.event_id, &all_events).ok_or_else(|| {
+            QueueKeeperError::NotFound {
+                resource: "event".to_string(),
+                id: event_id.to_string(),
+            }
+        })
+    }
+
     async fn list_sessions(
         &self,
         params: SessionListParams,
     ) -> Result<SessionListResponse, QueueKeeperError> {
+        let cursor = PageCursor::from_param(params.cursor.as_deref())?;
         let filter = PayloadFilter {
             repository: params.repository.clone(),
             ..Default::default()
@@ -804,9 +1464,11 @@ impl EventStore for BlobBackedEventStore {
         }
 
         // Apply entity_type filter if provided
-        let limit = params.limit.unwrap_or(usize::MAX);
+        let limit = params.limit.unwrap_or(usize::MAX).max(1);
 
-        let mut sessions: Vec<SessionSummary> = session_map
+        // Each session is keyed by its newest event ID, which orders sessions
+        // by last activity and gives the cursor a stable position.
+        let mut sessions: Vec<(EventId, SessionSummary)> = session_map
             .iter()
             .filter(|(sid, _events)| {
                 // entity_type filter: session_id format is owner/repo/entity_type/entity_id
@@ -846,7 +1508,13 @@ impl EventStore for BlobBackedEventStore {
                 let entity_type = parts.get(2).copied().unwrap_or("unknown").to_string();
                 let entity_id = parts.get(3).copied().unwrap_or("0").to_string();
 
-                SessionSummary {
+                let newest_event_id = events
+                    .iter()
+                    .map(|e| e.event_id)
+                    .max()
+                    .unwrap_or(first_event.event_id);
+
+                let summary = SessionSummary {
                     session_id: first_event.session_id.clone().unwrap_or_else(|| {
                         SessionId::from_parts("unknown", "unknown", "unknown", "0")
                     }),
@@ -855,16 +1523,26 @@ impl EventStore for BlobBackedEventStore {
                     entity_id,
                     status: "active".to_string(),
                     event_count: events.len() as u32,
+                    pending_events: 0,
                     last_activity,
-                }
+                };
+                (newest_event_id, summary)
             })
             .collect();
 
-        sessions.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+        sessions.sort_by(|a, b| b.0.cmp(&a.0));
 
-        let total = sessions.len(); // total before applying the limit
-        sessions.truncate(limit);
-        Ok(SessionListResponse { sessions, total })
+        // Total is counted before the cursor and limit are applied
+        let total = params
+            .include_total
+            .unwrap_or(true)
+            .then_some(sessions.len());
+        let (sessions, next_cursor) = paginate(sessions, cursor, limit);
+        Ok(SessionListResponse {
+            sessions,
+            next_cursor,
+            total,
+        })
     }
 
     async fn get_session(
@@ -918,25 +1596,8 @@ impl EventStore for BlobBackedEventStore {
             .expect("session_events non-empty after is_empty guard");
         // Use the shared helper for payload-based repository extraction; fall back
         // to session_id parts when the payload lacks repository metadata.
-        let repository = extract_repository_from_wrapped_event(first_event).unwrap_or_else(|| {
-            use queue_keeper_core::{Repository, RepositoryId, User, UserId, UserType};
-            let (owner, repo) = if parts.len() >= 2 {
-                (parts[0].to_string(), parts[1].to_string())
-            } else {
-                ("unknown".to_string(), "unknown".to_string())
-            };
-            Repository::new(
-                RepositoryId::new(0),
-                repo.clone(),
-                repo_full_name.clone(),
-                User {
-                    id: UserId::new(0),
-                    login: owner,
-                    user_type: UserType::User,
-                },
-                false,
-            )
-        });
+        let repository = extract_repository_from_wrapped_event(first_event)
+            .unwrap_or_else(|| repository_from_full_name(&repo_full_name));
 
         let event_summaries: Vec<EventSummary> = session_events
             .iter()
@@ -952,6 +1613,7 @@ impl EventStore for BlobBackedEventStore {
             created_at,
             last_activity,
             event_count: event_summaries.len() as u32,
+            pending_events: 0,
             events: event_summaries,
         })
     }
@@ -968,7 +1630,23 @@ impl EventStore for BlobBackedEventStore {
         // immediately visible rather than silently hidden behind partial stats.
         let all_events = self.load_all_events(&PayloadFilter::default()).await?;
 
-        let total_events = all_events.len() as u64;
+        // Injected events (replays, self-test events, imports) are delivered
+        // like any other event but are not part of the intake rate.
+        let mut events_by_origin = BTreeMap::new();
+        for event in &all_events {
+            *events_by_origin.entry(event.origin).or_insert(0u64) += 1;
+        }
+        let live_events: Vec<&WrappedEvent> = all_events
+            .iter()
+            .filter(|e| e.origin.counts_toward_baselines())
+            .collect();
+        let total_events = live_events.len() as u64;
+        let mut events_by_type = BTreeMap::new();
+        for event in &live_events {
+            *events_by_type
+                .entry(event.event_type.clone())
+                .or_insert(0u64) += 1;
+        }
 
         // Count distinct session IDs from the loaded event bodies.
         let active_sessions = all_events
@@ -979,8 +1657,8 @@ impl EventStore for BlobBackedEventStore {
 
         // Estimate events per hour from oldest and newest received_at timestamps.
         let events_per_hour = if total_events >= 2 {
-            let oldest = all_events.iter().map(|e| e.received_at).min();
-            let newest = all_events.iter().map(|e| e.received_at).max();
+            let oldest = live_events.iter().map(|e| e.received_at).min();
+            let newest = live_events.iter().map(|e| e.received_at).max();
             if let (Some(oldest), Some(newest)) = (oldest, newest) {
                 let span_secs = newest
                     .as_datetime()
@@ -1003,8 +1681,51 @@ impl EventStore for BlobBackedEventStore {
             active_sessions,
             error_rate: 0.0,
             uptime_seconds,
+            events_by_type,
+            events_by_origin,
         })
     }
+
+    async fn delete_event(
+        &self,
+        event_id: &EventId,
+        deleted_by: &str,
+        reason: &str,
+    ) -> Result<EventTombstone, QueueKeeperError> {
+        // Only stored events can be deleted
+        self.get_event(event_id).await?;
+        self.tombstones
+            .delete(*event_id, deleted_by, reason)
+            .map_err(|e| QueueKeeperError::Internal {
+                message: e.to_string(),
+            })
+    }
+
+    async fn purge_deleted(&self, now: Timestamp) -> Result<Vec<EventTombstone>, QueueKeeperError> {
+        let mut purged = Vec::new();
+        for tombstone in self.tombstones.due(now) {
+            match self.storage.delete_payload(&tombstone.event_id).await {
+                Ok(()) | Err(BlobStorageError::BlobNotFound { .. }) => {}
+                Err(e) => {
+                    warn!(
+                        event_id = %tombstone.event_id,
+                        error = %e,
+                        "Failed to purge deleted event; retrying at the next purge"
+                    );
+                    continue;
+                }
+            }
+            if let Err(e) = self.tombstones.remove(&tombstone.event_id) {
+                warn!(
+                    event_id = %tombstone.event_id,
+                    error = %e,
+                    "Purged event but failed to remove its tombstone"
+                );
+            }
+            purged.push(tombstone);
+        }
+        Ok(purged)
+    }
 }
 
 /// Default event store implementation
@@ -1017,11 +1738,12 @@ impl EventStore for DefaultEventStore {
         params: EventListParams,
     ) -> Result<EventListResponse, QueueKeeperError> {
         // For now, return empty list - implementation will come with storage integration
+        PageCursor::from_param(params.cursor.as_deref())?;
         Ok(EventListResponse {
             events: vec![],
-            total: 0,
-            page: params.page.unwrap_or(1),
-            per_page: params.per_page.unwrap_or(50),
+            next_cursor: None,
+            limit: params.limit.unwrap_or(50),
+            total: params.include_total.unwrap_or(true).then_some(0),
         })
     }
 
@@ -1038,10 +1760,11 @@ impl EventStore for DefaultEventStore {
         params: SessionListParams,
     ) -> Result<SessionListResponse, QueueKeeperError> {
         // For now, return empty list - implementation will come with storage integration
-        let _ = params; // Silence unused warning
+        PageCursor::from_param(params.cursor.as_deref())?;
         Ok(SessionListResponse {
             sessions: vec![],
-            total: 0,
+            next_cursor: None,
+            total: params.include_total.unwrap_or(true).then_some(0),
         })
     }
 
@@ -1064,6 +1787,8 @@ impl EventStore for DefaultEventStore {
             active_sessions: 0,
             error_rate: 0.0,
             uptime_seconds: 0,
+            events_by_type: BTreeMap::new(),
+            events_by_origin: BTreeMap::new(),
         })
     }
 }
@@ -224,6 +224,78 @@ mod service_health_checker_tests {
             "check_basic_health and check_deep_health must agree on is_healthy"
         );
     }
+
+    /// Verify that attached event storage is reported by check_deep_health()
+    /// and that unavailable storage fails the check.
+    #[tokio::test]
+    async fn test_check_deep_health_reports_event_storage() {
+        use queue_keeper_core::adapters::filesystem_storage::FilesystemBlobStorage;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events");
+        let storage = FilesystemBlobStorage::new(path.clone()).await.unwrap();
+        let checker =
+            ServiceHealthChecker::new(populated_registry()).with_event_storage(Arc::new(storage));
+
+        let status = checker.check_deep_health().await;
+        let storage_check = status
+            .checks
+            .get("event_storage")
+            .expect("'event_storage' check must be present");
+        assert!(status.is_healthy);
+        assert!(storage_check.healthy);
+        assert!(!storage_check.warning);
+
+        std::fs::remove_dir_all(&path).unwrap();
+        let status = checker.check_deep_health().await;
+        assert!(!status.is_healthy);
+        assert!(!status.checks["event_storage"].healthy);
+    }
+
+    /// Circuit breaker group reporting fixed states.
+    struct FixedCircuits(Vec<(String, CircuitState)>);
+
+    impl CircuitBreakerGroup for FixedCircuits {
+        fn circuit_states(&self) -> Vec<(String, CircuitState)> {
+            self.0.clone()
+        }
+
+        fn attach_metrics(
+            &self,
+            _metrics: Arc<dyn queue_keeper_core::monitoring::MetricsCollector>,
+        ) {
+        }
+    }
+
+    /// Verify that check_deep_health() reports every circuit, warns about
+    /// half-open circuits and fails on open ones.
+    #[tokio::test]
+    async fn test_check_deep_health_reports_circuit_breakers() {
+        let checker = |states: Vec<(&str, CircuitState)>| {
+            let states = states
+                .into_iter()
+                .map(|(name, state)| (name.to_string(), state))
+                .collect();
+            ServiceHealthChecker::new(populated_registry())
+                .with_circuit_breakers(Arc::new(FixedCircuits(states)))
+        };
+
+        let status = checker(vec![
+            ("queue:bot-a", CircuitState::Closed),
+            ("queue:bot-b", CircuitState::HalfOpen),
+        ])
+        .check_deep_health()
+        .await;
+        assert!(status.is_healthy);
+        assert!(!status.checks["circuit_queue:bot-a"].warning);
+        assert!(status.checks["circuit_queue:bot-b"].warning);
+
+        let status = checker(vec![("queue:bot-a", CircuitState::Open)])
+            .check_deep_health()
+            .await;
+        assert!(!status.is_healthy);
+        assert!(!status.checks["circuit_queue:bot-a"].healthy);
+    }
 }
 
 // ============================================================================
@@ -237,7 +309,7 @@ mod blob_backed_event_store_tests {
     };
     use queue_keeper_core::adapters::filesystem_storage::FilesystemBlobStorage;
     use queue_keeper_core::blob_storage::BlobStorage;
-    use queue_keeper_core::webhook::WrappedEvent;
+    use queue_keeper_core::webhook::{EventOrigin, LineageCause, WrappedEvent};
     use queue_keeper_core::SessionId;
     use std::sync::Arc;
 
@@ -309,6 +381,47 @@ mod blob_backed_event_store_tests {
         let _ = std::fs::remove_dir_all(dir);
     }
 
+    /// `get_lineage` returns the whole tree from any event in the lineage.
+    #[tokio::test]
+    async fn test_get_lineage_returns_tree_from_any_member() {
+        use queue_keeper_core::webhook::LineageCause;
+
+        let (storage, dir) = make_storage("get-lineage").await;
+        let store = BlobBackedEventStore::new(Arc::clone(&storage));
+
+        let root = WrappedEvent::new(
+            "github".to_string(),
+            "push".to_string(),
+            None,
+            None,
+            serde_json::json!({}),
+            None,
+        );
+        let replay = root.derive(LineageCause::Replay);
+        let redrive = replay.derive(LineageCause::Redrive);
+        for event in [&root, &replay, &redrive] {
+            store_wrapped_event_to_blob(storage.as_ref(), event)
+                .await
+                .expect("store must succeed");
+        }
+
+        let tree = store
+            .get_lineage(&redrive.event_id)
+            .await
+            .expect("get_lineage must succeed");
+
+        assert_eq!(tree.event_id, root.event_id);
+        assert_eq!(tree.len(), 3);
+        assert_eq!(tree.children[0].event_id, replay.event_id);
+        assert_eq!(tree.children[0].children[0].event_id, redrive.event_id);
+        assert_eq!(
+            tree.children[0].children[0].cause,
+            Some(LineageCause::Redrive)
+        );
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
     /// `list_events` returns all stored events with correct pagination.
     #[tokio::test]
     async fn test_list_events_returns_stored_events() {
@@ -331,19 +444,21 @@ mod blob_backed_event_store_tests {
         }
 
         let params = EventListParams {
-            page: None,
-            per_page: None,
+            cursor: None,
+            limit: None,
+            include_total: None,
             event_type: None,
             repository: None,
             session_id: None,
             since: None,
+            include_deleted: None,
         };
         let response = store
             .list_events(params)
             .await
             .expect("list_events must succeed");
 
-        assert_eq!(response.total, 3, "must return 3 stored events");
+        assert_eq!(response.total, Some(3), "must return 3 stored events");
         assert_eq!(response.events.len(), 3);
         assert!(response.events.iter().all(|e| e.event_type == "push"));
 
@@ -373,16 +488,19 @@ mod blob_backed_event_store_tests {
 
         let params = EventListParams {
             event_type: Some("pull_request".to_string()),
-            page: None,
-            per_page: None,
+            cursor: None,
+            limit: None,
+            include_total: None,
             repository: None,
             session_id: None,
             since: None,
+            include_deleted: None,
         };
         let response = store.list_events(params).await.unwrap();
 
         assert_eq!(
-            response.total, 1,
+            response.total,
+            Some(1),
             "only pull_request events should be returned"
         );
         assert_eq!(response.events[0].event_type, "pull_request");
@@ -390,7 +508,8 @@ mod blob_backed_event_store_tests {
         let _ = std::fs::remove_dir_all(dir);
     }
 
-    /// `list_events` pagination: page 2 with per_page=1 returns the second event.
+    /// `list_events` pagination: cursors walk the events newest first without
+    /// gaps or repeats, even when events arrive between pages.
     #[tokio::test]
     async fn test_list_events_pagination() {
         let (storage, dir) = make_storage("list-events-page").await;
@@ -410,24 +529,98 @@ mod blob_backed_event_store_tests {
                 .unwrap();
         }
 
-        let params = EventListParams {
-            page: Some(1),
-            per_page: Some(2),
+        let params = |cursor: Option<String>| EventListParams {
+            cursor,
+            limit: Some(2),
+            include_total: None,
             event_type: None,
             repository: None,
             session_id: None,
             since: None,
+            include_deleted: None,
         };
-        let response = store.list_events(params).await.unwrap();
+        let first = store.list_events(params(None)).await.unwrap();
 
-        assert_eq!(response.total, 3, "total must include all events");
-        assert_eq!(
-            response.events.len(),
-            2,
-            "per_page=2 → only 2 items returned"
+        assert_eq!(first.total, Some(3), "total must include all events");
+        assert_eq!(first.events.len(), 2, "limit=2 → only 2 items returned");
+        assert_eq!(first.limit, 2);
+        assert!(first.events[0].event_id > first.events[1].event_id);
+
+        // A newer event must not shift the next page
+        let late = WrappedEvent::new(
+            "github".to_string(),
+            "push".to_string(),
+            None,
+            None,
+            serde_json::json!({}),
+            None,
         );
-        assert_eq!(response.page, 1);
-        assert_eq!(response.per_page, 2);
+        store_wrapped_event_to_blob(storage.as_ref(), &late)
+            .await
+            .unwrap();
+
+        let second = store.list_events(params(first.next_cursor)).await.unwrap();
+
+        assert_eq!(second.events.len(), 1);
+        assert!(second.events[0].event_id < first.events[1].event_id);
+        assert_eq!(second.next_cursor, None, "last page has no cursor");
+        assert_eq!(second.total, Some(4));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    /// Totals are omitted when `include_total=false`.
+    #[tokio::test]
+    async fn test_list_total_opt_out() {
+        let (storage, dir) = make_storage("list-total-opt-out").await;
+        let store = BlobBackedEventStore::new(Arc::clone(&storage));
+
+        let e = WrappedEvent::new(
+            "github".to_string(),
+            "issues".to_string(),
+            None,
+            Some(SessionId::from_parts("owner", "repo", "issues", "1")),
+            serde_json::json!({}),
+            None,
+        );
+        store_wrapped_event_to_blob(storage.as_ref(), &e)
+            .await
+            .unwrap();
+
+        let events = store
+            .list_events(EventListParams {
+                cursor: None,
+                limit: None,
+                include_total: Some(false),
+                event_type: None,
+                repository: None,
+                session_id: None,
+                since: None,
+                include_deleted: None,
+            })
+            .await
+            .unwrap();
+        let sessions = store
+            .list_sessions(SessionListParams {
+                repository: None,
+                entity_type: None,
+                status: None,
+                pending: None,
+                cursor: None,
+                limit: None,
+                include_total: Some(false),
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(events.events.len(), 1);
+        assert_eq!(events.total, None);
+        assert!(serde_json::to_value(&events)
+            .unwrap()
+            .get("total")
+            .is_none());
+        assert_eq!(sessions.sessions.len(), 1);
+        assert_eq!(sessions.total, None);
 
         let _ = std::fs::remove_dir_all(dir);
     }
@@ -460,11 +653,14 @@ mod blob_backed_event_store_tests {
             repository: None,
             entity_type: None,
             status: None,
+            pending: None,
+            cursor: None,
             limit: None,
+            include_total: None,
         };
         let response = store.list_sessions(params).await.unwrap();
 
-        assert_eq!(response.total, 2, "must return 2 distinct sessions");
+        assert_eq!(response.total, Some(2), "must return 2 distinct sessions");
 
         let session_a_summary = response
             .sessions
@@ -577,6 +773,46 @@ mod blob_backed_event_store_tests {
         let _ = std::fs::remove_dir_all(dir);
     }
 
+    /// Replayed and synthetic events are reported per origin but not counted
+    /// as intake.
+    #[tokio::test]
+    async fn test_get_statistics_excludes_injected_events() {
+        let (storage, dir) = make_storage("get-statistics-origin").await;
+        let store = BlobBackedEventStore::new(Arc::clone(&storage));
+
+        let live = WrappedEvent::new(
+            "github".to_string(),
+            "push".to_string(),
+            None,
+            None,
+            serde_json::json!({}),
+            None,
+        );
+        let replay = live.derive(LineageCause::Replay);
+        let synthetic = WrappedEvent::new(
+            "github".to_string(),
+            "ping".to_string(),
+            None,
+            None,
+            serde_json::json!({}),
+            None,
+        )
+        .with_origin(EventOrigin::Synthetic);
+        for e in [&live, &replay, &synthetic] {
+            store_wrapped_event_to_blob(storage.as_ref(), e)
+                .await
+                .unwrap();
+        }
+
+        let stats = store.get_statistics().await.unwrap();
+        assert_eq!(stats.total_events, 1);
+        assert_eq!(stats.events_by_origin[&EventOrigin::Github], 1);
+        assert_eq!(stats.events_by_origin[&EventOrigin::Replay], 1);
+        assert_eq!(stats.events_by_origin[&EventOrigin::Synthetic], 1);
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
     /// `store_wrapped_event_to_blob` on a tampered event causes `get_event` to fail
     /// with `QueueKeeperError::Internal` (wrapping the `ChecksumMismatch`).
     #[tokio::test]
@@ -673,17 +909,20 @@ mod blob_backed_event_store_tests {
             .unwrap();
 
         let params = EventListParams {
-            page: None,
-            per_page: None,
+            cursor: None,
+            limit: None,
+            include_total: None,
             event_type: None,
             repository: None,
             session_id: None,
             since: Some(cutoff.to_rfc3339()),
+            include_deleted: None,
         };
         let response = store.list_events(params).await.unwrap();
 
         assert_eq!(
-            response.total, 1,
+            response.total,
+            Some(1),
             "only the event after the cutoff should be returned"
         );
 
@@ -735,7 +974,10 @@ mod blob_backed_event_store_tests {
             repository: None,
             entity_type: None,
             status: None,
+            pending: None,
+            cursor: None,
             limit: Some(1),
+            include_total: None,
         };
         let response = store.list_sessions(params).await.unwrap();
 
@@ -749,10 +991,218 @@ mod blob_backed_event_store_tests {
             "the most recently active session must be returned when limit=1"
         );
         assert_eq!(
-            response.total, 2,
+            response.total,
+            Some(2),
             "total includes all sessions before the limit"
         );
 
+        // The cursor continues with the older session
+        let next = store
+            .list_sessions(SessionListParams {
+                repository: None,
+                entity_type: None,
+                status: None,
+                pending: None,
+                cursor: response.next_cursor,
+                limit: Some(1),
+                include_total: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(next.sessions.len(), 1);
+        assert_eq!(next.sessions[0].session_id, session_a);
+        assert_eq!(next.next_cursor, None);
+
         let _ = std::fs::remove_dir_all(dir);
     }
 }
+
+// ============================================================================
+// PageCursor tests
+// ============================================================================
+
+mod page_cursor_tests {
+    use super::*;
+
+    /// Cursors round-trip and do not expose the raw event ID.
+    #[test]
+    fn test_cursor_round_trip() {
+        let event_id = EventId::new();
+        let encoded = PageCursor::after(event_id).encode();
+
+        assert!(!encoded.contains(&event_id.to_string()));
+        assert_eq!(PageCursor::decode(&encoded).unwrap().event_id(), event_id);
+    }
+
+    /// Arbitrary strings and bare event IDs are rejected.
+    #[test]
+    fn test_cursor_rejects_foreign_values() {
+        assert!(PageCursor::decode("not-a-cursor").is_err());
+        assert!(PageCursor::decode(&EventId::new().to_string()).is_err());
+        assert!(PageCursor::decode(&hex::encode("v1:not-a-ulid")).is_err());
+    }
+}
+
+// ============================================================================
+// Tracked session tests
+// ============================================================================
+
+mod tracked_session_tests {
+    use super::*;
+    use queue_keeper_core::{SessionTrackerConfig, TrackedEventStatus, Ulid};
+
+    /// Event in `session` whose ID orders after every event created before.
+    fn event(session: &str, sequence: u64) -> WrappedEvent {
+        let mut event = WrappedEvent::new(
+            "github".to_string(),
+            "pull_request".to_string(),
+            Some("opened".to_string()),
+            Some(SessionId::new(session.to_string()).unwrap()),
+            serde_json::json!({}),
+            None,
+        );
+        event.event_id = Ulid::from_parts(sequence, 0).to_string().parse().unwrap();
+        event
+    }
+
+    fn params() -> SessionListParams {
+        SessionListParams {
+            repository: None,
+            entity_type: None,
+            status: None,
+            pending: None,
+            cursor: None,
+            limit: None,
+            include_total: None,
+        }
+    }
+
+    /// Tracker with a delivered pull request session, a pending issue
+    /// session and a failed pull request session in another repository.
+    fn tracker() -> SessionTracker {
+        let tracker = SessionTracker::new(SessionTrackerConfig::default());
+        let delivered = event("owner/repo/pull_request/1", 1);
+        let pending = event("owner/repo/issue/2", 2);
+        let failed = event("owner/other/pull_request/3", 3);
+        for event in [&delivered, &pending, &failed] {
+            tracker.record_received(event);
+        }
+        for (event, status) in [
+            (&delivered, TrackedEventStatus::Delivered),
+            (&failed, TrackedEventStatus::Failed),
+        ] {
+            tracker.record_outcome(event.session_id.as_ref().unwrap(), event.event_id, status);
+        }
+        tracker
+    }
+
+    fn session_ids(response: &SessionListResponse) -> Vec<&str> {
+        response
+            .sessions
+            .iter()
+            .map(|s| s.session_id.as_str())
+            .collect()
+    }
+
+    /// Tracked sessions are filtered by repository, entity type, status and
+    /// pending events.
+    #[test]
+    fn test_list_tracked_sessions_filters() {
+        let tracker = tracker();
+
+        let by_repository = list_tracked_sessions(
+            &tracker,
+            &SessionListParams {
+                repository: Some("owner/repo".to_string()),
+                ..params()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            session_ids(&by_repository),
+            vec!["owner/repo/issue/2", "owner/repo/pull_request/1"]
+        );
+
+        let by_entity_type = list_tracked_sessions(
+            &tracker,
+            &SessionListParams {
+                entity_type: Some("pull_request".to_string()),
+                ..params()
+            },
+        )
+        .unwrap();
+        assert_eq!(by_entity_type.total, Some(2));
+
+        let failed = list_tracked_sessions(
+            &tracker,
+            &SessionListParams {
+                status: Some("failed".to_string()),
+                ..params()
+            },
+        )
+        .unwrap();
+        assert_eq!(session_ids(&failed), vec!["owner/other/pull_request/3"]);
+
+        let pending = list_tracked_sessions(
+            &tracker,
+            &SessionListParams {
+                pending: Some(true),
+                ..params()
+            },
+        )
+        .unwrap();
+        assert_eq!(session_ids(&pending), vec!["owner/repo/issue/2"]);
+        assert_eq!(pending.sessions[0].pending_events, 1);
+    }
+
+    /// Tracked sessions page newest first with continuation cursors.
+    #[test]
+    fn test_list_tracked_sessions_pages() {
+        let tracker = tracker();
+
+        let first = list_tracked_sessions(
+            &tracker,
+            &SessionListParams {
+                limit: Some(2),
+                ..params()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            session_ids(&first),
+            vec!["owner/other/pull_request/3", "owner/repo/issue/2"]
+        );
+        assert_eq!(first.total, Some(3));
+
+        let second = list_tracked_sessions(
+            &tracker,
+            &SessionListParams {
+                cursor: first.next_cursor,
+                limit: Some(2),
+                ..params()
+            },
+        )
+        .unwrap();
+        assert_eq!(session_ids(&second), vec!["owner/repo/pull_request/1"]);
+        assert_eq!(second.next_cursor, None);
+    }
+
+    /// Session details carry the tracked status and latest events.
+    #[test]
+    fn test_session_details_from_tracked_session() {
+        let tracker = tracker();
+        let tracked = tracker
+            .get(&SessionId::new("owner/other/pull_request/3".to_string()).unwrap())
+            .unwrap();
+
+        let details = SessionDetails::from(tracked);
+
+        assert_eq!(details.status, "failed");
+        assert_eq!(details.repository.full_name, "owner/other");
+        assert_eq!(details.entity_type, "pull_request");
+        assert_eq!(details.entity_id, "3");
+        assert_eq!(details.event_count, 1);
+        assert_eq!(details.events.len(), 1);
+        assert_eq!(details.events[0].status, "failed");
+    }
+}
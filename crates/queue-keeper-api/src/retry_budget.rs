@@ -0,0 +1,249 @@
+//! Global retry budget.
+//!
+//! Each delivery retries its own transient failures with backoff, which is
+//! harmless for one event but multiplies load when a dependency browns out
+//! and every in-flight delivery retries at once. The [`RetryBudget`] caps
+//! the retries of all delivery workers together at a fraction of the
+//! successful sends of the last `window`:
+//!
+//! ```text
+//! retries allowed in window = max(min_retries, ratio × successful sends in window)
+//! ```
+//!
+//! A retry beyond the budget is not dropped: it is deferred by `deferral`
+//! and tried again, so deliveries trickle through while the budget is
+//! exhausted and resume at full rate once sends succeed again. Every
+//! deferral increments `retry_budget_exhausted_total`, and the transition
+//! into and out of exhaustion is logged.
+
+use crate::metrics::ServiceMetrics;
+use queue_keeper_core::units::human_duration;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+/// Number of buckets the sliding window is divided into.
+const WINDOW_BUCKETS: u32 = 10;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Retry budget settings (the `delivery.retry_budget` configuration section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// delivery:
+///   retry_budget:
+///     enabled: true
+///     ratio: 0.1
+///     min_retries: 10
+///     window: 10s
+///     deferral: 30s
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetryBudgetConfig {
+    /// Whether retries are limited. Off by default.
+    pub enabled: bool,
+
+    /// Retries allowed per successful send in the window.
+    pub ratio: f64,
+
+    /// Retries always allowed in the window, so that deliveries recover
+    /// when nothing has succeeded recently.
+    pub min_retries: u32,
+
+    /// Sliding window over which sends and retries are counted.
+    #[serde(with = "human_duration")]
+    pub window: Duration,
+
+    /// How long a retry beyond the budget waits before trying again.
+    #[serde(with = "human_duration")]
+    pub deferral: Duration,
+}
+
+impl Default for RetryBudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ratio: 0.1,
+            min_retries: 10,
+            window: Duration::from_secs(10),
+            deferral: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryBudgetConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.ratio.is_finite() || self.ratio < 0.0 {
+            return Err(format!(
+                "retry_budget.ratio must be zero or positive (got {})",
+                self.ratio
+            ));
+        }
+        if self.ratio == 0.0 && self.min_retries == 0 {
+            return Err(
+                "retry_budget.ratio and retry_budget.min_retries must not both be zero".to_string(),
+            );
+        }
+        if self.window < Duration::from_secs(1) {
+            return Err("retry_budget.window must be at least 1s".to_string());
+        }
+        if self.deferral.is_zero() {
+            return Err("retry_budget.deferral must not be zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Retry Budget
+// ============================================================================
+
+/// Sends and retries counted in one slice of the window.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    started: Instant,
+    sends: u64,
+    retries: u64,
+}
+
+/// Retry budget shared by every delivery worker.
+///
+/// A disabled budget (the default) allows every retry.
+#[derive(Debug, Default)]
+pub struct RetryBudget {
+    config: RetryBudgetConfig,
+    buckets: Mutex<VecDeque<Bucket>>,
+    exhausted: AtomicBool,
+    metrics: Option<Arc<ServiceMetrics>>,
+}
+
+impl RetryBudget {
+    /// Create a budget from its configuration.
+    pub fn new(config: RetryBudgetConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    /// Count deferred retries in `retry_budget_exhausted_total`.
+    pub fn with_metrics(mut self, metrics: Arc<ServiceMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Whether retries are limited.
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// How long a retry beyond the budget waits before trying again.
+    pub fn deferral(&self) -> Duration {
+        self.config.deferral
+    }
+
+    /// Record `count` successful sends.
+    pub fn record_sends(&self, count: usize) {
+        if self.is_enabled() && count > 0 {
+            self.record_sends_at(count, Instant::now());
+        }
+    }
+
+    /// Take `retries` retries from the budget.
+    ///
+    /// Returns `false`, and counts the deferral, when the budget does not
+    /// allow them; the caller should wait [`Self::deferral`] and ask again.
+    pub fn try_acquire(&self, retries: usize) -> bool {
+        if !self.is_enabled() {
+            return true;
+        }
+        let acquired = self.try_acquire_at(retries, Instant::now());
+        if acquired {
+            if self.exhausted.swap(false, Ordering::Relaxed) {
+                info!("Retry budget recovered; deferred retries resume");
+            }
+        } else {
+            if !self.exhausted.swap(true, Ordering::Relaxed) {
+                warn!(
+                    ratio = self.config.ratio,
+                    min_retries = self.config.min_retries,
+                    deferral_ms = self.config.deferral.as_millis() as u64,
+                    "Retry budget exhausted; deferring delivery retries"
+                );
+            }
+            if let Some(metrics) = &self.metrics {
+                metrics.retry_budget_exhausted_total.inc();
+            }
+        }
+        acquired
+    }
+
+    fn record_sends_at(&self, count: usize, now: Instant) {
+        let mut buckets = self.buckets.lock().unwrap();
+        self.current_bucket(&mut buckets, now).sends += count as u64;
+    }
+
+    fn try_acquire_at(&self, retries: usize, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = self.current_bucket(&mut buckets, now);
+        bucket.retries += retries as u64;
+
+        let (sends, spent) = buckets.iter().fold((0, 0), |(sends, spent), b| {
+            (sends + b.sends, spent + b.retries)
+        });
+        let allowed = (self.config.ratio * sends as f64) as u64;
+        if spent <= allowed.max(u64::from(self.config.min_retries)) {
+            return true;
+        }
+
+        // Denied retries do not count against the budget.
+        buckets.back_mut().expect("current bucket exists").retries -= retries as u64;
+        false
+    }
+
+    /// Drop buckets that left the window and return the bucket for `now`.
+    fn current_bucket<'a>(
+        &self,
+        buckets: &'a mut VecDeque<Bucket>,
+        now: Instant,
+    ) -> &'a mut Bucket {
+        let width = self.config.window / WINDOW_BUCKETS;
+        while buckets
+            .front()
+            .is_some_and(|b| now.duration_since(b.started) >= self.config.window)
+        {
+            buckets.pop_front();
+        }
+        if buckets
+            .back()
+            .is_none_or(|b| now.duration_since(b.started) >= width)
+        {
+            buckets.push_back(Bucket {
+                started: now,
+                sends: 0,
+                retries: 0,
+            });
+        }
+        buckets.back_mut().expect("current bucket exists")
+    }
+}
+
+#[cfg(test)]
+#[path = "retry_budget_tests.rs"]
+mod tests;
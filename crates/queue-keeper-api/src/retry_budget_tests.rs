@@ -0,0 +1,126 @@
+//! Tests for the global retry budget.
+
+use super::*;
+
+fn budget(ratio: f64, min_retries: u32) -> RetryBudget {
+    RetryBudget::new(RetryBudgetConfig {
+        enabled: true,
+        ratio,
+        min_retries,
+        ..RetryBudgetConfig::default()
+    })
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Verify that the retry budget is disabled by default.
+#[test]
+fn test_default_config_is_disabled_and_valid() {
+    let config = RetryBudgetConfig::default();
+
+    assert!(!config.enabled);
+    assert!(config.validate().is_ok());
+}
+
+/// Verify that settings which would block or never refill retries are rejected.
+#[test]
+fn test_invalid_settings_rejected() {
+    for config in [
+        RetryBudgetConfig {
+            ratio: -0.1,
+            ..RetryBudgetConfig::default()
+        },
+        RetryBudgetConfig {
+            ratio: f64::NAN,
+            ..RetryBudgetConfig::default()
+        },
+        RetryBudgetConfig {
+            ratio: 0.0,
+            min_retries: 0,
+            ..RetryBudgetConfig::default()
+        },
+        RetryBudgetConfig {
+            window: Duration::from_millis(500),
+            ..RetryBudgetConfig::default()
+        },
+        RetryBudgetConfig {
+            deferral: Duration::ZERO,
+            ..RetryBudgetConfig::default()
+        },
+    ] {
+        assert!(config.validate().is_err(), "{:?} should be invalid", config);
+    }
+}
+
+// ============================================================================
+// Budget
+// ============================================================================
+
+/// Verify that a disabled budget allows every retry.
+#[test]
+fn test_disabled_budget_allows_all_retries() {
+    let budget = RetryBudget::default();
+
+    assert!((0..1000).all(|_| budget.try_acquire(1)));
+}
+
+/// Verify that `min_retries` are allowed without any successful sends.
+#[test]
+fn test_min_retries_allowed_without_sends() {
+    let budget = budget(0.1, 3);
+
+    assert!(budget.try_acquire(2));
+    assert!(budget.try_acquire(1));
+    assert!(!budget.try_acquire(1));
+}
+
+/// Verify that successful sends raise the budget by `ratio`.
+#[test]
+fn test_sends_raise_budget() {
+    let budget = budget(0.5, 1);
+    let now = Instant::now();
+    budget.record_sends_at(10, now);
+
+    assert!(budget.try_acquire_at(5, now));
+    assert!(!budget.try_acquire_at(1, now));
+
+    budget.record_sends_at(2, now);
+    assert!(budget.try_acquire_at(1, now));
+}
+
+/// Verify that sends and retries leave the budget with the window.
+#[test]
+fn test_window_slides() {
+    let budget = budget(1.0, 0);
+    let start = Instant::now();
+    budget.record_sends_at(4, start);
+    assert!(budget.try_acquire_at(4, start));
+    assert!(!budget.try_acquire_at(1, start));
+
+    let later = start + budget.config.window / 2;
+    budget.record_sends_at(2, later);
+    assert!(budget.try_acquire_at(2, later));
+    assert!(!budget.try_acquire_at(1, later));
+
+    // The first sends and retries have left the window; the later ones
+    // have not.
+    let after_window = start + budget.config.window;
+    budget.record_sends_at(1, after_window);
+    assert!(budget.try_acquire_at(1, after_window));
+    assert!(!budget.try_acquire_at(1, after_window));
+}
+
+/// Verify that denied retries are counted in `retry_budget_exhausted_total`.
+#[test]
+fn test_denied_retries_counted() {
+    let metrics = Arc::new(ServiceMetrics::default());
+    let budget = budget(0.1, 1).with_metrics(metrics.clone());
+
+    assert!(budget.try_acquire(1));
+    assert!(!budget.try_acquire(1));
+    assert!(!budget.try_acquire(1));
+
+    assert_eq!(metrics.retry_budget_exhausted_total.get(), 2);
+}
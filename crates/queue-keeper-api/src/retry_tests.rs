@@ -297,3 +297,35 @@ fn test_very_large_attempt_number() {
     let delay = policy.calculate_delay(50);
     assert_eq!(delay, Duration::from_secs(60));
 }
+
+#[test]
+fn test_retry_policy_deserializes_human_readable_delays() {
+    let policy: RetryPolicy =
+        serde_json::from_str(r#"{"max_attempts":3,"initial_delay":"500ms","max_delay":"1m"}"#)
+            .unwrap();
+
+    assert_eq!(policy.max_attempts, 3);
+    assert_eq!(policy.initial_delay, Duration::from_millis(500));
+    assert_eq!(policy.max_delay, Duration::from_secs(60));
+    // Omitted fields keep their defaults
+    assert!(policy.use_jitter);
+    assert!(policy.validate().is_ok());
+
+    let json = serde_json::to_value(&policy).unwrap();
+    assert_eq!(json["initial_delay"], "500ms");
+}
+
+#[test]
+fn test_retry_policy_validation() {
+    assert!(RetryPolicy::default().validate().is_ok());
+    assert!(
+        RetryPolicy::new(3, Duration::from_secs(10), Duration::from_secs(1), 2.0)
+            .validate()
+            .is_err()
+    );
+    assert!(
+        RetryPolicy::new(3, Duration::from_secs(1), Duration::from_secs(10), 0.5)
+            .validate()
+            .is_err()
+    );
+}
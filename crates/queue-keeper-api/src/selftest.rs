@@ -0,0 +1,602 @@
+//! # Pipeline Self-Test
+//!
+//! Exercises the full webhook pipeline with a synthetic event and reports
+//! pass/fail for each stage. Exposed as `POST /admin/selftest` and the
+//! `queue-keeper selftest` CLI command for post-deploy verification.
+//!
+//! The self-test signs a ping-like payload with an ephemeral secret and
+//! pushes it through:
+//!
+//! 1. **signature_validation** — the processor's HMAC-SHA256 check, which
+//!    must accept the signed payload and reject a tampered one;
+//! 2. **storage** — write, read back and delete of the payload in the
+//!    configured blob storage;
+//! 3. **normalization** — conversion to a [`WrappedEvent`];
+//! 4. **routing** — delivery through the event router to the loopback
+//!    queue [`SELFTEST_QUEUE`];
+//! 5. **consumption** — receipt and completion of that message.
+//!
+//! Routing uses a dedicated loopback subscription rather than the deployed
+//! bot configuration, so the synthetic event never reaches a real bot. The
+//! event's origin is `synthetic`, so it is not counted in intake statistics.
+//! The stored payload is deleted and the queued message completed once checked.
+//! Stages whose backend is not configured are reported as skipped; stages
+//! that depend on a failed stage are skipped too.
+
+use crate::AppState;
+use bytes::Bytes;
+use hmac::{Hmac, KeyInit, Mac};
+use queue_keeper_core::{
+    blob_storage::{BlobStorage, PayloadMetadata, WebhookPayload},
+    bot_config::{BotConfiguration, BotSpecificConfig, BotSubscription, EventTypePattern},
+    queue_integration::EventRouter,
+    webhook::{
+        EventOrigin, SecretError, SignatureValidator, WebhookHeaders, WebhookProcessor,
+        WebhookProcessorImpl, WebhookRequest, WrappedEvent,
+    },
+    BotName, EventId, QueueName, Timestamp, ValidationError,
+};
+use queue_runtime::QueueClient;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+/// Event type of the synthetic self-test event.
+pub const SELFTEST_EVENT_TYPE: &str = "ping";
+
+/// Loopback queue the synthetic event is routed to and consumed from.
+///
+/// Must exist on the queue backend for the routing and consumption stages
+/// to pass. No real bot should subscribe to it.
+pub const SELFTEST_QUEUE: &str = "queue-keeper-selftest";
+
+/// Bot name of the loopback subscription.
+pub const SELFTEST_BOT: &str = "queue-keeper-selftest";
+
+/// Default time to wait for the routed message on the loopback queue.
+const DEFAULT_RECEIVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum messages drained from the loopback queue while looking for the
+/// synthetic event. Earlier messages are leftovers from interrupted runs.
+const MAX_RECEIVE_ATTEMPTS: usize = 10;
+
+// ============================================================================
+// Report Types
+// ============================================================================
+
+/// A stage of the self-test, in pipeline order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTestStage {
+    /// HMAC signature validation of the signed payload.
+    SignatureValidation,
+    /// Blob storage write, read-back and delete.
+    Storage,
+    /// Normalization to a wrapped event.
+    Normalization,
+    /// Routing to the loopback queue.
+    Routing,
+    /// Receiving and completing the routed message.
+    Consumption,
+}
+
+impl SelfTestStage {
+    /// Every stage, in pipeline order.
+    pub const ALL: [SelfTestStage; 5] = [
+        SelfTestStage::SignatureValidation,
+        SelfTestStage::Storage,
+        SelfTestStage::Normalization,
+        SelfTestStage::Routing,
+        SelfTestStage::Consumption,
+    ];
+
+    /// Stage name as reported in responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SelfTestStage::SignatureValidation => "signature_validation",
+            SelfTestStage::Storage => "storage",
+            SelfTestStage::Normalization => "normalization",
+            SelfTestStage::Routing => "routing",
+            SelfTestStage::Consumption => "consumption",
+        }
+    }
+}
+
+/// Outcome of a single stage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelfTestStatus {
+    /// The stage ran and behaved as expected.
+    Passed,
+    /// The stage ran and did not behave as expected.
+    Failed,
+    /// The stage did not run.
+    Skipped,
+}
+
+/// Result of a single stage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfTestStageResult {
+    /// Stage that ran.
+    pub stage: SelfTestStage,
+    /// Stage outcome.
+    pub status: SelfTestStatus,
+    /// Time spent in the stage, in milliseconds.
+    pub duration_ms: u64,
+    /// What happened: the failure reason, why the stage was skipped, or a
+    /// short confirmation.
+    pub message: String,
+}
+
+/// Result of a full self-test run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    /// `true` when no stage failed. Skipped stages do not fail the run.
+    pub passed: bool,
+    /// When the run started.
+    pub started_at: Timestamp,
+    /// Total run time, in milliseconds.
+    pub duration_ms: u64,
+    /// Per-stage results, in pipeline order.
+    pub stages: Vec<SelfTestStageResult>,
+}
+
+impl SelfTestReport {
+    /// Result for `stage`, if it was reported.
+    pub fn stage(&self, stage: SelfTestStage) -> Option<&SelfTestStageResult> {
+        self.stages.iter().find(|s| s.stage == stage)
+    }
+}
+
+// ============================================================================
+// Self-Test Runner
+// ============================================================================
+
+/// Runs the pipeline self-test against a set of backends.
+#[derive(Clone)]
+pub struct SelfTest {
+    event_router: Arc<dyn EventRouter>,
+    blob_storage: Option<Arc<dyn BlobStorage>>,
+    queue_client: Option<Arc<dyn QueueClient>>,
+    receive_timeout: Duration,
+}
+
+impl SelfTest {
+    /// Create a self-test routing through `event_router`, with no storage or
+    /// queue backend (those stages are skipped).
+    pub fn new(event_router: Arc<dyn EventRouter>) -> Self {
+        Self {
+            event_router,
+            blob_storage: None,
+            queue_client: None,
+            receive_timeout: DEFAULT_RECEIVE_TIMEOUT,
+        }
+    }
+
+    /// Create a self-test using the backends of a running service.
+    pub fn from_state(state: &AppState) -> Self {
+        Self {
+            event_router: state.event_router.clone(),
+            blob_storage: state.event_blob_storage.clone(),
+            queue_client: state.queue_client.clone(),
+            receive_timeout: DEFAULT_RECEIVE_TIMEOUT,
+        }
+    }
+
+    /// Exercise this blob storage in the storage stage.
+    pub fn with_blob_storage(mut self, blob_storage: Arc<dyn BlobStorage>) -> Self {
+        self.blob_storage = Some(blob_storage);
+        self
+    }
+
+    /// Route to and consume from this queue client.
+    pub fn with_queue_client(mut self, queue_client: Arc<dyn QueueClient>) -> Self {
+        self.queue_client = Some(queue_client);
+        self
+    }
+
+    /// Set how long to wait for each receive from the loopback queue.
+    pub fn with_receive_timeout(mut self, timeout: Duration) -> Self {
+        self.receive_timeout = timeout;
+        self
+    }
+
+    /// Run every stage and report the results.
+    ///
+    /// Never returns an error: every failure is recorded against the stage
+    /// it occurred in.
+    pub async fn run(&self) -> SelfTestReport {
+        let start = Instant::now();
+        let started_at = Timestamp::now();
+        let mut stages = Vec::with_capacity(SelfTestStage::ALL.len());
+
+        let secret = format!("selftest-{}", uuid::Uuid::new_v4());
+        let body = synthetic_payload();
+        let signature = sign_payload(&secret, &body);
+        let processor = WebhookProcessorImpl::new(
+            Some(Arc::new(EphemeralSecretValidator { secret })),
+            None,
+            None,
+        );
+
+        // 1. Signature validation
+        let stage_start = Instant::now();
+        let outcome: StageOutcome = check_signature(&processor, &body, &signature).await.into();
+        let signature_ok = outcome.is_passed();
+        stages.push(outcome.finish(SelfTestStage::SignatureValidation, stage_start));
+
+        // 2. Storage
+        let stage_start = Instant::now();
+        let outcome = match &self.blob_storage {
+            _ if !signature_ok => not_run(SelfTestStage::SignatureValidation),
+            Some(storage) => check_storage(storage.as_ref(), &body).await.into(),
+            None => StageOutcome::Skipped("blob storage is not configured".to_string()),
+        };
+        stages.push(outcome.finish(SelfTestStage::Storage, stage_start));
+
+        // 3. Normalization
+        let stage_start = Instant::now();
+        let (outcome, event) = if signature_ok {
+            match check_normalization(&processor, body, signature).await {
+                Ok(event) => (
+                    StageOutcome::Passed(format!("normalized as event {}", event.event_id)),
+                    Some(event),
+                ),
+                Err(message) => (StageOutcome::Failed(message), None),
+            }
+        } else {
+            (not_run(SelfTestStage::SignatureValidation), None)
+        };
+        stages.push(outcome.finish(SelfTestStage::Normalization, stage_start));
+
+        // 4. Routing
+        let stage_start = Instant::now();
+        let outcome = match (&event, &self.queue_client) {
+            (None, _) => not_run(SelfTestStage::Normalization),
+            (Some(_), None) => {
+                StageOutcome::Skipped("queue delivery is not configured".to_string())
+            }
+            (Some(event), Some(client)) => self.check_routing(event, client.as_ref()).await.into(),
+        };
+        let routed = outcome.is_passed();
+        stages.push(outcome.finish(SelfTestStage::Routing, stage_start));
+
+        // 5. Consumption
+        let stage_start = Instant::now();
+        let outcome = match (&event, &self.queue_client) {
+            (Some(event), Some(client)) if routed => {
+                self.check_consumption(event, client.as_ref()).await.into()
+            }
+            (Some(_), None) => {
+                StageOutcome::Skipped("queue delivery is not configured".to_string())
+            }
+            _ => not_run(SelfTestStage::Routing),
+        };
+        stages.push(outcome.finish(SelfTestStage::Consumption, stage_start));
+
+        let passed = stages.iter().all(|s| s.status != SelfTestStatus::Failed);
+        SelfTestReport {
+            passed,
+            started_at,
+            duration_ms: start.elapsed().as_millis() as u64,
+            stages,
+        }
+    }
+
+    /// Route `event` with the loopback subscription.
+    async fn check_routing(
+        &self,
+        event: &WrappedEvent,
+        client: &dyn QueueClient,
+    ) -> Result<String, String> {
+        let config = loopback_configuration()
+            .map_err(|e| format!("invalid loopback subscription: {}", e))?;
+        let result = self
+            .event_router
+            .route_event(event, &config, client)
+            .await
+            .map_err(|e| format!("routing failed: {}", e))?;
+
+        if let Some(failure) = result.failed.first() {
+            return Err(format!(
+                "delivery to {} failed: {}",
+                failure.queue_name, failure.error
+            ));
+        }
+        if result.successful.is_empty() {
+            return Err("loopback subscription did not match the event".to_string());
+        }
+        Ok(format!("delivered to {}", SELFTEST_QUEUE))
+    }
+
+    /// Receive from the loopback queue until the routed `event` arrives.
+    async fn check_consumption(
+        &self,
+        event: &WrappedEvent,
+        client: &dyn QueueClient,
+    ) -> Result<String, String> {
+        let queue = queue_runtime::QueueName::new(SELFTEST_QUEUE.to_string())
+            .map_err(|e| format!("invalid loopback queue name: {}", e))?;
+        let timeout = chrono::TimeDelta::from_std(self.receive_timeout)
+            .unwrap_or(chrono::TimeDelta::seconds(10));
+
+        for _ in 0..MAX_RECEIVE_ATTEMPTS {
+            let received = client
+                .receive_message(&queue, timeout)
+                .await
+                .map_err(|e| format!("receive failed: {}", e))?
+                .ok_or_else(|| {
+                    format!(
+                        "message not received within {}s",
+                        self.receive_timeout.as_secs()
+                    )
+                })?;
+
+            let matches = serde_json::from_slice::<WrappedEvent>(&received.body)
+                .map(|received_event| received_event.event_id == event.event_id)
+                .unwrap_or(false);
+
+            // Leftovers from earlier runs are completed as well so they do
+            // not accumulate on the loopback queue.
+            client
+                .complete_message(received.receipt_handle)
+                .await
+                .map_err(|e| format!("complete failed: {}", e))?;
+
+            if matches {
+                return Ok(format!("received and completed event {}", event.event_id));
+            }
+            debug!("Discarded stale message from the self-test queue");
+        }
+
+        Err(format!(
+            "event not found among {} messages on {}",
+            MAX_RECEIVE_ATTEMPTS, SELFTEST_QUEUE
+        ))
+    }
+}
+
+// ============================================================================
+// Stage Checks
+// ============================================================================
+
+/// The signed payload must validate and a tampered copy must not.
+async fn check_signature(
+    processor: &WebhookProcessorImpl,
+    body: &[u8],
+    signature: &str,
+) -> Result<String, String> {
+    processor
+        .validate_signature(body, signature, SELFTEST_EVENT_TYPE)
+        .await
+        .map_err(|e| format!("valid signature rejected: {}", e))?;
+
+    let mut tampered = body.to_vec();
+    tampered.extend_from_slice(b" ");
+    if processor
+        .validate_signature(&tampered, signature, SELFTEST_EVENT_TYPE)
+        .await
+        .is_ok()
+    {
+        return Err("tampered payload accepted".to_string());
+    }
+
+    Ok("signed payload accepted, tampered payload rejected".to_string())
+}
+
+/// Normalize the signed payload as a GitHub request.
+async fn check_normalization(
+    processor: &WebhookProcessorImpl,
+    body: Bytes,
+    signature: String,
+) -> Result<WrappedEvent, String> {
+    let headers = WebhookHeaders {
+        event_type: SELFTEST_EVENT_TYPE.to_string(),
+        delivery_id: uuid::Uuid::new_v4().to_string(),
+        signature: Some(signature),
+        user_agent: Some("queue-keeper-selftest".to_string()),
+        content_type: "application/json".to_string(),
+    };
+    let request = WebhookRequest::new(headers, body).with_origin(EventOrigin::Synthetic);
+    let event = processor
+        .normalize_event(&request)
+        .await
+        .map_err(|e| format!("normalization failed: {}", e))?;
+    if event.event_type != SELFTEST_EVENT_TYPE {
+        return Err(format!(
+            "normalized event has type '{}', expected '{}'",
+            event.event_type, SELFTEST_EVENT_TYPE
+        ));
+    }
+    Ok(event)
+}
+
+/// Store, read back and delete the payload.
+async fn check_storage(storage: &dyn BlobStorage, body: &Bytes) -> Result<String, String> {
+    let event_id = EventId::new();
+    let mut headers = HashMap::new();
+    headers.insert(
+        "x-github-event".to_string(),
+        SELFTEST_EVENT_TYPE.to_string(),
+    );
+    let payload = WebhookPayload {
+        body: body.clone(),
+        headers,
+        metadata: PayloadMetadata {
+            event_id,
+            event_type: SELFTEST_EVENT_TYPE.to_string(),
+            repository: None,
+            signature_valid: true,
+            received_at: Timestamp::now(),
+            delivery_id: None,
+        },
+    };
+
+    storage
+        .store_payload(&event_id, &payload)
+        .await
+        .map_err(|e| format!("write failed: {}", e))?;
+
+    let read_back = storage.get_payload(&event_id).await;
+
+    // Remove the synthetic payload whatever the read-back found.
+    let deleted = storage.delete_payload(&event_id).await;
+
+    match read_back {
+        Ok(Some(stored)) if stored.payload.body == *body => {}
+        Ok(Some(_)) => return Err("read-back payload does not match".to_string()),
+        Ok(None) => return Err("stored payload not found on read-back".to_string()),
+        Err(e) => return Err(format!("read-back failed: {}", e)),
+    }
+    deleted.map_err(|e| format!("delete failed: {}", e))?;
+
+    Ok(format!(
+        "payload {} written, read back and deleted",
+        event_id
+    ))
+}
+
+// ============================================================================
+// Private Helpers
+// ============================================================================
+
+/// Validates signatures against the self-test's ephemeral secret.
+struct EphemeralSecretValidator {
+    secret: String,
+}
+
+#[async_trait::async_trait]
+impl SignatureValidator for EphemeralSecretValidator {
+    async fn validate_signature(
+        &self,
+        payload: &[u8],
+        signature: &str,
+        secret_key: &str,
+    ) -> Result<(), ValidationError> {
+        let invalid = |message: &str| ValidationError::InvalidFormat {
+            field: "signature".to_string(),
+            message: message.to_string(),
+        };
+        let hex_digest = signature
+            .strip_prefix("sha256=")
+            .ok_or_else(|| invalid("expected sha256=<hex>"))?;
+        let expected = hex::decode(hex_digest).map_err(|_| invalid("signature is not hex"))?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret_key.as_bytes())
+            .map_err(|_| invalid("invalid secret"))?;
+        mac.update(payload);
+        mac.verify_slice(&expected)
+            .map_err(|_| invalid("signature mismatch"))
+    }
+
+    async fn get_webhook_secret(&self, _event_type: &str) -> Result<String, SecretError> {
+        Ok(self.secret.clone())
+    }
+
+    fn supports_constant_time_comparison(&self) -> bool {
+        true
+    }
+}
+
+/// Compute the `X-Hub-Signature-256` header value for a payload.
+fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// A ping-like payload with the repository fields normalization requires.
+fn synthetic_payload() -> Bytes {
+    let payload = serde_json::json!({
+        "zen": "Keep it logically awesome.",
+        "hook_id": 0,
+        "queue_keeper_selftest": true,
+        "repository": {
+            "id": 0,
+            "name": "selftest",
+            "full_name": "queue-keeper/selftest",
+            "private": true,
+            "owner": {
+                "id": 0,
+                "login": "queue-keeper",
+                "type": "Organization"
+            }
+        }
+    });
+    Bytes::from(serde_json::to_vec(&payload).expect("static JSON serializes"))
+}
+
+/// Subscription routing the self-test event, and nothing else, to the
+/// loopback queue.
+fn loopback_configuration() -> Result<BotConfiguration, ValidationError> {
+    Ok(BotConfiguration {
+        bots: vec![BotSubscription {
+            name: BotName::new(SELFTEST_BOT)?,
+            queue: QueueName::new(SELFTEST_QUEUE)?,
+            events: vec![EventTypePattern::Exact(SELFTEST_EVENT_TYPE.to_string())],
+            ordered: false,
+            repository_filter: None,
+            config: BotSpecificConfig::default(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
+        }],
+        settings: Default::default(),
+    })
+}
+
+/// Outcome of a stage before it is timed into a result.
+enum StageOutcome {
+    Passed(String),
+    Failed(String),
+    Skipped(String),
+}
+
+impl StageOutcome {
+    fn is_passed(&self) -> bool {
+        matches!(self, StageOutcome::Passed(_))
+    }
+
+    fn finish(self, stage: SelfTestStage, start: Instant) -> SelfTestStageResult {
+        let (status, message) = match self {
+            StageOutcome::Passed(message) => (SelfTestStatus::Passed, message),
+            StageOutcome::Failed(message) => {
+                warn!(stage = stage.as_str(), reason = %message, "Self-test stage failed");
+                (SelfTestStatus::Failed, message)
+            }
+            StageOutcome::Skipped(message) => (SelfTestStatus::Skipped, message),
+        };
+        SelfTestStageResult {
+            stage,
+            status,
+            duration_ms: start.elapsed().as_millis() as u64,
+            message,
+        }
+    }
+}
+
+impl From<Result<String, String>> for StageOutcome {
+    fn from(result: Result<String, String>) -> Self {
+        match result {
+            Ok(message) => StageOutcome::Passed(message),
+            Err(message) => StageOutcome::Failed(message),
+        }
+    }
+}
+
+/// A stage skipped because `upstream` did not pass.
+fn not_run(upstream: SelfTestStage) -> StageOutcome {
+    StageOutcome::Skipped(format!("not run: {} did not pass", upstream.as_str()))
+}
+
+#[cfg(test)]
+#[path = "selftest_tests.rs"]
+mod tests;
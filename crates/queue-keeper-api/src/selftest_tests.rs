@@ -0,0 +1,331 @@
+//! Tests for the pipeline self-test.
+
+use super::*;
+use async_trait::async_trait;
+use chrono::TimeDelta;
+use queue_keeper_core::{
+    blob_storage::{
+        BlobMetadata, BlobStorageError, PayloadFilter, StorageHealthStatus, StorageMetrics,
+        StoredWebhook,
+    },
+    queue_integration::DefaultEventRouter,
+};
+use queue_runtime::{
+    Message, MessageId, ProviderType, QueueError, ReceiptHandle, ReceivedMessage, SessionClient,
+};
+use std::sync::Mutex;
+
+// ============================================================================
+// Mock Backends
+// ============================================================================
+
+/// In-memory blob storage that can lose payloads on read-back.
+#[derive(Default)]
+struct MockBlobStorage {
+    payloads: Mutex<HashMap<EventId, StoredWebhook>>,
+    drop_on_write: bool,
+}
+
+#[async_trait]
+impl BlobStorage for MockBlobStorage {
+    async fn store_payload(
+        &self,
+        event_id: &EventId,
+        payload: &WebhookPayload,
+    ) -> Result<BlobMetadata, BlobStorageError> {
+        let metadata = BlobMetadata {
+            event_id: *event_id,
+            blob_path: format!("selftest/{}.json", event_id),
+            size_bytes: payload.body.len() as u64,
+            content_type: "application/json".to_string(),
+            created_at: Timestamp::now(),
+            checksum_sha256: queue_keeper_core::blob_storage::compute_checksum(&payload.body),
+            format_version: queue_keeper_core::storage_format::CURRENT_FORMAT_VERSION,
+            content_encoding: queue_keeper_core::blob_storage::ContentEncoding::Identity,
+            encryption: None,
+            metadata: payload.metadata.clone(),
+        };
+        if !self.drop_on_write {
+            self.payloads.lock().unwrap().insert(
+                *event_id,
+                StoredWebhook {
+                    metadata: metadata.clone(),
+                    payload: payload.clone(),
+                },
+            );
+        }
+        Ok(metadata)
+    }
+
+    async fn get_payload(
+        &self,
+        event_id: &EventId,
+    ) -> Result<Option<StoredWebhook>, BlobStorageError> {
+        Ok(self.payloads.lock().unwrap().get(event_id).cloned())
+    }
+
+    async fn list_payloads(
+        &self,
+        _filter: &PayloadFilter,
+    ) -> Result<Vec<BlobMetadata>, BlobStorageError> {
+        Ok(Vec::new())
+    }
+
+    async fn delete_payload(&self, event_id: &EventId) -> Result<(), BlobStorageError> {
+        self.payloads.lock().unwrap().remove(event_id);
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<StorageHealthStatus, BlobStorageError> {
+        Ok(StorageHealthStatus {
+            healthy: true,
+            connected: true,
+            last_success: Some(Timestamp::now()),
+            error_message: None,
+            metrics: StorageMetrics {
+                avg_write_latency_ms: 1.0,
+                avg_read_latency_ms: 1.0,
+                success_rate: 1.0,
+            },
+        })
+    }
+}
+
+/// Queue client that records sends and never delivers anything back.
+#[derive(Default)]
+struct MockQueueClient {
+    sent: Mutex<Vec<String>>,
+    fail_sends: bool,
+}
+
+#[async_trait]
+impl QueueClient for MockQueueClient {
+    async fn send_message(
+        &self,
+        queue: &queue_runtime::QueueName,
+        _message: Message,
+    ) -> Result<MessageId, QueueError> {
+        if self.fail_sends {
+            return Err(QueueError::QueueNotFound {
+                queue_name: queue.as_str().to_string(),
+            });
+        }
+        self.sent.lock().unwrap().push(queue.as_str().to_string());
+        Ok(MessageId::new())
+    }
+
+    async fn send_messages(
+        &self,
+        _queue: &queue_runtime::QueueName,
+        _messages: Vec<Message>,
+    ) -> Result<Vec<MessageId>, QueueError> {
+        unimplemented!("Batch sending not used by the self-test")
+    }
+
+    async fn receive_message(
+        &self,
+        _queue: &queue_runtime::QueueName,
+        _timeout: TimeDelta,
+    ) -> Result<Option<ReceivedMessage>, QueueError> {
+        Ok(None)
+    }
+
+    async fn receive_messages(
+        &self,
+        _queue: &queue_runtime::QueueName,
+        _max_messages: u32,
+        _timeout: TimeDelta,
+    ) -> Result<Vec<ReceivedMessage>, QueueError> {
+        unimplemented!("Batch receiving not used by the self-test")
+    }
+
+    async fn complete_message(&self, _receipt: ReceiptHandle) -> Result<(), QueueError> {
+        Ok(())
+    }
+
+    async fn abandon_message(&self, _receipt: ReceiptHandle) -> Result<(), QueueError> {
+        unimplemented!("Abandon not used by the self-test")
+    }
+
+    async fn dead_letter_message(
+        &self,
+        _receipt: ReceiptHandle,
+        _reason: String,
+    ) -> Result<(), QueueError> {
+        unimplemented!("Dead letter not used by the self-test")
+    }
+
+    async fn accept_session(
+        &self,
+        _queue: &queue_runtime::QueueName,
+        _session_id: Option<queue_runtime::SessionId>,
+    ) -> Result<Box<dyn SessionClient>, QueueError> {
+        unimplemented!("Sessions not used by the self-test")
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::InMemory
+    }
+
+    fn supports_sessions(&self) -> bool {
+        false
+    }
+
+    fn supports_batching(&self) -> bool {
+        false
+    }
+}
+
+fn self_test() -> SelfTest {
+    SelfTest::new(Arc::new(DefaultEventRouter::new()))
+}
+
+fn status(report: &SelfTestReport, stage: SelfTestStage) -> SelfTestStatus {
+    report.stage(stage).expect("every stage is reported").status
+}
+
+// ============================================================================
+// Tests
+// ============================================================================
+
+/// Without storage or queues the in-process stages pass and the rest skip.
+#[tokio::test]
+async fn test_run_without_backends_skips_backend_stages() {
+    let report = self_test().run().await;
+
+    assert!(report.passed);
+    assert_eq!(report.stages.len(), SelfTestStage::ALL.len());
+    assert_eq!(
+        status(&report, SelfTestStage::SignatureValidation),
+        SelfTestStatus::Passed
+    );
+    assert_eq!(
+        status(&report, SelfTestStage::Normalization),
+        SelfTestStatus::Passed
+    );
+    for stage in [
+        SelfTestStage::Storage,
+        SelfTestStage::Routing,
+        SelfTestStage::Consumption,
+    ] {
+        assert_eq!(status(&report, stage), SelfTestStatus::Skipped);
+    }
+}
+
+/// The storage stage writes, reads back and removes the synthetic payload.
+#[tokio::test]
+async fn test_storage_stage_round_trips_and_cleans_up() {
+    let storage = Arc::new(MockBlobStorage::default());
+    let report = self_test().with_blob_storage(storage.clone()).run().await;
+
+    assert_eq!(
+        status(&report, SelfTestStage::Storage),
+        SelfTestStatus::Passed
+    );
+    assert!(storage.payloads.lock().unwrap().is_empty());
+}
+
+/// A payload missing on read-back fails the storage stage and the run.
+#[tokio::test]
+async fn test_storage_stage_fails_when_payload_is_lost() {
+    let storage = Arc::new(MockBlobStorage {
+        drop_on_write: true,
+        ..Default::default()
+    });
+    let report = self_test().with_blob_storage(storage).run().await;
+
+    assert!(!report.passed);
+    let stage = report.stage(SelfTestStage::Storage).unwrap();
+    assert_eq!(stage.status, SelfTestStatus::Failed);
+    assert!(stage.message.contains("not found"));
+}
+
+/// The event is routed only to the loopback queue; a message that never
+/// arrives fails consumption.
+#[tokio::test]
+async fn test_routes_to_loopback_queue_and_reports_missing_message() {
+    let client = Arc::new(MockQueueClient::default());
+    let report = self_test()
+        .with_queue_client(client.clone())
+        .with_receive_timeout(Duration::from_millis(10))
+        .run()
+        .await;
+
+    assert_eq!(
+        status(&report, SelfTestStage::Routing),
+        SelfTestStatus::Passed
+    );
+    assert_eq!(
+        *client.sent.lock().unwrap(),
+        vec![SELFTEST_QUEUE.to_string()]
+    );
+    assert_eq!(
+        status(&report, SelfTestStage::Consumption),
+        SelfTestStatus::Failed
+    );
+    assert!(!report.passed);
+}
+
+/// A failed delivery fails routing and skips consumption.
+#[tokio::test]
+async fn test_routing_failure_skips_consumption() {
+    let client = Arc::new(MockQueueClient {
+        fail_sends: true,
+        ..Default::default()
+    });
+    let report = self_test().with_queue_client(client).run().await;
+
+    assert!(!report.passed);
+    assert_eq!(
+        status(&report, SelfTestStage::Routing),
+        SelfTestStatus::Failed
+    );
+    let consumption = report.stage(SelfTestStage::Consumption).unwrap();
+    assert_eq!(consumption.status, SelfTestStatus::Skipped);
+    assert!(consumption.message.contains("routing"));
+}
+
+/// The validator accepts the signed payload and rejects tampering.
+#[tokio::test]
+async fn test_signature_check_rejects_tampered_payload() {
+    let secret = "selftest-secret".to_string();
+    let body = synthetic_payload();
+    let signature = sign_payload(&secret, &body);
+    let processor = WebhookProcessorImpl::new(
+        Some(Arc::new(EphemeralSecretValidator { secret })),
+        None,
+        None,
+    );
+
+    assert!(check_signature(&processor, &body, &signature).await.is_ok());
+    assert!(processor
+        .validate_signature(b"{}", &signature, SELFTEST_EVENT_TYPE)
+        .await
+        .is_err());
+    assert!(processor
+        .validate_signature(&body, "sha1=abc", SELFTEST_EVENT_TYPE)
+        .await
+        .is_err());
+}
+
+/// The report serializes with snake_case stage names and statuses.
+#[test]
+fn test_report_serialization() {
+    let report = SelfTestReport {
+        passed: true,
+        started_at: Timestamp::now(),
+        duration_ms: 3,
+        stages: vec![SelfTestStageResult {
+            stage: SelfTestStage::SignatureValidation,
+            status: SelfTestStatus::Passed,
+            duration_ms: 1,
+            message: "ok".to_string(),
+        }],
+    };
+
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["stages"][0]["stage"], "signature_validation");
+    assert_eq!(json["stages"][0]["status"], "passed");
+    let round_trip: SelfTestReport = serde_json::from_value(json).unwrap();
+    assert_eq!(round_trip, report);
+}
@@ -0,0 +1,185 @@
+//! Session reset epochs and pending-delivery cancellation.
+//!
+//! Resetting a session discards everything Queue-Keeper still holds for it:
+//!
+//! 1. Pending deliveries for the session (including those sleeping between
+//!    retry attempts or waiting on a queue send) are cancelled, which drops
+//!    their retry state. Messages a queue already accepted stay queued.
+//! 2. The session's epoch is incremented. Every event delivered afterwards is
+//!    stamped with the new epoch in its envelope
+//!    ([`WrappedEvent::session_epoch`](queue_keeper_core::webhook::WrappedEvent::session_epoch))
+//!    and in the `session_epoch` queue message attribute, so consumers can
+//!    discard in-flight work that was started under a different epoch.
+//!
+//! Cancelled events are intentionally **not** written to the DLQ: the reset
+//! is an explicit operator decision to drop them.
+//!
+//! # Limitations
+//!
+//! Epochs are held in memory. After a restart every session starts again at
+//! epoch `0`, so consumers should treat an epoch change in either direction
+//! as a reset rather than relying on epochs increasing monotonically across
+//! service restarts.
+
+use queue_keeper_core::SessionId;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::watch;
+
+// ============================================================================
+// Session Epoch Registry
+// ============================================================================
+
+/// Result of a session reset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SessionResetSummary {
+    /// Epoch before the reset.
+    pub previous_epoch: u64,
+
+    /// Epoch stamped on events delivered after the reset.
+    pub new_epoch: u64,
+
+    /// Number of pending deliveries that were cancelled.
+    pub cancelled_deliveries: usize,
+}
+
+/// Tracks the reset epoch and pending deliveries of each session.
+///
+/// Each tracked session owns a `watch` channel carrying its current epoch.
+/// Pending deliveries hold a [`SessionDeliveryGuard`] subscribed to that
+/// channel; a reset publishes a new epoch, which wakes every guard so the
+/// delivery can abandon its remaining attempts.
+///
+/// Sessions that have never been reset are forgotten as soon as their last
+/// pending delivery completes, so the registry only grows with the number of
+/// sessions that have actually been reset.
+#[derive(Debug, Default)]
+pub struct SessionEpochRegistry {
+    sessions: Mutex<HashMap<String, watch::Sender<u64>>>,
+}
+
+impl SessionEpochRegistry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the current epoch of a session (`0` if it has never been reset).
+    pub fn current_epoch(&self, session_id: &SessionId) -> u64 {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .get(session_id.as_str())
+            .map(|sender| *sender.borrow())
+            .unwrap_or(0)
+    }
+
+    /// Get the number of deliveries currently pending for a session.
+    pub fn pending_deliveries(&self, session_id: &SessionId) -> usize {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .get(session_id.as_str())
+            .map(|sender| sender.receiver_count())
+            .unwrap_or(0)
+    }
+
+    /// Register a pending delivery for a session.
+    ///
+    /// The returned guard records the session's epoch at the time of the
+    /// call and reports cancellation once the session is reset. Dropping the
+    /// guard unregisters the delivery.
+    pub fn track(self: &Arc<Self>, session_id: &SessionId) -> SessionDeliveryGuard {
+        let mut sessions = self.sessions.lock().unwrap();
+        let receiver = sessions
+            .entry(session_id.as_str().to_string())
+            .or_insert_with(|| watch::channel(0).0)
+            .subscribe();
+        let epoch = *receiver.borrow();
+
+        SessionDeliveryGuard {
+            registry: Arc::clone(self),
+            session_key: session_id.as_str().to_string(),
+            receiver,
+            epoch,
+        }
+    }
+
+    /// Reset a session: bump its epoch and cancel all pending deliveries.
+    pub fn reset(&self, session_id: &SessionId) -> SessionResetSummary {
+        let mut sessions = self.sessions.lock().unwrap();
+        let sender = sessions
+            .entry(session_id.as_str().to_string())
+            .or_insert_with(|| watch::channel(0).0);
+
+        let previous_epoch = *sender.borrow();
+        let new_epoch = previous_epoch + 1;
+        let cancelled_deliveries = sender.receiver_count();
+        sender.send_replace(new_epoch);
+
+        SessionResetSummary {
+            previous_epoch,
+            new_epoch,
+            cancelled_deliveries,
+        }
+    }
+
+    /// Forget a never-reset session once its last delivery completes.
+    fn release(&self, session_key: &str) {
+        let mut sessions = self.sessions.lock().unwrap();
+        let forget = sessions
+            .get(session_key)
+            // The releasing guard's receiver is still alive at this point.
+            .map(|sender| sender.receiver_count() <= 1 && *sender.borrow() == 0)
+            .unwrap_or(false);
+        if forget {
+            sessions.remove(session_key);
+        }
+    }
+}
+
+// ============================================================================
+// Session Delivery Guard
+// ============================================================================
+
+/// Registration of one pending delivery with the [`SessionEpochRegistry`].
+#[derive(Debug)]
+pub struct SessionDeliveryGuard {
+    registry: Arc<SessionEpochRegistry>,
+    session_key: String,
+    receiver: watch::Receiver<u64>,
+    epoch: u64,
+}
+
+impl SessionDeliveryGuard {
+    /// Epoch of the session when the delivery was registered.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Check whether the session has been reset since registration.
+    pub fn is_cancelled(&self) -> bool {
+        *self.receiver.borrow() != self.epoch
+    }
+
+    /// Wait until the session is reset.
+    ///
+    /// Never completes if the session is not reset.
+    pub async fn cancelled(&mut self) {
+        while *self.receiver.borrow_and_update() == self.epoch {
+            if self.receiver.changed().await.is_err() {
+                std::future::pending::<()>().await;
+            }
+        }
+    }
+}
+
+impl Drop for SessionDeliveryGuard {
+    fn drop(&mut self) {
+        self.registry.release(&self.session_key);
+    }
+}
+
+#[cfg(test)]
+#[path = "session_epochs_tests.rs"]
+mod tests;
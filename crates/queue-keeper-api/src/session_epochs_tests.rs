@@ -0,0 +1,88 @@
+//! Tests for session reset epochs.
+
+use super::*;
+use std::time::Duration;
+
+fn session() -> SessionId {
+    SessionId::from_parts("owner", "repo", "pull_request", "1")
+}
+
+/// Verify that a session that was never reset is at epoch zero.
+#[test]
+fn test_unknown_session_is_at_epoch_zero() {
+    let registry = SessionEpochRegistry::new();
+    assert_eq!(registry.current_epoch(&session()), 0);
+    assert_eq!(registry.pending_deliveries(&session()), 0);
+}
+
+/// Verify that reset increments the epoch and counts pending deliveries.
+#[test]
+fn test_reset_bumps_epoch_and_counts_pending() {
+    let registry = Arc::new(SessionEpochRegistry::new());
+    let _first = registry.track(&session());
+    let _second = registry.track(&session());
+
+    let summary = registry.reset(&session());
+
+    assert_eq!(summary.previous_epoch, 0);
+    assert_eq!(summary.new_epoch, 1);
+    assert_eq!(summary.cancelled_deliveries, 2);
+    assert_eq!(registry.current_epoch(&session()), 1);
+}
+
+/// Verify that guards observe a reset that happens after registration.
+#[test]
+fn test_guard_is_cancelled_after_reset() {
+    let registry = Arc::new(SessionEpochRegistry::new());
+    let guard = registry.track(&session());
+    assert!(!guard.is_cancelled());
+
+    registry.reset(&session());
+
+    assert!(guard.is_cancelled());
+}
+
+/// Verify that deliveries registered after a reset carry the new epoch and
+/// are not cancelled by it.
+#[test]
+fn test_guard_after_reset_uses_new_epoch() {
+    let registry = Arc::new(SessionEpochRegistry::new());
+    registry.reset(&session());
+
+    let guard = registry.track(&session());
+
+    assert_eq!(guard.epoch(), 1);
+    assert!(!guard.is_cancelled());
+}
+
+/// Verify that never-reset sessions are forgotten once deliveries complete,
+/// while reset sessions keep their epoch.
+#[test]
+fn test_release_forgets_only_unreset_sessions() {
+    let registry = Arc::new(SessionEpochRegistry::new());
+    let other = SessionId::from_parts("owner", "repo", "issue", "2");
+
+    drop(registry.track(&session()));
+    assert!(registry.sessions.lock().unwrap().is_empty());
+
+    registry.reset(&other);
+    drop(registry.track(&other));
+    assert_eq!(registry.current_epoch(&other), 1);
+}
+
+/// Verify that `cancelled()` wakes a waiting delivery when the session resets.
+#[tokio::test]
+async fn test_cancelled_wakes_on_reset() {
+    let registry = Arc::new(SessionEpochRegistry::new());
+    let mut guard = registry.track(&session());
+
+    let resetter = Arc::clone(&registry);
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        resetter.reset(&session());
+    });
+
+    tokio::time::timeout(Duration::from_secs(1), guard.cancelled())
+        .await
+        .expect("reset should wake the guard");
+}
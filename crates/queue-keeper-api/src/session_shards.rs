@@ -0,0 +1,452 @@
+//! Sticky session routing of deliveries to worker shards.
+//!
+//! By default every processed webhook gets its own delivery task, and the
+//! tasks of a session wait for each other in the
+//! [`SessionSequencer`](queue_keeper_core::queue_integration::SessionSequencer).
+//! When `delivery.worker_shards` is set, deliveries are instead handed to a
+//! fixed set of worker tasks ("shards"):
+//!
+//! - Each delivery is keyed by its session ID (or its event ID when the event
+//!   has no session) and assigned to a shard by consistent hashing.
+//! - A shard runs its deliveries one after another, so events of a session
+//!   are delivered in the order they were accepted.
+//! - When a shard's worker fails (its task panics), the shard is removed from
+//!   the hash ring. Only the sessions it owned move, to the neighbouring
+//!   shards, together with the deliveries still queued on it; the delivery
+//!   that was running when the worker failed is lost and logged.
+//!
+//! Shard load is exported as the `delivery_shard_pending` gauge (per shard)
+//! and summarised by `delivery_shard_load_imbalance`: the busiest shard's
+//! pending count divided by the mean over active shards.
+
+use crate::metrics::ServiceMetrics;
+use crate::queue_batching::QueueBatchingConfig;
+use crate::retry::RetryPolicy;
+use crate::retry_budget::RetryBudgetConfig;
+use queue_keeper_core::webhook::WrappedEvent;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    hash::{DefaultHasher, Hash, Hasher},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, RwLock, Weak,
+    },
+};
+use tokio::sync::{mpsc, Mutex};
+use tracing::{error, info, warn};
+
+/// A delivery handed to a shard.
+pub type DeliveryJob = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Delivery worker settings (the `delivery` configuration section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// delivery:
+///   worker_shards: 8
+///   shard_virtual_nodes: 64
+///   retry:
+///     max_attempts: 5
+///     initial_delay: 1s
+///     max_delay: 16s
+///   retry_budget:
+///     enabled: true
+///     ratio: 0.1
+///   batching:
+///     enabled: true
+///     max_messages: 50
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeliveryWorkerConfig {
+    /// Number of delivery worker shards.
+    ///
+    /// `0` (the default) spawns one task per event, without ordering between
+    /// events of a session.
+    #[serde(default)]
+    pub worker_shards: usize,
+
+    /// Points each shard places on the consistent-hash ring. More points
+    /// spread sessions more evenly. Defaults to 64.
+    #[serde(default = "DeliveryWorkerConfig::default_shard_virtual_nodes")]
+    pub shard_virtual_nodes: usize,
+
+    /// Retry policy for transient queue delivery failures, used when the
+    /// `retry.delivery` section is absent.
+    #[serde(default)]
+    pub retry: RetryPolicy,
+
+    /// Cap on the retries of all deliveries together.
+    #[serde(default)]
+    pub retry_budget: RetryBudgetConfig,
+
+    /// Batching of the sends to each bot queue.
+    #[serde(default)]
+    pub batching: QueueBatchingConfig,
+}
+
+impl DeliveryWorkerConfig {
+    fn default_shard_virtual_nodes() -> usize {
+        64
+    }
+
+    /// Whether deliveries are routed to worker shards.
+    pub fn is_sharded(&self) -> bool {
+        self.worker_shards > 0
+    }
+
+    /// Validate the worker settings.
+    ///
+    /// # Errors
+    /// Returns a description of the problem when sharding is enabled with
+    /// no virtual nodes.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.is_sharded() && self.shard_virtual_nodes == 0 {
+            return Err("delivery.shard_virtual_nodes must be at least 1".to_string());
+        }
+        self.retry
+            .validate()
+            .map_err(|message| format!("delivery.{}", message))?;
+        self.retry_budget
+            .validate()
+            .map_err(|message| format!("delivery.{}", message))?;
+        self.batching
+            .validate()
+            .map_err(|message| format!("delivery.{}", message))
+    }
+}
+
+impl Default for DeliveryWorkerConfig {
+    fn default() -> Self {
+        Self {
+            worker_shards: 0,
+            shard_virtual_nodes: Self::default_shard_virtual_nodes(),
+            retry: RetryPolicy::default(),
+            retry_budget: RetryBudgetConfig::default(),
+            batching: QueueBatchingConfig::default(),
+        }
+    }
+}
+
+/// Shard key of an event: its session ID, or its event ID when the event
+/// has no session and needs no ordering.
+pub fn shard_key(event: &WrappedEvent) -> String {
+    match &event.session_id {
+        Some(session_id) => session_id.to_string(),
+        None => event.event_id.to_string(),
+    }
+}
+
+// ============================================================================
+// Consistent Hash Ring
+// ============================================================================
+
+/// Consistent-hash ring mapping keys to shard indices.
+#[derive(Debug, Clone)]
+struct HashRing {
+    points: BTreeMap<u64, usize>,
+}
+
+impl HashRing {
+    fn new(shards: usize, virtual_nodes: usize) -> Self {
+        let mut points = BTreeMap::new();
+        for shard in 0..shards {
+            for node in 0..virtual_nodes {
+                points.insert(hash(&(shard, node)), shard);
+            }
+        }
+        Self { points }
+    }
+
+    /// Shard owning `key`: the first point at or after the key's hash.
+    fn owner(&self, key: &str) -> Option<usize> {
+        let h = hash(&key);
+        self.points
+            .range(h..)
+            .next()
+            .or_else(|| self.points.iter().next())
+            .map(|(_, shard)| *shard)
+    }
+
+    /// Remove every point of `shard`. Returns `false` if it had none.
+    fn remove(&mut self, shard: usize) -> bool {
+        let before = self.points.len();
+        self.points.retain(|_, s| *s != shard);
+        self.points.len() != before
+    }
+
+    fn contains(&self, shard: usize) -> bool {
+        self.points.values().any(|s| *s == shard)
+    }
+}
+
+fn hash<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+// ============================================================================
+// Dispatcher
+// ============================================================================
+
+struct ShardJob {
+    key: String,
+    delivery: DeliveryJob,
+}
+
+struct Shard {
+    sender: mpsc::UnboundedSender<ShardJob>,
+    /// Kept outside the worker so that queued jobs survive a worker failure.
+    receiver: Arc<Mutex<mpsc::UnboundedReceiver<ShardJob>>>,
+    pending: Arc<AtomicUsize>,
+}
+
+struct DispatcherInner {
+    ring: RwLock<HashRing>,
+    shards: Vec<Shard>,
+    metrics: Option<Arc<ServiceMetrics>>,
+}
+
+/// Routes deliveries to worker shards by session (see the module docs).
+///
+/// Cloning is cheap; clones share the same shards.
+#[derive(Clone)]
+pub struct SessionShardDispatcher {
+    inner: Arc<DispatcherInner>,
+}
+
+impl SessionShardDispatcher {
+    /// Start `config.worker_shards` worker tasks.
+    ///
+    /// Must be called from within a Tokio runtime. `metrics`, when given,
+    /// receives the shard load gauges.
+    pub fn start(config: &DeliveryWorkerConfig, metrics: Option<Arc<ServiceMetrics>>) -> Self {
+        let shards: Vec<Shard> = (0..config.worker_shards)
+            .map(|_| {
+                let (sender, receiver) = mpsc::unbounded_channel();
+                Shard {
+                    sender,
+                    receiver: Arc::new(Mutex::new(receiver)),
+                    pending: Arc::new(AtomicUsize::new(0)),
+                }
+            })
+            .collect();
+        let inner = Arc::new(DispatcherInner {
+            ring: RwLock::new(HashRing::new(
+                config.worker_shards,
+                config.shard_virtual_nodes,
+            )),
+            shards,
+            metrics,
+        });
+
+        for (index, shard) in inner.shards.iter().enumerate() {
+            let worker = tokio::spawn(run_shard(
+                shard.receiver.clone(),
+                shard.pending.clone(),
+                Arc::downgrade(&inner),
+            ));
+            let supervisor = Arc::downgrade(&inner);
+            tokio::spawn(async move {
+                if let Err(join_err) = worker.await {
+                    if join_err.is_panic() {
+                        if let Some(inner) = supervisor.upgrade() {
+                            inner.fail_shard(index);
+                        }
+                    }
+                }
+            });
+        }
+
+        info!(
+            shards = config.worker_shards,
+            virtual_nodes = config.shard_virtual_nodes,
+            "Sticky session delivery shards started"
+        );
+        inner.record_load();
+        Self { inner }
+    }
+
+    /// Queue `delivery` on the shard owning `key`.
+    ///
+    /// Returns the shard index, or `None` when every shard has failed; the
+    /// delivery is then spawned on its own task without ordering.
+    pub fn dispatch(&self, key: &str, delivery: DeliveryJob) -> Option<usize> {
+        let ring = self.inner.ring.read().unwrap_or_else(|e| e.into_inner());
+        let Some(shard) = ring.owner(key) else {
+            warn!(
+                key = %key,
+                "No delivery shard available; delivering without session ordering"
+            );
+            tokio::spawn(delivery);
+            return None;
+        };
+        self.inner.enqueue(
+            shard,
+            ShardJob {
+                key: key.to_string(),
+                delivery,
+            },
+        );
+        drop(ring);
+        self.inner.record_load();
+        Some(shard)
+    }
+
+    /// Shard currently owning `key`, if any shard is active.
+    pub fn shard_for(&self, key: &str) -> Option<usize> {
+        self.inner
+            .ring
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .owner(key)
+    }
+
+    /// Indices of the shards still on the ring.
+    pub fn active_shards(&self) -> Vec<usize> {
+        let ring = self.inner.ring.read().unwrap_or_else(|e| e.into_inner());
+        (0..self.inner.shards.len())
+            .filter(|shard| ring.contains(*shard))
+            .collect()
+    }
+
+    /// Deliveries queued or in flight, per shard.
+    pub fn pending(&self) -> Vec<usize> {
+        self.inner
+            .shards
+            .iter()
+            .map(|s| s.pending.load(Ordering::Relaxed))
+            .collect()
+    }
+
+    /// Busiest active shard's pending count divided by the mean over active
+    /// shards. `1.0` when balanced or idle.
+    pub fn load_imbalance(&self) -> f64 {
+        self.inner.load_imbalance()
+    }
+}
+
+impl DispatcherInner {
+    fn enqueue(&self, shard: usize, job: ShardJob) {
+        let target = &self.shards[shard];
+        target.pending.fetch_add(1, Ordering::Relaxed);
+        // The receiver is owned by the dispatcher, so the channel never closes
+        // while the dispatcher exists.
+        if let Err(mpsc::error::SendError(job)) = target.sender.send(job) {
+            target.pending.fetch_sub(1, Ordering::Relaxed);
+            tokio::spawn(job.delivery);
+        }
+    }
+
+    /// Take a failed shard off the ring and move its queued deliveries to
+    /// the shards that now own their sessions.
+    ///
+    /// Holds the ring's write lock throughout, so deliveries dispatched
+    /// afterwards queue behind the moved ones.
+    fn fail_shard(&self, shard: usize) {
+        let mut ring = self.ring.write().unwrap_or_else(|e| e.into_inner());
+        if !ring.remove(shard) {
+            return;
+        }
+
+        let failed = &self.shards[shard];
+        let mut moved = 0usize;
+        match failed.receiver.try_lock() {
+            Ok(mut receiver) => {
+                while let Ok(job) = receiver.try_recv() {
+                    match ring.owner(&job.key) {
+                        Some(owner) => self.enqueue(owner, job),
+                        None => {
+                            tokio::spawn(job.delivery);
+                        }
+                    }
+                    moved += 1;
+                }
+            }
+            Err(_) => {
+                error!(
+                    shard = shard,
+                    "Failed delivery shard is still locked; its queued deliveries were not moved"
+                );
+            }
+        }
+        failed.pending.store(0, Ordering::Relaxed);
+        drop(ring);
+
+        error!(
+            shard = shard,
+            moved_deliveries = moved,
+            active_shards = self.active_count(),
+            "Delivery shard failed; its sessions were rebalanced to the remaining shards \
+             (the delivery in progress was lost)"
+        );
+        self.record_load();
+    }
+
+    fn active_count(&self) -> usize {
+        let ring = self.ring.read().unwrap_or_else(|e| e.into_inner());
+        (0..self.shards.len()).filter(|s| ring.contains(*s)).count()
+    }
+
+    fn load_imbalance(&self) -> f64 {
+        let ring = self.ring.read().unwrap_or_else(|e| e.into_inner());
+        let active: Vec<usize> = (0..self.shards.len())
+            .filter(|s| ring.contains(*s))
+            .map(|s| self.shards[s].pending.load(Ordering::Relaxed))
+            .collect();
+        let total: usize = active.iter().sum();
+        if total == 0 {
+            return 1.0;
+        }
+        let mean = total as f64 / active.len() as f64;
+        *active.iter().max().unwrap_or(&0) as f64 / mean
+    }
+
+    fn record_load(&self) {
+        let Some(metrics) = &self.metrics else {
+            return;
+        };
+        for (index, shard) in self.shards.iter().enumerate() {
+            let label = index.to_string();
+            metrics
+                .delivery_shard_pending
+                .with_label_values(&[label.as_str()])
+                .set(shard.pending.load(Ordering::Relaxed) as i64);
+        }
+        metrics
+            .delivery_shard_load_imbalance
+            .set(self.load_imbalance());
+        metrics
+            .delivery_shards_active
+            .set(self.active_count() as i64);
+    }
+}
+
+/// Worker loop: run the shard's deliveries one at a time, in order.
+async fn run_shard(
+    receiver: Arc<Mutex<mpsc::UnboundedReceiver<ShardJob>>>,
+    pending: Arc<AtomicUsize>,
+    dispatcher: Weak<DispatcherInner>,
+) {
+    let mut receiver = receiver.lock().await;
+    while let Some(job) = receiver.recv().await {
+        job.delivery.await;
+        pending.fetch_sub(1, Ordering::Relaxed);
+        match dispatcher.upgrade() {
+            Some(inner) => inner.record_load(),
+            None => break,
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "session_shards_tests.rs"]
+mod tests;
@@ -0,0 +1,218 @@
+//! Tests for sticky session routing to delivery shards.
+
+use super::*;
+use crate::test_fixtures::session_event;
+use std::sync::Mutex as StdMutex;
+use std::time::Duration;
+use tokio::sync::{oneshot, Notify};
+
+fn config(shards: usize) -> DeliveryWorkerConfig {
+    DeliveryWorkerConfig {
+        worker_shards: shards,
+        ..Default::default()
+    }
+}
+
+/// Job that records `label` in `log`, after `delay`.
+fn record(log: &Arc<StdMutex<Vec<String>>>, label: &str, delay: Duration) -> DeliveryJob {
+    let log = log.clone();
+    let label = label.to_string();
+    Box::pin(async move {
+        tokio::time::sleep(delay).await;
+        log.lock().unwrap().push(label);
+    })
+}
+
+/// Job that signals `done` when it runs.
+fn signal(done: oneshot::Sender<()>) -> DeliveryJob {
+    Box::pin(async move {
+        let _ = done.send(());
+    })
+}
+
+/// Job that waits for `release` and then panics, failing its shard.
+fn panic_after(release: Arc<Notify>) -> DeliveryJob {
+    Box::pin(async move {
+        release.notified().await;
+        panic!("delivery worker failure injected by test");
+    })
+}
+
+/// Find a key owned by `shard`.
+fn key_on(dispatcher: &SessionShardDispatcher, shard: usize) -> String {
+    (0..10_000)
+        .map(|i| format!("owner/repo/pull_request/{}", i))
+        .find(|key| dispatcher.shard_for(key) == Some(shard))
+        .expect("every shard owns some keys")
+}
+
+async fn wait(done: oneshot::Receiver<()>) {
+    tokio::time::timeout(Duration::from_secs(5), done)
+        .await
+        .expect("job should run")
+        .expect("job should signal");
+}
+
+// ============================================================================
+// Routing
+// ============================================================================
+
+/// A key always maps to the same shard and every shard receives keys.
+#[tokio::test]
+async fn test_keys_are_sticky_and_spread() {
+    let dispatcher = SessionShardDispatcher::start(&config(4), None);
+
+    let mut per_shard = [0usize; 4];
+    for i in 0..1_000 {
+        let key = format!("owner/repo-{}/issue/{}", i % 17, i);
+        let shard = dispatcher.shard_for(&key).unwrap();
+        assert_eq!(dispatcher.shard_for(&key), Some(shard));
+        per_shard[shard] += 1;
+    }
+
+    assert!(
+        per_shard.iter().all(|count| *count > 100),
+        "{:?}",
+        per_shard
+    );
+    assert_eq!(dispatcher.active_shards(), vec![0, 1, 2, 3]);
+}
+
+/// Deliveries of a session run in dispatch order even when earlier ones are
+/// slower.
+#[tokio::test]
+async fn test_session_deliveries_run_in_order() {
+    let dispatcher = SessionShardDispatcher::start(&config(3), None);
+    let log = Arc::new(StdMutex::new(Vec::new()));
+
+    for (i, delay_ms) in [30u64, 1, 20, 0].iter().enumerate() {
+        dispatcher.dispatch(
+            "owner/repo/pull_request/1",
+            record(&log, &i.to_string(), Duration::from_millis(*delay_ms)),
+        );
+    }
+    let (done, finished) = oneshot::channel();
+    dispatcher.dispatch("owner/repo/pull_request/1", signal(done));
+    wait(finished).await;
+
+    assert_eq!(*log.lock().unwrap(), vec!["0", "1", "2", "3"]);
+    assert!(dispatcher.pending().iter().all(|p| *p == 0));
+}
+
+/// The shard key is the session ID, or the event ID without a session.
+#[test]
+fn test_shard_key_prefers_session() {
+    let mut event = session_event("owner/repo/branch/main");
+    assert_eq!(shard_key(&event), "owner/repo/branch/main");
+
+    event.session_id = None;
+    assert_eq!(shard_key(&event), event.event_id.to_string());
+}
+
+// ============================================================================
+// Failure and Rebalancing
+// ============================================================================
+
+/// A failed shard leaves the ring; its queued deliveries and sessions move
+/// to other shards while other sessions keep their shard.
+#[tokio::test]
+async fn test_failed_shard_is_rebalanced() {
+    let dispatcher = SessionShardDispatcher::start(&config(4), None);
+    let failing_key = key_on(&dispatcher, 2);
+    let stable_key = key_on(&dispatcher, 1);
+
+    let release = Arc::new(Notify::new());
+    dispatcher.dispatch(&failing_key, panic_after(release.clone()));
+    let (queued_done, queued_finished) = oneshot::channel();
+    assert_eq!(
+        dispatcher.dispatch(&failing_key, signal(queued_done)),
+        Some(2)
+    );
+
+    release.notify_one();
+    wait(queued_finished).await;
+
+    assert_eq!(dispatcher.active_shards(), vec![0, 1, 3]);
+    assert_ne!(dispatcher.shard_for(&failing_key), Some(2));
+    assert_eq!(dispatcher.shard_for(&stable_key), Some(1));
+    assert_eq!(dispatcher.pending()[2], 0);
+}
+
+/// With every shard failed, deliveries still run, without ordering.
+#[tokio::test]
+async fn test_all_shards_failed_falls_back_to_direct_delivery() {
+    let dispatcher = SessionShardDispatcher::start(&config(1), None);
+    let release = Arc::new(Notify::new());
+    dispatcher.dispatch("owner/repo/issue/1", panic_after(release.clone()));
+    release.notify_one();
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !dispatcher.active_shards().is_empty() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("shard should be marked failed");
+
+    let (done, finished) = oneshot::channel();
+    assert_eq!(
+        dispatcher.dispatch("owner/repo/issue/1", signal(done)),
+        None
+    );
+    wait(finished).await;
+}
+
+// ============================================================================
+// Load Reporting
+// ============================================================================
+
+/// Imbalance is 1.0 when idle and grows when one shard holds the backlog.
+#[tokio::test]
+async fn test_load_imbalance_and_metrics() {
+    let metrics = Arc::new(ServiceMetrics::default());
+    let dispatcher = SessionShardDispatcher::start(&config(4), Some(metrics.clone()));
+    assert_eq!(dispatcher.load_imbalance(), 1.0);
+    assert_eq!(metrics.delivery_shards_active.get(), 4);
+
+    let key = key_on(&dispatcher, 0);
+    let release = Arc::new(Notify::new());
+    for _ in 0..3 {
+        let release = release.clone();
+        dispatcher.dispatch(
+            &key,
+            Box::pin(async move {
+                release.notified().await;
+            }),
+        );
+    }
+
+    assert_eq!(dispatcher.pending(), vec![3, 0, 0, 0]);
+    assert_eq!(dispatcher.load_imbalance(), 4.0);
+    assert_eq!(metrics.delivery_shard_load_imbalance.get(), 4.0);
+    assert_eq!(
+        metrics
+            .delivery_shard_pending
+            .with_label_values(&["0"])
+            .get(),
+        3
+    );
+
+    for _ in 0..3 {
+        release.notify_one();
+        tokio::task::yield_now().await;
+    }
+}
+
+/// Sharding needs at least one virtual node per shard.
+#[test]
+fn test_config_validation() {
+    assert!(DeliveryWorkerConfig::default().validate().is_ok());
+    assert!(!DeliveryWorkerConfig::default().is_sharded());
+
+    let invalid = DeliveryWorkerConfig {
+        worker_shards: 2,
+        shard_virtual_nodes: 0,
+        ..Default::default()
+    };
+    assert!(invalid.validate().is_err());
+}
@@ -0,0 +1,356 @@
+//! # Shutdown Report
+//!
+//! A single structured summary of how the previous process stopped, to
+//! speed up incident analysis. When the service exits it builds a
+//! [`ShutdownReport`] covering:
+//!
+//! - uptime and the reason for exit (the signal received, or the server
+//!   error);
+//! - the webhooks processed and rejected over the process lifetime;
+//! - residue: work accepted but not finished when the process stopped, such
+//!   as events still in flight, events held for paused bots, deliveries
+//!   queued on session shards and events waiting on the secondary event
+//!   storage to be reconciled. Queue-Keeper keeps no outbox or write-ahead
+//!   log, so this in-memory work is what a restart loses or leaves behind;
+//! - background jobs that had not stopped cleanly.
+//!
+//! The report is logged and written to `shutdown_report.path`. A crash, an
+//! out-of-memory kill or `SIGKILL` cannot write a report, so while running
+//! the service overwrites the file every `checkpoint_interval` with a
+//! provisional report whose reason is `unclean`; if the process dies, that
+//! checkpoint is what the next start finds.
+//!
+//! On the next start the previous report is read before the first
+//! checkpoint replaces it, logged (at `WARN` when the exit was unclean) and
+//! served at `GET /debug/last-shutdown`.
+
+use crate::metrics::ServiceMetrics;
+use crate::supervisor::{JobState, JobStatus, Supervisor};
+use queue_keeper_core::{units::human_duration, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use tracing::{info, warn};
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Shutdown report settings (the `shutdown_report` configuration section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// shutdown_report:
+///   enabled: true
+///   path: "/var/lib/queue-keeper/last-shutdown.json"
+///   checkpoint_interval: 30s
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShutdownReportConfig {
+    /// Whether shutdown reports are written and read. On by default.
+    pub enabled: bool,
+
+    /// File holding the report of the last shutdown.
+    pub path: PathBuf,
+
+    /// Interval between two provisional reports written while running.
+    #[serde(with = "human_duration")]
+    pub checkpoint_interval: Duration,
+}
+
+impl Default for ShutdownReportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            path: PathBuf::from("./data/last-shutdown.json"),
+            checkpoint_interval: Duration::from_secs(30),
+        }
+    }
+}
+
+impl ShutdownReportConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && self.path.as_os_str().is_empty() {
+            return Err("shutdown_report.path must not be empty".to_string());
+        }
+        if self.checkpoint_interval.is_zero() {
+            return Err(
+                "shutdown_report.checkpoint_interval must be greater than zero".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Report Types
+// ============================================================================
+
+/// Why the process stopped.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ExitReason {
+    /// Graceful shutdown after a termination signal.
+    Signal {
+        /// `SIGINT` or `SIGTERM`.
+        signal: String,
+    },
+
+    /// The server stopped with an error.
+    Error { message: String },
+
+    /// The process stopped without writing a final report (crash,
+    /// out-of-memory kill, `SIGKILL`); the report is the last checkpoint.
+    Unclean,
+}
+
+impl ExitReason {
+    /// Whether the process shut down gracefully.
+    pub fn is_graceful(&self) -> bool {
+        matches!(self, ExitReason::Signal { .. })
+    }
+}
+
+/// Structured shutdown report served at `GET /debug/last-shutdown`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ShutdownReport {
+    /// Service version.
+    pub service_version: String,
+
+    /// When the process started.
+    pub started_at: Timestamp,
+
+    /// When the report was written.
+    pub stopped_at: Timestamp,
+
+    /// Seconds between start and the report.
+    pub uptime_seconds: u64,
+
+    /// Why the process stopped.
+    pub reason: ExitReason,
+
+    /// Webhooks processed successfully.
+    pub events_processed: u64,
+
+    /// Webhooks rejected or failed.
+    pub events_failed: u64,
+
+    /// Unfinished work by kind, e.g. `in_flight_events`.
+    pub residue: BTreeMap<String, u64>,
+
+    /// Background jobs that had not completed or stopped cleanly.
+    pub unfinished_jobs: Vec<JobStatus>,
+}
+
+impl ShutdownReport {
+    /// Read the report at `path`; a missing file is `Ok(None)`.
+    ///
+    /// # Errors
+    /// Returns an error when the file cannot be read or is not a report.
+    pub fn load(path: &Path) -> std::io::Result<Option<Self>> {
+        match std::fs::read(path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Write the report to `path`, creating its directory.
+    ///
+    /// The report is written to a temporary file and renamed into place, so
+    /// a crash while writing leaves the previous report intact.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(dir) = path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let bytes = serde_json::to_vec_pretty(self).map_err(std::io::Error::other)?;
+        let temporary = path.with_extension("tmp");
+        std::fs::write(&temporary, bytes)?;
+        std::fs::rename(&temporary, path)
+    }
+
+    /// Total residue across all kinds.
+    pub fn total_residue(&self) -> u64 {
+        self.residue.values().sum()
+    }
+
+    /// Log the report as one line, at `WARN` when the exit was not graceful
+    /// or left work unfinished.
+    pub fn log(&self, message: &str) {
+        let report = serde_json::to_string(self).unwrap_or_default();
+        if self.reason.is_graceful() && self.total_residue() == 0 && self.unfinished_jobs.is_empty()
+        {
+            info!(shutdown_report = %report, "{}", message);
+        } else {
+            warn!(shutdown_report = %report, "{}", message);
+        }
+    }
+}
+
+// ============================================================================
+// Recorder
+// ============================================================================
+
+type ResidueProbe = Box<dyn Fn() -> u64 + Send + Sync>;
+
+/// Builds, checkpoints and writes the report of this process, and holds the
+/// report of the previous one.
+pub struct ShutdownRecorder {
+    config: ShutdownReportConfig,
+    started_at: Timestamp,
+    started: Instant,
+    previous: Option<ShutdownReport>,
+    supervisor: Arc<Supervisor>,
+    metrics: OnceLock<Arc<ServiceMetrics>>,
+    residue: Mutex<Vec<(String, ResidueProbe)>>,
+}
+
+impl std::fmt::Debug for ShutdownRecorder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ShutdownRecorder")
+            .field("config", &self.config)
+            .field("started_at", &self.started_at)
+            .field("previous", &self.previous)
+            .finish()
+    }
+}
+
+impl ShutdownRecorder {
+    /// Create the recorder for this process, reading the previous report
+    /// from `config.path`.
+    ///
+    /// A report that cannot be read is logged and ignored.
+    pub fn new(config: ShutdownReportConfig, supervisor: Arc<Supervisor>) -> Self {
+        let previous = if config.enabled {
+            match ShutdownReport::load(&config.path) {
+                Ok(previous) => previous,
+                Err(e) => {
+                    warn!(
+                        path = %config.path.display(),
+                        error = %e,
+                        "Cannot read the previous shutdown report; ignoring it"
+                    );
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
+        Self {
+            config,
+            started_at: Timestamp::now(),
+            started: Instant::now(),
+            previous,
+            supervisor,
+            metrics: OnceLock::new(),
+            residue: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Report of the previous process, if one was found.
+    pub fn previous(&self) -> Option<&ShutdownReport> {
+        self.previous.as_ref()
+    }
+
+    /// Read event counts from `metrics`. Only the first call has an effect.
+    pub fn attach_metrics(&self, metrics: Arc<ServiceMetrics>) {
+        let _ = self.metrics.set(metrics);
+    }
+
+    /// Report the value of `probe` as residue `name`.
+    pub fn track_residue(&self, name: &str, probe: impl Fn() -> u64 + Send + Sync + 'static) {
+        self.residue
+            .lock()
+            .unwrap()
+            .push((name.to_string(), Box::new(probe)));
+    }
+
+    /// Build the report of this process as of now.
+    pub fn report(&self, reason: ExitReason) -> ShutdownReport {
+        let (total, failed) = self.metrics.get().map_or((0, 0), |metrics| {
+            (
+                metrics.webhook_requests_total.get(),
+                metrics.webhook_validation_failures.get(),
+            )
+        });
+        let residue = self
+            .residue
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, probe)| (name.clone(), probe()))
+            .collect();
+        let unfinished_jobs = self
+            .supervisor
+            .statuses()
+            .into_iter()
+            .filter(|job| !matches!(job.state, JobState::Completed | JobState::Stopped))
+            .collect();
+
+        ShutdownReport {
+            service_version: env!("CARGO_PKG_VERSION").to_string(),
+            started_at: self.started_at,
+            stopped_at: Timestamp::now(),
+            uptime_seconds: self.started.elapsed().as_secs(),
+            reason,
+            events_processed: total.saturating_sub(failed),
+            events_failed: failed,
+            residue,
+            unfinished_jobs,
+        }
+    }
+
+    /// Write a provisional report, which stands if the process dies.
+    pub fn checkpoint(&self) {
+        if self.config.enabled {
+            self.save(&self.report(ExitReason::Unclean));
+        }
+    }
+
+    /// Write checkpoints every `checkpoint_interval`, starting now. Runs
+    /// until the future is dropped.
+    pub async fn run_checkpoints(&self) {
+        let mut ticker = tokio::time::interval(self.config.checkpoint_interval);
+        loop {
+            ticker.tick().await;
+            self.checkpoint();
+        }
+    }
+
+    /// Build, log and write the final report of this process.
+    pub fn finish(&self, reason: ExitReason) -> ShutdownReport {
+        let report = self.report(reason);
+        report.log("Shutdown report");
+        if self.config.enabled {
+            self.save(&report);
+        }
+        report
+    }
+
+    fn save(&self, report: &ShutdownReport) {
+        if let Err(e) = report.save(&self.config.path) {
+            warn!(
+                path = %self.config.path.display(),
+                error = %e,
+                "Failed to write shutdown report"
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "shutdown_report_tests.rs"]
+mod tests;
@@ -0,0 +1,136 @@
+//! Tests for the shutdown report.
+
+use super::*;
+use crate::supervisor::SupervisorConfig;
+
+fn recorder(path: &Path) -> ShutdownRecorder {
+    ShutdownRecorder::new(
+        ShutdownReportConfig {
+            path: path.to_path_buf(),
+            ..ShutdownReportConfig::default()
+        },
+        Arc::new(Supervisor::new(SupervisorConfig::default())),
+    )
+}
+
+/// The final report carries the reason, event counts and residue, and is
+/// what the next process finds.
+#[test]
+fn test_finish_writes_report_for_next_start() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("state").join("last-shutdown.json");
+
+    let first = recorder(&path);
+    assert!(first.previous().is_none());
+
+    let metrics = Arc::new(ServiceMetrics::default());
+    for success in [true, true, false] {
+        metrics.record_webhook_request(Duration::from_millis(1), success);
+    }
+    first.attach_metrics(metrics);
+    first.track_residue("in_flight_events", || 3);
+    first.track_residue("held_events", || 0);
+
+    let report = first.finish(ExitReason::Signal {
+        signal: "SIGTERM".to_string(),
+    });
+    assert_eq!(report.events_processed, 2);
+    assert_eq!(report.events_failed, 1);
+    assert_eq!(report.residue["in_flight_events"], 3);
+    assert_eq!(report.total_residue(), 3);
+    assert!(report.reason.is_graceful());
+
+    let second = recorder(&path);
+    assert_eq!(second.previous(), Some(&report));
+}
+
+/// A checkpoint is an unclean report; a later final report replaces it.
+#[test]
+fn test_checkpoint_reads_as_unclean_exit() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("last-shutdown.json");
+
+    let first = recorder(&path);
+    first.checkpoint();
+    let previous = recorder(&path).previous().cloned().unwrap();
+    assert_eq!(previous.reason, ExitReason::Unclean);
+    assert!(!previous.reason.is_graceful());
+
+    first.finish(ExitReason::Error {
+        message: "listener closed".to_string(),
+    });
+    let previous = recorder(&path).previous().cloned().unwrap();
+    assert_eq!(
+        previous.reason,
+        ExitReason::Error {
+            message: "listener closed".to_string()
+        }
+    );
+}
+
+/// Jobs that are still running are reported as unfinished.
+#[tokio::test]
+async fn test_running_jobs_are_unfinished() {
+    let dir = tempfile::tempdir().unwrap();
+    let supervisor = Arc::new(Supervisor::new(SupervisorConfig::default()));
+    supervisor.spawn("poller", |mut ctx| async move {
+        ctx.stopped().await;
+        Ok(())
+    });
+    let recorder = ShutdownRecorder::new(
+        ShutdownReportConfig {
+            path: dir.path().join("last-shutdown.json"),
+            ..ShutdownReportConfig::default()
+        },
+        supervisor.clone(),
+    );
+
+    let report = recorder.report(ExitReason::Unclean);
+    assert_eq!(report.unfinished_jobs.len(), 1);
+    assert_eq!(report.unfinished_jobs[0].name, "poller");
+
+    supervisor.shutdown().await;
+    assert!(recorder
+        .report(ExitReason::Unclean)
+        .unfinished_jobs
+        .is_empty());
+}
+
+/// An unreadable report is ignored, and a disabled recorder neither reads
+/// nor writes.
+#[test]
+fn test_unreadable_or_disabled() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("last-shutdown.json");
+    std::fs::write(&path, "not json").unwrap();
+    assert!(recorder(&path).previous().is_none());
+
+    std::fs::remove_file(&path).unwrap();
+    let disabled = ShutdownRecorder::new(
+        ShutdownReportConfig {
+            enabled: false,
+            path: path.clone(),
+            ..ShutdownReportConfig::default()
+        },
+        Arc::new(Supervisor::new(SupervisorConfig::default())),
+    );
+    disabled.checkpoint();
+    disabled.finish(ExitReason::Unclean);
+    assert!(!path.exists());
+}
+
+/// The settings are validated.
+#[test]
+fn test_config_validation() {
+    assert!(ShutdownReportConfig::default().validate().is_ok());
+    let config: ShutdownReportConfig =
+        serde_json::from_str(r#"{"path":"/tmp/report.json","checkpoint_interval":"1m"}"#).unwrap();
+    assert_eq!(config.checkpoint_interval, Duration::from_secs(60));
+    assert!(config.enabled);
+
+    let no_interval = ShutdownReportConfig {
+        checkpoint_interval: Duration::ZERO,
+        ..ShutdownReportConfig::default()
+    };
+    assert!(no_interval.validate().is_err());
+}
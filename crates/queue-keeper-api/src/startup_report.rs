@@ -0,0 +1,638 @@
+//! # Startup Report
+//!
+//! A single structured summary of how this process started, to speed up
+//! triage of misconfigured deployments. It is logged once at startup (the
+//! `startup_report` field of one `INFO` line, as JSON) and served at
+//! `GET /debug/startup-report`. It covers:
+//!
+//! - the configuration sources and whether each was found;
+//! - the webhook providers, their signature requirement and secret source;
+//! - the cargo features compiled into the binary;
+//! - the detected runtime environment (Kubernetes, container CPU and memory
+//!   limits);
+//! - preflight checks, derived from the configuration and added by the
+//!   binary as it initialises its backends.
+//!
+//! The report never contains secret values: secrets are reported by source
+//! type and environment overrides by variable name only.
+
+use crate::config::{ProviderSecretConfig, QueueBackendConfig, ServiceConfig};
+use queue_keeper_core::{webhook::generic_provider::WebhookSecretConfig, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::{info, warn};
+
+/// Memory limits at or above this are cgroup v1's "unlimited" sentinel.
+const UNLIMITED_MEMORY_BYTES: u64 = 1 << 60;
+
+// ============================================================================
+// Report Types
+// ============================================================================
+
+/// Kind of a configuration source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigSourceKind {
+    /// Built-in defaults.
+    Defaults,
+    /// A YAML file.
+    File,
+    /// `QK__`-prefixed environment variables.
+    Environment,
+}
+
+/// One configuration source, in the order sources are applied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigSource {
+    /// Kind of source.
+    pub kind: ConfigSourceKind,
+
+    /// File path, or a description for non-file sources.
+    pub name: String,
+
+    /// Whether the source was found and applied.
+    pub loaded: bool,
+
+    /// Environment variable names, for [`ConfigSourceKind::Environment`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variables: Vec<String>,
+
+    /// Dotted keys of the settings a file or defaults source sets, for
+    /// [`value_sources`]. Not part of the report.
+    #[serde(skip)]
+    pub keys: Vec<String>,
+}
+
+impl ConfigSource {
+    /// The built-in defaults.
+    pub fn defaults() -> Self {
+        Self {
+            kind: ConfigSourceKind::Defaults,
+            name: "built-in defaults".to_string(),
+            loaded: true,
+            variables: Vec::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    /// The developer mode defaults, applied over the system-wide file.
+    pub fn development_defaults() -> Self {
+        Self {
+            kind: ConfigSourceKind::Defaults,
+            name: "developer mode defaults".to_string(),
+            loaded: true,
+            variables: Vec::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    /// A configuration file and whether it was found.
+    pub fn file(path: impl Into<String>, loaded: bool) -> Self {
+        Self {
+            kind: ConfigSourceKind::File,
+            name: path.into(),
+            loaded,
+            variables: Vec::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    /// Environment variables with `prefix`, reported by name only.
+    pub fn environment(prefix: &str, variables: impl IntoIterator<Item = String>) -> Self {
+        let mut variables: Vec<String> = variables
+            .into_iter()
+            .filter(|name| name.starts_with(prefix))
+            .collect();
+        variables.sort();
+        Self {
+            kind: ConfigSourceKind::Environment,
+            name: format!("{}* environment variables", prefix),
+            loaded: !variables.is_empty(),
+            variables,
+            keys: Vec::new(),
+        }
+    }
+
+    /// Record the dotted keys of the settings this source sets.
+    pub fn with_keys(mut self, keys: Vec<String>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Dotted keys of the settings this source sets, each with the file,
+    /// variable or defaults that set it.
+    fn set_keys(&self) -> Vec<(String, ValueSource)> {
+        if !self.loaded {
+            return Vec::new();
+        }
+        match self.kind {
+            ConfigSourceKind::Environment => self
+                .variables
+                .iter()
+                .filter_map(|variable| {
+                    let (_, path) = variable.split_once("__")?;
+                    let key = path.split("__").collect::<Vec<_>>().join(".");
+                    Some((key.to_lowercase(), ValueSource::new(self.kind, variable)))
+                })
+                .collect(),
+            _ => self
+                .keys
+                .iter()
+                .map(|key| (key.clone(), ValueSource::new(self.kind, &self.name)))
+                .collect(),
+        }
+    }
+}
+
+/// Where one configuration value came from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValueSource {
+    /// Kind of source.
+    pub kind: ConfigSourceKind,
+
+    /// File path, environment variable name, or a description of the
+    /// defaults.
+    pub name: String,
+}
+
+impl ValueSource {
+    fn new(kind: ConfigSourceKind, name: &str) -> Self {
+        Self {
+            kind,
+            name: name.to_string(),
+        }
+    }
+}
+
+/// The source of every value of `config`, a serialized configuration loaded
+/// from `sources` in order, keyed by dotted path.
+///
+/// Objects are descended into; arrays and scalars are single values. A value
+/// takes the last source that set it, or a part of it, and otherwise the
+/// first source, the built-in defaults.
+pub fn value_sources(
+    config: &serde_json::Value,
+    sources: &[ConfigSource],
+) -> BTreeMap<String, ValueSource> {
+    let defaults = sources
+        .first()
+        .cloned()
+        .unwrap_or_else(ConfigSource::defaults);
+    let defaults = ValueSource::new(defaults.kind, &defaults.name);
+    let set_keys: Vec<(String, ValueSource)> =
+        sources.iter().flat_map(ConfigSource::set_keys).collect();
+
+    let mut paths = Vec::new();
+    collect_value_paths(config, String::new(), &mut paths);
+    paths
+        .into_iter()
+        .map(|path| {
+            let source = set_keys
+                .iter()
+                .rev()
+                .find(|(key, _)| {
+                    key == &path
+                        || key.starts_with(&format!("{}.", path))
+                        || path.starts_with(&format!("{}.", key))
+                })
+                .map_or_else(|| defaults.clone(), |(_, source)| source.clone());
+            (path, source)
+        })
+        .collect()
+}
+
+/// Push the dotted path of every non-object value within `value`.
+fn collect_value_paths(value: &serde_json::Value, prefix: String, paths: &mut Vec<String>) {
+    match value {
+        serde_json::Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                collect_value_paths(value, path, paths);
+            }
+        }
+        _ if !prefix.is_empty() => paths.push(prefix),
+        _ => {}
+    }
+}
+
+/// A webhook provider as configured.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderSummary {
+    /// Provider ID (the `/webhook/{id}` path segment).
+    pub id: String,
+
+    /// `github`, `generic`, or a webhook source kind (`gitlab`, `bitbucket`).
+    pub kind: String,
+
+    /// Whether deliveries must carry a valid signature.
+    pub signature_required: bool,
+
+    /// Secret source type (`key_vault`, `environment_variable`, `literal`
+    /// or `none`).
+    pub secret_source: String,
+}
+
+/// A cargo feature and whether it is compiled in.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeatureFlag {
+    /// Feature name.
+    pub name: String,
+
+    /// Whether the feature is enabled in this build.
+    pub enabled: bool,
+}
+
+impl FeatureFlag {
+    /// A feature and whether it is compiled in.
+    pub fn new(name: &str, enabled: bool) -> Self {
+        Self {
+            name: name.to_string(),
+            enabled,
+        }
+    }
+}
+
+/// Kubernetes placement of the pod, when running in Kubernetes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KubernetesInfo {
+    /// Namespace from the service account mount.
+    pub namespace: Option<String>,
+
+    /// Pod name (`HOSTNAME`).
+    pub pod_name: Option<String>,
+
+    /// Node name, when exposed as `NODE_NAME` through the downward API.
+    pub node_name: Option<String>,
+}
+
+/// Where the process is running and the resources it may use.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RuntimeEnvironment {
+    /// Host name.
+    pub hostname: Option<String>,
+
+    /// Whether the process runs in a container.
+    pub container: bool,
+
+    /// Kubernetes placement, `None` outside Kubernetes.
+    pub kubernetes: Option<KubernetesInfo>,
+
+    /// CPU limit in cores from the cgroup, `None` when unlimited or unknown.
+    pub cpu_limit: Option<f64>,
+
+    /// Memory limit in bytes from the cgroup, `None` when unlimited or
+    /// unknown.
+    pub memory_limit_bytes: Option<u64>,
+
+    /// CPUs available to the process as seen by the runtime.
+    pub available_parallelism: Option<usize>,
+}
+
+impl RuntimeEnvironment {
+    /// Detect the environment of the current process.
+    pub fn detect() -> Self {
+        let mut environment = Self::from_sources(
+            |name| std::env::var(name).ok(),
+            |path| std::fs::read_to_string(path).ok(),
+        );
+        environment.available_parallelism =
+            std::thread::available_parallelism().ok().map(|n| n.get());
+        environment
+    }
+
+    /// Detect the environment from environment variables and files.
+    ///
+    /// Reads cgroup v2 limits (`cpu.max`, `memory.max`) and falls back to
+    /// the cgroup v1 files.
+    pub fn from_sources(
+        env: impl Fn(&str) -> Option<String>,
+        read_file: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let kubernetes = env("KUBERNETES_SERVICE_HOST").map(|_| KubernetesInfo {
+            namespace: read_file("/var/run/secrets/kubernetes.io/serviceaccount/namespace")
+                .map(|namespace| namespace.trim().to_string()),
+            pod_name: env("HOSTNAME"),
+            node_name: env("NODE_NAME"),
+        });
+
+        let container = kubernetes.is_some()
+            || env("container").is_some()
+            || read_file("/.dockerenv").is_some()
+            || read_file("/proc/1/cgroup").is_some_and(|cgroup| {
+                ["docker", "kubepods", "containerd", "libpod"]
+                    .iter()
+                    .any(|marker| cgroup.contains(marker))
+            });
+
+        let cpu_limit = match read_file("/sys/fs/cgroup/cpu.max") {
+            Some(cpu_max) => parse_cpu_max(&cpu_max),
+            None => {
+                let quota = read_file("/sys/fs/cgroup/cpu/cpu.cfs_quota_us");
+                let period = read_file("/sys/fs/cgroup/cpu/cpu.cfs_period_us");
+                match (quota, period) {
+                    (Some(quota), Some(period)) => parse_cpu_quota(quota.trim(), period.trim()),
+                    _ => None,
+                }
+            }
+        };
+
+        let memory_limit_bytes = read_file("/sys/fs/cgroup/memory.max")
+            .or_else(|| read_file("/sys/fs/cgroup/memory/memory.limit_in_bytes"))
+            .and_then(|limit| limit.trim().parse::<u64>().ok())
+            .filter(|limit| *limit < UNLIMITED_MEMORY_BYTES);
+
+        Self {
+            hostname: env("HOSTNAME"),
+            container,
+            kubernetes,
+            cpu_limit,
+            memory_limit_bytes,
+            available_parallelism: None,
+        }
+    }
+}
+
+/// Parse cgroup v2 `cpu.max` (`"<quota> <period>"` or `"max <period>"`).
+fn parse_cpu_max(cpu_max: &str) -> Option<f64> {
+    let mut parts = cpu_max.split_whitespace();
+    parse_cpu_quota(parts.next()?, parts.next()?)
+}
+
+/// Cores allowed by a CFS quota and period; `None` when unlimited.
+fn parse_cpu_quota(quota: &str, period: &str) -> Option<f64> {
+    let quota: f64 = quota.parse().ok()?;
+    let period: f64 = period.parse().ok()?;
+    (quota > 0.0 && period > 0.0).then(|| quota / period)
+}
+
+/// Outcome of a preflight check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PreflightStatus {
+    /// Nothing to report.
+    Passed,
+    /// The service runs, but probably not as intended.
+    Warning,
+    /// A component the service needs did not initialise.
+    Failed,
+}
+
+/// One preflight check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PreflightCheck {
+    /// Check name.
+    pub name: String,
+
+    /// Outcome.
+    pub status: PreflightStatus,
+
+    /// What was found.
+    pub message: String,
+}
+
+/// Structured startup report served at `GET /debug/startup-report`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StartupReport {
+    /// Service version.
+    pub service_version: String,
+
+    /// When the report was generated.
+    pub generated_at: Timestamp,
+
+    /// Configuration sources in the order they are applied.
+    pub config_sources: Vec<ConfigSource>,
+
+    /// Configured webhook providers.
+    pub providers: Vec<ProviderSummary>,
+
+    /// Queue backend name.
+    pub queue_backend: String,
+
+    /// Cargo features of this build.
+    pub features: Vec<FeatureFlag>,
+
+    /// Detected runtime environment.
+    pub environment: RuntimeEnvironment,
+
+    /// Preflight checks in the order they ran.
+    pub preflight: Vec<PreflightCheck>,
+}
+
+impl StartupReport {
+    /// Build the report for `config`, running the configuration checks.
+    pub fn new(
+        config: &ServiceConfig,
+        config_sources: Vec<ConfigSource>,
+        features: Vec<FeatureFlag>,
+        environment: RuntimeEnvironment,
+    ) -> Self {
+        let mut providers: Vec<ProviderSummary> = config
+            .providers
+            .iter()
+            .map(|provider| ProviderSummary {
+                id: provider.id.clone(),
+                kind: "github".to_string(),
+                signature_required: provider.require_signature,
+                secret_source: provider
+                    .secret
+                    .as_ref()
+                    .map_or("none", provider_secret_source)
+                    .to_string(),
+            })
+            .collect();
+        providers.extend(config.generic_providers.iter().map(|provider| {
+            ProviderSummary {
+                id: provider.provider_id.clone(),
+                kind: "generic".to_string(),
+                signature_required: provider.signature.is_some(),
+                secret_source: provider
+                    .webhook_secret
+                    .as_ref()
+                    .map_or("none", generic_secret_source)
+                    .to_string(),
+            }
+        }));
+        providers.extend(config.webhook_sources.iter().map(|source| {
+            ProviderSummary {
+                id: source.provider_id().to_string(),
+                kind: source.kind.as_str().to_string(),
+                signature_required: source.require_signature,
+                secret_source: source
+                    .webhook_secret
+                    .as_ref()
+                    .map_or("none", generic_secret_source)
+                    .to_string(),
+            }
+        }));
+
+        let mut report = Self {
+            service_version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: Timestamp::now(),
+            config_sources,
+            providers,
+            queue_backend: config.queue.provider_name().to_string(),
+            features,
+            environment,
+            preflight: Vec::new(),
+        };
+        report.run_config_checks(config);
+        report
+    }
+
+    /// Add a preflight check.
+    pub fn with_check(
+        mut self,
+        name: &str,
+        status: PreflightStatus,
+        message: impl Into<String>,
+    ) -> Self {
+        self.preflight.push(PreflightCheck {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        });
+        self
+    }
+
+    /// Worst outcome of all preflight checks.
+    pub fn status(&self) -> PreflightStatus {
+        self.preflight
+            .iter()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(PreflightStatus::Passed)
+    }
+
+    /// Log the report as one line, at `WARN` when a check did not pass.
+    pub fn log(&self) {
+        let report = serde_json::to_string(self).unwrap_or_default();
+        let status = self.status();
+        if status == PreflightStatus::Passed {
+            info!(startup_report = %report, "Startup report");
+        } else {
+            warn!(startup_report = %report, status = ?status, "Startup report");
+        }
+    }
+
+    fn run_config_checks(&mut self, config: &ServiceConfig) {
+        let check = |name: &str, status, message: String| PreflightCheck {
+            name: name.to_string(),
+            status,
+            message,
+        };
+
+        self.preflight.push(if self.providers.is_empty() {
+            check(
+                "providers",
+                PreflightStatus::Warning,
+                "no webhook providers are configured; every webhook returns 404".to_string(),
+            )
+        } else {
+            check(
+                "providers",
+                PreflightStatus::Passed,
+                format!("{} provider(s) configured", self.providers.len()),
+            )
+        });
+
+        let unsigned: Vec<&str> = self
+            .providers
+            .iter()
+            .filter(|p| !p.signature_required || p.secret_source == "none")
+            .map(|p| p.id.as_str())
+            .collect();
+        self.preflight.push(if unsigned.is_empty() {
+            check(
+                "signature_validation",
+                PreflightStatus::Passed,
+                "every provider validates signatures".to_string(),
+            )
+        } else {
+            check(
+                "signature_validation",
+                PreflightStatus::Warning,
+                format!("signatures are not validated for: {}", unsigned.join(", ")),
+            )
+        });
+
+        let literal: Vec<&str> = self
+            .providers
+            .iter()
+            .filter(|p| p.secret_source == "literal")
+            .map(|p| p.id.as_str())
+            .chain(
+                config
+                    .gateways
+                    .iter()
+                    .filter(|g| matches!(g.secret, ProviderSecretConfig::Literal { .. }))
+                    .map(|g| g.name.as_str()),
+            )
+            .collect();
+        if !literal.is_empty() {
+            self.preflight.push(check(
+                "literal_secrets",
+                PreflightStatus::Warning,
+                format!(
+                    "literal secrets are for development only: {}",
+                    literal.join(", ")
+                ),
+            ));
+        }
+
+        self.preflight
+            .push(if config.security.admin_api_key.is_some() {
+                check(
+                    "admin_api_key",
+                    PreflightStatus::Passed,
+                    "admin endpoints require an API key".to_string(),
+                )
+            } else {
+                check(
+                    "admin_api_key",
+                    PreflightStatus::Warning,
+                    "no admin API key is set; admin endpoints are open".to_string(),
+                )
+            });
+
+        self.preflight.push(
+            if matches!(config.queue, QueueBackendConfig::InMemory { .. }) {
+                check(
+                    "queue_backend",
+                    PreflightStatus::Warning,
+                    "in-memory queue: events are lost on restart and not shared between replicas"
+                        .to_string(),
+                )
+            } else {
+                check(
+                    "queue_backend",
+                    PreflightStatus::Passed,
+                    format!("{} queue backend", self.queue_backend),
+                )
+            },
+        );
+    }
+}
+
+fn provider_secret_source(secret: &ProviderSecretConfig) -> &'static str {
+    match secret {
+        ProviderSecretConfig::KeyVault { .. } => "key_vault",
+        ProviderSecretConfig::EnvironmentVariable { .. } => "environment_variable",
+        ProviderSecretConfig::Literal { .. } => "literal",
+    }
+}
+
+fn generic_secret_source(secret: &WebhookSecretConfig) -> &'static str {
+    match secret {
+        WebhookSecretConfig::KeyVault { .. } => "key_vault",
+        WebhookSecretConfig::EnvironmentVariable { .. } => "environment_variable",
+        WebhookSecretConfig::Literal { .. } => "literal",
+    }
+}
+
+#[cfg(test)]
+#[path = "startup_report_tests.rs"]
+mod tests;
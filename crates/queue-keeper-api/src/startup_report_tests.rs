@@ -0,0 +1,279 @@
+//! Tests for the startup report.
+
+use super::*;
+use crate::config::ProviderConfig;
+use std::collections::HashMap;
+
+fn provider(id: &str, secret: Option<ProviderSecretConfig>) -> ProviderConfig {
+    ProviderConfig {
+        id: id.to_string(),
+        require_signature: secret.is_some(),
+        secret,
+        additional_secrets: vec![],
+        allowed_event_types: vec![],
+        github_enterprise: None,
+        installation_ids: vec![],
+        per_repository_secrets: false,
+    }
+}
+
+fn report(config: &ServiceConfig) -> StartupReport {
+    StartupReport::new(
+        config,
+        vec![ConfigSource::defaults()],
+        vec![FeatureFlag::new("aws-sqs", true)],
+        RuntimeEnvironment::default(),
+    )
+}
+
+fn check<'a>(report: &'a StartupReport, name: &str) -> &'a PreflightCheck {
+    report
+        .preflight
+        .iter()
+        .find(|check| check.name == name)
+        .unwrap_or_else(|| panic!("no '{}' check", name))
+}
+
+/// Detect the environment from a fixed set of variables and files.
+fn detect(env: &[(&str, &str)], files: &[(&str, &str)]) -> RuntimeEnvironment {
+    let env: HashMap<String, String> = env
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    let files: HashMap<String, String> = files
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+    RuntimeEnvironment::from_sources(
+        |name| env.get(name).cloned(),
+        |path| files.get(path).cloned(),
+    )
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Providers are summarised by secret source, and literal values never
+/// appear in the report.
+#[test]
+fn test_providers_are_summarised_without_secrets() {
+    let config = ServiceConfig {
+        providers: vec![
+            provider(
+                "github",
+                Some(ProviderSecretConfig::KeyVault {
+                    secret_name: "github-secret".to_string(),
+                }),
+            ),
+            provider(
+                "dev",
+                Some(ProviderSecretConfig::Literal {
+                    value: "super-secret-value".to_string(),
+                }),
+            ),
+        ],
+        ..Default::default()
+    };
+    let report = report(&config);
+
+    assert_eq!(report.providers[0].secret_source, "key_vault");
+    assert_eq!(report.providers[1].secret_source, "literal");
+    assert_eq!(report.providers[1].kind, "github");
+    assert_eq!(
+        check(&report, "literal_secrets").status,
+        PreflightStatus::Warning
+    );
+    let json = serde_json::to_string(&report).unwrap();
+    assert!(!json.contains("super-secret-value"));
+}
+
+/// The default configuration warns about missing providers, the open admin
+/// API and the in-memory queue.
+#[test]
+fn test_default_config_warnings() {
+    let report = report(&ServiceConfig::default());
+
+    assert_eq!(check(&report, "providers").status, PreflightStatus::Warning);
+    assert_eq!(
+        check(&report, "admin_api_key").status,
+        PreflightStatus::Warning
+    );
+    assert_eq!(
+        check(&report, "queue_backend").status,
+        PreflightStatus::Warning
+    );
+    assert_eq!(report.queue_backend, "in_memory");
+    assert_eq!(report.status(), PreflightStatus::Warning);
+}
+
+/// Providers without a secret are reported as unsigned.
+#[test]
+fn test_unsigned_provider_is_reported() {
+    let config = ServiceConfig {
+        providers: vec![provider("open", None)],
+        ..Default::default()
+    };
+    let report = report(&config);
+
+    let signatures = check(&report, "signature_validation");
+    assert_eq!(signatures.status, PreflightStatus::Warning);
+    assert!(signatures.message.contains("open"));
+}
+
+/// Added checks are kept in order and the worst outcome wins.
+#[test]
+fn test_added_checks_set_overall_status() {
+    let report = report(&ServiceConfig::default())
+        .with_check("event_storage", PreflightStatus::Passed, "ok")
+        .with_check("key_vault", PreflightStatus::Failed, "unreachable");
+
+    assert_eq!(report.preflight.last().unwrap().name, "key_vault");
+    assert_eq!(report.status(), PreflightStatus::Failed);
+}
+
+/// Environment variables are listed by name only, sorted, filtered by
+/// prefix.
+#[test]
+fn test_environment_source_lists_names_only() {
+    let source = ConfigSource::environment(
+        "QK__",
+        vec![
+            "QK__SERVER__PORT".to_string(),
+            "PATH".to_string(),
+            "QK__LOGGING__LEVEL".to_string(),
+        ],
+    );
+    assert!(source.loaded);
+    assert_eq!(
+        source.variables,
+        vec!["QK__LOGGING__LEVEL", "QK__SERVER__PORT"]
+    );
+
+    assert!(!ConfigSource::environment("QK__", Vec::new()).loaded);
+}
+
+/// Each value takes the last source that set it, and the defaults otherwise.
+#[test]
+fn test_value_sources() {
+    let config = serde_json::json!({
+        "server": {"host": "0.0.0.0", "port": 9000},
+        "logging": {"level": "info"},
+        "providers": [{"id": "github"}]
+    });
+    let sources = vec![
+        ConfigSource::defaults(),
+        ConfigSource::file("config/service", true).with_keys(vec![
+            "logging.level".to_string(),
+            "providers".to_string(),
+            "server.port".to_string(),
+        ]),
+        ConfigSource::file("/etc/missing", false).with_keys(vec!["server.host".to_string()]),
+        ConfigSource::environment("QK__", vec!["QK__SERVER__PORT".to_string()]),
+    ];
+
+    let sources = value_sources(&config, &sources);
+
+    let source = |path: &str| {
+        let source = &sources[path];
+        (source.kind, source.name.as_str())
+    };
+    assert_eq!(
+        source("server.host"),
+        (ConfigSourceKind::Defaults, "built-in defaults")
+    );
+    assert_eq!(
+        source("server.port"),
+        (ConfigSourceKind::Environment, "QK__SERVER__PORT")
+    );
+    assert_eq!(
+        source("logging.level"),
+        (ConfigSourceKind::File, "config/service")
+    );
+    assert_eq!(
+        source("providers"),
+        (ConfigSourceKind::File, "config/service")
+    );
+    assert_eq!(sources.len(), 4);
+}
+
+// ============================================================================
+// Environment detection
+// ============================================================================
+
+/// Kubernetes placement and cgroup v2 limits are detected.
+#[test]
+fn test_detects_kubernetes_with_cgroup_v2_limits() {
+    let environment = detect(
+        &[
+            ("KUBERNETES_SERVICE_HOST", "10.0.0.1"),
+            ("HOSTNAME", "queue-keeper-7d9f-abc"),
+            ("NODE_NAME", "node-1"),
+        ],
+        &[
+            (
+                "/var/run/secrets/kubernetes.io/serviceaccount/namespace",
+                "bots\n",
+            ),
+            ("/sys/fs/cgroup/cpu.max", "150000 100000\n"),
+            ("/sys/fs/cgroup/memory.max", "536870912\n"),
+        ],
+    );
+
+    assert!(environment.container);
+    let kubernetes = environment.kubernetes.unwrap();
+    assert_eq!(kubernetes.namespace.as_deref(), Some("bots"));
+    assert_eq!(
+        kubernetes.pod_name.as_deref(),
+        Some("queue-keeper-7d9f-abc")
+    );
+    assert_eq!(kubernetes.node_name.as_deref(), Some("node-1"));
+    assert_eq!(environment.cpu_limit, Some(1.5));
+    assert_eq!(environment.memory_limit_bytes, Some(536_870_912));
+}
+
+/// Unlimited cgroup v2 values are reported as no limit.
+#[test]
+fn test_cgroup_v2_unlimited() {
+    let environment = detect(
+        &[],
+        &[
+            ("/sys/fs/cgroup/cpu.max", "max 100000"),
+            ("/sys/fs/cgroup/memory.max", "max"),
+        ],
+    );
+    assert_eq!(environment.cpu_limit, None);
+    assert_eq!(environment.memory_limit_bytes, None);
+}
+
+/// cgroup v1 limits are read when the v2 files are absent.
+#[test]
+fn test_detects_docker_with_cgroup_v1_limits() {
+    let environment = detect(
+        &[],
+        &[
+            ("/proc/1/cgroup", "12:cpu,cpuacct:/docker/0123abcd\n"),
+            ("/sys/fs/cgroup/cpu/cpu.cfs_quota_us", "200000"),
+            ("/sys/fs/cgroup/cpu/cpu.cfs_period_us", "100000"),
+            (
+                "/sys/fs/cgroup/memory/memory.limit_in_bytes",
+                "9223372036854771712",
+            ),
+        ],
+    );
+    assert!(environment.container);
+    assert!(environment.kubernetes.is_none());
+    assert_eq!(environment.cpu_limit, Some(2.0));
+    assert_eq!(environment.memory_limit_bytes, None);
+}
+
+/// A bare host is not reported as a container.
+#[test]
+fn test_bare_host_is_not_a_container() {
+    let environment = detect(
+        &[("HOSTNAME", "build-agent")],
+        &[("/proc/1/cgroup", "0::/init.scope\n")],
+    );
+    assert!(!environment.container);
+    assert_eq!(environment.hostname.as_deref(), Some("build-agent"));
+}
@@ -0,0 +1,216 @@
+//! Service statistics served at `GET /api/stats`.
+//!
+//! [`StatisticsCollector`] counts what this replica handles since it started:
+//!
+//! - live webhook events accepted, in total and per event type;
+//! - events accepted during the last hour (`events_per_hour`);
+//! - the fraction of webhook requests answered with an error during the last
+//!   hour (`error_rate`);
+//! - events per origin, including replays, which are delivered like any
+//!   other event but do not count toward the live totals and rates.
+//!
+//! The last hour is kept as one-minute buckets, so the rates cover the
+//! current minute and the 59 before it, and are lower than the steady-state
+//! rate while the service has been up for less than an hour. Counters are in
+//! memory and reset on restart; each replica reports its own.
+//!
+//! The number of distinct event types is capped at [`MAX_EVENT_TYPES`]; events
+//! of types seen after the cap is reached are counted under
+//! [`OTHER_EVENT_TYPE`].
+
+use crate::responses::StatisticsResponse;
+use queue_keeper_core::webhook::EventOrigin;
+use std::{
+    collections::{BTreeMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Width of one rate bucket.
+const BUCKET_WIDTH: Duration = Duration::from_secs(60);
+
+/// Buckets covering the rate window of one hour.
+const WINDOW_BUCKETS: u64 = 60;
+
+/// Distinct event types counted separately.
+pub const MAX_EVENT_TYPES: usize = 200;
+
+/// Event type under which events beyond [`MAX_EVENT_TYPES`] are counted.
+pub const OTHER_EVENT_TYPE: &str = "other";
+
+/// Requests handled during one bucket.
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    /// Minutes since the collector started.
+    minute: u64,
+    events: u64,
+    requests: u64,
+    failures: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    total_events: u64,
+    events_by_type: BTreeMap<String, u64>,
+    events_by_origin: BTreeMap<EventOrigin, u64>,
+    /// Buckets of the rate window, oldest first.
+    buckets: VecDeque<Bucket>,
+}
+
+impl Counters {
+    /// The bucket of `minute`, dropping buckets that left the window.
+    fn bucket(&mut self, minute: u64) -> &mut Bucket {
+        self.expire(minute);
+        if self.buckets.back().is_none_or(|last| last.minute < minute) {
+            self.buckets.push_back(Bucket {
+                minute,
+                events: 0,
+                requests: 0,
+                failures: 0,
+            });
+        }
+        self.buckets.back_mut().expect("bucket was just pushed")
+    }
+
+    fn expire(&mut self, minute: u64) {
+        while self
+            .buckets
+            .front()
+            .is_some_and(|first| first.minute + WINDOW_BUCKETS <= minute)
+        {
+            self.buckets.pop_front();
+        }
+    }
+}
+
+/// Aggregates the statistics reported by `GET /api/stats`.
+///
+/// Fed by the webhook handler and the replay executor; see the module
+/// documentation for what is counted.
+#[derive(Debug)]
+pub struct StatisticsCollector {
+    started_at: Instant,
+    counters: Mutex<Counters>,
+}
+
+impl Default for StatisticsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StatisticsCollector {
+    /// Create a collector; uptime is measured from now.
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            counters: Mutex::new(Counters::default()),
+        }
+    }
+
+    /// Record an accepted event.
+    ///
+    /// Every event is counted under its origin. Only live events (see
+    /// [`EventOrigin::counts_toward_baselines`]) count toward the totals,
+    /// the per-type counts and the rates.
+    pub fn record_event(&self, event_type: &str, origin: EventOrigin) {
+        self.record_event_at(event_type, origin, Instant::now());
+    }
+
+    /// Record a webhook request accepted without a new event, such as a
+    /// duplicate delivery.
+    pub fn record_duplicate(&self) {
+        self.record_duplicate_at(Instant::now());
+    }
+
+    /// Record a webhook request answered with an error.
+    pub fn record_failure(&self) {
+        self.record_failure_at(Instant::now());
+    }
+
+    /// Current statistics, with `active_sessions` taken from the session
+    /// tracker.
+    pub fn snapshot(&self, active_sessions: u64) -> StatisticsResponse {
+        self.snapshot_at(active_sessions, Instant::now())
+    }
+
+    fn minute(&self, now: Instant) -> u64 {
+        now.saturating_duration_since(self.started_at).as_secs() / BUCKET_WIDTH.as_secs()
+    }
+
+    fn record_event_at(&self, event_type: &str, origin: EventOrigin, now: Instant) {
+        let minute = self.minute(now);
+        let mut counters = self.counters.lock().unwrap();
+        *counters.events_by_origin.entry(origin).or_insert(0) += 1;
+        if !origin.counts_toward_baselines() {
+            return;
+        }
+
+        counters.total_events += 1;
+        let event_type = if counters.events_by_type.contains_key(event_type)
+            || counters.events_by_type.len() < MAX_EVENT_TYPES
+        {
+            event_type
+        } else {
+            OTHER_EVENT_TYPE
+        };
+        *counters
+            .events_by_type
+            .entry(event_type.to_string())
+            .or_insert(0) += 1;
+
+        let bucket = counters.bucket(minute);
+        bucket.events += 1;
+        bucket.requests += 1;
+    }
+
+    fn record_duplicate_at(&self, now: Instant) {
+        let minute = self.minute(now);
+        self.counters.lock().unwrap().bucket(minute).requests += 1;
+    }
+
+    fn record_failure_at(&self, now: Instant) {
+        let minute = self.minute(now);
+        let mut counters = self.counters.lock().unwrap();
+        let bucket = counters.bucket(minute);
+        bucket.requests += 1;
+        bucket.failures += 1;
+    }
+
+    fn snapshot_at(&self, active_sessions: u64, now: Instant) -> StatisticsResponse {
+        let minute = self.minute(now);
+        let mut counters = self.counters.lock().unwrap();
+        counters.expire(minute);
+
+        let (events, requests, failures) =
+            counters
+                .buckets
+                .iter()
+                .fold((0, 0, 0), |(events, requests, failures), bucket| {
+                    (
+                        events + bucket.events,
+                        requests + bucket.requests,
+                        failures + bucket.failures,
+                    )
+                });
+        let error_rate = if requests == 0 {
+            0.0
+        } else {
+            failures as f64 / requests as f64
+        };
+
+        StatisticsResponse {
+            total_events: counters.total_events,
+            events_per_hour: events as f64,
+            active_sessions,
+            error_rate,
+            uptime_seconds: now.saturating_duration_since(self.started_at).as_secs(),
+            events_by_type: counters.events_by_type.clone(),
+            events_by_origin: counters.events_by_origin.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "statistics_tests.rs"]
+mod tests;
@@ -0,0 +1,80 @@
+//! Tests for the service statistics collector.
+
+use super::*;
+
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// Live events are counted in total, per type and per origin; replays only
+/// per origin.
+#[test]
+fn test_counts_live_events_by_type_and_origin() {
+    let collector = StatisticsCollector::new();
+    let now = collector.started_at;
+    collector.record_event_at("pull_request", EventOrigin::Github, now);
+    collector.record_event_at("pull_request", EventOrigin::Github, now);
+    collector.record_event_at("push", EventOrigin::Github, now);
+    collector.record_event_at("push", EventOrigin::Replay, now);
+
+    let stats = collector.snapshot_at(3, now);
+    assert_eq!(stats.total_events, 3);
+    assert_eq!(stats.events_per_hour, 3.0);
+    assert_eq!(stats.active_sessions, 3);
+    assert_eq!(stats.events_by_type["pull_request"], 2);
+    assert_eq!(stats.events_by_type["push"], 1);
+    assert_eq!(stats.events_by_origin[&EventOrigin::Github], 3);
+    assert_eq!(stats.events_by_origin[&EventOrigin::Replay], 1);
+}
+
+/// The rate and error rate only cover the last hour; totals and uptime do
+/// not expire.
+#[test]
+fn test_rates_cover_last_hour() {
+    let collector = StatisticsCollector::new();
+    let start = collector.started_at;
+    collector.record_event_at("push", EventOrigin::Github, start);
+    collector.record_failure_at(start);
+    collector.record_event_at("push", EventOrigin::Github, start + 30 * MINUTE);
+    collector.record_duplicate_at(start + 30 * MINUTE);
+
+    let stats = collector.snapshot_at(0, start + 59 * MINUTE);
+    assert_eq!(stats.events_per_hour, 2.0);
+    assert_eq!(stats.error_rate, 0.25);
+
+    let stats = collector.snapshot_at(0, start + 60 * MINUTE);
+    assert_eq!(stats.total_events, 2);
+    assert_eq!(stats.events_per_hour, 1.0);
+    assert_eq!(stats.error_rate, 0.0);
+    assert_eq!(stats.uptime_seconds, 3600);
+
+    let stats = collector.snapshot_at(0, start + 120 * MINUTE);
+    assert_eq!(stats.events_per_hour, 0.0);
+    assert_eq!(stats.total_events, 2);
+}
+
+/// Event types beyond the cap are counted under `other`.
+#[test]
+fn test_event_types_capped() {
+    let collector = StatisticsCollector::new();
+    let now = collector.started_at;
+    for i in 0..MAX_EVENT_TYPES {
+        collector.record_event_at(&format!("type_{}", i), EventOrigin::Github, now);
+    }
+    collector.record_event_at("type_0", EventOrigin::Github, now);
+    collector.record_event_at("one_too_many", EventOrigin::Github, now);
+
+    let stats = collector.snapshot_at(0, now);
+    assert_eq!(stats.events_by_type.len(), MAX_EVENT_TYPES + 1);
+    assert_eq!(stats.events_by_type["type_0"], 2);
+    assert_eq!(stats.events_by_type[OTHER_EVENT_TYPE], 1);
+    assert!(!stats.events_by_type.contains_key("one_too_many"));
+}
+
+/// A fresh collector reports zeros, not NaN.
+#[test]
+fn test_empty_collector() {
+    let collector = StatisticsCollector::new();
+    let stats = collector.snapshot_at(0, collector.started_at);
+    assert_eq!(stats.total_events, 0);
+    assert_eq!(stats.error_rate, 0.0);
+    assert!(stats.events_by_type.is_empty());
+}
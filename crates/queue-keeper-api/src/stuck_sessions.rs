@@ -0,0 +1,91 @@
+//! Detection of stuck sessions.
+//!
+//! Events of an ordered session are delivered one after another, so a
+//! poison message that keeps being retried, or a consumer that stopped
+//! reading its session queue, holds up every later event of the session
+//! without failing any webhook request. The [`StuckSessionDetector`] checks
+//! the session tracker periodically for sessions whose oldest in-flight
+//! event is older than `sessions.stuck_after`:
+//!
+//! - `sessions_stuck` is set to the number of stuck sessions, for alerting.
+//! - `session_in_flight_deliveries` is set to the events accepted but not
+//!   yet delivered across all tracked sessions.
+//! - A warning is logged once per session when it becomes stuck, and an
+//!   info message when it recovers.
+//!
+//! `GET /api/sessions/stuck` lists the stuck sessions on demand.
+
+use crate::metrics::ServiceMetrics;
+use queue_keeper_core::{
+    session_tracker::{SessionTracker, StuckSession},
+    SessionId,
+};
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{info, warn};
+
+/// Interval between two checks of the session tracker.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Periodically reports the sessions stuck behind an old in-flight event.
+#[derive(Debug)]
+pub struct StuckSessionDetector {
+    tracker: Arc<SessionTracker>,
+    /// Sessions stuck at the last check, to log transitions once.
+    stuck: Mutex<HashSet<SessionId>>,
+}
+
+impl StuckSessionDetector {
+    /// Create a detector over the sessions of `tracker`.
+    pub fn new(tracker: Arc<SessionTracker>) -> Self {
+        Self {
+            tracker,
+            stuck: Mutex::new(HashSet::new()),
+        }
+    }
+
+    /// Find the stuck sessions and update the session gauges.
+    pub fn check(&self, metrics: &ServiceMetrics) -> Vec<StuckSession> {
+        let sessions = self.tracker.stuck();
+        metrics.sessions_stuck.set(sessions.len() as i64);
+        metrics
+            .session_in_flight_deliveries
+            .set(self.tracker.in_flight_events() as i64);
+
+        let now_stuck: HashSet<SessionId> = sessions.iter().map(|s| s.session_id.clone()).collect();
+        let mut previously_stuck = self.stuck.lock().unwrap();
+        for session in &sessions {
+            if !previously_stuck.contains(&session.session_id) {
+                warn!(
+                    session_id = %session.session_id,
+                    oldest_event_id = %session.oldest_event_id,
+                    age_seconds = session.age_seconds,
+                    in_flight_events = session.in_flight_events,
+                    "Session is stuck behind an undelivered event"
+                );
+            }
+        }
+        for session_id in previously_stuck.difference(&now_stuck) {
+            info!(session_id = %session_id, "Session is no longer stuck");
+        }
+        *previously_stuck = now_stuck;
+
+        sessions
+    }
+
+    /// Run [`check`](Self::check) every [`CHECK_INTERVAL`], forever.
+    pub async fn run(&self, metrics: &ServiceMetrics) {
+        let mut ticker = tokio::time::interval(CHECK_INTERVAL);
+        loop {
+            ticker.tick().await;
+            self.check(metrics);
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "stuck_sessions_tests.rs"]
+mod tests;
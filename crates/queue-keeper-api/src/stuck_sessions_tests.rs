@@ -0,0 +1,43 @@
+//! Tests for stuck session detection.
+
+use super::*;
+use crate::test_fixtures::session_event;
+use queue_keeper_core::session_tracker::{SessionTrackerConfig, TrackedEventStatus};
+
+/// Verify that the gauges follow stuck sessions and in-flight events.
+#[test]
+fn test_check_updates_session_gauges() {
+    let tracker = Arc::new(SessionTracker::new(SessionTrackerConfig {
+        stuck_after: Duration::from_millis(1),
+        ..SessionTrackerConfig::default()
+    }));
+    let detector = StuckSessionDetector::new(tracker.clone());
+    let metrics = ServiceMetrics::default();
+    let poison = session_event("owner/repo/pull_request/1");
+    tracker.record_received(&poison);
+    tracker.record_received(&session_event("owner/repo/pull_request/1"));
+    std::thread::sleep(Duration::from_millis(20));
+
+    let stuck = detector.check(&metrics);
+    assert_eq!(stuck.len(), 1);
+    assert_eq!(stuck[0].oldest_event_id, poison.event_id);
+    assert_eq!(metrics.sessions_stuck.get(), 1);
+    assert_eq!(metrics.session_in_flight_deliveries.get(), 2);
+
+    tracker.record_outcome(
+        poison.session_id.as_ref().unwrap(),
+        poison.event_id,
+        TrackedEventStatus::Failed,
+    );
+    std::thread::sleep(Duration::from_millis(20));
+    // The next event has now waited long enough to hold up the session
+    assert_eq!(detector.check(&metrics).len(), 1);
+    assert_eq!(metrics.session_in_flight_deliveries.get(), 1);
+
+    let tracker = Arc::new(SessionTracker::new(SessionTrackerConfig::default()));
+    tracker.record_received(&session_event("owner/repo/pull_request/2"));
+    assert!(StuckSessionDetector::new(tracker)
+        .check(&metrics)
+        .is_empty());
+    assert_eq!(metrics.sessions_stuck.get(), 0);
+}
@@ -0,0 +1,477 @@
+//! Supervision of background jobs.
+//!
+//! Long-running background work (freshness checks, sweepers, pollers) runs
+//! as jobs registered with a [`Supervisor`] instead of bare `tokio::spawn`
+//! calls:
+//!
+//! - A job that panics or returns an error is restarted after an
+//!   exponential backoff (`supervisor.initial_backoff`, doubling up to
+//!   `supervisor.max_backoff`). A job that ran for `stable_after` before
+//!   failing starts again from the initial backoff.
+//! - With `max_restarts` set, a job that keeps failing is given up on and
+//!   reported as failed.
+//! - Each job's state, restart count and last error are reported by
+//!   `GET /health/deep` as a `job_<name>` check, and restarts are counted in
+//!   `background_job_restarts_total{job}`.
+//! - [`Supervisor::shutdown`] stops jobs one at a time in reverse start
+//!   order, so a job started after another (and possibly depending on it)
+//!   stops first. Each job is asked to stop through its [`JobContext`] and
+//!   aborted if it has not finished within `stop_timeout`.
+//!
+//! A job that returns `Ok(())` without being asked to stop has completed and
+//! is not restarted.
+
+use crate::{metrics::ServiceMetrics, responses::HealthCheckResult};
+use queue_keeper_core::{units::human_duration, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+use tokio::{sync::watch, task::JoinHandle};
+use tracing::{error, info, warn};
+
+/// Future of one run of a job.
+pub type JobFuture = Pin<Box<dyn Future<Output = Result<(), String>> + Send>>;
+
+type JobFactory = Arc<dyn Fn(JobContext) -> JobFuture + Send + Sync>;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Restart and shutdown behaviour (the `supervisor` configuration section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// supervisor:
+///   initial_backoff: 1s
+///   max_backoff: 1m
+///   stable_after: 1m
+///   stop_timeout: 10s
+///   max_restarts: null
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SupervisorConfig {
+    /// Delay before the first restart of a failed job.
+    #[serde(with = "human_duration")]
+    pub initial_backoff: Duration,
+
+    /// Cap on the restart delay, which doubles with each consecutive failure.
+    #[serde(with = "human_duration")]
+    pub max_backoff: Duration,
+
+    /// A job that ran at least this long before failing restarts with the
+    /// initial backoff again.
+    #[serde(with = "human_duration")]
+    pub stable_after: Duration,
+
+    /// How long a job may take to stop at shutdown before it is aborted.
+    #[serde(with = "human_duration")]
+    pub stop_timeout: Duration,
+
+    /// Restarts after which a failing job is given up on. `None` (the
+    /// default) restarts forever.
+    pub max_restarts: Option<u32>,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            stable_after: Duration::from_secs(60),
+            stop_timeout: Duration::from_secs(10),
+            max_restarts: None,
+        }
+    }
+}
+
+impl SupervisorConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first inconsistent setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.initial_backoff.is_zero() {
+            return Err("supervisor.initial_backoff must be greater than zero".to_string());
+        }
+        if self.initial_backoff > self.max_backoff {
+            return Err(
+                "supervisor.initial_backoff must not exceed supervisor.max_backoff".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Job Status
+// ============================================================================
+
+/// Lifecycle state of a supervised job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// The job is running.
+    Running,
+
+    /// The job failed and waits for its restart backoff.
+    Backoff,
+
+    /// The job returned successfully and will not be restarted.
+    Completed,
+
+    /// The job exceeded `max_restarts` and was given up on.
+    Failed,
+
+    /// The job was stopped at shutdown.
+    Stopped,
+
+    /// The job did not stop within `stop_timeout` at shutdown and was
+    /// aborted.
+    Aborted,
+}
+
+/// Health of one supervised job.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JobStatus {
+    pub name: String,
+
+    pub state: JobState,
+
+    /// Number of times the job has been restarted after a failure.
+    pub restarts: u32,
+
+    /// When the current (or last) run started.
+    pub started_at: Timestamp,
+
+    /// Panic message or error of the most recent failure.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error_at: Option<Timestamp>,
+}
+
+impl JobStatus {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            state: JobState::Running,
+            restarts: 0,
+            started_at: Timestamp::now(),
+            last_error: None,
+            last_error_at: None,
+        }
+    }
+
+    /// The `job_<name>` health check: failed jobs are unhealthy, jobs
+    /// waiting to restart are a warning.
+    pub fn health_check(&self) -> HealthCheckResult {
+        let failure = self.last_error.as_deref().unwrap_or("unknown error");
+        let message = match self.state {
+            JobState::Running if self.restarts == 0 => "Running".to_string(),
+            JobState::Running => format!(
+                "Running ({} restart(s), last error: {})",
+                self.restarts, failure
+            ),
+            JobState::Backoff => format!("Restarting after failure: {}", failure),
+            JobState::Completed => "Completed".to_string(),
+            JobState::Failed => format!("Failed after {} restart(s): {}", self.restarts, failure),
+            JobState::Stopped => "Stopped".to_string(),
+            JobState::Aborted => "Aborted at shutdown".to_string(),
+        };
+
+        HealthCheckResult {
+            healthy: self.state != JobState::Failed,
+            warning: self.state == JobState::Backoff,
+            message,
+            duration_ms: 0,
+        }
+    }
+}
+
+// ============================================================================
+// Job Context
+// ============================================================================
+
+/// Handed to each run of a job; tells the job when to stop.
+#[derive(Clone)]
+pub struct JobContext {
+    name: Arc<str>,
+    stop: watch::Receiver<bool>,
+}
+
+impl JobContext {
+    /// Name the job was registered under.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the job has been asked to stop.
+    pub fn is_stopping(&self) -> bool {
+        *self.stop.borrow()
+    }
+
+    /// Resolve once the job is asked to stop.
+    pub async fn stopped(&mut self) {
+        stop_requested(&mut self.stop).await;
+    }
+}
+
+// ============================================================================
+// Supervisor
+// ============================================================================
+
+struct SupervisedJob {
+    name: String,
+    status: Arc<Mutex<JobStatus>>,
+    stop: watch::Sender<bool>,
+    task: Option<JoinHandle<()>>,
+}
+
+/// Runs background jobs and restarts them when they fail (see the module
+/// docs).
+pub struct Supervisor {
+    config: SupervisorConfig,
+    metrics: OnceLock<Arc<ServiceMetrics>>,
+    /// Jobs in start order.
+    jobs: Mutex<Vec<SupervisedJob>>,
+}
+
+impl std::fmt::Debug for Supervisor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Supervisor")
+            .field("config", &self.config)
+            .field("jobs", &self.statuses())
+            .finish()
+    }
+}
+
+impl Supervisor {
+    /// Create a supervisor with no jobs.
+    pub fn new(config: SupervisorConfig) -> Self {
+        Self {
+            config,
+            metrics: OnceLock::new(),
+            jobs: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Count restarts in `metrics`. Only the first call has an effect.
+    pub fn attach_metrics(&self, metrics: Arc<ServiceMetrics>) {
+        let _ = self.metrics.set(metrics);
+    }
+
+    /// Start `job` under supervision as `name`.
+    ///
+    /// `job` is called again for every restart. Must be called from within a
+    /// Tokio runtime.
+    pub fn spawn<F, Fut>(self: &Arc<Self>, name: impl Into<String>, job: F)
+    where
+        F: Fn(JobContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), String>> + Send + 'static,
+    {
+        let name = name.into();
+        let factory: JobFactory = Arc::new(move |ctx| Box::pin(job(ctx)));
+        let status = Arc::new(Mutex::new(JobStatus::new(name.clone())));
+        let (stop, stop_rx) = watch::channel(false);
+
+        let task = tokio::spawn(supervise(
+            Arc::from(name.as_str()),
+            factory,
+            self.config.clone(),
+            status.clone(),
+            stop_rx,
+            Arc::downgrade(self),
+        ));
+
+        info!(job = %name, "Background job started");
+        self.jobs.lock().unwrap().push(SupervisedJob {
+            name,
+            status,
+            stop,
+            task: Some(task),
+        });
+    }
+
+    /// Status of every job, in start order.
+    pub fn statuses(&self) -> Vec<JobStatus> {
+        self.jobs
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|job| job.status.lock().unwrap().clone())
+            .collect()
+    }
+
+    /// One `job_<name>` health check per job.
+    pub fn health_checks(&self) -> Vec<(String, HealthCheckResult)> {
+        self.statuses()
+            .iter()
+            .map(|status| (format!("job_{}", status.name), status.health_check()))
+            .collect()
+    }
+
+    /// Stop every job, newest first, waiting for each before stopping the
+    /// next.
+    pub async fn shutdown(&self) {
+        let stopping: Vec<(String, watch::Sender<bool>, Option<JoinHandle<()>>)> = self
+            .jobs
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .rev()
+            .map(|job| (job.name.clone(), job.stop.clone(), job.task.take()))
+            .collect();
+
+        for (name, stop, task) in stopping {
+            let _ = stop.send(true);
+            if let Some(task) = task {
+                let _ = task.await;
+                info!(job = %name, "Background job stopped");
+            }
+        }
+    }
+
+    fn record_restart(&self, name: &str) {
+        if let Some(metrics) = self.metrics.get() {
+            metrics
+                .background_job_restarts_total
+                .with_label_values(&[name])
+                .inc();
+        }
+    }
+}
+
+/// Resolve once `stop` is set, or its sender (the supervisor) is dropped.
+async fn stop_requested(stop: &mut watch::Receiver<bool>) {
+    let _ = stop.wait_for(|stop| *stop).await;
+}
+
+/// Run `factory` until it completes, is stopped, or is given up on.
+async fn supervise(
+    name: Arc<str>,
+    factory: JobFactory,
+    config: SupervisorConfig,
+    status: Arc<Mutex<JobStatus>>,
+    mut stop: watch::Receiver<bool>,
+    supervisor: std::sync::Weak<Supervisor>,
+) {
+    let set_state = |state: JobState| status.lock().unwrap().state = state;
+    let mut backoff = config.initial_backoff;
+
+    loop {
+        let started = Instant::now();
+        {
+            let mut status = status.lock().unwrap();
+            status.state = JobState::Running;
+            status.started_at = Timestamp::now();
+        }
+
+        let mut run = tokio::spawn(factory(JobContext {
+            name: name.clone(),
+            stop: stop.clone(),
+        }));
+
+        let outcome = tokio::select! {
+            outcome = &mut run => outcome,
+            _ = stop_requested(&mut stop) => {
+                match tokio::time::timeout(config.stop_timeout, &mut run).await {
+                    Ok(_) => set_state(JobState::Stopped),
+                    Err(_) => {
+                        warn!(
+                            job = %name,
+                            timeout_ms = config.stop_timeout.as_millis() as u64,
+                            "Background job did not stop in time; aborting"
+                        );
+                        run.abort();
+                        set_state(JobState::Aborted);
+                    }
+                }
+                return;
+            }
+        };
+
+        if *stop.borrow() {
+            set_state(JobState::Stopped);
+            return;
+        }
+
+        let failure = match outcome {
+            Ok(Ok(())) => {
+                info!(job = %name, "Background job completed");
+                set_state(JobState::Completed);
+                return;
+            }
+            Ok(Err(message)) => message,
+            Err(join_err) if join_err.is_panic() => {
+                let panic = join_err.into_panic();
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| s.to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "non-string panic payload".to_string());
+                format!("panicked: {}", message)
+            }
+            Err(_) => {
+                // Cancelled from outside the supervisor
+                set_state(JobState::Stopped);
+                return;
+            }
+        };
+
+        if started.elapsed() >= config.stable_after {
+            backoff = config.initial_backoff;
+        }
+
+        let restarts = {
+            let mut status = status.lock().unwrap();
+            status.last_error = Some(failure.clone());
+            status.last_error_at = Some(Timestamp::now());
+            status.restarts
+        };
+
+        if config.max_restarts.is_some_and(|max| restarts >= max) {
+            error!(
+                job = %name,
+                error = %failure,
+                restarts = restarts,
+                "Background job failed too often; giving up"
+            );
+            set_state(JobState::Failed);
+            return;
+        }
+
+        error!(
+            job = %name,
+            error = %failure,
+            restart_in_ms = backoff.as_millis() as u64,
+            "Background job failed; restarting"
+        );
+        set_state(JobState::Backoff);
+
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = stop_requested(&mut stop) => {
+                set_state(JobState::Stopped);
+                return;
+            }
+        }
+
+        status.lock().unwrap().restarts += 1;
+        if let Some(supervisor) = supervisor.upgrade() {
+            supervisor.record_restart(&name);
+        }
+        backoff = (backoff * 2).min(config.max_backoff);
+    }
+}
+
+#[cfg(test)]
+#[path = "supervisor_tests.rs"]
+mod tests;
@@ -0,0 +1,218 @@
+//! Tests for background job supervision.
+
+use super::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+fn fast_config() -> SupervisorConfig {
+    SupervisorConfig {
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(40),
+        stable_after: Duration::from_secs(60),
+        stop_timeout: Duration::from_millis(100),
+        max_restarts: None,
+    }
+}
+
+fn status(supervisor: &Supervisor, name: &str) -> JobStatus {
+    supervisor
+        .statuses()
+        .into_iter()
+        .find(|s| s.name == name)
+        .unwrap()
+}
+
+/// Poll until `name` satisfies `done`, failing after two seconds.
+async fn wait_until(supervisor: &Supervisor, name: &str, done: impl Fn(&JobStatus) -> bool) {
+    let deadline = Instant::now() + Duration::from_secs(2);
+    loop {
+        let current = status(supervisor, name);
+        if done(&current) {
+            return;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "job {} never reached the expected state: {:?}",
+            name,
+            current
+        );
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+// ============================================================================
+// Restarts
+// ============================================================================
+
+/// A job that panics is restarted and the panic is reported.
+#[tokio::test]
+async fn test_panicking_job_is_restarted() {
+    let supervisor = Arc::new(Supervisor::new(fast_config()));
+    let runs = Arc::new(AtomicU32::new(0));
+
+    let counter = runs.clone();
+    supervisor.spawn("flaky", move |mut ctx| {
+        let run = counter.fetch_add(1, Ordering::SeqCst);
+        async move {
+            if run == 0 {
+                panic!("poller lost its connection");
+            }
+            ctx.stopped().await;
+            Ok(())
+        }
+    });
+
+    wait_until(&supervisor, "flaky", |s| {
+        s.restarts == 1 && s.state == JobState::Running
+    })
+    .await;
+    let flaky = status(&supervisor, "flaky");
+    assert!(flaky
+        .last_error
+        .unwrap()
+        .contains("poller lost its connection"));
+    assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+    let check = status(&supervisor, "flaky").health_check();
+    assert!(check.healthy);
+    assert!(!check.warning);
+
+    supervisor.shutdown().await;
+    assert_eq!(status(&supervisor, "flaky").state, JobState::Stopped);
+}
+
+/// A job that keeps failing is given up on once `max_restarts` is reached
+/// and fails its health check.
+#[tokio::test]
+async fn test_job_is_given_up_after_max_restarts() {
+    let supervisor = Arc::new(Supervisor::new(SupervisorConfig {
+        max_restarts: Some(2),
+        ..fast_config()
+    }));
+    let runs = Arc::new(AtomicU32::new(0));
+
+    let counter = runs.clone();
+    supervisor.spawn("sweeper", move |_ctx| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async { Err("storage unavailable".to_string()) }
+    });
+
+    wait_until(&supervisor, "sweeper", |s| s.state == JobState::Failed).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 3);
+
+    let checks: std::collections::HashMap<_, _> = supervisor.health_checks().into_iter().collect();
+    let check = &checks["job_sweeper"];
+    assert!(!check.healthy);
+    assert!(check.message.contains("storage unavailable"));
+}
+
+/// A job waiting out its backoff is reported as a warning.
+#[tokio::test]
+async fn test_backoff_is_a_warning() {
+    let supervisor = Arc::new(Supervisor::new(SupervisorConfig {
+        initial_backoff: Duration::from_secs(30),
+        max_backoff: Duration::from_secs(30),
+        ..fast_config()
+    }));
+    supervisor.spawn("poller", |_ctx| async { Err("timeout".to_string()) });
+
+    wait_until(&supervisor, "poller", |s| s.state == JobState::Backoff).await;
+    let check = status(&supervisor, "poller").health_check();
+    assert!(check.healthy);
+    assert!(check.warning);
+
+    // Shutdown interrupts the backoff
+    supervisor.shutdown().await;
+    assert_eq!(status(&supervisor, "poller").state, JobState::Stopped);
+}
+
+/// A job that returns successfully is not restarted.
+#[tokio::test]
+async fn test_completed_job_is_not_restarted() {
+    let supervisor = Arc::new(Supervisor::new(fast_config()));
+    let runs = Arc::new(AtomicU32::new(0));
+
+    let counter = runs.clone();
+    supervisor.spawn("migration", move |_ctx| {
+        counter.fetch_add(1, Ordering::SeqCst);
+        async { Ok(()) }
+    });
+
+    wait_until(&supervisor, "migration", |s| s.state == JobState::Completed).await;
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(runs.load(Ordering::SeqCst), 1);
+}
+
+// ============================================================================
+// Shutdown
+// ============================================================================
+
+/// Jobs stop one at a time, newest first.
+#[tokio::test]
+async fn test_shutdown_stops_jobs_in_reverse_start_order() {
+    let supervisor = Arc::new(Supervisor::new(fast_config()));
+    let stopped = Arc::new(Mutex::new(Vec::new()));
+
+    for name in ["outbox", "sweeper", "poller"] {
+        let stopped = stopped.clone();
+        supervisor.spawn(name, move |mut ctx| {
+            let stopped = stopped.clone();
+            async move {
+                ctx.stopped().await;
+                stopped.lock().unwrap().push(ctx.name().to_string());
+                Ok(())
+            }
+        });
+    }
+
+    supervisor.shutdown().await;
+    assert_eq!(
+        *stopped.lock().unwrap(),
+        vec![
+            "poller".to_string(),
+            "sweeper".to_string(),
+            "outbox".to_string()
+        ]
+    );
+}
+
+/// A job that ignores the stop request is aborted after `stop_timeout`.
+#[tokio::test]
+async fn test_unresponsive_job_is_aborted() {
+    let supervisor = Arc::new(Supervisor::new(fast_config()));
+    supervisor.spawn("stubborn", |_ctx| async {
+        std::future::pending::<()>().await;
+        Ok(())
+    });
+
+    let started = Instant::now();
+    supervisor.shutdown().await;
+    assert!(started.elapsed() < Duration::from_secs(2));
+    assert_eq!(status(&supervisor, "stubborn").state, JobState::Aborted);
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Durations are human-readable and inconsistent backoffs are rejected.
+#[test]
+fn test_config_parsing_and_validation() {
+    let config: SupervisorConfig =
+        serde_json::from_str(r#"{"initial_backoff":"500ms","max_restarts":5}"#).unwrap();
+    assert_eq!(config.initial_backoff, Duration::from_millis(500));
+    assert_eq!(config.max_backoff, Duration::from_secs(60));
+    assert_eq!(config.max_restarts, Some(5));
+    assert!(config.validate().is_ok());
+
+    let inverted = SupervisorConfig {
+        initial_backoff: Duration::from_secs(120),
+        ..SupervisorConfig::default()
+    };
+    assert!(inverted.validate().is_err());
+
+    let zero = SupervisorConfig {
+        initial_backoff: Duration::ZERO,
+        ..SupervisorConfig::default()
+    };
+    assert!(zero.validate().is_err());
+}
@@ -0,0 +1,273 @@
+//! OpenTelemetry trace export.
+//!
+//! When the `telemetry` section enables export, the service sends its
+//! tracing spans to an OTLP collector over gRPC or HTTP. The pipeline itself
+//! is built by the service binary; this module holds what the HTTP layer
+//! needs:
+//!
+//! - [`TelemetryExportConfig`], the `telemetry` configuration section;
+//! - [`TraceSampler`], the sampler of the exported traces, whose ratio
+//!   `PUT /admin/tracing/sampling` changes at runtime;
+//! - trace context propagation: the webhook span continues the caller's W3C
+//!   `traceparent` ([`set_parent_from_headers`]) and its own trace context
+//!   is passed on to queue messages as the `traceparent` attribute
+//!   ([`trace_parent`],
+//!   [`TRACE_PARENT_ATTRIBUTE`](queue_keeper_core::queue_integration::TRACE_PARENT_ATTRIBUTE)),
+//!   so bot consumers can join the same trace.
+//!
+//! Without export the sampler ratio is still reported and can be changed,
+//! and the caller's `traceparent` is passed through to queue messages
+//! unchanged.
+
+use axum::http::HeaderMap;
+use opentelemetry::{
+    propagation::{Extractor, TextMapPropagator},
+    trace::{Link, SamplingResult, SpanContext, SpanKind, TraceContextExt, TraceId},
+    Context, KeyValue,
+};
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{Sampler, ShouldSample},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+use tracing::{debug, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// OTLP transport of exported spans.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OtlpProtocol {
+    /// OTLP over gRPC, by default to `http://localhost:4317`.
+    #[default]
+    Grpc,
+
+    /// OTLP over HTTP with protobuf bodies, by default to
+    /// `http://localhost:4318/v1/traces`.
+    Http,
+}
+
+/// Trace export settings (the `telemetry` configuration section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// telemetry:
+///   enabled: true
+///   protocol: grpc
+///   endpoint: "http://otel-collector:4317"
+///   sampling_ratio: 0.1
+///   export_timeout_seconds: 10
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TelemetryExportConfig {
+    /// Whether spans are exported. Off by default.
+    pub enabled: bool,
+
+    /// OTLP transport.
+    pub protocol: OtlpProtocol,
+
+    /// Collector URL. For `http` this is the full traces URL, ending in
+    /// `/v1/traces`. Defaults to `OTEL_EXPORTER_OTLP_ENDPOINT`, then to the
+    /// protocol's local default.
+    pub endpoint: Option<String>,
+
+    /// Fraction of new traces that are sampled, in `[0.0, 1.0]`. Traces
+    /// continued from a caller keep the caller's decision. Defaults to 0.1
+    /// in production and 1.0 elsewhere.
+    pub sampling_ratio: Option<f64>,
+
+    /// Time allowed for one export request.
+    pub export_timeout_seconds: u64,
+}
+
+impl Default for TelemetryExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            protocol: OtlpProtocol::Grpc,
+            endpoint: None,
+            sampling_ratio: None,
+            export_timeout_seconds: 10,
+        }
+    }
+}
+
+impl TelemetryExportConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(endpoint) = &self.endpoint {
+            if !endpoint.starts_with("http://") && !endpoint.starts_with("https://") {
+                return Err(format!(
+                    "telemetry.endpoint must be an http:// or https:// URL, got '{}'",
+                    endpoint
+                ));
+            }
+        }
+        if let Some(ratio) = self.sampling_ratio {
+            validate_ratio(ratio).map_err(|e| format!("telemetry.sampling_ratio: {}", e))?;
+        }
+        if self.export_timeout_seconds == 0 {
+            return Err("telemetry.export_timeout_seconds must be greater than 0".to_string());
+        }
+        Ok(())
+    }
+
+    /// The sampling ratio the service starts with: the configured ratio,
+    /// else 0.1 in `production` and 1.0 in any other environment.
+    pub fn initial_sampling_ratio(&self, environment: &str) -> f64 {
+        self.sampling_ratio
+            .unwrap_or(if environment == "production" {
+                0.1
+            } else {
+                1.0
+            })
+    }
+}
+
+fn validate_ratio(ratio: f64) -> Result<(), String> {
+    if (0.0..=1.0).contains(&ratio) {
+        Ok(())
+    } else {
+        Err("Sampling ratio must be between 0.0 and 1.0 inclusive".to_string())
+    }
+}
+
+// ============================================================================
+// Sampler
+// ============================================================================
+
+/// Sampler of exported traces with a ratio that can change at runtime.
+///
+/// Samples like `ParentBased(TraceIdRatioBased(ratio))`: spans with a parent
+/// follow the parent's decision, new traces are sampled with the current
+/// ratio. Clones share the ratio, so the clone installed in the tracer
+/// provider sees changes made through the admin API.
+#[derive(Debug, Clone)]
+pub struct TraceSampler {
+    ratio_bits: Arc<AtomicU64>,
+}
+
+impl TraceSampler {
+    /// Create a sampler; `ratio` is clamped to `[0.0, 1.0]`.
+    pub fn new(ratio: f64) -> Self {
+        Self {
+            ratio_bits: Arc::new(AtomicU64::new(ratio.clamp(0.0, 1.0).to_bits())),
+        }
+    }
+
+    /// Current sampling ratio.
+    pub fn ratio(&self) -> f64 {
+        f64::from_bits(self.ratio_bits.load(Ordering::Relaxed))
+    }
+
+    /// Change the sampling ratio of new traces.
+    ///
+    /// # Errors
+    /// Returns an error when `ratio` is outside `[0.0, 1.0]`.
+    pub fn set_ratio(&self, ratio: f64) -> Result<(), String> {
+        validate_ratio(ratio)?;
+        self.ratio_bits.store(ratio.to_bits(), Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+impl ShouldSample for TraceSampler {
+    fn should_sample(
+        &self,
+        parent_context: Option<&Context>,
+        trace_id: TraceId,
+        name: &str,
+        span_kind: &SpanKind,
+        attributes: &[KeyValue],
+        links: &[Link],
+    ) -> SamplingResult {
+        Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(self.ratio()))).should_sample(
+            parent_context,
+            trace_id,
+            name,
+            span_kind,
+            attributes,
+            links,
+        )
+    }
+}
+
+// ============================================================================
+// Trace Context Propagation
+// ============================================================================
+
+/// Reads W3C trace context headers from an HTTP request.
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+/// The remote span context of the request's `traceparent` header, if it is
+/// a valid W3C trace context.
+fn remote_span_context(headers: &HeaderMap) -> Option<SpanContext> {
+    let context = TraceContextPropagator::new().extract(&HeaderExtractor(headers));
+    let span_context = context.span().span_context().clone();
+    span_context.is_valid().then_some(span_context)
+}
+
+/// Continue the caller's trace: make the request's `traceparent` the parent
+/// of `span`.
+///
+/// Does nothing when the request carries no valid `traceparent` or spans
+/// are not exported.
+pub fn set_parent_from_headers(span: &Span, headers: &HeaderMap) {
+    let Some(remote) = remote_span_context(headers) else {
+        return;
+    };
+    if let Err(e) = span.set_parent(Context::new().with_remote_span_context(remote)) {
+        debug!(error = %e, "Could not continue the caller's trace");
+    }
+}
+
+/// The W3C `traceparent` to hand on with an event received in `span`.
+///
+/// The span's own context when spans are exported; otherwise the caller's
+/// valid `traceparent`, so consumers still join the caller's trace.
+pub fn trace_parent(span: &Span, headers: &HeaderMap) -> Option<String> {
+    let context = span.context();
+    let own = context.span().span_context().clone();
+    let span_context = if own.is_valid() {
+        own
+    } else {
+        remote_span_context(headers)?
+    };
+    Some(format_trace_parent(&span_context))
+}
+
+/// Format a span context as a W3C `traceparent` value.
+fn format_trace_parent(span_context: &SpanContext) -> String {
+    format!(
+        "00-{}-{}-{:02x}",
+        span_context.trace_id(),
+        span_context.span_id(),
+        span_context.trace_flags().to_u8()
+    )
+}
+
+#[cfg(test)]
+#[path = "telemetry_tests.rs"]
+mod tests;
@@ -0,0 +1,130 @@
+//! Tests for trace export configuration, sampling and propagation.
+
+use super::*;
+use opentelemetry::trace::{SamplingDecision, SpanId, TraceFlags, TraceState};
+
+const TRACE_PARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+fn sample(sampler: &TraceSampler, parent: Option<&Context>) -> SamplingDecision {
+    sampler
+        .should_sample(
+            parent,
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            "webhook",
+            &SpanKind::Server,
+            &[],
+            &[],
+        )
+        .decision
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Export is off by default and the settings are checked.
+#[test]
+fn test_config_validation() {
+    let config = TelemetryExportConfig::default();
+    assert!(!config.enabled);
+    assert!(config.validate().is_ok());
+
+    let config: TelemetryExportConfig = serde_json::from_value(serde_json::json!({
+        "enabled": true,
+        "protocol": "http",
+        "endpoint": "http://collector:4318/v1/traces",
+        "sampling_ratio": 0.25
+    }))
+    .unwrap();
+    assert_eq!(config.protocol, OtlpProtocol::Http);
+    assert_eq!(config.sampling_ratio, Some(0.25));
+    assert!(config.validate().is_ok());
+
+    for invalid in [
+        TelemetryExportConfig {
+            endpoint: Some("collector:4317".to_string()),
+            ..Default::default()
+        },
+        TelemetryExportConfig {
+            sampling_ratio: Some(1.5),
+            ..Default::default()
+        },
+        TelemetryExportConfig {
+            export_timeout_seconds: 0,
+            ..Default::default()
+        },
+    ] {
+        assert!(invalid.validate().is_err(), "{:?}", invalid);
+    }
+}
+
+/// Without a configured ratio, production samples a tenth of new traces.
+#[test]
+fn test_initial_sampling_ratio() {
+    let config = TelemetryExportConfig::default();
+    assert_eq!(config.initial_sampling_ratio("production"), 0.1);
+    assert_eq!(config.initial_sampling_ratio("development"), 1.0);
+
+    let config = TelemetryExportConfig {
+        sampling_ratio: Some(0.5),
+        ..Default::default()
+    };
+    assert_eq!(config.initial_sampling_ratio("production"), 0.5);
+}
+
+// ============================================================================
+// Sampler
+// ============================================================================
+
+/// Ratio changes apply to every clone of the sampler.
+#[test]
+fn test_sampler_ratio_changes_at_runtime() {
+    let sampler = TraceSampler::new(1.0);
+    let installed = sampler.clone();
+    assert_eq!(sample(&installed, None), SamplingDecision::RecordAndSample);
+
+    sampler.set_ratio(0.0).unwrap();
+    assert_eq!(installed.ratio(), 0.0);
+    assert_eq!(sample(&installed, None), SamplingDecision::Drop);
+
+    assert!(sampler.set_ratio(-0.1).is_err());
+    assert!(sampler.set_ratio(1.1).is_err());
+    assert_eq!(installed.ratio(), 0.0);
+}
+
+/// Traces continued from a sampled caller stay sampled whatever the ratio.
+#[test]
+fn test_sampler_follows_parent_decision() {
+    let sampler = TraceSampler::new(0.0);
+    let parent = Context::new().with_remote_span_context(SpanContext::new(
+        TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+        SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    ));
+    assert_eq!(
+        sample(&sampler, Some(&parent)),
+        SamplingDecision::RecordAndSample
+    );
+}
+
+// ============================================================================
+// Propagation
+// ============================================================================
+
+/// Without span export the caller's valid `traceparent` is passed on.
+#[test]
+fn test_trace_parent_passes_caller_context_through() {
+    let mut headers = HeaderMap::new();
+    assert_eq!(trace_parent(&Span::none(), &headers), None);
+
+    headers.insert("traceparent", "not-a-trace-context".parse().unwrap());
+    assert_eq!(trace_parent(&Span::none(), &headers), None);
+
+    headers.insert("traceparent", TRACE_PARENT.parse().unwrap());
+    assert_eq!(
+        trace_parent(&Span::none(), &headers).as_deref(),
+        Some(TRACE_PARENT)
+    );
+}
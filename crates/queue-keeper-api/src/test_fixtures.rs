@@ -0,0 +1,102 @@
+//! Events, bots and delivery results shared by the unit tests of this crate.
+
+use queue_keeper_core::{
+    bot_config::{BotConfigurationSettings, BotSpecificConfig, BotSubscription, EventTypePattern},
+    queue_integration::{FailedDelivery, SuccessfulDelivery},
+    BotConfiguration, BotName, QueueName, SessionId, WrappedEvent,
+};
+use queue_runtime::MessageId;
+use std::sync::Arc;
+
+/// A GitHub `pull_request` `opened` event without a session.
+pub(crate) fn event() -> WrappedEvent {
+    event_with_payload(serde_json::json!({}))
+}
+
+/// A GitHub `pull_request` `opened` event carrying `payload`.
+pub(crate) fn event_with_payload(payload: serde_json::Value) -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        None,
+        payload,
+        None,
+    )
+}
+
+/// A GitHub event of `event_type` without an action or a session.
+pub(crate) fn event_of_type(event_type: &str) -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        event_type.to_string(),
+        None,
+        None,
+        serde_json::json!({}),
+        None,
+    )
+}
+
+/// A GitHub `pull_request` `opened` event in `session`.
+pub(crate) fn session_event(session: &str) -> WrappedEvent {
+    WrappedEvent {
+        session_id: Some(SessionId::new(session.to_string()).unwrap()),
+        ..event()
+    }
+}
+
+pub(crate) fn bot(name: &str) -> BotName {
+    BotName::new(name).unwrap()
+}
+
+/// The queue of bot `name`, `queue-keeper-<name>`.
+pub(crate) fn bot_queue(name: &str) -> QueueName {
+    QueueName::new(format!("queue-keeper-{}", name)).unwrap()
+}
+
+/// Subscription of bot `name` to `events`, delivering to its queue.
+pub(crate) fn subscription(name: &str, events: EventTypePattern) -> BotSubscription {
+    BotSubscription {
+        name: bot(name),
+        queue: bot_queue(name),
+        events: vec![events],
+        ordered: false,
+        repository_filter: None,
+        config: BotSpecificConfig::new(),
+        encryption: None,
+        transform: None,
+        projection: None,
+        http: None,
+        sender_filter: None,
+    }
+}
+
+/// Bot configuration where every bot subscribes to every event.
+pub(crate) fn bot_config(names: &[&str]) -> Arc<BotConfiguration> {
+    Arc::new(BotConfiguration {
+        bots: names
+            .iter()
+            .map(|name| subscription(name, EventTypePattern::Wildcard("*".to_string())))
+            .collect(),
+        settings: BotConfigurationSettings::default(),
+    })
+}
+
+/// A delivery accepted by the queue of bot `name`.
+pub(crate) fn delivered(name: &str) -> SuccessfulDelivery {
+    SuccessfulDelivery {
+        bot_name: bot(name),
+        queue_name: bot_queue(name),
+        message_id: MessageId::new(),
+    }
+}
+
+/// A delivery rejected by the queue of bot `name`.
+pub(crate) fn failed(name: &str, is_transient: bool) -> FailedDelivery {
+    FailedDelivery {
+        bot_name: bot(name),
+        queue_name: bot_queue(name),
+        error: "queue unavailable".to_string(),
+        is_transient,
+    }
+}
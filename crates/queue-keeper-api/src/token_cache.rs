@@ -0,0 +1,827 @@
+//! Shared cache of GitHub App installation tokens.
+//!
+//! An installation token is valid for an hour and minting one counts against
+//! the App's rate limit. When every replica keeps its own cache, each replica
+//! mints its own token for the same installation. [`TokenCache`] keeps the
+//! tokens in a [`TokenCacheStore`] that all replicas can share:
+//!
+//! - [`InMemoryTokenCacheStore`] keeps tokens in process memory (the
+//!   default). Each replica mints its own tokens.
+//! - `RedisTokenCacheStore` (cargo feature `redis-token-cache`) keeps tokens
+//!   in Redis, so a token minted by one replica is used by all of them.
+//!   Tokens are encrypted at rest with AES-256-GCM using a key read from
+//!   Key Vault, and each key expires in Redis together with its token.
+//!
+//! A cached token is refreshed `refresh_before` ahead of its expiry, brought
+//! forward by a random share of `refresh_jitter` that each process picks
+//! once at startup. Replicas therefore reach the refresh point at different
+//! times: the first to get there mints and stores a new token, and the
+//! others pick that token up instead of minting their own.
+//!
+//! Within a process, concurrent requests for the same installation wait for
+//! a single mint. Store errors never fail a request: the token is minted
+//! directly and the error is logged, so an unavailable Redis degrades to
+//! per-replica minting rather than to an outage.
+//!
+//! [`TokenCache`] also implements github-bot-sdk's
+//! [`TokenCache`](github_bot_sdk::auth::TokenCache) trait and is the cache of
+//! the GitHub App authentication provider (see [`crate::github_app`]). App
+//! JWTs are signed locally and are only cached in process memory.
+
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use github_bot_sdk::{
+    auth::{
+        GitHubAppId, InstallationId, InstallationPermissions, JsonWebToken, RepositoryId,
+        TokenCache as SdkTokenCache,
+    },
+    error::CacheError,
+};
+use queue_keeper_core::{
+    field_encryption::{decrypt_fields, encrypt_fields, FieldEncryptionKey},
+    key_vault::{KeyVaultProvider, SecretName},
+    units::human_duration,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::errors::ConfigError;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Installation token cache settings (the `token_cache` configuration
+/// section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// token_cache:
+///   refresh_before: 5m
+///   refresh_jitter: 2m
+///   store:
+///     backend: redis
+///     # Supply via QK__TOKEN_CACHE__STORE__URL
+///     url: "rediss://:password@cache.example.com:6380/0"
+///     key_prefix: queue-keeper:tokens
+///     encryption_key_secret: queue-keeper-token-cache-key
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenCacheConfig {
+    /// How long before expiry a cached token is replaced.
+    #[serde(with = "human_duration")]
+    pub refresh_before: Duration,
+
+    /// Upper bound of the random extra lead time each process adds to
+    /// `refresh_before`, spreading refreshes across replicas.
+    #[serde(with = "human_duration")]
+    pub refresh_jitter: Duration,
+
+    /// Where cached tokens are kept.
+    pub store: TokenCacheStoreConfig,
+}
+
+impl Default for TokenCacheConfig {
+    fn default() -> Self {
+        Self {
+            refresh_before: Duration::from_secs(300),
+            refresh_jitter: Duration::from_secs(120),
+            store: TokenCacheStoreConfig::default(),
+        }
+    }
+}
+
+impl TokenCacheConfig {
+    /// Validate the token cache configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] when the refresh lead time reaches
+    /// the one-hour installation token lifetime, or the store settings are
+    /// invalid (see [`TokenCacheStoreConfig::validate`]).
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.refresh_before + self.refresh_jitter >= TOKEN_LIFETIME {
+            return Err(ConfigError::Invalid {
+                message: "token_cache.refresh_before plus token_cache.refresh_jitter must be \
+                          less than the one-hour installation token lifetime"
+                    .to_string(),
+            });
+        }
+        self.store.validate()
+    }
+}
+
+/// Backend for cached installation tokens.
+#[derive(Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "backend", rename_all = "snake_case")]
+pub enum TokenCacheStoreConfig {
+    /// Tokens are kept in process memory and lost on restart.
+    #[default]
+    InMemory,
+
+    /// Tokens are kept in Redis, encrypted, and shared by every replica
+    /// using the same `key_prefix` and key.
+    Redis {
+        /// Redis connection URL (`redis://` or `rediss://`).
+        ///
+        /// Excluded from serialization (e.g. `/admin/config` response) because
+        /// the URL may embed a password.
+        #[serde(default, skip_serializing)]
+        url: String,
+
+        /// Prefix for every key written by the cache.
+        #[serde(default = "TokenCacheStoreConfig::default_key_prefix")]
+        key_prefix: String,
+
+        /// Name of the Key Vault secret holding the base64-encoded 256-bit
+        /// key that tokens are encrypted with.
+        encryption_key_secret: String,
+    },
+}
+
+impl TokenCacheStoreConfig {
+    /// Configuration name of the selected backend (the `backend` tag).
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            Self::InMemory => "in_memory",
+            Self::Redis { .. } => "redis",
+        }
+    }
+
+    /// Validate the token cache store configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ConfigError::Invalid`] when the Redis backend has an empty
+    /// `url`, `key_prefix` or `encryption_key_secret`, or a URL that is not
+    /// `redis://` or `rediss://`.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        match self {
+            Self::InMemory => Ok(()),
+            Self::Redis {
+                url,
+                key_prefix,
+                encryption_key_secret,
+            } => {
+                let invalid = |message: &str| {
+                    Err(ConfigError::Invalid {
+                        message: format!("token_cache.store.{}", message),
+                    })
+                };
+                if url.is_empty() {
+                    return invalid("url must be set for the redis backend");
+                }
+                if !url.starts_with("redis://") && !url.starts_with("rediss://") {
+                    return invalid("url must use the redis:// or rediss:// scheme");
+                }
+                if key_prefix.is_empty() {
+                    return invalid("key_prefix must not be empty");
+                }
+                if encryption_key_secret.is_empty() {
+                    return invalid("encryption_key_secret must be set for the redis backend");
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn default_key_prefix() -> String {
+        "queue-keeper:tokens".to_string()
+    }
+}
+
+/// The Redis URL is redacted in `Debug` output.
+impl std::fmt::Debug for TokenCacheStoreConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InMemory => f.write_str("InMemory"),
+            Self::Redis {
+                url,
+                key_prefix,
+                encryption_key_secret,
+            } => f
+                .debug_struct("Redis")
+                .field("url", &(!url.is_empty()).then_some("<REDACTED>"))
+                .field("key_prefix", key_prefix)
+                .field("encryption_key_secret", encryption_key_secret)
+                .finish(),
+        }
+    }
+}
+
+/// Lifetime of a GitHub App installation token.
+const TOKEN_LIFETIME: Duration = Duration::from_secs(3600);
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors returned by a [`TokenCacheStore`].
+#[derive(Debug, thiserror::Error)]
+pub enum TokenCacheError {
+    /// The backend could not be reached or rejected the operation.
+    #[error("Token cache store unavailable: {message}")]
+    Unavailable { message: String },
+
+    /// A stored entry could not be decrypted or decoded.
+    #[error("Token cache entry for installation {installation_id} is invalid: {message}")]
+    InvalidEntry {
+        installation_id: u64,
+        message: String,
+    },
+}
+
+// ============================================================================
+// Tokens
+// ============================================================================
+
+/// An installation access token and its expiry.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallationToken {
+    /// The token value.
+    pub token: String,
+    /// When GitHub stops accepting the token.
+    pub expires_at: DateTime<Utc>,
+    /// Permissions GitHub granted the token.
+    #[serde(default)]
+    pub permissions: InstallationPermissions,
+    /// IDs of the repositories the token is limited to; empty for all of
+    /// the installation's repositories.
+    #[serde(default)]
+    pub repositories: Vec<u64>,
+}
+
+impl InstallationToken {
+    /// Create a token expiring at `expires_at`, without permissions.
+    pub fn new(token: impl Into<String>, expires_at: DateTime<Utc>) -> Self {
+        Self {
+            token: token.into(),
+            expires_at,
+            permissions: InstallationPermissions::default(),
+            repositories: Vec::new(),
+        }
+    }
+
+    /// Convert into the github-bot-sdk token for `installation_id`.
+    pub fn to_sdk(&self, installation_id: u64) -> github_bot_sdk::auth::InstallationToken {
+        github_bot_sdk::auth::InstallationToken::new(
+            self.token.clone(),
+            InstallationId::new(installation_id),
+            self.expires_at,
+            self.permissions.clone(),
+            self.repositories
+                .iter()
+                .copied()
+                .map(RepositoryId::new)
+                .collect(),
+        )
+    }
+}
+
+impl From<&github_bot_sdk::auth::InstallationToken> for InstallationToken {
+    fn from(token: &github_bot_sdk::auth::InstallationToken) -> Self {
+        Self {
+            token: token.token().to_string(),
+            expires_at: token.expires_at(),
+            permissions: token.permissions().clone(),
+            repositories: token.repositories().iter().map(|r| r.as_u64()).collect(),
+        }
+    }
+}
+
+impl std::fmt::Debug for InstallationToken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InstallationToken")
+            .field("token", &"<REDACTED>")
+            .field("expires_at", &self.expires_at)
+            .finish()
+    }
+}
+
+// ============================================================================
+// Store Trait
+// ============================================================================
+
+/// Storage for installation tokens, keyed by installation ID.
+#[async_trait]
+pub trait TokenCacheStore: Send + Sync {
+    /// Return the cached token for `installation_id`, if any. Expired tokens
+    /// may be returned; the caller checks expiry.
+    async fn get(&self, installation_id: u64)
+        -> Result<Option<InstallationToken>, TokenCacheError>;
+
+    /// Store `token` for `installation_id`, replacing any previous token.
+    async fn put(
+        &self,
+        installation_id: u64,
+        token: &InstallationToken,
+    ) -> Result<(), TokenCacheError>;
+
+    /// Remove the cached token for `installation_id`.
+    async fn remove(&self, installation_id: u64) -> Result<(), TokenCacheError>;
+}
+
+/// Process-local [`TokenCacheStore`].
+#[derive(Debug, Default)]
+pub struct InMemoryTokenCacheStore {
+    tokens: Mutex<HashMap<u64, InstallationToken>>,
+}
+
+impl InMemoryTokenCacheStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl TokenCacheStore for InMemoryTokenCacheStore {
+    async fn get(
+        &self,
+        installation_id: u64,
+    ) -> Result<Option<InstallationToken>, TokenCacheError> {
+        Ok(self.tokens.lock().unwrap().get(&installation_id).cloned())
+    }
+
+    async fn put(
+        &self,
+        installation_id: u64,
+        token: &InstallationToken,
+    ) -> Result<(), TokenCacheError> {
+        self.tokens
+            .lock()
+            .unwrap()
+            .insert(installation_id, token.clone());
+        Ok(())
+    }
+
+    async fn remove(&self, installation_id: u64) -> Result<(), TokenCacheError> {
+        self.tokens.lock().unwrap().remove(&installation_id);
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Encryption
+// ============================================================================
+
+/// JSON pointer of the encrypted record within a stored entry.
+const RECORD_POINTER: &str = "/record";
+
+#[derive(Serialize, Deserialize)]
+struct TokenRecord {
+    installation_id: u64,
+    #[serde(flatten)]
+    token: InstallationToken,
+}
+
+/// Encrypt `token` for storage outside the process.
+///
+/// The installation ID is encrypted together with the token and bound into
+/// the ciphertext, so an entry copied to another installation's key is
+/// rejected by [`open_token`].
+pub fn seal_token(
+    installation_id: u64,
+    token: &InstallationToken,
+    key: &FieldEncryptionKey,
+    key_id: &str,
+) -> Result<String, TokenCacheError> {
+    let invalid = |message: String| TokenCacheError::InvalidEntry {
+        installation_id,
+        message,
+    };
+    let record = TokenRecord {
+        installation_id,
+        token: token.clone(),
+    };
+    let mut entry = serde_json::json!({ "record": record });
+    encrypt_fields(
+        &mut entry,
+        &[RECORD_POINTER.to_string()],
+        key,
+        key_id,
+        &installation_id.to_string(),
+    )
+    .map_err(|e| invalid(e.to_string()))?;
+    serde_json::to_string(&entry).map_err(|e| invalid(e.to_string()))
+}
+
+/// Decrypt an entry written by [`seal_token`] for `installation_id`.
+pub fn open_token(
+    installation_id: u64,
+    sealed: &str,
+    key: &FieldEncryptionKey,
+) -> Result<InstallationToken, TokenCacheError> {
+    let invalid = |message: String| TokenCacheError::InvalidEntry {
+        installation_id,
+        message,
+    };
+    let mut entry: serde_json::Value =
+        serde_json::from_str(sealed).map_err(|e| invalid(e.to_string()))?;
+    if decrypt_fields(&mut entry, key, &installation_id.to_string())
+        .map_err(|e| invalid(e.to_string()))?
+        == 0
+    {
+        return Err(invalid("entry is not encrypted".to_string()));
+    }
+    let record: TokenRecord = entry
+        .get("record")
+        .cloned()
+        .ok_or_else(|| invalid("entry has no record".to_string()))
+        .and_then(|record| serde_json::from_value(record).map_err(|e| invalid(e.to_string())))?;
+    if record.installation_id != installation_id {
+        return Err(invalid(format!(
+            "entry belongs to installation {}",
+            record.installation_id
+        )));
+    }
+    Ok(record.token)
+}
+
+// ============================================================================
+// Token Cache
+// ============================================================================
+
+/// Installation token cache with jittered refresh (see the module docs).
+pub struct TokenCache {
+    store: Arc<dyn TokenCacheStore>,
+    refresh_before: Duration,
+    refresh_jitter: Duration,
+    /// This process's share of `refresh_jitter`, in `[0, 1)`.
+    jitter_fraction: f64,
+    /// One lock per installation so that concurrent callers mint once.
+    mint_locks: Mutex<HashMap<u64, Arc<tokio::sync::Mutex<()>>>>,
+    /// App JWTs by App ID, kept in process only.
+    jwts: Mutex<HashMap<u64, JsonWebToken>>,
+}
+
+impl std::fmt::Debug for TokenCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TokenCache")
+            .field("refresh_before", &self.refresh_before)
+            .field("refresh_jitter", &self.refresh_jitter)
+            .field("jitter_fraction", &self.jitter_fraction)
+            .finish()
+    }
+}
+
+impl TokenCache {
+    /// Create a cache over `store` with a random jitter share.
+    pub fn new(store: Arc<dyn TokenCacheStore>, config: &TokenCacheConfig) -> Self {
+        Self {
+            store,
+            refresh_before: config.refresh_before,
+            refresh_jitter: config.refresh_jitter,
+            jitter_fraction: rand::random::<f64>(),
+            mint_locks: Mutex::new(HashMap::new()),
+            jwts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Use a fixed share of `refresh_jitter` (clamped to `[0, 1]`) instead
+    /// of a random one.
+    pub fn with_jitter_fraction(mut self, fraction: f64) -> Self {
+        self.jitter_fraction = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// The time at which this process stops using `token` and mints a new
+    /// one.
+    pub fn refresh_at(&self, token: &InstallationToken) -> DateTime<Utc> {
+        let lead = self.refresh_before + self.refresh_jitter.mul_f64(self.jitter_fraction);
+        token.expires_at - chrono::Duration::milliseconds(lead.as_millis() as i64)
+    }
+
+    /// Return a cached token for `installation_id`, or mint, cache and
+    /// return a new one with `mint`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error from `mint`. Store errors are logged, not returned.
+    pub async fn get_or_mint<F, Fut, E>(
+        &self,
+        installation_id: u64,
+        mint: F,
+    ) -> Result<InstallationToken, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<InstallationToken, E>>,
+    {
+        if let Some(token) = self.cached(installation_id).await {
+            return Ok(token);
+        }
+
+        let lock = self
+            .mint_locks
+            .lock()
+            .unwrap()
+            .entry(installation_id)
+            .or_default()
+            .clone();
+        let _guard = lock.lock().await;
+
+        // Another caller (or replica) may have minted while we waited
+        if let Some(token) = self.cached(installation_id).await {
+            return Ok(token);
+        }
+
+        let token = mint().await?;
+        debug!(
+            installation_id = installation_id,
+            expires_at = %token.expires_at,
+            "Minted installation token"
+        );
+        self.store_token(installation_id, &token).await;
+        Ok(token)
+    }
+
+    /// Drop the cached token for `installation_id`, e.g. after GitHub
+    /// rejected it.
+    pub async fn invalidate(&self, installation_id: u64) {
+        if let Err(e) = self.store.remove(installation_id).await {
+            warn!(
+                installation_id = installation_id,
+                error = %e,
+                "Failed to remove installation token"
+            );
+        }
+    }
+
+    async fn store_token(&self, installation_id: u64, token: &InstallationToken) {
+        if let Err(e) = self.store.put(installation_id, token).await {
+            warn!(
+                installation_id = installation_id,
+                error = %e,
+                "Failed to store installation token"
+            );
+        }
+    }
+
+    async fn cached(&self, installation_id: u64) -> Option<InstallationToken> {
+        match self.store.get(installation_id).await {
+            Ok(Some(token)) if Utc::now() < self.refresh_at(&token) => Some(token),
+            Ok(_) => None,
+            Err(e) => {
+                warn!(
+                    installation_id = installation_id,
+                    error = %e,
+                    "Failed to read installation token cache"
+                );
+                None
+            }
+        }
+    }
+}
+
+/// The cache of github-bot-sdk's installation-token provider.
+///
+/// An installation token is returned until this process's refresh point
+/// ([`TokenCache::refresh_at`]); store errors are logged and reported as
+/// a cache miss, so the provider mints a new token.
+#[async_trait]
+impl SdkTokenCache for TokenCache {
+    async fn get_jwt(&self, app_id: GitHubAppId) -> Result<Option<JsonWebToken>, CacheError> {
+        Ok(self
+            .jwts
+            .lock()
+            .unwrap()
+            .get(&app_id.as_u64())
+            .filter(|jwt| !jwt.is_expired())
+            .cloned())
+    }
+
+    async fn store_jwt(&self, jwt: JsonWebToken) -> Result<(), CacheError> {
+        self.jwts.lock().unwrap().insert(jwt.app_id().as_u64(), jwt);
+        Ok(())
+    }
+
+    async fn get_installation_token(
+        &self,
+        installation_id: InstallationId,
+    ) -> Result<Option<github_bot_sdk::auth::InstallationToken>, CacheError> {
+        let installation_id = installation_id.as_u64();
+        Ok(self
+            .cached(installation_id)
+            .await
+            .map(|token| token.to_sdk(installation_id)))
+    }
+
+    async fn store_installation_token(
+        &self,
+        token: github_bot_sdk::auth::InstallationToken,
+    ) -> Result<(), CacheError> {
+        let installation_id = token.installation_id().as_u64();
+        debug!(
+            installation_id = installation_id,
+            expires_at = %token.expires_at(),
+            "Minted installation token"
+        );
+        self.store_token(installation_id, &InstallationToken::from(&token))
+            .await;
+        Ok(())
+    }
+
+    async fn invalidate_installation_token(
+        &self,
+        installation_id: InstallationId,
+    ) -> Result<(), CacheError> {
+        self.invalidate(installation_id.as_u64()).await;
+        Ok(())
+    }
+
+    /// Drops expired JWTs. Stored installation tokens expire in the store.
+    fn cleanup_expired_tokens(&self) {
+        self.jwts.lock().unwrap().retain(|_, jwt| !jwt.is_expired());
+    }
+}
+
+/// Build the token cache configured in `config`.
+///
+/// # Errors
+///
+/// - [`ConfigError::ProviderNotCompiled`] when the Redis backend is selected
+///   but the `redis-token-cache` feature is disabled.
+/// - [`ConfigError::Invalid`] when the Redis backend is selected without a
+///   Key Vault, or the encryption key or Redis client cannot be loaded.
+pub async fn build_token_cache(
+    config: &TokenCacheConfig,
+    key_vault: Option<&Arc<dyn KeyVaultProvider>>,
+) -> Result<TokenCache, ConfigError> {
+    let store: Arc<dyn TokenCacheStore> = match &config.store {
+        TokenCacheStoreConfig::InMemory => Arc::new(InMemoryTokenCacheStore::new()),
+        #[cfg(feature = "redis-token-cache")]
+        TokenCacheStoreConfig::Redis {
+            url,
+            key_prefix,
+            encryption_key_secret,
+        } => {
+            let key = load_encryption_key(encryption_key_secret, key_vault).await?;
+            let store = redis_store::RedisTokenCacheStore::connect(
+                url,
+                key_prefix.clone(),
+                key,
+                encryption_key_secret.clone(),
+            )
+            .await
+            .map_err(|e| ConfigError::Invalid {
+                message: format!("token_cache.store: {}", e),
+            })?;
+            Arc::new(store)
+        }
+        #[cfg(not(feature = "redis-token-cache"))]
+        other @ TokenCacheStoreConfig::Redis { .. } => {
+            let _ = key_vault;
+            return Err(ConfigError::ProviderNotCompiled {
+                component: "Token cache store".to_string(),
+                provider: other.backend_name().to_string(),
+                feature: "redis-token-cache".to_string(),
+            });
+        }
+    };
+    Ok(TokenCache::new(store, config))
+}
+
+/// Read the token encryption key from Key Vault.
+#[cfg_attr(not(feature = "redis-token-cache"), allow(dead_code))]
+async fn load_encryption_key(
+    secret: &str,
+    key_vault: Option<&Arc<dyn KeyVaultProvider>>,
+) -> Result<FieldEncryptionKey, ConfigError> {
+    let invalid = |message: String| ConfigError::Invalid {
+        message: format!("token_cache.store.encryption_key_secret: {}", message),
+    };
+    let key_vault =
+        key_vault.ok_or_else(|| invalid("a `key_vault` section is required".to_string()))?;
+    let name = SecretName::new(secret).map_err(|e| invalid(e.to_string()))?;
+    let value = key_vault
+        .get_secret(&name)
+        .await
+        .map_err(|e| invalid(e.to_string()))?;
+    FieldEncryptionKey::from_secret(&value).map_err(|e| invalid(e.to_string()))
+}
+
+// ============================================================================
+// Redis Store
+// ============================================================================
+
+#[cfg(feature = "redis-token-cache")]
+pub use redis_store::RedisTokenCacheStore;
+
+#[cfg(feature = "redis-token-cache")]
+mod redis_store {
+    use async_trait::async_trait;
+    use chrono::Utc;
+    use queue_keeper_core::field_encryption::FieldEncryptionKey;
+    use redis::aio::ConnectionManager;
+
+    use super::{open_token, seal_token, InstallationToken, TokenCacheError, TokenCacheStore};
+
+    /// Redis-backed [`TokenCacheStore`] shared by all replicas.
+    ///
+    /// Each token is stored at `{prefix}:installation:{id}` as an encrypted
+    /// entry (see [`seal_token`]) that expires in Redis when the token does.
+    pub struct RedisTokenCacheStore {
+        connection: ConnectionManager,
+        key_prefix: String,
+        key: FieldEncryptionKey,
+        key_id: String,
+    }
+
+    impl RedisTokenCacheStore {
+        /// Connect to the Redis server at `url`. Tokens are encrypted with
+        /// `key`, recorded in each entry as `key_id`.
+        ///
+        /// # Errors
+        ///
+        /// Returns [`TokenCacheError::Unavailable`] when the URL is invalid
+        /// or the initial connection fails.
+        pub async fn connect(
+            url: &str,
+            key_prefix: String,
+            key: FieldEncryptionKey,
+            key_id: String,
+        ) -> Result<Self, TokenCacheError> {
+            let client = redis::Client::open(url).map_err(unavailable)?;
+            let connection = ConnectionManager::new(client).await.map_err(unavailable)?;
+            Ok(Self {
+                connection,
+                key_prefix,
+                key,
+                key_id,
+            })
+        }
+
+        fn entry_key(&self, installation_id: u64) -> String {
+            format!("{}:installation:{}", self.key_prefix, installation_id)
+        }
+    }
+
+    #[async_trait]
+    impl TokenCacheStore for RedisTokenCacheStore {
+        async fn get(
+            &self,
+            installation_id: u64,
+        ) -> Result<Option<InstallationToken>, TokenCacheError> {
+            let mut connection = self.connection.clone();
+            let sealed: Option<String> = redis::cmd("GET")
+                .arg(self.entry_key(installation_id))
+                .query_async(&mut connection)
+                .await
+                .map_err(unavailable)?;
+            sealed
+                .map(|sealed| open_token(installation_id, &sealed, &self.key))
+                .transpose()
+        }
+
+        async fn put(
+            &self,
+            installation_id: u64,
+            token: &InstallationToken,
+        ) -> Result<(), TokenCacheError> {
+            let ttl_ms = (token.expires_at - Utc::now()).num_milliseconds();
+            if ttl_ms <= 0 {
+                return Ok(());
+            }
+            let sealed = seal_token(installation_id, token, &self.key, &self.key_id)?;
+            let mut connection = self.connection.clone();
+            let _: Option<String> = redis::cmd("SET")
+                .arg(self.entry_key(installation_id))
+                .arg(sealed)
+                .arg("PX")
+                .arg(ttl_ms)
+                .query_async(&mut connection)
+                .await
+                .map_err(unavailable)?;
+            Ok(())
+        }
+
+        async fn remove(&self, installation_id: u64) -> Result<(), TokenCacheError> {
+            let mut connection = self.connection.clone();
+            let _: i64 = redis::cmd("DEL")
+                .arg(self.entry_key(installation_id))
+                .query_async(&mut connection)
+                .await
+                .map_err(unavailable)?;
+            Ok(())
+        }
+    }
+
+    fn unavailable(e: redis::RedisError) -> TokenCacheError {
+        TokenCacheError::Unavailable {
+            message: e.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "token_cache_tests.rs"]
+mod tests;
@@ -0,0 +1,416 @@
+//! Tests for the shared installation token cache.
+
+use super::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+fn token_expiring_in(name: &str, minutes: i64) -> InstallationToken {
+    InstallationToken::new(name, Utc::now() + chrono::Duration::minutes(minutes))
+}
+
+fn cache(store: Arc<dyn TokenCacheStore>, fraction: f64) -> TokenCache {
+    TokenCache::new(store, &TokenCacheConfig::default()).with_jitter_fraction(fraction)
+}
+
+fn key() -> FieldEncryptionKey {
+    FieldEncryptionKey::from_bytes([7u8; 32])
+}
+
+/// A store whose every operation fails.
+struct UnavailableStore;
+
+#[async_trait]
+impl TokenCacheStore for UnavailableStore {
+    async fn get(&self, _: u64) -> Result<Option<InstallationToken>, TokenCacheError> {
+        Err(TokenCacheError::Unavailable {
+            message: "connection refused".to_string(),
+        })
+    }
+
+    async fn put(&self, _: u64, _: &InstallationToken) -> Result<(), TokenCacheError> {
+        Err(TokenCacheError::Unavailable {
+            message: "connection refused".to_string(),
+        })
+    }
+
+    async fn remove(&self, _: u64) -> Result<(), TokenCacheError> {
+        Err(TokenCacheError::Unavailable {
+            message: "connection refused".to_string(),
+        })
+    }
+}
+
+// ============================================================================
+// Refresh
+// ============================================================================
+
+/// The refresh point is `refresh_before` plus this process's jitter share
+/// ahead of expiry.
+#[test]
+fn test_refresh_at_includes_jitter_share() {
+    let store = Arc::new(InMemoryTokenCacheStore::new());
+    let token = token_expiring_in("t", 60);
+
+    let earliest = cache(store.clone(), 0.0).refresh_at(&token);
+    assert_eq!(token.expires_at - earliest, chrono::Duration::minutes(5));
+
+    let latest = cache(store.clone(), 1.0).refresh_at(&token);
+    assert_eq!(token.expires_at - latest, chrono::Duration::minutes(7));
+
+    let middle = cache(store, 0.5).refresh_at(&token);
+    assert_eq!(token.expires_at - middle, chrono::Duration::minutes(6));
+}
+
+/// A fresh cached token is reused; one inside the refresh window is
+/// replaced.
+#[tokio::test]
+async fn test_cached_token_is_reused_until_refresh() {
+    let store = Arc::new(InMemoryTokenCacheStore::new());
+    let cache = cache(store.clone(), 0.5);
+    let mints = AtomicU32::new(0);
+    let mint = |name: &'static str, minutes: i64| {
+        mints.fetch_add(1, Ordering::SeqCst);
+        async move { Ok::<_, String>(token_expiring_in(name, minutes)) }
+    };
+
+    let first = cache.get_or_mint(42, || mint("first", 60)).await.unwrap();
+    let again = cache.get_or_mint(42, || mint("second", 60)).await.unwrap();
+    assert_eq!(again.token, first.token);
+    assert_eq!(mints.load(Ordering::SeqCst), 1);
+
+    // Five minutes left is inside the 6m (5m + 0.5 × 2m) refresh lead
+    store
+        .put(42, &token_expiring_in("expiring", 5))
+        .await
+        .unwrap();
+    let refreshed = cache.get_or_mint(42, || mint("third", 60)).await.unwrap();
+    assert_eq!(refreshed.token, "third");
+    assert_eq!(store.get(42).await.unwrap().unwrap().token, "third");
+}
+
+/// A token minted by one replica is picked up by another sharing the store.
+#[tokio::test]
+async fn test_replicas_share_minted_tokens() {
+    let store: Arc<dyn TokenCacheStore> = Arc::new(InMemoryTokenCacheStore::new());
+    let replica_a = cache(store.clone(), 0.2);
+    let replica_b = cache(store, 0.8);
+
+    replica_a
+        .get_or_mint(7, || async { Ok::<_, String>(token_expiring_in("a", 60)) })
+        .await
+        .unwrap();
+    let token = replica_b
+        .get_or_mint(7, || async { Err::<InstallationToken, _>("minted twice") })
+        .await
+        .unwrap();
+    assert_eq!(token.token, "a");
+}
+
+/// Concurrent callers for one installation wait for a single mint.
+#[tokio::test]
+async fn test_concurrent_callers_mint_once() {
+    let cache = Arc::new(cache(Arc::new(InMemoryTokenCacheStore::new()), 0.0));
+    let mints = Arc::new(AtomicU32::new(0));
+
+    let callers: Vec<_> = (0..8)
+        .map(|_| {
+            let cache = cache.clone();
+            let mints = mints.clone();
+            tokio::spawn(async move {
+                cache
+                    .get_or_mint(9, || async move {
+                        mints.fetch_add(1, Ordering::SeqCst);
+                        tokio::time::sleep(Duration::from_millis(20)).await;
+                        Ok::<_, String>(token_expiring_in("shared", 60))
+                    })
+                    .await
+                    .unwrap()
+            })
+        })
+        .collect();
+    for caller in callers {
+        assert_eq!(caller.await.unwrap().token, "shared");
+    }
+    assert_eq!(mints.load(Ordering::SeqCst), 1);
+}
+
+/// An unavailable store degrades to minting directly.
+#[tokio::test]
+async fn test_unavailable_store_still_mints() {
+    let cache = cache(Arc::new(UnavailableStore), 0.0);
+    let token = cache
+        .get_or_mint(1, || async {
+            Ok::<_, String>(token_expiring_in("direct", 60))
+        })
+        .await
+        .unwrap();
+    assert_eq!(token.token, "direct");
+    cache.invalidate(1).await;
+}
+
+/// Mint errors are returned and nothing is cached.
+#[tokio::test]
+async fn test_mint_error_is_returned() {
+    let store = Arc::new(InMemoryTokenCacheStore::new());
+    let cache = cache(store.clone(), 0.0);
+    let error = cache
+        .get_or_mint(3, || async { Err::<InstallationToken, _>("rate limited") })
+        .await
+        .unwrap_err();
+    assert_eq!(error, "rate limited");
+    assert!(store.get(3).await.unwrap().is_none());
+}
+
+/// Invalidated tokens are minted again.
+#[tokio::test]
+async fn test_invalidate_forces_mint() {
+    let cache = cache(Arc::new(InMemoryTokenCacheStore::new()), 0.0);
+    cache
+        .get_or_mint(5, || async {
+            Ok::<_, String>(token_expiring_in("old", 60))
+        })
+        .await
+        .unwrap();
+    cache.invalidate(5).await;
+    let token = cache
+        .get_or_mint(5, || async {
+            Ok::<_, String>(token_expiring_in("new", 60))
+        })
+        .await
+        .unwrap();
+    assert_eq!(token.token, "new");
+}
+
+// ============================================================================
+// SDK Token Cache
+// ============================================================================
+
+fn sdk_token(installation_id: u64, minutes: i64) -> github_bot_sdk::auth::InstallationToken {
+    let permissions = InstallationPermissions {
+        issues: github_bot_sdk::auth::PermissionLevel::Write,
+        ..Default::default()
+    };
+    github_bot_sdk::auth::InstallationToken::new(
+        "ghs_sdk".to_string(),
+        InstallationId::new(installation_id),
+        Utc::now() + chrono::Duration::minutes(minutes),
+        permissions,
+        vec![RepositoryId::new(7)],
+    )
+}
+
+/// Tokens stored through the SDK trait keep their permissions and
+/// repositories, and are shared through the store.
+#[tokio::test]
+async fn test_sdk_tokens_round_trip_through_store() {
+    let store: Arc<dyn TokenCacheStore> = Arc::new(InMemoryTokenCacheStore::new());
+    let first = cache(store.clone(), 0.0);
+    let second = cache(store.clone(), 0.0);
+
+    first
+        .store_installation_token(sdk_token(4, 60))
+        .await
+        .unwrap();
+    let token = second
+        .get_installation_token(InstallationId::new(4))
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(token.token(), "ghs_sdk");
+    assert_eq!(token.installation_id(), InstallationId::new(4));
+    assert_eq!(token.repositories(), &[RepositoryId::new(7)]);
+    assert_eq!(
+        token.permissions().issues,
+        github_bot_sdk::auth::PermissionLevel::Write
+    );
+}
+
+/// Tokens past this process's refresh point are a cache miss for the SDK.
+#[tokio::test]
+async fn test_sdk_token_past_refresh_point_is_miss() {
+    let cache = cache(Arc::new(InMemoryTokenCacheStore::new()), 0.0);
+    cache
+        .store_installation_token(sdk_token(4, 4))
+        .await
+        .unwrap();
+    assert!(cache
+        .get_installation_token(InstallationId::new(4))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+/// Store errors are a cache miss, not an error.
+#[tokio::test]
+async fn test_sdk_store_errors_are_misses() {
+    let cache = cache(Arc::new(UnavailableStore), 0.0);
+    assert!(cache
+        .store_installation_token(sdk_token(4, 60))
+        .await
+        .is_ok());
+    assert!(cache
+        .get_installation_token(InstallationId::new(4))
+        .await
+        .unwrap()
+        .is_none());
+}
+
+/// Invalidation through the SDK trait removes the stored token.
+#[tokio::test]
+async fn test_sdk_invalidate_removes_token() {
+    let store: Arc<dyn TokenCacheStore> = Arc::new(InMemoryTokenCacheStore::new());
+    let cache = cache(store.clone(), 0.0);
+    cache
+        .store_installation_token(sdk_token(4, 60))
+        .await
+        .unwrap();
+    cache
+        .invalidate_installation_token(InstallationId::new(4))
+        .await
+        .unwrap();
+    assert!(store.get(4).await.unwrap().is_none());
+}
+
+/// App JWTs are cached until they expire.
+#[tokio::test]
+async fn test_sdk_jwts_cached_until_expiry() {
+    let cache = cache(Arc::new(InMemoryTokenCacheStore::new()), 0.0);
+    let app_id = GitHubAppId::new(42);
+    cache
+        .store_jwt(JsonWebToken::new(
+            "jwt".to_string(),
+            app_id,
+            Utc::now() + chrono::Duration::minutes(10),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(cache.get_jwt(app_id).await.unwrap().unwrap().token(), "jwt");
+
+    cache
+        .store_jwt(JsonWebToken::new(
+            "expired".to_string(),
+            app_id,
+            Utc::now() - chrono::Duration::minutes(1),
+        ))
+        .await
+        .unwrap();
+    assert!(cache.get_jwt(app_id).await.unwrap().is_none());
+    cache.cleanup_expired_tokens();
+    assert!(cache.jwts.lock().unwrap().is_empty());
+}
+
+// ============================================================================
+// Encryption
+// ============================================================================
+
+/// Sealed entries do not contain the token and open to the original.
+#[test]
+fn test_seal_and_open_round_trip() {
+    let token = token_expiring_in("ghs_secretvalue", 60);
+    let sealed = seal_token(11, &token, &key(), "token-cache-key").unwrap();
+    assert!(!sealed.contains("ghs_secretvalue"));
+    assert!(sealed.contains("token-cache-key"));
+
+    assert_eq!(open_token(11, &sealed, &key()).unwrap(), token);
+}
+
+/// Entries opened with another key or under another installation are
+/// rejected.
+#[test]
+fn test_open_rejects_wrong_key_and_installation() {
+    let sealed = seal_token(11, &token_expiring_in("t", 60), &key(), "k").unwrap();
+
+    let other_key = FieldEncryptionKey::from_bytes([8u8; 32]);
+    assert!(matches!(
+        open_token(11, &sealed, &other_key),
+        Err(TokenCacheError::InvalidEntry { .. })
+    ));
+
+    assert!(matches!(
+        open_token(12, &sealed, &key()),
+        Err(TokenCacheError::InvalidEntry {
+            installation_id: 12,
+            ..
+        })
+    ));
+
+    let plaintext =
+        r#"{"record":{"installation_id":11,"token":"t","expires_at":"2030-01-01T00:00:00Z"}}"#;
+    assert!(open_token(11, plaintext, &key()).is_err());
+}
+
+/// Token values are not printed.
+#[test]
+fn test_token_debug_is_redacted() {
+    let rendered = format!("{:?}", token_expiring_in("ghs_secretvalue", 60));
+    assert!(!rendered.contains("ghs_secretvalue"));
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// The Redis backend parses with human-readable durations and validates.
+#[test]
+fn test_config_parses_redis_backend() {
+    let config: TokenCacheConfig = serde_json::from_str(
+        r#"{
+            "refresh_before": "10m",
+            "store": {
+                "backend": "redis",
+                "url": "redis://localhost:6379",
+                "encryption_key_secret": "token-cache-key"
+            }
+        }"#,
+    )
+    .unwrap();
+    assert_eq!(config.refresh_before, Duration::from_secs(600));
+    assert_eq!(config.refresh_jitter, Duration::from_secs(120));
+    assert_eq!(config.store.backend_name(), "redis");
+    assert!(config.validate().is_ok());
+    assert!(!format!("{:?}", config).contains("localhost"));
+}
+
+/// Invalid lead times and incomplete Redis settings are rejected.
+#[test]
+fn test_config_validation() {
+    assert!(TokenCacheConfig::default().validate().is_ok());
+
+    let too_early = TokenCacheConfig {
+        refresh_before: Duration::from_secs(3500),
+        ..Default::default()
+    };
+    assert!(too_early.validate().is_err());
+
+    let no_key = TokenCacheStoreConfig::Redis {
+        url: "redis://localhost:6379".to_string(),
+        key_prefix: "queue-keeper:tokens".to_string(),
+        encryption_key_secret: String::new(),
+    };
+    assert!(no_key.validate().is_err());
+
+    let bad_scheme = TokenCacheStoreConfig::Redis {
+        url: "http://localhost:6379".to_string(),
+        key_prefix: "queue-keeper:tokens".to_string(),
+        encryption_key_secret: "k".to_string(),
+    };
+    assert!(bad_scheme.validate().is_err());
+}
+
+/// Selecting Redis without the cargo feature is a configuration error.
+#[cfg(not(feature = "redis-token-cache"))]
+#[tokio::test]
+async fn test_redis_without_feature_is_rejected() {
+    let config = TokenCacheConfig {
+        store: TokenCacheStoreConfig::Redis {
+            url: "redis://localhost:6379".to_string(),
+            key_prefix: "queue-keeper:tokens".to_string(),
+            encryption_key_secret: "k".to_string(),
+        },
+        ..Default::default()
+    };
+    assert!(matches!(
+        build_token_cache(&config, None).await,
+        Err(ConfigError::ProviderNotCompiled { .. })
+    ));
+}
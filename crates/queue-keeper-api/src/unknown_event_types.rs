@@ -0,0 +1,177 @@
+//! Strict handling of unknown GitHub event types.
+//!
+//! Event types missing from the known-types registry
+//! ([`KNOWN_EVENT_TYPES`] plus `unknown_event_types.known_types`) get an
+//! `unknown` entity and session, so ordering and routing for them are
+//! guesswork. Every such delivery from a GitHub provider is counted in
+//! `unknown_event_types_total{provider,event_type,action}` and logged, so a
+//! new GitHub event family is noticed promptly. What happens to it depends on
+//! `unknown_event_types.mode`:
+//!
+//! - `accept` (default): processed and routed like any other event.
+//! - `reject`: refused with `400 Bad Request` before the payload is stored.
+//! - `review`: processed and stored, but sent to `review_queue` instead of
+//!   the bot queues, for someone to decide on a mapping.
+//!
+//! Generic providers define their own event types and are never checked.
+//!
+//! [`KNOWN_EVENT_TYPES`]: queue_keeper_core::webhook::KNOWN_EVENT_TYPES
+
+use queue_keeper_core::{
+    event_error, event_info, queue_integration::event_filter_attributes,
+    webhook::is_known_event_type, WrappedEvent,
+};
+use queue_runtime::{Message, QueueClient, QueueName};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Message attribute marking why an event was sent to the review queue.
+pub const ATTRIBUTE_REVIEW_REASON: &str = "review_reason";
+
+/// [`ATTRIBUTE_REVIEW_REASON`] value for unknown event types.
+pub const REVIEW_REASON_UNKNOWN_EVENT_TYPE: &str = "unknown_event_type";
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// What to do with a delivery whose event type is unknown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnknownEventTypeMode {
+    /// Process and route the event as usual.
+    #[default]
+    Accept,
+
+    /// Refuse the delivery.
+    Reject,
+
+    /// Send the event to the review queue instead of the bot queues.
+    Review,
+}
+
+impl UnknownEventTypeMode {
+    /// Stable label used in metrics and logs.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Accept => "accept",
+            Self::Reject => "reject",
+            Self::Review => "review",
+        }
+    }
+}
+
+/// Handling of unknown event types (the `unknown_event_types` configuration
+/// section).
+///
+/// # YAML example
+///
+/// ```yaml
+/// unknown_event_types:
+///   mode: review
+///   review_queue: "queue-keeper-review"
+///   known_types: ["merge_group", "security_advisory"]
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnknownEventTypesConfig {
+    /// What to do with unknown event types.
+    #[serde(default)]
+    pub mode: UnknownEventTypeMode,
+
+    /// Queue receiving unknown events in `review` mode.
+    #[serde(default)]
+    pub review_queue: Option<String>,
+
+    /// Event types to treat as known in addition to [`KNOWN_EVENT_TYPES`].
+    /// They keep the `unknown` entity and session.
+    ///
+    /// [`KNOWN_EVENT_TYPES`]: queue_keeper_core::webhook::KNOWN_EVENT_TYPES
+    #[serde(default)]
+    pub known_types: Vec<String>,
+}
+
+impl UnknownEventTypesConfig {
+    /// Validate the review queue.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        match (&self.mode, &self.review_queue) {
+            (UnknownEventTypeMode::Review, None) => Err(
+                "unknown_event_types.review_queue is required when mode is 'review'".to_string(),
+            ),
+            (_, Some(queue)) => QueueName::new(queue.clone())
+                .map(|_| ())
+                .map_err(|e| format!("unknown_event_types.review_queue is invalid: {}", e)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `event_type` is in the known-types registry.
+    pub fn is_known(&self, event_type: &str) -> bool {
+        is_known_event_type(event_type) || self.known_types.iter().any(|t| t == event_type)
+    }
+}
+
+// ============================================================================
+// Review Delivery
+// ============================================================================
+
+/// Send `event` to the review queue, logging the outcome.
+pub async fn deliver_for_review(
+    event: WrappedEvent,
+    queue_client: Arc<dyn QueueClient>,
+    queue: QueueName,
+) {
+    let message = match review_message(&event) {
+        Ok(message) => message,
+        Err(e) => {
+            event_error!(
+                event,
+                error = %e,
+                "Failed to serialize unknown event for the review queue"
+            );
+            return;
+        }
+    };
+    match queue_client.send_message(&queue, message).await {
+        Ok(message_id) => {
+            event_info!(
+                event,
+                review_queue = queue.as_str(),
+                message_id = %message_id,
+                "Unknown event type sent to the review queue"
+            );
+        }
+        Err(e) => {
+            event_error!(
+                event,
+                review_queue = queue.as_str(),
+                error = %e,
+                "Failed to send unknown event type to the review queue"
+            );
+        }
+    }
+}
+
+/// Build the review queue message for an event.
+///
+/// Carries the same filter attributes as a bot delivery, plus the review
+/// reason.
+fn review_message(event: &WrappedEvent) -> Result<Message, serde_json::Error> {
+    let body = serde_json::to_vec(event)?;
+    let mut message = Message::new(body.into())
+        .with_correlation_id(event.correlation_id.to_string())
+        .with_attribute(
+            ATTRIBUTE_REVIEW_REASON.to_string(),
+            REVIEW_REASON_UNKNOWN_EVENT_TYPE.to_string(),
+        );
+    for (name, value) in event_filter_attributes(event) {
+        message = message.with_attribute(name.to_string(), value);
+    }
+    Ok(message)
+}
+
+#[cfg(test)]
+#[path = "unknown_event_types_tests.rs"]
+mod tests;
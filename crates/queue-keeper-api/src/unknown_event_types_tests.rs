@@ -0,0 +1,70 @@
+//! Tests for strict handling of unknown event types.
+
+use super::*;
+use crate::test_fixtures::event_of_type;
+
+/// Verify that unknown event types are accepted by default.
+#[test]
+fn test_default_config_accepts() {
+    let config = UnknownEventTypesConfig::default();
+
+    assert_eq!(config.mode, UnknownEventTypeMode::Accept);
+    assert!(config.validate().is_ok());
+}
+
+/// Verify that review mode needs a valid review queue.
+#[test]
+fn test_review_mode_requires_queue() {
+    let mut config = UnknownEventTypesConfig {
+        mode: UnknownEventTypeMode::Review,
+        ..UnknownEventTypesConfig::default()
+    };
+    assert!(config.validate().unwrap_err().contains("review_queue"));
+
+    config.review_queue = Some("Not A Queue!".to_string());
+    assert!(config.validate().is_err());
+
+    config.review_queue = Some("queue-keeper-review".to_string());
+    assert!(config.validate().is_ok());
+}
+
+/// Verify that the registry and configured extra types are known.
+#[test]
+fn test_is_known_includes_configured_types() {
+    let config = UnknownEventTypesConfig {
+        known_types: vec!["merge_group".to_string()],
+        ..UnknownEventTypesConfig::default()
+    };
+
+    assert!(config.is_known("pull_request"));
+    assert!(config.is_known("merge_group"));
+    assert!(!config.is_known("security_advisory"));
+}
+
+/// Verify that the mode is read in snake case.
+#[test]
+fn test_mode_deserializes_in_snake_case() {
+    let config: UnknownEventTypesConfig = serde_json::from_value(serde_json::json!({
+        "mode": "reject",
+        "known_types": ["merge_group"]
+    }))
+    .unwrap();
+
+    assert_eq!(config.mode, UnknownEventTypeMode::Reject);
+    assert_eq!(config.known_types, vec!["merge_group".to_string()]);
+}
+
+/// Verify that review messages carry the review reason and filter attributes.
+#[test]
+fn test_review_message_attributes() {
+    let message = review_message(&event_of_type("merge_group")).unwrap();
+
+    assert_eq!(
+        message.attributes.get(ATTRIBUTE_REVIEW_REASON),
+        Some(&REVIEW_REASON_UNKNOWN_EVENT_TYPE.to_string())
+    );
+    assert_eq!(
+        message.attributes.get("event_type"),
+        Some(&"merge_group".to_string())
+    );
+}
@@ -0,0 +1,51 @@
+//! # Shell Completions
+//!
+//! Generates the scripts of `queue-keeper completions <SHELL>` for bash,
+//! zsh, fish, PowerShell and elvish with clap_complete.
+//!
+//! Arguments taking a value enum, such as output formats, service modes and
+//! shells, complete from the enum. Event type arguments accept any type, so
+//! the script offers the GitHub event types the service recognises
+//! ([`KNOWN_EVENT_TYPES`]) as candidates for them; parsing is unchanged and
+//! other types, or `type.action` filters, are still accepted.
+
+use clap::{builder::PossibleValuesParser, Command};
+use clap_complete::Shell;
+use queue_keeper_core::webhook::KNOWN_EVENT_TYPES;
+use std::io::Write;
+
+/// IDs of the arguments that complete to [`KNOWN_EVENT_TYPES`].
+pub const EVENT_TYPE_ARGS: &[&str] = &["event_type"];
+
+/// `command` with event type candidates on every [`EVENT_TYPE_ARGS`]
+/// argument of it and its subcommands.
+///
+/// Only used to generate scripts: the candidates would reject other event
+/// types if the command parsed arguments.
+pub fn completion_command(command: Command) -> Command {
+    let command = command.mut_args(|arg| {
+        if EVENT_TYPE_ARGS.contains(&arg.get_id().as_str()) {
+            arg.value_parser(PossibleValuesParser::new(KNOWN_EVENT_TYPES.iter().copied()))
+        } else {
+            arg
+        }
+    });
+    let subcommands: Vec<String> = command
+        .get_subcommands()
+        .map(|subcommand| subcommand.get_name().to_string())
+        .collect();
+    subcommands.into_iter().fold(command, |command, name| {
+        command.mut_subcommand(name, completion_command)
+    })
+}
+
+/// Write the completion script of `command` for `shell` to `out`.
+pub fn generate(shell: Shell, command: Command, out: &mut dyn Write) {
+    let mut command = completion_command(command);
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
+}
+
+#[cfg(test)]
+#[path = "completions_tests.rs"]
+mod tests;
@@ -0,0 +1,62 @@
+//! Tests for shell completion generation.
+
+use super::*;
+use crate::Cli;
+use clap::{CommandFactory, Parser};
+
+fn script(shell: Shell) -> String {
+    let mut out = Vec::new();
+    generate(shell, Cli::command(), &mut out);
+    String::from_utf8(out).unwrap()
+}
+
+fn event_type_values(command: &Command, path: &[&str]) -> Vec<String> {
+    let command = path.iter().fold(command, |command, name| {
+        command.find_subcommand(name).unwrap()
+    });
+    let arg = command
+        .get_arguments()
+        .find(|arg| arg.get_id().as_str() == "event_type")
+        .unwrap();
+    arg.get_possible_values()
+        .iter()
+        .map(|value| value.get_name().to_string())
+        .collect()
+}
+
+/// Every shell gets a script naming the binary; those that complete
+/// argument values offer the known event types and the output formats.
+#[test]
+fn test_scripts_complete_event_types_and_formats() {
+    assert!(script(Shell::PowerShell).contains("queue-keeper"));
+    for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+        let script = script(shell);
+        assert!(script.contains("queue-keeper"), "{:?}", shell);
+        assert!(script.contains("pull_request_review"), "{:?}", shell);
+        assert!(script.contains("yaml"), "{:?}", shell);
+    }
+}
+
+/// The event type arguments of every subcommand complete to the known types.
+#[test]
+fn test_event_type_arguments_have_candidates() {
+    let command = completion_command(Cli::command());
+    for path in [&["monitor"][..], &["events", "list"][..]] {
+        let values = event_type_values(&command, path);
+        assert_eq!(values.len(), KNOWN_EVENT_TYPES.len(), "{:?}", path);
+        assert!(values.contains(&"pull_request".to_string()), "{:?}", path);
+    }
+}
+
+/// Parsing still accepts event types outside the known list.
+#[test]
+fn test_parsing_accepts_any_event_type() {
+    assert!(event_type_values(&Cli::command(), &["monitor"]).is_empty());
+    let cli = Cli::try_parse_from([
+        "queue-keeper",
+        "monitor",
+        "--event-type",
+        "pull_request.opened",
+    ]);
+    assert!(cli.is_ok());
+}
@@ -0,0 +1,426 @@
+//! # Event Management
+//!
+//! Implements the `queue-keeper events` commands against a running service:
+//!
+//! - `events list` pages through `GET /api/events`, filtered by event type,
+//!   repository, session and receipt time;
+//! - `events show` reads one event envelope from `GET /api/events/{id}`;
+//! - `events replay` re-delivers a stored event through
+//!   `POST /admin/events/{id}/replay`. A target queue is resolved to its bot
+//!   with a dry run first, as the service replays to bots, not queues;
+//! - `events delete` soft-deletes an event through
+//!   `DELETE /admin/events/{id}`. The service records a tombstone with the
+//!   acting principal and the reason, hides the event from listings and
+//!   purges it after `event_deletion.purge_after`; the command prints when
+//!   that happens.
+//!
+//! Every request carries the admin API token of the connection, when one is
+//! configured.
+
+use crate::sessions::encode_component;
+use queue_keeper_core::{webhook::WrappedEvent, EventId, SessionId, Timestamp};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Largest page the service returns from `GET /api/events`.
+const MAX_PAGE_SIZE: usize = 500;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors managing events through the service.
+#[derive(Debug, thiserror::Error)]
+pub enum EventsError {
+    #[error("Event not found: {event_id}")]
+    NotFound { event_id: String },
+
+    #[error("Event {event_id} is not routed to queue {queue}")]
+    QueueNotRouted { event_id: String, queue: String },
+
+    #[error("Events request failed: {message}")]
+    Request { message: String },
+}
+
+// ============================================================================
+// Listing Types
+// ============================================================================
+
+/// An event as listed by `GET /api/events`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventSummary {
+    pub event_id: EventId,
+    pub event_type: String,
+    pub repository: String,
+    pub session_id: SessionId,
+    pub occurred_at: Timestamp,
+    pub status: String,
+}
+
+/// Page of events returned by `GET /api/events`.
+#[derive(Debug, Deserialize)]
+struct EventPage {
+    events: Vec<EventSummary>,
+    #[serde(default)]
+    next_cursor: Option<String>,
+}
+
+/// Body of `GET /api/events/{id}`.
+#[derive(Debug, Deserialize)]
+struct EventDetailBody {
+    event: WrappedEvent,
+}
+
+/// Filters of `queue-keeper events list`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventFilter {
+    /// Event type, e.g. `pull_request`.
+    pub event_type: Option<String>,
+    /// Repository full name (`owner/repo`).
+    pub repository: Option<String>,
+    /// Session ID, e.g. `owner/repo/pull_request/7`.
+    pub session: Option<String>,
+    /// Only events received at or after this time.
+    pub since: Option<Timestamp>,
+}
+
+impl EventFilter {
+    /// Query string of `GET /api/events` for this filter and page size.
+    fn query(&self, page_size: usize) -> String {
+        let mut query = format!("limit={}&include_total=false", page_size);
+        if let Some(event_type) = &self.event_type {
+            query.push_str(&format!("&event_type={}", encode_component(event_type)));
+        }
+        if let Some(repository) = &self.repository {
+            query.push_str(&format!("&repository={}", encode_component(repository)));
+        }
+        if let Some(session) = &self.session {
+            query.push_str(&format!("&session_id={}", encode_component(session)));
+        }
+        if let Some(since) = &self.since {
+            query.push_str(&format!("&since={}", encode_component(&since.to_rfc3339())));
+        }
+        query
+    }
+}
+
+/// Render events as a human-readable table.
+pub fn events_table(events: &[EventSummary]) -> String {
+    let mut out = format!(
+        "{:<26} {:<24} {:<32} {:<10} {}\n",
+        "EVENT", "TYPE", "REPOSITORY", "STATUS", "OCCURRED"
+    );
+    for event in events {
+        out.push_str(&format!(
+            "{:<26} {:<24} {:<32} {:<10} {}\n",
+            event.event_id,
+            event.event_type,
+            event.repository,
+            event.status,
+            event.occurred_at.to_rfc3339()
+        ));
+    }
+    out.push_str(&format!("\n{} event(s)\n", events.len()));
+    out
+}
+
+/// Render an event envelope as human-readable text, without its payload.
+pub fn event_text(event: &WrappedEvent) -> String {
+    let mut out = String::new();
+    let event_type = match &event.action {
+        Some(action) => format!("{}.{}", event.event_type, action),
+        None => event.event_type.clone(),
+    };
+    for (label, value) in [
+        ("Event", event.event_id.to_string()),
+        ("Provider", event.provider.clone()),
+        ("Type", event_type),
+        (
+            "Session",
+            event
+                .session_id
+                .as_ref()
+                .map_or_else(|| "-".to_string(), |s| s.to_string()),
+        ),
+        ("Correlation", event.correlation_id.to_string()),
+        ("Received", event.received_at.to_rfc3339()),
+        ("Processed", event.processed_at.to_rfc3339()),
+    ] {
+        out.push_str(&format!("{:<12} {}\n", format!("{}:", label), value));
+    }
+    out
+}
+
+// ============================================================================
+// Replay Types
+// ============================================================================
+
+/// Request body of `POST /admin/events/{id}/replay`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReplayOptions {
+    /// Deliver only to this bot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_bot: Option<String>,
+    /// Report the routing decisions without delivering.
+    pub dry_run: bool,
+    /// Why the event is replayed, recorded in the audit log.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// A bot queue a replayed event was, or would be, routed to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayRoute {
+    pub bot_name: String,
+    pub queue: String,
+    /// The bot is paused, so the event is held instead of delivered.
+    pub held: bool,
+}
+
+/// Result of `POST /admin/events/{id}/replay`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplayResult {
+    pub event_id: EventId,
+    /// `replayed`, `dry_run`, `skipped` or `failed`.
+    pub status: String,
+    pub message: String,
+    pub replay_id: String,
+    #[serde(default)]
+    pub replay_event_id: Option<EventId>,
+    pub dry_run: bool,
+    pub routes: Vec<ReplayRoute>,
+}
+
+impl ReplayResult {
+    /// Render the replay as a human-readable summary.
+    pub fn to_text(&self) -> String {
+        let mut out = format!("{}\nReplay: {}\n", self.message, self.replay_id);
+        if let Some(replay_event_id) = &self.replay_event_id {
+            out.push_str(&format!("Replayed as event {}\n", replay_event_id));
+        }
+        for route in &self.routes {
+            let held = if route.held {
+                " (held, bot paused)"
+            } else {
+                ""
+            };
+            out.push_str(&format!(
+                "  {} -> {}{}\n",
+                route.bot_name, route.queue, held
+            ));
+        }
+        out
+    }
+}
+
+// ============================================================================
+// Deletion Types
+// ============================================================================
+
+/// Tombstone returned by `DELETE /admin/events/{id}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventTombstone {
+    pub event_id: EventId,
+    pub deleted_by: String,
+    pub reason: String,
+    pub deleted_at: Timestamp,
+    pub purge_at: Timestamp,
+}
+
+impl EventTombstone {
+    /// Render the tombstone as a human-readable summary.
+    pub fn to_text(&self) -> String {
+        format!(
+            "Event {} deleted by {} at {}\nReason: {}\nPurged at {}\n",
+            self.event_id, self.deleted_by, self.deleted_at, self.reason, self.purge_at
+        )
+    }
+}
+
+// ============================================================================
+// Service Client
+// ============================================================================
+
+/// Manages events on a Queue-Keeper service through its API.
+pub struct ApiEvents {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl ApiEvents {
+    /// Create a client for the service at `base_url`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Authenticate with this admin API key.
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// Up to `limit` events matching `filter`, newest first.
+    pub async fn list(
+        &self,
+        filter: &EventFilter,
+        limit: usize,
+    ) -> Result<Vec<EventSummary>, EventsError> {
+        let mut events = Vec::new();
+        let mut cursor: Option<String> = None;
+        while events.len() < limit {
+            let page_size = (limit - events.len()).min(MAX_PAGE_SIZE);
+            let mut url = format!("{}/api/events?{}", self.base_url, filter.query(page_size));
+            if let Some(cursor) = &cursor {
+                url.push_str(&format!("&cursor={}", encode_component(cursor)));
+            }
+            let page: EventPage = self.send(self.client.get(&url), "GET", &url, None).await?;
+            let received = page.events.len();
+            events.extend(page.events);
+            debug!(received, listed = events.len(), "Listed event page");
+            match page.next_cursor {
+                Some(next) if received > 0 => cursor = Some(next),
+                _ => break,
+            }
+        }
+        events.truncate(limit);
+        Ok(events)
+    }
+
+    /// The stored envelope of `event_id`.
+    pub async fn show(&self, event_id: &str) -> Result<WrappedEvent, EventsError> {
+        let url = format!(
+            "{}/api/events/{}",
+            self.base_url,
+            encode_component(event_id)
+        );
+        let body: EventDetailBody = self
+            .send(self.client.get(&url), "GET", &url, Some(event_id))
+            .await?;
+        Ok(body.event)
+    }
+
+    /// Replay `event_id` as described by `options`.
+    pub async fn replay(
+        &self,
+        event_id: &str,
+        options: &ReplayOptions,
+    ) -> Result<ReplayResult, EventsError> {
+        let url = format!(
+            "{}/admin/events/{}/replay",
+            self.base_url,
+            encode_component(event_id)
+        );
+        debug!(url = %url, dry_run = options.dry_run, "Replaying event");
+        self.send(
+            self.client.post(&url).json(options),
+            "POST",
+            &url,
+            Some(event_id),
+        )
+        .await
+    }
+
+    /// Replay `event_id` to the bot whose queue is `queue`.
+    ///
+    /// A dry run finds the bot the event would be routed to through
+    /// `queue`; the event is then replayed to that bot only.
+    pub async fn replay_to_queue(
+        &self,
+        event_id: &str,
+        queue: &str,
+        reason: Option<String>,
+    ) -> Result<ReplayResult, EventsError> {
+        let dry_run = self
+            .replay(
+                event_id,
+                &ReplayOptions {
+                    dry_run: true,
+                    ..ReplayOptions::default()
+                },
+            )
+            .await?;
+        let route = dry_run
+            .routes
+            .into_iter()
+            .find(|route| route.queue == queue)
+            .ok_or_else(|| EventsError::QueueNotRouted {
+                event_id: event_id.to_string(),
+                queue: queue.to_string(),
+            })?;
+        self.replay(
+            event_id,
+            &ReplayOptions {
+                target_bot: Some(route.bot_name),
+                dry_run: false,
+                reason,
+            },
+        )
+        .await
+    }
+
+    /// Soft-delete `event_id`, recording `reason`.
+    pub async fn delete(
+        &self,
+        event_id: &str,
+        reason: &str,
+    ) -> Result<EventTombstone, EventsError> {
+        let url = format!("{}/admin/events/{}", self.base_url, event_id);
+        debug!(url = %url, "Deleting event");
+
+        let request = self
+            .client
+            .delete(&url)
+            .json(&serde_json::json!({ "reason": reason }));
+        self.send(request, "DELETE", &url, Some(event_id)).await
+    }
+
+    /// Send `request` with the API key and decode its JSON body.
+    ///
+    /// A 404 is [`EventsError::NotFound`] for `event_id` when the request
+    /// targets one event.
+    async fn send<T: for<'de> Deserialize<'de>>(
+        &self,
+        mut request: reqwest::RequestBuilder,
+        method: &str,
+        url: &str,
+        event_id: Option<&str>,
+    ) -> Result<T, EventsError> {
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await.map_err(|e| EventsError::Request {
+            message: format!("{} {}: {}", method, url, e),
+        })?;
+
+        let status = response.status();
+        if let (reqwest::StatusCode::NOT_FOUND, Some(event_id)) = (status, event_id) {
+            return Err(EventsError::NotFound {
+                event_id: event_id.to_string(),
+            });
+        }
+        if !status.is_success() {
+            let detail = response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|body| body.get("message")?.as_str().map(str::to_string))
+                .map(|message| format!(": {}", message))
+                .unwrap_or_default();
+            return Err(EventsError::Request {
+                message: format!("{} {}: HTTP {}{}", method, url, status.as_u16(), detail),
+            });
+        }
+        response.json().await.map_err(|e| EventsError::Request {
+            message: format!("{} {}: invalid response body: {}", method, url, e),
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "events_tests.rs"]
+mod tests;
@@ -0,0 +1,150 @@
+//! Tests for the event commands.
+
+use super::*;
+
+/// A service response deserializes, ignoring the status field, and renders
+/// the purge time.
+#[test]
+fn test_tombstone_parses_service_response() {
+    let tombstone: EventTombstone = serde_json::from_value(serde_json::json!({
+        "status": "deleted",
+        "event_id": "01J00000000000000000000001",
+        "deleted_by": "alice",
+        "reason": "leaked token",
+        "deleted_at": "2026-01-01T00:00:00Z",
+        "purge_at": "2026-01-31T00:00:00Z"
+    }))
+    .unwrap();
+
+    assert_eq!(tombstone.deleted_by, "alice");
+    let text = tombstone.to_text();
+    assert!(text.contains("deleted by alice"));
+    assert!(text.contains("Reason: leaked token"));
+    assert!(text.contains("Purged at 2026-01-31"));
+}
+
+/// An unreachable service is a request error.
+#[tokio::test]
+async fn test_unreachable_service_is_request_error() {
+    let result = ApiEvents::new("http://127.0.0.1:1/")
+        .delete("01J00000000000000000000001", "cleanup")
+        .await;
+
+    assert!(matches!(result, Err(EventsError::Request { .. })));
+}
+
+fn summary_json() -> serde_json::Value {
+    serde_json::json!({
+        "event_id": "01J00000000000000000000001",
+        "event_type": "pull_request",
+        "repository": "owner/repo",
+        "session_id": "owner/repo/pull_request/7",
+        "occurred_at": "2026-01-01T00:05:00Z",
+        "status": "delivered"
+    })
+}
+
+/// Filters become query parameters, encoded so slashes and `+` offsets
+/// survive.
+#[test]
+fn test_filter_query_encodes_values() {
+    let filter = EventFilter {
+        event_type: Some("pull_request".to_string()),
+        repository: Some("owner/repo".to_string()),
+        session: Some("owner/repo/pull_request/7".to_string()),
+        since: Some(Timestamp::from_rfc3339("2026-01-01T00:00:00+00:00").unwrap()),
+    };
+
+    let query = filter.query(50);
+
+    assert!(
+        query.starts_with("limit=50&include_total=false"),
+        "{}",
+        query
+    );
+    assert!(query.contains("&event_type=pull_request"), "{}", query);
+    assert!(query.contains("&repository=owner%2Frepo"), "{}", query);
+    assert!(
+        query.contains("&session_id=owner%2Frepo%2Fpull_request%2F7"),
+        "{}",
+        query
+    );
+    assert!(
+        query.contains("&since=2026-01-01T00%3A00%3A00"),
+        "{}",
+        query
+    );
+    assert!(!query.contains('+'), "{}", query);
+    assert_eq!(
+        EventFilter::default().query(10),
+        "limit=10&include_total=false"
+    );
+}
+
+/// A listing page deserializes and renders one row per event.
+#[test]
+fn test_event_page_renders_as_table() {
+    let page: EventPage = serde_json::from_value(serde_json::json!({
+        "events": [summary_json()],
+        "next_cursor": "v1:01J00000000000000000000001",
+        "limit": 1
+    }))
+    .unwrap();
+
+    let table = events_table(&page.events);
+
+    assert!(table.contains("01J00000000000000000000001"));
+    assert!(table.contains("owner/repo"));
+    assert!(table.contains("delivered"));
+    assert!(table.ends_with("1 event(s)\n"));
+}
+
+/// A replay response lists the bot queues and marks held deliveries.
+#[test]
+fn test_replay_result_parses_service_response() {
+    let result: ReplayResult = serde_json::from_value(serde_json::json!({
+        "event_id": "01J00000000000000000000001",
+        "status": "replayed",
+        "message": "Event replayed to 2 bot(s)",
+        "replay_id": "01J00000000000000000000009",
+        "replay_event_id": "01J0000000000000000000000A",
+        "dry_run": false,
+        "routes": [
+            { "bot_name": "labeler", "queue": "queue-keeper-labeler", "held": false },
+            { "bot_name": "releaser", "queue": "queue-keeper-releaser", "held": true }
+        ]
+    }))
+    .unwrap();
+
+    let text = result.to_text();
+    assert!(text.starts_with("Event replayed to 2 bot(s)"));
+    assert!(text.contains("Replayed as event 01J0000000000000000000000A"));
+    assert!(text.contains("labeler -> queue-keeper-labeler\n"));
+    assert!(text.contains("releaser -> queue-keeper-releaser (held, bot paused)"));
+}
+
+/// Replay options leave unset fields out of the request body.
+#[test]
+fn test_replay_options_body() {
+    let body = serde_json::to_value(ReplayOptions {
+        target_bot: Some("labeler".to_string()),
+        dry_run: true,
+        reason: None,
+    })
+    .unwrap();
+
+    assert_eq!(
+        body,
+        serde_json::json!({ "target_bot": "labeler", "dry_run": true })
+    );
+}
+
+/// Listing from an unreachable service is a request error.
+#[tokio::test]
+async fn test_list_from_unreachable_service_is_request_error() {
+    let result = ApiEvents::new("http://127.0.0.1:1")
+        .list(&EventFilter::default(), 10)
+        .await;
+
+    assert!(matches!(result, Err(EventsError::Request { .. })));
+}
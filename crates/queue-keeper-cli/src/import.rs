@@ -0,0 +1,1000 @@
+//! # Backfill Import
+//!
+//! Implements `queue-keeper import`, which replays webhook payloads captured by
+//! another system (or an earlier Queue-Keeper deployment) through the
+//! normalization pipeline and, optionally, into a running service for routing.
+//!
+//! ## Sources
+//!
+//! | URL | Reads |
+//! |-----|-------|
+//! | `dir:///path/to/archive` | Every file below the directory, recursively |
+//! | `s3://bucket/prefix` | Every object under the prefix (requires the `s3` feature) |
+//!
+//! Keys are processed in lexicographic order so that a checkpoint can record
+//! progress as a single position. Files whose name starts with `.` are ignored.
+//!
+//! ## Record Formats
+//!
+//! Each file is auto-detected as one of:
+//!
+//! - a Queue-Keeper [`StoredWebhook`] blob (existing storage), compressed
+//!   bodies included; encrypted blobs are rejected, as the import has no
+//!   access to their keys;
+//! - a raw capture object `{"headers": {...}, "body": ...}`;
+//! - GitHub Archive events, one JSON object per line
+//!   (`{"type": "PullRequestEvent", "repo": {...}, "payload": {...}}`).
+//!
+//! ## Routing
+//!
+//! Without a delivery target the import only normalizes records and reports
+//! the ones that fail. With `--deliver-to <url>` every record that normalizes
+//! successfully is posted to the service's `/webhook/github` endpoint (signed
+//! when a webhook secret is supplied), so the service routes it exactly like a
+//! live delivery. Records keep their original delivery ID; archive IDs that are
+//! not UUIDs are mapped to a deterministic UUID so re-runs reuse the same ID.
+//!
+//! ## Checkpoints
+//!
+//! Progress is written atomically to a checkpoint file every
+//! `checkpoint_interval` records and after each file. Re-running the same
+//! command resumes after the last checkpointed record. A checkpoint written
+//! for a different source is rejected unless `--restart` is given.
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use queue_keeper_core::{
+    blob_storage::StoredWebhook,
+    webhook::{GithubWebhookProvider, WebhookHeaders, WebhookProcessor, WebhookRequest},
+    MonotonicTimestamp, Timestamp,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+
+/// User agent sent with records delivered to the service.
+pub const IMPORT_USER_AGENT: &str = "queue-keeper-import";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors that stop an import.
+///
+/// Problems with individual records (unparseable files, normalization
+/// failures, records rejected by the service) are counted in
+/// [`ImportStats`] and do not stop the import.
+#[derive(Debug, thiserror::Error)]
+pub enum ImportError {
+    #[error("Invalid import source '{url}': {message}")]
+    InvalidSource { url: String, message: String },
+
+    #[error("Failed to read '{key}': {message}")]
+    Read { key: String, message: String },
+
+    #[error("Unrecognised record format in '{key}': {message}")]
+    Parse { key: String, message: String },
+
+    #[error(
+        "Checkpoint {path} belongs to source '{found}', not '{expected}'; use --restart to discard it"
+    )]
+    CheckpointMismatch {
+        path: PathBuf,
+        expected: String,
+        found: String,
+    },
+
+    #[error("Checkpoint error: {0}")]
+    Checkpoint(String),
+
+    #[error("Delivery target unavailable: {0}")]
+    DeliveryUnavailable(String),
+}
+
+// ============================================================================
+// Source Location
+// ============================================================================
+
+/// Location of the payload files to import.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceLocation {
+    /// Local directory (`dir://<path>`).
+    Directory(PathBuf),
+
+    /// S3 bucket and key prefix (`s3://<bucket>/<prefix>`).
+    S3 { bucket: String, prefix: String },
+}
+
+impl FromStr for SourceLocation {
+    type Err = ImportError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |message: &str| ImportError::InvalidSource {
+            url: s.to_string(),
+            message: message.to_string(),
+        };
+
+        if let Some(path) = s.strip_prefix("dir://") {
+            if path.is_empty() {
+                return Err(invalid("directory path must not be empty"));
+            }
+            return Ok(Self::Directory(PathBuf::from(path)));
+        }
+
+        if let Some(rest) = s.strip_prefix("s3://") {
+            let (bucket, prefix) = rest.split_once('/').unwrap_or((rest, ""));
+            if bucket.is_empty() {
+                return Err(invalid("bucket name must not be empty"));
+            }
+            return Ok(Self::S3 {
+                bucket: bucket.to_string(),
+                prefix: prefix.to_string(),
+            });
+        }
+
+        Err(invalid("expected dir://<path> or s3://<bucket>/<prefix>"))
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Directory(path) => write!(f, "dir://{}", path.display()),
+            Self::S3 { bucket, prefix } => write!(f, "s3://{}/{}", bucket, prefix),
+        }
+    }
+}
+
+// ============================================================================
+// Import Sources
+// ============================================================================
+
+/// Readable collection of payload files.
+#[async_trait]
+pub trait ImportSource: Send + Sync {
+    /// List every file key, sorted lexicographically.
+    async fn list_keys(&self) -> Result<Vec<String>, ImportError>;
+
+    /// Read the contents of a file.
+    async fn read(&self, key: &str) -> Result<Vec<u8>, ImportError>;
+}
+
+/// Open the import source for a location.
+pub async fn open_source(location: &SourceLocation) -> Result<Arc<dyn ImportSource>, ImportError> {
+    match location {
+        SourceLocation::Directory(path) => Ok(Arc::new(DirectorySource::new(path.clone())?)),
+        #[cfg(feature = "s3")]
+        SourceLocation::S3 { bucket, prefix } => Ok(Arc::new(
+            S3Source::new(bucket.clone(), prefix.clone()).await,
+        )),
+        #[cfg(not(feature = "s3"))]
+        SourceLocation::S3 { .. } => Err(ImportError::InvalidSource {
+            url: location.to_string(),
+            message: "S3 sources require queue-keeper-cli to be built with the `s3` feature"
+                .to_string(),
+        }),
+    }
+}
+
+/// Files below a local directory.
+///
+/// Keys are paths relative to the root, using `/` as the separator.
+#[derive(Debug, Clone)]
+pub struct DirectorySource {
+    root: PathBuf,
+}
+
+impl DirectorySource {
+    /// Create a source for an existing directory.
+    pub fn new(root: PathBuf) -> Result<Self, ImportError> {
+        if !root.is_dir() {
+            return Err(ImportError::InvalidSource {
+                url: format!("dir://{}", root.display()),
+                message: "directory does not exist".to_string(),
+            });
+        }
+        Ok(Self { root })
+    }
+}
+
+#[async_trait]
+impl ImportSource for DirectorySource {
+    async fn list_keys(&self) -> Result<Vec<String>, ImportError> {
+        let mut keys = Vec::new();
+        let mut pending = vec![self.root.clone()];
+
+        while let Some(dir) = pending.pop() {
+            let mut entries = tokio::fs::read_dir(&dir)
+                .await
+                .map_err(|e| read_error(&dir.display().to_string(), e))?;
+            while let Some(entry) = entries
+                .next_entry()
+                .await
+                .map_err(|e| read_error(&dir.display().to_string(), e))?
+            {
+                let path = entry.path();
+                if entry.file_name().to_string_lossy().starts_with('.') {
+                    continue;
+                }
+                if path.is_dir() {
+                    pending.push(path);
+                } else if let Ok(relative) = path.strip_prefix(&self.root) {
+                    let key = relative
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    keys.push(key);
+                }
+            }
+        }
+
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn read(&self, key: &str) -> Result<Vec<u8>, ImportError> {
+        tokio::fs::read(self.root.join(key))
+            .await
+            .map_err(|e| read_error(key, e))
+    }
+}
+
+/// Objects below a prefix in an S3 bucket.
+///
+/// Credentials and region are resolved from the standard AWS environment
+/// (environment variables, profile, instance metadata).
+#[cfg(feature = "s3")]
+pub struct S3Source {
+    client: aws_sdk_s3::Client,
+    bucket: String,
+    prefix: String,
+}
+
+#[cfg(feature = "s3")]
+impl S3Source {
+    /// Create a source using the default AWS configuration.
+    pub async fn new(bucket: String, prefix: String) -> Self {
+        let config = aws_config::load_defaults(aws_config::BehaviorVersion::latest()).await;
+        Self {
+            client: aws_sdk_s3::Client::new(&config),
+            bucket,
+            prefix,
+        }
+    }
+}
+
+#[cfg(feature = "s3")]
+#[async_trait]
+impl ImportSource for S3Source {
+    async fn list_keys(&self) -> Result<Vec<String>, ImportError> {
+        let mut keys = Vec::new();
+        let mut continuation: Option<String> = None;
+
+        loop {
+            let output = self
+                .client
+                .list_objects_v2()
+                .bucket(&self.bucket)
+                .prefix(&self.prefix)
+                .set_continuation_token(continuation.take())
+                .send()
+                .await
+                .map_err(|e| read_error(&self.prefix, e))?;
+
+            keys.extend(
+                output
+                    .contents()
+                    .iter()
+                    .filter_map(|object| object.key())
+                    .filter(|key| !key.ends_with('/'))
+                    .map(str::to_string),
+            );
+
+            match output.next_continuation_token() {
+                Some(token) => continuation = Some(token.to_string()),
+                None => break,
+            }
+        }
+
+        keys.sort();
+        Ok(keys)
+    }
+
+    async fn read(&self, key: &str) -> Result<Vec<u8>, ImportError> {
+        let output = self
+            .client
+            .get_object()
+            .bucket(&self.bucket)
+            .key(key)
+            .send()
+            .await
+            .map_err(|e| read_error(key, e))?;
+        let data = output
+            .body
+            .collect()
+            .await
+            .map_err(|e| read_error(key, e))?;
+        Ok(data.into_bytes().to_vec())
+    }
+}
+
+fn read_error(key: &str, error: impl fmt::Display) -> ImportError {
+    ImportError::Read {
+        key: key.to_string(),
+        message: error.to_string(),
+    }
+}
+
+// ============================================================================
+// Import Records
+// ============================================================================
+
+/// A raw webhook delivery recovered from an import file.
+#[derive(Debug, Clone)]
+pub struct ImportRecord {
+    /// GitHub event type (`X-GitHub-Event`).
+    pub event_type: String,
+
+    /// Delivery ID (`X-GitHub-Delivery`), always a UUID.
+    pub delivery_id: String,
+
+    /// Original receive time, when the source recorded one.
+    pub received_at: Option<Timestamp>,
+
+    /// Raw JSON payload.
+    pub body: Bytes,
+}
+
+impl ImportRecord {
+    /// Build the webhook request used for normalization.
+    pub fn to_webhook_request(&self) -> WebhookRequest {
+        let headers = WebhookHeaders {
+            event_type: self.event_type.clone(),
+            delivery_id: self.delivery_id.clone(),
+            signature: None,
+            user_agent: Some(IMPORT_USER_AGENT.to_string()),
+            content_type: "application/json".to_string(),
+        };
+        let request = WebhookRequest::new(headers, self.body.clone());
+        match self.received_at {
+            Some(received_at) => request.with_receipt(MonotonicTimestamp::from_wall(received_at)),
+            None => request,
+        }
+    }
+}
+
+/// Parse every record in an import file.
+///
+/// # Errors
+///
+/// Returns [`ImportError::Parse`] when the file matches none of the supported
+/// formats (see the module documentation).
+pub fn parse_records(key: &str, data: &[u8]) -> Result<Vec<ImportRecord>, ImportError> {
+    if let Ok(value) = serde_json::from_slice::<serde_json::Value>(data) {
+        if value
+            .get("payload")
+            .and_then(|p| p.get("metadata"))
+            .is_some()
+        {
+            let stored: StoredWebhook =
+                serde_json::from_value(value).map_err(|e| parse_error(key, e))?;
+            if let Some(encryption) = &stored.metadata.encryption {
+                return Err(parse_error(
+                    key,
+                    format!(
+                        "payload is encrypted with key '{}'; read it through the service",
+                        encryption.key_id
+                    ),
+                ));
+            }
+            let stored = stored.decode().map_err(|e| parse_error(key, e))?;
+            return Ok(vec![record_from_stored(key, stored)]);
+        }
+        if value.get("headers").is_some() && value.get("body").is_some() {
+            return record_from_capture(key, &value).map(|record| vec![record]);
+        }
+        if value.get("type").is_some() && value.get("payload").is_some() {
+            return record_from_archive(key, &value).map(|record| vec![record]);
+        }
+        return Err(parse_error(
+            key,
+            "expected a stored webhook, raw capture or GitHub Archive event",
+        ));
+    }
+
+    // Multi-line files are GitHub Archive JSON lines
+    let text = std::str::from_utf8(data).map_err(|e| parse_error(key, e))?;
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let value: serde_json::Value = serde_json::from_str(line)
+                .map_err(|e| parse_error(key, format!("line {}: {}", index + 1, e)))?;
+            record_from_archive(key, &value)
+        })
+        .collect()
+}
+
+fn parse_error(key: &str, error: impl fmt::Display) -> ImportError {
+    ImportError::Parse {
+        key: key.to_string(),
+        message: error.to_string(),
+    }
+}
+
+/// Record from a Queue-Keeper blob storage object.
+fn record_from_stored(key: &str, stored: StoredWebhook) -> ImportRecord {
+    let delivery_id = header_value(&stored.payload.headers, "x-github-delivery")
+        .map(delivery_uuid)
+        .unwrap_or_else(|| delivery_uuid(key));
+
+    ImportRecord {
+        event_type: stored.payload.metadata.event_type,
+        delivery_id,
+        received_at: Some(stored.payload.metadata.received_at),
+        body: stored.payload.body,
+    }
+}
+
+/// Record from a `{"headers": {...}, "body": ...}` capture.
+fn record_from_capture(key: &str, value: &serde_json::Value) -> Result<ImportRecord, ImportError> {
+    let headers: HashMap<String, String> = value["headers"]
+        .as_object()
+        .ok_or_else(|| parse_error(key, "`headers` must be an object"))?
+        .iter()
+        .filter_map(|(name, v)| v.as_str().map(|s| (name.clone(), s.to_string())))
+        .collect();
+
+    let event_type = header_value(&headers, "x-github-event")
+        .ok_or_else(|| parse_error(key, "missing X-GitHub-Event header"))?
+        .to_string();
+    let delivery_id = header_value(&headers, "x-github-delivery")
+        .map(delivery_uuid)
+        .unwrap_or_else(|| delivery_uuid(key));
+
+    let body = match &value["body"] {
+        serde_json::Value::String(raw) => Bytes::from(raw.clone().into_bytes()),
+        other => Bytes::from(serde_json::to_vec(other).map_err(|e| parse_error(key, e))?),
+    };
+
+    let received_at = value
+        .get("received_at")
+        .and_then(|v| v.as_str())
+        .and_then(|s| Timestamp::from_rfc3339(s).ok());
+
+    Ok(ImportRecord {
+        event_type,
+        delivery_id,
+        received_at,
+        body,
+    })
+}
+
+/// Record from a GitHub Archive event.
+///
+/// Archive events carry a trimmed `repo` object instead of the webhook
+/// `repository` object, so a minimal `repository` is synthesised when the
+/// payload does not already contain one.
+fn record_from_archive(key: &str, value: &serde_json::Value) -> Result<ImportRecord, ImportError> {
+    let archive_type = value["type"]
+        .as_str()
+        .ok_or_else(|| parse_error(key, "`type` must be a string"))?;
+    let event_type = archive_event_type(archive_type);
+
+    let mut payload = value["payload"].clone();
+    let payload_object = payload
+        .as_object_mut()
+        .ok_or_else(|| parse_error(key, "`payload` must be an object"))?;
+
+    if !payload_object.contains_key("repository") {
+        if let Some(repository) = archive_repository(value) {
+            payload_object.insert("repository".to_string(), repository);
+        }
+    }
+
+    let id = match &value["id"] {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Number(n) => n.to_string(),
+        _ => key.to_string(),
+    };
+
+    let received_at = value
+        .get("created_at")
+        .and_then(|v| v.as_str())
+        .and_then(|s| Timestamp::from_rfc3339(s).ok());
+
+    Ok(ImportRecord {
+        event_type,
+        delivery_id: delivery_uuid(&id),
+        received_at,
+        body: Bytes::from(serde_json::to_vec(&payload).map_err(|e| parse_error(key, e))?),
+    })
+}
+
+/// Convert a GitHub Archive type (`PullRequestEvent`) to a webhook event
+/// name (`pull_request`).
+fn archive_event_type(archive_type: &str) -> String {
+    let name = archive_type.strip_suffix("Event").unwrap_or(archive_type);
+    let mut event_type = String::with_capacity(name.len() + 4);
+    for (index, c) in name.chars().enumerate() {
+        if c.is_ascii_uppercase() {
+            if index > 0 {
+                event_type.push('_');
+            }
+            event_type.push(c.to_ascii_lowercase());
+        } else {
+            event_type.push(c);
+        }
+    }
+    event_type
+}
+
+/// Build a webhook-style `repository` object from archive `repo`/`org` fields.
+fn archive_repository(value: &serde_json::Value) -> Option<serde_json::Value> {
+    let repo = value.get("repo")?;
+    let full_name = repo.get("name")?.as_str()?;
+    let (owner, name) = full_name.split_once('/')?;
+
+    // The owner ID is only known when the archive records the organisation.
+    let owner_id = value
+        .get("org")
+        .filter(|org| org.get("login").and_then(|l| l.as_str()) == Some(owner))
+        .and_then(|org| org.get("id"))
+        .cloned()
+        .unwrap_or(serde_json::json!(0));
+
+    Some(serde_json::json!({
+        "id": repo.get("id").cloned().unwrap_or(serde_json::json!(0)),
+        "name": name,
+        "full_name": full_name,
+        "owner": { "id": owner_id, "login": owner },
+    }))
+}
+
+/// Look up a header case-insensitively.
+fn header_value<'a>(headers: &'a HashMap<String, String>, name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+/// Return `id` if it is a UUID, otherwise a UUID derived deterministically
+/// from it.
+fn delivery_uuid(id: &str) -> String {
+    if uuid::Uuid::parse_str(id).is_ok() {
+        return id.to_string();
+    }
+    let digest = Sha256::digest(id.as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest[..16]);
+    uuid::Uuid::from_bytes(bytes).to_string()
+}
+
+// ============================================================================
+// Checkpoints
+// ============================================================================
+
+/// Counters reported at the end of an import.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportStats {
+    /// Files read (including unparseable ones).
+    pub files: u64,
+
+    /// Files that matched no supported format.
+    pub unparseable_files: u64,
+
+    /// Records processed.
+    pub records: u64,
+
+    /// Records that normalized successfully.
+    pub normalized: u64,
+
+    /// Records accepted by the delivery target.
+    pub delivered: u64,
+
+    /// Records that failed normalization or were rejected by the target.
+    pub failed: u64,
+}
+
+/// Resumable import progress.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportCheckpoint {
+    /// Source URL the checkpoint belongs to.
+    pub source: String,
+
+    /// Key of the file being imported when the checkpoint was written.
+    pub key: Option<String>,
+
+    /// Number of records of `key` already processed.
+    pub records_done: usize,
+
+    /// Whether every record of `key` has been processed.
+    pub key_complete: bool,
+
+    /// Counters accumulated so far.
+    pub stats: ImportStats,
+
+    /// When the checkpoint was written.
+    pub updated_at: Timestamp,
+}
+
+impl ImportCheckpoint {
+    /// Create an empty checkpoint for a source.
+    pub fn new(source: impl Into<String>) -> Self {
+        Self {
+            source: source.into(),
+            key: None,
+            records_done: 0,
+            key_complete: false,
+            stats: ImportStats::default(),
+            updated_at: Timestamp::now(),
+        }
+    }
+
+    /// Load a checkpoint, returning `None` when the file does not exist.
+    pub async fn load(path: &Path) -> Result<Option<Self>, ImportError> {
+        match tokio::fs::read(path).await {
+            Ok(data) => serde_json::from_slice(&data)
+                .map(Some)
+                .map_err(|e| ImportError::Checkpoint(format!("{}: {}", path.display(), e))),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(ImportError::Checkpoint(format!(
+                "{}: {}",
+                path.display(),
+                e
+            ))),
+        }
+    }
+
+    /// Write the checkpoint atomically (temporary file, then rename).
+    pub async fn save(&mut self, path: &Path) -> Result<(), ImportError> {
+        self.updated_at = Timestamp::now();
+        let data =
+            serde_json::to_vec_pretty(self).map_err(|e| ImportError::Checkpoint(e.to_string()))?;
+        let tmp = path.with_extension("tmp");
+        let to_error =
+            |e: std::io::Error| ImportError::Checkpoint(format!("{}: {}", path.display(), e));
+        tokio::fs::write(&tmp, data).await.map_err(to_error)?;
+        tokio::fs::rename(&tmp, path).await.map_err(to_error)
+    }
+
+    /// Number of leading records of `key` to skip, or `None` when the whole
+    /// file was already imported.
+    pub fn records_to_skip(&self, key: &str) -> Option<usize> {
+        match self.key.as_deref() {
+            None => Some(0),
+            Some(done) if key < done => None,
+            Some(done) if key == done => (!self.key_complete).then_some(self.records_done),
+            Some(_) => Some(0),
+        }
+    }
+}
+
+// ============================================================================
+// Delivery
+// ============================================================================
+
+/// Failure to deliver a record to the routing target.
+#[derive(Debug, thiserror::Error)]
+pub enum DeliveryError {
+    /// The target rejected this record; other records may still succeed.
+    #[error("rejected with HTTP {status}: {body}")]
+    Rejected { status: u16, body: String },
+
+    /// The target cannot accept records right now; the import stops.
+    #[error("{0}")]
+    Unavailable(String),
+}
+
+/// Destination for records that normalized successfully.
+#[async_trait]
+pub trait ImportSink: Send + Sync {
+    /// Deliver one record.
+    async fn deliver(&self, record: &ImportRecord) -> Result<(), DeliveryError>;
+}
+
+/// Posts records to a running Queue-Keeper's GitHub webhook endpoint.
+pub struct WebhookForwarder {
+    client: reqwest::Client,
+    url: String,
+    webhook_secret: Option<String>,
+}
+
+impl WebhookForwarder {
+    /// Create a forwarder for a service base URL (e.g. `http://localhost:8080`).
+    pub fn new(base_url: &str, webhook_secret: Option<String>) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            url: format!("{}/webhook/github", base_url.trim_end_matches('/')),
+            webhook_secret,
+        }
+    }
+}
+
+#[async_trait]
+impl ImportSink for WebhookForwarder {
+    async fn deliver(&self, record: &ImportRecord) -> Result<(), DeliveryError> {
+        let mut request = self
+            .client
+            .post(&self.url)
+            .header("X-GitHub-Event", &record.event_type)
+            .header("X-GitHub-Delivery", &record.delivery_id)
+            .header("Content-Type", "application/json")
+            .header("User-Agent", IMPORT_USER_AGENT);
+        if let Some(secret) = &self.webhook_secret {
+            request = request.header("X-Hub-Signature-256", sign_payload(secret, &record.body));
+        }
+
+        let response = request
+            .body(record.body.clone())
+            .send()
+            .await
+            .map_err(|e| DeliveryError::Unavailable(e.to_string()))?;
+
+        let status = response.status();
+        if status.is_success() {
+            return Ok(());
+        }
+        let body = response.text().await.unwrap_or_default();
+        if status.is_server_error() || status.as_u16() == 429 {
+            return Err(DeliveryError::Unavailable(format!(
+                "HTTP {}: {}",
+                status.as_u16(),
+                body
+            )));
+        }
+        Err(DeliveryError::Rejected {
+            status: status.as_u16(),
+            body,
+        })
+    }
+}
+
+/// Compute the `X-Hub-Signature-256` header value for a payload.
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    use hmac::{Hmac, KeyInit, Mac};
+    type HmacSha256 = Hmac<Sha256>;
+
+    let mut mac =
+        HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+// ============================================================================
+// Importer
+// ============================================================================
+
+/// Import behaviour settings.
+#[derive(Debug, Clone)]
+pub struct ImportOptions {
+    /// Checkpoint file path.
+    pub checkpoint_path: PathBuf,
+
+    /// Discard any existing checkpoint and start from the first file.
+    pub restart: bool,
+
+    /// Maximum records per second; `0` disables rate limiting.
+    pub rate_limit: u32,
+
+    /// Records processed between checkpoint writes.
+    pub checkpoint_interval: usize,
+}
+
+impl ImportOptions {
+    /// Create options with the default rate limit and checkpoint interval.
+    pub fn new(checkpoint_path: PathBuf) -> Self {
+        Self {
+            checkpoint_path,
+            restart: false,
+            rate_limit: 10,
+            checkpoint_interval: 100,
+        }
+    }
+}
+
+/// Outcome of importing a single record.
+enum RecordOutcome {
+    Normalized,
+    Delivered,
+    Failed,
+}
+
+/// Drives a backfill from a source through normalization and optional delivery.
+pub struct Importer {
+    source_url: String,
+    source: Arc<dyn ImportSource>,
+    sink: Option<Arc<dyn ImportSink>>,
+    normalizer: GithubWebhookProvider,
+    options: ImportOptions,
+}
+
+impl Importer {
+    /// Create an importer that only normalizes records.
+    pub fn new(
+        source_url: impl Into<String>,
+        source: Arc<dyn ImportSource>,
+        options: ImportOptions,
+    ) -> Self {
+        Self {
+            source_url: source_url.into(),
+            source,
+            sink: None,
+            normalizer: GithubWebhookProvider::new(None, None, None),
+            options,
+        }
+    }
+
+    /// Deliver normalized records to a routing target.
+    pub fn with_sink(mut self, sink: Arc<dyn ImportSink>) -> Self {
+        self.sink = Some(sink);
+        self
+    }
+
+    /// Run the import to completion, resuming from the checkpoint if present.
+    ///
+    /// # Errors
+    ///
+    /// Stops with an error when the source cannot be read, the checkpoint is
+    /// unusable, or the delivery target is unavailable. Progress up to the
+    /// failing record is checkpointed first, so re-running resumes there.
+    pub async fn run(&self) -> Result<ImportStats, ImportError> {
+        let path = &self.options.checkpoint_path;
+        let existing = if self.options.restart {
+            None
+        } else {
+            ImportCheckpoint::load(path).await?
+        };
+
+        let mut checkpoint = match existing {
+            Some(checkpoint) if checkpoint.source != self.source_url => {
+                return Err(ImportError::CheckpointMismatch {
+                    path: path.clone(),
+                    expected: self.source_url.clone(),
+                    found: checkpoint.source,
+                });
+            }
+            Some(checkpoint) => {
+                info!(
+                    source = %self.source_url,
+                    key = ?checkpoint.key,
+                    records_done = checkpoint.records_done,
+                    "Resuming import from checkpoint"
+                );
+                checkpoint
+            }
+            None => ImportCheckpoint::new(&self.source_url),
+        };
+
+        let mut pacer = (self.options.rate_limit > 0).then(|| {
+            let mut interval = tokio::time::interval(Duration::from_secs_f64(
+                1.0 / f64::from(self.options.rate_limit),
+            ));
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            interval
+        });
+
+        let keys = self.source.list_keys().await?;
+        info!(source = %self.source_url, files = keys.len(), "Starting import");
+
+        let mut since_save = 0;
+        for key in keys {
+            let Some(skip) = checkpoint.records_to_skip(&key) else {
+                continue;
+            };
+
+            let data = self.source.read(&key).await?;
+            if skip == 0 {
+                checkpoint.stats.files += 1;
+            }
+            checkpoint.key = Some(key.clone());
+            checkpoint.records_done = skip;
+            checkpoint.key_complete = false;
+
+            let records = match parse_records(&key, &data) {
+                Ok(records) => records,
+                Err(e) => {
+                    warn!(key = %key, error = %e, "Skipping unparseable import file");
+                    checkpoint.stats.unparseable_files += 1;
+                    Vec::new()
+                }
+            };
+
+            for record in records.iter().skip(skip) {
+                if let Some(pacer) = pacer.as_mut() {
+                    pacer.tick().await;
+                }
+
+                let outcome = match self.import_record(&key, record).await {
+                    Ok(outcome) => outcome,
+                    Err(e) => {
+                        checkpoint.save(path).await?;
+                        return Err(e);
+                    }
+                };
+
+                let stats = &mut checkpoint.stats;
+                stats.records += 1;
+                match outcome {
+                    RecordOutcome::Normalized => stats.normalized += 1,
+                    RecordOutcome::Delivered => {
+                        stats.normalized += 1;
+                        stats.delivered += 1;
+                    }
+                    RecordOutcome::Failed => stats.failed += 1,
+                }
+                checkpoint.records_done += 1;
+
+                since_save += 1;
+                if since_save >= self.options.checkpoint_interval {
+                    checkpoint.save(path).await?;
+                    since_save = 0;
+                }
+            }
+
+            checkpoint.key_complete = true;
+            checkpoint.save(path).await?;
+            since_save = 0;
+        }
+
+        info!(source = %self.source_url, stats = ?checkpoint.stats, "Import complete");
+        Ok(checkpoint.stats)
+    }
+
+    async fn import_record(
+        &self,
+        key: &str,
+        record: &ImportRecord,
+    ) -> Result<RecordOutcome, ImportError> {
+        if let Err(e) = self
+            .normalizer
+            .normalize_event(&record.to_webhook_request())
+            .await
+        {
+            warn!(
+                key = %key,
+                delivery_id = %record.delivery_id,
+                event_type = %record.event_type,
+                error = %e,
+                "Record failed normalization; skipping"
+            );
+            return Ok(RecordOutcome::Failed);
+        }
+
+        let Some(sink) = &self.sink else {
+            return Ok(RecordOutcome::Normalized);
+        };
+
+        match sink.deliver(record).await {
+            Ok(()) => Ok(RecordOutcome::Delivered),
+            Err(DeliveryError::Rejected { status, body }) => {
+                warn!(
+                    key = %key,
+                    delivery_id = %record.delivery_id,
+                    status = status,
+                    body = %body,
+                    "Record rejected by delivery target; skipping"
+                );
+                Ok(RecordOutcome::Failed)
+            }
+            Err(DeliveryError::Unavailable(message)) => {
+                Err(ImportError::DeliveryUnavailable(message))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "import_tests.rs"]
+mod tests;
@@ -0,0 +1,325 @@
+//! Tests for the backfill importer.
+
+use super::*;
+use std::sync::Mutex;
+
+fn repository_json() -> serde_json::Value {
+    serde_json::json!({
+        "id": 42,
+        "name": "repo",
+        "full_name": "owner/repo",
+        "owner": { "id": 7, "login": "owner" }
+    })
+}
+
+fn capture_json(delivery_id: &str) -> String {
+    serde_json::json!({
+        "headers": {
+            "X-GitHub-Event": "issues",
+            "X-GitHub-Delivery": delivery_id
+        },
+        "body": { "action": "opened", "issue": { "number": 1 }, "repository": repository_json() }
+    })
+    .to_string()
+}
+
+fn archive_line(id: u64) -> String {
+    serde_json::json!({
+        "id": id.to_string(),
+        "type": "PullRequestEvent",
+        "repo": { "id": 42, "name": "owner/repo" },
+        "org": { "id": 7, "login": "owner" },
+        "created_at": "2024-01-01T00:00:00Z",
+        "payload": { "action": "opened", "number": id, "pull_request": { "number": id } }
+    })
+    .to_string()
+}
+
+fn options(dir: &Path) -> ImportOptions {
+    ImportOptions {
+        checkpoint_path: dir.join("checkpoint.json"),
+        restart: false,
+        rate_limit: 0,
+        checkpoint_interval: 1,
+    }
+}
+
+/// Sink that records delivered IDs and can simulate an outage.
+#[derive(Default)]
+struct RecordingSink {
+    delivered: Mutex<Vec<String>>,
+    fail_after: Option<usize>,
+}
+
+#[async_trait]
+impl ImportSink for RecordingSink {
+    async fn deliver(&self, record: &ImportRecord) -> Result<(), DeliveryError> {
+        let mut delivered = self.delivered.lock().unwrap();
+        if self.fail_after == Some(delivered.len()) {
+            return Err(DeliveryError::Unavailable("connection refused".to_string()));
+        }
+        delivered.push(record.delivery_id.clone());
+        Ok(())
+    }
+}
+
+mod source_location_tests {
+    use super::*;
+
+    /// Verify that directory and S3 URLs parse into their locations.
+    #[test]
+    fn test_parses_directory_and_s3_urls() {
+        assert_eq!(
+            "dir:///var/archive".parse::<SourceLocation>().unwrap(),
+            SourceLocation::Directory(PathBuf::from("/var/archive"))
+        );
+        assert_eq!(
+            "s3://bucket/2024/01".parse::<SourceLocation>().unwrap(),
+            SourceLocation::S3 {
+                bucket: "bucket".to_string(),
+                prefix: "2024/01".to_string()
+            }
+        );
+    }
+
+    /// Verify that unsupported schemes and empty locations are rejected.
+    #[test]
+    fn test_rejects_invalid_urls() {
+        assert!("http://example.com".parse::<SourceLocation>().is_err());
+        assert!("dir://".parse::<SourceLocation>().is_err());
+        assert!("s3:///prefix".parse::<SourceLocation>().is_err());
+    }
+}
+
+mod parse_tests {
+    use super::*;
+
+    /// Verify that a raw capture yields one record with its headers.
+    #[test]
+    fn test_parses_raw_capture() {
+        let id = "0b4b5b1e-2a2c-4c3e-9d7e-2f1d6a2b8c90";
+        let records = parse_records("a.json", capture_json(id).as_bytes()).unwrap();
+
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].event_type, "issues");
+        assert_eq!(records[0].delivery_id, id);
+    }
+
+    /// Verify that GitHub Archive lines are converted to webhook payloads.
+    #[test]
+    fn test_parses_archive_lines() {
+        let data = format!("{}\n{}\n", archive_line(1), archive_line(2));
+        let records = parse_records("2024-01-01-0.json", data.as_bytes()).unwrap();
+
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].event_type, "pull_request");
+        assert!(records[0].received_at.is_some());
+
+        let payload: serde_json::Value = serde_json::from_slice(&records[0].body).unwrap();
+        assert_eq!(payload["repository"]["full_name"], "owner/repo");
+        assert_eq!(payload["repository"]["owner"]["id"], 7);
+    }
+
+    /// Verify that archive IDs map to stable UUID delivery IDs.
+    #[test]
+    fn test_archive_ids_map_to_deterministic_uuids() {
+        let first = parse_records("k", archive_line(1).as_bytes()).unwrap();
+        let again = parse_records("k", archive_line(1).as_bytes()).unwrap();
+        let other = parse_records("k", archive_line(2).as_bytes()).unwrap();
+
+        assert!(uuid::Uuid::parse_str(&first[0].delivery_id).is_ok());
+        assert_eq!(first[0].delivery_id, again[0].delivery_id);
+        assert_ne!(first[0].delivery_id, other[0].delivery_id);
+    }
+
+    /// Verify that archive type names are converted to webhook event names.
+    #[test]
+    fn test_archive_event_type_conversion() {
+        assert_eq!(archive_event_type("PushEvent"), "push");
+        assert_eq!(archive_event_type("PullRequestEvent"), "pull_request");
+        assert_eq!(
+            archive_event_type("PullRequestReviewCommentEvent"),
+            "pull_request_review_comment"
+        );
+    }
+
+    /// Verify that unrecognised JSON is reported as a parse error.
+    #[test]
+    fn test_rejects_unknown_format() {
+        let result = parse_records("x.json", br#"{"hello": "world"}"#);
+        assert!(matches!(result, Err(ImportError::Parse { .. })));
+    }
+}
+
+mod checkpoint_tests {
+    use super::*;
+
+    /// Verify the resume position relative to the checkpointed key.
+    #[test]
+    fn test_records_to_skip() {
+        let mut checkpoint = ImportCheckpoint::new("dir://x");
+        assert_eq!(checkpoint.records_to_skip("a"), Some(0));
+
+        checkpoint.key = Some("b".to_string());
+        checkpoint.records_done = 3;
+        assert_eq!(checkpoint.records_to_skip("a"), None);
+        assert_eq!(checkpoint.records_to_skip("b"), Some(3));
+        assert_eq!(checkpoint.records_to_skip("c"), Some(0));
+
+        checkpoint.key_complete = true;
+        assert_eq!(checkpoint.records_to_skip("b"), None);
+    }
+
+    /// Verify that a saved checkpoint loads back unchanged.
+    #[tokio::test]
+    async fn test_save_and_load_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cp.json");
+        assert!(ImportCheckpoint::load(&path).await.unwrap().is_none());
+
+        let mut checkpoint = ImportCheckpoint::new("dir://x");
+        checkpoint.key = Some("a".to_string());
+        checkpoint.records_done = 5;
+        checkpoint.save(&path).await.unwrap();
+
+        let loaded = ImportCheckpoint::load(&path).await.unwrap().unwrap();
+        assert_eq!(loaded.key.as_deref(), Some("a"));
+        assert_eq!(loaded.records_done, 5);
+    }
+}
+
+mod importer_tests {
+    use super::*;
+
+    fn write_archive(dir: &Path) {
+        std::fs::create_dir_all(dir.join("2024")).unwrap();
+        std::fs::write(
+            dir.join("2024/01.json"),
+            format!("{}\n{}\n", archive_line(1), archive_line(2)),
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("2024/02.json"),
+            capture_json("0b4b5b1e-2a2c-4c3e-9d7e-2f1d6a2b8c90"),
+        )
+        .unwrap();
+        std::fs::write(dir.join("notes.txt"), "not json").unwrap();
+    }
+
+    /// Verify that a directory import normalizes and delivers every record.
+    #[tokio::test]
+    async fn test_imports_directory() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        write_archive(source_dir.path());
+
+        let sink = Arc::new(RecordingSink::default());
+        let source = Arc::new(DirectorySource::new(source_dir.path().to_path_buf()).unwrap());
+        let stats = Importer::new("dir://test", source, options(state_dir.path()))
+            .with_sink(sink.clone())
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(stats.files, 3);
+        assert_eq!(stats.unparseable_files, 1);
+        assert_eq!(stats.records, 3);
+        assert_eq!(stats.normalized, 3);
+        assert_eq!(stats.delivered, 3);
+        assert_eq!(sink.delivered.lock().unwrap().len(), 3);
+    }
+
+    /// Verify that records failing normalization are counted and skipped.
+    #[tokio::test]
+    async fn test_counts_normalization_failures() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        let line = serde_json::json!({"id": "1", "type": "PushEvent", "payload": {}});
+        std::fs::write(source_dir.path().join("a.json"), line.to_string()).unwrap();
+
+        let source = Arc::new(DirectorySource::new(source_dir.path().to_path_buf()).unwrap());
+        let stats = Importer::new("dir://test", source, options(state_dir.path()))
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(stats.records, 1);
+        assert_eq!(stats.failed, 1);
+        assert_eq!(stats.normalized, 0);
+    }
+
+    /// Verify that an interrupted import resumes after the last delivered record.
+    #[tokio::test]
+    async fn test_resumes_from_checkpoint_after_outage() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        write_archive(source_dir.path());
+        let source: Arc<dyn ImportSource> =
+            Arc::new(DirectorySource::new(source_dir.path().to_path_buf()).unwrap());
+
+        let failing = Arc::new(RecordingSink {
+            fail_after: Some(1),
+            ..Default::default()
+        });
+        let result = Importer::new("dir://test", source.clone(), options(state_dir.path()))
+            .with_sink(failing.clone())
+            .run()
+            .await;
+        assert!(matches!(result, Err(ImportError::DeliveryUnavailable(_))));
+
+        let resumed = Arc::new(RecordingSink::default());
+        let stats = Importer::new("dir://test", source, options(state_dir.path()))
+            .with_sink(resumed.clone())
+            .run()
+            .await
+            .unwrap();
+
+        let first = failing.delivered.lock().unwrap().clone();
+        let rest = resumed.delivered.lock().unwrap().clone();
+        assert_eq!(first.len(), 1);
+        assert_eq!(rest.len(), 2);
+        assert!(!rest.contains(&first[0]));
+        assert_eq!(stats.delivered, 3);
+    }
+
+    /// Verify that a checkpoint for another source is rejected unless restarting.
+    #[tokio::test]
+    async fn test_rejects_checkpoint_for_other_source() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let state_dir = tempfile::tempdir().unwrap();
+        let mut checkpoint = ImportCheckpoint::new("dir://elsewhere");
+        checkpoint
+            .save(&state_dir.path().join("checkpoint.json"))
+            .await
+            .unwrap();
+        let source: Arc<dyn ImportSource> =
+            Arc::new(DirectorySource::new(source_dir.path().to_path_buf()).unwrap());
+
+        let result = Importer::new("dir://test", source.clone(), options(state_dir.path()))
+            .run()
+            .await;
+        assert!(matches!(
+            result,
+            Err(ImportError::CheckpointMismatch { .. })
+        ));
+
+        let mut restart = options(state_dir.path());
+        restart.restart = true;
+        assert!(Importer::new("dir://test", source, restart)
+            .run()
+            .await
+            .is_ok());
+    }
+}
+
+mod signing_tests {
+    use super::*;
+
+    /// Verify the signature format matches GitHub's X-Hub-Signature-256.
+    #[test]
+    fn test_sign_payload_format() {
+        let signature = sign_payload("secret", b"{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+    }
+}
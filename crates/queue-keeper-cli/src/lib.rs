@@ -1,905 +1,1855 @@
-//! # Queue-Keeper CLI
-//!
-//! Command-line interface for Queue-Keeper event processing system.
-//!
-//! This module provides CLI commands for:
-//! - Starting/stopping the service
-//! - Configuration validation
-//! - Status monitoring
-//! - Debugging and troubleshooting
-//!
-//! See specs/interfaces/cli-interface.md for complete specification.
-
-use clap::{Parser, Subcommand};
-use queue_keeper_core::{QueueKeeperError, ValidationError};
-use std::path::PathBuf;
-use tracing::info;
-
-// ============================================================================
-// CLI Structure
-// ============================================================================
-
-/// Queue-Keeper CLI - Event processing for GitHub webhooks
-#[derive(Parser)]
-#[command(name = "queue-keeper")]
-#[command(version = env!("CARGO_PKG_VERSION"))]
-#[command(about = "Event processing system for GitHub webhooks")]
-#[command(
-    long_about = "Queue-Keeper processes GitHub webhooks with ordered delivery and reliable processing"
-)]
-pub struct Cli {
-    /// Configuration file path
-    #[arg(short, long, env = "QUEUE_KEEPER_CONFIG")]
-    pub config: Option<PathBuf>,
-
-    /// Logging level
-    #[arg(short, long, default_value = "info")]
-    pub log_level: String,
-
-    /// Enable JSON logging
-    #[arg(long)]
-    pub json_logs: bool,
-
-    /// Subcommand to execute
-    #[command(subcommand)]
-    pub command: Commands,
-}
-
-/// Available CLI commands
-#[derive(Subcommand)]
-pub enum Commands {
-    /// Start the Queue-Keeper service
-    Start {
-        /// Service mode (server or worker)
-        #[arg(short, long, default_value = "server")]
-        mode: ServiceMode,
-
-        /// Port to bind HTTP server
-        #[arg(short, long, default_value = "8080")]
-        port: u16,
-
-        /// Host to bind HTTP server
-        #[arg(long, default_value = "0.0.0.0")]
-        host: String,
-
-        /// Run in foreground (don't daemonize)
-        #[arg(short, long)]
-        foreground: bool,
-    },
-
-    /// Stop the Queue-Keeper service
-    Stop {
-        /// Graceful shutdown timeout in seconds
-        #[arg(short, long, default_value = "30")]
-        timeout: u64,
-
-        /// Force kill if graceful shutdown fails
-        #[arg(short, long)]
-        force: bool,
-    },
-
-    /// Show service status
-    Status {
-        /// Show detailed status information
-        #[arg(short, long)]
-        verbose: bool,
-
-        /// Output format
-        #[arg(short, long, default_value = "text")]
-        format: OutputFormat,
-    },
-
-    /// Validate configuration
-    Config {
-        /// Configuration file to validate
-        #[arg(short, long)]
-        file: Option<PathBuf>,
-
-        /// Show resolved configuration
-        #[arg(short, long)]
-        show: bool,
-
-        /// Output format for configuration
-        #[arg(short = 'f', long, default_value = "yaml")]
-        format: ConfigFormat,
-    },
-
-    /// Monitor event processing
-    Monitor {
-        /// Follow log output
-        #[arg(short, long)]
-        follow: bool,
-
-        /// Filter by event type
-        #[arg(short, long)]
-        event_type: Option<String>,
-
-        /// Filter by repository
-        #[arg(short, long)]
-        repository: Option<String>,
-
-        /// Show only errors
-        #[arg(long)]
-        errors_only: bool,
-
-        /// Number of recent events to show
-        #[arg(short, long, default_value = "100")]
-        limit: usize,
-    },
-
-    /// Event management commands
-    Events {
-        #[command(subcommand)]
-        action: EventCommands,
-    },
-
-    /// Session management commands
-    Sessions {
-        #[command(subcommand)]
-        action: SessionCommands,
-    },
-
-    /// Health check commands
-    Health {
-        #[command(subcommand)]
-        action: HealthCommands,
-    },
-
-    /// Generate shell completions
-    Completions {
-        /// Shell to generate completions for
-        #[arg(value_enum)]
-        shell: clap_complete::Shell,
-    },
-}
-
-/// Service operating modes
-#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
-pub enum ServiceMode {
-    /// HTTP server receiving webhooks
-    Server,
-    /// Background worker processing events
-    Worker,
-    /// Combined server and worker
-    Combined,
-}
-
-/// Output format options
-#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
-pub enum OutputFormat {
-    /// Human-readable text
-    Text,
-    /// JSON output
-    Json,
-    /// YAML output
-    Yaml,
-    /// Table format
-    Table,
-}
-
-/// Configuration format options
-#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
-pub enum ConfigFormat {
-    /// YAML format
-    Yaml,
-    /// JSON format
-    Json,
-    /// TOML format
-    Toml,
-}
-
-// ============================================================================
-// Event Commands
-// ============================================================================
-
-/// Event management subcommands
-#[derive(Subcommand)]
-pub enum EventCommands {
-    /// List recent events
-    List {
-        /// Number of events to show
-        #[arg(short, long, default_value = "50")]
-        limit: usize,
-
-        /// Filter by event type
-        #[arg(short, long)]
-        event_type: Option<String>,
-
-        /// Filter by repository
-        #[arg(short, long)]
-        repository: Option<String>,
-
-        /// Filter by session ID
-        #[arg(short, long)]
-        session: Option<String>,
-
-        /// Show events since timestamp
-        #[arg(short = 'S', long)]
-        since: Option<String>,
-
-        /// Output format
-        #[arg(short, long, default_value = "table")]
-        format: OutputFormat,
-    },
-
-    /// Show event details
-    Show {
-        /// Event ID to display
-        event_id: String,
-
-        /// Output format
-        #[arg(short, long, default_value = "yaml")]
-        format: OutputFormat,
-
-        /// Show raw payload
-        #[arg(long)]
-        raw: bool,
-    },
-
-    /// Replay an event
-    Replay {
-        /// Event ID to replay
-        event_id: String,
-
-        /// Force replay even if already processed
-        #[arg(short, long)]
-        force: bool,
-
-        /// Target queue for replay
-        #[arg(short, long)]
-        queue: Option<String>,
-    },
-
-    /// Delete an event
-    Delete {
-        /// Event ID to delete
-        event_id: String,
-
-        /// Skip confirmation prompt
-        #[arg(short, long)]
-        yes: bool,
-    },
-}
-
-// ============================================================================
-// Session Commands
-// ============================================================================
-
-/// Session management subcommands
-#[derive(Subcommand)]
-pub enum SessionCommands {
-    /// List active sessions
-    List {
-        /// Repository filter
-        #[arg(short, long)]
-        repository: Option<String>,
-
-        /// Entity type filter
-        #[arg(short, long)]
-        entity_type: Option<String>,
-
-        /// Show sessions with pending events
-        #[arg(short, long)]
-        pending_only: bool,
-
-        /// Output format
-        #[arg(short, long, default_value = "table")]
-        format: OutputFormat,
-    },
-
-    /// Show session details
-    Show {
-        /// Session ID to display
-        session_id: String,
-
-        /// Output format
-        #[arg(short, long, default_value = "yaml")]
-        format: OutputFormat,
-
-        /// Include event history
-        #[arg(long)]
-        with_events: bool,
-    },
-
-    /// Reset session state
-    Reset {
-        /// Session ID to reset
-        session_id: String,
-
-        /// Skip confirmation prompt
-        #[arg(short, long)]
-        yes: bool,
-
-        /// Reset reason
-        #[arg(short, long)]
-        reason: Option<String>,
-    },
-
-    /// Pause session processing
-    Pause {
-        /// Session ID to pause
-        session_id: String,
-
-        /// Pause reason
-        #[arg(short, long)]
-        reason: Option<String>,
-    },
-
-    /// Resume session processing
-    Resume {
-        /// Session ID to resume
-        session_id: String,
-    },
-}
-
-// ============================================================================
-// Health Commands
-// ============================================================================
-
-/// Health check subcommands
-#[derive(Subcommand)]
-pub enum HealthCommands {
-    /// Check overall system health
-    Check {
-        /// Include detailed component checks
-        #[arg(short, long)]
-        verbose: bool,
-
-        /// Timeout for health checks in seconds
-        #[arg(short, long, default_value = "10")]
-        timeout: u64,
-
-        /// Output format
-        #[arg(short, long, default_value = "text")]
-        format: OutputFormat,
-    },
-
-    /// Check queue connectivity
-    Queue {
-        /// Queue provider to check
-        #[arg(short, long)]
-        provider: Option<String>,
-
-        /// Include queue statistics
-        #[arg(short, long)]
-        stats: bool,
-    },
-
-    /// Check GitHub API connectivity
-    Github {
-        /// Test authentication
-        #[arg(short, long)]
-        auth: bool,
-
-        /// Test rate limits
-        #[arg(short, long)]
-        rate_limits: bool,
-    },
-
-    /// Check storage connectivity
-    Storage {
-        /// Storage type to check
-        #[arg(short, long)]
-        storage_type: Option<String>,
-
-        /// Include storage statistics
-        #[arg(short, long)]
-        stats: bool,
-    },
-}
-
-// ============================================================================
-// CLI Error Types
-// ============================================================================
-
-/// CLI-specific errors
-#[derive(Debug, thiserror::Error)]
-pub enum CliError {
-    #[error("Configuration error: {0}")]
-    Configuration(#[from] CliConfigError),
-
-    #[error("Service error: {0}")]
-    Service(#[from] CliServiceError),
-
-    #[error("Command failed: {message}")]
-    CommandFailed { message: String },
-
-    #[error("Invalid argument: {arg} - {message}")]
-    InvalidArgument { arg: String, message: String },
-
-    #[error("IO error: {0}")]
-    Io(#[from] std::io::Error),
-
-    #[error("Queue-Keeper error: {0}")]
-    QueueKeeper(#[from] QueueKeeperError),
-}
-
-/// Configuration-related errors for the CLI process.
-///
-/// Distinct from `queue_keeper_api::ConfigError` which handles server-side
-/// provider configuration validation.
-#[derive(Debug, thiserror::Error)]
-pub enum CliConfigError {
-    #[error("Configuration file not found: {path}")]
-    FileNotFound { path: PathBuf },
-
-    #[error("Invalid configuration format: {0}")]
-    InvalidFormat(#[from] toml::de::Error),
-
-    #[error("Validation error: {0}")]
-    Validation(#[from] ValidationError),
-
-    #[error("Missing required configuration: {key}")]
-    MissingRequired { key: String },
-}
-
-/// Service process-management errors for the CLI.
-///
-/// Distinct from `queue_keeper_api::ServiceError` which handles HTTP server
-/// lifecycle failures.
-#[derive(Debug, thiserror::Error)]
-pub enum CliServiceError {
-    #[error("Service not running")]
-    NotRunning,
-
-    #[error("Service already running: PID {pid}")]
-    AlreadyRunning { pid: u32 },
-
-    #[error("Service start failed: {message}")]
-    StartFailed { message: String },
-
-    #[error("Service stop failed: {message}")]
-    StopFailed { message: String },
-
-    #[error("Service timeout: operation took longer than {seconds}s")]
-    Timeout { seconds: u64 },
-}
-
-// ============================================================================
-// Configuration Types
-// ============================================================================
-
-/// CLI configuration structure
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-pub struct CliConfig {
-    /// Default service mode
-    pub default_mode: ServiceMode,
-
-    /// Default HTTP server settings
-    pub server: CliServerConfig,
-
-    /// Default logging configuration
-    pub logging: CliLoggingConfig,
-
-    /// Output formatting preferences
-    pub output: OutputConfig,
-}
-
-impl Default for CliConfig {
-    fn default() -> Self {
-        Self {
-            default_mode: ServiceMode::Combined,
-            server: CliServerConfig::default(),
-            logging: CliLoggingConfig::default(),
-            output: OutputConfig::default(),
-        }
-    }
-}
-
-/// HTTP server connection settings used by the CLI when launching the service.
-///
-/// Contains only the fields the CLI user can override; the full server
-/// configuration lives in `queue_keeper_api::ServerConfig`.
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-pub struct CliServerConfig {
-    pub host: String,
-    pub port: u16,
-    pub timeout_seconds: u64,
-}
-
-impl Default for CliServerConfig {
-    fn default() -> Self {
-        Self {
-            host: "0.0.0.0".to_string(),
-            port: 8080,
-            timeout_seconds: 30,
-        }
-    }
-}
-
-/// Logging preferences for the CLI process.
-///
-/// Distinct from `queue_keeper_api::LoggingConfig` which uses a boolean
-/// `json_format` field and `file_path: Option<String>`.
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-pub struct CliLoggingConfig {
-    pub level: String,
-    pub format: LogFormat,
-    pub file: Option<PathBuf>,
-}
-
-impl Default for CliLoggingConfig {
-    fn default() -> Self {
-        Self {
-            level: "info".to_string(),
-            format: LogFormat::Text,
-            file: None,
-        }
-    }
-}
-
-/// Log format options
-#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
-pub enum LogFormat {
-    #[serde(rename = "text")]
-    Text,
-    #[serde(rename = "json")]
-    Json,
-}
-
-/// Output formatting preferences
-#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
-pub struct OutputConfig {
-    pub default_format: OutputFormat,
-    pub colors: bool,
-    pub timestamps: bool,
-}
-
-impl Default for OutputConfig {
-    fn default() -> Self {
-        Self {
-            default_format: OutputFormat::Text,
-            colors: true,
-            timestamps: true,
-        }
-    }
-}
-
-// ============================================================================
-// Main Entry Point (Stub)
-// ============================================================================
-
-/// Main CLI entry point
-pub async fn run_cli() -> Result<(), CliError> {
-    let cli = Cli::parse();
-
-    // Initialize logging
-    initialize_logging(&cli)?;
-
-    // Load configuration
-    let config = load_configuration(cli.config.as_ref()).await?;
-
-    // Execute command
-    match cli.command {
-        Commands::Start {
-            mode,
-            port,
-            host,
-            foreground,
-        } => execute_start_command(mode, port, host, foreground, &config).await,
-        Commands::Stop { timeout, force } => execute_stop_command(timeout, force).await,
-        Commands::Status { verbose, format } => execute_status_command(verbose, format).await,
-        Commands::Config { file, show, format } => execute_config_command(file, show, format).await,
-        Commands::Monitor {
-            follow,
-            event_type,
-            repository,
-            errors_only,
-            limit,
-        } => execute_monitor_command(follow, event_type, repository, errors_only, limit).await,
-        Commands::Events { action } => execute_events_command(action).await,
-        Commands::Sessions { action } => execute_sessions_command(action).await,
-        Commands::Health { action } => execute_health_command(action).await,
-        Commands::Completions { shell } => execute_completions_command(shell).await,
-    }
-}
-
-// ============================================================================
-// Command Implementations (Stubs)
-// ============================================================================
-
-/// Initialize logging based on CLI arguments
-fn initialize_logging(_cli: &Cli) -> Result<(), CliError> {
-    // See specs/interfaces/cli-interface.md
-    tracing::warn!("logging initialisation not yet implemented");
-    Ok(())
-}
-
-/// Load configuration from file or defaults
-async fn load_configuration(_config_path: Option<&PathBuf>) -> Result<CliConfig, CliConfigError> {
-    // See specs/interfaces/cli-interface.md
-    Ok(CliConfig::default())
-}
-
-/// Execute start command
-async fn execute_start_command(
-    mode: ServiceMode,
-    port: u16,
-    host: String,
-    foreground: bool,
-    _config: &CliConfig,
-) -> Result<(), CliError> {
-    info!(
-        mode = ?mode,
-        port = port,
-        host = %host,
-        foreground = foreground,
-        "Starting Queue-Keeper service"
-    );
-    // See specs/interfaces/cli-interface.md
-    Err(CliError::CommandFailed {
-        message: "not yet implemented".to_string(),
-    })
-}
-
-/// Execute stop command
-async fn execute_stop_command(timeout: u64, force: bool) -> Result<(), CliError> {
-    info!(
-        timeout = timeout,
-        force = force,
-        "Stopping Queue-Keeper service"
-    );
-    // See specs/interfaces/cli-interface.md
-    Err(CliError::CommandFailed {
-        message: "not yet implemented".to_string(),
-    })
-}
-
-/// Execute status command
-async fn execute_status_command(verbose: bool, format: OutputFormat) -> Result<(), CliError> {
-    info!(
-        verbose = verbose,
-        format = ?format,
-        "Checking service status"
-    );
-    // See specs/interfaces/cli-interface.md
-    Err(CliError::CommandFailed {
-        message: "not yet implemented".to_string(),
-    })
-}
-
-/// Execute config command
-async fn execute_config_command(
-    file: Option<PathBuf>,
-    show: bool,
-    format: ConfigFormat,
-) -> Result<(), CliError> {
-    info!(
-        file = ?file,
-        show = show,
-        format = ?format,
-        "Processing config command"
-    );
-    // See specs/interfaces/cli-interface.md
-    Err(CliError::CommandFailed {
-        message: "not yet implemented".to_string(),
-    })
-}
-
-/// Execute monitor command
-async fn execute_monitor_command(
-    follow: bool,
-    event_type: Option<String>,
-    repository: Option<String>,
-    errors_only: bool,
-    limit: usize,
-) -> Result<(), CliError> {
-    info!(
-        follow = follow,
-        event_type = ?event_type,
-        repository = ?repository,
-        errors_only = errors_only,
-        limit = limit,
-        "Starting event monitoring"
-    );
-    // See specs/interfaces/cli-interface.md
-    Err(CliError::CommandFailed {
-        message: "not yet implemented".to_string(),
-    })
-}
-
-/// Execute events command
-async fn execute_events_command(action: EventCommands) -> Result<(), CliError> {
-    match action {
-        EventCommands::List {
-            limit,
-            event_type,
-            repository,
-            session,
-            since,
-            format,
-        } => {
-            info!(
-                limit = limit,
-                event_type = ?event_type,
-                repository = ?repository,
-                session = ?session,
-                since = ?since,
-                format = ?format,
-                "Listing events"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-        EventCommands::Show {
-            event_id,
-            format,
-            raw,
-        } => {
-            info!(
-                event_id = %event_id,
-                format = ?format,
-                raw = raw,
-                "Showing event details"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-        EventCommands::Replay {
-            event_id,
-            force,
-            queue,
-        } => {
-            info!(
-                event_id = %event_id,
-                force = force,
-                queue = ?queue,
-                "Replaying event"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-        EventCommands::Delete { event_id, yes } => {
-            info!(
-                event_id = %event_id,
-                yes = yes,
-                "Deleting event"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-    }
-}
-
-/// Execute sessions command
-async fn execute_sessions_command(action: SessionCommands) -> Result<(), CliError> {
-    match action {
-        SessionCommands::List {
-            repository,
-            entity_type,
-            pending_only,
-            format,
-        } => {
-            info!(
-                repository = ?repository,
-                entity_type = ?entity_type,
-                pending_only = pending_only,
-                format = ?format,
-                "Listing sessions"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-        SessionCommands::Show {
-            session_id,
-            format,
-            with_events,
-        } => {
-            info!(
-                session_id = %session_id,
-                format = ?format,
-                with_events = with_events,
-                "Showing session details"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-        SessionCommands::Reset {
-            session_id,
-            yes,
-            reason,
-        } => {
-            info!(
-                session_id = %session_id,
-                yes = yes,
-                reason = ?reason,
-                "Resetting session"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-        SessionCommands::Pause { session_id, reason } => {
-            info!(
-                session_id = %session_id,
-                reason = ?reason,
-                "Pausing session"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-        SessionCommands::Resume { session_id } => {
-            info!(
-                session_id = %session_id,
-                "Resuming session"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-    }
-}
-
-/// Execute health command
-async fn execute_health_command(action: HealthCommands) -> Result<(), CliError> {
-    match action {
-        HealthCommands::Check {
-            verbose,
-            timeout,
-            format,
-        } => {
-            info!(
-                verbose = verbose,
-                timeout = timeout,
-                format = ?format,
-                "Checking system health"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-        HealthCommands::Queue { provider, stats } => {
-            info!(
-                provider = ?provider,
-                stats = stats,
-                "Checking queue health"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-        HealthCommands::Github { auth, rate_limits } => {
-            info!(
-                auth = auth,
-                rate_limits = rate_limits,
-                "Checking GitHub connectivity"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-        HealthCommands::Storage {
-            storage_type,
-            stats,
-        } => {
-            info!(
-                storage_type = ?storage_type,
-                stats = stats,
-                "Checking storage health"
-            );
-            Err(CliError::CommandFailed {
-                message: "not yet implemented".to_string(),
-            })
-        }
-    }
-}
-
-/// Execute completions command
-async fn execute_completions_command(shell: clap_complete::Shell) -> Result<(), CliError> {
-    info!(shell = ?shell, "Generating shell completions");
-    // See specs/interfaces/cli-interface.md
-    Err(CliError::CommandFailed {
-        message: "not yet implemented".to_string(),
-    })
-}
-
-#[cfg(test)]
-#[path = "lib_tests.rs"]
-mod tests;
+//! # Queue-Keeper CLI
+//!
+//! Command-line interface for Queue-Keeper event processing system.
+//!
+//! This module provides CLI commands for:
+//! - Starting/stopping the service on the local host ([`process`])
+//! - Configuration validation ([`validate`])
+//! - Status monitoring and following processed events live ([`monitor`])
+//! - Debugging and troubleshooting
+//! - Backfilling events from archives ([`import`])
+//! - Upgrading persisted artifacts after a release ([`migrate`])
+//! - Capturing and restoring operational state ([`snapshot`])
+//! - Previewing bot subscription changes ([`simulate`])
+//! - Verifying a deployment end to end ([`selftest`])
+//! - Inspecting tracked sessions ([`sessions`])
+//! - Listing, showing, replaying and soft-deleting stored events ([`events`])
+//! - Targeting environments through connection profiles ([`profile`])
+//! - Generating shell completion scripts ([`completions`])
+//!
+//! See specs/interfaces/cli-interface.md for complete specification.
+
+pub mod completions;
+pub mod events;
+pub mod import;
+pub mod migrate;
+pub mod monitor;
+pub mod process;
+pub mod profile;
+pub mod selftest;
+pub mod sessions;
+pub mod simulate;
+pub mod snapshot;
+pub mod status;
+pub mod validate;
+
+use clap::{CommandFactory, Parser, Subcommand};
+use events::{event_text, events_table, ApiEvents, EventFilter, EventsError, ReplayOptions};
+use import::{ImportError, ImportOptions, Importer, SourceLocation, WebhookForwarder};
+use migrate::{MigrateError, StorageMigrator};
+use monitor::{event_line, ApiMonitor, MonitorError, MonitorFilter, MonitorUpdate};
+use process::{PidFile, StartOptions};
+use profile::{Connection, Profile, ProfileError, ProfileStore};
+use queue_keeper_core::{QueueKeeperError, Timestamp, ValidationError};
+use selftest::{ApiSelfTest, SelfTestError};
+use sessions::{pause_status_text, sessions_table, ApiSessions, SessionFilter, SessionsError};
+use simulate::{ApiEventHistory, EventHistory, SimulateError};
+use snapshot::{RestoreTargets, SnapshotArchive, SnapshotError, SnapshotSources};
+use status::{ApiStatus, ServiceStatus, StatusError};
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::info;
+use validate::ValidateError;
+
+// ============================================================================
+// CLI Structure
+// ============================================================================
+
+/// Queue-Keeper CLI - Event processing for GitHub webhooks
+#[derive(Parser)]
+#[command(name = "queue-keeper")]
+#[command(version = env!("CARGO_PKG_VERSION"))]
+#[command(about = "Event processing system for GitHub webhooks")]
+#[command(
+    long_about = "Queue-Keeper processes GitHub webhooks with ordered delivery and reliable processing"
+)]
+pub struct Cli {
+    /// Configuration file path
+    #[arg(short, long, env = "QUEUE_KEEPER_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Logging level
+    #[arg(short, long, default_value = "info")]
+    pub log_level: String,
+
+    /// Enable JSON logging
+    #[arg(long)]
+    pub json_logs: bool,
+
+    /// Queue-Keeper base URL [env: QUEUE_KEEPER_URL]
+    #[arg(long, global = true)]
+    pub endpoint: Option<String>,
+
+    /// Admin API token [env: QUEUE_KEEPER_ADMIN_API_KEY]
+    #[arg(long, global = true, alias = "api-key")]
+    pub token: Option<String>,
+
+    /// Connection profile to use instead of the current one
+    #[arg(long, global = true, env = "QUEUE_KEEPER_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Profiles file (default: ~/.config/queue-keeper/profiles.toml)
+    #[arg(long, global = true, env = "QUEUE_KEEPER_PROFILES_FILE")]
+    pub profiles_file: Option<PathBuf>,
+
+    /// Subcommand to execute
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+/// Available CLI commands
+#[derive(Subcommand)]
+pub enum Commands {
+    /// Start the Queue-Keeper service
+    Start {
+        /// Service mode (server or worker)
+        #[arg(short, long, default_value = "server")]
+        mode: ServiceMode,
+
+        /// Port to bind HTTP server
+        #[arg(short, long, default_value = "8080")]
+        port: u16,
+
+        /// Host to bind HTTP server
+        #[arg(long, default_value = "0.0.0.0")]
+        host: String,
+
+        /// Run in foreground (don't daemonize)
+        #[arg(short, long)]
+        foreground: bool,
+    },
+
+    /// Stop the Queue-Keeper service
+    Stop {
+        /// Graceful shutdown timeout in seconds
+        #[arg(short, long, default_value = "30")]
+        timeout: u64,
+
+        /// Kill the service if it does not stop within the timeout
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Show service status
+    Status {
+        /// Show detailed status information
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Output format [default: text]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Validate configuration
+    Config {
+        /// Service or bot configuration file to validate [default: --config]
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+
+        /// Show resolved configuration
+        #[arg(short, long)]
+        show: bool,
+
+        /// Output format for configuration
+        #[arg(long, default_value = "yaml")]
+        format: ConfigFormat,
+    },
+
+    /// Monitor event processing
+    Monitor {
+        /// Stream events live as the service processes them
+        #[arg(short, long)]
+        follow: bool,
+
+        /// Filter by event type, optionally with an action (`type.action`)
+        #[arg(short, long)]
+        event_type: Option<String>,
+
+        /// Filter by repository
+        #[arg(short, long)]
+        repository: Option<String>,
+
+        /// Show only rejected webhooks and failed deliveries (with --follow)
+        #[arg(long)]
+        errors_only: bool,
+
+        /// Number of recent events to show (without --follow)
+        #[arg(short, long, default_value = "100")]
+        limit: usize,
+    },
+
+    /// Event management commands
+    Events {
+        #[command(subcommand)]
+        action: EventCommands,
+    },
+
+    /// Session management commands
+    Sessions {
+        #[command(subcommand)]
+        action: SessionCommands,
+    },
+
+    /// Health check commands
+    Health {
+        #[command(subcommand)]
+        action: HealthCommands,
+    },
+
+    /// Bot subscription commands
+    Bots {
+        #[command(subcommand)]
+        action: BotCommands,
+    },
+
+    /// Backfill webhook payloads from an archive or existing storage
+    Import {
+        /// Payload source (dir://<path> or s3://<bucket>/<prefix>)
+        #[arg(short, long)]
+        source: String,
+
+        /// Checkpoint file used to resume an interrupted import
+        #[arg(long, default_value = "queue-keeper-import.checkpoint.json")]
+        checkpoint: PathBuf,
+
+        /// Ignore any existing checkpoint and start from the first file
+        #[arg(long)]
+        restart: bool,
+
+        /// Maximum records per second (0 = unlimited)
+        #[arg(long, default_value = "10")]
+        rate_limit: u32,
+
+        /// Records processed between checkpoint writes
+        #[arg(long, default_value = "100")]
+        checkpoint_interval: usize,
+
+        /// Queue-Keeper base URL to deliver normalized records to for routing
+        #[arg(long)]
+        deliver_to: Option<String>,
+
+        /// Webhook secret used to sign delivered records
+        #[arg(long, env = "QK_IMPORT_WEBHOOK_SECRET", hide_env_values = true)]
+        webhook_secret: Option<String>,
+    },
+
+    /// Rewrite stored blobs and DLQ records from older releases in the current format
+    MigrateStorage {
+        /// Filesystem storage directory (payload, event or DLQ storage root)
+        #[arg(short, long)]
+        path: PathBuf,
+
+        /// Report what would be migrated without rewriting anything
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Output format [default: text]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Capture or restore the persisted state of a deployment
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotCommands,
+    },
+
+    /// Run a synthetic event through the full pipeline of a running service
+    Selftest {
+        /// Output format [default: text]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Manage connection profiles
+    Profile {
+        #[command(subcommand)]
+        action: ProfileCommands,
+    },
+
+    /// Generate shell completions
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: clap_complete::Shell,
+    },
+}
+
+/// Service operating modes
+#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+pub enum ServiceMode {
+    /// HTTP server receiving webhooks
+    Server,
+    /// Background worker processing events
+    Worker,
+    /// Combined server and worker
+    Combined,
+}
+
+/// Output format options
+#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human-readable text
+    Text,
+    /// JSON output
+    Json,
+    /// YAML output
+    Yaml,
+    /// Table format
+    Table,
+}
+
+/// Configuration format options
+#[derive(Clone, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ConfigFormat {
+    /// YAML format
+    Yaml,
+    /// JSON format
+    Json,
+    /// TOML format
+    Toml,
+}
+
+// ============================================================================
+// Event Commands
+// ============================================================================
+
+/// Event management subcommands
+#[derive(Subcommand)]
+pub enum EventCommands {
+    /// List recent events
+    List {
+        /// Number of events to show
+        #[arg(short, long, default_value = "50")]
+        limit: usize,
+
+        /// Filter by event type
+        #[arg(short, long)]
+        event_type: Option<String>,
+
+        /// Filter by repository
+        #[arg(short, long)]
+        repository: Option<String>,
+
+        /// Filter by session ID
+        #[arg(short, long)]
+        session: Option<String>,
+
+        /// Show events received within this window (e.g. 24h, 7d) or since an RFC 3339 timestamp
+        #[arg(short = 'S', long)]
+        since: Option<String>,
+
+        /// Output format [default: table]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Show event details
+    Show {
+        /// Event ID to display
+        event_id: String,
+
+        /// Output format [default: yaml]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+
+        /// Show raw payload
+        #[arg(long)]
+        raw: bool,
+    },
+
+    /// Replay an event
+    Replay {
+        /// Event ID to replay
+        event_id: String,
+
+        /// Replay without asking for confirmation
+        #[arg(short, long)]
+        force: bool,
+
+        /// Target queue for replay (default: every queue the event routes to)
+        #[arg(short, long)]
+        queue: Option<String>,
+
+        /// Replay reason, recorded in the audit log
+        #[arg(short, long)]
+        reason: Option<String>,
+    },
+
+    /// Soft-delete an event; the service purges it after a grace period
+    Delete {
+        /// Event ID to delete
+        event_id: String,
+
+        /// Deletion reason, recorded in the tombstone and the audit log
+        #[arg(short, long)]
+        reason: String,
+
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+    },
+}
+
+// ============================================================================
+// Session Commands
+// ============================================================================
+
+/// Session management subcommands
+#[derive(Subcommand)]
+pub enum SessionCommands {
+    /// List sessions seen since the service started
+    List {
+        /// Repository filter
+        #[arg(short, long)]
+        repository: Option<String>,
+
+        /// Entity type filter
+        #[arg(short, long)]
+        entity_type: Option<String>,
+
+        /// Show sessions with pending events
+        #[arg(short, long)]
+        pending_only: bool,
+
+        /// Output format [default: table]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Show session details
+    Show {
+        /// Session ID to display
+        session_id: String,
+
+        /// Output format [default: yaml]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+
+        /// Include event history
+        #[arg(long)]
+        with_events: bool,
+    },
+
+    /// Reset session state, cancelling its pending deliveries
+    Reset {
+        /// Session ID to reset
+        session_id: String,
+
+        /// Skip confirmation prompt
+        #[arg(short, long)]
+        yes: bool,
+
+        /// Reset reason, recorded in the audit log
+        #[arg(short, long)]
+        reason: String,
+    },
+
+    /// Pause session processing, buffering its events
+    Pause {
+        /// Session ID to pause
+        session_id: String,
+
+        /// Pause reason, recorded in the audit log
+        #[arg(short, long)]
+        reason: String,
+    },
+
+    /// Resume session processing, delivering its buffered events in order
+    Resume {
+        /// Session ID to resume
+        session_id: String,
+    },
+}
+
+// ============================================================================
+// Health Commands
+// ============================================================================
+
+/// Health check subcommands
+#[derive(Subcommand)]
+pub enum HealthCommands {
+    /// Check overall system health
+    Check {
+        /// Include detailed component checks
+        #[arg(short, long)]
+        verbose: bool,
+
+        /// Timeout for health checks in seconds
+        #[arg(short, long, default_value = "10")]
+        timeout: u64,
+
+        /// Output format [default: text]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Check queue connectivity
+    Queue {
+        /// Queue provider to check
+        #[arg(short, long)]
+        provider: Option<String>,
+
+        /// Include queue statistics
+        #[arg(short, long)]
+        stats: bool,
+    },
+
+    /// Check GitHub API connectivity
+    Github {
+        /// Test authentication
+        #[arg(short, long)]
+        auth: bool,
+
+        /// Test rate limits
+        #[arg(short, long)]
+        rate_limits: bool,
+    },
+
+    /// Check storage connectivity
+    Storage {
+        /// Storage type to check
+        #[arg(long)]
+        storage_type: Option<String>,
+
+        /// Include storage statistics
+        #[arg(short, long)]
+        stats: bool,
+    },
+}
+
+// ============================================================================
+// Bot Commands
+// ============================================================================
+
+/// Bot subscription subcommands
+#[derive(Subcommand)]
+pub enum BotCommands {
+    /// Preview how a bot configuration change would re-route past events
+    Simulate {
+        /// Proposed bot configuration file (YAML or JSON)
+        #[arg(short, long)]
+        config: PathBuf,
+
+        /// Current bot configuration file (defaults to BOT_CONFIGURATION)
+        #[arg(short, long)]
+        baseline: Option<PathBuf>,
+
+        /// Replay events received within this window (e.g. 7d, 12h) or since an RFC 3339 timestamp
+        #[arg(short = 'S', long, default_value = "7d")]
+        since: String,
+
+        /// Output format [default: text]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+}
+
+// ============================================================================
+// Snapshot Commands
+// ============================================================================
+
+/// Snapshot subcommands
+#[derive(Subcommand)]
+pub enum SnapshotCommands {
+    /// Write events, DLQ records and configuration to a snapshot archive
+    Create {
+        /// Snapshot archive to write
+        #[arg(short, long)]
+        output: PathBuf,
+
+        /// Event storage directory
+        #[arg(long, env = "QK_EVENT_STORAGE_PATH", default_value = "./data/events")]
+        event_storage: PathBuf,
+
+        /// DLQ storage directory
+        #[arg(long)]
+        dlq_storage: Option<PathBuf>,
+
+        /// Configuration file to include (repeatable)
+        #[arg(long = "include-config")]
+        config_files: Vec<PathBuf>,
+
+        /// Output format [default: text]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+
+    /// Write the contents of a snapshot archive to storage directories
+    Restore {
+        /// Snapshot archive to read
+        #[arg(short, long)]
+        input: PathBuf,
+
+        /// Event storage directory
+        #[arg(long, env = "QK_EVENT_STORAGE_PATH", default_value = "./data/events")]
+        event_storage: PathBuf,
+
+        /// DLQ storage directory (the DLQ section is skipped without it)
+        #[arg(long)]
+        dlq_storage: Option<PathBuf>,
+
+        /// Directory for configuration files (skipped without it)
+        #[arg(long)]
+        config_dir: Option<PathBuf>,
+
+        /// Restore into directories that already contain files
+        #[arg(long)]
+        force: bool,
+
+        /// Output format [default: text]
+        #[arg(short, long)]
+        format: Option<OutputFormat>,
+    },
+}
+
+// ============================================================================
+// Profile Commands
+// ============================================================================
+
+/// Connection profile subcommands
+#[derive(Subcommand)]
+pub enum ProfileCommands {
+    /// List profiles, marking the current one
+    List,
+
+    /// Make a profile the current one
+    Use {
+        /// Profile name
+        name: String,
+    },
+
+    /// Create or update a profile from the global --endpoint and --token flags
+    Set {
+        /// Profile name
+        name: String,
+
+        /// Output format for commands run without --format
+        #[arg(long)]
+        output: Option<OutputFormat>,
+    },
+
+    /// Remove a profile
+    Remove {
+        /// Profile name
+        name: String,
+    },
+}
+
+// ============================================================================
+// CLI Error Types
+// ============================================================================
+
+/// CLI-specific errors
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    #[error("Configuration error: {0}")]
+    Configuration(#[from] CliConfigError),
+
+    #[error("Service error: {0}")]
+    Service(#[from] CliServiceError),
+
+    #[error("Command failed: {message}")]
+    CommandFailed { message: String },
+
+    #[error("Invalid argument: {arg} - {message}")]
+    InvalidArgument { arg: String, message: String },
+
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Queue-Keeper error: {0}")]
+    QueueKeeper(#[from] QueueKeeperError),
+
+    #[error("Import error: {0}")]
+    Import(#[from] ImportError),
+
+    #[error("Migration error: {0}")]
+    Migrate(#[from] MigrateError),
+
+    #[error("Simulation error: {0}")]
+    Simulate(#[from] SimulateError),
+
+    #[error("Snapshot error: {0}")]
+    Snapshot(#[from] SnapshotError),
+
+    #[error("Self-test error: {0}")]
+    SelfTest(#[from] SelfTestError),
+
+    #[error("Sessions error: {0}")]
+    Sessions(#[from] SessionsError),
+
+    #[error("Events error: {0}")]
+    Events(#[from] EventsError),
+
+    #[error("Status error: {0}")]
+    Status(#[from] StatusError),
+
+    #[error("Profile error: {0}")]
+    Profile(#[from] ProfileError),
+
+    #[error("Config validation error: {0}")]
+    Validate(#[from] ValidateError),
+
+    #[error("Monitor error: {0}")]
+    Monitor(#[from] MonitorError),
+}
+
+/// Configuration-related errors for the CLI process.
+///
+/// Distinct from `queue_keeper_api::ConfigError` which handles server-side
+/// provider configuration validation.
+#[derive(Debug, thiserror::Error)]
+pub enum CliConfigError {
+    #[error("Configuration file not found: {path}")]
+    FileNotFound { path: PathBuf },
+
+    #[error("Invalid configuration format: {0}")]
+    InvalidFormat(#[from] toml::de::Error),
+
+    #[error("Validation error: {0}")]
+    Validation(#[from] ValidationError),
+
+    #[error("Missing required configuration: {key}")]
+    MissingRequired { key: String },
+}
+
+/// Service process-management errors for the CLI.
+///
+/// Distinct from `queue_keeper_api::ServiceError` which handles HTTP server
+/// lifecycle failures.
+#[derive(Debug, thiserror::Error)]
+pub enum CliServiceError {
+    #[error("Service not running")]
+    NotRunning,
+
+    #[error("Service already running: PID {pid}")]
+    AlreadyRunning { pid: u32 },
+
+    #[error("Service start failed: {message}")]
+    StartFailed { message: String },
+
+    #[error("Service stop failed: {message}")]
+    StopFailed { message: String },
+
+    #[error("Service timeout: operation took longer than {seconds}s")]
+    Timeout { seconds: u64 },
+
+    #[error("PID file {path}: {message}")]
+    PidFile { path: PathBuf, message: String },
+}
+
+// ============================================================================
+// Configuration Types
+// ============================================================================
+
+/// CLI configuration structure
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct CliConfig {
+    /// Default service mode
+    pub default_mode: ServiceMode,
+
+    /// Default HTTP server settings
+    pub server: CliServerConfig,
+
+    /// Default logging configuration
+    pub logging: CliLoggingConfig,
+
+    /// Output formatting preferences
+    pub output: OutputConfig,
+}
+
+impl Default for CliConfig {
+    fn default() -> Self {
+        Self {
+            default_mode: ServiceMode::Combined,
+            server: CliServerConfig::default(),
+            logging: CliLoggingConfig::default(),
+            output: OutputConfig::default(),
+        }
+    }
+}
+
+/// HTTP server connection settings used by the CLI when launching the service.
+///
+/// Contains only the fields the CLI user can override; the full server
+/// configuration lives in `queue_keeper_api::ServerConfig`.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct CliServerConfig {
+    pub host: String,
+    pub port: u16,
+    pub timeout_seconds: u64,
+    /// PID file of a service started by the CLI; defaults to
+    /// [`PidFile::default_path`].
+    #[serde(default)]
+    pub pid_file: Option<PathBuf>,
+}
+
+impl CliServerConfig {
+    /// The PID file of a service started by the CLI.
+    pub fn pid_file(&self) -> PidFile {
+        PidFile::new(self.pid_file.clone().unwrap_or_else(PidFile::default_path))
+    }
+}
+
+impl Default for CliServerConfig {
+    fn default() -> Self {
+        Self {
+            host: "0.0.0.0".to_string(),
+            port: 8080,
+            timeout_seconds: 30,
+            pid_file: None,
+        }
+    }
+}
+
+/// Logging preferences for the CLI process.
+///
+/// Distinct from `queue_keeper_api::LoggingConfig` which uses a boolean
+/// `json_format` field and `file_path: Option<String>`.
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct CliLoggingConfig {
+    pub level: String,
+    pub format: LogFormat,
+    pub file: Option<PathBuf>,
+}
+
+impl Default for CliLoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            format: LogFormat::Text,
+            file: None,
+        }
+    }
+}
+
+/// Log format options
+#[derive(Debug, Clone, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum LogFormat {
+    #[serde(rename = "text")]
+    Text,
+    #[serde(rename = "json")]
+    Json,
+}
+
+/// Output formatting preferences
+#[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
+pub struct OutputConfig {
+    pub default_format: OutputFormat,
+    pub colors: bool,
+    pub timestamps: bool,
+}
+
+impl Default for OutputConfig {
+    fn default() -> Self {
+        Self {
+            default_format: OutputFormat::Text,
+            colors: true,
+            timestamps: true,
+        }
+    }
+}
+
+// ============================================================================
+// Main Entry Point (Stub)
+// ============================================================================
+
+/// Main CLI entry point
+pub async fn run_cli() -> Result<(), CliError> {
+    let cli = Cli::parse();
+
+    // Initialize logging
+    initialize_logging(&cli)?;
+
+    // Load configuration
+    let config = load_configuration(cli.config.as_ref()).await?;
+
+    // Profile commands manage the profiles rather than connect with them
+    let connection = if matches!(cli.command, Commands::Profile { .. }) {
+        Connection::default()
+    } else {
+        resolve_connection(&cli)?
+    };
+
+    // Execute command
+    match cli.command {
+        Commands::Start {
+            mode,
+            port,
+            host,
+            foreground,
+        } => execute_start_command(mode, port, host, foreground, cli.config, &config).await,
+        Commands::Stop { timeout, force } => execute_stop_command(timeout, force, &config).await,
+        Commands::Status { verbose, format } => {
+            let format = connection.output_format(format, OutputFormat::Text);
+            execute_status_command(verbose, format, &connection, &config).await
+        }
+        Commands::Config { file, show, format } => {
+            execute_config_command(file.or(cli.config), show, format).await
+        }
+        Commands::Monitor {
+            follow,
+            event_type,
+            repository,
+            errors_only,
+            limit,
+        } => {
+            execute_monitor_command(
+                follow,
+                event_type,
+                repository,
+                errors_only,
+                limit,
+                &connection,
+            )
+            .await
+        }
+        Commands::Events { action } => execute_events_command(action, &connection).await,
+        Commands::Sessions { action } => execute_sessions_command(action, &connection).await,
+        Commands::Health { action } => execute_health_command(action, &connection).await,
+        Commands::Bots { action } => execute_bots_command(action, &connection).await,
+        Commands::Import {
+            source,
+            checkpoint,
+            restart,
+            rate_limit,
+            checkpoint_interval,
+            deliver_to,
+            webhook_secret,
+        } => {
+            let options = ImportOptions {
+                checkpoint_path: checkpoint,
+                restart,
+                rate_limit,
+                checkpoint_interval,
+            };
+            execute_import_command(source, options, deliver_to, webhook_secret).await
+        }
+        Commands::MigrateStorage {
+            path,
+            dry_run,
+            format,
+        } => {
+            let format = connection.output_format(format, OutputFormat::Text);
+            execute_migrate_storage_command(path, dry_run, format).await
+        }
+        Commands::Snapshot { action } => execute_snapshot_command(action, &connection).await,
+        Commands::Selftest { format } => {
+            let format = connection.output_format(format, OutputFormat::Text);
+            execute_selftest_command(&connection, format).await
+        }
+        Commands::Profile { action } => {
+            execute_profile_command(action, cli.profiles_file, cli.endpoint, cli.token)
+        }
+        Commands::Completions { shell } => execute_completions_command(shell).await,
+    }
+}
+
+// ============================================================================
+// Command Implementations (Stubs)
+// ============================================================================
+
+/// Initialize logging based on CLI arguments
+fn initialize_logging(_cli: &Cli) -> Result<(), CliError> {
+    // See specs/interfaces/cli-interface.md
+    tracing::warn!("logging initialisation not yet implemented");
+    Ok(())
+}
+
+/// Location of the profiles file: `profiles_file` when given, otherwise the
+/// default path.
+fn profiles_path(profiles_file: Option<PathBuf>) -> Result<PathBuf, ProfileError> {
+    match profiles_file {
+        Some(path) => Ok(path),
+        None => ProfileStore::default_path(),
+    }
+}
+
+/// Read a non-empty environment variable.
+fn env_value(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Resolve the service connection from the global flags, their environment
+/// variables and the selected profile.
+///
+/// Without a home directory, and no `--profiles-file`, no profiles exist.
+fn resolve_connection(cli: &Cli) -> Result<Connection, CliError> {
+    let store = match profiles_path(cli.profiles_file.clone()) {
+        Ok(path) => ProfileStore::load(&path)?,
+        Err(ProfileError::NoHomeDirectory) => ProfileStore::default(),
+        Err(e) => return Err(e.into()),
+    };
+    let profile = store.selected(cli.profile.as_deref())?;
+    Ok(Connection::resolve(
+        cli.endpoint
+            .clone()
+            .or_else(|| env_value("QUEUE_KEEPER_URL")),
+        cli.token
+            .clone()
+            .or_else(|| env_value("QUEUE_KEEPER_ADMIN_API_KEY")),
+        profile,
+    ))
+}
+
+/// Load configuration from file or defaults
+async fn load_configuration(_config_path: Option<&PathBuf>) -> Result<CliConfig, CliConfigError> {
+    // See specs/interfaces/cli-interface.md
+    Ok(CliConfig::default())
+}
+
+/// Execute start command
+///
+/// The service binary runs the HTTP server and the delivery workers in one
+/// process, so `server` and `combined` start the same service; a worker-only
+/// process does not exist.
+async fn execute_start_command(
+    mode: ServiceMode,
+    port: u16,
+    host: String,
+    foreground: bool,
+    config_file: Option<PathBuf>,
+    config: &CliConfig,
+) -> Result<(), CliError> {
+    info!(
+        mode = ?mode,
+        port = port,
+        host = %host,
+        foreground = foreground,
+        "Starting Queue-Keeper service"
+    );
+    if mode == ServiceMode::Worker {
+        return Err(CliError::InvalidArgument {
+            arg: "mode".to_string(),
+            message: "the service has no worker-only mode; use server or combined".to_string(),
+        });
+    }
+
+    let pid_file = config.server.pid_file();
+    let options = StartOptions {
+        binary: process::service_binary(),
+        host,
+        port,
+        config_file,
+        foreground,
+    };
+    let process = process::start(&pid_file, &options).await?;
+    if foreground {
+        println!("Service (PID {}) exited", process.pid);
+    } else {
+        println!(
+            "Started service (PID {}) on {}:{}",
+            process.pid, process.host, process.port
+        );
+        if let Some(log_file) = &process.log_file {
+            println!("Logs: {}", log_file.display());
+        }
+    }
+    Ok(())
+}
+
+/// Execute stop command
+async fn execute_stop_command(
+    timeout: u64,
+    force: bool,
+    config: &CliConfig,
+) -> Result<(), CliError> {
+    info!(
+        timeout = timeout,
+        force = force,
+        "Stopping Queue-Keeper service"
+    );
+    let pid_file = config.server.pid_file();
+    let process = process::stop(&pid_file, Duration::from_secs(timeout), force).await?;
+    println!("Stopped service (PID {})", process.pid);
+    Ok(())
+}
+
+/// Execute status command
+///
+/// The local process is read from the PID file; a missing or unreadable PID
+/// file only leaves it out.
+async fn execute_status_command(
+    verbose: bool,
+    format: OutputFormat,
+    connection: &Connection,
+    config: &CliConfig,
+) -> Result<(), CliError> {
+    info!(
+        verbose = verbose,
+        format = ?format,
+        endpoint = %connection.endpoint,
+        "Checking service status"
+    );
+    let api = ApiStatus::new(&connection.endpoint);
+    let health = api.health().await?;
+    let mut status = ServiceStatus {
+        endpoint: connection.endpoint.clone(),
+        health,
+        process: config.server.pid_file().running().ok().flatten(),
+        statistics: api.statistics().await?,
+    };
+
+    match format {
+        OutputFormat::Text | OutputFormat::Table => print!("{}", status.to_text(verbose)),
+        OutputFormat::Json | OutputFormat::Yaml => {
+            if !verbose {
+                status.statistics.events_by_type.clear();
+                status.statistics.events_by_origin.clear();
+            }
+            if format == OutputFormat::Json {
+                println!("{}", to_json(&status)?);
+            } else {
+                print!("{}", to_yaml(&status)?);
+            }
+        }
+    }
+    if !status.health.is_healthy() {
+        return Err(CliError::CommandFailed {
+            message: format!("service is {}", status.health.status),
+        });
+    }
+    Ok(())
+}
+
+/// Execute config command
+async fn execute_config_command(
+    file: Option<PathBuf>,
+    show: bool,
+    format: ConfigFormat,
+) -> Result<(), CliError> {
+    info!(
+        file = ?file,
+        show = show,
+        format = ?format,
+        "Processing config command"
+    );
+    let file = file.ok_or_else(|| CliError::InvalidArgument {
+        arg: "file".to_string(),
+        message: "no configuration file given; use --file, --config or QUEUE_KEEPER_CONFIG"
+            .to_string(),
+    })?;
+    let report = validate::validate_file(&file)?;
+
+    if show {
+        // Diagnostics go to stderr so that the configuration can be piped.
+        eprint!("{}", report.to_text());
+        if report.is_valid() {
+            print!("{}", report.render(format)?);
+        }
+    } else {
+        print!("{}", report.to_text());
+    }
+    if !report.is_valid() {
+        return Err(ValidateError::Invalid {
+            path: file,
+            errors: report.errors(),
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Execute monitor command
+///
+/// Lists the `limit` most recent events, or with `follow` streams event
+/// summaries until interrupted. Stored events carry no delivery outcome, so
+/// `errors_only` requires `follow`.
+async fn execute_monitor_command(
+    follow: bool,
+    event_type: Option<String>,
+    repository: Option<String>,
+    errors_only: bool,
+    limit: usize,
+    connection: &Connection,
+) -> Result<(), CliError> {
+    info!(
+        follow = follow,
+        event_type = ?event_type,
+        repository = ?repository,
+        errors_only = errors_only,
+        limit = limit,
+        endpoint = %connection.endpoint,
+        "Starting event monitoring"
+    );
+
+    if !follow {
+        if errors_only {
+            return Err(CliError::InvalidArgument {
+                arg: "errors-only".to_string(),
+                message: "stored events carry no delivery outcome; use it with --follow"
+                    .to_string(),
+            });
+        }
+        let filter = EventFilter {
+            event_type,
+            repository,
+            ..EventFilter::default()
+        };
+        let events = ApiEvents::new(&connection.endpoint)
+            .with_api_key(connection.token.clone())
+            .list(&filter, limit)
+            .await?;
+        print!("{}", events_table(&events));
+        return Ok(());
+    }
+
+    let filter = MonitorFilter {
+        event_type,
+        repository,
+        errors_only,
+    };
+    ApiMonitor::new(&connection.endpoint)
+        .with_api_key(connection.token.clone())
+        .follow(&filter, |update| match update {
+            MonitorUpdate::Event(event) => println!("{}", event_line(&event)),
+            MonitorUpdate::Lagged { missed } => {
+                eprintln!("Skipped {} event(s): the monitor fell behind", missed)
+            }
+            MonitorUpdate::Reconnecting { delay, reason } => eprintln!(
+                "Event stream dropped ({}); reconnecting in {}s",
+                reason,
+                delay.as_secs()
+            ),
+        })
+        .await?;
+    Ok(())
+}
+
+/// Execute events command
+async fn execute_events_command(
+    action: EventCommands,
+    connection: &Connection,
+) -> Result<(), CliError> {
+    match action {
+        EventCommands::List {
+            limit,
+            event_type,
+            repository,
+            session,
+            since,
+            format,
+        } => {
+            let format = connection.output_format(format, OutputFormat::Table);
+            info!(
+                limit = limit,
+                event_type = ?event_type,
+                repository = ?repository,
+                session = ?session,
+                since = ?since,
+                format = ?format,
+                endpoint = %connection.endpoint,
+                "Listing events"
+            );
+            let since = since
+                .map(|since| simulate::parse_since(&since, Timestamp::now()))
+                .transpose()
+                .map_err(|e| CliError::InvalidArgument {
+                    arg: "since".to_string(),
+                    message: e.to_string(),
+                })?;
+            let filter = EventFilter {
+                event_type,
+                repository,
+                session,
+                since,
+            };
+            let events = ApiEvents::new(&connection.endpoint)
+                .with_api_key(connection.token.clone())
+                .list(&filter, limit)
+                .await?;
+
+            match format {
+                OutputFormat::Json => println!("{}", to_json(&events)?),
+                OutputFormat::Yaml => print!("{}", to_yaml(&events)?),
+                OutputFormat::Text | OutputFormat::Table => print!("{}", events_table(&events)),
+            }
+            Ok(())
+        }
+        EventCommands::Show {
+            event_id,
+            format,
+            raw,
+        } => {
+            let format = connection.output_format(format, OutputFormat::Yaml);
+            info!(
+                event_id = %event_id,
+                format = ?format,
+                raw = raw,
+                endpoint = %connection.endpoint,
+                "Showing event details"
+            );
+            let event = ApiEvents::new(&connection.endpoint)
+                .with_api_key(connection.token.clone())
+                .show(&event_id)
+                .await?;
+
+            match (format, raw) {
+                (OutputFormat::Yaml, true) => print!("{}", to_yaml(&event.payload)?),
+                (_, true) => println!("{}", to_json(&event.payload)?),
+                (OutputFormat::Json, false) => println!("{}", to_json(&event)?),
+                (OutputFormat::Yaml, false) => print!("{}", to_yaml(&event)?),
+                (OutputFormat::Text | OutputFormat::Table, false) => {
+                    print!("{}", event_text(&event))
+                }
+            }
+            Ok(())
+        }
+        EventCommands::Replay {
+            event_id,
+            force,
+            queue,
+            reason,
+        } => {
+            info!(
+                event_id = %event_id,
+                force = force,
+                queue = ?queue,
+                endpoint = %connection.endpoint,
+                "Replaying event"
+            );
+            if !force && !confirm(&format!("Replay event {}?", event_id))? {
+                return Err(CliError::CommandFailed {
+                    message: "replay cancelled".to_string(),
+                });
+            }
+            let events =
+                ApiEvents::new(&connection.endpoint).with_api_key(connection.token.clone());
+            let result = match queue {
+                Some(queue) => events.replay_to_queue(&event_id, &queue, reason).await?,
+                None => {
+                    let options = ReplayOptions {
+                        reason,
+                        ..ReplayOptions::default()
+                    };
+                    events.replay(&event_id, &options).await?
+                }
+            };
+            print!("{}", result.to_text());
+            if result.status == "failed" {
+                return Err(CliError::CommandFailed {
+                    message: result.message,
+                });
+            }
+            Ok(())
+        }
+        EventCommands::Delete {
+            event_id,
+            reason,
+            yes,
+        } => {
+            info!(
+                event_id = %event_id,
+                yes = yes,
+                endpoint = %connection.endpoint,
+                "Deleting event"
+            );
+            if !yes && !confirm(&format!("Delete event {}?", event_id))? {
+                return Err(CliError::CommandFailed {
+                    message: "deletion cancelled".to_string(),
+                });
+            }
+            let tombstone = ApiEvents::new(&connection.endpoint)
+                .with_api_key(connection.token.clone())
+                .delete(&event_id, &reason)
+                .await?;
+            print!("{}", tombstone.to_text());
+            Ok(())
+        }
+    }
+}
+
+/// Execute sessions command
+async fn execute_sessions_command(
+    action: SessionCommands,
+    connection: &Connection,
+) -> Result<(), CliError> {
+    match action {
+        SessionCommands::List {
+            repository,
+            entity_type,
+            pending_only,
+            format,
+        } => {
+            info!(
+                repository = ?repository,
+                entity_type = ?entity_type,
+                pending_only = pending_only,
+                endpoint = %connection.endpoint,
+                "Listing sessions"
+            );
+            let filter = SessionFilter {
+                repository,
+                entity_type,
+                pending_only,
+            };
+            let sessions = ApiSessions::new(&connection.endpoint).list(&filter).await?;
+
+            match connection.output_format(format, OutputFormat::Table) {
+                OutputFormat::Json => println!("{}", to_json(&sessions)?),
+                OutputFormat::Yaml => print!("{}", to_yaml(&sessions)?),
+                OutputFormat::Text | OutputFormat::Table => {
+                    print!("{}", sessions_table(&sessions))
+                }
+            }
+            Ok(())
+        }
+        SessionCommands::Show {
+            session_id,
+            format,
+            with_events,
+        } => {
+            info!(
+                session_id = %session_id,
+                with_events = with_events,
+                endpoint = %connection.endpoint,
+                "Showing session details"
+            );
+            let mut details = ApiSessions::new(&connection.endpoint)
+                .show(&session_id)
+                .await?;
+            let format = connection.output_format(format, OutputFormat::Yaml);
+
+            match format {
+                OutputFormat::Text | OutputFormat::Table => {
+                    print!("{}", details.to_text(with_events))
+                }
+                OutputFormat::Json | OutputFormat::Yaml => {
+                    if !with_events {
+                        details.events.clear();
+                    }
+                    if format == OutputFormat::Json {
+                        println!("{}", to_json(&details)?);
+                    } else {
+                        print!("{}", to_yaml(&details)?);
+                    }
+                }
+            }
+            Ok(())
+        }
+        SessionCommands::Reset {
+            session_id,
+            yes,
+            reason,
+        } => {
+            info!(
+                session_id = %session_id,
+                yes = yes,
+                endpoint = %connection.endpoint,
+                "Resetting session"
+            );
+            let reason = required_reason(&reason)?;
+            if !yes && !confirm(&format!("Reset session {}?", session_id))? {
+                return Err(CliError::CommandFailed {
+                    message: "reset cancelled".to_string(),
+                });
+            }
+            let result = ApiSessions::new(&connection.endpoint)
+                .with_api_key(connection.token.clone())
+                .reset(&session_id, reason)
+                .await?;
+            println!("{}", result.message);
+            Ok(())
+        }
+        SessionCommands::Pause { session_id, reason } => {
+            info!(
+                session_id = %session_id,
+                endpoint = %connection.endpoint,
+                "Pausing session"
+            );
+            let reason = required_reason(&reason)?;
+            let status = ApiSessions::new(&connection.endpoint)
+                .with_api_key(connection.token.clone())
+                .pause(&session_id, reason)
+                .await?;
+            print!("{}", pause_status_text(&status));
+            Ok(())
+        }
+        SessionCommands::Resume { session_id } => {
+            info!(
+                session_id = %session_id,
+                endpoint = %connection.endpoint,
+                "Resuming session"
+            );
+            let status = ApiSessions::new(&connection.endpoint)
+                .with_api_key(connection.token.clone())
+                .resume(&session_id)
+                .await?;
+            println!(
+                "Resuming session {}; {} buffered event(s) will be delivered in order",
+                status.session_id, status.buffered_events
+            );
+            Ok(())
+        }
+    }
+}
+
+/// Execute health command
+async fn execute_health_command(
+    action: HealthCommands,
+    connection: &Connection,
+) -> Result<(), CliError> {
+    match action {
+        HealthCommands::Check {
+            verbose,
+            timeout,
+            format,
+        } => {
+            let format = connection.output_format(format, OutputFormat::Text);
+            info!(
+                verbose = verbose,
+                timeout = timeout,
+                format = ?format,
+                "Checking system health"
+            );
+            Err(CliError::CommandFailed {
+                message: "not yet implemented".to_string(),
+            })
+        }
+        HealthCommands::Queue { provider, stats } => {
+            info!(
+                provider = ?provider,
+                stats = stats,
+                "Checking queue health"
+            );
+            Err(CliError::CommandFailed {
+                message: "not yet implemented".to_string(),
+            })
+        }
+        HealthCommands::Github { auth, rate_limits } => {
+            info!(
+                auth = auth,
+                rate_limits = rate_limits,
+                "Checking GitHub connectivity"
+            );
+            Err(CliError::CommandFailed {
+                message: "not yet implemented".to_string(),
+            })
+        }
+        HealthCommands::Storage {
+            storage_type,
+            stats,
+        } => {
+            info!(
+                storage_type = ?storage_type,
+                stats = stats,
+                "Checking storage health"
+            );
+            Err(CliError::CommandFailed {
+                message: "not yet implemented".to_string(),
+            })
+        }
+    }
+}
+
+/// Execute import command
+async fn execute_import_command(
+    source: String,
+    options: ImportOptions,
+    deliver_to: Option<String>,
+    webhook_secret: Option<String>,
+) -> Result<(), CliError> {
+    if options.checkpoint_interval == 0 {
+        return Err(CliError::InvalidArgument {
+            arg: "checkpoint-interval".to_string(),
+            message: "must be greater than zero".to_string(),
+        });
+    }
+
+    let location: SourceLocation = source.parse()?;
+    let import_source = import::open_source(&location).await?;
+
+    let mut importer = Importer::new(location.to_string(), import_source, options);
+    if let Some(url) = deliver_to {
+        importer = importer.with_sink(std::sync::Arc::new(WebhookForwarder::new(
+            &url,
+            webhook_secret,
+        )));
+    }
+
+    let stats = importer.run().await?;
+    println!(
+        "Imported {} records from {} files: {} normalized, {} delivered, {} failed, {} unparseable files",
+        stats.records,
+        stats.files,
+        stats.normalized,
+        stats.delivered,
+        stats.failed,
+        stats.unparseable_files
+    );
+    Ok(())
+}
+
+/// Execute migrate-storage command
+async fn execute_migrate_storage_command(
+    path: PathBuf,
+    dry_run: bool,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    info!(path = %path.display(), dry_run = dry_run, "Migrating persisted storage");
+    let report = StorageMigrator::new(path)
+        .with_dry_run(dry_run)
+        .run()
+        .await?;
+
+    match format {
+        OutputFormat::Json => {
+            let json =
+                serde_json::to_string_pretty(&report).map_err(|e| CliError::CommandFailed {
+                    message: format!("failed to serialize report: {}", e),
+                })?;
+            println!("{}", json);
+        }
+        _ => print!("{}", report.to_text(dry_run)),
+    }
+
+    if report.failures.is_empty() {
+        Ok(())
+    } else {
+        Err(CliError::CommandFailed {
+            message: format!("{} file(s) could not be migrated", report.failures.len()),
+        })
+    }
+}
+
+/// Execute snapshot command
+async fn execute_snapshot_command(
+    action: SnapshotCommands,
+    connection: &Connection,
+) -> Result<(), CliError> {
+    match action {
+        SnapshotCommands::Create {
+            output,
+            event_storage,
+            dlq_storage,
+            config_files,
+            format,
+        } => {
+            info!(output = %output.display(), "Creating snapshot");
+            let sources = SnapshotSources {
+                events: event_storage,
+                dlq: dlq_storage,
+                config_files,
+            };
+            let archive = snapshot::create_snapshot(&sources).await?;
+            archive.write_to(&output).await?;
+
+            match connection.output_format(format, OutputFormat::Text) {
+                OutputFormat::Json => print_json(&archive.manifest)?,
+                _ => println!("{}Written to {}", archive.to_text(), output.display()),
+            }
+            Ok(())
+        }
+        SnapshotCommands::Restore {
+            input,
+            event_storage,
+            dlq_storage,
+            config_dir,
+            force,
+            format,
+        } => {
+            info!(input = %input.display(), force = force, "Restoring snapshot");
+            let archive = SnapshotArchive::read_from(&input).await?;
+            let targets = RestoreTargets {
+                events: event_storage,
+                dlq: dlq_storage,
+                config_dir,
+                force,
+            };
+            let report = snapshot::restore_snapshot(&archive, &targets).await?;
+
+            match connection.output_format(format, OutputFormat::Text) {
+                OutputFormat::Json => print_json(&report)?,
+                _ => print!("{}", report.to_text()),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Print `value` as pretty JSON.
+fn print_json(value: &impl serde::Serialize) -> Result<(), CliError> {
+    let json = serde_json::to_string_pretty(value).map_err(|e| CliError::CommandFailed {
+        message: format!("failed to serialize report: {}", e),
+    })?;
+    println!("{}", json);
+    Ok(())
+}
+
+/// Execute bots command
+async fn execute_bots_command(
+    action: BotCommands,
+    connection: &Connection,
+) -> Result<(), CliError> {
+    match action {
+        BotCommands::Simulate {
+            config,
+            baseline,
+            since,
+            format,
+        } => {
+            let proposed = simulate::load_bot_configuration(&config)?;
+            let current = match baseline {
+                Some(path) => simulate::load_bot_configuration(&path)?,
+                None => queue_keeper_core::BotConfiguration::load_from_env().map_err(|source| {
+                    SimulateError::Configuration {
+                        path: "BOT_CONFIGURATION".to_string(),
+                        source,
+                    }
+                })?,
+            };
+            let since = simulate::parse_since(&since, Timestamp::now())?;
+
+            let endpoint = &connection.endpoint;
+            info!(endpoint = %endpoint, since = %since, "Simulating bot subscription change");
+            let events = ApiEventHistory::new(endpoint).events_since(since).await?;
+            let report = simulate::simulate(&current, &proposed, &events, since);
+
+            match connection.output_format(format, OutputFormat::Text) {
+                OutputFormat::Json => {
+                    let json = serde_json::to_string_pretty(&report).map_err(|e| {
+                        CliError::CommandFailed {
+                            message: format!("failed to serialize report: {}", e),
+                        }
+                    })?;
+                    println!("{}", json);
+                }
+                _ => print!("{}", report.to_text()),
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Execute selftest command
+async fn execute_selftest_command(
+    connection: &Connection,
+    format: OutputFormat,
+) -> Result<(), CliError> {
+    info!(endpoint = %connection.endpoint, "Running pipeline self-test");
+    let report = ApiSelfTest::new(&connection.endpoint)
+        .with_api_key(connection.token.clone())
+        .run()
+        .await?;
+
+    match format {
+        OutputFormat::Json => {
+            let json =
+                serde_json::to_string_pretty(&report).map_err(|e| CliError::CommandFailed {
+                    message: format!("failed to serialize report: {}", e),
+                })?;
+            println!("{}", json);
+        }
+        _ => print!("{}", report.to_text()),
+    }
+
+    if report.passed {
+        Ok(())
+    } else {
+        Err(CliError::CommandFailed {
+            message: format!(
+                "self-test failed: {} stage(s) failed",
+                report.failed_stages()
+            ),
+        })
+    }
+}
+
+/// Execute profile command
+///
+/// `endpoint` and `token` are the global flags, which `profile set` stores.
+fn execute_profile_command(
+    action: ProfileCommands,
+    profiles_file: Option<PathBuf>,
+    endpoint: Option<String>,
+    token: Option<String>,
+) -> Result<(), CliError> {
+    let path = profiles_path(profiles_file)?;
+    let mut store = ProfileStore::load(&path)?;
+    match action {
+        ProfileCommands::List => {
+            if store.profiles.is_empty() {
+                println!("No profiles in {}", path.display());
+            } else {
+                print!("{}", store.to_table());
+            }
+            return Ok(());
+        }
+        ProfileCommands::Use { name } => {
+            store.use_profile(&name)?;
+            println!("Using profile {}", name);
+        }
+        ProfileCommands::Set { name, output } => {
+            store.set(
+                &name,
+                Profile {
+                    endpoint,
+                    token,
+                    output,
+                },
+            )?;
+            println!("Saved profile {}", name);
+        }
+        ProfileCommands::Remove { name } => {
+            store.remove(&name)?;
+            println!("Removed profile {}", name);
+        }
+    }
+    info!(path = %path.display(), "Writing profiles");
+    store.save(&path)?;
+    Ok(())
+}
+
+/// Serialize command output as pretty-printed JSON.
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, CliError> {
+    serde_json::to_string_pretty(value).map_err(|e| CliError::CommandFailed {
+        message: format!("failed to serialize output: {}", e),
+    })
+}
+
+/// Serialize command output as YAML.
+fn to_yaml<T: serde::Serialize>(value: &T) -> Result<String, CliError> {
+    serde_yaml::to_string(value).map_err(|e| CliError::CommandFailed {
+        message: format!("failed to serialize output: {}", e),
+    })
+}
+
+/// Ask `question` on the terminal; only an explicit "y" or "yes" confirms.
+fn confirm(question: &str) -> Result<bool, CliError> {
+    use std::io::Write;
+
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(
+        answer.trim().to_ascii_lowercase().as_str(),
+        "y" | "yes"
+    ))
+}
+
+/// `reason` trimmed, rejecting a blank one before the service is contacted.
+fn required_reason(reason: &str) -> Result<&str, CliError> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(CliError::InvalidArgument {
+            arg: "reason".to_string(),
+            message: "a non-empty reason is required".to_string(),
+        });
+    }
+    Ok(reason)
+}
+
+/// Execute completions command
+async fn execute_completions_command(shell: clap_complete::Shell) -> Result<(), CliError> {
+    info!(shell = ?shell, "Generating shell completions");
+    completions::generate(shell, Cli::command(), &mut std::io::stdout());
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "lib_tests.rs"]
+mod tests;
@@ -15,6 +15,226 @@ fn test_cli_parsing() {
     }
 }
 
+/// Verify `bots simulate` parsing and its defaults.
+#[test]
+fn test_bots_simulate_parsing() {
+    let cli =
+        Cli::try_parse_from(["queue-keeper", "bots", "simulate", "--config", "new.yaml"]).unwrap();
+
+    match cli.command {
+        Commands::Bots {
+            action:
+                BotCommands::Simulate {
+                    config,
+                    baseline,
+                    since,
+                    format,
+                    ..
+                },
+        } => {
+            assert_eq!(config, PathBuf::from("new.yaml"));
+            assert_eq!(baseline, None);
+            assert_eq!(since, "7d");
+            assert_eq!(format, None);
+        }
+        _ => panic!("Expected Bots Simulate command"),
+    }
+}
+
+/// Verify `selftest` parsing and its output format flag.
+#[test]
+fn test_selftest_parsing() {
+    let cli = Cli::try_parse_from(["queue-keeper", "selftest", "--format", "json"]).unwrap();
+
+    match cli.command {
+        Commands::Selftest { format } => assert_eq!(format, Some(OutputFormat::Json)),
+        _ => panic!("Expected Selftest command"),
+    }
+}
+
+/// Verify `sessions list` parsing of its filters and the global endpoint.
+#[test]
+fn test_sessions_list_parsing() {
+    let cli = Cli::try_parse_from([
+        "queue-keeper",
+        "sessions",
+        "list",
+        "--repository",
+        "owner/repo",
+        "--pending-only",
+        "--endpoint",
+        "http://queue-keeper:8080",
+    ])
+    .unwrap();
+
+    match cli.command {
+        Commands::Sessions {
+            action:
+                SessionCommands::List {
+                    repository,
+                    entity_type,
+                    pending_only,
+                    format,
+                },
+        } => {
+            assert_eq!(repository.as_deref(), Some("owner/repo"));
+            assert_eq!(entity_type, None);
+            assert!(pending_only);
+            assert_eq!(format, None);
+        }
+        _ => panic!("Expected Sessions List command"),
+    }
+    assert_eq!(cli.endpoint.as_deref(), Some("http://queue-keeper:8080"));
+}
+
+/// Verify the global connection flags and `profile set` parsing.
+#[test]
+fn test_profile_set_parsing() {
+    let cli = Cli::try_parse_from([
+        "queue-keeper",
+        "profile",
+        "set",
+        "prod",
+        "--endpoint",
+        "https://qk.example",
+        "--api-key",
+        "secret",
+        "--output",
+        "json",
+    ])
+    .unwrap();
+
+    match cli.command {
+        Commands::Profile {
+            action: ProfileCommands::Set { name, output },
+        } => {
+            assert_eq!(name, "prod");
+            assert_eq!(output, Some(OutputFormat::Json));
+        }
+        _ => panic!("Expected Profile Set command"),
+    }
+    assert_eq!(cli.endpoint.as_deref(), Some("https://qk.example"));
+    assert_eq!(cli.token.as_deref(), Some("secret"));
+}
+
+/// Verify that `--profile` selects a profile from the profiles file and that
+/// flags override it.
+#[test]
+fn test_resolve_connection_from_profile() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("profiles.toml");
+    let mut store = ProfileStore::default();
+    store
+        .set(
+            "staging",
+            Profile {
+                endpoint: Some("https://staging.example".to_string()),
+                token: Some("staging-token".to_string()),
+                output: Some(OutputFormat::Json),
+            },
+        )
+        .unwrap();
+    store.save(&path).unwrap();
+    let profiles_file = path.to_str().unwrap();
+
+    let cli = Cli::try_parse_from([
+        "queue-keeper",
+        "--profile",
+        "staging",
+        "--profiles-file",
+        profiles_file,
+        "--token",
+        "flag-token",
+        "selftest",
+    ])
+    .unwrap();
+    let connection = resolve_connection(&cli).unwrap();
+    assert_eq!(connection.endpoint, "https://staging.example");
+    assert_eq!(connection.token.as_deref(), Some("flag-token"));
+    assert_eq!(connection.output, Some(OutputFormat::Json));
+
+    let cli = Cli::try_parse_from([
+        "queue-keeper",
+        "--profile",
+        "prod",
+        "--profiles-file",
+        profiles_file,
+        "selftest",
+    ])
+    .unwrap();
+    assert!(matches!(
+        resolve_connection(&cli),
+        Err(CliError::Profile(ProfileError::NotFound { .. }))
+    ));
+}
+
+/// Verify `migrate-storage` parsing and its dry-run flag.
+#[test]
+fn test_migrate_storage_parsing() {
+    let cli = Cli::try_parse_from([
+        "queue-keeper",
+        "migrate-storage",
+        "--path",
+        "/var/lib/queue-keeper/payloads",
+        "--dry-run",
+    ])
+    .unwrap();
+
+    match cli.command {
+        Commands::MigrateStorage {
+            path,
+            dry_run,
+            format,
+        } => {
+            assert_eq!(path, PathBuf::from("/var/lib/queue-keeper/payloads"));
+            assert!(dry_run);
+            assert_eq!(format, None);
+        }
+        _ => panic!("Expected MigrateStorage command"),
+    }
+}
+
+/// Verify `snapshot create` parsing with repeated configuration files.
+#[test]
+fn test_snapshot_create_parsing() {
+    let cli = Cli::try_parse_from([
+        "queue-keeper",
+        "snapshot",
+        "create",
+        "--output",
+        "prod.qks",
+        "--event-storage",
+        "/var/lib/queue-keeper/events",
+        "--include-config",
+        "service.yaml",
+        "--include-config",
+        "bots.yaml",
+    ])
+    .unwrap();
+
+    match cli.command {
+        Commands::Snapshot {
+            action:
+                SnapshotCommands::Create {
+                    output,
+                    event_storage,
+                    dlq_storage,
+                    config_files,
+                    ..
+                },
+        } => {
+            assert_eq!(output, PathBuf::from("prod.qks"));
+            assert_eq!(event_storage, PathBuf::from("/var/lib/queue-keeper/events"));
+            assert!(dlq_storage.is_none());
+            assert_eq!(
+                config_files,
+                vec![PathBuf::from("service.yaml"), PathBuf::from("bots.yaml")]
+            );
+        }
+        _ => panic!("Expected Snapshot Create command"),
+    }
+}
+
 #[test]
 fn test_config_defaults() {
     let config = CliConfig::default();
@@ -23,89 +243,132 @@ fn test_config_defaults() {
     assert_eq!(config.logging.level, "info");
 }
 
-/// Verify that all command handlers return Err(CliError::CommandFailed) rather than panicking.
+/// The service has no worker-only process to start.
 #[tokio::test]
-async fn test_command_handlers_return_err_not_panic() {
-    let config = CliConfig::default();
-
+async fn test_start_rejects_worker_mode() {
     let result = execute_start_command(
-        ServiceMode::Server,
+        ServiceMode::Worker,
         8080,
         "127.0.0.1".into(),
         false,
-        &config,
+        None,
+        &CliConfig::default(),
     )
     .await;
     assert!(
-        matches!(result, Err(CliError::CommandFailed { .. })),
+        matches!(result, Err(CliError::InvalidArgument { ref arg, .. }) if arg == "mode"),
         "start: {result:?}"
     );
+}
 
-    let result = execute_stop_command(30, false).await;
-    assert!(
-        matches!(result, Err(CliError::CommandFailed { .. })),
-        "stop: {result:?}"
-    );
+/// Stopping without a recorded service reports that it is not running.
+#[tokio::test]
+async fn test_stop_without_pid_file_reports_not_running() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = CliConfig::default();
+    config.server.pid_file = Some(dir.path().join("queue-keeper.pid"));
 
-    let result = execute_status_command(false, OutputFormat::Text).await;
+    let result = execute_stop_command(1, false, &config).await;
     assert!(
-        matches!(result, Err(CliError::CommandFailed { .. })),
-        "status: {result:?}"
+        matches!(result, Err(CliError::Service(CliServiceError::NotRunning))),
+        "stop: {result:?}"
     );
+}
 
+/// `config` needs a file, and fails on one with errors.
+#[tokio::test]
+async fn test_config_command_validates_file() {
     let result = execute_config_command(None, false, ConfigFormat::Yaml).await;
     assert!(
-        matches!(result, Err(CliError::CommandFailed { .. })),
+        matches!(result, Err(CliError::InvalidArgument { ref arg, .. }) if arg == "file"),
         "config: {result:?}"
     );
 
-    let result = execute_monitor_command(false, None, None, false, 10).await;
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("service.yaml");
+    std::fs::write(&path, "server:\n  port: not-a-port\n").unwrap();
+    let result = execute_config_command(Some(path), false, ConfigFormat::Yaml).await;
     assert!(
-        matches!(result, Err(CliError::CommandFailed { .. })),
-        "monitor: {result:?}"
+        matches!(
+            result,
+            Err(CliError::Validate(ValidateError::Invalid { errors: 1, .. }))
+        ),
+        "config: {result:?}"
     );
+}
 
-    let result = execute_events_command(EventCommands::List {
-        limit: 10,
-        event_type: None,
-        repository: None,
-        session: None,
-        since: None,
-        format: OutputFormat::Text,
-    })
-    .await;
+/// Errors-only monitoring needs the live stream, as stored events carry no
+/// delivery outcome.
+#[tokio::test]
+async fn test_monitor_errors_only_requires_follow() {
+    let result = execute_monitor_command(false, None, None, true, 10, &Connection::default()).await;
     assert!(
-        matches!(result, Err(CliError::CommandFailed { .. })),
-        "events: {result:?}"
+        matches!(result, Err(CliError::InvalidArgument { ref arg, .. }) if arg == "errors-only"),
+        "monitor: {result:?}"
     );
+}
 
-    let result = execute_sessions_command(SessionCommands::List {
-        repository: None,
-        entity_type: None,
-        pending_only: false,
-        format: OutputFormat::Text,
-    })
-    .await;
-    assert!(
-        matches!(result, Err(CliError::CommandFailed { .. })),
-        "sessions: {result:?}"
-    );
+/// Verify that all command handlers return Err(CliError::CommandFailed) rather than panicking.
+#[tokio::test]
+async fn test_command_handlers_return_err_not_panic() {
+    let connection = Connection::default();
 
-    let result = execute_health_command(HealthCommands::Check {
-        verbose: false,
-        timeout: 10,
-        format: OutputFormat::Text,
-    })
+    let result = execute_health_command(
+        HealthCommands::Check {
+            verbose: false,
+            timeout: 10,
+            format: None,
+        },
+        &connection,
+    )
     .await;
     assert!(
         matches!(result, Err(CliError::CommandFailed { .. })),
         "health: {result:?}"
     );
+}
+
+/// A blank session pause or reset reason is rejected before the service is
+/// contacted.
+#[tokio::test]
+async fn test_sessions_require_reason() {
+    for action in [
+        SessionCommands::Pause {
+            session_id: "owner/repo/pull_request/1".to_string(),
+            reason: "  ".to_string(),
+        },
+        SessionCommands::Reset {
+            session_id: "owner/repo/pull_request/1".to_string(),
+            yes: true,
+            reason: String::new(),
+        },
+    ] {
+        let result = execute_sessions_command(action, &Connection::default()).await;
+        assert!(
+            matches!(result, Err(CliError::InvalidArgument { ref arg, .. }) if arg == "reason"),
+            "sessions: {result:?}"
+        );
+    }
+}
 
-    let result = execute_completions_command(clap_complete::Shell::Bash).await;
+/// An unparseable `--since` is rejected before the service is contacted.
+#[tokio::test]
+async fn test_events_list_rejects_invalid_since() {
+    let result = execute_events_command(
+        EventCommands::List {
+            limit: 10,
+            event_type: None,
+            repository: None,
+            session: None,
+            since: Some("last tuesday".to_string()),
+            format: Some(OutputFormat::Table),
+        },
+        &Connection::default(),
+    )
+    .await;
     assert!(
-        matches!(result, Err(CliError::CommandFailed { .. })),
-        "completions: {result:?}"
+        matches!(&result, Err(CliError::InvalidArgument { arg, .. }) if arg == "since"),
+        "{result:?}"
     );
 }
 
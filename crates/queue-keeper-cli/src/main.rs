@@ -15,6 +15,17 @@ async fn main() {
             queue_keeper_cli::CliError::InvalidArgument { .. } => 4,
             queue_keeper_cli::CliError::Io(_) => 5,
             queue_keeper_cli::CliError::QueueKeeper(_) => 6,
+            queue_keeper_cli::CliError::Import(_) => 7,
+            queue_keeper_cli::CliError::Simulate(_) => 8,
+            queue_keeper_cli::CliError::SelfTest(_) => 9,
+            queue_keeper_cli::CliError::Migrate(_) => 10,
+            queue_keeper_cli::CliError::Snapshot(_) => 11,
+            queue_keeper_cli::CliError::Sessions(_) => 12,
+            queue_keeper_cli::CliError::Events(_) => 13,
+            queue_keeper_cli::CliError::Profile(_) => 14,
+            queue_keeper_cli::CliError::Status(_) => 15,
+            queue_keeper_cli::CliError::Validate(_) => 16,
+            queue_keeper_cli::CliError::Monitor(_) => 17,
         };
 
         std::process::exit(exit_code);
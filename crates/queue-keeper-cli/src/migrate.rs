@@ -0,0 +1,175 @@
+//! # Storage Migration
+//!
+//! Implements `queue-keeper migrate-storage`, which rewrites artifacts
+//! persisted by an earlier release in the current format (see
+//! [`queue_keeper_core::storage_format`]).
+//!
+//! The command walks a filesystem blob store — the payload, event or DLQ
+//! storage directory of a deployment — and upgrades every `*.json` blob
+//! written in the previous format version. Blobs already in the current
+//! format are left untouched, so the command can be re-run safely. Each
+//! rewrite goes to a temporary file that is renamed over the original.
+//!
+//! Files that are not stored blobs, and blobs written by a newer release,
+//! are reported and skipped. With `--dry-run` nothing is written.
+//!
+//! Archived DLQ bundles are compressed and are not rewritten; their records
+//! are upgraded when a bundle is restored.
+
+use crate::import::{DirectorySource, ImportError, ImportSource};
+use queue_keeper_core::storage_format::{migrate_stored_blob, MigrationOutcome};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tracing::{info, warn};
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors that stop a migration.
+///
+/// Problems with individual files are recorded in
+/// [`MigrationReport::failures`] and do not stop the migration.
+#[derive(Debug, thiserror::Error)]
+pub enum MigrateError {
+    #[error("Cannot read storage directory: {0}")]
+    Source(#[from] ImportError),
+}
+
+// ============================================================================
+// Report
+// ============================================================================
+
+/// A file that could not be migrated.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MigrationFailure {
+    /// Path relative to the storage root.
+    pub key: String,
+
+    /// Why the file was skipped.
+    pub message: String,
+}
+
+/// Outcome of a migration run.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct MigrationReport {
+    /// Blob files examined.
+    pub scanned: usize,
+
+    /// Blobs rewritten in the current format (or, in a dry run, that would be).
+    pub migrated: usize,
+
+    /// Blobs already in the current format.
+    pub current: usize,
+
+    /// Files that could not be read or migrated.
+    pub failures: Vec<MigrationFailure>,
+}
+
+impl MigrationReport {
+    /// Human-readable summary.
+    pub fn to_text(&self, dry_run: bool) -> String {
+        let mut text = format!(
+            "Scanned {} blobs: {} {}, {} already current, {} failed\n",
+            self.scanned,
+            self.migrated,
+            if dry_run { "to migrate" } else { "migrated" },
+            self.current,
+            self.failures.len()
+        );
+        for failure in &self.failures {
+            text.push_str(&format!("  {}: {}\n", failure.key, failure.message));
+        }
+        text
+    }
+}
+
+// ============================================================================
+// Migrator
+// ============================================================================
+
+/// Upgrades the blobs below a filesystem storage root.
+#[derive(Debug, Clone)]
+pub struct StorageMigrator {
+    root: PathBuf,
+    dry_run: bool,
+}
+
+impl StorageMigrator {
+    /// Create a migrator for the storage directory `root`.
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            dry_run: false,
+        }
+    }
+
+    /// Report what would change without rewriting anything.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Migrate every blob below the root.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`MigrateError::Source`] if the root does not exist or cannot
+    /// be listed.
+    pub async fn run(&self) -> Result<MigrationReport, MigrateError> {
+        let source = DirectorySource::new(self.root.clone())?;
+        let mut report = MigrationReport::default();
+
+        for key in source.list_keys().await? {
+            if !key.ends_with(".json") {
+                continue;
+            }
+            report.scanned += 1;
+
+            match self.migrate_file(&source, &key).await {
+                Ok(true) => report.migrated += 1,
+                Ok(false) => report.current += 1,
+                Err(message) => {
+                    warn!(key = %key, error = %message, "Skipping blob that cannot be migrated");
+                    report.failures.push(MigrationFailure { key, message });
+                }
+            }
+        }
+
+        info!(
+            root = %self.root.display(),
+            scanned = report.scanned,
+            migrated = report.migrated,
+            failed = report.failures.len(),
+            dry_run = self.dry_run,
+            "Storage migration finished"
+        );
+        Ok(report)
+    }
+
+    /// Migrate one file; returns whether it needed migrating.
+    async fn migrate_file(&self, source: &DirectorySource, key: &str) -> Result<bool, String> {
+        let document = source.read(key).await.map_err(|e| e.to_string())?;
+        match migrate_stored_blob(&document).map_err(|e| e.to_string())? {
+            MigrationOutcome::Current => Ok(false),
+            MigrationOutcome::Migrated(_) if self.dry_run => Ok(true),
+            MigrationOutcome::Migrated(bytes) => {
+                replace_file(&self.root.join(key), &bytes)
+                    .await
+                    .map_err(|e| format!("failed to write migrated blob: {}", e))?;
+                Ok(true)
+            }
+        }
+    }
+}
+
+/// Replace `path` with `contents` via a temporary file and rename.
+async fn replace_file(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let temp_path = path.with_extension("migrating");
+    tokio::fs::write(&temp_path, contents).await?;
+    tokio::fs::rename(&temp_path, path).await
+}
+
+#[cfg(test)]
+#[path = "migrate_tests.rs"]
+mod tests;
@@ -0,0 +1,117 @@
+//! Tests for the storage migration command.
+
+use super::*;
+use bytes::Bytes;
+use queue_keeper_core::{
+    blob_storage::{
+        compute_checksum, BlobMetadata, PayloadMetadata, StoredWebhook, WebhookPayload,
+    },
+    storage_format::{CURRENT_FORMAT_VERSION, LEGACY_FORMAT_VERSION},
+    EventId, Timestamp,
+};
+use std::collections::HashMap;
+
+/// Write a stored blob below `root`, without a version marker when `legacy`.
+fn write_blob(root: &Path, legacy: bool) -> PathBuf {
+    let event_id = EventId::new();
+    let body = Bytes::from_static(br#"{"action":"opened"}"#);
+    let metadata = PayloadMetadata {
+        event_id,
+        event_type: "issues".to_string(),
+        repository: None,
+        signature_valid: true,
+        received_at: Timestamp::now(),
+        delivery_id: None,
+    };
+    let stored = StoredWebhook {
+        metadata: BlobMetadata {
+            event_id,
+            blob_path: event_id.to_blob_path(),
+            size_bytes: body.len() as u64,
+            content_type: "application/json".to_string(),
+            created_at: Timestamp::now(),
+            checksum_sha256: compute_checksum(&body),
+            format_version: CURRENT_FORMAT_VERSION,
+            content_encoding: queue_keeper_core::blob_storage::ContentEncoding::Identity,
+            encryption: None,
+            metadata: metadata.clone(),
+        },
+        payload: WebhookPayload {
+            body,
+            headers: HashMap::new(),
+            metadata,
+        },
+    };
+    let mut value = serde_json::to_value(&stored).unwrap();
+    if legacy {
+        value["metadata"]
+            .as_object_mut()
+            .unwrap()
+            .remove("format_version");
+    }
+
+    let path = root.join(event_id.to_blob_path());
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, serde_json::to_vec(&value).unwrap()).unwrap();
+    path
+}
+
+fn format_version(path: &Path) -> u32 {
+    let stored: StoredWebhook = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    stored.metadata.format_version
+}
+
+/// Legacy blobs are rewritten, current ones left alone and other files
+/// reported; a second run finds nothing to do.
+#[tokio::test]
+async fn test_migrates_legacy_blobs() {
+    let dir = tempfile::tempdir().unwrap();
+    let legacy = write_blob(dir.path(), true);
+    let current = write_blob(dir.path(), false);
+    std::fs::write(dir.path().join("notes.json"), b"not a blob").unwrap();
+    std::fs::write(dir.path().join("README.txt"), b"ignored").unwrap();
+
+    let report = StorageMigrator::new(dir.path().to_path_buf())
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(report.scanned, 3);
+    assert_eq!(report.migrated, 1);
+    assert_eq!(report.current, 1);
+    assert_eq!(report.failures.len(), 1);
+    assert_eq!(report.failures[0].key, "notes.json");
+
+    assert_eq!(format_version(&legacy), CURRENT_FORMAT_VERSION);
+    assert_eq!(format_version(&current), CURRENT_FORMAT_VERSION);
+
+    let rerun = StorageMigrator::new(dir.path().to_path_buf())
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(rerun.migrated, 0);
+    assert_eq!(rerun.current, 2);
+}
+
+/// A dry run counts legacy blobs without rewriting them.
+#[tokio::test]
+async fn test_dry_run_does_not_write() {
+    let dir = tempfile::tempdir().unwrap();
+    let legacy = write_blob(dir.path(), true);
+
+    let report = StorageMigrator::new(dir.path().to_path_buf())
+        .with_dry_run(true)
+        .run()
+        .await
+        .unwrap();
+    assert_eq!(report.migrated, 1);
+    assert_eq!(format_version(&legacy), LEGACY_FORMAT_VERSION);
+    assert!(report.to_text(true).contains("1 to migrate"));
+}
+
+/// A missing storage directory stops the migration.
+#[tokio::test]
+async fn test_missing_directory_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let result = StorageMigrator::new(dir.path().join("missing")).run().await;
+    assert!(matches!(result, Err(MigrateError::Source(_))));
+}
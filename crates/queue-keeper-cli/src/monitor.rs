@@ -0,0 +1,307 @@
+//! # Event Monitoring
+//!
+//! Implements `queue-keeper monitor` against a running service:
+//!
+//! - without `--follow`, the command lists the most recent events from
+//!   `GET /api/events`, like `events list`;
+//! - with `--follow`, it reads the server-sent events of
+//!   `GET /api/events/stream` and prints one line per event summary as the
+//!   service publishes it, until interrupted.
+//!
+//! The stream carries every summary, so the event type, repository and
+//! errors-only filters of a followed stream apply on the client. A stream
+//! that drops is reopened after a delay that doubles up to
+//! [`MAX_RECONNECT_DELAY`]; summaries published meanwhile are missed.
+
+use queue_keeper_api::event_stream::StreamedEvent;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Delay before the first attempt to reopen a dropped stream.
+pub const MIN_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+
+/// Longest delay between two attempts to reopen a dropped stream.
+pub const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors following the event stream of the service.
+#[derive(Debug, thiserror::Error)]
+pub enum MonitorError {
+    #[error("The service at {endpoint} does not stream events (event_stream.enabled is off)")]
+    Disabled { endpoint: String },
+
+    #[error("Event stream request failed: {message}")]
+    Request { message: String },
+}
+
+// ============================================================================
+// Filtering and Rendering
+// ============================================================================
+
+/// Client-side filters of `queue-keeper monitor --follow`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MonitorFilter {
+    /// Event type, e.g. `pull_request`, or type and action, e.g.
+    /// `pull_request.opened`.
+    pub event_type: Option<String>,
+    /// Repository full name (`owner/repo`), compared case-insensitively.
+    pub repository: Option<String>,
+    /// Only rejected webhooks and failed deliveries.
+    pub errors_only: bool,
+}
+
+impl MonitorFilter {
+    /// Whether `event` passes every filter.
+    pub fn matches(&self, event: &StreamedEvent) -> bool {
+        if self.errors_only && !event.status.is_error() {
+            return false;
+        }
+        if let Some(event_type) = &self.event_type {
+            let matches_type = match event_type.split_once('.') {
+                Some((name, action)) => {
+                    event.event_type == name && event.action.as_deref() == Some(action)
+                }
+                None => event.event_type == *event_type,
+            };
+            if !matches_type {
+                return false;
+            }
+        }
+        match &self.repository {
+            Some(repository) => event
+                .repository
+                .as_deref()
+                .is_some_and(|name| name.eq_ignore_ascii_case(repository)),
+            None => true,
+        }
+    }
+}
+
+/// Render an event summary as one line of `monitor --follow` output.
+pub fn event_line(event: &StreamedEvent) -> String {
+    let event_type = match &event.action {
+        Some(action) => format!("{}.{}", event.event_type, action),
+        None => event.event_type.clone(),
+    };
+    let mut line = format!(
+        "{} {:<15} {:<32} {:<32} {}",
+        event.timestamp.to_rfc3339(),
+        event.status.as_str(),
+        event_type,
+        event.repository.as_deref().unwrap_or("-"),
+        event
+            .event_id
+            .map_or_else(|| "-".to_string(), |id| id.to_string())
+    );
+    if let Some(bot_name) = &event.bot_name {
+        line.push_str(&format!(" bot={}", bot_name));
+    }
+    if let Some(message) = &event.message {
+        line.push_str(&format!(" ({})", message));
+    }
+    line
+}
+
+// ============================================================================
+// Server-Sent Events
+// ============================================================================
+
+/// A message of a server-sent events stream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseMessage {
+    /// Event name; `message` when the server sent none.
+    pub event: String,
+    /// Data lines, joined with `\n`.
+    pub data: String,
+}
+
+/// Splits a server-sent events stream into messages.
+///
+/// Chunks may end anywhere, even inside a line. Comments, such as the
+/// keep-alives of the service, and the `id` and `retry` fields are ignored.
+#[derive(Debug, Default)]
+pub struct SseParser {
+    pending: Vec<u8>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl SseParser {
+    /// Feed the next chunk of the stream, returning the messages it
+    /// completed.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseMessage> {
+        self.pending.extend_from_slice(chunk);
+        let mut messages = Vec::new();
+        while let Some(end) = self.pending.iter().position(|&byte| byte == b'\n') {
+            let line: Vec<u8> = self.pending.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                let event = self.event.take();
+                if !self.data.is_empty() {
+                    messages.push(SseMessage {
+                        event: event.unwrap_or_else(|| "message".to_string()),
+                        data: self.data.join("\n"),
+                    });
+                    self.data.clear();
+                }
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+            let (field, value) = match line.split_once(':') {
+                Some((field, value)) => (field, value.strip_prefix(' ').unwrap_or(value)),
+                None => (line, ""),
+            };
+            match field {
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+        }
+        messages
+    }
+}
+
+/// What a followed stream reports.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MonitorUpdate {
+    /// An event summary passing the filters.
+    Event(StreamedEvent),
+    /// The service skipped summaries because the monitor fell behind.
+    Lagged { missed: u64 },
+    /// The stream dropped and is reopened after `delay`.
+    Reconnecting { delay: Duration, reason: String },
+}
+
+/// The update a stream message reports under `filter`, if any.
+///
+/// Summaries the filter rejects, unknown messages and summaries that fail
+/// to decode report nothing.
+pub fn decode_message(message: &SseMessage, filter: &MonitorFilter) -> Option<MonitorUpdate> {
+    match message.event.as_str() {
+        "event" => match serde_json::from_str::<StreamedEvent>(&message.data) {
+            Ok(event) if filter.matches(&event) => Some(MonitorUpdate::Event(event)),
+            Ok(_) => None,
+            Err(e) => {
+                debug!(error = %e, "Skipping undecodable event summary");
+                None
+            }
+        },
+        "lagged" => message
+            .data
+            .trim()
+            .parse()
+            .ok()
+            .map(|missed| MonitorUpdate::Lagged { missed }),
+        _ => None,
+    }
+}
+
+// ============================================================================
+// Service Client
+// ============================================================================
+
+/// Follows the event stream of a Queue-Keeper service.
+pub struct ApiMonitor {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl ApiMonitor {
+    /// Create a client for the service at `base_url`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Authenticate with this admin API key.
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// Report every update of the stream matching `filter` to `on_update`.
+    ///
+    /// Runs until interrupted. A stream that drops is reopened, but a
+    /// stream that cannot be opened in the first place is an error.
+    pub async fn follow(
+        &self,
+        filter: &MonitorFilter,
+        mut on_update: impl FnMut(MonitorUpdate),
+    ) -> Result<(), MonitorError> {
+        let mut connected = false;
+        let mut delay = MIN_RECONNECT_DELAY;
+        loop {
+            let reason = match self.open().await {
+                Ok(mut response) => {
+                    connected = true;
+                    delay = MIN_RECONNECT_DELAY;
+                    let mut parser = SseParser::default();
+                    loop {
+                        match response.chunk().await {
+                            Ok(Some(chunk)) => {
+                                for message in parser.feed(&chunk) {
+                                    if let Some(update) = decode_message(&message, filter) {
+                                        on_update(update);
+                                    }
+                                }
+                            }
+                            Ok(None) => break "closed by the service".to_string(),
+                            Err(e) => break e.to_string(),
+                        }
+                    }
+                }
+                Err(error @ MonitorError::Disabled { .. }) => return Err(error),
+                Err(error) if !connected => return Err(error),
+                Err(error) => error.to_string(),
+            };
+            warn!(reason = %reason, delay_secs = delay.as_secs(), "Event stream dropped");
+            on_update(MonitorUpdate::Reconnecting { delay, reason });
+            tokio::time::sleep(delay).await;
+            delay = (delay * 2).min(MAX_RECONNECT_DELAY);
+        }
+    }
+
+    /// Open `GET /api/events/stream`.
+    async fn open(&self) -> Result<reqwest::Response, MonitorError> {
+        let url = format!("{}/api/events/stream", self.base_url);
+        debug!(url = %url, "Opening event stream");
+        let mut request = self
+            .client
+            .get(&url)
+            .header(reqwest::header::ACCEPT, "text/event-stream");
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await.map_err(|e| MonitorError::Request {
+            message: format!("GET {}: {}", url, e),
+        })?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Err(MonitorError::Disabled {
+                endpoint: self.base_url.clone(),
+            });
+        }
+        if !status.is_success() {
+            return Err(MonitorError::Request {
+                message: format!("GET {}: HTTP {}", url, status.as_u16()),
+            });
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+#[path = "monitor_tests.rs"]
+mod tests;
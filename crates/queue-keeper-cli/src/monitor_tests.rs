@@ -0,0 +1,132 @@
+//! Tests for event monitoring.
+
+use super::*;
+use queue_keeper_api::event_stream::StreamedEventStatus;
+use queue_keeper_core::{EventId, Timestamp};
+
+fn streamed(status: StreamedEventStatus) -> StreamedEvent {
+    StreamedEvent {
+        event_id: Some(EventId::new()),
+        provider: "github".to_string(),
+        event_type: "pull_request".to_string(),
+        action: Some("opened".to_string()),
+        repository: Some("Owner/Repo".to_string()),
+        session_id: None,
+        status,
+        bot_name: None,
+        queue_name: None,
+        message: None,
+        timestamp: Timestamp::now(),
+    }
+}
+
+fn message(event: &str, data: &str) -> SseMessage {
+    SseMessage {
+        event: event.to_string(),
+        data: data.to_string(),
+    }
+}
+
+/// Messages split across chunks are reassembled; comments are skipped.
+#[test]
+fn test_parser_reassembles_chunks() {
+    let mut parser = SseParser::default();
+    assert!(parser.feed(b": keep-alive\n\nevent: ev").is_empty());
+    assert!(parser.feed(b"ent\ndata: {\"a\":").is_empty());
+
+    let messages = parser.feed(b"1}\n\ndata: plain\r\n\r\n");
+    assert_eq!(
+        messages,
+        vec![message("event", "{\"a\":1}"), message("message", "plain")]
+    );
+}
+
+/// Data lines of one message are joined; a message without data is dropped.
+#[test]
+fn test_parser_joins_data_lines() {
+    let mut parser = SseParser::default();
+    let messages = parser.feed(b"event: empty\n\nevent: lagged\nid: 7\ndata: 1\ndata:2\n\n");
+    assert_eq!(messages, vec![message("lagged", "1\n2")]);
+}
+
+/// Every filter must match; event types may name an action.
+#[test]
+fn test_filter_matches() {
+    let delivered = streamed(StreamedEventStatus::Delivered);
+    let failed = streamed(StreamedEventStatus::DeliveryFailed);
+    assert!(MonitorFilter::default().matches(&delivered));
+
+    let errors_only = MonitorFilter {
+        errors_only: true,
+        ..MonitorFilter::default()
+    };
+    assert!(!errors_only.matches(&delivered));
+    assert!(errors_only.matches(&failed));
+
+    for (event_type, expected) in [
+        ("pull_request", true),
+        ("pull_request.opened", true),
+        ("pull_request.closed", false),
+        ("issues", false),
+    ] {
+        let filter = MonitorFilter {
+            event_type: Some(event_type.to_string()),
+            ..MonitorFilter::default()
+        };
+        assert_eq!(filter.matches(&delivered), expected, "{}", event_type);
+    }
+
+    let repository = MonitorFilter {
+        repository: Some("owner/repo".to_string()),
+        ..MonitorFilter::default()
+    };
+    assert!(repository.matches(&delivered));
+    let mut unnamed = delivered.clone();
+    unnamed.repository = None;
+    assert!(!repository.matches(&unnamed));
+}
+
+/// Stream messages decode to updates; filtered and unknown ones to nothing.
+#[test]
+fn test_decode_message() {
+    let failed = streamed(StreamedEventStatus::DeliveryFailed);
+    let data = serde_json::to_string(&failed).unwrap();
+    let filter = MonitorFilter::default();
+
+    assert_eq!(
+        decode_message(&message("event", &data), &filter),
+        Some(MonitorUpdate::Event(failed))
+    );
+    assert_eq!(
+        decode_message(&message("lagged", "12"), &filter),
+        Some(MonitorUpdate::Lagged { missed: 12 })
+    );
+    assert_eq!(decode_message(&message("event", "not json"), &filter), None);
+    assert_eq!(decode_message(&message("message", &data), &filter), None);
+
+    let other_type = MonitorFilter {
+        event_type: Some("issues".to_string()),
+        ..MonitorFilter::default()
+    };
+    assert_eq!(decode_message(&message("event", &data), &other_type), None);
+}
+
+/// A line names the status, type, repository, bot and failure.
+#[test]
+fn test_event_line() {
+    let mut failed = streamed(StreamedEventStatus::DeliveryFailed);
+    failed.bot_name = Some("labeler".to_string());
+    failed.message = Some("queue unavailable".to_string());
+
+    let line = event_line(&failed);
+    assert!(line.starts_with(&failed.timestamp.to_rfc3339()));
+    assert!(line.contains(" delivery_failed "));
+    assert!(line.contains(" pull_request.opened "));
+    assert!(line.contains(" Owner/Repo "));
+    assert!(line.ends_with(" bot=labeler (queue unavailable)"));
+
+    let mut rejected = streamed(StreamedEventStatus::Rejected);
+    rejected.event_id = None;
+    rejected.repository = None;
+    assert!(event_line(&rejected).trim_end().ends_with(" -"));
+}
@@ -0,0 +1,407 @@
+//! # Service Process Management
+//!
+//! Implements `queue-keeper start` and `queue-keeper stop` for a service
+//! running on the local host.
+//!
+//! `start` launches the `queue-keeper-service` binary, detached with its
+//! output appended to a log file, or attached to the terminal with
+//! `--foreground`. The process is recorded in a PID file; a second `start`
+//! is refused while the recorded process is alive, and a PID file left
+//! behind by a process that no longer runs is treated as stale and replaced.
+//!
+//! `stop` asks the recorded process to shut down gracefully (`SIGTERM`),
+//! waits up to the timeout for it to exit and, with `--force`, kills it when
+//! it does not.
+//!
+//! The service binary is taken from `QUEUE_KEEPER_SERVICE_BIN`, else from
+//! the directory of the CLI executable, else from `PATH`.
+
+use crate::CliServiceError;
+use queue_keeper_core::Timestamp;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Name of the service binary.
+pub const SERVICE_BINARY: &str = "queue-keeper-service";
+
+/// Environment variable overriding the service binary location.
+pub const SERVICE_BINARY_ENV: &str = "QUEUE_KEEPER_SERVICE_BIN";
+
+/// Interval between two checks whether a stopping process has exited.
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Time a detached service is given to fail at startup before it is
+/// reported as started.
+const STARTUP_GRACE: Duration = Duration::from_millis(500);
+
+/// Time allowed for a killed process to disappear.
+const KILL_WAIT: Duration = Duration::from_secs(5);
+
+// ============================================================================
+// PID File
+// ============================================================================
+
+/// A service process started by the CLI, as recorded in the PID file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceProcess {
+    pub pid: u32,
+    pub started_at: Timestamp,
+    pub host: String,
+    pub port: u16,
+    /// Where the output of a detached service goes; `None` in the
+    /// foreground.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<PathBuf>,
+}
+
+/// The PID file recording the service process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PidFile {
+    path: PathBuf,
+}
+
+impl PidFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    /// `queue-keeper/queue-keeper.pid` in the runtime directory, else in the
+    /// local data directory, else in the temporary directory.
+    pub fn default_path() -> PathBuf {
+        dirs::runtime_dir()
+            .or_else(dirs::data_local_dir)
+            .unwrap_or_else(std::env::temp_dir)
+            .join("queue-keeper")
+            .join("queue-keeper.pid")
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Log file of a detached service: `queue-keeper.log` next to the PID
+    /// file.
+    pub fn log_path(&self) -> PathBuf {
+        self.path.with_file_name("queue-keeper.log")
+    }
+
+    /// The recorded process, alive or not; `None` without a PID file.
+    ///
+    /// # Errors
+    /// Returns an error when the PID file cannot be read or parsed.
+    pub fn read(&self) -> Result<Option<ServiceProcess>, CliServiceError> {
+        let content = match std::fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(self.error(e)),
+        };
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| self.error(e))
+    }
+
+    /// The recorded process if it is still running.
+    ///
+    /// A PID file whose process is gone is stale: it is removed and `None`
+    /// returned.
+    ///
+    /// # Errors
+    /// Returns an error when the PID file cannot be read or removed.
+    pub fn running(&self) -> Result<Option<ServiceProcess>, CliServiceError> {
+        match self.read()? {
+            Some(process) if is_running(process.pid) => Ok(Some(process)),
+            Some(process) => {
+                warn!(
+                    pid = process.pid,
+                    path = %self.path.display(),
+                    "Removing stale PID file"
+                );
+                self.remove()?;
+                Ok(None)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Record `process`, creating the parent directory if needed.
+    ///
+    /// # Errors
+    /// Returns an error when the PID file cannot be written.
+    pub fn write(&self, process: &ServiceProcess) -> Result<(), CliServiceError> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| self.error(e))?;
+        }
+        let content = serde_json::to_string_pretty(process).map_err(|e| self.error(e))?;
+        std::fs::write(&self.path, content).map_err(|e| self.error(e))
+    }
+
+    /// Remove the PID file; a missing file is not an error.
+    ///
+    /// # Errors
+    /// Returns an error when the PID file exists but cannot be removed.
+    pub fn remove(&self) -> Result<(), CliServiceError> {
+        match std::fs::remove_file(&self.path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(self.error(e)),
+            _ => Ok(()),
+        }
+    }
+
+    fn error(&self, error: impl std::fmt::Display) -> CliServiceError {
+        CliServiceError::PidFile {
+            path: self.path.clone(),
+            message: error.to_string(),
+        }
+    }
+}
+
+// ============================================================================
+// Process Control
+// ============================================================================
+
+/// Whether a process with `pid` exists.
+#[cfg(unix)]
+pub fn is_running(pid: u32) -> bool {
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Whether a process with `pid` exists.
+#[cfg(windows)]
+pub fn is_running(pid: u32) -> bool {
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .stderr(Stdio::null())
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+}
+
+/// Ask `pid` to shut down, or kill it when `kill` is set.
+#[cfg(unix)]
+fn signal(pid: u32, kill: bool) -> std::io::Result<bool> {
+    let signal = if kill { "-KILL" } else { "-TERM" };
+    Command::new("kill")
+        .args([signal, &pid.to_string()])
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+}
+
+/// Ask `pid` to shut down, or kill it when `kill` is set.
+#[cfg(windows)]
+fn signal(pid: u32, kill: bool) -> std::io::Result<bool> {
+    let pid = pid.to_string();
+    let mut args = vec!["/PID", pid.as_str()];
+    if kill {
+        args.push("/F");
+    }
+    Command::new("taskkill")
+        .args(args)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+}
+
+/// Wait up to `timeout` for `pid` to exit; `true` when it did.
+async fn wait_for_exit(pid: u32, timeout: Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    while is_running(pid) {
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+    true
+}
+
+/// Location of the service binary.
+pub fn service_binary() -> PathBuf {
+    if let Some(path) = std::env::var_os(SERVICE_BINARY_ENV) {
+        return PathBuf::from(path);
+    }
+    let sibling = std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join(SERVICE_BINARY)))
+        .map(|path| path.with_extension(std::env::consts::EXE_EXTENSION));
+    match sibling {
+        Some(path) if path.is_file() => path,
+        _ => PathBuf::from(SERVICE_BINARY),
+    }
+}
+
+// ============================================================================
+// Start and Stop
+// ============================================================================
+
+/// How `queue-keeper start` launches the service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StartOptions {
+    /// The service binary.
+    pub binary: PathBuf,
+    pub host: String,
+    pub port: u16,
+    /// Service configuration file, passed on as `--config`.
+    pub config_file: Option<PathBuf>,
+    /// Stay attached to the terminal until the service exits.
+    pub foreground: bool,
+}
+
+impl StartOptions {
+    /// The command running the service; host and port are set through the
+    /// `QK__SERVER__*` configuration overrides.
+    fn command(&self) -> tokio::process::Command {
+        let mut command = tokio::process::Command::new(&self.binary);
+        if let Some(config_file) = &self.config_file {
+            command.arg("--config").arg(config_file);
+        }
+        command
+            .env("QK__SERVER__HOST", &self.host)
+            .env("QK__SERVER__PORT", self.port.to_string());
+        command
+    }
+}
+
+/// Start the service and record it in `pid_file`.
+///
+/// A detached service is returned once it survived its first moments; a
+/// foreground service is waited for, and its PID file removed when it
+/// exits.
+///
+/// # Errors
+/// Returns [`CliServiceError::AlreadyRunning`] while the recorded process is
+/// alive, and [`CliServiceError::StartFailed`] when the service cannot be
+/// launched, exits during startup or, in the foreground, exits with an
+/// error.
+pub async fn start(
+    pid_file: &PidFile,
+    options: &StartOptions,
+) -> Result<ServiceProcess, CliServiceError> {
+    if let Some(process) = pid_file.running()? {
+        return Err(CliServiceError::AlreadyRunning { pid: process.pid });
+    }
+
+    let mut command = options.command();
+    let log_file = if options.foreground {
+        None
+    } else {
+        let log_path = pid_file.log_path();
+        if let Some(parent) = log_path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| start_failed(&log_path, e))?;
+        }
+        let log = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_path)
+            .map_err(|e| start_failed(&log_path, e))?;
+        let err_log = log.try_clone().map_err(|e| start_failed(&log_path, e))?;
+        command
+            .stdin(Stdio::null())
+            .stdout(Stdio::from(log))
+            .stderr(Stdio::from(err_log));
+        // Keep the service out of the terminal's process group, so Ctrl-C
+        // in the shell that started it does not stop it.
+        #[cfg(unix)]
+        command.process_group(0);
+        Some(log_path)
+    };
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| start_failed(&options.binary, e))?;
+    let pid = child.id().ok_or_else(|| CliServiceError::StartFailed {
+        message: "service exited before its PID was known".to_string(),
+    })?;
+    let process = ServiceProcess {
+        pid,
+        started_at: Timestamp::now(),
+        host: options.host.clone(),
+        port: options.port,
+        log_file,
+    };
+    pid_file.write(&process)?;
+    debug!(pid, path = %pid_file.path().display(), "Recorded service process");
+
+    if options.foreground {
+        let status = child.wait().await;
+        pid_file.remove()?;
+        let status = status.map_err(|e| start_failed(&options.binary, e))?;
+        if !status.success() {
+            return Err(CliServiceError::StartFailed {
+                message: format!("service exited with {}", status),
+            });
+        }
+        return Ok(process);
+    }
+
+    tokio::time::sleep(STARTUP_GRACE).await;
+    if let Ok(Some(status)) = child.try_wait() {
+        pid_file.remove()?;
+        return Err(CliServiceError::StartFailed {
+            message: format!(
+                "service exited during startup with {}; see {}",
+                status,
+                pid_file.log_path().display()
+            ),
+        });
+    }
+    Ok(process)
+}
+
+fn start_failed(path: &Path, error: impl std::fmt::Display) -> CliServiceError {
+    CliServiceError::StartFailed {
+        message: format!("{}: {}", path.display(), error),
+    }
+}
+
+/// Stop the process recorded in `pid_file` and remove the PID file.
+///
+/// The process is asked to shut down and given `timeout` to exit; with
+/// `force` it is then killed.
+///
+/// # Errors
+/// Returns [`CliServiceError::NotRunning`] without a live recorded process,
+/// [`CliServiceError::Timeout`] when it outlives `timeout` without `force`,
+/// and [`CliServiceError::StopFailed`] when it cannot be signalled or
+/// survives being killed.
+pub async fn stop(
+    pid_file: &PidFile,
+    timeout: Duration,
+    force: bool,
+) -> Result<ServiceProcess, CliServiceError> {
+    let process = pid_file.running()?.ok_or(CliServiceError::NotRunning)?;
+    let pid = process.pid;
+
+    let signalled = signal(pid, false).map_err(|e| CliServiceError::StopFailed {
+        message: format!("cannot signal PID {}: {}", pid, e),
+    })?;
+    if signalled && wait_for_exit(pid, timeout).await {
+        pid_file.remove()?;
+        return Ok(process);
+    }
+    if !force {
+        return Err(CliServiceError::Timeout {
+            seconds: timeout.as_secs(),
+        });
+    }
+
+    warn!(pid, "Service did not stop in time; killing it");
+    let _ = signal(pid, true);
+    if !wait_for_exit(pid, KILL_WAIT).await {
+        return Err(CliServiceError::StopFailed {
+            message: format!("PID {} survived being killed", pid),
+        });
+    }
+    pid_file.remove()?;
+    Ok(process)
+}
+
+#[cfg(test)]
+#[path = "process_tests.rs"]
+mod tests;
@@ -0,0 +1,163 @@
+//! Tests for service process management.
+
+use super::*;
+
+fn process(pid: u32) -> ServiceProcess {
+    ServiceProcess {
+        pid,
+        started_at: Timestamp::now(),
+        host: "127.0.0.1".to_string(),
+        port: 8080,
+        log_file: None,
+    }
+}
+
+/// A PID no process uses on any test host.
+const DEAD_PID: u32 = 999_999_999;
+
+/// A written record reads back unchanged, and the parent directory is
+/// created on the way.
+#[test]
+fn test_pid_file_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = PidFile::new(dir.path().join("run").join("queue-keeper.pid"));
+    assert_eq!(pid_file.read().unwrap(), None);
+
+    let recorded = process(std::process::id());
+    pid_file.write(&recorded).unwrap();
+    assert_eq!(pid_file.read().unwrap(), Some(recorded.clone()));
+    assert_eq!(pid_file.running().unwrap(), Some(recorded));
+
+    pid_file.remove().unwrap();
+    pid_file.remove().unwrap();
+    assert_eq!(pid_file.read().unwrap(), None);
+}
+
+/// A PID file whose process is gone is removed instead of reported.
+#[test]
+fn test_stale_pid_file_is_removed() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = PidFile::new(dir.path().join("queue-keeper.pid"));
+    pid_file.write(&process(DEAD_PID)).unwrap();
+
+    assert_eq!(pid_file.running().unwrap(), None);
+    assert!(!pid_file.path().exists());
+}
+
+/// A corrupt PID file is an error naming the file.
+#[test]
+fn test_corrupt_pid_file_is_an_error() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = PidFile::new(dir.path().join("queue-keeper.pid"));
+    std::fs::write(pid_file.path(), "not json").unwrap();
+
+    let error = pid_file.read().unwrap_err();
+    assert!(matches!(error, CliServiceError::PidFile { ref path, .. } if path == pid_file.path()));
+}
+
+/// Starting is refused while the recorded process runs.
+#[tokio::test]
+async fn test_start_refuses_second_instance() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = PidFile::new(dir.path().join("queue-keeper.pid"));
+    pid_file.write(&process(std::process::id())).unwrap();
+    let options = StartOptions {
+        binary: dir.path().join("missing-service"),
+        host: "127.0.0.1".to_string(),
+        port: 8080,
+        config_file: None,
+        foreground: false,
+    };
+
+    let error = start(&pid_file, &options).await.unwrap_err();
+    assert!(matches!(error, CliServiceError::AlreadyRunning { pid } if pid == std::process::id()));
+}
+
+/// A binary that cannot be launched fails the start and leaves no PID file.
+#[tokio::test]
+async fn test_start_reports_missing_binary() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = PidFile::new(dir.path().join("queue-keeper.pid"));
+    let options = StartOptions {
+        binary: dir.path().join("missing-service"),
+        host: "127.0.0.1".to_string(),
+        port: 8080,
+        config_file: None,
+        foreground: false,
+    };
+
+    let error = start(&pid_file, &options).await.unwrap_err();
+    assert!(matches!(error, CliServiceError::StartFailed { .. }));
+    assert!(!pid_file.path().exists());
+}
+
+/// Stopping without a live recorded process reports that it is not running.
+#[tokio::test]
+async fn test_stop_without_service() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = PidFile::new(dir.path().join("queue-keeper.pid"));
+    pid_file.write(&process(DEAD_PID)).unwrap();
+
+    let error = stop(&pid_file, Duration::from_secs(1), false)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, CliServiceError::NotRunning));
+}
+
+/// A process that honours SIGTERM is stopped gracefully and its PID file
+/// removed.
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stop_terminates_process() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = PidFile::new(dir.path().join("queue-keeper.pid"));
+    let mut child = tokio::process::Command::new("sleep")
+        .arg("30")
+        .spawn()
+        .unwrap();
+    let pid = child.id().unwrap();
+    pid_file.write(&process(pid)).unwrap();
+    // Reap the child as soon as it exits, so it does not linger as a zombie
+    // that `kill -0` still reports.
+    let reaper = tokio::spawn(async move { child.wait().await });
+
+    let stopped = stop(&pid_file, Duration::from_secs(5), false)
+        .await
+        .unwrap();
+    assert_eq!(stopped.pid, pid);
+    assert!(!pid_file.path().exists());
+    assert!(!reaper.await.unwrap().unwrap().success());
+}
+
+/// A process ignoring SIGTERM times out, and is killed with `force`.
+#[cfg(unix)]
+#[tokio::test]
+async fn test_stop_forces_stubborn_process() {
+    let dir = tempfile::tempdir().unwrap();
+    let pid_file = PidFile::new(dir.path().join("queue-keeper.pid"));
+    let ready = dir.path().join("ready");
+    let mut child = tokio::process::Command::new("sh")
+        .args(["-c", "trap '' TERM; touch \"$0\"; sleep 30 & wait"])
+        .arg(&ready)
+        .spawn()
+        .unwrap();
+    let pid = child.id().unwrap();
+    pid_file.write(&process(pid)).unwrap();
+    let reaper = tokio::spawn(async move { child.wait().await });
+    // SIGTERM sent before the trap is installed would end the shell
+    while !ready.exists() {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+
+    let error = stop(&pid_file, Duration::from_millis(500), false)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, CliServiceError::Timeout { .. }));
+    assert!(pid_file.path().exists());
+
+    stop(&pid_file, Duration::from_millis(500), true)
+        .await
+        .unwrap();
+    assert!(!pid_file.path().exists());
+    assert!(!reaper.await.unwrap().unwrap().success());
+}
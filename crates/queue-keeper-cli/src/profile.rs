@@ -0,0 +1,293 @@
+//! # Connection Profiles
+//!
+//! Named connection settings for the environments a CLI user works with
+//! (for example `dev`, `staging` and `prod`), stored in
+//! `~/.config/queue-keeper/profiles.toml`:
+//!
+//! ```toml
+//! current = "staging"
+//!
+//! [profiles.staging]
+//! endpoint = "https://queue-keeper.staging.example"
+//! token = "..."
+//! output = "json"
+//! ```
+//!
+//! Commands that talk to a service resolve their [`Connection`] from, in
+//! order of precedence, the `--endpoint`/`--token` flags (or their
+//! environment variables), the profile selected with `--profile` or
+//! `queue-keeper profile use`, and the built-in defaults.
+
+use crate::OutputFormat;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Endpoint used when neither a flag nor a profile sets one.
+pub const DEFAULT_ENDPOINT: &str = "http://localhost:8080";
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors reading, writing or selecting profiles.
+#[derive(Debug, thiserror::Error)]
+pub enum ProfileError {
+    #[error("Profile not found: {name}")]
+    NotFound { name: String },
+
+    #[error("Invalid profile name '{name}': {message}")]
+    InvalidName { name: String, message: String },
+
+    #[error("Cannot read profiles file {path}: {message}")]
+    Read { path: PathBuf, message: String },
+
+    #[error("Cannot write profiles file {path}: {message}")]
+    Write { path: PathBuf, message: String },
+
+    #[error("Cannot locate the profiles file: no home directory")]
+    NoHomeDirectory,
+}
+
+// ============================================================================
+// Profile Types
+// ============================================================================
+
+/// Connection settings for one environment.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Queue-Keeper base URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+
+    /// Admin API token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// Output format for commands run without `--format`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<OutputFormat>,
+}
+
+/// The contents of the profiles file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileStore {
+    /// Profile used when `--profile` is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub current: Option<String>,
+
+    /// Profiles by name
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+impl ProfileStore {
+    /// Default location of the profiles file,
+    /// `~/.config/queue-keeper/profiles.toml`.
+    pub fn default_path() -> Result<PathBuf, ProfileError> {
+        dirs::home_dir()
+            .map(|home| {
+                home.join(".config")
+                    .join("queue-keeper")
+                    .join("profiles.toml")
+            })
+            .ok_or(ProfileError::NoHomeDirectory)
+    }
+
+    /// Read the profiles file at `path`; a missing file is an empty store.
+    pub fn load(path: &Path) -> Result<Self, ProfileError> {
+        let read_error = |message: String| ProfileError::Read {
+            path: path.to_path_buf(),
+            message,
+        };
+        match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| read_error(e.to_string())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(read_error(e.to_string())),
+        }
+    }
+
+    /// Write the store to `path`, creating its directory.
+    ///
+    /// The file holds tokens, so on Unix it is only readable by its owner.
+    pub fn save(&self, path: &Path) -> Result<(), ProfileError> {
+        let write_error = |message: String| ProfileError::Write {
+            path: path.to_path_buf(),
+            message,
+        };
+        let text = toml::to_string_pretty(self).map_err(|e| write_error(e.to_string()))?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir).map_err(|e| write_error(e.to_string()))?;
+        }
+        std::fs::write(path, text).map_err(|e| write_error(e.to_string()))?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| write_error(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Create or update profile `name`; only the given settings change.
+    pub fn set(&mut self, name: &str, update: Profile) -> Result<(), ProfileError> {
+        validate_name(name)?;
+        let profile = self.profiles.entry(name.to_string()).or_default();
+        if update.endpoint.is_some() {
+            profile.endpoint = update.endpoint;
+        }
+        if update.token.is_some() {
+            profile.token = update.token;
+        }
+        if update.output.is_some() {
+            profile.output = update.output;
+        }
+        Ok(())
+    }
+
+    /// Make `name` the current profile.
+    pub fn use_profile(&mut self, name: &str) -> Result<(), ProfileError> {
+        if !self.profiles.contains_key(name) {
+            return Err(ProfileError::NotFound {
+                name: name.to_string(),
+            });
+        }
+        self.current = Some(name.to_string());
+        Ok(())
+    }
+
+    /// Remove profile `name`, clearing it as the current profile.
+    pub fn remove(&mut self, name: &str) -> Result<Profile, ProfileError> {
+        let profile = self
+            .profiles
+            .remove(name)
+            .ok_or_else(|| ProfileError::NotFound {
+                name: name.to_string(),
+            })?;
+        if self.current.as_deref() == Some(name) {
+            self.current = None;
+        }
+        Ok(profile)
+    }
+
+    /// The profile named `selected`, or the current profile when `None`.
+    ///
+    /// Returns `Ok(None)` when no profile is selected.
+    pub fn selected(&self, selected: Option<&str>) -> Result<Option<&Profile>, ProfileError> {
+        match selected.or(self.current.as_deref()) {
+            Some(name) => self
+                .profiles
+                .get(name)
+                .map(Some)
+                .ok_or_else(|| ProfileError::NotFound {
+                    name: name.to_string(),
+                }),
+            None => Ok(None),
+        }
+    }
+
+    /// Render the profiles as a table, marking the current one. Tokens are
+    /// never shown.
+    pub fn to_table(&self) -> String {
+        let mut out = format!(
+            "  {:<16} {:<40} {:<6} {}\n",
+            "NAME", "ENDPOINT", "TOKEN", "OUTPUT"
+        );
+        for (name, profile) in &self.profiles {
+            let marker = if self.current.as_deref() == Some(name) {
+                '*'
+            } else {
+                ' '
+            };
+            out.push_str(&format!(
+                "{} {:<16} {:<40} {:<6} {}\n",
+                marker,
+                name,
+                profile.endpoint.as_deref().unwrap_or("-"),
+                if profile.token.is_some() { "set" } else { "-" },
+                profile
+                    .output
+                    .as_ref()
+                    .and_then(|o| o.to_possible_value())
+                    .map(|v| v.get_name().to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ));
+        }
+        out
+    }
+}
+
+/// Profile names are used on the command line; keep them to `[A-Za-z0-9_-]`.
+fn validate_name(name: &str) -> Result<(), ProfileError> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(ProfileError::InvalidName {
+            name: name.to_string(),
+            message: "use letters, digits, '-' and '_'".to_string(),
+        });
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Connection Resolution
+// ============================================================================
+
+/// Service connection settings a command runs with.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    /// Queue-Keeper base URL
+    pub endpoint: String,
+
+    /// Admin API token
+    pub token: Option<String>,
+
+    /// Output format for commands run without `--format`
+    pub output: Option<OutputFormat>,
+}
+
+impl Connection {
+    /// Resolve the connection from flag values and the selected profile.
+    ///
+    /// `endpoint` and `token` are the flag (or environment) values; when
+    /// absent the profile's settings apply, then the defaults.
+    pub fn resolve(
+        endpoint: Option<String>,
+        token: Option<String>,
+        profile: Option<&Profile>,
+    ) -> Self {
+        let profile = profile.cloned().unwrap_or_default();
+        Self {
+            endpoint: endpoint
+                .or(profile.endpoint)
+                .unwrap_or_else(|| DEFAULT_ENDPOINT.to_string()),
+            token: token.or(profile.token),
+            output: profile.output,
+        }
+    }
+
+    /// `format` when given, then the profile's output format, then
+    /// `default`.
+    pub fn output_format(
+        &self,
+        format: Option<OutputFormat>,
+        default: OutputFormat,
+    ) -> OutputFormat {
+        format.or_else(|| self.output.clone()).unwrap_or(default)
+    }
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Self::resolve(None, None, None)
+    }
+}
+
+#[cfg(test)]
+#[path = "profile_tests.rs"]
+mod tests;
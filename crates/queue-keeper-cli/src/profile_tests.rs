@@ -0,0 +1,145 @@
+//! Tests for connection profiles.
+
+use super::*;
+
+fn profile(endpoint: &str, token: Option<&str>, output: Option<OutputFormat>) -> Profile {
+    Profile {
+        endpoint: Some(endpoint.to_string()),
+        token: token.map(str::to_string),
+        output,
+    }
+}
+
+/// A store survives a save and load, and a missing file is an empty store.
+#[test]
+fn test_store_round_trip() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("queue-keeper").join("profiles.toml");
+    assert_eq!(ProfileStore::load(&path).unwrap(), ProfileStore::default());
+
+    let mut store = ProfileStore::default();
+    store
+        .set(
+            "prod",
+            profile(
+                "https://qk.example",
+                Some("secret"),
+                Some(OutputFormat::Json),
+            ),
+        )
+        .unwrap();
+    store.use_profile("prod").unwrap();
+    store.save(&path).unwrap();
+
+    let text = std::fs::read_to_string(&path).unwrap();
+    assert!(text.contains("current = \"prod\""));
+    assert!(text.contains("output = \"json\""));
+    assert_eq!(ProfileStore::load(&path).unwrap(), store);
+}
+
+/// Updating a profile keeps the settings that were not given.
+#[test]
+fn test_set_updates_given_settings_only() {
+    let mut store = ProfileStore::default();
+    store
+        .set("dev", profile("http://localhost:8080", Some("t1"), None))
+        .unwrap();
+    store
+        .set(
+            "dev",
+            Profile {
+                output: Some(OutputFormat::Yaml),
+                ..Profile::default()
+            },
+        )
+        .unwrap();
+
+    assert_eq!(
+        store.profiles["dev"],
+        profile(
+            "http://localhost:8080",
+            Some("t1"),
+            Some(OutputFormat::Yaml)
+        )
+    );
+    assert!(matches!(
+        store.set("bad name", Profile::default()),
+        Err(ProfileError::InvalidName { .. })
+    ));
+}
+
+/// Only existing profiles can be used or removed; removing the current
+/// profile clears it.
+#[test]
+fn test_use_and_remove() {
+    let mut store = ProfileStore::default();
+    assert!(matches!(
+        store.use_profile("staging"),
+        Err(ProfileError::NotFound { .. })
+    ));
+
+    store
+        .set("staging", profile("https://staging", None, None))
+        .unwrap();
+    store.use_profile("staging").unwrap();
+    assert_eq!(
+        store.selected(None).unwrap().unwrap().endpoint.as_deref(),
+        Some("https://staging")
+    );
+    assert!(matches!(
+        store.selected(Some("prod")),
+        Err(ProfileError::NotFound { .. })
+    ));
+
+    store.remove("staging").unwrap();
+    assert_eq!(store.current, None);
+    assert_eq!(store.selected(None).unwrap(), None);
+}
+
+/// Flags override the profile, which overrides the defaults.
+#[test]
+fn test_connection_precedence() {
+    let prod = profile("https://prod", Some("prod-token"), Some(OutputFormat::Json));
+
+    let connection = Connection::resolve(None, None, Some(&prod));
+    assert_eq!(connection.endpoint, "https://prod");
+    assert_eq!(connection.token.as_deref(), Some("prod-token"));
+    assert_eq!(
+        connection.output_format(None, OutputFormat::Table),
+        OutputFormat::Json
+    );
+    assert_eq!(
+        connection.output_format(Some(OutputFormat::Yaml), OutputFormat::Table),
+        OutputFormat::Yaml
+    );
+
+    let connection = Connection::resolve(
+        Some("http://override".to_string()),
+        Some("flag-token".to_string()),
+        Some(&prod),
+    );
+    assert_eq!(connection.endpoint, "http://override");
+    assert_eq!(connection.token.as_deref(), Some("flag-token"));
+
+    let connection = Connection::default();
+    assert_eq!(connection.endpoint, DEFAULT_ENDPOINT);
+    assert_eq!(connection.token, None);
+    assert_eq!(
+        connection.output_format(None, OutputFormat::Table),
+        OutputFormat::Table
+    );
+}
+
+/// The table marks the current profile and hides tokens.
+#[test]
+fn test_table_hides_tokens() {
+    let mut store = ProfileStore::default();
+    store
+        .set("prod", profile("https://prod", Some("prod-token"), None))
+        .unwrap();
+    store.use_profile("prod").unwrap();
+
+    let table = store.to_table();
+    assert!(table.contains("* prod"));
+    assert!(!table.contains("prod-token"));
+}
@@ -0,0 +1,152 @@
+//! # Pipeline Self-Test
+//!
+//! Implements `queue-keeper selftest`, which asks a running service to push
+//! a signed synthetic event through its whole pipeline (`POST
+//! /admin/selftest`) and prints the per-stage result. Intended for
+//! post-deploy verification: the command exits non-zero when any stage
+//! fails.
+//!
+//! Stage names and statuses are kept as strings so that a CLI built against
+//! an older service still prints stages it does not know about.
+
+use queue_keeper_core::Timestamp;
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors that prevent a self-test report from being obtained.
+#[derive(Debug, thiserror::Error)]
+pub enum SelfTestError {
+    #[error("Self-test request failed: {message}")]
+    Request { message: String },
+}
+
+// ============================================================================
+// Report Types
+// ============================================================================
+
+/// Result of a single pipeline stage.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfTestStageResult {
+    /// Stage name, e.g. `signature_validation`.
+    pub stage: String,
+    /// `passed`, `failed` or `skipped`.
+    pub status: String,
+    /// Time spent in the stage, in milliseconds.
+    pub duration_ms: u64,
+    /// Failure reason, skip reason or confirmation.
+    pub message: String,
+}
+
+impl SelfTestStageResult {
+    /// Whether the stage failed.
+    pub fn is_failed(&self) -> bool {
+        self.status == "failed"
+    }
+}
+
+/// Self-test report returned by `POST /admin/selftest`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SelfTestReport {
+    /// `true` when no stage failed.
+    pub passed: bool,
+    /// When the run started.
+    pub started_at: Timestamp,
+    /// Total run time, in milliseconds.
+    pub duration_ms: u64,
+    /// Per-stage results, in pipeline order.
+    pub stages: Vec<SelfTestStageResult>,
+}
+
+impl SelfTestReport {
+    /// Number of failed stages.
+    pub fn failed_stages(&self) -> usize {
+        self.stages.iter().filter(|s| s.is_failed()).count()
+    }
+
+    /// Render the report as a human-readable table.
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "{:<22} {:<8} {:>8}  {}\n",
+            "STAGE", "STATUS", "MS", "DETAIL"
+        );
+        for stage in &self.stages {
+            out.push_str(&format!(
+                "{:<22} {:<8} {:>8}  {}\n",
+                stage.stage,
+                stage.status.to_uppercase(),
+                stage.duration_ms,
+                stage.message
+            ));
+        }
+        out.push_str(&format!(
+            "\nSelf-test {} in {}ms\n",
+            if self.passed { "passed" } else { "FAILED" },
+            self.duration_ms
+        ));
+        out
+    }
+}
+
+// ============================================================================
+// Service Client
+// ============================================================================
+
+/// Runs the self-test on a Queue-Keeper service through its admin API.
+pub struct ApiSelfTest {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl ApiSelfTest {
+    /// Create a client for the service at `base_url`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Authenticate with this admin API key.
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// Run the self-test and return the service's report.
+    ///
+    /// A failing self-test is still a report (the service answers 503 with
+    /// the report body); only transport, authentication and decoding
+    /// problems are errors.
+    pub async fn run(&self) -> Result<SelfTestReport, SelfTestError> {
+        let url = format!("{}/admin/selftest", self.base_url);
+        debug!(url = %url, "Requesting pipeline self-test");
+
+        let mut request = self.client.post(&url);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await.map_err(|e| SelfTestError::Request {
+            message: format!("POST {}: {}", url, e),
+        })?;
+
+        let status = response.status();
+        if !status.is_success() && status != reqwest::StatusCode::SERVICE_UNAVAILABLE {
+            return Err(SelfTestError::Request {
+                message: format!("POST {}: HTTP {}", url, status.as_u16()),
+            });
+        }
+        response.json().await.map_err(|e| SelfTestError::Request {
+            message: format!("POST {}: invalid response body: {}", url, e),
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "selftest_tests.rs"]
+mod tests;
@@ -0,0 +1,58 @@
+//! Tests for the self-test command.
+
+use super::*;
+
+fn report_json(passed: bool, consumption_status: &str) -> String {
+    serde_json::json!({
+        "passed": passed,
+        "started_at": "2026-01-01T00:00:00Z",
+        "duration_ms": 42,
+        "stages": [
+            {
+                "stage": "signature_validation",
+                "status": "passed",
+                "duration_ms": 1,
+                "message": "signed payload accepted, tampered payload rejected"
+            },
+            {
+                "stage": "consumption",
+                "status": consumption_status,
+                "duration_ms": 40,
+                "message": "message not received within 10s"
+            }
+        ]
+    })
+    .to_string()
+}
+
+/// A service report deserializes and counts its failed stages.
+#[test]
+fn test_report_parses_service_response() {
+    let report: SelfTestReport = serde_json::from_str(&report_json(false, "failed")).unwrap();
+
+    assert!(!report.passed);
+    assert_eq!(report.stages.len(), 2);
+    assert_eq!(report.failed_stages(), 1);
+    assert!(report.stages[1].is_failed());
+}
+
+/// The text rendering lists every stage and the overall verdict.
+#[test]
+fn test_to_text_lists_stages_and_verdict() {
+    let passed: SelfTestReport = serde_json::from_str(&report_json(true, "skipped")).unwrap();
+    let text = passed.to_text();
+    assert!(text.contains("signature_validation"));
+    assert!(text.contains("SKIPPED"));
+    assert!(text.contains("Self-test passed in 42ms"));
+
+    let failed: SelfTestReport = serde_json::from_str(&report_json(false, "failed")).unwrap();
+    assert!(failed.to_text().contains("Self-test FAILED"));
+}
+
+/// An unreachable service is a request error, not a failed report.
+#[tokio::test]
+async fn test_unreachable_service_is_request_error() {
+    let result = ApiSelfTest::new("http://127.0.0.1:1/").run().await;
+
+    assert!(matches!(result, Err(SelfTestError::Request { .. })));
+}
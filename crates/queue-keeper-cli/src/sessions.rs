@@ -0,0 +1,414 @@
+//! # Session Inspection
+//!
+//! Implements `queue-keeper sessions list` and `queue-keeper sessions show`,
+//! which read the session tracker of a running service through
+//! `GET /api/sessions` and `GET /api/sessions/{id}`.
+//!
+//! The service tracks the sessions it has seen since it started: their event
+//! counts, pending events, last activity and status (`active`, `failed` or
+//! `idle`). Sessions not seen since the last restart are still shown by
+//! `sessions show` when their events are stored, but are not listed.
+//!
+//! `sessions pause`, `sessions resume` and `sessions reset` go through the
+//! admin API (`POST /admin/sessions/{id}/pause`, `.../resume` and
+//! `.../reset`) and need an admin API key. A paused session's events are
+//! buffered by the service and delivered in order when it resumes.
+
+use queue_keeper_core::{
+    session_control::{SessionPauseState, SessionPauseStatus},
+    EventId, SessionId, Timestamp,
+};
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+/// Page size used when listing sessions from the service.
+const PAGE_SIZE: usize = 500;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors reading sessions from the service.
+#[derive(Debug, thiserror::Error)]
+pub enum SessionsError {
+    #[error("Session not found: {session_id}")]
+    NotFound { session_id: String },
+
+    #[error("Session is not paused: {session_id}")]
+    NotPaused { session_id: String },
+
+    #[error("Sessions request failed: {message}")]
+    Request { message: String },
+}
+
+// ============================================================================
+// Session Types
+// ============================================================================
+
+/// A session as listed by `GET /api/sessions`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionSummary {
+    pub session_id: SessionId,
+    pub repository: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub status: String,
+    pub event_count: u32,
+    #[serde(default)]
+    pub pending_events: u64,
+    pub last_activity: Timestamp,
+}
+
+/// Page of sessions returned by `GET /api/sessions`.
+#[derive(Debug, Deserialize)]
+struct SessionPage {
+    sessions: Vec<SessionSummary>,
+    #[serde(default)]
+    next_cursor: Option<String>,
+}
+
+/// The repository fields of a session shown by the CLI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionRepository {
+    pub full_name: String,
+}
+
+/// An event of a session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionEvent {
+    pub event_id: EventId,
+    pub event_type: String,
+    pub occurred_at: Timestamp,
+    pub status: String,
+}
+
+/// A session as returned by `GET /api/sessions/{id}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionDetails {
+    pub session_id: SessionId,
+    pub repository: SessionRepository,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub status: String,
+    pub created_at: Timestamp,
+    pub last_activity: Timestamp,
+    pub event_count: u32,
+    #[serde(default)]
+    pub pending_events: u64,
+    pub events: Vec<SessionEvent>,
+}
+
+/// Body of `GET /api/sessions/{id}`.
+#[derive(Debug, Deserialize)]
+struct SessionDetailBody {
+    session: SessionDetails,
+}
+
+impl SessionDetails {
+    /// Render the session as human-readable text, with its events when
+    /// `with_events` is set.
+    pub fn to_text(&self, with_events: bool) -> String {
+        let mut out = String::new();
+        for (label, value) in [
+            ("Session", self.session_id.to_string()),
+            ("Repository", self.repository.full_name.clone()),
+            ("Entity", format!("{} {}", self.entity_type, self.entity_id)),
+            ("Status", self.status.clone()),
+            ("Events", self.event_count.to_string()),
+            ("Pending", self.pending_events.to_string()),
+            ("Created", self.created_at.to_rfc3339()),
+            ("Last activity", self.last_activity.to_rfc3339()),
+        ] {
+            out.push_str(&format!("{:<14} {}\n", format!("{}:", label), value));
+        }
+
+        if with_events {
+            out.push_str(&format!(
+                "\n{:<26} {:<24} {:<10} {}\n",
+                "EVENT", "TYPE", "STATUS", "OCCURRED"
+            ));
+            for event in &self.events {
+                out.push_str(&format!(
+                    "{:<26} {:<24} {:<10} {}\n",
+                    event.event_id,
+                    event.event_type,
+                    event.status,
+                    event.occurred_at.to_rfc3339()
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Result of `POST /admin/sessions/{id}/reset`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionResetResult {
+    pub session_id: SessionId,
+    pub status: String,
+    pub message: String,
+    pub previous_epoch: u64,
+    pub session_epoch: u64,
+    pub cancelled_deliveries: usize,
+}
+
+/// Render the pause state of a session as a human-readable summary.
+pub fn pause_status_text(status: &SessionPauseStatus) -> String {
+    let state = match status.state {
+        SessionPauseState::Paused => "paused",
+        SessionPauseState::Resuming => "resuming",
+    };
+    let mut out = format!(
+        "Session {} {} (paused by {} at {})\nReason: {}\nBuffered events: {}\n",
+        status.session_id,
+        state,
+        status.paused_by,
+        status.paused_at.to_rfc3339(),
+        status.reason,
+        status.buffered_events
+    );
+    if let Some(oldest) = &status.oldest_buffered_at {
+        out.push_str(&format!("Oldest buffered: {}\n", oldest.to_rfc3339()));
+    }
+    out
+}
+
+/// Render sessions as a human-readable table.
+pub fn sessions_table(sessions: &[SessionSummary]) -> String {
+    let mut out = format!(
+        "{:<48} {:<8} {:>7} {:>8}  {}\n",
+        "SESSION", "STATUS", "EVENTS", "PENDING", "LAST ACTIVITY"
+    );
+    for session in sessions {
+        out.push_str(&format!(
+            "{:<48} {:<8} {:>7} {:>8}  {}\n",
+            session.session_id,
+            session.status,
+            session.event_count,
+            session.pending_events,
+            session.last_activity.to_rfc3339()
+        ));
+    }
+    out.push_str(&format!("\n{} session(s)\n", sessions.len()));
+    out
+}
+
+// ============================================================================
+// Service Client
+// ============================================================================
+
+/// Filters of `queue-keeper sessions list`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionFilter {
+    /// Repository full name (`owner/repo`).
+    pub repository: Option<String>,
+    /// Entity type, e.g. `pull_request`.
+    pub entity_type: Option<String>,
+    /// Only sessions with events waiting for delivery.
+    pub pending_only: bool,
+}
+
+impl SessionFilter {
+    /// Query string of `GET /api/sessions` for this filter.
+    fn query(&self) -> String {
+        let mut query = format!("limit={}&include_total=false", PAGE_SIZE);
+        if let Some(repository) = &self.repository {
+            query.push_str(&format!("&repository={}", encode_component(repository)));
+        }
+        if let Some(entity_type) = &self.entity_type {
+            query.push_str(&format!("&entity_type={}", encode_component(entity_type)));
+        }
+        if self.pending_only {
+            query.push_str("&pending=true");
+        }
+        query
+    }
+}
+
+/// Percent-encode everything but unreserved characters, so session IDs can
+/// be used as a single path segment.
+pub(crate) fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Reads and controls sessions of a Queue-Keeper service through its API.
+pub struct ApiSessions {
+    client: reqwest::Client,
+    base_url: String,
+    api_key: Option<String>,
+}
+
+impl ApiSessions {
+    /// Create a client for the service at `base_url`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            api_key: None,
+        }
+    }
+
+    /// Authenticate admin requests with this API key.
+    pub fn with_api_key(mut self, api_key: Option<String>) -> Self {
+        self.api_key = api_key;
+        self
+    }
+
+    /// All tracked sessions matching `filter`, most recently active first.
+    pub async fn list(&self, filter: &SessionFilter) -> Result<Vec<SessionSummary>, SessionsError> {
+        let mut sessions = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut url = format!("{}/api/sessions?{}", self.base_url, filter.query());
+            if let Some(cursor) = &cursor {
+                url.push_str(&format!("&cursor={}", cursor));
+            }
+            let page: SessionPage =
+                self.get_json(&url)
+                    .await?
+                    .ok_or_else(|| SessionsError::Request {
+                        message: format!("GET {}: HTTP 404", url),
+                    })?;
+            let received = page.sessions.len();
+            sessions.extend(page.sessions);
+            debug!(received, listed = sessions.len(), "Listed session page");
+            match page.next_cursor {
+                Some(next) if received > 0 => cursor = Some(next),
+                _ => break,
+            }
+        }
+        Ok(sessions)
+    }
+
+    /// Details of one session.
+    pub async fn show(&self, session_id: &str) -> Result<SessionDetails, SessionsError> {
+        let url = format!(
+            "{}/api/sessions/{}",
+            self.base_url,
+            encode_component(session_id)
+        );
+        match self.get_json::<SessionDetailBody>(&url).await? {
+            Some(body) => Ok(body.session),
+            None => Err(SessionsError::NotFound {
+                session_id: session_id.to_string(),
+            }),
+        }
+    }
+
+    /// Pause deliveries of `session_id`, recording `reason`.
+    pub async fn pause(
+        &self,
+        session_id: &str,
+        reason: &str,
+    ) -> Result<SessionPauseStatus, SessionsError> {
+        self.post_admin(session_id, "pause", serde_json::json!({ "reason": reason }))
+            .await
+    }
+
+    /// Resume deliveries of the paused `session_id`; its buffered events are
+    /// released by the service in the background.
+    pub async fn resume(&self, session_id: &str) -> Result<SessionPauseStatus, SessionsError> {
+        self.post_admin(session_id, "resume", serde_json::json!({}))
+            .await
+    }
+
+    /// Reset `session_id`, recording `reason`.
+    pub async fn reset(
+        &self,
+        session_id: &str,
+        reason: &str,
+    ) -> Result<SessionResetResult, SessionsError> {
+        self.post_admin(session_id, "reset", serde_json::json!({ "reason": reason }))
+            .await
+    }
+
+    /// POST `body` to `/admin/sessions/{id}/{operation}` with the API key
+    /// and decode the JSON response.
+    async fn post_admin<T: for<'de> Deserialize<'de>>(
+        &self,
+        session_id: &str,
+        operation: &str,
+        body: serde_json::Value,
+    ) -> Result<T, SessionsError> {
+        let url = format!(
+            "{}/admin/sessions/{}/{}",
+            self.base_url,
+            encode_component(session_id),
+            operation
+        );
+        debug!(url = %url, "Sending session admin request");
+
+        let mut request = self.client.post(&url).json(&body);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await.map_err(|e| SessionsError::Request {
+            message: format!("POST {}: {}", url, e),
+        })?;
+
+        let status = response.status();
+        if status == reqwest::StatusCode::CONFLICT && operation == "resume" {
+            return Err(SessionsError::NotPaused {
+                session_id: session_id.to_string(),
+            });
+        }
+        if !status.is_success() {
+            let detail = response
+                .json::<serde_json::Value>()
+                .await
+                .ok()
+                .and_then(|body| body.get("message")?.as_str().map(str::to_string))
+                .map(|message| format!(": {}", message))
+                .unwrap_or_default();
+            return Err(SessionsError::Request {
+                message: format!("POST {}: HTTP {}{}", url, status.as_u16(), detail),
+            });
+        }
+        response.json().await.map_err(|e| SessionsError::Request {
+            message: format!("POST {}: invalid response body: {}", url, e),
+        })
+    }
+
+    /// GET `url` and decode its JSON body; `None` when the service answers 404.
+    async fn get_json<T: for<'de> Deserialize<'de>>(
+        &self,
+        url: &str,
+    ) -> Result<Option<T>, SessionsError> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| SessionsError::Request {
+                message: format!("GET {}: {}", url, e),
+            })?;
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(SessionsError::Request {
+                message: format!("GET {}: HTTP {}", url, status.as_u16()),
+            });
+        }
+        response
+            .json()
+            .await
+            .map(Some)
+            .map_err(|e| SessionsError::Request {
+                message: format!("GET {}: invalid response body: {}", url, e),
+            })
+    }
+}
+
+#[cfg(test)]
+#[path = "sessions_tests.rs"]
+mod tests;
@@ -0,0 +1,133 @@
+//! Tests for the session commands.
+
+use super::*;
+
+fn details_json() -> String {
+    serde_json::json!({
+        "session": {
+            "session_id": "owner/repo/pull_request/7",
+            "repository": {
+                "id": 0,
+                "name": "repo",
+                "full_name": "owner/repo",
+                "owner": { "id": 0, "login": "owner", "type": "User" },
+                "private": false
+            },
+            "entity_type": "pull_request",
+            "entity_id": "7",
+            "status": "failed",
+            "created_at": "2026-01-01T00:00:00Z",
+            "last_activity": "2026-01-01T00:05:00Z",
+            "event_count": 2,
+            "pending_events": 0,
+            "events": [
+                {
+                    "event_id": "01J00000000000000000000001",
+                    "event_type": "pull_request",
+                    "repository": "owner/repo",
+                    "session_id": "owner/repo/pull_request/7",
+                    "occurred_at": "2026-01-01T00:05:00Z",
+                    "status": "failed"
+                }
+            ]
+        }
+    })
+    .to_string()
+}
+
+/// A service response deserializes, ignoring fields the CLI does not show.
+#[test]
+fn test_details_parse_service_response() {
+    let body: SessionDetailBody = serde_json::from_str(&details_json()).unwrap();
+
+    assert_eq!(body.session.repository.full_name, "owner/repo");
+    assert_eq!(body.session.status, "failed");
+    assert_eq!(body.session.events.len(), 1);
+}
+
+/// The text rendering only lists events when asked to.
+#[test]
+fn test_details_text_lists_events_on_request() {
+    let details = serde_json::from_str::<SessionDetailBody>(&details_json())
+        .unwrap()
+        .session;
+
+    let without = details.to_text(false);
+    let with = details.to_text(true);
+
+    assert!(without.contains("owner/repo/pull_request/7"));
+    assert!(without.contains("failed"));
+    assert!(!without.contains("01J00000000000000000000001"));
+    assert!(with.contains("01J00000000000000000000001"));
+}
+
+/// The session table has one row per session and a count.
+#[test]
+fn test_sessions_table() {
+    let page: SessionPage = serde_json::from_value(serde_json::json!({
+        "sessions": [{
+            "session_id": "owner/repo/issue/3",
+            "repository": "owner/repo",
+            "entity_type": "issue",
+            "entity_id": "3",
+            "status": "active",
+            "event_count": 4,
+            "pending_events": 1,
+            "last_activity": "2026-01-01T00:00:00Z"
+        }]
+    }))
+    .unwrap();
+
+    let table = sessions_table(&page.sessions);
+
+    assert!(table.starts_with("SESSION"));
+    assert!(table.contains("owner/repo/issue/3"));
+    assert!(table.contains("1 session(s)"));
+    assert_eq!(page.next_cursor, None);
+}
+
+/// Filters become query parameters, with values percent-encoded.
+#[test]
+fn test_filter_query() {
+    let filter = SessionFilter {
+        repository: Some("owner/repo".to_string()),
+        entity_type: Some("pull_request".to_string()),
+        pending_only: true,
+    };
+
+    assert_eq!(
+        filter.query(),
+        "limit=500&include_total=false&repository=owner%2Frepo&entity_type=pull_request&pending=true"
+    );
+    assert_eq!(
+        SessionFilter::default().query(),
+        "limit=500&include_total=false"
+    );
+    assert_eq!(
+        encode_component("owner/repo/pull_request/7"),
+        "owner%2Frepo%2Fpull_request%2F7"
+    );
+}
+
+/// The pause state of the admin API deserializes and renders its reason and
+/// buffered events.
+#[test]
+fn test_pause_status_text() {
+    let status: SessionPauseStatus = serde_json::from_value(serde_json::json!({
+        "session_id": "owner/repo/pull_request/7",
+        "state": "resuming",
+        "paused_at": "2026-01-01T00:00:00Z",
+        "paused_by": "on-call",
+        "reason": "bot fix",
+        "buffered_events": 3,
+        "oldest_buffered_at": null
+    }))
+    .unwrap();
+
+    let text = pause_status_text(&status);
+
+    assert!(text.starts_with("Session owner/repo/pull_request/7 resuming (paused by on-call"));
+    assert!(text.contains("Reason: bot fix\n"));
+    assert!(text.contains("Buffered events: 3\n"));
+    assert!(!text.contains("Oldest buffered"));
+}
@@ -0,0 +1,397 @@
+//! # Subscription Impact Simulation
+//!
+//! Implements `queue-keeper bots simulate`, which answers "what would change
+//! if this bot configuration were deployed?" before it is deployed.
+//!
+//! Historical events are read from a running service's event store through
+//! `GET /api/events` and `GET /api/events/{id}`. Each event is matched against
+//! both the baseline (currently deployed) and the proposed bot configuration
+//! using the same [`BotConfiguration::get_target_bots`] logic the router uses,
+//! and the differences are reported per bot:
+//!
+//! - **gained** — events the bot would receive with the proposed
+//!   configuration but does not receive today;
+//! - **lost** — events the bot receives today but would no longer receive.
+//!
+//! Nothing is delivered: the simulation only reads events.
+
+use async_trait::async_trait;
+use queue_keeper_core::{
+    bot_config::{BotConfigError, BotConfiguration},
+    webhook::WrappedEvent,
+    EventId, Timestamp,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::Duration,
+};
+use tracing::{debug, info};
+
+/// Maximum number of example event IDs reported per bot and direction.
+pub const MAX_EXAMPLES: usize = 5;
+
+/// Page size used when listing events from the service.
+const PAGE_SIZE: usize = 500;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors that stop a simulation.
+#[derive(Debug, thiserror::Error)]
+pub enum SimulateError {
+    #[error("Invalid bot configuration '{path}': {source}")]
+    Configuration {
+        path: String,
+        #[source]
+        source: BotConfigError,
+    },
+
+    #[error("Invalid --since value '{value}': expected a duration such as 7d, 12h or 30m, or an RFC 3339 timestamp")]
+    InvalidSince { value: String },
+
+    #[error("Failed to read event history: {message}")]
+    History { message: String },
+}
+
+// ============================================================================
+// Time Window
+// ============================================================================
+
+/// Resolve a `--since` argument to the oldest event timestamp to include.
+///
+/// Accepts a relative duration (`<n>d`, `<n>h`, `<n>m`, `<n>s`) counted back
+/// from `now`, or an absolute RFC 3339 timestamp.
+pub fn parse_since(value: &str, now: Timestamp) -> Result<Timestamp, SimulateError> {
+    let invalid = || SimulateError::InvalidSince {
+        value: value.to_string(),
+    };
+    let trimmed = value.trim();
+
+    if let Ok(ts) = Timestamp::from_rfc3339(trimmed) {
+        return Ok(ts);
+    }
+
+    let split = trimmed.len().checked_sub(1).ok_or_else(invalid)?;
+    let (amount, unit) = trimmed.split_at(split);
+    let amount: u64 = amount.parse().map_err(|_| invalid())?;
+    let seconds_per_unit = match unit {
+        "d" => 86_400,
+        "h" => 3_600,
+        "m" => 60,
+        "s" => 1,
+        _ => return Err(invalid()),
+    };
+    let seconds = amount.checked_mul(seconds_per_unit).ok_or_else(invalid)?;
+    Ok(now.subtract_duration(Duration::from_secs(seconds)))
+}
+
+// ============================================================================
+// Event History
+// ============================================================================
+
+/// Source of historical events to simulate against.
+#[async_trait]
+pub trait EventHistory: Send + Sync {
+    /// Events received at or after `since`.
+    async fn events_since(&self, since: Timestamp) -> Result<Vec<WrappedEvent>, SimulateError>;
+}
+
+/// Page of event summaries returned by `GET /api/events`.
+#[derive(Debug, Deserialize)]
+struct EventListPage {
+    events: Vec<EventSummaryEntry>,
+    #[serde(default)]
+    next_cursor: Option<String>,
+}
+
+/// The fields of an event summary used by the simulation.
+#[derive(Debug, Deserialize)]
+struct EventSummaryEntry {
+    event_id: EventId,
+}
+
+/// Body of `GET /api/events/{id}`.
+#[derive(Debug, Deserialize)]
+struct EventDetail {
+    event: WrappedEvent,
+}
+
+/// Reads event history from a running Queue-Keeper service's query API.
+pub struct ApiEventHistory {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl ApiEventHistory {
+    /// Create a history reader for the service at `base_url`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn get_json<T: for<'de> Deserialize<'de>>(&self, url: &str) -> Result<T, SimulateError> {
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| SimulateError::History {
+                message: format!("GET {}: {}", url, e),
+            })?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(SimulateError::History {
+                message: format!("GET {}: HTTP {}", url, status.as_u16()),
+            });
+        }
+        response.json().await.map_err(|e| SimulateError::History {
+            message: format!("GET {}: invalid response body: {}", url, e),
+        })
+    }
+}
+
+#[async_trait]
+impl EventHistory for ApiEventHistory {
+    async fn events_since(&self, since: Timestamp) -> Result<Vec<WrappedEvent>, SimulateError> {
+        let mut ids = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let mut url = format!(
+                "{}/api/events?since={}&limit={}&include_total=false",
+                self.base_url,
+                since.to_rfc3339().replace('+', "%2B"),
+                PAGE_SIZE
+            );
+            if let Some(cursor) = &cursor {
+                url.push_str(&format!("&cursor={}", cursor));
+            }
+            let listing: EventListPage = self.get_json(&url).await?;
+            let received = listing.events.len();
+            ids.extend(listing.events.into_iter().map(|e| e.event_id));
+            debug!(received, listed = ids.len(), "Listed event page");
+            match listing.next_cursor {
+                Some(next) if received > 0 => cursor = Some(next),
+                _ => break,
+            }
+        }
+        // The API lists newest first; simulate in arrival order
+        ids.reverse();
+
+        let mut events = Vec::with_capacity(ids.len());
+        for id in ids {
+            let url = format!("{}/api/events/{}", self.base_url, id);
+            let detail: EventDetail = self.get_json(&url).await?;
+            events.push(detail.event);
+        }
+        Ok(events)
+    }
+}
+
+// ============================================================================
+// Simulation
+// ============================================================================
+
+/// How a bot's presence differs between the two configurations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BotChange {
+    /// Present in both configurations.
+    Existing,
+    /// Only present in the proposed configuration.
+    Added,
+    /// Only present in the baseline configuration.
+    Removed,
+}
+
+/// Routing differences for a single bot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BotDelta {
+    /// Bot name.
+    pub bot: String,
+
+    /// Whether the bot is new, removed or present in both configurations.
+    pub change: BotChange,
+
+    /// Events the bot receives with the baseline configuration.
+    pub baseline_matches: usize,
+
+    /// Events the bot would receive with the proposed configuration.
+    pub proposed_matches: usize,
+
+    /// Events only matched by the proposed configuration.
+    pub gained: usize,
+
+    /// Events only matched by the baseline configuration.
+    pub lost: usize,
+
+    /// Up to [`MAX_EXAMPLES`] gained event IDs.
+    pub examples_gained: Vec<EventId>,
+
+    /// Up to [`MAX_EXAMPLES`] lost event IDs.
+    pub examples_lost: Vec<EventId>,
+}
+
+impl BotDelta {
+    /// Check whether the bot's routing changes at all.
+    pub fn is_changed(&self) -> bool {
+        self.gained > 0 || self.lost > 0 || self.change != BotChange::Existing
+    }
+}
+
+/// Result of simulating a configuration change over historical events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SimulationReport {
+    /// Oldest event timestamp included.
+    pub since: Timestamp,
+
+    /// Number of historical events evaluated.
+    pub events_evaluated: usize,
+
+    /// Events routed to no bot under the baseline configuration.
+    pub baseline_unrouted: usize,
+
+    /// Events routed to no bot under the proposed configuration.
+    pub proposed_unrouted: usize,
+
+    /// Per-bot deltas, ordered by bot name.
+    pub bots: Vec<BotDelta>,
+}
+
+impl SimulationReport {
+    /// Render the report as human-readable text.
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "Evaluated {} events since {}\nUnrouted events: {} -> {}\n\n",
+            self.events_evaluated, self.since, self.baseline_unrouted, self.proposed_unrouted
+        );
+        out.push_str(&format!(
+            "{:<32} {:<9} {:>8} {:>8} {:>8} {:>8}\n",
+            "BOT", "CHANGE", "BEFORE", "AFTER", "GAINED", "LOST"
+        ));
+        for delta in &self.bots {
+            let change = match delta.change {
+                BotChange::Existing if delta.is_changed() => "changed",
+                BotChange::Existing => "-",
+                BotChange::Added => "added",
+                BotChange::Removed => "removed",
+            };
+            out.push_str(&format!(
+                "{:<32} {:<9} {:>8} {:>8} {:>+8} {:>8}\n",
+                delta.bot,
+                change,
+                delta.baseline_matches,
+                delta.proposed_matches,
+                delta.gained,
+                format!("-{}", delta.lost)
+            ));
+        }
+        out
+    }
+}
+
+/// Match every event against both configurations and compute per-bot deltas.
+pub fn simulate(
+    baseline: &BotConfiguration,
+    proposed: &BotConfiguration,
+    events: &[WrappedEvent],
+    since: Timestamp,
+) -> SimulationReport {
+    let mut deltas: BTreeMap<String, BotDelta> = BTreeMap::new();
+    let baseline_names: HashSet<String> =
+        baseline.bots.iter().map(|b| b.name.to_string()).collect();
+    let proposed_names: HashSet<String> =
+        proposed.bots.iter().map(|b| b.name.to_string()).collect();
+
+    for name in baseline_names.union(&proposed_names) {
+        let change = match (baseline_names.contains(name), proposed_names.contains(name)) {
+            (true, true) => BotChange::Existing,
+            (false, true) => BotChange::Added,
+            _ => BotChange::Removed,
+        };
+        deltas.insert(
+            name.clone(),
+            BotDelta {
+                bot: name.clone(),
+                change,
+                baseline_matches: 0,
+                proposed_matches: 0,
+                gained: 0,
+                lost: 0,
+                examples_gained: Vec::new(),
+                examples_lost: Vec::new(),
+            },
+        );
+    }
+
+    let mut baseline_unrouted = 0;
+    let mut proposed_unrouted = 0;
+
+    for event in events {
+        let before: HashSet<String> = baseline
+            .get_target_bots(event)
+            .iter()
+            .map(|b| b.name.to_string())
+            .collect();
+        let after: HashSet<String> = proposed
+            .get_target_bots(event)
+            .iter()
+            .map(|b| b.name.to_string())
+            .collect();
+
+        baseline_unrouted += usize::from(before.is_empty());
+        proposed_unrouted += usize::from(after.is_empty());
+
+        for name in &before {
+            if let Some(delta) = deltas.get_mut(name) {
+                delta.baseline_matches += 1;
+                if !after.contains(name) {
+                    delta.lost += 1;
+                    if delta.examples_lost.len() < MAX_EXAMPLES {
+                        delta.examples_lost.push(event.event_id);
+                    }
+                }
+            }
+        }
+        for name in &after {
+            if let Some(delta) = deltas.get_mut(name) {
+                delta.proposed_matches += 1;
+                if !before.contains(name) {
+                    delta.gained += 1;
+                    if delta.examples_gained.len() < MAX_EXAMPLES {
+                        delta.examples_gained.push(event.event_id);
+                    }
+                }
+            }
+        }
+    }
+
+    info!(
+        events = events.len(),
+        bots = deltas.len(),
+        "Subscription simulation complete"
+    );
+
+    SimulationReport {
+        since,
+        events_evaluated: events.len(),
+        baseline_unrouted,
+        proposed_unrouted,
+        bots: deltas.into_values().collect(),
+    }
+}
+
+/// Load and validate a bot configuration file for simulation.
+pub fn load_bot_configuration(path: &std::path::Path) -> Result<BotConfiguration, SimulateError> {
+    BotConfiguration::load_from_file(path).map_err(|source| SimulateError::Configuration {
+        path: path.display().to_string(),
+        source,
+    })
+}
+
+#[cfg(test)]
+#[path = "simulate_tests.rs"]
+mod tests;
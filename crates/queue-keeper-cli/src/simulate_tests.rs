@@ -0,0 +1,152 @@
+//! Tests for subscription impact simulation.
+
+use super::*;
+use queue_keeper_core::{
+    bot_config::{BotConfigurationSettings, BotSpecificConfig},
+    BotName, BotSubscription, EventTypePattern, QueueName,
+};
+
+fn bot(name: &str, events: &[&str]) -> BotSubscription {
+    BotSubscription {
+        name: BotName::new(name).unwrap(),
+        queue: QueueName::new(format!("queue-keeper-{}", name)).unwrap(),
+        events: events
+            .iter()
+            .map(|e| EventTypePattern::Exact(e.to_string()))
+            .collect(),
+        ordered: false,
+        repository_filter: None,
+        config: BotSpecificConfig::new(),
+        encryption: None,
+        transform: None,
+        projection: None,
+        http: None,
+        sender_filter: None,
+    }
+}
+
+fn configuration(bots: Vec<BotSubscription>) -> BotConfiguration {
+    BotConfiguration {
+        bots,
+        settings: BotConfigurationSettings::default(),
+    }
+}
+
+fn event(event_type: &str) -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        event_type.to_string(),
+        None,
+        None,
+        serde_json::json!({}),
+        None,
+    )
+}
+
+mod parse_since_tests {
+    use super::*;
+
+    /// Relative windows are counted back from now.
+    #[test]
+    fn test_relative_windows() {
+        let now = Timestamp::now();
+
+        let week = parse_since("7d", now).unwrap();
+        assert_eq!(week, now.subtract_duration(Duration::from_secs(7 * 86_400)));
+
+        let half_hour = parse_since("30m", now).unwrap();
+        assert_eq!(half_hour, now.subtract_duration(Duration::from_secs(1_800)));
+    }
+
+    /// Absolute RFC 3339 timestamps are used as-is.
+    #[test]
+    fn test_absolute_timestamp() {
+        let since = parse_since("2024-01-01T00:00:00Z", Timestamp::now()).unwrap();
+        assert_eq!(
+            since,
+            Timestamp::from_rfc3339("2024-01-01T00:00:00Z").unwrap()
+        );
+    }
+
+    /// Malformed values are rejected.
+    #[test]
+    fn test_invalid_values() {
+        for value in ["", "d", "7w", "seven days", "-1h"] {
+            assert!(
+                matches!(
+                    parse_since(value, Timestamp::now()),
+                    Err(SimulateError::InvalidSince { .. })
+                ),
+                "{value:?} must be rejected"
+            );
+        }
+    }
+}
+
+mod simulate_tests {
+    use super::*;
+
+    /// Narrowing and widening filters are reported as lost and gained events.
+    #[test]
+    fn test_reports_gained_and_lost_events() {
+        let baseline = configuration(vec![bot("triage", &["issues", "pull_request"])]);
+        let proposed = configuration(vec![bot("triage", &["issues", "push"])]);
+        let events = vec![event("issues"), event("pull_request"), event("push")];
+
+        let report = simulate(&baseline, &proposed, &events, Timestamp::now());
+
+        assert_eq!(report.events_evaluated, 3);
+        let delta = &report.bots[0];
+        assert_eq!(delta.change, BotChange::Existing);
+        assert_eq!(delta.baseline_matches, 2);
+        assert_eq!(delta.proposed_matches, 2);
+        assert_eq!(delta.gained, 1);
+        assert_eq!(delta.lost, 1);
+        assert_eq!(delta.examples_gained, vec![events[2].event_id]);
+        assert_eq!(delta.examples_lost, vec![events[1].event_id]);
+        assert_eq!(report.baseline_unrouted, 1);
+        assert_eq!(report.proposed_unrouted, 1);
+    }
+
+    /// Added and removed bots gain or lose every event they match.
+    #[test]
+    fn test_added_and_removed_bots() {
+        let baseline = configuration(vec![bot("legacy", &["issues"])]);
+        let proposed = configuration(vec![bot("modern", &["issues"])]);
+        let events = vec![event("issues"), event("issues")];
+
+        let report = simulate(&baseline, &proposed, &events, Timestamp::now());
+
+        let names: Vec<&str> = report.bots.iter().map(|d| d.bot.as_str()).collect();
+        assert_eq!(names, ["legacy", "modern"]);
+        assert_eq!(report.bots[0].change, BotChange::Removed);
+        assert_eq!(report.bots[0].lost, 2);
+        assert_eq!(report.bots[1].change, BotChange::Added);
+        assert_eq!(report.bots[1].gained, 2);
+    }
+
+    /// Unchanged configurations produce no deltas.
+    #[test]
+    fn test_identical_configurations_have_no_changes() {
+        let config = configuration(vec![bot("triage", &["issues"])]);
+        let events = vec![event("issues"), event("push")];
+
+        let report = simulate(&config, &config, &events, Timestamp::now());
+
+        assert!(report.bots.iter().all(|d| !d.is_changed()));
+        assert!(report.to_text().contains("triage"));
+    }
+
+    /// Example event IDs are capped per bot.
+    #[test]
+    fn test_examples_are_capped() {
+        let baseline = configuration(vec![]);
+        let proposed = configuration(vec![bot("triage", &["issues"])]);
+        let events: Vec<WrappedEvent> = (0..MAX_EXAMPLES + 3).map(|_| event("issues")).collect();
+
+        let report = simulate(&baseline, &proposed, &events, Timestamp::now());
+
+        assert_eq!(report.bots[0].gained, MAX_EXAMPLES + 3);
+        assert_eq!(report.bots[0].examples_gained.len(), MAX_EXAMPLES);
+    }
+}
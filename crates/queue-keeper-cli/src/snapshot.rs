@@ -0,0 +1,595 @@
+//! # Operational State Snapshots
+//!
+//! Implements `queue-keeper snapshot create|restore`, which copies the
+//! persisted state of a deployment into a single portable archive and back.
+//! Use it to clone an environment for debugging, or as the tested path for
+//! disaster recovery.
+//!
+//! A snapshot has up to three sections:
+//!
+//! - `events`: the event storage directory (`QK_EVENT_STORAGE_PATH`). Session
+//!   state is derived from the stored events, so restoring the events
+//!   restores every session's history.
+//! - `dlq`: the DLQ storage directory, including archived bundles.
+//! - `config`: the service and bot configuration files given on the command
+//!   line, stored by file name.
+//!
+//! Stored blobs from the previous format version are upgraded as they are
+//! captured (see [`queue_keeper_core::storage_format`]), so every blob in a
+//! snapshot is in the current format; the source directories are not
+//! modified. A blob that cannot be read, or was written by a newer release,
+//! fails the snapshot instead of producing a partial one.
+//!
+//! Queue-Keeper does not keep an outbox: deliveries are in flight only in
+//! the queues themselves, and held or paused deliveries live in memory.
+//! Neither is part of a snapshot.
+//!
+//! Blobs are written once, so a snapshot taken from a running deployment
+//! contains every blob present when the storage directories were listed.
+//! Blobs removed while the snapshot is taken (for example by DLQ retention)
+//! are left out.
+//!
+//! The archive is gzip-compressed JSON holding a [`SnapshotManifest`] and the
+//! files with their SHA-256 checksums. Restore verifies every checksum before
+//! writing anything and refuses to write into non-empty directories unless
+//! `--force` is given.
+
+use crate::import::{DirectorySource, ImportError, ImportSource};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use bytes::Bytes;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use queue_keeper_core::{
+    blob_storage::compute_checksum,
+    storage_format::{migrate_stored_blob, MigrationOutcome},
+    Timestamp,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashSet,
+    fmt,
+    path::{Component, Path, PathBuf},
+};
+use tracing::info;
+
+/// Value of [`SnapshotManifest::format`] identifying a snapshot archive.
+pub const SNAPSHOT_FORMAT: &str = "queue-keeper-snapshot";
+
+/// Archive layout version written by this release.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors creating or restoring a snapshot.
+#[derive(Debug, thiserror::Error)]
+pub enum SnapshotError {
+    #[error("Cannot read {section} directory: {source}")]
+    Source {
+        section: SnapshotSection,
+        #[source]
+        source: ImportError,
+    },
+
+    #[error("Cannot capture {section}/{path}: {message}")]
+    Capture {
+        section: SnapshotSection,
+        path: String,
+        message: String,
+    },
+
+    #[error("I/O error on '{path}': {message}")]
+    Io { path: String, message: String },
+
+    #[error("Invalid snapshot archive: {message}")]
+    InvalidArchive { message: String },
+
+    #[error("Restore target '{path}' is not empty (use --force to restore into it)")]
+    TargetNotEmpty { path: String },
+}
+
+fn io_error(path: &Path, error: impl fmt::Display) -> SnapshotError {
+    SnapshotError::Io {
+        path: path.display().to_string(),
+        message: error.to_string(),
+    }
+}
+
+fn invalid_archive(message: impl ToString) -> SnapshotError {
+    SnapshotError::InvalidArchive {
+        message: message.to_string(),
+    }
+}
+
+// ============================================================================
+// Archive
+// ============================================================================
+
+/// Part of the operational state held in a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotSection {
+    /// Event storage; also the source of session state.
+    Events,
+
+    /// DLQ records and archived bundles.
+    Dlq,
+
+    /// Service and bot configuration files.
+    Config,
+}
+
+impl SnapshotSection {
+    /// Stable name used in the archive and in reports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Events => "events",
+            Self::Dlq => "dlq",
+            Self::Config => "config",
+        }
+    }
+}
+
+impl fmt::Display for SnapshotSection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// File counts of one section.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SectionSummary {
+    pub section: SnapshotSection,
+
+    /// Files in the section.
+    pub files: usize,
+
+    /// Total size of the files, in bytes.
+    pub bytes: u64,
+
+    /// Blobs upgraded to the current format while capturing.
+    #[serde(default)]
+    pub migrated: usize,
+}
+
+/// Describes a snapshot archive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Always [`SNAPSHOT_FORMAT`].
+    pub format: String,
+
+    /// Archive layout version; see [`SNAPSHOT_VERSION`].
+    pub version: u32,
+
+    /// Version of the CLI that created the snapshot.
+    pub created_by: String,
+
+    /// When the storage directories were listed.
+    pub created_at: Timestamp,
+
+    /// Sections in the snapshot, in capture order.
+    pub sections: Vec<SectionSummary>,
+}
+
+/// One file in a snapshot.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotFile {
+    pub section: SnapshotSection,
+
+    /// Path relative to the section root, with `/` separators.
+    pub path: String,
+
+    /// Hex SHA-256 of the contents.
+    pub sha256: String,
+
+    /// Base64-encoded contents.
+    pub contents: String,
+}
+
+impl SnapshotFile {
+    fn new(section: SnapshotSection, path: String, contents: Vec<u8>) -> Self {
+        Self {
+            section,
+            path,
+            sha256: compute_checksum(&Bytes::from(contents.clone())),
+            contents: STANDARD.encode(contents),
+        }
+    }
+
+    /// Decode the contents and check them against the recorded checksum.
+    fn verified_contents(&self) -> Result<Vec<u8>, SnapshotError> {
+        let contents = STANDARD
+            .decode(&self.contents)
+            .map_err(|e| invalid_archive(format!("{}/{}: {}", self.section, self.path, e)))?;
+        if compute_checksum(&Bytes::from(contents.clone())) != self.sha256 {
+            return Err(invalid_archive(format!(
+                "{}/{}: checksum mismatch",
+                self.section, self.path
+            )));
+        }
+        Ok(contents)
+    }
+}
+
+/// A snapshot: manifest plus files.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotArchive {
+    pub manifest: SnapshotManifest,
+    pub files: Vec<SnapshotFile>,
+}
+
+impl SnapshotArchive {
+    /// Summary of `section`, if the snapshot has it.
+    pub fn section(&self, section: SnapshotSection) -> Option<&SectionSummary> {
+        self.manifest
+            .sections
+            .iter()
+            .find(|summary| summary.section == section)
+    }
+
+    /// Serialize as gzip-compressed JSON.
+    pub fn encode(&self) -> Result<Vec<u8>, SnapshotError> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        serde_json::to_writer(&mut encoder, self).map_err(invalid_archive)?;
+        encoder.finish().map_err(invalid_archive)
+    }
+
+    /// Parse an archive produced by [`encode`](Self::encode).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SnapshotError::InvalidArchive`] when the data is not a
+    /// snapshot, or was written with a newer archive layout.
+    pub fn decode(data: &[u8]) -> Result<Self, SnapshotError> {
+        let archive: Self = serde_json::from_reader(GzDecoder::new(data))
+            .map_err(|e| invalid_archive(format!("not a snapshot archive: {}", e)))?;
+        if archive.manifest.format != SNAPSHOT_FORMAT {
+            return Err(invalid_archive(format!(
+                "unknown format '{}'",
+                archive.manifest.format
+            )));
+        }
+        if archive.manifest.version > SNAPSHOT_VERSION {
+            return Err(invalid_archive(format!(
+                "archive version {} is newer than the supported version {}",
+                archive.manifest.version, SNAPSHOT_VERSION
+            )));
+        }
+        Ok(archive)
+    }
+
+    /// Write the archive to `path`.
+    pub async fn write_to(&self, path: &Path) -> Result<(), SnapshotError> {
+        let data = self.encode()?;
+        tokio::fs::write(path, data)
+            .await
+            .map_err(|e| io_error(path, e))
+    }
+
+    /// Read an archive from `path`.
+    pub async fn read_from(path: &Path) -> Result<Self, SnapshotError> {
+        let data = tokio::fs::read(path).await.map_err(|e| io_error(path, e))?;
+        Self::decode(&data)
+    }
+
+    /// Human-readable summary.
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Snapshot created {} by queue-keeper {}\n",
+            self.manifest.created_at, self.manifest.created_by
+        );
+        for summary in &self.manifest.sections {
+            text.push_str(&format!(
+                "  {}: {} files, {} bytes, {} migrated\n",
+                summary.section, summary.files, summary.bytes, summary.migrated
+            ));
+        }
+        text
+    }
+}
+
+// ============================================================================
+// Create
+// ============================================================================
+
+/// Where the state of a deployment is read from.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotSources {
+    /// Event storage directory.
+    pub events: PathBuf,
+
+    /// DLQ storage directory.
+    pub dlq: Option<PathBuf>,
+
+    /// Configuration files; stored by file name, which must be unique.
+    pub config_files: Vec<PathBuf>,
+}
+
+/// Capture the state below `sources`.
+///
+/// # Errors
+///
+/// Returns [`SnapshotError::Source`] when a storage directory cannot be
+/// listed, and [`SnapshotError::Capture`] or [`SnapshotError::Io`] for a file
+/// that cannot be read or upgraded.
+pub async fn create_snapshot(sources: &SnapshotSources) -> Result<SnapshotArchive, SnapshotError> {
+    let created_at = Timestamp::now();
+    let mut sections = Vec::new();
+    let mut files = Vec::new();
+
+    let mut directories = vec![(SnapshotSection::Events, &sources.events)];
+    if let Some(dlq) = &sources.dlq {
+        directories.push((SnapshotSection::Dlq, dlq));
+    }
+    for (section, root) in directories {
+        let (summary, section_files) = capture_directory(section, root).await?;
+        sections.push(summary);
+        files.extend(section_files);
+    }
+
+    if !sources.config_files.is_empty() {
+        let (summary, section_files) = capture_config(&sources.config_files).await?;
+        sections.push(summary);
+        files.extend(section_files);
+    }
+
+    let archive = SnapshotArchive {
+        manifest: SnapshotManifest {
+            format: SNAPSHOT_FORMAT.to_string(),
+            version: SNAPSHOT_VERSION,
+            created_by: env!("CARGO_PKG_VERSION").to_string(),
+            created_at,
+            sections,
+        },
+        files,
+    };
+    info!(
+        files = archive.files.len(),
+        sections = archive.manifest.sections.len(),
+        "Snapshot captured"
+    );
+    Ok(archive)
+}
+
+/// Capture every file below a storage directory, upgrading stored blobs.
+async fn capture_directory(
+    section: SnapshotSection,
+    root: &Path,
+) -> Result<(SectionSummary, Vec<SnapshotFile>), SnapshotError> {
+    let source_error = |source| SnapshotError::Source { section, source };
+    let keys = DirectorySource::new(root.to_path_buf())
+        .map_err(source_error)?
+        .list_keys()
+        .await
+        .map_err(source_error)?;
+
+    let mut summary = SectionSummary {
+        section,
+        files: 0,
+        bytes: 0,
+        migrated: 0,
+    };
+    let mut files = Vec::with_capacity(keys.len());
+    for key in keys {
+        let path = root.join(&key);
+        let contents = match tokio::fs::read(&path).await {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(io_error(&path, e)),
+        };
+        let contents = if key.ends_with(".json") {
+            match migrate_stored_blob(&contents) {
+                Ok(MigrationOutcome::Current) => contents,
+                Ok(MigrationOutcome::Migrated(upgraded)) => {
+                    summary.migrated += 1;
+                    upgraded
+                }
+                Err(e) => {
+                    return Err(SnapshotError::Capture {
+                        section,
+                        path: key,
+                        message: e.to_string(),
+                    })
+                }
+            }
+        } else {
+            contents
+        };
+        summary.files += 1;
+        summary.bytes += contents.len() as u64;
+        files.push(SnapshotFile::new(section, key, contents));
+    }
+    Ok((summary, files))
+}
+
+/// Capture configuration files by file name.
+async fn capture_config(
+    paths: &[PathBuf],
+) -> Result<(SectionSummary, Vec<SnapshotFile>), SnapshotError> {
+    let section = SnapshotSection::Config;
+    let mut summary = SectionSummary {
+        section,
+        files: 0,
+        bytes: 0,
+        migrated: 0,
+    };
+    let mut names = HashSet::new();
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .ok_or_else(|| SnapshotError::Capture {
+                section,
+                path: path.display().to_string(),
+                message: "not a file".to_string(),
+            })?;
+        if !names.insert(name.clone()) {
+            return Err(SnapshotError::Capture {
+                section,
+                path: path.display().to_string(),
+                message: format!("another configuration file is also named '{}'", name),
+            });
+        }
+        let contents = tokio::fs::read(path).await.map_err(|e| io_error(path, e))?;
+        summary.files += 1;
+        summary.bytes += contents.len() as u64;
+        files.push(SnapshotFile::new(section, name, contents));
+    }
+    Ok((summary, files))
+}
+
+// ============================================================================
+// Restore
+// ============================================================================
+
+/// Where a snapshot is restored to.
+#[derive(Debug, Clone, Default)]
+pub struct RestoreTargets {
+    /// Event storage directory.
+    pub events: PathBuf,
+
+    /// DLQ storage directory; the `dlq` section is skipped without it.
+    pub dlq: Option<PathBuf>,
+
+    /// Directory for configuration files; the `config` section is skipped
+    /// without it.
+    pub config_dir: Option<PathBuf>,
+
+    /// Restore into directories that already contain files, overwriting
+    /// files with the same path.
+    pub force: bool,
+}
+
+impl RestoreTargets {
+    fn root(&self, section: SnapshotSection) -> Option<&Path> {
+        match section {
+            SnapshotSection::Events => Some(&self.events),
+            SnapshotSection::Dlq => self.dlq.as_deref(),
+            SnapshotSection::Config => self.config_dir.as_deref(),
+        }
+    }
+}
+
+/// Outcome of a restore.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RestoreReport {
+    /// Sections written, with their file counts.
+    pub restored: Vec<SectionSummary>,
+
+    /// Sections in the snapshot that had no restore target.
+    pub skipped: Vec<SnapshotSection>,
+}
+
+impl RestoreReport {
+    /// Human-readable summary.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for summary in &self.restored {
+            text.push_str(&format!(
+                "Restored {}: {} files, {} bytes\n",
+                summary.section, summary.files, summary.bytes
+            ));
+        }
+        for section in &self.skipped {
+            text.push_str(&format!("Skipped {}: no target directory given\n", section));
+        }
+        text
+    }
+}
+
+/// Write the files of `archive` below `targets`.
+///
+/// Every file is verified before the first one is written, so a damaged
+/// archive leaves the targets untouched.
+///
+/// # Errors
+///
+/// Returns [`SnapshotError::InvalidArchive`] for a damaged archive or a file
+/// path escaping its section, [`SnapshotError::TargetNotEmpty`] when a target
+/// already has files and `force` is not set, and [`SnapshotError::Io`] when
+/// writing fails.
+pub async fn restore_snapshot(
+    archive: &SnapshotArchive,
+    targets: &RestoreTargets,
+) -> Result<RestoreReport, SnapshotError> {
+    let mut report = RestoreReport::default();
+    let mut pending = Vec::new();
+    for summary in &archive.manifest.sections {
+        let Some(root) = targets.root(summary.section) else {
+            report.skipped.push(summary.section);
+            continue;
+        };
+        if !targets.force && has_files(root).await? {
+            return Err(SnapshotError::TargetNotEmpty {
+                path: root.display().to_string(),
+            });
+        }
+        let mut restored = SectionSummary {
+            section: summary.section,
+            files: 0,
+            bytes: 0,
+            migrated: 0,
+        };
+        for file in archive
+            .files
+            .iter()
+            .filter(|f| f.section == summary.section)
+        {
+            let relative = safe_relative_path(&file.path).ok_or_else(|| {
+                invalid_archive(format!("{}/{}: invalid path", file.section, file.path))
+            })?;
+            let contents = file.verified_contents()?;
+            restored.files += 1;
+            restored.bytes += contents.len() as u64;
+            pending.push((root.join(relative), contents));
+        }
+        report.restored.push(restored);
+    }
+
+    for (path, contents) in pending {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| io_error(parent, e))?;
+        }
+        tokio::fs::write(&path, contents)
+            .await
+            .map_err(|e| io_error(&path, e))?;
+    }
+
+    info!(
+        restored = report.restored.len(),
+        skipped = report.skipped.len(),
+        "Snapshot restored"
+    );
+    Ok(report)
+}
+
+/// Convert an archive path to a relative path that stays below its root.
+fn safe_relative_path(path: &str) -> Option<PathBuf> {
+    let relative = PathBuf::from(path);
+    let is_safe = !path.is_empty()
+        && relative
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    is_safe.then_some(relative)
+}
+
+/// Whether `root` exists and contains any entry.
+async fn has_files(root: &Path) -> Result<bool, SnapshotError> {
+    let mut entries = match tokio::fs::read_dir(root).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(io_error(root, e)),
+    };
+    entries
+        .next_entry()
+        .await
+        .map(|entry| entry.is_some())
+        .map_err(|e| io_error(root, e))
+}
+
+#[cfg(test)]
+#[path = "snapshot_tests.rs"]
+mod tests;
@@ -0,0 +1,253 @@
+//! Tests for snapshot create and restore.
+
+use super::*;
+use queue_keeper_core::{
+    blob_storage::{BlobMetadata, PayloadMetadata, StoredWebhook, WebhookPayload},
+    storage_format::{CURRENT_FORMAT_VERSION, LEGACY_FORMAT_VERSION},
+    EventId,
+};
+use std::collections::HashMap;
+
+/// Write a stored blob below `root`, without a version marker when `legacy`.
+fn write_blob(root: &Path, legacy: bool) -> String {
+    let event_id = EventId::new();
+    let body = Bytes::from_static(br#"{"action":"opened"}"#);
+    let metadata = PayloadMetadata {
+        event_id,
+        event_type: "issues".to_string(),
+        repository: None,
+        signature_valid: true,
+        received_at: Timestamp::now(),
+        delivery_id: None,
+    };
+    let stored = StoredWebhook {
+        metadata: BlobMetadata {
+            event_id,
+            blob_path: event_id.to_blob_path(),
+            size_bytes: body.len() as u64,
+            content_type: "application/json".to_string(),
+            created_at: Timestamp::now(),
+            checksum_sha256: compute_checksum(&body),
+            format_version: CURRENT_FORMAT_VERSION,
+            content_encoding: queue_keeper_core::blob_storage::ContentEncoding::Identity,
+            encryption: None,
+            metadata: metadata.clone(),
+        },
+        payload: WebhookPayload {
+            body,
+            headers: HashMap::new(),
+            metadata,
+        },
+    };
+    let mut value = serde_json::to_value(&stored).unwrap();
+    if legacy {
+        value["metadata"]
+            .as_object_mut()
+            .unwrap()
+            .remove("format_version");
+    }
+
+    let key = event_id.to_blob_path();
+    let path = root.join(&key);
+    std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+    std::fs::write(&path, serde_json::to_vec(&value).unwrap()).unwrap();
+    key
+}
+
+fn format_version(path: &Path) -> u32 {
+    let stored: StoredWebhook = serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap();
+    stored.metadata.format_version
+}
+
+/// A deployment with two events, a DLQ bundle and a configuration file.
+struct Deployment {
+    _dir: tempfile::TempDir,
+    sources: SnapshotSources,
+    legacy_key: String,
+    current_key: String,
+}
+
+fn deployment() -> Deployment {
+    let dir = tempfile::tempdir().unwrap();
+    let events = dir.path().join("events");
+    let dlq = dir.path().join("dlq");
+    std::fs::create_dir_all(&events).unwrap();
+    std::fs::create_dir_all(dlq.join("archive")).unwrap();
+    let legacy_key = write_blob(&events, true);
+    let current_key = write_blob(&events, false);
+    std::fs::write(dlq.join("archive/bundle-1.ndjson.gz"), [0x1f, 0x8b, 0x00]).unwrap();
+    let config = dir.path().join("service.yaml");
+    std::fs::write(&config, "server:\n  port: 8080\n").unwrap();
+
+    Deployment {
+        sources: SnapshotSources {
+            events,
+            dlq: Some(dlq),
+            config_files: vec![config],
+        },
+        _dir: dir,
+        legacy_key,
+        current_key,
+    }
+}
+
+/// A snapshot holds every section, with legacy blobs upgraded and the source
+/// left untouched.
+#[tokio::test]
+async fn test_create_captures_sections_and_upgrades_blobs() {
+    let deployment = deployment();
+
+    let archive = create_snapshot(&deployment.sources).await.unwrap();
+
+    let events = archive.section(SnapshotSection::Events).unwrap();
+    assert_eq!(events.files, 2);
+    assert_eq!(events.migrated, 1);
+    assert_eq!(archive.section(SnapshotSection::Dlq).unwrap().files, 1);
+    assert_eq!(archive.section(SnapshotSection::Config).unwrap().files, 1);
+    assert_eq!(
+        format_version(&deployment.sources.events.join(&deployment.legacy_key)),
+        LEGACY_FORMAT_VERSION
+    );
+    assert!(archive.to_text().contains("events: 2 files"));
+}
+
+/// A snapshot written to disk restores the same files into empty
+/// directories, every blob in the current format.
+#[tokio::test]
+async fn test_round_trip_restores_files() {
+    let deployment = deployment();
+    let archive_dir = tempfile::tempdir().unwrap();
+    let archive_path = archive_dir.path().join("snapshot.qks");
+    create_snapshot(&deployment.sources)
+        .await
+        .unwrap()
+        .write_to(&archive_path)
+        .await
+        .unwrap();
+
+    let target = tempfile::tempdir().unwrap();
+    let targets = RestoreTargets {
+        events: target.path().join("events"),
+        dlq: Some(target.path().join("dlq")),
+        config_dir: Some(target.path().join("config")),
+        force: false,
+    };
+    let archive = SnapshotArchive::read_from(&archive_path).await.unwrap();
+    let report = restore_snapshot(&archive, &targets).await.unwrap();
+
+    assert_eq!(report.restored.len(), 3);
+    assert!(report.skipped.is_empty());
+    for key in [&deployment.legacy_key, &deployment.current_key] {
+        assert_eq!(
+            format_version(&targets.events.join(key)),
+            CURRENT_FORMAT_VERSION
+        );
+    }
+    assert_eq!(
+        std::fs::read(target.path().join("dlq/archive/bundle-1.ndjson.gz")).unwrap(),
+        vec![0x1f, 0x8b, 0x00]
+    );
+    assert_eq!(
+        std::fs::read_to_string(target.path().join("config/service.yaml")).unwrap(),
+        "server:\n  port: 8080\n"
+    );
+}
+
+/// Sections without a restore target are skipped and reported.
+#[tokio::test]
+async fn test_restore_skips_sections_without_target() {
+    let archive = create_snapshot(&deployment().sources).await.unwrap();
+    let target = tempfile::tempdir().unwrap();
+    let targets = RestoreTargets {
+        events: target.path().join("events"),
+        ..RestoreTargets::default()
+    };
+
+    let report = restore_snapshot(&archive, &targets).await.unwrap();
+
+    assert_eq!(report.restored.len(), 1);
+    assert_eq!(
+        report.skipped,
+        vec![SnapshotSection::Dlq, SnapshotSection::Config]
+    );
+}
+
+/// Restoring into a directory that has files needs `force`.
+#[tokio::test]
+async fn test_restore_refuses_non_empty_target() {
+    let deployment = deployment();
+    let archive = create_snapshot(&deployment.sources).await.unwrap();
+    let mut targets = RestoreTargets {
+        events: deployment.sources.events.clone(),
+        ..RestoreTargets::default()
+    };
+
+    let result = restore_snapshot(&archive, &targets).await;
+    assert!(matches!(result, Err(SnapshotError::TargetNotEmpty { .. })));
+
+    targets.force = true;
+    restore_snapshot(&archive, &targets).await.unwrap();
+    assert_eq!(
+        format_version(&deployment.sources.events.join(&deployment.legacy_key)),
+        CURRENT_FORMAT_VERSION
+    );
+}
+
+/// A damaged file fails the restore before anything is written.
+#[tokio::test]
+async fn test_restore_rejects_checksum_mismatch() {
+    let mut archive = create_snapshot(&deployment().sources).await.unwrap();
+    archive.files.last_mut().unwrap().contents = STANDARD.encode(b"tampered");
+    let target = tempfile::tempdir().unwrap();
+    let targets = RestoreTargets {
+        events: target.path().join("events"),
+        config_dir: Some(target.path().join("config")),
+        ..RestoreTargets::default()
+    };
+
+    let result = restore_snapshot(&archive, &targets).await;
+
+    assert!(matches!(result, Err(SnapshotError::InvalidArchive { .. })));
+    assert!(!target.path().join("events").exists());
+}
+
+/// Archive paths may not escape their section root.
+#[tokio::test]
+async fn test_restore_rejects_path_traversal() {
+    let mut archive = create_snapshot(&deployment().sources).await.unwrap();
+    archive.files[0].path = "../outside.json".to_string();
+    let target = tempfile::tempdir().unwrap();
+    let targets = RestoreTargets {
+        events: target.path().join("events"),
+        ..RestoreTargets::default()
+    };
+
+    let result = restore_snapshot(&archive, &targets).await;
+
+    assert!(matches!(result, Err(SnapshotError::InvalidArchive { .. })));
+    assert!(!target.path().join("outside.json").exists());
+}
+
+/// Files that are not stored blobs fail the snapshot.
+#[tokio::test]
+async fn test_create_rejects_unreadable_blob() {
+    let deployment = deployment();
+    std::fs::write(deployment.sources.events.join("notes.json"), b"not a blob").unwrap();
+
+    let result = create_snapshot(&deployment.sources).await;
+
+    assert!(matches!(
+        result,
+        Err(SnapshotError::Capture { section: SnapshotSection::Events, ref path, .. })
+            if path == "notes.json"
+    ));
+}
+
+/// Data that is not a snapshot archive is rejected.
+#[test]
+fn test_decode_rejects_other_data() {
+    assert!(matches!(
+        SnapshotArchive::decode(b"not gzip"),
+        Err(SnapshotError::InvalidArchive { .. })
+    ));
+}
@@ -0,0 +1,258 @@
+//! # Service Status
+//!
+//! Implements `queue-keeper status`, which probes `GET /health` for the
+//! liveness and version of a running service and reads its statistics
+//! through `GET /api/stats`: live events received, the event rate and error
+//! rate over the last hour, active sessions and uptime. With `--verbose` the
+//! events are also broken down per event type and origin.
+//!
+//! When the service was started on this host by `queue-keeper start`, the
+//! process from its PID file is shown as well.
+//!
+//! Statistics are per replica and reset when the service restarts.
+
+use crate::process::ServiceProcess;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use tracing::debug;
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors reading the status of the service.
+#[derive(Debug, thiserror::Error)]
+pub enum StatusError {
+    #[error("Status request failed: {message}")]
+    Request { message: String },
+
+    #[error("Service not reachable at {endpoint}: {message}")]
+    Unreachable { endpoint: String, message: String },
+}
+
+// ============================================================================
+// Health
+// ============================================================================
+
+/// The part of the `GET /health` response the status command shows.
+///
+/// The service answers `503` with the same body when a check fails, so the
+/// body is read whatever the status code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServiceHealth {
+    /// `healthy` or `unhealthy`.
+    pub status: String,
+    pub version: String,
+}
+
+impl ServiceHealth {
+    pub fn is_healthy(&self) -> bool {
+        self.status == "healthy"
+    }
+}
+
+// ============================================================================
+// Statistics
+// ============================================================================
+
+/// Statistics returned by `GET /api/stats`.
+///
+/// Origins are kept as strings so that a CLI built against an older service
+/// still prints origins it does not know about.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceStatistics {
+    pub total_events: u64,
+    pub events_per_hour: f64,
+    pub active_sessions: u64,
+    pub error_rate: f64,
+    pub uptime_seconds: u64,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub events_by_type: BTreeMap<String, u64>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub events_by_origin: BTreeMap<String, u64>,
+}
+
+impl ServiceStatistics {
+    /// Render the statistics as human-readable text, with the per-type and
+    /// per-origin breakdown when `verbose` is set.
+    pub fn to_text(&self, endpoint: &str, verbose: bool) -> String {
+        let mut rows = vec![("Service", endpoint.to_string())];
+        rows.extend(self.summary_rows());
+        let mut out = format_rows(rows);
+        if verbose {
+            out.push_str(&self.breakdown());
+        }
+        out
+    }
+
+    /// Labelled summary values, uptime first.
+    fn summary_rows(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("Uptime", format_uptime(self.uptime_seconds)),
+            ("Events", self.total_events.to_string()),
+            ("Events/hour", format!("{:.0}", self.events_per_hour)),
+            ("Error rate", format!("{:.2}%", self.error_rate * 100.0)),
+            ("Sessions", format!("{} active", self.active_sessions)),
+        ]
+    }
+
+    /// Events per type, busiest first, and per origin.
+    fn breakdown(&self) -> String {
+        let mut out = String::new();
+        let mut by_type: Vec<(&String, &u64)> = self.events_by_type.iter().collect();
+        by_type.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+        out.push_str(&format!("\n{:<32} {:>10}\n", "EVENT TYPE", "EVENTS"));
+        for (event_type, count) in by_type {
+            out.push_str(&format!("{:<32} {:>10}\n", event_type, count));
+        }
+
+        out.push_str(&format!("\n{:<32} {:>10}\n", "ORIGIN", "EVENTS"));
+        for (origin, count) in &self.events_by_origin {
+            out.push_str(&format!("{:<32} {:>10}\n", origin, count));
+        }
+        out
+    }
+}
+
+// ============================================================================
+// Status
+// ============================================================================
+
+/// Everything `queue-keeper status` reports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceStatus {
+    pub endpoint: String,
+    pub health: ServiceHealth,
+    /// The local process recorded by `queue-keeper start`, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process: Option<ServiceProcess>,
+    #[serde(flatten)]
+    pub statistics: ServiceStatistics,
+}
+
+impl ServiceStatus {
+    /// Render the status as human-readable text: liveness, version and
+    /// local process ahead of the statistics.
+    pub fn to_text(&self, verbose: bool) -> String {
+        let mut rows = vec![
+            ("Service", self.endpoint.clone()),
+            ("Liveness", self.health.status.clone()),
+            ("Version", self.health.version.clone()),
+        ];
+        if let Some(process) = &self.process {
+            rows.push((
+                "Process",
+                format!("PID {} since {}", process.pid, process.started_at),
+            ));
+        }
+        rows.extend(self.statistics.summary_rows());
+        let mut out = format_rows(rows);
+        if verbose {
+            out.push_str(&self.statistics.breakdown());
+        }
+        out
+    }
+}
+
+/// One `Label:  value` line per row, values aligned.
+fn format_rows(rows: Vec<(&str, String)>) -> String {
+    rows.into_iter()
+        .map(|(label, value)| format!("{:<14} {}\n", format!("{}:", label), value))
+        .collect()
+}
+
+/// Uptime as days, hours, minutes and seconds, e.g. `1d 2h 3m 4s`.
+fn format_uptime(seconds: u64) -> String {
+    let (days, hours, minutes, seconds) = (
+        seconds / 86_400,
+        seconds % 86_400 / 3_600,
+        seconds % 3_600 / 60,
+        seconds % 60,
+    );
+    if days > 0 {
+        format!("{}d {}h {}m {}s", days, hours, minutes, seconds)
+    } else if hours > 0 {
+        format!("{}h {}m {}s", hours, minutes, seconds)
+    } else if minutes > 0 {
+        format!("{}m {}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+// ============================================================================
+// Service Client
+// ============================================================================
+
+/// Reads the statistics of a Queue-Keeper service through its API.
+pub struct ApiStatus {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl ApiStatus {
+    /// Create a client for the service at `base_url`.
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Liveness and version of the service.
+    ///
+    /// # Errors
+    /// Returns [`StatusError::Unreachable`] when the service does not answer,
+    /// and [`StatusError::Request`] when it answers without a health body.
+    pub async fn health(&self) -> Result<ServiceHealth, StatusError> {
+        let url = format!("{}/health", self.base_url);
+        debug!(url = %url, "Probing service health");
+
+        let response =
+            self.client
+                .get(&url)
+                .send()
+                .await
+                .map_err(|e| StatusError::Unreachable {
+                    endpoint: self.base_url.clone(),
+                    message: e.to_string(),
+                })?;
+        let status = response.status();
+        response.json().await.map_err(|e| StatusError::Request {
+            message: format!(
+                "GET {}: HTTP {}: invalid health body: {}",
+                url,
+                status.as_u16(),
+                e
+            ),
+        })
+    }
+
+    /// Current statistics of the service.
+    pub async fn statistics(&self) -> Result<ServiceStatistics, StatusError> {
+        let url = format!("{}/api/stats", self.base_url);
+        debug!(url = %url, "Requesting service statistics");
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| StatusError::Request {
+                message: format!("GET {}: {}", url, e),
+            })?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(StatusError::Request {
+                message: format!("GET {}: HTTP {}", url, status.as_u16()),
+            });
+        }
+        response.json().await.map_err(|e| StatusError::Request {
+            message: format!("GET {}: invalid response body: {}", url, e),
+        })
+    }
+}
+
+#[cfg(test)]
+#[path = "status_tests.rs"]
+mod tests;
@@ -0,0 +1,111 @@
+//! Tests for the status command.
+
+use super::*;
+
+fn statistics() -> ServiceStatistics {
+    serde_json::from_value(serde_json::json!({
+        "total_events": 12500,
+        "events_per_hour": 145.8,
+        "active_sessions": 42,
+        "error_rate": 0.0016,
+        "uptime_seconds": 93784,
+        "events_by_type": {"push": 2500, "pull_request": 10000},
+        "events_by_origin": {"github": 12500, "replay": 37}
+    }))
+    .unwrap()
+}
+
+/// A service response deserializes, with the breakdowns optional.
+#[test]
+fn test_statistics_parse_service_response() {
+    let stats = statistics();
+    assert_eq!(stats.total_events, 12500);
+    assert_eq!(stats.events_by_type["pull_request"], 10000);
+    assert_eq!(stats.events_by_origin["replay"], 37);
+
+    let stats: ServiceStatistics = serde_json::from_str(
+        r#"{"total_events":0,"events_per_hour":0.0,"active_sessions":0,"error_rate":0.0,"uptime_seconds":5}"#,
+    )
+    .unwrap();
+    assert!(stats.events_by_type.is_empty());
+}
+
+/// The summary is always shown; the breakdown only when verbose, busiest
+/// event type first.
+#[test]
+fn test_statistics_text() {
+    let stats = statistics();
+
+    let text = stats.to_text("http://localhost:8080", false);
+    assert!(text.contains("Service:       http://localhost:8080\n"));
+    assert!(text.contains("Uptime:        1d 2h 3m 4s\n"));
+    assert!(text.contains("Events/hour:   146\n"));
+    assert!(text.contains("Error rate:    0.16%\n"));
+    assert!(text.contains("Sessions:      42 active\n"));
+    assert!(!text.contains("EVENT TYPE"));
+
+    let text = stats.to_text("http://localhost:8080", true);
+    let pull_request = text.find("pull_request").unwrap();
+    let push = text.find("push ").unwrap();
+    assert!(pull_request < push);
+    assert!(text.contains("ORIGIN"));
+    assert!(text.contains("replay"));
+}
+
+/// Uptime is shown with the largest non-zero unit first.
+#[test]
+fn test_format_uptime() {
+    assert_eq!(format_uptime(5), "5s");
+    assert_eq!(format_uptime(65), "1m 5s");
+    assert_eq!(format_uptime(3_665), "1h 1m 5s");
+    assert_eq!(format_uptime(86_400), "1d 0h 0m 0s");
+}
+
+/// The health body is read for its status and version; the checks are
+/// ignored.
+#[test]
+fn test_health_parse_service_response() {
+    let health: ServiceHealth = serde_json::from_value(serde_json::json!({
+        "status": "unhealthy",
+        "timestamp": "2026-01-01T00:00:00Z",
+        "checks": {"queue": {"healthy": false, "message": "unreachable"}},
+        "version": "0.2.0"
+    }))
+    .unwrap();
+    assert_eq!(health.version, "0.2.0");
+    assert!(!health.is_healthy());
+}
+
+/// Liveness, version and the local process lead the statistics; the
+/// statistics are flattened into the structured output.
+#[test]
+fn test_status_text_and_json() {
+    let status = ServiceStatus {
+        endpoint: "http://localhost:8080".to_string(),
+        health: ServiceHealth {
+            status: "healthy".to_string(),
+            version: "0.2.0".to_string(),
+        },
+        process: Some(ServiceProcess {
+            pid: 4242,
+            started_at: queue_keeper_core::Timestamp::now(),
+            host: "0.0.0.0".to_string(),
+            port: 8080,
+            log_file: None,
+        }),
+        statistics: statistics(),
+    };
+
+    let text = status.to_text(false);
+    assert!(text.starts_with("Service:       http://localhost:8080\nLiveness:      healthy\n"));
+    assert!(text.contains("Version:       0.2.0\n"));
+    assert!(text.contains("Process:       PID 4242 since "));
+    assert!(text.contains("Uptime:        1d 2h 3m 4s\n"));
+    assert!(!text.contains("EVENT TYPE"));
+    assert!(status.to_text(true).contains("EVENT TYPE"));
+
+    let json = serde_json::to_value(&status).unwrap();
+    assert_eq!(json["health"]["status"], "healthy");
+    assert_eq!(json["process"]["pid"], 4242);
+    assert_eq!(json["uptime_seconds"], 93784);
+}
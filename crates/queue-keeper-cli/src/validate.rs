@@ -0,0 +1,562 @@
+//! # Configuration Validation
+//!
+//! Implements `queue-keeper config`, which checks a service or bot
+//! configuration file the way the service does before it would start on it.
+//!
+//! A file with a top-level `bots` key is a bot configuration, anything else
+//! a service configuration. Validation runs in three passes, each reporting
+//! where in the file the problem is:
+//!
+//! 1. **Syntax**: the file is parsed as YAML, JSON or TOML, following its
+//!    extension (none means YAML, as for the service).
+//! 2. **Schema**: the file is layered over the built-in defaults and read
+//!    into the service types, so a setting of the wrong type or with an
+//!    unknown value names its field. Unknown top-level sections, which the
+//!    service silently ignores, are reported as warnings.
+//! 3. **Semantics**: the service's own validation runs on the result:
+//!    `ServiceConfig::validate` for providers, queue backend, storage and
+//!    the other sections, `BotConfiguration::validate` for bot names, queue
+//!    names and subscriptions.
+//!
+//! Environment overrides (`QK__*`, `QUEUE_KEEPER__*`) and the other files
+//! the service layers are not applied: the report is about this file.
+//!
+//! The resolved configuration, defaults included, can be rendered as YAML,
+//! JSON or TOML; secrets are redacted as in `GET /admin/config`.
+
+use crate::ConfigFormat;
+use queue_keeper_api::config::RedactedServiceConfig;
+use queue_keeper_api::ServiceConfig;
+use queue_keeper_core::bot_config::{BotConfigError, BotConfiguration};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors validating a configuration file, other than problems in the file.
+#[derive(Debug, thiserror::Error)]
+pub enum ValidateError {
+    #[error("Cannot read {}: {message}", path.display())]
+    Read { path: PathBuf, message: String },
+
+    #[error("{}: unsupported extension; use .toml, .yaml, .yml or .json", path.display())]
+    UnsupportedFormat { path: PathBuf },
+
+    #[error("{}: {errors} configuration error(s)", path.display())]
+    Invalid { path: PathBuf, errors: usize },
+
+    #[error("Cannot render configuration: {message}")]
+    Render { message: String },
+}
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+/// File formats of configuration files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileFormat {
+    Yaml,
+    Json,
+    Toml,
+}
+
+impl FileFormat {
+    /// Format of `path` by extension; no extension means YAML.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            None | Some("yaml" | "yml") => Some(Self::Yaml),
+            Some("json") => Some(Self::Json),
+            Some("toml") => Some(Self::Toml),
+            Some(_) => None,
+        }
+    }
+
+    fn config_format(self) -> config::FileFormat {
+        match self {
+            Self::Yaml => config::FileFormat::Yaml,
+            Self::Json => config::FileFormat::Json,
+            Self::Toml => config::FileFormat::Toml,
+        }
+    }
+
+    /// Whether `line` sets `key`, or opens a TOML table ending in `key`.
+    fn declares_key(self, line: &str, key: &str) -> bool {
+        let line = line.trim_start();
+        match self {
+            Self::Yaml => line
+                .trim_start_matches("- ")
+                .strip_prefix(key)
+                .is_some_and(|rest| rest.starts_with(':')),
+            Self::Json => line.starts_with(&format!("\"{}\"", key)),
+            Self::Toml => {
+                if line.starts_with('[') {
+                    let table =
+                        line.trim_matches(|c: char| c == '[' || c == ']' || c.is_whitespace());
+                    table.rsplit('.').next() == Some(key)
+                } else {
+                    line.strip_prefix(key)
+                        .is_some_and(|rest| rest.trim_start().starts_with('='))
+                }
+            }
+        }
+    }
+}
+
+/// How serious a diagnostic is; only errors fail the validation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A problem found in a configuration file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigDiagnostic {
+    pub severity: Severity,
+    /// 1-based line the problem was found on, when known.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// 1-based column, for syntax errors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+    /// Dotted path of the offending setting, e.g. `server.port`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    pub message: String,
+}
+
+impl ConfigDiagnostic {
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            line: None,
+            column: None,
+            field: None,
+            message: message.into(),
+        }
+    }
+
+    /// Render as `path:line:column: severity: field: message`.
+    pub fn to_text(&self, path: &Path) -> String {
+        let mut location = path.display().to_string();
+        if let Some(line) = self.line {
+            location.push_str(&format!(":{}", line));
+            if let Some(column) = self.column {
+                location.push_str(&format!(":{}", column));
+            }
+        }
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        match &self.field {
+            Some(field) => format!("{}: {}: {}: {}", location, severity, field, self.message),
+            None => format!("{}: {}: {}", location, severity, self.message),
+        }
+    }
+}
+
+// ============================================================================
+// Report
+// ============================================================================
+
+/// Kinds of configuration files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigKind {
+    Service,
+    Bots,
+}
+
+/// The outcome of validating one configuration file.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ConfigReport {
+    pub path: PathBuf,
+    pub kind: ConfigKind,
+    pub diagnostics: Vec<ConfigDiagnostic>,
+    /// The configuration with defaults applied and secrets redacted; `None`
+    /// when the file could not be read into the service types.
+    #[serde(skip)]
+    pub resolved: Option<serde_json::Value>,
+}
+
+impl ConfigReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors() == 0
+    }
+
+    pub fn errors(&self) -> usize {
+        self.diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Error)
+            .count()
+    }
+
+    /// One line per diagnostic, then a verdict.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        for diagnostic in &self.diagnostics {
+            out.push_str(&diagnostic.to_text(&self.path));
+            out.push('\n');
+        }
+        let kind = match self.kind {
+            ConfigKind::Service => "service",
+            ConfigKind::Bots => "bot",
+        };
+        if self.is_valid() {
+            out.push_str(&format!(
+                "{}: valid {} configuration\n",
+                self.path.display(),
+                kind
+            ));
+        } else {
+            out.push_str(&format!(
+                "{}: invalid {} configuration ({} error(s))\n",
+                self.path.display(),
+                kind,
+                self.errors()
+            ));
+        }
+        out
+    }
+
+    /// The resolved configuration in `format`.
+    ///
+    /// # Errors
+    /// Returns an error when there is no resolved configuration or it cannot
+    /// be expressed in `format`.
+    pub fn render(&self, format: ConfigFormat) -> Result<String, ValidateError> {
+        let value = self
+            .resolved
+            .as_ref()
+            .ok_or_else(|| ValidateError::Render {
+                message: "the configuration has errors".to_string(),
+            })?;
+        let rendered = match format {
+            ConfigFormat::Yaml => serde_yaml::to_string(value).map_err(|e| e.to_string()),
+            ConfigFormat::Json => serde_json::to_string_pretty(value)
+                .map(|json| json + "\n")
+                .map_err(|e| e.to_string()),
+            // TOML has no null; unset optional settings are left out.
+            ConfigFormat::Toml => {
+                toml::to_string(&without_nulls(value.clone())).map_err(|e| e.to_string())
+            }
+        };
+        rendered.map_err(|message| ValidateError::Render { message })
+    }
+}
+
+/// Drop null values from objects and arrays.
+fn without_nulls(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(object) => object
+            .into_iter()
+            .filter(|(_, value)| !value.is_null())
+            .map(|(key, value)| (key, without_nulls(value)))
+            .collect(),
+        serde_json::Value::Array(items) => items
+            .into_iter()
+            .filter(|value| !value.is_null())
+            .map(without_nulls)
+            .collect(),
+        other => other,
+    }
+}
+
+// ============================================================================
+// Validation
+// ============================================================================
+
+/// Validate the configuration file at `path`.
+///
+/// # Errors
+/// Returns an error when the file cannot be read or has an unsupported
+/// extension. Problems in the file are diagnostics of the report.
+pub fn validate_file(path: &Path) -> Result<ConfigReport, ValidateError> {
+    let format = FileFormat::from_path(path).ok_or_else(|| ValidateError::UnsupportedFormat {
+        path: path.to_path_buf(),
+    })?;
+    let content = std::fs::read_to_string(path).map_err(|e| ValidateError::Read {
+        path: path.to_path_buf(),
+        message: e.to_string(),
+    })?;
+    Ok(validate_str(path, &content, format))
+}
+
+/// Validate `content`, read from `path` in `format`.
+pub fn validate_str(path: &Path, content: &str, format: FileFormat) -> ConfigReport {
+    let mut report = ConfigReport {
+        path: path.to_path_buf(),
+        kind: ConfigKind::Service,
+        diagnostics: Vec::new(),
+        resolved: None,
+    };
+    let document = match parse(content, format) {
+        Ok(document) => document,
+        Err(diagnostic) => {
+            report.diagnostics.push(diagnostic);
+            return report;
+        }
+    };
+    if document.get("bots").is_some() {
+        report.kind = ConfigKind::Bots;
+        validate_bots(content, format, &mut report);
+    } else {
+        validate_service(content, format, &document, &mut report);
+    }
+    report
+}
+
+/// Parse `content` into a JSON value, reporting syntax errors with their
+/// position.
+fn parse(content: &str, format: FileFormat) -> Result<serde_json::Value, ConfigDiagnostic> {
+    let syntax_error = |message: String, line, column| ConfigDiagnostic {
+        line,
+        column,
+        ..ConfigDiagnostic::error(without_position(&message))
+    };
+    match format {
+        // Read as YAML first: bot event patterns are YAML tags (`!Exact`),
+        // which have no JSON equivalent to deserialize into.
+        FileFormat::Yaml => serde_yaml::from_str::<serde_yaml::Value>(content)
+            .map_err(|e| {
+                let location = e.location();
+                syntax_error(
+                    e.to_string(),
+                    location.as_ref().map(|l| l.line()),
+                    location.as_ref().map(|l| l.column()),
+                )
+            })
+            .and_then(|value| {
+                serde_json::to_value(value).map_err(|e| syntax_error(e.to_string(), None, None))
+            }),
+        FileFormat::Json => serde_json::from_str(content)
+            .map_err(|e| syntax_error(e.to_string(), Some(e.line()), Some(e.column()))),
+        FileFormat::Toml => toml::from_str::<toml::Table>(content)
+            .map_err(|e| {
+                let (line, column) = e.span().map(|span| position(content, span.start)).unzip();
+                syntax_error(e.message().to_string(), line, column)
+            })
+            .and_then(|table| {
+                serde_json::to_value(table).map_err(|e| syntax_error(e.to_string(), None, None))
+            }),
+    }
+}
+
+/// 1-based line and column of byte `offset` in `content`.
+fn position(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.len() - before.rfind('\n').map_or(0, |i| i + 1) + 1;
+    (line, column)
+}
+
+/// `message` without the ` at line L column C` the parsers append.
+fn without_position(message: &str) -> String {
+    match message.find(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message.to_string(),
+    }
+}
+
+fn validate_service(
+    content: &str,
+    format: FileFormat,
+    document: &serde_json::Value,
+    report: &mut ConfigReport,
+) {
+    let defaults = serde_json::to_value(ServiceConfig::default())
+        .expect("ServiceConfig::default() must be JSON-serialisable");
+
+    // Layered over the defaults like the service does, so a partial section
+    // is read the same way.
+    let loaded = config::Config::builder()
+        .add_source(config::File::from_str(
+            &defaults.to_string(),
+            config::FileFormat::Json,
+        ))
+        .add_source(config::File::from_str(content, format.config_format()))
+        .build()
+        .and_then(|config| config.try_deserialize::<ServiceConfig>());
+    let config = match loaded {
+        Ok(config) => config,
+        Err(e) => {
+            let message = e.to_string();
+            let (message, field) = match message.split_once(" for key `") {
+                Some((message, rest)) => (
+                    message.to_string(),
+                    rest.split('`').next().map(str::to_string),
+                ),
+                None => (message, None),
+            };
+            report.diagnostics.push(ConfigDiagnostic {
+                line: field.as_deref().and_then(|f| locate(content, format, f)),
+                field,
+                ..ConfigDiagnostic::error(message)
+            });
+            return;
+        }
+    };
+
+    // Every section the service reads appears in its serialized
+    // configuration, set or not.
+    let known = serde_json::to_value(&config).expect("ServiceConfig must be serialisable");
+    if let Some(sections) = document.as_object() {
+        for section in sections.keys() {
+            if known.get(section).is_none() {
+                report.diagnostics.push(ConfigDiagnostic {
+                    severity: Severity::Warning,
+                    line: locate(content, format, section),
+                    field: Some(section.clone()),
+                    ..ConfigDiagnostic::error("unknown section; the service ignores it")
+                });
+            }
+        }
+    }
+
+    if let Err(e) = config.validate() {
+        let message = e.to_string();
+        let field = setting_in(&message, &known);
+        report.diagnostics.push(ConfigDiagnostic {
+            line: field.as_deref().and_then(|f| locate(content, format, f)),
+            field,
+            ..ConfigDiagnostic::error(message)
+        });
+        return;
+    }
+    report.resolved = Some(RedactedServiceConfig(&config).to_value());
+}
+
+fn validate_bots(content: &str, format: FileFormat, report: &mut ConfigReport) {
+    let parsed = match format {
+        FileFormat::Yaml => serde_yaml::from_str::<BotConfiguration>(content).map_err(|e| {
+            let location = e.location();
+            ConfigDiagnostic {
+                line: location.as_ref().map(|l| l.line()),
+                column: location.as_ref().map(|l| l.column()),
+                ..ConfigDiagnostic::error(without_position(&e.to_string()))
+            }
+        }),
+        FileFormat::Json => {
+            serde_json::from_str::<BotConfiguration>(content).map_err(|e| ConfigDiagnostic {
+                line: Some(e.line()),
+                column: Some(e.column()),
+                ..ConfigDiagnostic::error(without_position(&e.to_string()))
+            })
+        }
+        FileFormat::Toml => Err(ConfigDiagnostic::error(
+            "the service reads bot configurations from YAML or JSON files only",
+        )),
+    };
+    let bots = match parsed {
+        Ok(bots) => bots,
+        Err(diagnostic) => {
+            report.diagnostics.push(diagnostic);
+            return;
+        }
+    };
+
+    match bots.validate() {
+        Ok(()) => {
+            report.resolved =
+                Some(serde_json::to_value(&bots).expect("BotConfiguration must be serialisable"));
+        }
+        Err(BotConfigError::ValidationError { errors }) => {
+            for message in errors {
+                report
+                    .diagnostics
+                    .push(bot_diagnostic(content, format, &bots, message));
+            }
+        }
+        Err(e) => report
+            .diagnostics
+            .push(ConfigDiagnostic::error(e.to_string())),
+    }
+}
+
+/// Diagnostic for a bot validation message, attributed to the bot it
+/// names (`Bot 'labeler': ...`).
+fn bot_diagnostic(
+    content: &str,
+    format: FileFormat,
+    bots: &BotConfiguration,
+    message: String,
+) -> ConfigDiagnostic {
+    let named = message
+        .strip_prefix("Bot '")
+        .and_then(|rest| rest.split_once("': "));
+    let Some((name, detail)) = named else {
+        return ConfigDiagnostic::error(message);
+    };
+    let index = bots.bots.iter().position(|bot| bot.name.as_str() == name);
+    ConfigDiagnostic {
+        line: locate_bot(content, format, name),
+        field: index.map(|i| format!("bots[{}]", i)),
+        ..ConfigDiagnostic::error(detail)
+    }
+}
+
+/// The first dotted setting path in `message` that starts with a known
+/// section, e.g. `queue_depth.sample_interval`.
+fn setting_in(message: &str, known: &serde_json::Value) -> Option<String> {
+    message
+        .split_whitespace()
+        .map(|word| {
+            word.trim_matches(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == ']'))
+        })
+        .find(|word| {
+            word.contains('.')
+                && word.chars().all(|c| {
+                    c.is_ascii_lowercase()
+                        || c.is_ascii_digit()
+                        || matches!(c, '_' | '.' | '[' | ']')
+                })
+                && word
+                    .split(['.', '['])
+                    .next()
+                    .is_some_and(|section| known.get(section).is_some())
+        })
+        .map(str::to_string)
+}
+
+/// Line of the setting at dotted `path`, found by following its keys down
+/// the file; the line of the deepest key present when the rest is defaulted.
+fn locate(content: &str, format: FileFormat, path: &str) -> Option<usize> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut found = None;
+    let mut from = 0;
+    for key in path
+        .split('.')
+        .map(|key| key.split('[').next().unwrap_or(key))
+    {
+        match (from..lines.len()).find(|&i| format.declares_key(lines[i], key)) {
+            Some(i) => {
+                found = Some(i + 1);
+                from = i + 1;
+            }
+            None => break,
+        }
+    }
+    found
+}
+
+/// Line declaring the bot called `name`.
+fn locate_bot(content: &str, format: FileFormat, name: &str) -> Option<usize> {
+    content
+        .lines()
+        .position(|line| {
+            format.declares_key(line, "name")
+                && line
+                    .split_once([':', '='])
+                    .is_some_and(|(_, value)| value.trim().trim_matches(['"', '\'', ',']) == name)
+        })
+        .map(|i| i + 1)
+}
+
+#[cfg(test)]
+#[path = "validate_tests.rs"]
+mod tests;
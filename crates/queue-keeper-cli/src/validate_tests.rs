@@ -0,0 +1,160 @@
+//! Tests for configuration validation.
+
+use super::*;
+use queue_keeper_core::{
+    bot_config::{BotConfigurationSettings, BotSpecificConfig, BotSubscription, EventTypePattern},
+    BotName, QueueName,
+};
+
+fn validate(name: &str, content: &str) -> ConfigReport {
+    let path = PathBuf::from(name);
+    validate_str(&path, content, FileFormat::from_path(&path).unwrap())
+}
+
+fn bot(name: &str, events: &[&str]) -> BotSubscription {
+    BotSubscription {
+        name: BotName::new(name).unwrap(),
+        queue: QueueName::new(format!("queue-keeper-{}", name)).unwrap(),
+        events: events
+            .iter()
+            .map(|e| EventTypePattern::Exact(e.to_string()))
+            .collect(),
+        ordered: false,
+        repository_filter: None,
+        config: BotSpecificConfig::new(),
+        encryption: None,
+        transform: None,
+        projection: None,
+        http: None,
+        sender_filter: None,
+    }
+}
+
+/// A file overriding a few settings is valid and resolves with defaults.
+#[test]
+fn test_valid_service_config_resolves_defaults() {
+    let report = validate("service.yaml", "server:\n  port: 9090\n");
+
+    assert!(report.is_valid(), "{:?}", report.diagnostics);
+    assert_eq!(report.kind, ConfigKind::Service);
+    let resolved = report.resolved.as_ref().unwrap();
+    assert_eq!(resolved["server"]["port"], 9090);
+    assert!(resolved["server"]["host"].is_string());
+    assert!(report
+        .to_text()
+        .ends_with("service.yaml: valid service configuration\n"));
+}
+
+/// Syntax errors carry the position the parser reports.
+#[test]
+fn test_syntax_error_has_position() {
+    let report = validate("service.yaml", "server:\n  port: [9090\n");
+    assert_eq!(report.errors(), 1);
+    assert!(report.diagnostics[0].line.is_some());
+    assert!(!report.diagnostics[0].message.contains(" at line "));
+
+    let report = validate(
+        "service.json",
+        "{\n  \"server\": {\n    \"port\": 9090,\n  }\n}",
+    );
+    assert_eq!(report.diagnostics[0].line, Some(4));
+
+    let report = validate("service.toml", "[server]\nport = = 9090\n");
+    assert_eq!(report.diagnostics[0].line, Some(2));
+}
+
+/// A setting of the wrong type names its field and line.
+#[test]
+fn test_type_error_names_field() {
+    let report = validate(
+        "service.yaml",
+        "server:\n  host: 127.0.0.1\n  port: not-a-port\n",
+    );
+
+    assert_eq!(report.errors(), 1);
+    let diagnostic = &report.diagnostics[0];
+    assert_eq!(diagnostic.field.as_deref(), Some("server.port"));
+    assert_eq!(diagnostic.line, Some(3));
+    assert!(report.resolved.is_none());
+}
+
+/// The service's own validation runs, and its error is placed on the
+/// setting it names.
+#[test]
+fn test_semantic_error_is_located() {
+    let report = validate(
+        "service.toml",
+        "[server]\nport = 9090\n\n[queue_depth]\nenabled = true\nsample_interval = \"100ms\"\n",
+    );
+
+    assert_eq!(report.errors(), 1);
+    let diagnostic = &report.diagnostics[0];
+    assert_eq!(
+        diagnostic.field.as_deref(),
+        Some("queue_depth.sample_interval")
+    );
+    assert_eq!(diagnostic.line, Some(6));
+    assert_eq!(
+        diagnostic.to_text(&report.path),
+        format!(
+            "service.toml:6: error: queue_depth.sample_interval: {}",
+            diagnostic.message
+        )
+    );
+}
+
+/// Unknown sections are warnings; the file stays valid.
+#[test]
+fn test_unknown_section_is_a_warning() {
+    let report = validate("service.yaml", "server:\n  port: 9090\nservr:\n  port: 1\n");
+
+    assert!(report.is_valid());
+    assert_eq!(report.diagnostics.len(), 1);
+    assert_eq!(report.diagnostics[0].severity, Severity::Warning);
+    assert_eq!(report.diagnostics[0].field.as_deref(), Some("servr"));
+    assert_eq!(report.diagnostics[0].line, Some(3));
+}
+
+/// Bot validation errors are attributed to the bot they name.
+#[test]
+fn test_bot_errors_are_attributed_to_bots() {
+    let bots = BotConfiguration {
+        bots: vec![bot("labeler", &["issues"]), bot("assigner", &[])],
+        settings: BotConfigurationSettings::default(),
+    };
+    let content = serde_yaml::to_string(&bots).unwrap();
+    let line = content
+        .lines()
+        .position(|line| line.contains("name: assigner"))
+        .unwrap()
+        + 1;
+
+    let report = validate("bots.yaml", &content);
+
+    assert_eq!(report.kind, ConfigKind::Bots);
+    assert_eq!(report.errors(), 1);
+    assert_eq!(report.diagnostics[0].field.as_deref(), Some("bots[1]"));
+    assert_eq!(report.diagnostics[0].line, Some(line));
+    assert!(report
+        .to_text()
+        .contains("invalid bot configuration (1 error(s))"));
+}
+
+/// The resolved configuration renders in every format, secrets redacted.
+#[test]
+fn test_render_redacts_secrets() {
+    let report = validate(
+        "service.yaml",
+        "providers:\n  - id: github\n    require_signature: true\n    secret:\n      type: literal\n      value: hunter2\n",
+    );
+    assert!(report.is_valid(), "{:?}", report.diagnostics);
+
+    for format in [ConfigFormat::Yaml, ConfigFormat::Json, ConfigFormat::Toml] {
+        let rendered = report.render(format.clone()).unwrap();
+        assert!(rendered.contains("github"), "{:?}", format);
+        assert!(!rendered.contains("hunter2"), "{:?}", format);
+    }
+
+    let invalid = validate("service.yaml", "server:\n  port: not-a-port\n");
+    assert!(invalid.render(ConfigFormat::Yaml).is_err());
+}
@@ -0,0 +1,714 @@
+//! # Azure Blob Storage Adapter
+//!
+//! Production implementation of the BlobStorage trait on Azure Blob Storage.
+//!
+//! - Authenticates with `DefaultAzureCredential`, so the service's managed
+//!   identity is used in Azure and the developer's Azure CLI login locally.
+//! - Creates the container on startup when it does not exist.
+//! - Uploads payloads up to `block_size_bytes` with a single Put Blob call
+//!   and larger ones block by block with Put Block / Put Block List.
+//! - Writes each blob once: uploads carry `If-None-Match: *`, so a blob is
+//!   never overwritten.
+//! - Compresses payload bodies with the configured `compression`, and
+//!   encrypts them when built [`with_encryption`](AzureBlobStorage::with_encryption);
+//!   bodies are decrypted and decompressed on read whatever the current
+//!   settings.
+//!
+//! # Tamper detection
+//!
+//! Every blob carries the SHA-256 hash of its content and of the webhook body
+//! in its blob metadata, next to the body hash stored in the document itself.
+//! Reads recompute both and fail with `ChecksumMismatch` when either differs,
+//! so a blob edited outside Queue-Keeper is detected rather than replayed.
+//!
+//! # Immutability
+//!
+//! Audit retention is enforced by a time-based retention policy (WORM) on the
+//! container, configured by the infrastructure that owns the storage account.
+//! While the policy holds, Azure rejects changes to blob content and metadata
+//! and deletes are reported as `PermissionDenied`.
+
+use crate::blob_storage::*;
+use crate::payload_encryption::{open_stored, PayloadEncryptor};
+use crate::storage_format::{check_format_version, CURRENT_FORMAT_VERSION};
+use crate::{EventId, Timestamp};
+use async_trait::async_trait;
+use azure_core::auth::TokenCredential;
+use azure_core::request_options::{IfMatchCondition, Metadata};
+use azure_identity::DefaultAzureCredential;
+use azure_storage::StorageCredentials;
+use azure_storage_blobs::prelude::*;
+use bytes::Bytes;
+use futures::stream::StreamExt;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::str::FromStr;
+use std::sync::Arc;
+use tracing::{debug, info, instrument, warn};
+
+/// Content type of stored blobs
+const CONTENT_TYPE: &str = "application/json";
+
+/// Prefix shared by all payload blobs (see [`EventId::to_blob_path`])
+const BLOB_PREFIX: &str = "webhook-payloads/";
+
+/// Blob metadata key holding the SHA-256 hash of the blob content
+const META_CONTENT_SHA256: &str = "content_sha256";
+
+/// Blob metadata key holding the SHA-256 hash of the webhook body
+const META_PAYLOAD_SHA256: &str = "payload_sha256";
+
+/// Blob metadata key holding the storage format version
+const META_FORMAT_VERSION: &str = "format_version";
+
+/// Blob metadata key holding the GitHub event type
+const META_EVENT_TYPE: &str = "event_type";
+
+/// Blob metadata key holding the repository full name
+const META_REPOSITORY: &str = "repository";
+
+/// Blob metadata key holding when the webhook was received (RFC 3339)
+const META_RECEIVED_AT: &str = "received_at";
+
+/// Default size of upload blocks, and the largest payload uploaded in one call
+pub const DEFAULT_BLOCK_SIZE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Largest block Azure Blob Storage accepts
+pub const MAX_BLOCK_SIZE_BYTES: usize = 4000 * 1024 * 1024;
+
+/// Azure Blob Storage adapter configuration
+///
+/// # Examples
+///
+/// ```
+/// use queue_keeper_core::adapters::AzureBlobConfiguration;
+///
+/// let config = AzureBlobConfiguration::new("qkaudit", "webhook-payloads");
+/// assert!(config.validate().is_ok());
+/// assert_eq!(
+///     config.container_url(),
+///     "https://qkaudit.blob.core.windows.net/webhook-payloads"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AzureBlobConfiguration {
+    /// Storage account name
+    pub account_name: String,
+
+    /// Container holding the payload blobs
+    pub container_name: String,
+
+    /// Create the container on startup when it does not exist
+    pub create_container: bool,
+
+    /// Size of upload blocks; larger payloads are uploaded in blocks
+    pub block_size_bytes: usize,
+
+    /// Compression of payload bodies written from now on
+    pub compression: ContentEncoding,
+}
+
+impl AzureBlobConfiguration {
+    /// Create configuration for a container with default upload settings
+    pub fn new(account_name: impl Into<String>, container_name: impl Into<String>) -> Self {
+        Self {
+            account_name: account_name.into(),
+            container_name: container_name.into(),
+            create_container: true,
+            block_size_bytes: DEFAULT_BLOCK_SIZE_BYTES,
+            compression: ContentEncoding::Identity,
+        }
+    }
+
+    /// URL of the container
+    pub fn container_url(&self) -> String {
+        format!(
+            "https://{}.blob.core.windows.net/{}",
+            self.account_name, self.container_name
+        )
+    }
+
+    /// Validate account and container names and the block size
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn validate(&self) -> Result<(), String> {
+        let account = &self.account_name;
+        if !(3..=24).contains(&account.len())
+            || !account
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit())
+        {
+            return Err(format!(
+                "account_name '{}' must be 3-24 lowercase letters and digits",
+                account
+            ));
+        }
+
+        let container = &self.container_name;
+        if !(3..=63).contains(&container.len())
+            || !container
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+            || container.starts_with('-')
+            || container.ends_with('-')
+            || container.contains("--")
+        {
+            return Err(format!(
+                "container_name '{}' must be 3-63 lowercase letters, digits and single hyphens, \
+                 starting and ending with a letter or digit",
+                container
+            ));
+        }
+
+        if self.block_size_bytes == 0 || self.block_size_bytes > MAX_BLOCK_SIZE_BYTES {
+            return Err(format!(
+                "block_size_bytes must be between 1 and {}",
+                MAX_BLOCK_SIZE_BYTES
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Azure Blob Storage implementation of [`BlobStorage`]
+///
+/// # Examples
+///
+/// ```no_run
+/// use queue_keeper_core::adapters::{AzureBlobConfiguration, AzureBlobStorage};
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let config = AzureBlobConfiguration::new("qkaudit", "webhook-payloads");
+/// let storage = AzureBlobStorage::new(config).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct AzureBlobStorage {
+    container: ContainerClient,
+    config: AzureBlobConfiguration,
+    encryption: Option<Arc<PayloadEncryptor>>,
+}
+
+impl AzureBlobStorage {
+    /// Connect with managed identity and create the container if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the configuration is invalid, no Azure credential is
+    /// available or the container cannot be created.
+    #[instrument]
+    pub async fn new(config: AzureBlobConfiguration) -> Result<Self, BlobStorageError> {
+        let credential = DefaultAzureCredential::create(Default::default()).map_err(|e| {
+            BlobStorageError::AuthenticationFailed {
+                message: format!("Failed to create Azure credential: {}", e),
+            }
+        })?;
+
+        Self::with_credential(config, Arc::new(credential)).await
+    }
+
+    /// Connect with a custom credential and create the container if needed
+    ///
+    /// Useful for testing or custom authentication scenarios.
+    ///
+    /// # Errors
+    ///
+    /// Returns error if the configuration is invalid or the container cannot
+    /// be created.
+    #[instrument(skip(credential))]
+    pub async fn with_credential(
+        config: AzureBlobConfiguration,
+        credential: Arc<dyn TokenCredential>,
+    ) -> Result<Self, BlobStorageError> {
+        config
+            .validate()
+            .map_err(|reason| BlobStorageError::InvalidPath {
+                path: format!("{}: {}", config.container_url(), reason),
+            })?;
+
+        info!(container_url = %config.container_url(), "Initializing Azure Blob Storage");
+
+        let credentials = StorageCredentials::token_credential(credential);
+        let container = ClientBuilder::new(config.account_name.clone(), credentials)
+            .container_client(config.container_name.clone());
+
+        let storage = Self {
+            container,
+            config,
+            encryption: None,
+        };
+        if storage.config.create_container {
+            storage.ensure_container().await?;
+        }
+        Ok(storage)
+    }
+
+    /// Encrypt payload bodies written from now on, and decrypt encrypted
+    /// ones on read
+    pub fn with_encryption(mut self, encryptor: Arc<PayloadEncryptor>) -> Self {
+        self.encryption = Some(encryptor);
+        self
+    }
+
+    /// Create the container unless it already exists
+    async fn ensure_container(&self) -> Result<(), BlobStorageError> {
+        match self.container.create().await {
+            Ok(_) => {
+                info!(container = %self.config.container_name, "Created blob container");
+                Ok(())
+            }
+            Err(e) if e.to_string().contains("ContainerAlreadyExists") => Ok(()),
+            Err(e) => Err(map_azure_error("create container", &e.to_string())),
+        }
+    }
+
+    /// Upload `content` as a new blob, in blocks when it is large
+    async fn upload(
+        &self,
+        blob: &BlobClient,
+        content: Bytes,
+        metadata: Metadata,
+    ) -> azure_core::Result<()> {
+        let write_once = IfMatchCondition::NotMatch("*".to_string());
+
+        if content.len() <= self.config.block_size_bytes {
+            blob.put_block_blob(content)
+                .content_type(CONTENT_TYPE)
+                .metadata(metadata)
+                .if_match(write_once)
+                .await?;
+            return Ok(());
+        }
+
+        let mut block_list = BlockList::default();
+        for (index, range) in block_ranges(content.len(), self.config.block_size_bytes)
+            .into_iter()
+            .enumerate()
+        {
+            let block_id = block_id(index);
+            blob.put_block(block_id.clone(), content.slice(range))
+                .await?;
+            block_list
+                .blocks
+                .push(BlobBlockType::new_uncommitted(block_id));
+        }
+
+        blob.put_block_list(block_list)
+            .content_type(CONTENT_TYPE)
+            .metadata(metadata)
+            .if_match(write_once)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl BlobStorage for AzureBlobStorage {
+    #[instrument(skip(self, payload))]
+    async fn store_payload(
+        &self,
+        event_id: &EventId,
+        payload: &WebhookPayload,
+    ) -> Result<BlobMetadata, BlobStorageError> {
+        let blob_path = event_id.to_blob_path();
+        let blob = self.container.blob_client(&blob_path);
+
+        let stored = StoredWebhook {
+            metadata: BlobMetadata {
+                event_id: *event_id,
+                blob_path: blob_path.clone(),
+                size_bytes: 0,
+                content_type: CONTENT_TYPE.to_string(),
+                created_at: Timestamp::now(),
+                // Checksum of the payload body (not the entire serialized
+                // JSON); encryption replaces it with that of the ciphertext
+                checksum_sha256: compute_checksum(&payload.body),
+                format_version: CURRENT_FORMAT_VERSION,
+                content_encoding: ContentEncoding::Identity,
+                encryption: None,
+                metadata: payload.metadata.clone(),
+            },
+            payload: payload.clone(),
+        }
+        .encode(self.config.compression)?;
+        let stored = match &self.encryption {
+            Some(encryptor) => encryptor.encrypt(stored).await?,
+            None => stored,
+        };
+
+        let content = Bytes::from(serde_json::to_vec_pretty(&stored).map_err(|e| {
+            BlobStorageError::SerializationFailed {
+                message: format!("Failed to serialize payload: {}", e),
+            }
+        })?);
+        let size_bytes = content.len() as u64;
+
+        let mut metadata = Metadata::new();
+        for (key, value) in blob_metadata(&stored, &compute_checksum(&content)) {
+            metadata.insert(key, value);
+        }
+
+        match self.upload(&blob, content, metadata).await {
+            Ok(()) => {
+                debug!(blob_path = %blob_path, size_bytes, "Stored payload blob");
+                Ok(BlobMetadata {
+                    size_bytes,
+                    ..stored.metadata
+                })
+            }
+            Err(e) if is_conflict(&e.to_string()) => {
+                // A retry of a write that already succeeded finds its own
+                // blob; anything else is an attempt to overwrite a payload.
+                // Encrypted bodies differ per write, so compare plaintexts.
+                match self.get_payload(event_id).await? {
+                    Some(existing) if existing.payload.body == payload.body => {
+                        Ok(existing.metadata)
+                    }
+                    _ => Err(BlobStorageError::PermissionDenied {
+                        operation: format!(
+                            "overwrite {} (payload blobs are write-once)",
+                            blob_path
+                        ),
+                    }),
+                }
+            }
+            Err(e) => Err(map_azure_error(
+                &format!("store {}", blob_path),
+                &e.to_string(),
+            )),
+        }
+    }
+
+    #[instrument(skip(self))]
+    async fn get_payload(
+        &self,
+        event_id: &EventId,
+    ) -> Result<Option<StoredWebhook>, BlobStorageError> {
+        let blob_path = event_id.to_blob_path();
+        let blob = self.container.blob_client(&blob_path);
+
+        let properties = match blob.get_properties().await {
+            Ok(properties) => properties,
+            Err(e) if is_not_found(&e.to_string()) => return Ok(None),
+            Err(e) => {
+                return Err(map_azure_error(
+                    &format!("read {}", blob_path),
+                    &e.to_string(),
+                ))
+            }
+        };
+        let content = Bytes::from(
+            blob.get_content()
+                .await
+                .map_err(|e| map_azure_error(&format!("read {}", blob_path), &e.to_string()))?,
+        );
+
+        verify_content(&blob_path, properties.blob.metadata.as_ref(), &content)?;
+
+        let stored: StoredWebhook = serde_json::from_slice(&content).map_err(|e| {
+            BlobStorageError::SerializationFailed {
+                message: format!("Failed to deserialize payload: {}", e),
+            }
+        })?;
+
+        // Blobs from the previous format are read as-is; newer ones are refused
+        check_format_version(stored.metadata.format_version).map_err(|_| {
+            BlobStorageError::UnsupportedFormatVersion {
+                path: blob_path.clone(),
+                version: stored.metadata.format_version,
+            }
+        })?;
+
+        // Verify checksum against the payload body (not the entire JSON)
+        let mut stored = open_stored(self.encryption.as_deref(), stored, &blob_path).await?;
+
+        stored.metadata.size_bytes = content.len() as u64;
+        Ok(Some(stored))
+    }
+
+    #[instrument(skip(self))]
+    async fn list_payloads(
+        &self,
+        filter: &PayloadFilter,
+    ) -> Result<Vec<BlobMetadata>, BlobStorageError> {
+        let offset = filter.offset.unwrap_or(0);
+        let wanted = filter.limit.map(|limit| offset.saturating_add(limit));
+        let mut results = Vec::new();
+
+        // Blob names sort by time, so listing stops once enough matched
+        let mut pages = self
+            .container
+            .list_blobs()
+            .prefix(BLOB_PREFIX)
+            .include_metadata(true)
+            .into_stream();
+        'pages: while let Some(page) = pages.next().await {
+            let page = page.map_err(|e| map_azure_error("list payloads", &e.to_string()))?;
+            for listed in page.blobs.blobs() {
+                if !listing_may_match(filter, listed.metadata.as_ref()) {
+                    continue;
+                }
+                let Some(event_id) = event_id_from_blob_path(&listed.name) else {
+                    continue;
+                };
+
+                let stored = match self.get_payload(&event_id).await {
+                    Ok(Some(stored)) => stored,
+                    Ok(None) => continue,
+                    Err(e) if e.is_transient() => return Err(e),
+                    Err(e) => {
+                        warn!(blob_path = %listed.name, error = %e, "Skipping unreadable payload blob");
+                        continue;
+                    }
+                };
+
+                if payload_matches(filter, &stored.payload.metadata) {
+                    results.push(stored.metadata);
+                    if wanted.is_some_and(|wanted| results.len() >= wanted) {
+                        break 'pages;
+                    }
+                }
+            }
+        }
+
+        let mut results: Vec<_> = results.into_iter().skip(offset).collect();
+        if let Some(limit) = filter.limit {
+            results.truncate(limit);
+        }
+        Ok(results)
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_payload(&self, event_id: &EventId) -> Result<(), BlobStorageError> {
+        let blob_path = event_id.to_blob_path();
+        match self.container.blob_client(&blob_path).delete().await {
+            Ok(_) => Ok(()),
+            Err(e) if is_not_found(&e.to_string()) => Err(BlobStorageError::BlobNotFound {
+                event_id: *event_id,
+            }),
+            Err(e) => Err(map_azure_error(
+                &format!("delete {}", blob_path),
+                &e.to_string(),
+            )),
+        }
+    }
+
+    async fn health_check(&self) -> Result<StorageHealthStatus, BlobStorageError> {
+        let started = std::time::Instant::now();
+        let result = self.container.get_properties().await;
+        let latency_ms = started.elapsed().as_secs_f64() * 1000.0;
+
+        Ok(match result {
+            Ok(_) => StorageHealthStatus {
+                healthy: true,
+                connected: true,
+                last_success: Some(Timestamp::now()),
+                error_message: None,
+                metrics: StorageMetrics {
+                    avg_write_latency_ms: 0.0,
+                    avg_read_latency_ms: latency_ms,
+                    success_rate: 1.0,
+                },
+            },
+            Err(e) => StorageHealthStatus {
+                healthy: false,
+                connected: false,
+                last_success: None,
+                error_message: Some(
+                    map_azure_error("read container properties", &e.to_string()).to_string(),
+                ),
+                metrics: StorageMetrics {
+                    avg_write_latency_ms: 0.0,
+                    avg_read_latency_ms: latency_ms,
+                    success_rate: 0.0,
+                },
+            },
+        })
+    }
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Blob metadata stored alongside a payload blob
+fn blob_metadata(stored: &StoredWebhook, content_sha256: &str) -> HashMap<String, String> {
+    let payload = &stored.payload.metadata;
+    let mut metadata = HashMap::from([
+        (META_CONTENT_SHA256.to_string(), content_sha256.to_string()),
+        (
+            META_PAYLOAD_SHA256.to_string(),
+            stored.metadata.checksum_sha256.clone(),
+        ),
+        (
+            META_FORMAT_VERSION.to_string(),
+            stored.metadata.format_version.to_string(),
+        ),
+        (
+            META_RECEIVED_AT.to_string(),
+            payload.received_at.to_rfc3339(),
+        ),
+    ]);
+
+    // Blob metadata values must be ASCII; the filters fall back to the
+    // document for anything left out here.
+    if payload.event_type.is_ascii() {
+        metadata.insert(META_EVENT_TYPE.to_string(), payload.event_type.clone());
+    }
+    if let Some(repository) = &payload.repository {
+        if repository.full_name.is_ascii() {
+            metadata.insert(META_REPOSITORY.to_string(), repository.full_name.clone());
+        }
+    }
+    metadata
+}
+
+/// Check the blob content against the hashes in its blob metadata
+fn verify_content(
+    blob_path: &str,
+    metadata: Option<&HashMap<String, String>>,
+    content: &Bytes,
+) -> Result<(), BlobStorageError> {
+    let expected = metadata
+        .and_then(|metadata| metadata.get(META_CONTENT_SHA256))
+        .map(String::as_str)
+        .unwrap_or_default();
+
+    if verify_checksum(content, expected) {
+        Ok(())
+    } else {
+        Err(BlobStorageError::ChecksumMismatch {
+            path: blob_path.to_string(),
+            expected: expected.to_string(),
+            actual: compute_checksum(content),
+        })
+    }
+}
+
+/// Whether a listed blob can match `filter`, judged by its blob metadata
+///
+/// Blobs missing a metadata entry are kept; [`payload_matches`] decides once
+/// they are read.
+fn listing_may_match(filter: &PayloadFilter, metadata: Option<&HashMap<String, String>>) -> bool {
+    let Some(metadata) = metadata else {
+        return true;
+    };
+
+    if let (Some(wanted), Some(event_type)) = (&filter.event_type, metadata.get(META_EVENT_TYPE)) {
+        if wanted != event_type {
+            return false;
+        }
+    }
+    if let (Some(wanted), Some(repository)) = (&filter.repository, metadata.get(META_REPOSITORY)) {
+        if wanted != repository {
+            return false;
+        }
+    }
+    if let (Some(range), Some(received_at)) = (
+        &filter.date_range,
+        metadata
+            .get(META_RECEIVED_AT)
+            .and_then(|value| Timestamp::from_rfc3339(value).ok()),
+    ) {
+        if received_at < range.start || received_at >= range.end {
+            return false;
+        }
+    }
+    true
+}
+
+/// Whether a stored payload matches `filter`
+fn payload_matches(filter: &PayloadFilter, payload: &PayloadMetadata) -> bool {
+    if let Some(wanted) = &filter.repository {
+        match &payload.repository {
+            Some(repository) if &repository.full_name == wanted => {}
+            _ => return false,
+        }
+    }
+    if let Some(wanted) = &filter.event_type {
+        if &payload.event_type != wanted {
+            return false;
+        }
+    }
+    if let Some(range) = &filter.date_range {
+        if payload.received_at < range.start || payload.received_at >= range.end {
+            return false;
+        }
+    }
+    true
+}
+
+/// Event ID of a payload blob, from the file name of its path
+fn event_id_from_blob_path(blob_path: &str) -> Option<EventId> {
+    let file_name = blob_path.rsplit('/').next()?;
+    EventId::from_str(file_name.strip_suffix(".json")?).ok()
+}
+
+/// Byte ranges of the blocks a payload of `len` bytes is uploaded in
+fn block_ranges(len: usize, block_size: usize) -> Vec<Range<usize>> {
+    (0..len)
+        .step_by(block_size.max(1))
+        .map(|start| start..(start + block_size).min(len))
+        .collect()
+}
+
+/// ID of the block at `index`; all IDs of a blob have the same length
+fn block_id(index: usize) -> BlockId {
+    BlockId::new(format!("block-{:06}", index))
+}
+
+/// Whether an Azure error reports a missing blob or container
+fn is_not_found(error: &str) -> bool {
+    error.contains("404") || error.contains("BlobNotFound") || error.contains("ContainerNotFound")
+}
+
+/// Whether an Azure error reports that a write-once blob already exists
+fn is_conflict(error: &str) -> bool {
+    error.contains("BlobAlreadyExists")
+        || error.contains("ConditionNotMet")
+        || error.contains("409")
+        || error.contains("412")
+}
+
+/// Map an Azure error message to a [`BlobStorageError`]
+fn map_azure_error(operation: &str, error: &str) -> BlobStorageError {
+    if error.contains("BlobImmutableDueToPolicy") || error.contains("BlobImmutableDueToLegalHold") {
+        BlobStorageError::PermissionDenied {
+            operation: format!("{} (blocked by immutability policy)", operation),
+        }
+    } else if error.contains("401")
+        || error.contains("InvalidAuthenticationInfo")
+        || error.contains("credential")
+    {
+        BlobStorageError::AuthenticationFailed {
+            message: format!("{}: {}", operation, error),
+        }
+    } else if error.contains("403")
+        || error.contains("AuthorizationFailure")
+        || error.contains("AuthorizationPermissionMismatch")
+    {
+        BlobStorageError::PermissionDenied {
+            operation: operation.to_string(),
+        }
+    } else if error.contains("429")
+        || error.contains("503")
+        || error.contains("ServerBusy")
+        || error.contains("timed out")
+        || error.contains("timeout")
+        || error.contains("connect")
+    {
+        BlobStorageError::ConnectionFailed {
+            message: format!("{}: {}", operation, error),
+        }
+    } else {
+        BlobStorageError::InternalError {
+            message: format!("{} failed: {}", operation, error),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "azure_blob_storage_tests.rs"]
+mod tests;
@@ -0,0 +1,239 @@
+//! Tests for Azure Blob Storage adapter
+
+use super::*;
+use crate::{Repository, RepositoryId, User, UserId, UserType};
+
+// Note: Tests against a real storage account are ignored; they need Azure
+// credentials and QK_TEST_STORAGE_ACCOUNT naming an account to write to.
+
+fn test_webhook(event_type: &str, repository: Option<&str>) -> StoredWebhook {
+    let event_id = EventId::new();
+    let body = Bytes::from(r#"{"action":"opened"}"#);
+    let repository = repository.map(|full_name| {
+        let (owner, name) = full_name.split_once('/').unwrap();
+        Repository::new(
+            RepositoryId::new(12345),
+            name.to_string(),
+            full_name.to_string(),
+            User {
+                id: UserId::new(1),
+                login: owner.to_string(),
+                user_type: UserType::User,
+            },
+            false,
+        )
+    });
+
+    StoredWebhook {
+        metadata: BlobMetadata {
+            event_id,
+            blob_path: event_id.to_blob_path(),
+            size_bytes: 0,
+            content_type: CONTENT_TYPE.to_string(),
+            created_at: Timestamp::now(),
+            checksum_sha256: compute_checksum(&body),
+            format_version: CURRENT_FORMAT_VERSION,
+            content_encoding: ContentEncoding::Identity,
+            encryption: None,
+            metadata: PayloadMetadata {
+                event_id,
+                event_type: event_type.to_string(),
+                repository: repository.clone(),
+                signature_valid: true,
+                received_at: Timestamp::now(),
+                delivery_id: None,
+            },
+        },
+        payload: WebhookPayload {
+            body,
+            headers: HashMap::new(),
+            metadata: PayloadMetadata {
+                event_id,
+                event_type: event_type.to_string(),
+                repository,
+                signature_valid: true,
+                received_at: Timestamp::now(),
+                delivery_id: None,
+            },
+        },
+    }
+}
+
+#[test]
+fn test_configuration_validation() {
+    let config = AzureBlobConfiguration::new("qkaudit", "webhook-payloads");
+    assert!(config.validate().is_ok());
+    assert!(config.create_container);
+    assert_eq!(config.block_size_bytes, DEFAULT_BLOCK_SIZE_BYTES);
+
+    for (account, container) in [
+        ("QKAudit", "webhook-payloads"),
+        ("qk", "webhook-payloads"),
+        ("qkaudit", "Webhook-Payloads"),
+        ("qkaudit", "-payloads"),
+        ("qkaudit", "webhook--payloads"),
+        ("qkaudit", "wp"),
+    ] {
+        assert!(
+            AzureBlobConfiguration::new(account, container)
+                .validate()
+                .is_err(),
+            "{}/{} should be rejected",
+            account,
+            container
+        );
+    }
+
+    let no_blocks = AzureBlobConfiguration {
+        block_size_bytes: 0,
+        ..config
+    };
+    assert!(no_blocks.validate().is_err());
+}
+
+/// Blob metadata carries both hashes and the fields used to filter listings.
+#[test]
+fn test_blob_metadata_includes_hashes_and_filter_fields() {
+    let stored = test_webhook("pull_request", Some("octo/widgets"));
+
+    let metadata = blob_metadata(&stored, "abc123");
+
+    assert_eq!(metadata[META_CONTENT_SHA256], "abc123");
+    assert_eq!(
+        metadata[META_PAYLOAD_SHA256],
+        stored.metadata.checksum_sha256
+    );
+    assert_eq!(
+        metadata[META_FORMAT_VERSION],
+        CURRENT_FORMAT_VERSION.to_string()
+    );
+    assert_eq!(metadata[META_EVENT_TYPE], "pull_request");
+    assert_eq!(metadata[META_REPOSITORY], "octo/widgets");
+    assert!(Timestamp::from_rfc3339(&metadata[META_RECEIVED_AT]).is_ok());
+}
+
+/// Content that no longer matches its stored hash, or has no hash, is
+/// reported as tampered.
+#[test]
+fn test_verify_content_detects_tampering() {
+    let content = Bytes::from(r#"{"stored":true}"#);
+    let metadata = HashMap::from([(META_CONTENT_SHA256.to_string(), compute_checksum(&content))]);
+
+    assert!(verify_content("path.json", Some(&metadata), &content).is_ok());
+
+    let tampered = Bytes::from(r#"{"stored":false}"#);
+    let error = verify_content("path.json", Some(&metadata), &tampered).unwrap_err();
+    assert!(error.is_corrupted());
+
+    let error = verify_content("path.json", None, &content).unwrap_err();
+    assert!(error.is_corrupted());
+}
+
+#[test]
+fn test_listing_prefilter_uses_blob_metadata() {
+    let stored = test_webhook("push", Some("octo/widgets"));
+    let metadata = blob_metadata(&stored, "abc123");
+
+    let by_event_type = |event_type: &str| PayloadFilter {
+        event_type: Some(event_type.to_string()),
+        ..Default::default()
+    };
+    assert!(listing_may_match(&by_event_type("push"), Some(&metadata)));
+    assert!(!listing_may_match(
+        &by_event_type("issues"),
+        Some(&metadata)
+    ));
+
+    let by_repository = PayloadFilter {
+        repository: Some("octo/gadgets".to_string()),
+        ..Default::default()
+    };
+    assert!(!listing_may_match(&by_repository, Some(&metadata)));
+
+    let received_at = stored.payload.metadata.received_at;
+    let before = PayloadFilter {
+        date_range: Some(DateRange {
+            start: received_at.subtract_duration(std::time::Duration::from_secs(7200)),
+            end: received_at.subtract_duration(std::time::Duration::from_secs(3600)),
+        }),
+        ..Default::default()
+    };
+    assert!(!listing_may_match(&before, Some(&metadata)));
+
+    // Blobs without metadata are read and filtered on their content
+    assert!(listing_may_match(&by_event_type("issues"), None));
+    assert!(!payload_matches(
+        &by_event_type("issues"),
+        &stored.payload.metadata
+    ));
+}
+
+#[test]
+fn test_event_id_from_blob_path() {
+    let event_id = EventId::new();
+
+    assert_eq!(
+        event_id_from_blob_path(&event_id.to_blob_path()),
+        Some(event_id)
+    );
+    assert_eq!(event_id_from_blob_path("webhook-payloads/readme.txt"), None);
+}
+
+/// Large payloads are split into blocks covering every byte once.
+#[test]
+fn test_block_ranges_cover_payload() {
+    assert_eq!(block_ranges(10, 4), vec![0..4, 4..8, 8..10]);
+    assert_eq!(block_ranges(8, 4), vec![0..4, 4..8]);
+    assert!(block_ranges(0, 4).is_empty());
+}
+
+#[test]
+fn test_map_azure_error() {
+    assert!(matches!(
+        map_azure_error("delete x", "409 Conflict: BlobImmutableDueToPolicy"),
+        BlobStorageError::PermissionDenied { operation } if operation.contains("immutability")
+    ));
+    assert!(matches!(
+        map_azure_error("read x", "401 InvalidAuthenticationInfo"),
+        BlobStorageError::AuthenticationFailed { .. }
+    ));
+    assert!(matches!(
+        map_azure_error("read x", "403 AuthorizationPermissionMismatch"),
+        BlobStorageError::PermissionDenied { .. }
+    ));
+    assert!(map_azure_error("store x", "503 ServerBusy").is_transient());
+    assert!(map_azure_error("store x", "unexpected response").is_transient());
+
+    assert!(is_not_found("404 Not Found: BlobNotFound"));
+    assert!(is_conflict("409 Conflict: BlobAlreadyExists"));
+    assert!(!is_conflict("404 Not Found: BlobNotFound"));
+}
+
+#[tokio::test]
+#[ignore = "Requires an Azure storage account"]
+async fn test_store_and_read_back() {
+    let account = std::env::var("QK_TEST_STORAGE_ACCOUNT").unwrap();
+    let storage = AzureBlobStorage::new(AzureBlobConfiguration {
+        block_size_bytes: 16,
+        ..AzureBlobConfiguration::new(account, "queue-keeper-tests")
+    })
+    .await
+    .expect("Failed to connect to storage account");
+    let stored = test_webhook("push", Some("octo/widgets"));
+    let event_id = stored.metadata.event_id;
+
+    let metadata = storage
+        .store_payload(&event_id, &stored.payload)
+        .await
+        .unwrap();
+    let read = storage.get_payload(&event_id).await.unwrap().unwrap();
+
+    assert_eq!(read.payload.body, stored.payload.body);
+    assert_eq!(read.metadata.size_bytes, metadata.size_bytes);
+
+    // Storing the same payload again is a no-op
+    assert!(storage
+        .store_payload(&event_id, &stored.payload)
+        .await
+        .is_ok());
+}
@@ -6,7 +6,7 @@
 #[cfg(feature = "azure")]
 use crate::key_vault::{
     CacheStatistics, KeyVaultConfiguration, KeyVaultError, KeyVaultProvider, SecretCache,
-    SecretName, SecretValue,
+    SecretFreshness, SecretName, SecretValue,
 };
 #[cfg(feature = "azure")]
 use async_trait::async_trait;
@@ -389,6 +389,11 @@ impl KeyVaultProvider for AzureKeyVaultProvider {
     async fn get_cache_stats(&self) -> Result<CacheStatistics, KeyVaultError> {
         self.cache.get_statistics().await
     }
+
+    #[instrument(skip(self))]
+    async fn secret_freshness(&self) -> Result<Vec<SecretFreshness>, KeyVaultError> {
+        self.cache.get_freshness().await
+    }
 }
 
 #[cfg(test)]
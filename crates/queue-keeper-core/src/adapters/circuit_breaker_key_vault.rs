@@ -241,6 +241,13 @@ impl KeyVaultProvider for CircuitBreakerKeyVaultProvider {
         // Delegate to inner provider
         self.inner.get_cache_stats().await
     }
+
+    async fn secret_freshness(
+        &self,
+    ) -> Result<Vec<crate::key_vault::SecretFreshness>, KeyVaultError> {
+        // Delegate to inner provider; freshness is read from the local cache
+        self.inner.secret_freshness().await
+    }
 }
 
 #[cfg(test)]
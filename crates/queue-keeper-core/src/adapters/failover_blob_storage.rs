@@ -0,0 +1,477 @@
+//! Failover of blob storage to a secondary endpoint.
+//!
+//! Wraps a primary and a secondary [`BlobStorage`], for example accounts in
+//! paired Azure regions. Writes go to the primary until
+//! `failure_threshold` consecutive writes have failed with a transient error
+//! (see [`BlobStorageError::is_transient`]); the storage then fails over and
+//! writes to the secondary. Reads and listings consult both endpoints, so
+//! objects are found wherever they were written.
+//!
+//! [`reconcile`](FailoverBlobStorage::reconcile), run every
+//! `reconcile_interval` by [`run`](FailoverBlobStorage::run), probes the
+//! primary while failed over. Once it reports healthy, writes return to the
+//! primary and the objects written to the secondary are copied back and
+//! removed from it. [`status`](FailoverBlobStorage::status) and
+//! [`health_check`](BlobStorage::health_check) report the failover state.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::blob_storage::{
+    BlobMetadata, BlobStorage, BlobStorageError, PayloadFilter, StorageHealthStatus, StoredWebhook,
+    WebhookPayload,
+};
+use crate::units::human_duration;
+use crate::{EventId, Timestamp};
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Failover settings (the `event_storage_failover` configuration section).
+///
+/// ```yaml
+/// event_storage_failover:
+///   failure_threshold: 3
+///   reconcile_interval: 1m
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FailoverConfig {
+    /// Consecutive transient write failures of the primary before writes
+    /// move to the secondary.
+    pub failure_threshold: u32,
+
+    /// Interval between two attempts to reconcile the secondary into the
+    /// primary.
+    #[serde(with = "human_duration")]
+    pub reconcile_interval: Duration,
+}
+
+impl Default for FailoverConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 3,
+            reconcile_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+impl FailoverConfig {
+    /// Validate the settings.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.failure_threshold == 0 {
+            return Err("failure_threshold must be at least 1".to_string());
+        }
+        if self.reconcile_interval.is_zero() {
+            return Err("reconcile_interval must not be zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Status
+// ============================================================================
+
+/// Storage endpoint receiving writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StorageEndpoint {
+    Primary,
+    Secondary,
+}
+
+impl std::fmt::Display for StorageEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Primary => write!(f, "primary"),
+            Self::Secondary => write!(f, "secondary"),
+        }
+    }
+}
+
+/// Failover state of a [`FailoverBlobStorage`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailoverStatus {
+    /// Endpoint receiving writes.
+    pub active: StorageEndpoint,
+
+    /// Consecutive transient write failures of the primary.
+    pub consecutive_failures: u32,
+
+    /// When writes moved to the secondary, while failed over.
+    pub failed_over_at: Option<Timestamp>,
+
+    /// Objects in the secondary not yet copied back to the primary.
+    pub pending_reconciliation: usize,
+}
+
+impl FailoverStatus {
+    /// Human-readable summary, used in health checks.
+    pub fn describe(&self) -> String {
+        match (self.active, self.failed_over_at) {
+            (StorageEndpoint::Secondary, Some(since)) => format!(
+                "failed over to secondary storage since {}; {} object(s) pending reconciliation",
+                since, self.pending_reconciliation
+            ),
+            _ if self.pending_reconciliation > 0 => format!(
+                "writing to primary storage; {} object(s) pending reconciliation",
+                self.pending_reconciliation
+            ),
+            _ => "writing to primary storage".to_string(),
+        }
+    }
+}
+
+/// Outcome of one [`reconcile`](FailoverBlobStorage::reconcile) run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReconcileReport {
+    /// Whether the primary was healthy, and writes were moved back to it.
+    pub primary_healthy: bool,
+
+    /// Objects copied from the secondary to the primary.
+    pub copied: usize,
+
+    /// Objects left in the secondary, to be copied by a later run.
+    pub remaining: usize,
+}
+
+// ============================================================================
+// Failover Storage
+// ============================================================================
+
+/// Blob storage that fails over from a primary to a secondary endpoint.
+pub struct FailoverBlobStorage {
+    primary: Arc<dyn BlobStorage>,
+    secondary: Arc<dyn BlobStorage>,
+    config: FailoverConfig,
+    state: Mutex<FailoverStatus>,
+    /// Set once a reconciliation has emptied the secondary, which may hold
+    /// objects written before a restart until then.
+    reconciled: AtomicBool,
+}
+
+impl FailoverBlobStorage {
+    /// Write to `primary`, failing over to `secondary` as `config` describes.
+    pub fn new(
+        primary: Arc<dyn BlobStorage>,
+        secondary: Arc<dyn BlobStorage>,
+        config: FailoverConfig,
+    ) -> Self {
+        Self {
+            primary,
+            secondary,
+            config,
+            state: Mutex::new(FailoverStatus {
+                active: StorageEndpoint::Primary,
+                consecutive_failures: 0,
+                failed_over_at: None,
+                pending_reconciliation: 0,
+            }),
+            reconciled: AtomicBool::new(false),
+        }
+    }
+
+    /// Current failover state.
+    pub fn status(&self) -> FailoverStatus {
+        self.state.lock().unwrap().clone()
+    }
+
+    /// Whether writes currently go to the secondary.
+    pub fn is_failed_over(&self) -> bool {
+        self.state.lock().unwrap().active == StorageEndpoint::Secondary
+    }
+
+    /// Whether the secondary may hold objects, so reads and listings must
+    /// consult it.
+    fn secondary_in_use(&self) -> bool {
+        if !self.reconciled.load(Ordering::Relaxed) {
+            return true;
+        }
+        let state = self.state.lock().unwrap();
+        state.active == StorageEndpoint::Secondary || state.pending_reconciliation > 0
+    }
+
+    fn record_primary_success(&self) {
+        self.state.lock().unwrap().consecutive_failures = 0;
+    }
+
+    /// Count a transient primary write failure; returns whether writes now
+    /// go to the secondary.
+    fn record_primary_failure(&self, error: &BlobStorageError) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+        if state.active == StorageEndpoint::Primary
+            && state.consecutive_failures >= self.config.failure_threshold
+        {
+            state.active = StorageEndpoint::Secondary;
+            state.failed_over_at = Some(Timestamp::now());
+            warn!(
+                consecutive_failures = state.consecutive_failures,
+                error = %error,
+                "Primary blob storage failing; writes moved to secondary storage"
+            );
+        }
+        state.active == StorageEndpoint::Secondary
+    }
+
+    /// Move writes back to the primary once it is healthy, then copy the
+    /// objects written to the secondary back to it.
+    ///
+    /// Objects that cannot be copied stay in the secondary and are retried
+    /// by the next run.
+    pub async fn reconcile(&self) -> Result<ReconcileReport, BlobStorageError> {
+        if !self.secondary_in_use() {
+            return Ok(ReconcileReport {
+                primary_healthy: true,
+                ..ReconcileReport::default()
+            });
+        }
+
+        match self.primary.health_check().await {
+            Ok(health) if health.healthy => {}
+            Ok(_) => {
+                return Ok(ReconcileReport {
+                    primary_healthy: false,
+                    remaining: self.status().pending_reconciliation,
+                    ..ReconcileReport::default()
+                });
+            }
+            Err(e) => {
+                warn!(error = %e, "Primary blob storage health check failed");
+                return Ok(ReconcileReport {
+                    primary_healthy: false,
+                    remaining: self.status().pending_reconciliation,
+                    ..ReconcileReport::default()
+                });
+            }
+        }
+
+        {
+            let mut state = self.state.lock().unwrap();
+            if state.active == StorageEndpoint::Secondary {
+                info!(
+                    failed_over_at = ?state.failed_over_at,
+                    "Primary blob storage recovered; writes moved back to primary storage"
+                );
+            }
+            state.active = StorageEndpoint::Primary;
+            state.consecutive_failures = 0;
+            state.failed_over_at = None;
+        }
+
+        let pending = self
+            .secondary
+            .list_payloads(&PayloadFilter::default())
+            .await?;
+        let mut report = ReconcileReport {
+            primary_healthy: true,
+            ..ReconcileReport::default()
+        };
+        for metadata in &pending {
+            match self.copy_to_primary(&metadata.event_id).await {
+                Ok(()) => report.copied += 1,
+                Err(e) => {
+                    warn!(
+                        event_id = %metadata.event_id,
+                        error = %e,
+                        "Failed to copy object back to primary blob storage"
+                    );
+                    report.remaining += 1;
+                }
+            }
+        }
+
+        {
+            // Writes that raced the switch back may have added objects.
+            let mut state = self.state.lock().unwrap();
+            state.pending_reconciliation = state
+                .pending_reconciliation
+                .saturating_sub(report.copied)
+                .max(report.remaining);
+        }
+        self.reconciled
+            .store(report.remaining == 0, Ordering::Relaxed);
+        if report.copied > 0 {
+            info!(
+                copied = report.copied,
+                remaining = report.remaining,
+                "Reconciled secondary blob storage into primary"
+            );
+        }
+        Ok(report)
+    }
+
+    /// Copy one object from the secondary to the primary and remove it from
+    /// the secondary.
+    async fn copy_to_primary(&self, event_id: &EventId) -> Result<(), BlobStorageError> {
+        if let Some(stored) = self.secondary.get_payload(event_id).await? {
+            if self.primary.get_payload(event_id).await?.is_none() {
+                self.primary
+                    .store_payload(event_id, &stored.payload)
+                    .await?;
+            }
+        }
+        match self.secondary.delete_payload(event_id).await {
+            Ok(()) | Err(BlobStorageError::BlobNotFound { .. }) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Run [`reconcile`](Self::reconcile) every `reconcile_interval`,
+    /// forever.
+    pub async fn run(&self) {
+        let mut ticker = tokio::time::interval(self.config.reconcile_interval);
+        loop {
+            ticker.tick().await;
+            if let Err(e) = self.reconcile().await {
+                warn!(error = %e, "Blob storage reconciliation failed");
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl BlobStorage for FailoverBlobStorage {
+    async fn store_payload(
+        &self,
+        event_id: &EventId,
+        payload: &WebhookPayload,
+    ) -> Result<BlobMetadata, BlobStorageError> {
+        if !self.is_failed_over() {
+            match self.primary.store_payload(event_id, payload).await {
+                Ok(metadata) => {
+                    self.record_primary_success();
+                    return Ok(metadata);
+                }
+                Err(e) if e.is_transient() => {
+                    if !self.record_primary_failure(&e) {
+                        return Err(e);
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let metadata = self.secondary.store_payload(event_id, payload).await?;
+        self.state.lock().unwrap().pending_reconciliation += 1;
+        Ok(metadata)
+    }
+
+    async fn get_payload(
+        &self,
+        event_id: &EventId,
+    ) -> Result<Option<StoredWebhook>, BlobStorageError> {
+        if !self.secondary_in_use() {
+            return self.primary.get_payload(event_id).await;
+        }
+
+        let (first, second) = if self.is_failed_over() {
+            (&self.secondary, &self.primary)
+        } else {
+            (&self.primary, &self.secondary)
+        };
+        match first.get_payload(event_id).await {
+            Ok(Some(stored)) => Ok(Some(stored)),
+            Ok(None) => second.get_payload(event_id).await,
+            Err(e) if e.is_transient() => match second.get_payload(event_id).await {
+                Ok(Some(stored)) => Ok(Some(stored)),
+                _ => Err(e),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn list_payloads(
+        &self,
+        filter: &PayloadFilter,
+    ) -> Result<Vec<BlobMetadata>, BlobStorageError> {
+        if !self.secondary_in_use() {
+            return self.primary.list_payloads(filter).await;
+        }
+
+        // Pagination applies to the merged listing.
+        let unpaged = PayloadFilter {
+            limit: None,
+            offset: None,
+            ..filter.clone()
+        };
+        let mut results = match self.primary.list_payloads(&unpaged).await {
+            Ok(results) => results,
+            Err(e) if e.is_transient() && self.is_failed_over() => {
+                warn!(error = %e, "Primary blob storage unavailable; listing secondary only");
+                Vec::new()
+            }
+            Err(e) => return Err(e),
+        };
+        for metadata in self.secondary.list_payloads(&unpaged).await? {
+            if !results.iter().any(|m| m.event_id == metadata.event_id) {
+                results.push(metadata);
+            }
+        }
+
+        let offset = filter.offset.unwrap_or(0);
+        let limit = filter.limit.unwrap_or(usize::MAX);
+        Ok(results.into_iter().skip(offset).take(limit).collect())
+    }
+
+    async fn delete_payload(&self, event_id: &EventId) -> Result<(), BlobStorageError> {
+        let primary = self.primary.delete_payload(event_id).await;
+        let secondary = if self.secondary_in_use() {
+            self.secondary.delete_payload(event_id).await
+        } else {
+            Err(BlobStorageError::BlobNotFound {
+                event_id: *event_id,
+            })
+        };
+
+        if secondary.is_ok() {
+            let mut state = self.state.lock().unwrap();
+            state.pending_reconciliation = state.pending_reconciliation.saturating_sub(1);
+        }
+
+        let mut deleted = false;
+        for result in [primary, secondary] {
+            match result {
+                Ok(()) => deleted = true,
+                Err(BlobStorageError::BlobNotFound { .. }) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if deleted {
+            Ok(())
+        } else {
+            Err(BlobStorageError::BlobNotFound {
+                event_id: *event_id,
+            })
+        }
+    }
+
+    /// Health of the endpoint receiving writes. While failed over, or while
+    /// objects await reconciliation, the message describes the failover
+    /// state.
+    async fn health_check(&self) -> Result<StorageHealthStatus, BlobStorageError> {
+        let status = self.status();
+        let mut health = match status.active {
+            StorageEndpoint::Primary => self.primary.health_check().await?,
+            StorageEndpoint::Secondary => self.secondary.health_check().await?,
+        };
+        if status.active == StorageEndpoint::Secondary || status.pending_reconciliation > 0 {
+            health.error_message = Some(match health.error_message {
+                Some(message) => format!("{}; {}", status.describe(), message),
+                None => status.describe(),
+            });
+        }
+        Ok(health)
+    }
+}
+
+#[cfg(test)]
+#[path = "failover_blob_storage_tests.rs"]
+mod tests;
@@ -0,0 +1,320 @@
+//! Tests for failover of blob storage to a secondary endpoint.
+
+use super::*;
+use crate::blob_storage::{PayloadMetadata, StorageMetrics};
+use bytes::Bytes;
+use std::collections::HashMap;
+
+/// In-memory storage whose operations fail with a connection error while it
+/// is down.
+#[derive(Default)]
+struct MemoryStorage {
+    objects: Mutex<HashMap<EventId, StoredWebhook>>,
+    down: AtomicBool,
+}
+
+impl MemoryStorage {
+    fn set_down(&self, down: bool) {
+        self.down.store(down, Ordering::SeqCst);
+    }
+
+    fn check(&self) -> Result<(), BlobStorageError> {
+        if self.down.load(Ordering::SeqCst) {
+            Err(BlobStorageError::ConnectionFailed {
+                message: "endpoint down".to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+
+    fn contains(&self, event_id: &EventId) -> bool {
+        self.objects.lock().unwrap().contains_key(event_id)
+    }
+}
+
+#[async_trait]
+impl BlobStorage for MemoryStorage {
+    async fn store_payload(
+        &self,
+        event_id: &EventId,
+        payload: &WebhookPayload,
+    ) -> Result<BlobMetadata, BlobStorageError> {
+        self.check()?;
+        let metadata = BlobMetadata {
+            event_id: *event_id,
+            blob_path: event_id.to_blob_path(),
+            size_bytes: payload.body.len() as u64,
+            content_type: "application/json".to_string(),
+            created_at: Timestamp::now(),
+            checksum_sha256: crate::blob_storage::compute_checksum(&payload.body),
+            format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+            content_encoding: crate::blob_storage::ContentEncoding::Identity,
+            encryption: None,
+            metadata: payload.metadata.clone(),
+        };
+        self.objects.lock().unwrap().insert(
+            *event_id,
+            StoredWebhook {
+                metadata: metadata.clone(),
+                payload: payload.clone(),
+            },
+        );
+        Ok(metadata)
+    }
+
+    async fn get_payload(
+        &self,
+        event_id: &EventId,
+    ) -> Result<Option<StoredWebhook>, BlobStorageError> {
+        self.check()?;
+        Ok(self.objects.lock().unwrap().get(event_id).cloned())
+    }
+
+    async fn list_payloads(
+        &self,
+        _filter: &PayloadFilter,
+    ) -> Result<Vec<BlobMetadata>, BlobStorageError> {
+        self.check()?;
+        Ok(self
+            .objects
+            .lock()
+            .unwrap()
+            .values()
+            .map(|stored| stored.metadata.clone())
+            .collect())
+    }
+
+    async fn delete_payload(&self, event_id: &EventId) -> Result<(), BlobStorageError> {
+        self.check()?;
+        match self.objects.lock().unwrap().remove(event_id) {
+            Some(_) => Ok(()),
+            None => Err(BlobStorageError::BlobNotFound {
+                event_id: *event_id,
+            }),
+        }
+    }
+
+    async fn health_check(&self) -> Result<StorageHealthStatus, BlobStorageError> {
+        let healthy = self.check().is_ok();
+        Ok(StorageHealthStatus {
+            healthy,
+            connected: healthy,
+            last_success: None,
+            error_message: None,
+            metrics: StorageMetrics {
+                avg_write_latency_ms: 0.0,
+                avg_read_latency_ms: 0.0,
+                success_rate: 1.0,
+            },
+        })
+    }
+}
+
+fn payload() -> (EventId, WebhookPayload) {
+    let event_id = EventId::new();
+    let payload = WebhookPayload {
+        body: Bytes::from_static(b"{}"),
+        headers: HashMap::new(),
+        metadata: PayloadMetadata {
+            event_id,
+            event_type: "push".to_string(),
+            repository: None,
+            signature_valid: true,
+            received_at: Timestamp::now(),
+            delivery_id: None,
+        },
+    };
+    (event_id, payload)
+}
+
+fn storage(
+    failure_threshold: u32,
+) -> (Arc<MemoryStorage>, Arc<MemoryStorage>, FailoverBlobStorage) {
+    let primary = Arc::new(MemoryStorage::default());
+    let secondary = Arc::new(MemoryStorage::default());
+    let failover = FailoverBlobStorage::new(
+        primary.clone(),
+        secondary.clone(),
+        FailoverConfig {
+            failure_threshold,
+            ..FailoverConfig::default()
+        },
+    );
+    (primary, secondary, failover)
+}
+
+/// Writes fail until the threshold is reached, then go to the secondary,
+/// where they can be read and listed.
+#[tokio::test]
+async fn test_writes_fail_over_after_threshold() {
+    let (primary, secondary, failover) = storage(2);
+    primary.set_down(true);
+
+    let (first_id, first) = payload();
+    assert!(failover.store_payload(&first_id, &first).await.is_err());
+    assert!(!failover.is_failed_over());
+
+    let (second_id, second) = payload();
+    failover.store_payload(&second_id, &second).await.unwrap();
+    assert!(failover.is_failed_over());
+    assert!(secondary.contains(&second_id));
+
+    let status = failover.status();
+    assert_eq!(status.active, StorageEndpoint::Secondary);
+    assert_eq!(status.pending_reconciliation, 1);
+    assert!(failover.get_payload(&second_id).await.unwrap().is_some());
+    let listed = failover
+        .list_payloads(&PayloadFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(listed.len(), 1);
+
+    let health = failover.health_check().await.unwrap();
+    assert!(health.healthy);
+    assert!(health
+        .error_message
+        .unwrap()
+        .contains("failed over to secondary storage"));
+}
+
+/// Permanent primary errors are returned without failing over.
+#[tokio::test]
+async fn test_permanent_errors_do_not_fail_over() {
+    struct Denied;
+
+    #[async_trait]
+    impl BlobStorage for Denied {
+        async fn store_payload(
+            &self,
+            _event_id: &EventId,
+            _payload: &WebhookPayload,
+        ) -> Result<BlobMetadata, BlobStorageError> {
+            Err(BlobStorageError::PermissionDenied {
+                operation: "write".to_string(),
+            })
+        }
+
+        async fn get_payload(
+            &self,
+            _event_id: &EventId,
+        ) -> Result<Option<StoredWebhook>, BlobStorageError> {
+            Ok(None)
+        }
+
+        async fn list_payloads(
+            &self,
+            _filter: &PayloadFilter,
+        ) -> Result<Vec<BlobMetadata>, BlobStorageError> {
+            Ok(Vec::new())
+        }
+
+        async fn delete_payload(&self, event_id: &EventId) -> Result<(), BlobStorageError> {
+            Err(BlobStorageError::BlobNotFound {
+                event_id: *event_id,
+            })
+        }
+
+        async fn health_check(&self) -> Result<StorageHealthStatus, BlobStorageError> {
+            unimplemented!()
+        }
+    }
+
+    let secondary = Arc::new(MemoryStorage::default());
+    let failover = FailoverBlobStorage::new(
+        Arc::new(Denied),
+        secondary.clone(),
+        FailoverConfig::default(),
+    );
+
+    let (event_id, payload) = payload();
+    for _ in 0..5 {
+        assert!(matches!(
+            failover.store_payload(&event_id, &payload).await,
+            Err(BlobStorageError::PermissionDenied { .. })
+        ));
+    }
+    assert!(!failover.is_failed_over());
+    assert!(!secondary.contains(&event_id));
+}
+
+/// Reconciliation waits for the primary, then moves writes back to it and
+/// copies the secondary's objects over.
+#[tokio::test]
+async fn test_reconcile_copies_back_when_primary_recovers() {
+    let (primary, secondary, failover) = storage(1);
+    primary.set_down(true);
+    let (event_id, payload) = payload();
+    failover.store_payload(&event_id, &payload).await.unwrap();
+
+    let report = failover.reconcile().await.unwrap();
+    assert!(!report.primary_healthy);
+    assert!(failover.is_failed_over());
+
+    primary.set_down(false);
+    let report = failover.reconcile().await.unwrap();
+    assert!(report.primary_healthy);
+    assert_eq!(report.copied, 1);
+    assert_eq!(report.remaining, 0);
+    assert!(primary.contains(&event_id));
+    assert!(!secondary.contains(&event_id));
+
+    let status = failover.status();
+    assert_eq!(status.active, StorageEndpoint::Primary);
+    assert_eq!(status.pending_reconciliation, 0);
+    assert!(failover
+        .health_check()
+        .await
+        .unwrap()
+        .error_message
+        .is_none());
+}
+
+/// Objects left in the secondary by an earlier run are found and
+/// reconciled.
+#[tokio::test]
+async fn test_objects_from_earlier_run_reconciled() {
+    let (primary, secondary, failover) = storage(3);
+    let (event_id, payload) = payload();
+    secondary.store_payload(&event_id, &payload).await.unwrap();
+
+    assert!(failover.get_payload(&event_id).await.unwrap().is_some());
+
+    let report = failover.reconcile().await.unwrap();
+    assert_eq!(report.copied, 1);
+    assert!(primary.contains(&event_id));
+    assert!(!secondary.contains(&event_id));
+}
+
+/// Deletes remove the object from whichever endpoint holds it.
+#[tokio::test]
+async fn test_delete_removes_from_either_endpoint() {
+    let (primary, _secondary, failover) = storage(1);
+    primary.set_down(true);
+    let (event_id, payload) = payload();
+    failover.store_payload(&event_id, &payload).await.unwrap();
+    primary.set_down(false);
+
+    failover.delete_payload(&event_id).await.unwrap();
+    assert!(failover.get_payload(&event_id).await.unwrap().is_none());
+    assert!(matches!(
+        failover.delete_payload(&event_id).await,
+        Err(BlobStorageError::BlobNotFound { .. })
+    ));
+}
+
+/// The failover settings are validated.
+#[test]
+fn test_config_validation() {
+    assert!(FailoverConfig::default().validate().is_ok());
+    let config: FailoverConfig =
+        serde_yaml::from_str("failure_threshold: 5\nreconcile_interval: 30s\n").unwrap();
+    assert_eq!(config.failure_threshold, 5);
+    assert_eq!(config.reconcile_interval, Duration::from_secs(30));
+
+    let no_threshold = FailoverConfig {
+        failure_threshold: 0,
+        ..FailoverConfig::default()
+    };
+    assert!(no_threshold.validate().is_err());
+}
@@ -3,16 +3,23 @@
 //! Local filesystem implementation of BlobStorage trait for development and testing.
 
 use crate::blob_storage::*;
+use crate::payload_encryption::{open_stored, PayloadEncryptor};
+use crate::storage_format::{check_format_version, CURRENT_FORMAT_VERSION};
 use crate::{EventId, Timestamp};
 use async_trait::async_trait;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tokio::fs;
 use tokio::io::AsyncWriteExt;
 
 /// Filesystem-based blob storage implementation
 ///
 /// Stores blobs as JSON files in a local directory structure following
-/// the standard partitioning scheme.
+/// the standard partitioning scheme. Payload bodies are stored uncompressed
+/// unless [`with_compression`](Self::with_compression) selects an encoding;
+/// compressed blobs are decompressed when read, whatever the current
+/// setting. [`with_encryption`](Self::with_encryption) encrypts bodies at
+/// rest the same way.
 ///
 /// # Examples
 ///
@@ -27,6 +34,8 @@ use tokio::io::AsyncWriteExt;
 #[derive(Debug, Clone)]
 pub struct FilesystemBlobStorage {
     base_path: PathBuf,
+    compression: ContentEncoding,
+    encryption: Option<Arc<PayloadEncryptor>>,
 }
 
 impl FilesystemBlobStorage {
@@ -47,7 +56,24 @@ impl FilesystemBlobStorage {
                 message: format!("Failed to create base directory: {}", e),
             })?;
 
-        Ok(Self { base_path })
+        Ok(Self {
+            base_path,
+            compression: ContentEncoding::Identity,
+            encryption: None,
+        })
+    }
+
+    /// Compress payload bodies written from now on with `compression`
+    pub fn with_compression(mut self, compression: ContentEncoding) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// Encrypt payload bodies written from now on, and decrypt encrypted
+    /// ones on read
+    pub fn with_encryption(mut self, encryptor: Arc<PayloadEncryptor>) -> Self {
+        self.encryption = Some(encryptor);
+        self
     }
 
     /// Get full path for event ID
@@ -84,6 +110,9 @@ impl BlobStorage for FilesystemBlobStorage {
             content_type: "application/json".to_string(),
             created_at,
             checksum_sha256: String::new(), // Temporary placeholder
+            format_version: CURRENT_FORMAT_VERSION,
+            content_encoding: ContentEncoding::Identity,
+            encryption: None,
             metadata: payload.metadata.clone(),
         };
 
@@ -92,18 +121,24 @@ impl BlobStorage for FilesystemBlobStorage {
             payload: payload.clone(),
         };
 
-        // Compute checksum of the payload body (not the entire serialized JSON)
+        // Compute checksum of the payload body (not the entire serialized
+        // JSON); encryption replaces it with that of the ciphertext
         let checksum = crate::blob_storage::compute_checksum(&payload.body);
 
         // Create final metadata with checksum
         let final_metadata = BlobMetadata {
-            checksum_sha256: checksum.clone(),
+            checksum_sha256: checksum,
             ..temp_webhook.metadata
         };
 
         let final_webhook = StoredWebhook {
             metadata: final_metadata,
             payload: payload.clone(),
+        }
+        .encode(self.compression)?;
+        let final_webhook = match &self.encryption {
+            Some(encryptor) => encryptor.encrypt(final_webhook).await?,
+            None => final_webhook,
         };
 
         // Final serialization with correct checksum
@@ -150,13 +185,8 @@ impl BlobStorage for FilesystemBlobStorage {
                 })?;
 
         Ok(BlobMetadata {
-            event_id: *event_id,
-            blob_path: event_id.to_blob_path(),
             size_bytes: file_metadata.len(),
-            content_type: "application/json".to_string(),
-            created_at,
-            checksum_sha256: checksum,
-            metadata: payload.metadata.clone(),
+            ..final_webhook.metadata
         })
     }
 
@@ -185,18 +215,21 @@ impl BlobStorage for FilesystemBlobStorage {
                 message: format!("Failed to deserialize payload: {}", e),
             })?;
 
-        // Verify checksum against the payload body (not the entire JSON)
-        let computed_checksum = crate::blob_storage::compute_checksum(&stored.payload.body);
-        if !crate::blob_storage::verify_checksum(
-            &stored.payload.body,
-            &stored.metadata.checksum_sha256,
-        ) {
-            return Err(BlobStorageError::ChecksumMismatch {
+        // Blobs from the previous format are read as-is; newer ones are refused
+        check_format_version(stored.metadata.format_version).map_err(|_| {
+            BlobStorageError::UnsupportedFormatVersion {
                 path: blob_path.display().to_string(),
-                expected: stored.metadata.checksum_sha256.clone(),
-                actual: computed_checksum,
-            });
-        }
+                version: stored.metadata.format_version,
+            }
+        })?;
+
+        // Verify checksum against the payload body (not the entire JSON)
+        let stored = open_stored(
+            self.encryption.as_deref(),
+            stored,
+            &blob_path.display().to_string(),
+        )
+        .await?;
 
         Ok(Some(stored))
     }
@@ -266,7 +299,10 @@ impl BlobStorage for FilesystemBlobStorage {
                             }
 
                             if matches {
-                                results.push(stored.metadata);
+                                // Blobs are written before their size is known
+                                let mut metadata = stored.metadata;
+                                metadata.size_bytes = json.len() as u64;
+                                results.push(metadata);
                             }
                         }
                     }
@@ -214,6 +214,79 @@ async fn test_filesystem_get_returns_none_for_missing() {
     assert!(result.is_none());
 }
 
+/// Store a payload, then rewrite its file with `format_version` set to
+/// `version`, or removed when `None`.
+async fn store_with_format_version(
+    storage: &FilesystemBlobStorage,
+    version: Option<u64>,
+) -> EventId {
+    let event_id = EventId::new();
+    let payload = WebhookPayload {
+        body: Bytes::from("{}"),
+        headers: HashMap::new(),
+        metadata: PayloadMetadata {
+            event_id,
+            event_type: "push".to_string(),
+            repository: None,
+            signature_valid: true,
+            received_at: Timestamp::now(),
+            delivery_id: None,
+        },
+    };
+    storage.store_payload(&event_id, &payload).await.unwrap();
+
+    let path = storage.get_blob_path(&event_id);
+    let mut document: serde_json::Value =
+        serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    let metadata = document["metadata"].as_object_mut().unwrap();
+    match version {
+        Some(version) => metadata.insert("format_version".to_string(), version.into()),
+        None => metadata.remove("format_version"),
+    };
+    std::fs::write(&path, serde_json::to_vec(&document).unwrap()).unwrap();
+    event_id
+}
+
+/// Verify that stored blobs carry the current format version and that
+/// blobs written before versioning are still readable.
+#[tokio::test]
+async fn test_filesystem_reads_current_and_legacy_formats() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = FilesystemBlobStorage::new(temp_dir.path().to_path_buf())
+        .await
+        .unwrap();
+
+    let current = store_with_format_version(&storage, Some(2)).await;
+    let stored = storage.get_payload(&current).await.unwrap().unwrap();
+    assert_eq!(
+        stored.metadata.format_version,
+        crate::storage_format::CURRENT_FORMAT_VERSION
+    );
+
+    let legacy = store_with_format_version(&storage, None).await;
+    let stored = storage.get_payload(&legacy).await.unwrap().unwrap();
+    assert_eq!(
+        stored.metadata.format_version,
+        crate::storage_format::LEGACY_FORMAT_VERSION
+    );
+}
+
+/// Verify that blobs written by a newer release are refused.
+#[tokio::test]
+async fn test_filesystem_rejects_newer_format() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = FilesystemBlobStorage::new(temp_dir.path().to_path_buf())
+        .await
+        .unwrap();
+
+    let event_id = store_with_format_version(&storage, Some(99)).await;
+    let result = storage.get_payload(&event_id).await;
+    assert!(matches!(
+        result,
+        Err(BlobStorageError::UnsupportedFormatVersion { version: 99, .. })
+    ));
+}
+
 // ============================================================================
 // List Operation Tests
 // ============================================================================
@@ -378,6 +451,141 @@ async fn test_filesystem_list_filters_by_event_type() {
     assert_eq!(results[0].metadata.event_type, "pull_request");
 }
 
+// ============================================================================
+// Compression Tests
+// ============================================================================
+
+/// Verify that compressed payloads are stored smaller and read back
+/// decompressed, also after the compression setting changed.
+#[tokio::test]
+async fn test_filesystem_compressed_payload_round_trip() {
+    let temp_dir = TempDir::new().unwrap();
+    let storage = FilesystemBlobStorage::new(temp_dir.path().to_path_buf())
+        .await
+        .unwrap()
+        .with_compression(ContentEncoding::Zstd);
+
+    let body = Bytes::from(r#"{"action":"opened","labels":[]}"#.repeat(200));
+    let payload_for = |event_id: EventId| WebhookPayload {
+        body: body.clone(),
+        headers: HashMap::new(),
+        metadata: PayloadMetadata {
+            event_id,
+            event_type: "pull_request".to_string(),
+            repository: None,
+            signature_valid: true,
+            received_at: Timestamp::now(),
+            delivery_id: None,
+        },
+    };
+
+    let compressed_id = EventId::new();
+    let compressed = storage
+        .store_payload(&compressed_id, &payload_for(compressed_id))
+        .await
+        .unwrap();
+    assert_eq!(compressed.content_encoding, ContentEncoding::Zstd);
+    assert_eq!(compressed.checksum_sha256, compute_checksum(&body));
+
+    let plain_storage = storage.clone().with_compression(ContentEncoding::Identity);
+    let plain_id = EventId::new();
+    let plain = plain_storage
+        .store_payload(&plain_id, &payload_for(plain_id))
+        .await
+        .unwrap();
+    assert!(compressed.size_bytes < plain.size_bytes);
+
+    // Reads decompress whatever the current setting
+    let read = plain_storage
+        .get_payload(&compressed_id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(read.payload.body, body);
+    assert_eq!(read.metadata.content_encoding, ContentEncoding::Zstd);
+    let read = storage.get_payload(&plain_id).await.unwrap().unwrap();
+    assert_eq!(read.payload.body, body);
+    assert_eq!(read.metadata.content_encoding, ContentEncoding::Identity);
+}
+
+/// Verify that encrypted payloads are not readable on disk, are read back
+/// decrypted, and cannot be read without the key.
+#[tokio::test]
+async fn test_filesystem_encrypted_payload_round_trip() {
+    use crate::adapters::InMemoryKeyVaultProvider;
+    use crate::key_vault::{SecretName, SecretValue};
+    use crate::payload_encryption::PayloadEncryptor;
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+    let key_vault = InMemoryKeyVaultProvider::new();
+    let key_secret = SecretName::new("queue-keeper-test-payload-key").unwrap();
+    key_vault.add_secret(
+        key_secret.clone(),
+        SecretValue::from_string(STANDARD.encode([9u8; 32])),
+    );
+    let temp_dir = TempDir::new().unwrap();
+    let plain_storage = FilesystemBlobStorage::new(temp_dir.path().to_path_buf())
+        .await
+        .unwrap()
+        .with_compression(ContentEncoding::Gzip);
+    let storage = plain_storage
+        .clone()
+        .with_encryption(Arc::new(PayloadEncryptor::new(
+            Arc::new(key_vault),
+            key_secret,
+        )));
+
+    let event_id = EventId::new();
+    let body = Bytes::from(r#"{"action":"created","comment":"confidential"}"#);
+    let payload = WebhookPayload {
+        body: body.clone(),
+        headers: HashMap::new(),
+        metadata: PayloadMetadata {
+            event_id,
+            event_type: "issue_comment".to_string(),
+            repository: None,
+            signature_valid: true,
+            received_at: Timestamp::now(),
+            delivery_id: None,
+        },
+    };
+
+    let metadata = storage.store_payload(&event_id, &payload).await.unwrap();
+    assert!(metadata.encryption.is_some());
+    assert_eq!(metadata.content_encoding, ContentEncoding::Gzip);
+
+    // On disk the body is ciphertext, not gzip data
+    let raw = std::fs::read_to_string(temp_dir.path().join(event_id.to_blob_path())).unwrap();
+    let raw: StoredWebhook = serde_json::from_str(&raw).unwrap();
+    assert!(ContentEncoding::Gzip.decode(&raw.payload.body).is_err());
+
+    // The stored checksum covers the ciphertext and reveals nothing about the plaintext
+    assert_eq!(
+        metadata.checksum_sha256,
+        compute_checksum(&raw.payload.body)
+    );
+    assert_ne!(metadata.checksum_sha256, compute_checksum(&body));
+
+    let read = storage.get_payload(&event_id).await.unwrap().unwrap();
+    assert_eq!(read.payload.body, body);
+
+    let error = plain_storage.get_payload(&event_id).await.unwrap_err();
+    assert!(matches!(error, BlobStorageError::EncryptionFailed { .. }));
+
+    // Tampered ciphertext is rejected by the checksum before decryption
+    let mut tampered = raw;
+    let mut ciphertext = tampered.payload.body.to_vec();
+    ciphertext[0] ^= 0xff;
+    tampered.payload.body = Bytes::from(ciphertext);
+    std::fs::write(
+        temp_dir.path().join(event_id.to_blob_path()),
+        serde_json::to_vec(&tampered).unwrap(),
+    )
+    .unwrap();
+    let error = storage.get_payload(&event_id).await.unwrap_err();
+    assert!(matches!(error, BlobStorageError::ChecksumMismatch { .. }));
+}
+
 // ============================================================================
 // Delete Operation Tests
 // ============================================================================
@@ -449,6 +657,8 @@ fn test_get_blob_path_combines_base_and_relative() {
     let base_path = PathBuf::from("/tmp/blobs");
     let storage = FilesystemBlobStorage {
         base_path: base_path.clone(),
+        compression: ContentEncoding::Identity,
+        encryption: None,
     };
 
     let event_id = EventId::new();
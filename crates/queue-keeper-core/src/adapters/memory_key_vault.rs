@@ -5,7 +5,7 @@
 
 use crate::key_vault::{
     CacheStatistics, CachedSecret, KeyVaultConfiguration, KeyVaultError, KeyVaultProvider,
-    SecretCache, SecretName, SecretValue,
+    SecretCache, SecretFreshness, SecretName, SecretValue,
 };
 use crate::Timestamp;
 use async_trait::async_trait;
@@ -217,6 +217,14 @@ impl SecretCache for InMemorySecretCache {
             collected_at: Timestamp::now(),
         })
     }
+
+    async fn get_freshness(&self) -> Result<Vec<SecretFreshness>, KeyVaultError> {
+        let secrets = self.secrets.read().unwrap();
+        let mut freshness: Vec<SecretFreshness> =
+            secrets.values().map(SecretFreshness::from).collect();
+        freshness.sort_by(|a, b| a.name.as_str().cmp(b.name.as_str()));
+        Ok(freshness)
+    }
 }
 
 /// In-memory Key Vault provider for testing and development
@@ -383,6 +391,10 @@ impl KeyVaultProvider for InMemoryKeyVaultProvider {
     async fn get_cache_stats(&self) -> Result<CacheStatistics, KeyVaultError> {
         self.cache.get_statistics().await
     }
+
+    async fn secret_freshness(&self) -> Result<Vec<SecretFreshness>, KeyVaultError> {
+        self.cache.get_freshness().await
+    }
 }
 
 #[cfg(test)]
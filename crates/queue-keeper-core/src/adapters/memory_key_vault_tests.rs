@@ -178,3 +178,19 @@ async fn test_cleanup_expired() {
     // Secret should be gone
     assert!(cache.get(&name).await.is_none());
 }
+
+#[tokio::test]
+async fn test_secret_freshness_reports_cached_secrets() {
+    let provider = InMemoryKeyVaultProvider::new();
+    let name = SecretName::new("fresh-secret").unwrap();
+    provider.add_secret(name.clone(), SecretValue::from_string("value".to_string()));
+
+    // Nothing is cached until the secret is read
+    assert!(provider.secret_freshness().await.unwrap().is_empty());
+
+    provider.get_secret(&name).await.unwrap();
+    let freshness = provider.secret_freshness().await.unwrap();
+    assert_eq!(freshness.len(), 1);
+    assert_eq!(freshness[0].name, name);
+    assert!(freshness[0].age() < Duration::from_secs(60));
+}
@@ -1,17 +1,34 @@
 //! # Infrastructure Adapters
 //!
-//! Infrastructure implementations of blob storage and key vault interfaces.
+//! Infrastructure implementations of blob storage and key vault interfaces,
+//! and decorators adding retries, failover and circuit breakers to them.
 
 pub mod circuit_breaker_key_vault;
-pub mod filesystem_storage;
+pub mod failover_blob_storage;
 pub mod memory_key_vault;
+pub mod retrying_blob_storage;
+
+#[cfg(feature = "filesystem-storage")]
+pub mod filesystem_storage;
+
+#[cfg(feature = "azure-blob-storage")]
+pub mod azure_blob_storage;
 
 #[cfg(feature = "azure")]
 pub mod azure_key_vault;
 
 pub use circuit_breaker_key_vault::CircuitBreakerKeyVaultProvider;
-pub use filesystem_storage::FilesystemBlobStorage;
+pub use failover_blob_storage::{
+    FailoverBlobStorage, FailoverConfig, FailoverStatus, ReconcileReport, StorageEndpoint,
+};
 pub use memory_key_vault::{InMemoryKeyVaultProvider, InMemorySecretCache};
+pub use retrying_blob_storage::RetryingBlobStorage;
+
+#[cfg(feature = "filesystem-storage")]
+pub use filesystem_storage::FilesystemBlobStorage;
+
+#[cfg(feature = "azure-blob-storage")]
+pub use azure_blob_storage::{AzureBlobConfiguration, AzureBlobStorage};
 
 #[cfg(feature = "azure")]
 pub use azure_key_vault::AzureKeyVaultProvider;
@@ -0,0 +1,106 @@
+//! Retries of transient blob storage failures.
+//!
+//! Wraps any [`BlobStorage`] and retries operations that fail with a
+//! transient error (see [`BlobStorageError::is_transient`]) according to a
+//! [`RetryPolicy`], normally the `retry.blob_storage` section of the service
+//! configuration. Permanent errors and health checks are passed through
+//! unchanged.
+
+use std::{future::Future, sync::Arc};
+
+use async_trait::async_trait;
+use tracing::warn;
+
+use crate::blob_storage::{
+    BlobMetadata, BlobStorage, BlobStorageError, PayloadFilter, StorageHealthStatus, StoredWebhook,
+    WebhookPayload,
+};
+use crate::{EventId, RetryPolicy};
+
+/// Blob storage that retries transient failures of its inner storage.
+#[derive(Clone)]
+pub struct RetryingBlobStorage {
+    inner: Arc<dyn BlobStorage>,
+    policy: RetryPolicy,
+}
+
+impl RetryingBlobStorage {
+    /// Retry the operations of `inner` with `policy`.
+    pub fn new(inner: Arc<dyn BlobStorage>, policy: RetryPolicy) -> Self {
+        Self { inner, policy }
+    }
+
+    /// Run `operation` until it succeeds, fails permanently or runs out of
+    /// retries.
+    async fn with_retries<T, F, Fut>(
+        &self,
+        operation: &'static str,
+        mut attempt_once: F,
+    ) -> Result<T, BlobStorageError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, BlobStorageError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match attempt_once().await {
+                Err(error) if error.is_transient() && self.policy.should_retry(attempt) => {
+                    let delay = self.policy.calculate_delay(attempt);
+                    warn!(
+                        operation,
+                        attempt = attempt + 1,
+                        delay_ms = delay.as_millis() as u64,
+                        error = %error,
+                        "Transient blob storage failure, retrying"
+                    );
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl BlobStorage for RetryingBlobStorage {
+    async fn store_payload(
+        &self,
+        event_id: &EventId,
+        payload: &WebhookPayload,
+    ) -> Result<BlobMetadata, BlobStorageError> {
+        self.with_retries("store_payload", || {
+            self.inner.store_payload(event_id, payload)
+        })
+        .await
+    }
+
+    async fn get_payload(
+        &self,
+        event_id: &EventId,
+    ) -> Result<Option<StoredWebhook>, BlobStorageError> {
+        self.with_retries("get_payload", || self.inner.get_payload(event_id))
+            .await
+    }
+
+    async fn list_payloads(
+        &self,
+        filter: &PayloadFilter,
+    ) -> Result<Vec<BlobMetadata>, BlobStorageError> {
+        self.with_retries("list_payloads", || self.inner.list_payloads(filter))
+            .await
+    }
+
+    async fn delete_payload(&self, event_id: &EventId) -> Result<(), BlobStorageError> {
+        self.with_retries("delete_payload", || self.inner.delete_payload(event_id))
+            .await
+    }
+
+    async fn health_check(&self) -> Result<StorageHealthStatus, BlobStorageError> {
+        self.inner.health_check().await
+    }
+}
+
+#[cfg(test)]
+#[path = "retrying_blob_storage_tests.rs"]
+mod tests;
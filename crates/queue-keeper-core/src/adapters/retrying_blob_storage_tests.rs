@@ -0,0 +1,118 @@
+//! Tests for retries of transient blob storage failures.
+
+use super::*;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+/// Storage whose deletes fail `failures` times with `error` before they
+/// succeed.
+struct FlakyStorage {
+    failures: AtomicU32,
+    error: fn() -> BlobStorageError,
+    calls: AtomicU32,
+}
+
+impl FlakyStorage {
+    fn new(failures: u32, error: fn() -> BlobStorageError) -> Arc<Self> {
+        Arc::new(Self {
+            failures: AtomicU32::new(failures),
+            error,
+            calls: AtomicU32::new(0),
+        })
+    }
+}
+
+#[async_trait]
+impl BlobStorage for FlakyStorage {
+    async fn store_payload(
+        &self,
+        _event_id: &EventId,
+        _payload: &WebhookPayload,
+    ) -> Result<BlobMetadata, BlobStorageError> {
+        unimplemented!()
+    }
+
+    async fn get_payload(
+        &self,
+        _event_id: &EventId,
+    ) -> Result<Option<StoredWebhook>, BlobStorageError> {
+        unimplemented!()
+    }
+
+    async fn list_payloads(
+        &self,
+        _filter: &PayloadFilter,
+    ) -> Result<Vec<BlobMetadata>, BlobStorageError> {
+        unimplemented!()
+    }
+
+    async fn delete_payload(&self, _event_id: &EventId) -> Result<(), BlobStorageError> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        let remaining = self.failures.load(Ordering::SeqCst);
+        if remaining > 0 {
+            self.failures.store(remaining - 1, Ordering::SeqCst);
+            return Err((self.error)());
+        }
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<StorageHealthStatus, BlobStorageError> {
+        unimplemented!()
+    }
+}
+
+fn timeout() -> BlobStorageError {
+    BlobStorageError::Timeout { timeout_ms: 100 }
+}
+
+fn denied() -> BlobStorageError {
+    BlobStorageError::PermissionDenied {
+        operation: "delete".to_string(),
+    }
+}
+
+fn retrying(inner: Arc<FlakyStorage>, max_attempts: u32) -> RetryingBlobStorage {
+    let policy = RetryPolicy::new(
+        max_attempts,
+        Duration::from_millis(1),
+        Duration::from_millis(1),
+        1.0,
+    )
+    .without_jitter();
+    RetryingBlobStorage::new(inner, policy)
+}
+
+/// Verify that transient failures are retried until the operation succeeds.
+#[tokio::test]
+async fn test_transient_failures_retried() {
+    let inner = FlakyStorage::new(2, timeout);
+    let storage = retrying(inner.clone(), 3);
+
+    storage.delete_payload(&EventId::new()).await.unwrap();
+
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+}
+
+/// Verify that the last transient error is returned once retries run out.
+#[tokio::test]
+async fn test_retries_exhausted() {
+    let inner = FlakyStorage::new(5, timeout);
+    let storage = retrying(inner.clone(), 2);
+
+    let error = storage.delete_payload(&EventId::new()).await.unwrap_err();
+
+    assert!(matches!(error, BlobStorageError::Timeout { .. }));
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+}
+
+/// Verify that permanent failures are not retried.
+#[tokio::test]
+async fn test_permanent_failures_not_retried() {
+    let inner = FlakyStorage::new(1, denied);
+    let storage = retrying(inner.clone(), 3);
+
+    let error = storage.delete_payload(&EventId::new()).await.unwrap_err();
+
+    assert!(matches!(error, BlobStorageError::PermissionDenied { .. }));
+    assert_eq!(inner.calls.load(Ordering::SeqCst), 1);
+}
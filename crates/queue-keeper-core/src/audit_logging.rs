@@ -2418,6 +2418,16 @@ impl AuditQuery for FilesystemAuditLogger {
             all_events.retain(|e| event_types.contains(&e.event_type));
         }
 
+        // Custom filters must all match entries of the additional context data
+        if !query.custom_filters.is_empty() {
+            all_events.retain(|e| {
+                query
+                    .custom_filters
+                    .iter()
+                    .all(|(key, value)| e.context.additional_data.get(key) == Some(value))
+            });
+        }
+
         // Sort by timestamp (descending by default)
         match pagination.sort_order {
             SortOrder::Descending => {
@@ -253,6 +253,59 @@ async fn test_query_events_filter_by_time_range() {
     assert_eq!(result.events.len(), 2);
 }
 
+/// Verify that query_events filters by custom filters correctly.
+///
+/// Custom filters match entries of the context's additional data; an event
+/// must match every filter to be returned.
+#[tokio::test]
+async fn test_query_events_filter_by_custom_filters() {
+    let (logger, _temp_dir) = create_test_logger();
+
+    for queue in ["queue-a", "queue-b", "queue-a"] {
+        let mut event = create_test_event(AuditEventType::WebhookProcessing);
+        event
+            .context
+            .additional_data
+            .insert("queue_name".to_string(), queue.to_string());
+        logger.log_event(event).await.expect("Failed to log event");
+    }
+    logger
+        .log_event(create_test_event(AuditEventType::WebhookProcessing))
+        .await
+        .expect("Failed to log event");
+
+    let query_spec = AuditQuerySpec {
+        time_range: None,
+        event_types: None,
+        actors: None,
+        resources: None,
+        actions: None,
+        results: None,
+        search_text: None,
+        custom_filters: HashMap::from([("queue_name".to_string(), "queue-a".to_string())]),
+    };
+
+    let pagination = PaginationOptions {
+        page: 1,
+        per_page: 10,
+        sort_by: None,
+        sort_order: SortOrder::Descending,
+    };
+
+    let result = logger
+        .query_events(query_spec, pagination)
+        .await
+        .expect("Query should succeed");
+
+    assert_eq!(result.total_count, 2);
+    assert!(result.events.iter().all(|e| e
+        .context
+        .additional_data
+        .get("queue_name")
+        .map(String::as_str)
+        == Some("queue-a")));
+}
+
 /// Verify that pagination works correctly.
 ///
 /// Creates multiple events, then queries with pagination to verify
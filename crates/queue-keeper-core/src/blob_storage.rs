@@ -4,12 +4,14 @@
 //!
 //! See specs/interfaces/blob-storage.md for complete specification.
 
+use crate::payload_encryption::PayloadEncryption;
 use crate::{EventId, Repository, Timestamp};
 use async_trait::async_trait;
 use bytes::Bytes;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
 use std::collections::HashMap;
+use std::io::{Read, Write};
 use thiserror::Error;
 
 // Custom serialization for Bytes
@@ -306,12 +308,148 @@ pub struct BlobMetadata {
     pub created_at: Timestamp,
 
     /// SHA-256 checksum of the stored payload (hex-encoded)
+    ///
+    /// Covers the uncompressed body, or the ciphertext when the body is
+    /// encrypted.
     pub checksum_sha256: String,
 
+    /// Persisted format version of the blob
+    ///
+    /// Blobs written before versioning read as
+    /// [`LEGACY_FORMAT_VERSION`](crate::storage_format::LEGACY_FORMAT_VERSION).
+    /// See [`storage_format`](crate::storage_format).
+    #[serde(default = "crate::storage_format::legacy_format_version")]
+    pub format_version: u32,
+
+    /// Compression of `payload.body` in the stored blob
+    ///
+    /// Blobs are returned with the body decompressed; this field still
+    /// reports how the body was stored. Blobs written without compression
+    /// omit the field.
+    #[serde(default, skip_serializing_if = "ContentEncoding::is_identity")]
+    pub content_encoding: ContentEncoding,
+
+    /// Encryption of `payload.body` in the stored blob
+    ///
+    /// Like `content_encoding`, still set on blobs returned decrypted.
+    /// Unencrypted blobs omit the field. See
+    /// [`payload_encryption`](crate::payload_encryption).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<PayloadEncryption>,
+
     /// Payload metadata
     pub metadata: PayloadMetadata,
 }
 
+/// Compression applied to payload bodies before they are stored
+///
+/// The checksum of a stored blob is always that of the uncompressed body, so
+/// a payload keeps its checksum whatever the storage provider compresses it
+/// with.
+///
+/// # Examples
+///
+/// ```
+/// use queue_keeper_core::blob_storage::ContentEncoding;
+/// use bytes::Bytes;
+///
+/// let body = Bytes::from(r#"{"action":"opened","number":1}"#.repeat(100));
+/// let stored = ContentEncoding::Zstd.encode(&body).unwrap();
+/// assert!(stored.len() < body.len());
+/// assert_eq!(ContentEncoding::Zstd.decode(&stored).unwrap(), body);
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentEncoding {
+    /// Body stored as received
+    #[default]
+    Identity,
+
+    /// Body compressed with gzip
+    Gzip,
+
+    /// Body compressed with Zstandard
+    Zstd,
+}
+
+impl ContentEncoding {
+    /// Get string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Identity => "identity",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+        }
+    }
+
+    /// Whether bodies are stored uncompressed
+    pub fn is_identity(&self) -> bool {
+        *self == Self::Identity
+    }
+
+    /// Compress `body`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlobStorageError::SerializationFailed`] if compression fails.
+    pub fn encode(&self, body: &Bytes) -> Result<Bytes, BlobStorageError> {
+        let encoded = match self {
+            Self::Identity => return Ok(body.clone()),
+            Self::Gzip => {
+                let mut encoder = flate2::write::GzEncoder::new(
+                    Vec::with_capacity(body.len() / 4),
+                    flate2::Compression::default(),
+                );
+                encoder
+                    .write_all(body)
+                    .and_then(|()| encoder.finish())
+                    .map_err(|e| self.failed("compress", e))?
+            }
+            Self::Zstd => zstd::bulk::compress(body, zstd::DEFAULT_COMPRESSION_LEVEL)
+                .map_err(|e| self.failed("compress", e))?,
+        };
+        Ok(Bytes::from(encoded))
+    }
+
+    /// Decompress `body`
+    ///
+    /// # Errors
+    ///
+    /// Returns [`BlobStorageError::SerializationFailed`] if `body` is not
+    /// valid compressed data.
+    pub fn decode(&self, body: &Bytes) -> Result<Bytes, BlobStorageError> {
+        let decoded = match self {
+            Self::Identity => return Ok(body.clone()),
+            Self::Gzip => {
+                let mut decoded = Vec::with_capacity(body.len() * 4);
+                flate2::read::GzDecoder::new(body.as_ref())
+                    .read_to_end(&mut decoded)
+                    .map(|_| decoded)
+            }
+            Self::Zstd => zstd::stream::decode_all(body.as_ref()),
+        }
+        .map_err(|e| self.failed("decompress", e))?;
+        Ok(Bytes::from(decoded))
+    }
+
+    fn failed(&self, operation: &str, error: std::io::Error) -> BlobStorageError {
+        BlobStorageError::SerializationFailed {
+            message: format!(
+                "Failed to {} {} payload: {}",
+                operation,
+                self.as_str(),
+                error
+            ),
+        }
+    }
+}
+
+impl std::fmt::Display for ContentEncoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// Complete webhook data retrieved from storage
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct StoredWebhook {
@@ -322,6 +460,43 @@ pub struct StoredWebhook {
     pub payload: WebhookPayload,
 }
 
+impl StoredWebhook {
+    /// Compress the payload body for writing, recording `encoding` in the
+    /// blob metadata
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the body is already compressed or compression
+    /// fails.
+    pub fn encode(mut self, encoding: ContentEncoding) -> Result<Self, BlobStorageError> {
+        if !self.metadata.content_encoding.is_identity() {
+            return Err(BlobStorageError::SerializationFailed {
+                message: format!(
+                    "Payload {} is already {} compressed",
+                    self.metadata.event_id, self.metadata.content_encoding
+                ),
+            });
+        }
+        self.payload.body = encoding.encode(&self.payload.body)?;
+        self.metadata.content_encoding = encoding;
+        Ok(self)
+    }
+
+    /// Decompress the payload body of a blob as read from storage
+    ///
+    /// Encrypted blobs must be decrypted first; see
+    /// [`PayloadEncryptor::decrypt`](crate::payload_encryption::PayloadEncryptor::decrypt).
+    /// `metadata.content_encoding` keeps reporting how the body was stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the body is not valid compressed data.
+    pub fn decode(mut self) -> Result<Self, BlobStorageError> {
+        self.payload.body = self.metadata.content_encoding.decode(&self.payload.body)?;
+        Ok(self)
+    }
+}
+
 /// Filter criteria for listing stored payloads
 #[derive(Debug, Clone, Default)]
 pub struct PayloadFilter {
@@ -351,6 +526,16 @@ pub struct DateRange {
     pub end: Timestamp,
 }
 
+impl DateRange {
+    /// Range from `start` (inclusive) with no upper bound.
+    pub fn since(start: Timestamp) -> Self {
+        Self {
+            start,
+            end: Timestamp::from_datetime(chrono::DateTime::<chrono::Utc>::MAX_UTC),
+        }
+    }
+}
+
 /// Health status of blob storage
 #[derive(Debug, Clone)]
 pub struct StorageHealthStatus {
@@ -422,6 +607,14 @@ pub enum BlobStorageError {
     #[error("Network timeout: {timeout_ms}ms")]
     Timeout { timeout_ms: u64 },
 
+    /// Blob was written in a format version this release cannot read
+    #[error("Unsupported format version {version} for {path}")]
+    UnsupportedFormatVersion { path: String, version: u32 },
+
+    /// Payload encryption or decryption failed
+    #[error("Encryption failed: {message}")]
+    EncryptionFailed { message: String },
+
     /// Checksum mismatch detected (tampered data)
     #[error("Checksum mismatch for {path}: expected {expected}, got {actual}")]
     ChecksumMismatch {
@@ -36,6 +36,9 @@ fn test_blob_metadata_creation() {
         content_type: "application/json".to_string(),
         created_at: Timestamp::now(),
         checksum_sha256: "test-checksum".to_string(),
+        format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+        content_encoding: ContentEncoding::Identity,
+        encryption: None,
         metadata: PayloadMetadata {
             event_id,
             event_type: "issues".to_string(),
@@ -74,6 +77,9 @@ fn test_stored_webhook_structure() {
             content_type: "application/json".to_string(),
             created_at: Timestamp::now(),
             checksum_sha256: "test-checksum".to_string(),
+            format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+            content_encoding: ContentEncoding::Identity,
+            encryption: None,
             metadata: payload.metadata.clone(),
         },
         payload: payload.clone(),
@@ -114,6 +120,15 @@ fn test_payload_filter_with_values() {
     assert_eq!(filter.offset, Some(50));
 }
 
+#[test]
+fn test_date_range_since_has_no_upper_bound() {
+    let start = Timestamp::now();
+    let range = DateRange::since(start);
+
+    assert_eq!(range.start, start);
+    assert!(range.end > start.add_seconds(100 * 365 * 24 * 60 * 60));
+}
+
 // ============================================================================
 // Error Classification Tests
 // ============================================================================
@@ -345,6 +360,9 @@ fn test_blob_metadata_serialization() {
         content_type: "application/json".to_string(),
         created_at: Timestamp::now(),
         checksum_sha256: "test-checksum".to_string(),
+        format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+        content_encoding: ContentEncoding::Identity,
+        encryption: None,
         metadata: PayloadMetadata {
             event_id,
             event_type: "pull_request".to_string(),
@@ -364,6 +382,89 @@ fn test_blob_metadata_serialization() {
     assert_eq!(metadata.content_type, deserialized.content_type);
 }
 
+/// Every encoding restores the original body, and invalid compressed data
+/// is reported rather than returned.
+#[test]
+fn test_content_encoding_round_trip() {
+    let body = Bytes::from(r#"{"action":"synchronize","commits":[]}"#.repeat(50));
+
+    for encoding in [
+        ContentEncoding::Identity,
+        ContentEncoding::Gzip,
+        ContentEncoding::Zstd,
+    ] {
+        let encoded = encoding.encode(&body).unwrap();
+        if !encoding.is_identity() {
+            assert!(encoded.len() < body.len(), "{} did not compress", encoding);
+            assert!(matches!(
+                encoding.decode(&body),
+                Err(BlobStorageError::SerializationFailed { .. })
+            ));
+        }
+        assert_eq!(encoding.decode(&encoded).unwrap(), body);
+    }
+}
+
+/// The encoding is omitted for uncompressed blobs, so they serialize as
+/// before, and blobs without it read as uncompressed.
+#[test]
+fn test_stored_webhook_encode_and_decode() {
+    let event_id = EventId::new();
+    let body = Bytes::from(r#"{"action":"opened"}"#.repeat(20));
+    let stored = StoredWebhook {
+        metadata: BlobMetadata {
+            event_id,
+            blob_path: event_id.to_blob_path(),
+            size_bytes: 0,
+            content_type: "application/json".to_string(),
+            created_at: Timestamp::now(),
+            checksum_sha256: compute_checksum(&body),
+            format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+            content_encoding: ContentEncoding::Identity,
+            encryption: None,
+            metadata: PayloadMetadata {
+                event_id,
+                event_type: "issues".to_string(),
+                repository: None,
+                signature_valid: true,
+                received_at: Timestamp::now(),
+                delivery_id: None,
+            },
+        },
+        payload: WebhookPayload {
+            body: body.clone(),
+            headers: HashMap::new(),
+            metadata: PayloadMetadata {
+                event_id,
+                event_type: "issues".to_string(),
+                repository: None,
+                signature_valid: true,
+                received_at: Timestamp::now(),
+                delivery_id: None,
+            },
+        },
+    };
+    let plain = serde_json::to_value(&stored).unwrap();
+    assert!(plain["metadata"].get("content_encoding").is_none());
+
+    let encoded = stored.clone().encode(ContentEncoding::Gzip).unwrap();
+    assert_eq!(encoded.metadata.content_encoding, ContentEncoding::Gzip);
+    assert_ne!(encoded.payload.body, body);
+    assert!(encoded.clone().encode(ContentEncoding::Zstd).is_err());
+
+    let json = serde_json::to_string(&encoded).unwrap();
+    assert!(json.contains(r#""content_encoding":"gzip""#));
+    let decoded = serde_json::from_str::<StoredWebhook>(&json)
+        .unwrap()
+        .decode()
+        .unwrap();
+    assert_eq!(decoded.payload.body, body);
+    assert_eq!(decoded.metadata.content_encoding, ContentEncoding::Gzip);
+
+    let legacy: StoredWebhook = serde_json::from_value(plain).unwrap();
+    assert_eq!(legacy.decode().unwrap().payload.body, body);
+}
+
 #[test]
 fn test_stored_webhook_serialization() {
     let event_id = EventId::new();
@@ -388,6 +489,9 @@ fn test_stored_webhook_serialization() {
             content_type: "application/json".to_string(),
             created_at: Timestamp::now(),
             checksum_sha256: "test-checksum".to_string(),
+            format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+            content_encoding: ContentEncoding::Identity,
+            encryption: None,
             metadata: payload.metadata.clone(),
         },
         payload: payload.clone(),
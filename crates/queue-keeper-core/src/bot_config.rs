@@ -3,21 +3,36 @@
 //! Defines how Queue-Keeper routes normalized events to specific bot queues based on
 //! static subscription configuration. Implements REQ-010 (Bot Subscription Configuration).
 //!
+//! The configuration in use is held by a [`SharedBotConfiguration`], which
+//! replaces it atomically at runtime; a [`ConfigurationWatcher`] reloads it
+//! when its file changes.
+//!
 //! See specs/interfaces/bot-configuration.md for complete specification.
 
-use crate::{webhook::WrappedEvent, BotName, EventId, QueueName, Repository, Timestamp};
+use crate::{
+    field_encryption::FieldEncryptionConfig, http_delivery::HttpDestinationConfig,
+    payload_projection::PayloadProjectionConfig, wasm_transform::WasmTransformConfig,
+    webhook::WrappedEvent, BotName, EventId, QueueName, Repository, Timestamp, User, UserType,
+};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, path::Path, str::FromStr};
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex, OnceLock, RwLock},
+    time::{Duration, SystemTime},
+};
 
 // ============================================================================
 // Core Configuration Types
 // ============================================================================
 
-/// Complete bot configuration loaded at startup
+/// Complete bot configuration
 ///
 /// Contains all bot subscription definitions and routing rules.
-/// Configuration is immutable after loading and validation.
+/// A configuration is immutable after loading and validation; a new version
+/// replaces it through [`SharedBotConfiguration`].
 ///
 /// See specs/interfaces/bot-configuration.md
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -150,6 +165,61 @@ impl BotConfiguration {
                     ));
                 }
             }
+
+            // Validate sender filter if present
+            if let Some(ref filter) = bot.sender_filter {
+                if let Err(e) = filter.validate() {
+                    errors.push(format!(
+                        "Bot '{}': Invalid sender filter: {}",
+                        bot.name.as_str(),
+                        e
+                    ));
+                }
+            }
+
+            // Validate field encryption settings if present
+            if let Some(ref encryption) = bot.encryption {
+                if let Err(e) = encryption.validate() {
+                    errors.push(format!(
+                        "Bot '{}': Invalid encryption settings: {}",
+                        bot.name.as_str(),
+                        e
+                    ));
+                }
+            }
+
+            // Validate transform settings if present
+            if let Some(ref transform) = bot.transform {
+                if let Err(e) = transform.validate() {
+                    errors.push(format!(
+                        "Bot '{}': Invalid transform settings: {}",
+                        bot.name.as_str(),
+                        e
+                    ));
+                }
+            }
+
+            // Validate payload projection settings if present
+            if let Some(ref projection) = bot.projection {
+                if let Err(e) = projection.validate() {
+                    errors.push(format!(
+                        "Bot '{}': Invalid projection settings: {}",
+                        bot.name.as_str(),
+                        e
+                    ));
+                }
+            }
+
+            // Validate HTTP destination settings if present
+            if let Some(ref http) = bot.http {
+                if let Err(e) = http.validate() {
+                    errors.push(format!(
+                        "Bot '{}': Invalid HTTP destination: {}",
+                        bot.name.as_str(),
+                        e
+                    ));
+                }
+            }
         }
 
         if !errors.is_empty() {
@@ -188,8 +258,39 @@ pub struct BotSubscription {
     /// Optional repository filters
     pub repository_filter: Option<RepositoryFilter>,
 
+    /// Optional filter on the account that triggered the event.
+    ///
+    /// `None` accepts events from every sender.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender_filter: Option<SenderFilter>,
+
     /// Bot-specific configuration options
     pub config: BotSpecificConfig,
+
+    /// Payload fields to encrypt with this bot's key before delivery.
+    ///
+    /// `None` delivers the payload unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<FieldEncryptionConfig>,
+
+    /// WebAssembly transform run over events before delivery to this bot.
+    ///
+    /// `None` delivers every matching event unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transform: Option<WasmTransformConfig>,
+
+    /// Projection of the payload delivered to this bot.
+    ///
+    /// `None` delivers the whole payload.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub projection: Option<PayloadProjectionConfig>,
+
+    /// HTTP endpoint receiving this bot's events instead of its queue.
+    ///
+    /// `None` delivers to the queue. The queue name still identifies the
+    /// bot's deliveries in results, metrics and the dead letter queue.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub http: Option<HttpDestinationConfig>,
 }
 
 impl BotSubscription {
@@ -236,6 +337,13 @@ impl BotSubscription {
             }
         }
 
+        // Check sender filter if specified
+        if let Some(ref filter) = self.sender_filter {
+            if !filter.matches(event.sender.as_ref()) {
+                return false;
+            }
+        }
+
         true
     }
 
@@ -457,6 +565,85 @@ impl RepositoryFilter {
     }
 }
 
+/// Sender-based filtering for bot subscriptions
+///
+/// Matches on the account that triggered the event
+/// ([`WrappedEvent::sender`]). The common use is ignoring events caused by
+/// bots, including the subscribing bot's own actions:
+///
+/// ```yaml
+/// sender_filter:
+///   exclude_types: [Bot]
+///   exclude_logins: ["release-automation"]
+/// ```
+///
+/// A sender counts as `Bot` when GitHub reports it as one or its login ends
+/// in `[bot]`. Events without a sender pass unless `include_types` is set.
+///
+/// See specs/interfaces/bot-configuration.md
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SenderFilter {
+    /// Sender types to accept; empty accepts every type
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub include_types: Vec<UserType>,
+
+    /// Sender types to reject
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_types: Vec<UserType>,
+
+    /// Sender logins to reject (case-insensitive)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude_logins: Vec<String>,
+}
+
+impl SenderFilter {
+    /// Check if this filter accepts events triggered by `sender`
+    pub fn matches(&self, sender: Option<&User>) -> bool {
+        let Some(sender) = sender else {
+            return self.include_types.is_empty();
+        };
+
+        let sender_type = if sender.is_bot() {
+            UserType::Bot
+        } else {
+            sender.user_type.clone()
+        };
+
+        if !self.include_types.is_empty() && !self.include_types.contains(&sender_type) {
+            return false;
+        }
+        if self.exclude_types.contains(&sender_type) {
+            return false;
+        }
+        !self
+            .exclude_logins
+            .iter()
+            .any(|login| login.eq_ignore_ascii_case(&sender.login))
+    }
+
+    /// Validate the filter (no empty logins, no type both included and excluded)
+    pub fn validate(&self) -> Result<(), String> {
+        if self
+            .exclude_logins
+            .iter()
+            .any(|login| login.trim().is_empty())
+        {
+            return Err("exclude_logins must not contain empty logins".to_string());
+        }
+        if let Some(conflict) = self
+            .include_types
+            .iter()
+            .find(|t| self.exclude_types.contains(t))
+        {
+            return Err(format!(
+                "sender type {:?} is both included and excluded",
+                conflict
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Bot-specific configuration options
 ///
 /// Opaque configuration data that is passed to bots without interpretation
@@ -618,6 +805,9 @@ pub struct QueueDestination {
 
     /// Bot-specific configuration to include with event
     pub bot_config: BotSpecificConfig,
+
+    /// HTTP endpoint receiving the event instead of the queue
+    pub http: Option<HttpDestinationConfig>,
 }
 
 impl QueueDestination {
@@ -633,13 +823,25 @@ impl QueueDestination {
             queue_name,
             ordered,
             bot_config,
+            http: None,
         }
     }
 
+    /// Deliver to an HTTP endpoint instead of the queue
+    pub fn with_http(mut self, http: HttpDestinationConfig) -> Self {
+        self.http = Some(http);
+        self
+    }
+
     /// Check if this destination requires ordered processing
     pub fn requires_ordering(&self) -> bool {
         self.ordered
     }
+
+    /// Check if this destination is an HTTP endpoint
+    pub fn is_http(&self) -> bool {
+        self.http.is_some()
+    }
 }
 
 /// Result of event routing decision
@@ -900,6 +1102,195 @@ impl ConfigurationLoader for FileConfigurationLoader {
     }
 }
 
+// ============================================================================
+// Runtime Reload
+// ============================================================================
+
+/// One version of the bot configuration.
+#[derive(Debug, Clone)]
+pub struct BotConfigurationVersion {
+    /// Version number, starting at 1 and incremented by every replacement.
+    pub version: u64,
+
+    /// When this version was loaded.
+    pub loaded_at: Timestamp,
+
+    /// The configuration.
+    pub configuration: Arc<BotConfiguration>,
+}
+
+type ReloadCheck =
+    Box<dyn Fn(&BotConfiguration, &BotConfiguration) -> Result<(), BotConfigError> + Send + Sync>;
+type ReloadListener = Box<dyn Fn(&BotConfigurationVersion) + Send + Sync>;
+
+/// The bot configuration in use, replaceable at runtime.
+///
+/// Readers take a [`current`](Self::current) snapshot per operation, so an
+/// event is routed entirely under one version. [`replace`](Self::replace)
+/// validates a new configuration and swaps it in atomically; a rejected
+/// configuration leaves the current version in place.
+pub struct SharedBotConfiguration {
+    current: RwLock<BotConfigurationVersion>,
+    check: OnceLock<ReloadCheck>,
+    listeners: Mutex<Vec<ReloadListener>>,
+}
+
+impl std::fmt::Debug for SharedBotConfiguration {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedBotConfiguration")
+            .field("current", &self.current)
+            .finish()
+    }
+}
+
+impl SharedBotConfiguration {
+    /// Hold `configuration` as version 1.
+    pub fn new(configuration: BotConfiguration) -> Self {
+        Self {
+            current: RwLock::new(BotConfigurationVersion {
+                version: 1,
+                loaded_at: Timestamp::now(),
+                configuration: Arc::new(configuration),
+            }),
+            check: OnceLock::new(),
+            listeners: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// The configuration in use.
+    pub fn current(&self) -> Arc<BotConfiguration> {
+        self.current.read().unwrap().configuration.clone()
+    }
+
+    /// The configuration in use with its version.
+    pub fn snapshot(&self) -> BotConfigurationVersion {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Version number of the configuration in use.
+    pub fn version(&self) -> u64 {
+        self.current.read().unwrap().version
+    }
+
+    /// Reject replacements for which `check(current, new)` fails, in
+    /// addition to [`BotConfiguration::validate`]. Only the first call has an
+    /// effect.
+    pub fn set_reload_check(
+        &self,
+        check: impl Fn(&BotConfiguration, &BotConfiguration) -> Result<(), BotConfigError>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        let _ = self.check.set(Box::new(check));
+    }
+
+    /// Call `listener` with every new version.
+    pub fn on_replace(&self, listener: impl Fn(&BotConfigurationVersion) + Send + Sync + 'static) {
+        self.listeners.lock().unwrap().push(Box::new(listener));
+    }
+
+    /// Validate `configuration` and make it the configuration in use.
+    ///
+    /// Returns the new version number.
+    ///
+    /// # Errors
+    /// Returns the validation or reload check error; the current version
+    /// stays in use.
+    pub fn replace(&self, configuration: BotConfiguration) -> Result<u64, BotConfigError> {
+        configuration.validate()?;
+
+        let replaced = {
+            let mut current = self.current.write().unwrap();
+            if let Some(check) = self.check.get() {
+                check(&current.configuration, &configuration)?;
+            }
+            *current = BotConfigurationVersion {
+                version: current.version + 1,
+                loaded_at: Timestamp::now(),
+                configuration: Arc::new(configuration),
+            };
+            current.clone()
+        };
+
+        for listener in self.listeners.lock().unwrap().iter() {
+            listener(&replaced);
+        }
+        Ok(replaced.version)
+    }
+}
+
+/// Reloads a [`SharedBotConfiguration`] from its file when the file changes.
+///
+/// Changes are detected by polling the file's modification time and size.
+/// A changed file that fails to parse or validate is logged and ignored, so
+/// the previous configuration keeps routing events until the file is fixed.
+#[derive(Debug)]
+pub struct ConfigurationWatcher {
+    path: PathBuf,
+    target: Arc<SharedBotConfiguration>,
+    interval: Duration,
+    state: Option<(Option<SystemTime>, u64)>,
+}
+
+impl ConfigurationWatcher {
+    /// Watch `path`, checking every `interval`, and reload `target` from it.
+    pub fn new(path: PathBuf, target: Arc<SharedBotConfiguration>, interval: Duration) -> Self {
+        let state = file_state(&path);
+        Self {
+            path,
+            target,
+            interval,
+            state,
+        }
+    }
+
+    /// The watched file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Reload the configuration if the file changed since the last poll.
+    ///
+    /// Returns `None` when the file is unchanged, otherwise the new version
+    /// or the reason the file was rejected.
+    pub fn poll(&mut self) -> Option<Result<u64, BotConfigError>> {
+        let state = file_state(&self.path);
+        if state == self.state {
+            return None;
+        }
+        self.state = state;
+        Some(BotConfiguration::load_from_file(&self.path).and_then(|c| self.target.replace(c)))
+    }
+
+    /// Poll every `interval`, logging each reload. Runs until the future is
+    /// dropped.
+    pub async fn run(mut self) {
+        loop {
+            tokio::time::sleep(self.interval).await;
+            match self.poll() {
+                Some(Ok(version)) => tracing::info!(
+                    path = %self.path.display(),
+                    version,
+                    "Bot configuration reloaded"
+                ),
+                Some(Err(e)) => tracing::warn!(
+                    path = %self.path.display(),
+                    error = %e,
+                    "Bot configuration file changed but was rejected; keeping the current configuration"
+                ),
+                None => {}
+            }
+        }
+    }
+}
+
+fn file_state(path: &Path) -> Option<(Option<SystemTime>, u64)> {
+    std::fs::metadata(path)
+        .ok()
+        .map(|metadata| (metadata.modified().ok(), metadata.len()))
+}
+
 /// Default event matcher implementation
 ///
 /// See specs/interfaces/bot-configuration.md
@@ -933,6 +1324,13 @@ impl EventMatcher for DefaultEventMatcher {
             }
         }
 
+        // Check sender filter if present
+        if let Some(ref filter) = subscription.sender_filter {
+            if !filter.matches(event.sender.as_ref()) {
+                return false;
+            }
+        }
+
         true
     }
 
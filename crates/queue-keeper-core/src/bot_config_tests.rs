@@ -44,6 +44,13 @@ fn test_queue_destination() {
 
     let destination = QueueDestination::new(bot_name, queue_name, true, config);
     assert!(destination.requires_ordering());
+    assert!(!destination.is_http());
+
+    let destination = destination.with_http(crate::HttpDestinationConfig::new(
+        "https://hooks.example.com/events",
+        "queue-keeper-test-bot-signing",
+    ));
+    assert!(destination.is_http());
 }
 
 #[test]
@@ -287,6 +294,112 @@ mod repository_filter_tests {
     }
 }
 
+// ============================================================================
+// SenderFilter Tests
+// ============================================================================
+
+mod sender_filter_tests {
+    use super::*;
+
+    fn sender(login: &str, user_type: UserType) -> User {
+        User {
+            id: UserId::new(42),
+            login: login.to_string(),
+            user_type,
+        }
+    }
+
+    fn event_from(sender: Option<User>) -> WrappedEvent {
+        let mut event = WrappedEvent::new(
+            "github".to_string(),
+            "issues".to_string(),
+            Some("opened".to_string()),
+            None,
+            serde_json::json!({}),
+            None,
+        );
+        event.sender = sender;
+        event
+    }
+
+    #[test]
+    fn test_exclude_bots() {
+        let filter = SenderFilter {
+            exclude_types: vec![UserType::Bot],
+            ..Default::default()
+        };
+        assert!(filter.matches(Some(&sender("octocat", UserType::User))));
+        assert!(!filter.matches(Some(&sender("renovate[bot]", UserType::Bot))));
+        // A `[bot]` login counts as a bot even when reported as a user
+        assert!(!filter.matches(Some(&sender("legacy[bot]", UserType::User))));
+        assert!(filter.matches(None));
+    }
+
+    #[test]
+    fn test_include_types() {
+        let filter = SenderFilter {
+            include_types: vec![UserType::User],
+            ..Default::default()
+        };
+        assert!(filter.matches(Some(&sender("octocat", UserType::User))));
+        assert!(!filter.matches(Some(&sender("acme", UserType::Organization))));
+        assert!(!filter.matches(None));
+    }
+
+    #[test]
+    fn test_exclude_logins_case_insensitive() {
+        let filter = SenderFilter {
+            exclude_logins: vec!["Release-Automation".to_string()],
+            ..Default::default()
+        };
+        assert!(!filter.matches(Some(&sender("release-automation", UserType::User))));
+        assert!(filter.matches(Some(&sender("octocat", UserType::User))));
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(SenderFilter::default().validate().is_ok());
+
+        let empty_login = SenderFilter {
+            exclude_logins: vec![" ".to_string()],
+            ..Default::default()
+        };
+        assert!(empty_login.validate().is_err());
+
+        let conflicting = SenderFilter {
+            include_types: vec![UserType::Bot],
+            exclude_types: vec![UserType::Bot],
+            ..Default::default()
+        };
+        assert!(conflicting.validate().is_err());
+    }
+
+    #[test]
+    fn test_subscription_ignores_bot_events() {
+        let sender_filter: SenderFilter = serde_yaml::from_str("exclude_types: [Bot]").unwrap();
+        let subscription = BotSubscription {
+            name: BotName::new("triage-bot").unwrap(),
+            queue: QueueName::new("queue-keeper-triage-bot").unwrap(),
+            events: vec![EventTypePattern::Exact("issues".to_string())],
+            ordered: false,
+            repository_filter: None,
+            config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: Some(sender_filter),
+        };
+
+        let human = event_from(Some(sender("octocat", UserType::User)));
+        let bot = event_from(Some(sender("triage-bot[bot]", UserType::Bot)));
+        assert!(subscription.matches_event(&human));
+        assert!(!subscription.matches_event(&bot));
+        assert!(DefaultEventMatcher.matches_subscription(&human, &subscription));
+        assert!(!DefaultEventMatcher.matches_subscription(&bot, &subscription));
+    }
+}
+
 // ============================================================================
 // BotConfiguration Tests
 // ============================================================================
@@ -304,6 +417,11 @@ mod bot_configuration_tests {
                     ordered: true,
                     repository_filter: None,
                     config: BotSpecificConfig::new(),
+                    encryption: None,
+                    transform: None,
+                    projection: None,
+                    http: None,
+                    sender_filter: None,
                 },
                 BotSubscription {
                     name: BotName::new("bot2").unwrap(),
@@ -312,6 +430,11 @@ mod bot_configuration_tests {
                     ordered: false,
                     repository_filter: Some(RepositoryFilter::Owner("test-org".to_string())),
                     config: BotSpecificConfig::new(),
+                    encryption: None,
+                    transform: None,
+                    projection: None,
+                    http: None,
+                    sender_filter: None,
                 },
             ],
             settings: BotConfigurationSettings::default(),
@@ -348,6 +471,11 @@ mod bot_configuration_tests {
             ordered: false,
             repository_filter: None,
             config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
         });
 
         let result = config.validate();
@@ -377,6 +505,24 @@ mod bot_configuration_tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn test_validation_invalid_http_destination() {
+        let mut config = create_test_configuration();
+        config.bots[0].http = Some(crate::HttpDestinationConfig::new(
+            "hooks.example.com/events",
+            "queue-keeper-test-bot1-signing",
+        ));
+
+        match config.validate() {
+            Err(BotConfigError::ValidationError { errors }) => {
+                assert!(errors
+                    .iter()
+                    .any(|e| e.starts_with("Bot 'bot1': Invalid HTTP destination")));
+            }
+            _ => panic!("Expected ValidationError"),
+        }
+    }
+
     #[test]
     fn test_get_target_bots_single_match() {
         let config = create_test_configuration();
@@ -397,6 +543,11 @@ mod bot_configuration_tests {
             ordered: false,
             repository_filter: None,
             config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
         });
 
         let event = create_test_event("issues.opened", "any-org", "any-repo");
@@ -469,6 +620,11 @@ mod bot_subscription_tests {
             ordered: true,
             repository_filter: None,
             config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
         };
 
         let event = create_test_event("issues.opened", "owner", "repo");
@@ -484,6 +640,11 @@ mod bot_subscription_tests {
             ordered: true,
             repository_filter: None,
             config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
         };
 
         let event = create_test_event("issues.closed", "owner", "repo");
@@ -499,6 +660,11 @@ mod bot_subscription_tests {
             ordered: true,
             repository_filter: Some(RepositoryFilter::Owner("specific-owner".to_string())),
             config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
         };
 
         let matching = create_test_event("issues.opened", "specific-owner", "repo");
@@ -517,6 +683,11 @@ mod bot_subscription_tests {
             ordered: true,
             repository_filter: None,
             config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
         };
 
         let parallel = BotSubscription {
@@ -526,6 +697,11 @@ mod bot_subscription_tests {
             ordered: false,
             repository_filter: None,
             config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
         };
 
         assert!(ordered.requires_ordering());
@@ -610,6 +786,11 @@ mod event_matcher_tests {
             ordered: true,
             repository_filter: Some(RepositoryFilter::Owner("test-owner".to_string())),
             config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
         };
 
         let matching_event = create_test_event("issues.opened", "test-owner", "repo");
@@ -641,6 +822,11 @@ mod configuration_loading_tests {
                 ordered: true,
                 repository_filter: None,
                 config: BotSpecificConfig::new(),
+                encryption: None,
+                transform: None,
+                projection: None,
+                http: None,
+                sender_filter: None,
             }],
             settings: BotConfigurationSettings::default(),
         }
@@ -751,3 +937,103 @@ mod configuration_loading_tests {
         assert!(matches!(result, Err(BotConfigError::FileNotFound { .. })));
     }
 }
+
+mod hot_reload_tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn config_with_bot(name: &str) -> BotConfiguration {
+        BotConfiguration {
+            bots: vec![BotSubscription {
+                name: BotName::new(name).unwrap(),
+                queue: QueueName::new(format!("queue-keeper-{}", name)).unwrap(),
+                events: vec![EventTypePattern::Exact("issues.opened".to_string())],
+                ordered: true,
+                repository_filter: None,
+                config: BotSpecificConfig::new(),
+                encryption: None,
+                transform: None,
+                projection: None,
+                http: None,
+                sender_filter: None,
+            }],
+            settings: BotConfigurationSettings::default(),
+        }
+    }
+
+    /// A valid replacement is swapped in under the next version and reported
+    /// to listeners.
+    #[test]
+    fn test_replace_bumps_version_and_notifies() {
+        let shared = SharedBotConfiguration::new(config_with_bot("first-bot"));
+        let notified = Arc::new(AtomicU64::new(0));
+        let seen = notified.clone();
+        shared.on_replace(move |version| seen.store(version.version, Ordering::SeqCst));
+
+        assert_eq!(shared.version(), 1);
+        let version = shared.replace(config_with_bot("second-bot")).unwrap();
+
+        assert_eq!(version, 2);
+        assert_eq!(shared.version(), 2);
+        assert_eq!(notified.load(Ordering::SeqCst), 2);
+        assert_eq!(shared.current().bots[0].name.as_str(), "second-bot");
+    }
+
+    /// An invalid or vetoed replacement leaves the current version in use.
+    #[test]
+    fn test_rejected_replace_keeps_current() {
+        let shared = SharedBotConfiguration::new(config_with_bot("first-bot"));
+
+        let mut duplicate = config_with_bot("dup-bot");
+        duplicate.bots.push(duplicate.bots[0].clone());
+        assert!(shared.replace(duplicate).is_err());
+
+        shared.set_reload_check(|_, new| {
+            if new.bots.len() > 1 {
+                return Err(BotConfigError::ValidationError {
+                    errors: vec!["too many bots".to_string()],
+                });
+            }
+            Ok(())
+        });
+        let mut two_bots = config_with_bot("first-bot");
+        two_bots.bots.extend(config_with_bot("other-bot").bots);
+        assert!(shared.replace(two_bots).is_err());
+
+        assert_eq!(shared.version(), 1);
+        assert_eq!(shared.current().bots[0].name.as_str(), "first-bot");
+    }
+
+    /// The watcher reloads a changed file and ignores an unchanged one.
+    #[test]
+    fn test_watcher_reloads_changed_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("bots.yaml");
+        std::fs::write(
+            &path,
+            serde_yaml::to_string(&config_with_bot("first-bot")).unwrap(),
+        )
+        .unwrap();
+
+        let shared = Arc::new(SharedBotConfiguration::new(
+            BotConfiguration::load_from_file(&path).unwrap(),
+        ));
+        let mut watcher =
+            ConfigurationWatcher::new(path.clone(), shared.clone(), Duration::from_secs(1));
+        assert!(watcher.poll().is_none());
+
+        std::fs::write(
+            &path,
+            serde_yaml::to_string(&config_with_bot("second-longer-bot")).unwrap(),
+        )
+        .unwrap();
+        assert!(matches!(watcher.poll(), Some(Ok(2))));
+        assert_eq!(shared.current().bots[0].name.as_str(), "second-longer-bot");
+
+        std::fs::write(&path, "bots: [not valid").unwrap();
+        assert!(matches!(watcher.poll(), Some(Err(_))));
+        assert_eq!(shared.version(), 2);
+    }
+}
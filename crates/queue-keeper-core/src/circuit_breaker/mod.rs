@@ -30,7 +30,9 @@ use crate::Timestamp;
 
 // Re-export implementation
 mod breaker;
+mod registry;
 pub use breaker::{DefaultCircuitBreaker, DefaultCircuitBreakerFactory};
+pub use registry::{CircuitBreakerGroup, CircuitBreakerRegistry};
 
 // ============================================================================
 // Circuit Breaker Trait
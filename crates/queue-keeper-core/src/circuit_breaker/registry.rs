@@ -0,0 +1,145 @@
+//! Keyed circuit breakers.
+//!
+//! Provides one circuit breaker per destination, so a single failing
+//! destination is isolated without failing fast for the others.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use super::{
+    CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError, CircuitState, DefaultCircuitBreaker,
+};
+use crate::monitoring::MetricsCollector;
+
+// ============================================================================
+// Circuit Breaker Group
+// ============================================================================
+
+/// A group of circuit breakers whose states are monitored together.
+///
+/// Object-safe view of a [`CircuitBreakerRegistry`], used by health checks
+/// and metrics that do not know the operation types of the group.
+pub trait CircuitBreakerGroup: Send + Sync {
+    /// Name and state of every circuit breaker in the group, sorted by name.
+    fn circuit_states(&self) -> Vec<(String, CircuitState)>;
+
+    /// Record the state of every circuit breaker in `metrics`, now and on
+    /// every later state change.
+    fn attach_metrics(&self, metrics: Arc<dyn MetricsCollector>);
+}
+
+// ============================================================================
+// Circuit Breaker Registry
+// ============================================================================
+
+/// Circuit breakers created on first use, one per key.
+///
+/// Every breaker uses the registry configuration and is named
+/// `<service_name>:<key>`; the name is the `service` label of the
+/// `circuit_breaker_state` metric.
+pub struct CircuitBreakerRegistry<T, E> {
+    config: CircuitBreakerConfig,
+    breakers: Mutex<BTreeMap<String, DefaultCircuitBreaker<T, E>>>,
+    metrics: OnceLock<Arc<dyn MetricsCollector>>,
+}
+
+impl<T, E> CircuitBreakerRegistry<T, E>
+where
+    T: Send + Sync + 'static,
+    E: Send + Sync + 'static,
+{
+    /// Create an empty registry creating breakers with `config`.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            breakers: Mutex::new(BTreeMap::new()),
+            metrics: OnceLock::new(),
+        }
+    }
+
+    /// Name of the circuit breaker of `key`.
+    pub fn circuit_name(&self, key: &str) -> String {
+        format!("{}:{}", self.config.service_name, key)
+    }
+
+    /// The circuit breaker of `key`, created closed on first use.
+    pub fn breaker(&self, key: &str) -> DefaultCircuitBreaker<T, E> {
+        let mut breakers = self.breakers.lock().unwrap();
+        if let Some(breaker) = breakers.get(key) {
+            return breaker.clone();
+        }
+        let breaker = DefaultCircuitBreaker::new(CircuitBreakerConfig {
+            service_name: self.circuit_name(key),
+            ..self.config.clone()
+        });
+        breakers.insert(key.to_string(), breaker.clone());
+        self.record_state(key, CircuitState::Closed);
+        breaker
+    }
+
+    /// Execute `operation` with the protection of the circuit breaker of
+    /// `key`.
+    pub async fn call<F, Fut>(&self, key: &str, operation: F) -> Result<T, CircuitBreakerError<E>>
+    where
+        F: FnOnce() -> Fut + Send,
+        Fut: std::future::Future<Output = Result<T, E>> + Send,
+    {
+        let breaker = self.breaker(key);
+        let before = breaker.state();
+        let result = breaker.call(operation).await;
+        let after = breaker.state();
+        if after != before {
+            self.record_state(key, after);
+        }
+        result
+    }
+
+    fn record_state(&self, key: &str, state: CircuitState) {
+        if let Some(metrics) = self.metrics.get() {
+            metrics.record_circuit_breaker_state(&self.circuit_name(key), state_value(state));
+        }
+    }
+}
+
+impl<T, E> CircuitBreakerGroup for CircuitBreakerRegistry<T, E>
+where
+    T: Send + Sync + 'static,
+    E: Send + Sync + 'static,
+{
+    fn circuit_states(&self) -> Vec<(String, CircuitState)> {
+        self.breakers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, breaker)| (self.circuit_name(key), breaker.state()))
+            .collect()
+    }
+
+    fn attach_metrics(&self, metrics: Arc<dyn MetricsCollector>) {
+        if self.metrics.set(metrics).is_ok() {
+            let states: Vec<(String, CircuitState)> = self
+                .breakers
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(key, breaker)| (key.clone(), breaker.state()))
+                .collect();
+            for (key, state) in states {
+                self.record_state(&key, state);
+            }
+        }
+    }
+}
+
+/// Value of `state` in the `circuit_breaker_state` gauge.
+fn state_value(state: CircuitState) -> i64 {
+    match state {
+        CircuitState::Closed => 0,
+        CircuitState::Open => 1,
+        CircuitState::HalfOpen => 2,
+    }
+}
+
+#[cfg(test)]
+#[path = "registry_tests.rs"]
+mod tests;
@@ -0,0 +1,106 @@
+//! Tests for keyed circuit breakers.
+
+use super::*;
+use crate::timing::PipelineStage;
+use async_trait::async_trait;
+use std::time::Duration;
+
+/// Collector recording circuit breaker states only.
+#[derive(Default)]
+struct StateRecorder {
+    states: Mutex<Vec<(String, i64)>>,
+}
+
+impl StateRecorder {
+    fn states(&self) -> Vec<(String, i64)> {
+        self.states.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl MetricsCollector for StateRecorder {
+    fn record_webhook_request(&self, _duration: Duration, _success: bool) {}
+    fn record_webhook_validation_failure(&self) {}
+    fn record_webhook_stage(&self, _stage: PipelineStage, _duration: Duration) {}
+    fn record_queue_routing(&self, _duration: Duration, _queue_count: usize) {}
+    fn record_queue_delivery_attempt(&self, _success: bool) {}
+    fn record_error(&self, _category: &str, _is_transient: bool) {}
+    fn record_circuit_breaker_state(&self, service: &str, state: i64) {
+        self.states
+            .lock()
+            .unwrap()
+            .push((service.to_string(), state));
+    }
+    fn record_retry_attempt(&self, _service: &str) {}
+    fn record_blob_storage_failure(&self) {}
+    fn record_queue_depth(&self, _queue_name: &str, _depth: i64) {}
+    fn record_dead_letter_queue_depth(&self, _depth: i64) {}
+    fn record_session_ordering_violation(&self) {}
+    fn record_queue_processing_rate(&self, _rate: f64) {}
+}
+
+fn registry() -> CircuitBreakerRegistry<u32, String> {
+    CircuitBreakerRegistry::new(CircuitBreakerConfig {
+        service_name: "queue".to_string(),
+        failure_threshold: 2,
+        failure_window_seconds: 60,
+        recovery_timeout_seconds: 0,
+        success_threshold: 1,
+        operation_timeout_seconds: 1,
+        half_open_max_requests: 1,
+    })
+}
+
+async fn fail(registry: &CircuitBreakerRegistry<u32, String>, key: &str) {
+    let _ = registry
+        .call(key, || async { Err::<u32, _>("unavailable".to_string()) })
+        .await;
+}
+
+/// Failures open the circuit of their key only.
+#[tokio::test]
+async fn test_breakers_are_per_key() {
+    let registry = registry();
+
+    fail(&registry, "bot-a").await;
+    fail(&registry, "bot-a").await;
+    assert!(registry
+        .call("bot-b", || async { Ok::<_, String>(1) })
+        .await
+        .is_ok());
+
+    assert_eq!(
+        registry.circuit_states(),
+        vec![
+            ("queue:bot-a".to_string(), CircuitState::Open),
+            ("queue:bot-b".to_string(), CircuitState::Closed),
+        ]
+    );
+}
+
+/// State changes are recorded once metrics are attached, starting with the
+/// current states.
+#[tokio::test]
+async fn test_attach_metrics_records_state_changes() {
+    let registry = registry();
+    let recorder = Arc::new(StateRecorder::default());
+    fail(&registry, "bot-a").await;
+    fail(&registry, "bot-a").await;
+
+    registry.attach_metrics(recorder.clone());
+    fail(&registry, "bot-b").await;
+    // The recovery timeout has passed: the probe succeeds and closes the circuit
+    assert!(registry
+        .call("bot-a", || async { Ok::<_, String>(1) })
+        .await
+        .is_ok());
+
+    assert_eq!(
+        recorder.states(),
+        vec![
+            ("queue:bot-a".to_string(), 1),
+            ("queue:bot-b".to_string(), 0),
+            ("queue:bot-a".to_string(), 0),
+        ]
+    );
+}
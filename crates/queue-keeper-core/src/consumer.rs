@@ -0,0 +1,253 @@
+//! # Consumer Message Settlement
+//!
+//! Settles messages a bot has received from its Queue-Keeper queue according
+//! to the [`ErrorCategory`] of the handler's error, so that every bot gets the
+//! same abandon and dead-letter behaviour without writing it itself:
+//!
+//! | Handler result | Settlement |
+//! |----------------|------------|
+//! | `Ok(())` | Complete |
+//! | [`ErrorCategory::Transient`] | Abandon after a backoff delay from the [`RetryPolicy`] |
+//! | [`ErrorCategory::Transient`], last attempt | Dead-letter as `RetriesExhausted` |
+//! | [`ErrorCategory::Permanent`] | Dead-letter as `PermanentFailure` |
+//! | [`ErrorCategory::Security`] | Dead-letter as `SecurityViolation` |
+//! | [`ErrorCategory::Configuration`] | Dead-letter as `ConfigurationError` |
+//!
+//! Non-transient failures are dead-lettered on the first delivery: retrying
+//! them only delays the DLQ alert, and they can be replayed from the DLQ once
+//! the cause is fixed.
+//!
+//! The queue runtime's abandon operation has no delay parameter, so
+//! [`MessageSettler`] holds the message for the backoff delay before
+//! abandoning it. Keep the policy's `max_delay` well below the queue's lock
+//! (visibility) timeout; the delay is capped at the receipt's remaining lock
+//! time so the abandon is never issued on an expired receipt.
+//!
+//! ```rust,ignore
+//! let settler = MessageSettler::new(client.clone(), SettlementPolicy::default());
+//! while let Some(message) = client.receive_message(&queue, timeout).await? {
+//!     let result = handle(&message).await; // Result<(), impl CategorizedError>
+//!     settler.settle(&message, result).await?;
+//! }
+//! ```
+
+use crate::{ErrorCategory, QueueKeeperError, RetryPolicy};
+use queue_runtime::{QueueClient, QueueError, ReceivedMessage};
+use std::{fmt, sync::Arc, time::Duration};
+
+// ============================================================================
+// Error Classification
+// ============================================================================
+
+/// Handler error that knows its [`ErrorCategory`].
+///
+/// Implement this for the bot's own error type to drive settlement.
+pub trait CategorizedError: fmt::Display {
+    /// Category deciding whether the message is retried or dead-lettered
+    fn error_category(&self) -> ErrorCategory;
+}
+
+impl CategorizedError for QueueKeeperError {
+    fn error_category(&self) -> ErrorCategory {
+        QueueKeeperError::error_category(self)
+    }
+}
+
+// ============================================================================
+// Settlement Decisions
+// ============================================================================
+
+/// Reason recorded on a dead-lettered message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadLetterReason {
+    /// Handler reported a permanent failure
+    PermanentFailure,
+    /// Handler reported a security failure
+    SecurityViolation,
+    /// Handler reported a configuration failure
+    ConfigurationError,
+    /// Transient failures continued until the last allowed delivery
+    RetriesExhausted,
+}
+
+impl DeadLetterReason {
+    /// Stable reason code written to the dead-letter queue
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PermanentFailure => "PermanentFailure",
+            Self::SecurityViolation => "SecurityViolation",
+            Self::ConfigurationError => "ConfigurationError",
+            Self::RetriesExhausted => "RetriesExhausted",
+        }
+    }
+}
+
+impl fmt::Display for DeadLetterReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How a received message is settled
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Settlement {
+    /// Remove the message from the queue
+    Complete,
+
+    /// Return the message to the queue for redelivery after `delay`
+    Abandon {
+        /// Backoff before the message is released
+        delay: Duration,
+        /// Delivery count of the failed attempt
+        delivery_count: u32,
+    },
+
+    /// Move the message to the dead-letter queue
+    DeadLetter {
+        /// Reason code
+        reason: DeadLetterReason,
+        /// Handler error message
+        description: String,
+    },
+}
+
+impl Settlement {
+    /// Reason string passed to the queue's dead-letter operation.
+    ///
+    /// Formatted as `<reason>: <description>` so the code stays
+    /// machine-readable at the start of the string.
+    pub fn dead_letter_reason(&self) -> Option<String> {
+        match self {
+            Self::DeadLetter {
+                reason,
+                description,
+            } => Some(format!("{}: {}", reason, description)),
+            _ => None,
+        }
+    }
+}
+
+/// Maps handler results to settlements
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SettlementPolicy {
+    /// Backoff for transient failures. `max_attempts` is the number of
+    /// deliveries after which a transient failure is dead-lettered.
+    pub retry: RetryPolicy,
+}
+
+impl SettlementPolicy {
+    /// Create policy with the given retry behaviour
+    pub fn new(retry: RetryPolicy) -> Self {
+        Self { retry }
+    }
+
+    /// Decide the settlement for a handler result.
+    ///
+    /// `delivery_count` is the message's delivery count, starting at 1 for
+    /// the first delivery.
+    pub fn decide<E: CategorizedError>(
+        &self,
+        result: &Result<(), E>,
+        delivery_count: u32,
+    ) -> Settlement {
+        let error = match result {
+            Ok(()) => return Settlement::Complete,
+            Err(error) => error,
+        };
+
+        let reason = match error.error_category() {
+            ErrorCategory::Transient if delivery_count < self.retry.max_attempts => {
+                return Settlement::Abandon {
+                    delay: self.retry.calculate_delay(delivery_count.saturating_sub(1)),
+                    delivery_count,
+                };
+            }
+            ErrorCategory::Transient => DeadLetterReason::RetriesExhausted,
+            ErrorCategory::Permanent => DeadLetterReason::PermanentFailure,
+            ErrorCategory::Security => DeadLetterReason::SecurityViolation,
+            ErrorCategory::Configuration => DeadLetterReason::ConfigurationError,
+        };
+
+        Settlement::DeadLetter {
+            reason,
+            description: error.to_string(),
+        }
+    }
+}
+
+// ============================================================================
+// Message Settler
+// ============================================================================
+
+/// Applies [`SettlementPolicy`] decisions to a queue
+pub struct MessageSettler {
+    client: Arc<dyn QueueClient>,
+    policy: SettlementPolicy,
+}
+
+impl MessageSettler {
+    /// Create settler for the given queue client
+    pub fn new(client: Arc<dyn QueueClient>, policy: SettlementPolicy) -> Self {
+        Self { client, policy }
+    }
+
+    /// Policy used to decide settlements
+    pub fn policy(&self) -> &SettlementPolicy {
+        &self.policy
+    }
+
+    /// Settle `message` according to the handler `result`.
+    ///
+    /// Returns the settlement that was applied.
+    pub async fn settle<E: CategorizedError>(
+        &self,
+        message: &ReceivedMessage,
+        result: Result<(), E>,
+    ) -> Result<Settlement, QueueError> {
+        let settlement = self.policy.decide(&result, message.delivery_count);
+        let receipt = message.receipt_handle.clone();
+
+        match &settlement {
+            Settlement::Complete => {
+                self.client.complete_message(receipt).await?;
+            }
+            Settlement::Abandon {
+                delay,
+                delivery_count,
+            } => {
+                let lock_remaining = receipt
+                    .time_until_expiry()
+                    .to_std()
+                    .unwrap_or(Duration::ZERO);
+                let delay = (*delay).min(lock_remaining);
+
+                tracing::warn!(
+                    message_id = ?message.message_id,
+                    delivery_count = delivery_count,
+                    delay_ms = delay.as_millis() as u64,
+                    "Transient handler failure, abandoning message after backoff"
+                );
+
+                tokio::time::sleep(delay).await;
+                self.client.abandon_message(receipt).await?;
+            }
+            Settlement::DeadLetter { reason, .. } => {
+                tracing::error!(
+                    message_id = ?message.message_id,
+                    delivery_count = message.delivery_count,
+                    reason = %reason,
+                    "Handler failure, dead-lettering message"
+                );
+
+                let reason = settlement.dead_letter_reason().unwrap_or_default();
+                self.client.dead_letter_message(receipt, reason).await?;
+            }
+        }
+
+        Ok(settlement)
+    }
+}
+
+#[cfg(test)]
+#[path = "consumer_tests.rs"]
+mod tests;
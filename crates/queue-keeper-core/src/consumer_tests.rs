@@ -0,0 +1,297 @@
+//! Tests for consumer message settlement.
+
+use super::*;
+use crate::ValidationError;
+use async_trait::async_trait;
+use bytes::Bytes;
+use chrono::TimeDelta;
+use queue_runtime::{
+    Message, MessageId, ProviderType, QueueName, ReceiptHandle, SessionClient, SessionId, Timestamp,
+};
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// ============================================================================
+// Mock Queue Client
+// ============================================================================
+
+/// Settlement call recorded by the mock client.
+#[derive(Debug, Clone, PartialEq)]
+enum Call {
+    Complete,
+    Abandon,
+    DeadLetter(String),
+}
+
+#[derive(Default)]
+struct RecordingQueueClient {
+    calls: Mutex<Vec<Call>>,
+}
+
+impl RecordingQueueClient {
+    fn calls(&self) -> Vec<Call> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl QueueClient for RecordingQueueClient {
+    async fn send_message(
+        &self,
+        _queue: &QueueName,
+        _message: Message,
+    ) -> Result<MessageId, QueueError> {
+        unimplemented!("Sending not needed for settlement tests")
+    }
+
+    async fn send_messages(
+        &self,
+        _queue: &QueueName,
+        _messages: Vec<Message>,
+    ) -> Result<Vec<MessageId>, QueueError> {
+        unimplemented!("Sending not needed for settlement tests")
+    }
+
+    async fn receive_message(
+        &self,
+        _queue: &QueueName,
+        _timeout: TimeDelta,
+    ) -> Result<Option<ReceivedMessage>, QueueError> {
+        unimplemented!("Receiving not needed for settlement tests")
+    }
+
+    async fn receive_messages(
+        &self,
+        _queue: &QueueName,
+        _max_messages: u32,
+        _timeout: TimeDelta,
+    ) -> Result<Vec<ReceivedMessage>, QueueError> {
+        unimplemented!("Receiving not needed for settlement tests")
+    }
+
+    async fn complete_message(&self, _receipt: ReceiptHandle) -> Result<(), QueueError> {
+        self.calls.lock().unwrap().push(Call::Complete);
+        Ok(())
+    }
+
+    async fn abandon_message(&self, _receipt: ReceiptHandle) -> Result<(), QueueError> {
+        self.calls.lock().unwrap().push(Call::Abandon);
+        Ok(())
+    }
+
+    async fn dead_letter_message(
+        &self,
+        _receipt: ReceiptHandle,
+        reason: String,
+    ) -> Result<(), QueueError> {
+        self.calls.lock().unwrap().push(Call::DeadLetter(reason));
+        Ok(())
+    }
+
+    async fn accept_session(
+        &self,
+        _queue: &QueueName,
+        _session_id: Option<SessionId>,
+    ) -> Result<Box<dyn SessionClient>, QueueError> {
+        unimplemented!("Sessions not needed for settlement tests")
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::InMemory
+    }
+
+    fn supports_sessions(&self) -> bool {
+        true
+    }
+
+    fn supports_batching(&self) -> bool {
+        false
+    }
+}
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+fn policy() -> SettlementPolicy {
+    SettlementPolicy::new(RetryPolicy {
+        max_attempts: 3,
+        initial_delay: Duration::from_millis(100),
+        max_delay: Duration::from_secs(1),
+        backoff_multiplier: 2.0,
+        use_jitter: false,
+        jitter_percent: 0.0,
+    })
+}
+
+fn transient() -> Result<(), QueueKeeperError> {
+    Err(QueueKeeperError::ExternalService {
+        service: "github".to_string(),
+        message: "503 Service Unavailable".to_string(),
+    })
+}
+
+fn permanent() -> Result<(), QueueKeeperError> {
+    Err(QueueKeeperError::Validation(ValidationError::Required {
+        field: "repository".to_string(),
+    }))
+}
+
+/// Message whose receipt lock has already run out.
+fn received(delivery_count: u32) -> ReceivedMessage {
+    ReceivedMessage {
+        message_id: MessageId::new(),
+        body: Bytes::from_static(b"{}"),
+        attributes: HashMap::new(),
+        session_id: None,
+        correlation_id: None,
+        receipt_handle: ReceiptHandle::new(
+            "receipt".to_string(),
+            Timestamp::now(),
+            ProviderType::InMemory,
+        ),
+        delivery_count,
+        first_delivered_at: Timestamp::now(),
+        delivered_at: Timestamp::now(),
+    }
+}
+
+// ============================================================================
+// Policy Tests
+// ============================================================================
+
+/// Success completes the message.
+#[test]
+fn test_success_completes() {
+    assert_eq!(
+        policy().decide::<QueueKeeperError>(&Ok(()), 1),
+        Settlement::Complete
+    );
+}
+
+/// Transient failures abandon with exponential backoff until the last
+/// attempt, which dead-letters.
+#[test]
+fn test_transient_failure_backs_off_then_dead_letters() {
+    let policy = policy();
+
+    assert_eq!(
+        policy.decide(&transient(), 1),
+        Settlement::Abandon {
+            delay: Duration::from_millis(100),
+            delivery_count: 1,
+        }
+    );
+    assert_eq!(
+        policy.decide(&transient(), 2),
+        Settlement::Abandon {
+            delay: Duration::from_millis(200),
+            delivery_count: 2,
+        }
+    );
+
+    let last = policy.decide(&transient(), 3);
+    assert!(matches!(
+        last,
+        Settlement::DeadLetter {
+            reason: DeadLetterReason::RetriesExhausted,
+            ..
+        }
+    ));
+}
+
+/// Non-transient categories dead-letter on the first delivery with their
+/// reason code.
+#[test]
+fn test_non_transient_failures_dead_letter_immediately() {
+    let settlement = policy().decide(&permanent(), 1);
+    assert_eq!(
+        settlement.dead_letter_reason().unwrap(),
+        "PermanentFailure: Validation error: Field 'repository' is required"
+    );
+
+    let configuration: Result<(), QueueKeeperError> = Err(QueueKeeperError::Configuration {
+        message: "missing token".to_string(),
+    });
+    assert!(matches!(
+        policy().decide(&configuration, 1),
+        Settlement::DeadLetter {
+            reason: DeadLetterReason::ConfigurationError,
+            ..
+        }
+    ));
+}
+
+/// Bot error types can carry the security category.
+#[test]
+fn test_custom_error_category() {
+    struct Forged;
+
+    impl fmt::Display for Forged {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("forged callback")
+        }
+    }
+
+    impl CategorizedError for Forged {
+        fn error_category(&self) -> ErrorCategory {
+            ErrorCategory::Security
+        }
+    }
+
+    assert_eq!(
+        policy().decide(&Err(Forged), 1),
+        Settlement::DeadLetter {
+            reason: DeadLetterReason::SecurityViolation,
+            description: "forged callback".to_string(),
+        }
+    );
+}
+
+// ============================================================================
+// Settler Tests
+// ============================================================================
+
+/// The settler issues the queue operation matching each decision.
+#[tokio::test]
+async fn test_settler_applies_settlements() {
+    let client = Arc::new(RecordingQueueClient::default());
+    let settler = MessageSettler::new(client.clone(), policy());
+
+    settler
+        .settle::<QueueKeeperError>(&received(1), Ok(()))
+        .await
+        .unwrap();
+    settler.settle(&received(1), permanent()).await.unwrap();
+
+    assert_eq!(
+        client.calls(),
+        vec![
+            Call::Complete,
+            Call::DeadLetter(
+                "PermanentFailure: Validation error: Field 'repository' is required".to_string()
+            ),
+        ]
+    );
+}
+
+/// The backoff never outlives the receipt lock.
+#[tokio::test]
+async fn test_abandon_delay_is_capped_by_lock_expiry() {
+    let client = Arc::new(RecordingQueueClient::default());
+    let settler = MessageSettler::new(
+        client.clone(),
+        SettlementPolicy::new(RetryPolicy::fixed(Duration::from_secs(60))),
+    );
+
+    let settlement = tokio::time::timeout(
+        Duration::from_secs(5),
+        settler.settle(&received(1), transient()),
+    )
+    .await
+    .expect("abandon should not wait past the expired lock")
+    .unwrap();
+
+    assert!(matches!(settlement, Settlement::Abandon { .. }));
+    assert_eq!(client.calls(), vec![Call::Abandon]);
+}
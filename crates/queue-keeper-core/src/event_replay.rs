@@ -765,8 +765,7 @@ impl StoredEvent {
 
     /// Get event age
     pub fn get_age(&self) -> Duration {
-        let now = Timestamp::now();
-        now.duration_since(self.envelope.received_at)
+        self.envelope.age()
     }
 
     /// Check if event has been replayed recently
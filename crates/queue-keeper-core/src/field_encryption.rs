@@ -0,0 +1,534 @@
+//! # Field-Level Payload Encryption
+//!
+//! Encrypts selected fields of an event payload before it is delivered to a
+//! bot queue, so that a misconfigured queue or an unrelated consumer cannot
+//! read them. Encryption is configured per bot subscription
+//! ([`BotSubscription::encryption`]) as a list of JSON pointers and the name
+//! of a Key Vault secret holding that bot's key.
+//!
+//! Each selected value is serialized to JSON, encrypted with AES-256-GCM and
+//! replaced in place by an envelope object:
+//!
+//! ```json
+//! {
+//!   "$qk_encrypted": {
+//!     "v": 1,
+//!     "alg": "A256GCM",
+//!     "kid": "queue-keeper-prod-advisory-bot-key",
+//!     "nonce": "<base64>",
+//!     "ct": "<base64>"
+//!   }
+//! }
+//! ```
+//!
+//! The ID of the event and the JSON pointer of the field are bound into the
+//! ciphertext as associated data, so an envelope moved to a different
+//! location, or copied into another event, fails to decrypt. Pointers that
+//! do not exist in a given payload are left alone.
+//!
+//! Consumers restore the original payload with [`decrypt_event`] (or
+//! [`decrypt_fields`] for a bare payload, given the context it was encrypted
+//! under) using the same key.
+//!
+//! The key secret must contain 32 random bytes encoded as standard base64,
+//! e.g. the output of `openssl rand -base64 32`.
+//!
+//! [`BotSubscription::encryption`]: crate::bot_config::BotSubscription::encryption
+
+use crate::{
+    key_vault::{KeyVaultError, KeyVaultProvider, SecretName, SecretValue},
+    webhook::WrappedEvent,
+};
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{fmt, sync::Arc};
+
+/// Object key marking an encrypted field envelope.
+pub const ENCRYPTED_FIELD_MARKER: &str = "$qk_encrypted";
+
+/// Envelope format version written by this module.
+pub const ENVELOPE_VERSION: u8 = 1;
+
+/// Algorithm identifier written to envelopes.
+pub const ALGORITHM: &str = "A256GCM";
+
+/// Key length in bytes.
+pub const KEY_LEN: usize = 32;
+
+/// AES-GCM nonce length in bytes.
+const NONCE_LEN: usize = 12;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Field encryption settings for a single bot subscription.
+///
+/// See specs/interfaces/bot-configuration.md
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldEncryptionConfig {
+    /// Name of the Key Vault secret holding the bot's base64-encoded key.
+    pub key_secret: String,
+
+    /// JSON pointers (RFC 6901) of the payload fields to encrypt,
+    /// e.g. `/security_advisory`.
+    pub fields: Vec<String>,
+}
+
+impl FieldEncryptionConfig {
+    /// Check the secret name and pointers.
+    ///
+    /// # Errors
+    /// Returns a description of the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        SecretName::new(&self.key_secret)
+            .map_err(|e| format!("invalid key_secret '{}': {}", self.key_secret, e))?;
+        if self.fields.is_empty() {
+            return Err("at least one field must be listed".to_string());
+        }
+        for pointer in &self.fields {
+            if !pointer.starts_with('/') {
+                return Err(format!(
+                    "field '{}' is not a JSON pointer (must start with '/')",
+                    pointer
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors from encrypting or decrypting payload fields.
+#[derive(Debug, thiserror::Error)]
+pub enum FieldEncryptionError {
+    /// The key could not be read from the Key Vault.
+    #[error("Failed to load field encryption key: {0}")]
+    KeyVault(#[from] KeyVaultError),
+
+    /// The key material is not 32 bytes of base64.
+    #[error("Invalid field encryption key: {message}")]
+    InvalidKey { message: String },
+
+    /// A configured field is not a JSON pointer.
+    #[error("Invalid JSON pointer '{pointer}': must start with '/'")]
+    InvalidPointer { pointer: String },
+
+    /// Encryption of a field failed.
+    #[error("Failed to encrypt field '{pointer}'")]
+    EncryptionFailed { pointer: String },
+
+    /// An envelope could not be decrypted (wrong key, tampering, moved
+    /// envelope or malformed envelope).
+    #[error("Failed to decrypt field '{pointer}': {reason}")]
+    DecryptionFailed { pointer: String, reason: String },
+}
+
+impl FieldEncryptionError {
+    /// Check if the error is transient and delivery should be retried.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            FieldEncryptionError::KeyVault(e) => e.is_transient(),
+            _ => false,
+        }
+    }
+}
+
+// ============================================================================
+// Keys
+// ============================================================================
+
+/// A bot's 256-bit field encryption key.
+///
+/// Zeroed on drop; never printed.
+#[derive(Clone)]
+pub struct FieldEncryptionKey {
+    bytes: zeroize::Zeroizing<[u8; KEY_LEN]>,
+}
+
+impl FieldEncryptionKey {
+    /// Create a key from raw bytes.
+    pub fn from_bytes(bytes: [u8; KEY_LEN]) -> Self {
+        Self {
+            bytes: zeroize::Zeroizing::new(bytes),
+        }
+    }
+
+    /// Decode a key from standard base64.
+    ///
+    /// # Errors
+    /// Returns [`FieldEncryptionError::InvalidKey`] if the value is not
+    /// base64 or does not decode to exactly 32 bytes.
+    pub fn from_base64(encoded: &str) -> Result<Self, FieldEncryptionError> {
+        let decoded = zeroize::Zeroizing::new(STANDARD.decode(encoded.trim()).map_err(|e| {
+            FieldEncryptionError::InvalidKey {
+                message: format!("not valid base64: {}", e),
+            }
+        })?);
+        let bytes: [u8; KEY_LEN] =
+            decoded
+                .as_slice()
+                .try_into()
+                .map_err(|_| FieldEncryptionError::InvalidKey {
+                    message: format!("expected {} bytes, got {}", KEY_LEN, decoded.len()),
+                })?;
+        Ok(Self::from_bytes(bytes))
+    }
+
+    /// Decode a key from a Key Vault secret.
+    pub fn from_secret(secret: &SecretValue) -> Result<Self, FieldEncryptionError> {
+        Self::from_base64(secret.expose_secret())
+    }
+
+    /// Raw key bytes, for wrapping the key under another one.
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        self.bytes.as_slice()
+    }
+
+    pub(crate) fn cipher(&self) -> Aes256Gcm {
+        Aes256Gcm::new_from_slice(self.bytes.as_slice()).expect("key has the AES-256 length")
+    }
+}
+
+impl fmt::Debug for FieldEncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FieldEncryptionKey(<REDACTED>)")
+    }
+}
+
+// ============================================================================
+// Envelope
+// ============================================================================
+
+/// Encrypted replacement for a payload field.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedField {
+    /// Envelope format version.
+    pub v: u8,
+    /// Encryption algorithm.
+    pub alg: String,
+    /// Name of the key secret used, so consumers can select the key.
+    pub kid: String,
+    /// Base64 AES-GCM nonce.
+    pub nonce: String,
+    /// Base64 ciphertext including the authentication tag.
+    pub ct: String,
+}
+
+// ============================================================================
+// Operations
+// ============================================================================
+
+/// Encrypt the fields at `pointers` in `payload` in place.
+///
+/// Each field is bound to `context` and its pointer; events use their event
+/// ID as context. Returns the number of fields encrypted; pointers that do
+/// not resolve are skipped.
+///
+/// # Errors
+/// Returns an error for a pointer not starting with `/`, or if encryption
+/// fails. On error `payload` may be partially encrypted and must not be
+/// delivered.
+pub fn encrypt_fields(
+    payload: &mut Value,
+    pointers: &[String],
+    key: &FieldEncryptionKey,
+    key_id: &str,
+    context: &str,
+) -> Result<usize, FieldEncryptionError> {
+    let cipher = key.cipher();
+    let mut encrypted = 0;
+    for pointer in pointers {
+        if !pointer.starts_with('/') {
+            return Err(FieldEncryptionError::InvalidPointer {
+                pointer: pointer.clone(),
+            });
+        }
+        let Some(target) = payload.pointer_mut(pointer) else {
+            continue;
+        };
+        if is_envelope(target) {
+            continue;
+        }
+
+        let failed = || FieldEncryptionError::EncryptionFailed {
+            pointer: pointer.clone(),
+        };
+        let plaintext = zeroize::Zeroizing::new(serde_json::to_vec(target).map_err(|_| failed())?);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &plaintext,
+                    aad: &aad(context, pointer),
+                },
+            )
+            .map_err(|_| failed())?;
+
+        let envelope = EncryptedField {
+            v: ENVELOPE_VERSION,
+            alg: ALGORITHM.to_string(),
+            kid: key_id.to_string(),
+            nonce: STANDARD.encode(nonce),
+            ct: STANDARD.encode(ciphertext),
+        };
+        let mut wrapper = serde_json::Map::new();
+        wrapper.insert(
+            ENCRYPTED_FIELD_MARKER.to_string(),
+            serde_json::to_value(envelope).map_err(|_| failed())?,
+        );
+        *target = Value::Object(wrapper);
+        encrypted += 1;
+    }
+    Ok(encrypted)
+}
+
+/// Decrypt every encrypted field envelope in `payload` in place.
+///
+/// This is the consumer-side counterpart of [`encrypt_fields`]; `context`
+/// must be the one the fields were encrypted under. Returns the number of
+/// fields decrypted.
+///
+/// # Errors
+/// Returns [`FieldEncryptionError::DecryptionFailed`] if any envelope cannot
+/// be decrypted with `key`. On error `payload` may be partially decrypted.
+pub fn decrypt_fields(
+    payload: &mut Value,
+    key: &FieldEncryptionKey,
+    context: &str,
+) -> Result<usize, FieldEncryptionError> {
+    let cipher = key.cipher();
+    let mut pointer = String::new();
+    decrypt_value(payload, &cipher, None, context, &mut pointer)
+}
+
+/// Decrypt the encrypted fields of a received event in place.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use queue_keeper_core::field_encryption::{decrypt_event, FieldEncryptionKey};
+/// use queue_keeper_core::webhook::WrappedEvent;
+///
+/// # fn example(body: &[u8], key_base64: &str) -> Result<(), Box<dyn std::error::Error>> {
+/// let mut event: WrappedEvent = serde_json::from_slice(body)?;
+/// let key = FieldEncryptionKey::from_base64(key_base64)?;
+/// decrypt_event(&mut event, &key)?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn decrypt_event(
+    event: &mut WrappedEvent,
+    key: &FieldEncryptionKey,
+) -> Result<usize, FieldEncryptionError> {
+    let context = event.event_id.to_string();
+    decrypt_fields(&mut event.payload, key, &context)
+}
+
+/// Encrypts bot payload fields with keys loaded from a Key Vault.
+pub struct FieldEncryptor {
+    key_vault: Arc<dyn KeyVaultProvider>,
+}
+
+impl FieldEncryptor {
+    /// Create an encryptor reading keys from `key_vault`.
+    pub fn new(key_vault: Arc<dyn KeyVaultProvider>) -> Self {
+        Self { key_vault }
+    }
+
+    /// Return a copy of `event` with the fields selected by `config`
+    /// encrypted under the configured key.
+    ///
+    /// # Errors
+    /// Returns an error if the key cannot be loaded or decoded, or if
+    /// encryption fails.
+    pub async fn encrypt_event(
+        &self,
+        event: &WrappedEvent,
+        config: &FieldEncryptionConfig,
+    ) -> Result<WrappedEvent, FieldEncryptionError> {
+        let name = SecretName::new(&config.key_secret)?;
+        let secret = self.key_vault.get_secret(&name).await?;
+        let key = FieldEncryptionKey::from_secret(&secret)?;
+
+        let mut encrypted = event.clone();
+        encrypt_fields(
+            &mut encrypted.payload,
+            &config.fields,
+            &key,
+            &config.key_secret,
+            &event.event_id.to_string(),
+        )?;
+        Ok(encrypted)
+    }
+
+    /// Return a copy of `event` with every encrypted field decrypted, each
+    /// with the key its envelope names.
+    ///
+    /// Restores events encrypted by [`encrypt_event`](Self::encrypt_event),
+    /// possibly under the keys of several bots.
+    ///
+    /// # Errors
+    /// Returns an error if a key cannot be loaded or decoded, or if a field
+    /// cannot be decrypted.
+    pub async fn decrypt_event(
+        &self,
+        event: &WrappedEvent,
+    ) -> Result<WrappedEvent, FieldEncryptionError> {
+        let mut key_ids = std::collections::BTreeSet::new();
+        collect_key_ids(&event.payload, &mut key_ids);
+
+        let context = event.event_id.to_string();
+        let mut decrypted = event.clone();
+        for key_id in key_ids {
+            let secret = self
+                .key_vault
+                .get_secret(&SecretName::new(&key_id)?)
+                .await?;
+            let key = FieldEncryptionKey::from_secret(&secret)?;
+            let mut pointer = String::new();
+            decrypt_value(
+                &mut decrypted.payload,
+                &key.cipher(),
+                Some(&key_id),
+                &context,
+                &mut pointer,
+            )?;
+        }
+        Ok(decrypted)
+    }
+}
+
+// ============================================================================
+// Private Helpers
+// ============================================================================
+
+/// Associated data of the field at `pointer`: `context`, a NUL byte and the
+/// pointer.
+fn aad(context: &str, pointer: &str) -> Vec<u8> {
+    [context.as_bytes(), b"\0", pointer.as_bytes()].concat()
+}
+
+fn is_envelope(value: &Value) -> bool {
+    value
+        .as_object()
+        .is_some_and(|o| o.len() == 1 && o.contains_key(ENCRYPTED_FIELD_MARKER))
+}
+
+/// Names of the keys of the envelopes in `value`.
+fn collect_key_ids(value: &Value, key_ids: &mut std::collections::BTreeSet<String>) {
+    if is_envelope(value) {
+        if let Some(key_id) = value[ENCRYPTED_FIELD_MARKER]["kid"].as_str() {
+            key_ids.insert(key_id.to_string());
+        }
+        return;
+    }
+    match value {
+        Value::Object(map) => map
+            .values()
+            .for_each(|child| collect_key_ids(child, key_ids)),
+        Value::Array(items) => items
+            .iter()
+            .for_each(|child| collect_key_ids(child, key_ids)),
+        _ => {}
+    }
+}
+
+/// Decrypt the envelopes in `value`, or only those of `key_id` when given.
+fn decrypt_value(
+    value: &mut Value,
+    cipher: &Aes256Gcm,
+    key_id: Option<&str>,
+    context: &str,
+    pointer: &mut String,
+) -> Result<usize, FieldEncryptionError> {
+    if is_envelope(value) {
+        let envelope = &value[ENCRYPTED_FIELD_MARKER];
+        if key_id.is_some_and(|key_id| envelope["kid"].as_str() != Some(key_id)) {
+            return Ok(0);
+        }
+        *value = decrypt_envelope(envelope, cipher, context, pointer)?;
+        // A configured pointer below this one was encrypted first, so its
+        // envelope is inside the plaintext.
+        return Ok(1 + decrypt_value(value, cipher, key_id, context, pointer)?);
+    }
+
+    let mut decrypted = 0;
+    let base_len = pointer.len();
+    match value {
+        Value::Object(map) => {
+            for (key, child) in map.iter_mut() {
+                pointer.push('/');
+                pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                decrypted += decrypt_value(child, cipher, key_id, context, pointer)?;
+                pointer.truncate(base_len);
+            }
+        }
+        Value::Array(items) => {
+            for (index, child) in items.iter_mut().enumerate() {
+                pointer.push('/');
+                pointer.push_str(&index.to_string());
+                decrypted += decrypt_value(child, cipher, key_id, context, pointer)?;
+                pointer.truncate(base_len);
+            }
+        }
+        _ => {}
+    }
+    Ok(decrypted)
+}
+
+fn decrypt_envelope(
+    envelope: &Value,
+    cipher: &Aes256Gcm,
+    context: &str,
+    pointer: &str,
+) -> Result<Value, FieldEncryptionError> {
+    let failed = |reason: &str| FieldEncryptionError::DecryptionFailed {
+        pointer: pointer.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let envelope: EncryptedField =
+        serde_json::from_value(envelope.clone()).map_err(|_| failed("malformed envelope"))?;
+    if envelope.v != ENVELOPE_VERSION || envelope.alg != ALGORITHM {
+        return Err(failed(&format!(
+            "unsupported envelope version {} / algorithm {}",
+            envelope.v, envelope.alg
+        )));
+    }
+    let nonce = STANDARD
+        .decode(&envelope.nonce)
+        .map_err(|_| failed("nonce is not base64"))?;
+    let nonce: [u8; NONCE_LEN] = nonce
+        .try_into()
+        .map_err(|_| failed("nonce has the wrong length"))?;
+    let ciphertext = STANDARD
+        .decode(&envelope.ct)
+        .map_err(|_| failed("ciphertext is not base64"))?;
+
+    let plaintext = zeroize::Zeroizing::new(
+        cipher
+            .decrypt(
+                &Nonce::from(nonce),
+                Payload {
+                    msg: &ciphertext,
+                    aad: &aad(context, pointer),
+                },
+            )
+            .map_err(|_| failed("authentication failed (wrong key or tampered field)"))?,
+    );
+    serde_json::from_slice(&plaintext).map_err(|_| failed("plaintext is not JSON"))
+}
+
+#[cfg(test)]
+#[path = "field_encryption_tests.rs"]
+mod tests;
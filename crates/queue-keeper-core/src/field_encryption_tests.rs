@@ -0,0 +1,255 @@
+//! Tests for field-level payload encryption.
+
+use super::*;
+use crate::adapters::InMemoryKeyVaultProvider;
+use serde_json::json;
+
+const KEY_SECRET: &str = "queue-keeper-test-advisory-key";
+
+/// Context the bare payloads of these tests are encrypted under.
+const CONTEXT: &str = "event-1";
+
+fn key() -> FieldEncryptionKey {
+    FieldEncryptionKey::from_bytes([7u8; KEY_LEN])
+}
+
+fn advisory_payload() -> Value {
+    json!({
+        "action": "published",
+        "security_advisory": {
+            "ghsa_id": "GHSA-xxxx-xxxx-xxxx",
+            "summary": "Remote code execution"
+        },
+        "alerts": [{ "detail": "secret" }, { "detail": "also secret" }],
+        "repository": { "full_name": "owner/repo" }
+    })
+}
+
+fn config(fields: &[&str]) -> FieldEncryptionConfig {
+    FieldEncryptionConfig {
+        key_secret: KEY_SECRET.to_string(),
+        fields: fields.iter().map(|f| f.to_string()).collect(),
+    }
+}
+
+/// Selected fields are replaced by envelopes and decrypt back unchanged.
+#[test]
+fn test_encrypt_then_decrypt_round_trips() {
+    let original = advisory_payload();
+    let mut payload = original.clone();
+
+    let pointers = vec![
+        "/security_advisory".to_string(),
+        "/alerts/1/detail".to_string(),
+        "/does/not/exist".to_string(),
+    ];
+    let encrypted = encrypt_fields(&mut payload, &pointers, &key(), KEY_SECRET, CONTEXT).unwrap();
+
+    assert_eq!(encrypted, 2);
+    assert!(is_envelope(&payload["security_advisory"]));
+    assert!(is_envelope(&payload["alerts"][1]["detail"]));
+    assert_eq!(payload["alerts"][0]["detail"], "secret");
+    assert_eq!(payload["repository"], original["repository"]);
+    assert!(!payload.to_string().contains("Remote code execution"));
+    assert_eq!(
+        payload["security_advisory"][ENCRYPTED_FIELD_MARKER]["kid"],
+        KEY_SECRET
+    );
+
+    assert_eq!(decrypt_fields(&mut payload, &key(), CONTEXT).unwrap(), 2);
+    assert_eq!(payload, original);
+}
+
+/// Fields under another configured field decrypt in either pointer order.
+#[test]
+fn test_nested_pointers_round_trip() {
+    let original = advisory_payload();
+    for pointers in [
+        ["/security_advisory/summary", "/security_advisory"],
+        ["/security_advisory", "/security_advisory/summary"],
+    ] {
+        let pointers: Vec<String> = pointers.iter().map(|p| p.to_string()).collect();
+        let mut payload = original.clone();
+        let encrypted =
+            encrypt_fields(&mut payload, &pointers, &key(), KEY_SECRET, CONTEXT).unwrap();
+
+        assert!(is_envelope(&payload["security_advisory"]));
+        assert_eq!(
+            decrypt_fields(&mut payload, &key(), CONTEXT).unwrap(),
+            encrypted
+        );
+        assert_eq!(payload, original);
+    }
+}
+
+/// Decryption with a different key fails.
+#[test]
+fn test_decrypt_with_wrong_key_fails() {
+    let mut payload = advisory_payload();
+    encrypt_fields(
+        &mut payload,
+        &["/security_advisory".to_string()],
+        &key(),
+        KEY_SECRET,
+        CONTEXT,
+    )
+    .unwrap();
+
+    let wrong = FieldEncryptionKey::from_bytes([8u8; KEY_LEN]);
+    assert!(matches!(
+        decrypt_fields(&mut payload, &wrong, CONTEXT),
+        Err(FieldEncryptionError::DecryptionFailed { pointer, .. }) if pointer == "/security_advisory"
+    ));
+}
+
+/// An envelope moved to another field does not decrypt there.
+#[test]
+fn test_moved_envelope_fails_to_decrypt() {
+    let mut payload = advisory_payload();
+    encrypt_fields(
+        &mut payload,
+        &["/security_advisory".to_string()],
+        &key(),
+        KEY_SECRET,
+        CONTEXT,
+    )
+    .unwrap();
+
+    let envelope = payload["security_advisory"].take();
+    payload["action"] = envelope;
+
+    assert!(decrypt_fields(&mut payload, &key(), CONTEXT).is_err());
+}
+
+/// An envelope copied into another event does not decrypt there.
+#[test]
+fn test_envelope_of_other_event_fails_to_decrypt() {
+    let mut payload = advisory_payload();
+    encrypt_fields(
+        &mut payload,
+        &["/security_advisory".to_string()],
+        &key(),
+        KEY_SECRET,
+        CONTEXT,
+    )
+    .unwrap();
+
+    let mut other = WrappedEvent::new(
+        "github".to_string(),
+        "security_advisory".to_string(),
+        None,
+        None,
+        payload.clone(),
+        None,
+    );
+    assert!(decrypt_event(&mut other, &key()).is_err());
+    assert_eq!(decrypt_fields(&mut payload, &key(), CONTEXT).unwrap(), 1);
+}
+
+/// Keys must be 32 bytes of base64.
+#[test]
+fn test_key_from_base64() {
+    let encoded = STANDARD.encode([1u8; KEY_LEN]);
+    assert!(FieldEncryptionKey::from_base64(&encoded).is_ok());
+    assert!(FieldEncryptionKey::from_base64(&STANDARD.encode([1u8; 16])).is_err());
+    assert!(FieldEncryptionKey::from_base64("not base64!").is_err());
+    assert_eq!(format!("{:?}", key()), "FieldEncryptionKey(<REDACTED>)");
+}
+
+/// Configuration requires a valid secret name and JSON pointers.
+#[test]
+fn test_config_validation() {
+    assert!(config(&["/security_advisory"]).validate().is_ok());
+    assert!(config(&[]).validate().is_err());
+    assert!(config(&["security_advisory"]).validate().is_err());
+
+    let mut bad_secret = config(&["/security_advisory"]);
+    bad_secret.key_secret = "bad_name!".to_string();
+    assert!(bad_secret.validate().is_err());
+}
+
+/// The encryptor loads the bot key from the Key Vault and leaves the
+/// original event untouched.
+#[tokio::test]
+async fn test_encryptor_uses_key_vault_key() {
+    let provider = InMemoryKeyVaultProvider::new();
+    provider.add_secret(
+        SecretName::new(KEY_SECRET).unwrap(),
+        SecretValue::from_string(STANDARD.encode([7u8; KEY_LEN])),
+    );
+    let encryptor = FieldEncryptor::new(Arc::new(provider));
+    let event = WrappedEvent::new(
+        "github".to_string(),
+        "security_advisory".to_string(),
+        Some("published".to_string()),
+        None,
+        advisory_payload(),
+        None,
+    );
+
+    let mut encrypted = encryptor
+        .encrypt_event(&event, &config(&["/security_advisory"]))
+        .await
+        .unwrap();
+
+    assert_eq!(event.payload, advisory_payload());
+    assert!(is_envelope(&encrypted.payload["security_advisory"]));
+    assert_eq!(decrypt_event(&mut encrypted, &key()).unwrap(), 1);
+    assert_eq!(encrypted.payload, event.payload);
+}
+
+/// Fields encrypted under the keys of several bots are decrypted, each
+/// with the key its envelope names.
+#[tokio::test]
+async fn test_encryptor_decrypts_fields_of_several_keys() {
+    let provider = InMemoryKeyVaultProvider::new();
+    for (name, byte) in [(KEY_SECRET, 7u8), ("queue-keeper-test-alerts-key", 9u8)] {
+        provider.add_secret(
+            SecretName::new(name).unwrap(),
+            SecretValue::from_string(STANDARD.encode([byte; KEY_LEN])),
+        );
+    }
+    let encryptor = FieldEncryptor::new(Arc::new(provider));
+    let event = WrappedEvent::new(
+        "github".to_string(),
+        "security_advisory".to_string(),
+        None,
+        None,
+        advisory_payload(),
+        None,
+    );
+    let alerts = FieldEncryptionConfig {
+        key_secret: "queue-keeper-test-alerts-key".to_string(),
+        fields: vec!["/alerts".to_string()],
+    };
+
+    let encrypted = encryptor
+        .encrypt_event(&event, &config(&["/security_advisory"]))
+        .await
+        .unwrap();
+    let encrypted = encryptor.encrypt_event(&encrypted, &alerts).await.unwrap();
+    assert!(is_envelope(&encrypted.payload["alerts"]));
+
+    let decrypted = encryptor.decrypt_event(&encrypted).await.unwrap();
+    assert_eq!(decrypted.payload, event.payload);
+}
+
+/// A missing key secret is reported as a Key Vault error.
+#[tokio::test]
+async fn test_encryptor_missing_key_fails() {
+    let encryptor = FieldEncryptor::new(Arc::new(InMemoryKeyVaultProvider::new()));
+    let event = WrappedEvent::new(
+        "github".to_string(),
+        "security_advisory".to_string(),
+        None,
+        None,
+        advisory_payload(),
+        None,
+    );
+
+    let result = encryptor
+        .encrypt_event(&event, &config(&["/security_advisory"]))
+        .await;
+
+    assert!(matches!(result, Err(FieldEncryptionError::KeyVault(_))));
+}
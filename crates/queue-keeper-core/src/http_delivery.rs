@@ -0,0 +1,446 @@
+//! # HTTP Delivery
+//!
+//! Pushes events to bots that are plain webhook receivers rather than queue
+//! consumers. A bot subscription with an HTTP destination
+//! ([`BotSubscription::http`]) receives each event as an HTTP `POST` of the
+//! same JSON envelope a queue consumer would read, instead of a queue
+//! message.
+//!
+//! Every request is signed with the destination's secret, read from the Key
+//! Vault:
+//!
+//! - `X-Hub-Signature-256` carries `sha256=` + hex HMAC-SHA256 of the body,
+//!   so receivers written for GitHub webhooks verify it unchanged.
+//! - `X-Queue-Keeper-Key-Id`, `X-Queue-Keeper-Timestamp` and
+//!   `X-Queue-Keeper-Signature` bind the body to the event ID and signing
+//!   time (see [`crate::request_signing`]). They are recomputed for every
+//!   attempt.
+//!
+//! Transport errors, `429` and `5xx` responses are retried with the
+//! destination's retry settings. Each destination has its own circuit
+//! breaker: once it trips, deliveries to that bot fail fast (as transient
+//! failures) until the recovery timeout has passed. Other responses outside
+//! `2xx` are permanent failures.
+//!
+//! The HTTP client itself is supplied by the service through
+//! [`HttpTransport`], so this crate does not depend on one.
+//!
+//! [`BotSubscription::http`]: crate::bot_config::BotSubscription::http
+
+use crate::{
+    circuit_breaker::{
+        CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError, DefaultCircuitBreaker,
+    },
+    key_vault::{KeyVaultError, KeyVaultProvider, SecretName},
+    request_signing::RequestSignature,
+    units::human_duration,
+    webhook::WrappedEvent,
+    BotName, RetryPolicy,
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use hmac::{Hmac, KeyInit, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Header carrying the GitHub-compatible body signature.
+pub const BODY_SIGNATURE_HEADER: &str = "x-hub-signature-256";
+
+/// Header carrying the Queue-Keeper event ID.
+pub const EVENT_ID_HEADER: &str = "x-queue-keeper-event-id";
+
+/// Header carrying the event type.
+pub const EVENT_TYPE_HEADER: &str = "x-queue-keeper-event-type";
+
+/// Header naming the bot the event is delivered to.
+pub const BOT_NAME_HEADER: &str = "x-queue-keeper-bot";
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// HTTP destination of a single bot subscription.
+///
+/// # YAML example
+///
+/// ```yaml
+/// http:
+///   url: https://triage.internal.example.com/events
+///   signing_secret: queue-keeper-prod-triage-bot-signing
+///   timeout: 5s
+///   retry:
+///     max_attempts: 5
+///     initial_delay: 500ms
+///   circuit_breaker:
+///     failure_threshold: 10
+///     recovery_timeout: 1m
+/// ```
+///
+/// See specs/interfaces/bot-configuration.md
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpDestinationConfig {
+    /// Receiver URL (`http://` or `https://`).
+    pub url: String,
+
+    /// Name of the Key Vault secret the requests are signed with.
+    pub signing_secret: String,
+
+    /// Timeout for one request.
+    #[serde(
+        default = "HttpDestinationConfig::default_timeout",
+        with = "human_duration"
+    )]
+    pub timeout: Duration,
+
+    /// Retries of failed requests.
+    #[serde(default)]
+    pub retry: HttpRetrySettings,
+
+    /// When to stop sending to an unavailable receiver.
+    #[serde(default)]
+    pub circuit_breaker: HttpCircuitBreakerSettings,
+}
+
+impl HttpDestinationConfig {
+    /// Create a destination with the default timeout, retries and circuit
+    /// breaker.
+    pub fn new(url: impl Into<String>, signing_secret: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            signing_secret: signing_secret.into(),
+            timeout: Self::default_timeout(),
+            retry: HttpRetrySettings::default(),
+            circuit_breaker: HttpCircuitBreakerSettings::default(),
+        }
+    }
+
+    fn default_timeout() -> Duration {
+        Duration::from_secs(10)
+    }
+
+    /// Check the URL, secret name and limits.
+    ///
+    /// # Errors
+    /// Returns a description of the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        let host = self
+            .url
+            .strip_prefix("https://")
+            .or_else(|| self.url.strip_prefix("http://"))
+            .and_then(|rest| rest.split('/').next())
+            .unwrap_or_default();
+        if host.is_empty() {
+            return Err(format!(
+                "url must be an http:// or https:// URL (got '{}')",
+                self.url
+            ));
+        }
+        SecretName::new(&self.signing_secret)
+            .map_err(|e| format!("invalid signing_secret '{}': {}", self.signing_secret, e))?;
+        if self.timeout.is_zero() {
+            return Err("timeout must be greater than zero".to_string());
+        }
+        if self.retry.initial_delay > self.retry.max_delay {
+            return Err("retry.initial_delay must not exceed retry.max_delay".to_string());
+        }
+        if self.circuit_breaker.failure_threshold == 0 {
+            return Err("circuit_breaker.failure_threshold must be at least 1".to_string());
+        }
+        if self.circuit_breaker.recovery_timeout.is_zero() {
+            return Err("circuit_breaker.recovery_timeout must be greater than zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Retries of failed requests to an HTTP destination.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpRetrySettings {
+    /// Retries after the first attempt; `0` never retries.
+    pub max_attempts: u32,
+
+    /// Delay before the first retry; doubled for every further retry.
+    #[serde(with = "human_duration")]
+    pub initial_delay: Duration,
+
+    /// Maximum delay between retries.
+    #[serde(with = "human_duration")]
+    pub max_delay: Duration,
+}
+
+impl Default for HttpRetrySettings {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl HttpRetrySettings {
+    /// The backoff policy of these settings.
+    pub fn policy(&self) -> RetryPolicy {
+        RetryPolicy::new(self.max_attempts, self.initial_delay, self.max_delay, 2.0)
+    }
+}
+
+/// Circuit breaker of an HTTP destination.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HttpCircuitBreakerSettings {
+    /// Consecutive failed requests that open the circuit.
+    pub failure_threshold: u32,
+
+    /// How long the circuit stays open before a request is let through to
+    /// probe the receiver.
+    #[serde(with = "human_duration")]
+    pub recovery_timeout: Duration,
+}
+
+impl Default for HttpCircuitBreakerSettings {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            recovery_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors from delivering an event to an HTTP destination.
+#[derive(Debug, thiserror::Error)]
+pub enum HttpDeliveryError {
+    /// The signing secret could not be read from the Key Vault.
+    #[error("Failed to load signing secret: {0}")]
+    Secret(#[from] KeyVaultError),
+
+    /// The event could not be serialized.
+    #[error("Failed to serialize event: {0}")]
+    Serialization(String),
+
+    /// The receiver answered with a status that is not retried.
+    #[error("Receiver rejected the event with status {status}")]
+    Rejected { status: u16 },
+
+    /// The receiver did not accept the event within the retry budget.
+    #[error("Receiver unavailable after {attempts} attempt(s): {reason}")]
+    Unavailable { attempts: u32, reason: String },
+
+    /// The destination's circuit breaker is open.
+    #[error("Circuit breaker is open for the receiver")]
+    CircuitOpen,
+}
+
+impl HttpDeliveryError {
+    /// Check if the error is transient and delivery should be retried.
+    ///
+    /// Exhausted retries are permanent: the destination's own retry policy
+    /// has already been applied.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            Self::Secret(e) => e.is_transient(),
+            Self::CircuitOpen => true,
+            Self::Serialization(_) | Self::Rejected { .. } | Self::Unavailable { .. } => false,
+        }
+    }
+}
+
+// ============================================================================
+// Transport
+// ============================================================================
+
+/// Sends HTTP requests for the [`HttpForwarder`].
+#[async_trait]
+pub trait HttpTransport: Send + Sync {
+    /// `POST` `body` to `url` with `headers`.
+    ///
+    /// # Returns
+    /// The response status, or a description of the transport error
+    /// (connection failure, timeout, ...).
+    async fn post(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        body: Bytes,
+        timeout: Duration,
+    ) -> Result<u16, String>;
+}
+
+// ============================================================================
+// Forwarder
+// ============================================================================
+
+/// Compute the `X-Hub-Signature-256` header value for `body`.
+pub fn sign_body(secret: &[u8], body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// A destination's circuit breaker and the settings it was built from.
+struct DestinationBreaker {
+    settings: HttpCircuitBreakerSettings,
+    timeout: Duration,
+    breaker: DefaultCircuitBreaker<u16, String>,
+}
+
+/// Delivers events to the HTTP destinations of bots.
+pub struct HttpForwarder {
+    transport: Arc<dyn HttpTransport>,
+    key_vault: Arc<dyn KeyVaultProvider>,
+    breakers: Mutex<HashMap<BotName, DestinationBreaker>>,
+}
+
+impl HttpForwarder {
+    /// Create a forwarder sending through `transport` and reading signing
+    /// secrets from `key_vault`.
+    pub fn new(transport: Arc<dyn HttpTransport>, key_vault: Arc<dyn KeyVaultProvider>) -> Self {
+        Self {
+            transport,
+            key_vault,
+            breakers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Deliver `event` to the HTTP destination of `bot`.
+    ///
+    /// # Returns
+    /// The status of the accepting response.
+    ///
+    /// # Errors
+    /// Returns an error if the secret cannot be read, the receiver rejects
+    /// the event, its retries are exhausted or its circuit is open.
+    pub async fn deliver(
+        &self,
+        event: &WrappedEvent,
+        bot: &BotName,
+        config: &HttpDestinationConfig,
+    ) -> Result<u16, HttpDeliveryError> {
+        let name = SecretName::new(&config.signing_secret)?;
+        let secret = self.key_vault.get_secret(&name).await?;
+        let body: Bytes = serde_json::to_vec(event)
+            .map_err(|e| HttpDeliveryError::Serialization(e.to_string()))?
+            .into();
+
+        let event_id = event.event_id.to_string();
+        let mut headers = vec![
+            ("content-type".to_string(), "application/json".to_string()),
+            (
+                BODY_SIGNATURE_HEADER.to_string(),
+                sign_body(secret.expose_bytes(), &body),
+            ),
+            (EVENT_ID_HEADER.to_string(), event_id.clone()),
+            (EVENT_TYPE_HEADER.to_string(), event.event_type.clone()),
+            (BOT_NAME_HEADER.to_string(), bot.as_str().to_string()),
+        ];
+        if let Some(ref trace_parent) = event.trace_parent {
+            headers.push((
+                crate::queue_integration::TRACE_PARENT_ATTRIBUTE.to_string(),
+                trace_parent.clone(),
+            ));
+        }
+
+        let breaker = self.breaker(bot, config);
+        let policy = config.retry.policy();
+        let mut attempt = 0;
+        loop {
+            // Re-signed per attempt so retries keep a fresh timestamp
+            let signature = RequestSignature::sign(
+                secret.expose_bytes(),
+                chrono::Utc::now().timestamp(),
+                &event_id,
+                &body,
+            );
+            let mut request_headers = headers.clone();
+            request_headers.extend(
+                signature
+                    .headers()
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), value)),
+            );
+
+            let transport = &self.transport;
+            let body = body.clone();
+            let result = breaker
+                .call(|| async move {
+                    match transport
+                        .post(&config.url, &request_headers, body, config.timeout)
+                        .await?
+                    {
+                        status if status == 429 || status >= 500 => {
+                            Err(format!("status {}", status))
+                        }
+                        status => Ok(status),
+                    }
+                })
+                .await;
+
+            let reason = match result {
+                Ok(status) if (200..300).contains(&status) => return Ok(status),
+                Ok(status) => return Err(HttpDeliveryError::Rejected { status }),
+                Err(e) if e.is_circuit_protection() => return Err(HttpDeliveryError::CircuitOpen),
+                Err(CircuitBreakerError::OperationFailed(reason)) => reason,
+                Err(e) => e.to_string(),
+            };
+            if !policy.should_retry(attempt) {
+                return Err(HttpDeliveryError::Unavailable {
+                    attempts: attempt + 1,
+                    reason,
+                });
+            }
+            tokio::time::sleep(policy.calculate_delay(attempt)).await;
+            attempt += 1;
+        }
+    }
+
+    /// The circuit breaker of `bot`'s destination, replaced when its
+    /// settings changed.
+    fn breaker(
+        &self,
+        bot: &BotName,
+        config: &HttpDestinationConfig,
+    ) -> DefaultCircuitBreaker<u16, String> {
+        let mut breakers = self.breakers.lock().unwrap();
+        let current = breakers.get(bot).filter(|current| {
+            current.settings == config.circuit_breaker && current.timeout == config.timeout
+        });
+        if let Some(current) = current {
+            return current.breaker.clone();
+        }
+
+        let breaker = DefaultCircuitBreaker::new(CircuitBreakerConfig {
+            service_name: format!("http-{}", bot.as_str()),
+            failure_threshold: config.circuit_breaker.failure_threshold,
+            failure_window_seconds: 60,
+            recovery_timeout_seconds: config.circuit_breaker.recovery_timeout.as_secs().max(1),
+            success_threshold: 1,
+            // The transport enforces the request timeout; this only catches
+            // a transport that ignores it
+            operation_timeout_seconds: config.timeout.as_secs() + 1,
+            half_open_max_requests: 1,
+        });
+        breakers.insert(
+            bot.clone(),
+            DestinationBreaker {
+                settings: config.circuit_breaker.clone(),
+                timeout: config.timeout,
+                breaker: breaker.clone(),
+            },
+        );
+        breaker
+    }
+}
+
+#[cfg(test)]
+#[path = "http_delivery_tests.rs"]
+mod tests;
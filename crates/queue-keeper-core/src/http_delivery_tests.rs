@@ -0,0 +1,274 @@
+//! Tests for HTTP delivery.
+
+use super::*;
+use crate::{
+    adapters::InMemoryKeyVaultProvider, key_vault::SecretValue, request_signing, RequestVerifier,
+};
+use std::collections::VecDeque;
+
+const SECRET_NAME: &str = "queue-keeper-test-hook-bot-signing";
+const SECRET: &str = "receiver-secret";
+
+/// Request recorded by the [`MockTransport`].
+#[derive(Debug, Clone)]
+struct SentRequest {
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Bytes,
+}
+
+impl SentRequest {
+    fn header(&self, name: &str) -> &str {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+            .unwrap()
+    }
+}
+
+/// Transport answering with scripted results, then `200`.
+struct MockTransport {
+    responses: Mutex<VecDeque<Result<u16, String>>>,
+    sent: Mutex<Vec<SentRequest>>,
+}
+
+impl MockTransport {
+    fn with_responses(responses: Vec<Result<u16, String>>) -> Arc<Self> {
+        Arc::new(Self {
+            responses: Mutex::new(responses.into()),
+            sent: Mutex::default(),
+        })
+    }
+
+    fn sent(&self) -> Vec<SentRequest> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl HttpTransport for MockTransport {
+    async fn post(
+        &self,
+        url: &str,
+        headers: &[(String, String)],
+        body: Bytes,
+        _timeout: Duration,
+    ) -> Result<u16, String> {
+        self.sent.lock().unwrap().push(SentRequest {
+            url: url.to_string(),
+            headers: headers.to_vec(),
+            body,
+        });
+        self.responses
+            .lock()
+            .unwrap()
+            .pop_front()
+            .unwrap_or(Ok(200))
+    }
+}
+
+fn forwarder(transport: Arc<MockTransport>) -> HttpForwarder {
+    let key_vault = InMemoryKeyVaultProvider::new();
+    key_vault.add_secret(
+        SecretName::new(SECRET_NAME).unwrap(),
+        SecretValue::from_string(SECRET.to_string()),
+    );
+    HttpForwarder::new(transport, Arc::new(key_vault))
+}
+
+fn config() -> HttpDestinationConfig {
+    let mut config = HttpDestinationConfig::new("https://hooks.example.com/events", SECRET_NAME);
+    config.retry = HttpRetrySettings {
+        max_attempts: 2,
+        initial_delay: Duration::from_millis(1),
+        max_delay: Duration::from_millis(2),
+    };
+    config
+}
+
+fn bot() -> BotName {
+    BotName::new("hook-bot".to_string()).unwrap()
+}
+
+fn event() -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        None,
+        serde_json::json!({ "number": 1 }),
+        None,
+    )
+}
+
+/// The envelope is posted with a GitHub-style signature and a request
+/// signature binding the event ID.
+#[tokio::test]
+async fn test_deliver_signs_request() {
+    let transport = MockTransport::with_responses(vec![Ok(202)]);
+    let event = event();
+
+    let status = forwarder(transport.clone())
+        .deliver(&event, &bot(), &config())
+        .await
+        .unwrap();
+
+    assert_eq!(status, 202);
+    let sent = transport.sent();
+    assert_eq!(sent.len(), 1);
+    let request = &sent[0];
+    assert_eq!(request.url, "https://hooks.example.com/events");
+    let delivered: WrappedEvent = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(delivered.event_id, event.event_id);
+    assert_eq!(
+        request.header(BODY_SIGNATURE_HEADER),
+        sign_body(SECRET.as_bytes(), &request.body)
+    );
+    assert_eq!(request.header(EVENT_TYPE_HEADER), "pull_request");
+    assert_eq!(request.header(BOT_NAME_HEADER), "hook-bot");
+
+    let event_id = request.header(EVENT_ID_HEADER);
+    assert_eq!(event_id, event.event_id.to_string());
+    assert!(RequestVerifier::new([SECRET])
+        .verify(
+            request.header(request_signing::KEY_ID_HEADER),
+            request.header(request_signing::TIMESTAMP_HEADER),
+            request.header(request_signing::SIGNATURE_HEADER),
+            event_id,
+            &request.body,
+        )
+        .is_ok());
+}
+
+/// Transport errors, 429 and 5xx responses are retried.
+#[tokio::test]
+async fn test_deliver_retries_transient_failures() {
+    let transport =
+        MockTransport::with_responses(vec![Err("connection reset".to_string()), Ok(503)]);
+
+    let status = forwarder(transport.clone())
+        .deliver(&event(), &bot(), &config())
+        .await
+        .unwrap();
+
+    assert_eq!(status, 200);
+    assert_eq!(transport.sent().len(), 3);
+}
+
+/// Exhausted retries and other error statuses fail permanently.
+#[tokio::test]
+async fn test_deliver_permanent_failures() {
+    let transport = MockTransport::with_responses(vec![Ok(429), Ok(500), Ok(502)]);
+    let err = forwarder(transport.clone())
+        .deliver(&event(), &bot(), &config())
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        HttpDeliveryError::Unavailable { attempts: 3, .. }
+    ));
+    assert!(!err.is_transient());
+
+    let transport = MockTransport::with_responses(vec![Ok(400)]);
+    let err = forwarder(transport.clone())
+        .deliver(&event(), &bot(), &config())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, HttpDeliveryError::Rejected { status: 400 }));
+    assert!(!err.is_transient());
+    assert_eq!(transport.sent().len(), 1);
+}
+
+/// Once the circuit opens, deliveries fail fast without a request.
+#[tokio::test]
+async fn test_deliver_circuit_opens() {
+    let transport = MockTransport::with_responses(vec![Ok(500); 3]);
+    let forwarder = forwarder(transport.clone());
+    let mut config = config();
+    config.retry.max_attempts = 0;
+    config.circuit_breaker.failure_threshold = 2;
+
+    for _ in 0..2 {
+        let err = forwarder
+            .deliver(&event(), &bot(), &config)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, HttpDeliveryError::Unavailable { .. }));
+    }
+    let err = forwarder
+        .deliver(&event(), &bot(), &config)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, HttpDeliveryError::CircuitOpen));
+    assert!(err.is_transient());
+    assert_eq!(transport.sent().len(), 2);
+
+    // Other destinations keep their own circuit
+    let other = BotName::new("other-bot".to_string()).unwrap();
+    assert!(forwarder.deliver(&event(), &other, &config).await.is_err());
+    assert_eq!(transport.sent().len(), 3);
+}
+
+/// A missing signing secret fails without sending the event.
+#[tokio::test]
+async fn test_deliver_without_secret_fails() {
+    let transport = MockTransport::with_responses(vec![]);
+    let mut config = config();
+    config.signing_secret = "queue-keeper-test-missing".to_string();
+
+    let err = forwarder(transport.clone())
+        .deliver(&event(), &bot(), &config)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, HttpDeliveryError::Secret(_)));
+    assert!(transport.sent().is_empty());
+}
+
+/// Destinations need an HTTP URL, a valid secret name and usable limits.
+#[test]
+fn test_validate() {
+    assert!(config().validate().is_ok());
+
+    let mut invalid = Vec::new();
+    for url in ["hooks.example.com", "ftp://hooks.example.com", "https://"] {
+        let mut config = config();
+        config.url = url.to_string();
+        invalid.push(config);
+    }
+    let mut config_with = |change: fn(&mut HttpDestinationConfig)| {
+        let mut config = config();
+        change(&mut config);
+        invalid.push(config);
+    };
+    config_with(|c| c.signing_secret = "not a secret name".to_string());
+    config_with(|c| c.timeout = Duration::ZERO);
+    config_with(|c| c.retry.initial_delay = Duration::from_secs(60));
+    config_with(|c| c.circuit_breaker.failure_threshold = 0);
+    config_with(|c| c.circuit_breaker.recovery_timeout = Duration::ZERO);
+
+    for config in invalid {
+        assert!(config.validate().is_err(), "{:?}", config);
+    }
+}
+
+/// Only the URL and secret are required.
+#[test]
+fn test_deserialize_yaml_defaults() {
+    let config: HttpDestinationConfig = serde_yaml::from_str(
+        "url: https://hooks.example.com/events\n\
+         signing_secret: queue-keeper-test-hook-bot-signing\n\
+         retry:\n  max_attempts: 5\n",
+    )
+    .unwrap();
+
+    assert_eq!(config.timeout, Duration::from_secs(10));
+    assert_eq!(config.retry.max_attempts, 5);
+    assert_eq!(config.retry.initial_delay, Duration::from_secs(1));
+    assert_eq!(
+        config.circuit_breaker,
+        HttpCircuitBreakerSettings::default()
+    );
+}
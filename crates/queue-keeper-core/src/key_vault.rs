@@ -79,6 +79,50 @@ impl SecretName {
         &self.0
     }
 
+    /// Name of the secret of one repository, derived from this base name
+    ///
+    /// Format: "{base}--{owner}--{repo}" for `repository` "owner/repo".
+    /// Names are lowercased and characters Key Vault does not allow in
+    /// names (`.` and `_`) become hyphens, so "My_Org/web.app" maps to
+    /// "{base}--my-org--web-app".
+    pub fn for_repository(&self, repository: &str) -> Result<Self, KeyVaultError> {
+        let (owner, repo) = split_repository(repository)?;
+        Self::new(format!(
+            "{}--{}--{}",
+            self.0,
+            secret_name_segment(owner),
+            secret_name_segment(repo)
+        ))
+    }
+
+    /// Name of the secret shared by every repository of `owner`, derived from
+    /// this base name
+    ///
+    /// Format: "{base}--{owner}"; the wildcard "owner/*" of
+    /// [`for_repository`](Self::for_repository).
+    pub fn for_repository_owner(&self, owner: &str) -> Result<Self, KeyVaultError> {
+        Self::new(format!("{}--{}", self.0, secret_name_segment(owner)))
+    }
+
+    /// Names to try, in order, for the secret of `repository`
+    ///
+    /// The repository's own secret, then its owner's wildcard secret, then
+    /// this base name. Derived names exceeding the Key Vault length limit
+    /// are left out.
+    pub fn repository_candidates(&self, repository: &str) -> Vec<Self> {
+        let mut names = Vec::with_capacity(3);
+        if let Ok(name) = self.for_repository(repository) {
+            names.push(name);
+        }
+        if let Ok((owner, _)) = split_repository(repository) {
+            if let Ok(name) = self.for_repository_owner(owner) {
+                names.push(name);
+            }
+        }
+        names.push(self.clone());
+        names
+    }
+
     /// Get components (service, environment, purpose)
     pub fn get_components(&self) -> Option<(String, String, String)> {
         let parts: Vec<&str> = self.0.split('-').collect();
@@ -93,6 +137,34 @@ impl SecretName {
     }
 }
 
+/// Split a repository full name into owner and name.
+fn split_repository(repository: &str) -> Result<(&str, &str), KeyVaultError> {
+    match repository.split_once('/') {
+        Some((owner, repo)) if !owner.is_empty() && !repo.is_empty() && !repo.contains('/') => {
+            Ok((owner, repo))
+        }
+        _ => Err(KeyVaultError::InvalidSecretName {
+            name: repository.to_string(),
+            reason: "Repository must be in owner/repo format".to_string(),
+        }),
+    }
+}
+
+/// Lowercase `segment` and replace characters not allowed in secret names
+/// with hyphens.
+fn secret_name_segment(segment: &str) -> String {
+    segment
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
 impl fmt::Display for SecretName {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{}", self.0)
@@ -237,6 +309,39 @@ impl CachedSecret {
     }
 }
 
+/// When a cached secret was last fetched from the vault
+///
+/// Reported by [`KeyVaultProvider::secret_freshness`] for staleness
+/// monitoring; carries no secret material.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretFreshness {
+    /// Secret name
+    pub name: SecretName,
+
+    /// When the cached value was fetched
+    pub refreshed_at: Timestamp,
+
+    /// Secret version of the cached value, when known
+    pub version: Option<String>,
+}
+
+impl SecretFreshness {
+    /// Time since the cached value was fetched
+    pub fn age(&self) -> Duration {
+        Timestamp::now().duration_since(self.refreshed_at)
+    }
+}
+
+impl From<&CachedSecret> for SecretFreshness {
+    fn from(cached: &CachedSecret) -> Self {
+        Self {
+            name: cached.name.clone(),
+            refreshed_at: cached.cached_at,
+            version: cached.version.clone(),
+        }
+    }
+}
+
 /// Configuration for Key Vault behavior
 ///
 /// See specs/interfaces/key-vault.md
@@ -340,6 +445,9 @@ pub trait KeyVaultProvider: Send + Sync {
 
     /// Get cache statistics for monitoring
     async fn get_cache_stats(&self) -> Result<CacheStatistics, KeyVaultError>;
+
+    /// Report when each cached secret was last fetched from the vault
+    async fn secret_freshness(&self) -> Result<Vec<SecretFreshness>, KeyVaultError>;
 }
 
 /// Interface for secure secret caching
@@ -387,6 +495,9 @@ pub trait SecretCache: Send + Sync {
 
     /// Get cache statistics
     async fn get_statistics(&self) -> Result<CacheStatistics, KeyVaultError>;
+
+    /// Report when each cached secret was fetched, ordered by name
+    async fn get_freshness(&self) -> Result<Vec<SecretFreshness>, KeyVaultError>;
 }
 
 /// Interface for handling secret rotation
@@ -736,6 +847,10 @@ impl KeyVaultProvider for DefaultKeyVaultProvider {
     async fn get_cache_stats(&self) -> Result<CacheStatistics, KeyVaultError> {
         self.cache.get_statistics().await
     }
+
+    async fn secret_freshness(&self) -> Result<Vec<SecretFreshness>, KeyVaultError> {
+        self.cache.get_freshness().await
+    }
 }
 
 #[cfg(test)]
@@ -26,6 +26,36 @@ fn test_secret_name_components() {
     assert_eq!(purpose, "github-webhook");
 }
 
+#[test]
+fn test_repository_secret_names() {
+    let base = SecretName::new("github-webhook").unwrap();
+
+    let name = base.for_repository("My_Org/web.app").unwrap();
+    assert_eq!(name.as_str(), "github-webhook--my-org--web-app");
+    let owner = base.for_repository_owner("My_Org").unwrap();
+    assert_eq!(owner.as_str(), "github-webhook--my-org");
+    assert!(base.for_repository("no-slash").is_err());
+    assert!(base.for_repository("a/b/c").is_err());
+
+    let candidates: Vec<String> = base
+        .repository_candidates("My_Org/web.app")
+        .iter()
+        .map(|n| n.to_string())
+        .collect();
+    assert_eq!(
+        candidates,
+        vec![
+            "github-webhook--my-org--web-app",
+            "github-webhook--my-org",
+            "github-webhook"
+        ]
+    );
+
+    // A derived name beyond the length limit falls back to the wildcards
+    let long = format!("org/{}", "r".repeat(120));
+    assert_eq!(base.repository_candidates(&long).len(), 2);
+}
+
 #[test]
 fn test_secret_value_security() {
     let secret = SecretValue::from_string("sensitive-data".to_string());
@@ -39,10 +39,25 @@ pub mod audit_logging;
 pub mod blob_storage;
 pub mod bot_config;
 pub mod circuit_breaker;
+pub mod consumer;
 pub mod event_replay;
+pub mod field_encryption;
+pub mod http_delivery;
 pub mod key_vault;
+pub mod logging;
 pub mod monitoring;
+pub mod payload_encryption;
+pub mod payload_projection;
+pub mod payload_retention;
+pub mod pubsub;
 pub mod queue_integration;
+pub mod request_signing;
+pub mod session_control;
+pub mod session_tracker;
+pub mod storage_format;
+pub mod timing;
+pub mod units;
+pub mod wasm_transform;
 pub mod webhook;
 
 /// Standard result type for queue-keeper operations
@@ -60,7 +75,10 @@ pub type QueueKeeperResult<T> = Result<T, QueueKeeperError>;
 /// Note: Implements Copy since ULID is a fixed-size value type (128 bits).
 /// This allows EventId to be used ergonomically in collections and iterations
 /// where the underlying Ulid would normally be copied anyway.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+///
+/// Ordering follows the ULID, i.e. creation time; the API's pagination
+/// cursors rely on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct EventId(Ulid);
 
 impl EventId {
@@ -449,6 +467,16 @@ pub struct User {
     pub user_type: UserType,
 }
 
+impl User {
+    /// Whether this is a bot account.
+    ///
+    /// GitHub App accounts have type `Bot` and a login ending in `[bot]`;
+    /// either is enough.
+    pub fn is_bot(&self) -> bool {
+        self.user_type == UserType::Bot || self.login.ends_with("[bot]")
+    }
+}
+
 /// GitHub user type enumeration
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum UserType {
@@ -821,86 +849,242 @@ pub enum ErrorCategory {
     Configuration,
 }
 
-/// Configuration for retry behavior
+/// Retry policy with exponential backoff, shared by every layer that retries.
+///
+/// Queue delivery, the webhook gateway, blob storage and message settlement
+/// all take this policy, so operators tune retries with the same settings
+/// everywhere.
+///
+/// # Examples
+///
+/// ```rust
+/// use queue_keeper_core::RetryPolicy;
+/// use std::time::Duration;
+///
+/// // Default policy: 5 attempts, 1s initial, 16s max, 2.0x multiplier
+/// let policy = RetryPolicy::default();
+///
+/// // Custom policy
+/// let policy = RetryPolicy::new(3, Duration::from_millis(500), Duration::from_secs(5), 1.5);
+/// ```
+///
+/// # YAML example
+///
+/// Delays are written with their unit; omitted fields keep their defaults.
+///
+/// ```yaml
+/// retry:
+///   delivery:
+///     max_attempts: 3
+///     initial_delay: 500ms
+///     max_delay: 30s
+/// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct RetryPolicy {
+    /// Maximum number of retry attempts
     pub max_attempts: u32,
-    pub base_delay: Duration,
+
+    /// Initial delay before first retry
+    #[serde(with = "units::human_duration")]
+    pub initial_delay: Duration,
+
+    /// Maximum delay between retries
+    #[serde(with = "units::human_duration")]
     pub max_delay: Duration,
+
+    /// Exponential backoff multiplier (typically 2.0)
     pub backoff_multiplier: f64,
-    pub jitter_enabled: bool,
+
+    /// Whether to add jitter to delays (recommended)
+    pub use_jitter: bool,
+
+    /// Jitter range as percentage (default 25% = ±25%)
+    pub jitter_percent: f64,
 }
 
-impl RetryPolicy {
-    /// Create exponential backoff retry policy
-    pub fn exponential() -> Self {
+impl Default for RetryPolicy {
+    fn default() -> Self {
         Self {
             max_attempts: 5,
-            base_delay: Duration::from_millis(100),
-            max_delay: Duration::from_secs(30),
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(16),
             backoff_multiplier: 2.0,
-            jitter_enabled: true,
+            use_jitter: true,
+            jitter_percent: 0.25, // ±25%
         }
     }
+}
 
-    /// Create linear backoff retry policy
-    pub fn linear() -> Self {
+impl RetryPolicy {
+    /// Create a new retry policy
+    ///
+    /// # Arguments
+    ///
+    /// * `max_attempts` - Maximum retry attempts (typically 3-5)
+    /// * `initial_delay` - Initial delay before first retry
+    /// * `max_delay` - Maximum delay cap
+    /// * `backoff_multiplier` - Exponential growth factor (typically 1.5-2.0)
+    pub fn new(
+        max_attempts: u32,
+        initial_delay: Duration,
+        max_delay: Duration,
+        backoff_multiplier: f64,
+    ) -> Self {
         Self {
-            max_attempts: 5,
-            base_delay: Duration::from_millis(500),
-            max_delay: Duration::from_secs(10),
-            backoff_multiplier: 1.0,
-            jitter_enabled: true,
+            max_attempts,
+            initial_delay,
+            max_delay,
+            backoff_multiplier,
+            use_jitter: true,
+            jitter_percent: 0.25,
         }
     }
 
+    /// Create exponential backoff retry policy
+    pub fn exponential() -> Self {
+        Self::new(5, Duration::from_millis(100), Duration::from_secs(30), 2.0)
+    }
+
+    /// Create linear backoff retry policy
+    pub fn linear() -> Self {
+        Self::new(5, Duration::from_millis(500), Duration::from_secs(10), 1.0)
+    }
+
     /// Create fixed delay retry policy
     pub fn fixed(delay: Duration) -> Self {
-        Self {
-            max_attempts: 5,
-            base_delay: delay,
-            max_delay: delay,
-            backoff_multiplier: 1.0,
-            jitter_enabled: false,
-        }
+        Self::new(5, delay, delay, 1.0).without_jitter()
     }
 
-    /// Calculate delay for specific attempt number
-    pub fn calculate_delay(&self, attempt: u32) -> Duration {
-        if attempt == 0 {
-            return Duration::ZERO;
+    /// Validate the policy.
+    ///
+    /// # Errors
+    /// Returns a description of the first inconsistent setting.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.initial_delay > self.max_delay {
+            return Err(format!(
+                "retry.initial_delay ({}) must not exceed retry.max_delay ({})",
+                HumanDuration::from(self.initial_delay),
+                HumanDuration::from(self.max_delay)
+            ));
+        }
+        if self.backoff_multiplier.is_nan() || self.backoff_multiplier < 1.0 {
+            return Err("retry.backoff_multiplier must be at least 1.0".to_string());
         }
+        if !(0.0..=1.0).contains(&self.jitter_percent) {
+            return Err("retry.jitter_percent must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
 
-        let mut delay = self.base_delay.as_millis() as f64;
+    /// Disable jitter (not recommended for production)
+    pub fn without_jitter(mut self) -> Self {
+        self.use_jitter = false;
+        self
+    }
 
-        // Apply backoff multiplier
-        for _ in 1..attempt {
-            delay *= self.backoff_multiplier;
-        }
+    /// Set custom jitter percentage (0.0 to 1.0)
+    pub fn with_jitter_percent(mut self, percent: f64) -> Self {
+        self.jitter_percent = percent.clamp(0.0, 1.0);
+        self
+    }
 
-        // Apply jitter if enabled
-        if self.jitter_enabled {
-            use std::collections::hash_map::DefaultHasher;
-            use std::hash::{Hash, Hasher};
+    /// Calculate delay for a specific retry attempt
+    ///
+    /// Uses exponential backoff formula: delay = initial * multiplier^attempt
+    /// Adds jitter if enabled to prevent thundering herd
+    ///
+    /// # Arguments
+    ///
+    /// * `attempt` - Retry attempt number (0-based)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_core::RetryPolicy;
+    ///
+    /// let policy = RetryPolicy::default();
+    ///
+    /// // First retry (attempt 0): ~1s
+    /// let delay = policy.calculate_delay(0);
+    /// assert!(delay.as_secs() >= 0 && delay.as_secs() <= 2);
+    ///
+    /// // Second retry (attempt 1): ~2s
+    /// let delay = policy.calculate_delay(1);
+    /// assert!(delay.as_secs() >= 1 && delay.as_secs() <= 3);
+    /// ```
+    pub fn calculate_delay(&self, attempt: u32) -> Duration {
+        // Calculate base delay: initial * multiplier^attempt
+        let base_delay_secs =
+            self.initial_delay.as_secs_f64() * self.backoff_multiplier.powi(attempt as i32);
 
-            let mut hasher = DefaultHasher::new();
-            attempt.hash(&mut hasher);
-            let hash = hasher.finish();
+        // Cap at max_delay
+        let capped_delay_secs = base_delay_secs.min(self.max_delay.as_secs_f64());
 
-            // ±25% jitter
-            let jitter_factor = 0.75 + (hash % 500) as f64 / 2000.0;
-            delay *= jitter_factor;
-        }
+        // Add jitter if enabled
+        let final_delay_secs = if self.use_jitter {
+            Self::add_jitter(capped_delay_secs, self.jitter_percent)
+        } else {
+            capped_delay_secs
+        };
 
-        // Cap at maximum delay
-        let delay_ms = delay.min(self.max_delay.as_millis() as f64) as u64;
-        Duration::from_millis(delay_ms)
+        Duration::from_secs_f64(final_delay_secs)
     }
-}
 
-impl Default for RetryPolicy {
-    fn default() -> Self {
-        Self::exponential()
+    /// Check if we should retry for this attempt number
+    ///
+    /// `attempt` is 0-based, where 0 is the first retry; returns `true` if
+    /// it does not exceed `max_attempts`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_core::RetryPolicy;
+    ///
+    /// let policy = RetryPolicy::default(); // max_attempts = 5
+    ///
+    /// assert!(policy.should_retry(0));  // First retry
+    /// assert!(policy.should_retry(4));  // Fifth retry
+    /// assert!(!policy.should_retry(5)); // Sixth would exceed max
+    /// ```
+    pub fn should_retry(&self, attempt: u32) -> bool {
+        attempt < self.max_attempts
+    }
+
+    /// Get total number of attempts (initial + retries)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_core::RetryPolicy;
+    ///
+    /// let policy = RetryPolicy::default(); // max_attempts = 5
+    /// assert_eq!(policy.total_attempts(), 6); // 1 initial + 5 retries
+    /// ```
+    pub fn total_attempts(&self) -> u32 {
+        self.max_attempts + 1 // Initial attempt + retries
+    }
+
+    /// Add jitter to a delay value
+    ///
+    /// Applies random variation in range [delay * (1-jitter), delay * (1+jitter)]
+    ///
+    /// # Note
+    ///
+    /// Uses `rng()` which is acceptable for retry scenarios (infrequent calls).
+    /// For high-frequency random generation in async contexts, consider `SmallRng` or `fastrand`.
+    fn add_jitter(delay_secs: f64, jitter_percent: f64) -> f64 {
+        use rand::RngExt;
+        let mut rng = rand::rng();
+
+        // Calculate jitter range: ±jitter_percent of delay
+        let jitter_range = delay_secs * jitter_percent;
+
+        // Generate random value in range [-jitter_range, +jitter_range]
+        let jitter = rng.random_range(-jitter_range..=jitter_range);
+
+        // Apply jitter, ensuring result is positive
+        (delay_secs + jitter).max(0.0)
     }
 }
 
@@ -924,6 +1108,9 @@ pub enum ValidationError {
         field: String,
         invalid_chars: String,
     },
+
+    #[error("Signature was made with retired secret '{label}'")]
+    RetiredSecret { label: String },
 }
 
 /// Error type for string parsing failures
@@ -991,6 +1178,9 @@ impl QueueKeeperError {
 }
 
 // Re-export key types for convenience
+#[cfg(feature = "azure-blob-storage")]
+pub use adapters::AzureBlobStorage;
+#[cfg(feature = "filesystem-storage")]
 pub use adapters::FilesystemBlobStorage;
 pub use audit_logging::{
     AuditActor, AuditContext, AuditError, AuditEvent, AuditEventType, AuditLogId, AuditLogger,
@@ -1001,21 +1191,38 @@ pub use blob_storage::{
     StorageHealthStatus, StorageMetrics, StoredWebhook, WebhookPayload,
 };
 pub use bot_config::{
-    BotConfigError, BotConfiguration, BotConfigurationProvider, BotSubscription,
-    ConfigurationLoader, EventMatcher, EventTypePattern, QueueDestination, RepositoryFilter,
-    RoutingDecision,
+    BotConfigError, BotConfiguration, BotConfigurationProvider, BotConfigurationVersion,
+    BotSubscription, ConfigurationLoader, ConfigurationWatcher, EventMatcher, EventTypePattern,
+    QueueDestination, RepositoryFilter, RoutingDecision, SenderFilter, SharedBotConfiguration,
+};
+pub use consumer::{
+    CategorizedError, DeadLetterReason, MessageSettler, Settlement, SettlementPolicy,
 };
 pub use event_replay::{
     EventFilter, EventReplayService, EventRetriever, ProcessingStatus, ReplayError, ReplayExecutor,
     ReplayId, ReplayRequest, ReplayState, ReplayStatus, ReplayType, StoredEvent,
 };
+pub use field_encryption::{
+    decrypt_event, FieldEncryptionConfig, FieldEncryptionError, FieldEncryptionKey, FieldEncryptor,
+};
+pub use http_delivery::{HttpDeliveryError, HttpDestinationConfig, HttpForwarder, HttpTransport};
 pub use key_vault::{
-    CachedSecret, KeyVaultConfiguration, KeyVaultError, KeyVaultProvider, SecretCache, SecretName,
-    SecretRotationHandler, SecretValue, StandardSecrets,
+    CachedSecret, KeyVaultConfiguration, KeyVaultError, KeyVaultProvider, SecretCache,
+    SecretFreshness, SecretName, SecretRotationHandler, SecretValue, StandardSecrets,
 };
 pub use queue_integration::{
     DefaultEventRouter, DeliveryResult, EventRouter, FailedDelivery, QueueDeliveryError,
-    SuccessfulDelivery,
+    SequenceTicket, SessionSequencer, SuccessfulDelivery,
+};
+pub use request_signing::{RequestSignature, RequestSignatureError, RequestVerifier};
+pub use session_tracker::{
+    SessionStatus, SessionTracker, SessionTrackerConfig, StuckSession, TrackedEvent,
+    TrackedEventStatus, TrackedSession,
+};
+pub use timing::{MonotonicTimestamp, PipelineStage, StageTimings};
+pub use units::{ByteSize, HumanDuration};
+pub use wasm_transform::{
+    TransformError, TransformOutcome, TransformRegistry, TransformStatus, WasmTransformConfig,
 };
 pub use webhook::{
     EventEntity, EventEnvelope, GithubWebhookProvider, ProcessingOutput, WebhookError,
@@ -0,0 +1,293 @@
+//! # Structured Logging Fields
+//!
+//! Standard field set for log records about an event, so that intake,
+//! routing and delivery logs from every crate can be joined on the same keys:
+//!
+//! | Field            | Meaning                                        |
+//! |------------------|------------------------------------------------|
+//! | `event_id`       | [`EventId`] of the event being handled         |
+//! | `session_id`     | [`SessionId`] for ordered events               |
+//! | `correlation_id` | [`CorrelationId`] shared across the pipeline   |
+//! | `bot`            | Target bot name during routing and delivery    |
+//! | `queue`          | Target queue name during routing and delivery  |
+//!
+//! Types that carry event context implement [`LogFields`]. The
+//! [`event_trace!`](crate::event_trace), [`event_debug!`](crate::event_debug),
+//! [`event_info!`](crate::event_info), [`event_warn!`](crate::event_warn) and
+//! [`event_error!`](crate::event_error) macros take such a value, attach the
+//! fields it knows, and then forward any extra fields and the message to the
+//! matching `tracing` macro. Fields the context does not know are omitted
+//! rather than logged as empty.
+//!
+//! ```rust
+//! use queue_keeper_core::{event_info, event_warn, logging::LogFields, webhook::WrappedEvent};
+//!
+//! let event = WrappedEvent::new(
+//!     "github".to_string(),
+//!     "push".to_string(),
+//!     None,
+//!     None,
+//!     serde_json::json!({}),
+//!     None,
+//! );
+//!
+//! event_info!(event, attempt = 1, "Routing event");
+//! event_warn!(
+//!     event.log_context().with_bot("merge-warden").with_queue("queue-keeper-merge-warden"),
+//!     error = "timeout",
+//!     "Queue delivery failed for bot"
+//! );
+//! ```
+
+use crate::{
+    queue_integration::{FailedDelivery, SuccessfulDelivery},
+    webhook::{DirectQueueMetadata, EventEnvelope, ProcessingOutput, WrappedEvent},
+    CorrelationId, EventId, SessionId,
+};
+use std::fmt;
+
+#[doc(hidden)]
+pub use tracing as __tracing;
+
+/// Names of the standard fields, in the order they are emitted.
+pub const STANDARD_FIELDS: [&str; 5] = ["event_id", "session_id", "correlation_id", "bot", "queue"];
+
+// ============================================================================
+// Log Context
+// ============================================================================
+
+/// Values for the standard log fields; unknown values are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogContext {
+    /// Event being handled.
+    pub event_id: Option<EventId>,
+
+    /// Ordering session of the event.
+    pub session_id: Option<SessionId>,
+
+    /// Correlation identifier of the event.
+    pub correlation_id: Option<CorrelationId>,
+
+    /// Target bot name.
+    pub bot: Option<String>,
+
+    /// Target queue name.
+    pub queue: Option<String>,
+}
+
+impl LogContext {
+    /// Create an empty context.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the event identifier.
+    pub fn with_event_id(mut self, event_id: EventId) -> Self {
+        self.event_id = Some(event_id);
+        self
+    }
+
+    /// Set the correlation identifier.
+    pub fn with_correlation_id(mut self, correlation_id: CorrelationId) -> Self {
+        self.correlation_id = Some(correlation_id);
+        self
+    }
+
+    /// Set the target bot.
+    pub fn with_bot(mut self, bot: impl fmt::Display) -> Self {
+        self.bot = Some(bot.to_string());
+        self
+    }
+
+    /// Set the target queue.
+    pub fn with_queue(mut self, queue: impl fmt::Display) -> Self {
+        self.queue = Some(queue.to_string());
+        self
+    }
+
+    /// Create an `INFO` span named `event` carrying the standard fields.
+    ///
+    /// Use with `tracing::Instrument` so that every record emitted inside a
+    /// future inherits the fields without repeating them.
+    pub fn span(&self) -> tracing::Span {
+        tracing::info_span!(
+            "event",
+            event_id = self.event_id.as_ref().map(tracing::field::display),
+            session_id = self.session_id.as_ref().map(tracing::field::display),
+            correlation_id = self.correlation_id.as_ref().map(tracing::field::display),
+            bot = self.bot.as_deref(),
+            queue = self.queue.as_deref(),
+        )
+    }
+}
+
+// ============================================================================
+// Log Fields
+// ============================================================================
+
+/// Source of the standard log fields.
+pub trait LogFields {
+    /// The standard field values known to this value.
+    fn log_context(&self) -> LogContext;
+}
+
+impl<T: LogFields + ?Sized> LogFields for &T {
+    fn log_context(&self) -> LogContext {
+        (**self).log_context()
+    }
+}
+
+impl LogFields for LogContext {
+    fn log_context(&self) -> LogContext {
+        self.clone()
+    }
+}
+
+impl LogFields for WrappedEvent {
+    fn log_context(&self) -> LogContext {
+        LogContext {
+            event_id: Some(self.event_id),
+            session_id: self.session_id.clone(),
+            correlation_id: Some(self.correlation_id.clone()),
+            bot: None,
+            queue: None,
+        }
+    }
+}
+
+impl LogFields for EventEnvelope {
+    fn log_context(&self) -> LogContext {
+        LogContext {
+            event_id: Some(self.event_id),
+            session_id: Some(self.session_id.clone()),
+            correlation_id: Some(self.correlation_id.clone()),
+            bot: None,
+            queue: None,
+        }
+    }
+}
+
+impl LogFields for DirectQueueMetadata {
+    fn log_context(&self) -> LogContext {
+        LogContext::new()
+            .with_event_id(self.event_id())
+            .with_correlation_id(self.correlation_id().clone())
+    }
+}
+
+impl LogFields for ProcessingOutput {
+    fn log_context(&self) -> LogContext {
+        match self {
+            Self::Wrapped(event) => event.log_context(),
+            Self::Direct {
+                metadata,
+                target_queue,
+                ..
+            } => {
+                let context = metadata.log_context();
+                match target_queue {
+                    Some(queue) => context.with_queue(queue),
+                    None => context,
+                }
+            }
+            Self::AlreadyProcessed {
+                event_id,
+                correlation_id,
+                ..
+            } => LogContext::new()
+                .with_event_id(*event_id)
+                .with_correlation_id(correlation_id.clone()),
+        }
+    }
+}
+
+impl LogFields for SuccessfulDelivery {
+    fn log_context(&self) -> LogContext {
+        LogContext::new()
+            .with_bot(&self.bot_name)
+            .with_queue(&self.queue_name)
+    }
+}
+
+impl LogFields for FailedDelivery {
+    fn log_context(&self) -> LogContext {
+        LogContext::new()
+            .with_bot(&self.bot_name)
+            .with_queue(&self.queue_name)
+    }
+}
+
+// ============================================================================
+// Macros
+// ============================================================================
+
+/// Emit a tracing event at `$level` with the standard fields of `$ctx`.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __log_with_context {
+    ($level:expr, $ctx:expr, $($rest:tt)+) => {{
+        let __log_context = $crate::logging::LogFields::log_context(&$ctx);
+        $crate::logging::__tracing::event!(
+            $level,
+            event_id = __log_context
+                .event_id
+                .as_ref()
+                .map($crate::logging::__tracing::field::display),
+            session_id = __log_context
+                .session_id
+                .as_ref()
+                .map($crate::logging::__tracing::field::display),
+            correlation_id = __log_context
+                .correlation_id
+                .as_ref()
+                .map($crate::logging::__tracing::field::display),
+            bot = __log_context.bot.as_deref(),
+            queue = __log_context.queue.as_deref(),
+            $($rest)+
+        )
+    }};
+}
+
+/// Log at `TRACE` with the standard fields of a [`LogFields`](crate::logging::LogFields) value.
+#[macro_export]
+macro_rules! event_trace {
+    ($ctx:expr, $($rest:tt)+) => {
+        $crate::__log_with_context!($crate::logging::__tracing::Level::TRACE, $ctx, $($rest)+)
+    };
+}
+
+/// Log at `DEBUG` with the standard fields of a [`LogFields`](crate::logging::LogFields) value.
+#[macro_export]
+macro_rules! event_debug {
+    ($ctx:expr, $($rest:tt)+) => {
+        $crate::__log_with_context!($crate::logging::__tracing::Level::DEBUG, $ctx, $($rest)+)
+    };
+}
+
+/// Log at `INFO` with the standard fields of a [`LogFields`](crate::logging::LogFields) value.
+#[macro_export]
+macro_rules! event_info {
+    ($ctx:expr, $($rest:tt)+) => {
+        $crate::__log_with_context!($crate::logging::__tracing::Level::INFO, $ctx, $($rest)+)
+    };
+}
+
+/// Log at `WARN` with the standard fields of a [`LogFields`](crate::logging::LogFields) value.
+#[macro_export]
+macro_rules! event_warn {
+    ($ctx:expr, $($rest:tt)+) => {
+        $crate::__log_with_context!($crate::logging::__tracing::Level::WARN, $ctx, $($rest)+)
+    };
+}
+
+/// Log at `ERROR` with the standard fields of a [`LogFields`](crate::logging::LogFields) value.
+#[macro_export]
+macro_rules! event_error {
+    ($ctx:expr, $($rest:tt)+) => {
+        $crate::__log_with_context!($crate::logging::__tracing::Level::ERROR, $ctx, $($rest)+)
+    };
+}
+
+#[cfg(test)]
+#[path = "logging_tests.rs"]
+mod tests;
@@ -0,0 +1,74 @@
+//! Tests for the standard logging field set.
+
+use super::*;
+use crate::{BotName, QueueName};
+
+fn event() -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        Some(SessionId::from_parts("owner", "repo", "pull_request", "1")),
+        serde_json::json!({}),
+        None,
+    )
+}
+
+/// A wrapped event supplies its identifiers but no routing target.
+#[test]
+fn test_wrapped_event_context() {
+    let event = event();
+    let context = event.log_context();
+
+    assert_eq!(context.event_id, Some(event.event_id));
+    assert_eq!(context.session_id, event.session_id);
+    assert_eq!(context.correlation_id, Some(event.correlation_id.clone()));
+    assert_eq!(context.bot, None);
+    assert_eq!(context.queue, None);
+}
+
+/// Routing targets can be layered onto an event's context.
+#[test]
+fn test_with_bot_and_queue() {
+    let event = event();
+    let context = event
+        .log_context()
+        .with_bot("merge-warden")
+        .with_queue("queue-keeper-merge-warden");
+
+    assert_eq!(context.event_id, Some(event.event_id));
+    assert_eq!(context.bot.as_deref(), Some("merge-warden"));
+    assert_eq!(context.queue.as_deref(), Some("queue-keeper-merge-warden"));
+}
+
+/// Delivery results supply the bot and queue they were sent to.
+#[test]
+fn test_failed_delivery_context() {
+    let failure = FailedDelivery {
+        bot_name: BotName::new("task-tactician").unwrap(),
+        queue_name: QueueName::new("queue-keeper-task-tactician").unwrap(),
+        error: "timeout".to_string(),
+        is_transient: true,
+    };
+    let context = failure.log_context();
+
+    assert_eq!(context.event_id, None);
+    assert_eq!(context.bot.as_deref(), Some("task-tactician"));
+    assert_eq!(
+        context.queue.as_deref(),
+        Some("queue-keeper-task-tactician")
+    );
+}
+
+/// The macros accept owned values, references and built contexts.
+#[test]
+fn test_macros_accept_any_log_fields() {
+    let event = event();
+
+    crate::event_trace!(event, "trace");
+    crate::event_debug!(&event, attempt = 1, "debug");
+    crate::event_info!(event.log_context().with_bot("bot"), "info {}", 1);
+    crate::event_warn!(LogContext::new().with_queue("queue"), error = %"boom", "warn");
+    crate::event_error!(event, "error");
+    let _entered = event.log_context().span().entered();
+}
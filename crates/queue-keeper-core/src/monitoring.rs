@@ -31,6 +31,7 @@
 //! metrics.record_queue_routing(Duration::from_millis(50), 3);
 //! ```
 
+use crate::timing::PipelineStage;
 use async_trait::async_trait;
 use std::time::Duration;
 
@@ -75,6 +76,18 @@ pub trait MetricsCollector: Send + Sync {
     /// - `webhook_validation_failures`: Incremented by 1
     fn record_webhook_validation_failure(&self);
 
+    /// Record the time one webhook request spent in a pipeline stage.
+    ///
+    /// # Parameters
+    ///
+    /// - `stage`: Pipeline stage that ran
+    /// - `duration`: Monotonic time spent in the stage
+    ///
+    /// # Metrics Updated
+    ///
+    /// - `webhook_stage_duration_seconds`: Histogram observation labelled by stage
+    fn record_webhook_stage(&self, stage: PipelineStage, duration: Duration);
+
     /// Record queue routing operation.
     ///
     /// # Parameters
@@ -217,6 +230,10 @@ impl MetricsCollector for NoOpMetricsCollector {
         // No-op
     }
 
+    fn record_webhook_stage(&self, _stage: PipelineStage, _duration: Duration) {
+        // No-op
+    }
+
     fn record_queue_routing(&self, _duration: Duration, _queue_count: usize) {
         // No-op
     }
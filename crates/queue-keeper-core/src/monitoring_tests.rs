@@ -32,6 +32,7 @@ fn test_noop_collector_webhook_metrics() {
     collector.record_webhook_request(Duration::from_millis(150), true);
     collector.record_webhook_request(Duration::from_millis(250), false);
     collector.record_webhook_validation_failure();
+    collector.record_webhook_stage(PipelineStage::Normalization, Duration::from_millis(3));
 }
 
 /// Test that NoOpMetricsCollector handles queue routing metrics.
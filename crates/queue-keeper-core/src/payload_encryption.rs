@@ -0,0 +1,355 @@
+//! # Payload Encryption at Rest
+//!
+//! Envelope encryption of webhook payload bodies before they are written to
+//! blob storage. Each payload is encrypted with AES-256-GCM under its own
+//! random data key; the data key is in turn encrypted ("wrapped") with a key
+//! encryption key read from a [`KeyVaultProvider`] secret. The blob metadata
+//! records everything needed to decrypt the body except the key encryption
+//! key itself ([`PayloadEncryption`]):
+//!
+//! ```json
+//! "encryption": {
+//!   "algorithm": "A256GCM",
+//!   "key_id": "queue-keeper-payload-key",
+//!   "wrapped_key": "<base64>",
+//!   "key_nonce": "<base64>",
+//!   "nonce": "<base64>"
+//! }
+//! ```
+//!
+//! The event ID is bound into both ciphertexts as associated data, so a body
+//! or wrapped key copied into another blob fails to decrypt. Bodies are
+//! compressed before they are encrypted. The blob checksum is that of the
+//! ciphertext, so the metadata does not reveal a hash of the plaintext, and
+//! [`open_stored`] verifies it before decrypting.
+//!
+//! `key_id` is the name of the secret that wrapped the data key, and reads
+//! load that secret, not the one currently configured for writes. To rotate
+//! the key, store a new secret and point the configuration at it; keep the
+//! old secret for as long as payloads encrypted under it are retained.
+//!
+//! The key secret must contain 32 random bytes encoded as standard base64,
+//! e.g. the output of `openssl rand -base64 32`.
+
+use crate::{
+    blob_storage::{compute_checksum, verify_checksum, BlobStorageError, StoredWebhook},
+    field_encryption::{FieldEncryptionKey, KEY_LEN},
+    key_vault::{KeyVaultError, KeyVaultProvider, SecretName},
+    EventId,
+};
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
+
+/// Algorithm identifier recorded in [`PayloadEncryption::algorithm`].
+pub const ALGORITHM: &str = "A256GCM";
+
+/// AES-GCM nonce length in bytes.
+const NONCE_LEN: usize = 12;
+
+/// How a stored payload body was encrypted (`BlobMetadata::encryption`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadEncryption {
+    /// Encryption algorithm of both the body and the wrapped data key.
+    pub algorithm: String,
+
+    /// Name of the Key Vault secret that wrapped the data key.
+    pub key_id: String,
+
+    /// Base64 data key encrypted with the key encryption key, including the
+    /// authentication tag.
+    pub wrapped_key: String,
+
+    /// Base64 nonce used to wrap the data key.
+    pub key_nonce: String,
+
+    /// Base64 nonce used to encrypt the body.
+    pub nonce: String,
+}
+
+/// Encrypts payload bodies with data keys wrapped by a Key Vault secret.
+///
+/// Adapters hold an encryptor when payload encryption is configured; see
+/// `FilesystemBlobStorage::with_encryption`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use queue_keeper_core::key_vault::{KeyVaultProvider, SecretName};
+/// use queue_keeper_core::payload_encryption::PayloadEncryptor;
+/// use std::sync::Arc;
+///
+/// # fn example(key_vault: Arc<dyn KeyVaultProvider>) -> Result<(), Box<dyn std::error::Error>> {
+/// let encryptor = PayloadEncryptor::new(key_vault, SecretName::new("queue-keeper-payload-key")?);
+/// # Ok(())
+/// # }
+/// ```
+pub struct PayloadEncryptor {
+    key_vault: Arc<dyn KeyVaultProvider>,
+    key_secret: SecretName,
+}
+
+impl PayloadEncryptor {
+    /// Create an encryptor wrapping data keys with the `key_secret` secret.
+    pub fn new(key_vault: Arc<dyn KeyVaultProvider>, key_secret: SecretName) -> Self {
+        Self {
+            key_vault,
+            key_secret,
+        }
+    }
+
+    /// Name of the secret wrapping the data keys of new payloads.
+    pub fn key_id(&self) -> &str {
+        self.key_secret.as_str()
+    }
+
+    /// Encrypt the payload body of a blob about to be written.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the body is already encrypted, the key cannot be
+    /// loaded or encryption fails. Key Vault outages are reported as
+    /// transient.
+    pub async fn encrypt(
+        &self,
+        mut stored: StoredWebhook,
+    ) -> Result<StoredWebhook, BlobStorageError> {
+        if stored.metadata.encryption.is_some() {
+            return Err(encryption_failed(format!(
+                "payload {} is already encrypted",
+                stored.metadata.event_id
+            )));
+        }
+        let key_encryption_key = self.load_key(&self.key_secret).await?;
+        let aad = aad(&stored.metadata.event_id);
+
+        let mut data_key = [0u8; KEY_LEN];
+        data_key.copy_from_slice(&Aes256Gcm::generate_key(&mut OsRng));
+        let data_key = FieldEncryptionKey::from_bytes(data_key);
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = data_key
+            .cipher()
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: &stored.payload.body,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| encryption_failed("failed to encrypt payload body"))?;
+
+        let key_nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let wrapped_key = key_encryption_key
+            .cipher()
+            .encrypt(
+                &key_nonce,
+                Payload {
+                    msg: data_key.as_bytes(),
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| encryption_failed("failed to wrap data key"))?;
+
+        stored.payload.body = Bytes::from(ciphertext);
+        stored.metadata.checksum_sha256 = compute_checksum(&stored.payload.body);
+        stored.metadata.encryption = Some(PayloadEncryption {
+            algorithm: ALGORITHM.to_string(),
+            key_id: self.key_id().to_string(),
+            wrapped_key: STANDARD.encode(wrapped_key),
+            key_nonce: STANDARD.encode(key_nonce),
+            nonce: STANDARD.encode(nonce),
+        });
+        Ok(stored)
+    }
+
+    /// Decrypt the payload body of a blob read from storage.
+    ///
+    /// The data key is unwrapped with the secret named by the blob's
+    /// `key_id`. `metadata.encryption` and `metadata.checksum_sha256` keep
+    /// reporting how the body was stored. Unencrypted blobs are returned
+    /// unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the key cannot be loaded or the body or data key
+    /// fail to authenticate (wrong key, tampering or a body moved from
+    /// another blob).
+    pub async fn decrypt(
+        &self,
+        mut stored: StoredWebhook,
+    ) -> Result<StoredWebhook, BlobStorageError> {
+        let Some(encryption) = &stored.metadata.encryption else {
+            return Ok(stored);
+        };
+        let event_id = stored.metadata.event_id;
+        let failed = |reason: &str| {
+            encryption_failed(format!(
+                "failed to decrypt payload {}: {}",
+                event_id, reason
+            ))
+        };
+        if encryption.algorithm != ALGORITHM {
+            return Err(failed(&format!(
+                "unsupported algorithm {}",
+                encryption.algorithm
+            )));
+        }
+        let key_secret = SecretName::new(&encryption.key_id)
+            .map_err(|_| failed(&format!("invalid key_id '{}'", encryption.key_id)))?;
+        let key_encryption_key = self.load_key(&key_secret).await?;
+        let aad = aad(&event_id);
+
+        let wrapped_key = STANDARD
+            .decode(&encryption.wrapped_key)
+            .map_err(|_| failed("wrapped_key is not base64"))?;
+        let data_key = zeroize::Zeroizing::new(
+            key_encryption_key
+                .cipher()
+                .decrypt(
+                    &decode_nonce(&encryption.key_nonce)
+                        .ok_or_else(|| failed("invalid key_nonce"))?,
+                    Payload {
+                        msg: &wrapped_key,
+                        aad: &aad,
+                    },
+                )
+                .map_err(|_| {
+                    failed("data key authentication failed (wrong key or tampered blob)")
+                })?,
+        );
+        let data_key: [u8; KEY_LEN] = data_key
+            .as_slice()
+            .try_into()
+            .map_err(|_| failed("data key has the wrong length"))?;
+
+        let body = FieldEncryptionKey::from_bytes(data_key)
+            .cipher()
+            .decrypt(
+                &decode_nonce(&encryption.nonce).ok_or_else(|| failed("invalid nonce"))?,
+                Payload {
+                    msg: &stored.payload.body,
+                    aad: &aad,
+                },
+            )
+            .map_err(|_| failed("body authentication failed (tampered blob)"))?;
+
+        stored.payload.body = Bytes::from(body);
+        Ok(stored)
+    }
+
+    async fn load_key(&self, name: &SecretName) -> Result<FieldEncryptionKey, BlobStorageError> {
+        let secret = self
+            .key_vault
+            .get_secret(name)
+            .await
+            .map_err(|e| key_vault_error(name, e))?;
+        FieldEncryptionKey::from_secret(&secret)
+            .map_err(|e| encryption_failed(format!("secret '{}': {}", name.as_str(), e)))
+    }
+}
+
+impl fmt::Debug for PayloadEncryptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PayloadEncryptor")
+            .field("key_secret", &self.key_secret.as_str())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Verify the checksum of a blob read from `path`, then decrypt and
+/// decompress its body.
+///
+/// The checksum of an encrypted blob covers the ciphertext and is verified
+/// before decryption; that of an unencrypted blob covers the decompressed
+/// body.
+///
+/// # Errors
+///
+/// Returns [`BlobStorageError::ChecksumMismatch`] when the checksum does not
+/// match, or the errors of [`decrypt_stored`] and [`StoredWebhook::decode`].
+pub async fn open_stored(
+    encryptor: Option<&PayloadEncryptor>,
+    stored: StoredWebhook,
+    path: &str,
+) -> Result<StoredWebhook, BlobStorageError> {
+    let check = |stored: &StoredWebhook| {
+        if verify_checksum(&stored.payload.body, &stored.metadata.checksum_sha256) {
+            Ok(())
+        } else {
+            Err(BlobStorageError::ChecksumMismatch {
+                path: path.to_string(),
+                expected: stored.metadata.checksum_sha256.clone(),
+                actual: compute_checksum(&stored.payload.body),
+            })
+        }
+    };
+    if stored.metadata.encryption.is_some() {
+        check(&stored)?;
+        decrypt_stored(encryptor, stored).await?.decode()
+    } else {
+        let stored = stored.decode()?;
+        check(&stored)?;
+        Ok(stored)
+    }
+}
+
+/// Decrypt `stored` when it is encrypted.
+///
+/// # Errors
+///
+/// Returns an error if the blob is encrypted but `encryptor` is `None`, or
+/// if decryption fails.
+pub async fn decrypt_stored(
+    encryptor: Option<&PayloadEncryptor>,
+    stored: StoredWebhook,
+) -> Result<StoredWebhook, BlobStorageError> {
+    match (encryptor, &stored.metadata.encryption) {
+        (_, None) => Ok(stored),
+        (Some(encryptor), Some(_)) => encryptor.decrypt(stored).await,
+        (None, Some(encryption)) => Err(encryption_failed(format!(
+            "payload {} is encrypted with key '{}' but payload encryption is not configured",
+            stored.metadata.event_id, encryption.key_id
+        ))),
+    }
+}
+
+// ============================================================================
+// Private Helpers
+// ============================================================================
+
+fn aad(event_id: &EventId) -> Vec<u8> {
+    event_id.to_string().into_bytes()
+}
+
+fn decode_nonce(encoded: &str) -> Option<Nonce<<Aes256Gcm as AeadCore>::NonceSize>> {
+    let nonce: [u8; NONCE_LEN] = STANDARD.decode(encoded).ok()?.try_into().ok()?;
+    Some(Nonce::from(nonce))
+}
+
+fn encryption_failed(message: impl Into<String>) -> BlobStorageError {
+    BlobStorageError::EncryptionFailed {
+        message: message.into(),
+    }
+}
+
+fn key_vault_error(name: &SecretName, error: KeyVaultError) -> BlobStorageError {
+    let message = format!(
+        "failed to load payload encryption key '{}': {}",
+        name.as_str(),
+        error
+    );
+    if error.is_transient() {
+        BlobStorageError::ConnectionFailed { message }
+    } else {
+        encryption_failed(message)
+    }
+}
+
+#[cfg(test)]
+#[path = "payload_encryption_tests.rs"]
+mod tests;
@@ -0,0 +1,161 @@
+//! Tests for payload encryption at rest.
+
+use super::*;
+use crate::adapters::InMemoryKeyVaultProvider;
+use crate::blob_storage::{
+    compute_checksum, BlobMetadata, ContentEncoding, PayloadMetadata, WebhookPayload,
+};
+use crate::key_vault::SecretValue;
+use crate::Timestamp;
+use std::collections::HashMap;
+
+const KEY_SECRET: &str = "queue-keeper-test-payload-key";
+const ROTATED_KEY_SECRET: &str = "queue-keeper-test-payload-key-2";
+
+fn key_vault() -> Arc<InMemoryKeyVaultProvider> {
+    let key_vault = InMemoryKeyVaultProvider::new();
+    for (name, byte) in [(KEY_SECRET, 5u8), (ROTATED_KEY_SECRET, 6u8)] {
+        key_vault.add_secret(
+            SecretName::new(name).unwrap(),
+            SecretValue::from_string(STANDARD.encode([byte; KEY_LEN])),
+        );
+    }
+    Arc::new(key_vault)
+}
+
+fn encryptor(key_vault: &Arc<InMemoryKeyVaultProvider>, key_secret: &str) -> PayloadEncryptor {
+    PayloadEncryptor::new(key_vault.clone(), SecretName::new(key_secret).unwrap())
+}
+
+fn stored_webhook() -> StoredWebhook {
+    let event_id = EventId::new();
+    let body = Bytes::from(r#"{"action":"opened","number":42}"#);
+    let metadata = PayloadMetadata {
+        event_id,
+        event_type: "pull_request".to_string(),
+        repository: None,
+        signature_valid: true,
+        received_at: Timestamp::now(),
+        delivery_id: None,
+    };
+    StoredWebhook {
+        metadata: BlobMetadata {
+            event_id,
+            blob_path: event_id.to_blob_path(),
+            size_bytes: 0,
+            content_type: "application/json".to_string(),
+            created_at: Timestamp::now(),
+            checksum_sha256: compute_checksum(&body),
+            format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+            content_encoding: ContentEncoding::Identity,
+            encryption: None,
+            metadata: metadata.clone(),
+        },
+        payload: WebhookPayload {
+            body,
+            headers: HashMap::new(),
+            metadata,
+        },
+    }
+}
+
+/// The body is encrypted under a fresh data key and decrypts back; the key
+/// id and nonces are recorded in the blob metadata.
+#[tokio::test]
+async fn test_encrypt_then_decrypt_round_trips() {
+    let key_vault = key_vault();
+    let encryptor = encryptor(&key_vault, KEY_SECRET);
+    let original = stored_webhook();
+
+    let encrypted = encryptor.encrypt(original.clone()).await.unwrap();
+    let encryption = encrypted.metadata.encryption.clone().unwrap();
+    assert_eq!(encryption.algorithm, ALGORITHM);
+    assert_eq!(encryption.key_id, KEY_SECRET);
+    assert_ne!(encrypted.payload.body, original.payload.body);
+    assert_eq!(
+        encrypted.metadata.checksum_sha256,
+        compute_checksum(&encrypted.payload.body)
+    );
+    assert!(encryptor.encrypt(encrypted.clone()).await.is_err());
+
+    // Every payload has its own data key and nonces
+    let again = encryptor.encrypt(original.clone()).await.unwrap();
+    assert_ne!(
+        again.metadata.encryption.unwrap().wrapped_key,
+        encryption.wrapped_key
+    );
+
+    let decrypted = encryptor.decrypt(encrypted).await.unwrap();
+    assert_eq!(decrypted.payload.body, original.payload.body);
+    assert_eq!(decrypted.metadata.encryption, Some(encryption));
+}
+
+/// Payloads written before a key rotation are read with the key they were
+/// wrapped with.
+#[tokio::test]
+async fn test_decrypt_uses_recorded_key_after_rotation() {
+    let key_vault = key_vault();
+    let original = stored_webhook();
+    let encrypted = encryptor(&key_vault, KEY_SECRET)
+        .encrypt(original.clone())
+        .await
+        .unwrap();
+
+    let rotated = encryptor(&key_vault, ROTATED_KEY_SECRET);
+    let decrypted = rotated.decrypt(encrypted).await.unwrap();
+    assert_eq!(decrypted.payload.body, original.payload.body);
+}
+
+/// Tampered bodies, bodies moved to another blob and missing keys fail to
+/// decrypt.
+#[tokio::test]
+async fn test_decrypt_rejects_tampering_and_missing_keys() {
+    let key_vault = key_vault();
+    let encryptor = encryptor(&key_vault, KEY_SECRET);
+    let encrypted = encryptor.encrypt(stored_webhook()).await.unwrap();
+
+    let mut tampered = encrypted.clone();
+    let mut body = tampered.payload.body.to_vec();
+    body[0] ^= 1;
+    tampered.payload.body = Bytes::from(body);
+    assert!(matches!(
+        encryptor.decrypt(tampered).await,
+        Err(BlobStorageError::EncryptionFailed { .. })
+    ));
+
+    let mut moved = encrypted.clone();
+    moved.metadata.event_id = EventId::new();
+    assert!(matches!(
+        encryptor.decrypt(moved).await,
+        Err(BlobStorageError::EncryptionFailed { .. })
+    ));
+
+    let mut unknown_key = encrypted.clone();
+    unknown_key.metadata.encryption.as_mut().unwrap().key_id =
+        "queue-keeper-test-deleted-key".to_string();
+    let error = encryptor.decrypt(unknown_key).await.unwrap_err();
+    assert!(!error.is_transient());
+
+    assert!(matches!(
+        decrypt_stored(None, encrypted).await,
+        Err(BlobStorageError::EncryptionFailed { .. })
+    ));
+}
+
+/// Unencrypted blobs are read as they are, with or without an encryptor.
+#[tokio::test]
+async fn test_decrypt_stored_passes_unencrypted_blobs() {
+    let key_vault = key_vault();
+    let original = stored_webhook();
+
+    assert_eq!(
+        decrypt_stored(None, original.clone()).await.unwrap(),
+        original
+    );
+    assert_eq!(
+        decrypt_stored(Some(&encryptor(&key_vault, KEY_SECRET)), original.clone())
+            .await
+            .unwrap(),
+        original
+    );
+}
@@ -0,0 +1,166 @@
+//! # Payload Projection
+//!
+//! Trims the payload of an event before it is delivered to a bot queue, so
+//! bots that only read a few fields do not receive the whole GitHub payload.
+//! Projection is configured per bot subscription
+//! ([`BotSubscription::projection`]) and combines:
+//!
+//! - `fields`: JSON pointers (RFC 6901) of the fields to keep. Everything
+//!   else is dropped; kept fields stay at their original location. Pointers
+//!   address object members: a pointer through an array keeps the whole
+//!   array.
+//! - `select`: a flat object built from named JSON pointers, e.g.
+//!   `number: /pull_request/number`. Replaces the payload; cannot be
+//!   combined with `fields`.
+//! - `slim`: drops the hypermedia links GitHub adds to every object (keys
+//!   ending in `_url`, such as `html_url`, `avatar_url` and the API URL
+//!   templates, plus `_links`) and `gravatar_id`. Applied after `fields` or
+//!   `select`.
+//!
+//! Fields missing from a payload are left out. Only the payload is
+//! projected: the envelope, including the event type, action and session,
+//! is delivered unchanged. Projection runs after the bot's transform and
+//! before field encryption, so encryption pointers address the projected
+//! payload.
+//!
+//! [`BotSubscription::projection`]: crate::bot_config::BotSubscription::projection
+
+use crate::webhook::WrappedEvent;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+
+/// Payload projection settings for a single bot subscription.
+///
+/// See specs/interfaces/bot-configuration.md
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadProjectionConfig {
+    /// Drop hypermedia links and avatar IDs from the payload.
+    #[serde(default)]
+    pub slim: bool,
+
+    /// JSON pointers of the payload fields to keep; empty keeps every field.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+
+    /// Output field names and the JSON pointers they are read from; replaces
+    /// the payload with a flat object when not empty.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub select: BTreeMap<String, String>,
+}
+
+impl PayloadProjectionConfig {
+    /// Check the pointers and that the settings change the payload.
+    ///
+    /// # Errors
+    /// Returns a description of the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.slim && self.fields.is_empty() && self.select.is_empty() {
+            return Err("at least one of slim, fields or select must be set".to_string());
+        }
+        if !self.fields.is_empty() && !self.select.is_empty() {
+            return Err("fields and select cannot be combined".to_string());
+        }
+        for pointer in self.fields.iter().chain(self.select.values()) {
+            if !pointer.starts_with('/') {
+                return Err(format!(
+                    "field '{}' is not a JSON pointer (must start with '/')",
+                    pointer
+                ));
+            }
+        }
+        if self.select.keys().any(|name| name.is_empty()) {
+            return Err("select field names must not be empty".to_string());
+        }
+        Ok(())
+    }
+
+    /// Project `payload`.
+    pub fn apply(&self, payload: &Value) -> Value {
+        let mut projected = if !self.select.is_empty() {
+            let selected: Map<String, Value> = self
+                .select
+                .iter()
+                .filter_map(|(name, pointer)| {
+                    payload
+                        .pointer(pointer)
+                        .map(|value| (name.clone(), value.clone()))
+                })
+                .collect();
+            Value::Object(selected)
+        } else if !self.fields.is_empty() {
+            let mut kept = Map::new();
+            for pointer in &self.fields {
+                keep_field(payload, &mut kept, &pointer_tokens(pointer));
+            }
+            Value::Object(kept)
+        } else {
+            payload.clone()
+        };
+
+        if self.slim {
+            slim(&mut projected);
+        }
+        projected
+    }
+
+    /// A copy of `event` with its payload projected.
+    pub fn apply_to_event(&self, event: &WrappedEvent) -> WrappedEvent {
+        WrappedEvent {
+            payload: self.apply(&event.payload),
+            ..event.clone()
+        }
+    }
+}
+
+/// Copy the field at the path `tokens` of `source` to the same location in
+/// `target`, creating the objects on its path.
+fn keep_field(source: &Value, target: &mut Map<String, Value>, tokens: &[String]) {
+    let Some((key, rest)) = tokens.split_first() else {
+        return;
+    };
+    let Some(value) = source.as_object().and_then(|fields| fields.get(key)) else {
+        return;
+    };
+    if rest.is_empty() || !value.is_object() {
+        // Arrays and scalars on the path are kept whole
+        target.insert(key.clone(), value.clone());
+        return;
+    }
+    if let Value::Object(nested) = target
+        .entry(key.clone())
+        .or_insert_with(|| Value::Object(Map::new()))
+    {
+        keep_field(value, nested, rest);
+    }
+}
+
+/// Unescaped reference tokens of a JSON pointer.
+fn pointer_tokens(pointer: &str) -> Vec<String> {
+    pointer
+        .split('/')
+        .skip(1)
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect()
+}
+
+/// Whether `key` is dropped by slim projection.
+fn is_slim_key(key: &str) -> bool {
+    key.ends_with("_url") || key == "_links" || key == "gravatar_id"
+}
+
+/// Drop slim keys from every object in `value`.
+fn slim(value: &mut Value) {
+    match value {
+        Value::Object(fields) => {
+            fields.retain(|key, _| !is_slim_key(key));
+            fields.values_mut().for_each(slim);
+        }
+        Value::Array(items) => items.iter_mut().for_each(slim),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+#[path = "payload_projection_tests.rs"]
+mod tests;
@@ -0,0 +1,156 @@
+//! Tests for payload projection.
+
+use super::*;
+use serde_json::json;
+
+fn payload() -> Value {
+    json!({
+        "action": "opened",
+        "number": 42,
+        "pull_request": {
+            "title": "Fix it",
+            "html_url": "https://github.com/owner/repo/pull/42",
+            "head": { "sha": "abc", "repo": { "full_name": "owner/repo" } },
+            "labels": [{ "name": "bug", "url": "https://api.github.com/labels/bug" }],
+            "_links": { "self": { "href": "https://api.github.com/pulls/42" } }
+        },
+        "sender": {
+            "login": "octocat",
+            "avatar_url": "https://avatars.githubusercontent.com/u/1",
+            "gravatar_id": "",
+            "followers_url": "https://api.github.com/users/octocat/followers"
+        }
+    })
+}
+
+fn config(slim: bool, fields: &[&str], select: &[(&str, &str)]) -> PayloadProjectionConfig {
+    PayloadProjectionConfig {
+        slim,
+        fields: fields.iter().map(|field| field.to_string()).collect(),
+        select: select
+            .iter()
+            .map(|(name, pointer)| (name.to_string(), pointer.to_string()))
+            .collect(),
+    }
+}
+
+/// Fields keep their location; missing ones are left out and arrays are kept
+/// whole.
+#[test]
+fn test_fields_keep_listed_fields() {
+    let projection = config(
+        false,
+        &[
+            "/action",
+            "/pull_request/head/sha",
+            "/pull_request/labels/0/name",
+            "/pull_request/merged",
+        ],
+        &[],
+    );
+
+    assert_eq!(
+        projection.apply(&payload()),
+        json!({
+            "action": "opened",
+            "pull_request": {
+                "head": { "sha": "abc" },
+                "labels": [{ "name": "bug", "url": "https://api.github.com/labels/bug" }]
+            }
+        })
+    );
+}
+
+/// A field and one of its members may both be listed, in either order.
+#[test]
+fn test_fields_overlapping_pointers() {
+    let whole_sender = json!({ "sender": payload()["sender"].clone() });
+    for fields in [["/sender", "/sender/login"], ["/sender/login", "/sender"]] {
+        let projection = config(false, &fields, &[]);
+        assert_eq!(projection.apply(&payload()), whole_sender, "{:?}", fields);
+    }
+}
+
+/// Select builds a flat object from the fields found.
+#[test]
+fn test_select_builds_flat_object() {
+    let projection = config(
+        false,
+        &[],
+        &[
+            ("number", "/number"),
+            ("repo", "/pull_request/head/repo/full_name"),
+            ("missing", "/issue/number"),
+        ],
+    );
+
+    assert_eq!(
+        projection.apply(&payload()),
+        json!({ "number": 42, "repo": "owner/repo" })
+    );
+}
+
+/// Slim mode drops links and avatar fields at every depth, including after
+/// a field allowlist.
+#[test]
+fn test_slim_drops_links() {
+    let slimmed = config(true, &[], &[]).apply(&payload());
+
+    assert_eq!(slimmed["pull_request"]["title"], "Fix it");
+    assert!(slimmed["pull_request"].get("html_url").is_none());
+    assert!(slimmed["pull_request"].get("_links").is_none());
+    assert_eq!(
+        slimmed["pull_request"]["labels"][0]["url"],
+        json!("https://api.github.com/labels/bug")
+    );
+    assert_eq!(slimmed["sender"], json!({ "login": "octocat" }));
+
+    let sender = config(true, &["/sender"], &[]).apply(&payload());
+    assert_eq!(sender, json!({ "sender": { "login": "octocat" } }));
+}
+
+/// Only the payload of an event is projected.
+#[test]
+fn test_apply_to_event_keeps_envelope() {
+    let event = WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        None,
+        payload(),
+        None,
+    );
+
+    let projected = config(false, &["/number"], &[]).apply_to_event(&event);
+
+    assert_eq!(projected.event_id, event.event_id);
+    assert_eq!(projected.action.as_deref(), Some("opened"));
+    assert_eq!(projected.payload, json!({ "number": 42 }));
+}
+
+/// Settings must change the payload and use valid pointers.
+#[test]
+fn test_validate() {
+    assert!(config(true, &[], &[]).validate().is_ok());
+    assert!(config(false, &["/action"], &[]).validate().is_ok());
+    assert!(config(true, &[], &[("n", "/number")]).validate().is_ok());
+
+    for invalid in [
+        config(false, &[], &[]),
+        config(false, &["/action"], &[("n", "/number")]),
+        config(false, &["action"], &[]),
+        config(false, &[], &[("n", "number")]),
+        config(false, &[], &[("", "/number")]),
+    ] {
+        assert!(invalid.validate().is_err(), "{:?}", invalid);
+    }
+}
+
+/// Configuration deserializes with every setting optional.
+#[test]
+fn test_deserialize_yaml() {
+    let projection: PayloadProjectionConfig =
+        serde_yaml::from_str("slim: true\nselect:\n  number: /number\n").unwrap();
+
+    assert_eq!(projection, config(true, &[], &[("number", "/number")]));
+}
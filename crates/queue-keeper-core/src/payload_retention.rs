@@ -0,0 +1,492 @@
+//! Retention and lifecycle management of stored webhook payloads.
+//!
+//! Without retention, stored payloads grow without bound. Retention rules
+//! give each event type a time to live (TTL); a payload whose TTL has passed
+//! since it was received is expired. The [`PayloadRetentionSweeper`] runs in
+//! the background and handles expired payloads according to the configured
+//! [`RetentionAction`]:
+//!
+//! - `delete` — the payload is removed from blob storage.
+//! - `archive` — the payload is written gzip-compressed to the archive
+//!   directory (cold storage, for example a mounted archive-tier volume) and
+//!   then removed from blob storage.
+//!
+//! Event types matching no rule use `default_ttl`; without a default they
+//! are kept forever. Every sweep is reported with the number of payloads and
+//! bytes it reclaimed, and the running totals are kept for the admin API.
+
+use crate::blob_storage::{
+    BlobMetadata, BlobStorage, BlobStorageError, DateRange, PayloadFilter, StoredWebhook,
+};
+use crate::bot_config::EventTypePattern;
+use crate::units::{human_duration, HumanDuration};
+use crate::Timestamp;
+use chrono::{DateTime, Utc};
+use flate2::{write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tracing::{error, info, warn};
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// What happens to an expired payload.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetentionAction {
+    /// Remove the payload from blob storage.
+    #[default]
+    Delete,
+
+    /// Write the payload compressed to the archive directory, then remove it
+    /// from blob storage.
+    Archive,
+}
+
+impl RetentionAction {
+    /// Get string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Delete => "delete",
+            Self::Archive => "archive",
+        }
+    }
+}
+
+/// Time to live of the payloads of some event types.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadRetentionRule {
+    /// Event type patterns, using the bot subscription syntax (`status`,
+    /// `check_*`, `!check_suite`).
+    pub events: Vec<String>,
+
+    /// Time after which matching payloads expire.
+    #[serde(with = "human_duration")]
+    pub ttl: Duration,
+}
+
+/// Payload retention settings (the `payload_retention` configuration
+/// section).
+///
+/// The first rule matching an event type applies; event types matching no
+/// rule use `default_ttl`, or are kept forever without one.
+///
+/// ```yaml
+/// payload_retention:
+///   enabled: true
+///   default_ttl: 90d
+///   rules:
+///     - events: ["status", "check_*"]
+///       ttl: 7d
+///   action: archive
+///   archive_directory: /mnt/archive/payloads
+///   sweep_interval: 1h
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PayloadRetentionConfig {
+    /// Whether expired payloads are swept. Off by default.
+    pub enabled: bool,
+
+    /// Time to live of event types matching no rule.
+    pub default_ttl: Option<HumanDuration>,
+
+    /// Per-event-type time to live.
+    pub rules: Vec<PayloadRetentionRule>,
+
+    /// What happens to expired payloads.
+    pub action: RetentionAction,
+
+    /// Directory archived payloads are written to; required by `archive`.
+    pub archive_directory: Option<PathBuf>,
+
+    /// Time between two sweeps.
+    #[serde(with = "human_duration")]
+    pub sweep_interval: Duration,
+}
+
+impl Default for PayloadRetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            default_ttl: None,
+            rules: Vec::new(),
+            action: RetentionAction::Delete,
+            archive_directory: None,
+            sweep_interval: Duration::from_secs(3600),
+        }
+    }
+}
+
+impl PayloadRetentionConfig {
+    /// Validate the settings.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting or rule.
+    pub fn validate(&self) -> Result<(), String> {
+        let policy = PayloadRetentionPolicy::from_config(self)?;
+        if self.enabled && policy.shortest_ttl().is_none() {
+            return Err(
+                "payload_retention is enabled but neither default_ttl nor rules are set"
+                    .to_string(),
+            );
+        }
+        if self.sweep_interval.is_zero() {
+            return Err("payload_retention.sweep_interval must not be zero".to_string());
+        }
+        if self.action == RetentionAction::Archive
+            && self
+                .archive_directory
+                .as_ref()
+                .is_none_or(|d| d.as_os_str().is_empty())
+        {
+            return Err(
+                "payload_retention.archive_directory is required by the archive action".to_string(),
+            );
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Policy
+// ============================================================================
+
+/// Retention rules resolved to the time to live of each event type.
+#[derive(Debug, Clone, Default)]
+pub struct PayloadRetentionPolicy {
+    rules: Vec<(Vec<EventTypePattern>, Duration)>,
+    default_ttl: Option<Duration>,
+}
+
+impl PayloadRetentionPolicy {
+    /// Build the policy from its configuration.
+    ///
+    /// # Errors
+    /// Returns a description of the first rule without events, with an
+    /// invalid event pattern or with a zero TTL.
+    pub fn from_config(config: &PayloadRetentionConfig) -> Result<Self, String> {
+        let mut rules = Vec::with_capacity(config.rules.len());
+        for (index, rule) in config.rules.iter().enumerate() {
+            let invalid =
+                |message: String| format!("payload_retention.rules[{}]: {}", index, message);
+            if rule.events.is_empty() {
+                return Err(invalid("events must list at least one pattern".to_string()));
+            }
+            if rule.ttl.is_zero() {
+                return Err(invalid("ttl must not be zero".to_string()));
+            }
+            let patterns = rule
+                .events
+                .iter()
+                .map(|pattern| EventTypePattern::from_str(pattern).map_err(|e| e.to_string()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(invalid)?;
+            rules.push((patterns, rule.ttl));
+        }
+
+        let default_ttl = config.default_ttl.map(Duration::from);
+        if default_ttl.is_some_and(|ttl| ttl.is_zero()) {
+            return Err("payload_retention.default_ttl must not be zero".to_string());
+        }
+        Ok(Self { rules, default_ttl })
+    }
+
+    /// Time to live of payloads of `event_type`; `None` keeps them forever.
+    pub fn ttl_for(&self, event_type: &str) -> Option<Duration> {
+        self.rules
+            .iter()
+            .find(|(patterns, _)| rule_matches(patterns, event_type))
+            .map(|(_, ttl)| *ttl)
+            .or(self.default_ttl)
+    }
+
+    /// Whether a payload of `event_type` received at `received_at` has
+    /// expired at `now`.
+    pub fn is_expired(&self, event_type: &str, received_at: Timestamp, now: Timestamp) -> bool {
+        self.ttl_for(event_type)
+            .is_some_and(|ttl| received_at < now.subtract_duration(ttl))
+    }
+
+    /// Shortest time to live of any event type; payloads younger than this
+    /// never expire.
+    pub fn shortest_ttl(&self) -> Option<Duration> {
+        self.rules
+            .iter()
+            .map(|(_, ttl)| *ttl)
+            .chain(self.default_ttl)
+            .min()
+    }
+}
+
+/// Whether `event_type` matches `patterns` and none of their exclusions.
+fn rule_matches(patterns: &[EventTypePattern], event_type: &str) -> bool {
+    let excluded = patterns.iter().any(
+        |pattern| matches!(pattern, EventTypePattern::Exclude(excluded) if excluded == event_type),
+    );
+    !excluded && patterns.iter().any(|pattern| pattern.matches(event_type))
+}
+
+// ============================================================================
+// Sweeper
+// ============================================================================
+
+/// Outcome of one retention sweep.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RetentionSweepReport {
+    /// When the sweep started.
+    pub started_at: Option<Timestamp>,
+
+    /// Expired payloads removed without archiving.
+    pub deleted_payloads: u64,
+
+    /// Expired payloads archived and removed.
+    pub archived_payloads: u64,
+
+    /// Stored bytes freed in blob storage.
+    pub reclaimed_bytes: u64,
+
+    /// Compressed bytes written to the archive directory.
+    pub archived_bytes: u64,
+
+    /// Expired payloads that could not be archived or removed; they are
+    /// retried by the next sweep.
+    pub failed_payloads: u64,
+}
+
+impl RetentionSweepReport {
+    /// Payloads removed from blob storage.
+    pub fn reclaimed_payloads(&self) -> u64 {
+        self.deleted_payloads + self.archived_payloads
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.deleted_payloads += other.deleted_payloads;
+        self.archived_payloads += other.archived_payloads;
+        self.reclaimed_bytes += other.reclaimed_bytes;
+        self.archived_bytes += other.archived_bytes;
+        self.failed_payloads += other.failed_payloads;
+    }
+}
+
+/// Retention state reported by the admin API.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PayloadRetentionStatus {
+    /// What happens to expired payloads.
+    pub action: RetentionAction,
+
+    /// Sweeps completed since startup.
+    pub sweeps: u64,
+
+    /// Sweeps that failed to list the stored payloads.
+    pub failed_sweeps: u64,
+
+    /// Most recent completed sweep.
+    pub last_sweep: Option<RetentionSweepReport>,
+
+    /// Totals of all sweeps since startup (`started_at` is unset).
+    pub totals: RetentionSweepReport,
+}
+
+/// Periodically archives or deletes the stored payloads whose TTL passed.
+pub struct PayloadRetentionSweeper {
+    storage: Arc<dyn BlobStorage>,
+    policy: PayloadRetentionPolicy,
+    action: RetentionAction,
+    archive_directory: Option<PathBuf>,
+    sweep_interval: Duration,
+    status: Mutex<PayloadRetentionStatus>,
+}
+
+impl PayloadRetentionSweeper {
+    /// Sweep the expired payloads of `storage` as configured in `config`.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid setting.
+    pub fn from_config(
+        config: &PayloadRetentionConfig,
+        storage: Arc<dyn BlobStorage>,
+    ) -> Result<Self, String> {
+        config.validate()?;
+        Ok(Self {
+            storage,
+            policy: PayloadRetentionPolicy::from_config(config)?,
+            action: config.action,
+            archive_directory: config.archive_directory.clone(),
+            sweep_interval: config.sweep_interval,
+            status: Mutex::new(PayloadRetentionStatus {
+                action: config.action,
+                ..Default::default()
+            }),
+        })
+    }
+
+    /// Retention rules applied by the sweeper.
+    pub fn policy(&self) -> &PayloadRetentionPolicy {
+        &self.policy
+    }
+
+    /// Sweep counts and reclaimed totals since startup.
+    pub fn status(&self) -> PayloadRetentionStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Archive or delete every payload expired at `now`.
+    ///
+    /// A payload is removed from blob storage only after it was archived.
+    /// Payloads that fail are counted in
+    /// [`RetentionSweepReport::failed_payloads`] and left for the next sweep.
+    ///
+    /// # Errors
+    /// Returns the storage error when the stored payloads cannot be listed.
+    pub async fn sweep(&self, now: Timestamp) -> Result<RetentionSweepReport, BlobStorageError> {
+        let mut report = RetentionSweepReport {
+            started_at: Some(now),
+            ..Default::default()
+        };
+
+        if let Some(shortest_ttl) = self.policy.shortest_ttl() {
+            // Nothing received after the shortest TTL can have expired.
+            let filter = PayloadFilter {
+                date_range: Some(DateRange {
+                    start: Timestamp::from_datetime(DateTime::<Utc>::UNIX_EPOCH),
+                    end: now.subtract_duration(shortest_ttl),
+                }),
+                ..Default::default()
+            };
+            let candidates = match self.storage.list_payloads(&filter).await {
+                Ok(candidates) => candidates,
+                Err(e) => {
+                    self.status.lock().unwrap().failed_sweeps += 1;
+                    return Err(e);
+                }
+            };
+
+            for candidate in candidates {
+                let payload = &candidate.metadata;
+                if !self
+                    .policy
+                    .is_expired(&payload.event_type, payload.received_at, now)
+                {
+                    continue;
+                }
+                if let Err(e) = self.reclaim(&candidate, &mut report).await {
+                    warn!(
+                        event_id = %candidate.event_id,
+                        action = self.action.as_str(),
+                        error = %e,
+                        "Failed to reclaim expired payload"
+                    );
+                    report.failed_payloads += 1;
+                }
+            }
+        }
+
+        let mut status = self.status.lock().unwrap();
+        status.sweeps += 1;
+        status.totals.add(&report);
+        status.last_sweep = Some(report.clone());
+        Ok(report)
+    }
+
+    /// Run [`sweep`](Self::sweep) every `sweep_interval`, forever, handing
+    /// each completed sweep to `on_sweep`.
+    pub async fn run(&self, mut on_sweep: impl FnMut(&RetentionSweepReport)) {
+        let mut ticker = tokio::time::interval(self.sweep_interval);
+        ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            ticker.tick().await;
+            match self.sweep(Timestamp::now()).await {
+                Ok(report) => {
+                    if report.reclaimed_payloads() > 0 || report.failed_payloads > 0 {
+                        info!(
+                            deleted_payloads = report.deleted_payloads,
+                            archived_payloads = report.archived_payloads,
+                            reclaimed_bytes = report.reclaimed_bytes,
+                            failed_payloads = report.failed_payloads,
+                            "Payload retention sweep complete"
+                        );
+                    }
+                    on_sweep(&report);
+                }
+                Err(e) => error!(error = %e, "Payload retention sweep failed"),
+            }
+        }
+    }
+
+    /// Archive (if configured) and delete one expired payload.
+    async fn reclaim(
+        &self,
+        candidate: &BlobMetadata,
+        report: &mut RetentionSweepReport,
+    ) -> Result<(), BlobStorageError> {
+        match (self.action, &self.archive_directory) {
+            (RetentionAction::Archive, Some(directory)) => {
+                let Some(stored) = self.storage.get_payload(&candidate.event_id).await? else {
+                    // Removed since it was listed
+                    return Ok(());
+                };
+                let archived_bytes = archive_payload(directory, &stored).await?;
+                self.storage.delete_payload(&candidate.event_id).await?;
+                report.archived_payloads += 1;
+                report.archived_bytes += archived_bytes;
+            }
+            _ => {
+                self.storage.delete_payload(&candidate.event_id).await?;
+                report.deleted_payloads += 1;
+            }
+        }
+        report.reclaimed_bytes += candidate.size_bytes;
+        Ok(())
+    }
+}
+
+/// Write `stored` gzip-compressed below `directory` at its blob path and
+/// return the compressed size.
+async fn archive_payload(
+    directory: &Path,
+    stored: &StoredWebhook,
+) -> Result<u64, BlobStorageError> {
+    let internal = |message: String| BlobStorageError::InternalError { message };
+
+    let json = serde_json::to_vec(stored).map_err(|e| BlobStorageError::SerializationFailed {
+        message: format!("Failed to serialize payload for archiving: {}", e),
+    })?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder
+        .write_all(&json)
+        .map_err(|e| internal(format!("Failed to compress payload: {}", e)))?;
+    let compressed = encoder
+        .finish()
+        .map_err(|e| internal(format!("Failed to compress payload: {}", e)))?;
+
+    let path = directory.join(format!("{}.gz", stored.metadata.blob_path));
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .map_err(|e| internal(format!("Failed to create archive directory: {}", e)))?;
+    }
+
+    // Written under a temporary name first, so an interrupted sweep never
+    // leaves a truncated archive behind.
+    let temp_path = path.with_extension("gz.tmp");
+    tokio::fs::write(&temp_path, &compressed)
+        .await
+        .map_err(|e| internal(format!("Failed to write archived payload: {}", e)))?;
+    tokio::fs::rename(&temp_path, &path)
+        .await
+        .map_err(|e| internal(format!("Failed to write archived payload: {}", e)))?;
+
+    Ok(compressed.len() as u64)
+}
+
+#[cfg(test)]
+#[path = "payload_retention_tests.rs"]
+mod tests;
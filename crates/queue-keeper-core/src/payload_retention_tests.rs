@@ -0,0 +1,267 @@
+//! Tests for payload retention.
+
+use super::*;
+use crate::blob_storage::{PayloadMetadata, StorageHealthStatus, StorageMetrics, WebhookPayload};
+use crate::EventId;
+use async_trait::async_trait;
+use bytes::Bytes;
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::io::Read;
+
+const DAY: Duration = Duration::from_secs(86_400);
+
+/// In-memory storage listing every object, whatever the filter, so the
+/// sweeper's own expiry check is exercised. Deletes fail while
+/// `deny_deletes` is set.
+#[derive(Default)]
+struct MemoryStorage {
+    objects: Mutex<HashMap<EventId, StoredWebhook>>,
+    deny_deletes: bool,
+}
+
+impl MemoryStorage {
+    fn insert(&self, event_type: &str, age: Duration) -> EventId {
+        let event_id = EventId::new();
+        let body = Bytes::from_static(br#"{"action":"completed"}"#);
+        let payload = WebhookPayload {
+            body: body.clone(),
+            headers: HashMap::new(),
+            metadata: PayloadMetadata {
+                event_id,
+                event_type: event_type.to_string(),
+                repository: None,
+                signature_valid: true,
+                received_at: Timestamp::now().subtract_duration(age),
+                delivery_id: None,
+            },
+        };
+        let metadata = BlobMetadata {
+            event_id,
+            blob_path: event_id.to_blob_path(),
+            size_bytes: 100,
+            content_type: "application/json".to_string(),
+            created_at: Timestamp::now(),
+            checksum_sha256: crate::blob_storage::compute_checksum(&body),
+            format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+            content_encoding: crate::blob_storage::ContentEncoding::Identity,
+            encryption: None,
+            metadata: payload.metadata.clone(),
+        };
+        self.objects
+            .lock()
+            .unwrap()
+            .insert(event_id, StoredWebhook { metadata, payload });
+        event_id
+    }
+
+    fn contains(&self, event_id: &EventId) -> bool {
+        self.objects.lock().unwrap().contains_key(event_id)
+    }
+}
+
+#[async_trait]
+impl BlobStorage for MemoryStorage {
+    async fn store_payload(
+        &self,
+        _event_id: &EventId,
+        _payload: &WebhookPayload,
+    ) -> Result<BlobMetadata, BlobStorageError> {
+        unimplemented!("Payloads are inserted directly by the retention tests")
+    }
+
+    async fn get_payload(
+        &self,
+        event_id: &EventId,
+    ) -> Result<Option<StoredWebhook>, BlobStorageError> {
+        Ok(self.objects.lock().unwrap().get(event_id).cloned())
+    }
+
+    async fn list_payloads(
+        &self,
+        _filter: &PayloadFilter,
+    ) -> Result<Vec<BlobMetadata>, BlobStorageError> {
+        Ok(self
+            .objects
+            .lock()
+            .unwrap()
+            .values()
+            .map(|stored| stored.metadata.clone())
+            .collect())
+    }
+
+    async fn delete_payload(&self, event_id: &EventId) -> Result<(), BlobStorageError> {
+        if self.deny_deletes {
+            return Err(BlobStorageError::PermissionDenied {
+                operation: "delete".to_string(),
+            });
+        }
+        self.objects.lock().unwrap().remove(event_id);
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<StorageHealthStatus, BlobStorageError> {
+        Ok(StorageHealthStatus {
+            healthy: true,
+            connected: true,
+            last_success: None,
+            error_message: None,
+            metrics: StorageMetrics {
+                avg_write_latency_ms: 0.0,
+                avg_read_latency_ms: 0.0,
+                success_rate: 1.0,
+            },
+        })
+    }
+}
+
+fn rule(events: &[&str], ttl: Duration) -> PayloadRetentionRule {
+    PayloadRetentionRule {
+        events: events.iter().map(|e| e.to_string()).collect(),
+        ttl,
+    }
+}
+
+fn config(
+    default_ttl: Option<Duration>,
+    rules: Vec<PayloadRetentionRule>,
+) -> PayloadRetentionConfig {
+    PayloadRetentionConfig {
+        enabled: true,
+        default_ttl: default_ttl.map(HumanDuration::from),
+        rules,
+        ..Default::default()
+    }
+}
+
+/// The first matching rule applies; unmatched event types use the default
+/// TTL, or are kept forever without one.
+#[test]
+fn test_policy_resolves_ttl_per_event_type() {
+    let policy = PayloadRetentionPolicy::from_config(&config(
+        Some(90 * DAY),
+        vec![
+            rule(&["check_*", "!check_suite"], 7 * DAY),
+            rule(&["status"], DAY),
+        ],
+    ))
+    .unwrap();
+
+    assert_eq!(policy.ttl_for("check_run"), Some(7 * DAY));
+    assert_eq!(policy.ttl_for("check_suite"), Some(90 * DAY));
+    assert_eq!(policy.ttl_for("status"), Some(DAY));
+    assert_eq!(policy.ttl_for("push"), Some(90 * DAY));
+    assert_eq!(policy.shortest_ttl(), Some(DAY));
+
+    let keep_unmatched =
+        PayloadRetentionPolicy::from_config(&config(None, vec![rule(&["status"], DAY)])).unwrap();
+    assert_eq!(keep_unmatched.ttl_for("push"), None);
+    assert!(!keep_unmatched.is_expired(
+        "push",
+        Timestamp::now().subtract_duration(365 * DAY),
+        Timestamp::now()
+    ));
+}
+
+#[test]
+fn test_config_validation() {
+    assert!(PayloadRetentionConfig::default().validate().is_ok());
+    assert!(config(Some(DAY), vec![]).validate().is_ok());
+
+    // Enabled without any TTL
+    assert!(config(None, vec![]).validate().is_err());
+    // Rule without events, with a zero TTL or an invalid pattern
+    assert!(config(None, vec![rule(&[], DAY)]).validate().is_err());
+    assert!(config(None, vec![rule(&["status"], Duration::ZERO)])
+        .validate()
+        .is_err());
+    assert!(config(None, vec![rule(&[""], DAY)]).validate().is_err());
+
+    let archive_without_directory = PayloadRetentionConfig {
+        action: RetentionAction::Archive,
+        ..config(Some(DAY), vec![])
+    };
+    assert!(archive_without_directory.validate().is_err());
+}
+
+/// Only expired payloads are deleted, and the reclaimed data is reported.
+#[tokio::test]
+async fn test_sweep_deletes_expired_payloads() {
+    let storage = Arc::new(MemoryStorage::default());
+    let expired_status = storage.insert("status", 2 * DAY);
+    let fresh_status = storage.insert("status", Duration::from_secs(60));
+    let old_push = storage.insert("push", 30 * DAY);
+    let sweeper = PayloadRetentionSweeper::from_config(
+        &config(None, vec![rule(&["status"], DAY)]),
+        storage.clone(),
+    )
+    .unwrap();
+
+    let report = sweeper.sweep(Timestamp::now()).await.unwrap();
+
+    assert_eq!(report.deleted_payloads, 1);
+    assert_eq!(report.reclaimed_bytes, 100);
+    assert!(!storage.contains(&expired_status));
+    assert!(storage.contains(&fresh_status));
+    assert!(storage.contains(&old_push));
+
+    let status = sweeper.status();
+    assert_eq!(status.sweeps, 1);
+    assert_eq!(status.totals.deleted_payloads, 1);
+    assert_eq!(status.last_sweep, Some(report));
+}
+
+/// Archived payloads are written compressed to the archive directory before
+/// they are removed from storage.
+#[tokio::test]
+async fn test_sweep_archives_expired_payloads() {
+    let archive = tempfile::tempdir().unwrap();
+    let storage = Arc::new(MemoryStorage::default());
+    let event_id = storage.insert("push", 10 * DAY);
+    let sweeper = PayloadRetentionSweeper::from_config(
+        &PayloadRetentionConfig {
+            action: RetentionAction::Archive,
+            archive_directory: Some(archive.path().to_path_buf()),
+            ..config(Some(7 * DAY), vec![])
+        },
+        storage.clone(),
+    )
+    .unwrap();
+
+    let report = sweeper.sweep(Timestamp::now()).await.unwrap();
+
+    assert_eq!(report.archived_payloads, 1);
+    assert!(report.archived_bytes > 0);
+    assert!(!storage.contains(&event_id));
+
+    let archived = std::fs::read(
+        archive
+            .path()
+            .join(format!("{}.gz", event_id.to_blob_path())),
+    )
+    .unwrap();
+    let mut json = String::new();
+    GzDecoder::new(archived.as_slice())
+        .read_to_string(&mut json)
+        .unwrap();
+    let stored: StoredWebhook = serde_json::from_str(&json).unwrap();
+    assert_eq!(stored.metadata.event_id, event_id);
+}
+
+/// Payloads that cannot be removed are counted and kept for the next sweep.
+#[tokio::test]
+async fn test_sweep_counts_failed_payloads() {
+    let storage = Arc::new(MemoryStorage {
+        deny_deletes: true,
+        ..Default::default()
+    });
+    let event_id = storage.insert("push", 10 * DAY);
+    let sweeper =
+        PayloadRetentionSweeper::from_config(&config(Some(DAY), vec![]), storage.clone()).unwrap();
+
+    let report = sweeper.sweep(Timestamp::now()).await.unwrap();
+
+    assert_eq!(report.failed_payloads, 1);
+    assert_eq!(report.reclaimed_payloads(), 0);
+    assert!(storage.contains(&event_id));
+}
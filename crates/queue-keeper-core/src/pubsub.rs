@@ -0,0 +1,554 @@
+//! # Topic (Pub-Sub) Routing
+//!
+//! In the default routing mode Queue-Keeper sends one message per matching
+//! bot, each to the bot's own queue. In topic mode it publishes each event
+//! once to a single Service Bus topic; every bot reads from its own
+//! subscription on that topic, and the provider applies the bot's filters
+//! through a subscription rule. Fan-out latency becomes one publish,
+//! regardless of how many bots receive the event.
+//!
+//! Subscription rules are materialized from the [`BotConfiguration`] by
+//! [`subscription_rules`] as Service Bus SQL filters over the message
+//! attributes set by [`event_filter_attributes`], and kept in sync with
+//! [`reconcile_subscription_rules`] through a [`SubscriptionRuleManager`].
+//!
+//! Only filters a subscription rule can express are supported. Bots with a
+//! repository name pattern, a sender filter, field encryption, a transform,
+//! a payload projection or an HTTP destination need per-bot processing and
+//! cannot be served from a topic; topic mode refuses such configurations
+//! instead of over-delivering.
+
+use crate::{
+    bot_config::{BotConfiguration, BotSubscription, EventTypePattern, RepositoryFilter},
+    event_debug, event_warn,
+    logging::LogFields,
+    queue_integration::{
+        event_filter_attributes, DeliveryResult, EventRouter, FailedDelivery, QueueDeliveryError,
+        SuccessfulDelivery, TRACE_PARENT_ATTRIBUTE,
+    },
+    webhook::WrappedEvent,
+    BotName, QueueName,
+};
+use async_trait::async_trait;
+use queue_runtime::{Message, QueueClient};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Mutex;
+use thiserror::Error;
+
+/// Maximum length of a Service Bus subscription name.
+pub const MAX_SUBSCRIPTION_NAME_LENGTH: usize = 50;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// How processed events reach bots.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingMode {
+    /// One message per matching bot, sent to the bot's own queue.
+    #[default]
+    Queues,
+
+    /// One message per event, published to a shared topic.
+    Topic,
+}
+
+/// Routing mode settings.
+///
+/// ```yaml
+/// routing:
+///   mode: topic
+///   topic: queue-keeper-events
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RoutingConfig {
+    /// Routing mode. Defaults to [`RoutingMode::Queues`].
+    pub mode: RoutingMode,
+
+    /// Topic events are published to. Required in topic mode.
+    pub topic: Option<String>,
+}
+
+impl RoutingConfig {
+    /// Whether events are published to a topic.
+    pub fn is_topic(&self) -> bool {
+        self.mode == RoutingMode::Topic
+    }
+
+    /// Validate the routing settings.
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.mode, &self.topic) {
+            (RoutingMode::Topic, None) => {
+                Err("routing.topic is required when routing.mode is 'topic'".to_string())
+            }
+            (RoutingMode::Topic, Some(topic)) => QueueName::new(topic.clone())
+                .map(|_| ())
+                .map_err(|e| format!("routing.topic '{}' is invalid: {}", topic, e)),
+            (RoutingMode::Queues, _) => Ok(()),
+        }
+    }
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors materializing or applying subscription rules.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum PubSubError {
+    /// The bot's subscription cannot be expressed as a subscription rule.
+    #[error("bot '{bot}' cannot be served from a topic: {reason}")]
+    UnsupportedSubscription { bot: String, reason: String },
+
+    /// The provider rejected a rule operation.
+    #[error("subscription rule operation failed for '{subscription}': {message}")]
+    RuleOperation {
+        subscription: String,
+        message: String,
+    },
+}
+
+// ============================================================================
+// Subscription Rules
+// ============================================================================
+
+/// The subscription and filter rule that deliver a bot's events from the
+/// topic.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SubscriptionRule {
+    /// Subscription name; the bot name.
+    pub subscription: String,
+
+    /// Service Bus SQL filter over the message attributes.
+    pub filter: String,
+
+    /// Whether the subscription must be session-enabled.
+    pub requires_session: bool,
+}
+
+/// Materialize the subscription rules for every bot in `config`.
+///
+/// # Errors
+///
+/// Returns [`PubSubError::UnsupportedSubscription`] for the first bot whose
+/// subscription cannot be expressed as a rule.
+pub fn subscription_rules(config: &BotConfiguration) -> Result<Vec<SubscriptionRule>, PubSubError> {
+    config.bots.iter().map(subscription_rule).collect()
+}
+
+/// Materialize the subscription rule for one bot.
+///
+/// # Errors
+///
+/// Returns [`PubSubError::UnsupportedSubscription`] when the bot needs
+/// per-bot processing or a filter that SQL rules cannot express.
+///
+/// # Examples
+///
+/// ```
+/// use queue_keeper_core::bot_config::{BotSpecificConfig, BotSubscription, EventTypePattern};
+/// use queue_keeper_core::pubsub::subscription_rule;
+/// use queue_keeper_core::{BotName, QueueName};
+///
+/// let bot = BotSubscription {
+///     name: BotName::new("triage").unwrap(),
+///     queue: QueueName::new("queue-keeper-triage").unwrap(),
+///     events: vec![EventTypePattern::Exact("issues".to_string())],
+///     ordered: false,
+///     repository_filter: None,
+///     sender_filter: None,
+///     config: BotSpecificConfig::new(),
+///     encryption: None,
+///     transform: None,
+///     projection: None,
+///     http: None,
+/// };
+///
+/// let rule = subscription_rule(&bot).unwrap();
+/// assert_eq!(rule.filter, "(event_type = 'issues')");
+/// ```
+pub fn subscription_rule(bot: &BotSubscription) -> Result<SubscriptionRule, PubSubError> {
+    let unsupported = |reason: &str| PubSubError::UnsupportedSubscription {
+        bot: bot.name.as_str().to_string(),
+        reason: reason.to_string(),
+    };
+    if bot.name.as_str().len() > MAX_SUBSCRIPTION_NAME_LENGTH {
+        return Err(unsupported(&format!(
+            "subscription names are limited to {} characters",
+            MAX_SUBSCRIPTION_NAME_LENGTH
+        )));
+    }
+    if bot.sender_filter.is_some() {
+        return Err(unsupported("sender filters cannot be expressed as rules"));
+    }
+    if bot.encryption.is_some() {
+        return Err(unsupported("field encryption is applied per bot"));
+    }
+    if bot.transform.is_some() {
+        return Err(unsupported("transforms are applied per bot"));
+    }
+    if bot.projection.is_some() {
+        return Err(unsupported("payload projections are applied per bot"));
+    }
+    if bot.http.is_some() {
+        return Err(unsupported(
+            "HTTP destinations are not served from the topic",
+        ));
+    }
+
+    let mut filter = events_filter(&bot.events);
+    if let Some(repository_filter) = &bot.repository_filter {
+        let repository = repository_sql(repository_filter)
+            .ok_or_else(|| unsupported("repository name patterns cannot be expressed as rules"))?;
+        filter = format!("{} AND {}", filter, repository);
+    }
+
+    Ok(SubscriptionRule {
+        subscription: bot.name.as_str().to_string(),
+        filter,
+        requires_session: bot.ordered,
+    })
+}
+
+/// SQL condition matching the bot's event type patterns.
+fn events_filter(events: &[EventTypePattern]) -> String {
+    let included: Vec<String> = events
+        .iter()
+        .filter_map(|pattern| match pattern {
+            EventTypePattern::Exact(event_type) => {
+                Some(format!("event_type = {}", sql_string(event_type)))
+            }
+            EventTypePattern::EntityAll(entity) => Some(format!(
+                "event_type = {} OR event_type LIKE {} ESCAPE '!'",
+                sql_string(entity),
+                sql_string(&format!("{}.%", like_escape(entity)))
+            )),
+            EventTypePattern::Wildcard(wildcard) => {
+                Some(if let Some(prefix) = wildcard.strip_suffix('*') {
+                    format!(
+                        "event_type LIKE {} ESCAPE '!'",
+                        sql_string(&format!("{}%", like_escape(prefix)))
+                    )
+                } else if let Some(suffix) = wildcard.strip_prefix('*') {
+                    format!(
+                        "event_type LIKE {} ESCAPE '!'",
+                        sql_string(&format!("%{}", like_escape(suffix)))
+                    )
+                } else {
+                    // Mirrors EventTypePattern::matches, which never matches
+                    // a wildcard in the middle of a pattern.
+                    "1 = 0".to_string()
+                })
+            }
+            EventTypePattern::Exclude(_) => None,
+        })
+        .collect();
+
+    let mut filter = if included.is_empty() {
+        "1 = 0".to_string()
+    } else {
+        format!("({})", included.join(" OR "))
+    };
+    for pattern in events {
+        if let EventTypePattern::Exclude(excluded) = pattern {
+            filter.push_str(&format!(" AND event_type <> {}", sql_string(excluded)));
+        }
+    }
+    filter
+}
+
+/// SQL condition matching a repository filter, or `None` when the filter
+/// uses a name pattern.
+fn repository_sql(filter: &RepositoryFilter) -> Option<String> {
+    match filter {
+        RepositoryFilter::Exact { owner, name } => Some(format!(
+            "repository = {}",
+            sql_string(&format!("{}/{}", owner, name))
+        )),
+        RepositoryFilter::Owner(owner) => Some(format!(
+            "repository LIKE {} ESCAPE '!'",
+            sql_string(&format!("{}/%", like_escape(owner)))
+        )),
+        RepositoryFilter::NamePattern(_) => None,
+        RepositoryFilter::AnyOf(filters) => combine(filters, " OR ", "1 = 0"),
+        RepositoryFilter::AllOf(filters) => combine(filters, " AND ", "1 = 1"),
+    }
+}
+
+fn combine(filters: &[RepositoryFilter], operator: &str, empty: &str) -> Option<String> {
+    if filters.is_empty() {
+        return Some(empty.to_string());
+    }
+    let parts = filters
+        .iter()
+        .map(repository_sql)
+        .collect::<Option<Vec<_>>>()?;
+    Some(format!("({})", parts.join(operator)))
+}
+
+/// Quote a SQL string literal.
+fn sql_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Escape `LIKE` wildcards with the `!` escape character.
+fn like_escape(value: &str) -> String {
+    value
+        .replace('!', "!!")
+        .replace('%', "!%")
+        .replace('_', "!_")
+}
+
+// ============================================================================
+// Rule Management
+// ============================================================================
+
+/// Provider-side storage of the subscription rules on a topic.
+///
+/// Implementations create a subscription per rule, replace its filter and
+/// delete subscriptions no longer configured. Only subscriptions created by
+/// Queue-Keeper are listed.
+#[async_trait]
+pub trait SubscriptionRuleManager: Send + Sync {
+    /// List the rules currently applied to `topic`.
+    async fn list_rules(&self, topic: &QueueName) -> Result<Vec<SubscriptionRule>, PubSubError>;
+
+    /// Create the subscription, or replace its filter.
+    async fn upsert_rule(
+        &self,
+        topic: &QueueName,
+        rule: &SubscriptionRule,
+    ) -> Result<(), PubSubError>;
+
+    /// Delete a subscription.
+    async fn delete_rule(&self, topic: &QueueName, subscription: &str) -> Result<(), PubSubError>;
+}
+
+/// Changes made by [`reconcile_subscription_rules`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct RuleReconciliation {
+    /// Subscriptions created.
+    pub created: Vec<String>,
+
+    /// Subscriptions whose rule changed.
+    pub updated: Vec<String>,
+
+    /// Subscriptions of bots no longer configured.
+    pub deleted: Vec<String>,
+
+    /// Subscriptions already up to date.
+    pub unchanged: usize,
+}
+
+/// Bring the rules on `topic` in line with `desired`.
+///
+/// # Errors
+///
+/// Returns the first error reported by the manager; earlier changes are
+/// kept, so the next reconciliation resumes where this one stopped.
+pub async fn reconcile_subscription_rules(
+    manager: &dyn SubscriptionRuleManager,
+    topic: &QueueName,
+    desired: &[SubscriptionRule],
+) -> Result<RuleReconciliation, PubSubError> {
+    let existing: BTreeMap<String, SubscriptionRule> = manager
+        .list_rules(topic)
+        .await?
+        .into_iter()
+        .map(|rule| (rule.subscription.clone(), rule))
+        .collect();
+    let mut report = RuleReconciliation::default();
+
+    for rule in desired {
+        match existing.get(&rule.subscription) {
+            Some(current) if current == rule => report.unchanged += 1,
+            Some(_) => {
+                manager.upsert_rule(topic, rule).await?;
+                report.updated.push(rule.subscription.clone());
+            }
+            None => {
+                manager.upsert_rule(topic, rule).await?;
+                report.created.push(rule.subscription.clone());
+            }
+        }
+    }
+
+    let desired_names: HashSet<&str> = desired.iter().map(|r| r.subscription.as_str()).collect();
+    for subscription in existing.keys() {
+        if !desired_names.contains(subscription.as_str()) {
+            manager.delete_rule(topic, subscription).await?;
+            report.deleted.push(subscription.clone());
+        }
+    }
+
+    Ok(report)
+}
+
+/// Rule manager that keeps rules in memory. For development and tests.
+#[derive(Debug, Default)]
+pub struct InMemorySubscriptionRuleManager {
+    rules: Mutex<BTreeMap<(String, String), SubscriptionRule>>,
+}
+
+impl InMemorySubscriptionRuleManager {
+    /// Create an empty rule manager.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl SubscriptionRuleManager for InMemorySubscriptionRuleManager {
+    async fn list_rules(&self, topic: &QueueName) -> Result<Vec<SubscriptionRule>, PubSubError> {
+        let rules = self.rules.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(rules
+            .iter()
+            .filter(|((t, _), _)| t == topic.as_str())
+            .map(|(_, rule)| rule.clone())
+            .collect())
+    }
+
+    async fn upsert_rule(
+        &self,
+        topic: &QueueName,
+        rule: &SubscriptionRule,
+    ) -> Result<(), PubSubError> {
+        self.rules.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            (topic.as_str().to_string(), rule.subscription.clone()),
+            rule.clone(),
+        );
+        Ok(())
+    }
+
+    async fn delete_rule(&self, topic: &QueueName, subscription: &str) -> Result<(), PubSubError> {
+        self.rules
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .remove(&(topic.as_str().to_string(), subscription.to_string()));
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Topic Router
+// ============================================================================
+
+/// Event router that publishes each event once to a topic.
+///
+/// The router still matches bots locally so the [`DeliveryResult`] names
+/// the bots that will receive the event, and so events no bot subscribes
+/// to are not published at all.
+pub struct TopicEventRouter {
+    topic: QueueName,
+}
+
+impl TopicEventRouter {
+    /// Create a router publishing to `topic`.
+    pub fn new(topic: QueueName) -> Self {
+        Self { topic }
+    }
+
+    /// Topic events are published to.
+    pub fn topic(&self) -> &QueueName {
+        &self.topic
+    }
+
+    /// Build the single message published for `event`.
+    fn create_topic_message(&self, event: &WrappedEvent) -> Result<Message, QueueDeliveryError> {
+        let body = serde_json::to_vec(event)
+            .map_err(|e| QueueDeliveryError::SerializationError(e.to_string()))?;
+        let mut message =
+            Message::new(body.into()).with_correlation_id(event.correlation_id.to_string());
+
+        // Session-enabled subscriptions need the session on every message;
+        // other subscriptions ignore it.
+        if let Some(ref core_session) = event.session_id {
+            let session_id = queue_runtime::SessionId::new(core_session.as_str().to_string())
+                .map_err(|e| {
+                    QueueDeliveryError::SerializationError(format!("Invalid session ID: {}", e))
+                })?;
+            message = message
+                .with_session_id(session_id)
+                .with_attribute("session_epoch".to_string(), event.session_epoch.to_string());
+        }
+
+        for (name, value) in event_filter_attributes(event) {
+            message = message.with_attribute(name.to_string(), value);
+        }
+        if let Some(ref trace_parent) = event.trace_parent {
+            message =
+                message.with_attribute(TRACE_PARENT_ATTRIBUTE.to_string(), trace_parent.clone());
+        }
+        Ok(message)
+    }
+}
+
+#[async_trait]
+impl EventRouter for TopicEventRouter {
+    async fn route_event(
+        &self,
+        event: &WrappedEvent,
+        config: &BotConfiguration,
+        queue_client: &dyn QueueClient,
+    ) -> Result<DeliveryResult, QueueDeliveryError> {
+        let mut result = DeliveryResult::new(event.event_id);
+        let target_bots: Vec<BotName> = config
+            .get_target_bots(event)
+            .into_iter()
+            .map(|bot| bot.name.clone())
+            .collect();
+        if target_bots.is_empty() {
+            return Ok(result);
+        }
+
+        let topic = queue_runtime::QueueName::new(self.topic.as_str().to_string())
+            .map_err(|e| QueueDeliveryError::ConfigurationError(e.to_string()))?;
+        let message = self.create_topic_message(event)?;
+        let log_context = event.log_context().with_queue(&self.topic);
+
+        match queue_client.send_message(&topic, message).await {
+            Ok(message_id) => {
+                event_debug!(
+                    log_context,
+                    message_id = ?message_id,
+                    bots = target_bots.len(),
+                    "Event published to topic"
+                );
+                result.successful = target_bots
+                    .into_iter()
+                    .map(|bot_name| SuccessfulDelivery {
+                        bot_name,
+                        queue_name: self.topic.clone(),
+                        message_id: message_id.clone(),
+                    })
+                    .collect();
+                Ok(result)
+            }
+            Err(queue_error) => {
+                event_warn!(
+                    log_context,
+                    error = %queue_error,
+                    is_transient = queue_error.is_transient(),
+                    "Failed to publish event to topic"
+                );
+                let failures = target_bots
+                    .into_iter()
+                    .map(|bot_name| FailedDelivery {
+                        bot_name,
+                        queue_name: self.topic.clone(),
+                        error: queue_error.to_string(),
+                        is_transient: queue_error.is_transient(),
+                    })
+                    .collect();
+                Err(QueueDeliveryError::CompleteFailure { failures })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "pubsub_tests.rs"]
+mod tests;
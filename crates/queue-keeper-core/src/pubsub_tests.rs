@@ -0,0 +1,369 @@
+//! Tests for topic routing and subscription rules.
+
+use super::*;
+use crate::bot_config::{BotConfigurationSettings, BotSpecificConfig, SenderFilter};
+use chrono::{Duration as ChronoDuration, TimeDelta};
+use queue_runtime::{
+    MessageId, ProviderType, QueueError, ReceiptHandle, ReceivedMessage, SessionClient,
+};
+use std::sync::Arc;
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+/// Queue client that records sent messages, or fails every send.
+#[derive(Default)]
+struct RecordingQueueClient {
+    sent: Mutex<Vec<(String, Message)>>,
+    fail: bool,
+}
+
+#[async_trait]
+impl QueueClient for RecordingQueueClient {
+    async fn send_message(
+        &self,
+        queue: &queue_runtime::QueueName,
+        message: Message,
+    ) -> Result<MessageId, QueueError> {
+        if self.fail {
+            return Err(QueueError::Timeout {
+                duration: ChronoDuration::seconds(30),
+            });
+        }
+        self.sent
+            .lock()
+            .unwrap()
+            .push((queue.as_str().to_string(), message));
+        Ok(MessageId::new())
+    }
+
+    async fn send_messages(
+        &self,
+        _queue: &queue_runtime::QueueName,
+        _messages: Vec<Message>,
+    ) -> Result<Vec<MessageId>, QueueError> {
+        unimplemented!("Batch sending not needed for topic tests")
+    }
+
+    async fn receive_message(
+        &self,
+        _queue: &queue_runtime::QueueName,
+        _timeout: TimeDelta,
+    ) -> Result<Option<ReceivedMessage>, QueueError> {
+        unimplemented!("Receiving not needed for topic tests")
+    }
+
+    async fn receive_messages(
+        &self,
+        _queue: &queue_runtime::QueueName,
+        _max_messages: u32,
+        _timeout: TimeDelta,
+    ) -> Result<Vec<ReceivedMessage>, QueueError> {
+        unimplemented!("Receiving not needed for topic tests")
+    }
+
+    async fn complete_message(&self, _receipt: ReceiptHandle) -> Result<(), QueueError> {
+        unimplemented!("Completion not needed for topic tests")
+    }
+
+    async fn abandon_message(&self, _receipt: ReceiptHandle) -> Result<(), QueueError> {
+        unimplemented!("Abandon not needed for topic tests")
+    }
+
+    async fn dead_letter_message(
+        &self,
+        _receipt: ReceiptHandle,
+        _reason: String,
+    ) -> Result<(), QueueError> {
+        unimplemented!("Dead letter not needed for topic tests")
+    }
+
+    async fn accept_session(
+        &self,
+        _queue: &queue_runtime::QueueName,
+        _session_id: Option<queue_runtime::SessionId>,
+    ) -> Result<Box<dyn SessionClient>, QueueError> {
+        unimplemented!("Sessions not needed for topic tests")
+    }
+
+    fn provider_type(&self) -> ProviderType {
+        ProviderType::InMemory
+    }
+
+    fn supports_sessions(&self) -> bool {
+        true
+    }
+
+    fn supports_batching(&self) -> bool {
+        false
+    }
+}
+
+fn bot(name: &str, events: &[&str]) -> BotSubscription {
+    BotSubscription {
+        name: BotName::new(name).unwrap(),
+        queue: QueueName::new(format!("queue-keeper-{}", name)).unwrap(),
+        events: events.iter().map(|e| e.parse().unwrap()).collect(),
+        ordered: false,
+        repository_filter: None,
+        sender_filter: None,
+        config: BotSpecificConfig::new(),
+        encryption: None,
+        transform: None,
+        projection: None,
+        http: None,
+    }
+}
+
+fn config(bots: Vec<BotSubscription>) -> BotConfiguration {
+    BotConfiguration {
+        bots,
+        settings: BotConfigurationSettings::default(),
+    }
+}
+
+fn pull_request_event() -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        Some(crate::SessionId::from_parts(
+            "owner",
+            "repo",
+            "pull_request",
+            "1",
+        )),
+        serde_json::json!({
+            "repository": {
+                "id": 1,
+                "name": "repo",
+                "full_name": "owner/repo",
+                "private": false,
+                "owner": {"id": 1, "login": "owner", "type": "User"}
+            }
+        }),
+        None,
+    )
+}
+
+fn topic() -> QueueName {
+    QueueName::new("queue-keeper-events").unwrap()
+}
+
+// ============================================================================
+// Configuration Tests
+// ============================================================================
+
+/// Topic mode needs a valid topic name; queue mode needs nothing.
+#[test]
+fn test_routing_config_validation() {
+    assert!(RoutingConfig::default().validate().is_ok());
+
+    let mut config = RoutingConfig {
+        mode: RoutingMode::Topic,
+        topic: None,
+    };
+    assert!(config.validate().is_err());
+
+    config.topic = Some("queue-keeper-events".to_string());
+    assert!(config.validate().is_ok());
+    assert!(config.is_topic());
+}
+
+// ============================================================================
+// Subscription Rule Tests
+// ============================================================================
+
+/// Each event pattern kind becomes an SQL condition, with LIKE wildcards in
+/// names escaped.
+#[test]
+fn test_event_patterns_become_sql_filters() {
+    let rule = subscription_rule(&bot("reviewer", &["pull_request", "issues.*", "!push"])).unwrap();
+
+    assert_eq!(
+        rule.filter,
+        "(event_type = 'pull_request' OR event_type LIKE 'pull!_request.%' ESCAPE '!' \
+         OR event_type LIKE 'issues.%' ESCAPE '!') AND event_type <> 'push'"
+    );
+    assert_eq!(rule.subscription, "reviewer");
+    assert!(!rule.requires_session);
+}
+
+/// Repository filters become conditions on the repository attribute.
+#[test]
+fn test_repository_filter_becomes_sql_filter() {
+    let mut subscription = bot("owner-bot", &["pull_request.opened"]);
+    subscription.ordered = true;
+    subscription.repository_filter = Some(RepositoryFilter::AnyOf(vec![
+        RepositoryFilter::Exact {
+            owner: "acme".to_string(),
+            name: "o'neil".to_string(),
+        },
+        RepositoryFilter::Owner("tools".to_string()),
+    ]));
+
+    let rule = subscription_rule(&subscription).unwrap();
+
+    assert_eq!(
+        rule.filter,
+        "(event_type = 'pull_request.opened') AND (repository = 'acme/o''neil' \
+         OR repository LIKE 'tools/%' ESCAPE '!')"
+    );
+    assert!(rule.requires_session);
+}
+
+/// Bots needing per-bot processing or unexpressible filters are refused.
+#[test]
+fn test_unsupported_subscriptions_are_refused() {
+    let mut pattern = bot("pattern-bot", &["issues"]);
+    pattern.repository_filter = Some(RepositoryFilter::NamePattern("^svc-".to_string()));
+
+    let mut sender = bot("sender-bot", &["issues"]);
+    sender.sender_filter = Some(SenderFilter::default());
+
+    let mut projected = bot("projected-bot", &["issues"]);
+    projected.projection = Some(crate::payload_projection::PayloadProjectionConfig {
+        slim: true,
+        ..Default::default()
+    });
+
+    let mut pushed = bot("pushed-bot", &["issues"]);
+    pushed.http = Some(crate::HttpDestinationConfig::new(
+        "https://hooks.example.com/events",
+        "queue-keeper-test-pushed-bot",
+    ));
+
+    let long = bot(&"a".repeat(MAX_SUBSCRIPTION_NAME_LENGTH + 1), &["issues"]);
+
+    for subscription in [pattern, sender, projected, pushed, long] {
+        assert!(matches!(
+            subscription_rule(&subscription),
+            Err(PubSubError::UnsupportedSubscription { .. })
+        ));
+    }
+    assert!(subscription_rules(&config(vec![bot("ok", &["issues"])])).is_ok());
+}
+
+// ============================================================================
+// Reconciliation Tests
+// ============================================================================
+
+/// Reconciliation creates, updates and deletes subscriptions, and a second
+/// pass changes nothing.
+#[tokio::test]
+async fn test_reconcile_subscription_rules() {
+    let manager = InMemorySubscriptionRuleManager::new();
+    let stale = subscription_rule(&bot("retired", &["issues"])).unwrap();
+    let mut changed = subscription_rule(&bot("reviewer", &["issues"])).unwrap();
+    manager.upsert_rule(&topic(), &stale).await.unwrap();
+    manager.upsert_rule(&topic(), &changed).await.unwrap();
+
+    changed.filter = "(event_type = 'pull_request')".to_string();
+    let added = subscription_rule(&bot("triage", &["issues"])).unwrap();
+    let desired = vec![changed, added];
+
+    let report = reconcile_subscription_rules(&manager, &topic(), &desired)
+        .await
+        .unwrap();
+    assert_eq!(report.created, vec!["triage".to_string()]);
+    assert_eq!(report.updated, vec!["reviewer".to_string()]);
+    assert_eq!(report.deleted, vec!["retired".to_string()]);
+
+    let rerun = reconcile_subscription_rules(&manager, &topic(), &desired)
+        .await
+        .unwrap();
+    assert_eq!(
+        rerun,
+        RuleReconciliation {
+            unchanged: 2,
+            ..RuleReconciliation::default()
+        }
+    );
+}
+
+// ============================================================================
+// Topic Router Tests
+// ============================================================================
+
+/// An event for several bots is published once, with filter attributes.
+#[tokio::test]
+async fn test_topic_router_publishes_once() {
+    let router = TopicEventRouter::new(topic());
+    let client = RecordingQueueClient::default();
+    let bots = config(vec![
+        bot("reviewer", &["pull_request"]),
+        bot("labeler", &["pull_request"]),
+        bot("triage", &["issues"]),
+    ]);
+
+    let result = router
+        .route_event(&pull_request_event(), &bots, &client)
+        .await
+        .unwrap();
+
+    let sent = client.sent.lock().unwrap();
+    assert_eq!(sent.len(), 1);
+    let (queue, message) = &sent[0];
+    assert_eq!(queue, "queue-keeper-events");
+    assert_eq!(
+        message.attributes.get("repository"),
+        Some(&"owner/repo".to_string())
+    );
+    assert!(message.session_id.is_some());
+    assert!(!message.attributes.contains_key("bot_name"));
+
+    let delivered: Vec<&str> = result
+        .successful
+        .iter()
+        .map(|d| d.bot_name.as_str())
+        .collect();
+    assert_eq!(delivered, vec!["reviewer", "labeler"]);
+}
+
+/// Events no bot subscribes to are not published.
+#[tokio::test]
+async fn test_topic_router_skips_unmatched_events() {
+    let router = TopicEventRouter::new(topic());
+    let client = RecordingQueueClient::default();
+
+    let result = router
+        .route_event(
+            &pull_request_event(),
+            &config(vec![bot("triage", &["issues"])]),
+            &client,
+        )
+        .await
+        .unwrap();
+
+    assert!(result.is_no_op());
+    assert!(client.sent.lock().unwrap().is_empty());
+}
+
+/// A failed publish fails delivery to every matched bot.
+#[tokio::test]
+async fn test_topic_router_publish_failure() {
+    let router = TopicEventRouter::new(topic());
+    let client = Arc::new(RecordingQueueClient {
+        fail: true,
+        ..RecordingQueueClient::default()
+    });
+
+    let error = router
+        .route_event(
+            &pull_request_event(),
+            &config(vec![bot("reviewer", &["pull_request"])]),
+            client.as_ref(),
+        )
+        .await
+        .unwrap_err();
+
+    assert!(error.is_transient());
+    match error {
+        QueueDeliveryError::CompleteFailure { failures } => {
+            assert_eq!(failures.len(), 1);
+            assert_eq!(failures[0].queue_name, topic());
+        }
+        other => panic!("expected CompleteFailure, got {:?}", other),
+    }
+}
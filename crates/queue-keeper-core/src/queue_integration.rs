@@ -3,7 +3,8 @@
 //! Integrates queue-runtime QueueClient with webhook processing for event publishing.
 //!
 //! This module provides the EventRouter trait and implementation for routing normalized
-//! events to configured bot queues based on bot subscriptions.
+//! events to configured bot queues based on bot subscriptions. Bots with an HTTP
+//! destination are pushed to through an [`HttpForwarder`] instead.
 //!
 //! See specs/interfaces/queue-client.md for queue operations specification.
 //! See specs/interfaces/bot-configuration.md for routing configuration.
@@ -11,10 +12,24 @@
 use crate::{
     audit_logging::{AuditContext, AuditLogger, AuditResult, WebhookProcessingAction},
     bot_config::{BotConfiguration, BotSubscription},
-    webhook::WrappedEvent,
+    event_debug, event_warn,
+    field_encryption::FieldEncryptor,
+    http_delivery::{HttpDestinationConfig, HttpForwarder},
+    logging::LogFields,
+    wasm_transform::{TransformOutcome, TransformRegistry},
+    webhook::{DirectQueueMetadata, WrappedEvent},
     BotName, EventId, Repository, RepositoryId, SessionId as CoreSessionId, User, UserId, UserType,
 };
 use async_trait::async_trait;
+use std::{
+    borrow::Cow,
+    collections::{BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+use tokio::sync::Notify;
 
 // Re-export queue-runtime types for convenience
 pub use queue_runtime::{Message, MessageId, QueueClient, QueueError, QueueName, SessionId};
@@ -94,7 +109,12 @@ pub struct FailedDelivery {
 #[derive(Debug, thiserror::Error)]
 pub enum QueueDeliveryError {
     #[error("Failed to deliver to all target queues: {successful} succeeded, {failed} failed")]
-    PartialDelivery { successful: usize, failed: usize },
+    PartialDelivery {
+        successful: usize,
+        failed: usize,
+        /// Per-bot outcome, so that only the failed deliveries are retried
+        result: DeliveryResult,
+    },
 
     #[error("Failed to deliver to any target queue: {failures:?}")]
     CompleteFailure { failures: Vec<FailedDelivery> },
@@ -167,6 +187,197 @@ pub trait EventRouter: Send + Sync {
     ) -> Result<DeliveryResult, QueueDeliveryError>;
 }
 
+// ============================================================================
+// Message Attributes
+// ============================================================================
+
+/// Message attribute naming the event type.
+pub const ATTRIBUTE_EVENT_TYPE: &str = "event_type";
+
+/// Message attribute naming the action within the event type.
+pub const ATTRIBUTE_ACTION: &str = "action";
+
+/// Message attribute naming the repository (`owner/name`).
+pub const ATTRIBUTE_REPOSITORY: &str = "repository";
+
+/// Message attribute naming the provider the event came from.
+pub const ATTRIBUTE_TENANT: &str = "tenant";
+
+/// Message attribute carrying the W3C trace context of the event
+/// ([`WrappedEvent::trace_parent`]).
+pub const TRACE_PARENT_ATTRIBUTE: &str = "traceparent";
+
+/// Attributes that let consumers filter messages without reading the body.
+///
+/// Queue-runtime maps message attributes to SQS message attributes and
+/// Service Bus application properties, so bots can use SNS filter policies
+/// or Service Bus subscription rules on them. Attributes whose value is not
+/// known for the event (no action, no repository) are omitted.
+pub fn event_filter_attributes(event: &WrappedEvent) -> Vec<(&'static str, String)> {
+    let mut attributes = vec![
+        (ATTRIBUTE_EVENT_TYPE, event.event_type.clone()),
+        (ATTRIBUTE_TENANT, event.provider.clone()),
+    ];
+    if let Some(action) = &event.action {
+        attributes.push((ATTRIBUTE_ACTION, action.clone()));
+    }
+    if let Some(repository) = event
+        .payload
+        .pointer("/repository/full_name")
+        .and_then(|v| v.as_str())
+    {
+        attributes.push((ATTRIBUTE_REPOSITORY, repository.to_string()));
+    }
+    attributes
+}
+
+/// Attributes for a raw payload forwarded by a direct-mode provider.
+///
+/// The body is not parsed in direct mode, so only the provider and content
+/// type are known.
+pub fn direct_filter_attributes(metadata: &DirectQueueMetadata) -> Vec<(&'static str, String)> {
+    vec![
+        ("provider_id", metadata.provider_id().to_string()),
+        ("content_type", metadata.content_type().to_string()),
+        (ATTRIBUTE_TENANT, metadata.provider_id().to_string()),
+    ]
+}
+
+// ============================================================================
+// Session Sequencing
+// ============================================================================
+
+/// Serializes queue sends per session while sends of different sessions run
+/// in parallel.
+///
+/// Deliveries run in concurrent tasks, so two events of one pull request can
+/// reach the bot queue in the opposite order to the one they were received
+/// in. Each delivery takes a [`SequenceTicket`] for its event's session when
+/// the event is accepted, and waits for [`SequenceTicket::turn`] before its
+/// first send: tickets of a session get their turn in the order they were
+/// taken, each once the tickets before it are dropped.
+///
+/// Sequence numbers increase monotonically per session but are not
+/// contiguous, since all sessions draw from one counter. A session is
+/// forgotten once it has no outstanding tickets.
+#[derive(Debug, Default)]
+pub struct SessionSequencer {
+    next_sequence: AtomicU64,
+    sessions: Mutex<HashMap<CoreSessionId, SessionQueue>>,
+}
+
+/// Outstanding tickets of one session.
+#[derive(Debug, Default)]
+struct SessionQueue {
+    outstanding: BTreeSet<u64>,
+    turn_changed: Arc<Notify>,
+}
+
+impl SessionSequencer {
+    /// Create a sequencer with no sessions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Take the next ticket of `session_id`.
+    ///
+    /// Tickets must be taken in the order the events were received; the
+    /// ticket is usually moved into the delivery task afterwards.
+    pub fn ticket(self: &Arc<Self>, session_id: &CoreSessionId) -> SequenceTicket {
+        let mut sessions = self.sessions.lock().unwrap();
+        let sequence = self.next_sequence.fetch_add(1, Ordering::Relaxed);
+        let queue = sessions.entry(session_id.clone()).or_default();
+        queue.outstanding.insert(sequence);
+        SequenceTicket {
+            sequencer: Arc::clone(self),
+            session_id: session_id.clone(),
+            sequence,
+            turn_changed: Arc::clone(&queue.turn_changed),
+        }
+    }
+
+    /// Number of sessions with outstanding tickets.
+    pub fn active_sessions(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    /// Number of outstanding tickets of `session_id`, including the one whose
+    /// turn it is.
+    pub fn pending(&self, session_id: &CoreSessionId) -> usize {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .map_or(0, |queue| queue.outstanding.len())
+    }
+
+    fn is_turn(&self, session_id: &CoreSessionId, sequence: u64) -> bool {
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .and_then(|queue| queue.outstanding.first())
+            == Some(&sequence)
+    }
+
+    fn finish(&self, session_id: &CoreSessionId, sequence: u64) {
+        let mut sessions = self.sessions.lock().unwrap();
+        let Some(queue) = sessions.get_mut(session_id) else {
+            return;
+        };
+        queue.outstanding.remove(&sequence);
+        if queue.outstanding.is_empty() {
+            sessions.remove(session_id);
+        } else {
+            queue.turn_changed.notify_waiters();
+        }
+    }
+}
+
+/// Place of one event in its session's send order.
+///
+/// Dropping the ticket, whether or not its turn came, lets the next ticket
+/// of the session go; a cancelled delivery therefore never stalls its
+/// session.
+#[derive(Debug)]
+pub struct SequenceTicket {
+    sequencer: Arc<SessionSequencer>,
+    session_id: CoreSessionId,
+    sequence: u64,
+    turn_changed: Arc<Notify>,
+}
+
+impl SequenceTicket {
+    /// Sequence number of the ticket within its session.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Session the ticket belongs to.
+    pub fn session_id(&self) -> &CoreSessionId {
+        &self.session_id
+    }
+
+    /// Wait until every earlier ticket of the session has been dropped.
+    pub async fn turn(&self) {
+        loop {
+            // Registered before checking, so a ticket dropped in between
+            // still wakes this one.
+            let turn_changed = self.turn_changed.notified();
+            if self.sequencer.is_turn(&self.session_id, self.sequence) {
+                return;
+            }
+            turn_changed.await;
+        }
+    }
+}
+
+impl Drop for SequenceTicket {
+    fn drop(&mut self) {
+        self.sequencer.finish(&self.session_id, self.sequence);
+    }
+}
+
 // ============================================================================
 // Default Implementation
 // ============================================================================
@@ -177,27 +388,239 @@ pub trait EventRouter: Send + Sync {
 /// and unordered delivery modes.
 pub struct DefaultEventRouter {
     audit_logger: Option<std::sync::Arc<dyn AuditLogger>>,
+    field_encryptor: Option<std::sync::Arc<FieldEncryptor>>,
+    transforms: Option<std::sync::Arc<TransformRegistry>>,
+    http_forwarder: Option<std::sync::Arc<HttpForwarder>>,
 }
 
 impl DefaultEventRouter {
     /// Create new default event router
     pub fn new() -> Self {
-        Self { audit_logger: None }
+        Self {
+            audit_logger: None,
+            field_encryptor: None,
+            transforms: None,
+            http_forwarder: None,
+        }
     }
 
     /// Create new default event router with audit logging
     pub fn with_audit_logger(audit_logger: std::sync::Arc<dyn AuditLogger>) -> Self {
         Self {
             audit_logger: Some(audit_logger),
+            field_encryptor: None,
+            transforms: None,
+            http_forwarder: None,
         }
     }
 
+    /// Encrypt payload fields for bots that configure
+    /// [`BotSubscription::encryption`].
+    ///
+    /// Without an encryptor, deliveries to such bots fail rather than send
+    /// the fields in plain text.
+    pub fn with_field_encryption(mut self, encryptor: std::sync::Arc<FieldEncryptor>) -> Self {
+        self.field_encryptor = Some(encryptor);
+        self
+    }
+
+    /// Run the WebAssembly transforms of bots that configure
+    /// [`BotSubscription::transform`].
+    ///
+    /// Without a registry holding the bot's transform, deliveries to such
+    /// bots fail rather than bypass the transform.
+    pub fn with_transforms(mut self, transforms: std::sync::Arc<TransformRegistry>) -> Self {
+        self.transforms = Some(transforms);
+        self
+    }
+
+    /// Push events to bots that configure [`BotSubscription::http`].
+    ///
+    /// Without a forwarder, deliveries to such bots fail rather than fall
+    /// back to their queue.
+    pub fn with_http_forwarder(mut self, forwarder: std::sync::Arc<HttpForwarder>) -> Self {
+        self.http_forwarder = Some(forwarder);
+        self
+    }
+
+    /// Apply the bot's transform to `event`, if it has one.
+    fn transform_for_bot(
+        &self,
+        event: &WrappedEvent,
+        bot: &BotSubscription,
+    ) -> Result<TransformOutcome, FailedDelivery> {
+        if bot.transform.is_none() {
+            return Ok(TransformOutcome::Unchanged);
+        }
+        let failed = |error: String| FailedDelivery {
+            bot_name: bot.name.clone(),
+            queue_name: bot.queue.clone(),
+            error,
+            is_transient: false,
+        };
+        let Some(transforms) = &self.transforms else {
+            return Err(failed(
+                "Transform is configured but no transform runtime is loaded".to_string(),
+            ));
+        };
+        transforms
+            .apply(event, &bot.name)
+            .map_err(|e| failed(e.to_string()))
+    }
+
+    /// Apply the bot's field encryption to `event`, if it has any.
+    ///
+    /// Returns `None` when the event can be delivered unchanged.
+    async fn encrypt_for_bot(
+        &self,
+        event: &WrappedEvent,
+        bot: &BotSubscription,
+    ) -> Result<Option<WrappedEvent>, FailedDelivery> {
+        let Some(config) = &bot.encryption else {
+            return Ok(None);
+        };
+        let failed = |error: String, is_transient: bool| FailedDelivery {
+            bot_name: bot.name.clone(),
+            queue_name: bot.queue.clone(),
+            error,
+            is_transient,
+        };
+        let Some(encryptor) = &self.field_encryptor else {
+            return Err(failed(
+                "Field encryption is configured but no key vault is available".to_string(),
+                false,
+            ));
+        };
+        encryptor
+            .encrypt_event(event, config)
+            .await
+            .map(Some)
+            .map_err(|e| failed(e.to_string(), e.is_transient()))
+    }
+
+    /// Run the bot's transform, projection and field encryption on `event`.
+    ///
+    /// Returns `None` when the bot's transform skips the event.
+    async fn prepare_for_bot<'a>(
+        &self,
+        event: &'a WrappedEvent,
+        bot: &BotSubscription,
+    ) -> Result<Option<BotEvent<'a>>, FailedDelivery> {
+        // Run the bot's transform, which may skip or reshape the event
+        let filter_source = match self.transform_for_bot(event, bot) {
+            Ok(TransformOutcome::Unchanged) => Cow::Borrowed(event),
+            Ok(TransformOutcome::Replaced(transformed)) => Cow::Owned(*transformed),
+            Ok(TransformOutcome::Skip) => {
+                event_debug!(
+                    event.log_context().with_bot(&bot.name),
+                    "Transform skipped delivery to bot"
+                );
+                return Ok(None);
+            }
+            Err(failure) => {
+                event_warn!(
+                    event.log_context().with_bot(&bot.name),
+                    error = %failure.error,
+                    "Transform failed for bot"
+                );
+                return Err(failure);
+            }
+        };
+
+        // Trim the payload to the bot's projection, if any
+        let projected = bot
+            .projection
+            .as_ref()
+            .map(|projection| projection.apply_to_event(&filter_source));
+
+        // Encrypt the bot's protected fields, if any
+        let encrypted = self
+            .encrypt_for_bot(projected.as_ref().unwrap_or(&filter_source), bot)
+            .await
+            .inspect_err(|failure| {
+                event_warn!(
+                    event.log_context().with_bot(&bot.name),
+                    error = %failure.error,
+                    "Failed to encrypt payload fields for bot"
+                );
+            })?;
+
+        Ok(Some(BotEvent {
+            filter_source,
+            reshaped: encrypted.or(projected),
+        }))
+    }
+
+    /// Build the queue message `bot` receives for `event`, after its
+    /// transform, projection and field encryption.
+    ///
+    /// For deliveries made outside [`route_event`](EventRouter::route_event),
+    /// such as diverting a held event to another queue. Returns `None` when
+    /// the bot's transform skips the event.
+    ///
+    /// # Errors
+    /// Returns the failure a delivery of `event` to `bot` would record.
+    pub async fn message_for_bot(
+        &self,
+        event: &WrappedEvent,
+        bot: &BotSubscription,
+    ) -> Result<Option<Message>, FailedDelivery> {
+        let Some(prepared) = self.prepare_for_bot(event, bot).await? else {
+            return Ok(None);
+        };
+        self.create_queue_message(prepared.delivered(), &prepared.filter_source, bot)
+            .map(Some)
+            .map_err(|e| FailedDelivery {
+                bot_name: bot.name.clone(),
+                queue_name: bot.queue.clone(),
+                error: e.to_string(),
+                is_transient: false,
+            })
+    }
+
+    /// Deliver `event` to the bot's HTTP destination.
+    ///
+    /// The delivery has no queue message; it is reported with a fresh
+    /// message ID.
+    async fn deliver_over_http(
+        &self,
+        event: &WrappedEvent,
+        bot: &BotSubscription,
+        http: &HttpDestinationConfig,
+    ) -> Result<SuccessfulDelivery, FailedDelivery> {
+        let failed = |error: String, is_transient: bool| FailedDelivery {
+            bot_name: bot.name.clone(),
+            queue_name: bot.queue.clone(),
+            error,
+            is_transient,
+        };
+        let Some(forwarder) = &self.http_forwarder else {
+            return Err(failed(
+                "HTTP destination is configured but no HTTP forwarder is available".to_string(),
+                false,
+            ));
+        };
+        forwarder
+            .deliver(event, &bot.name, http)
+            .await
+            .map(|_| SuccessfulDelivery {
+                bot_name: bot.name.clone(),
+                queue_name: bot.queue.clone(),
+                message_id: MessageId::new(),
+            })
+            .map_err(|e| failed(e.to_string(), e.is_transient()))
+    }
+
     /// Create queue message from event envelope
     ///
     /// Serializes event to JSON and creates Message with appropriate metadata.
+    /// The filter attributes are read from `filter_source`, the event before
+    /// payload projection and encryption, so consumers can still filter on
+    /// fields the bot does not receive.
     fn create_queue_message(
         &self,
         event: &WrappedEvent,
+        filter_source: &WrappedEvent,
         bot: &BotSubscription,
     ) -> Result<Message, QueueDeliveryError> {
         // Serialize event to JSON
@@ -225,8 +648,38 @@ impl DefaultEventRouter {
         // Add bot name as attribute
         message = message.with_attribute("bot_name".to_string(), bot.name.as_str().to_string());
 
-        // Add event type as attribute
-        message = message.with_attribute("event_type".to_string(), event.event_type.clone());
+        // Add event type, action, repository and tenant for consumer-side
+        // filtering
+        for (name, value) in event_filter_attributes(filter_source) {
+            message = message.with_attribute(name.to_string(), value);
+        }
+
+        // Name the field encryption key so consumers can select it
+        if let Some(ref encryption) = bot.encryption {
+            message = message.with_attribute(
+                "field_encryption_key".to_string(),
+                encryption.key_secret.clone(),
+            );
+        }
+
+        // Add session epoch so consumers can discard stale work without
+        // deserializing the body
+        if event.session_id.is_some() {
+            message = message
+                .with_attribute("session_epoch".to_string(), event.session_epoch.to_string());
+        }
+
+        // Add the session sequence so consumers can detect events that
+        // arrive out of order
+        if let Some(sequence) = event.session_sequence {
+            message = message.with_attribute("session_sequence".to_string(), sequence.to_string());
+        }
+
+        // Add the trace context so consumers can continue the trace
+        if let Some(ref trace_parent) = event.trace_parent {
+            message =
+                message.with_attribute(TRACE_PARENT_ATTRIBUTE.to_string(), trace_parent.clone());
+        }
 
         Ok(message)
     }
@@ -247,11 +700,29 @@ impl DefaultEventRouter {
             Err(QueueDeliveryError::PartialDelivery {
                 successful: result.successful.len(),
                 failed: result.failed.len(),
+                result: result.clone(),
             })
         }
     }
 }
 
+/// An event as one bot receives it.
+struct BotEvent<'a> {
+    /// The event after the bot's transform, read for filter attributes.
+    filter_source: Cow<'a, WrappedEvent>,
+
+    /// The projected or encrypted event, when it differs from
+    /// `filter_source`.
+    reshaped: Option<WrappedEvent>,
+}
+
+impl BotEvent<'_> {
+    /// The event sent to the bot.
+    fn delivered(&self) -> &WrappedEvent {
+        self.reshaped.as_ref().unwrap_or(&self.filter_source)
+    }
+}
+
 impl Default for DefaultEventRouter {
     fn default() -> Self {
         Self::new()
@@ -342,24 +813,66 @@ impl EventRouter for DefaultEventRouter {
                 }
             };
 
-            // Create message for this bot
-            let message = match self.create_queue_message(event, bot) {
-                Ok(msg) => msg,
-                Err(e) => {
-                    // Serialization failure - permanent error
-                    result.failed.push(FailedDelivery {
-                        bot_name: bot.name.clone(),
-                        queue_name: bot.queue.clone(),
-                        error: e.to_string(),
-                        is_transient: false,
-                    });
+            // Transform, project and encrypt the event for this bot
+            let prepared = match self.prepare_for_bot(event, bot).await {
+                Ok(Some(prepared)) => prepared,
+                Ok(None) => continue,
+                Err(failure) => {
+                    result.failed.push(failure);
                     continue;
                 }
             };
 
+            // Push to the bot's HTTP destination instead of its queue
+            if let Some(ref http) = bot.http {
+                let log_context = event.log_context().with_bot(&bot.name);
+                match self
+                    .deliver_over_http(prepared.delivered(), bot, http)
+                    .await
+                {
+                    Ok(delivery) => {
+                        event_debug!(log_context, url = %http.url, "Event pushed to bot endpoint");
+                        result.successful.push(delivery);
+                    }
+                    Err(failure) => {
+                        event_warn!(
+                            log_context,
+                            url = %http.url,
+                            error = %failure.error,
+                            is_transient = failure.is_transient,
+                            "Failed to push event to bot endpoint"
+                        );
+                        result.failed.push(failure);
+                    }
+                }
+                continue;
+            }
+
+            // Create message for this bot
+            let message =
+                match self.create_queue_message(prepared.delivered(), &prepared.filter_source, bot)
+                {
+                    Ok(msg) => msg,
+                    Err(e) => {
+                        // Serialization failure - permanent error
+                        result.failed.push(FailedDelivery {
+                            bot_name: bot.name.clone(),
+                            queue_name: bot.queue.clone(),
+                            error: e.to_string(),
+                            is_transient: false,
+                        });
+                        continue;
+                    }
+                };
+
             // Send message to queue
+            let log_context = event
+                .log_context()
+                .with_bot(&bot.name)
+                .with_queue(&bot.queue);
             match queue_client.send_message(&queue_name, message).await {
                 Ok(message_id) => {
+                    event_debug!(log_context, message_id = ?message_id, "Event sent to bot queue");
                     result.successful.push(SuccessfulDelivery {
                         bot_name: bot.name.clone(),
                         queue_name: bot.queue.clone(),
@@ -367,6 +880,12 @@ impl EventRouter for DefaultEventRouter {
                     });
                 }
                 Err(queue_error) => {
+                    event_warn!(
+                        log_context,
+                        error = %queue_error,
+                        is_transient = queue_error.is_transient(),
+                        "Failed to send event to bot queue"
+                    );
                     result.failed.push(FailedDelivery {
                         bot_name: bot.name.clone(),
                         queue_name: bot.queue.clone(),
@@ -192,6 +192,11 @@ fn create_test_bot(name: &str, queue: &str, ordered: bool) -> BotSubscription {
         ordered,
         repository_filter: None,
         config: BotSpecificConfig::new(),
+        encryption: None,
+        transform: None,
+        projection: None,
+        http: None,
+        sender_filter: None,
     }
 }
 
@@ -262,6 +267,7 @@ fn test_queue_delivery_error_transient_classification() {
     let error = QueueDeliveryError::PartialDelivery {
         successful: 1,
         failed: 1,
+        result: DeliveryResult::new(EventId::new()),
     };
     assert!(error.is_transient());
     assert!(error.should_retry());
@@ -370,6 +376,11 @@ async fn test_route_event_no_bot_subscriptions_match() {
         ordered: false,
         repository_filter: None,
         config: BotSpecificConfig::new(),
+        encryption: None,
+        transform: None,
+        projection: None,
+        http: None,
+        sender_filter: None,
     };
 
     let config = create_test_config(vec![bot]);
@@ -436,9 +447,14 @@ async fn test_route_event_partial_delivery_failure() {
 
     assert!(result.is_err());
     match result.unwrap_err() {
-        QueueDeliveryError::PartialDelivery { successful, failed } => {
+        QueueDeliveryError::PartialDelivery {
+            successful,
+            failed,
+            result,
+        } => {
             assert_eq!(successful, 1);
             assert_eq!(failed, 1);
+            assert_eq!(result.failed[0].bot_name.as_str(), "bot2");
         }
         other => panic!("Expected PartialDelivery, got {:?}", other),
     }
@@ -548,6 +564,198 @@ async fn test_route_event_message_attributes_include_metadata() {
     );
 }
 
+/// Action, repository and tenant are set so consumers can filter on them.
+#[tokio::test]
+async fn test_route_event_message_attributes_support_filtering() {
+    let router = DefaultEventRouter::new();
+    let event = create_test_event();
+    let bot = create_test_bot("test-bot", "queue-keeper-test-bot", false);
+    let config = create_test_config(vec![bot]);
+    let queue_client = MockQueueClient::new();
+
+    router
+        .route_event(&event, &config, &queue_client)
+        .await
+        .expect("Routing should succeed");
+
+    let messages = queue_client.get_sent_messages();
+    let (_queue, message) = &messages[0];
+    assert_eq!(
+        message.attributes.get(ATTRIBUTE_ACTION),
+        Some(&"opened".to_string())
+    );
+    assert_eq!(
+        message.attributes.get(ATTRIBUTE_REPOSITORY),
+        Some(&"owner/test-repo".to_string())
+    );
+    assert_eq!(
+        message.attributes.get(ATTRIBUTE_TENANT),
+        Some(&"github".to_string())
+    );
+}
+
+/// The event's trace context is passed on so consumers can continue the
+/// trace.
+#[tokio::test]
+async fn test_route_event_message_attributes_carry_trace_parent() {
+    let router = DefaultEventRouter::new();
+    let bot = create_test_bot("test-bot", "queue-keeper-test-bot", false);
+    let config = create_test_config(vec![bot]);
+    let queue_client = MockQueueClient::new();
+
+    let untraced = create_test_event();
+    let mut traced = create_test_event();
+    traced.trace_parent =
+        Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string());
+    for event in [&untraced, &traced] {
+        router
+            .route_event(event, &config, &queue_client)
+            .await
+            .expect("Routing should succeed");
+    }
+
+    let messages = queue_client.get_sent_messages();
+    assert!(!messages[0]
+        .1
+        .attributes
+        .contains_key(TRACE_PARENT_ATTRIBUTE));
+    assert_eq!(
+        messages[1].1.attributes.get(TRACE_PARENT_ATTRIBUTE),
+        traced.trace_parent.as_ref()
+    );
+}
+
+/// Attributes without a value for the event are omitted.
+#[test]
+fn test_event_filter_attributes_omit_unknown_values() {
+    let event = WrappedEvent::new(
+        "jira".to_string(),
+        "issue_updated".to_string(),
+        None,
+        None,
+        serde_json::json!({}),
+        None,
+    );
+
+    let attributes = event_filter_attributes(&event);
+
+    assert_eq!(
+        attributes,
+        vec![
+            (ATTRIBUTE_EVENT_TYPE, "issue_updated".to_string()),
+            (ATTRIBUTE_TENANT, "jira".to_string()),
+        ]
+    );
+}
+
+/// Direct-mode payloads carry the provider and content type.
+#[test]
+fn test_direct_filter_attributes() {
+    let metadata = crate::webhook::DirectQueueMetadata::new("gitlab", "application/json", None);
+
+    let attributes = direct_filter_attributes(&metadata);
+
+    assert!(attributes.contains(&("provider_id", "gitlab".to_string())));
+    assert!(attributes.contains(&("content_type", "application/json".to_string())));
+    assert!(attributes.contains(&(ATTRIBUTE_TENANT, "gitlab".to_string())));
+}
+
+/// The session sequence is sent as a message attribute when assigned.
+#[tokio::test]
+async fn test_route_event_sets_session_sequence_attribute() {
+    let router = DefaultEventRouter::new();
+    let bot = create_test_bot("test-bot", "queue-keeper-test-bot", true);
+    let config = create_test_config(vec![bot]);
+    let queue_client = MockQueueClient::new();
+
+    router
+        .route_event(&create_test_event(), &config, &queue_client)
+        .await
+        .expect("Routing should succeed");
+    router
+        .route_event(
+            &create_test_event().with_session_sequence(4),
+            &config,
+            &queue_client,
+        )
+        .await
+        .expect("Routing should succeed");
+
+    let messages = queue_client.get_sent_messages();
+    assert!(!messages[0].1.attributes.contains_key("session_sequence"));
+    assert_eq!(
+        messages[1].1.attributes.get("session_sequence"),
+        Some(&"4".to_string())
+    );
+}
+
+// ============================================================================
+// Session Sequencing Tests
+// ============================================================================
+
+fn session(number: &str) -> crate::SessionId {
+    crate::SessionId::from_parts("owner", "test-repo", "pull_request", number)
+}
+
+/// Tickets of one session get their turn in the order they were taken.
+#[tokio::test]
+async fn test_sequencer_serializes_session_in_ticket_order() {
+    let sequencer = Arc::new(SessionSequencer::new());
+    let first = sequencer.ticket(&session("1"));
+    let second = sequencer.ticket(&session("1"));
+    assert!(second.sequence() > first.sequence());
+    assert_eq!(sequencer.pending(&session("1")), 2);
+
+    let sent = Arc::new(Mutex::new(Vec::new()));
+    let waiting = tokio::spawn({
+        let sent = sent.clone();
+        async move {
+            second.turn().await;
+            sent.lock().unwrap().push(2);
+        }
+    });
+    tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+    assert!(sent.lock().unwrap().is_empty(), "Second ticket must wait");
+
+    first.turn().await;
+    sent.lock().unwrap().push(1);
+    drop(first);
+    waiting.await.unwrap();
+
+    assert_eq!(*sent.lock().unwrap(), vec![1, 2]);
+    assert_eq!(sequencer.active_sessions(), 0);
+}
+
+/// Tickets of different sessions do not wait for each other.
+#[tokio::test]
+async fn test_sequencer_runs_sessions_in_parallel() {
+    let sequencer = Arc::new(SessionSequencer::new());
+    let _held = sequencer.ticket(&session("1"));
+    let other = sequencer.ticket(&session("2"));
+
+    tokio::time::timeout(std::time::Duration::from_secs(1), other.turn())
+        .await
+        .expect("Another session's ticket must not wait");
+    assert_eq!(sequencer.active_sessions(), 2);
+}
+
+/// A ticket dropped before its turn does not stall later tickets.
+#[tokio::test]
+async fn test_sequencer_skips_dropped_ticket() {
+    let sequencer = Arc::new(SessionSequencer::new());
+    let first = sequencer.ticket(&session("1"));
+    let cancelled = sequencer.ticket(&session("1"));
+    let third = sequencer.ticket(&session("1"));
+
+    drop(cancelled);
+    drop(first);
+
+    tokio::time::timeout(std::time::Duration::from_secs(1), third.turn())
+        .await
+        .expect("Dropped tickets must not block the session");
+    assert_eq!(sequencer.pending(&session("1")), 1);
+}
+
 #[tokio::test]
 async fn test_route_event_message_body_contains_serialized_event() {
     let router = DefaultEventRouter::new();
@@ -574,6 +782,306 @@ async fn test_route_event_message_body_contains_serialized_event() {
     assert_eq!(deserialized.event_type, event.event_type);
 }
 
+// ============================================================================
+// Field Encryption Tests
+// ============================================================================
+
+fn create_encrypting_bot() -> BotSubscription {
+    let mut bot = create_test_bot("secure-bot", "queue-keeper-secure-bot", false);
+    bot.encryption = Some(crate::FieldEncryptionConfig {
+        key_secret: "queue-keeper-test-secure-bot".to_string(),
+        fields: vec!["/pull_request".to_string()],
+    });
+    bot
+}
+
+/// A bot with field encryption but no key vault is never sent plaintext.
+#[tokio::test]
+async fn test_route_event_encryption_without_encryptor_fails() {
+    let router = DefaultEventRouter::new();
+    let event = create_test_event();
+    let config = create_test_config(vec![create_encrypting_bot()]);
+    let queue_client = MockQueueClient::new();
+
+    let result = router.route_event(&event, &config, &queue_client).await;
+
+    let err = result.expect_err("Routing should fail without an encryptor");
+    assert!(!err.is_transient());
+    assert_eq!(queue_client.message_count(), 0);
+}
+
+/// Configured fields are encrypted only in the subscribing bot's message.
+#[tokio::test]
+async fn test_route_event_encrypts_fields_for_bot() {
+    use crate::{
+        adapters::InMemoryKeyVaultProvider,
+        field_encryption::ENCRYPTED_FIELD_MARKER,
+        key_vault::{SecretName, SecretValue},
+        FieldEncryptionKey, FieldEncryptor,
+    };
+    use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+    let key_vault = InMemoryKeyVaultProvider::new();
+    key_vault.add_secret(
+        SecretName::new("queue-keeper-test-secure-bot").unwrap(),
+        SecretValue::from_string(STANDARD.encode([3u8; 32])),
+    );
+    let router = DefaultEventRouter::new()
+        .with_field_encryption(Arc::new(FieldEncryptor::new(Arc::new(key_vault))));
+    let event = create_test_event();
+    let plain_bot = create_test_bot("plain-bot", "queue-keeper-plain-bot", false);
+    let config = create_test_config(vec![create_encrypting_bot(), plain_bot]);
+    let queue_client = MockQueueClient::new();
+
+    router
+        .route_event(&event, &config, &queue_client)
+        .await
+        .expect("Routing should succeed");
+
+    let messages = queue_client.get_sent_messages();
+    assert_eq!(messages.len(), 2);
+    for (queue, message) in &messages {
+        let mut delivered: WrappedEvent = serde_json::from_slice(&message.body).unwrap();
+        if queue.as_str() == "queue-keeper-secure-bot" {
+            assert!(delivered.payload["pull_request"]
+                .get(ENCRYPTED_FIELD_MARKER)
+                .is_some());
+            assert_eq!(
+                message.attributes.get("field_encryption_key"),
+                Some(&"queue-keeper-test-secure-bot".to_string())
+            );
+            let key = FieldEncryptionKey::from_bytes([3u8; 32]);
+            crate::decrypt_event(&mut delivered, &key).unwrap();
+        } else {
+            assert!(!message.attributes.contains_key("field_encryption_key"));
+        }
+        assert_eq!(delivered.payload, event.payload);
+    }
+}
+
+// ============================================================================
+// Transform Tests
+// ============================================================================
+
+/// Transform engine returning a fixed decision.
+struct FixedTransform(&'static str);
+
+impl crate::wasm_transform::TransformEngine for FixedTransform {
+    fn run(&self, _input: &[u8]) -> Result<Vec<u8>, crate::TransformError> {
+        Ok(self.0.as_bytes().to_vec())
+    }
+}
+
+fn create_transforming_bot(name: &str) -> BotSubscription {
+    let mut bot = create_test_bot(name, &format!("queue-keeper-{}", name), false);
+    bot.transform = Some(crate::WasmTransformConfig::new(format!("{}.wasm", name)));
+    bot
+}
+
+/// Transforms can skip a bot or replace the payload it receives.
+#[tokio::test]
+async fn test_route_event_applies_transforms() {
+    let skip_bot = create_transforming_bot("skip-bot");
+    let reshape_bot = create_transforming_bot("reshape-bot");
+    let mut transforms = crate::TransformRegistry::new();
+    for (bot, output) in [
+        (&skip_bot, r#"{"action":"skip"}"#),
+        (
+            &reshape_bot,
+            r#"{"action":"deliver","payload":{"number":1}}"#,
+        ),
+    ] {
+        transforms.register(
+            &bot.name,
+            bot.transform.clone().unwrap(),
+            Box::new(FixedTransform(output)),
+        );
+    }
+    let router = DefaultEventRouter::new().with_transforms(Arc::new(transforms));
+    let event = create_test_event();
+    let config = create_test_config(vec![skip_bot, reshape_bot]);
+    let queue_client = MockQueueClient::new();
+
+    let result = router
+        .route_event(&event, &config, &queue_client)
+        .await
+        .expect("Routing should succeed");
+
+    assert_eq!(result.successful.len(), 1);
+    let messages = queue_client.get_sent_messages();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].0.as_str(), "queue-keeper-reshape-bot");
+    let delivered: WrappedEvent = serde_json::from_slice(&messages[0].1.body).unwrap();
+    assert_eq!(delivered.payload, serde_json::json!({ "number": 1 }));
+    assert_eq!(delivered.event_id, event.event_id);
+}
+
+/// A bot with a transform but no loaded runtime is never sent the event.
+#[tokio::test]
+async fn test_route_event_transform_without_registry_fails() {
+    let router = DefaultEventRouter::new();
+    let event = create_test_event();
+    let config = create_test_config(vec![create_transforming_bot("custom-bot")]);
+    let queue_client = MockQueueClient::new();
+
+    let err = router
+        .route_event(&event, &config, &queue_client)
+        .await
+        .expect_err("Routing should fail without a transform registry");
+    assert!(!err.is_transient());
+    assert_eq!(queue_client.message_count(), 0);
+}
+
+// ============================================================================
+// Payload Projection Tests
+// ============================================================================
+
+/// Only the projecting bot receives a trimmed payload; other bots and the
+/// repository filter still see the whole event.
+#[tokio::test]
+async fn test_route_event_projects_payload_for_bot() {
+    let mut projecting_bot = create_test_bot("slim-bot", "queue-keeper-slim-bot", false);
+    projecting_bot.repository_filter = Some(crate::bot_config::RepositoryFilter::Exact {
+        owner: "owner".to_string(),
+        name: "test-repo".to_string(),
+    });
+    projecting_bot.projection = Some(crate::payload_projection::PayloadProjectionConfig {
+        fields: vec!["/pull_request/number".to_string()],
+        ..Default::default()
+    });
+    let plain_bot = create_test_bot("plain-bot", "queue-keeper-plain-bot", false);
+    let router = DefaultEventRouter::new();
+    let event = create_test_event();
+    let config = create_test_config(vec![projecting_bot, plain_bot]);
+    let queue_client = MockQueueClient::new();
+
+    router
+        .route_event(&event, &config, &queue_client)
+        .await
+        .expect("Routing should succeed");
+
+    let messages = queue_client.get_sent_messages();
+    assert_eq!(messages.len(), 2);
+    for (queue, message) in &messages {
+        let delivered: WrappedEvent = serde_json::from_slice(&message.body).unwrap();
+        assert_eq!(delivered.event_id, event.event_id);
+        if queue.as_str() == "queue-keeper-slim-bot" {
+            assert_eq!(
+                delivered.payload,
+                serde_json::json!({ "pull_request": { "number": 1 } })
+            );
+            assert_eq!(
+                message.attributes.get("repository"),
+                Some(&"owner/test-repo".to_string())
+            );
+        } else {
+            assert_eq!(delivered.payload, event.payload);
+        }
+    }
+}
+
+// ============================================================================
+// HTTP Destination Tests
+// ============================================================================
+
+/// HTTP transport recording the bodies it posts and answering with a fixed
+/// status.
+struct RecordingTransport {
+    status: u16,
+    bodies: Mutex<Vec<bytes::Bytes>>,
+}
+
+#[async_trait]
+impl crate::HttpTransport for RecordingTransport {
+    async fn post(
+        &self,
+        _url: &str,
+        _headers: &[(String, String)],
+        body: bytes::Bytes,
+        _timeout: std::time::Duration,
+    ) -> Result<u16, String> {
+        self.bodies.lock().unwrap().push(body);
+        Ok(self.status)
+    }
+}
+
+fn create_http_bot() -> BotSubscription {
+    let mut bot = create_test_bot("hook-bot", "queue-keeper-hook-bot", false);
+    bot.http = Some(crate::HttpDestinationConfig::new(
+        "https://hooks.example.com/events",
+        "queue-keeper-test-hook-bot",
+    ));
+    bot
+}
+
+fn http_router(transport: Arc<RecordingTransport>) -> DefaultEventRouter {
+    use crate::{
+        adapters::InMemoryKeyVaultProvider,
+        key_vault::{SecretName, SecretValue},
+        HttpForwarder,
+    };
+
+    let key_vault = InMemoryKeyVaultProvider::new();
+    key_vault.add_secret(
+        SecretName::new("queue-keeper-test-hook-bot").unwrap(),
+        SecretValue::from_string("hook-secret".to_string()),
+    );
+    DefaultEventRouter::new()
+        .with_http_forwarder(Arc::new(HttpForwarder::new(transport, Arc::new(key_vault))))
+}
+
+/// HTTP bots are pushed to instead of their queue; queue bots are unaffected.
+#[tokio::test]
+async fn test_route_event_pushes_to_http_destination() {
+    let transport = Arc::new(RecordingTransport {
+        status: 200,
+        bodies: Mutex::default(),
+    });
+    let router = http_router(transport.clone());
+    let event = create_test_event();
+    let queue_bot = create_test_bot("queue-bot", "queue-keeper-queue-bot", false);
+    let config = create_test_config(vec![create_http_bot(), queue_bot]);
+    let queue_client = MockQueueClient::new();
+
+    let result = router
+        .route_event(&event, &config, &queue_client)
+        .await
+        .expect("Routing should succeed");
+
+    assert_eq!(result.successful.len(), 2);
+    let messages = queue_client.get_sent_messages();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].0.as_str(), "queue-keeper-queue-bot");
+
+    let bodies = transport.bodies.lock().unwrap();
+    assert_eq!(bodies.len(), 1);
+    let delivered: WrappedEvent = serde_json::from_slice(&bodies[0]).unwrap();
+    assert_eq!(delivered.event_id, event.event_id);
+}
+
+/// Rejected pushes and bots without a forwarder fail permanently, and never
+/// fall back to the queue.
+#[tokio::test]
+async fn test_route_event_http_failures() {
+    let event = create_test_event();
+    let config = create_test_config(vec![create_http_bot()]);
+
+    let transport = Arc::new(RecordingTransport {
+        status: 410,
+        bodies: Mutex::default(),
+    });
+    for router in [http_router(transport), DefaultEventRouter::new()] {
+        let queue_client = MockQueueClient::new();
+        let err = router
+            .route_event(&event, &config, &queue_client)
+            .await
+            .expect_err("Routing should fail");
+
+        assert!(!err.is_transient());
+        assert_eq!(queue_client.message_count(), 0);
+    }
+}
+
 // ============================================================================
 // Mock Audit Logger for Testing
 // ============================================================================
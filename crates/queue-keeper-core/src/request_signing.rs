@@ -0,0 +1,269 @@
+//! # Request Signing
+//!
+//! Signs the HTTP requests Queue-Keeper pushes to receivers (gateway
+//! destinations) so they can authenticate them, and verifies those
+//! signatures on the receiving side. The scheme is published in
+//! `docs/request-signing.md`.
+//!
+//! Every signed request carries three headers:
+//!
+//! | Header | Value |
+//! |--------|-------|
+//! | `X-Queue-Keeper-Key-Id` | [`key_id`] of the signing key |
+//! | `X-Queue-Keeper-Timestamp` | Unix time of signing, in seconds |
+//! | `X-Queue-Keeper-Signature` | `v1=` + hex HMAC-SHA256 of the signed content |
+//!
+//! The signed content binds the timestamp and the event ID to the raw body:
+//!
+//! ```text
+//! v1:{timestamp}:{event_id}:{body}
+//! ```
+//!
+//! Key IDs are derived from the key itself, so a key rotated in Key Vault
+//! gets a new ID as soon as the sender reads it. A receiver holding both the
+//! previous and the new key ([`RequestVerifier::new`]) picks the right one
+//! by ID and keeps accepting requests throughout the rotation.
+//!
+//! ```
+//! use queue_keeper_core::request_signing::{RequestSignature, RequestVerifier};
+//!
+//! let body = br#"{"action":"opened"}"#;
+//! let signed = RequestSignature::sign(b"new-key", 1_700_000_000, "01J0EVENT", body);
+//!
+//! let verifier = RequestVerifier::new(["old-key", "new-key"]);
+//! assert!(verifier
+//!     .verify_at(
+//!         &signed.key_id,
+//!         &signed.timestamp.to_string(),
+//!         &signed.signature,
+//!         "01J0EVENT",
+//!         body,
+//!         1_700_000_060,
+//!     )
+//!     .is_ok());
+//! ```
+
+use crate::key_vault::SecretValue;
+use hmac::{Hmac, KeyInit, Mac};
+use sha2::Sha256;
+use std::{fmt, time::Duration};
+
+/// Header carrying the ID of the signing key.
+pub const KEY_ID_HEADER: &str = "x-queue-keeper-key-id";
+
+/// Header carrying the signing time as Unix seconds.
+pub const TIMESTAMP_HEADER: &str = "x-queue-keeper-timestamp";
+
+/// Header carrying the versioned signature.
+pub const SIGNATURE_HEADER: &str = "x-queue-keeper-signature";
+
+/// Version of the signing scheme, prefixed to the signed content and the
+/// signature.
+pub const SIGNATURE_VERSION: &str = "v1";
+
+/// How far the timestamp of a request may be from the receiver's clock.
+pub const DEFAULT_TOLERANCE: Duration = Duration::from_secs(300);
+
+/// Context string of the key ID derivation.
+const KEY_ID_CONTEXT: &[u8] = b"queue-keeper-key-id";
+
+/// Hex characters of a key ID.
+const KEY_ID_LEN: usize = 16;
+
+// ============================================================================
+// Signing
+// ============================================================================
+
+/// Derive the ID of a signing key: the first 16 hex characters of
+/// HMAC-SHA256 of `"queue-keeper-key-id"` keyed with the key.
+pub fn key_id(key: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(KEY_ID_CONTEXT);
+    let mut id = hex::encode(mac.finalize().into_bytes());
+    id.truncate(KEY_ID_LEN);
+    id
+}
+
+/// HMAC of the signed content of a request.
+fn content_mac(key: &[u8], timestamp: &str, event_id: &str, body: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}:{}:{}:", SIGNATURE_VERSION, timestamp, event_id).as_bytes());
+    mac.update(body);
+    mac
+}
+
+/// The signature headers of one request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestSignature {
+    /// Value of [`KEY_ID_HEADER`].
+    pub key_id: String,
+
+    /// Value of [`TIMESTAMP_HEADER`].
+    pub timestamp: i64,
+
+    /// Value of [`SIGNATURE_HEADER`] (`v1=<hex>`).
+    pub signature: String,
+}
+
+impl RequestSignature {
+    /// Sign `body` of event `event_id` with `key` at Unix time `timestamp`.
+    pub fn sign(key: &[u8], timestamp: i64, event_id: &str, body: &[u8]) -> Self {
+        let mac = content_mac(key, &timestamp.to_string(), event_id, body);
+        Self {
+            key_id: key_id(key),
+            timestamp,
+            signature: format!(
+                "{}={}",
+                SIGNATURE_VERSION,
+                hex::encode(mac.finalize().into_bytes())
+            ),
+        }
+    }
+
+    /// Header names and values to attach to the request.
+    pub fn headers(&self) -> [(&'static str, String); 3] {
+        [
+            (KEY_ID_HEADER, self.key_id.clone()),
+            (TIMESTAMP_HEADER, self.timestamp.to_string()),
+            (SIGNATURE_HEADER, self.signature.clone()),
+        ]
+    }
+}
+
+// ============================================================================
+// Verification
+// ============================================================================
+
+/// Reasons a signed request is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum RequestSignatureError {
+    #[error("No verification key with ID '{key_id}'")]
+    UnknownKey { key_id: String },
+
+    #[error("Invalid request timestamp '{timestamp}'")]
+    InvalidTimestamp { timestamp: String },
+
+    #[error("Request timestamp {timestamp} is outside the allowed clock skew")]
+    StaleTimestamp { timestamp: i64 },
+
+    #[error("Signature does not match the request")]
+    Mismatch,
+}
+
+/// Verifies signed requests against a set of keys.
+///
+/// Hold the current key and, during a rotation, the previous one; requests
+/// are matched to a key by its ID.
+#[derive(Clone)]
+pub struct RequestVerifier {
+    keys: Vec<(String, SecretValue)>,
+    tolerance: Duration,
+}
+
+impl RequestVerifier {
+    /// Create a verifier accepting requests signed with any of `keys`.
+    pub fn new<I, K>(keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        Self {
+            keys: keys
+                .into_iter()
+                .map(|key| {
+                    let key = SecretValue::from_string(key.into());
+                    (key_id(key.expose_bytes()), key)
+                })
+                .collect(),
+            tolerance: DEFAULT_TOLERANCE,
+        }
+    }
+
+    /// Accept timestamps up to `tolerance` from the local clock.
+    pub fn with_tolerance(mut self, tolerance: Duration) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// IDs of the keys this verifier accepts.
+    pub fn key_ids(&self) -> Vec<&str> {
+        self.keys.iter().map(|(id, _)| id.as_str()).collect()
+    }
+
+    /// Verify a request from the values of its signature headers, its
+    /// `X-Queue-Keeper-Event-Id` header and its raw body.
+    ///
+    /// # Errors
+    /// Returns the first check that fails: the key ID, the timestamp or the
+    /// signature.
+    pub fn verify(
+        &self,
+        key_id: &str,
+        timestamp: &str,
+        signature: &str,
+        event_id: &str,
+        body: &[u8],
+    ) -> Result<(), RequestSignatureError> {
+        self.verify_at(
+            key_id,
+            timestamp,
+            signature,
+            event_id,
+            body,
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    /// [`Self::verify`] against the Unix time `now`.
+    pub fn verify_at(
+        &self,
+        key_id: &str,
+        timestamp: &str,
+        signature: &str,
+        event_id: &str,
+        body: &[u8],
+        now: i64,
+    ) -> Result<(), RequestSignatureError> {
+        let (_, key) = self
+            .keys
+            .iter()
+            .find(|(id, _)| id == key_id)
+            .ok_or_else(|| RequestSignatureError::UnknownKey {
+                key_id: key_id.to_string(),
+            })?;
+
+        let signed_at: i64 =
+            timestamp
+                .parse()
+                .map_err(|_| RequestSignatureError::InvalidTimestamp {
+                    timestamp: timestamp.to_string(),
+                })?;
+        if now.abs_diff(signed_at) > self.tolerance.as_secs() {
+            return Err(RequestSignatureError::StaleTimestamp {
+                timestamp: signed_at,
+            });
+        }
+
+        let expected = signature
+            .strip_prefix(SIGNATURE_VERSION)
+            .and_then(|rest| rest.strip_prefix('='))
+            .and_then(|digest| hex::decode(digest).ok())
+            .ok_or(RequestSignatureError::Mismatch)?;
+        content_mac(key.expose_bytes(), timestamp, event_id, body)
+            .verify_slice(&expected)
+            .map_err(|_| RequestSignatureError::Mismatch)
+    }
+}
+
+impl fmt::Debug for RequestVerifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestVerifier")
+            .field("key_ids", &self.key_ids())
+            .field("tolerance", &self.tolerance)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+#[path = "request_signing_tests.rs"]
+mod tests;
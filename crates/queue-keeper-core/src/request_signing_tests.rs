@@ -0,0 +1,133 @@
+//! Tests for request signing.
+
+use super::*;
+
+const NOW: i64 = 1_700_000_000;
+const EVENT_ID: &str = "01J00000000000000000000001";
+const BODY: &[u8] = br#"{"action":"opened"}"#;
+
+fn verify(
+    verifier: &RequestVerifier,
+    signed: &RequestSignature,
+    event_id: &str,
+    body: &[u8],
+    now: i64,
+) -> Result<(), RequestSignatureError> {
+    verifier.verify_at(
+        &signed.key_id,
+        &signed.timestamp.to_string(),
+        &signed.signature,
+        event_id,
+        body,
+        now,
+    )
+}
+
+/// Verify that key IDs are stable, short and differ between keys.
+#[test]
+fn test_key_id_derived_from_key() {
+    assert_eq!(key_id(b"key-a"), key_id(b"key-a"));
+    assert_ne!(key_id(b"key-a"), key_id(b"key-b"));
+    assert_eq!(key_id(b"key-a").len(), 16);
+    assert!(!key_id(b"key-a").contains("key-a"));
+}
+
+/// Verify that a signed request carries the documented headers.
+#[test]
+fn test_signature_headers() {
+    let signed = RequestSignature::sign(b"key", NOW, EVENT_ID, BODY);
+
+    let headers = signed.headers();
+    assert_eq!(headers[0], (KEY_ID_HEADER, key_id(b"key")));
+    assert_eq!(headers[1], (TIMESTAMP_HEADER, NOW.to_string()));
+    assert_eq!(headers[2].0, SIGNATURE_HEADER);
+    assert!(headers[2].1.starts_with("v1="));
+    assert_eq!(headers[2].1.len(), "v1=".len() + 64);
+}
+
+/// Verify that a request signed with either key of a rotation verifies.
+#[test]
+fn test_verify_accepts_current_and_previous_key() {
+    let verifier = RequestVerifier::new(["previous-key", "current-key"]);
+
+    for key in [b"previous-key".as_slice(), b"current-key".as_slice()] {
+        let signed = RequestSignature::sign(key, NOW, EVENT_ID, BODY);
+        assert_eq!(verify(&verifier, &signed, EVENT_ID, BODY, NOW + 10), Ok(()));
+    }
+}
+
+/// Verify that requests signed with a retired key are rejected by ID.
+#[test]
+fn test_verify_rejects_unknown_key() {
+    let verifier = RequestVerifier::new(["current-key"]);
+    let signed = RequestSignature::sign(b"retired-key", NOW, EVENT_ID, BODY);
+
+    assert_eq!(
+        verify(&verifier, &signed, EVENT_ID, BODY, NOW),
+        Err(RequestSignatureError::UnknownKey {
+            key_id: key_id(b"retired-key")
+        })
+    );
+}
+
+/// Verify that a changed body, event ID or timestamp breaks the signature.
+#[test]
+fn test_verify_rejects_tampering() {
+    let verifier = RequestVerifier::new(["key"]);
+    let signed = RequestSignature::sign(b"key", NOW, EVENT_ID, BODY);
+
+    assert_eq!(
+        verify(&verifier, &signed, EVENT_ID, b"{}", NOW),
+        Err(RequestSignatureError::Mismatch)
+    );
+    assert_eq!(
+        verify(&verifier, &signed, "01J00000000000000000000002", BODY, NOW),
+        Err(RequestSignatureError::Mismatch)
+    );
+    let moved = RequestSignature {
+        timestamp: NOW + 1,
+        ..signed.clone()
+    };
+    assert_eq!(
+        verify(&verifier, &moved, EVENT_ID, BODY, NOW),
+        Err(RequestSignatureError::Mismatch)
+    );
+    let garbled = RequestSignature {
+        signature: "sha256=abc".to_string(),
+        ..signed
+    };
+    assert_eq!(
+        verify(&verifier, &garbled, EVENT_ID, BODY, NOW),
+        Err(RequestSignatureError::Mismatch)
+    );
+}
+
+/// Verify that timestamps outside the tolerance are rejected.
+#[test]
+fn test_verify_rejects_stale_timestamps() {
+    let verifier = RequestVerifier::new(["key"]).with_tolerance(Duration::from_secs(60));
+    let signed = RequestSignature::sign(b"key", NOW, EVENT_ID, BODY);
+
+    assert_eq!(verify(&verifier, &signed, EVENT_ID, BODY, NOW + 60), Ok(()));
+    assert_eq!(
+        verify(&verifier, &signed, EVENT_ID, BODY, NOW + 61),
+        Err(RequestSignatureError::StaleTimestamp { timestamp: NOW })
+    );
+    assert_eq!(
+        verify(&verifier, &signed, EVENT_ID, BODY, NOW - 61),
+        Err(RequestSignatureError::StaleTimestamp { timestamp: NOW })
+    );
+    assert_eq!(
+        verifier.verify_at(
+            &signed.key_id,
+            "yesterday",
+            &signed.signature,
+            EVENT_ID,
+            BODY,
+            NOW
+        ),
+        Err(RequestSignatureError::InvalidTimestamp {
+            timestamp: "yesterday".to_string()
+        })
+    );
+}
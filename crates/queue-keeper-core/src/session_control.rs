@@ -0,0 +1,250 @@
+//! # Session Control
+//!
+//! Pausing a session holds back its events without affecting any other
+//! session, for instance while the bot handling one pull request is being
+//! fixed. The [`SessionControlStore`] records the paused sessions and
+//! buffers their events:
+//!
+//! - [`SessionControlStore::pause`] marks a session paused. The delivery
+//!   pipeline offers every event to [`SessionControlStore::buffer`] before
+//!   routing it, and the events of a paused session are kept in arrival
+//!   order instead of being delivered.
+//! - [`SessionControlStore::resume`] marks the session resuming. The caller
+//!   takes the buffered events with [`SessionControlStore::next_buffered`]
+//!   and delivers each before taking the next. Events arriving meanwhile are
+//!   buffered behind them, so the session keeps its order; the session is
+//!   unpaused once its buffer is empty.
+//! - [`SessionControlStore::discard`] drops the buffered events of a
+//!   session, for a session reset. The session stays paused.
+//!
+//! Events without a session ID are never buffered. The store is held in
+//! memory: buffered events are lost when the service stops, and each
+//! replica buffers only the events it received.
+
+use crate::{webhook::WrappedEvent, EventId, SessionId, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+};
+
+// ============================================================================
+// Status Types
+// ============================================================================
+
+/// Delivery state of a paused session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionPauseState {
+    /// Events are buffered.
+    Paused,
+
+    /// Buffered events are being delivered; new events are still buffered
+    /// behind them.
+    Resuming,
+}
+
+/// Pause state of one session, as reported by the admin API.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionPauseStatus {
+    pub session_id: SessionId,
+    pub state: SessionPauseState,
+    /// When the session was paused.
+    pub paused_at: Timestamp,
+    /// Principal that paused the session.
+    pub paused_by: String,
+    /// Why the session was paused.
+    pub reason: String,
+    /// Events waiting for the session to resume.
+    pub buffered_events: usize,
+    /// When the oldest waiting event was buffered.
+    pub oldest_buffered_at: Option<Timestamp>,
+}
+
+/// An event waiting for its session to resume.
+#[derive(Debug, Clone)]
+pub struct BufferedEvent {
+    /// When the event was buffered.
+    pub buffered_at: Timestamp,
+    pub event: WrappedEvent,
+}
+
+#[derive(Debug)]
+struct PausedSession {
+    paused_at: Timestamp,
+    paused_by: String,
+    reason: String,
+    resuming: bool,
+    buffered: VecDeque<BufferedEvent>,
+}
+
+impl PausedSession {
+    fn status(&self, session_id: &SessionId) -> SessionPauseStatus {
+        SessionPauseStatus {
+            session_id: session_id.clone(),
+            state: if self.resuming {
+                SessionPauseState::Resuming
+            } else {
+                SessionPauseState::Paused
+            },
+            paused_at: self.paused_at,
+            paused_by: self.paused_by.clone(),
+            reason: self.reason.clone(),
+            buffered_events: self.buffered.len(),
+            oldest_buffered_at: self.buffered.front().map(|oldest| oldest.buffered_at),
+        }
+    }
+}
+
+// ============================================================================
+// Store
+// ============================================================================
+
+/// Paused sessions and their buffered events.
+#[derive(Debug, Default)]
+pub struct SessionControlStore {
+    sessions: Mutex<HashMap<SessionId, PausedSession>>,
+}
+
+impl SessionControlStore {
+    /// Create a store with no paused sessions.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Pause a session.
+    ///
+    /// Pausing a session that is already paused keeps its original pause
+    /// details; pausing a resuming session stops the release and keeps the
+    /// remaining events buffered.
+    pub fn pause(
+        &self,
+        session_id: &SessionId,
+        paused_by: &str,
+        reason: &str,
+    ) -> SessionPauseStatus {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions
+            .entry(session_id.clone())
+            .or_insert_with(|| PausedSession {
+                paused_at: Timestamp::now(),
+                paused_by: paused_by.to_string(),
+                reason: reason.to_string(),
+                resuming: false,
+                buffered: VecDeque::new(),
+            });
+        session.resuming = false;
+        session.status(session_id)
+    }
+
+    /// Mark a paused session as resuming.
+    ///
+    /// Returns `None` if the session is not paused. The caller delivers the
+    /// buffered events it takes with [`next_buffered`](Self::next_buffered).
+    pub fn resume(&self, session_id: &SessionId) -> Option<SessionPauseStatus> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.get_mut(session_id)?;
+        session.resuming = true;
+        Some(session.status(session_id))
+    }
+
+    /// Pause state of a session, if it is paused or resuming.
+    pub fn status(&self, session_id: &SessionId) -> Option<SessionPauseStatus> {
+        let sessions = self.sessions.lock().unwrap();
+        sessions
+            .get(session_id)
+            .map(|session| session.status(session_id))
+    }
+
+    /// Pause state of every paused or resuming session, by session ID.
+    pub fn statuses(&self) -> Vec<SessionPauseStatus> {
+        let sessions = self.sessions.lock().unwrap();
+        let mut statuses: Vec<_> = sessions
+            .iter()
+            .map(|(session_id, session)| session.status(session_id))
+            .collect();
+        statuses.sort_by(|a, b| a.session_id.as_str().cmp(b.session_id.as_str()));
+        statuses
+    }
+
+    /// Whether the session is paused or resuming.
+    pub fn is_paused(&self, session_id: &SessionId) -> bool {
+        self.sessions.lock().unwrap().contains_key(session_id)
+    }
+
+    /// Buffer `event` if its session is paused or resuming.
+    ///
+    /// Returns the event back when its session is not paused, for the
+    /// caller to deliver.
+    pub fn buffer(&self, event: WrappedEvent) -> Option<WrappedEvent> {
+        let Some(session_id) = &event.session_id else {
+            return Some(event);
+        };
+        let mut sessions = self.sessions.lock().unwrap();
+        let Some(session) = sessions.get_mut(session_id) else {
+            return Some(event);
+        };
+        session.buffered.push_back(BufferedEvent {
+            buffered_at: Timestamp::now(),
+            event,
+        });
+        None
+    }
+
+    /// Take the next buffered event of a resuming session.
+    ///
+    /// Returns `None` when the session was paused again, or when its buffer
+    /// is empty; in the latter case the session is unpaused.
+    pub fn next_buffered(&self, session_id: &SessionId) -> Option<WrappedEvent> {
+        let mut sessions = self.sessions.lock().unwrap();
+        let session = sessions.get_mut(session_id)?;
+        if !session.resuming {
+            return None;
+        }
+        let next = session.buffered.pop_front();
+        if next.is_none() {
+            sessions.remove(session_id);
+        }
+        next.map(|buffered| buffered.event)
+    }
+
+    /// Drop the buffered events of a session, leaving it paused.
+    ///
+    /// Returns the IDs of the dropped events, oldest first.
+    pub fn discard(&self, session_id: &SessionId) -> Vec<EventId> {
+        let mut sessions = self.sessions.lock().unwrap();
+        sessions
+            .get_mut(session_id)
+            .map(|session| {
+                session
+                    .buffered
+                    .drain(..)
+                    .map(|buffered| buffered.event.event_id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Unpause a session and drop its buffered events.
+    ///
+    /// Used when there is nowhere to deliver the events to. Returns the IDs
+    /// of the dropped events, oldest first.
+    pub fn remove(&self, session_id: &SessionId) -> Vec<EventId> {
+        self.sessions
+            .lock()
+            .unwrap()
+            .remove(session_id)
+            .map(|session| {
+                session
+                    .buffered
+                    .into_iter()
+                    .map(|buffered| buffered.event.event_id)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+#[path = "session_control_tests.rs"]
+mod tests;
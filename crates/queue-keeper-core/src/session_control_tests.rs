@@ -0,0 +1,124 @@
+//! Tests for session control.
+
+use super::*;
+
+fn session(number: &str) -> SessionId {
+    SessionId::from_parts("owner", "repo", "pull_request", number)
+}
+
+fn event(session_id: Option<SessionId>) -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("synchronize".to_string()),
+        session_id,
+        serde_json::json!({}),
+        None,
+    )
+}
+
+/// Events of other sessions, and events without a session, pass through.
+#[test]
+fn test_only_paused_sessions_buffer() {
+    let store = SessionControlStore::new();
+    store.pause(&session("1"), "on-call", "bot fix");
+
+    assert!(store.buffer(event(Some(session("2")))).is_some());
+    assert!(store.buffer(event(None)).is_some());
+    assert!(store.buffer(event(Some(session("1")))).is_none());
+
+    let status = store.status(&session("1")).unwrap();
+    assert_eq!(status.state, SessionPauseState::Paused);
+    assert_eq!(status.paused_by, "on-call");
+    assert_eq!(status.buffered_events, 1);
+    assert!(status.oldest_buffered_at.is_some());
+    assert_eq!(store.status(&session("2")), None);
+}
+
+/// Resuming releases the buffered events in arrival order, including events
+/// buffered during the release, then unpauses the session.
+#[test]
+fn test_resume_releases_in_order() {
+    let store = SessionControlStore::new();
+    let id = session("1");
+    assert!(store.resume(&id).is_none());
+    store.pause(&id, "on-call", "bot fix");
+
+    let first = event(Some(id.clone()));
+    let second = event(Some(id.clone()));
+    let third = event(Some(id.clone()));
+    let expected = [first.event_id, second.event_id, third.event_id];
+    assert!(store.buffer(first).is_none());
+    assert!(store.buffer(second).is_none());
+
+    // Not resuming yet
+    assert!(store.next_buffered(&id).is_none());
+
+    let status = store.resume(&id).unwrap();
+    assert_eq!(status.state, SessionPauseState::Resuming);
+    assert_eq!(status.buffered_events, 2);
+
+    let mut released = vec![store.next_buffered(&id).unwrap().event_id];
+    assert!(store.buffer(third).is_none());
+    while let Some(event) = store.next_buffered(&id) {
+        released.push(event.event_id);
+    }
+
+    assert_eq!(released, expected);
+    assert!(!store.is_paused(&id));
+    assert!(store.buffer(event(Some(id))).is_some());
+}
+
+/// Pausing a resuming session stops the release.
+#[test]
+fn test_pause_stops_release() {
+    let store = SessionControlStore::new();
+    let id = session("1");
+    store.pause(&id, "on-call", "bot fix");
+    assert!(store.buffer(event(Some(id.clone()))).is_none());
+    assert!(store.buffer(event(Some(id.clone()))).is_none());
+
+    store.resume(&id);
+    assert!(store.next_buffered(&id).is_some());
+    let status = store.pause(&id, "someone-else", "still broken");
+
+    assert_eq!(status.state, SessionPauseState::Paused);
+    assert_eq!(status.paused_by, "on-call");
+    assert_eq!(status.buffered_events, 1);
+    assert!(store.next_buffered(&id).is_none());
+}
+
+/// Discarding drops the buffer but keeps the pause; removing drops both.
+#[test]
+fn test_discard_and_remove() {
+    let store = SessionControlStore::new();
+    let id = session("1");
+    store.pause(&id, "on-call", "bot fix");
+    let buffered = event(Some(id.clone()));
+    let event_id = buffered.event_id;
+    assert!(store.buffer(buffered).is_none());
+
+    assert_eq!(store.discard(&id), vec![event_id]);
+    assert!(store.is_paused(&id));
+    assert_eq!(store.status(&id).unwrap().buffered_events, 0);
+
+    assert!(store.buffer(event(Some(id.clone()))).is_none());
+    assert_eq!(store.remove(&id).len(), 1);
+    assert!(!store.is_paused(&id));
+    assert!(store.discard(&id).is_empty());
+}
+
+/// Statuses are listed by session ID.
+#[test]
+fn test_statuses_are_sorted() {
+    let store = SessionControlStore::new();
+    store.pause(&session("2"), "on-call", "b");
+    store.pause(&session("1"), "on-call", "a");
+
+    let ids: Vec<_> = store
+        .statuses()
+        .into_iter()
+        .map(|status| status.session_id)
+        .collect();
+    assert_eq!(ids, vec![session("1"), session("2")]);
+}
@@ -0,0 +1,493 @@
+//! # Session Tracking
+//!
+//! Events that share a [`SessionId`] belong to one session: a pull request,
+//! an issue or another entity whose events are delivered in order. The
+//! [`SessionTracker`] follows each session as its events flow through the
+//! delivery pipeline and answers `GET /api/sessions` without scanning event
+//! storage:
+//!
+//! - [`SessionTracker::record_received`] counts an event when it is accepted
+//!   and marks it pending.
+//! - [`SessionTracker::record_outcome`] settles the event once its delivery
+//!   succeeds, fails or is cancelled.
+//!
+//! Each session keeps its in-flight deliveries: the events accepted but not
+//! yet settled. A session whose oldest in-flight event has waited longer than
+//! `stuck_after` is reported by [`SessionTracker::stuck`]; this usually means
+//! a poison message is being retried or the consumer of an ordered queue
+//! stopped, and later events of the session wait behind it.
+//!
+//! A session's [`SessionStatus`] follows from its pending events, the outcome
+//! of its latest delivery and the time since its last activity. The tracker
+//! is held in memory and bounded by `max_sessions`: when it is full, the
+//! session idle the longest is forgotten. Events without a session ID are
+//! not tracked.
+
+use crate::{units::human_duration, webhook::WrappedEvent, EventId, SessionId, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Session tracking settings (the `sessions` configuration section).
+///
+/// ```yaml
+/// sessions:
+///   max_sessions: 10000
+///   idle_after: 1h
+///   recent_events: 20
+///   stuck_after: 15m
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionTrackerConfig {
+    /// Sessions kept in memory; the session idle the longest is forgotten
+    /// first.
+    pub max_sessions: usize,
+
+    /// Time without events after which a session is reported idle.
+    #[serde(with = "human_duration")]
+    pub idle_after: Duration,
+
+    /// Latest events kept per session for its detail view.
+    pub recent_events: usize,
+
+    /// Age of the oldest in-flight event after which a session is reported
+    /// stuck.
+    #[serde(with = "human_duration")]
+    pub stuck_after: Duration,
+}
+
+impl Default for SessionTrackerConfig {
+    fn default() -> Self {
+        Self {
+            max_sessions: 10_000,
+            idle_after: Duration::from_secs(3600),
+            recent_events: 20,
+            stuck_after: Duration::from_secs(15 * 60),
+        }
+    }
+}
+
+impl SessionTrackerConfig {
+    /// Validate the settings.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_sessions == 0 {
+            return Err("sessions.max_sessions must be at least 1".to_string());
+        }
+        if self.idle_after.is_zero() {
+            return Err("sessions.idle_after must not be zero".to_string());
+        }
+        if self.stuck_after.is_zero() {
+            return Err("sessions.stuck_after must not be zero".to_string());
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// Session State
+// ============================================================================
+
+/// Status of a tracked session.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionStatus {
+    /// Events are pending, or the session was recently active and its latest
+    /// delivery succeeded.
+    Active,
+
+    /// The latest delivery failed and no event is pending.
+    Failed,
+
+    /// No event for longer than `idle_after`.
+    Idle,
+}
+
+impl SessionStatus {
+    /// Name used in API responses and filters.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SessionStatus::Active => "active",
+            SessionStatus::Failed => "failed",
+            SessionStatus::Idle => "idle",
+        }
+    }
+}
+
+impl fmt::Display for SessionStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How the delivery of a tracked event ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrackedEventStatus {
+    /// Accepted and waiting for delivery.
+    Pending,
+
+    /// Delivered to every target queue, or matched none.
+    Delivered,
+
+    /// At least one target queue did not receive the event.
+    Failed,
+
+    /// Abandoned because the session was reset.
+    Cancelled,
+}
+
+impl TrackedEventStatus {
+    /// Name used in API responses.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TrackedEventStatus::Pending => "pending",
+            TrackedEventStatus::Delivered => "delivered",
+            TrackedEventStatus::Failed => "failed",
+            TrackedEventStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// One of the latest events of a tracked session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackedEvent {
+    pub event_id: EventId,
+    pub event_type: String,
+    pub action: Option<String>,
+    pub received_at: Timestamp,
+    pub status: TrackedEventStatus,
+}
+
+/// Snapshot of a tracked session.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TrackedSession {
+    pub session_id: SessionId,
+    /// Repository full name (`owner/repo`) taken from the session ID.
+    pub repository: String,
+    pub entity_type: String,
+    pub entity_id: String,
+    pub status: SessionStatus,
+    /// Events received since the session was first seen.
+    pub event_count: u64,
+    /// Events accepted but not yet delivered.
+    pub pending_events: u64,
+    /// Events whose delivery failed.
+    pub failed_events: u64,
+    pub first_seen: Timestamp,
+    pub last_activity: Timestamp,
+    /// Latest event received; orders sessions by activity.
+    pub last_event_id: EventId,
+    /// Latest events, newest first.
+    pub recent_events: Vec<TrackedEvent>,
+}
+
+/// A session whose oldest in-flight event has waited longer than
+/// `stuck_after`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StuckSession {
+    pub session_id: SessionId,
+    /// Events accepted but not yet delivered.
+    pub in_flight_events: u64,
+    /// The event the rest of the session waits behind.
+    pub oldest_event_id: EventId,
+    /// When the oldest in-flight event was received.
+    pub oldest_received_at: Timestamp,
+    pub age_seconds: u64,
+}
+
+/// An event accepted for delivery and not yet settled.
+#[derive(Debug)]
+struct InFlightEvent {
+    received_at: Timestamp,
+    accepted: Instant,
+}
+
+/// Session state held by the tracker.
+#[derive(Debug)]
+struct SessionEntry {
+    session_id: SessionId,
+    event_count: u64,
+    in_flight: HashMap<EventId, InFlightEvent>,
+    failed_events: u64,
+    last_failed: bool,
+    first_seen: Timestamp,
+    last_activity: Timestamp,
+    last_seen: Instant,
+    last_event_id: EventId,
+    recent: VecDeque<TrackedEvent>,
+}
+
+impl SessionEntry {
+    fn status_at(&self, idle_after: Duration, now: Instant) -> SessionStatus {
+        if !self.in_flight.is_empty() {
+            SessionStatus::Active
+        } else if self.last_failed {
+            SessionStatus::Failed
+        } else if now.duration_since(self.last_seen) >= idle_after {
+            SessionStatus::Idle
+        } else {
+            SessionStatus::Active
+        }
+    }
+
+    fn snapshot(&self, idle_after: Duration, now: Instant) -> TrackedSession {
+        // Session IDs have the form owner/repo/entity_type/entity_id
+        let parts: Vec<&str> = self.session_id.as_str().splitn(4, '/').collect();
+        let repository = match parts.as_slice() {
+            [owner, repo, ..] => format!("{}/{}", owner, repo),
+            _ => "unknown/unknown".to_string(),
+        };
+        TrackedSession {
+            session_id: self.session_id.clone(),
+            repository,
+            entity_type: parts.get(2).copied().unwrap_or("unknown").to_string(),
+            entity_id: parts.get(3).copied().unwrap_or("0").to_string(),
+            status: self.status_at(idle_after, now),
+            event_count: self.event_count,
+            pending_events: self.in_flight.len() as u64,
+            failed_events: self.failed_events,
+            first_seen: self.first_seen,
+            last_activity: self.last_activity,
+            last_event_id: self.last_event_id,
+            recent_events: self.recent.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// The session as stuck, if its oldest in-flight event was accepted more
+    /// than `stuck_after` before `now`.
+    fn stuck_at(&self, stuck_after: Duration, now: Instant) -> Option<StuckSession> {
+        let (event_id, oldest) = self
+            .in_flight
+            .iter()
+            .min_by_key(|(event_id, event)| (event.accepted, **event_id))?;
+        let age = now.saturating_duration_since(oldest.accepted);
+        if age <= stuck_after {
+            return None;
+        }
+        Some(StuckSession {
+            session_id: self.session_id.clone(),
+            in_flight_events: self.in_flight.len() as u64,
+            oldest_event_id: *event_id,
+            oldest_received_at: oldest.received_at,
+            age_seconds: age.as_secs(),
+        })
+    }
+}
+
+// ============================================================================
+// Session Tracker
+// ============================================================================
+
+/// In-memory record of the sessions seen by the delivery pipeline.
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    config: SessionTrackerConfig,
+    sessions: Mutex<HashMap<SessionId, SessionEntry>>,
+}
+
+impl SessionTracker {
+    /// Create a tracker from its configuration.
+    pub fn new(config: SessionTrackerConfig) -> Self {
+        Self {
+            config,
+            sessions: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Record that `event` was accepted for delivery.
+    pub fn record_received(&self, event: &WrappedEvent) {
+        self.record_received_at(event, Instant::now());
+    }
+
+    /// Record how the delivery of event `event_id` of `session_id` ended.
+    ///
+    /// Takes the IDs rather than the event, as the event has usually moved
+    /// into the delivery by the time it ends. A pending `status` is ignored.
+    pub fn record_outcome(
+        &self,
+        session_id: &SessionId,
+        event_id: EventId,
+        status: TrackedEventStatus,
+    ) {
+        if status == TrackedEventStatus::Pending {
+            return;
+        }
+        let mut sessions = self.sessions.lock().unwrap();
+        let Some(entry) = sessions.get_mut(session_id) else {
+            return;
+        };
+
+        if let Some(tracked) = entry.recent.iter_mut().find(|e| e.event_id == event_id) {
+            tracked.status = status;
+        }
+        entry.in_flight.remove(&event_id);
+        match status {
+            TrackedEventStatus::Failed => {
+                entry.failed_events += 1;
+                entry.last_failed = true;
+            }
+            TrackedEventStatus::Delivered => entry.last_failed = false,
+            TrackedEventStatus::Cancelled | TrackedEventStatus::Pending => {}
+        }
+    }
+
+    /// Snapshot of one session, if it is tracked.
+    pub fn get(&self, session_id: &SessionId) -> Option<TrackedSession> {
+        let now = Instant::now();
+        self.sessions
+            .lock()
+            .unwrap()
+            .get(session_id)
+            .map(|entry| entry.snapshot(self.config.idle_after, now))
+    }
+
+    /// Snapshots of all tracked sessions, most recently active first.
+    pub fn list(&self) -> Vec<TrackedSession> {
+        let now = Instant::now();
+        let mut sessions: Vec<TrackedSession> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.snapshot(self.config.idle_after, now))
+            .collect();
+        sessions.sort_by_key(|session| std::cmp::Reverse(session.last_event_id));
+        sessions
+    }
+
+    /// Sessions whose oldest in-flight event has waited longer than
+    /// `stuck_after`, longest waiting first.
+    pub fn stuck(&self) -> Vec<StuckSession> {
+        self.stuck_at(Instant::now())
+    }
+
+    /// Number of tracked sessions whose status is
+    /// [`SessionStatus::Active`].
+    pub fn active_sessions(&self) -> u64 {
+        self.active_sessions_at(Instant::now())
+    }
+
+    /// In-flight events across all tracked sessions.
+    pub fn in_flight_events(&self) -> u64 {
+        self.sessions
+            .lock()
+            .unwrap()
+            .values()
+            .map(|entry| entry.in_flight.len() as u64)
+            .sum()
+    }
+
+    /// The configured age after which a session is stuck.
+    pub fn stuck_after(&self) -> Duration {
+        self.config.stuck_after
+    }
+
+    /// Number of tracked sessions.
+    pub fn len(&self) -> usize {
+        self.sessions.lock().unwrap().len()
+    }
+
+    /// Whether no session is tracked.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn active_sessions_at(&self, now: Instant) -> u64 {
+        self.sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter(|entry| entry.status_at(self.config.idle_after, now) == SessionStatus::Active)
+            .count() as u64
+    }
+
+    fn stuck_at(&self, now: Instant) -> Vec<StuckSession> {
+        let mut stuck: Vec<StuckSession> = self
+            .sessions
+            .lock()
+            .unwrap()
+            .values()
+            .filter_map(|entry| entry.stuck_at(self.config.stuck_after, now))
+            .collect();
+        stuck.sort_by(|a, b| {
+            b.age_seconds
+                .cmp(&a.age_seconds)
+                .then_with(|| a.oldest_event_id.cmp(&b.oldest_event_id))
+        });
+        stuck
+    }
+
+    fn record_received_at(&self, event: &WrappedEvent, now: Instant) {
+        let Some(session_id) = &event.session_id else {
+            return;
+        };
+        let mut sessions = self.sessions.lock().unwrap();
+        if !sessions.contains_key(session_id) && sessions.len() >= self.config.max_sessions {
+            evict_longest_idle(&mut sessions);
+        }
+
+        let entry = sessions
+            .entry(session_id.clone())
+            .or_insert_with(|| SessionEntry {
+                session_id: session_id.clone(),
+                event_count: 0,
+                in_flight: HashMap::new(),
+                failed_events: 0,
+                last_failed: false,
+                first_seen: event.received_at,
+                last_activity: event.received_at,
+                last_seen: now,
+                last_event_id: event.event_id,
+                recent: VecDeque::new(),
+            });
+        entry.event_count += 1;
+        entry.in_flight.insert(
+            event.event_id,
+            InFlightEvent {
+                received_at: event.received_at,
+                accepted: now,
+            },
+        );
+        entry.last_activity = entry.last_activity.max(event.received_at);
+        entry.last_seen = now;
+        entry.last_event_id = event.event_id;
+
+        while !entry.recent.is_empty() && entry.recent.len() >= self.config.recent_events {
+            entry.recent.pop_front();
+        }
+        if self.config.recent_events > 0 {
+            entry.recent.push_back(TrackedEvent {
+                event_id: event.event_id,
+                event_type: event.event_type.clone(),
+                action: event.action.clone(),
+                received_at: event.received_at,
+                status: TrackedEventStatus::Pending,
+            });
+        }
+    }
+}
+
+/// Forget the session without pending events that was seen the longest ago,
+/// or the oldest session when every session has pending events.
+fn evict_longest_idle(sessions: &mut HashMap<SessionId, SessionEntry>) {
+    let oldest = sessions
+        .values()
+        .min_by_key(|entry| (!entry.in_flight.is_empty(), entry.last_seen))
+        .map(|entry| entry.session_id.clone());
+    if let Some(session_id) = oldest {
+        sessions.remove(&session_id);
+    }
+}
+
+#[cfg(test)]
+#[path = "session_tracker_tests.rs"]
+mod tests;
@@ -0,0 +1,287 @@
+//! Tests for session tracking.
+
+use super::*;
+use crate::Ulid;
+use std::sync::atomic::AtomicU64;
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn event(session: &str) -> WrappedEvent {
+    let mut event = WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("synchronize".to_string()),
+        Some(SessionId::new(session.to_string()).unwrap()),
+        serde_json::json!({}),
+        None,
+    );
+    // ULIDs created within the same millisecond are not ordered
+    event.event_id = EventId(Ulid::from_parts(
+        NEXT_ID.fetch_add(1, std::sync::atomic::Ordering::Relaxed),
+        0,
+    ));
+    event
+}
+
+fn tracker(max_sessions: usize) -> SessionTracker {
+    SessionTracker::new(SessionTrackerConfig {
+        max_sessions,
+        recent_events: 2,
+        ..SessionTrackerConfig::default()
+    })
+}
+
+fn settle(tracker: &SessionTracker, event: &WrappedEvent, status: TrackedEventStatus) {
+    tracker.record_outcome(event.session_id.as_ref().unwrap(), event.event_id, status);
+}
+
+fn session(id: &str) -> SessionId {
+    SessionId::new(id.to_string()).unwrap()
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Verify that the default settings are valid and invalid ones rejected.
+#[test]
+fn test_config_validation() {
+    assert!(SessionTrackerConfig::default().validate().is_ok());
+
+    for config in [
+        SessionTrackerConfig {
+            max_sessions: 0,
+            ..SessionTrackerConfig::default()
+        },
+        SessionTrackerConfig {
+            idle_after: Duration::ZERO,
+            ..SessionTrackerConfig::default()
+        },
+        SessionTrackerConfig {
+            stuck_after: Duration::ZERO,
+            ..SessionTrackerConfig::default()
+        },
+    ] {
+        assert!(config.validate().is_err(), "{:?} should be invalid", config);
+    }
+}
+
+// ============================================================================
+// Tracking
+// ============================================================================
+
+/// Verify that received events are counted and pending until delivered.
+#[test]
+fn test_received_events_pending_until_delivered() {
+    let tracker = tracker(10);
+    let first = event("owner/repo/pull_request/1");
+    let second = event("owner/repo/pull_request/1");
+    tracker.record_received(&first);
+    tracker.record_received(&second);
+
+    let tracked = tracker.get(&session("owner/repo/pull_request/1")).unwrap();
+    assert_eq!(tracked.repository, "owner/repo");
+    assert_eq!(tracked.entity_type, "pull_request");
+    assert_eq!(tracked.entity_id, "1");
+    assert_eq!(tracked.event_count, 2);
+    assert_eq!(tracked.pending_events, 2);
+    assert_eq!(tracked.status, SessionStatus::Active);
+    assert_eq!(tracked.last_event_id, second.event_id);
+
+    settle(&tracker, &first, TrackedEventStatus::Delivered);
+    settle(&tracker, &second, TrackedEventStatus::Delivered);
+
+    let tracked = tracker.get(&session("owner/repo/pull_request/1")).unwrap();
+    assert_eq!(tracked.pending_events, 0);
+    assert_eq!(tracked.status, SessionStatus::Active);
+    assert!(tracked
+        .recent_events
+        .iter()
+        .all(|e| e.status == TrackedEventStatus::Delivered));
+}
+
+/// Verify that a failed delivery marks the session failed until the next
+/// successful one.
+#[test]
+fn test_failed_delivery_marks_session_failed() {
+    let tracker = tracker(10);
+    let failed = event("owner/repo/issue/7");
+    tracker.record_received(&failed);
+    settle(&tracker, &failed, TrackedEventStatus::Failed);
+
+    let tracked = tracker.get(&session("owner/repo/issue/7")).unwrap();
+    assert_eq!(tracked.status, SessionStatus::Failed);
+    assert_eq!(tracked.failed_events, 1);
+
+    let delivered = event("owner/repo/issue/7");
+    tracker.record_received(&delivered);
+    assert_eq!(
+        tracker.get(&session("owner/repo/issue/7")).unwrap().status,
+        SessionStatus::Active
+    );
+    settle(&tracker, &delivered, TrackedEventStatus::Delivered);
+    assert_eq!(
+        tracker.get(&session("owner/repo/issue/7")).unwrap().status,
+        SessionStatus::Active
+    );
+}
+
+/// Verify that sessions without recent events are reported idle.
+#[test]
+fn test_session_idle_after_inactivity() {
+    let tracker = tracker(10);
+    let start = Instant::now();
+    let event = event("owner/repo/pull_request/1");
+    tracker.record_received_at(&event, start);
+    settle(&tracker, &event, TrackedEventStatus::Delivered);
+
+    let sessions = tracker.sessions.lock().unwrap();
+    let entry = &sessions[&session("owner/repo/pull_request/1")];
+    let idle_after = tracker.config.idle_after;
+    assert_eq!(entry.status_at(idle_after, start), SessionStatus::Active);
+    assert_eq!(
+        entry.status_at(idle_after, start + idle_after),
+        SessionStatus::Idle
+    );
+}
+
+/// Verify that failed and idle sessions are not counted as active.
+#[test]
+fn test_active_sessions_excludes_failed_and_idle() {
+    let tracker = tracker(10);
+    let start = Instant::now();
+    let pending = event("owner/repo/pull_request/1");
+    let delivered = event("owner/repo/pull_request/2");
+    let failed = event("owner/repo/pull_request/3");
+    for e in [&pending, &delivered, &failed] {
+        tracker.record_received_at(e, start);
+    }
+    settle(&tracker, &delivered, TrackedEventStatus::Delivered);
+    settle(&tracker, &failed, TrackedEventStatus::Failed);
+
+    assert_eq!(tracker.active_sessions_at(start), 2);
+    // The delivered session goes idle; the pending one stays active
+    let idle_after = tracker.config.idle_after;
+    assert_eq!(tracker.active_sessions_at(start + idle_after), 1);
+}
+
+/// Verify that only the latest events are kept, newest first.
+#[test]
+fn test_recent_events_bounded() {
+    let tracker = tracker(10);
+    let events: Vec<_> = (0..3).map(|_| event("owner/repo/pull_request/1")).collect();
+    for event in &events {
+        tracker.record_received(event);
+    }
+    settle(&tracker, &events[0], TrackedEventStatus::Delivered);
+
+    let tracked = tracker.get(&session("owner/repo/pull_request/1")).unwrap();
+    let ids: Vec<EventId> = tracked.recent_events.iter().map(|e| e.event_id).collect();
+    assert_eq!(ids, vec![events[2].event_id, events[1].event_id]);
+    assert_eq!(tracked.event_count, 3);
+    assert_eq!(tracked.pending_events, 2);
+}
+
+/// Verify that events without a session are ignored.
+#[test]
+fn test_events_without_session_ignored() {
+    let tracker = tracker(10);
+    let mut event = event("owner/repo/pull_request/1");
+    event.session_id = None;
+    tracker.record_received(&event);
+
+    assert!(tracker.is_empty());
+}
+
+/// Verify that a full tracker forgets the longest idle session, keeping
+/// sessions with pending events.
+#[test]
+fn test_full_tracker_evicts_longest_idle_session() {
+    let tracker = tracker(2);
+    let start = Instant::now();
+    let pending = event("owner/repo/pull_request/1");
+    let settled = event("owner/repo/pull_request/2");
+    tracker.record_received_at(&pending, start);
+    tracker.record_received_at(&settled, start + Duration::from_secs(1));
+    settle(&tracker, &settled, TrackedEventStatus::Delivered);
+
+    tracker.record_received_at(
+        &event("owner/repo/pull_request/3"),
+        start + Duration::from_secs(2),
+    );
+
+    assert_eq!(tracker.len(), 2);
+    assert!(tracker.get(&session("owner/repo/pull_request/1")).is_some());
+    assert!(tracker.get(&session("owner/repo/pull_request/2")).is_none());
+}
+
+/// Verify that sessions are listed most recently active first.
+#[test]
+fn test_list_orders_by_last_event() {
+    let tracker = tracker(10);
+    tracker.record_received(&event("owner/repo/pull_request/1"));
+    tracker.record_received(&event("owner/repo/pull_request/2"));
+    tracker.record_received(&event("owner/repo/pull_request/1"));
+
+    let ids: Vec<String> = tracker
+        .list()
+        .into_iter()
+        .map(|s| s.session_id.to_string())
+        .collect();
+
+    assert_eq!(
+        ids,
+        vec!["owner/repo/pull_request/1", "owner/repo/pull_request/2"]
+    );
+}
+
+// ============================================================================
+// Stuck Sessions
+// ============================================================================
+
+/// Verify that a session is stuck once its oldest in-flight event is older
+/// than `stuck_after`, and no longer once that event is settled.
+#[test]
+fn test_session_stuck_behind_oldest_in_flight_event() {
+    let tracker = tracker(10);
+    let start = Instant::now();
+    let stuck_after = tracker.stuck_after();
+    let poison = event("owner/repo/pull_request/1");
+    let waiting = event("owner/repo/pull_request/1");
+    tracker.record_received_at(&poison, start);
+    tracker.record_received_at(&waiting, start + Duration::from_secs(60));
+    tracker.record_received_at(&event("owner/repo/pull_request/2"), start + stuck_after);
+
+    assert!(tracker.stuck_at(start + stuck_after).is_empty());
+
+    let stuck = tracker.stuck_at(start + stuck_after + Duration::from_secs(30));
+    assert_eq!(stuck.len(), 1);
+    assert_eq!(stuck[0].session_id, session("owner/repo/pull_request/1"));
+    assert_eq!(stuck[0].in_flight_events, 2);
+    assert_eq!(stuck[0].oldest_event_id, poison.event_id);
+    assert_eq!(stuck[0].oldest_received_at, poison.received_at);
+    assert_eq!(stuck[0].age_seconds, stuck_after.as_secs() + 30);
+    assert_eq!(tracker.in_flight_events(), 3);
+
+    settle(&tracker, &poison, TrackedEventStatus::Failed);
+    assert!(tracker
+        .stuck_at(start + stuck_after + Duration::from_secs(30))
+        .is_empty());
+    assert_eq!(tracker.in_flight_events(), 2);
+}
+
+/// Verify that stuck sessions are listed longest waiting first.
+#[test]
+fn test_stuck_sessions_ordered_by_age() {
+    let tracker = tracker(10);
+    let start = Instant::now();
+    tracker.record_received_at(
+        &event("owner/repo/issue/1"),
+        start + Duration::from_secs(10),
+    );
+    tracker.record_received_at(&event("owner/repo/issue/2"), start);
+
+    let stuck = tracker.stuck_at(start + tracker.stuck_after() + Duration::from_secs(20));
+    let ids: Vec<&str> = stuck.iter().map(|s| s.session_id.as_str()).collect();
+    assert_eq!(ids, ["owner/repo/issue/2", "owner/repo/issue/1"]);
+}
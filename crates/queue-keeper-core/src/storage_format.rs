@@ -0,0 +1,192 @@
+//! # Persisted Format Versioning
+//!
+//! Every artifact Queue-Keeper persists carries a `format_version` so that a
+//! format change can be rolled out without breaking data written by the
+//! previous release:
+//!
+//! - stored blobs ([`BlobMetadata::format_version`]) — raw webhook payloads,
+//!   persisted wrapped events and DLQ entries are all stored as blobs;
+//! - DLQ records, inside the blob body (`FailedEventRecord` in the API
+//!   crate).
+//!
+//! | Version | Change |
+//! |---------|--------|
+//! | 1 | Artifacts written before version markers existed (no `format_version` field). |
+//! | 2 | `format_version` added to blob metadata and DLQ records. |
+//!
+//! Readers accept the current version and the one before it; a missing
+//! marker reads as version 1. Versions newer than [`CURRENT_FORMAT_VERSION`]
+//! are rejected, so a rolled-back replica fails loudly instead of misreading
+//! artifacts written by a newer one.
+//!
+//! `queue-keeper migrate-storage` rewrites previous-version artifacts in the
+//! current format with [`migrate_stored_blob`]. Once every artifact is
+//! current, the next release may drop the compatibility reader.
+//!
+//! [`BlobMetadata::format_version`]: crate::blob_storage::BlobMetadata::format_version
+
+use crate::blob_storage::compute_checksum;
+use bytes::Bytes;
+use serde_json::{Map, Value};
+use thiserror::Error;
+
+/// Format version written by this release.
+pub const CURRENT_FORMAT_VERSION: u32 = 2;
+
+/// Oldest format version this release can read.
+///
+/// Artifacts without a `format_version` field are this version.
+pub const LEGACY_FORMAT_VERSION: u32 = 1;
+
+/// Serde default for `format_version` fields, so unmarked artifacts read as
+/// [`LEGACY_FORMAT_VERSION`].
+pub fn legacy_format_version() -> u32 {
+    LEGACY_FORMAT_VERSION
+}
+
+// ============================================================================
+// Compatibility
+// ============================================================================
+
+/// How a readable format version relates to the current one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatCompatibility {
+    /// Written in the current format.
+    Current,
+
+    /// Written in the previous format; readable, but should be migrated.
+    Legacy,
+}
+
+/// Errors reading or migrating persisted artifacts.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum StorageFormatError {
+    /// The artifact was written in a format this release cannot read.
+    #[error(
+        "unsupported format version {version} (supported: {}-{})",
+        LEGACY_FORMAT_VERSION,
+        CURRENT_FORMAT_VERSION
+    )]
+    UnsupportedVersion { version: u32 },
+
+    /// The document is not a stored blob.
+    #[error("malformed stored blob: {message}")]
+    Malformed { message: String },
+}
+
+/// Check that `version` can be read by this release.
+///
+/// # Errors
+///
+/// Returns [`StorageFormatError::UnsupportedVersion`] for versions older
+/// than [`LEGACY_FORMAT_VERSION`] or newer than [`CURRENT_FORMAT_VERSION`].
+///
+/// # Examples
+///
+/// ```
+/// use queue_keeper_core::storage_format::{check_format_version, FormatCompatibility};
+///
+/// assert_eq!(check_format_version(2).unwrap(), FormatCompatibility::Current);
+/// assert_eq!(check_format_version(1).unwrap(), FormatCompatibility::Legacy);
+/// assert!(check_format_version(3).is_err());
+/// ```
+pub fn check_format_version(version: u32) -> Result<FormatCompatibility, StorageFormatError> {
+    match version {
+        CURRENT_FORMAT_VERSION => Ok(FormatCompatibility::Current),
+        LEGACY_FORMAT_VERSION => Ok(FormatCompatibility::Legacy),
+        _ => Err(StorageFormatError::UnsupportedVersion { version }),
+    }
+}
+
+// ============================================================================
+// Migration
+// ============================================================================
+
+/// Result of migrating one stored blob.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MigrationOutcome {
+    /// The blob is already in the current format.
+    Current,
+
+    /// The blob was upgraded; contains the rewritten document.
+    Migrated(Vec<u8>),
+}
+
+/// Upgrade a serialized [`StoredWebhook`] document to the current format.
+///
+/// Sets the blob's `format_version`. When the body is a DLQ record (a JSON
+/// object with `event`, `reason` and `failed_queues`), the record's
+/// `format_version` is set too and the blob checksum recomputed. Fields this
+/// release does not know are kept.
+///
+/// # Errors
+///
+/// Returns [`StorageFormatError::Malformed`] when the document is not a
+/// stored blob and [`StorageFormatError::UnsupportedVersion`] when the blob
+/// or record was written by a newer release.
+///
+/// [`StoredWebhook`]: crate::blob_storage::StoredWebhook
+pub fn migrate_stored_blob(document: &[u8]) -> Result<MigrationOutcome, StorageFormatError> {
+    let mut document: Value = serde_json::from_slice(document).map_err(malformed)?;
+    let mut changed = false;
+
+    let metadata = document
+        .get_mut("metadata")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| malformed("missing 'metadata' object"))?;
+    if check_format_version(read_version(metadata)?)? == FormatCompatibility::Legacy {
+        metadata.insert("format_version".to_string(), CURRENT_FORMAT_VERSION.into());
+        changed = true;
+    }
+
+    let body_value = document
+        .pointer("/payload/body")
+        .ok_or_else(|| malformed("missing 'payload.body'"))?;
+    let body: Vec<u8> = serde_json::from_value(body_value.clone()).map_err(malformed)?;
+    if let Ok(Value::Object(mut record)) = serde_json::from_slice::<Value>(&body) {
+        if is_dlq_record(&record)
+            && check_format_version(read_version(&record)?)? == FormatCompatibility::Legacy
+        {
+            record.insert("format_version".to_string(), CURRENT_FORMAT_VERSION.into());
+            let body = serde_json::to_vec_pretty(&record).map_err(malformed)?;
+            document["metadata"]["checksum_sha256"] =
+                Value::String(compute_checksum(&Bytes::from(body.clone())));
+            document["payload"]["body"] = serde_json::to_value(body).map_err(malformed)?;
+            changed = true;
+        }
+    }
+
+    if !changed {
+        return Ok(MigrationOutcome::Current);
+    }
+    serde_json::to_vec_pretty(&document)
+        .map(MigrationOutcome::Migrated)
+        .map_err(malformed)
+}
+
+/// Read the `format_version` field of an object; missing means legacy.
+fn read_version(object: &Map<String, Value>) -> Result<u32, StorageFormatError> {
+    match object.get("format_version") {
+        None => Ok(LEGACY_FORMAT_VERSION),
+        Some(value) => value
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .ok_or_else(|| malformed(format!("invalid format_version {}", value))),
+    }
+}
+
+fn is_dlq_record(object: &Map<String, Value>) -> bool {
+    ["event", "reason", "failed_queues"]
+        .iter()
+        .all(|key| object.contains_key(*key))
+}
+
+fn malformed(message: impl ToString) -> StorageFormatError {
+    StorageFormatError::Malformed {
+        message: message.to_string(),
+    }
+}
+
+#[cfg(test)]
+#[path = "storage_format_tests.rs"]
+mod tests;
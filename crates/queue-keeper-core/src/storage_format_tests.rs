@@ -0,0 +1,169 @@
+//! Tests for persisted format versioning.
+
+use super::*;
+use crate::blob_storage::{BlobMetadata, PayloadMetadata, StoredWebhook, WebhookPayload};
+use crate::{EventId, Timestamp};
+use std::collections::HashMap;
+
+/// Serialize a stored blob with `body`, in the current format or, with
+/// `legacy`, without any `format_version` marker.
+fn stored_blob(body: &[u8], legacy: bool) -> Vec<u8> {
+    let event_id = EventId::new();
+    let body = Bytes::from(body.to_vec());
+    let metadata = PayloadMetadata {
+        event_id,
+        event_type: "push".to_string(),
+        repository: None,
+        signature_valid: true,
+        received_at: Timestamp::now(),
+        delivery_id: None,
+    };
+    let stored = StoredWebhook {
+        metadata: BlobMetadata {
+            event_id,
+            blob_path: event_id.to_blob_path(),
+            size_bytes: body.len() as u64,
+            content_type: "application/json".to_string(),
+            created_at: Timestamp::now(),
+            checksum_sha256: compute_checksum(&body),
+            format_version: CURRENT_FORMAT_VERSION,
+            content_encoding: crate::blob_storage::ContentEncoding::Identity,
+            encryption: None,
+            metadata: metadata.clone(),
+        },
+        payload: WebhookPayload {
+            body,
+            headers: HashMap::new(),
+            metadata,
+        },
+    };
+
+    let mut value = serde_json::to_value(&stored).unwrap();
+    if legacy {
+        value["metadata"]
+            .as_object_mut()
+            .unwrap()
+            .remove("format_version");
+    }
+    serde_json::to_vec(&value).unwrap()
+}
+
+fn legacy_dlq_record() -> Vec<u8> {
+    serde_json::to_vec(&serde_json::json!({
+        "event_id": "01JQZM7XK4B3VYFNHD0G2T8P1X",
+        "event": {},
+        "reason": { "type": "retries_exhausted", "attempts": 3 },
+        "failed_queues": [],
+        "successful_queues": [],
+        "retry_attempts": 3
+    }))
+    .unwrap()
+}
+
+fn migrated(document: &[u8]) -> StoredWebhook {
+    match migrate_stored_blob(document).unwrap() {
+        MigrationOutcome::Migrated(bytes) => serde_json::from_slice(&bytes).unwrap(),
+        MigrationOutcome::Current => panic!("expected the blob to be migrated"),
+    }
+}
+
+// ============================================================================
+// Compatibility
+// ============================================================================
+
+/// The current and previous versions are readable; others are not.
+#[test]
+fn test_check_format_version() {
+    assert_eq!(
+        check_format_version(CURRENT_FORMAT_VERSION),
+        Ok(FormatCompatibility::Current)
+    );
+    assert_eq!(
+        check_format_version(LEGACY_FORMAT_VERSION),
+        Ok(FormatCompatibility::Legacy)
+    );
+    for version in [0, CURRENT_FORMAT_VERSION + 1] {
+        assert_eq!(
+            check_format_version(version),
+            Err(StorageFormatError::UnsupportedVersion { version })
+        );
+    }
+}
+
+/// Blobs without a marker deserialize as the legacy version.
+#[test]
+fn test_unmarked_blob_reads_as_legacy() {
+    let stored: StoredWebhook = serde_json::from_slice(&stored_blob(b"{}", true)).unwrap();
+    assert_eq!(stored.metadata.format_version, LEGACY_FORMAT_VERSION);
+}
+
+// ============================================================================
+// Migration
+// ============================================================================
+
+/// Legacy payload blobs gain a version marker and keep their body.
+#[test]
+fn test_migrates_legacy_payload_blob() {
+    let body = br#"{"action":"opened"}"#;
+    let stored = migrated(&stored_blob(body, true));
+
+    assert_eq!(stored.metadata.format_version, CURRENT_FORMAT_VERSION);
+    assert_eq!(stored.payload.body.as_ref(), body);
+    assert_eq!(
+        stored.metadata.checksum_sha256,
+        compute_checksum(&stored.payload.body)
+    );
+}
+
+/// Current blobs are left alone, so migration is idempotent.
+#[test]
+fn test_current_blob_is_unchanged() {
+    assert_eq!(
+        migrate_stored_blob(&stored_blob(b"{}", false)).unwrap(),
+        MigrationOutcome::Current
+    );
+
+    let once = match migrate_stored_blob(&stored_blob(&legacy_dlq_record(), true)).unwrap() {
+        MigrationOutcome::Migrated(bytes) => bytes,
+        MigrationOutcome::Current => panic!("expected the blob to be migrated"),
+    };
+    assert_eq!(
+        migrate_stored_blob(&once).unwrap(),
+        MigrationOutcome::Current
+    );
+}
+
+/// DLQ records inside a blob are versioned and the checksum recomputed.
+#[test]
+fn test_migrates_legacy_dlq_record() {
+    let stored = migrated(&stored_blob(&legacy_dlq_record(), false));
+
+    let record: Value = serde_json::from_slice(&stored.payload.body).unwrap();
+    assert_eq!(record["format_version"], CURRENT_FORMAT_VERSION);
+    assert_eq!(record["retry_attempts"], 3);
+    assert_eq!(
+        stored.metadata.checksum_sha256,
+        compute_checksum(&stored.payload.body)
+    );
+}
+
+/// Blobs written by a newer release and documents that are not blobs are
+/// reported, not rewritten.
+#[test]
+fn test_migration_rejects_newer_and_malformed_documents() {
+    let mut newer: Value = serde_json::from_slice(&stored_blob(b"{}", false)).unwrap();
+    newer["metadata"]["format_version"] = (CURRENT_FORMAT_VERSION + 1).into();
+    assert!(matches!(
+        migrate_stored_blob(&serde_json::to_vec(&newer).unwrap()),
+        Err(StorageFormatError::UnsupportedVersion { .. })
+    ));
+
+    assert!(matches!(
+        migrate_stored_blob(b"not json"),
+        Err(StorageFormatError::Malformed { .. })
+    ));
+    assert!(matches!(
+        migrate_stored_blob(br#"{"headers": {}, "body": {}}"#),
+        Err(StorageFormatError::Malformed { .. })
+    ));
+}
@@ -0,0 +1,81 @@
+//! Tests for monotonic timing.
+
+use super::*;
+
+/// Durations are measured on the monotonic clock and never go negative.
+#[test]
+fn test_duration_since_is_monotonic_and_saturating() {
+    let start = MonotonicTimestamp::now();
+    std::thread::sleep(Duration::from_millis(5));
+    let end = MonotonicTimestamp::now();
+
+    assert!(end.duration_since(&start) >= Duration::from_millis(5));
+    assert_eq!(start.duration_since(&end), Duration::ZERO);
+    assert!(start.elapsed() >= end.duration_since(&start));
+}
+
+/// A moment reconstructed from storage keeps its wall time and its age.
+#[test]
+fn test_from_wall_preserves_age() {
+    let wall = Timestamp::now().subtract_duration(Duration::from_secs(30));
+    let restored = MonotonicTimestamp::from_wall(wall);
+
+    assert_eq!(restored.wall(), wall);
+    assert!(restored.elapsed() >= Duration::from_secs(30));
+    assert!(restored.elapsed() < Duration::from_secs(60));
+}
+
+/// Wall timestamps in the future are treated as now.
+#[test]
+fn test_from_wall_future_is_now() {
+    let wall = Timestamp::now().add_seconds(3_600);
+    let restored = MonotonicTimestamp::from_wall(wall);
+
+    assert!(restored.elapsed() < Duration::from_secs(1));
+}
+
+/// Repeated stages accumulate and clones share one record.
+#[test]
+fn test_stage_timings_accumulate_and_share() {
+    let timings = StageTimings::new();
+    let handle = timings.clone();
+
+    timings.record(PipelineStage::HeaderParse, Duration::from_millis(2));
+    timings.record(PipelineStage::BlobStore, Duration::from_millis(5));
+    timings.record(PipelineStage::HeaderParse, Duration::from_millis(1));
+
+    assert_eq!(
+        handle.get(PipelineStage::HeaderParse),
+        Some(Duration::from_millis(3))
+    );
+    assert_eq!(handle.get(PipelineStage::Normalization), None);
+    assert_eq!(
+        handle.breakdown(),
+        vec![
+            (PipelineStage::HeaderParse, Duration::from_millis(3)),
+            (PipelineStage::BlobStore, Duration::from_millis(5)),
+        ]
+    );
+    assert_eq!(handle.total(), Duration::from_millis(8));
+    assert_eq!(
+        handle.to_string(),
+        "header_parse=3.000ms blob_store=5.000ms"
+    );
+    assert_eq!(StageTimings::new().to_string(), "none");
+}
+
+/// Timing a future records its duration and passes its output through.
+#[tokio::test]
+async fn test_stage_timings_time_future() {
+    let timings = StageTimings::new();
+
+    let output = timings
+        .time(PipelineStage::SignatureValidation, async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            42
+        })
+        .await;
+
+    assert_eq!(output, 42);
+    assert!(timings.get(PipelineStage::SignatureValidation).unwrap() >= Duration::from_millis(5));
+}
@@ -0,0 +1,358 @@
+//! # Human-Readable Configuration Units
+//!
+//! Configuration values for durations and sizes are easier to read and
+//! harder to get wrong with their unit attached: `timeout: 30s` and
+//! `max_body_size: 10MB` rather than `timeout_seconds: 30` and
+//! `max_body_size: 10485760`.
+//!
+//! - [`HumanDuration`] parses `500ms`, `30s`, `5m`, `1h30m`, `7d`. A bare
+//!   integer is a number of seconds, so existing `*_seconds` values keep
+//!   working.
+//! - [`ByteSize`] parses `512B`, `64KB`, `10MB`, `1GB`. Units are binary:
+//!   `KB` and `KiB` both mean 1024 bytes, matching how sizes have always been
+//!   computed in this codebase. A bare integer is a number of bytes.
+//!
+//! Both serialize back to their canonical string form (`1m30s`, `10MB`).
+//! Invalid values are rejected with a [`ParseError`] naming the expected
+//! format and the offending input.
+//!
+//! ```rust
+//! use queue_keeper_core::units::{ByteSize, HumanDuration};
+//! use std::time::Duration;
+//!
+//! let timeout: HumanDuration = "1m30s".parse().unwrap();
+//! assert_eq!(timeout.as_duration(), Duration::from_secs(90));
+//!
+//! let limit: ByteSize = "10MB".parse().unwrap();
+//! assert_eq!(limit.as_u64(), 10 * 1024 * 1024);
+//! ```
+
+use crate::ParseError;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr, time::Duration};
+
+const DURATION_FORMAT: &str =
+    "a duration such as \"500ms\", \"30s\", \"5m\" or \"1h30m\" (units: ms, s, m, h, d)";
+const SIZE_FORMAT: &str =
+    "a size such as \"512B\", \"64KB\" or \"10MB\" (units: B, KB, MB, GB, TB)";
+
+// ============================================================================
+// HumanDuration
+// ============================================================================
+
+/// A duration written with its unit, e.g. `30s` or `1h30m`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct HumanDuration(Duration);
+
+impl HumanDuration {
+    /// Duration of `secs` seconds.
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+
+    /// Duration of `millis` milliseconds.
+    pub const fn from_millis(millis: u64) -> Self {
+        Self(Duration::from_millis(millis))
+    }
+
+    /// The wrapped [`Duration`].
+    pub const fn as_duration(&self) -> Duration {
+        self.0
+    }
+
+    /// Whole seconds, truncating any milliseconds.
+    pub const fn as_secs(&self) -> u64 {
+        self.0.as_secs()
+    }
+
+    /// Whether the duration is zero.
+    pub const fn is_zero(&self) -> bool {
+        self.0.is_zero()
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<HumanDuration> for Duration {
+    fn from(duration: HumanDuration) -> Self {
+        duration.0
+    }
+}
+
+impl FromStr for HumanDuration {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::InvalidFormat {
+            expected: DURATION_FORMAT.to_string(),
+            actual: s.to_string(),
+        };
+
+        let input = s.trim();
+        if input.is_empty() {
+            return Err(invalid());
+        }
+        if let Ok(secs) = input.parse::<u64>() {
+            return Ok(Self::from_secs(secs));
+        }
+
+        let mut total_millis: u64 = 0;
+        let mut rest = input;
+        while !rest.is_empty() {
+            let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+            if digits == 0 {
+                return Err(invalid());
+            }
+            let value: u64 = rest[..digits].parse().map_err(|_| invalid())?;
+            rest = &rest[digits..];
+
+            let unit_len = rest.len()
+                - rest
+                    .trim_start_matches(|c: char| c.is_ascii_alphabetic())
+                    .len();
+            let millis_per_unit: u64 = match &rest[..unit_len] {
+                "ms" => 1,
+                "s" => 1_000,
+                "m" => 60_000,
+                "h" => 3_600_000,
+                "d" => 86_400_000,
+                _ => return Err(invalid()),
+            };
+            rest = &rest[unit_len..];
+
+            total_millis = value
+                .checked_mul(millis_per_unit)
+                .and_then(|millis| total_millis.checked_add(millis))
+                .ok_or_else(invalid)?;
+        }
+
+        Ok(Self::from_millis(total_millis))
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut millis = self.0.as_millis();
+        if millis == 0 {
+            return f.write_str("0s");
+        }
+        for (unit, size) in [
+            ("d", 86_400_000),
+            ("h", 3_600_000),
+            ("m", 60_000),
+            ("s", 1_000),
+            ("ms", 1),
+        ] {
+            if millis >= size {
+                write!(f, "{}{}", millis / size, unit)?;
+                millis %= size;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for HumanDuration {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(UnitVisitor::<Self>::new(DURATION_FORMAT, Self::from_secs))
+    }
+}
+
+/// `#[serde(with = "...")]` adapter storing a [`Duration`] field in
+/// [`HumanDuration`] form.
+///
+/// ```rust
+/// use serde::Deserialize;
+/// use std::time::Duration;
+///
+/// #[derive(Deserialize)]
+/// struct Retry {
+///     #[serde(with = "queue_keeper_core::units::human_duration")]
+///     delay: Duration,
+/// }
+///
+/// let retry: Retry = serde_json::from_str(r#"{"delay":"250ms"}"#).unwrap();
+/// assert_eq!(retry.delay, Duration::from_millis(250));
+/// ```
+pub mod human_duration {
+    use super::HumanDuration;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::time::Duration;
+
+    /// Serialize `duration` in its canonical string form.
+    pub fn serialize<S: Serializer>(duration: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        HumanDuration::from(*duration).serialize(serializer)
+    }
+
+    /// Deserialize a duration string or a bare number of seconds.
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        HumanDuration::deserialize(deserializer).map(Duration::from)
+    }
+}
+
+// ============================================================================
+// ByteSize
+// ============================================================================
+
+/// A size in bytes written with its unit, e.g. `10MB`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(u64);
+
+impl ByteSize {
+    const KIB: u64 = 1024;
+    const MIB: u64 = 1024 * Self::KIB;
+    const GIB: u64 = 1024 * Self::MIB;
+    const TIB: u64 = 1024 * Self::GIB;
+
+    /// Size of `bytes` bytes.
+    pub const fn bytes(bytes: u64) -> Self {
+        Self(bytes)
+    }
+
+    /// Size of `kib` × 1024 bytes.
+    pub const fn kib(kib: u64) -> Self {
+        Self(kib * Self::KIB)
+    }
+
+    /// Size of `mib` × 1024² bytes.
+    pub const fn mib(mib: u64) -> Self {
+        Self(mib * Self::MIB)
+    }
+
+    /// Size of `gib` × 1024³ bytes.
+    pub const fn gib(gib: u64) -> Self {
+        Self(gib * Self::GIB)
+    }
+
+    /// Number of bytes.
+    pub const fn as_u64(&self) -> u64 {
+        self.0
+    }
+
+    /// Number of bytes, saturating on 32-bit targets.
+    pub fn as_usize(&self) -> usize {
+        usize::try_from(self.0).unwrap_or(usize::MAX)
+    }
+}
+
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+
+impl FromStr for ByteSize {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || ParseError::InvalidFormat {
+            expected: SIZE_FORMAT.to_string(),
+            actual: s.to_string(),
+        };
+
+        let input = s.trim();
+        let digits = input.len() - input.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        if digits == 0 {
+            return Err(invalid());
+        }
+        let value: u64 = input[..digits].parse().map_err(|_| invalid())?;
+
+        let multiplier = match input[digits..].trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "k" | "kb" | "kib" => Self::KIB,
+            "m" | "mb" | "mib" => Self::MIB,
+            "g" | "gb" | "gib" => Self::GIB,
+            "t" | "tb" | "tib" => Self::TIB,
+            _ => return Err(invalid()),
+        };
+
+        value.checked_mul(multiplier).map(Self).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (unit, size) in [
+            ("TB", Self::TIB),
+            ("GB", Self::GIB),
+            ("MB", Self::MIB),
+            ("KB", Self::KIB),
+        ] {
+            if self.0 >= size && self.0.is_multiple_of(size) {
+                return write!(f, "{}{}", self.0 / size, unit);
+            }
+        }
+        write!(f, "{}B", self.0)
+    }
+}
+
+impl Serialize for ByteSize {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for ByteSize {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(UnitVisitor::<Self>::new(SIZE_FORMAT, Self::bytes))
+    }
+}
+
+// ============================================================================
+// Deserialization
+// ============================================================================
+
+/// Accepts either the string form or a bare non-negative integer, which is
+/// converted with `from_int`.
+struct UnitVisitor<T> {
+    expecting: &'static str,
+    from_int: fn(u64) -> T,
+}
+
+impl<T> UnitVisitor<T> {
+    fn new(expecting: &'static str, from_int: fn(u64) -> T) -> Self {
+        Self {
+            expecting,
+            from_int,
+        }
+    }
+}
+
+impl<T> de::Visitor<'_> for UnitVisitor<T>
+where
+    T: FromStr<Err = ParseError>,
+{
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.expecting)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<T, E> {
+        Ok((self.from_int)(value))
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<T, E> {
+        u64::try_from(value)
+            .map(self.from_int)
+            .map_err(|_| E::invalid_value(de::Unexpected::Signed(value), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<T, E> {
+        value.parse().map_err(E::custom)
+    }
+}
+
+#[cfg(test)]
+#[path = "units_tests.rs"]
+mod tests;
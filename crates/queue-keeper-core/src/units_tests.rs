@@ -0,0 +1,174 @@
+//! Tests for human-readable configuration units.
+
+use super::*;
+
+// ============================================================================
+// HumanDuration
+// ============================================================================
+
+/// Single and compound units parse to the expected duration.
+#[test]
+fn test_duration_parses_units() {
+    for (input, expected) in [
+        ("500ms", Duration::from_millis(500)),
+        ("30s", Duration::from_secs(30)),
+        ("5m", Duration::from_secs(300)),
+        ("2h", Duration::from_secs(7200)),
+        ("7d", Duration::from_secs(7 * 86400)),
+        ("1h30m", Duration::from_secs(5400)),
+        ("1m30s250ms", Duration::from_millis(90_250)),
+        (" 45s ", Duration::from_secs(45)),
+    ] {
+        let parsed: HumanDuration = input.parse().unwrap();
+        assert_eq!(parsed.as_duration(), expected, "parsing {:?}", input);
+    }
+}
+
+/// A bare number is a number of seconds.
+#[test]
+fn test_duration_bare_number_is_seconds() {
+    assert_eq!(
+        "30".parse::<HumanDuration>().unwrap(),
+        HumanDuration::from_secs(30)
+    );
+}
+
+/// Malformed durations name the expected format and the input.
+#[test]
+fn test_duration_rejects_bad_formats() {
+    for input in [
+        "",
+        "s",
+        "30x",
+        "1.5s",
+        "-5s",
+        "5 m",
+        "30sec",
+        "99999999999999999999d",
+    ] {
+        let error = input.parse::<HumanDuration>().unwrap_err();
+        let message = error.to_string();
+        assert!(message.contains("duration"), "{}", message);
+        assert!(message.contains(input.trim()), "{}", message);
+    }
+}
+
+/// Durations display in canonical form and round-trip through it.
+#[test]
+fn test_duration_display_round_trips() {
+    for (duration, expected) in [
+        (Duration::ZERO, "0s"),
+        (Duration::from_millis(250), "250ms"),
+        (Duration::from_secs(90), "1m30s"),
+        (Duration::from_secs(3600), "1h"),
+        (Duration::from_secs(86400 + 1), "1d1s"),
+    ] {
+        let human = HumanDuration::from(duration);
+        assert_eq!(human.to_string(), expected);
+        assert_eq!(expected.parse::<HumanDuration>().unwrap(), human);
+    }
+}
+
+/// Strings and integers deserialize; negative numbers do not.
+#[test]
+fn test_duration_deserialization() {
+    let from_str: HumanDuration = serde_json::from_str("\"2m\"").unwrap();
+    assert_eq!(from_str.as_secs(), 120);
+
+    let from_int: HumanDuration = serde_yaml::from_str("45").unwrap();
+    assert_eq!(from_int.as_secs(), 45);
+
+    assert!(serde_json::from_str::<HumanDuration>("-1").is_err());
+    let error = serde_json::from_str::<HumanDuration>("\"ten seconds\"").unwrap_err();
+    assert!(error.to_string().contains("ten seconds"));
+
+    assert_eq!(
+        serde_json::to_string(&HumanDuration::from_secs(90)).unwrap(),
+        "\"1m30s\""
+    );
+}
+
+// ============================================================================
+// ByteSize
+// ============================================================================
+
+/// Units are binary and case-insensitive.
+#[test]
+fn test_size_parses_units() {
+    for (input, expected) in [
+        ("0", 0),
+        ("512", 512),
+        ("512B", 512),
+        ("64KB", 64 * 1024),
+        ("64kib", 64 * 1024),
+        ("10MB", 10 * 1024 * 1024),
+        ("10 MiB", 10 * 1024 * 1024),
+        ("1GB", 1024 * 1024 * 1024),
+        ("2T", 2 * 1024 * 1024 * 1024 * 1024),
+    ] {
+        assert_eq!(
+            input.parse::<ByteSize>().unwrap().as_u64(),
+            expected,
+            "parsing {:?}",
+            input
+        );
+    }
+}
+
+/// Malformed sizes name the expected format and the input.
+#[test]
+fn test_size_rejects_bad_formats() {
+    for input in ["", "MB", "10XB", "1.5MB", "-1MB", "99999999999TB"] {
+        let message = input.parse::<ByteSize>().unwrap_err().to_string();
+        assert!(message.contains("size"), "{}", message);
+        assert!(message.contains(input), "{}", message);
+    }
+}
+
+/// Sizes display with the largest exact unit.
+#[test]
+fn test_size_display_round_trips() {
+    for (size, expected) in [
+        (ByteSize::bytes(0), "0B"),
+        (ByteSize::bytes(1500), "1500B"),
+        (ByteSize::kib(64), "64KB"),
+        (ByteSize::mib(10), "10MB"),
+        (ByteSize::gib(2), "2GB"),
+    ] {
+        assert_eq!(size.to_string(), expected);
+        assert_eq!(expected.parse::<ByteSize>().unwrap(), size);
+    }
+}
+
+/// Strings and integers deserialize.
+#[test]
+fn test_size_deserialization() {
+    let from_str: ByteSize = serde_yaml::from_str("10MB").unwrap();
+    assert_eq!(from_str, ByteSize::mib(10));
+
+    let from_int: ByteSize = serde_json::from_str("10485760").unwrap();
+    assert_eq!(from_int, ByteSize::mib(10));
+
+    assert!(serde_json::from_str::<ByteSize>("\"ten megs\"").is_err());
+}
+
+// ============================================================================
+// Serde Adapter
+// ============================================================================
+
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct WithDuration {
+    #[serde(with = "human_duration")]
+    delay: Duration,
+}
+
+/// `Duration` fields accept and emit the human-readable form.
+#[test]
+fn test_human_duration_adapter() {
+    let value: WithDuration = serde_json::from_str(r#"{"delay":"1s500ms"}"#).unwrap();
+    assert_eq!(value.delay, Duration::from_millis(1500));
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        r#"{"delay":"1s500ms"}"#
+    );
+}
@@ -0,0 +1,650 @@
+//! # User-Defined Transforms
+//!
+//! Runs a bot-specific WebAssembly module over each event before it is
+//! delivered to that bot's queue, so routing decisions and payload reshaping
+//! that do not fit the static subscription rules can be changed without
+//! rebuilding Queue-Keeper. Transforms are configured per bot subscription
+//! ([`BotSubscription::transform`]).
+//!
+//! A transform runs after the subscription has matched and can only narrow
+//! routing: it either delivers the event (optionally with a new `payload`
+//! and `extensions`) or skips this bot. Event IDs, session, correlation and
+//! timing fields are never taken from the module's output, so a transform
+//! cannot break ordering or tracing.
+//!
+//! ## Module Interface
+//!
+//! Modules run in a sandbox with no host imports (no WASI, no clock, no
+//! network or filesystem); a module that imports anything fails to load.
+//! It must export:
+//!
+//! | Export | Signature | Purpose |
+//! |--------|-----------|---------|
+//! | `memory` | memory | Linear memory used to exchange data |
+//! | `qk_alloc` | `(len: i32) -> i32` | Reserve `len` bytes for the input and return their offset |
+//! | `qk_transform` | `(ptr: i32, len: i32) -> i64` | Transform the input; return `(out_ptr << 32) \| out_len` |
+//!
+//! The input is the UTF-8 JSON object `{"bot": "<bot name>", "event": <envelope>}`.
+//! The output is one of:
+//!
+//! ```json
+//! { "action": "deliver" }
+//! { "action": "deliver", "payload": { }, "extensions": { } }
+//! { "action": "skip" }
+//! ```
+//!
+//! ## Limits and Kill Switch
+//!
+//! Each invocation gets a fresh instance with a fuel budget (an instruction
+//! count limit) and a memory cap. A transform that fails
+//! `max_consecutive_failures` times in a row trips and is bypassed — events
+//! are delivered unchanged — until it is re-enabled. Operators can also
+//! switch a transform off and on at runtime with
+//! [`TransformRegistry::set_enabled`]; a disabled transform is bypassed the
+//! same way.
+//!
+//! The WebAssembly runtime is compiled in with the `wasm-transforms` cargo
+//! feature. Without it, [`TransformRegistry::load`] rejects configurations
+//! that use transforms.
+//!
+//! [`BotSubscription::transform`]: crate::bot_config::BotSubscription::transform
+
+use crate::{bot_config::BotConfiguration, webhook::WrappedEvent, BotName};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::PathBuf,
+    sync::atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+};
+use tracing::{info, warn};
+
+/// Default fuel (instruction budget) per invocation.
+pub const DEFAULT_TRANSFORM_FUEL: u64 = 50_000_000;
+
+/// Default memory cap per invocation (16 MiB).
+pub const DEFAULT_TRANSFORM_MAX_MEMORY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Default number of consecutive failures after which a transform trips.
+pub const DEFAULT_TRANSFORM_MAX_CONSECUTIVE_FAILURES: u32 = 5;
+
+/// Size of one WebAssembly memory page; the smallest usable memory cap.
+const WASM_PAGE_SIZE: usize = 64 * 1024;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Transform settings for a single bot subscription.
+///
+/// See specs/interfaces/bot-configuration.md
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WasmTransformConfig {
+    /// Path of the compiled `.wasm` module, relative to the working
+    /// directory of the service.
+    pub module: PathBuf,
+
+    /// Run the transform. `false` loads the module but bypasses it until it
+    /// is enabled at runtime.
+    #[serde(default = "WasmTransformConfig::default_enabled")]
+    pub enabled: bool,
+
+    /// Fuel (instruction budget) per invocation.
+    #[serde(default = "WasmTransformConfig::default_fuel")]
+    pub fuel: u64,
+
+    /// Maximum linear memory per invocation, in bytes.
+    #[serde(default = "WasmTransformConfig::default_max_memory_bytes")]
+    pub max_memory_bytes: usize,
+
+    /// Consecutive failures after which the transform trips and is
+    /// bypassed. `0` never trips.
+    #[serde(default = "WasmTransformConfig::default_max_consecutive_failures")]
+    pub max_consecutive_failures: u32,
+
+    /// What to do with an event when the transform fails.
+    #[serde(default)]
+    pub on_error: TransformFailureMode,
+}
+
+impl WasmTransformConfig {
+    fn default_enabled() -> bool {
+        true
+    }
+
+    fn default_fuel() -> u64 {
+        DEFAULT_TRANSFORM_FUEL
+    }
+
+    fn default_max_memory_bytes() -> usize {
+        DEFAULT_TRANSFORM_MAX_MEMORY_BYTES
+    }
+
+    fn default_max_consecutive_failures() -> u32 {
+        DEFAULT_TRANSFORM_MAX_CONSECUTIVE_FAILURES
+    }
+
+    /// Create settings for `module` with default limits.
+    pub fn new(module: impl Into<PathBuf>) -> Self {
+        Self {
+            module: module.into(),
+            enabled: Self::default_enabled(),
+            fuel: Self::default_fuel(),
+            max_memory_bytes: Self::default_max_memory_bytes(),
+            max_consecutive_failures: Self::default_max_consecutive_failures(),
+            on_error: TransformFailureMode::default(),
+        }
+    }
+
+    /// Check the module path and limits.
+    ///
+    /// # Errors
+    /// Returns a description of the first problem found.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.module.as_os_str().is_empty() {
+            return Err("module path must not be empty".to_string());
+        }
+        if self.fuel == 0 {
+            return Err("fuel must be greater than zero".to_string());
+        }
+        if self.max_memory_bytes < WASM_PAGE_SIZE {
+            return Err(format!(
+                "max_memory_bytes must be at least {} (one WebAssembly page)",
+                WASM_PAGE_SIZE
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Handling of events whose transform failed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransformFailureMode {
+    /// Fail the delivery to this bot; the event goes to the dead-letter
+    /// queue and can be replayed once the module is fixed.
+    #[default]
+    Fail,
+
+    /// Deliver the event unchanged.
+    DeliverOriginal,
+
+    /// Do not deliver the event to this bot.
+    Skip,
+}
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Errors from loading or running a transform.
+#[derive(Debug, thiserror::Error)]
+pub enum TransformError {
+    /// The module could not be read or compiled.
+    #[error("Failed to load transform module '{module}': {message}")]
+    ModuleLoad { module: String, message: String },
+
+    /// The module ran out of fuel.
+    #[error("Transform exceeded its fuel budget")]
+    FuelExhausted,
+
+    /// The module trapped or violated the interface.
+    #[error("Transform execution failed: {message}")]
+    Execution { message: String },
+
+    /// The module returned output that is not a valid decision.
+    #[error("Transform returned invalid output: {message}")]
+    InvalidOutput { message: String },
+
+    /// The bot configures a transform that was not loaded.
+    #[error("Transform is configured for bot '{bot_name}' but not loaded")]
+    NotLoaded { bot_name: String },
+
+    /// Transforms are configured but the runtime is not compiled in.
+    #[error("WebAssembly transforms are not compiled into this binary; rebuild with `--features wasm-transforms`")]
+    NotCompiled,
+}
+
+// ============================================================================
+// Transform Engine
+// ============================================================================
+
+/// Runs a transform over the serialized input and returns its raw output.
+///
+/// Implemented by [`WasmModule`] when the `wasm-transforms` feature is
+/// enabled.
+pub trait TransformEngine: Send + Sync {
+    /// Run the transform once.
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>, TransformError>;
+}
+
+/// Decision returned by a module.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+enum TransformOutput {
+    Deliver {
+        #[serde(default)]
+        payload: Option<Value>,
+        #[serde(default)]
+        extensions: Option<BTreeMap<String, Value>>,
+    },
+    Skip,
+}
+
+/// Result of applying a bot's transform to an event.
+#[derive(Debug, Clone)]
+pub enum TransformOutcome {
+    /// Deliver the event as it is.
+    Unchanged,
+
+    /// Deliver this event instead.
+    Replaced(Box<WrappedEvent>),
+
+    /// Do not deliver the event to this bot.
+    Skip,
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Runtime state of one bot's transform.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransformStatus {
+    pub bot_name: String,
+    pub module: String,
+
+    /// Switched on by configuration or at runtime.
+    pub enabled: bool,
+
+    /// Bypassed after too many consecutive failures.
+    pub tripped: bool,
+
+    pub consecutive_failures: u32,
+    pub invocations: u64,
+    pub failures: u64,
+    pub skipped: u64,
+}
+
+struct BotTransform {
+    config: WasmTransformConfig,
+    engine: Box<dyn TransformEngine>,
+    enabled: AtomicBool,
+    tripped: AtomicBool,
+    consecutive_failures: AtomicU32,
+    invocations: AtomicU64,
+    failures: AtomicU64,
+    skipped: AtomicU64,
+}
+
+impl BotTransform {
+    fn status(&self, bot_name: &str) -> TransformStatus {
+        TransformStatus {
+            bot_name: bot_name.to_string(),
+            module: self.config.module.display().to_string(),
+            enabled: self.enabled.load(Ordering::Relaxed),
+            tripped: self.tripped.load(Ordering::Relaxed),
+            consecutive_failures: self.consecutive_failures.load(Ordering::Relaxed),
+            invocations: self.invocations.load(Ordering::Relaxed),
+            failures: self.failures.load(Ordering::Relaxed),
+            skipped: self.skipped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Loaded transforms, keyed by bot name.
+///
+/// Shared by the event router, which applies transforms, and the admin API,
+/// which reports their state and switches them on and off.
+#[derive(Default)]
+pub struct TransformRegistry {
+    transforms: HashMap<String, BotTransform>,
+}
+
+impl std::fmt::Debug for TransformRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransformRegistry")
+            .field("transforms", &self.statuses())
+            .finish()
+    }
+}
+
+impl TransformRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the transform modules of every bot that configures one.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransformError::ModuleLoad`] if a module cannot be compiled,
+    /// or [`TransformError::NotCompiled`] if transforms are configured in a
+    /// build without the `wasm-transforms` feature.
+    pub fn load(config: &BotConfiguration) -> Result<Self, TransformError> {
+        let mut registry = Self::new();
+        for bot in &config.bots {
+            let Some(transform) = &bot.transform else {
+                continue;
+            };
+
+            let engine = Self::compile(transform)?;
+            info!(
+                bot_name = %bot.name,
+                module = %transform.module.display(),
+                enabled = transform.enabled,
+                "Loaded WebAssembly transform"
+            );
+            registry.register(&bot.name, transform.clone(), engine);
+        }
+        Ok(registry)
+    }
+
+    #[cfg(feature = "wasm-transforms")]
+    fn compile(config: &WasmTransformConfig) -> Result<Box<dyn TransformEngine>, TransformError> {
+        Ok(Box::new(WasmModule::load(config)?))
+    }
+
+    #[cfg(not(feature = "wasm-transforms"))]
+    fn compile(_config: &WasmTransformConfig) -> Result<Box<dyn TransformEngine>, TransformError> {
+        Err(TransformError::NotCompiled)
+    }
+
+    /// Register `engine` as the transform of `bot_name`, replacing any
+    /// existing one.
+    pub fn register(
+        &mut self,
+        bot_name: &BotName,
+        config: WasmTransformConfig,
+        engine: Box<dyn TransformEngine>,
+    ) {
+        let enabled = config.enabled;
+        self.transforms.insert(
+            bot_name.as_str().to_string(),
+            BotTransform {
+                config,
+                engine,
+                enabled: AtomicBool::new(enabled),
+                tripped: AtomicBool::new(false),
+                consecutive_failures: AtomicU32::new(0),
+                invocations: AtomicU64::new(0),
+                failures: AtomicU64::new(0),
+                skipped: AtomicU64::new(0),
+            },
+        );
+    }
+
+    /// Check whether a transform is loaded for `bot_name`
+    pub fn contains(&self, bot_name: &BotName) -> bool {
+        self.transforms.contains_key(bot_name.as_str())
+    }
+
+    /// Check whether no transforms are loaded
+    pub fn is_empty(&self) -> bool {
+        self.transforms.is_empty()
+    }
+
+    /// Apply the transform of `bot_name` to `event`.
+    ///
+    /// Disabled and tripped transforms return [`TransformOutcome::Unchanged`].
+    /// Failures are handled according to the transform's
+    /// [`TransformFailureMode`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransformError::NotLoaded`] if no transform is loaded for
+    /// the bot, or the transform's error when it fails in
+    /// [`TransformFailureMode::Fail`] mode.
+    pub fn apply(
+        &self,
+        event: &WrappedEvent,
+        bot_name: &BotName,
+    ) -> Result<TransformOutcome, TransformError> {
+        let transform =
+            self.transforms
+                .get(bot_name.as_str())
+                .ok_or_else(|| TransformError::NotLoaded {
+                    bot_name: bot_name.as_str().to_string(),
+                })?;
+
+        if !transform.enabled.load(Ordering::Relaxed) || transform.tripped.load(Ordering::Relaxed) {
+            return Ok(TransformOutcome::Unchanged);
+        }
+
+        transform.invocations.fetch_add(1, Ordering::Relaxed);
+        match Self::run(transform, event, bot_name) {
+            Ok(outcome) => {
+                transform.consecutive_failures.store(0, Ordering::Relaxed);
+                if matches!(outcome, TransformOutcome::Skip) {
+                    transform.skipped.fetch_add(1, Ordering::Relaxed);
+                }
+                Ok(outcome)
+            }
+            Err(error) => {
+                transform.failures.fetch_add(1, Ordering::Relaxed);
+                let consecutive = transform
+                    .consecutive_failures
+                    .fetch_add(1, Ordering::Relaxed)
+                    + 1;
+                let limit = transform.config.max_consecutive_failures;
+                if limit > 0
+                    && consecutive >= limit
+                    && !transform.tripped.swap(true, Ordering::Relaxed)
+                {
+                    warn!(
+                        bot_name = %bot_name,
+                        consecutive_failures = consecutive,
+                        error = %error,
+                        "Transform tripped after repeated failures; delivering events unchanged until re-enabled"
+                    );
+                }
+
+                match transform.config.on_error {
+                    TransformFailureMode::Fail => Err(error),
+                    TransformFailureMode::DeliverOriginal => Ok(TransformOutcome::Unchanged),
+                    TransformFailureMode::Skip => {
+                        transform.skipped.fetch_add(1, Ordering::Relaxed);
+                        Ok(TransformOutcome::Skip)
+                    }
+                }
+            }
+        }
+    }
+
+    fn run(
+        transform: &BotTransform,
+        event: &WrappedEvent,
+        bot_name: &BotName,
+    ) -> Result<TransformOutcome, TransformError> {
+        let input = serde_json::to_vec(&serde_json::json!({
+            "bot": bot_name.as_str(),
+            "event": event,
+        }))
+        .map_err(|e| TransformError::Execution {
+            message: format!("failed to serialize input: {}", e),
+        })?;
+
+        let output = transform.engine.run(&input)?;
+        let output: TransformOutput =
+            serde_json::from_slice(&output).map_err(|e| TransformError::InvalidOutput {
+                message: e.to_string(),
+            })?;
+
+        Ok(match output {
+            TransformOutput::Skip => TransformOutcome::Skip,
+            TransformOutput::Deliver {
+                payload: None,
+                extensions: None,
+            } => TransformOutcome::Unchanged,
+            TransformOutput::Deliver {
+                payload,
+                extensions,
+            } => {
+                let mut transformed = event.clone();
+                if let Some(payload) = payload {
+                    transformed.payload = payload;
+                }
+                if let Some(extensions) = extensions {
+                    transformed.extensions = extensions;
+                }
+                TransformOutcome::Replaced(Box::new(transformed))
+            }
+        })
+    }
+
+    /// Switch the transform of `bot_name` on or off.
+    ///
+    /// Enabling also clears a tripped state and the consecutive failure
+    /// count. Returns the new status, or `None` if no transform is loaded
+    /// for the bot.
+    pub fn set_enabled(&self, bot_name: &BotName, enabled: bool) -> Option<TransformStatus> {
+        let transform = self.transforms.get(bot_name.as_str())?;
+        transform.enabled.store(enabled, Ordering::Relaxed);
+        if enabled {
+            transform.tripped.store(false, Ordering::Relaxed);
+            transform.consecutive_failures.store(0, Ordering::Relaxed);
+        }
+        Some(transform.status(bot_name.as_str()))
+    }
+
+    /// Status of every loaded transform, ordered by bot name
+    pub fn statuses(&self) -> Vec<TransformStatus> {
+        let mut statuses: Vec<TransformStatus> = self
+            .transforms
+            .iter()
+            .map(|(bot_name, transform)| transform.status(bot_name))
+            .collect();
+        statuses.sort_by(|a, b| a.bot_name.cmp(&b.bot_name));
+        statuses
+    }
+}
+
+// ============================================================================
+// WebAssembly Runtime
+// ============================================================================
+
+/// Compiled WebAssembly transform module.
+///
+/// Each [`run`](TransformEngine::run) instantiates the module in a fresh
+/// store with the configured fuel and memory limits and no host imports.
+#[cfg(feature = "wasm-transforms")]
+pub struct WasmModule {
+    engine: wasmtime::Engine,
+    module: wasmtime::Module,
+    fuel: u64,
+    max_memory_bytes: usize,
+}
+
+#[cfg(feature = "wasm-transforms")]
+impl WasmModule {
+    /// Compile the module named by `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransformError::ModuleLoad`] if the file cannot be read or
+    /// is not a valid module.
+    pub fn load(config: &WasmTransformConfig) -> Result<Self, TransformError> {
+        let bytes = std::fs::read(&config.module).map_err(|e| TransformError::ModuleLoad {
+            module: config.module.display().to_string(),
+            message: e.to_string(),
+        })?;
+        Self::from_bytes(&bytes, config).map_err(|e| match e {
+            TransformError::ModuleLoad { message, .. } => TransformError::ModuleLoad {
+                module: config.module.display().to_string(),
+                message,
+            },
+            other => other,
+        })
+    }
+
+    /// Compile a module from its binary (or, for tests, text) form.
+    ///
+    /// The module path in `config` is only used for error messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`TransformError::ModuleLoad`] if the bytes are not a valid
+    /// module.
+    pub fn from_bytes(bytes: &[u8], config: &WasmTransformConfig) -> Result<Self, TransformError> {
+        let load_error = |e: wasmtime::Error| TransformError::ModuleLoad {
+            module: config.module.display().to_string(),
+            message: e.to_string(),
+        };
+
+        let mut engine_config = wasmtime::Config::new();
+        engine_config.consume_fuel(true);
+        let engine = wasmtime::Engine::new(&engine_config).map_err(load_error)?;
+        let module = wasmtime::Module::new(&engine, bytes).map_err(load_error)?;
+
+        Ok(Self {
+            engine,
+            module,
+            fuel: config.fuel,
+            max_memory_bytes: config.max_memory_bytes,
+        })
+    }
+}
+
+#[cfg(feature = "wasm-transforms")]
+impl TransformEngine for WasmModule {
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>, TransformError> {
+        use wasmtime::{Linker, Store, StoreLimits, StoreLimitsBuilder, Trap};
+
+        let execution = |e: wasmtime::Error| match e.downcast_ref::<Trap>() {
+            Some(Trap::OutOfFuel) => TransformError::FuelExhausted,
+            _ => TransformError::Execution {
+                message: e.to_string(),
+            },
+        };
+        let interface = |message: String| TransformError::Execution { message };
+
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.max_memory_bytes)
+            .instances(1)
+            .trap_on_grow_failure(true)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.fuel).map_err(execution)?;
+
+        // No host functions are defined, so modules with imports fail here
+        let linker: Linker<StoreLimits> = Linker::new(&self.engine);
+        let instance = linker
+            .instantiate(&mut store, &self.module)
+            .map_err(execution)?;
+
+        let memory = instance
+            .get_memory(&mut store, "memory")
+            .ok_or_else(|| interface("module does not export 'memory'".to_string()))?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&mut store, "qk_alloc")
+            .map_err(|e| interface(format!("invalid 'qk_alloc' export: {}", e)))?;
+        let transform = instance
+            .get_typed_func::<(i32, i32), i64>(&mut store, "qk_transform")
+            .map_err(|e| interface(format!("invalid 'qk_transform' export: {}", e)))?;
+
+        let input_len =
+            i32::try_from(input.len()).map_err(|_| interface("input exceeds 2 GiB".to_string()))?;
+        let input_ptr = alloc.call(&mut store, input_len).map_err(execution)?;
+        memory
+            .write(&mut store, input_ptr as u32 as usize, input)
+            .map_err(|e| interface(format!("'qk_alloc' returned an invalid buffer: {}", e)))?;
+
+        let packed = transform
+            .call(&mut store, (input_ptr, input_len))
+            .map_err(execution)?;
+        let output_ptr = (packed as u64 >> 32) as usize;
+        let output_len = (packed as u64 & 0xffff_ffff) as usize;
+
+        let mut output = vec![0u8; output_len];
+        memory
+            .read(&store, output_ptr, &mut output)
+            .map_err(|e| interface(format!("output is outside linear memory: {}", e)))?;
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+#[path = "wasm_transform_tests.rs"]
+mod tests;
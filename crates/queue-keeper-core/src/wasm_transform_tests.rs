@@ -0,0 +1,342 @@
+//! Tests for user-defined transforms.
+
+use super::*;
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+/// Engine replaying scripted results and recording its inputs.
+struct ScriptedEngine {
+    results: Mutex<Vec<Result<&'static str, &'static str>>>,
+    inputs: Arc<Mutex<Vec<Value>>>,
+}
+
+impl ScriptedEngine {
+    fn new(results: Vec<Result<&'static str, &'static str>>) -> Self {
+        Self {
+            results: Mutex::new(results),
+            inputs: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+impl TransformEngine for ScriptedEngine {
+    fn run(&self, input: &[u8]) -> Result<Vec<u8>, TransformError> {
+        self.inputs
+            .lock()
+            .unwrap()
+            .push(serde_json::from_slice(input).unwrap());
+        let mut results = self.results.lock().unwrap();
+        let next = if results.len() > 1 {
+            results.remove(0)
+        } else {
+            results[0]
+        };
+        next.map(|output| output.as_bytes().to_vec())
+            .map_err(|message| TransformError::Execution {
+                message: message.to_string(),
+            })
+    }
+}
+
+fn bot() -> BotName {
+    BotName::new("custom-bot").unwrap()
+}
+
+fn event() -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        None,
+        json!({ "number": 7, "title": "PLAT-1" }),
+        None,
+    )
+}
+
+fn registry(config: WasmTransformConfig, engine: ScriptedEngine) -> TransformRegistry {
+    let mut registry = TransformRegistry::new();
+    registry.register(&bot(), config, Box::new(engine));
+    registry
+}
+
+// ============================================================================
+// Decisions
+// ============================================================================
+
+/// The module sees the bot and envelope; its payload replaces the original
+/// while identity fields are kept.
+#[test]
+fn test_deliver_with_payload_replaces_payload_only() {
+    let engine = ScriptedEngine::new(vec![Ok(
+        r#"{"action":"deliver","payload":{"number":7},"extensions":{"team":"web"}}"#,
+    )]);
+    let inputs = engine.inputs.clone();
+    let registry = registry(WasmTransformConfig::new("custom.wasm"), engine);
+    let event = event();
+
+    let TransformOutcome::Replaced(transformed) = registry.apply(&event, &bot()).unwrap() else {
+        panic!("expected a replaced event");
+    };
+    assert_eq!(transformed.payload, json!({ "number": 7 }));
+    assert_eq!(transformed.extensions["team"], json!("web"));
+    assert_eq!(transformed.event_id, event.event_id);
+    assert_eq!(transformed.correlation_id, event.correlation_id);
+
+    let input = &inputs.lock().unwrap()[0];
+    assert_eq!(input["bot"], "custom-bot");
+    assert_eq!(input["event"]["event_id"], event.event_id.to_string());
+}
+
+/// Plain deliver and skip decisions are reported as such.
+#[test]
+fn test_deliver_and_skip_decisions() {
+    let registry = registry(
+        WasmTransformConfig::new("custom.wasm"),
+        ScriptedEngine::new(vec![
+            Ok(r#"{"action":"deliver"}"#),
+            Ok(r#"{"action":"skip"}"#),
+        ]),
+    );
+
+    assert!(matches!(
+        registry.apply(&event(), &bot()).unwrap(),
+        TransformOutcome::Unchanged
+    ));
+    assert!(matches!(
+        registry.apply(&event(), &bot()).unwrap(),
+        TransformOutcome::Skip
+    ));
+    assert_eq!(registry.statuses()[0].skipped, 1);
+}
+
+/// Output that is not a decision is a failure.
+#[test]
+fn test_invalid_output_fails() {
+    let registry = registry(
+        WasmTransformConfig::new("custom.wasm"),
+        ScriptedEngine::new(vec![Ok(r#"{"action":"explode"}"#)]),
+    );
+
+    assert!(matches!(
+        registry.apply(&event(), &bot()),
+        Err(TransformError::InvalidOutput { .. })
+    ));
+}
+
+/// Failures follow the configured failure mode.
+#[test]
+fn test_failure_modes() {
+    for (mode, expected) in [
+        (TransformFailureMode::DeliverOriginal, "unchanged"),
+        (TransformFailureMode::Skip, "skip"),
+    ] {
+        let mut config = WasmTransformConfig::new("custom.wasm");
+        config.on_error = mode;
+        let registry = registry(config, ScriptedEngine::new(vec![Err("trap")]));
+
+        let outcome = match registry.apply(&event(), &bot()).unwrap() {
+            TransformOutcome::Unchanged => "unchanged",
+            TransformOutcome::Skip => "skip",
+            TransformOutcome::Replaced(_) => "replaced",
+        };
+        assert_eq!(outcome, expected);
+    }
+}
+
+/// Bots without a loaded transform are reported, not bypassed.
+#[test]
+fn test_unknown_bot_is_not_loaded() {
+    let registry = TransformRegistry::new();
+    assert!(matches!(
+        registry.apply(&event(), &bot()),
+        Err(TransformError::NotLoaded { .. })
+    ));
+}
+
+// ============================================================================
+// Kill Switch
+// ============================================================================
+
+/// Repeated failures trip the transform; re-enabling resets it.
+#[test]
+fn test_consecutive_failures_trip_transform() {
+    let mut config = WasmTransformConfig::new("custom.wasm");
+    config.max_consecutive_failures = 2;
+    let registry = registry(
+        config,
+        ScriptedEngine::new(vec![Err("trap"), Err("trap"), Ok(r#"{"action":"skip"}"#)]),
+    );
+
+    assert!(registry.apply(&event(), &bot()).is_err());
+    assert!(registry.apply(&event(), &bot()).is_err());
+    assert!(registry.statuses()[0].tripped);
+
+    // Tripped transforms are bypassed without running the module
+    assert!(matches!(
+        registry.apply(&event(), &bot()).unwrap(),
+        TransformOutcome::Unchanged
+    ));
+    assert_eq!(registry.statuses()[0].invocations, 2);
+
+    let status = registry.set_enabled(&bot(), true).unwrap();
+    assert!(!status.tripped);
+    assert_eq!(status.consecutive_failures, 0);
+    assert!(matches!(
+        registry.apply(&event(), &bot()).unwrap(),
+        TransformOutcome::Skip
+    ));
+}
+
+/// A disabled transform delivers events unchanged.
+#[test]
+fn test_disabled_transform_is_bypassed() {
+    let mut config = WasmTransformConfig::new("custom.wasm");
+    config.enabled = false;
+    let registry = registry(
+        config,
+        ScriptedEngine::new(vec![Ok(r#"{"action":"skip"}"#)]),
+    );
+
+    assert!(matches!(
+        registry.apply(&event(), &bot()).unwrap(),
+        TransformOutcome::Unchanged
+    ));
+    assert!(registry.set_enabled(&bot(), true).unwrap().enabled);
+    assert!(matches!(
+        registry.apply(&event(), &bot()).unwrap(),
+        TransformOutcome::Skip
+    ));
+    assert!(registry
+        .set_enabled(&BotName::new("other-bot").unwrap(), false)
+        .is_none());
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Limits default when omitted and invalid limits are rejected.
+#[test]
+fn test_config_defaults_and_validation() {
+    let config: WasmTransformConfig =
+        serde_json::from_value(json!({ "module": "transforms/custom.wasm" })).unwrap();
+    assert!(config.enabled);
+    assert_eq!(config.fuel, DEFAULT_TRANSFORM_FUEL);
+    assert_eq!(config.on_error, TransformFailureMode::Fail);
+    assert!(config.validate().is_ok());
+
+    let mut invalid = config.clone();
+    invalid.fuel = 0;
+    assert!(invalid.validate().is_err());
+
+    let mut invalid = config;
+    invalid.max_memory_bytes = 1024;
+    assert!(invalid.validate().is_err());
+}
+
+/// Builds without the runtime refuse configurations that use transforms.
+#[cfg(not(feature = "wasm-transforms"))]
+#[test]
+fn test_load_without_runtime_is_rejected() {
+    use crate::{
+        bot_config::{BotConfigurationSettings, BotSpecificConfig, BotSubscription},
+        QueueName,
+    };
+
+    let config = BotConfiguration {
+        bots: vec![BotSubscription {
+            name: bot(),
+            queue: QueueName::new("queue-keeper-custom-bot".to_string()).unwrap(),
+            events: vec![],
+            ordered: false,
+            repository_filter: None,
+            config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: Some(WasmTransformConfig::new("custom.wasm")),
+            projection: None,
+            http: None,
+            sender_filter: None,
+        }],
+        settings: BotConfigurationSettings::default(),
+    };
+
+    assert!(matches!(
+        TransformRegistry::load(&config),
+        Err(TransformError::NotCompiled)
+    ));
+}
+
+// ============================================================================
+// WebAssembly Runtime
+// ============================================================================
+
+#[cfg(feature = "wasm-transforms")]
+mod wasm_module_tests {
+    use super::*;
+
+    /// Module that always answers `{"action":"skip"}`.
+    const SKIP_MODULE: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (global $next (mut i32) (i32.const 1024))
+          (data (i32.const 0) "{\"action\":\"skip\"}")
+          (func (export "qk_alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get $len)))
+            (local.get $ptr))
+          (func (export "qk_transform") (param i32 i32) (result i64)
+            (i64.const 17)))
+    "#;
+
+    /// Module that never returns.
+    const LOOP_MODULE: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (func (export "qk_alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "qk_transform") (param i32 i32) (result i64)
+            (loop $spin (br $spin))
+            (i64.const 0)))
+    "#;
+
+    /// Module asking the host for a function.
+    const IMPORTING_MODULE: &str = r#"
+        (module
+          (import "wasi_snapshot_preview1" "fd_write"
+            (func (param i32 i32 i32 i32) (result i32)))
+          (memory (export "memory") 1)
+          (func (export "qk_alloc") (param i32) (result i32) (i32.const 0))
+          (func (export "qk_transform") (param i32 i32) (result i64) (i64.const 0)))
+    "#;
+
+    fn module(source: &str) -> WasmModule {
+        let mut config = WasmTransformConfig::new("test.wasm");
+        config.fuel = 1_000_000;
+        WasmModule::from_bytes(source.as_bytes(), &config).unwrap()
+    }
+
+    /// A module's decision is read back from its linear memory.
+    #[test]
+    fn test_module_output_is_returned() {
+        let output = module(SKIP_MODULE).run(br#"{"bot":"custom-bot"}"#).unwrap();
+        assert_eq!(output, br#"{"action":"skip"}"#);
+    }
+
+    /// Runaway modules stop when their fuel runs out.
+    #[test]
+    fn test_fuel_limits_execution() {
+        assert!(matches!(
+            module(LOOP_MODULE).run(b"{}"),
+            Err(TransformError::FuelExhausted)
+        ));
+    }
+
+    /// Modules get no host imports.
+    #[test]
+    fn test_imports_are_rejected() {
+        assert!(matches!(
+            module(IMPORTING_MODULE).run(b"{}"),
+            Err(TransformError::Execution { .. })
+        ));
+    }
+}
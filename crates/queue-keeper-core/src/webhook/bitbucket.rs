@@ -0,0 +1,227 @@
+//! Bitbucket Cloud webhook source.
+//!
+//! Reads Bitbucket's `X-Event-Key`, `X-Request-UUID` and `X-Hub-Signature`
+//! headers. The event key is split into the event type and action:
+//! `pullrequest:created` becomes event type `pullrequest` with action
+//! `created`. Payloads map onto [`EventEntity`]:
+//!
+//! | Event key       | Entity                                   |
+//! |-----------------|------------------------------------------|
+//! | `pullrequest:*` | `PullRequest` (the pull request ID)      |
+//! | `issue:*`       | `Issue` (the issue ID)                   |
+//! | `repo:push`     | `Branch` or `Release` of the first change |
+//! | other `repo:*`  | `Repository`                             |
+//!
+//! Bitbucket signs the body with HMAC-SHA256 of the webhook secret, sent as
+//! `X-Hub-Signature: sha256=<hex>`.
+
+use crate::{
+    webhook::{
+        EventEntity, EventEnvelope, NormalizationError, WebhookHeaders, WebhookSource,
+        WebhookSourceKind,
+    },
+    Repository, RepositoryId, User, UserId, UserType, ValidationError,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// [`WebhookSource`] for Bitbucket Cloud repository webhooks.
+///
+/// Bitbucket identifies repositories and accounts by UUID only: repository,
+/// owner and sender IDs are `0`, and `full_name` (`workspace/repo`)
+/// identifies the repository.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BitbucketSource;
+
+impl BitbucketSource {
+    /// Event type and action of an `X-Event-Key` header value.
+    pub fn split_event_key(key: &str) -> (&str, Option<&str>) {
+        match key.split_once(':') {
+            Some((event_type, action)) => (event_type, Some(action)),
+            None => (key, None),
+        }
+    }
+
+    fn repository(payload: &Value) -> Result<Repository, NormalizationError> {
+        let repository = payload
+            .get("repository")
+            .ok_or_else(|| missing("repository"))?;
+        let full_name = repository
+            .get("full_name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| missing("repository.full_name"))?;
+        let (workspace, name) =
+            full_name
+                .split_once('/')
+                .ok_or_else(|| NormalizationError::InvalidFieldFormat {
+                    field: "repository.full_name".to_string(),
+                    message: format!("'{}' has no workspace", full_name),
+                })?;
+        let private = repository
+            .get("is_private")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let user_type = match repository
+            .get("owner")
+            .and_then(|o| o.get("type"))
+            .and_then(Value::as_str)
+        {
+            Some("user") => UserType::User,
+            _ => UserType::Organization,
+        };
+
+        let owner = User {
+            id: UserId::new(0),
+            login: workspace.to_string(),
+            user_type,
+        };
+        Ok(Repository::new(
+            RepositoryId::new(0),
+            name.to_string(),
+            full_name.to_string(),
+            owner,
+            private,
+        ))
+    }
+
+    fn entity(event_type: &str, action: Option<&str>, payload: &Value) -> EventEntity {
+        let id = |field: &str| {
+            payload
+                .get(field)
+                .and_then(|o| o.get("id"))
+                .and_then(Value::as_u64)
+                .map(|n| n as u32)
+        };
+
+        let entity = match (event_type, action) {
+            ("pullrequest", _) => {
+                id("pullrequest").map(|number| EventEntity::PullRequest { number })
+            }
+            ("issue", _) => id("issue").map(|number| EventEntity::Issue { number }),
+            ("repo", Some("push")) => {
+                let new = payload.pointer("/push/changes/0/new");
+                let name = new
+                    .and_then(|n| n.get("name"))
+                    .and_then(Value::as_str)
+                    .map(String::from);
+                match new.and_then(|n| n.get("type")).and_then(Value::as_str) {
+                    Some("branch") => name.map(|name| EventEntity::Branch { name }),
+                    Some("tag") => name.map(|tag| EventEntity::Release { tag }),
+                    _ => None,
+                }
+            }
+            ("repo", _) => Some(EventEntity::Repository),
+            _ => None,
+        };
+        entity.unwrap_or(EventEntity::Unknown)
+    }
+
+    fn sender(payload: &Value) -> Option<User> {
+        let actor = payload.get("actor")?;
+        let login = actor
+            .get("nickname")
+            .or_else(|| actor.get("display_name"))
+            .and_then(Value::as_str)?;
+        let user_type = match actor.get("type").and_then(Value::as_str) {
+            Some("team") => UserType::Organization,
+            Some("app_user") => UserType::Bot,
+            _ => UserType::User,
+        };
+        Some(User {
+            id: UserId::new(0),
+            login: login.to_string(),
+            user_type,
+        })
+    }
+}
+
+impl WebhookSource for BitbucketSource {
+    fn kind(&self) -> WebhookSourceKind {
+        WebhookSourceKind::Bitbucket
+    }
+
+    /// `X-Event-Key` is required; a delivery without `X-Request-UUID` gets
+    /// a generated delivery ID.
+    fn parse_headers(
+        &self,
+        headers: &HashMap<String, String>,
+    ) -> Result<WebhookHeaders, ValidationError> {
+        let event_key = headers
+            .get("x-event-key")
+            .ok_or_else(|| ValidationError::Required {
+                field: "X-Event-Key".to_string(),
+            })?;
+
+        Ok(WebhookHeaders {
+            event_type: event_key.clone(),
+            delivery_id: headers
+                .get("x-request-uuid")
+                .cloned()
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            signature: headers.get("x-hub-signature").cloned(),
+            user_agent: headers.get("user-agent").cloned(),
+            content_type: headers
+                .get("content-type")
+                .cloned()
+                .unwrap_or_else(|| "application/json".to_string()),
+        })
+    }
+
+    fn authenticate(
+        &self,
+        payload: &[u8],
+        credential: &str,
+        secret: &str,
+    ) -> Result<(), ValidationError> {
+        use hmac::{Hmac, KeyInit, Mac};
+        use sha2::Sha256;
+
+        let invalid = |message: &str| ValidationError::InvalidFormat {
+            field: "X-Hub-Signature".to_string(),
+            message: message.to_string(),
+        };
+        let signature = credential
+            .strip_prefix("sha256=")
+            .ok_or_else(|| invalid("signature must start with 'sha256='"))?;
+        let signature = hex::decode(signature).map_err(|_| invalid("invalid hex encoding"))?;
+
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .map_err(|_| invalid("failed to initialize HMAC-SHA256"))?;
+        mac.update(payload);
+        mac.verify_slice(&signature)
+            .map_err(|_| invalid("HMAC-SHA256 signature mismatch"))
+    }
+
+    /// The event key header value is passed as `event_type`; the envelope
+    /// gets its event type and action.
+    fn normalize(
+        &self,
+        event_type: &str,
+        payload: Value,
+    ) -> Result<EventEnvelope, NormalizationError> {
+        let (event_type, action) = Self::split_event_key(event_type);
+        let repository = Self::repository(&payload)?;
+        let entity = Self::entity(event_type, action, &payload);
+        let sender = Self::sender(&payload);
+
+        let mut envelope = EventEnvelope::new(
+            event_type.to_string(),
+            action.map(String::from),
+            repository,
+            entity,
+            payload,
+        );
+        envelope.sender = sender;
+        Ok(envelope)
+    }
+}
+
+fn missing(field: &str) -> NormalizationError {
+    NormalizationError::MissingRequiredField {
+        field: field.to_string(),
+    }
+}
+
+#[cfg(test)]
+#[path = "bitbucket_tests.rs"]
+mod tests;
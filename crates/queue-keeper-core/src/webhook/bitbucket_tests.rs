@@ -0,0 +1,141 @@
+//! Tests for the Bitbucket webhook source.
+
+use super::*;
+use hmac::{Hmac, KeyInit, Mac};
+use serde_json::json;
+use sha2::Sha256;
+
+fn repository() -> Value {
+    json!({
+        "uuid": "{6a3f2b1c-0000-4000-8000-000000000001}",
+        "name": "widgets",
+        "full_name": "acme/widgets",
+        "is_private": true,
+        "owner": { "type": "team", "username": "acme" },
+    })
+}
+
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Event keys are split into event type and action.
+#[test]
+fn test_split_event_key() {
+    assert_eq!(
+        BitbucketSource::split_event_key("pullrequest:comment_created"),
+        ("pullrequest", Some("comment_created"))
+    );
+    assert_eq!(BitbucketSource::split_event_key("ping"), ("ping", None));
+}
+
+/// The event key, request UUID and signature are read from Bitbucket's
+/// headers; the event key is required.
+#[test]
+fn test_parse_headers() {
+    let headers: HashMap<String, String> = [
+        ("x-event-key", "repo:push"),
+        ("x-request-uuid", "5b0c1d2e-3f40-4a51-8b62-7c83d9e4f5a6"),
+        ("x-hub-signature", "sha256=00"),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value.to_string()))
+    .collect();
+
+    let parsed = BitbucketSource.parse_headers(&headers).unwrap();
+    assert_eq!(parsed.event_type, "repo:push");
+    assert_eq!(parsed.delivery_id, "5b0c1d2e-3f40-4a51-8b62-7c83d9e4f5a6");
+    assert_eq!(parsed.signature.as_deref(), Some("sha256=00"));
+
+    assert!(BitbucketSource.parse_headers(&HashMap::new()).is_err());
+}
+
+/// Only the HMAC-SHA256 of the body with the secret is accepted.
+#[test]
+fn test_authenticate_checks_hmac() {
+    let body = br#"{"repository":{}}"#;
+    let signature = sign("s3cret", body);
+
+    assert!(BitbucketSource
+        .authenticate(body, &signature, "s3cret")
+        .is_ok());
+    assert!(BitbucketSource
+        .authenticate(body, &signature, "other")
+        .is_err());
+    assert!(BitbucketSource
+        .authenticate(b"{}", &signature, "s3cret")
+        .is_err());
+    assert!(BitbucketSource
+        .authenticate(body, signature.trim_start_matches("sha256="), "s3cret")
+        .is_err());
+}
+
+/// Pull request events are sessions of the pull request.
+#[test]
+fn test_normalize_pull_request() {
+    let envelope = BitbucketSource
+        .normalize(
+            "pullrequest:created",
+            json!({
+                "actor": { "type": "user", "nickname": "alice" },
+                "repository": repository(),
+                "pullrequest": { "id": 42, "title": "Add widgets" },
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(envelope.event_type, "pullrequest");
+    assert_eq!(envelope.action.as_deref(), Some("created"));
+    assert_eq!(envelope.entity, EventEntity::PullRequest { number: 42 });
+    assert_eq!(envelope.repository.owner.login, "acme");
+    assert!(envelope.repository.private);
+    assert_eq!(envelope.sender.unwrap().login, "alice");
+    assert_eq!(envelope.session_id.as_str(), "acme/widgets/pull_request/42");
+}
+
+/// Pushes are sessions of the first branch or tag changed; other
+/// repository events belong to the repository.
+#[test]
+fn test_normalize_repository_events() {
+    let push = |new: Value| {
+        BitbucketSource
+            .normalize(
+                "repo:push",
+                json!({
+                    "repository": repository(),
+                    "push": { "changes": [{ "new": new }] },
+                }),
+            )
+            .unwrap()
+            .entity
+    };
+    assert_eq!(
+        push(json!({ "type": "branch", "name": "main" })),
+        EventEntity::Branch {
+            name: "main".to_string()
+        }
+    );
+    assert_eq!(
+        push(json!({ "type": "tag", "name": "v1.0" })),
+        EventEntity::Release {
+            tag: "v1.0".to_string()
+        }
+    );
+    // Deleted branches have no new state
+    assert_eq!(push(Value::Null), EventEntity::Unknown);
+
+    let fork = BitbucketSource
+        .normalize("repo:fork", json!({ "repository": repository() }))
+        .unwrap();
+    assert_eq!(fork.entity, EventEntity::Repository);
+}
+
+/// Payloads without a repository cannot be attributed.
+#[test]
+fn test_normalize_requires_repository() {
+    assert!(BitbucketSource
+        .normalize("pullrequest:created", json!({ "pullrequest": { "id": 1 } }))
+        .is_err());
+}
@@ -0,0 +1,138 @@
+//! Deduplication of webhook deliveries by delivery ID.
+//!
+//! GitHub retries a delivery it believes has failed, and every retry carries
+//! the `X-GitHub-Delivery` ID of the original. Without deduplication each
+//! retry is processed as a new event and bots see the same change twice.
+//!
+//! A [`DeduplicationStore`] remembers the delivery IDs that were processed
+//! and the event each of them produced. The webhook processor claims the
+//! delivery ID for the event it is about to create once the signature is
+//! valid; a delivery whose ID is already claimed is not processed again and
+//! produces [`ProcessingOutput::AlreadyProcessed`](super::ProcessingOutput::AlreadyProcessed)
+//! with the original event's ID, which the HTTP layer reports as a
+//! successful `already_processed` response, so GitHub stops retrying. When
+//! processing fails the claim is released, so a retry of a failed delivery
+//! is processed.
+//!
+//! Unlike [replay protection](super::ReplayGuard), which rejects repeated
+//! deliveries as a security measure, deduplication accepts them without
+//! processing them. The in-memory store is a [`ReplayGuard`] of its own; a
+//! shared backend, such as Redis, is needed to deduplicate across replicas.
+
+use super::ReplayGuard;
+use crate::EventId;
+use async_trait::async_trait;
+use tracing::warn;
+
+/// Errors returned by a [`DeduplicationStore`].
+#[derive(Debug, thiserror::Error)]
+pub enum DeduplicationError {
+    /// The backend could not be reached or rejected the operation.
+    #[error("Deduplication store unavailable: {message}")]
+    Unavailable { message: String },
+
+    /// A stored entry could not be decoded.
+    #[error("Deduplication entry '{key}' is invalid: {message}")]
+    InvalidEntry { key: String, message: String },
+}
+
+/// Result of claiming a delivery ID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryClaim {
+    /// The delivery ID was not known; the caller processes the delivery.
+    Claimed,
+
+    /// The delivery ID was already claimed.
+    Duplicate {
+        /// Event the first delivery was claimed for.
+        event_id: EventId,
+    },
+}
+
+impl DeliveryClaim {
+    /// Whether the delivery was already claimed.
+    pub fn is_duplicate(&self) -> bool {
+        matches!(self, Self::Duplicate { .. })
+    }
+}
+
+/// Processed delivery IDs, shared by everything that processes webhooks.
+///
+/// Implementations must make [`claim`](Self::claim) atomic: of two
+/// concurrent claims of one delivery ID, exactly one returns
+/// [`DeliveryClaim::Claimed`].
+#[async_trait]
+pub trait DeduplicationStore: Send + Sync {
+    /// Claim `delivery_id` for `event_id` unless it is already claimed.
+    async fn claim(
+        &self,
+        delivery_id: &str,
+        event_id: EventId,
+    ) -> Result<DeliveryClaim, DeduplicationError>;
+
+    /// Release the claim on `delivery_id`, so a redelivery is processed.
+    async fn release(&self, delivery_id: &str) -> Result<(), DeduplicationError>;
+}
+
+/// In-memory store: the window is the TTL of a delivery ID.
+#[async_trait]
+impl DeduplicationStore for ReplayGuard {
+    async fn claim(
+        &self,
+        delivery_id: &str,
+        event_id: EventId,
+    ) -> Result<DeliveryClaim, DeduplicationError> {
+        match self.claim_for(delivery_id, Some(event_id)) {
+            Ok(()) => Ok(DeliveryClaim::Claimed),
+            Err(Some(event_id)) => Ok(DeliveryClaim::Duplicate { event_id }),
+            Err(None) => Err(DeduplicationError::InvalidEntry {
+                key: delivery_id.to_string(),
+                message: "claimed by replay protection, not for an event".to_string(),
+            }),
+        }
+    }
+
+    async fn release(&self, delivery_id: &str) -> Result<(), DeduplicationError> {
+        ReplayGuard::release(self, delivery_id);
+        Ok(())
+    }
+}
+
+/// Claim `delivery_id` for `event_id` in `store`.
+///
+/// Returns whether the delivery holds a claim to release on failure, or the
+/// original event when the delivery was already processed. An unavailable
+/// store is logged and the delivery processed.
+pub(super) async fn claim_delivery(
+    store: &dyn DeduplicationStore,
+    delivery_id: &str,
+    event_id: EventId,
+) -> Result<bool, EventId> {
+    match store.claim(delivery_id, event_id).await {
+        Ok(DeliveryClaim::Claimed) => Ok(true),
+        Ok(DeliveryClaim::Duplicate { event_id }) => Err(event_id),
+        Err(e) => {
+            warn!(
+                delivery_id = %delivery_id,
+                error = %e,
+                "Deduplication store unavailable; processing delivery without deduplication"
+            );
+            Ok(false)
+        }
+    }
+}
+
+/// Release the claim on `delivery_id` in `store`.
+pub(super) async fn release_delivery(store: &dyn DeduplicationStore, delivery_id: &str) {
+    if let Err(e) = store.release(delivery_id).await {
+        warn!(
+            delivery_id = %delivery_id,
+            error = %e,
+            "Failed to release deduplication claim; redeliveries will be skipped"
+        );
+    }
+}
+
+#[cfg(test)]
+#[path = "deduplication_tests.rs"]
+mod tests;
@@ -0,0 +1,65 @@
+//! Tests for delivery deduplication.
+
+use super::*;
+use std::time::Duration;
+
+/// A claimed delivery ID is a duplicate that reports the event it was first
+/// claimed for.
+#[tokio::test]
+async fn test_repeated_delivery_is_duplicate() {
+    let guard = ReplayGuard::new(Duration::from_secs(60), 10);
+    let store: &dyn DeduplicationStore = &guard;
+    let original = EventId::new();
+
+    assert_eq!(
+        store.claim("d-1", original).await.unwrap(),
+        DeliveryClaim::Claimed
+    );
+    assert_eq!(
+        store.claim("d-1", EventId::new()).await.unwrap(),
+        DeliveryClaim::Duplicate { event_id: original }
+    );
+    assert_eq!(
+        store.claim("d-2", EventId::new()).await.unwrap(),
+        DeliveryClaim::Claimed
+    );
+    assert_eq!(guard.len(), 2);
+}
+
+/// A released delivery ID can be claimed again.
+#[tokio::test]
+async fn test_released_delivery_claimed_again() {
+    let guard = ReplayGuard::new(Duration::from_secs(60), 10);
+    let store: &dyn DeduplicationStore = &guard;
+
+    assert_eq!(
+        store.claim("d-1", EventId::new()).await.unwrap(),
+        DeliveryClaim::Claimed
+    );
+    store.release("d-1").await.unwrap();
+    assert!(guard.is_empty());
+
+    assert_eq!(
+        store.claim("d-1", EventId::new()).await.unwrap(),
+        DeliveryClaim::Claimed
+    );
+    assert!(store
+        .claim("d-1", EventId::new())
+        .await
+        .unwrap()
+        .is_duplicate());
+}
+
+/// An ID claimed without an event cannot answer a duplicate and is reported
+/// as an invalid entry.
+#[tokio::test]
+async fn test_replay_claim_is_invalid_entry() {
+    let guard = ReplayGuard::new(Duration::from_secs(60), 10);
+    let store: &dyn DeduplicationStore = &guard;
+
+    assert!(guard.claim("d-1"));
+    assert!(matches!(
+        store.claim("d-1", EventId::new()).await,
+        Err(DeduplicationError::InvalidEntry { .. })
+    ));
+}
@@ -0,0 +1,139 @@
+//! Schema versions of stored event envelopes and their migration.
+//!
+//! Every [`WrappedEvent`] is written with the `schema_version` it was
+//! created under. Envelopes stored in blob storage can be read back long
+//! after the service has moved on, e.g. by a replay of a year-old event, so
+//! stored envelopes are upgraded to [`SCHEMA_VERSION`] before they are
+//! deserialized:
+//!
+//! 1. [`migrate`] reads the envelope's `schema_version` (`0` when absent).
+//! 2. Each migration step from that version up rewrites the JSON object.
+//! 3. The envelope is stamped with [`SCHEMA_VERSION`].
+//!
+//! Envelopes written by a newer service than this one are refused rather
+//! than guessed at.
+//!
+//! # Versions
+//!
+//! | Version | Change |
+//! |---------|--------|
+//! | `0` | Envelopes written before `schema_version` existed |
+//! | `1` | `schema_version` added |
+//!
+//! Fields added so far are optional with serde defaults, so version `0`
+//! envelopes need no rewriting. A change that renames, removes or
+//! restructures a field bumps [`SCHEMA_VERSION`] and appends a step to
+//! `MIGRATIONS` that rewrites older envelopes into the new shape.
+
+use crate::webhook::WrappedEvent;
+use serde_json::{Map, Value};
+
+/// Schema version of envelopes written by this build.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Name of the version field in serialized envelopes.
+const VERSION_FIELD: &str = "schema_version";
+
+/// One migration step, upgrading an envelope object by one version.
+type Migration = fn(&mut Map<String, Value>) -> Result<(), EnvelopeMigrationError>;
+
+/// Migration steps; entry `n` upgrades version `n` to `n + 1`.
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [unversioned_to_v1];
+
+/// 0 → 1: unversioned envelopes already match version 1.
+fn unversioned_to_v1(_envelope: &mut Map<String, Value>) -> Result<(), EnvelopeMigrationError> {
+    Ok(())
+}
+
+/// Errors upgrading a stored envelope.
+#[derive(Debug, thiserror::Error)]
+pub enum EnvelopeMigrationError {
+    #[error("Envelope is not a JSON object")]
+    NotAnObject,
+
+    #[error("Envelope schema_version is not a non-negative integer: {value}")]
+    InvalidVersion { value: Value },
+
+    #[error("Envelope schema_version {found} is newer than the supported version {supported}")]
+    UnsupportedVersion { found: u64, supported: u32 },
+
+    #[error("Envelope could not be deserialized: {0}")]
+    Deserialize(#[from] serde_json::Error),
+}
+
+/// Schema version of a serialized envelope; `0` when it has none.
+///
+/// # Errors
+///
+/// Returns [`EnvelopeMigrationError::NotAnObject`] or
+/// [`EnvelopeMigrationError::InvalidVersion`] when `envelope` is not an
+/// object or its `schema_version` is not an integer.
+pub fn schema_version_of(envelope: &Value) -> Result<u64, EnvelopeMigrationError> {
+    let object = envelope
+        .as_object()
+        .ok_or(EnvelopeMigrationError::NotAnObject)?;
+    match object.get(VERSION_FIELD) {
+        None => Ok(0),
+        Some(value) => value
+            .as_u64()
+            .ok_or_else(|| EnvelopeMigrationError::InvalidVersion {
+                value: value.clone(),
+            }),
+    }
+}
+
+/// Upgrade a serialized envelope to [`SCHEMA_VERSION`].
+///
+/// Envelopes already at the current version are returned unchanged.
+///
+/// # Errors
+///
+/// Returns [`EnvelopeMigrationError::UnsupportedVersion`] for envelopes
+/// written by a newer service, and the errors of [`schema_version_of`].
+///
+/// # Examples
+///
+/// ```rust
+/// use queue_keeper_core::webhook::{envelope_migration, SCHEMA_VERSION};
+///
+/// let legacy = serde_json::json!({ "event_type": "push" });
+/// let upgraded = envelope_migration::migrate(legacy).unwrap();
+/// assert_eq!(upgraded["schema_version"], SCHEMA_VERSION);
+/// ```
+pub fn migrate(mut envelope: Value) -> Result<Value, EnvelopeMigrationError> {
+    let version = schema_version_of(&envelope)?;
+    if version > u64::from(SCHEMA_VERSION) {
+        return Err(EnvelopeMigrationError::UnsupportedVersion {
+            found: version,
+            supported: SCHEMA_VERSION,
+        });
+    }
+
+    let object = envelope
+        .as_object_mut()
+        .ok_or(EnvelopeMigrationError::NotAnObject)?;
+    for step in &MIGRATIONS[version as usize..] {
+        step(object)?;
+    }
+    object.insert(VERSION_FIELD.to_string(), SCHEMA_VERSION.into());
+    Ok(envelope)
+}
+
+/// Deserialize a stored envelope, upgrading it to [`SCHEMA_VERSION`] first.
+///
+/// Use for envelopes read back from storage instead of deserializing them
+/// directly.
+///
+/// # Errors
+///
+/// Returns [`EnvelopeMigrationError::Deserialize`] when `bytes` are not
+/// JSON or the upgraded envelope is not a valid [`WrappedEvent`], and the
+/// errors of [`migrate`].
+pub fn deserialize_envelope(bytes: &[u8]) -> Result<WrappedEvent, EnvelopeMigrationError> {
+    let envelope: Value = serde_json::from_slice(bytes)?;
+    Ok(serde_json::from_value(migrate(envelope)?)?)
+}
+
+#[cfg(test)]
+#[path = "envelope_migration_tests.rs"]
+mod tests;
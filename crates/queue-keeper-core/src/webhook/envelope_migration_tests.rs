@@ -0,0 +1,129 @@
+//! Tests for envelope schema versions and migration.
+
+use super::*;
+use serde_json::json;
+
+/// Envelope as written before `schema_version` existed.
+fn legacy_envelope() -> Value {
+    json!({
+        "event_id": "01HV5ZQ2JX8N4M7P3R6S9T0W1Y",
+        "provider": "github",
+        "event_type": "pull_request",
+        "action": "opened",
+        "session_id": "owner/repo/pull_request/42",
+        "correlation_id": "550e8400-e29b-41d4-a716-446655440000",
+        "received_at": "2025-03-01T12:00:00Z",
+        "processed_at": "2025-03-01T12:00:00.050Z",
+        "payload": { "number": 42 },
+    })
+}
+
+/// There is one migration step per version below the current one.
+#[test]
+fn test_migration_per_version() {
+    assert_eq!(MIGRATIONS.len(), SCHEMA_VERSION as usize);
+}
+
+/// Envelopes without a version are version 0 and are upgraded to the
+/// current version.
+#[test]
+fn test_migrate_unversioned_envelope() {
+    let legacy = legacy_envelope();
+    assert_eq!(schema_version_of(&legacy).unwrap(), 0);
+
+    let upgraded = migrate(legacy.clone()).unwrap();
+    assert_eq!(upgraded["schema_version"], json!(SCHEMA_VERSION));
+    assert_eq!(upgraded["payload"], legacy["payload"]);
+}
+
+/// Envelopes at the current version are left as they are.
+#[test]
+fn test_migrate_current_envelope_unchanged() {
+    let mut current = legacy_envelope();
+    current["schema_version"] = json!(SCHEMA_VERSION);
+
+    assert_eq!(migrate(current.clone()).unwrap(), current);
+}
+
+/// Envelopes written by a newer service are refused.
+#[test]
+fn test_migrate_rejects_newer_version() {
+    let mut newer = legacy_envelope();
+    newer["schema_version"] = json!(SCHEMA_VERSION + 1);
+
+    let error = migrate(newer).unwrap_err();
+    assert!(matches!(
+        error,
+        EnvelopeMigrationError::UnsupportedVersion { found, supported }
+            if found == u64::from(SCHEMA_VERSION) + 1 && supported == SCHEMA_VERSION
+    ));
+}
+
+/// Versions must be non-negative integers and envelopes must be objects.
+#[test]
+fn test_migrate_rejects_malformed_envelopes() {
+    for version in [json!("1"), json!(-1), json!(1.5), json!(null)] {
+        let mut envelope = legacy_envelope();
+        envelope["schema_version"] = version;
+        assert!(matches!(
+            migrate(envelope),
+            Err(EnvelopeMigrationError::InvalidVersion { .. })
+        ));
+    }
+
+    assert!(matches!(
+        migrate(json!([1, 2])),
+        Err(EnvelopeMigrationError::NotAnObject)
+    ));
+}
+
+/// Stored legacy envelopes deserialize as current events.
+#[test]
+fn test_deserialize_legacy_envelope() {
+    let bytes = serde_json::to_vec(&legacy_envelope()).unwrap();
+
+    let event = deserialize_envelope(&bytes).unwrap();
+    assert_eq!(event.schema_version, SCHEMA_VERSION);
+    assert_eq!(event.event_type, "pull_request");
+    assert_eq!(
+        event.session_id.unwrap().as_str(),
+        "owner/repo/pull_request/42"
+    );
+    assert_eq!(event.session_epoch, 0);
+    assert!(event.lineage.is_none());
+}
+
+/// Bytes that are not an envelope fail to deserialize.
+#[test]
+fn test_deserialize_invalid_envelope() {
+    assert!(matches!(
+        deserialize_envelope(b"not json"),
+        Err(EnvelopeMigrationError::Deserialize(_))
+    ));
+    assert!(matches!(
+        deserialize_envelope(br#"{"event_type": "push"}"#),
+        Err(EnvelopeMigrationError::Deserialize(_))
+    ));
+}
+
+/// New events carry the current version and round-trip through migration.
+#[test]
+fn test_new_event_round_trip() {
+    let event = WrappedEvent::new(
+        "github".to_string(),
+        "push".to_string(),
+        None,
+        None,
+        json!({ "ref": "refs/heads/main" }),
+        None,
+    );
+    assert_eq!(event.schema_version, SCHEMA_VERSION);
+
+    let bytes = serde_json::to_vec(&event).unwrap();
+    let value: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(value["schema_version"], json!(SCHEMA_VERSION));
+
+    let read_back = deserialize_envelope(&bytes).unwrap();
+    assert_eq!(read_back.event_id, event.event_id);
+    assert_eq!(read_back.schema_version, SCHEMA_VERSION);
+}
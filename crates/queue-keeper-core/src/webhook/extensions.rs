@@ -0,0 +1,284 @@
+//! Envelope extensions: custom fields derived from the webhook payload.
+//!
+//! Teams often want a value computed from the payload on every event — a
+//! Jira issue key taken from a pull request title, a label set, a team owner
+//! — without every bot parsing the payload for it. An [`EnvelopeExtension`]
+//! computes such a value once, during normalization, and the
+//! [`EnvelopeExtensionRegistry`] held by the
+//! [`WebhookProcessorImpl`](super::WebhookProcessorImpl) stores it in the
+//! envelope's `extensions` map under the extension's namespace:
+//!
+//! ```json
+//! "extensions": {
+//!   "jira": { "keys": ["PLAT-123"] }
+//! }
+//! ```
+//!
+//! Namespaces keep extensions from overwriting each other and tell consumers
+//! which extension produced a value. An extension that finds nothing to add
+//! returns `None` and leaves no entry; events without extension values omit
+//! the `extensions` field entirely.
+//!
+//! Built-in extensions are off by default and are enabled through
+//! [`EnvelopeExtensionsConfig`]; custom extensions are registered in code
+//! with [`EnvelopeExtensionRegistry::register`].
+
+use super::WrappedEvent;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::{fmt, sync::Arc};
+
+// ============================================================================
+// Extension Trait
+// ============================================================================
+
+/// Computes a namespaced value for the envelope from a normalized event.
+///
+/// Implementations must be cheap and must not fail the webhook: return
+/// `None` when the event has nothing for this extension.
+pub trait EnvelopeExtension: Send + Sync {
+    /// Key under which the value is stored in `extensions`.
+    ///
+    /// Must be non-empty and contain only `[a-z0-9_-]`.
+    fn namespace(&self) -> &str;
+
+    /// Value to attach to `event`, or `None` to attach nothing.
+    fn extract(&self, event: &WrappedEvent) -> Option<serde_json::Value>;
+}
+
+/// Errors building an [`EnvelopeExtensionRegistry`]
+#[derive(Debug, thiserror::Error)]
+pub enum ExtensionError {
+    /// The namespace is empty or contains characters outside `[a-z0-9_-]`.
+    #[error(
+        "invalid extension namespace '{namespace}': must be non-empty and contain only [a-z0-9_-]"
+    )]
+    InvalidNamespace { namespace: String },
+
+    /// Another registered extension already uses the namespace.
+    #[error("duplicate extension namespace '{namespace}'")]
+    DuplicateNamespace { namespace: String },
+
+    /// A built-in extension's configuration is invalid.
+    #[error("extension '{namespace}': {message}")]
+    InvalidConfig { namespace: String, message: String },
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Ordered set of extensions applied to every normalized event.
+///
+/// Cheap to clone; extensions are shared.
+#[derive(Clone, Default)]
+pub struct EnvelopeExtensionRegistry {
+    extensions: Vec<Arc<dyn EnvelopeExtension>>,
+}
+
+impl fmt::Debug for EnvelopeExtensionRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnvelopeExtensionRegistry")
+            .field("namespaces", &self.namespaces())
+            .finish()
+    }
+}
+
+impl EnvelopeExtensionRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a registry holding the built-in extensions enabled in `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExtensionError::InvalidConfig`] when an enabled built-in is
+    /// misconfigured.
+    pub fn from_config(config: &EnvelopeExtensionsConfig) -> Result<Self, ExtensionError> {
+        let mut registry = Self::new();
+        if config.jira.enabled {
+            registry.register(Arc::new(JiraKeyExtension::new(&config.jira)?))?;
+        }
+        Ok(registry)
+    }
+
+    /// Add an extension, applied after those already registered.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the namespace is invalid or already registered.
+    pub fn register(
+        &mut self,
+        extension: Arc<dyn EnvelopeExtension>,
+    ) -> Result<(), ExtensionError> {
+        let namespace = extension.namespace();
+        let valid = !namespace.is_empty()
+            && namespace
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+        if !valid {
+            return Err(ExtensionError::InvalidNamespace {
+                namespace: namespace.to_string(),
+            });
+        }
+        if self.namespaces().contains(&namespace) {
+            return Err(ExtensionError::DuplicateNamespace {
+                namespace: namespace.to_string(),
+            });
+        }
+
+        self.extensions.push(extension);
+        Ok(())
+    }
+
+    /// Namespaces of the registered extensions, in application order
+    pub fn namespaces(&self) -> Vec<&str> {
+        self.extensions.iter().map(|e| e.namespace()).collect()
+    }
+
+    /// Check whether no extensions are registered
+    pub fn is_empty(&self) -> bool {
+        self.extensions.is_empty()
+    }
+
+    /// Run every extension against `event` and store the values it returns.
+    ///
+    /// Values already present under a namespace are replaced.
+    pub fn apply(&self, event: &mut WrappedEvent) {
+        for extension in &self.extensions {
+            if let Some(value) = extension.extract(event) {
+                event
+                    .extensions
+                    .insert(extension.namespace().to_string(), value);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Built-in envelope extensions, all disabled by default
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnvelopeExtensionsConfig {
+    /// Jira issue keys referenced by pull requests, issues and pushes.
+    #[serde(default)]
+    pub jira: JiraKeyExtensionConfig,
+}
+
+/// Configuration for [`JiraKeyExtension`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JiraKeyExtensionConfig {
+    /// Add the `jira` namespace to events.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Jira project keys to accept (e.g. `["PLAT", "OPS"]`).
+    ///
+    /// Empty accepts any key-shaped token, which also matches strings such
+    /// as `UTF-8`; list the projects in use to avoid false positives.
+    #[serde(default)]
+    pub projects: Vec<String>,
+}
+
+// ============================================================================
+// Built-in: Jira Keys
+// ============================================================================
+
+/// Payload fields searched for Jira keys, in order.
+const JIRA_KEY_SOURCES: &[&str] = &[
+    "/pull_request/title",
+    "/pull_request/head/ref",
+    "/issue/title",
+    "/ref",
+    "/head_commit/message",
+];
+
+/// Extracts Jira issue keys (e.g. `PLAT-123`) into the `jira` namespace.
+///
+/// Searches the pull request title and head branch, the issue title, and
+/// for pushes the ref and head commit message. Produces
+/// `{ "keys": [...] }` with each key once, in order of first appearance.
+#[derive(Debug, Clone)]
+pub struct JiraKeyExtension {
+    pattern: Regex,
+    projects: Vec<String>,
+}
+
+impl JiraKeyExtension {
+    /// Namespace used by this extension
+    pub const NAMESPACE: &'static str = "jira";
+
+    /// Create the extension from configuration.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExtensionError::InvalidConfig`] if a project key is not an
+    /// upper-case Jira project key.
+    pub fn new(config: &JiraKeyExtensionConfig) -> Result<Self, ExtensionError> {
+        let pattern =
+            Regex::new(r"\b([A-Z][A-Z0-9_]+)-([0-9]+)\b").expect("Jira key pattern is valid");
+
+        for project in &config.projects {
+            let valid = project.len() >= 2
+                && project.starts_with(|c: char| c.is_ascii_uppercase())
+                && project
+                    .chars()
+                    .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+            if !valid {
+                return Err(ExtensionError::InvalidConfig {
+                    namespace: Self::NAMESPACE.to_string(),
+                    message: format!(
+                        "invalid project key '{}': expected upper-case letters, digits and '_'",
+                        project
+                    ),
+                });
+            }
+        }
+
+        Ok(Self {
+            pattern,
+            projects: config.projects.clone(),
+        })
+    }
+}
+
+impl EnvelopeExtension for JiraKeyExtension {
+    fn namespace(&self) -> &str {
+        Self::NAMESPACE
+    }
+
+    fn extract(&self, event: &WrappedEvent) -> Option<serde_json::Value> {
+        let mut keys: Vec<String> = Vec::new();
+        let texts = JIRA_KEY_SOURCES
+            .iter()
+            .filter_map(|pointer| event.payload.pointer(pointer))
+            .filter_map(|value| value.as_str());
+
+        for text in texts {
+            for captures in self.pattern.captures_iter(text) {
+                let project = &captures[1];
+                if !self.projects.is_empty() && !self.projects.iter().any(|p| p == project) {
+                    continue;
+                }
+                let key = captures[0].to_string();
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+        }
+
+        if keys.is_empty() {
+            None
+        } else {
+            Some(serde_json::json!({ "keys": keys }))
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "extensions_tests.rs"]
+mod tests;
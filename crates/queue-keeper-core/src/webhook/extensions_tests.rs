@@ -0,0 +1,152 @@
+//! Tests for envelope extensions and the built-in Jira key extension.
+
+use super::*;
+use serde_json::json;
+
+fn event(payload: serde_json::Value) -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        None,
+        payload,
+        None,
+    )
+}
+
+fn jira(projects: &[&str]) -> JiraKeyExtension {
+    JiraKeyExtension::new(&JiraKeyExtensionConfig {
+        enabled: true,
+        projects: projects.iter().map(|p| p.to_string()).collect(),
+    })
+    .unwrap()
+}
+
+/// Extension returning a fixed value under a fixed namespace.
+struct Fixed(&'static str, Option<serde_json::Value>);
+
+impl EnvelopeExtension for Fixed {
+    fn namespace(&self) -> &str {
+        self.0
+    }
+
+    fn extract(&self, _event: &WrappedEvent) -> Option<serde_json::Value> {
+        self.1.clone()
+    }
+}
+
+// ============================================================================
+// Registry
+// ============================================================================
+
+/// Values are stored under their namespace; `None` leaves no entry.
+#[test]
+fn test_apply_stores_values_by_namespace() {
+    let mut registry = EnvelopeExtensionRegistry::new();
+    registry
+        .register(Arc::new(Fixed("team", Some(json!("platform")))))
+        .unwrap();
+    registry.register(Arc::new(Fixed("empty", None))).unwrap();
+
+    let mut event = event(json!({}));
+    registry.apply(&mut event);
+
+    assert_eq!(event.extensions.len(), 1);
+    assert_eq!(event.extensions["team"], json!("platform"));
+    assert_eq!(registry.namespaces(), vec!["team", "empty"]);
+}
+
+/// Invalid and duplicate namespaces are rejected.
+#[test]
+fn test_register_rejects_bad_namespaces() {
+    let mut registry = EnvelopeExtensionRegistry::new();
+    registry.register(Arc::new(Fixed("team", None))).unwrap();
+
+    assert!(matches!(
+        registry.register(Arc::new(Fixed("team", None))),
+        Err(ExtensionError::DuplicateNamespace { .. })
+    ));
+    for namespace in ["", "Team", "team.owner"] {
+        assert!(matches!(
+            registry.register(Arc::new(Fixed(namespace, None))),
+            Err(ExtensionError::InvalidNamespace { .. })
+        ));
+    }
+}
+
+/// Built-ins are only registered when enabled.
+#[test]
+fn test_from_config_registers_enabled_built_ins() {
+    let disabled = EnvelopeExtensionRegistry::from_config(&Default::default()).unwrap();
+    assert!(disabled.is_empty());
+
+    let config: EnvelopeExtensionsConfig =
+        serde_json::from_value(json!({ "jira": { "enabled": true, "projects": ["PLAT"] } }))
+            .unwrap();
+    let enabled = EnvelopeExtensionRegistry::from_config(&config).unwrap();
+    assert_eq!(enabled.namespaces(), vec!["jira"]);
+}
+
+/// Events without extension values omit the field when serialized.
+#[test]
+fn test_empty_extensions_are_not_serialized() {
+    let mut event = event(json!({}));
+    let json = serde_json::to_value(&event).unwrap();
+    assert!(json.get("extensions").is_none());
+
+    event
+        .extensions
+        .insert("team".to_string(), json!("platform"));
+    let json = serde_json::to_value(&event).unwrap();
+    assert_eq!(json["extensions"]["team"], "platform");
+}
+
+// ============================================================================
+// Jira Keys
+// ============================================================================
+
+/// Keys are collected from title and branch, once each, in order.
+#[test]
+fn test_jira_keys_from_pull_request() {
+    let event = event(json!({
+        "pull_request": {
+            "title": "PLAT-12: fix login (see OPS-7)",
+            "head": { "ref": "feature/PLAT-12-login" }
+        }
+    }));
+
+    assert_eq!(
+        jira(&[]).extract(&event),
+        Some(json!({ "keys": ["PLAT-12", "OPS-7"] }))
+    );
+}
+
+/// A project allow-list filters out other key-shaped tokens.
+#[test]
+fn test_jira_project_allow_list() {
+    let event = event(json!({
+        "head_commit": { "message": "PLAT-3 switch encoding to UTF-8" }
+    }));
+
+    assert_eq!(
+        jira(&["PLAT"]).extract(&event),
+        Some(json!({ "keys": ["PLAT-3"] }))
+    );
+    assert_eq!(jira(&["OPS"]).extract(&event), None);
+}
+
+/// Malformed project keys are a configuration error.
+#[test]
+fn test_jira_rejects_invalid_project_keys() {
+    for project in ["plat", "P", "PL-AT", "1PLAT"] {
+        let result = JiraKeyExtension::new(&JiraKeyExtensionConfig {
+            enabled: true,
+            projects: vec![project.to_string()],
+        });
+        assert!(
+            matches!(result, Err(ExtensionError::InvalidConfig { .. })),
+            "{}",
+            project
+        );
+    }
+}
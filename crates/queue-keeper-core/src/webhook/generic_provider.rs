@@ -50,7 +50,7 @@ use crate::{
         StorageError, StorageReference, ValidationStatus, WebhookError, WebhookProcessor,
         WebhookRequest, WrappedEvent,
     },
-    ValidationError,
+    PipelineStage, ValidationError,
 };
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
@@ -869,7 +869,16 @@ impl WebhookProcessor for GenericWebhookProvider {
             match request.raw_headers.get(&sig_header) {
                 Some(signature) => {
                     // event_type is available from headers (default "webhook" for generic providers)
-                    self.validate_signature(&request.body, signature, &request.headers.event_type)
+                    request
+                        .timings
+                        .time(
+                            PipelineStage::SignatureValidation,
+                            self.validate_signature(
+                                &request.body,
+                                signature,
+                                &request.headers.event_type,
+                            ),
+                        )
                         .await
                         .map_err(|e| WebhookError::InvalidSignature(e.to_string()))?;
                 }
@@ -882,43 +891,12 @@ impl WebhookProcessor for GenericWebhookProvider {
             }
         }
 
-        // 2. Parse the body as JSON (needed for event_type extraction in both modes and
-        //    for field extraction in wrap mode). In direct mode we retain the original bytes.
-        //
-        //    We fail immediately on parse errors here rather than swallowing them with
-        //    `unwrap_or(Value::Null)`, which would make a valid JSON `null` body
-        //    indistinguishable from a malformed body in the downstream checks.
-        let needs_json = self.config.processing_mode == ProcessingMode::Wrap
-            || self
-                .config
-                .event_type_source
-                .as_ref()
-                .map_or(false, |s| matches!(s, FieldSource::JsonPath { .. }));
-
-        let payload: serde_json::Value = if needs_json {
-            serde_json::from_slice(&request.body).map_err(|e| WebhookError::MalformedPayload {
-                message: format!(
-                    "provider '{}': invalid JSON body: {}",
-                    self.config.provider_id, e
-                ),
-            })?
-        } else {
-            serde_json::Value::Null
-        };
-
-        // 3. Resolve the event type.
-        let event_type = self
-            .config
-            .event_type_source
-            .as_ref()
-            .and_then(|src| resolve_field_source(src, &request.raw_headers, &payload))
-            .unwrap_or_else(|| "webhook".to_string());
-
-        // 4. Dispatch by processing mode.
-        match self.config.processing_mode {
-            ProcessingMode::Wrap => self.process_wrap_mode(request, payload, &event_type).await,
-            ProcessingMode::Direct => self.process_direct_mode(request, &event_type).await,
-        }
+        // 2. Parse, resolve the event type and dispatch by processing mode.
+        let normalization = std::time::Instant::now();
+        let timings = request.timings.clone();
+        let output = self.normalize_request(request).await;
+        timings.record(PipelineStage::Normalization, normalization.elapsed());
+        output
     }
 
     /// Validate the webhook signature using the algorithm specified in [`SignatureConfig`].
@@ -1113,11 +1091,13 @@ impl WebhookProcessor for GenericWebhookProvider {
             None, // Generic providers do not impose session-based ordering
             payload,
             request.trace_context.clone(),
-        );
+        )
+        .with_receipt(request.receipt)
+        .with_origin(request.origin);
 
-        tracing::info!(
+        crate::event_info!(
+            event,
             provider = %self.config.provider_id,
-            event_id = %event.event_id,
             event_type = %event.event_type,
             "Generic provider wrapped event normalised"
         );
@@ -1131,6 +1111,50 @@ impl WebhookProcessor for GenericWebhookProvider {
 // ============================================================================
 
 impl GenericWebhookProvider {
+    /// Parse the body, resolve the event type and build the output.
+    async fn normalize_request(
+        &self,
+        request: WebhookRequest,
+    ) -> Result<ProcessingOutput, WebhookError> {
+        // Parse the body as JSON (needed for event_type extraction in both modes and
+        // for field extraction in wrap mode). In direct mode we retain the original bytes.
+        //
+        // We fail immediately on parse errors here rather than swallowing them with
+        // `unwrap_or(Value::Null)`, which would make a valid JSON `null` body
+        // indistinguishable from a malformed body in the downstream checks.
+        let needs_json = self.config.processing_mode == ProcessingMode::Wrap
+            || self
+                .config
+                .event_type_source
+                .as_ref()
+                .is_some_and(|s| matches!(s, FieldSource::JsonPath { .. }));
+
+        let payload: serde_json::Value = if needs_json {
+            serde_json::from_slice(&request.body).map_err(|e| WebhookError::MalformedPayload {
+                message: format!(
+                    "provider '{}': invalid JSON body: {}",
+                    self.config.provider_id, e
+                ),
+            })?
+        } else {
+            serde_json::Value::Null
+        };
+
+        // Resolve the event type.
+        let event_type = self
+            .config
+            .event_type_source
+            .as_ref()
+            .and_then(|src| resolve_field_source(src, &request.raw_headers, &payload))
+            .unwrap_or_else(|| "webhook".to_string());
+
+        // Dispatch by processing mode.
+        match self.config.processing_mode {
+            ProcessingMode::Wrap => self.process_wrap_mode(request, payload, &event_type).await,
+            ProcessingMode::Direct => self.process_direct_mode(request, &event_type).await,
+        }
+    }
+
     /// Process a webhook in wrap mode — normalise to [`WrappedEvent`].
     async fn process_wrap_mode(
         &self,
@@ -1162,11 +1186,13 @@ impl GenericWebhookProvider {
             None, // Generic providers do not support session-based ordering
             payload,
             request.trace_context.clone(),
-        );
+        )
+        .with_receipt(request.receipt)
+        .with_origin(request.origin);
 
-        tracing::info!(
+        crate::event_info!(
+            event,
             provider = %self.config.provider_id,
-            event_id = %event.event_id,
             event_type = %event.event_type,
             "Generic provider wrap-mode output"
         );
@@ -1186,10 +1212,10 @@ impl GenericWebhookProvider {
             request.trace_context.clone(),
         );
 
-        tracing::info!(
+        crate::event_info!(
+            metadata,
             provider = %self.config.provider_id,
             event_type = %event_type,
-            event_id = %metadata.event_id(),
             target_queue = ?self.config.target_queue,
             "Generic provider direct-mode output — forwarding raw payload"
         );
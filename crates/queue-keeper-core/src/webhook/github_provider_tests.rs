@@ -238,6 +238,19 @@ mod process_webhook_tests {
         assert_eq!(output.event_type(), Some("ping"));
     }
 
+    /// Verify that events are stamped with the configured provider ID.
+    #[tokio::test]
+    async fn test_process_stamps_configured_provider_id() {
+        let github = GithubWebhookProvider::new(None, None, None);
+        let ghes = GithubWebhookProvider::new(None, None, None).with_provider_id("ghes-eu");
+
+        let github_output = github.process_webhook(ping_request()).await.unwrap();
+        let ghes_output = ghes.process_webhook(ping_request()).await.unwrap();
+
+        assert_eq!(github_output.as_wrapped().unwrap().provider, "github");
+        assert_eq!(ghes_output.as_wrapped().unwrap().provider, "ghes-eu");
+    }
+
     /// Verify that a pull_request event without a signature fails validation.
     ///
     /// WebhookHeaders::validate() requires signature for non-ping events.
@@ -0,0 +1,250 @@
+//! GitLab webhook source.
+//!
+//! Reads GitLab's `X-Gitlab-Event`, `X-Gitlab-Event-UUID` and
+//! `X-Gitlab-Token` headers and maps project, merge request, issue, push,
+//! release and pipeline payloads onto [`EventEntity`]:
+//!
+//! | GitLab event         | Event type      | Entity                          |
+//! |----------------------|-----------------|---------------------------------|
+//! | `Merge Request Hook` | `merge_request` | `PullRequest` (the MR `iid`)    |
+//! | `Issue Hook`         | `issue`         | `Issue` (the issue `iid`)       |
+//! | `Note Hook`          | `note`          | MR or issue commented on        |
+//! | `Push Hook`          | `push`          | `Branch`                        |
+//! | `Tag Push Hook`      | `tag_push`      | `Release` (the tag)             |
+//! | `Release Hook`       | `release`       | `Release`                       |
+//! | `Pipeline Hook`      | `pipeline`      | `WorkflowRun` (the pipeline ID) |
+//! | `Job Hook`           | `job`           | `WorkflowRun` (its pipeline)    |
+//!
+//! Other events are attributed to the project as a whole.
+//!
+//! GitLab sends the configured secret token verbatim; it is compared with
+//! the secret in constant time.
+
+use crate::{
+    webhook::{
+        EventEntity, EventEnvelope, NormalizationError, WebhookHeaders, WebhookSource,
+        WebhookSourceKind,
+    },
+    Repository, RepositoryId, User, UserId, UserType, ValidationError,
+};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// GitLab visibility level of public projects.
+const VISIBILITY_PUBLIC: u64 = 20;
+
+/// [`WebhookSource`] for GitLab project and group webhooks.
+///
+/// GitLab payloads carry no namespace ID: the repository owner is the
+/// project namespace path with ID `0`. Subgroup separators in the namespace
+/// are replaced by `:` in the owner login (`group:subgroup`) so session IDs
+/// keep their four `/`-separated segments; `full_name` keeps the original
+/// `path_with_namespace`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GitlabSource;
+
+impl GitlabSource {
+    /// Event type of an `X-Gitlab-Event` header value.
+    ///
+    /// The `Hook` suffix is dropped and the rest written in snake case, so
+    /// `Merge Request Hook` becomes `merge_request`.
+    pub fn event_type(header: &str) -> String {
+        let name = header.trim();
+        let name = name.strip_suffix(" Hook").unwrap_or(name);
+        name.split_whitespace()
+            .map(str::to_ascii_lowercase)
+            .collect::<Vec<_>>()
+            .join("_")
+    }
+
+    fn repository(payload: &Value) -> Result<Repository, NormalizationError> {
+        let project = payload.get("project").ok_or_else(|| missing("project"))?;
+        let id = project
+            .get("id")
+            .and_then(Value::as_u64)
+            .ok_or_else(|| missing("project.id"))?;
+        let full_name = project
+            .get("path_with_namespace")
+            .and_then(Value::as_str)
+            .ok_or_else(|| missing("project.path_with_namespace"))?;
+        let (namespace, name) =
+            full_name
+                .rsplit_once('/')
+                .ok_or_else(|| NormalizationError::InvalidFieldFormat {
+                    field: "project.path_with_namespace".to_string(),
+                    message: format!("'{}' has no namespace", full_name),
+                })?;
+        let private = project
+            .get("visibility_level")
+            .and_then(Value::as_u64)
+            .is_some_and(|level| level != VISIBILITY_PUBLIC);
+
+        let owner = User {
+            id: UserId::new(0),
+            login: namespace.replace('/', ":"),
+            user_type: UserType::Organization,
+        };
+        Ok(Repository::new(
+            RepositoryId::new(id),
+            name.to_string(),
+            full_name.to_string(),
+            owner,
+            private,
+        ))
+    }
+
+    fn entity(event_type: &str, payload: &Value) -> EventEntity {
+        let attributes = payload.get("object_attributes");
+        let attribute = |name: &str| attributes.and_then(|a| a.get(name));
+        let iid = |object: Option<&Value>| {
+            object
+                .and_then(|o| o.get("iid"))
+                .and_then(Value::as_u64)
+                .map(|n| n as u32)
+        };
+
+        let entity = match event_type {
+            "merge_request" => iid(attributes).map(|number| EventEntity::PullRequest { number }),
+            "issue" | "confidential_issue" | "work_item" => {
+                iid(attributes).map(|number| EventEntity::Issue { number })
+            }
+            "note" | "confidential_note" => iid(payload.get("merge_request"))
+                .map(|number| EventEntity::PullRequest { number })
+                .or_else(|| iid(payload.get("issue")).map(|number| EventEntity::Issue { number }))
+                .or(Some(EventEntity::Repository)),
+            "push" => ref_name(payload, "refs/heads/").map(|name| EventEntity::Branch { name }),
+            "tag_push" => ref_name(payload, "refs/tags/").map(|tag| EventEntity::Release { tag }),
+            "release" => {
+                payload
+                    .get("tag")
+                    .and_then(Value::as_str)
+                    .map(|tag| EventEntity::Release {
+                        tag: tag.to_string(),
+                    })
+            }
+            "pipeline" => attribute("id")
+                .and_then(Value::as_u64)
+                .map(|id| EventEntity::WorkflowRun { id }),
+            "job" | "build" => payload
+                .get("pipeline_id")
+                .and_then(Value::as_u64)
+                .map(|id| EventEntity::WorkflowRun { id }),
+            _ => Some(EventEntity::Repository),
+        };
+        entity.unwrap_or(EventEntity::Unknown)
+    }
+
+    fn action(payload: &Value) -> Option<String> {
+        payload
+            .get("object_attributes")
+            .and_then(|a| a.get("action"))
+            .or_else(|| payload.get("action"))
+            .and_then(Value::as_str)
+            .map(String::from)
+    }
+
+    fn sender(payload: &Value) -> Option<User> {
+        let (id, login) = match payload.get("user") {
+            Some(user) => (
+                user.get("id").and_then(Value::as_u64)?,
+                user.get("username").and_then(Value::as_str)?,
+            ),
+            // Push events name the user in top-level fields
+            None => (
+                payload.get("user_id").and_then(Value::as_u64)?,
+                payload.get("user_username").and_then(Value::as_str)?,
+            ),
+        };
+        Some(User {
+            id: UserId::new(id),
+            login: login.to_string(),
+            user_type: UserType::User,
+        })
+    }
+}
+
+impl WebhookSource for GitlabSource {
+    fn kind(&self) -> WebhookSourceKind {
+        WebhookSourceKind::Gitlab
+    }
+
+    /// `X-Gitlab-Event` is required. Deliveries from GitLab releases before
+    /// `X-Gitlab-Event-UUID` was introduced get a generated delivery ID.
+    fn parse_headers(
+        &self,
+        headers: &HashMap<String, String>,
+    ) -> Result<WebhookHeaders, ValidationError> {
+        let event = headers
+            .get("x-gitlab-event")
+            .ok_or_else(|| ValidationError::Required {
+                field: "X-Gitlab-Event".to_string(),
+            })?;
+
+        Ok(WebhookHeaders {
+            event_type: Self::event_type(event),
+            delivery_id: headers
+                .get("x-gitlab-event-uuid")
+                .cloned()
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+            signature: headers.get("x-gitlab-token").cloned(),
+            user_agent: headers.get("user-agent").cloned(),
+            content_type: headers
+                .get("content-type")
+                .cloned()
+                .unwrap_or_else(|| "application/json".to_string()),
+        })
+    }
+
+    fn authenticate(
+        &self,
+        _payload: &[u8],
+        credential: &str,
+        secret: &str,
+    ) -> Result<(), ValidationError> {
+        use subtle::ConstantTimeEq;
+
+        if bool::from(credential.as_bytes().ct_eq(secret.as_bytes())) {
+            Ok(())
+        } else {
+            Err(ValidationError::InvalidFormat {
+                field: "X-Gitlab-Token".to_string(),
+                message: "secret token mismatch".to_string(),
+            })
+        }
+    }
+
+    fn normalize(
+        &self,
+        event_type: &str,
+        payload: Value,
+    ) -> Result<EventEnvelope, NormalizationError> {
+        let repository = Self::repository(&payload)?;
+        let entity = Self::entity(event_type, &payload);
+        let action = Self::action(&payload);
+        let sender = Self::sender(&payload);
+
+        let mut envelope =
+            EventEnvelope::new(event_type.to_string(), action, repository, entity, payload);
+        envelope.sender = sender;
+        Ok(envelope)
+    }
+}
+
+/// The payload `ref` with `prefix` removed, if it has that prefix.
+fn ref_name(payload: &Value, prefix: &str) -> Option<String> {
+    payload
+        .get("ref")
+        .and_then(Value::as_str)
+        .and_then(|r| r.strip_prefix(prefix))
+        .map(String::from)
+}
+
+fn missing(field: &str) -> NormalizationError {
+    NormalizationError::MissingRequiredField {
+        field: field.to_string(),
+    }
+}
+
+#[cfg(test)]
+#[path = "gitlab_tests.rs"]
+mod tests;
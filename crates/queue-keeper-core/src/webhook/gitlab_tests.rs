@@ -0,0 +1,196 @@
+//! Tests for the GitLab webhook source.
+
+use super::*;
+use serde_json::json;
+
+fn project(path: &str) -> Value {
+    json!({
+        "id": 15,
+        "name": path.rsplit('/').next().unwrap(),
+        "path_with_namespace": path,
+        "visibility_level": 0,
+    })
+}
+
+fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Header values become snake case event types without the `Hook` suffix.
+#[test]
+fn test_event_type_from_header() {
+    for (header, expected) in [
+        ("Merge Request Hook", "merge_request"),
+        ("Tag Push Hook", "tag_push"),
+        ("Pipeline Hook", "pipeline"),
+        ("Confidential Issue Hook", "confidential_issue"),
+        ("System Hook", "system"),
+    ] {
+        assert_eq!(GitlabSource::event_type(header), expected);
+    }
+}
+
+/// The event type, delivery UUID and secret token are read from GitLab's
+/// headers; the event header is required.
+#[test]
+fn test_parse_headers() {
+    let parsed = GitlabSource
+        .parse_headers(&headers(&[
+            ("x-gitlab-event", "Issue Hook"),
+            (
+                "x-gitlab-event-uuid",
+                "13792a34-cac6-4fda-95a8-c58e00a3954e",
+            ),
+            ("x-gitlab-token", "s3cret"),
+        ]))
+        .unwrap();
+    assert_eq!(parsed.event_type, "issue");
+    assert_eq!(parsed.delivery_id, "13792a34-cac6-4fda-95a8-c58e00a3954e");
+    assert_eq!(parsed.signature.as_deref(), Some("s3cret"));
+
+    assert!(GitlabSource
+        .parse_headers(&headers(&[("x-gitlab-token", "s3cret")]))
+        .is_err());
+}
+
+/// The token must equal the secret.
+#[test]
+fn test_authenticate_compares_token() {
+    assert!(GitlabSource.authenticate(b"{}", "s3cret", "s3cret").is_ok());
+    assert!(GitlabSource.authenticate(b"{}", "guess", "s3cret").is_err());
+    assert!(GitlabSource.authenticate(b"{}", "", "s3cret").is_err());
+}
+
+/// Merge requests are sessions of their project, keyed by the MR `iid`.
+#[test]
+fn test_normalize_merge_request() {
+    let envelope = GitlabSource
+        .normalize(
+            "merge_request",
+            json!({
+                "object_kind": "merge_request",
+                "user": { "id": 7, "username": "alice" },
+                "project": project("acme/widgets"),
+                "object_attributes": { "id": 9001, "iid": 12, "action": "open" },
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(envelope.event_type, "merge_request");
+    assert_eq!(envelope.action.as_deref(), Some("open"));
+    assert_eq!(envelope.entity, EventEntity::PullRequest { number: 12 });
+    assert_eq!(envelope.repository.full_name, "acme/widgets");
+    assert!(envelope.repository.private);
+    assert_eq!(envelope.sender.unwrap().login, "alice");
+    assert_eq!(envelope.session_id.as_str(), "acme/widgets/pull_request/12");
+}
+
+/// Comments join the session of the merge request or issue they are on.
+#[test]
+fn test_normalize_note() {
+    let on_issue = GitlabSource
+        .normalize(
+            "note",
+            json!({
+                "project": project("acme/widgets"),
+                "object_attributes": { "id": 1, "noteable_type": "Issue" },
+                "issue": { "iid": 3 },
+            }),
+        )
+        .unwrap();
+    assert_eq!(on_issue.entity, EventEntity::Issue { number: 3 });
+
+    let on_commit = GitlabSource
+        .normalize(
+            "note",
+            json!({
+                "project": project("acme/widgets"),
+                "object_attributes": { "id": 2, "noteable_type": "Commit" },
+            }),
+        )
+        .unwrap();
+    assert_eq!(on_commit.entity, EventEntity::Repository);
+}
+
+/// Pushes, tag pushes and pipelines map to branches, releases and runs.
+#[test]
+fn test_normalize_push_tag_and_pipeline() {
+    let push = GitlabSource
+        .normalize(
+            "push",
+            json!({
+                "ref": "refs/heads/main",
+                "user_id": 4,
+                "user_username": "bob",
+                "project": project("acme/widgets"),
+            }),
+        )
+        .unwrap();
+    assert_eq!(
+        push.entity,
+        EventEntity::Branch {
+            name: "main".to_string()
+        }
+    );
+    assert_eq!(push.sender.unwrap().login, "bob");
+
+    let tag = GitlabSource
+        .normalize(
+            "tag_push",
+            json!({ "ref": "refs/tags/v1.2.0", "project": project("acme/widgets") }),
+        )
+        .unwrap();
+    assert_eq!(
+        tag.entity,
+        EventEntity::Release {
+            tag: "v1.2.0".to_string()
+        }
+    );
+
+    let pipeline = GitlabSource
+        .normalize(
+            "pipeline",
+            json!({
+                "project": project("acme/widgets"),
+                "object_attributes": { "id": 31, "status": "success" },
+            }),
+        )
+        .unwrap();
+    assert_eq!(pipeline.entity, EventEntity::WorkflowRun { id: 31 });
+}
+
+/// Subgroup separators are kept in `full_name` but not in the session ID.
+#[test]
+fn test_normalize_subgroup_project() {
+    let envelope = GitlabSource
+        .normalize(
+            "issue",
+            json!({
+                "project": project("acme/platform/widgets"),
+                "object_attributes": { "iid": 5, "action": "close" },
+            }),
+        )
+        .unwrap();
+
+    assert_eq!(envelope.repository.full_name, "acme/platform/widgets");
+    assert_eq!(envelope.repository.owner.login, "acme:platform");
+    assert_eq!(
+        envelope.session_id.as_str(),
+        "acme:platform/widgets/issue/5"
+    );
+}
+
+/// Payloads without a project cannot be attributed.
+#[test]
+fn test_normalize_requires_project() {
+    let error = GitlabSource
+        .normalize("push", json!({ "ref": "refs/heads/main" }))
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        NormalizationError::MissingRequiredField { ref field } if field == "project"
+    ));
+}
@@ -0,0 +1,198 @@
+//! Event lineage across replays, redrives and retries.
+//!
+//! When a stored event is delivered again as a new event — replayed by an
+//! operator, redriven from the dead-letter queue, or re-emitted by a retry —
+//! the new event carries an [`EventLineage`] pointing at the event it was
+//! derived from (`parent_event_id`) and at the original webhook event that
+//! started the chain (`root_event_id`). Original webhook deliveries carry no
+//! lineage.
+//!
+//! [`LineageNode::build_tree`] assembles the events sharing a root into a
+//! tree for the `/api/events/{id}/lineage` endpoint.
+
+use super::WrappedEvent;
+use crate::{EventId, Timestamp};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// ============================================================================
+// Lineage References
+// ============================================================================
+
+/// Why an event was derived from an earlier event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LineageCause {
+    /// An operator replayed the parent event.
+    Replay,
+
+    /// The parent event was redriven from the dead-letter queue.
+    Redrive,
+
+    /// The parent event was re-emitted by automatic retry.
+    Retry,
+}
+
+impl LineageCause {
+    /// Get string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Replay => "replay",
+            Self::Redrive => "redrive",
+            Self::Retry => "retry",
+        }
+    }
+}
+
+impl fmt::Display for LineageCause {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Reference from a derived event to the events it descends from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EventLineage {
+    /// Event this event was directly derived from.
+    pub parent_event_id: EventId,
+
+    /// Original webhook event at the top of the lineage chain.
+    pub root_event_id: EventId,
+
+    /// Why this event was derived from its parent.
+    pub cause: LineageCause,
+}
+
+impl EventLineage {
+    /// Lineage for an event derived from `parent`.
+    ///
+    /// The root is inherited from the parent's lineage, or is the parent
+    /// itself when the parent is an original webhook event.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_core::webhook::{EventLineage, LineageCause, WrappedEvent};
+    ///
+    /// let original = WrappedEvent::new(
+    ///     "github".to_string(),
+    ///     "push".to_string(),
+    ///     None,
+    ///     None,
+    ///     serde_json::json!({}),
+    ///     None,
+    /// );
+    /// let lineage = EventLineage::derived_from(&original, LineageCause::Replay);
+    /// assert_eq!(lineage.parent_event_id, original.event_id);
+    /// assert_eq!(lineage.root_event_id, original.event_id);
+    /// ```
+    pub fn derived_from(parent: &WrappedEvent, cause: LineageCause) -> Self {
+        Self {
+            parent_event_id: parent.event_id,
+            root_event_id: parent.root_event_id(),
+            cause,
+        }
+    }
+}
+
+// ============================================================================
+// Lineage Tree
+// ============================================================================
+
+/// One event in a lineage tree.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LineageNode {
+    /// Event identifier.
+    pub event_id: EventId,
+
+    /// Event type of the event.
+    pub event_type: String,
+
+    /// When the event was received or re-injected.
+    pub received_at: Timestamp,
+
+    /// Why this event was derived from its parent; `None` for an original
+    /// webhook event.
+    pub cause: Option<LineageCause>,
+
+    /// Events derived directly from this one, oldest first.
+    pub children: Vec<LineageNode>,
+}
+
+impl LineageNode {
+    /// Build the lineage tree below `top_event_id` from `events`.
+    ///
+    /// `top_event_id` is normally the root of the lineage, but may be any
+    /// event in it (e.g. the oldest surviving ancestor when the root has been
+    /// removed by retention). `events` may contain unrelated events; only
+    /// descendants of the top event are included. When the top event is the
+    /// lineage root, descendants whose parent is missing are attached
+    /// directly to it so they remain visible. Returns `None` when the top
+    /// event is not present.
+    pub fn build_tree(top_event_id: EventId, events: &[WrappedEvent]) -> Option<Self> {
+        let top = events.iter().find(|e| e.event_id == top_event_id)?;
+        let root_event_id = top.root_event_id();
+
+        let mut related: Vec<&WrappedEvent> = events
+            .iter()
+            .filter(|e| e.event_id != top_event_id)
+            .filter(|e| e.lineage.is_some_and(|l| l.root_event_id == root_event_id))
+            .collect();
+        related.sort_by_key(|e| e.received_at);
+
+        let known: HashSet<EventId> = related
+            .iter()
+            .map(|e| e.event_id)
+            .chain(std::iter::once(top_event_id))
+            .collect();
+
+        let mut children: HashMap<EventId, Vec<&WrappedEvent>> = HashMap::new();
+        for event in related {
+            let Some(lineage) = event.lineage else {
+                continue;
+            };
+            let parent = if known.contains(&lineage.parent_event_id) {
+                lineage.parent_event_id
+            } else if top_event_id == root_event_id {
+                top_event_id
+            } else {
+                continue;
+            };
+            children.entry(parent).or_default().push(event);
+        }
+
+        Some(Self::assemble(top, &children))
+    }
+
+    /// Number of events in this subtree, including this one.
+    pub fn len(&self) -> usize {
+        1 + self.children.iter().map(Self::len).sum::<usize>()
+    }
+
+    /// Always `false`: a lineage tree contains at least its root.
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    fn assemble(event: &WrappedEvent, children: &HashMap<EventId, Vec<&WrappedEvent>>) -> Self {
+        Self {
+            event_id: event.event_id,
+            event_type: event.event_type.clone(),
+            received_at: event.received_at,
+            cause: event.lineage.map(|l| l.cause),
+            children: children
+                .get(&event.event_id)
+                .map(|kids| {
+                    kids.iter()
+                        .map(|child| Self::assemble(child, children))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[path = "lineage_tests.rs"]
+mod tests;
@@ -0,0 +1,127 @@
+//! Tests for event lineage references and tree assembly.
+
+use super::*;
+
+fn original() -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        None,
+        serde_json::json!({ "number": 1 }),
+        None,
+    )
+}
+
+mod derive_tests {
+    use super::*;
+
+    /// A derived event references its parent and inherits the root.
+    #[test]
+    fn test_derived_event_references_parent_and_root() {
+        let root = original();
+        let replay = root.derive(LineageCause::Replay);
+        let retry = replay.derive(LineageCause::Retry);
+
+        let lineage = retry.lineage.unwrap();
+        assert_eq!(lineage.parent_event_id, replay.event_id);
+        assert_eq!(lineage.root_event_id, root.event_id);
+        assert_eq!(lineage.cause, LineageCause::Retry);
+        assert_eq!(retry.payload, root.payload);
+        assert_eq!(retry.correlation_id, root.correlation_id);
+    }
+
+    /// Original events have no lineage and are their own root.
+    #[test]
+    fn test_original_event_is_its_own_root() {
+        let event = original();
+        assert!(event.lineage.is_none());
+        assert_eq!(event.root_event_id(), event.event_id);
+    }
+
+    /// Lineage survives a serde round trip; original events omit the field.
+    #[test]
+    fn test_lineage_serialization() {
+        let root = original();
+        let json = serde_json::to_value(&root).unwrap();
+        assert!(json.get("lineage").is_none());
+
+        let redrive = root.derive(LineageCause::Redrive);
+        let json = serde_json::to_value(&redrive).unwrap();
+        assert_eq!(json["lineage"]["cause"], "redrive");
+
+        let back: WrappedEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(back.lineage, redrive.lineage);
+    }
+}
+
+mod build_tree_tests {
+    use super::*;
+
+    /// Descendants are nested under their parents.
+    #[test]
+    fn test_builds_nested_tree() {
+        let root = original();
+        let replay = root.derive(LineageCause::Replay);
+        let redrive = root.derive(LineageCause::Redrive);
+        let retry = replay.derive(LineageCause::Retry);
+        let unrelated = original();
+        let events = vec![
+            retry.clone(),
+            unrelated,
+            root.clone(),
+            redrive.clone(),
+            replay.clone(),
+        ];
+
+        let tree = LineageNode::build_tree(root.event_id, &events).unwrap();
+
+        assert_eq!(tree.event_id, root.event_id);
+        assert_eq!(tree.cause, None);
+        assert_eq!(tree.len(), 4);
+        let replay_node = tree
+            .children
+            .iter()
+            .find(|n| n.event_id == replay.event_id)
+            .unwrap();
+        assert_eq!(replay_node.cause, Some(LineageCause::Replay));
+        assert_eq!(replay_node.children[0].event_id, retry.event_id);
+    }
+
+    /// Descendants of a missing parent are attached to the root.
+    #[test]
+    fn test_orphans_attach_to_root() {
+        let root = original();
+        let replay = root.derive(LineageCause::Replay);
+        let retry = replay.derive(LineageCause::Retry);
+
+        let tree = LineageNode::build_tree(root.event_id, &[root.clone(), retry.clone()]).unwrap();
+
+        assert_eq!(tree.children.len(), 1);
+        assert_eq!(tree.children[0].event_id, retry.event_id);
+    }
+
+    /// A tree can be built below an intermediate event.
+    #[test]
+    fn test_builds_subtree_from_intermediate_event() {
+        let root = original();
+        let replay = root.derive(LineageCause::Replay);
+        let redrive = root.derive(LineageCause::Redrive);
+        let retry = replay.derive(LineageCause::Retry);
+        let events = vec![replay.clone(), redrive, retry.clone()];
+
+        let tree = LineageNode::build_tree(replay.event_id, &events).unwrap();
+
+        assert_eq!(tree.len(), 2);
+        assert_eq!(tree.children[0].event_id, retry.event_id);
+    }
+
+    /// A missing root yields no tree.
+    #[test]
+    fn test_missing_root_returns_none() {
+        let root = original();
+        let replay = root.derive(LineageCause::Replay);
+
+        assert!(LineageNode::build_tree(root.event_id, &[replay]).is_none());
+    }
+}
@@ -9,14 +9,14 @@ use crate::{
         AuditAction, AuditActor, AuditContext, AuditEvent, AuditEventType, AuditLogger,
         AuditResource, AuditResult, WebhookProcessingAction,
     },
-    CorrelationId, EventId, Repository, RepositoryId, SessionId, Timestamp, TraceContext, User,
-    UserId, UserType, ValidationError,
+    CorrelationId, EventId, MonotonicTimestamp, PipelineStage, Repository, RepositoryId, SessionId,
+    StageTimings, Timestamp, TraceContext, User, UserId, UserType, ValidationError,
 };
 use async_trait::async_trait;
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use tracing::info;
+use std::collections::{BTreeMap, HashMap};
+use tracing::{debug, info, warn};
 
 // ============================================================================
 // Core Types
@@ -27,7 +27,13 @@ use tracing::info;
 pub struct WebhookRequest {
     pub headers: WebhookHeaders,
     pub body: Bytes,
+    /// Wall-clock receive time; always equal to `receipt.wall()`.
     pub received_at: Timestamp,
+    /// Receive time on both clocks, for latency measurement.
+    ///
+    /// See [`MonotonicTimestamp`] for why latencies are not computed from
+    /// `received_at`.
+    pub receipt: MonotonicTimestamp,
     /// Raw HTTP headers (lowercase names) for providers that use non-GitHub header names.
     ///
     /// Populated by [`WebhookRequest::with_raw_headers`]; empty when constructed via
@@ -39,17 +45,33 @@ pub struct WebhookRequest {
     /// `traceparent`, `x-correlation-id`, and `x-request-id` (in priority order).
     /// `None` when constructed via [`WebhookRequest::new`] (backward compat).
     pub trace_context: Option<TraceContext>,
+    /// Per-stage processing durations for this request.
+    ///
+    /// Processors record the stages they run; the HTTP handler keeps a clone
+    /// (see [`WebhookRequest::with_timings`]) to report them once processing
+    /// completes.
+    pub timings: StageTimings,
+    /// Where the request entered the pipeline.
+    ///
+    /// [`EventOrigin::Github`] for both constructors; callers injecting
+    /// events set another origin with [`WebhookRequest::with_origin`]. The
+    /// HTTP intake never takes the origin from request headers.
+    pub origin: EventOrigin,
 }
 
 impl WebhookRequest {
     /// Create new webhook request (backward-compatible, no raw headers).
     pub fn new(headers: WebhookHeaders, body: Bytes) -> Self {
+        let receipt = MonotonicTimestamp::now();
         Self {
             headers,
             body,
-            received_at: Timestamp::now(),
+            received_at: receipt.wall(),
+            receipt,
             raw_headers: HashMap::new(),
             trace_context: None,
+            timings: StageTimings::new(),
+            origin: EventOrigin::Github,
         }
     }
 
@@ -69,15 +91,47 @@ impl WebhookRequest {
         body: Bytes,
     ) -> Self {
         let trace_context = TraceContext::from_headers(&raw_headers);
+        let receipt = MonotonicTimestamp::now();
         Self {
             headers,
             body,
-            received_at: Timestamp::now(),
+            received_at: receipt.wall(),
+            receipt,
             raw_headers,
             trace_context,
+            timings: StageTimings::new(),
+            origin: EventOrigin::Github,
         }
     }
 
+    /// Set the moment the request was received.
+    ///
+    /// Use when timing started before the request was constructed, e.g. at
+    /// the start of the HTTP handler, so that the stored receive time and the
+    /// latency metrics share the same origin.
+    pub fn with_receipt(mut self, receipt: MonotonicTimestamp) -> Self {
+        self.received_at = receipt.wall();
+        self.receipt = receipt;
+        self
+    }
+
+    /// Record stage durations into `timings` instead of a fresh record.
+    ///
+    /// Pass a clone of a record the caller keeps, so stages timed by the
+    /// processor are visible after the request has been consumed.
+    pub fn with_timings(mut self, timings: StageTimings) -> Self {
+        self.timings = timings;
+        self
+    }
+
+    /// Mark the request as entering the pipeline from `origin`.
+    ///
+    /// Normalization copies the origin into the resulting event.
+    pub fn with_origin(mut self, origin: EventOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
     /// Get event type from headers
     pub fn event_type(&self) -> &str {
         &self.headers.event_type
@@ -92,14 +146,30 @@ impl WebhookRequest {
     pub fn signature(&self) -> Option<&str> {
         self.headers.signature.as_deref()
     }
+
+    /// GitHub Enterprise Server instance named by the
+    /// `X-GitHub-Enterprise-Host` header, trimmed and lowercased.
+    ///
+    /// `None` for github.com deliveries and requests built without raw
+    /// headers.
+    pub fn enterprise_host(&self) -> Option<String> {
+        self.raw_headers
+            .get(ENTERPRISE_HOST_HEADER)
+            .map(|host| host.trim().to_ascii_lowercase())
+            .filter(|host| !host.is_empty())
+    }
 }
 
+/// Header naming the GitHub Enterprise Server instance that sent a
+/// delivery (lowercase, as stored in [`WebhookRequest::raw_headers`]).
+pub const ENTERPRISE_HOST_HEADER: &str = "x-github-enterprise-host";
+
 /// GitHub-specific HTTP headers required for processing
 #[derive(Debug, Clone)]
 pub struct WebhookHeaders {
     pub event_type: String,         // X-GitHub-Event
     pub delivery_id: String,        // X-GitHub-Delivery
-    pub signature: Option<String>,  // X-Hub-Signature-256
+    pub signature: Option<String>,  // X-Hub-Signature-256 (or legacy X-Hub-Signature)
     pub user_agent: Option<String>, // User-Agent
     pub content_type: String,       // Content-Type
 }
@@ -130,7 +200,7 @@ impl WebhookHeaders {
             })?
             .clone();
 
-        let signature = headers.get("x-hub-signature-256").cloned();
+        let signature = Self::signature_from_headers(headers);
 
         let user_agent = headers.get("user-agent").cloned();
 
@@ -173,7 +243,7 @@ impl WebhookHeaders {
             .cloned()
             .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
 
-        let signature = headers.get("x-hub-signature-256").cloned();
+        let signature = Self::signature_from_headers(headers);
         let user_agent = headers.get("user-agent").cloned();
         let content_type = headers
             .get("content-type")
@@ -189,6 +259,19 @@ impl WebhookHeaders {
         }
     }
 
+    /// Select the signature header, preferring `X-Hub-Signature-256`.
+    ///
+    /// Falls back to the legacy SHA-1 `X-Hub-Signature` header sent by older
+    /// GitHub Enterprise Server releases. The value keeps its `sha1=` prefix
+    /// so validators can reject it unless SHA-1 has been explicitly enabled
+    /// for the provider.
+    fn signature_from_headers(headers: &HashMap<String, String>) -> Option<String> {
+        headers
+            .get("x-hub-signature-256")
+            .or_else(|| headers.get("x-hub-signature"))
+            .cloned()
+    }
+
     /// Validate header values
     pub fn validate(&self) -> Result<(), ValidationError> {
         if self.event_type.is_empty() {
@@ -233,16 +316,28 @@ impl WebhookHeaders {
 /// Normalized event structure after webhook processing
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EventEnvelope {
+    /// Envelope schema version; see [`envelope_migration`].
+    #[serde(default)]
+    pub schema_version: u32,
     pub event_id: EventId,
     pub event_type: String,
     pub action: Option<String>,
     pub repository: Repository,
+    /// Account that triggered the event (the payload's `sender`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<User>,
     pub entity: EventEntity,
     pub session_id: SessionId,
     pub correlation_id: CorrelationId,
     pub occurred_at: Timestamp,
     pub processed_at: Timestamp,
     pub payload: serde_json::Value,
+    /// Parent and root events when this event is a replay, redrive or retry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lineage: Option<EventLineage>,
+    /// Values added by envelope extensions, keyed by extension namespace.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, serde_json::Value>,
 }
 
 impl EventEnvelope {
@@ -268,16 +363,20 @@ impl EventEnvelope {
         let now = Timestamp::now();
 
         Self {
+            schema_version: SCHEMA_VERSION,
             event_id,
             event_type,
             action,
             repository,
+            sender: None,
             entity,
             session_id,
             correlation_id,
             occurred_at: now,
             processed_at: now,
             payload,
+            lineage: None,
+            extensions: BTreeMap::new(),
         }
     }
 
@@ -314,6 +413,56 @@ pub(crate) fn generate_session_id(repository: &Repository, entity: &EventEntity)
     )
 }
 
+/// GitHub event types that [`EventEntity::from_payload`] maps to an entity.
+///
+/// Any other event type gets [`EventEntity::Unknown`] and an `unknown`
+/// session. Keep this list in sync with `from_payload`.
+pub const KNOWN_EVENT_TYPES: &[&str] = &[
+    "pull_request",
+    "pull_request_review",
+    "pull_request_review_comment",
+    "issues",
+    "issue_comment",
+    "issue_dependencies",
+    "push",
+    "create",
+    "delete",
+    "release",
+    "discussion",
+    "discussion_comment",
+    "workflow_run",
+    "workflow_job",
+    "team",
+    "check_run",
+    "check_suite",
+    "deployment",
+    "deployment_status",
+    "commit_comment",
+    "repository",
+    "status",
+    "custom_property",
+    "custom_property_values",
+    "label",
+    "milestone",
+    "projects_v2",
+    "projects_v2_item",
+    "projects_v2_status_update",
+    "workflow_dispatch",
+    "deploy_key",
+    "repository_ruleset",
+    "github_app_authorization",
+    "installation",
+    "installation_repositories",
+    "installation_target",
+    "ping",
+    "team_add",
+];
+
+/// Whether `event_type` is in [`KNOWN_EVENT_TYPES`].
+pub fn is_known_event_type(event_type: &str) -> bool {
+    KNOWN_EVENT_TYPES.contains(&event_type)
+}
+
 /// The primary GitHub object affected by the event (for session grouping)
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EventEntity {
@@ -353,6 +502,38 @@ pub enum EventEntity {
     Team {
         slug: String,
     },
+    /// A GitHub check run, identified by its ID.
+    ///
+    /// Used for sequential processing of the lifecycle of one check
+    /// (`check_run`).
+    /// Example session ID: `owner/repo/check_run/98765`
+    CheckRun {
+        id: u64,
+    },
+    /// A GitHub check suite, identified by its ID.
+    ///
+    /// Used for sequential processing of the checks requested for one
+    /// commit by one app (`check_suite`).
+    /// Example session ID: `owner/repo/check_suite/11111`
+    CheckSuite {
+        id: u64,
+    },
+    /// A GitHub deployment, identified by its ID.
+    ///
+    /// Used for sequential processing of a deployment and its status
+    /// updates (`deployment`, `deployment_status`).
+    /// Example session ID: `owner/repo/deployment/123456`
+    Deployment {
+        id: u64,
+    },
+    /// A commit, identified by its SHA.
+    ///
+    /// Used for sequential processing of comments on the same commit
+    /// (`commit_comment`).
+    /// Example session ID: `owner/repo/commit/6dcb09b5b57875f334f61aebed695e2e4193db5e`
+    Commit {
+        sha: String,
+    },
     Repository,
     Unknown,
 }
@@ -442,8 +623,37 @@ impl EventEntity {
                     }
                 }
             }
+            "check_suite" => {
+                if let Some(suite) = payload.get("check_suite") {
+                    if let Some(id) = suite.get("id").and_then(|n| n.as_u64()) {
+                        return Self::CheckSuite { id };
+                    }
+                }
+            }
+            "check_run" => {
+                if let Some(run) = payload.get("check_run") {
+                    if let Some(id) = run.get("id").and_then(|n| n.as_u64()) {
+                        return Self::CheckRun { id };
+                    }
+                }
+            }
+            "deployment" | "deployment_status" => {
+                if let Some(deployment) = payload.get("deployment") {
+                    if let Some(id) = deployment.get("id").and_then(|n| n.as_u64()) {
+                        return Self::Deployment { id };
+                    }
+                }
+            }
+            "commit_comment" => {
+                if let Some(comment) = payload.get("comment") {
+                    if let Some(sha) = comment.get("commit_id").and_then(|s| s.as_str()) {
+                        return Self::Commit {
+                            sha: sha.to_string(),
+                        };
+                    }
+                }
+            }
             "repository"
-            | "commit_comment"
             | "status"
             | "custom_property"
             | "custom_property_values"
@@ -454,7 +664,6 @@ impl EventEntity {
             | "projects_v2_status_update"
             | "workflow_dispatch"
             | "deploy_key"
-            | "deployment"
             | "repository_ruleset"
             | "github_app_authorization"
             | "installation"
@@ -483,6 +692,10 @@ impl EventEntity {
             Self::Discussion { .. } => "discussion",
             Self::WorkflowRun { .. } => "workflow_run",
             Self::Team { .. } => "team",
+            Self::CheckRun { .. } => "check_run",
+            Self::CheckSuite { .. } => "check_suite",
+            Self::Deployment { .. } => "deployment",
+            Self::Commit { .. } => "commit",
             Self::Repository => "repository",
             Self::Unknown => "unknown",
         }
@@ -501,6 +714,10 @@ impl EventEntity {
             Self::Discussion { number } => number.to_string(),
             Self::WorkflowRun { id } => id.to_string(),
             Self::Team { slug } => slug.clone(),
+            Self::CheckRun { id } => id.to_string(),
+            Self::CheckSuite { id } => id.to_string(),
+            Self::Deployment { id } => id.to_string(),
+            Self::Commit { sha } => sha.clone(),
             Self::Repository => "repository".to_string(),
             Self::Unknown => "unknown".to_string(),
         }
@@ -534,6 +751,9 @@ pub enum WebhookError {
 
     #[error("JSON parsing failed: {0}")]
     JsonParsing(#[from] serde_json::Error),
+
+    #[error("Delivery '{delivery_id}' was already received")]
+    ReplayedDelivery { delivery_id: String },
 }
 
 impl WebhookError {
@@ -547,6 +767,7 @@ impl WebhookError {
             Self::Validation(_) => false,
             Self::Normalization(_) => false,
             Self::JsonParsing(_) => false,
+            Self::ReplayedDelivery { .. } => false,
         }
     }
 
@@ -566,6 +787,7 @@ impl WebhookError {
             Self::Validation(_) => crate::ErrorCategory::Permanent,
             Self::Normalization(_) => crate::ErrorCategory::Permanent,
             Self::JsonParsing(_) => crate::ErrorCategory::Permanent,
+            Self::ReplayedDelivery { .. } => crate::ErrorCategory::Security,
         }
     }
 
@@ -681,6 +903,42 @@ pub trait WebhookProcessor: Send + Sync {
     ) -> Result<WrappedEvent, NormalizationError>;
 }
 
+/// Maximum number of candidate secrets tried for one signature.
+pub const MAX_CANDIDATE_SECRETS: usize = 4;
+
+/// Label of the secret returned by [`SignatureValidator::get_webhook_secret`]
+/// when it is offered as a candidate.
+pub const PRIMARY_SECRET_LABEL: &str = "primary";
+
+/// A webhook secret that may have signed a delivery.
+///
+/// While a secret is rotated, a provider accepts deliveries signed with its
+/// new secret and with the previous one. Once the sender has switched, the
+/// previous secret can be marked `retired`: it is still tried, so deliveries
+/// signed with it fail with [`ValidationError::RetiredSecret`] instead of a
+/// plain digest mismatch.
+#[derive(Clone)]
+pub struct CandidateSecret {
+    /// Name logged when this secret matches; never the secret itself.
+    pub label: String,
+
+    /// The secret value.
+    pub secret: String,
+
+    /// Whether a match with this secret is refused.
+    pub retired: bool,
+}
+
+impl std::fmt::Debug for CandidateSecret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CandidateSecret")
+            .field("label", &self.label)
+            .field("secret", &"<REDACTED>")
+            .field("retired", &self.retired)
+            .finish()
+    }
+}
+
 /// Interface for GitHub webhook signature validation
 #[async_trait]
 pub trait SignatureValidator: Send + Sync {
@@ -695,6 +953,64 @@ pub trait SignatureValidator: Send + Sync {
     /// Get webhook secret for event type
     async fn get_webhook_secret(&self, event_type: &str) -> Result<String, SecretError>;
 
+    /// Get webhook secret for event type sent by `repository` (full name,
+    /// `owner/repo`)
+    ///
+    /// Validators with per-repository secrets resolve the repository's own
+    /// secret, falling back to a wildcard. Defaults to
+    /// [`get_webhook_secret`](Self::get_webhook_secret).
+    async fn get_repository_webhook_secret(
+        &self,
+        event_type: &str,
+        repository: &str,
+    ) -> Result<String, SecretError> {
+        let _ = repository;
+        self.get_webhook_secret(event_type).await
+    }
+
+    /// Get the secrets to try for event type, in order of preference
+    ///
+    /// Defaults to the [`get_webhook_secret`](Self::get_webhook_secret)
+    /// secret, labelled [`PRIMARY_SECRET_LABEL`]. At most
+    /// [`MAX_CANDIDATE_SECRETS`] are tried.
+    async fn get_candidate_secrets(
+        &self,
+        event_type: &str,
+    ) -> Result<Vec<CandidateSecret>, SecretError> {
+        Ok(vec![CandidateSecret {
+            label: PRIMARY_SECRET_LABEL.to_string(),
+            secret: self.get_webhook_secret(event_type).await?,
+            retired: false,
+        }])
+    }
+
+    /// Get the secrets to try for event type sent by `repository`, in order
+    /// of preference
+    ///
+    /// `repository` is `None` when the payload names no repository; the
+    /// candidates are then those of
+    /// [`get_candidate_secrets`](Self::get_candidate_secrets). Otherwise
+    /// defaults to the
+    /// [`get_repository_webhook_secret`](Self::get_repository_webhook_secret)
+    /// secret, so validators overriding `get_candidate_secrets` should
+    /// override this too.
+    async fn get_repository_candidate_secrets(
+        &self,
+        event_type: &str,
+        repository: Option<&str>,
+    ) -> Result<Vec<CandidateSecret>, SecretError> {
+        let Some(repository) = repository else {
+            return self.get_candidate_secrets(event_type).await;
+        };
+        Ok(vec![CandidateSecret {
+            label: PRIMARY_SECRET_LABEL.to_string(),
+            secret: self
+                .get_repository_webhook_secret(event_type, repository)
+                .await?,
+            retired: false,
+        }])
+    }
+
     /// Check if implementation supports constant-time comparison
     fn supports_constant_time_comparison(&self) -> bool;
 }
@@ -801,6 +1117,10 @@ pub struct WebhookProcessorImpl {
     signature_validator: Option<std::sync::Arc<dyn SignatureValidator>>,
     payload_storer: Option<std::sync::Arc<dyn PayloadStorer>>,
     audit_logger: Option<std::sync::Arc<dyn AuditLogger>>,
+    extensions: EnvelopeExtensionRegistry,
+    storage_sampler: StorageSampler,
+    replay_guard: Option<std::sync::Arc<ReplayGuard>>,
+    deduplication: Option<std::sync::Arc<dyn DeduplicationStore>>,
 }
 
 impl WebhookProcessorImpl {
@@ -833,9 +1153,102 @@ impl WebhookProcessorImpl {
             signature_validator,
             payload_storer,
             audit_logger,
+            extensions: EnvelopeExtensionRegistry::new(),
+            storage_sampler: StorageSampler::default(),
+            replay_guard: None,
+            deduplication: None,
         }
     }
 
+    /// Apply `extensions` to every normalized event.
+    ///
+    /// Extension values are added to the event's `extensions` map after
+    /// normalization. See [`EnvelopeExtension`].
+    pub fn with_extensions(mut self, extensions: EnvelopeExtensionRegistry) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Decide with `sampler` which payloads are stored.
+    ///
+    /// Payloads that are sampled out or stored only on failure skip the
+    /// payload storage stage; the decision is recorded in the event's
+    /// `payload_storage` field. See [`StorageSampler`].
+    pub fn with_storage_sampling(mut self, sampler: StorageSampler) -> Self {
+        self.storage_sampler = sampler;
+        self
+    }
+
+    /// Reject deliveries whose delivery ID `guard` has already seen.
+    ///
+    /// The delivery ID is claimed after signature validation and released
+    /// again when storage or normalization fails. See [`ReplayGuard`].
+    pub fn with_replay_protection(mut self, guard: std::sync::Arc<ReplayGuard>) -> Self {
+        self.replay_guard = Some(guard);
+        self
+    }
+
+    /// Process each delivery ID at most once, remembering them in `store`.
+    ///
+    /// The delivery ID is claimed after signature validation for the event
+    /// the delivery will produce; a delivery whose ID is already claimed
+    /// produces [`ProcessingOutput::AlreadyProcessed`] with the original
+    /// event's ID. The claim is released again when storage or
+    /// normalization fails. An unavailable store is logged and the delivery
+    /// processed. See [`DeduplicationStore`].
+    pub fn with_deduplication(mut self, store: std::sync::Arc<dyn DeduplicationStore>) -> Self {
+        self.deduplication = Some(store);
+        self
+    }
+
+    /// Release the deduplication claim on `delivery_id`.
+    async fn release_delivery(&self, delivery_id: &str) {
+        if let Some(store) = &self.deduplication {
+            deduplication::release_delivery(store.as_ref(), delivery_id).await;
+        }
+    }
+
+    /// Store the raw payload, unless storage sampling skips or defers it,
+    /// and normalize the request into a wrapped event.
+    async fn store_and_normalize(
+        &self,
+        request: &WebhookRequest,
+    ) -> Result<WrappedEvent, WebhookError> {
+        // Store raw payload for audit/replay (if storer available),
+        // unless the storage sampling rules skip or defer it
+        let payload_storage = self
+            .storage_sampler
+            .decide(request.event_type(), request.delivery_id());
+        if payload_storage.is_stored() {
+            let validation_status = ValidationStatus::Valid;
+            let _storage_ref = request
+                .timings
+                .time(
+                    PipelineStage::BlobStore,
+                    self.store_raw_payload(request, validation_status),
+                )
+                .await?;
+        } else {
+            debug!(
+                event_type = %request.event_type(),
+                delivery_id = %request.delivery_id(),
+                payload_storage = %payload_storage,
+                "Payload storage skipped by storage sampling"
+            );
+        }
+
+        // Normalize to provider-agnostic wrapped event and add
+        // extension values
+        let mut wrapped_event = request
+            .timings
+            .time(PipelineStage::Normalization, self.normalize_event(request))
+            .await?;
+        self.extensions.apply(&mut wrapped_event);
+        wrapped_event.payload_storage = payload_storage;
+
+        Ok(wrapped_event)
+    }
+
     /// Extract repository information from payload
     ///
     /// Parses repository data from GitHub webhook payload, including
@@ -926,6 +1339,121 @@ impl WebhookProcessorImpl {
 
         Ok(repository)
     }
+
+    /// Extract the account that triggered the event from the payload's
+    /// `sender` object.
+    ///
+    /// Returns `None` when there is no `sender` or it lacks an `id` or
+    /// `login`. Unknown account types are reported as [`UserType::User`].
+    fn extract_sender(&self, payload: &serde_json::Value) -> Option<User> {
+        let sender = payload.get("sender")?;
+        let id = sender.get("id").and_then(|i| i.as_u64())?;
+        let login = sender.get("login").and_then(|l| l.as_str())?.to_string();
+        let user_type = match sender.get("type").and_then(|t| t.as_str()) {
+            Some("Bot") => UserType::Bot,
+            Some("Organization") => UserType::Organization,
+            _ => UserType::User,
+        };
+
+        Some(User {
+            id: UserId::new(id),
+            login,
+            user_type,
+        })
+    }
+}
+
+/// Full name (`owner/repo`) of the repository a webhook payload names.
+///
+/// Read before the signature is checked, to select the repository's
+/// secret; a forged name only selects a secret the sender must still know.
+/// Returns `None` when the payload is not JSON or names no repository.
+pub fn payload_repository_full_name(payload: &[u8]) -> Option<String> {
+    #[derive(Deserialize)]
+    struct Payload {
+        repository: Option<PayloadRepository>,
+    }
+
+    #[derive(Deserialize)]
+    struct PayloadRepository {
+        full_name: String,
+    }
+
+    serde_json::from_slice::<Payload>(payload)
+        .ok()?
+        .repository
+        .map(|r| r.full_name)
+}
+
+/// Try `candidates` concurrently and return the label of the first one, in
+/// order, whose secret produced `signature`.
+///
+/// Each comparison is made by `validator` in constant time. A single
+/// candidate is checked inline.
+///
+/// # Errors
+///
+/// - [`ValidationError::RetiredSecret`] — only retired candidates match.
+/// - The first candidate's error — no candidate matches.
+async fn match_candidate_secrets(
+    validator: &std::sync::Arc<dyn SignatureValidator>,
+    payload: &[u8],
+    signature: &str,
+    candidates: &[CandidateSecret],
+) -> Result<String, ValidationError> {
+    let results = if let [candidate] = candidates {
+        vec![
+            validator
+                .validate_signature(payload, signature, &candidate.secret)
+                .await,
+        ]
+    } else {
+        let payload = Bytes::copy_from_slice(payload);
+        let mut tasks = tokio::task::JoinSet::new();
+        for (index, candidate) in candidates.iter().enumerate() {
+            let validator = std::sync::Arc::clone(validator);
+            let payload = payload.clone();
+            let signature = signature.to_string();
+            let secret = candidate.secret.clone();
+            tasks.spawn(async move {
+                let result = validator
+                    .validate_signature(&payload, &signature, &secret)
+                    .await;
+                (index, result)
+            });
+        }
+
+        let mut results = vec![None; candidates.len()];
+        while let Some(joined) = tasks.join_next().await {
+            let (index, result) = joined.map_err(|e| ValidationError::InvalidFormat {
+                field: "signature".to_string(),
+                message: format!("signature comparison failed: {}", e),
+            })?;
+            results[index] = Some(result);
+        }
+        results.into_iter().flatten().collect()
+    };
+
+    let mut retired_match = None;
+    let mut first_error = None;
+    for (candidate, result) in candidates.iter().zip(results) {
+        match result {
+            Ok(()) if !candidate.retired => return Ok(candidate.label.clone()),
+            Ok(()) => {
+                retired_match.get_or_insert_with(|| candidate.label.clone());
+            }
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+
+    if let Some(label) = retired_match {
+        return Err(ValidationError::RetiredSecret { label });
+    }
+    Err(first_error.unwrap_or_else(|| ValidationError::Required {
+        field: "secret".to_string(),
+    }))
 }
 
 #[async_trait]
@@ -943,13 +1471,22 @@ impl WebhookProcessor for WebhookProcessorImpl {
         );
 
         // 1. Validate headers and basic structure
-        request.headers.validate()?;
+        let header_check = std::time::Instant::now();
+        let headers_valid = request.headers.validate();
+        request
+            .timings
+            .record(PipelineStage::HeaderParse, header_check.elapsed());
+        headers_valid?;
 
         // 2. Validate webhook signature (if present and validator available).
         //    On failure, log a security audit event before propagating the error.
         if let Some(signature) = request.signature() {
-            if let Err(err) = self
-                .validate_signature(&request.body, signature, request.event_type())
+            if let Err(err) = request
+                .timings
+                .time(
+                    PipelineStage::SignatureValidation,
+                    self.validate_signature(&request.body, signature, request.event_type()),
+                )
                 .await
             {
                 if let Some(audit_logger) = &self.audit_logger {
@@ -984,14 +1521,69 @@ impl WebhookProcessor for WebhookProcessorImpl {
             }
         }
 
-        // 3. Store raw payload for audit/replay (if storer available)
-        let validation_status = ValidationStatus::Valid;
-        let _storage_ref = self.store_raw_payload(&request, validation_status).await?;
+        // 3. Skip deliveries that were already processed. The delivery ID
+        //    is claimed for the event this delivery will produce.
+        let event_id = EventId::new();
+        let deduplication_claimed = match &self.deduplication {
+            Some(store) => {
+                match deduplication::claim_delivery(store.as_ref(), request.delivery_id(), event_id)
+                    .await
+                {
+                    Ok(claimed) => claimed,
+                    Err(original_event_id) => {
+                        info!(
+                            event_type = %request.event_type(),
+                            delivery_id = %request.delivery_id(),
+                            original_event_id = %original_event_id,
+                            "Skipped already processed webhook delivery"
+                        );
+                        return Ok(ProcessingOutput::AlreadyProcessed {
+                            delivery_id: request.delivery_id().to_string(),
+                            event_id: original_event_id,
+                            correlation_id: CorrelationId::new(),
+                        });
+                    }
+                }
+            }
+            None => false,
+        };
+
+        // 4. Reject replays of a delivery accepted within the replay window
+        if let Some(guard) = &self.replay_guard {
+            if !guard.claim(request.delivery_id()) {
+                warn!(
+                    event_type = %request.event_type(),
+                    delivery_id = %request.delivery_id(),
+                    "Rejected replayed webhook delivery"
+                );
+                if deduplication_claimed {
+                    self.release_delivery(request.delivery_id()).await;
+                }
+                return Err(WebhookError::ReplayedDelivery {
+                    delivery_id: request.delivery_id().to_string(),
+                });
+            }
+        }
 
-        // 4. Normalize to provider-agnostic wrapped event
-        let wrapped_event = self.normalize_event(&request).await?;
+        // 5. Store the payload and normalize the event. A failed delivery
+        //    releases its claims so that a redelivery is accepted.
+        let mut wrapped_event = match self.store_and_normalize(&request).await {
+            Ok(event) => event,
+            Err(err) => {
+                if let Some(guard) = &self.replay_guard {
+                    guard.release(request.delivery_id());
+                }
+                if deduplication_claimed {
+                    self.release_delivery(request.delivery_id()).await;
+                }
+                return Err(err);
+            }
+        };
+        if deduplication_claimed {
+            wrapped_event.event_id = event_id;
+        }
 
-        // 5. Log successful webhook processing to audit trail (GitHub-specific path:
+        // 6. Log successful webhook processing to audit trail (GitHub-specific path:
         //    only emit the full audit record when session_id and repository are available)
         if let Some(audit_logger) = &self.audit_logger {
             if let (Some(session_id), Ok(repository)) = (
@@ -1025,9 +1617,8 @@ impl WebhookProcessor for WebhookProcessorImpl {
             }
         }
 
-        info!(
-            event_id = %wrapped_event.event_id,
-            session_id = ?wrapped_event.session_id,
+        crate::event_info!(
+            wrapped_event,
             event_type = %wrapped_event.event_type,
             "Successfully processed webhook"
         );
@@ -1042,22 +1633,35 @@ impl WebhookProcessor for WebhookProcessorImpl {
         event_type: &str,
     ) -> Result<(), ValidationError> {
         if let Some(validator) = &self.signature_validator {
-            // Get webhook secret for this event type
-            let secret = validator
-                .get_webhook_secret(event_type)
+            // Get the candidate webhook secrets for this event type and the
+            // repository the payload names
+            let repository = payload_repository_full_name(payload);
+            let mut candidates = validator
+                .get_repository_candidate_secrets(event_type, repository.as_deref())
                 .await
                 .map_err(|e| ValidationError::InvalidFormat {
                     field: "signature".to_string(),
                     message: format!("Failed to retrieve webhook secret: {}", e),
                 })?;
+            candidates.truncate(MAX_CANDIDATE_SECRETS);
 
             // Validate signature using constant-time comparison
-            validator
-                .validate_signature(payload, signature, &secret)
-                .await?;
+            let matched = match_candidate_secrets(validator, payload, signature, &candidates)
+                .await
+                .inspect_err(|e| {
+                    if let ValidationError::RetiredSecret { label } = e {
+                        warn!(
+                            event_type = %event_type,
+                            secret = %label,
+                            "Webhook signed with a retired secret"
+                        );
+                    }
+                })?;
 
             info!(
                 event_type = %event_type,
+                repository = repository.as_deref().unwrap_or("-"),
+                secret = %matched,
                 "Webhook signature validated successfully"
             );
         } else {
@@ -1121,22 +1725,28 @@ impl WebhookProcessor for WebhookProcessorImpl {
             .and_then(|a| a.as_str())
             .map(String::from);
 
+        // Extract the account that triggered the event
+        let sender = self.extract_sender(&payload);
+
         // Build provider-agnostic wrapped event.
         // The provider field is intentionally left empty here; the outer
         // GithubWebhookProvider stamps the final provider name.
-        let event = WrappedEvent::new(
+        let mut event = WrappedEvent::new(
             String::new(),
             request.event_type().to_string(),
             action,
             Some(session_id),
             payload,
             request.trace_context.clone(),
-        );
-
-        info!(
-            event_id = %event.event_id,
+        )
+        .with_receipt(request.receipt)
+        .with_origin(request.origin);
+        event.sender = sender;
+        event.enterprise_host = request.enterprise_host();
+
+        crate::event_info!(
+            event,
             event_type = %event.event_type,
-            session_id = ?event.session_id,
             "Event normalized successfully"
         );
 
@@ -1162,10 +1772,57 @@ pub use github_provider::GithubWebhookProvider;
 pub mod generic_provider;
 pub use generic_provider::GenericWebhookProvider;
 
+// Pluggable sources for forges other than GitHub
+mod bitbucket;
+mod gitlab;
+mod source;
+pub use bitbucket::BitbucketSource;
+pub use gitlab::GitlabSource;
+pub use source::{SourceWebhookProvider, WebhookSource, WebhookSourceConfig, WebhookSourceKind};
+
+// Namespaced custom fields computed during normalization
+mod extensions;
+pub use extensions::{
+    EnvelopeExtension, EnvelopeExtensionRegistry, EnvelopeExtensionsConfig, ExtensionError,
+    JiraKeyExtension, JiraKeyExtensionConfig,
+};
+
+// Event lineage across replays, redrives and retries
+mod lineage;
+pub use lineage::{EventLineage, LineageCause, LineageNode};
+
+// Where an event entered the intake pipeline
+mod origin;
+pub use origin::EventOrigin;
+
+// Per-event-type sampling of payload storage
+mod storage_sampling;
+pub use storage_sampling::{
+    PayloadStorage, StorageSampler, StorageSamplingConfig, StorageSamplingMode, StorageSamplingRule,
+};
+
+// Deduplication of deliveries by delivery ID
+mod deduplication;
+pub use deduplication::{DeduplicationError, DeduplicationStore, DeliveryClaim};
+
+// Rejection of replayed deliveries
+mod replay_protection;
+pub use replay_protection::{ReplayGuard, ReplayProtectionConfig};
+
+// Envelope schema versions and migration of stored envelopes
+pub mod envelope_migration;
+pub use envelope_migration::{deserialize_envelope, EnvelopeMigrationError, SCHEMA_VERSION};
+
 // Processing output types for multi-mode webhook processing
 mod processing_output;
 pub use processing_output::{DirectQueueMetadata, ProcessingOutput, WrappedEvent};
 
+// Typed models of common GitHub payloads
+#[cfg(feature = "typed-payloads")]
+pub mod typed_payloads;
+#[cfg(feature = "typed-payloads")]
+pub use typed_payloads::TypedEvent;
+
 #[cfg(test)]
 #[path = "mod_tests.rs"]
 mod tests;
@@ -256,6 +256,33 @@ mod webhook_request_tests {
         assert!(result.is_ok());
     }
 
+    /// Verify that the legacy SHA-1 header is used when the SHA-256 header is absent.
+    #[test]
+    fn test_legacy_sha1_signature_header_fallback() {
+        let mut headers = create_test_headers();
+        headers.remove("x-hub-signature-256");
+        headers.insert(
+            "x-hub-signature".to_string(),
+            "sha1=legacy-signature".to_string(),
+        );
+
+        let result = WebhookHeaders::from_http_headers(&headers).unwrap();
+        assert_eq!(result.signature, Some("sha1=legacy-signature".to_string()));
+    }
+
+    /// Verify that the SHA-256 header wins when both signature headers are present.
+    #[test]
+    fn test_sha256_signature_header_preferred_over_sha1() {
+        let mut headers = create_test_headers();
+        headers.insert(
+            "x-hub-signature".to_string(),
+            "sha1=legacy-signature".to_string(),
+        );
+
+        let result = WebhookHeaders::from_http_headers(&headers).unwrap();
+        assert_eq!(result.signature, Some("sha256=test-signature".to_string()));
+    }
+
     #[test]
     fn test_webhook_request_creation() {
         let headers = WebhookHeaders::from_http_headers(&create_test_headers()).unwrap();
@@ -392,6 +419,194 @@ mod signature_validation_tests {
         // Should skip validation gracefully when no validator provided
         assert!(result.is_ok());
     }
+
+    /// Validator offering `candidates` in order and accepting only
+    /// signatures made with `signing_secret`.
+    struct RotatingValidator {
+        signing_secret: String,
+        candidates: Vec<CandidateSecret>,
+    }
+
+    #[async_trait]
+    impl SignatureValidator for RotatingValidator {
+        async fn validate_signature(
+            &self,
+            _payload: &[u8],
+            _signature: &str,
+            secret_key: &str,
+        ) -> Result<(), ValidationError> {
+            if secret_key == self.signing_secret {
+                Ok(())
+            } else {
+                Err(ValidationError::InvalidFormat {
+                    field: "signature".to_string(),
+                    message: "invalid signature".to_string(),
+                })
+            }
+        }
+
+        async fn get_webhook_secret(&self, _event_type: &str) -> Result<String, SecretError> {
+            Ok(self.candidates[0].secret.clone())
+        }
+
+        async fn get_candidate_secrets(
+            &self,
+            _event_type: &str,
+        ) -> Result<Vec<CandidateSecret>, SecretError> {
+            Ok(self.candidates.clone())
+        }
+
+        fn supports_constant_time_comparison(&self) -> bool {
+            true
+        }
+    }
+
+    fn candidate(label: &str, retired: bool) -> CandidateSecret {
+        CandidateSecret {
+            label: label.to_string(),
+            secret: format!("{}-secret", label),
+            retired,
+        }
+    }
+
+    async fn validate_with(
+        signing_secret: &str,
+        candidates: Vec<CandidateSecret>,
+    ) -> Result<(), ValidationError> {
+        let validator = Arc::new(RotatingValidator {
+            signing_secret: signing_secret.to_string(),
+            candidates,
+        });
+        WebhookProcessorImpl::new(Some(validator), None, None)
+            .validate_signature(b"test payload", "sha256=any", "push")
+            .await
+    }
+
+    #[tokio::test]
+    async fn test_signature_matches_later_candidate() {
+        let candidates = vec![candidate("primary", false), candidate("previous", false)];
+
+        let result = validate_with("previous-secret", candidates).await;
+
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_signature_with_only_retired_match_fails() {
+        let candidates = vec![candidate("primary", false), candidate("2025-q4", true)];
+
+        let result = validate_with("2025-q4-secret", candidates).await;
+
+        match result {
+            Err(ValidationError::RetiredSecret { label }) => assert_eq!(label, "2025-q4"),
+            other => panic!("Expected RetiredSecret, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_signature_without_matching_candidate_fails() {
+        let candidates = vec![candidate("primary", false), candidate("2025-q4", true)];
+
+        let result = validate_with("unknown-secret", candidates).await;
+
+        assert!(matches!(result, Err(ValidationError::InvalidFormat { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_candidates_beyond_limit_not_tried() {
+        let candidates: Vec<_> = (0..=MAX_CANDIDATE_SECRETS)
+            .map(|i| candidate(&format!("secret-{}", i), false))
+            .collect();
+        let last = format!("secret-{}-secret", MAX_CANDIDATE_SECRETS);
+
+        let result = validate_with(&last, candidates).await;
+
+        assert!(result.is_err());
+    }
+
+    /// Validator with one secret per repository and `shared-secret` for the
+    /// rest, accepting only signatures made with `signing_secret`.
+    struct PerRepositoryValidator {
+        signing_secret: String,
+    }
+
+    #[async_trait]
+    impl SignatureValidator for PerRepositoryValidator {
+        async fn validate_signature(
+            &self,
+            _payload: &[u8],
+            _signature: &str,
+            secret_key: &str,
+        ) -> Result<(), ValidationError> {
+            if secret_key == self.signing_secret {
+                Ok(())
+            } else {
+                Err(ValidationError::InvalidFormat {
+                    field: "signature".to_string(),
+                    message: "invalid signature".to_string(),
+                })
+            }
+        }
+
+        async fn get_webhook_secret(&self, _event_type: &str) -> Result<String, SecretError> {
+            Ok("shared-secret".to_string())
+        }
+
+        async fn get_repository_webhook_secret(
+            &self,
+            _event_type: &str,
+            repository: &str,
+        ) -> Result<String, SecretError> {
+            Ok(format!("{}-secret", repository))
+        }
+
+        fn supports_constant_time_comparison(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_payload_repository_full_name() {
+        assert_eq!(
+            payload_repository_full_name(br#"{"repository":{"full_name":"org/app"}}"#),
+            Some("org/app".to_string())
+        );
+        assert_eq!(payload_repository_full_name(br#"{"zen":"hi"}"#), None);
+        assert_eq!(payload_repository_full_name(b"not json"), None);
+    }
+
+    #[tokio::test]
+    async fn test_signature_validated_with_repository_secret() {
+        let processor = WebhookProcessorImpl::new(
+            Some(Arc::new(PerRepositoryValidator {
+                signing_secret: "org/app-secret".to_string(),
+            })),
+            None,
+            None,
+        );
+
+        let own = processor
+            .validate_signature(
+                br#"{"repository":{"full_name":"org/app"}}"#,
+                "sha256=any",
+                "push",
+            )
+            .await;
+        let other = processor
+            .validate_signature(
+                br#"{"repository":{"full_name":"org/other"}}"#,
+                "sha256=any",
+                "push",
+            )
+            .await;
+        let none = processor
+            .validate_signature(br#"{"zen":"hi"}"#, "sha256=any", "ping")
+            .await;
+
+        assert!(own.is_ok());
+        assert!(other.is_err());
+        assert!(none.is_err());
+    }
 }
 
 // ============================================================================
@@ -427,6 +642,22 @@ mod event_normalization_tests {
             "session_id should encode entity: {:?}",
             event.session_id
         );
+        assert_eq!(event.origin, EventOrigin::Github);
+    }
+
+    /// The request's origin is carried into the normalized event.
+    #[tokio::test]
+    async fn test_normalization_keeps_request_origin() {
+        let processor = WebhookProcessorImpl::new(None, None, None);
+        let mut headers = create_test_headers();
+        headers.insert("x-github-event".to_string(), "pull_request".to_string());
+        let webhook_headers = WebhookHeaders::from_http_headers(&headers).unwrap();
+        let body = Bytes::from(serde_json::to_vec(&create_pr_payload()).unwrap());
+        let request =
+            WebhookRequest::new(webhook_headers, body).with_origin(EventOrigin::Synthetic);
+
+        let event = processor.normalize_event(&request).await.unwrap();
+        assert_eq!(event.origin, EventOrigin::Synthetic);
     }
 
     #[tokio::test]
@@ -755,6 +986,107 @@ mod event_normalization_tests {
         let id_str = event.correlation_id.as_str();
         assert!(!id_str.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_sender_extraction() {
+        let processor = WebhookProcessorImpl::new(None, None, None);
+        let headers = WebhookHeaders::from_http_headers(&create_test_headers()).unwrap();
+        let mut payload = create_pr_payload();
+        payload["sender"] = json!({
+            "id": 49699333,
+            "login": "dependabot[bot]",
+            "type": "Bot",
+            "site_admin": false
+        });
+        let body = Bytes::from(serde_json::to_vec(&payload).unwrap());
+        let request = WebhookRequest::new(headers, body);
+
+        let event = processor.normalize_event(&request).await.unwrap();
+        let sender = event.sender.expect("sender should be extracted");
+        assert_eq!(sender.id, UserId::new(49699333));
+        assert_eq!(sender.login, "dependabot[bot]");
+        assert_eq!(sender.user_type, UserType::Bot);
+        assert!(sender.is_bot());
+
+        // The sender is serialized with the envelope
+        let serialized = serde_json::to_value(WrappedEvent::new(
+            "github".to_string(),
+            "push".to_string(),
+            None,
+            None,
+            json!({}),
+            None,
+        ))
+        .unwrap();
+        assert!(serialized.get("sender").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_missing_or_incomplete_sender() {
+        let processor = WebhookProcessorImpl::new(None, None, None);
+
+        for sender in [None, Some(json!({ "login": "octocat" }))] {
+            let headers = WebhookHeaders::from_http_headers(&create_test_headers()).unwrap();
+            let mut payload = create_pr_payload();
+            if let Some(sender) = sender {
+                payload["sender"] = sender;
+            }
+            let body = Bytes::from(serde_json::to_vec(&payload).unwrap());
+            let request = WebhookRequest::new(headers, body);
+
+            let event = processor.normalize_event(&request).await.unwrap();
+            assert!(event.sender.is_none());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unknown_sender_type_is_user() {
+        let processor = WebhookProcessorImpl::new(None, None, None);
+        let headers = WebhookHeaders::from_http_headers(&create_test_headers()).unwrap();
+        let mut payload = create_pr_payload();
+        payload["sender"] = json!({ "id": 7, "login": "ghost", "type": "Mannequin" });
+        let body = Bytes::from(serde_json::to_vec(&payload).unwrap());
+        let request = WebhookRequest::new(headers, body);
+
+        let event = processor.normalize_event(&request).await.unwrap();
+        let sender = event.sender.unwrap();
+        assert_eq!(sender.user_type, UserType::User);
+        assert!(!sender.is_bot());
+    }
+
+    #[tokio::test]
+    async fn test_enterprise_host_captured() {
+        let processor = WebhookProcessorImpl::new(None, None, None);
+        let mut raw = create_test_headers();
+        let body = Bytes::from(serde_json::to_vec(&create_pr_payload()).unwrap());
+
+        let request = WebhookRequest::with_raw_headers(
+            WebhookHeaders::from_http_headers(&raw).unwrap(),
+            raw.clone(),
+            body.clone(),
+        );
+        let event = processor.normalize_event(&request).await.unwrap();
+        assert!(event.enterprise_host.is_none());
+        assert!(serde_json::to_value(&event)
+            .unwrap()
+            .get("enterprise_host")
+            .is_none());
+
+        raw.insert(
+            ENTERPRISE_HOST_HEADER.to_string(),
+            " GitHub.Corp.Example".to_string(),
+        );
+        let request = WebhookRequest::with_raw_headers(
+            WebhookHeaders::from_http_headers(&raw).unwrap(),
+            raw,
+            body,
+        );
+        let event = processor.normalize_event(&request).await.unwrap();
+        assert_eq!(
+            event.enterprise_host.as_deref(),
+            Some("github.corp.example")
+        );
+    }
 }
 
 // ============================================================================
@@ -998,6 +1330,180 @@ mod integration_tests {
             _ => panic!("Expected Storage error"),
         }
     }
+
+    /// Payloads skipped by storage sampling never reach the storer, and the
+    /// decision is recorded on the event.
+    #[tokio::test]
+    async fn test_pipeline_skips_storage_by_sampling() {
+        let storer = Arc::new(MockPayloadStorer { should_fail: true });
+        let config: StorageSamplingConfig =
+            serde_yaml::from_str("rules: [{ events: [pull_request], mode: on_failure }]").unwrap();
+        let processor = WebhookProcessorImpl::new(None, Some(storer), None)
+            .with_storage_sampling(StorageSampler::from_config(&config).unwrap());
+
+        let mut headers = create_test_headers();
+        headers.insert("x-github-event".to_string(), "pull_request".to_string());
+        let webhook_headers = WebhookHeaders::from_http_headers(&headers).unwrap();
+        let body = Bytes::from(serde_json::to_vec(&create_pr_payload()).unwrap());
+
+        let output = processor
+            .process_webhook(WebhookRequest::new(webhook_headers, body))
+            .await
+            .expect("sampled-out payloads are not stored");
+        let event = output.as_wrapped().expect("should be Wrapped output");
+        assert_eq!(event.payload_storage, PayloadStorage::OnFailure);
+    }
+
+    /// A delivery ID seen before is rejected; a delivery that failed
+    /// releases its ID so a redelivery is accepted.
+    #[tokio::test]
+    async fn test_pipeline_rejects_replayed_delivery() {
+        let guard = Arc::new(ReplayGuard::new(std::time::Duration::from_secs(60), 100));
+        let request = || {
+            let mut headers = create_test_headers();
+            headers.insert("x-github-event".to_string(), "pull_request".to_string());
+            let webhook_headers = WebhookHeaders::from_http_headers(&headers).unwrap();
+            let body = Bytes::from(serde_json::to_vec(&create_pr_payload()).unwrap());
+            WebhookRequest::new(webhook_headers, body)
+        };
+
+        let failing = WebhookProcessorImpl::new(
+            Some(Arc::new(MockSignatureValidator { should_fail: false })),
+            Some(Arc::new(MockPayloadStorer { should_fail: true })),
+            None,
+        )
+        .with_replay_protection(guard.clone());
+        assert!(matches!(
+            failing.process_webhook(request()).await,
+            Err(WebhookError::Storage(_))
+        ));
+        assert!(guard.is_empty());
+
+        let processor = WebhookProcessorImpl::new(
+            Some(Arc::new(MockSignatureValidator { should_fail: false })),
+            None,
+            None,
+        )
+        .with_replay_protection(guard.clone());
+        processor
+            .process_webhook(request())
+            .await
+            .expect("first delivery is accepted");
+
+        let error = processor.process_webhook(request()).await.unwrap_err();
+        assert!(matches!(error, WebhookError::ReplayedDelivery { .. }));
+        assert_eq!(error.error_category(), crate::ErrorCategory::Security);
+        assert!(!error.is_transient());
+    }
+
+    /// A delivery ID already processed short-circuits with the original
+    /// event; a delivery that failed releases its ID so a redelivery is
+    /// processed.
+    #[tokio::test]
+    async fn test_pipeline_skips_already_processed_delivery() {
+        let store = Arc::new(ReplayGuard::new(std::time::Duration::from_secs(60), 100));
+        let request = || {
+            let mut headers = create_test_headers();
+            headers.insert("x-github-event".to_string(), "pull_request".to_string());
+            let webhook_headers = WebhookHeaders::from_http_headers(&headers).unwrap();
+            let body = Bytes::from(serde_json::to_vec(&create_pr_payload()).unwrap());
+            WebhookRequest::new(webhook_headers, body)
+        };
+
+        let failing = WebhookProcessorImpl::new(
+            None,
+            Some(Arc::new(MockPayloadStorer { should_fail: true })),
+            None,
+        )
+        .with_deduplication(store.clone());
+        assert!(matches!(
+            failing.process_webhook(request()).await,
+            Err(WebhookError::Storage(_))
+        ));
+        assert!(store.is_empty());
+
+        let processor = WebhookProcessorImpl::new(None, None, None).with_deduplication(store);
+        let first = processor
+            .process_webhook(request())
+            .await
+            .expect("first delivery is processed");
+
+        match processor.process_webhook(request()).await.unwrap() {
+            ProcessingOutput::AlreadyProcessed {
+                delivery_id,
+                event_id,
+                ..
+            } => {
+                assert_eq!(delivery_id, request().delivery_id());
+                assert_eq!(event_id, first.event_id());
+            }
+            other => panic!("expected AlreadyProcessed, got {:?}", other),
+        }
+    }
+
+    /// Registered extensions add their values to the processed event.
+    #[tokio::test]
+    async fn test_pipeline_applies_extensions() {
+        let config = EnvelopeExtensionsConfig {
+            jira: JiraKeyExtensionConfig {
+                enabled: true,
+                projects: vec![],
+            },
+        };
+        let processor = WebhookProcessorImpl::new(None, None, None)
+            .with_extensions(EnvelopeExtensionRegistry::from_config(&config).unwrap());
+
+        let mut headers = create_test_headers();
+        headers.insert("x-github-event".to_string(), "pull_request".to_string());
+        let webhook_headers = WebhookHeaders::from_http_headers(&headers).unwrap();
+        let mut payload = create_pr_payload();
+        payload["pull_request"]["title"] = json!("PLAT-42: Add feature");
+        let body = Bytes::from(serde_json::to_vec(&payload).unwrap());
+        let request = WebhookRequest::new(webhook_headers, body);
+
+        let output = processor.process_webhook(request).await.unwrap();
+        let event = output.as_wrapped().expect("should be Wrapped output");
+        assert_eq!(event.extensions["jira"], json!({ "keys": ["PLAT-42"] }));
+    }
+
+    /// Every stage of a successful run is timed into the caller's record.
+    #[tokio::test]
+    async fn test_pipeline_records_stage_timings() {
+        let validator = Arc::new(MockSignatureValidator { should_fail: false });
+        let storer = Arc::new(MockPayloadStorer { should_fail: false });
+        let processor = WebhookProcessorImpl::new(Some(validator), Some(storer), None);
+
+        let mut headers = create_test_headers();
+        headers.insert("x-github-event".to_string(), "pull_request".to_string());
+        let webhook_headers = WebhookHeaders::from_http_headers(&headers).unwrap();
+        let body = Bytes::from(serde_json::to_vec(&create_pr_payload()).unwrap());
+        let timings = StageTimings::new();
+        let request = WebhookRequest::new(webhook_headers, body).with_timings(timings.clone());
+
+        processor.process_webhook(request).await.unwrap();
+
+        for stage in PipelineStage::ALL {
+            assert!(timings.get(stage).is_some(), "{} not recorded", stage);
+        }
+    }
+
+    /// Stages after a failure are not recorded.
+    #[tokio::test]
+    async fn test_pipeline_failure_stops_stage_timings() {
+        let validator = Arc::new(MockSignatureValidator { should_fail: true });
+        let storer = Arc::new(MockPayloadStorer { should_fail: false });
+        let processor = WebhookProcessorImpl::new(Some(validator), Some(storer), None);
+
+        let headers = WebhookHeaders::from_http_headers(&create_test_headers()).unwrap();
+        let body = Bytes::from(serde_json::to_vec(&create_pr_payload()).unwrap());
+        let timings = StageTimings::new();
+        let request = WebhookRequest::new(headers, body).with_timings(timings.clone());
+
+        assert!(processor.process_webhook(request).await.is_err());
+        assert!(timings.get(PipelineStage::SignatureValidation).is_some());
+        assert!(timings.get(PipelineStage::BlobStore).is_none());
+        assert!(timings.get(PipelineStage::Normalization).is_none());
+    }
 }
 
 // ============================================================================
@@ -1532,7 +2038,6 @@ mod event_entity_new_events_tests {
     fn test_repository_mapped_events_return_repository_entity() {
         let repository_events = [
             "repository",
-            "commit_comment",
             "status",
             "custom_property",
             "custom_property_values",
@@ -1543,7 +2048,6 @@ mod event_entity_new_events_tests {
             "projects_v2_status_update",
             "workflow_dispatch",
             "deploy_key",
-            "deployment",
             "repository_ruleset",
             "github_app_authorization",
             "installation",
@@ -1563,6 +2067,15 @@ mod event_entity_new_events_tests {
         }
     }
 
+    /// Every event type in the registry is known, and no other is.
+    #[test]
+    fn test_known_event_types_registry() {
+        assert!(is_known_event_type("pull_request"));
+        assert!(is_known_event_type("team_add"));
+        assert!(!is_known_event_type("some_future_github_event"));
+        assert!(!is_known_event_type(""));
+    }
+
     /// Unknown event types must fall through to Unknown, never Repository.
     #[test]
     fn test_completely_unknown_event_type_returns_unknown() {
@@ -1573,3 +2086,149 @@ mod event_entity_new_events_tests {
         );
     }
 }
+
+// ============================================================================
+// Entity extraction from recorded GitHub payloads
+// ============================================================================
+
+mod event_entity_fixture_tests {
+    use super::*;
+
+    /// Normalize `fixture` as a delivery of `event_type`.
+    async fn normalize_fixture(event_type: &str, fixture: &str) -> WrappedEvent {
+        let mut raw = create_test_headers();
+        raw.insert("x-github-event".to_string(), event_type.to_string());
+        let request = WebhookRequest::new(
+            WebhookHeaders::from_http_headers(&raw).unwrap(),
+            Bytes::from(fixture.to_string()),
+        );
+        WebhookProcessorImpl::new(None, None, None)
+            .normalize_event(&request)
+            .await
+            .unwrap()
+    }
+
+    fn fixture_entity(event_type: &str, fixture: &str) -> EventEntity {
+        let payload: serde_json::Value = serde_json::from_str(fixture).unwrap();
+        EventEntity::from_payload(event_type, &payload)
+    }
+
+    /// Check runs and check suites get sessions of their own.
+    #[tokio::test]
+    async fn test_check_events() {
+        let check_run = include_str!("fixtures/check_run.json");
+        assert_eq!(
+            fixture_entity("check_run", check_run),
+            EventEntity::CheckRun { id: 128620228 }
+        );
+        let check_suite = include_str!("fixtures/check_suite.json");
+        assert_eq!(
+            fixture_entity("check_suite", check_suite),
+            EventEntity::CheckSuite { id: 118578147 }
+        );
+
+        let event = normalize_fixture("check_suite", check_suite).await;
+        assert_eq!(
+            event.session_id.unwrap().as_str(),
+            "Codertocat/Hello-World/check_suite/118578147"
+        );
+    }
+
+    /// Deployments and their status updates share the deployment's session.
+    #[tokio::test]
+    async fn test_deployment_events() {
+        let deployment = include_str!("fixtures/deployment.json");
+        let status = include_str!("fixtures/deployment_status.json");
+        assert_eq!(
+            fixture_entity("deployment", deployment),
+            EventEntity::Deployment { id: 145988746 }
+        );
+        assert_eq!(
+            fixture_entity("deployment_status", status),
+            fixture_entity("deployment", deployment)
+        );
+
+        let event = normalize_fixture("deployment_status", status).await;
+        assert_eq!(
+            event.session_id.unwrap().as_str(),
+            "Codertocat/Hello-World/deployment/145988746"
+        );
+    }
+
+    /// Commit comments are sessions of the commit they are on.
+    #[tokio::test]
+    async fn test_commit_comment_event() {
+        let fixture = include_str!("fixtures/commit_comment.json");
+        assert_eq!(
+            fixture_entity("commit_comment", fixture),
+            EventEntity::Commit {
+                sha: "6113728f27ae82c7b1a177c8d03f9e96e0adf246".to_string()
+            }
+        );
+
+        let event = normalize_fixture("commit_comment", fixture).await;
+        assert_eq!(
+            event.session_id.unwrap().as_str(),
+            "Codertocat/Hello-World/commit/6113728f27ae82c7b1a177c8d03f9e96e0adf246"
+        );
+    }
+
+    /// Workflow runs and discussions keep their existing sessions.
+    #[tokio::test]
+    async fn test_workflow_run_and_discussion_events() {
+        let workflow_run = include_str!("fixtures/workflow_run.json");
+        assert_eq!(
+            fixture_entity("workflow_run", workflow_run),
+            EventEntity::WorkflowRun { id: 30433642 }
+        );
+        let discussion = include_str!("fixtures/discussion.json");
+        assert_eq!(
+            fixture_entity("discussion", discussion),
+            EventEntity::Discussion { number: 90 }
+        );
+
+        let event = normalize_fixture("workflow_run", workflow_run).await;
+        assert_eq!(
+            event.session_id.unwrap().as_str(),
+            "octo-org/octo-repo/workflow_run/30433642"
+        );
+    }
+
+    /// Payloads missing the entity's ID fall back to Unknown.
+    #[test]
+    fn test_missing_ids_return_unknown() {
+        for event_type in [
+            "check_run",
+            "check_suite",
+            "deployment",
+            "deployment_status",
+            "commit_comment",
+        ] {
+            assert_eq!(
+                EventEntity::from_payload(event_type, &json!({})),
+                EventEntity::Unknown,
+                "expected Unknown for event type '{event_type}'"
+            );
+        }
+    }
+
+    /// New entities produce the expected session ID segments.
+    #[test]
+    fn test_new_entity_segments() {
+        for (entity, entity_type, entity_id) in [
+            (EventEntity::CheckRun { id: 1 }, "check_run", "1"),
+            (EventEntity::CheckSuite { id: 2 }, "check_suite", "2"),
+            (EventEntity::Deployment { id: 3 }, "deployment", "3"),
+            (
+                EventEntity::Commit {
+                    sha: "abc123".to_string(),
+                },
+                "commit",
+                "abc123",
+            ),
+        ] {
+            assert_eq!(entity.entity_type(), entity_type);
+            assert_eq!(entity.entity_id(), entity_id);
+        }
+    }
+}
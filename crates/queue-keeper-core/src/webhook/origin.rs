@@ -0,0 +1,118 @@
+//! Where an event entered the intake pipeline.
+//!
+//! Most events arrive as live webhook deliveries from a provider. Events can
+//! also be injected by Queue-Keeper itself or by an operator: replays and
+//! redrives of stored events, synthetic events from the self-test, and bulk
+//! imports. Those events must reach bots like any other event but must not be
+//! counted again toward per-source baselines — rate limits, duplicate
+//! detection, anomaly detection and intake statistics — or a replay of a
+//! thousand events would look like a traffic spike.
+//!
+//! [`EventOrigin`] is recorded on the [`WebhookRequest`](super::WebhookRequest)
+//! and copied into the [`WrappedEvent`](super::WrappedEvent) during
+//! normalization. Baseline consumers check
+//! [`EventOrigin::counts_toward_baselines`].
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// Where an event entered the intake pipeline.
+///
+/// Serialized as `"github"`, `"replay"`, `"synthetic"` or `"import"`.
+/// Envelopes written before the field existed deserialize as
+/// [`EventOrigin::Github`].
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum EventOrigin {
+    /// Live webhook delivery received over HTTP from a provider.
+    ///
+    /// Named after GitHub, the primary provider; deliveries from generic
+    /// providers use the same origin.
+    #[default]
+    Github,
+
+    /// Re-injection of a stored event: an operator replay, a dead-letter
+    /// redrive or a retry that created a new event.
+    Replay,
+
+    /// Event generated by Queue-Keeper, e.g. by the self-test.
+    Synthetic,
+
+    /// Event loaded in bulk from an external source.
+    Import,
+}
+
+impl EventOrigin {
+    /// All origins, in declaration order.
+    pub const ALL: [EventOrigin; 4] = [
+        EventOrigin::Github,
+        EventOrigin::Replay,
+        EventOrigin::Synthetic,
+        EventOrigin::Import,
+    ];
+
+    /// Get string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Github => "github",
+            Self::Replay => "replay",
+            Self::Synthetic => "synthetic",
+            Self::Import => "import",
+        }
+    }
+
+    /// Whether this is a live provider delivery.
+    pub fn is_github(&self) -> bool {
+        matches!(self, Self::Github)
+    }
+
+    /// Whether events of this origin count toward rate limits, duplicate
+    /// detection, anomaly detection and intake statistics.
+    ///
+    /// Only live deliveries do; injected events would otherwise be counted
+    /// twice or distort the baseline of real traffic.
+    pub fn counts_toward_baselines(&self) -> bool {
+        self.is_github()
+    }
+
+    /// Origin of an event derived from an event of this origin.
+    ///
+    /// Re-injecting a live delivery makes a replay; events that were already
+    /// injected keep their origin, so a redrive of an imported event is
+    /// still an import.
+    pub fn derived(&self) -> Self {
+        match self {
+            Self::Github => Self::Replay,
+            other => *other,
+        }
+    }
+}
+
+impl fmt::Display for EventOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl FromStr for EventOrigin {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|origin| origin.as_str() == s)
+            .ok_or_else(|| {
+                format!(
+                    "unknown event origin '{}' (expected github, replay, synthetic or import)",
+                    s
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+#[path = "origin_tests.rs"]
+mod tests;
@@ -0,0 +1,33 @@
+//! Tests for event origins.
+
+use super::*;
+
+/// Origins serialize as snake_case strings and parse back.
+#[test]
+fn test_origin_round_trips() {
+    for origin in EventOrigin::ALL {
+        let json = serde_json::to_string(&origin).unwrap();
+        assert_eq!(json, format!("\"{}\"", origin));
+        assert_eq!(serde_json::from_str::<EventOrigin>(&json).unwrap(), origin);
+        assert_eq!(origin.as_str().parse::<EventOrigin>().unwrap(), origin);
+    }
+    assert!("webhook".parse::<EventOrigin>().is_err());
+}
+
+/// Only live deliveries count toward baselines.
+#[test]
+fn test_only_github_counts_toward_baselines() {
+    assert!(EventOrigin::Github.counts_toward_baselines());
+    assert!(!EventOrigin::Replay.counts_toward_baselines());
+    assert!(!EventOrigin::Synthetic.counts_toward_baselines());
+    assert!(!EventOrigin::Import.counts_toward_baselines());
+}
+
+/// Deriving from a live delivery makes a replay; injected origins are kept.
+#[test]
+fn test_derived_origin() {
+    assert_eq!(EventOrigin::Github.derived(), EventOrigin::Replay);
+    assert_eq!(EventOrigin::Replay.derived(), EventOrigin::Replay);
+    assert_eq!(EventOrigin::Synthetic.derived(), EventOrigin::Synthetic);
+    assert_eq!(EventOrigin::Import.derived(), EventOrigin::Import);
+}
@@ -26,8 +26,14 @@
 //! | **Wrap**   | `Wrapped(WrappedEvent)`         | Normalise into provider-agnostic form |
 //! | **Direct** | `Direct { payload, metadata, target_queue }` | Forward raw payload as-is |
 
-use crate::{CorrelationId, EventId, SessionId, Timestamp, TraceContext};
+use super::lineage::{EventLineage, LineageCause};
+use super::origin::EventOrigin;
+use super::storage_sampling::PayloadStorage;
+use super::SCHEMA_VERSION;
+use crate::{CorrelationId, EventId, MonotonicTimestamp, SessionId, Timestamp, TraceContext, User};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
 
 // ============================================================================
 // WrappedEvent
@@ -52,6 +58,18 @@ use serde::{Deserialize, Serialize};
 /// GitHub events on the same pull request share a session ID derived from
 /// repository and entity). It is `None` when no ordering concept applies.
 ///
+/// `session_epoch` is incremented each time an operator resets the session.
+/// Consumers should discard in-flight work for a session when they receive an
+/// event whose epoch differs from the one they started with. Epochs are held
+/// in memory and restart at `0` when the service restarts, so compare them
+/// for equality rather than treating a lower epoch as stale.
+///
+/// # Lineage
+///
+/// `lineage` is `None` for events created from a webhook delivery. Events
+/// re-injected by a replay, DLQ redrive or retry reference their parent and
+/// the original root event; see [`WrappedEvent::derive`].
+///
 /// # Payload Completeness
 ///
 /// All provider-specific details (GitHub: `repository`, `entity`; Jira:
@@ -79,6 +97,15 @@ use serde::{Deserialize, Serialize};
 /// ```
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WrappedEvent {
+    /// Schema version the envelope was written under.
+    ///
+    /// [`SCHEMA_VERSION`] for envelopes created by this build; `0` for
+    /// envelopes written before versioning. Read stored envelopes with
+    /// [`deserialize_envelope`](super::deserialize_envelope), which upgrades
+    /// them to the current version first.
+    #[serde(default)]
+    pub schema_version: u32,
+
     /// Unique identifier for this event (auto-generated ULID).
     pub event_id: EventId,
 
@@ -91,6 +118,22 @@ pub struct WrappedEvent {
     /// Optional action within the event type (e.g. `"opened"`, `"closed"`).
     pub action: Option<String>,
 
+    /// Account that triggered the event, with its login, ID and type.
+    ///
+    /// For GitHub this is the payload's `sender`; a GitHub App acting on a
+    /// repository appears with type `Bot`. `None` for providers without a
+    /// sender concept and for envelopes written before the field existed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sender: Option<User>,
+
+    /// GitHub Enterprise Server instance that sent the event.
+    ///
+    /// The `X-GitHub-Enterprise-Host` header of the delivery, lowercased.
+    /// `None` for github.com and non-GitHub providers, and omitted from
+    /// serialized envelopes in that case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enterprise_host: Option<String>,
+
     /// Session identifier for ordered processing.
     ///
     /// `Some` when the provider or event warrants ordered processing.
@@ -99,6 +142,23 @@ pub struct WrappedEvent {
     /// `None` for providers or events without an ordering requirement.
     pub session_id: Option<SessionId>,
 
+    /// Reset epoch of the session at the time the event was delivered.
+    ///
+    /// Starts at `0` and is bumped by each session reset. Always `0` for
+    /// events without a `session_id`. Defaults to `0` when deserializing
+    /// envelopes written before epochs existed.
+    #[serde(default)]
+    pub session_epoch: u64,
+
+    /// Position of the event in its session's send order.
+    ///
+    /// Assigned by the [`SessionSequencer`](crate::queue_integration::SessionSequencer)
+    /// when the event is accepted for delivery; increases with every event
+    /// of the session but may skip numbers. `None` for events without a
+    /// `session_id` and omitted from serialized envelopes in that case.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_sequence: Option<u64>,
+
     /// Correlation identifier for distributed tracing.
     pub correlation_id: CorrelationId,
 
@@ -114,6 +174,54 @@ pub struct WrappedEvent {
     /// extract what they need using the fields appropriate for their
     /// provider (e.g. `payload["repository"]["full_name"]` for GitHub).
     pub payload: serde_json::Value,
+
+    /// Parent and root events when this event is a replay, redrive or retry.
+    ///
+    /// Omitted from serialized envelopes of original webhook events.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lineage: Option<EventLineage>,
+
+    /// Where the event entered the intake pipeline.
+    ///
+    /// Omitted from serialized envelopes of live deliveries, and defaults to
+    /// [`EventOrigin::Github`] for envelopes written before the field
+    /// existed. Consumers building baselines of real traffic should skip
+    /// events whose origin does not
+    /// [count toward baselines](EventOrigin::counts_toward_baselines).
+    #[serde(default, skip_serializing_if = "EventOrigin::is_github")]
+    pub origin: EventOrigin,
+
+    /// Whether the payload was written to storage when the event was
+    /// received.
+    ///
+    /// Set by the storage sampling rules; replays check it before looking
+    /// for a blob that was never written. Omitted from serialized envelopes
+    /// of stored events, and defaults to [`PayloadStorage::Stored`] for
+    /// envelopes written before the field existed.
+    #[serde(default, skip_serializing_if = "PayloadStorage::is_stored")]
+    pub payload_storage: PayloadStorage,
+
+    /// Values added by envelope extensions, keyed by extension namespace.
+    ///
+    /// See [`EnvelopeExtension`](super::EnvelopeExtension). Omitted from
+    /// serialized envelopes when no extension produced a value.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, serde_json::Value>,
+
+    /// Receive time on both clocks, for latency measurement.
+    ///
+    /// Only present on events created in this process; not serialized, so
+    /// events read back from storage or a queue fall back to `received_at`.
+    #[serde(skip)]
+    pub receipt: Option<MonotonicTimestamp>,
+
+    /// W3C `traceparent` of the request that delivered the event.
+    ///
+    /// Sent as the `traceparent` attribute of queue messages so consumers
+    /// can continue the trace. Only present on events received in this
+    /// process; not serialized and not copied to derived events.
+    #[serde(skip)]
+    pub trace_parent: Option<String>,
 }
 
 impl WrappedEvent {
@@ -156,15 +264,26 @@ impl WrappedEvent {
         let now = Timestamp::now();
         let correlation_id = trace_context.map(CorrelationId::from).unwrap_or_default();
         Self {
+            schema_version: SCHEMA_VERSION,
             event_id: EventId::new(),
             provider,
             event_type,
             action,
+            sender: None,
+            enterprise_host: None,
             session_id,
+            session_epoch: 0,
+            session_sequence: None,
             correlation_id,
             received_at: now,
             processed_at: now,
             payload,
+            lineage: None,
+            origin: EventOrigin::Github,
+            payload_storage: PayloadStorage::Stored,
+            extensions: BTreeMap::new(),
+            receipt: None,
+            trace_parent: None,
         }
     }
 
@@ -209,17 +328,173 @@ impl WrappedEvent {
     ) -> Self {
         let correlation_id = trace_context.map(CorrelationId::from).unwrap_or_default();
         Self {
+            schema_version: SCHEMA_VERSION,
             event_id: EventId::new(),
             provider,
             event_type,
             action,
+            sender: None,
+            enterprise_host: None,
             session_id,
+            session_epoch: 0,
+            session_sequence: None,
             correlation_id,
             received_at,
             processed_at: Timestamp::now(),
             payload,
+            lineage: None,
+            origin: EventOrigin::Github,
+            payload_storage: PayloadStorage::Stored,
+            extensions: BTreeMap::new(),
+            receipt: None,
+            trace_parent: None,
         }
     }
+
+    /// Return a copy of this event stamped with the given session epoch.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_core::webhook::WrappedEvent;
+    ///
+    /// let event = WrappedEvent::new(
+    ///     "github".to_string(),
+    ///     "push".to_string(),
+    ///     None,
+    ///     None,
+    ///     serde_json::json!({}),
+    ///     None,
+    /// )
+    /// .with_session_epoch(3);
+    /// assert_eq!(event.session_epoch, 3);
+    /// ```
+    pub fn with_session_epoch(mut self, session_epoch: u64) -> Self {
+        self.session_epoch = session_epoch;
+        self
+    }
+
+    /// Return a copy of this event stamped with its session sequence number.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_core::webhook::WrappedEvent;
+    ///
+    /// let event = WrappedEvent::new(
+    ///     "github".to_string(),
+    ///     "push".to_string(),
+    ///     None,
+    ///     None,
+    ///     serde_json::json!({}),
+    ///     None,
+    /// )
+    /// .with_session_sequence(7);
+    /// assert_eq!(event.session_sequence, Some(7));
+    /// ```
+    pub fn with_session_sequence(mut self, session_sequence: u64) -> Self {
+        self.session_sequence = Some(session_sequence);
+        self
+    }
+
+    /// Return a copy of this event marked as entering the pipeline from
+    /// `origin`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_core::webhook::{EventOrigin, WrappedEvent};
+    ///
+    /// let event = WrappedEvent::new(
+    ///     "github".to_string(),
+    ///     "push".to_string(),
+    ///     None,
+    ///     None,
+    ///     serde_json::json!({}),
+    ///     None,
+    /// )
+    /// .with_origin(EventOrigin::Synthetic);
+    /// assert!(!event.origin.counts_toward_baselines());
+    /// ```
+    pub fn with_origin(mut self, origin: EventOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Create a new event re-injecting this one, linked to it by lineage.
+    ///
+    /// The derived event gets a fresh `event_id` and timestamps, keeps the
+    /// payload, routing fields and correlation ID, and records this event as
+    /// its parent. The session epoch is reset to `0` and the session sequence
+    /// cleared; delivery stamps the current epoch and a new sequence. A derived live delivery becomes a
+    /// [`EventOrigin::Replay`]; other origins are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_core::webhook::{LineageCause, WrappedEvent};
+    ///
+    /// let original = WrappedEvent::new(
+    ///     "github".to_string(),
+    ///     "push".to_string(),
+    ///     None,
+    ///     None,
+    ///     serde_json::json!({}),
+    ///     None,
+    /// );
+    /// let replay = original.derive(LineageCause::Replay);
+    /// let redrive = replay.derive(LineageCause::Redrive);
+    ///
+    /// assert_ne!(replay.event_id, original.event_id);
+    /// assert_eq!(redrive.lineage.unwrap().parent_event_id, replay.event_id);
+    /// assert_eq!(redrive.root_event_id(), original.event_id);
+    /// ```
+    pub fn derive(&self, cause: LineageCause) -> Self {
+        let receipt = MonotonicTimestamp::now();
+        Self {
+            schema_version: SCHEMA_VERSION,
+            event_id: EventId::new(),
+            session_epoch: 0,
+            session_sequence: None,
+            received_at: receipt.wall(),
+            processed_at: receipt.wall(),
+            lineage: Some(EventLineage::derived_from(self, cause)),
+            origin: self.origin.derived(),
+            receipt: Some(receipt),
+            trace_parent: None,
+            ..self.clone()
+        }
+    }
+
+    /// Return a copy of this event received at `receipt`.
+    ///
+    /// Sets `received_at` to the wall-clock part of `receipt` and keeps the
+    /// monotonic part for [`Self::age`].
+    pub fn with_receipt(mut self, receipt: MonotonicTimestamp) -> Self {
+        self.received_at = receipt.wall();
+        self.receipt = Some(receipt);
+        self
+    }
+
+    /// Time since the event was received.
+    ///
+    /// Measured on the monotonic clock when the event was created in this
+    /// process; otherwise estimated from the wall clock and `received_at`.
+    pub fn age(&self) -> Duration {
+        match self.receipt {
+            Some(receipt) => receipt.elapsed(),
+            None => Timestamp::now().duration_since(self.received_at),
+        }
+    }
+
+    /// Original webhook event at the top of this event's lineage.
+    ///
+    /// Returns this event's own ID when it has no lineage.
+    pub fn root_event_id(&self) -> EventId {
+        self.lineage
+            .map(|l| l.root_event_id)
+            .unwrap_or(self.event_id)
+    }
 }
 
 // ============================================================================
@@ -246,6 +521,8 @@ impl WrappedEvent {
 /// }
 /// # }
 /// ```
+// Wrapped is the common output; boxing it would allocate for every event.
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone)]
 pub enum ProcessingOutput {
     /// A provider-agnostic normalised event produced by wrap-mode providers.
@@ -274,6 +551,22 @@ pub enum ProcessingOutput {
         /// queue is configured for this provider.
         target_queue: Option<String>,
     },
+
+    /// A delivery skipped because its delivery ID was already processed.
+    ///
+    /// Produced by processors with deduplication enabled (see
+    /// [`DeduplicationStore`](super::DeduplicationStore)). Nothing is stored
+    /// or delivered for it.
+    AlreadyProcessed {
+        /// Delivery ID shared by the original delivery and this one.
+        delivery_id: String,
+
+        /// Event created from the original delivery.
+        event_id: EventId,
+
+        /// Correlation ID of this delivery's processing.
+        correlation_id: CorrelationId,
+    },
 }
 
 impl ProcessingOutput {
@@ -295,17 +588,18 @@ impl ProcessingOutput {
         match self {
             Self::Wrapped(event) => event.event_id,
             Self::Direct { metadata, .. } => metadata.event_id,
+            Self::AlreadyProcessed { event_id, .. } => *event_id,
         }
     }
 
     /// Returns the target queue name for direct-mode outputs.
     ///
-    /// Returns `None` for wrapped outputs or direct outputs where no target
+    /// Returns `None` for other outputs or direct outputs where no target
     /// queue was configured.
     pub fn direct_target_queue(&self) -> Option<&str> {
         match self {
-            Self::Wrapped(_) => None,
             Self::Direct { target_queue, .. } => target_queue.as_deref(),
+            Self::Wrapped(_) | Self::AlreadyProcessed { .. } => None,
         }
     }
 
@@ -317,39 +611,40 @@ impl ProcessingOutput {
         match self {
             Self::Wrapped(event) => &event.correlation_id,
             Self::Direct { metadata, .. } => &metadata.correlation_id,
+            Self::AlreadyProcessed { correlation_id, .. } => correlation_id,
         }
     }
 
     /// Returns the session ID if this is a [`Wrapped`](Self::Wrapped) output
     /// and the event has an associated session.
     ///
-    /// Returns `None` for direct outputs or when the wrapped event has no
+    /// Returns `None` for other outputs or when the wrapped event has no
     /// session (e.g. providers without ordered-processing support).
     pub fn session_id(&self) -> Option<&SessionId> {
         match self {
             Self::Wrapped(event) => event.session_id.as_ref(),
-            Self::Direct { .. } => None,
+            Self::Direct { .. } | Self::AlreadyProcessed { .. } => None,
         }
     }
 
     /// Returns the event type string if this is a [`Wrapped`](Self::Wrapped) output.
     ///
-    /// Returns `None` for direct outputs.
+    /// Returns `None` for other outputs.
     pub fn event_type(&self) -> Option<&str> {
         match self {
             Self::Wrapped(event) => Some(&event.event_type),
-            Self::Direct { .. } => None,
+            Self::Direct { .. } | Self::AlreadyProcessed { .. } => None,
         }
     }
 
     /// Returns a reference to the inner [`WrappedEvent`] if this is a
     /// [`Wrapped`](Self::Wrapped) output.
     ///
-    /// Returns `None` for direct outputs.
+    /// Returns `None` for other outputs.
     pub fn as_wrapped(&self) -> Option<&WrappedEvent> {
         match self {
             Self::Wrapped(event) => Some(event),
-            Self::Direct { .. } => None,
+            Self::Direct { .. } | Self::AlreadyProcessed { .. } => None,
         }
     }
 }
@@ -421,6 +421,84 @@ mod wrapped_event_tests {
         assert_eq!(deser.session_id, event.session_id);
         assert_eq!(deser.payload, event.payload);
     }
+
+    /// Verify that new events start at session epoch zero.
+    #[test]
+    fn test_new_event_has_zero_session_epoch() {
+        assert_eq!(test_wrapped_event_with_session().session_epoch, 0);
+    }
+
+    /// Verify that envelopes without a `session_epoch` field deserialize with
+    /// epoch zero, so events persisted before epochs existed remain readable.
+    #[test]
+    fn test_missing_session_epoch_defaults_to_zero() {
+        let event = test_wrapped_event_with_session().with_session_epoch(7);
+        let mut value = serde_json::to_value(&event).expect("serialisation should succeed");
+        assert_eq!(value["session_epoch"], 7);
+
+        value.as_object_mut().unwrap().remove("session_epoch");
+        let deser: WrappedEvent =
+            serde_json::from_value(value).expect("deserialisation should succeed");
+        assert_eq!(deser.session_epoch, 0);
+    }
+
+    /// Verify that `with_receipt` sets `received_at` and that the monotonic
+    /// receipt is not serialized.
+    #[test]
+    fn test_receipt_sets_received_at_and_is_not_serialized() {
+        let receipt = MonotonicTimestamp::now();
+        let event = test_wrapped_event().with_receipt(receipt);
+        assert_eq!(event.received_at, receipt.wall());
+        assert_eq!(event.receipt, Some(receipt));
+
+        let json = serde_json::to_value(&event).expect("serialisation should succeed");
+        assert!(json.get("receipt").is_none());
+        let deser: WrappedEvent =
+            serde_json::from_value(json).expect("deserialisation should succeed");
+        assert!(deser.receipt.is_none());
+    }
+
+    /// Verify that `age` falls back to the wall clock without a receipt.
+    #[test]
+    fn test_age_without_receipt_uses_received_at() {
+        let mut event = test_wrapped_event();
+        event.received_at = Timestamp::now().subtract_duration(Duration::from_secs(120));
+
+        assert!(event.age() >= Duration::from_secs(120));
+    }
+
+    /// Verify that live deliveries omit `origin` when serialized and that
+    /// other origins round-trip.
+    #[test]
+    fn test_origin_serialization() {
+        let live = serde_json::to_value(test_wrapped_event()).unwrap();
+        assert!(live.get("origin").is_none());
+        let deser: WrappedEvent = serde_json::from_value(live).unwrap();
+        assert_eq!(deser.origin, EventOrigin::Github);
+
+        let synthetic = test_wrapped_event().with_origin(EventOrigin::Synthetic);
+        let json = serde_json::to_value(&synthetic).unwrap();
+        assert_eq!(json["origin"], "synthetic");
+        let deser: WrappedEvent = serde_json::from_value(json).unwrap();
+        assert_eq!(deser.origin, EventOrigin::Synthetic);
+    }
+
+    /// Verify that deriving a live delivery makes a replay and that injected
+    /// origins survive further derivation.
+    #[test]
+    fn test_derive_sets_origin() {
+        let replay = test_wrapped_event().derive(LineageCause::Replay);
+        assert_eq!(replay.origin, EventOrigin::Replay);
+        assert_eq!(
+            replay.derive(LineageCause::Redrive).origin,
+            EventOrigin::Replay
+        );
+
+        let import = test_wrapped_event()
+            .with_origin(EventOrigin::Import)
+            .derive(LineageCause::Retry);
+        assert_eq!(import.origin, EventOrigin::Import);
+    }
 }
 
 // ============================================================================
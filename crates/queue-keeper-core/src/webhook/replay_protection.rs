@@ -0,0 +1,181 @@
+//! Rejection of replayed webhook deliveries.
+//!
+//! A signed webhook stays valid forever: anyone who captures a delivery can
+//! send it again and its signature still matches. GitHub gives every
+//! delivery a unique `X-GitHub-Delivery` ID, so a delivery ID seen again
+//! within a short window is a replay and is rejected with
+//! [`WebhookError::ReplayedDelivery`](super::WebhookError::ReplayedDelivery).
+//!
+//! Replay protection is off by default. Manual redeliveries from the GitHub
+//! UI reuse the original delivery ID, so the window should be short enough
+//! that an operator redelivering a failed webhook is not rejected.
+//!
+//! A delivery ID is claimed once its signature is valid and released again
+//! if processing then fails, so a redelivery of a failed webhook is
+//! accepted. The IDs are kept in memory: at most `max_tracked` of them, the
+//! oldest forgotten first, and each instance of the service keeps its own.
+//!
+//! A [`ReplayGuard`] is also the in-memory
+//! [`DeduplicationStore`](super::DeduplicationStore): deduplication claims a
+//! delivery ID for the event it will produce and answers a repeated ID with
+//! that event instead of rejecting it.
+
+use crate::{units::human_duration, EventId};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Replay protection settings (the `replay_protection` configuration
+/// section).
+///
+/// ```yaml
+/// replay_protection:
+///   enabled: true
+///   window: 5m
+///   max_tracked: 100000
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReplayProtectionConfig {
+    /// Reject deliveries whose ID was seen within `window`.
+    pub enabled: bool,
+
+    /// How long a delivery ID is remembered.
+    #[serde(with = "human_duration")]
+    pub window: Duration,
+
+    /// Delivery IDs kept in memory; the oldest is forgotten first.
+    pub max_tracked: usize,
+}
+
+impl Default for ReplayProtectionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window: Duration::from_secs(5 * 60),
+            max_tracked: 100_000,
+        }
+    }
+}
+
+impl ReplayProtectionConfig {
+    /// Validate the settings.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.window.is_zero() {
+            return Err("replay_protection.window must not be zero".to_string());
+        }
+        if self.max_tracked == 0 {
+            return Err("replay_protection.max_tracked must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Delivery IDs seen within the replay window.
+#[derive(Debug)]
+pub struct ReplayGuard {
+    window: Duration,
+    max_tracked: usize,
+    seen: Mutex<SeenDeliveries>,
+}
+
+#[derive(Debug, Default)]
+struct SeenDeliveries {
+    /// Delivery ID to the time it was claimed and, for deduplication
+    /// claims, the event it was claimed for.
+    claimed: HashMap<String, (Instant, Option<EventId>)>,
+    /// Claims in the order they were made. Entries of released IDs are
+    /// skipped when they reach the front.
+    order: VecDeque<(String, Instant)>,
+}
+
+impl SeenDeliveries {
+    /// Forget claims made before `cutoff`, and the oldest claims beyond
+    /// `max_tracked`.
+    fn prune(&mut self, cutoff: Option<Instant>, max_tracked: usize) {
+        while let Some((delivery_id, claimed_at)) = self.order.front() {
+            let expired = cutoff.is_some_and(|cutoff| *claimed_at < cutoff);
+            let current = self
+                .claimed
+                .get(delivery_id)
+                .is_some_and(|(at, _)| at == claimed_at);
+            if current && !expired && self.claimed.len() < max_tracked {
+                break;
+            }
+            if current {
+                self.claimed.remove(delivery_id);
+            }
+            self.order.pop_front();
+        }
+    }
+}
+
+impl ReplayGuard {
+    /// Remember delivery IDs for `window`, at most `max_tracked` of them.
+    pub fn new(window: Duration, max_tracked: usize) -> Self {
+        Self {
+            window,
+            max_tracked: max_tracked.max(1),
+            seen: Mutex::new(SeenDeliveries::default()),
+        }
+    }
+
+    /// Build the guard described by `config`, or `None` when replay
+    /// protection is disabled.
+    pub fn from_config(config: &ReplayProtectionConfig) -> Option<Self> {
+        config
+            .enabled
+            .then(|| Self::new(config.window, config.max_tracked))
+    }
+
+    /// Claim `delivery_id`.
+    ///
+    /// Returns `false` when the ID was already claimed within the window,
+    /// i.e. the delivery is a replay.
+    pub fn claim(&self, delivery_id: &str) -> bool {
+        self.claim_for(delivery_id, None).is_ok()
+    }
+
+    /// Claim `delivery_id` for `event_id`.
+    ///
+    /// Returns the event the ID was claimed for earlier when it was already
+    /// claimed within the window.
+    pub(super) fn claim_for(
+        &self,
+        delivery_id: &str,
+        event_id: Option<EventId>,
+    ) -> Result<(), Option<EventId>> {
+        let now = Instant::now();
+        let mut seen = self.seen.lock().unwrap();
+        seen.prune(now.checked_sub(self.window), self.max_tracked);
+        if let Some((_, claimed_for)) = seen.claimed.get(delivery_id) {
+            return Err(*claimed_for);
+        }
+        seen.claimed
+            .insert(delivery_id.to_string(), (now, event_id));
+        seen.order.push_back((delivery_id.to_string(), now));
+        Ok(())
+    }
+
+    /// Release the claim on `delivery_id`, so a redelivery is accepted.
+    pub fn release(&self, delivery_id: &str) {
+        self.seen.lock().unwrap().claimed.remove(delivery_id);
+    }
+
+    /// Number of delivery IDs currently remembered.
+    pub fn len(&self) -> usize {
+        self.seen.lock().unwrap().claimed.len()
+    }
+
+    /// Whether no delivery ID is remembered.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+#[path = "replay_protection_tests.rs"]
+mod tests;
@@ -0,0 +1,77 @@
+//! Tests for replay protection.
+
+use super::*;
+
+/// A delivery ID is rejected when seen again within the window.
+#[test]
+fn test_repeated_delivery_rejected() {
+    let guard = ReplayGuard::new(Duration::from_secs(60), 10);
+
+    assert!(guard.claim("d-1"));
+    assert!(guard.claim("d-2"));
+    assert!(!guard.claim("d-1"));
+    assert_eq!(guard.len(), 2);
+}
+
+/// A delivery ID is accepted again once the window has passed.
+#[test]
+fn test_delivery_accepted_after_window() {
+    let guard = ReplayGuard::new(Duration::from_millis(10), 10);
+
+    assert!(guard.claim("d-1"));
+    std::thread::sleep(Duration::from_millis(20));
+
+    assert!(guard.claim("d-1"));
+    assert_eq!(guard.len(), 1);
+}
+
+/// A released delivery ID is accepted again.
+#[test]
+fn test_released_delivery_accepted() {
+    let guard = ReplayGuard::new(Duration::from_secs(60), 10);
+
+    assert!(guard.claim("d-1"));
+    guard.release("d-1");
+    assert!(guard.is_empty());
+
+    assert!(guard.claim("d-1"));
+    assert!(!guard.claim("d-1"));
+}
+
+/// The oldest delivery ID is forgotten when `max_tracked` is reached.
+#[test]
+fn test_oldest_delivery_forgotten_at_capacity() {
+    let guard = ReplayGuard::new(Duration::from_secs(60), 2);
+
+    assert!(guard.claim("d-1"));
+    assert!(guard.claim("d-2"));
+    assert!(guard.claim("d-3"));
+
+    assert_eq!(guard.len(), 2);
+    assert!(!guard.claim("d-3"));
+    assert!(guard.claim("d-1"));
+}
+
+/// Replay protection is off by default and its settings are validated.
+#[test]
+fn test_config_defaults_and_validation() {
+    let config = ReplayProtectionConfig::default();
+    assert!(config.validate().is_ok());
+    assert!(ReplayGuard::from_config(&config).is_none());
+
+    let config: ReplayProtectionConfig =
+        serde_yaml::from_str("enabled: true\nwindow: 2m\n").unwrap();
+    assert_eq!(config.window, Duration::from_secs(120));
+    assert!(ReplayGuard::from_config(&config).is_some());
+
+    let zero_window = ReplayProtectionConfig {
+        window: Duration::ZERO,
+        ..ReplayProtectionConfig::default()
+    };
+    assert!(zero_window.validate().is_err());
+    let no_capacity = ReplayProtectionConfig {
+        max_tracked: 0,
+        ..ReplayProtectionConfig::default()
+    };
+    assert!(no_capacity.validate().is_err());
+}
@@ -0,0 +1,471 @@
+//! Pluggable webhook sources for forges other than GitHub.
+//!
+//! A [`WebhookSource`] knows one forge's request format: which headers carry
+//! the event type, delivery ID and credential, how the credential is checked
+//! and how a payload maps onto the [`EventEnvelope`] and
+//! [`EventEntity`](super::EventEntity) model GitHub events use.
+//! [`SourceWebhookProvider`] runs a source through the same intake pipeline
+//! as GitHub deliveries — authentication, deduplication, replay protection,
+//! normalization and envelope extensions — so events from every forge reach
+//! bots with session IDs of the same shape.
+//!
+//! Built-in sources:
+//!
+//! | Kind        | Adapter             | Default route        |
+//! |-------------|---------------------|----------------------|
+//! | `gitlab`    | [`GitlabSource`]    | `/webhook/gitlab`    |
+//! | `bitbucket` | [`BitbucketSource`] | `/webhook/bitbucket` |
+//!
+//! Unlike [`GenericWebhookProvider`](super::GenericWebhookProvider), whose
+//! events carry no session, source events are ordered per merge request,
+//! issue, branch or pipeline exactly like GitHub events.
+
+use crate::{
+    webhook::{
+        deduplication, generic_provider::WebhookSecretConfig, BitbucketSource, DeduplicationStore,
+        EnvelopeExtensionRegistry, EventEnvelope, GitlabSource, NormalizationError,
+        ProcessingOutput, ReplayGuard, SignatureValidator, StorageError, StorageReference,
+        ValidationStatus, WebhookError, WebhookHeaders, WebhookProcessor, WebhookRequest,
+        WrappedEvent,
+    },
+    CorrelationId, EventId, PipelineStage, ValidationError,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, info, instrument, warn};
+
+// ============================================================================
+// WebhookSource
+// ============================================================================
+
+/// Request format of one webhook-sending forge.
+///
+/// Implementations are stateless adapters; secrets are resolved by the
+/// [`SourceWebhookProvider`] running them.
+pub trait WebhookSource: Send + Sync {
+    /// Kind of the source, also its default provider ID (e.g. `"gitlab"`).
+    fn kind(&self) -> WebhookSourceKind;
+
+    /// Read the event type, delivery ID and credential from the request
+    /// headers (lowercase names).
+    ///
+    /// The credential — a token or signature — is returned in
+    /// [`WebhookHeaders::signature`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError`] when a header the source always sends is
+    /// missing.
+    fn parse_headers(
+        &self,
+        headers: &HashMap<String, String>,
+    ) -> Result<WebhookHeaders, ValidationError>;
+
+    /// Check the request `credential` against `secret`, in constant time.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ValidationError`] when the credential does not match.
+    fn authenticate(
+        &self,
+        payload: &[u8],
+        credential: &str,
+        secret: &str,
+    ) -> Result<(), ValidationError>;
+
+    /// Normalize `payload` of `event_type` into an event envelope.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NormalizationError`] when the payload does not name the
+    /// repository (project) it belongs to.
+    fn normalize(
+        &self,
+        event_type: &str,
+        payload: serde_json::Value,
+    ) -> Result<EventEnvelope, NormalizationError>;
+}
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// Built-in webhook sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookSourceKind {
+    /// GitLab (gitlab.com and self-managed) project and group webhooks.
+    Gitlab,
+    /// Bitbucket Cloud repository webhooks.
+    Bitbucket,
+}
+
+impl WebhookSourceKind {
+    /// Configuration name of the kind, also its default provider ID.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Gitlab => "gitlab",
+            Self::Bitbucket => "bitbucket",
+        }
+    }
+
+    /// The adapter for this kind.
+    pub fn source(&self) -> Arc<dyn WebhookSource> {
+        match self {
+            Self::Gitlab => Arc::new(GitlabSource),
+            Self::Bitbucket => Arc::new(BitbucketSource),
+        }
+    }
+}
+
+impl std::fmt::Display for WebhookSourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// One webhook source served at `/webhook/{provider_id}`.
+///
+/// # Examples
+///
+/// ```rust
+/// use queue_keeper_core::webhook::{WebhookSourceConfig, WebhookSourceKind};
+///
+/// let config: WebhookSourceConfig = serde_yaml::from_str(
+///     "kind: gitlab\nwebhook_secret:\n  type: environment_variable\n  env_var_name: GITLAB_TOKEN\n",
+/// )
+/// .unwrap();
+/// assert_eq!(config.kind, WebhookSourceKind::Gitlab);
+/// assert_eq!(config.provider_id(), "gitlab");
+/// assert!(config.validate().is_ok());
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookSourceConfig {
+    /// Forge sending the webhooks.
+    pub kind: WebhookSourceKind,
+
+    /// Provider ID, and so the route, of the source. Defaults to the kind
+    /// name, e.g. `gitlab` for `/webhook/gitlab`; set it to serve several
+    /// instances of one forge.
+    #[serde(default)]
+    pub id: Option<String>,
+
+    /// Whether deliveries must be authenticated. When `true`, a
+    /// `webhook_secret` must be configured. Defaults to `true`.
+    #[serde(default = "default_require_signature")]
+    pub require_signature: bool,
+
+    /// Secret the forge authenticates deliveries with: the GitLab secret
+    /// token or the Bitbucket HMAC secret. When `None`, deliveries are not
+    /// authenticated.
+    #[serde(default)]
+    pub webhook_secret: Option<WebhookSecretConfig>,
+}
+
+fn default_require_signature() -> bool {
+    true
+}
+
+impl WebhookSourceConfig {
+    /// Provider ID the source is registered under.
+    pub fn provider_id(&self) -> &str {
+        self.id.as_deref().unwrap_or(self.kind.as_str())
+    }
+
+    /// Check that the provider ID is URL-safe (`[a-z0-9\-_]+`) and that a
+    /// source requiring signatures has a secret.
+    pub fn validate(&self) -> Result<(), String> {
+        let id = self.provider_id();
+        if id.is_empty()
+            || !id
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "webhook source id '{}' must be non-empty lowercase alphanumeric, hyphens, or underscores",
+                id
+            ));
+        }
+        if self.require_signature && self.webhook_secret.is_none() {
+            return Err(format!(
+                "webhook source '{}': require_signature is true but no webhook_secret is configured",
+                id
+            ));
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// SourceWebhookProvider
+// ============================================================================
+
+/// [`WebhookProcessor`] for a [`WebhookSource`].
+///
+/// Processing steps:
+///
+/// 1. Parse the request headers with the source.
+/// 2. Authenticate the delivery when a secret is configured; a delivery
+///    without a credential is rejected.
+/// 3. Skip already processed and replayed delivery IDs.
+/// 4. Normalize the payload and apply envelope extensions.
+///
+/// Payloads are not archived: like generic providers, sources have no blob
+/// storage adapter yet.
+pub struct SourceWebhookProvider {
+    source: Arc<dyn WebhookSource>,
+    provider_id: String,
+    signature_validator: Option<Arc<dyn SignatureValidator>>,
+    extensions: EnvelopeExtensionRegistry,
+    replay_guard: Option<Arc<ReplayGuard>>,
+    deduplication: Option<Arc<dyn DeduplicationStore>>,
+}
+
+impl SourceWebhookProvider {
+    /// Create a provider for `source`, registered under the source kind.
+    ///
+    /// `signature_validator` supplies the secret deliveries are checked
+    /// against; when `None`, deliveries are not authenticated.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_core::webhook::{SourceWebhookProvider, WebhookSourceKind};
+    ///
+    /// let provider = SourceWebhookProvider::new(WebhookSourceKind::Gitlab.source(), None);
+    /// assert_eq!(provider.provider_id(), "gitlab");
+    /// ```
+    pub fn new(
+        source: Arc<dyn WebhookSource>,
+        signature_validator: Option<Arc<dyn SignatureValidator>>,
+    ) -> Self {
+        Self {
+            provider_id: source.kind().as_str().to_string(),
+            source,
+            signature_validator,
+            extensions: EnvelopeExtensionRegistry::new(),
+            replay_guard: None,
+            deduplication: None,
+        }
+    }
+
+    /// Stamp events with `provider_id` instead of the source kind.
+    pub fn with_provider_id(mut self, provider_id: impl Into<String>) -> Self {
+        self.provider_id = provider_id.into();
+        self
+    }
+
+    /// Apply `extensions` to every normalized event.
+    pub fn with_extensions(mut self, extensions: EnvelopeExtensionRegistry) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    /// Reject deliveries whose delivery ID `guard` has already seen.
+    pub fn with_replay_protection(mut self, guard: Arc<ReplayGuard>) -> Self {
+        self.replay_guard = Some(guard);
+        self
+    }
+
+    /// Process each delivery ID at most once, remembering them in `store`.
+    pub fn with_deduplication(mut self, store: Arc<dyn DeduplicationStore>) -> Self {
+        self.deduplication = Some(store);
+        self
+    }
+
+    /// The provider ID events are stamped with.
+    pub fn provider_id(&self) -> &str {
+        &self.provider_id
+    }
+
+    /// Release the deduplication claim on `delivery_id`.
+    async fn release_delivery(&self, delivery_id: &str) {
+        if let Some(store) = &self.deduplication {
+            deduplication::release_delivery(store.as_ref(), delivery_id).await;
+        }
+    }
+}
+
+#[async_trait]
+impl WebhookProcessor for SourceWebhookProvider {
+    #[instrument(skip(self, request), fields(provider = %self.provider_id))]
+    async fn process_webhook(
+        &self,
+        mut request: WebhookRequest,
+    ) -> Result<ProcessingOutput, WebhookError> {
+        // 1. Re-read the headers the HTTP layer parsed GitHub-style
+        let header_parse = std::time::Instant::now();
+        let headers = self.source.parse_headers(&request.raw_headers);
+        request
+            .timings
+            .record(PipelineStage::HeaderParse, header_parse.elapsed());
+        request.headers = headers?;
+
+        // 2. Authenticate the delivery
+        if self.signature_validator.is_some() {
+            let Some(credential) = request.signature() else {
+                return Err(WebhookError::InvalidSignature(format!(
+                    "delivery for provider '{}' carries no credential",
+                    self.provider_id
+                )));
+            };
+            request
+                .timings
+                .time(
+                    PipelineStage::SignatureValidation,
+                    self.validate_signature(&request.body, credential, request.event_type()),
+                )
+                .await
+                .map_err(|e| WebhookError::InvalidSignature(e.to_string()))?;
+        }
+
+        // 3. Skip processed and replayed deliveries
+        let delivery_id = request.delivery_id().to_string();
+        let event_id = EventId::new();
+        let deduplication_claimed = match &self.deduplication {
+            Some(store) => {
+                match deduplication::claim_delivery(store.as_ref(), &delivery_id, event_id).await {
+                    Ok(claimed) => claimed,
+                    Err(original_event_id) => {
+                        info!(
+                            provider = %self.provider_id,
+                            delivery_id = %delivery_id,
+                            original_event_id = %original_event_id,
+                            "Skipped already processed webhook delivery"
+                        );
+                        return Ok(ProcessingOutput::AlreadyProcessed {
+                            delivery_id,
+                            event_id: original_event_id,
+                            correlation_id: CorrelationId::new(),
+                        });
+                    }
+                }
+            }
+            None => false,
+        };
+        if let Some(guard) = &self.replay_guard {
+            if !guard.claim(&delivery_id) {
+                warn!(
+                    provider = %self.provider_id,
+                    delivery_id = %delivery_id,
+                    "Rejected replayed webhook delivery"
+                );
+                if deduplication_claimed {
+                    self.release_delivery(&delivery_id).await;
+                }
+                return Err(WebhookError::ReplayedDelivery { delivery_id });
+            }
+        }
+
+        // 4. Normalize; a failed delivery releases its claims
+        let normalized = request
+            .timings
+            .time(PipelineStage::Normalization, self.normalize_event(&request))
+            .await;
+        let mut event = match normalized {
+            Ok(event) => event,
+            Err(e) => {
+                if let Some(guard) = &self.replay_guard {
+                    guard.release(&delivery_id);
+                }
+                if deduplication_claimed {
+                    self.release_delivery(&delivery_id).await;
+                }
+                return Err(e.into());
+            }
+        };
+        if deduplication_claimed {
+            event.event_id = event_id;
+        }
+        self.extensions.apply(&mut event);
+
+        crate::event_info!(
+            event,
+            provider = %self.provider_id,
+            event_type = %event.event_type,
+            "Successfully processed webhook"
+        );
+
+        Ok(ProcessingOutput::Wrapped(event))
+    }
+
+    async fn validate_signature(
+        &self,
+        payload: &[u8],
+        signature: &str,
+        event_type: &str,
+    ) -> Result<(), ValidationError> {
+        let Some(validator) = &self.signature_validator else {
+            debug!(
+                provider = %self.provider_id,
+                "Webhook authentication skipped - no secret configured"
+            );
+            return Ok(());
+        };
+
+        let secret = validator
+            .get_webhook_secret(event_type)
+            .await
+            .map_err(|e| ValidationError::InvalidFormat {
+                field: "signature".to_string(),
+                message: format!("failed to retrieve webhook secret: {}", e),
+            })?;
+        self.source.authenticate(payload, signature, &secret)?;
+
+        info!(
+            provider = %self.provider_id,
+            event_type = %event_type,
+            "Webhook authenticated successfully"
+        );
+        Ok(())
+    }
+
+    /// Sources do not archive payloads; returns a placeholder reference.
+    async fn store_raw_payload(
+        &self,
+        request: &WebhookRequest,
+        _validation_status: ValidationStatus,
+    ) -> Result<StorageReference, StorageError> {
+        Ok(StorageReference {
+            blob_path: format!("not-stored/{}", request.delivery_id()),
+            stored_at: crate::Timestamp::now(),
+            size_bytes: request.body.len() as u64,
+        })
+    }
+
+    async fn normalize_event(
+        &self,
+        request: &WebhookRequest,
+    ) -> Result<WrappedEvent, NormalizationError> {
+        let payload: serde_json::Value = serde_json::from_slice(&request.body)?;
+        let envelope = self.source.normalize(request.event_type(), payload)?;
+
+        let mut event = WrappedEvent::with_received_at(
+            request.received_at,
+            self.provider_id.clone(),
+            envelope.event_type,
+            envelope.action,
+            Some(envelope.session_id),
+            envelope.payload,
+            request.trace_context.clone(),
+        )
+        .with_receipt(request.receipt)
+        .with_origin(request.origin);
+        event.sender = envelope.sender;
+
+        crate::event_info!(
+            event,
+            provider = %self.provider_id,
+            event_type = %event.event_type,
+            "Event normalized successfully"
+        );
+
+        Ok(event)
+    }
+}
+
+#[cfg(test)]
+#[path = "source_tests.rs"]
+mod tests;
@@ -0,0 +1,190 @@
+//! Tests for webhook sources and their provider.
+
+use super::*;
+use crate::webhook::SecretError;
+use bytes::Bytes;
+use serde_json::json;
+
+/// Validator handing out a fixed secret.
+struct FixedSecret;
+
+#[async_trait]
+impl SignatureValidator for FixedSecret {
+    async fn validate_signature(
+        &self,
+        _payload: &[u8],
+        _signature: &str,
+        _secret_key: &str,
+    ) -> Result<(), ValidationError> {
+        Ok(())
+    }
+
+    async fn get_webhook_secret(&self, _event_type: &str) -> Result<String, SecretError> {
+        Ok("s3cret".to_string())
+    }
+
+    fn supports_constant_time_comparison(&self) -> bool {
+        true
+    }
+}
+
+fn gitlab_request(token: Option<&str>) -> WebhookRequest {
+    let mut raw: HashMap<String, String> = [
+        ("content-type", "application/json"),
+        ("x-gitlab-event", "Merge Request Hook"),
+        (
+            "x-gitlab-event-uuid",
+            "13792a34-cac6-4fda-95a8-c58e00a3954e",
+        ),
+    ]
+    .into_iter()
+    .map(|(name, value)| (name.to_string(), value.to_string()))
+    .collect();
+    if let Some(token) = token {
+        raw.insert("x-gitlab-token".to_string(), token.to_string());
+    }
+    let body = json!({
+        "object_kind": "merge_request",
+        "user": { "id": 7, "username": "alice" },
+        "project": { "id": 15, "name": "widgets", "path_with_namespace": "acme/widgets" },
+        "object_attributes": { "iid": 12, "action": "open" },
+    });
+
+    // The HTTP layer parses non-GitHub requests with the relaxed parser
+    WebhookRequest::with_raw_headers(
+        WebhookHeaders::from_http_headers_relaxed(&raw),
+        raw,
+        Bytes::from(body.to_string()),
+    )
+}
+
+fn gitlab_provider() -> SourceWebhookProvider {
+    SourceWebhookProvider::new(
+        WebhookSourceKind::Gitlab.source(),
+        Some(Arc::new(FixedSecret)),
+    )
+}
+
+/// Authenticated deliveries become wrapped events with a session.
+#[tokio::test]
+async fn test_process_authenticated_delivery() {
+    let output = gitlab_provider()
+        .process_webhook(gitlab_request(Some("s3cret")))
+        .await
+        .unwrap();
+
+    let ProcessingOutput::Wrapped(event) = output else {
+        panic!("expected a wrapped event");
+    };
+    assert_eq!(event.provider, "gitlab");
+    assert_eq!(event.event_type, "merge_request");
+    assert_eq!(event.action.as_deref(), Some("open"));
+    assert_eq!(
+        event.session_id.unwrap().as_str(),
+        "acme/widgets/pull_request/12"
+    );
+    assert_eq!(event.sender.unwrap().login, "alice");
+}
+
+/// With a secret configured, wrong and missing credentials are rejected.
+#[tokio::test]
+async fn test_process_rejects_bad_credentials() {
+    for token in [Some("guess"), None] {
+        let error = gitlab_provider()
+            .process_webhook(gitlab_request(token))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(error, WebhookError::InvalidSignature(_)),
+            "{:?}",
+            error
+        );
+    }
+}
+
+/// Without a secret, deliveries are not authenticated.
+#[tokio::test]
+async fn test_process_without_secret() {
+    let provider = SourceWebhookProvider::new(WebhookSourceKind::Gitlab.source(), None)
+        .with_provider_id("gitlab-eu");
+
+    let output = provider
+        .process_webhook(gitlab_request(None))
+        .await
+        .unwrap();
+    assert_eq!(output.as_wrapped().unwrap().provider, "gitlab-eu");
+}
+
+/// A redelivery of a processed delivery UUID is skipped.
+#[tokio::test]
+async fn test_process_skips_processed_delivery() {
+    let store = Arc::new(ReplayGuard::new(std::time::Duration::from_secs(60), 100));
+    let provider = gitlab_provider().with_deduplication(store);
+
+    let first = provider
+        .process_webhook(gitlab_request(Some("s3cret")))
+        .await
+        .unwrap();
+    let second = provider
+        .process_webhook(gitlab_request(Some("s3cret")))
+        .await
+        .unwrap();
+
+    assert!(matches!(
+        second,
+        ProcessingOutput::AlreadyProcessed { event_id, .. } if event_id == first.event_id()
+    ));
+}
+
+/// Requests without the source's event header are rejected.
+#[tokio::test]
+async fn test_process_requires_source_headers() {
+    let raw = HashMap::from([("content-type".to_string(), "application/json".to_string())]);
+    let request = WebhookRequest::with_raw_headers(
+        WebhookHeaders::from_http_headers_relaxed(&raw),
+        raw,
+        Bytes::from("{}"),
+    );
+
+    let error = SourceWebhookProvider::new(WebhookSourceKind::Bitbucket.source(), None)
+        .process_webhook(request)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, WebhookError::Validation(_)));
+}
+
+/// Sources are registered under their kind unless an ID is configured.
+#[test]
+fn test_config_provider_id() {
+    let config: WebhookSourceConfig =
+        serde_yaml::from_str("kind: bitbucket\nrequire_signature: false").unwrap();
+    assert_eq!(config.provider_id(), "bitbucket");
+    assert!(config.validate().is_ok());
+
+    let config: WebhookSourceConfig =
+        serde_yaml::from_str("kind: gitlab\nid: gitlab-eu\nrequire_signature: false").unwrap();
+    assert_eq!(config.provider_id(), "gitlab-eu");
+
+    let config = WebhookSourceConfig {
+        id: Some("GitLab/EU".to_string()),
+        ..config
+    };
+    assert!(config.validate().is_err());
+}
+
+/// A source requires a secret unless signatures are explicitly optional.
+#[test]
+fn test_config_requires_secret_by_default() {
+    let config: WebhookSourceConfig = serde_yaml::from_str("kind: gitlab").unwrap();
+    assert!(config.require_signature);
+    let error = config.validate().unwrap_err();
+    assert!(error.contains("webhook_secret"), "{}", error);
+
+    let config = WebhookSourceConfig {
+        webhook_secret: Some(WebhookSecretConfig::Literal {
+            value: "token".to_string(),
+        }),
+        ..config
+    };
+    assert!(config.validate().is_ok());
+}
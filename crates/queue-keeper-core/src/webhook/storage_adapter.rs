@@ -4,7 +4,7 @@
 
 use super::{PayloadStorer, StorageError, StorageReference, ValidationStatus, WebhookRequest};
 use crate::blob_storage::{BlobStorage, BlobStorageError, PayloadMetadata, WebhookPayload};
-use crate::Timestamp;
+use crate::{MonotonicTimestamp, StageTimings, Timestamp};
 use async_trait::async_trait;
 use std::collections::HashMap;
 use std::str::FromStr;
@@ -289,13 +289,17 @@ impl PayloadStorer for BlobStorageAdapter {
                 .unwrap_or_else(|| "application/json".to_string()),
         };
 
-        // Reconstruct WebhookRequest (no raw headers available from blob storage)
+        // Reconstruct WebhookRequest (no raw headers available from blob storage).
+        // Processing a stored payload again is a replay, not a live delivery.
         Ok(WebhookRequest {
             headers,
             body: stored.payload.body,
             received_at: stored.payload.metadata.received_at,
+            receipt: MonotonicTimestamp::from_wall(stored.payload.metadata.received_at),
             raw_headers: std::collections::HashMap::new(),
             trace_context: None,
+            timings: StageTimings::new(),
+            origin: super::EventOrigin::Replay,
         })
     }
 
@@ -63,6 +63,9 @@ impl BlobStorage for MockBlobStorage {
             content_type: "application/json".to_string(),
             created_at: Timestamp::now(),
             checksum_sha256: "mock-checksum".to_string(),
+            format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+            content_encoding: crate::blob_storage::ContentEncoding::Identity,
+            encryption: None,
             metadata: payload.metadata.clone(),
         })
     }
@@ -90,6 +93,9 @@ impl BlobStorage for MockBlobStorage {
                     content_type: "application/json".to_string(),
                     created_at: Timestamp::now(),
                     checksum_sha256: "mock-checksum".to_string(),
+                    format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+                    content_encoding: crate::blob_storage::ContentEncoding::Identity,
+                    encryption: None,
                     metadata: payload.metadata.clone(),
                 },
                 payload: payload.clone(),
@@ -118,6 +124,9 @@ impl BlobStorage for MockBlobStorage {
                 content_type: "application/json".to_string(),
                 created_at: Timestamp::now(),
                 checksum_sha256: "mock-checksum".to_string(),
+                format_version: crate::storage_format::CURRENT_FORMAT_VERSION,
+                content_encoding: crate::blob_storage::ContentEncoding::Identity,
+                encryption: None,
                 metadata: payload.metadata.clone(),
             })
             .collect())
@@ -0,0 +1,235 @@
+//! Storage sampling of webhook payloads.
+//!
+//! Storing every payload of high-volume, low-value event types such as
+//! `status` and `check_run` is expensive. Storage sampling rules choose,
+//! per event type, whether a payload is stored:
+//!
+//! - `store_all` — every payload is stored (the default).
+//! - `sample` — `percent` of the payloads are stored.
+//! - `on_failure` — a payload is stored only when its delivery fails.
+//!
+//! Rules are applied at the payload storage stage of the processing
+//! pipeline, and the decision is recorded in the envelope as
+//! [`PayloadStorage`], so replays and redrives can tell that no blob exists
+//! for an event rather than treating the missing blob as an error.
+//!
+//! The sampling decision is derived from the provider's delivery ID, so a
+//! redelivery of the same webhook gets the same decision.
+
+use crate::bot_config::EventTypePattern;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{fmt, str::FromStr};
+
+/// Resolution of sampling percentages: decisions are made in units of
+/// 0.01%.
+const SAMPLE_BUCKETS: u64 = 10_000;
+
+// ============================================================================
+// Configuration
+// ============================================================================
+
+/// How payloads matching a rule are stored.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum StorageSamplingMode {
+    /// Store every payload.
+    StoreAll,
+
+    /// Store `percent` (0–100) of the payloads.
+    Sample { percent: f64 },
+
+    /// Store a payload only when its delivery fails.
+    OnFailure,
+}
+
+/// One storage sampling rule.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageSamplingRule {
+    /// Event type patterns, using the bot subscription syntax (`status`,
+    /// `check_*`, `!check_suite`).
+    pub events: Vec<String>,
+
+    /// How matching payloads are stored.
+    #[serde(flatten)]
+    pub mode: StorageSamplingMode,
+}
+
+/// Storage sampling rules (the `storage_sampling` configuration section).
+///
+/// The first rule matching an event type applies; event types matching no
+/// rule are always stored.
+///
+/// ```yaml
+/// storage_sampling:
+///   rules:
+///     - events: ["status"]
+///       mode: sample
+///       percent: 5
+///     - events: ["check_run", "check_suite"]
+///       mode: on_failure
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageSamplingConfig {
+    pub rules: Vec<StorageSamplingRule>,
+}
+
+impl StorageSamplingConfig {
+    /// Validate the rules.
+    ///
+    /// # Errors
+    /// Returns a description of the first invalid rule.
+    pub fn validate(&self) -> Result<(), String> {
+        StorageSampler::from_config(self).map(|_| ())
+    }
+}
+
+// ============================================================================
+// Decision
+// ============================================================================
+
+/// Whether the payload of an event was written to storage, recorded in the
+/// envelope.
+///
+/// Serialized as `"stored"`, `"sampled_out"` or `"on_failure"`. Envelopes
+/// written before the field existed deserialize as
+/// [`PayloadStorage::Stored`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PayloadStorage {
+    /// The payload was stored.
+    #[default]
+    Stored,
+
+    /// The payload was not stored because it was not sampled.
+    SampledOut,
+
+    /// The payload is stored only if its delivery fails; no blob exists for
+    /// an event that was delivered.
+    OnFailure,
+}
+
+impl PayloadStorage {
+    /// All decisions, in declaration order.
+    pub const ALL: [PayloadStorage; 3] = [
+        PayloadStorage::Stored,
+        PayloadStorage::SampledOut,
+        PayloadStorage::OnFailure,
+    ];
+
+    /// Get string representation
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stored => "stored",
+            Self::SampledOut => "sampled_out",
+            Self::OnFailure => "on_failure",
+        }
+    }
+
+    /// Whether the payload was stored when the event was received.
+    pub fn is_stored(&self) -> bool {
+        matches!(self, Self::Stored)
+    }
+}
+
+impl fmt::Display for PayloadStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+// ============================================================================
+// Sampler
+// ============================================================================
+
+/// Applies storage sampling rules to incoming webhooks.
+///
+/// An empty sampler (the default) stores every payload.
+#[derive(Debug, Clone, Default)]
+pub struct StorageSampler {
+    rules: Vec<(Vec<EventTypePattern>, StorageSamplingMode)>,
+}
+
+impl StorageSampler {
+    /// Build the sampler from its configuration.
+    ///
+    /// # Errors
+    /// Returns a description of the first rule without events, with an
+    /// invalid event pattern or with a percentage outside 0–100.
+    pub fn from_config(config: &StorageSamplingConfig) -> Result<Self, String> {
+        let mut rules = Vec::with_capacity(config.rules.len());
+        for (index, rule) in config.rules.iter().enumerate() {
+            let invalid =
+                |message: String| format!("storage_sampling.rules[{}]: {}", index, message);
+            if rule.events.is_empty() {
+                return Err(invalid("events must list at least one pattern".to_string()));
+            }
+            if let StorageSamplingMode::Sample { percent } = rule.mode {
+                if !(0.0..=100.0).contains(&percent) {
+                    return Err(invalid(format!(
+                        "percent must be between 0 and 100 (got {})",
+                        percent
+                    )));
+                }
+            }
+            let patterns = rule
+                .events
+                .iter()
+                .map(|pattern| EventTypePattern::from_str(pattern).map_err(|e| e.to_string()))
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(invalid)?;
+            rules.push((patterns, rule.mode.clone()));
+        }
+        Ok(Self { rules })
+    }
+
+    /// Whether no rule is configured.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Decide whether the payload of webhook `delivery_id` of type
+    /// `event_type` is stored.
+    pub fn decide(&self, event_type: &str, delivery_id: &str) -> PayloadStorage {
+        let Some((_, mode)) = self
+            .rules
+            .iter()
+            .find(|(patterns, _)| rule_matches(patterns, event_type))
+        else {
+            return PayloadStorage::Stored;
+        };
+        match mode {
+            StorageSamplingMode::StoreAll => PayloadStorage::Stored,
+            StorageSamplingMode::OnFailure => PayloadStorage::OnFailure,
+            StorageSamplingMode::Sample { percent } => {
+                let threshold = (percent * (SAMPLE_BUCKETS as f64 / 100.0)).round() as u64;
+                if sample_bucket(delivery_id) < threshold {
+                    PayloadStorage::Stored
+                } else {
+                    PayloadStorage::SampledOut
+                }
+            }
+        }
+    }
+}
+
+/// Whether `event_type` matches `patterns` and none of their exclusions.
+fn rule_matches(patterns: &[EventTypePattern], event_type: &str) -> bool {
+    let excluded = patterns.iter().any(
+        |pattern| matches!(pattern, EventTypePattern::Exclude(excluded) if excluded == event_type),
+    );
+    !excluded && patterns.iter().any(|pattern| pattern.matches(event_type))
+}
+
+/// Stable bucket in `0..SAMPLE_BUCKETS` for `key`.
+fn sample_bucket(key: &str) -> u64 {
+    let digest = Sha256::digest(key.as_bytes());
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(prefix) % SAMPLE_BUCKETS
+}
+
+#[cfg(test)]
+#[path = "storage_sampling_tests.rs"]
+mod tests;
@@ -0,0 +1,130 @@
+//! Tests for storage sampling.
+
+use super::*;
+
+fn sampler(yaml: &str) -> StorageSampler {
+    let config: StorageSamplingConfig = serde_yaml::from_str(yaml).unwrap();
+    StorageSampler::from_config(&config).unwrap()
+}
+
+/// Rules parse from YAML and the first matching rule applies.
+#[test]
+fn test_first_matching_rule_applies() {
+    let sampler = sampler(
+        r#"
+rules:
+  - events: ["check_suite"]
+    mode: store_all
+  - events: ["check_*"]
+    mode: on_failure
+  - events: ["status"]
+    mode: sample
+    percent: 0
+"#,
+    );
+
+    assert_eq!(sampler.decide("check_suite", "d-1"), PayloadStorage::Stored);
+    assert_eq!(
+        sampler.decide("check_run", "d-1"),
+        PayloadStorage::OnFailure
+    );
+    assert_eq!(sampler.decide("status", "d-1"), PayloadStorage::SampledOut);
+    assert_eq!(sampler.decide("push", "d-1"), PayloadStorage::Stored);
+}
+
+/// Exclusions keep event types out of a broader rule.
+#[test]
+fn test_exclusions_skip_rule() {
+    let sampler = sampler(
+        r#"
+rules:
+  - events: ["*", "!pull_request"]
+    mode: on_failure
+"#,
+    );
+
+    assert_eq!(sampler.decide("push", "d-1"), PayloadStorage::OnFailure);
+    assert_eq!(
+        sampler.decide("pull_request", "d-1"),
+        PayloadStorage::Stored
+    );
+}
+
+/// Sampling keeps roughly the configured share, and the same delivery
+/// always gets the same decision.
+#[test]
+fn test_sampling_rate_and_stability() {
+    let sampler = sampler(
+        r#"
+rules:
+  - events: ["status"]
+    mode: sample
+    percent: 10
+"#,
+    );
+
+    let stored = (0..10_000)
+        .filter(|i| sampler.decide("status", &format!("delivery-{}", i)) == PayloadStorage::Stored)
+        .count();
+    assert!((800..1200).contains(&stored), "stored {} of 10000", stored);
+
+    for i in 0..100 {
+        let delivery_id = format!("delivery-{}", i);
+        assert_eq!(
+            sampler.decide("status", &delivery_id),
+            sampler.decide("status", &delivery_id)
+        );
+    }
+}
+
+/// Sampling everything or nothing is exact.
+#[test]
+fn test_sampling_bounds() {
+    let all = sampler("rules: [{ events: [status], mode: sample, percent: 100 }]");
+    let none = sampler("rules: [{ events: [status], mode: sample, percent: 0 }]");
+
+    for i in 0..1000 {
+        let delivery_id = format!("delivery-{}", i);
+        assert_eq!(all.decide("status", &delivery_id), PayloadStorage::Stored);
+        assert_eq!(
+            none.decide("status", &delivery_id),
+            PayloadStorage::SampledOut
+        );
+    }
+}
+
+/// Invalid rules are rejected with their index.
+#[test]
+fn test_invalid_rules_rejected() {
+    assert!(StorageSamplingConfig::default().validate().is_ok());
+    assert!(StorageSampler::default().is_empty());
+
+    for (yaml, expected) in [
+        ("rules: [{ events: [], mode: store_all }]", "rules[0]"),
+        (
+            "rules: [{ events: [status], mode: sample, percent: 150 }]",
+            "percent",
+        ),
+        ("rules: [{ events: [''], mode: on_failure }]", "rules[0]"),
+    ] {
+        let config: StorageSamplingConfig = serde_yaml::from_str(yaml).unwrap();
+        let error = config.validate().unwrap_err();
+        assert!(error.contains(expected), "{}: {}", yaml, error);
+    }
+}
+
+/// Decisions serialize as snake_case strings and default to stored.
+#[test]
+fn test_payload_storage_serialization() {
+    for storage in PayloadStorage::ALL {
+        let json = serde_json::to_string(&storage).unwrap();
+        assert_eq!(json, format!("\"{}\"", storage));
+        assert_eq!(
+            serde_json::from_str::<PayloadStorage>(&json).unwrap(),
+            storage
+        );
+    }
+    assert_eq!(PayloadStorage::default(), PayloadStorage::Stored);
+    assert!(PayloadStorage::Stored.is_stored());
+    assert!(!PayloadStorage::OnFailure.is_stored());
+}
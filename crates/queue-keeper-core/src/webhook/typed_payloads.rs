@@ -0,0 +1,531 @@
+//! Typed models of common GitHub webhook payloads.
+//!
+//! Enabled by the `typed-payloads` feature. [`TypedEvent::from_payload`]
+//! (and [`EventEnvelope::typed`] / [`WrappedEvent::typed`]) deserializes
+//! the payload of `pull_request`, `issues`, `push` and `check_run` events
+//! into the structs below:
+//!
+//! - Only the fields bots commonly read are modelled. Every other field is
+//!   kept in the `extra` map of the struct it appears on, so nothing in the
+//!   payload is lost and new fields added by GitHub never break parsing.
+//! - Payloads of other event types, and payloads missing a required field
+//!   or holding a field of the wrong type, fall back to
+//!   [`TypedEvent::Raw`]. Use [`TypedEvent::parse`] to see why a payload
+//!   did not match its model.
+//!
+//! Timestamps are kept as the strings GitHub sends.
+
+use crate::webhook::{EventEnvelope, WrappedEvent};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// A webhook payload deserialized into its typed model.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedEvent {
+    /// A `pull_request` event.
+    PullRequest(Box<PullRequestEvent>),
+
+    /// An `issues` event.
+    Issues(Box<IssuesEvent>),
+
+    /// A `push` event.
+    Push(Box<PushEvent>),
+
+    /// A `check_run` event.
+    CheckRun(Box<CheckRunEvent>),
+
+    /// The payload as JSON: an event type without a typed model, or a
+    /// payload that does not match its model.
+    Raw(Value),
+}
+
+impl TypedEvent {
+    /// Deserialize `payload` of `event_type`, falling back to
+    /// [`TypedEvent::Raw`] when it cannot be.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use queue_keeper_core::webhook::typed_payloads::TypedEvent;
+    ///
+    /// let payload = serde_json::json!({ "zen": "Keep it logically awesome." });
+    /// assert!(TypedEvent::from_payload("ping", &payload).is_raw());
+    /// assert!(TypedEvent::from_payload("push", &payload).is_raw());
+    /// ```
+    pub fn from_payload(event_type: &str, payload: &Value) -> Self {
+        Self::parse(event_type, payload).unwrap_or_else(|_| Self::Raw(payload.clone()))
+    }
+
+    /// Deserialize `payload` of `event_type`.
+    ///
+    /// Event types without a typed model give [`TypedEvent::Raw`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the deserialization error when the payload of a typed event
+    /// type lacks a required field or holds a field of the wrong type.
+    pub fn parse(event_type: &str, payload: &Value) -> Result<Self, serde_json::Error> {
+        Ok(match event_type {
+            "pull_request" => Self::PullRequest(deserialize(payload)?),
+            "issues" => Self::Issues(deserialize(payload)?),
+            "push" => Self::Push(deserialize(payload)?),
+            "check_run" => Self::CheckRun(deserialize(payload)?),
+            _ => Self::Raw(payload.clone()),
+        })
+    }
+
+    /// Whether the payload was not deserialized into a typed model.
+    pub fn is_raw(&self) -> bool {
+        matches!(self, Self::Raw(_))
+    }
+}
+
+fn deserialize<T: DeserializeOwned>(payload: &Value) -> Result<Box<T>, serde_json::Error> {
+    T::deserialize(payload).map(Box::new)
+}
+
+impl EventEnvelope {
+    /// The payload as a [`TypedEvent`].
+    ///
+    /// Requires the `typed-payloads` feature.
+    pub fn typed(&self) -> TypedEvent {
+        TypedEvent::from_payload(&self.event_type, &self.payload)
+    }
+}
+
+impl WrappedEvent {
+    /// The payload as a [`TypedEvent`].
+    ///
+    /// The models follow GitHub's payloads; events of other providers
+    /// normally give [`TypedEvent::Raw`]. Requires the `typed-payloads`
+    /// feature.
+    pub fn typed(&self) -> TypedEvent {
+        TypedEvent::from_payload(&self.event_type, &self.payload)
+    }
+}
+
+// ============================================================================
+// Events
+// ============================================================================
+
+/// Payload of a `pull_request` event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PullRequestEvent {
+    /// What happened, e.g. `opened`, `synchronize` or `closed`.
+    pub action: String,
+
+    /// The pull request number.
+    pub number: u64,
+
+    /// The pull request after the action.
+    pub pull_request: PullRequest,
+
+    /// The repository of the pull request.
+    pub repository: RepositoryInfo,
+
+    /// The account that performed the action.
+    #[serde(default)]
+    pub sender: Option<Account>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Payload of an `issues` event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IssuesEvent {
+    /// What happened, e.g. `opened`, `labeled` or `closed`.
+    pub action: String,
+
+    /// The issue after the action.
+    pub issue: Issue,
+
+    /// The repository of the issue.
+    pub repository: RepositoryInfo,
+
+    /// The account that performed the action.
+    #[serde(default)]
+    pub sender: Option<Account>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// Payload of a `push` event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushEvent {
+    /// The full ref pushed, e.g. `refs/heads/main` or `refs/tags/v1.0`.
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+
+    /// SHA of the ref before the push; all zeros when it was created.
+    pub before: String,
+
+    /// SHA of the ref after the push; all zeros when it was deleted.
+    pub after: String,
+
+    /// Whether the push created the ref.
+    #[serde(default)]
+    pub created: bool,
+
+    /// Whether the push deleted the ref.
+    #[serde(default)]
+    pub deleted: bool,
+
+    /// Whether the push was a force push.
+    #[serde(default)]
+    pub forced: bool,
+
+    /// Pushed commits, oldest first (at most 20).
+    #[serde(default)]
+    pub commits: Vec<PushCommit>,
+
+    /// The most recent commit on the ref after the push; `None` when the
+    /// ref was deleted.
+    #[serde(default)]
+    pub head_commit: Option<PushCommit>,
+
+    /// The repository pushed to.
+    pub repository: RepositoryInfo,
+
+    /// Git identity of the pusher.
+    #[serde(default)]
+    pub pusher: Option<GitActor>,
+
+    /// The account that pushed.
+    #[serde(default)]
+    pub sender: Option<Account>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl PushEvent {
+    /// Branch name when a branch was pushed.
+    pub fn branch(&self) -> Option<&str> {
+        self.git_ref.strip_prefix("refs/heads/")
+    }
+
+    /// Tag name when a tag was pushed.
+    pub fn tag(&self) -> Option<&str> {
+        self.git_ref.strip_prefix("refs/tags/")
+    }
+}
+
+/// Payload of a `check_run` event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckRunEvent {
+    /// What happened, e.g. `created`, `completed` or `rerequested`.
+    pub action: String,
+
+    /// The check run after the action.
+    pub check_run: CheckRun,
+
+    /// The repository of the checked commit.
+    pub repository: RepositoryInfo,
+
+    /// The account that performed the action.
+    #[serde(default)]
+    pub sender: Option<Account>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+// ============================================================================
+// Objects
+// ============================================================================
+
+/// A GitHub account: user, bot or organization.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Account {
+    /// Stable account ID.
+    pub id: u64,
+
+    /// Current login.
+    pub login: String,
+
+    /// `User`, `Bot` or `Organization`.
+    #[serde(rename = "type", default)]
+    pub account_type: Option<String>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A repository as embedded in webhook payloads.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RepositoryInfo {
+    /// Stable repository ID.
+    pub id: u64,
+
+    /// Repository name without owner.
+    pub name: String,
+
+    /// `owner/name`.
+    pub full_name: String,
+
+    /// Whether the repository is private.
+    #[serde(default)]
+    pub private: bool,
+
+    /// The owning account.
+    pub owner: Account,
+
+    /// The default branch, e.g. `main`.
+    #[serde(default)]
+    pub default_branch: Option<String>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A label on a pull request or issue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Label {
+    /// Label name.
+    pub name: String,
+
+    /// Hex colour without `#`.
+    #[serde(default)]
+    pub color: Option<String>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A pull request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PullRequest {
+    /// Stable pull request ID.
+    pub id: u64,
+
+    /// Number within the repository.
+    pub number: u64,
+
+    /// `open` or `closed`.
+    pub state: String,
+
+    /// Title.
+    pub title: String,
+
+    /// Description; `None` when empty.
+    #[serde(default)]
+    pub body: Option<String>,
+
+    /// Whether the pull request is a draft.
+    #[serde(default)]
+    pub draft: bool,
+
+    /// Whether the pull request was merged; absent from some payloads.
+    #[serde(default)]
+    pub merged: Option<bool>,
+
+    /// The author.
+    #[serde(default)]
+    pub user: Option<Account>,
+
+    /// Labels.
+    #[serde(default)]
+    pub labels: Vec<Label>,
+
+    /// The branch the changes come from.
+    pub head: BranchRef,
+
+    /// The branch the changes would be merged into.
+    pub base: BranchRef,
+
+    /// Web URL.
+    #[serde(default)]
+    pub html_url: Option<String>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The head or base of a pull request.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BranchRef {
+    /// Branch name, e.g. `main`.
+    #[serde(rename = "ref")]
+    pub git_ref: String,
+
+    /// Commit SHA.
+    pub sha: String,
+
+    /// `owner:branch`.
+    #[serde(default)]
+    pub label: Option<String>,
+
+    /// Repository holding the branch; `None` when a fork was deleted.
+    #[serde(default)]
+    pub repo: Option<RepositoryInfo>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// An issue.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Issue {
+    /// Stable issue ID.
+    pub id: u64,
+
+    /// Number within the repository.
+    pub number: u64,
+
+    /// `open` or `closed`.
+    pub state: String,
+
+    /// Title.
+    pub title: String,
+
+    /// Description; `None` when empty.
+    #[serde(default)]
+    pub body: Option<String>,
+
+    /// The author.
+    #[serde(default)]
+    pub user: Option<Account>,
+
+    /// Labels.
+    #[serde(default)]
+    pub labels: Vec<Label>,
+
+    /// Web URL.
+    #[serde(default)]
+    pub html_url: Option<String>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A commit in a `push` event.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PushCommit {
+    /// Commit SHA.
+    pub id: String,
+
+    /// Commit message.
+    pub message: String,
+
+    /// Commit time as sent, e.g. `2019-05-15T15:20:30-07:00`.
+    #[serde(default)]
+    pub timestamp: Option<String>,
+
+    /// Git author.
+    pub author: GitActor,
+
+    /// Git committer.
+    #[serde(default)]
+    pub committer: Option<GitActor>,
+
+    /// Paths added by the commit.
+    #[serde(default)]
+    pub added: Vec<String>,
+
+    /// Paths removed by the commit.
+    #[serde(default)]
+    pub removed: Vec<String>,
+
+    /// Paths modified by the commit.
+    #[serde(default)]
+    pub modified: Vec<String>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A git identity: commit author, committer or pusher.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GitActor {
+    /// Name.
+    pub name: String,
+
+    /// Email address; absent for some pushers.
+    #[serde(default)]
+    pub email: Option<String>,
+
+    /// GitHub login, when the email belongs to an account.
+    #[serde(default)]
+    pub username: Option<String>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// A check run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckRun {
+    /// Stable check run ID.
+    pub id: u64,
+
+    /// Check name.
+    pub name: String,
+
+    /// SHA of the checked commit.
+    pub head_sha: String,
+
+    /// `queued`, `in_progress`, `completed`, `waiting`, `requested` or
+    /// `pending`.
+    pub status: String,
+
+    /// Outcome once completed, e.g. `success` or `failure`.
+    #[serde(default)]
+    pub conclusion: Option<String>,
+
+    /// Start time as sent.
+    #[serde(default)]
+    pub started_at: Option<String>,
+
+    /// Completion time as sent.
+    #[serde(default)]
+    pub completed_at: Option<String>,
+
+    /// URL of the integrator's details page.
+    #[serde(default)]
+    pub details_url: Option<String>,
+
+    /// The suite the run belongs to.
+    #[serde(default)]
+    pub check_suite: Option<CheckSuiteRef>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+/// The check suite of a check run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckSuiteRef {
+    /// Stable check suite ID.
+    pub id: u64,
+
+    /// Branch of the checked commit; `None` for commits not on a branch.
+    #[serde(default)]
+    pub head_branch: Option<String>,
+
+    /// SHA of the checked commit.
+    #[serde(default)]
+    pub head_sha: Option<String>,
+
+    /// Fields not modelled above.
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+#[cfg(test)]
+#[path = "typed_payloads_tests.rs"]
+mod tests;
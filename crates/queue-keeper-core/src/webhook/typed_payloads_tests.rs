@@ -0,0 +1,139 @@
+//! Tests for the typed payload models.
+
+use super::*;
+use serde_json::json;
+
+fn fixture(json: &str) -> Value {
+    serde_json::from_str(json).unwrap()
+}
+
+/// Pull request payloads expose the pull request, its branches and labels.
+#[test]
+fn test_pull_request_event() {
+    let payload = fixture(include_str!("fixtures/pull_request.json"));
+
+    let TypedEvent::PullRequest(event) = TypedEvent::from_payload("pull_request", &payload) else {
+        panic!("expected a pull request event");
+    };
+    assert_eq!(event.action, "opened");
+    assert_eq!(event.number, 2);
+    let pr = &event.pull_request;
+    assert_eq!(pr.title, "Update the README with new information.");
+    assert!(!pr.draft);
+    assert_eq!(pr.merged, Some(false));
+    assert_eq!(pr.head.git_ref, "changes");
+    assert_eq!(pr.base.git_ref, "master");
+    assert_eq!(pr.labels[0].name, "bug");
+    assert_eq!(event.repository.full_name, "Codertocat/Hello-World");
+    assert_eq!(event.sender.as_ref().unwrap().login, "Codertocat");
+
+    // Fields without a model are kept
+    assert_eq!(pr.extra["changed_files"], json!(1));
+}
+
+/// Issue payloads expose the issue; unmodelled top-level fields are kept.
+#[test]
+fn test_issues_event() {
+    let payload = fixture(include_str!("fixtures/issues.json"));
+
+    let TypedEvent::Issues(event) = TypedEvent::from_payload("issues", &payload) else {
+        panic!("expected an issues event");
+    };
+    assert_eq!(event.action, "edited");
+    assert_eq!(event.issue.number, 1);
+    assert_eq!(event.issue.state, "open");
+    assert_eq!(event.issue.labels[0].color.as_deref(), Some("d73a4a"));
+    assert!(event.extra.contains_key("changes"));
+}
+
+/// Push payloads expose the ref and commits.
+#[test]
+fn test_push_event() {
+    let payload = fixture(include_str!("fixtures/push.json"));
+
+    let TypedEvent::Push(event) = TypedEvent::from_payload("push", &payload) else {
+        panic!("expected a push event");
+    };
+    assert_eq!(event.branch(), Some("main"));
+    assert_eq!(event.tag(), None);
+    assert_eq!(event.commits.len(), 1);
+    assert_eq!(event.commits[0].modified, vec!["README.md".to_string()]);
+    assert_eq!(
+        event
+            .head_commit
+            .as_ref()
+            .unwrap()
+            .author
+            .username
+            .as_deref(),
+        Some("Codertocat")
+    );
+    assert_eq!(event.pusher.as_ref().unwrap().name, "Codertocat");
+}
+
+/// Check run payloads expose the run and its suite.
+#[test]
+fn test_check_run_event() {
+    let payload = fixture(include_str!("fixtures/check_run.json"));
+
+    let TypedEvent::CheckRun(event) = TypedEvent::from_payload("check_run", &payload) else {
+        panic!("expected a check run event");
+    };
+    assert_eq!(event.check_run.id, 128620228);
+    assert_eq!(event.check_run.status, "completed");
+    assert_eq!(event.check_run.conclusion.as_deref(), Some("success"));
+    assert_eq!(event.check_run.check_suite.as_ref().unwrap().id, 118578147);
+}
+
+/// Serializing a typed event gives back the payload it was read from.
+#[test]
+fn test_round_trip_keeps_unmodelled_fields() {
+    let payload = fixture(include_str!("fixtures/pull_request.json"));
+
+    let TypedEvent::PullRequest(event) = TypedEvent::parse("pull_request", &payload).unwrap()
+    else {
+        panic!("expected a pull request event");
+    };
+    assert_eq!(serde_json::to_value(&event).unwrap(), payload);
+}
+
+/// Payloads that do not match their model, and event types without a
+/// model, fall back to the raw payload.
+#[test]
+fn test_fallback_to_raw() {
+    let mut payload = fixture(include_str!("fixtures/pull_request.json"));
+    payload["pull_request"]["number"] = json!("two");
+
+    assert!(TypedEvent::parse("pull_request", &payload).is_err());
+    assert_eq!(
+        TypedEvent::from_payload("pull_request", &payload),
+        TypedEvent::Raw(payload.clone())
+    );
+
+    let deployment = fixture(include_str!("fixtures/deployment.json"));
+    assert!(TypedEvent::parse("deployment", &deployment)
+        .unwrap()
+        .is_raw());
+}
+
+/// Envelopes and wrapped events type their own payload.
+#[test]
+fn test_typed_from_events() {
+    let payload = fixture(include_str!("fixtures/issues.json"));
+
+    let event = WrappedEvent::new(
+        "github".to_string(),
+        "issues".to_string(),
+        Some("edited".to_string()),
+        None,
+        payload,
+        None,
+    );
+    assert!(matches!(event.typed(), TypedEvent::Issues(_)));
+
+    let event = WrappedEvent {
+        event_type: "issue_comment".to_string(),
+        ..event
+    };
+    assert!(event.typed().is_raw());
+}
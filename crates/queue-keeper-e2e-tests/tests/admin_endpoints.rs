@@ -82,6 +82,19 @@ async fn test_set_log_level() {
     let level: serde_json::Value = response.json().await.expect("Failed to parse JSON");
 
     assert_eq!(level["level"], "debug");
+
+    let response = client
+        .get(server.url("/admin/logging/level"))
+        .send()
+        .await
+        .expect("Failed to send request");
+    let level: serde_json::Value = response.json().await.expect("Failed to parse JSON");
+
+    assert_eq!(level["level"], "debug");
+    assert!(level["filter"]
+        .as_str()
+        .expect("filter should be a string")
+        .contains("queue_keeper_api=debug"));
 }
 
 /// Verify that PUT /admin/logging/level rejects invalid levels
@@ -149,6 +162,33 @@ async fn test_set_trace_sampling() {
     assert_eq!(sampling["sampling_ratio"], 0.5);
 }
 
+/// Verify that a sampling ratio set at runtime is reported afterwards
+#[tokio::test]
+async fn test_set_trace_sampling_persists() {
+    // Arrange
+    let server = TestContainer::start().await;
+    let client = http_client();
+
+    // Act
+    client
+        .put(server.url("/admin/tracing/sampling"))
+        .json(&json!({"sampling_ratio": 0.25}))
+        .send()
+        .await
+        .expect("Failed to send request");
+    let response = client
+        .get(server.url("/admin/tracing/sampling"))
+        .send()
+        .await
+        .expect("Failed to send request");
+
+    // Assert
+    let sampling: serde_json::Value = response.json().await.expect("Failed to parse JSON");
+
+    assert_eq!(sampling["sampling_ratio"], 0.25);
+    assert_eq!(sampling["export_enabled"], false);
+}
+
 /// Verify that PUT /admin/tracing/sampling rejects invalid ratios
 #[tokio::test]
 async fn test_set_invalid_trace_sampling() {
@@ -211,25 +251,27 @@ async fn test_replay_event() {
     assert!(response.status().is_success() || response.status() == 404);
 }
 
-/// Verify that POST /admin/sessions/:id/reset returns proper response
+/// Verify that POST /admin/sessions/:id/reset bumps the session epoch
 #[tokio::test]
-#[ignore = "Requires session reset implementation"]
 async fn test_reset_session() {
     // Arrange
     let server = TestContainer::start().await;
     let client = http_client();
-    let session_id = "pr-123";
+    let session_id = "owner%2Frepo%2Fpull_request%2F123";
 
     // Act
     let response = client
         .post(server.url(&format!("/admin/sessions/{}/reset", session_id)))
+        .json(&json!({"reason": "e2e test"}))
         .send()
         .await
         .expect("Failed to send request");
 
     // Assert
-    // Should return 200 if session exists, 404 if not
-    assert!(response.status().is_success() || response.status() == 404);
+    assert!(response.status().is_success());
+    let body: serde_json::Value = response.json().await.expect("Invalid JSON");
+    assert_eq!(body["status"], "reset");
+    assert_eq!(body["session_epoch"], 1);
 }
 
 /// Verify that admin endpoints require authentication when an admin API key
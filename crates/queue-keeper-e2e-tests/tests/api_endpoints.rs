@@ -34,8 +34,7 @@ async fn test_list_events() {
     // Verify response structure
     assert!(events.get("events").is_some());
     assert!(events.get("total").is_some());
-    assert!(events.get("page").is_some());
-    assert!(events.get("per_page").is_some());
+    assert!(events.get("limit").is_some());
 }
 
 /// Verify that GET /api/events supports pagination
@@ -46,9 +45,9 @@ async fn test_list_events_pagination() {
     let server = TestContainer::start().await;
     let client = http_client();
 
-    // Act - Request page 2 with 10 items per page
+    // Act - Request 10 items per page without a total count
     let response = client
-        .get(server.url("/api/events?page=2&per_page=10"))
+        .get(server.url("/api/events?limit=10&include_total=false"))
         .send()
         .await
         .expect("Failed to send request");
@@ -58,8 +57,18 @@ async fn test_list_events_pagination() {
 
     let events: serde_json::Value = response.json().await.expect("Failed to parse JSON");
 
-    assert_eq!(events["page"], 2);
-    assert_eq!(events["per_page"], 10);
+    assert_eq!(events["limit"], 10);
+    assert!(events.get("total").is_none());
+
+    // Act - Follow the cursor to the next page, if any
+    if let Some(cursor) = events["next_cursor"].as_str() {
+        let response = client
+            .get(server.url(&format!("/api/events?limit=10&cursor={}", cursor)))
+            .send()
+            .await
+            .expect("Failed to send request");
+        assert_eq!(response.status(), 200);
+    }
 }
 
 /// Verify that GET /api/events supports filtering
@@ -199,7 +208,6 @@ async fn test_get_session_details() {
 
 /// Verify that GET /api/stats returns system statistics
 #[tokio::test]
-#[ignore = "Requires statistics implementation"]
 async fn test_get_statistics() {
     // Arrange
     let server = TestContainer::start().await;
@@ -284,7 +292,7 @@ async fn test_api_invalid_query_parameters() {
 
     // Act - Invalid pagination parameters
     let response = client
-        .get(server.url("/api/events?page=-1&per_page=999999"))
+        .get(server.url("/api/events?cursor=-1&limit=999999"))
         .send()
         .await
         .expect("Failed to send request");
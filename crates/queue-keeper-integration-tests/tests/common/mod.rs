@@ -255,6 +255,9 @@ impl BlobStorage for MockBlobStorage {
             created_at: Timestamp::now(),
             content_type: "application/json".to_string(),
             checksum_sha256: "mock-checksum-sha256".to_string(),
+            format_version: queue_keeper_core::storage_format::CURRENT_FORMAT_VERSION,
+            content_encoding: queue_keeper_core::blob_storage::ContentEncoding::Identity,
+            encryption: None,
             metadata: queue_keeper_core::blob_storage::PayloadMetadata {
                 event_id: *event_id,
                 event_type: payload.metadata.event_type.clone(),
@@ -282,6 +285,9 @@ impl BlobStorage for MockBlobStorage {
                     created_at: Timestamp::now(),
                     content_type: "application/json".to_string(),
                     checksum_sha256: "mock-checksum-sha256".to_string(),
+                    format_version: queue_keeper_core::storage_format::CURRENT_FORMAT_VERSION,
+                    content_encoding: queue_keeper_core::blob_storage::ContentEncoding::Identity,
+                    encryption: None,
                     metadata: queue_keeper_core::blob_storage::PayloadMetadata {
                         event_id: *id,
                         event_type: payload.metadata.event_type.clone(),
@@ -341,6 +347,11 @@ pub fn create_test_bot_config(count: usize) -> BotConfiguration {
             ordered: false,
             repository_filter: None,
             config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
         })
         .collect();
 
@@ -502,6 +513,7 @@ impl HealthChecker for MockHealthChecker {
             "service".to_string(),
             queue_keeper_api::HealthCheckResult {
                 healthy,
+                warning: false,
                 duration_ms: 0,
                 message: "Mock health check".to_string(),
             },
@@ -568,14 +580,25 @@ impl EventStore for MockEventStore {
         params: queue_keeper_api::EventListParams,
     ) -> Result<queue_keeper_api::EventListResponse, QueueKeeperError> {
         let events = self.events.lock().unwrap();
-        let per_page = params.per_page.unwrap_or(100);
-        let page = params.page.unwrap_or(1);
-        let offset = (page - 1) * per_page;
+        let limit = params.limit.unwrap_or(100);
+        let cursor = params
+            .cursor
+            .as_deref()
+            .map(queue_keeper_api::PageCursor::decode)
+            .transpose()?;
+
+        let mut newest_first: Vec<&WrappedEvent> = events.iter().collect();
+        newest_first.sort_by(|a, b| b.event_id.cmp(&a.event_id));
+        let remaining: Vec<&WrappedEvent> = newest_first
+            .into_iter()
+            .filter(|e| cursor.is_none_or(|c| e.event_id < c.event_id()))
+            .collect();
 
-        let items: Vec<queue_keeper_api::EventSummary> = events
+        let next_cursor = (remaining.len() > limit)
+            .then(|| queue_keeper_api::PageCursor::after(remaining[limit - 1].event_id).encode());
+        let items: Vec<queue_keeper_api::EventSummary> = remaining
             .iter()
-            .skip(offset)
-            .take(per_page)
+            .take(limit)
             .map(|e| queue_keeper_api::EventSummary {
                 event_id: e.event_id,
                 event_type: e.event_type.clone(),
@@ -597,9 +620,9 @@ impl EventStore for MockEventStore {
 
         Ok(queue_keeper_api::EventListResponse {
             events: items,
-            total: events.len(),
-            page,
-            per_page,
+            next_cursor,
+            limit,
+            total: params.include_total.unwrap_or(true).then_some(events.len()),
         })
     }
 
@@ -609,7 +632,8 @@ impl EventStore for MockEventStore {
     ) -> Result<queue_keeper_api::SessionListResponse, QueueKeeperError> {
         Ok(queue_keeper_api::SessionListResponse {
             sessions: vec![],
-            total: 0,
+            next_cursor: None,
+            total: Some(0),
         })
     }
 
@@ -631,6 +655,8 @@ impl EventStore for MockEventStore {
             active_sessions: 0,
             error_rate: 0.0,
             uptime_seconds: 0,
+            events_by_type: Default::default(),
+            events_by_origin: Default::default(),
         })
     }
 }
@@ -687,10 +713,12 @@ pub fn create_test_app_state_with_providers(
         std::collections::HashSet::new(),
         None, // queue_client: disabled in unit/integration tests
         Arc::new(queue_keeper_core::queue_integration::DefaultEventRouter::new()),
-        Arc::new(queue_keeper_core::bot_config::BotConfiguration {
-            bots: vec![],
-            settings: queue_keeper_core::bot_config::BotConfigurationSettings::default(),
-        }),
+        Arc::new(queue_keeper_core::bot_config::SharedBotConfiguration::new(
+            queue_keeper_core::bot_config::BotConfiguration {
+                bots: vec![],
+                settings: queue_keeper_core::bot_config::BotConfigurationSettings::default(),
+            },
+        )),
         queue_keeper_api::queue_delivery::QueueDeliveryConfig::default(),
         None, // ip_rate_limiter: disabled in unit/integration tests
         None, // admin_api_key: no auth in unit/integration tests
@@ -3,6 +3,7 @@
 mod common;
 
 use queue_keeper_api::{ServerConfig, ServiceConfig, WebhookConfig};
+use queue_keeper_core::units::{ByteSize, HumanDuration};
 
 /// Verify that ServiceConfig has proper defaults
 #[test]
@@ -21,9 +22,9 @@ fn test_server_config_defaults() {
 
     assert_eq!(config.host, "0.0.0.0");
     assert_eq!(config.port, 8080);
-    assert_eq!(config.timeout_seconds, 30);
-    assert_eq!(config.shutdown_timeout_seconds, 30);
-    assert!(config.max_body_size > 0);
+    assert_eq!(config.timeout, HumanDuration::from_secs(30));
+    assert_eq!(config.shutdown_timeout, HumanDuration::from_secs(30));
+    assert!(config.max_body_size.as_u64() > 0);
     assert!(config.enable_cors);
     assert!(config.enable_compression);
 }
@@ -32,11 +33,11 @@ fn test_server_config_defaults() {
 #[test]
 fn test_custom_shutdown_timeout() {
     let config = ServerConfig {
-        shutdown_timeout_seconds: 60,
+        shutdown_timeout: HumanDuration::from_secs(60),
         ..Default::default()
     };
 
-    assert_eq!(config.shutdown_timeout_seconds, 60);
+    assert_eq!(config.shutdown_timeout.as_secs(), 60);
 }
 
 /// Verify that webhook endpoint path can be customized
@@ -66,8 +67,8 @@ fn test_signature_validation_can_be_disabled() {
 fn test_server_config_includes_timeouts() {
     let config = ServerConfig::default();
 
-    assert!(config.timeout_seconds > 0);
-    assert!(config.shutdown_timeout_seconds > 0);
+    assert!(!config.timeout.is_zero());
+    assert!(!config.shutdown_timeout.is_zero());
 }
 
 /// Verify that max body size is reasonable
@@ -76,8 +77,8 @@ fn test_max_body_size_is_reasonable() {
     let config = ServerConfig::default();
 
     // Should be at least 1MB for webhook payloads
-    assert!(config.max_body_size >= 1024 * 1024);
+    assert!(config.max_body_size >= ByteSize::mib(1));
 
     // Should not be too large (prevent memory exhaustion)
-    assert!(config.max_body_size <= 100 * 1024 * 1024);
+    assert!(config.max_body_size <= ByteSize::mib(100));
 }
@@ -0,0 +1,192 @@
+//! Integration tests for the event replay admin endpoint
+//!
+//! These tests verify that `POST /admin/events/{event_id}/replay`:
+//! - re-delivers a stored event to every matching bot queue as a new event
+//!   linked to the original by lineage
+//! - restricts delivery to a target bot
+//! - reports routing decisions without delivering in a dry run
+
+mod common;
+
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use common::{
+    create_test_bot_config, MockBlobStorage, MockEventStore, MockHealthChecker, MockQueueClient,
+};
+use queue_keeper_api::{
+    queue_delivery::QueueDeliveryConfig, store_wrapped_event_to_blob, AppState, ProviderRegistry,
+    ServiceConfig, ServiceMetrics, TelemetryConfig,
+};
+use queue_keeper_core::{
+    blob_storage::BlobStorage, bot_config::SharedBotConfiguration,
+    queue_integration::DefaultEventRouter, webhook::WrappedEvent, EventId, SessionId,
+};
+use std::sync::Arc;
+use tower::ServiceExt;
+
+/// App state delivering to two bots through `queue_client`, with `event`
+/// stored in the event blob store.
+async fn replay_state(queue_client: Arc<MockQueueClient>, event: &WrappedEvent) -> AppState {
+    let storage = Arc::new(MockBlobStorage::new());
+    store_wrapped_event_to_blob(storage.as_ref(), event)
+        .await
+        .unwrap();
+
+    AppState::new(
+        ServiceConfig::default(),
+        Arc::new(ProviderRegistry::new()),
+        Arc::new(MockHealthChecker::new()),
+        Arc::new(MockEventStore::new()),
+        Arc::new(ServiceMetrics::default()),
+        Arc::new(TelemetryConfig::default()),
+        std::collections::HashSet::new(),
+        Some(queue_client),
+        Arc::new(DefaultEventRouter::new()),
+        Arc::new(SharedBotConfiguration::new(create_test_bot_config(2))),
+        QueueDeliveryConfig::default(),
+        None,
+        None,
+        Some(storage as Arc<dyn BlobStorage>),
+    )
+}
+
+fn stored_event() -> WrappedEvent {
+    WrappedEvent::new(
+        "github".to_string(),
+        "pull_request".to_string(),
+        Some("opened".to_string()),
+        Some(SessionId::from_parts("owner", "repo", "pull_request", "7")),
+        serde_json::json!({ "action": "opened", "number": 7 }),
+        None,
+    )
+}
+
+/// POST a replay request and return the status and JSON body.
+async fn replay(
+    state: AppState,
+    event_id: EventId,
+    body: serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("/admin/events/{}/replay", event_id))
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap();
+    let response = queue_keeper_api::create_router(state)
+        .oneshot(request)
+        .await
+        .unwrap();
+    let status = response.status();
+    let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+        .await
+        .unwrap();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+/// Verify that a replay delivers a new event, linked to the stored one, to
+/// every matching bot queue.
+#[tokio::test]
+async fn test_replay_delivers_derived_event_to_all_bots() {
+    // Arrange
+    let event = stored_event();
+    let queue_client = Arc::new(MockQueueClient::new());
+    let state = replay_state(queue_client.clone(), &event).await;
+
+    // Act
+    let (status, body) = replay(state, event.event_id, serde_json::json!({})).await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "replayed");
+    assert_eq!(body["routes"].as_array().unwrap().len(), 2);
+    let replay_event_id = body["replay_event_id"].as_str().unwrap();
+    assert_ne!(replay_event_id, event.event_id.to_string());
+    assert_eq!(queue_client.send_count(), 2);
+
+    for (_, message) in queue_client.sent_messages() {
+        let delivered: WrappedEvent = serde_json::from_slice(&message.body).unwrap();
+        assert_eq!(delivered.event_id.to_string(), replay_event_id);
+        assert_eq!(
+            delivered.lineage.unwrap().parent_event_id,
+            event.event_id,
+            "replay must be linked to the stored event"
+        );
+    }
+}
+
+/// Verify that a replay can be restricted to one bot.
+#[tokio::test]
+async fn test_replay_to_target_bot() {
+    // Arrange
+    let event = stored_event();
+    let queue_client = Arc::new(MockQueueClient::new());
+    let state = replay_state(queue_client.clone(), &event).await;
+
+    // Act
+    let (status, body) = replay(
+        state,
+        event.event_id,
+        serde_json::json!({ "target_bot": "test-bot-2", "reason": "consumer fix" }),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["routes"][0]["bot_name"], "test-bot-2");
+    assert_eq!(body["routes"][0]["queue"], "queue-keeper-test-bot-2");
+    let sent = queue_client.sent_messages();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].0.as_str(), "queue-keeper-test-bot-2");
+}
+
+/// Verify that a dry run reports the routing decisions without delivering.
+#[tokio::test]
+async fn test_replay_dry_run_does_not_deliver() {
+    // Arrange
+    let event = stored_event();
+    let queue_client = Arc::new(MockQueueClient::new());
+    let state = replay_state(queue_client.clone(), &event).await;
+
+    // Act
+    let (status, body) = replay(
+        state,
+        event.event_id,
+        serde_json::json!({ "dry_run": true }),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(status, StatusCode::OK, "{}", body);
+    assert_eq!(body["status"], "dry_run");
+    assert_eq!(body["dry_run"], true);
+    assert_eq!(body["routes"].as_array().unwrap().len(), 2);
+    assert!(body.get("replay_event_id").is_none());
+    assert_eq!(queue_client.send_count(), 0);
+}
+
+/// Verify that unknown events and unknown target bots are rejected.
+#[tokio::test]
+async fn test_replay_rejects_unknown_event_and_bot() {
+    let event = stored_event();
+    let queue_client = Arc::new(MockQueueClient::new());
+
+    let (status, body) = replay(
+        replay_state(queue_client.clone(), &event).await,
+        EventId::new(),
+        serde_json::json!({}),
+    )
+    .await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body["error"], "event_not_found");
+
+    let (status, body) = replay(
+        replay_state(queue_client.clone(), &event).await,
+        event.event_id,
+        serde_json::json!({ "target_bot": "missing-bot" }),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["error"], "invalid_replay_request");
+    assert_eq!(queue_client.send_count(), 0);
+}
@@ -1,16 +1,39 @@
 //! Integration tests for HTTP middleware (logging, metrics, tracing,
-//! IP rate limiting, admin authentication)
+//! IP rate limiting, admin authentication and authorization)
 
 mod common;
 
 use axum::body::Body;
+use axum::extract::ConnectInfo;
 use axum::http::{Request, StatusCode};
 use common::create_test_app_state;
-use queue_keeper_api::{middleware::IpFailureTracker, AppState};
+use queue_keeper_api::{
+    middleware::IpFailureTracker, AdminPrincipalConfig, AdminRole, AppState, RequestRateLimiter,
+};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use tower::ServiceExt;
 
+/// `state` trusting the client addresses forwarded by proxies in 10.0.0.0/8.
+fn behind_proxy(mut state: AppState) -> AppState {
+    state.config.security.trusted_proxies = vec!["10.0.0.0/8".parse().unwrap()];
+    state
+}
+
+/// `request` as received over a connection from `peer`.
+fn from_peer(peer: &str, mut request: Request<Body>) -> Request<Body> {
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(peer.parse::<SocketAddr>().unwrap()));
+    request
+}
+
+/// `request` as received from a trusted proxy.
+fn from_proxy(request: Request<Body>) -> Request<Body> {
+    from_peer("10.0.0.1:443", request)
+}
+
 /// Verify that request logging middleware processes requests
 #[tokio::test]
 async fn test_request_logging_middleware_processes_requests() {
@@ -211,7 +234,7 @@ async fn test_ip_rate_limit_blocks_ip_at_threshold() {
     for _ in 0..3 {
         tracker.record_failure("203.0.113.10");
     }
-    let mut state = create_test_app_state();
+    let mut state = behind_proxy(create_test_app_state());
     state.ip_rate_limiter = Some(tracker);
     let app = queue_keeper_api::create_router(state);
 
@@ -227,7 +250,7 @@ async fn test_ip_rate_limit_blocks_ip_at_threshold() {
         .unwrap();
 
     // Act
-    let response = app.oneshot(request).await.unwrap();
+    let response = app.oneshot(from_proxy(request)).await.unwrap();
 
     // Assert: IP is blocked — 429 Too Many Requests
     assert_eq!(
@@ -251,7 +274,7 @@ async fn test_ip_rate_limit_response_includes_rate_limit_headers() {
     for _ in 0..5 {
         tracker.record_failure("203.0.113.11");
     }
-    let mut state = create_test_app_state();
+    let mut state = behind_proxy(create_test_app_state());
     state.ip_rate_limiter = Some(tracker);
     let app = queue_keeper_api::create_router(state);
 
@@ -265,7 +288,7 @@ async fn test_ip_rate_limit_response_includes_rate_limit_headers() {
         .body(Body::from("{}"))
         .unwrap();
 
-    let response = app.oneshot(request).await.unwrap();
+    let response = app.oneshot(from_proxy(request)).await.unwrap();
 
     assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
 
@@ -300,6 +323,109 @@ async fn test_ip_rate_limit_response_includes_rate_limit_headers() {
     );
 }
 
+// ============================================================================
+// Request rate limiting integration tests
+// ============================================================================
+
+fn api_request(client_ip: &str) -> Request<Body> {
+    from_proxy(
+        Request::builder()
+            .uri("/api/sessions")
+            .header("x-forwarded-for", client_ip)
+            .body(Body::empty())
+            .unwrap(),
+    )
+}
+
+/// Verify that an IP over its request limit receives HTTP 429 with a
+/// Retry-After header and is counted in the metrics, while other IPs are
+/// still served.
+#[tokio::test]
+async fn test_request_rate_limit_rejects_ip_over_limit() {
+    let state = behind_proxy(create_test_app_state())
+        .with_request_rate_limiter(Arc::new(RequestRateLimiter::new(1_000, Some(2))));
+    let metrics = state.metrics.clone();
+    let app = queue_keeper_api::create_router(state);
+
+    for _ in 0..2 {
+        let response = app
+            .clone()
+            .oneshot(api_request("203.0.113.20"))
+            .await
+            .unwrap();
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    let response = app
+        .clone()
+        .oneshot(api_request("203.0.113.20"))
+        .await
+        .unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = response
+        .headers()
+        .get("retry-after")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .expect("Retry-After must be a numeric value");
+    assert!(retry_after > 0 && retry_after <= 30, "got {}", retry_after);
+    assert_eq!(
+        metrics
+            .rate_limited_requests_total
+            .with_label_values(&["ip"])
+            .get(),
+        1
+    );
+
+    let response = app.oneshot(api_request("203.0.113.21")).await.unwrap();
+    assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+/// Verify that a client that is not a trusted proxy is limited by its own
+/// address, whatever it claims in X-Forwarded-For.
+#[tokio::test]
+async fn test_request_rate_limit_ignores_untrusted_forwarded_for() {
+    let state = behind_proxy(create_test_app_state())
+        .with_request_rate_limiter(Arc::new(RequestRateLimiter::new(1_000, Some(2))));
+    let app = queue_keeper_api::create_router(state);
+
+    let spoofed = |client_ip: &str| {
+        from_peer(
+            "198.51.100.9:50000",
+            Request::builder()
+                .uri("/api/sessions")
+                .header("x-forwarded-for", client_ip)
+                .body(Body::empty())
+                .unwrap(),
+        )
+    };
+    for client_ip in ["203.0.113.30", "203.0.113.31"] {
+        let response = app.clone().oneshot(spoofed(client_ip)).await.unwrap();
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    let response = app.oneshot(spoofed("203.0.113.32")).await.unwrap();
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+}
+
+/// Verify that health endpoints are not subject to request rate limits.
+#[tokio::test]
+async fn test_request_rate_limit_skips_health_endpoints() {
+    let state = create_test_app_state()
+        .with_request_rate_limiter(Arc::new(RequestRateLimiter::new(1, Some(1))));
+    let app = queue_keeper_api::create_router(state);
+
+    for _ in 0..3 {
+        let request = Request::builder()
+            .uri("/health/live")
+            .header("x-forwarded-for", "203.0.113.22")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        assert_ne!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    }
+}
+
 // ============================================================================
 // Admin authentication integration tests
 // ============================================================================
@@ -452,12 +578,12 @@ async fn test_admin_auth_failures_trigger_rate_limiting() {
         .body(Body::empty())
         .unwrap();
 
-    let mut state = create_test_app_state();
+    let mut state = behind_proxy(create_test_app_state());
     state.admin_api_key = Some("real-key".to_string());
-    state.ip_rate_limiter = Some(Arc::clone(&tracker));
+    state.ip_rate_limiter = Some(tracker.clone());
     let app = queue_keeper_api::create_router(state);
 
-    let response = app.oneshot(blocked_request).await.unwrap();
+    let response = app.oneshot(from_proxy(blocked_request)).await.unwrap();
 
     assert_eq!(
         response.status(),
@@ -465,3 +591,154 @@ async fn test_admin_auth_failures_trigger_rate_limiting() {
         "Admin requests from a rate-limited IP must receive 429 even with correct key"
     );
 }
+
+// ============================================================================
+// Admin role-based authorization integration tests
+// ============================================================================
+
+/// Helper: build an AppState with a single configured admin principal.
+fn state_with_principal(api_key: &str, roles: Vec<AdminRole>) -> AppState {
+    let mut state = create_test_app_state();
+    state.config.security.admin_principals = vec![AdminPrincipalConfig {
+        name: "test-principal".to_string(),
+        api_key: api_key.to_string(),
+        roles,
+    }];
+    state
+}
+
+/// Verify that a viewer principal can use read-only admin endpoints.
+#[tokio::test]
+async fn test_viewer_principal_allowed_read_api() {
+    // Arrange
+    let app = queue_keeper_api::create_router(state_with_principal(
+        "viewer-key",
+        vec![AdminRole::Viewer],
+    ));
+
+    let request = Request::builder()
+        .uri("/admin/config")
+        .header("Authorization", "Bearer viewer-key")
+        .body(Body::empty())
+        .unwrap();
+
+    // Act
+    let response = app.oneshot(request).await.unwrap();
+
+    // Assert
+    assert!(
+        response.status().is_success(),
+        "Viewer must be able to read admin config, got {}",
+        response.status()
+    );
+}
+
+/// Verify that a viewer principal is forbidden from changing configuration.
+#[tokio::test]
+async fn test_viewer_principal_forbidden_config_change() {
+    // Arrange
+    let app = queue_keeper_api::create_router(state_with_principal(
+        "viewer-key",
+        vec![AdminRole::Viewer],
+    ));
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/admin/metrics/reset")
+        .header("Authorization", "Bearer viewer-key")
+        .body(Body::empty())
+        .unwrap();
+
+    // Act
+    let response = app.oneshot(request).await.unwrap();
+
+    // Assert
+    assert_eq!(
+        response.status(),
+        StatusCode::FORBIDDEN,
+        "Viewer must not be able to change configuration"
+    );
+}
+
+/// Verify that an operator principal passes authorization for replay.
+#[tokio::test]
+async fn test_operator_principal_not_forbidden_replay() {
+    // Arrange
+    let app = queue_keeper_api::create_router(state_with_principal(
+        "operator-key",
+        vec![AdminRole::Operator],
+    ));
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/admin/events/01HZZZZZZZZZZZZZZZZZZZZZZZ/replay")
+        .header("Authorization", "Bearer operator-key")
+        .body(Body::empty())
+        .unwrap();
+
+    // Act
+    let response = app.oneshot(request).await.unwrap();
+
+    // Assert: the replay fails without event storage, but auth must pass
+    assert_ne!(response.status(), StatusCode::UNAUTHORIZED);
+    assert_ne!(response.status(), StatusCode::FORBIDDEN);
+}
+
+/// Build an event deletion request authenticated with the given key.
+fn delete_event_request(api_key: &str) -> Request<Body> {
+    Request::builder()
+        .method("DELETE")
+        .uri("/admin/events/01HZZZZZZZZZZZZZZZZZZZZZZZ")
+        .header("Authorization", format!("Bearer {}", api_key))
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"reason":"test"}"#))
+        .unwrap()
+}
+
+/// Verify that only the security role may delete events.
+#[tokio::test]
+async fn test_event_delete_role_matrix() {
+    for (role, permitted) in [
+        (AdminRole::Viewer, false),
+        (AdminRole::Operator, false),
+        (AdminRole::Admin, false),
+        (AdminRole::Security, true),
+    ] {
+        // Arrange
+        let app = queue_keeper_api::create_router(state_with_principal("role-key", vec![role]));
+
+        // Act
+        let response = app.oneshot(delete_event_request("role-key")).await.unwrap();
+
+        // Assert: the deletion fails without event storage, but auth decides 403
+        assert_eq!(
+            response.status() == StatusCode::FORBIDDEN,
+            !permitted,
+            "role {} got {}",
+            role,
+            response.status()
+        );
+    }
+}
+
+/// Verify that an unknown key is rejected when only principals are configured.
+#[tokio::test]
+async fn test_unknown_key_rejected_with_principals_configured() {
+    // Arrange
+    let app = queue_keeper_api::create_router(state_with_principal(
+        "viewer-key",
+        vec![AdminRole::Viewer],
+    ));
+
+    let request = Request::builder()
+        .uri("/admin/config")
+        .header("Authorization", "Bearer not-a-key")
+        .body(Body::empty())
+        .unwrap();
+
+    // Act
+    let response = app.oneshot(request).await.unwrap();
+
+    // Assert
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+}
@@ -0,0 +1,504 @@
+//! Queue provider conformance suite
+//!
+//! Queue-Keeper relies on a small set of guarantees from every queue
+//! provider. These checks run against any [`QueueClient`] and assert:
+//! - Per-session FIFO ordering, independent across sessions (Assertion #7)
+//! - At-least-once delivery: abandoned messages are redelivered with an
+//!   increased delivery count (Assertion #10)
+//! - Dead-lettered messages leave the queue and do not block the session
+//!   (Assertion #9)
+//! - Session lock renewal extends the lock without losing messages
+//! - Deterministic replay ordering: replayed events follow the original
+//!   events on their session, in the original order
+//!
+//! The in-memory provider runs on every build. AWS SQS FIFO and Azure
+//! Service Bus run nightly against real queues (see
+//! `.github/workflows/queue-conformance.yml`) and are `#[ignore]`d
+//! otherwise; they skip when their environment variables are unset:
+//!
+//! | Provider | Variables |
+//! |----------|-----------|
+//! | AWS SQS FIFO | `QK_CONFORMANCE_SQS_REGION`, `QK_CONFORMANCE_SQS_QUEUE` |
+//! | Azure Service Bus | `QK_CONFORMANCE_SERVICE_BUS_CONNECTION_STRING`, `QK_CONFORMANCE_SERVICE_BUS_QUEUE` |
+//!
+//! Every check uses fresh session IDs, so runs against shared queues do not
+//! see each other's messages. A new provider is added with one
+//! `conformance_suite!` invocation.
+
+use bytes::Bytes;
+use chrono::Duration;
+use queue_keeper_core::{
+    bot_config::{
+        BotConfiguration, BotConfigurationSettings, BotSpecificConfig, BotSubscription,
+        EventTypePattern,
+    },
+    queue_integration::{DefaultEventRouter, EventRouter},
+    webhook::{LineageCause, WrappedEvent},
+    BotName, EventId,
+};
+use queue_runtime::{
+    InMemoryConfig, InMemoryProvider, Message, QueueClient, QueueConfig, QueueName,
+    ReceivedMessage, SessionClient, SessionId, StandardQueueClient,
+};
+use std::sync::Arc;
+
+// ============================================================================
+// Targets
+// ============================================================================
+
+/// A queue the suite runs against.
+struct ConformanceTarget {
+    /// Provider name used in assertion messages.
+    name: &'static str,
+    client: Arc<dyn QueueClient>,
+    queue: QueueName,
+    /// How long to wait for a message that is expected to arrive.
+    receive_timeout: Duration,
+    /// How long to wait before concluding that no message will arrive.
+    empty_timeout: Duration,
+    /// Whether the session client reports the new expiry after a renewal.
+    /// The in-memory client keeps the expiry it was accepted with.
+    reports_renewed_expiry: bool,
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+async fn in_memory_target() -> Option<ConformanceTarget> {
+    let provider = InMemoryProvider::new(InMemoryConfig::default());
+    Some(ConformanceTarget {
+        name: "in-memory",
+        client: Arc::new(StandardQueueClient::new(
+            Box::new(provider),
+            QueueConfig::default(),
+        )),
+        queue: QueueName::new("queue-keeper-conformance".to_string()).unwrap(),
+        receive_timeout: Duration::seconds(1),
+        empty_timeout: Duration::milliseconds(100),
+        reports_renewed_expiry: false,
+    })
+}
+
+async fn sqs_fifo_target() -> Option<ConformanceTarget> {
+    use queue_runtime::{AwsSqsConfig, ProviderConfig, QueueClientFactory};
+
+    let (Some(region), Some(queue)) = (
+        env("QK_CONFORMANCE_SQS_REGION"),
+        env("QK_CONFORMANCE_SQS_QUEUE"),
+    ) else {
+        eprintln!("Skipping SQS FIFO conformance: QK_CONFORMANCE_SQS_* not set");
+        return None;
+    };
+
+    let config = QueueConfig {
+        provider: ProviderConfig::AwsSqs(AwsSqsConfig {
+            region,
+            access_key_id: None,
+            secret_access_key: None,
+            use_fifo_queues: true,
+        }),
+        ..QueueConfig::default()
+    };
+    let client = QueueClientFactory::create_client(config)
+        .await
+        .expect("Failed to connect to AWS SQS");
+
+    Some(ConformanceTarget {
+        name: "aws-sqs-fifo",
+        client: Arc::from(client),
+        queue: QueueName::new(queue).expect("Invalid QK_CONFORMANCE_SQS_QUEUE"),
+        receive_timeout: Duration::seconds(20),
+        empty_timeout: Duration::seconds(5),
+        reports_renewed_expiry: true,
+    })
+}
+
+async fn service_bus_target() -> Option<ConformanceTarget> {
+    use queue_runtime::{
+        providers::{AzureAuthMethod, AzureServiceBusProvider},
+        AzureServiceBusConfig,
+    };
+
+    let (Some(connection_string), Some(queue)) = (
+        env("QK_CONFORMANCE_SERVICE_BUS_CONNECTION_STRING"),
+        env("QK_CONFORMANCE_SERVICE_BUS_QUEUE"),
+    ) else {
+        eprintln!("Skipping Service Bus conformance: QK_CONFORMANCE_SERVICE_BUS_* not set");
+        return None;
+    };
+
+    let provider = AzureServiceBusProvider::new(AzureServiceBusConfig {
+        connection_string: Some(connection_string),
+        namespace: None,
+        auth_method: AzureAuthMethod::ConnectionString,
+        use_sessions: true,
+        session_timeout: Duration::minutes(5),
+    })
+    .await
+    .expect("Failed to connect to Azure Service Bus");
+
+    Some(ConformanceTarget {
+        name: "azure-service-bus",
+        client: Arc::new(StandardQueueClient::new(
+            Box::new(provider),
+            QueueConfig::default(),
+        )),
+        queue: QueueName::new(queue).expect("Invalid QK_CONFORMANCE_SERVICE_BUS_QUEUE"),
+        receive_timeout: Duration::seconds(20),
+        empty_timeout: Duration::seconds(5),
+        reports_renewed_expiry: true,
+    })
+}
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// A session ID no other run uses.
+fn unique_session(label: &str) -> SessionId {
+    SessionId::new(format!("conformance/{}/{}", label, uuid::Uuid::new_v4())).unwrap()
+}
+
+async fn send(target: &ConformanceTarget, session: &SessionId, body: &str) {
+    let message = Message::new(Bytes::from(body.to_string())).with_session_id(session.clone());
+    target
+        .client
+        .send_message(&target.queue, message)
+        .await
+        .unwrap_or_else(|e| panic!("[{}] send failed: {}", target.name, e));
+}
+
+async fn accept(target: &ConformanceTarget, session: &SessionId) -> Box<dyn SessionClient> {
+    target
+        .client
+        .accept_session(&target.queue, Some(session.clone()))
+        .await
+        .unwrap_or_else(|e| panic!("[{}] accept_session failed: {}", target.name, e))
+}
+
+async fn receive(target: &ConformanceTarget, session: &dyn SessionClient) -> ReceivedMessage {
+    session
+        .receive_message(target.receive_timeout)
+        .await
+        .unwrap_or_else(|e| panic!("[{}] receive failed: {}", target.name, e))
+        .unwrap_or_else(|| panic!("[{}] expected a message, got none", target.name))
+}
+
+async fn assert_empty(target: &ConformanceTarget, session: &dyn SessionClient, context: &str) {
+    let received = session
+        .receive_message(target.empty_timeout)
+        .await
+        .unwrap_or_else(|e| panic!("[{}] receive failed: {}", target.name, e));
+    assert!(
+        received.is_none(),
+        "[{}] {}: unexpected message {:?}",
+        target.name,
+        context,
+        received.map(|m| body(&m))
+    );
+}
+
+fn body(message: &ReceivedMessage) -> String {
+    String::from_utf8(message.body.to_vec()).unwrap()
+}
+
+// ============================================================================
+// Checks
+// ============================================================================
+
+/// Messages are received in send order within each session, even when
+/// sends to several sessions interleave.
+async fn check_session_ordering(target: &ConformanceTarget) {
+    let sessions: Vec<SessionId> = (0..3).map(|_| unique_session("ordering")).collect();
+    for seq in 0..5 {
+        for session in &sessions {
+            send(target, session, &format!("{}", seq)).await;
+        }
+    }
+
+    for session in &sessions {
+        let client = accept(target, session).await;
+        for expected in 0..5 {
+            let message = receive(target, client.as_ref()).await;
+            assert_eq!(
+                body(&message),
+                expected.to_string(),
+                "[{}] session {} delivered out of order",
+                target.name,
+                session.as_str()
+            );
+            client
+                .complete_message(message.receipt_handle)
+                .await
+                .unwrap();
+        }
+        assert_empty(target, client.as_ref(), "after draining session").await;
+        client.close_session().await.unwrap();
+    }
+}
+
+/// An abandoned message is redelivered, ahead of later messages on its
+/// session, with a higher delivery count.
+async fn check_at_least_once_delivery(target: &ConformanceTarget) {
+    let session = unique_session("redelivery");
+    send(target, &session, "first").await;
+    send(target, &session, "second").await;
+
+    let client = accept(target, &session).await;
+    let first = receive(target, client.as_ref()).await;
+    assert_eq!(body(&first), "first");
+    client.abandon_message(first.receipt_handle).await.unwrap();
+
+    let redelivered = receive(target, client.as_ref()).await;
+    assert_eq!(
+        redelivered.message_id, first.message_id,
+        "[{}] abandoned message was not redelivered first",
+        target.name
+    );
+    assert!(
+        redelivered.delivery_count > first.delivery_count,
+        "[{}] delivery count did not increase ({} -> {})",
+        target.name,
+        first.delivery_count,
+        redelivered.delivery_count
+    );
+    client
+        .complete_message(redelivered.receipt_handle)
+        .await
+        .unwrap();
+
+    let second = receive(target, client.as_ref()).await;
+    assert_eq!(body(&second), "second");
+    client
+        .complete_message(second.receipt_handle)
+        .await
+        .unwrap();
+
+    // Completed messages are never redelivered
+    assert_empty(target, client.as_ref(), "after completing all messages").await;
+    client.close_session().await.unwrap();
+}
+
+/// A dead-lettered message leaves the queue and the session continues with
+/// the next message.
+async fn check_dead_letter(target: &ConformanceTarget) {
+    let session = unique_session("dead-letter");
+    send(target, &session, "poison").await;
+    send(target, &session, "healthy").await;
+
+    let client = accept(target, &session).await;
+    let poison = receive(target, client.as_ref()).await;
+    assert_eq!(body(&poison), "poison");
+    client
+        .dead_letter_message(poison.receipt_handle, "conformance".to_string())
+        .await
+        .unwrap();
+
+    let healthy = receive(target, client.as_ref()).await;
+    assert_eq!(
+        body(&healthy),
+        "healthy",
+        "[{}] dead-lettered message was redelivered or blocked the session",
+        target.name
+    );
+    client
+        .complete_message(healthy.receipt_handle)
+        .await
+        .unwrap();
+
+    assert_empty(target, client.as_ref(), "after dead-lettering").await;
+    client.close_session().await.unwrap();
+}
+
+/// Renewing a session lock moves its expiry forward and keeps the session's
+/// messages settleable.
+async fn check_session_lock_renewal(target: &ConformanceTarget) {
+    let session = unique_session("lock-renewal");
+    send(target, &session, "long-running").await;
+
+    let client = accept(target, &session).await;
+    let message = receive(target, client.as_ref()).await;
+    let expires_before = client.session_expires_at();
+
+    tokio::time::sleep(std::time::Duration::from_millis(1100)).await;
+    client
+        .renew_session_lock()
+        .await
+        .unwrap_or_else(|e| panic!("[{}] renew_session_lock failed: {}", target.name, e));
+
+    if target.reports_renewed_expiry {
+        assert!(
+            client.session_expires_at() > expires_before,
+            "[{}] renewal did not extend the session lock",
+            target.name
+        );
+    }
+    assert_eq!(client.session_id(), &session);
+    client
+        .complete_message(message.receipt_handle)
+        .await
+        .unwrap_or_else(|e| panic!("[{}] complete after renewal failed: {}", target.name, e));
+    client.close_session().await.unwrap();
+}
+
+/// Events routed for an ordered bot, then replayed, arrive on their session
+/// as the originals followed by the replays, both in the original order.
+async fn check_replay_ordering(target: &ConformanceTarget) {
+    let bot_config = BotConfiguration {
+        bots: vec![BotSubscription {
+            name: BotName::new("conformance-bot".to_string()).unwrap(),
+            queue: queue_keeper_core::QueueName::new(target.queue.as_str().to_string()).unwrap(),
+            events: vec![EventTypePattern::Wildcard("*".to_string())],
+            ordered: true,
+            repository_filter: None,
+            config: BotSpecificConfig::new(),
+            encryption: None,
+            transform: None,
+            projection: None,
+            http: None,
+            sender_filter: None,
+        }],
+        settings: BotConfigurationSettings::default(),
+    };
+    let router = DefaultEventRouter::new();
+    let session = unique_session("replay");
+    let core_session = queue_keeper_core::SessionId::new(session.as_str().to_string()).unwrap();
+
+    let originals: Vec<WrappedEvent> = ["opened", "synchronize", "labeled", "closed"]
+        .into_iter()
+        .map(|action| {
+            WrappedEvent::new(
+                "github".to_string(),
+                "pull_request".to_string(),
+                Some(action.to_string()),
+                Some(core_session.clone()),
+                serde_json::json!({ "action": action }),
+                None,
+            )
+        })
+        .collect();
+    let replays: Vec<WrappedEvent> = originals
+        .iter()
+        .map(|event| event.derive(LineageCause::Replay))
+        .collect();
+
+    for event in originals.iter().chain(replays.iter()) {
+        let result = router
+            .route_event(event, &bot_config, target.client.as_ref())
+            .await
+            .unwrap();
+        assert!(
+            result.is_complete_success(),
+            "[{}] routing failed",
+            target.name
+        );
+    }
+
+    let client = accept(target, &session).await;
+    let mut received: Vec<WrappedEvent> = Vec::new();
+    for _ in 0..originals.len() + replays.len() {
+        let message = receive(target, client.as_ref()).await;
+        received.push(serde_json::from_slice(&message.body).unwrap());
+        client
+            .complete_message(message.receipt_handle)
+            .await
+            .unwrap();
+    }
+    client.close_session().await.unwrap();
+
+    let received_ids: Vec<EventId> = received.iter().map(|e| e.event_id).collect();
+    let expected_ids: Vec<EventId> = originals
+        .iter()
+        .chain(replays.iter())
+        .map(|e| e.event_id)
+        .collect();
+    assert_eq!(
+        received_ids, expected_ids,
+        "[{}] replayed events were delivered out of order",
+        target.name
+    );
+
+    let replay_parents: Vec<EventId> = received[originals.len()..]
+        .iter()
+        .map(|e| e.lineage.as_ref().unwrap().parent_event_id)
+        .collect();
+    let original_ids: Vec<EventId> = originals.iter().map(|e| e.event_id).collect();
+    assert_eq!(replay_parents, original_ids);
+}
+
+// ============================================================================
+// Suites
+// ============================================================================
+
+/// Generate one test per check for a target.
+///
+/// Extra attributes (e.g. `#[ignore]`) apply to every generated test.
+macro_rules! conformance_suite {
+    ($suite:ident, $target:path $(, #[$attr:meta])*) => {
+        mod $suite {
+            use super::*;
+
+            async fn run<F>(check: impl FnOnce(ConformanceTarget) -> F)
+            where
+                F: std::future::Future<Output = ()>,
+            {
+                if let Some(target) = $target().await {
+                    check(target).await;
+                }
+            }
+
+            /// Per-session FIFO ordering.
+            #[tokio::test]
+            $(#[$attr])*
+            async fn test_session_ordering() {
+                run(|t| async move { check_session_ordering(&t).await }).await;
+            }
+
+            /// Abandoned messages are redelivered.
+            #[tokio::test]
+            $(#[$attr])*
+            async fn test_at_least_once_delivery() {
+                run(|t| async move { check_at_least_once_delivery(&t).await }).await;
+            }
+
+            /// Dead-lettered messages leave the queue.
+            #[tokio::test]
+            $(#[$attr])*
+            async fn test_dead_letter() {
+                run(|t| async move { check_dead_letter(&t).await }).await;
+            }
+
+            /// Session lock renewal extends the lock.
+            #[tokio::test]
+            $(#[$attr])*
+            async fn test_session_lock_renewal() {
+                run(|t| async move { check_session_lock_renewal(&t).await }).await;
+            }
+
+            /// Replays follow the originals in order.
+            #[tokio::test]
+            $(#[$attr])*
+            async fn test_replay_ordering() {
+                run(|t| async move { check_replay_ordering(&t).await }).await;
+            }
+        }
+    };
+}
+
+conformance_suite!(in_memory, in_memory_target);
+conformance_suite!(aws_sqs_fifo, sqs_fifo_target, #[ignore = "requires AWS SQS FIFO queue"]);
+conformance_suite!(
+    azure_service_bus,
+    service_bus_target,
+    #[ignore = "requires Azure Service Bus session queue"]
+);
+
+/// Replaying the same input twice yields the same delivery order.
+#[tokio::test]
+async fn test_replay_ordering_is_deterministic() {
+    for _ in 0..3 {
+        let target = in_memory_target().await.unwrap();
+        check_replay_ordering(&target).await;
+    }
+}
@@ -10,13 +10,25 @@ mod common;
 
 use common::{create_empty_bot_config, create_test_bot_config, MockBlobStorage, MockQueueClient};
 use queue_keeper_api::{
+    bot_pause::release_held_events,
     dlq_storage::DlqStorageService,
-    queue_delivery::{deliver_event_to_queues, QueueDeliveryConfig, QueueDeliveryOutcome},
+    queue_delivery::{
+        deliver_event_to_queues, release_session_events, spawn_queue_delivery, QueueDeliveryConfig,
+        QueueDeliveryOutcome,
+    },
     retry::RetryPolicy,
+    retry_budget::{RetryBudget, RetryBudgetConfig},
+};
+use queue_keeper_core::{
+    queue_integration::{
+        DefaultEventRouter, DeliveryResult, EventRouter, QueueClient, QueueDeliveryError,
+    },
+    webhook::WrappedEvent,
+    BotConfiguration, SessionId, SessionStatus,
 };
-use queue_keeper_core::{queue_integration::DefaultEventRouter, webhook::WrappedEvent, SessionId};
 use std::sync::Arc;
 use std::time::Duration;
+use tokio::sync::Notify;
 
 /// Helper to create a test wrapped event
 fn create_test_event() -> WrappedEvent {
@@ -147,6 +159,66 @@ async fn test_retry_on_transient_failure() {
     assert_eq!(queue_client.send_count(), 2);
 }
 
+/// Verify that a retry beyond the global retry budget is deferred, not
+/// dropped, and is sent once the budget refills.
+#[tokio::test]
+async fn test_retry_deferred_while_budget_exhausted() {
+    // Arrange: 1 bot, first attempt fails transiently; another delivery has
+    // already spent the only retry of the window.
+    let event = create_test_event();
+    let bot_config = create_test_bot_config(1);
+    let queue_client = Arc::new(MockQueueClient::new());
+    queue_client.expect_transient_failure();
+
+    let retry_budget = RetryBudget::new(RetryBudgetConfig {
+        enabled: true,
+        ratio: 0.0,
+        min_retries: 1,
+        window: Duration::from_millis(200),
+        deferral: Duration::from_millis(50),
+    });
+    assert!(retry_budget.try_acquire(1));
+
+    let event_router = Arc::new(DefaultEventRouter::new());
+    let config = QueueDeliveryConfig {
+        retry_policy: RetryPolicy {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(10),
+            max_delay: Duration::from_millis(50),
+            backoff_multiplier: 2.0,
+            use_jitter: false,
+            jitter_percent: 0.0,
+        },
+        retry_budget: Arc::new(retry_budget),
+        ..Default::default()
+    };
+
+    let start = std::time::Instant::now();
+
+    // Act
+    let outcome = deliver_event_to_queues(
+        event,
+        event_router,
+        Arc::new(bot_config),
+        queue_client.clone(),
+        config,
+    )
+    .await;
+
+    // Assert: the retry waited for the spent retry to leave the window
+    assert!(
+        matches!(outcome, QueueDeliveryOutcome::AllQueuesSucceeded { .. }),
+        "Expected AllQueuesSucceeded after deferred retry, got {:?}",
+        outcome
+    );
+    assert!(
+        start.elapsed() >= Duration::from_millis(200),
+        "Retry was not deferred; elapsed {:?}",
+        start.elapsed()
+    );
+    assert_eq!(queue_client.send_count(), 2);
+}
+
 /// Verify that permanent failures do not trigger retry.
 ///
 /// Asserts Assertion #10: Retry Behavior — permanent errors MUST NOT be retried
@@ -174,6 +246,19 @@ async fn test_no_retry_on_permanent_failure() {
         },
         enable_dlq: false,
         dlq_service: None,
+        session_epochs: Default::default(),
+        session_sequencer: Default::default(),
+        session_control: Default::default(),
+        delivery_index: Default::default(),
+        session_tracker: Default::default(),
+        delivery_audit: Default::default(),
+        bot_pauses: Default::default(),
+        failing_bots: Default::default(),
+        dev_tools: None,
+        event_stream: None,
+        retry_budget: Default::default(),
+        metrics: None,
+        outbox: None,
     };
 
     let start = std::time::Instant::now();
@@ -209,17 +294,11 @@ async fn test_no_retry_on_permanent_failure() {
 /// Verify that partial delivery failures are handled and tracked correctly.
 ///
 /// Asserts Assertion #6: One-to-Many Routing — failures on individual queues
-/// MUST be recorded; the router exhausts retries and reports the outcome.
-///
-/// Note: Because `PartialDelivery` errors are classified as transient by
-/// `QueueDeliveryError`, the delivery loop retries the full batch up to
-/// `max_attempts` times before giving up. With `max_attempts=1` no retry
-/// occurs, so the outcome is `CompleteFailure`.
+/// MUST be recorded; the queues that accepted the event count as delivered.
 #[tokio::test]
 async fn test_partial_delivery_failure_tracking() {
     // Arrange: 3 bots; the second send call returns a permanent failure,
-    // the others succeed. max_attempts=1 to prevent the partial-delivery
-    // retry loop from re-sending to already-successful queues.
+    // the others succeed.
     let event = create_test_event();
     let bot_config = create_test_bot_config(3);
     let queue_client = Arc::new(MockQueueClient::new());
@@ -241,6 +320,19 @@ async fn test_partial_delivery_failure_tracking() {
         },
         enable_dlq: false,
         dlq_service: None,
+        session_epochs: Default::default(),
+        session_sequencer: Default::default(),
+        session_control: Default::default(),
+        delivery_index: Default::default(),
+        session_tracker: Default::default(),
+        delivery_audit: Default::default(),
+        bot_pauses: Default::default(),
+        failing_bots: Default::default(),
+        dev_tools: None,
+        event_stream: None,
+        retry_budget: Default::default(),
+        metrics: None,
+        outbox: None,
     };
 
     // Act
@@ -253,18 +345,89 @@ async fn test_partial_delivery_failure_tracking() {
     )
     .await;
 
-    // Assert: failure reported; the partial-delivery error is classified as
-    // transient (PartialDelivery), but with max_attempts=0 (no retries allowed)
-    // it immediately converts to CompleteFailure.
+    // Assert: the permanent failure is reported next to the two deliveries
     assert!(
-        matches!(outcome, QueueDeliveryOutcome::CompleteFailure { .. }),
-        "Expected CompleteFailure for partial delivery with max_attempts=1, got {:?}",
+        matches!(
+            outcome,
+            QueueDeliveryOutcome::SomeQueuesFailed {
+                successful_count: 2,
+                failed_count: 1,
+                ..
+            }
+        ),
+        "Expected SomeQueuesFailed(2, 1), got {:?}",
         outcome
     );
     // All 3 sends happened within the single attempt
     assert_eq!(queue_client.send_count(), 3);
 }
 
+/// Verify that a retry after a partial failure only re-sends to the failed
+/// queue.
+///
+/// Asserts Assertion #10: Retry Behavior — queues that accepted the event
+/// MUST NOT receive it again when another queue's delivery is retried.
+#[tokio::test]
+async fn test_retry_resends_only_to_failed_queue() {
+    // Arrange: 3 bots; bot-2 fails transiently once
+    let event = create_test_event();
+    let bot_config = create_test_bot_config(3);
+    let queue_client = Arc::new(MockQueueClient::new());
+    queue_client.expect_success();
+    queue_client.expect_transient_failure();
+
+    let event_router = Arc::new(DefaultEventRouter::new());
+    let config = QueueDeliveryConfig {
+        retry_policy: RetryPolicy {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(5),
+            max_delay: Duration::from_millis(20),
+            backoff_multiplier: 2.0,
+            use_jitter: false,
+            jitter_percent: 0.0,
+        },
+        ..Default::default()
+    };
+
+    // Act
+    let outcome = deliver_event_to_queues(
+        event,
+        event_router,
+        Arc::new(bot_config),
+        queue_client.clone(),
+        config,
+    )
+    .await;
+
+    // Assert: every queue counts as delivered, and only bot-2 was sent to
+    // again
+    assert!(
+        matches!(
+            outcome,
+            QueueDeliveryOutcome::AllQueuesSucceeded {
+                successful_count: 3,
+                ..
+            }
+        ),
+        "Expected AllQueuesSucceeded(3), got {:?}",
+        outcome
+    );
+    let queues: Vec<String> = queue_client
+        .sent_messages()
+        .iter()
+        .map(|(queue, _)| queue.as_str().to_string())
+        .collect();
+    assert_eq!(
+        queues,
+        vec![
+            "queue-keeper-test-bot-1",
+            "queue-keeper-test-bot-2",
+            "queue-keeper-test-bot-3",
+            "queue-keeper-test-bot-2",
+        ]
+    );
+}
+
 /// Verify that events with no matching queues are handled gracefully.
 ///
 /// Tests the edge case where no bot subscriptions match the incoming event.
@@ -326,6 +489,19 @@ async fn test_dlq_after_max_retries() {
         },
         enable_dlq: true,
         dlq_service: Some(dlq_service),
+        session_epochs: Default::default(),
+        session_sequencer: Default::default(),
+        session_control: Default::default(),
+        delivery_index: Default::default(),
+        session_tracker: Default::default(),
+        delivery_audit: Default::default(),
+        bot_pauses: Default::default(),
+        failing_bots: Default::default(),
+        dev_tools: None,
+        event_stream: None,
+        retry_budget: Default::default(),
+        metrics: None,
+        outbox: None,
     };
 
     // Act
@@ -436,3 +612,389 @@ async fn test_exponential_backoff_with_jitter() {
         "Should not be able to retry at attempt 5"
     );
 }
+
+/// Verify that resetting the session cancels a delivery waiting to retry and
+/// that the event is not persisted to the DLQ.
+#[tokio::test]
+async fn test_session_reset_cancels_pending_retry() {
+    // Arrange: every attempt fails transiently with a long retry delay
+    let event = create_test_event();
+    let session_id = event.session_id.clone().unwrap();
+    let bot_config = create_test_bot_config(1);
+    let queue_client = Arc::new(MockQueueClient::new());
+    queue_client.always_fail_transient(10);
+    let event_router = Arc::new(DefaultEventRouter::new());
+    let config = QueueDeliveryConfig {
+        retry_policy: RetryPolicy {
+            max_attempts: 5,
+            initial_delay: Duration::from_secs(30),
+            max_delay: Duration::from_secs(30),
+            backoff_multiplier: 1.0,
+            use_jitter: false,
+            jitter_percent: 0.0,
+        },
+        ..Default::default()
+    };
+    let session_epochs = config.session_epochs.clone();
+
+    let delivery = tokio::spawn(deliver_event_to_queues(
+        event,
+        event_router,
+        Arc::new(bot_config),
+        queue_client.clone(),
+        config,
+    ));
+
+    // Wait until the first attempt has been made
+    while queue_client.send_count() == 0 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    // Act
+    let summary = session_epochs.reset(&session_id);
+    let outcome = tokio::time::timeout(Duration::from_secs(5), delivery)
+        .await
+        .expect("reset should end the delivery promptly")
+        .unwrap();
+
+    // Assert
+    assert_eq!(summary.cancelled_deliveries, 1);
+    assert!(
+        matches!(
+            outcome,
+            QueueDeliveryOutcome::CancelledBySessionReset {
+                session_epoch: 0,
+                ..
+            }
+        ),
+        "Expected CancelledBySessionReset, got {:?}",
+        outcome
+    );
+    assert_eq!(queue_client.send_count(), 1, "No retry after reset");
+}
+
+/// Router whose sends never complete, signalling when one starts.
+struct StalledRouter {
+    started: Arc<Notify>,
+}
+
+#[async_trait::async_trait]
+impl EventRouter for StalledRouter {
+    async fn route_event(
+        &self,
+        _event: &WrappedEvent,
+        _config: &BotConfiguration,
+        _queue_client: &dyn QueueClient,
+    ) -> Result<DeliveryResult, QueueDeliveryError> {
+        self.started.notify_one();
+        std::future::pending().await
+    }
+}
+
+/// Verify that resetting the session cancels a delivery whose send is still
+/// in flight.
+#[tokio::test]
+async fn test_session_reset_cancels_in_flight_send() {
+    // Arrange: the first send never completes
+    let event = create_test_event();
+    let session_id = event.session_id.clone().unwrap();
+    let started = Arc::new(Notify::new());
+    let event_router = Arc::new(StalledRouter {
+        started: started.clone(),
+    });
+    let config = QueueDeliveryConfig::default();
+    let session_epochs = config.session_epochs.clone();
+
+    let delivery = tokio::spawn(deliver_event_to_queues(
+        event,
+        event_router,
+        Arc::new(create_test_bot_config(1)),
+        Arc::new(MockQueueClient::new()),
+        config,
+    ));
+    started.notified().await;
+
+    // Act
+    let summary = session_epochs.reset(&session_id);
+    let outcome = tokio::time::timeout(Duration::from_secs(5), delivery)
+        .await
+        .expect("reset should end the in-flight send")
+        .unwrap();
+
+    // Assert
+    assert_eq!(summary.cancelled_deliveries, 1);
+    assert!(
+        matches!(
+            outcome,
+            QueueDeliveryOutcome::CancelledBySessionReset {
+                session_epoch: 0,
+                ..
+            }
+        ),
+        "Expected CancelledBySessionReset, got {:?}",
+        outcome
+    );
+}
+
+/// Verify that events delivered after a reset carry the new session epoch in
+/// the envelope and message attributes.
+#[tokio::test]
+async fn test_delivery_after_reset_carries_new_epoch() {
+    // Arrange
+    let event = create_test_event();
+    let session_id = event.session_id.clone().unwrap();
+    let bot_config = create_test_bot_config(1);
+    let queue_client = Arc::new(MockQueueClient::new());
+    let event_router = Arc::new(DefaultEventRouter::new());
+    let config = QueueDeliveryConfig::default();
+    config.session_epochs.reset(&session_id);
+    config.session_epochs.reset(&session_id);
+
+    // Act
+    let outcome = deliver_event_to_queues(
+        event,
+        event_router,
+        Arc::new(bot_config),
+        queue_client.clone(),
+        config,
+    )
+    .await;
+
+    // Assert
+    assert!(outcome.is_success(), "Unexpected outcome: {:?}", outcome);
+    let (_, message) = queue_client.sent_messages().pop().unwrap();
+    assert_eq!(
+        message.attributes.get("session_epoch").map(String::as_str),
+        Some("2")
+    );
+    let delivered: WrappedEvent = serde_json::from_slice(&message.body).unwrap();
+    assert_eq!(delivered.session_epoch, 2);
+}
+
+/// Verify that a session's events are sent in the order they were accepted
+/// even when the earlier delivery is retried.
+#[tokio::test]
+async fn test_concurrent_deliveries_of_session_keep_order() {
+    // Arrange: the first event's first send fails transiently
+    let first = create_test_event();
+    let second = create_test_event();
+    let bot_config = Arc::new(create_test_bot_config(1));
+    let queue_client = Arc::new(MockQueueClient::new());
+    queue_client.expect_transient_failure();
+    let event_router = Arc::new(DefaultEventRouter::new());
+    let config = QueueDeliveryConfig {
+        retry_policy: RetryPolicy {
+            max_attempts: 3,
+            initial_delay: Duration::from_millis(50),
+            max_delay: Duration::from_millis(50),
+            backoff_multiplier: 1.0,
+            use_jitter: false,
+            jitter_percent: 0.0,
+        },
+        ..Default::default()
+    };
+
+    // Act: both deliveries run concurrently
+    let first_task = spawn_queue_delivery(
+        first.clone(),
+        event_router.clone(),
+        bot_config.clone(),
+        queue_client.clone(),
+        config.clone(),
+    );
+    let second_task = spawn_queue_delivery(
+        second.clone(),
+        event_router,
+        bot_config,
+        queue_client.clone(),
+        config.clone(),
+    );
+    assert!(first_task.await.unwrap().is_success());
+    assert!(second_task.await.unwrap().is_success());
+
+    // Assert: the second event waited for the retry of the first
+    let sent: Vec<WrappedEvent> = queue_client
+        .sent_messages()
+        .iter()
+        .map(|(_, message)| serde_json::from_slice(&message.body).unwrap())
+        .collect();
+    let order: Vec<_> = sent.iter().map(|event| event.event_id).collect();
+    assert_eq!(order, [first.event_id, first.event_id, second.event_id]);
+    assert!(sent[2].session_sequence > sent[0].session_sequence);
+    assert_eq!(config.session_sequencer.active_sessions(), 0);
+}
+
+/// Verify that the session tracker records each delivered event and the
+/// session's status after a failed delivery.
+#[tokio::test]
+async fn test_session_tracker_records_delivery_outcomes() {
+    // Arrange: the second event of the session fails permanently
+    let delivered = create_test_event();
+    let failed = create_test_event();
+    let session_id = delivered.session_id.clone().unwrap();
+    let bot_config = Arc::new(create_test_bot_config(1));
+    let queue_client = Arc::new(MockQueueClient::new());
+    let event_router = Arc::new(DefaultEventRouter::new());
+    let config = QueueDeliveryConfig::default();
+    let session_tracker = config.session_tracker.clone();
+
+    // Act
+    let first = deliver_event_to_queues(
+        delivered,
+        event_router.clone(),
+        bot_config.clone(),
+        queue_client.clone(),
+        config.clone(),
+    )
+    .await;
+    queue_client.expect_permanent_failure();
+    let second = deliver_event_to_queues(
+        failed,
+        event_router,
+        bot_config,
+        queue_client.clone(),
+        config,
+    )
+    .await;
+
+    // Assert
+    assert!(first.is_success(), "Unexpected outcome: {:?}", first);
+    assert!(second.has_failures(), "Unexpected outcome: {:?}", second);
+    let tracked = session_tracker.get(&session_id).unwrap();
+    assert_eq!(tracked.event_count, 2);
+    assert_eq!(tracked.pending_events, 0);
+    assert_eq!(tracked.failed_events, 1);
+    assert_eq!(tracked.status, SessionStatus::Failed);
+}
+
+/// Verify that a paused bot's events are held while other bots still
+/// receive them, and are released in order on resume.
+#[tokio::test]
+async fn test_paused_bot_holds_events_until_resumed() {
+    // Arrange: 2 bots, the first of which is paused
+    let bot_config = Arc::new(create_test_bot_config(2));
+    let paused = bot_config.bots[0].name.clone();
+    let queue_client = Arc::new(MockQueueClient::new());
+    let event_router = Arc::new(DefaultEventRouter::new());
+    let config = QueueDeliveryConfig::default();
+    config.bot_pauses.pause(&paused, "ops", "maintenance");
+
+    let first = create_test_event();
+    let second = create_test_event();
+    for event in [first.clone(), second.clone()] {
+        let outcome = deliver_event_to_queues(
+            event,
+            event_router.clone(),
+            bot_config.clone(),
+            queue_client.clone(),
+            config.clone(),
+        )
+        .await;
+        assert!(outcome.is_success(), "Unexpected outcome: {:?}", outcome);
+    }
+    assert_eq!(
+        queue_client.send_count(),
+        2,
+        "Only the unpaused bot receives"
+    );
+    assert_eq!(config.bot_pauses.statuses()[0].held_events, 2);
+
+    // Act
+    config.bot_pauses.resume(&paused).unwrap();
+    let released = release_held_events(
+        paused.clone(),
+        event_router,
+        bot_config,
+        queue_client.clone(),
+        config.clone(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(released, 2);
+    assert!(!config.bot_pauses.is_paused(&paused));
+    assert_eq!(queue_client.send_count(), 4);
+    let delivered: Vec<WrappedEvent> = queue_client
+        .sent_messages()
+        .into_iter()
+        .skip(2)
+        .map(|(_, message)| serde_json::from_slice(&message.body).unwrap())
+        .collect();
+    assert_eq!(delivered[0].event_id, first.event_id);
+    assert_eq!(delivered[1].event_id, second.event_id);
+}
+
+/// Verify that a paused session's events are buffered while other sessions
+/// are delivered, and are released in order on resume.
+#[tokio::test]
+async fn test_paused_session_buffers_events_until_resumed() {
+    // Arrange: the test event's session is paused
+    let bot_config = Arc::new(create_test_bot_config(1));
+    let queue_client = Arc::new(MockQueueClient::new());
+    let event_router = Arc::new(DefaultEventRouter::new());
+    let config = QueueDeliveryConfig::default();
+    let first = create_test_event();
+    let second = create_test_event();
+    let session_id = first.session_id.clone().unwrap();
+    config.session_control.pause(&session_id, "ops", "bot fix");
+
+    let mut other_session = create_test_event();
+    other_session.session_id = Some(SessionId::from_parts("owner", "repo", "issue", "7"));
+    let outcome = deliver_event_to_queues(
+        other_session,
+        event_router.clone(),
+        bot_config.clone(),
+        queue_client.clone(),
+        config.clone(),
+    )
+    .await;
+    assert!(outcome.is_success(), "Unexpected outcome: {:?}", outcome);
+
+    for event in [first.clone(), second.clone()] {
+        let outcome = deliver_event_to_queues(
+            event,
+            event_router.clone(),
+            bot_config.clone(),
+            queue_client.clone(),
+            config.clone(),
+        )
+        .await;
+        assert!(
+            matches!(
+                outcome,
+                QueueDeliveryOutcome::BufferedForPausedSession { .. }
+            ),
+            "Unexpected outcome: {:?}",
+            outcome
+        );
+    }
+    assert_eq!(queue_client.send_count(), 1, "Only the other session");
+    let tracked = config.session_tracker.get(&session_id).unwrap();
+    assert_eq!(tracked.pending_events, 2);
+
+    // Act
+    config.session_control.resume(&session_id).unwrap();
+    let released = release_session_events(
+        session_id.clone(),
+        event_router,
+        bot_config,
+        queue_client.clone(),
+        config.clone(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(released, 2);
+    assert!(!config.session_control.is_paused(&session_id));
+    let delivered: Vec<WrappedEvent> = queue_client
+        .sent_messages()
+        .into_iter()
+        .skip(1)
+        .map(|(_, message)| serde_json::from_slice(&message.body).unwrap())
+        .collect();
+    let order: Vec<_> = delivered.iter().map(|event| event.event_id).collect();
+    assert_eq!(order, [first.event_id, second.event_id]);
+    let tracked = config.session_tracker.get(&session_id).unwrap();
+    assert_eq!(tracked.pending_events, 0);
+    assert_eq!(tracked.event_count, 2);
+}
@@ -8,8 +8,12 @@ mod common;
 use axum::extract::{Path, State};
 use bytes::Bytes;
 use common::{
-    create_test_app_state_with_processor, create_valid_webhook_headers,
-    AlwaysFailingSignatureValidator, MockWebhookProcessor,
+    create_default_wrapped_event, create_test_app_state_with_processor,
+    create_valid_webhook_headers, AlwaysFailingSignatureValidator, MockWebhookProcessor,
+};
+use queue_keeper_core::{
+    webhook::{DeduplicationStore, ReplayGuard},
+    EventId,
 };
 use std::sync::Arc;
 use std::time::Duration;
@@ -431,3 +435,83 @@ async fn test_x_correlation_id_header_is_propagated_when_no_traceparent() {
         "trace_context must equal x-correlation-id when no traceparent is present"
     );
 }
+
+/// Verify that a repository over its rate limit is throttled without
+/// affecting other repositories
+#[tokio::test]
+async fn test_webhook_rate_limited_per_repository() {
+    // Arrange: Allow two webhooks per repository per minute
+    let processor = MockWebhookProcessor::new();
+    let base_state = create_test_app_state_with_processor(Arc::new(processor.clone()));
+    let limiter =
+        queue_keeper_api::RepositoryRateLimiter::new(2).with_metrics(base_state.metrics.clone());
+    let state = base_state.with_repository_rate_limiter(Arc::new(limiter));
+
+    // The limit is keyed on the processed event, not the raw body.
+    let send = |repository: &str| {
+        let mut event = create_default_wrapped_event();
+        event.payload["repository"]["full_name"] = serde_json::json!(repository);
+        processor.set_result(event);
+        queue_keeper_api::handle_provider_webhook(
+            State(state.clone()),
+            Path("github".to_string()),
+            create_valid_webhook_headers(),
+            Bytes::from(r#"{"action":"opened","repository":{"full_name":"forged/repo"}}"#),
+        )
+    };
+
+    // Act & Assert: The third webhook from the noisy repository is throttled
+    assert!(send("octo/noisy").await.is_ok());
+    assert!(send("octo/noisy").await.is_ok());
+    let result = send("octo/noisy").await;
+    assert!(
+        matches!(
+            result,
+            Err(queue_keeper_api::WebhookHandlerError::RateLimitExceeded {
+                retry_after_seconds
+            }) if retry_after_seconds > 0
+        ),
+        "Expected the noisy repository to be rate limited"
+    );
+
+    // Assert: Other repositories are still accepted
+    assert!(send("octo/quiet").await.is_ok());
+    assert_eq!(processor.call_count(), 4);
+    assert_eq!(state.metrics.webhook_repository_throttled_total.get(), 1);
+}
+
+/// Verify that a throttled delivery is released from deduplication so that
+/// a redelivery is processed
+#[tokio::test]
+async fn test_throttled_delivery_released_from_deduplication() {
+    // Arrange: One webhook per repository per minute, with deduplication
+    let guard = Arc::new(ReplayGuard::new(Duration::from_secs(60), 10));
+    let processor = MockWebhookProcessor::new();
+    let state = create_test_app_state_with_processor(Arc::new(processor.clone()))
+        .with_repository_rate_limiter(Arc::new(queue_keeper_api::RepositoryRateLimiter::new(1)))
+        .with_deduplication(guard.clone());
+    let send = || {
+        queue_keeper_api::handle_provider_webhook(
+            State(state.clone()),
+            Path("github".to_string()),
+            create_valid_webhook_headers(),
+            Bytes::from(r#"{"action":"opened"}"#),
+        )
+    };
+    assert!(send().await.is_ok());
+
+    // Act: The processor claims the delivery, then the repository is throttled
+    let store: &dyn DeduplicationStore = guard.as_ref();
+    store
+        .claim("12345678-1234-1234-1234-123456789012", EventId::new())
+        .await
+        .unwrap();
+    let result = send().await;
+
+    // Assert: The claim was released
+    assert!(matches!(
+        result,
+        Err(queue_keeper_api::WebhookHandlerError::RateLimitExceeded { .. })
+    ));
+    assert!(guard.is_empty());
+}
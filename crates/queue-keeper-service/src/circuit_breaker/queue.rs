@@ -1,17 +1,38 @@
 //! Circuit breaker wrapper for queue operations.
+//!
+//! Sends are protected per destination queue, so a queue that keeps failing
+//! fails fast without holding up deliveries to the other queues. The send
+//! circuits of a backend are a [`QueueSendCircuits`] registry that the
+//! service hands to the HTTP layer for metrics and health checks.
 
 use std::sync::Arc;
 
 use async_trait::async_trait;
 use chrono::Duration;
 use queue_keeper_core::circuit_breaker::{
-    service_bus_circuit_breaker_config, CircuitBreaker, CircuitBreakerError, CircuitBreakerFactory,
-    DefaultCircuitBreaker, DefaultCircuitBreakerFactory,
+    service_bus_circuit_breaker_config, CircuitBreaker, CircuitBreakerConfig, CircuitBreakerError,
+    CircuitBreakerFactory, CircuitBreakerRegistry, DefaultCircuitBreaker,
+    DefaultCircuitBreakerFactory,
 };
+use queue_keeper_core::{event_warn, logging::LogContext};
 use queue_runtime::{
-    Message, MessageId, ProviderType, QueueClient, QueueError, QueueName, QueueProvider,
-    ReceiptHandle, ReceivedMessage, SessionClient, SessionId, SessionProvider, SessionSupport,
+    Message, MessageId, ProviderType, QueueError, QueueName, QueueProvider, ReceiptHandle,
+    ReceivedMessage, SessionId, SessionProvider, SessionSupport,
 };
+#[cfg(feature = "aws-sqs")]
+use queue_runtime::{QueueClient, SessionClient};
+
+/// Send circuit breakers of a queue backend, one per destination queue.
+pub type QueueSendCircuits = CircuitBreakerRegistry<Vec<MessageId>, QueueError>;
+
+/// Per-queue send circuit breakers with the Service Bus settings, named
+/// `queue:<queue name>`.
+pub fn queue_send_circuits() -> QueueSendCircuits {
+    CircuitBreakerRegistry::new(CircuitBreakerConfig {
+        service_name: "queue".to_string(),
+        ..service_bus_circuit_breaker_config()
+    })
+}
 
 /// Queue provider with circuit breaker protection.
 ///
@@ -21,8 +42,8 @@ use queue_runtime::{
 pub struct CircuitBreakerQueueProvider {
     /// Underlying queue provider
     inner: Arc<dyn QueueProvider>,
-    /// Circuit breaker for protecting queue operations
-    circuit_breaker_send: DefaultCircuitBreaker<Vec<MessageId>, QueueError>,
+    /// Circuit breakers for protecting sends, one per destination queue
+    send_circuits: Arc<QueueSendCircuits>,
     circuit_breaker_receive: DefaultCircuitBreaker<Vec<ReceivedMessage>, QueueError>,
 }
 
@@ -31,19 +52,16 @@ impl CircuitBreakerQueueProvider {
     ///
     /// # Arguments
     /// - `inner`: Underlying QueueProvider to protect
-    pub fn new(inner: Arc<dyn QueueProvider>) -> Self {
-        let factory = DefaultCircuitBreakerFactory;
-        let circuit_breaker_config = service_bus_circuit_breaker_config();
-
+    /// - `send_circuits`: Circuit breakers of the destination queues
+    pub fn new(inner: Arc<dyn QueueProvider>, send_circuits: Arc<QueueSendCircuits>) -> Self {
         // Use separate circuit breakers for send and receive operations
         // as they may have different failure modes
-        let circuit_breaker_send =
-            factory.create_typed_circuit_breaker(circuit_breaker_config.clone());
-        let circuit_breaker_receive = factory.create_typed_circuit_breaker(circuit_breaker_config);
+        let circuit_breaker_receive = DefaultCircuitBreakerFactory
+            .create_typed_circuit_breaker(service_bus_circuit_breaker_config());
 
         Self {
             inner,
-            circuit_breaker_send,
+            send_circuits,
             circuit_breaker_receive,
         }
     }
@@ -57,18 +75,20 @@ impl QueueProvider for CircuitBreakerQueueProvider {
         message: &Message,
     ) -> Result<MessageId, QueueError> {
         let inner = Arc::clone(&self.inner);
-        let queue = queue.clone();
+        let log_context = LogContext::new().with_queue(queue.as_str());
+        let target = queue.clone();
         let message = message.clone();
 
-        self.circuit_breaker_send
-            .call(|| async move {
-                let message_id = inner.send_message(&queue, &message).await?;
+        self.send_circuits
+            .call(queue.as_str(), || async move {
+                let message_id = inner.send_message(&target, &message).await?;
                 Ok(vec![message_id])
             })
             .await
             // INVARIANT: the closure always returns Ok(vec![one_id]), so the
             // vec is guaranteed to have exactly one element; unwrap cannot panic.
             .map(|ids| ids.into_iter().next().unwrap())
+            .inspect_err(|e| log_rejection(&log_context, "send", e))
             .map_err(map_send_cb_error)
     }
 
@@ -78,12 +98,16 @@ impl QueueProvider for CircuitBreakerQueueProvider {
         messages: &[Message],
     ) -> Result<Vec<MessageId>, QueueError> {
         let inner = Arc::clone(&self.inner);
-        let queue = queue.clone();
+        let log_context = LogContext::new().with_queue(queue.as_str());
+        let target = queue.clone();
         let messages = messages.to_vec();
 
-        self.circuit_breaker_send
-            .call(|| async move { inner.send_messages(&queue, &messages).await })
+        self.send_circuits
+            .call(queue.as_str(), || async move {
+                inner.send_messages(&target, &messages).await
+            })
             .await
+            .inspect_err(|e| log_rejection(&log_context, "send", e))
             .map_err(map_send_cb_error)
     }
 
@@ -104,11 +128,13 @@ impl QueueProvider for CircuitBreakerQueueProvider {
         timeout: Duration,
     ) -> Result<Vec<ReceivedMessage>, QueueError> {
         let inner = Arc::clone(&self.inner);
+        let log_context = LogContext::new().with_queue(queue.as_str());
         let queue = queue.clone();
 
         self.circuit_breaker_receive
             .call(|| async move { inner.receive_messages(&queue, max_messages, timeout).await })
             .await
+            .inspect_err(|e| log_rejection(&log_context, "receive", e))
             .map_err(|e| match e {
                 CircuitBreakerError::CircuitOpen => QueueError::ProviderError {
                     provider: "CircuitBreaker".to_string(),
@@ -192,43 +218,57 @@ impl QueueProvider for CircuitBreakerQueueProvider {
 ///
 /// # Circuit Breaker Behaviour
 ///
-/// - Send operations (`send_message`, `send_messages`) share a single circuit breaker.
+/// - Send operations (`send_message`, `send_messages`) share one circuit breaker per
+///   destination queue.
 /// - Receive-batch operations (`receive_messages`) have their own circuit breaker.
 /// - Pass-through operations that consume an already-established receipt
 ///   (`complete_message`, `abandon_message`, `dead_letter_message`) are never circuit-broken
 ///   because they are lower-risk follow-ups to a successfully received message.
+#[cfg(feature = "aws-sqs")]
 #[derive(Clone)]
 pub struct CircuitBreakerQueueClient {
     /// Underlying queue client
     inner: Arc<dyn QueueClient>,
-    /// Circuit breaker for protecting send operations
-    circuit_breaker_send: DefaultCircuitBreaker<Vec<MessageId>, QueueError>,
+    /// Circuit breakers for protecting send operations, one per destination queue
+    send_circuits: Arc<QueueSendCircuits>,
     /// Circuit breaker for protecting batch receive operations
     circuit_breaker_receive: DefaultCircuitBreaker<Vec<ReceivedMessage>, QueueError>,
 }
 
+#[cfg(feature = "aws-sqs")]
 impl CircuitBreakerQueueClient {
     /// Create a new circuit-breaker-protected queue client.
     ///
     /// # Arguments
     /// - `inner`: Underlying [`QueueClient`] implementation to protect.
-    pub fn new(inner: Arc<dyn QueueClient>) -> Self {
-        let factory = DefaultCircuitBreakerFactory;
-        let circuit_breaker_config = service_bus_circuit_breaker_config();
-
+    /// - `send_circuits`: Circuit breakers of the destination queues.
+    pub fn new(inner: Arc<dyn QueueClient>, send_circuits: Arc<QueueSendCircuits>) -> Self {
         // Separate circuit breakers for send and receive — different failure modes.
-        let circuit_breaker_send =
-            factory.create_typed_circuit_breaker(circuit_breaker_config.clone());
-        let circuit_breaker_receive = factory.create_typed_circuit_breaker(circuit_breaker_config);
+        let circuit_breaker_receive = DefaultCircuitBreakerFactory
+            .create_typed_circuit_breaker(service_bus_circuit_breaker_config());
 
         Self {
             inner,
-            circuit_breaker_send,
+            send_circuits,
             circuit_breaker_receive,
         }
     }
 }
 
+/// Log a queue operation that the circuit breaker rejected without calling
+/// the provider. Failures returned by the provider itself are logged by the
+/// caller.
+fn log_rejection(log_context: &LogContext, operation: &str, e: &CircuitBreakerError<QueueError>) {
+    if !matches!(e, CircuitBreakerError::OperationFailed(_)) {
+        event_warn!(
+            log_context,
+            operation = operation,
+            error = %e,
+            "Queue operation rejected by circuit breaker"
+        );
+    }
+}
+
 /// Map a `CircuitBreakerError<QueueError>` to a `QueueError` for send operations.
 fn map_send_cb_error(e: CircuitBreakerError<QueueError>) -> QueueError {
     match e {
@@ -257,6 +297,7 @@ fn map_send_cb_error(e: CircuitBreakerError<QueueError>) -> QueueError {
 }
 
 /// Map a `CircuitBreakerError<QueueError>` to a `QueueError` for receive operations.
+#[cfg(feature = "aws-sqs")]
 fn map_receive_cb_error(e: CircuitBreakerError<QueueError>) -> QueueError {
     match e {
         CircuitBreakerError::CircuitOpen => QueueError::ProviderError {
@@ -283,6 +324,7 @@ fn map_receive_cb_error(e: CircuitBreakerError<QueueError>) -> QueueError {
     }
 }
 
+#[cfg(feature = "aws-sqs")]
 #[async_trait]
 impl QueueClient for CircuitBreakerQueueClient {
     async fn send_message(
@@ -291,17 +333,19 @@ impl QueueClient for CircuitBreakerQueueClient {
         message: Message,
     ) -> Result<MessageId, QueueError> {
         let inner = Arc::clone(&self.inner);
-        let queue = queue.clone();
+        let log_context = LogContext::new().with_queue(queue.as_str());
+        let target = queue.clone();
 
-        self.circuit_breaker_send
-            .call(|| async move {
-                let message_id = inner.send_message(&queue, message).await?;
+        self.send_circuits
+            .call(queue.as_str(), || async move {
+                let message_id = inner.send_message(&target, message).await?;
                 Ok(vec![message_id])
             })
             .await
             // INVARIANT: the closure always returns Ok(vec![one_id]), so the
             // vec is guaranteed to have exactly one element; unwrap cannot panic.
             .map(|ids| ids.into_iter().next().unwrap())
+            .inspect_err(|e| log_rejection(&log_context, "send", e))
             .map_err(map_send_cb_error)
     }
 
@@ -311,11 +355,15 @@ impl QueueClient for CircuitBreakerQueueClient {
         messages: Vec<Message>,
     ) -> Result<Vec<MessageId>, QueueError> {
         let inner = Arc::clone(&self.inner);
-        let queue = queue.clone();
+        let log_context = LogContext::new().with_queue(queue.as_str());
+        let target = queue.clone();
 
-        self.circuit_breaker_send
-            .call(|| async move { inner.send_messages(&queue, messages).await })
+        self.send_circuits
+            .call(queue.as_str(), || async move {
+                inner.send_messages(&target, messages).await
+            })
             .await
+            .inspect_err(|e| log_rejection(&log_context, "send", e))
             .map_err(map_send_cb_error)
     }
 
@@ -335,11 +383,13 @@ impl QueueClient for CircuitBreakerQueueClient {
         timeout: Duration,
     ) -> Result<Vec<ReceivedMessage>, QueueError> {
         let inner = Arc::clone(&self.inner);
+        let log_context = LogContext::new().with_queue(queue.as_str());
         let queue = queue.clone();
 
         self.circuit_breaker_receive
             .call(|| async move { inner.receive_messages(&queue, max_messages, timeout).await })
             .await
+            .inspect_err(|e| log_rejection(&log_context, "receive", e))
             .map_err(map_receive_cb_error)
     }
 
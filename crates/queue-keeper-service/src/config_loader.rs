@@ -0,0 +1,390 @@
+//! Layered service configuration loading.
+//!
+//! Sources, applied in order (later sources override earlier ones):
+//!
+//! 0. `ServiceConfig::default()` — built-in Rust defaults (lowest priority)
+//! 1. `/etc/queue-keeper/service.yaml` — system-wide defaults, followed in
+//!    developer mode by `developer::development_defaults()`
+//! 2. `./config/service.yaml` — deployment-local override
+//! 3. The file named by `--config <path>`, else by `QUEUE_KEEPER_CONFIG`,
+//!    else by the legacy `QK_CONFIG_FILE`. Its format follows the extension
+//!    (`.toml`, `.yaml`/`.yml` or `.json`; none means YAML) and it must exist.
+//! 4. Environment variables prefixed `QK__`, then `QUEUE_KEEPER__`
+//!    (highest priority), e.g. `QUEUE_KEEPER__SERVER__PORT=9090` sets
+//!    `server.port`.
+//!
+//! Source 0 is critical: without it, setting a single `QK__SECURITY__*`
+//! variable causes the `config` crate to create a partial `security` table.
+//! The deserialiser then fails for sibling fields that are absent from that
+//! partial table, even though they carry `#[serde(default)]` annotations.
+//! Pre-populating every field from the Rust defaults ensures the deserialiser
+//! always sees a fully-populated starting point before file/env overrides.
+//!
+//! The loaded configuration is validated before it is returned, so the
+//! service never starts on a configuration it would reject later. Every
+//! [`ConfigLoadError`] names the source to fix.
+//!
+//! Each file and defaults source records the keys it sets, so that
+//! `GET /admin/config?include_defaults=true` can name the source of every
+//! value.
+//!
+//! Developer mode is switched on only by `--dev`, never by a file or
+//! environment variable, and is refused when `QK_ENVIRONMENT` or
+//! `QK__TELEMETRY__ENVIRONMENT` names production.
+
+use config::builder::DefaultState;
+use config::Source;
+use queue_keeper_api::developer::{development_defaults, relax_signature_requirements};
+use queue_keeper_api::startup_report::ConfigSource;
+use queue_keeper_api::{ConfigError, ServiceConfig};
+use queue_keeper_core::Environment;
+use std::path::{Path, PathBuf};
+
+/// System-wide configuration file, without extension.
+const SYSTEM_CONFIG_FILE: &str = "/etc/queue-keeper/service";
+
+/// Deployment-local configuration file, without extension.
+const LOCAL_CONFIG_FILE: &str = "config/service";
+
+/// Environment variable prefixes for field overrides, lowest priority first.
+const ENVIRONMENT_PREFIXES: [&str; 2] = ["QK", "QUEUE_KEEPER"];
+
+/// Environment variables naming the deployment environment.
+const DEPLOYMENT_ENVIRONMENT_VARIABLES: [&str; 2] =
+    ["QK_ENVIRONMENT", "QK__TELEMETRY__ENVIRONMENT"];
+
+// ============================================================================
+// Errors
+// ============================================================================
+
+/// Why the configuration could not be loaded.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigLoadError {
+    /// `--config` was given without a path.
+    #[error("--config requires a path, e.g. --config /etc/queue-keeper/service.toml")]
+    MissingConfigPath,
+
+    /// The explicitly named configuration file does not exist.
+    #[error(
+        "configuration file '{}' (from {origin}) does not exist; fix the path or remove {origin}",
+        path.display()
+    )]
+    FileNotFound { path: PathBuf, origin: String },
+
+    /// The explicitly named configuration file has an unknown extension.
+    #[error(
+        "configuration file '{}' (from {origin}) has an unsupported extension; \
+         use .toml, .yaml, .yml or .json",
+        path.display()
+    )]
+    UnsupportedFormat { path: PathBuf, origin: String },
+
+    /// A configuration file could not be read or parsed.
+    #[error("cannot read configuration: {0}; fix the file named in the error")]
+    Read(#[source] config::ConfigError),
+
+    /// A setting has the wrong type or an unknown value.
+    #[error(
+        "invalid configuration value: {0}; check the setting in the configuration \
+         files and QK__/QUEUE_KEEPER__ environment variables"
+    )]
+    Deserialize(#[source] config::ConfigError),
+
+    /// The settings are individually valid but rejected together.
+    #[error("configuration is invalid: {0}")]
+    Invalid(#[source] ConfigError),
+
+    /// `--dev` was given in a production deployment.
+    #[error(
+        "developer mode cannot run in production ({variable}={value}); \
+         remove --dev or point {variable} at a non-production environment"
+    )]
+    DeveloperModeInProduction { variable: String, value: String },
+}
+
+// ============================================================================
+// Inputs
+// ============================================================================
+
+/// A configuration file named by the operator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExplicitConfigFile {
+    /// Path of the file.
+    pub path: PathBuf,
+
+    /// Where the path came from: `--config` or the environment variable.
+    pub origin: String,
+}
+
+/// Everything the configuration is loaded from.
+#[derive(Debug, Clone, Default)]
+pub struct ConfigInputs {
+    /// Command line arguments, without the program name.
+    pub args: Vec<String>,
+
+    /// Environment variables.
+    pub environment: Vec<(String, String)>,
+
+    /// System-wide YAML file searched without extension.
+    pub system_file: Option<String>,
+
+    /// Deployment-local YAML file searched without extension.
+    pub local_file: Option<String>,
+
+    /// Whether developer mode was requested with `--dev`.
+    pub developer_mode: bool,
+}
+
+impl ConfigInputs {
+    /// Inputs of this process.
+    pub fn from_process(developer_mode: bool) -> Self {
+        Self {
+            args: std::env::args().skip(1).collect(),
+            environment: std::env::vars().collect(),
+            system_file: Some(SYSTEM_CONFIG_FILE.to_string()),
+            local_file: Some(LOCAL_CONFIG_FILE.to_string()),
+            developer_mode,
+        }
+    }
+
+    fn env(&self, name: &str) -> Option<&str> {
+        self.environment
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+            .filter(|value| !value.is_empty())
+    }
+
+    /// The configuration file named by `--config`, `QUEUE_KEEPER_CONFIG` or
+    /// `QK_CONFIG_FILE`, in that order.
+    ///
+    /// # Errors
+    /// Returns [`ConfigLoadError::MissingConfigPath`] when `--config` has no
+    /// value.
+    pub fn explicit_file(&self) -> Result<Option<ExplicitConfigFile>, ConfigLoadError> {
+        let mut args = self.args.iter();
+        while let Some(arg) = args.next() {
+            let path = if arg == "--config" {
+                args.next()
+                    .map(String::as_str)
+                    .filter(|path| !path.starts_with("--"))
+            } else if let Some(path) = arg.strip_prefix("--config=") {
+                Some(path).filter(|path| !path.is_empty())
+            } else {
+                continue;
+            };
+            let path = path.ok_or(ConfigLoadError::MissingConfigPath)?;
+            return Ok(Some(ExplicitConfigFile {
+                path: PathBuf::from(path),
+                origin: "--config".to_string(),
+            }));
+        }
+
+        Ok(["QUEUE_KEEPER_CONFIG", "QK_CONFIG_FILE"]
+            .iter()
+            .find_map(|name| {
+                self.env(name).map(|path| ExplicitConfigFile {
+                    path: PathBuf::from(path),
+                    origin: name.to_string(),
+                })
+            }))
+    }
+
+    /// The variable and value naming a production deployment, if any.
+    fn production_environment(&self) -> Option<(&str, &str)> {
+        DEPLOYMENT_ENVIRONMENT_VARIABLES.iter().find_map(|name| {
+            self.env(name)
+                .filter(|value| value.parse::<Environment>().ok() == Some(Environment::Production))
+                .map(|value| (*name, value))
+        })
+    }
+}
+
+// ============================================================================
+// Loading
+// ============================================================================
+
+/// A loaded and validated configuration.
+#[derive(Debug)]
+pub struct LoadedConfig {
+    /// The configuration.
+    pub config: ServiceConfig,
+
+    /// Sources as reported in the startup report.
+    pub sources: Vec<ConfigSource>,
+
+    /// Providers that accept unsigned deliveries because developer mode is
+    /// on and they have no secret.
+    pub relaxed_providers: Vec<String>,
+}
+
+/// Load, merge and validate the configuration described by `inputs`.
+///
+/// # Errors
+/// Returns a [`ConfigLoadError`] naming the source to fix.
+pub fn load(inputs: &ConfigInputs) -> Result<LoadedConfig, ConfigLoadError> {
+    if inputs.developer_mode {
+        if let Some((variable, value)) = inputs.production_environment() {
+            return Err(ConfigLoadError::DeveloperModeInProduction {
+                variable: variable.to_string(),
+                value: value.to_string(),
+            });
+        }
+    }
+
+    let defaults_json = serde_json::to_string(&ServiceConfig::default())
+        .expect("ServiceConfig::default() must be JSON-serialisable");
+    let mut builder = config::Config::builder().add_source(config::File::from_str(
+        &defaults_json,
+        config::FileFormat::Json,
+    ));
+    let mut sources = vec![ConfigSource::defaults()];
+
+    if let Some(name) = &inputs.system_file {
+        builder = add_search_file(builder, &mut sources, name);
+    }
+
+    // Developer mode keeps every backend in memory unless a local file or
+    // environment variable says otherwise.
+    if inputs.developer_mode {
+        let defaults = config::File::from_str(
+            &development_defaults().to_string(),
+            config::FileFormat::Json,
+        );
+        sources.push(ConfigSource::development_defaults().with_keys(source_keys(&defaults)));
+        builder = builder.add_source(defaults);
+    }
+
+    if let Some(name) = &inputs.local_file {
+        builder = add_search_file(builder, &mut sources, name);
+    }
+
+    if let Some(explicit) = inputs.explicit_file()? {
+        let file = explicit_file_source(&explicit)?;
+        sources.push(
+            ConfigSource::file(explicit.path.display().to_string(), true)
+                .with_keys(source_keys(&file)),
+        );
+        builder = builder.add_source(file);
+    }
+
+    let environment: config::Map<String, String> = inputs.environment.iter().cloned().collect();
+    for prefix in ENVIRONMENT_PREFIXES {
+        sources.push(ConfigSource::environment(
+            &format!("{}__", prefix),
+            inputs.environment.iter().map(|(name, _)| name.clone()),
+        ));
+        builder = builder.add_source(
+            config::Environment::with_prefix(prefix)
+                .separator("__")
+                .source(Some(environment.clone())),
+        );
+    }
+
+    let mut config: ServiceConfig = builder
+        .build()
+        .map_err(ConfigLoadError::Read)?
+        .try_deserialize()
+        .map_err(ConfigLoadError::Deserialize)?;
+
+    config.developer.enabled = inputs.developer_mode;
+    let relaxed_providers = if inputs.developer_mode {
+        relax_signature_requirements(&mut config)
+    } else {
+        Vec::new()
+    };
+
+    config.validate().map_err(ConfigLoadError::Invalid)?;
+
+    Ok(LoadedConfig {
+        config,
+        sources,
+        relaxed_providers,
+    })
+}
+
+/// Add the optional YAML file `name`, searched without extension.
+fn add_search_file(
+    builder: config::ConfigBuilder<DefaultState>,
+    sources: &mut Vec<ConfigSource>,
+    name: &str,
+) -> config::ConfigBuilder<DefaultState> {
+    let file = config::File::with_name(name)
+        .required(false)
+        .format(config::FileFormat::Yaml);
+    sources.push(ConfigSource::file(name, config_file_found(name)).with_keys(source_keys(&file)));
+    builder.add_source(file)
+}
+
+/// Dotted keys of the settings `source` sets; arrays count as one setting.
+///
+/// A source that cannot be read sets nothing here; building the
+/// configuration reports its error.
+fn source_keys(source: &impl Source) -> Vec<String> {
+    fn collect(path: String, value: &config::Value, keys: &mut Vec<String>) {
+        match &value.kind {
+            config::ValueKind::Table(table) => {
+                for (key, value) in table {
+                    collect(format!("{}.{}", path, key), value, keys);
+                }
+            }
+            _ => keys.push(path),
+        }
+    }
+
+    let mut keys = Vec::new();
+    for (key, value) in source.collect().unwrap_or_default() {
+        collect(key, &value, &mut keys);
+    }
+    keys.sort();
+    keys
+}
+
+/// The `config` source for an operator-named file, with its format taken
+/// from the extension.
+fn explicit_file_source(
+    explicit: &ExplicitConfigFile,
+) -> Result<config::File<config::FileSourceFile, config::FileFormat>, ConfigLoadError> {
+    let path = &explicit.path;
+    let format = match path.extension().and_then(|extension| extension.to_str()) {
+        // An extensionless name is resolved like the default files.
+        None => {
+            let name = path.to_string_lossy();
+            if !config_file_found(&name) {
+                return Err(ConfigLoadError::FileNotFound {
+                    path: path.clone(),
+                    origin: explicit.origin.clone(),
+                });
+            }
+            return Ok(config::File::with_name(&name).format(config::FileFormat::Yaml));
+        }
+        Some("toml") => config::FileFormat::Toml,
+        Some("yaml" | "yml") => config::FileFormat::Yaml,
+        Some("json") => config::FileFormat::Json,
+        Some(_) => {
+            return Err(ConfigLoadError::UnsupportedFormat {
+                path: path.clone(),
+                origin: explicit.origin.clone(),
+            })
+        }
+    };
+    if !path.is_file() {
+        return Err(ConfigLoadError::FileNotFound {
+            path: path.clone(),
+            origin: explicit.origin.clone(),
+        });
+    }
+    Ok(config::File::from(path.as_path()).format(format))
+}
+
+/// Whether the `config` crate finds a YAML file for the extensionless
+/// `name` (tried as-is, then with `.yaml` and `.yml`).
+pub fn config_file_found(name: &str) -> bool {
+    ["", ".yaml", ".yml"]
+        .iter()
+        .any(|extension| Path::new(&format!("{}{}", name, extension)).is_file())
+}
+
+#[cfg(test)]
+#[path = "config_loader_tests.rs"]
+mod tests;
@@ -0,0 +1,190 @@
+//! Tests for layered configuration loading.
+
+use super::*;
+
+/// Inputs without the default search files, so tests see only what they set.
+fn inputs(args: &[&str], environment: &[(&str, &str)]) -> ConfigInputs {
+    ConfigInputs {
+        args: args.iter().map(|arg| arg.to_string()).collect(),
+        environment: environment
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        ..ConfigInputs::default()
+    }
+}
+
+/// `--config` wins over `QUEUE_KEEPER_CONFIG`, which wins over the legacy
+/// `QK_CONFIG_FILE`.
+#[test]
+fn test_explicit_file_precedence() {
+    let environment = [
+        ("QUEUE_KEEPER_CONFIG", "/env/service.toml"),
+        ("QK_CONFIG_FILE", "/legacy/service.yaml"),
+    ];
+
+    let explicit = |args: &[&str], environment: &[(&str, &str)]| {
+        inputs(args, environment).explicit_file().unwrap().unwrap()
+    };
+    assert_eq!(
+        explicit(&["--config", "/flag/service.toml"], &environment),
+        ExplicitConfigFile {
+            path: PathBuf::from("/flag/service.toml"),
+            origin: "--config".to_string(),
+        }
+    );
+    assert_eq!(
+        explicit(&["--dev", "--config=/flag/service.yaml"], &environment).path,
+        PathBuf::from("/flag/service.yaml")
+    );
+    assert_eq!(
+        explicit(&[], &environment).origin,
+        "QUEUE_KEEPER_CONFIG".to_string()
+    );
+    assert_eq!(
+        explicit(&[], &environment[1..]).path,
+        PathBuf::from("/legacy/service.yaml")
+    );
+    assert_eq!(inputs(&[], &[]).explicit_file().unwrap(), None);
+    assert!(matches!(
+        inputs(&["--config"], &[]).explicit_file(),
+        Err(ConfigLoadError::MissingConfigPath)
+    ));
+}
+
+/// A TOML file is applied over the defaults and `QUEUE_KEEPER__` variables
+/// over the file and `QK__` variables.
+#[test]
+fn test_toml_file_with_environment_overrides() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("service.toml");
+    std::fs::write(
+        &path,
+        "[server]\nport = 9000\nhost = \"127.0.0.1\"\n\n[logging]\nlevel = \"debug\"\n",
+    )
+    .unwrap();
+
+    let loaded = load(&inputs(
+        &["--config", path.to_str().unwrap()],
+        &[
+            ("QK__SERVER__PORT", "9100"),
+            ("QK__LOGGING__LEVEL", "warn"),
+            ("QUEUE_KEEPER__SERVER__PORT", "9200"),
+        ],
+    ))
+    .unwrap();
+
+    assert_eq!(loaded.config.server.port, 9200);
+    assert_eq!(loaded.config.server.host, "127.0.0.1");
+    assert_eq!(loaded.config.logging.level, "warn");
+    let file = loaded
+        .sources
+        .iter()
+        .find(|source| source.name == path.display().to_string())
+        .unwrap();
+    assert!(file.loaded);
+    assert_eq!(file.keys, ["logging.level", "server.host", "server.port"]);
+    assert!(loaded
+        .sources
+        .iter()
+        .any(|source| source.variables == ["QUEUE_KEEPER__SERVER__PORT"]));
+}
+
+/// A YAML file named by `QUEUE_KEEPER_CONFIG` is loaded.
+#[test]
+fn test_yaml_file_from_environment() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("service.yml");
+    std::fs::write(&path, "server:\n  port: 9300\n").unwrap();
+
+    let loaded = load(&inputs(
+        &[],
+        &[("QUEUE_KEEPER_CONFIG", path.to_str().unwrap())],
+    ))
+    .unwrap();
+
+    assert_eq!(loaded.config.server.port, 9300);
+}
+
+/// Problems are reported with the source to fix.
+#[test]
+fn test_errors_name_the_source() {
+    let dir = tempfile::tempdir().unwrap();
+
+    let missing = dir.path().join("missing.toml");
+    let error = load(&inputs(
+        &[],
+        &[("QUEUE_KEEPER_CONFIG", missing.to_str().unwrap())],
+    ))
+    .unwrap_err();
+    assert!(matches!(error, ConfigLoadError::FileNotFound { .. }));
+    assert!(error.to_string().contains("QUEUE_KEEPER_CONFIG"));
+
+    let ini = dir.path().join("service.ini");
+    std::fs::write(&ini, "").unwrap();
+    let error = load(&inputs(&["--config", ini.to_str().unwrap()], &[])).unwrap_err();
+    assert!(matches!(error, ConfigLoadError::UnsupportedFormat { .. }));
+
+    let error = load(&inputs(&[], &[("QUEUE_KEEPER__SERVER__PORT", "eighty")])).unwrap_err();
+    assert!(matches!(error, ConfigLoadError::Deserialize(_)));
+    assert!(error.to_string().contains("server.port"));
+
+    let error = load(&inputs(
+        &[],
+        &[("QUEUE_KEEPER__WEBHOOKS__RATE_LIMIT_PER_REPO", "0")],
+    ))
+    .unwrap_err();
+    assert!(matches!(error, ConfigLoadError::Invalid(_)));
+    assert!(error.to_string().contains("rate_limit_per_repo"));
+}
+
+/// Developer mode follows `--dev` only; files and environment variables
+/// cannot switch it on.
+#[test]
+fn test_developer_mode_only_from_flag() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("service.yaml");
+    std::fs::write(&path, "developer:\n  enabled: true\n").unwrap();
+
+    let loaded = load(&inputs(
+        &["--config", path.to_str().unwrap()],
+        &[
+            ("QK__DEVELOPER__ENABLED", "true"),
+            ("QK_ENVIRONMENT", "development"),
+        ],
+    ))
+    .unwrap();
+    assert!(!loaded.config.developer.enabled);
+    assert!(loaded.config.webhooks.require_signature);
+
+    let loaded = load(&ConfigInputs {
+        developer_mode: true,
+        ..inputs(&["--dev"], &[])
+    })
+    .unwrap();
+    assert!(loaded.config.developer.enabled);
+    assert!(!loaded.config.webhooks.require_signature);
+}
+
+/// `--dev` is refused in a production deployment.
+#[test]
+fn test_developer_mode_refused_in_production() {
+    for variable in ["QK_ENVIRONMENT", "QK__TELEMETRY__ENVIRONMENT"] {
+        let error = load(&ConfigInputs {
+            developer_mode: true,
+            ..inputs(&["--dev"], &[(variable, "prod")])
+        })
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            ConfigLoadError::DeveloperModeInProduction { .. }
+        ));
+        assert!(error.to_string().contains(variable));
+    }
+
+    assert!(load(&ConfigInputs {
+        developer_mode: true,
+        ..inputs(&["--dev"], &[("QK_ENVIRONMENT", "staging")])
+    })
+    .is_ok());
+}
@@ -11,38 +11,81 @@
 //! See specs/interfaces/http-service.md for complete specification.
 
 mod circuit_breaker;
+mod config_loader;
+mod queue_depth;
 mod signature_validator;
+mod telemetry;
 
-use circuit_breaker::queue::{CircuitBreakerQueueClient, CircuitBreakerQueueProvider};
+#[cfg(feature = "aws-sqs")]
+use circuit_breaker::queue::CircuitBreakerQueueClient;
+use circuit_breaker::queue::{queue_send_circuits, CircuitBreakerQueueProvider, QueueSendCircuits};
+use config_loader::{ConfigInputs, LoadedConfig};
+use queue_keeper_api::developer::ConfigWatcher;
+use queue_keeper_api::startup_report::{ConfigSource, FeatureFlag};
 use queue_keeper_api::{
-    start_server, BlobBackedEventStore, ProviderId, ProviderRegistry, QueueBackendConfig,
-    ServiceConfig, ServiceError, ServiceHealthChecker,
+    start_server, BlobBackedEventStore, ConfigError, DeduplicationStoreConfig, EventTombstones,
+    FreshnessMonitor, LogFilter, PreflightStatus, ProviderId, ProviderRegistry, QueueBackendConfig,
+    RateLimiterStoreConfig, RuntimeEnvironment, ServiceConfig, ServiceError, ServiceHealthChecker,
+    ShutdownRecorder, StartupReport, Supervisor, TokenCacheStoreConfig, TraceSampler,
 };
+#[cfg(feature = "azure-key-vault")]
+use queue_keeper_core::adapters::{memory_key_vault::InMemorySecretCache, AzureKeyVaultProvider};
+#[cfg(feature = "filesystem-storage")]
 use queue_keeper_core::adapters::{
-    memory_key_vault::InMemorySecretCache, AzureKeyVaultProvider, FilesystemBlobStorage,
+    FailoverBlobStorage, FilesystemBlobStorage, RetryingBlobStorage,
 };
 use queue_keeper_core::blob_storage::BlobStorage;
-use queue_keeper_core::bot_config::BotConfiguration;
-use queue_keeper_core::key_vault::{KeyVaultConfiguration, KeyVaultProvider, SecretName};
-use queue_keeper_core::webhook::{generic_provider::GenericWebhookProvider, GithubWebhookProvider};
-use queue_runtime::{
-    InMemoryConfig, ProviderConfig, QueueClientFactory, QueueConfig, StandardQueueClient,
+use queue_keeper_core::bot_config::{
+    BotConfiguration, ConfigurationWatcher, SharedBotConfiguration,
 };
-use signature_validator::{KeyVaultSignatureValidator, LiteralSignatureValidator};
+#[cfg(feature = "azure-key-vault")]
+use queue_keeper_core::key_vault::KeyVaultConfiguration;
+use queue_keeper_core::key_vault::{KeyVaultProvider, SecretName};
+#[cfg(feature = "filesystem-storage")]
+use queue_keeper_core::payload_encryption::PayloadEncryptor;
+use queue_keeper_core::webhook::{
+    generic_provider::GenericWebhookProvider, EnvelopeExtensionRegistry, GithubWebhookProvider,
+    ReplayGuard, SignatureValidator, SourceWebhookProvider, StorageSampler,
+};
+use queue_runtime::{InMemoryConfig, QueueConfig, StandardQueueClient};
+#[cfg(feature = "aws-sqs")]
+use queue_runtime::{ProviderConfig, QueueClientFactory};
+use signature_validator::{
+    DeveloperSignatureValidator, KeyVaultSignatureValidator, LiteralSignatureValidator,
+    RotatingSignatureValidator,
+};
+#[cfg(feature = "filesystem-storage")]
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use tracing::{error, info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use tracing_subscriber::{layer::SubscriberExt, reload, util::SubscriberInitExt};
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logging
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+/// Bot configuration file read when `BOT_CONFIGURATION` is not set.
+const BOT_CONFIG_FILE: &str = "config/bots.yaml";
+
+/// Interval between two checks of the bot configuration file for changes.
+const BOT_CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let developer_mode = developer_mode_requested();
+
+    // Initialize logging. Span export is filled in once the telemetry
+    // configuration is loaded; the filter is replaced by
+    // `PUT /admin/logging/level`.
+    let (tracing_layer, tracing_layer_handle) = telemetry::TracingLayer::new();
+    let (filter_layer, filter_handle) = reload::Layer::new(
+        tracing_subscriber::EnvFilter::try_from_default_env().unwrap_or_else(|_| {
+            if developer_mode {
+                "queue_keeper_service=debug,queue_keeper_api=debug,queue_keeper_core=debug,tower_http=debug".into()
+            } else {
                 "queue_keeper_service=info,queue_keeper_api=info,tower_http=debug".into()
-            }),
-        )
+            }
+        }),
+    );
+    tracing_subscriber::registry()
+        .with(tracing_layer)
+        .with(filter_layer)
         .with(tracing_subscriber::fmt::layer())
         .init();
 
@@ -51,80 +94,130 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // -------------------------------------------------------------------------
     // Load configuration
     //
-    // Sources (applied in order — later sources override earlier ones):
-    //  0. ServiceConfig::default()             — built-in Rust defaults (lowest priority)
-    //  1. /etc/queue-keeper/service.yaml       — system-wide defaults
-    //  2. ./config/service.yaml                — deployment-local override
-    //  3. Path given by QK_CONFIG_FILE env     — operator-specified file
-    //  4. Environment variables prefixed QK__  — (highest priority)
-    //     e.g. QK__SERVER__PORT=9090 sets server.port = 9090
-    //
-    // Source 0 is critical: without it, setting a single QK__SECURITY__*
-    // variable causes the `config` crate to create a partial `security` table.
-    // The deserialiser then fails for sibling fields that are absent from that
-    // partial table, even though they carry `#[serde(default)]` annotations.
-    // Pre-populating every field from the Rust defaults ensures the deserialiser
-    // always sees a fully-populated starting point before file/env overrides.
+    // Defaults, then /etc/queue-keeper/service.yaml, ./config/service.yaml,
+    // the file named by --config or QUEUE_KEEPER_CONFIG, and QK__ and
+    // QUEUE_KEEPER__ environment variables; see config_loader.
     // -------------------------------------------------------------------------
-    let defaults_json = serde_json::to_string(&ServiceConfig::default())
-        .expect("ServiceConfig::default() must be JSON-serialisable");
-
-    let mut config_builder = config::Config::builder()
-        .add_source(config::File::from_str(
-            &defaults_json,
-            config::FileFormat::Json,
-        ))
-        .add_source(
-            config::File::with_name("/etc/queue-keeper/service")
-                .required(false)
-                .format(config::FileFormat::Yaml),
-        )
-        .add_source(
-            config::File::with_name("config/service")
-                .required(false)
-                .format(config::FileFormat::Yaml),
+    let config_inputs = ConfigInputs::from_process(developer_mode);
+    let LoadedConfig {
+        config: service_config,
+        sources: config_sources,
+        relaxed_providers,
+    } = match config_loader::load(&config_inputs) {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            error!(error = %e, "Failed to load service configuration; aborting");
+            std::process::exit(3);
+        }
+    };
+    if let Ok(Some(explicit)) = config_inputs.explicit_file() {
+        info!(
+            path = %explicit.path.display(),
+            origin = %explicit.origin,
+            "Loaded configuration from explicit path"
         );
+    }
 
-    // Optional explicit path supplied by the operator.
-    if let Ok(explicit_path) = std::env::var("QK_CONFIG_FILE") {
-        if !explicit_path.is_empty() {
-            config_builder = config_builder.add_source(
-                config::File::with_name(&explicit_path)
-                    .required(true)
-                    .format(config::FileFormat::Yaml),
+    if developer_mode {
+        warn!(
+            "DEVELOPER MODE: webhook signatures are optional and mismatches are only logged. \
+             Never run developer mode in production."
+        );
+        for provider in relaxed_providers {
+            warn!(
+                provider = %provider,
+                "DEVELOPER MODE: provider has no webhook secret; unsigned webhooks are accepted"
             );
-            info!(path = %explicit_path, "Loading configuration from explicit path");
         }
     }
 
-    let config = match config_builder
-        .add_source(config::Environment::with_prefix("QK").separator("__"))
-        .build()
+    if let Err(e) = ensure_providers_compiled(&service_config) {
+        error!(error = %e, "Service configuration selects a provider missing from this build; aborting");
+        std::process::exit(3);
+    }
+
+    // -------------------------------------------------------------------------
+    // Apply outbound proxy and CA settings.
+    //
+    // github-bot-sdk, the queue-runtime providers and the Key Vault client
+    // build their own HTTP clients and read proxy/trust settings from the
+    // process environment, so this must run before any of them is created,
+    // and before the runtime starts threads that may read the environment.
+    // -------------------------------------------------------------------------
+    let _ca_bundle = match queue_keeper_api::outbound::apply_network_config(&service_config.network)
     {
-        Ok(cfg) => cfg,
+        Ok(ca_bundle) => ca_bundle,
         Err(e) => {
-            error!(error = %e, "Failed to build configuration; aborting");
+            error!(error = %e, "Failed to apply outbound network configuration; aborting");
             std::process::exit(3);
         }
     };
+    queue_keeper_api::outbound::apply_endpoint_config(&service_config.endpoints);
 
-    let mut service_config: ServiceConfig = match config.try_deserialize() {
-        Ok(sc) => sc,
-        Err(e) => {
-            error!(
-                error = %e,
-                "Could not deserialize service configuration; aborting. \
-                 Fix the configuration and restart."
-            );
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(serve(
+            developer_mode,
+            config_inputs,
+            service_config,
+            config_sources,
+            tracing_layer_handle,
+            LogFilter::new(filter_handle),
+        ))
+}
+
+/// Build the service from the loaded configuration and run it until it
+/// shuts down.
+async fn serve(
+    developer_mode: bool,
+    config_inputs: ConfigInputs,
+    mut service_config: ServiceConfig,
+    config_sources: Vec<ConfigSource>,
+    tracing_layer_handle: telemetry::TracingLayerHandle,
+    log_filter: LogFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    // -------------------------------------------------------------------------
+    // Start trace export.
+    //
+    // The sampler is shared with the admin API, so the sampling ratio can be
+    // changed without a restart. The OTLP exporter builds its own HTTP
+    // client, so this runs after the outbound network settings are applied.
+    // -------------------------------------------------------------------------
+    let environment =
+        std::env::var("QK__TELEMETRY__ENVIRONMENT").unwrap_or_else(|_| "production".to_string());
+    let trace_sampler = TraceSampler::new(
+        service_config
+            .telemetry
+            .initial_sampling_ratio(&environment),
+    );
+    let tracer_provider = if service_config.telemetry.enabled {
+        let provider = match telemetry::build_tracer_provider(
+            &service_config.telemetry,
+            trace_sampler.clone(),
+            &environment,
+        ) {
+            Ok(provider) => provider,
+            Err(e) => {
+                error!(error = %e, "Failed to create the OTLP trace exporter; aborting");
+                std::process::exit(3);
+            }
+        };
+        if let Err(e) = telemetry::install(&tracing_layer_handle, &provider) {
+            error!(error = %e, "Failed to start trace export; aborting");
             std::process::exit(3);
         }
+        info!(
+            protocol = ?service_config.telemetry.protocol,
+            endpoint = service_config.telemetry.endpoint.as_deref().unwrap_or("default"),
+            sampling_ratio = trace_sampler.ratio(),
+            "Exporting traces over OTLP"
+        );
+        Some(provider)
+    } else {
+        None
     };
 
-    if let Err(e) = service_config.validate() {
-        error!(error = %e, "Service configuration is invalid; aborting");
-        std::process::exit(3);
-    }
-
     // -------------------------------------------------------------------------
     // Initialise Azure Key Vault provider (when Key Vault secrets are used).
     //
@@ -135,7 +228,10 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // service_config.validate() already guarantees that `key_vault` is Some
     // with a non-empty vault_url whenever any provider uses KeyVault secrets,
     // so if we reach the `None` branch here no KV provider is needed.
+    // Builds without the `azure-key-vault` feature never have a KV provider;
+    // ensure_providers_compiled() has already rejected configs that need one.
     // -------------------------------------------------------------------------
+    #[cfg(feature = "azure-key-vault")]
     let key_vault_provider: Option<Arc<dyn KeyVaultProvider>> =
         if let Some(kv_cfg) = &service_config.key_vault {
             let core_config = KeyVaultConfiguration {
@@ -160,6 +256,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         } else {
             None
         };
+    #[cfg(not(feature = "azure-key-vault"))]
+    let key_vault_provider: Option<Arc<dyn KeyVaultProvider>> = None;
 
     // -------------------------------------------------------------------------
     // Build provider registry
@@ -172,6 +270,49 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // -------------------------------------------------------------------------
     let mut provider_registry = ProviderRegistry::new();
 
+    // service_config.validate() has already checked the extension settings.
+    let extensions = EnvelopeExtensionRegistry::from_config(&service_config.extensions)
+        .expect("extension configuration was validated at startup");
+    if !extensions.is_empty() {
+        info!(namespaces = ?extensions.namespaces(), "Envelope extensions enabled");
+    }
+    let storage_sampler = StorageSampler::from_config(&service_config.storage_sampling)
+        .expect("storage sampling configuration was validated at startup");
+    if !storage_sampler.is_empty() {
+        info!(
+            rules = service_config.storage_sampling.rules.len(),
+            "Payload storage sampling enabled"
+        );
+    }
+    // One guard for all GitHub providers: delivery IDs are unique across
+    // GitHub, whichever app or webhook sent them.
+    let replay_guard = ReplayGuard::from_config(&service_config.replay_protection).map(Arc::new);
+    if replay_guard.is_some() {
+        info!(
+            window_seconds = service_config.replay_protection.window.as_secs(),
+            "Replay protection enabled"
+        );
+    }
+    // One deduplication store for all GitHub providers, for the same reason.
+    let deduplication = match queue_keeper_api::deduplication::build_deduplication_store(
+        &service_config.deduplication,
+    )
+    .await
+    {
+        Ok(store) => store,
+        Err(e) => {
+            error!(error = %e, "Failed to initialise the deduplication store; aborting");
+            std::process::exit(3);
+        }
+    };
+    if deduplication.is_some() {
+        info!(
+            backend = service_config.deduplication.store.backend_name(),
+            ttl_seconds = service_config.deduplication.ttl.as_secs(),
+            "Delivery deduplication enabled"
+        );
+    }
+
     for provider_config in &service_config.providers {
         match ProviderId::new(&provider_config.id) {
             Ok(provider_id) => {
@@ -179,9 +320,35 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     provider_config,
                     key_vault_provider.as_ref(),
                 );
-                let processor = Arc::new(GithubWebhookProvider::new(validator, None, None));
+                let validator = relax_in_developer_mode(validator, developer_mode);
+                let mut provider = GithubWebhookProvider::new(validator, None, None)
+                    .with_provider_id(&provider_config.id)
+                    .with_extensions(extensions.clone())
+                    .with_storage_sampling(storage_sampler.clone());
+                if let Some(guard) = &replay_guard {
+                    provider = provider.with_replay_protection(guard.clone());
+                }
+                if let Some(store) = &deduplication {
+                    provider = provider.with_deduplication(store.clone());
+                }
+                let processor = Arc::new(provider);
                 provider_registry.register(provider_id, processor);
-                info!(provider = %provider_config.id, "Registered GitHub webhook provider from config");
+                match &provider_config.github_enterprise {
+                    Some(enterprise) => info!(
+                        provider = %provider_config.id,
+                        base_url = %enterprise.base_url,
+                        api_url = %service_config.api_url_for(&provider_config.id),
+                        enterprise_host = %enterprise.expected_host(),
+                        installations = provider_config.installation_ids.len(),
+                        signature_scheme = ?enterprise.signature_scheme,
+                        "Registered GitHub Enterprise Server webhook provider from config"
+                    ),
+                    None => info!(
+                        provider = %provider_config.id,
+                        api_url = %service_config.api_url_for(&provider_config.id),
+                        "Registered GitHub webhook provider from config"
+                    ),
+                }
             }
             Err(e) => {
                 error!(
@@ -196,7 +363,16 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Ensure the default GitHub provider is always available for backward
     // compatibility when no explicit provider configuration has been supplied.
     if !provider_registry.contains(GithubWebhookProvider::PROVIDER_ID) {
-        let github_processor = Arc::new(GithubWebhookProvider::new(None, None, None));
+        let mut provider = GithubWebhookProvider::new(None, None, None)
+            .with_extensions(extensions.clone())
+            .with_storage_sampling(storage_sampler.clone());
+        if let Some(guard) = &replay_guard {
+            provider = provider.with_replay_protection(guard.clone());
+        }
+        if let Some(store) = &deduplication {
+            provider = provider.with_deduplication(store.clone());
+        }
+        let github_processor = Arc::new(provider);
         provider_registry.register(
             ProviderId::new(GithubWebhookProvider::PROVIDER_ID)
                 .expect("GithubWebhookProvider::PROVIDER_ID is a valid provider ID"),
@@ -220,6 +396,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .generic_providers
         .iter()
         .map(|p| p.provider_id.clone())
+        .chain(
+            service_config
+                .webhook_sources
+                .iter()
+                .map(|s| s.provider_id().to_string()),
+        )
         .collect();
 
     for generic_config in std::mem::take(&mut service_config.generic_providers) {
@@ -228,10 +410,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         match ProviderId::new(&provider_id_str) {
             Ok(provider_id) => {
                 // Build a signature validator for this generic provider.
-                let validator = build_validator_from_generic_config(
-                    &generic_config,
+                let validator = build_validator_from_secret_config(
+                    &generic_config.provider_id,
+                    generic_config.webhook_secret.as_ref(),
                     key_vault_provider.as_ref(),
                 );
+                let validator = relax_in_developer_mode(validator, developer_mode);
 
                 let provider = GenericWebhookProvider::with_signature_validator(
                     generic_config,
@@ -266,8 +450,74 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     }
 
+    // -------------------------------------------------------------------------
+    // Wire webhook sources for other forges (GitLab, Bitbucket)
+    //
+    // Source requests carry no GitHub headers, so their IDs are part of
+    // `generic_provider_ids`; the source re-reads its own headers.
+    // -------------------------------------------------------------------------
+    for source_config in &service_config.webhook_sources {
+        let provider_id_str = source_config.provider_id();
+        match ProviderId::new(provider_id_str) {
+            Ok(provider_id) => {
+                let validator = build_validator_from_secret_config(
+                    provider_id_str,
+                    source_config.webhook_secret.as_ref(),
+                    key_vault_provider.as_ref(),
+                );
+                let mut provider =
+                    SourceWebhookProvider::new(source_config.kind.source(), validator)
+                        .with_provider_id(provider_id_str)
+                        .with_extensions(extensions.clone());
+                if let Some(guard) = &replay_guard {
+                    provider = provider.with_replay_protection(guard.clone());
+                }
+                if let Some(store) = &deduplication {
+                    provider = provider.with_deduplication(store.clone());
+                }
+                provider_registry.register(provider_id, Arc::new(provider));
+                info!(
+                    provider = %provider_id_str,
+                    kind = %source_config.kind,
+                    authenticated = source_config.webhook_secret.is_some(),
+                    "Registered webhook source from config"
+                );
+                if source_config.webhook_secret.is_none() {
+                    warn!(
+                        provider = %provider_id_str,
+                        "Webhook source has no webhook secret; unauthenticated deliveries are accepted"
+                    );
+                }
+            }
+            Err(e) => {
+                error!(
+                    provider = %provider_id_str,
+                    error = %e,
+                    "Skipping webhook source with invalid ID in configuration"
+                );
+            }
+        }
+    }
+
     let provider_registry = Arc::new(provider_registry);
-    let health_checker = Arc::new(ServiceHealthChecker::new(Arc::clone(&provider_registry)));
+
+    // Ages of the bot configuration and cached secrets, reported as gauges
+    // and as warnings in the deep health check.
+    let mut freshness = FreshnessMonitor::new(service_config.freshness.clone());
+    if let Some(key_vault) = &key_vault_provider {
+        freshness = freshness.with_key_vault(Arc::clone(key_vault));
+    }
+    let freshness = Arc::new(freshness);
+
+    // Background jobs are supervised and reported in the deep health check.
+    let supervisor = Arc::new(Supervisor::new(service_config.supervisor.clone()));
+
+    // Reads how the previous process stopped before this one's first
+    // checkpoint overwrites it.
+    let shutdown_recorder = Arc::new(ShutdownRecorder::new(
+        service_config.shutdown_report.clone(),
+        Arc::clone(&supervisor),
+    ));
 
     // -------------------------------------------------------------------------
     // Initialise blob storage for persisting processed events.
@@ -276,16 +526,118 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // endpoints. The default is filesystem-backed storage at ./data/events
     // (relative to the working directory). In production you would replace
     // this with an Azure Blob Storage adapter configured from the azure section
-    // of service.yaml.
+    // of service.yaml. Builds without the `filesystem-storage` feature run
+    // without event storage.
+    //
+    // Payload bodies are compressed as `event_storage.compression` selects,
+    // and encrypted with data keys wrapped by the Key Vault secret named by
+    // `event_storage.encryption_key_secret`. With `event_storage_failover.secondary_path` set, writes move to the
+    // secondary storage while the primary fails, and a supervised job copies
+    // them back once it recovers.
     // -------------------------------------------------------------------------
+    #[cfg(feature = "filesystem-storage")]
     let event_blob_path =
         std::env::var("QK_EVENT_STORAGE_PATH").unwrap_or_else(|_| "./data/events".to_string());
 
+    // Payloads must never be written in the clear once encryption is
+    // configured, so a missing Key Vault is fatal.
+    #[cfg(feature = "filesystem-storage")]
+    let payload_encryptor: Option<Arc<PayloadEncryptor>> =
+        match &service_config.event_storage.encryption_key_secret {
+            Some(secret) => match (&key_vault_provider, SecretName::new(secret)) {
+                (Some(key_vault), Ok(name)) => {
+                    info!(key_secret = %secret, "Payload encryption at rest enabled");
+                    Some(Arc::new(PayloadEncryptor::new(Arc::clone(key_vault), name)))
+                }
+                _ => {
+                    error!(
+                        key_secret = %secret,
+                        "Payload encryption is configured but Key Vault is unavailable; aborting"
+                    );
+                    std::process::exit(3);
+                }
+            },
+            None => None,
+        };
+
+    #[cfg(feature = "filesystem-storage")]
     let event_blob_storage: Option<Arc<dyn BlobStorage>> =
         match FilesystemBlobStorage::new(PathBuf::from(&event_blob_path)).await {
             Ok(storage) => {
-                info!(path = %event_blob_path, "Event blob storage initialised (filesystem)");
-                Some(Arc::new(storage))
+                let compression = service_config.event_storage.compression;
+                info!(
+                    path = %event_blob_path,
+                    compression = %compression,
+                    "Event blob storage initialised (filesystem)"
+                );
+                let storage = storage.with_compression(compression);
+                let storage = match &payload_encryptor {
+                    Some(encryptor) => storage.with_encryption(Arc::clone(encryptor)),
+                    None => storage,
+                };
+                let primary: Arc<dyn BlobStorage> = Arc::new(RetryingBlobStorage::new(
+                    Arc::new(storage),
+                    service_config.retry.blob_storage.clone(),
+                ));
+                match &service_config.event_storage_failover.secondary_path {
+                    Some(secondary_path) => {
+                        let secondary_compression = service_config
+                            .event_storage_failover
+                            .compression
+                            .unwrap_or(compression);
+                        let secondary = match FilesystemBlobStorage::new(secondary_path.clone())
+                            .await
+                        {
+                            Ok(secondary) => {
+                                let secondary = secondary.with_compression(secondary_compression);
+                                match &payload_encryptor {
+                                    Some(encryptor) => {
+                                        secondary.with_encryption(Arc::clone(encryptor))
+                                    }
+                                    None => secondary,
+                                }
+                            }
+                            Err(e) => {
+                                error!(
+                                    path = %secondary_path.display(),
+                                    error = %e,
+                                    "Failed to initialise secondary event storage; aborting"
+                                );
+                                std::process::exit(3);
+                            }
+                        };
+                        info!(
+                            path = %secondary_path.display(),
+                            "Secondary event storage initialised (filesystem)"
+                        );
+                        let failover = Arc::new(FailoverBlobStorage::new(
+                            primary,
+                            Arc::new(RetryingBlobStorage::new(
+                                Arc::new(secondary),
+                                service_config.retry.blob_storage.clone(),
+                            )),
+                            service_config.event_storage_failover.failover.clone(),
+                        ));
+                        let pending = Arc::clone(&failover);
+                        shutdown_recorder
+                            .track_residue("pending_storage_reconciliation", move || {
+                                pending.status().pending_reconciliation as u64
+                            });
+                        let reconciler = Arc::clone(&failover);
+                        supervisor.spawn("storage_reconciler", move |mut ctx| {
+                            let reconciler = reconciler.clone();
+                            async move {
+                                tokio::select! {
+                                    _ = reconciler.run() => {}
+                                    _ = ctx.stopped() => {}
+                                }
+                                Ok(())
+                            }
+                        });
+                        Some(failover as Arc<dyn BlobStorage>)
+                    }
+                    None => Some(primary),
+                }
             }
             Err(e) => {
                 warn!(
@@ -296,13 +648,46 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 None
             }
         };
+    #[cfg(not(feature = "filesystem-storage"))]
+    let event_blob_storage: Option<Arc<dyn BlobStorage>> = {
+        warn!(
+            "Built without the `filesystem-storage` feature; /api/events will return empty results"
+        );
+        None
+    };
 
-    let event_store: Arc<dyn queue_keeper_api::EventStore> =
-        if let Some(ref storage) = event_blob_storage {
-            Arc::new(BlobBackedEventStore::new(Arc::clone(storage)))
-        } else {
-            Arc::new(queue_keeper_api::DefaultEventStore)
+    // Sends to each bot queue are protected by their own circuit breaker;
+    // open circuits fail the deep health check.
+    let queue_circuits = Arc::new(queue_send_circuits());
+
+    let mut health_checker = ServiceHealthChecker::new(Arc::clone(&provider_registry))
+        .with_freshness(Arc::clone(&freshness))
+        .with_supervisor(Arc::clone(&supervisor))
+        .with_circuit_breakers(queue_circuits.clone());
+    if let Some(storage) = &event_blob_storage {
+        health_checker = health_checker.with_event_storage(Arc::clone(storage));
+    }
+    let health_checker = Arc::new(health_checker);
+
+    let event_store: Arc<dyn queue_keeper_api::EventStore> = if let Some(ref storage) =
+        event_blob_storage
+    {
+        let tombstones = match EventTombstones::open(&service_config.event_deletion) {
+            Ok(tombstones) => tombstones,
+            Err(e) => {
+                error!(error = %e, "Failed to load event tombstones; aborting");
+                std::process::exit(3);
+            }
         };
+        if !tombstones.is_empty() {
+            info!(pending_purges = tombstones.len(), "Loaded event tombstones");
+        }
+        Arc::new(
+            BlobBackedEventStore::new(Arc::clone(storage)).with_tombstones(Arc::new(tombstones)),
+        )
+    } else {
+        Arc::new(queue_keeper_api::DefaultEventStore)
+    };
 
     // -------------------------------------------------------------------------
     // Build queue client from runtime configuration.
@@ -314,25 +699,142 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     //
     // All providers are wrapped with the circuit breaker so cascading failures
     // are contained (spec assertion #11: 5 consecutive failures → circuit open
-    // for 30 s).  When the queue config is absent the default is in-memory.
+    // for 30 s).  Sends trip the circuit of their destination queue only.
+    // When the queue config is absent the default is in-memory.
     // -------------------------------------------------------------------------
-    let queue_client = match build_queue_client(&service_config.queue).await {
+    let queue_client = match build_queue_client(&service_config.queue, queue_circuits.clone()).await
+    {
         Ok(client) => client,
         Err(e) => {
             error!(error = %e, "Failed to initialise queue backend; aborting");
             std::process::exit(3);
         }
     };
+    let queue_depth_source = if service_config.queue_depth.enabled {
+        queue_depth::queue_depth_source(&service_config.queue).await
+    } else {
+        None
+    };
 
     // Load bot configuration from environment or file; fall back to empty config.
-    let bot_config = Arc::new(
-        BotConfiguration::load_from_env()
-            .or_else(|_| BotConfiguration::load_from_file(std::path::Path::new("config/bots.yaml")))
-            .unwrap_or_else(|_| BotConfiguration {
-                bots: vec![],
-                settings: queue_keeper_core::bot_config::BotConfigurationSettings::default(),
-            }),
+    // A configuration read from the file is reloaded when the file changes.
+    let (bot_config, bot_config_file) = match BotConfiguration::load_from_env() {
+        Ok(bot_config) => (bot_config, None),
+        Err(_) => (
+            BotConfiguration::load_from_file(std::path::Path::new(BOT_CONFIG_FILE)).unwrap_or_else(
+                |_| BotConfiguration {
+                    bots: vec![],
+                    settings: queue_keeper_core::bot_config::BotConfigurationSettings::default(),
+                },
+            ),
+            Some(std::path::PathBuf::from(BOT_CONFIG_FILE)),
+        ),
+    };
+    let bot_config = Arc::new(SharedBotConfiguration::new(bot_config));
+    freshness.record_bot_config_loaded();
+
+    // One structured report of how this process started, logged now and
+    // served at /debug/startup-report.
+    let mut startup_report = StartupReport::new(
+        &service_config,
+        config_sources,
+        compiled_features(),
+        RuntimeEnvironment::detect(),
     );
+    if service_config.key_vault.is_some() {
+        startup_report = startup_report.with_check(
+            "key_vault",
+            PreflightStatus::Passed,
+            "Key Vault provider initialised",
+        );
+    }
+    startup_report = if event_blob_storage.is_some() {
+        startup_report.with_check(
+            "event_storage",
+            PreflightStatus::Passed,
+            "event storage initialised",
+        )
+    } else {
+        startup_report.with_check(
+            "event_storage",
+            PreflightStatus::Warning,
+            "no event storage; /api/events returns empty results",
+        )
+    };
+    let bot_count = bot_config.current().bots.len();
+    startup_report = if bot_count == 0 {
+        startup_report.with_check(
+            "bot_configuration",
+            PreflightStatus::Warning,
+            "no bots configured; processed events are not routed to any queue",
+        )
+    } else {
+        startup_report.with_check(
+            "bot_configuration",
+            PreflightStatus::Passed,
+            format!("{} bot(s) configured", bot_count),
+        )
+    };
+    if developer_mode {
+        startup_report = startup_report.with_check(
+            "developer_mode",
+            PreflightStatus::Warning,
+            "developer mode is enabled; webhook signatures are not enforced",
+        );
+    }
+    if let Some(previous) = shutdown_recorder.previous() {
+        startup_report = if previous.reason.is_graceful() {
+            startup_report.with_check(
+                "previous_shutdown",
+                PreflightStatus::Passed,
+                "previous process shut down gracefully",
+            )
+        } else {
+            startup_report.with_check(
+                "previous_shutdown",
+                PreflightStatus::Warning,
+                "previous process did not shut down gracefully; see /debug/last-shutdown",
+            )
+        };
+    }
+    startup_report.log();
+
+    // Developer mode restarts the service when its configuration changes.
+    if developer_mode && service_config.developer.watch_config {
+        let interval = Duration::from_secs(service_config.developer.watch_interval_seconds);
+        let watched_files = watched_config_files(&config_inputs);
+        supervisor.spawn("config_watcher", move |mut ctx| {
+            let watched_files = watched_files.clone();
+            async move {
+                let mut watcher = ConfigWatcher::new(watched_files, interval);
+                tokio::select! {
+                    path = watcher.changed() => {
+                        warn!(path = %path.display(), "Configuration changed; restarting");
+                        restart_process()
+                    }
+                    _ = ctx.stopped() => Ok(()),
+                }
+            }
+        });
+    }
+
+    if let Some(path) = bot_config_file {
+        let bot_config = bot_config.clone();
+        supervisor.spawn("bot_config_watcher", move |mut ctx| {
+            let watcher = ConfigurationWatcher::new(
+                path.clone(),
+                bot_config.clone(),
+                BOT_CONFIG_WATCH_INTERVAL,
+            );
+            async move {
+                tokio::select! {
+                    _ = watcher.run() => {}
+                    _ = ctx.stopped() => {}
+                }
+                Ok(())
+            }
+        });
+    }
 
     info!(
         host = %service_config.server.host,
@@ -341,7 +843,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     );
 
     // Start the server
-    if let Err(e) = start_server(
+    let result = start_server(
         service_config,
         provider_registry,
         health_checker,
@@ -350,9 +852,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Some(queue_client),
         bot_config,
         event_blob_storage,
+        key_vault_provider,
+        Some(freshness),
+        supervisor,
+        Some(Arc::new(startup_report)),
+        Some(shutdown_recorder),
+        Some(trace_sampler),
+        Some(log_filter),
+        queue_depth_source,
+        Some(queue_circuits),
+        deduplication,
     )
-    .await
-    {
+    .await;
+
+    // Export the spans still queued before the process exits.
+    if let Some(provider) = tracer_provider {
+        if let Err(e) = provider.shutdown() {
+            warn!(error = %e, "Failed to flush exported traces");
+        }
+    }
+
+    if let Err(e) = result {
         error!("Failed to start server: {}", e);
 
         let exit_code = match e {
@@ -372,11 +892,163 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 // Private helpers
 // ============================================================================
 
+/// Whether developer mode was requested with `--dev`.
+fn developer_mode_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg == "--dev")
+}
+
+/// Accept mismatched signatures with a warning in developer mode.
+fn relax_in_developer_mode(
+    validator: Option<Arc<dyn SignatureValidator>>,
+    developer_mode: bool,
+) -> Option<Arc<dyn SignatureValidator>> {
+    match validator {
+        Some(validator) if developer_mode => {
+            Some(Arc::new(DeveloperSignatureValidator::new(validator)))
+        }
+        validator => validator,
+    }
+}
+
+/// Files whose changes restart the service in developer mode.
+///
+/// The bot configuration file is reloaded in place instead.
+fn watched_config_files(inputs: &ConfigInputs) -> Vec<std::path::PathBuf> {
+    let mut files: Vec<std::path::PathBuf> = [&inputs.system_file, &inputs.local_file]
+        .into_iter()
+        .flatten()
+        .flat_map(|name| {
+            ["", ".yaml", ".yml"]
+                .iter()
+                .map(move |extension| format!("{}{}", name, extension).into())
+        })
+        .collect();
+    if let Ok(Some(explicit)) = inputs.explicit_file() {
+        files.push(explicit.path);
+    }
+    files
+}
+
+/// Replace this process with a fresh copy started with the same arguments.
+///
+/// Only returns if the restart failed.
+fn restart_process() -> Result<(), String> {
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("cannot locate the service executable: {}", e))?;
+    let mut command = std::process::Command::new(&exe);
+    command.args(std::env::args_os().skip(1));
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::CommandExt;
+        // Sockets are close-on-exec, so the new process can bind the port.
+        let error = command.exec();
+        Err(format!("failed to restart {}: {}", exe.display(), error))
+    }
+    #[cfg(not(unix))]
+    {
+        command
+            .spawn()
+            .map_err(|e| format!("failed to restart {}: {}", exe.display(), e))?;
+        std::process::exit(0);
+    }
+}
+
+/// Cargo features of this build, for the startup report.
+fn compiled_features() -> Vec<FeatureFlag> {
+    vec![
+        FeatureFlag::new("azure-service-bus", cfg!(feature = "azure-service-bus")),
+        FeatureFlag::new("aws-sqs", cfg!(feature = "aws-sqs")),
+        FeatureFlag::new("azure-key-vault", cfg!(feature = "azure-key-vault")),
+        FeatureFlag::new("filesystem-storage", cfg!(feature = "filesystem-storage")),
+        FeatureFlag::new("redis-deduplication", cfg!(feature = "redis-deduplication")),
+        FeatureFlag::new("redis-rate-limiter", cfg!(feature = "redis-rate-limiter")),
+        FeatureFlag::new("redis-token-cache", cfg!(feature = "redis-token-cache")),
+        FeatureFlag::new("wasm-transforms", cfg!(feature = "wasm-transforms")),
+    ]
+}
+
+/// Reject a configuration that selects a provider this binary was built
+/// without.
+///
+/// Runs before any provider is constructed so that a slimmed-down build fails
+/// at startup and names the cargo feature to enable, instead of failing later
+/// with a less obvious error.
+fn ensure_providers_compiled(config: &ServiceConfig) -> Result<(), ConfigError> {
+    let queue_feature = match &config.queue {
+        QueueBackendConfig::InMemory { .. } => None,
+        QueueBackendConfig::AzureServiceBus { .. } => {
+            (!cfg!(feature = "azure-service-bus")).then_some("azure-service-bus")
+        }
+        QueueBackendConfig::AwsSqs { .. } => (!cfg!(feature = "aws-sqs")).then_some("aws-sqs"),
+    };
+    if let Some(feature) = queue_feature {
+        return Err(ConfigError::ProviderNotCompiled {
+            component: "Queue".to_string(),
+            provider: config.queue.provider_name().to_string(),
+            feature: feature.to_string(),
+        });
+    }
+
+    if config.key_vault.is_some() && !cfg!(feature = "azure-key-vault") {
+        return Err(ConfigError::ProviderNotCompiled {
+            component: "Secret".to_string(),
+            provider: "azure_key_vault".to_string(),
+            feature: "azure-key-vault".to_string(),
+        });
+    }
+
+    if matches!(
+        config.security.rate_limiter_store,
+        RateLimiterStoreConfig::Redis { .. }
+    ) && !cfg!(feature = "redis-rate-limiter")
+    {
+        return Err(ConfigError::ProviderNotCompiled {
+            component: "Rate limiter store".to_string(),
+            provider: config
+                .security
+                .rate_limiter_store
+                .backend_name()
+                .to_string(),
+            feature: "redis-rate-limiter".to_string(),
+        });
+    }
+
+    if matches!(
+        config.token_cache.store,
+        TokenCacheStoreConfig::Redis { .. }
+    ) && !cfg!(feature = "redis-token-cache")
+    {
+        return Err(ConfigError::ProviderNotCompiled {
+            component: "Token cache store".to_string(),
+            provider: config.token_cache.store.backend_name().to_string(),
+            feature: "redis-token-cache".to_string(),
+        });
+    }
+
+    if matches!(
+        config.deduplication.store,
+        DeduplicationStoreConfig::Redis { .. }
+    ) && !cfg!(feature = "redis-deduplication")
+    {
+        return Err(ConfigError::ProviderNotCompiled {
+            component: "Deduplication store".to_string(),
+            provider: config.deduplication.store.backend_name().to_string(),
+            feature: "redis-deduplication".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
 /// Build a circuit-breaker-wrapped [`QueueClient`] from the service's queue
 /// backend configuration.
 ///
 /// All provider variants are wrapped with [`CircuitBreakerQueueProvider`] so
-/// cascading failures are contained regardless of the chosen backend.
+/// cascading failures are contained regardless of the chosen backend. Sends
+/// use the per-queue circuit breakers of `send_circuits`.
+/// Providers whose cargo feature is disabled are not compiled in; see
+/// [`ensure_providers_compiled`].
 ///
 /// # Errors
 ///
@@ -384,9 +1056,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 /// so the caller can log it and `std::process::exit`.
 async fn build_queue_client(
     queue_config: &QueueBackendConfig,
+    send_circuits: Arc<QueueSendCircuits>,
 ) -> Result<Arc<dyn queue_runtime::QueueClient>, String> {
+    #[cfg(feature = "azure-service-bus")]
     use queue_runtime::providers::{AzureAuthMethod, AzureServiceBusProvider};
-    use queue_runtime::{AwsSqsConfig, AzureServiceBusConfig, InMemoryProvider};
+    #[cfg(feature = "aws-sqs")]
+    use queue_runtime::AwsSqsConfig;
+    #[cfg(feature = "azure-service-bus")]
+    use queue_runtime::AzureServiceBusConfig;
+    use queue_runtime::InMemoryProvider;
 
     match queue_config {
         QueueBackendConfig::InMemory { max_queue_size } => {
@@ -399,11 +1077,12 @@ async fn build_queue_client(
                 cfg.max_queue_size = *size;
             }
             let provider = Arc::new(InMemoryProvider::new(cfg));
-            let cb = CircuitBreakerQueueProvider::new(provider);
+            let cb = CircuitBreakerQueueProvider::new(provider, send_circuits);
             let client = StandardQueueClient::new(Box::new(cb), QueueConfig::default());
             Ok(Arc::new(client))
         }
 
+        #[cfg(feature = "azure-service-bus")]
         QueueBackendConfig::AzureServiceBus {
             namespace,
             connection_string,
@@ -453,11 +1132,12 @@ async fn build_queue_client(
 
             info!("Azure Service Bus connection established");
 
-            let cb = CircuitBreakerQueueProvider::new(Arc::new(provider));
+            let cb = CircuitBreakerQueueProvider::new(Arc::new(provider), send_circuits);
             let client = StandardQueueClient::new(Box::new(cb), QueueConfig::default());
             Ok(Arc::new(client))
         }
 
+        #[cfg(feature = "aws-sqs")]
         QueueBackendConfig::AwsSqs {
             region,
             use_fifo_queues,
@@ -488,46 +1168,108 @@ async fn build_queue_client(
             // QueueClientFactory returns a Box<dyn QueueClient> via its own internal
             // provider — wrap it with CircuitBreakerQueueClient so cascading failures
             // are contained the same way they are for Azure Service Bus.
-            let cb_client = CircuitBreakerQueueClient::new(Arc::from(client));
+            let cb_client = CircuitBreakerQueueClient::new(Arc::from(client), send_circuits);
             Ok(Arc::new(cb_client))
         }
+
+        #[allow(unreachable_patterns)]
+        other => Err(ConfigError::ProviderNotCompiled {
+            component: "Queue".to_string(),
+            provider: other.provider_name().to_string(),
+            feature: other.provider_name().replace('_', "-"),
+        }
+        .to_string()),
     }
 }
 
 /// Build a [`SignatureValidator`] from a standard [`ProviderConfig`].
 ///
+/// The main `secret` is built by [`build_validator_from_secret`]. When the
+/// provider has `additional_secrets`, the result is a
+/// [`RotatingSignatureValidator`] trying them after the main secret; an
+/// additional secret that cannot be built is logged and left out.
+///
+/// Returns `None` (no signature validation) when the provider has no
+/// `secret`, or it cannot be used.
+fn build_validator_from_provider_config(
+    provider_config: &queue_keeper_api::ProviderConfig,
+    key_vault: Option<&Arc<dyn KeyVaultProvider>>,
+) -> Option<Arc<dyn queue_keeper_core::webhook::SignatureValidator>> {
+    let scheme = provider_config.signature_scheme();
+    let primary = build_validator_from_secret(
+        &provider_config.id,
+        provider_config.secret.as_ref()?,
+        scheme,
+        provider_config.per_repository_secrets,
+        key_vault,
+    )?;
+    if provider_config.additional_secrets.is_empty() {
+        return Some(primary);
+    }
+
+    let mut rotating = RotatingSignatureValidator::new(primary);
+    for additional in &provider_config.additional_secrets {
+        match build_validator_from_secret(
+            &provider_config.id,
+            &additional.source,
+            scheme,
+            provider_config.per_repository_secrets,
+            key_vault,
+        ) {
+            Some(validator) => {
+                rotating = rotating.with_secret(&additional.label, additional.retired, validator);
+            }
+            None => error!(
+                provider = %provider_config.id,
+                secret = %additional.label,
+                "Additional webhook secret could not be loaded; it will not be tried"
+            ),
+        }
+    }
+    Some(Arc::new(rotating))
+}
+
+/// Build a [`SignatureValidator`] for one secret source of a standard provider.
+///
 /// - `Literal` secret → [`LiteralSignatureValidator`] (dev/test only, emits `WARN`).
 /// - `EnvironmentVariable` secret → [`LiteralSignatureValidator`] seeded from the
 ///   named env var at startup (cloud-agnostic; emits `WARN`).
 /// - `KeyVault` secret → [`KeyVaultSignatureValidator`] backed by the provided
-///   [`KeyVaultProvider`]. `key_vault` must be `Some` here; `ServiceConfig::validate()`
-///   already guarantees this.
-/// - `None` secret → returns `None` (no signature validation).
-fn build_validator_from_provider_config(
-    provider_config: &queue_keeper_api::ProviderConfig,
+///   [`KeyVaultProvider`], resolving secrets per repository when
+///   `per_repository` is set. `key_vault` must be `Some` here;
+///   `ServiceConfig::validate()` already guarantees this.
+///
+/// Returns `None`, after logging why, when the source cannot be used.
+fn build_validator_from_secret(
+    provider_id: &str,
+    secret: &queue_keeper_api::ProviderSecretConfig,
+    scheme: queue_keeper_api::GitHubSignatureScheme,
+    per_repository: bool,
     key_vault: Option<&Arc<dyn KeyVaultProvider>>,
 ) -> Option<Arc<dyn queue_keeper_core::webhook::SignatureValidator>> {
     use queue_keeper_api::ProviderSecretConfig;
 
-    match provider_config.secret.as_ref()? {
-        ProviderSecretConfig::Literal { value } => {
-            Some(Arc::new(LiteralSignatureValidator::new(value.clone())))
-        }
+    match secret {
+        ProviderSecretConfig::Literal { value } => Some(Arc::new(
+            LiteralSignatureValidator::new(value.clone()).with_signature_scheme(scheme),
+        )),
         ProviderSecretConfig::EnvironmentVariable { env_var_name } => {
             match std::env::var(env_var_name) {
                 Ok(value) if !value.is_empty() => {
                     warn!(
-                        provider = %provider_config.id,
+                        provider = %provider_id,
                         env_var = %env_var_name,
                         "Provider uses an environment-variable secret. \
                          This is acceptable for CI and on-premises deployments \
                          but prefer Key Vault for production."
                     );
-                    Some(Arc::new(LiteralSignatureValidator::new(value)))
+                    Some(Arc::new(
+                        LiteralSignatureValidator::new(value).with_signature_scheme(scheme),
+                    ))
                 }
                 Ok(_) => {
                     error!(
-                        provider = %provider_config.id,
+                        provider = %provider_id,
                         env_var = %env_var_name,
                         "Environment variable for webhook secret is set but empty; \
                          signature validation will be SKIPPED"
@@ -536,7 +1278,7 @@ fn build_validator_from_provider_config(
                 }
                 Err(_) => {
                     error!(
-                        provider = %provider_config.id,
+                        provider = %provider_id,
                         env_var = %env_var_name,
                         "Environment variable for webhook secret is not set; \
                          signature validation will be SKIPPED"
@@ -551,7 +1293,7 @@ fn build_validator_from_provider_config(
                 None => {
                     // Defensive guard — validate() prevents this in practice.
                     error!(
-                        provider = %provider_config.id,
+                        provider = %provider_id,
                         secret_name = %secret_name,
                         "Key Vault secret configured but no Key Vault provider is available; \
                          signature validation will be SKIPPED"
@@ -560,13 +1302,14 @@ fn build_validator_from_provider_config(
                 }
             };
             match SecretName::new(secret_name.as_str()) {
-                Ok(name) => Some(Arc::new(KeyVaultSignatureValidator::new(
-                    Arc::clone(kv),
-                    name,
-                ))),
+                Ok(name) => Some(Arc::new(
+                    KeyVaultSignatureValidator::new(Arc::clone(kv), name)
+                        .with_signature_scheme(scheme)
+                        .with_per_repository_secrets(per_repository),
+                )),
                 Err(e) => {
                     error!(
-                        provider = %provider_config.id,
+                        provider = %provider_id,
                         secret_name = %secret_name,
                         error = %e,
                         "Invalid Key Vault secret name; signature validation will be SKIPPED"
@@ -578,16 +1321,18 @@ fn build_validator_from_provider_config(
     }
 }
 
-/// Build a [`SignatureValidator`] from a [`GenericProviderConfig`] signature section.
+/// Build a [`SignatureValidator`] from the `webhook_secret` of a generic
+/// provider or webhook source registered as `provider_id`.
 ///
 /// Follows the same logic as [`build_validator_from_provider_config`].
-fn build_validator_from_generic_config(
-    generic_config: &queue_keeper_core::webhook::generic_provider::GenericProviderConfig,
+fn build_validator_from_secret_config(
+    provider_id: &str,
+    webhook_secret: Option<&queue_keeper_core::webhook::generic_provider::WebhookSecretConfig>,
     key_vault: Option<&Arc<dyn KeyVaultProvider>>,
 ) -> Option<Arc<dyn queue_keeper_core::webhook::SignatureValidator>> {
     use queue_keeper_core::webhook::generic_provider::WebhookSecretConfig;
 
-    match generic_config.webhook_secret.as_ref()? {
+    match webhook_secret? {
         WebhookSecretConfig::Literal { value } => {
             Some(Arc::new(LiteralSignatureValidator::new(value.clone())))
         }
@@ -595,7 +1340,7 @@ fn build_validator_from_generic_config(
             match std::env::var(env_var_name) {
                 Ok(value) if !value.is_empty() => {
                     warn!(
-                        provider = %generic_config.provider_id,
+                        provider = %provider_id,
                         env_var = %env_var_name,
                         "Provider uses an environment-variable secret. \
                          This is acceptable for CI and on-premises deployments \
@@ -605,7 +1350,7 @@ fn build_validator_from_generic_config(
                 }
                 Ok(_) => {
                     error!(
-                        provider = %generic_config.provider_id,
+                        provider = %provider_id,
                         env_var = %env_var_name,
                         "Environment variable for webhook secret is set but empty; \
                          signature validation will be SKIPPED"
@@ -614,7 +1359,7 @@ fn build_validator_from_generic_config(
                 }
                 Err(_) => {
                     error!(
-                        provider = %generic_config.provider_id,
+                        provider = %provider_id,
                         env_var = %env_var_name,
                         "Environment variable for webhook secret is not set; \
                          signature validation will be SKIPPED"
@@ -628,7 +1373,7 @@ fn build_validator_from_generic_config(
                 Some(kv) => kv,
                 None => {
                     error!(
-                        provider = %generic_config.provider_id,
+                        provider = %provider_id,
                         secret_name = %secret_name,
                         "Key Vault secret configured but no Key Vault provider is available; \
                          signature validation will be SKIPPED"
@@ -643,7 +1388,7 @@ fn build_validator_from_generic_config(
                 ))),
                 Err(e) => {
                     error!(
-                        provider = %generic_config.provider_id,
+                        provider = %provider_id,
                         secret_name = %secret_name,
                         error = %e,
                         "Invalid Key Vault secret name; signature validation will be SKIPPED"
@@ -0,0 +1,118 @@
+//! Queue depth sources for the configured queue backend.
+//!
+//! Only AWS SQS reports depths: each sample is a `GetQueueAttributes` call
+//! reading `ApproximateNumberOfMessages`. Azure Service Bus depths need the
+//! management API, which queue-runtime does not expose, and the in-memory
+//! backend has nothing worth sampling; with those backends
+//! [`queue_depth_source`] returns `None` and no sampler runs.
+
+use queue_keeper_api::{QueueBackendConfig, QueueDepthSource};
+use std::sync::Arc;
+
+#[cfg(feature = "aws-sqs")]
+pub use sqs::SqsQueueDepthSource;
+
+/// The depth source of `queue_config`, if its backend can report depths.
+pub async fn queue_depth_source(
+    queue_config: &QueueBackendConfig,
+) -> Option<Arc<dyn QueueDepthSource>> {
+    match queue_config {
+        #[cfg(feature = "aws-sqs")]
+        QueueBackendConfig::AwsSqs {
+            region,
+            use_fifo_queues,
+        } => Some(Arc::new(
+            SqsQueueDepthSource::new(region, *use_fifo_queues).await,
+        )),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "aws-sqs")]
+mod sqs {
+    use async_trait::async_trait;
+    use aws_sdk_sqs::types::QueueAttributeName;
+    use queue_keeper_api::QueueDepthSource;
+    use std::{collections::HashMap, sync::Mutex};
+
+    /// Reads the depth of SQS queues.
+    ///
+    /// Queue URLs are looked up once per queue and cached. Endpoint
+    /// overrides are picked up from `AWS_ENDPOINT_URL_SQS`, which the
+    /// `endpoints.aws.sqs_endpoint_url` setting exports.
+    pub struct SqsQueueDepthSource {
+        client: aws_sdk_sqs::Client,
+        use_fifo_queues: bool,
+        queue_urls: Mutex<HashMap<String, String>>,
+    }
+
+    impl SqsQueueDepthSource {
+        /// Create a source for the queues of `region`, with credentials from
+        /// the standard AWS credential chain.
+        pub async fn new(region: &str, use_fifo_queues: bool) -> Self {
+            let config = aws_config::defaults(aws_config::BehaviorVersion::latest())
+                .region(aws_config::Region::new(region.to_string()))
+                .load()
+                .await;
+            Self {
+                client: aws_sdk_sqs::Client::new(&config),
+                use_fifo_queues,
+                queue_urls: Mutex::new(HashMap::new()),
+            }
+        }
+
+        /// SQS name of `queue_name`; FIFO queues carry a `.fifo` suffix.
+        fn sqs_queue_name(&self, queue_name: &str) -> String {
+            if self.use_fifo_queues && !queue_name.ends_with(".fifo") {
+                format!("{}.fifo", queue_name)
+            } else {
+                queue_name.to_string()
+            }
+        }
+
+        async fn queue_url(&self, queue_name: &str) -> Result<String, String> {
+            if let Some(url) = self.queue_urls.lock().unwrap().get(queue_name) {
+                return Ok(url.clone());
+            }
+            let output = self
+                .client
+                .get_queue_url()
+                .queue_name(self.sqs_queue_name(queue_name))
+                .send()
+                .await
+                .map_err(|e| format!("GetQueueUrl failed: {}", e))?;
+            let url = output
+                .queue_url()
+                .ok_or_else(|| "GetQueueUrl returned no URL".to_string())?
+                .to_string();
+            self.queue_urls
+                .lock()
+                .unwrap()
+                .insert(queue_name.to_string(), url.clone());
+            Ok(url)
+        }
+    }
+
+    #[async_trait]
+    impl QueueDepthSource for SqsQueueDepthSource {
+        async fn queue_depth(&self, queue_name: &str) -> Result<u64, String> {
+            let url = self.queue_url(queue_name).await?;
+            let output = self
+                .client
+                .get_queue_attributes()
+                .queue_url(url)
+                .attribute_names(QueueAttributeName::ApproximateNumberOfMessages)
+                .send()
+                .await
+                .map_err(|e| format!("GetQueueAttributes failed: {}", e))?;
+            output
+                .attributes()
+                .and_then(|attributes| {
+                    attributes.get(&QueueAttributeName::ApproximateNumberOfMessages)
+                })
+                .ok_or_else(|| "ApproximateNumberOfMessages was not returned".to_string())?
+                .parse()
+                .map_err(|e| format!("Invalid ApproximateNumberOfMessages: {}", e))
+        }
+    }
+}
@@ -10,15 +10,38 @@
 //! |------|-----|---------|
 //! | [`LiteralSignatureValidator`] | Dev / CI with a hard-coded secret | Not for production |
 //! | [`KeyVaultSignatureValidator`] | Production with Azure Key Vault | Production-safe |
+//! | [`RotatingSignatureValidator`] | Several secrets while one is rotated | As its secrets |
+//! | [`DeveloperSignatureValidator`] | Developer mode; mismatches are only logged | Not for production |
+//!
+//! Both accept `sha256=` signatures. Legacy `sha1=` signatures (sent by GitHub
+//! Enterprise Server releases before 3.0) are rejected unless the validator is
+//! built with [`GitHubSignatureScheme::Sha256OrSha1`].
+//!
+//! A [`KeyVaultSignatureValidator`] built with
+//! [`with_per_repository_secrets`](KeyVaultSignatureValidator::with_per_repository_secrets)
+//! resolves the secret of the repository a delivery names, falling back to
+//! its owner's secret and then to the configured one.
 
 use async_trait::async_trait;
 use hmac::{Hmac, KeyInit, Mac};
+use queue_keeper_api::config::GitHubSignatureScheme;
 use queue_keeper_core::key_vault::{KeyVaultError, KeyVaultProvider, SecretName};
-use queue_keeper_core::webhook::{SecretError, SignatureValidator};
+use queue_keeper_core::webhook::{
+    CandidateSecret, SecretError, SignatureValidator, PRIMARY_SECRET_LABEL,
+};
 use queue_keeper_core::ValidationError;
+use sha1::Sha1;
 use sha2::Sha256;
-use std::sync::Arc;
-use tracing::{instrument, warn};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, instrument, warn};
+
+/// How long a per-repository secret found missing is not looked up again.
+///
+/// Matches the Key Vault provider's cache TTL, so a newly added secret is
+/// picked up as quickly as a rotated one.
+const MISSING_SECRET_TTL: Duration = Duration::from_secs(300);
 
 // ============================================================================
 // Private helpers
@@ -71,6 +94,67 @@ fn validate_hmac_sha256(
         })
 }
 
+/// Validate a legacy HMAC-SHA1 `sha1=<hex>` signature in constant time.
+///
+/// # Errors
+///
+/// - [`ValidationError::InvalidFormat`] — `signature` is not valid hex.
+/// - [`ValidationError::InvalidFormat`] — the HMAC digest does not match.
+fn validate_hmac_sha1(
+    payload: &[u8],
+    signature: &str,
+    secret_key: &str,
+) -> Result<(), ValidationError> {
+    type HmacSha1 = Hmac<Sha1>;
+
+    let sig_bytes =
+        hex::decode(signature.strip_prefix("sha1=").unwrap_or(signature)).map_err(|_| {
+            ValidationError::InvalidFormat {
+                field: "signature".to_string(),
+                message: "signature is not valid hex".to_string(),
+            }
+        })?;
+
+    let mut mac = HmacSha1::new_from_slice(secret_key.as_bytes()).map_err(|_| {
+        ValidationError::InvalidFormat {
+            field: "secret".to_string(),
+            message: "secret cannot be used as HMAC key".to_string(),
+        }
+    })?;
+    mac.update(payload);
+
+    mac.verify_slice(&sig_bytes)
+        .map_err(|_| ValidationError::InvalidFormat {
+            field: "signature".to_string(),
+            message: "HMAC-SHA1 digest does not match".to_string(),
+        })
+}
+
+/// Validate a webhook signature according to the provider's signature scheme.
+///
+/// `sha1=` signatures are only verified when `scheme` allows them; otherwise
+/// they are rejected outright so a downgrade to SHA-1 is never possible for
+/// providers that have not opted in.
+fn validate_signature_for_scheme(
+    payload: &[u8],
+    signature: &str,
+    secret_key: &str,
+    scheme: GitHubSignatureScheme,
+) -> Result<(), ValidationError> {
+    if signature.starts_with("sha1=") {
+        return match scheme {
+            GitHubSignatureScheme::Sha256OrSha1 => {
+                validate_hmac_sha1(payload, signature, secret_key)
+            }
+            GitHubSignatureScheme::Sha256 => Err(ValidationError::InvalidFormat {
+                field: "signature".to_string(),
+                message: "SHA-1 signatures are not accepted for this provider".to_string(),
+            }),
+        };
+    }
+    validate_hmac_sha256(payload, signature, secret_key)
+}
+
 /// Map a [`KeyVaultError`] to the [`SecretError`] type used by the webhook layer.
 fn map_key_vault_error(e: KeyVaultError, name: &SecretName) -> SecretError {
     match e {
@@ -115,6 +199,7 @@ fn map_key_vault_error(e: KeyVaultError, name: &SecretName) -> SecretError {
 /// ```
 pub struct LiteralSignatureValidator {
     secret: String,
+    signature_scheme: GitHubSignatureScheme,
 }
 
 impl LiteralSignatureValidator {
@@ -132,7 +217,16 @@ impl LiteralSignatureValidator {
              literal secrets in configuration are not safe for production. \
              Migrate to Azure Key Vault before deploying."
         );
-        Self { secret }
+        Self {
+            secret,
+            signature_scheme: GitHubSignatureScheme::default(),
+        }
+    }
+
+    /// Set the accepted signature scheme (default: SHA-256 only).
+    pub fn with_signature_scheme(mut self, scheme: GitHubSignatureScheme) -> Self {
+        self.signature_scheme = scheme;
+        self
     }
 }
 
@@ -140,6 +234,7 @@ impl std::fmt::Debug for LiteralSignatureValidator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("LiteralSignatureValidator")
             .field("secret", &"<REDACTED>")
+            .field("signature_scheme", &self.signature_scheme)
             .finish()
     }
 }
@@ -168,7 +263,7 @@ impl SignatureValidator for LiteralSignatureValidator {
         signature: &str,
         secret_key: &str,
     ) -> Result<(), ValidationError> {
-        validate_hmac_sha256(payload, signature, secret_key)
+        validate_signature_for_scheme(payload, signature, secret_key, self.signature_scheme)
     }
 
     /// Return the literal secret for any event type.
@@ -207,9 +302,20 @@ impl SignatureValidator for LiteralSignatureValidator {
 ///   provider's in-memory cache for up to 5 minutes).
 /// - `Debug` output shows only the secret name (the Key Vault lookup key),
 ///   which is not sensitive.
+///
+/// # Per-repository secrets
+///
+/// With per-repository secrets enabled, a delivery from `owner/repo` is
+/// validated with the first of `{secret_name}--{owner}--{repo}`,
+/// `{secret_name}--{owner}` and `secret_name` that exists. Names found
+/// missing are remembered for [`MISSING_SECRET_TTL`] so repositories without
+/// their own secret do not cost a Key Vault request per delivery.
 pub struct KeyVaultSignatureValidator {
     provider: Arc<dyn KeyVaultProvider>,
     secret_name: SecretName,
+    signature_scheme: GitHubSignatureScheme,
+    per_repository: bool,
+    missing: Mutex<HashMap<SecretName, Instant>>,
 }
 
 impl KeyVaultSignatureValidator {
@@ -223,14 +329,52 @@ impl KeyVaultSignatureValidator {
         Self {
             provider,
             secret_name,
+            signature_scheme: GitHubSignatureScheme::default(),
+            per_repository: false,
+            missing: Mutex::new(HashMap::new()),
         }
     }
+
+    /// Set the accepted signature scheme (default: SHA-256 only).
+    pub fn with_signature_scheme(mut self, scheme: GitHubSignatureScheme) -> Self {
+        self.signature_scheme = scheme;
+        self
+    }
+
+    /// Resolve secrets per repository (default: off).
+    pub fn with_per_repository_secrets(mut self, enabled: bool) -> Self {
+        self.per_repository = enabled;
+        self
+    }
+
+    /// Whether `name` was found missing within [`MISSING_SECRET_TTL`].
+    fn recently_missing(&self, name: &SecretName) -> bool {
+        let mut missing = self.missing.lock().unwrap();
+        match missing.get(name) {
+            Some(since) if since.elapsed() < MISSING_SECRET_TTL => true,
+            Some(_) => {
+                missing.remove(name);
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Remember that `name` does not exist.
+    fn record_missing(&self, name: &SecretName) {
+        self.missing
+            .lock()
+            .unwrap()
+            .insert(name.clone(), Instant::now());
+    }
 }
 
 impl std::fmt::Debug for KeyVaultSignatureValidator {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("KeyVaultSignatureValidator")
             .field("secret_name", &self.secret_name.as_str())
+            .field("signature_scheme", &self.signature_scheme)
+            .field("per_repository", &self.per_repository)
             .finish()
     }
 }
@@ -250,7 +394,7 @@ impl SignatureValidator for KeyVaultSignatureValidator {
         signature: &str,
         secret_key: &str,
     ) -> Result<(), ValidationError> {
-        validate_hmac_sha256(payload, signature, secret_key)
+        validate_signature_for_scheme(payload, signature, secret_key, self.signature_scheme)
     }
 
     /// Retrieve the webhook secret from Azure Key Vault.
@@ -273,12 +417,336 @@ impl SignatureValidator for KeyVaultSignatureValidator {
             .map_err(|e| map_key_vault_error(e, &self.secret_name))
     }
 
+    /// Retrieve the webhook secret of `repository` from Azure Key Vault.
+    ///
+    /// Without per-repository secrets this is
+    /// [`get_webhook_secret`](Self::get_webhook_secret). Otherwise the
+    /// repository's and then its owner's secret are tried before the
+    /// configured one.
+    ///
+    /// # Errors
+    ///
+    /// As [`get_webhook_secret`](Self::get_webhook_secret); a missing
+    /// repository or owner secret is not an error.
+    #[instrument(skip(self), fields(secret_name = %self.secret_name))]
+    async fn get_repository_webhook_secret(
+        &self,
+        event_type: &str,
+        repository: &str,
+    ) -> Result<String, SecretError> {
+        if self.per_repository {
+            let names = self.secret_name.repository_candidates(repository);
+            // The last candidate is the configured name itself.
+            for name in &names[..names.len() - 1] {
+                if self.recently_missing(name) {
+                    continue;
+                }
+                match self.provider.get_secret(name).await {
+                    Ok(value) => {
+                        debug!(resolved = %name, "Using repository webhook secret");
+                        return Ok(value.expose_secret().to_string());
+                    }
+                    Err(KeyVaultError::SecretNotFound { .. }) => self.record_missing(name),
+                    Err(e) => return Err(map_key_vault_error(e, name)),
+                }
+            }
+        }
+        self.get_webhook_secret(event_type).await
+    }
+
     /// Returns `true`; the HMAC verification path uses constant-time comparison.
     fn supports_constant_time_comparison(&self) -> bool {
         true
     }
 }
 
+// ============================================================================
+// RotatingSignatureValidator
+// ============================================================================
+
+/// A [`SignatureValidator`] offering several secrets as candidates.
+///
+/// Wraps one validator per secret source: the provider's main secret,
+/// labelled [`PRIMARY_SECRET_LABEL`], followed by its additional secrets in
+/// the order they should be tried. The webhook pipeline compares the
+/// signature against every candidate and logs the label of the one that
+/// matched.
+///
+/// An additional secret that cannot be retrieved is logged and left out of
+/// the candidates; only a failure to retrieve the main secret is an error.
+/// For a delivery naming a repository, every source resolves that
+/// repository's secret.
+pub struct RotatingSignatureValidator {
+    primary: Arc<dyn SignatureValidator>,
+    additional: Vec<AdditionalSecret>,
+}
+
+/// An additional secret source of a [`RotatingSignatureValidator`].
+struct AdditionalSecret {
+    label: String,
+    retired: bool,
+    validator: Arc<dyn SignatureValidator>,
+}
+
+impl RotatingSignatureValidator {
+    /// Construct a validator whose main secret comes from `primary`.
+    pub fn new(primary: Arc<dyn SignatureValidator>) -> Self {
+        Self {
+            primary,
+            additional: Vec::new(),
+        }
+    }
+
+    /// Add a secret tried after those already added.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - Name logged when this secret matches.
+    /// * `retired` - Whether a match with this secret is refused.
+    /// * `validator` - Validator supplying the secret.
+    pub fn with_secret(
+        mut self,
+        label: impl Into<String>,
+        retired: bool,
+        validator: Arc<dyn SignatureValidator>,
+    ) -> Self {
+        self.additional.push(AdditionalSecret {
+            label: label.into(),
+            retired,
+            validator,
+        });
+        self
+    }
+}
+
+/// Get `validator`'s secret for `repository`, or its plain secret when the
+/// delivery names no repository.
+async fn source_secret(
+    validator: &dyn SignatureValidator,
+    event_type: &str,
+    repository: Option<&str>,
+) -> Result<String, SecretError> {
+    match repository {
+        Some(repository) => {
+            validator
+                .get_repository_webhook_secret(event_type, repository)
+                .await
+        }
+        None => validator.get_webhook_secret(event_type).await,
+    }
+}
+
+impl std::fmt::Debug for RotatingSignatureValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let labels: Vec<_> = self.additional.iter().map(|a| a.label.as_str()).collect();
+        f.debug_struct("RotatingSignatureValidator")
+            .field("additional_secrets", &labels)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl SignatureValidator for RotatingSignatureValidator {
+    /// Validate a signature with the main secret's validator.
+    ///
+    /// All sources of one provider share its signature scheme, so the main
+    /// validator checks every candidate.
+    #[instrument(skip(self, payload, secret_key), fields(sig_len = signature.len()))]
+    async fn validate_signature(
+        &self,
+        payload: &[u8],
+        signature: &str,
+        secret_key: &str,
+    ) -> Result<(), ValidationError> {
+        self.primary
+            .validate_signature(payload, signature, secret_key)
+            .await
+    }
+
+    /// Return the main secret.
+    async fn get_webhook_secret(&self, event_type: &str) -> Result<String, SecretError> {
+        self.primary.get_webhook_secret(event_type).await
+    }
+
+    /// Return the main secret for `repository`.
+    async fn get_repository_webhook_secret(
+        &self,
+        event_type: &str,
+        repository: &str,
+    ) -> Result<String, SecretError> {
+        self.primary
+            .get_repository_webhook_secret(event_type, repository)
+            .await
+    }
+
+    /// Return the main secret followed by every retrievable additional
+    /// secret.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`SecretError`] of the main secret's source.
+    async fn get_candidate_secrets(
+        &self,
+        event_type: &str,
+    ) -> Result<Vec<CandidateSecret>, SecretError> {
+        self.get_repository_candidate_secrets(event_type, None)
+            .await
+    }
+
+    /// Return the main secret followed by every retrievable additional
+    /// secret, each resolved for `repository`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the [`SecretError`] of the main secret's source.
+    #[instrument(skip(self))]
+    async fn get_repository_candidate_secrets(
+        &self,
+        event_type: &str,
+        repository: Option<&str>,
+    ) -> Result<Vec<CandidateSecret>, SecretError> {
+        let mut candidates = vec![CandidateSecret {
+            label: PRIMARY_SECRET_LABEL.to_string(),
+            secret: source_secret(self.primary.as_ref(), event_type, repository).await?,
+            retired: false,
+        }];
+        for additional in &self.additional {
+            match source_secret(additional.validator.as_ref(), event_type, repository).await {
+                Ok(secret) => candidates.push(CandidateSecret {
+                    label: additional.label.clone(),
+                    secret,
+                    retired: additional.retired,
+                }),
+                Err(e) => warn!(
+                    secret = %additional.label,
+                    error = %e,
+                    "Additional webhook secret unavailable; it will not be tried"
+                ),
+            }
+        }
+        Ok(candidates)
+    }
+
+    /// Returns `true` when every secret source compares in constant time.
+    fn supports_constant_time_comparison(&self) -> bool {
+        self.primary.supports_constant_time_comparison()
+            && self
+                .additional
+                .iter()
+                .all(|a| a.validator.supports_constant_time_comparison())
+    }
+}
+
+// ============================================================================
+// DeveloperSignatureValidator
+// ============================================================================
+
+/// A [`SignatureValidator`] that accepts every signature, for developer mode.
+///
+/// Signatures are still checked by the wrapped validator so a wrong secret is
+/// noticed, but a mismatch, or a secret that cannot be retrieved, is logged
+/// as a warning and the delivery is accepted.
+pub struct DeveloperSignatureValidator {
+    inner: Arc<dyn SignatureValidator>,
+}
+
+impl DeveloperSignatureValidator {
+    /// Wrap the validator a provider would use outside developer mode.
+    pub fn new(inner: Arc<dyn SignatureValidator>) -> Self {
+        Self { inner }
+    }
+}
+
+impl std::fmt::Debug for DeveloperSignatureValidator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DeveloperSignatureValidator")
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl SignatureValidator for DeveloperSignatureValidator {
+    /// Check the signature with the wrapped validator; accept it either way.
+    #[instrument(skip(self, payload, secret_key), fields(sig_len = signature.len()))]
+    async fn validate_signature(
+        &self,
+        payload: &[u8],
+        signature: &str,
+        secret_key: &str,
+    ) -> Result<(), ValidationError> {
+        if let Err(e) = self
+            .inner
+            .validate_signature(payload, signature, secret_key)
+            .await
+        {
+            warn!(
+                error = %e,
+                "DEVELOPER MODE: accepting webhook whose signature does not match; \
+                 this delivery would be rejected in production"
+            );
+        }
+        Ok(())
+    }
+
+    /// Return the wrapped validator's secret.
+    async fn get_webhook_secret(&self, event_type: &str) -> Result<String, SecretError> {
+        self.inner.get_webhook_secret(event_type).await
+    }
+
+    /// Return the wrapped validator's secret for `repository`.
+    async fn get_repository_webhook_secret(
+        &self,
+        event_type: &str,
+        repository: &str,
+    ) -> Result<String, SecretError> {
+        self.inner
+            .get_repository_webhook_secret(event_type, repository)
+            .await
+    }
+
+    /// Return the wrapped validator's candidates, or a placeholder when they
+    /// cannot be retrieved so the delivery is still accepted.
+    async fn get_candidate_secrets(
+        &self,
+        event_type: &str,
+    ) -> Result<Vec<CandidateSecret>, SecretError> {
+        self.get_repository_candidate_secrets(event_type, None)
+            .await
+    }
+
+    /// As [`get_candidate_secrets`](Self::get_candidate_secrets), for
+    /// `repository`.
+    async fn get_repository_candidate_secrets(
+        &self,
+        event_type: &str,
+        repository: Option<&str>,
+    ) -> Result<Vec<CandidateSecret>, SecretError> {
+        match self
+            .inner
+            .get_repository_candidate_secrets(event_type, repository)
+            .await
+        {
+            Ok(candidates) => Ok(candidates),
+            Err(e) => {
+                warn!(
+                    error = %e,
+                    "DEVELOPER MODE: webhook secret unavailable; accepting the delivery \
+                     without checking its signature"
+                );
+                Ok(vec![CandidateSecret {
+                    label: PRIMARY_SECRET_LABEL.to_string(),
+                    secret: String::new(),
+                    retired: false,
+                }])
+            }
+        }
+    }
+
+    fn supports_constant_time_comparison(&self) -> bool {
+        self.inner.supports_constant_time_comparison()
+    }
+}
+
 // ============================================================================
 // Tests
 // ============================================================================
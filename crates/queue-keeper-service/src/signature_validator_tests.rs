@@ -1,4 +1,5 @@
-//! Tests for [`LiteralSignatureValidator`] and [`KeyVaultSignatureValidator`].
+//! Tests for [`LiteralSignatureValidator`], [`KeyVaultSignatureValidator`],
+//! [`RotatingSignatureValidator`] and [`DeveloperSignatureValidator`].
 //!
 //! Verifies HMAC-SHA256 validation behaviour, secret retrieval, and the
 //! constant-time comparison flag.
@@ -125,6 +126,72 @@ mod validate_signature_tests {
     }
 }
 
+// ============================================================================
+// signature scheme tests
+// ============================================================================
+
+mod signature_scheme_tests {
+    use super::*;
+    use sha1::Sha1;
+
+    fn compute_sha1_signature(secret: &str, payload: &[u8]) -> String {
+        let mut mac = Hmac::<Sha1>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(payload);
+        format!("sha1={}", hex::encode(mac.finalize().into_bytes()))
+    }
+
+    /// A SHA-1 signature is rejected under the default SHA-256 scheme.
+    #[tokio::test]
+    async fn test_sha1_rejected_by_default() {
+        let secret = "ghes-secret";
+        let payload = b"{}";
+        let signature = compute_sha1_signature(secret, payload);
+
+        let validator = LiteralSignatureValidator::new(secret.to_string());
+        let result = validator
+            .validate_signature(payload, &signature, secret)
+            .await;
+
+        assert!(result.is_err(), "SHA-1 must not be accepted by default");
+    }
+
+    /// A SHA-1 signature is accepted when the provider allows legacy GHES signatures.
+    #[tokio::test]
+    async fn test_sha1_accepted_when_scheme_allows() {
+        let secret = "ghes-secret";
+        let payload = b"{}";
+        let signature = compute_sha1_signature(secret, payload);
+
+        let validator = LiteralSignatureValidator::new(secret.to_string())
+            .with_signature_scheme(GitHubSignatureScheme::Sha256OrSha1);
+
+        assert!(validator
+            .validate_signature(payload, &signature, secret)
+            .await
+            .is_ok());
+        assert!(validator
+            .validate_signature(payload, "sha1=00", secret)
+            .await
+            .is_err());
+    }
+
+    /// SHA-256 signatures are still accepted under the legacy scheme.
+    #[tokio::test]
+    async fn test_sha256_accepted_under_legacy_scheme() {
+        let secret = "ghes-secret";
+        let payload = b"{}";
+        let signature = compute_sha256_signature(secret, payload);
+
+        let validator = LiteralSignatureValidator::new(secret.to_string())
+            .with_signature_scheme(GitHubSignatureScheme::Sha256OrSha1);
+
+        assert!(validator
+            .validate_signature(payload, &signature, secret)
+            .await
+            .is_ok());
+    }
+}
+
 // ============================================================================
 // get_webhook_secret tests
 // ============================================================================
@@ -356,4 +423,273 @@ mod key_vault_signature_validator_tests {
             "debug must never include the actual secret value: {debug_str}"
         );
     }
+
+    /// Per-repository secrets fall back from the repository to its owner to
+    /// the configured secret.
+    #[tokio::test]
+    async fn test_per_repository_secret_fallbacks() {
+        let provider = Arc::new(InMemoryKeyVaultProvider::new());
+        for (name, value) in [
+            ("webhook", "default"),
+            ("webhook--octo", "owner"),
+            ("webhook--octo--widgets", "repository"),
+        ] {
+            provider.add_secret(
+                secret_name(name),
+                SecretValue::from_string(value.to_string()),
+            );
+        }
+        let validator = KeyVaultSignatureValidator::new(provider, secret_name("webhook"))
+            .with_per_repository_secrets(true);
+
+        for (repository, expected) in [
+            ("octo/widgets", "repository"),
+            ("Octo/Gadgets", "owner"),
+            ("other/widgets", "default"),
+        ] {
+            let secret = validator
+                .get_repository_webhook_secret("push", repository)
+                .await
+                .unwrap();
+            assert_eq!(secret, expected, "secret for {repository}");
+        }
+    }
+
+    /// Without per-repository secrets the configured secret is always used.
+    #[tokio::test]
+    async fn test_per_repository_secrets_off_by_default() {
+        let provider = Arc::new(InMemoryKeyVaultProvider::new());
+        provider.add_secret(
+            secret_name("webhook"),
+            SecretValue::from_string("default".to_string()),
+        );
+        provider.add_secret(
+            secret_name("webhook--octo--widgets"),
+            SecretValue::from_string("repository".to_string()),
+        );
+        let validator = KeyVaultSignatureValidator::new(provider, secret_name("webhook"));
+
+        let secret = validator
+            .get_repository_webhook_secret("push", "octo/widgets")
+            .await
+            .unwrap();
+
+        assert_eq!(secret, "default");
+    }
+
+    /// A repository secret found missing is not looked up again until the
+    /// negative cache expires.
+    #[tokio::test]
+    async fn test_missing_repository_secret_remembered() {
+        let provider = Arc::new(InMemoryKeyVaultProvider::new());
+        provider.add_secret(
+            secret_name("webhook"),
+            SecretValue::from_string("default".to_string()),
+        );
+        let validator = KeyVaultSignatureValidator::new(provider.clone(), secret_name("webhook"))
+            .with_per_repository_secrets(true);
+        let secret = validator
+            .get_repository_webhook_secret("push", "octo/widgets")
+            .await
+            .unwrap();
+        assert_eq!(secret, "default");
+
+        provider.add_secret(
+            secret_name("webhook--octo--widgets"),
+            SecretValue::from_string("repository".to_string()),
+        );
+        let secret = validator
+            .get_repository_webhook_secret("push", "octo/widgets")
+            .await
+            .unwrap();
+        assert_eq!(secret, "default");
+
+        validator.missing.lock().unwrap().clear();
+        let secret = validator
+            .get_repository_webhook_secret("push", "octo/widgets")
+            .await
+            .unwrap();
+        assert_eq!(secret, "repository");
+    }
+}
+
+// ============================================================================
+// RotatingSignatureValidator tests
+// ============================================================================
+
+mod rotating_signature_validator_tests {
+    use super::*;
+    use queue_keeper_core::adapters::memory_key_vault::InMemoryKeyVaultProvider;
+    use queue_keeper_core::key_vault::SecretValue;
+
+    fn literal(secret: &str) -> Arc<dyn SignatureValidator> {
+        Arc::new(LiteralSignatureValidator::new(secret.to_string()))
+    }
+
+    fn missing_key_vault_secret() -> Arc<dyn SignatureValidator> {
+        Arc::new(KeyVaultSignatureValidator::new(
+            Arc::new(InMemoryKeyVaultProvider::new()),
+            SecretName::new("missing-secret").unwrap(),
+        ))
+    }
+
+    /// Candidates are the main secret followed by the additional secrets in
+    /// the order they were added.
+    #[tokio::test]
+    async fn test_candidates_in_order() {
+        let validator = RotatingSignatureValidator::new(literal("new"))
+            .with_secret("previous", false, literal("old"))
+            .with_secret("2025-q4", true, literal("older"));
+
+        let candidates = validator.get_candidate_secrets("push").await.unwrap();
+
+        let summary: Vec<_> = candidates
+            .iter()
+            .map(|c| (c.label.as_str(), c.secret.as_str(), c.retired))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (PRIMARY_SECRET_LABEL, "new", false),
+                ("previous", "old", false),
+                ("2025-q4", "older", true),
+            ]
+        );
+        assert_eq!(validator.get_webhook_secret("push").await.unwrap(), "new");
+    }
+
+    /// An unavailable additional secret is left out of the candidates.
+    #[tokio::test]
+    async fn test_unavailable_additional_secret_skipped() {
+        let validator = RotatingSignatureValidator::new(literal("new")).with_secret(
+            "previous",
+            false,
+            missing_key_vault_secret(),
+        );
+
+        let candidates = validator.get_candidate_secrets("push").await.unwrap();
+
+        assert_eq!(candidates.len(), 1);
+    }
+
+    /// Every secret source resolves the delivery's repository.
+    #[tokio::test]
+    async fn test_candidates_resolved_for_repository() {
+        let provider = Arc::new(InMemoryKeyVaultProvider::new());
+        for (name, value) in [("main", "new"), ("main--octo", "octo-new")] {
+            provider.add_secret(
+                SecretName::new(name).unwrap(),
+                SecretValue::from_string(value.to_string()),
+            );
+        }
+        let primary = Arc::new(
+            KeyVaultSignatureValidator::new(provider, SecretName::new("main").unwrap())
+                .with_per_repository_secrets(true),
+        );
+        let validator =
+            RotatingSignatureValidator::new(primary).with_secret("previous", false, literal("old"));
+
+        let candidates = validator
+            .get_repository_candidate_secrets("push", Some("octo/widgets"))
+            .await
+            .unwrap();
+        let secrets: Vec<_> = candidates.iter().map(|c| c.secret.as_str()).collect();
+        assert_eq!(secrets, vec!["octo-new", "old"]);
+
+        let candidates = validator.get_candidate_secrets("push").await.unwrap();
+        let secrets: Vec<_> = candidates.iter().map(|c| c.secret.as_str()).collect();
+        assert_eq!(secrets, vec!["new", "old"]);
+    }
+
+    /// An unavailable main secret is an error.
+    #[tokio::test]
+    async fn test_unavailable_primary_secret_fails() {
+        let validator = RotatingSignatureValidator::new(missing_key_vault_secret()).with_secret(
+            "previous",
+            false,
+            literal("old"),
+        );
+
+        let result = validator.get_candidate_secrets("push").await;
+
+        assert!(matches!(result, Err(SecretError::NotFound { .. })));
+    }
+
+    /// Signatures are checked with the main validator whatever the secret.
+    #[tokio::test]
+    async fn test_validates_with_any_candidate_secret() {
+        let payload = b"hello world";
+        let signature = compute_sha256_signature("old", payload);
+        let validator = RotatingSignatureValidator::new(literal("new")).with_secret(
+            "previous",
+            false,
+            literal("old"),
+        );
+
+        let result = validator
+            .validate_signature(payload, &signature, "old")
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    /// Debug output lists labels but no secret values.
+    #[test]
+    fn test_debug_shows_labels_only() {
+        let validator = RotatingSignatureValidator::new(literal("top-secret")).with_secret(
+            "previous",
+            false,
+            literal("old-secret"),
+        );
+
+        let debug_str = format!("{:?}", validator);
+
+        assert!(debug_str.contains("previous"));
+        assert!(!debug_str.contains("top-secret"));
+        assert!(!debug_str.contains("old-secret"));
+    }
+}
+
+// ============================================================================
+// DeveloperSignatureValidator tests
+// ============================================================================
+
+mod developer_signature_validator_tests {
+    use super::*;
+    use queue_keeper_core::adapters::memory_key_vault::InMemoryKeyVaultProvider;
+
+    /// A signature made with the wrong secret is accepted.
+    #[tokio::test]
+    async fn test_mismatched_signature_accepted() {
+        let payload = b"hello world";
+        let signature = compute_sha256_signature("wrong", payload);
+        let inner = LiteralSignatureValidator::new("right".to_string());
+        assert!(inner
+            .validate_signature(payload, &signature, "right")
+            .await
+            .is_err());
+        let validator = DeveloperSignatureValidator::new(Arc::new(inner));
+
+        let result = validator
+            .validate_signature(payload, &signature, "right")
+            .await;
+
+        assert!(result.is_ok());
+    }
+
+    /// An unavailable secret still yields a candidate, so the delivery is
+    /// accepted.
+    #[tokio::test]
+    async fn test_unavailable_secret_yields_placeholder() {
+        let validator =
+            DeveloperSignatureValidator::new(Arc::new(KeyVaultSignatureValidator::new(
+                Arc::new(InMemoryKeyVaultProvider::new()),
+                SecretName::new("missing-secret").unwrap(),
+            )));
+
+        let candidates = validator.get_candidate_secrets("push").await.unwrap();
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].label, PRIMARY_SECRET_LABEL);
+    }
 }
@@ -0,0 +1,205 @@
+//! OpenTelemetry trace export pipeline.
+//!
+//! Logging starts before the configuration is loaded, so the subscriber is
+//! installed with an empty [`TracingLayer`]. Once the `telemetry` section is
+//! known and enables export, [`build_tracer_provider`] creates the OTLP
+//! exporter and [`install`] fills the layer in, so every span from then on
+//! is exported.
+//!
+//! The layer is filled in at most once and never replaced. Unlike a
+//! `reload::Layer`, it can therefore hand the subscriber's OpenTelemetry
+//! context to `OpenTelemetrySpanExt::context`, which the `traceparent` of
+//! routed events is read from.
+//!
+//! The tracer provider samples with the service's [`TraceSampler`], so
+//! `PUT /admin/tracing/sampling` changes the ratio of exported traces
+//! without a restart.
+
+use opentelemetry::{global, trace::TracerProvider as _, KeyValue};
+use opentelemetry_otlp::{ExporterBuildError, Protocol, SpanExporter, WithExportConfig};
+use opentelemetry_sdk::{
+    propagation::TraceContextPropagator,
+    trace::{SdkTracer, SdkTracerProvider},
+    Resource,
+};
+use queue_keeper_api::{OtlpProtocol, TelemetryExportConfig, TraceSampler};
+use std::{
+    any::TypeId,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
+use tracing::{
+    span::{Attributes, Id, Record},
+    Event,
+};
+use tracing_opentelemetry::OpenTelemetryLayer;
+use tracing_subscriber::{
+    layer::{Context, Layer},
+    Registry,
+};
+
+/// Service name reported with every exported span.
+const SERVICE_NAME: &str = "queue-keeper";
+
+type ExportLayer = OpenTelemetryLayer<Registry, SdkTracer>;
+
+/// The OpenTelemetry layer of the subscriber; empty until export starts.
+pub struct TracingLayer {
+    inner: Arc<OnceLock<ExportLayer>>,
+}
+
+/// Handle used to fill in the [`TracingLayer`] after startup.
+#[derive(Clone)]
+pub struct TracingLayerHandle {
+    inner: Arc<OnceLock<ExportLayer>>,
+}
+
+/// Error returned by [`install`] when trace export was already started.
+#[derive(Debug, thiserror::Error)]
+#[error("Trace export is already installed")]
+pub struct AlreadyInstalled;
+
+impl TracingLayer {
+    /// Create an empty layer and the handle that fills it in.
+    pub fn new() -> (Self, TracingLayerHandle) {
+        let inner = Arc::new(OnceLock::new());
+        (
+            Self {
+                inner: inner.clone(),
+            },
+            TracingLayerHandle { inner },
+        )
+    }
+}
+
+impl Layer<Registry> for TracingLayer {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, Registry>) {
+        if let Some(layer) = self.inner.get() {
+            layer.on_new_span(attrs, id, ctx);
+        }
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, Registry>) {
+        if let Some(layer) = self.inner.get() {
+            layer.on_record(id, values, ctx);
+        }
+    }
+
+    fn on_follows_from(&self, id: &Id, follows: &Id, ctx: Context<'_, Registry>) {
+        if let Some(layer) = self.inner.get() {
+            layer.on_follows_from(id, follows, ctx);
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, Registry>) {
+        if let Some(layer) = self.inner.get() {
+            layer.on_event(event, ctx);
+        }
+    }
+
+    fn on_enter(&self, id: &Id, ctx: Context<'_, Registry>) {
+        if let Some(layer) = self.inner.get() {
+            layer.on_enter(id, ctx);
+        }
+    }
+
+    fn on_exit(&self, id: &Id, ctx: Context<'_, Registry>) {
+        if let Some(layer) = self.inner.get() {
+            layer.on_exit(id, ctx);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, Registry>) {
+        if let Some(layer) = self.inner.get() {
+            layer.on_close(id, ctx);
+        }
+    }
+
+    fn on_id_change(&self, old: &Id, new: &Id, ctx: Context<'_, Registry>) {
+        if let Some(layer) = self.inner.get() {
+            layer.on_id_change(old, new, ctx);
+        }
+    }
+
+    unsafe fn downcast_raw(&self, id: TypeId) -> Option<*const ()> {
+        if id == TypeId::of::<Self>() {
+            return Some(self as *const Self as *const ());
+        }
+        // Safety: the inner layer is never replaced or dropped while `self`
+        // is alive, so pointers into it stay valid.
+        unsafe { self.inner.get()?.downcast_raw(id) }
+    }
+}
+
+/// Build the tracer provider exporting spans as configured.
+///
+/// Spans are exported in batches. Without a configured endpoint the
+/// exporter reads `OTEL_EXPORTER_OTLP_ENDPOINT`, then falls back to the
+/// protocol's local default.
+///
+/// # Errors
+/// Returns an error when the OTLP exporter cannot be created.
+pub fn build_tracer_provider(
+    config: &TelemetryExportConfig,
+    sampler: TraceSampler,
+    environment: &str,
+) -> Result<SdkTracerProvider, ExporterBuildError> {
+    let timeout = Duration::from_secs(config.export_timeout_seconds);
+    let exporter = match config.protocol {
+        OtlpProtocol::Grpc => {
+            let mut builder = SpanExporter::builder().with_tonic().with_timeout(timeout);
+            if let Some(endpoint) = &config.endpoint {
+                builder = builder.with_endpoint(endpoint);
+            }
+            builder.build()?
+        }
+        OtlpProtocol::Http => {
+            let mut builder = SpanExporter::builder()
+                .with_http()
+                .with_protocol(Protocol::HttpBinary)
+                .with_timeout(timeout);
+            if let Some(endpoint) = &config.endpoint {
+                builder = builder.with_endpoint(endpoint);
+            }
+            builder.build()?
+        }
+    };
+
+    let resource = Resource::builder()
+        .with_service_name(SERVICE_NAME)
+        .with_attributes([
+            KeyValue::new("service.version", env!("CARGO_PKG_VERSION")),
+            KeyValue::new("deployment.environment", environment.to_string()),
+        ])
+        .build();
+
+    Ok(SdkTracerProvider::builder()
+        .with_batch_exporter(exporter)
+        .with_sampler(sampler)
+        .with_resource(resource)
+        .build())
+}
+
+/// Start exporting the spans of the subscriber through `provider`.
+///
+/// Also registers `provider` and the W3C trace context propagator globally.
+///
+/// # Errors
+/// Returns an error when export was already installed through `handle`.
+pub fn install(
+    handle: &TracingLayerHandle,
+    provider: &SdkTracerProvider,
+) -> Result<(), AlreadyInstalled> {
+    let tracer = provider.tracer(SERVICE_NAME);
+    handle
+        .inner
+        .set(tracing_opentelemetry::layer().with_tracer(tracer))
+        .map_err(|_| AlreadyInstalled)?;
+    global::set_tracer_provider(provider.clone());
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    Ok(())
+}
+
+#[cfg(test)]
+#[path = "telemetry_tests.rs"]
+mod tests;
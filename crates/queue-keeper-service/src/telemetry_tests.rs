@@ -0,0 +1,63 @@
+//! Tests for the trace export pipeline.
+
+use super::*;
+use queue_keeper_api::telemetry::trace_parent;
+use tracing_subscriber::layer::SubscriberExt;
+
+/// Once installed, spans get a trace context sampled by the service's
+/// sampler.
+#[tokio::test]
+async fn test_installed_layer_traces_spans() {
+    let (layer, handle) = TracingLayer::new();
+    let subscriber = tracing_subscriber::registry().with(layer);
+
+    let config = TelemetryExportConfig {
+        enabled: true,
+        endpoint: Some("http://localhost:4317".to_string()),
+        ..Default::default()
+    };
+    let provider = build_tracer_provider(&config, TraceSampler::new(0.0), "test").unwrap();
+
+    tracing::subscriber::with_default(subscriber, || {
+        let span = tracing::info_span!("webhook");
+        assert_eq!(trace_parent(&span, &Default::default()), None);
+
+        install(&handle, &provider).unwrap();
+        let span = tracing::info_span!("webhook");
+        let traceparent = trace_parent(&span, &Default::default()).unwrap();
+        assert!(traceparent.starts_with("00-"));
+        assert!(traceparent.ends_with("-00"), "{}", traceparent);
+    });
+}
+
+/// Each protocol builds an exporter.
+#[tokio::test]
+async fn test_build_tracer_provider_for_each_protocol() {
+    for (protocol, endpoint) in [
+        (OtlpProtocol::Grpc, "http://collector:4317"),
+        (OtlpProtocol::Http, "http://collector:4318/v1/traces"),
+    ] {
+        let config = TelemetryExportConfig {
+            enabled: true,
+            protocol,
+            endpoint: Some(endpoint.to_string()),
+            ..Default::default()
+        };
+        assert!(build_tracer_provider(&config, TraceSampler::new(1.0), "test").is_ok());
+    }
+}
+
+/// Export is installed at most once.
+#[tokio::test]
+async fn test_second_install_refused() {
+    let (_layer, handle) = TracingLayer::new();
+    let config = TelemetryExportConfig {
+        enabled: true,
+        endpoint: Some("http://localhost:4317".to_string()),
+        ..Default::default()
+    };
+    let provider = build_tracer_provider(&config, TraceSampler::new(1.0), "test").unwrap();
+
+    assert!(install(&handle, &provider).is_ok());
+    assert!(install(&handle, &provider).is_err());
+}